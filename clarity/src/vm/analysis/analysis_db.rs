@@ -18,28 +18,79 @@ use std::collections::{BTreeMap, BTreeSet};
 
 use stacks_common::types::StacksEpochId;
 
+use crate::vm::analysis::call_graph::ContractCallGraph;
+use crate::vm::analysis::dependency_graph::DependencyGraph;
 use crate::vm::analysis::errors::{CheckError, CheckErrors, CheckResult};
 use crate::vm::analysis::type_checker::ContractAnalysis;
-use crate::vm::database::{
-    ClarityBackingStore, ClarityDeserializable, ClaritySerializable, RollbackWrapper,
-};
+use crate::vm::database::{ClarityBackingStore, ClaritySerializable, RollbackWrapper};
 use crate::vm::representations::ClarityName;
 use crate::vm::types::signatures::FunctionSignature;
-use crate::vm::types::{FunctionType, QualifiedContractIdentifier, TraitIdentifier, TypeSignature};
+use crate::vm::types::{
+    FunctionType, QualifiedContractIdentifier, TraitIdentifier, TypeSignature, ValueLimits,
+};
 use crate::vm::ClarityVersion;
 
 pub struct AnalysisDatabase<'a> {
     store: RollbackWrapper<'a>,
+    dependency_graph: DependencyGraph,
 }
 
 impl<'a> AnalysisDatabase<'a> {
     pub fn new(store: &'a mut dyn ClarityBackingStore) -> AnalysisDatabase<'a> {
         AnalysisDatabase {
             store: RollbackWrapper::new(store),
+            dependency_graph: DependencyGraph::new(),
         }
     }
     pub fn new_with_rollback_wrapper(store: RollbackWrapper<'a>) -> AnalysisDatabase<'a> {
-        AnalysisDatabase { store }
+        AnalysisDatabase {
+            store,
+            dependency_graph: DependencyGraph::new(),
+        }
+    }
+
+    /// Use `dependency_graph`, e.g. one kept from a previous analysis database, to reuse the
+    ///  dependencies it has already loaded.
+    pub fn with_dependency_graph(mut self, dependency_graph: DependencyGraph) -> Self {
+        self.dependency_graph = dependency_graph;
+        self
+    }
+
+    pub fn dependency_graph(&self) -> &DependencyGraph {
+        &self.dependency_graph
+    }
+
+    /// Take the dependency graph, to be reused by another analysis database
+    pub fn take_dependency_graph(&mut self) -> DependencyGraph {
+        std::mem::take(&mut self.dependency_graph)
+    }
+
+    /// Forget the cached analysis of `contract_identifier` and of the contracts that depend on
+    ///  it.  Returns those dependents, which must be analyzed again.
+    pub fn invalidate(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+    ) -> BTreeSet<QualifiedContractIdentifier> {
+        self.dependency_graph.invalidate(contract_identifier)
+    }
+
+    /// Start recording the contracts loaded by the analysis of a contract
+    pub fn begin_dependency_tracking(&mut self) {
+        self.dependency_graph.begin_analysis();
+    }
+
+    /// Record the contracts loaded since `begin_dependency_tracking()` as the dependencies of
+    ///  `contract_identifier`
+    pub fn finish_dependency_tracking(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+    ) {
+        self.dependency_graph.finish_analysis(contract_identifier);
+    }
+
+    /// The limits on values that the store asks analyses to apply
+    pub fn get_value_limits(&self) -> ValueLimits {
+        self.store.get_value_limits()
     }
 
     pub fn execute<F, T, E>(&mut self, f: F) -> Result<T, E>
@@ -99,18 +150,26 @@ impl<'a> AnalysisDatabase<'a> {
         &mut self,
         contract_identifier: &QualifiedContractIdentifier,
     ) -> CheckResult<Option<ContractAnalysis>> {
-        self.store
-            .get_metadata(contract_identifier, AnalysisDatabase::storage_key())
-            // treat NoSuchContract error thrown by get_metadata as an Option::None --
-            //    the analysis will propagate that as a CheckError anyways.
+        // if the commitment can't be read, just bypass the dependency graph
+        let commitment = self
+            .store
+            .get_contract_commitment(contract_identifier)
             .ok()
-            .flatten()
-            .map(|x| {
-                ContractAnalysis::deserialize(&x).map_err(|_| {
-                    CheckErrors::Expects("Bad data deserialized from DB".into()).into()
-                })
-            })
-            .transpose()
+            .flatten();
+        if let Some(commitment) = commitment.as_ref() {
+            if let Some(analysis) = self.dependency_graph.get(contract_identifier, commitment) {
+                return Ok(Some(analysis));
+            }
+        }
+        let analysis = self
+            .store
+            .get_contract_analysis(contract_identifier)
+            .map_err(|_| CheckErrors::Expects("Bad data deserialized from DB".into()))?;
+        if let (Some(commitment), Some(analysis)) = (commitment, analysis.as_ref()) {
+            self.dependency_graph
+                .insert(contract_identifier, commitment, analysis.clone());
+        }
+        Ok(analysis)
     }
 
     pub fn load_contract(
@@ -119,17 +178,7 @@ impl<'a> AnalysisDatabase<'a> {
         epoch: &StacksEpochId,
     ) -> CheckResult<Option<ContractAnalysis>> {
         Ok(self
-            .store
-            .get_metadata(contract_identifier, AnalysisDatabase::storage_key())
-            // treat NoSuchContract error thrown by get_metadata as an Option::None --
-            //    the analysis will propagate that as a CheckError anyways.
-            .ok()
-            .flatten()
-            .map(|x| {
-                ContractAnalysis::deserialize(&x)
-                    .map_err(|_| CheckErrors::Expects("Bad data deserialized from DB".into()))
-            })
-            .transpose()?
+            .load_contract_non_canonical(contract_identifier)?
             .and_then(|mut x| {
                 x.canonicalize_types(epoch);
                 Some(x)
@@ -166,6 +215,18 @@ impl<'a> AnalysisDatabase<'a> {
         Ok(contract.clarity_version)
     }
 
+    /// The static call graph of a contract, or `None` if it was analyzed before call graphs
+    /// were recorded
+    pub fn get_call_graph(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+    ) -> CheckResult<Option<ContractCallGraph>> {
+        let contract = self
+            .load_contract_non_canonical(contract_identifier)?
+            .ok_or(CheckErrors::NoSuchContract(contract_identifier.to_string()))?;
+        Ok(contract.call_graph)
+    }
+
     pub fn get_public_function_type(
         &mut self,
         contract_identifier: &QualifiedContractIdentifier,
@@ -179,11 +179,15 @@ impl<'a> ArithmeticOnlyChecker<'a> {
             | InsertEntry | SetVar | MintAsset | MintToken | TransferAsset | TransferToken
             | ContractCall | StxTransfer | StxTransferMemo | StxBurn | AtBlock | GetStxBalance
             | GetTokenSupply | BurnToken | FromConsensusBuff | ToConsensusBuff | BurnAsset
-            | StxGetAccount => Err(Error::FunctionNotPermitted(function)),
+            | StxGetAccount | ImplementsTrait | BlockVrfSeed => {
+                Err(Error::FunctionNotPermitted(function))
+            }
             Append | Concat | AsMaxLen | ContractOf | PrincipalOf | ListCons | Print
             | AsContract | ElementAt | ElementAtAlias | IndexOf | IndexOfAlias | Map | Filter
-            | Fold | Slice | ReplaceAt => Err(Error::FunctionNotPermitted(function)),
-            BuffToIntLe | BuffToUIntLe | BuffToIntBe | BuffToUIntBe => {
+            | Fold | Slice | ReplaceAt | EmitEvent => Err(Error::FunctionNotPermitted(function)),
+            BuffToIntLe | BuffToUIntLe | BuffToIntBe | BuffToUIntBe | BuffAnd | BuffOr
+            | BuffXor | BuffNot => Err(Error::FunctionNotPermitted(function)),
+            AltBn128Add | AltBn128Mul | AltBn128PairingCheck => {
                 Err(Error::FunctionNotPermitted(function))
             }
             IsStandard | PrincipalDestruct | PrincipalConstruct => {
@@ -192,8 +196,8 @@ impl<'a> ArithmeticOnlyChecker<'a> {
             IntToAscii | IntToUtf8 | StringToInt | StringToUInt => {
                 Err(Error::FunctionNotPermitted(function))
             }
-            Sha512 | Sha512Trunc256 | Secp256k1Recover | Secp256k1Verify | Hash160 | Sha256
-            | Keccak256 => Err(Error::FunctionNotPermitted(function)),
+            Sha512 | Sha512Trunc256 | Secp256k1Recover | Secp256k1Verify | Secp256k1VerifyMany
+            | Hash160 | Sha256 | Keccak256 => Err(Error::FunctionNotPermitted(function)),
             Add | Subtract | Divide | Multiply | CmpGeq | CmpLeq | CmpLess | CmpGreater
             | Modulo | Power | Sqrti | Log2 | BitwiseXor | And | Or | Not | Equals | If
             | ConsSome | ConsOkay | ConsError | DefaultTo | UnwrapRet | UnwrapErrRet | IsOkay
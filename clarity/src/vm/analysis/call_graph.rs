@@ -0,0 +1,340 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Static call graph of a contract.
+//!
+//! `build_call_graph()` records, for each function a contract defines, the functions of the
+//! same contract that it calls (including those passed to `map`, `filter` and `fold`) and the
+//! `contract-call?`s it makes.  A call to a literal contract is resolved to that contract; a
+//! call through a trait-typed argument is resolved to the trait.  Only function bodies are
+//! walked: calls made by top-level expressions when the contract is deployed aren't included.
+//!
+//! The graph is stored with the contract's analysis, so it's available from
+//! `AnalysisDatabase::load_contract()` for every contract analyzed since it was added.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::vm::analysis::types::ContractAnalysis;
+use crate::vm::analysis::CheckResult;
+use crate::vm::functions::define::DefineFunctionsParsed;
+use crate::vm::functions::NativeFunctions;
+use crate::vm::representations::SymbolicExpressionType;
+use crate::vm::types::signatures::CallableSubtype;
+use crate::vm::types::{
+    FunctionType, PrincipalData, QualifiedContractIdentifier, TraitIdentifier, TypeSignature, Value,
+};
+use crate::vm::{ClarityName, ClarityVersion, SymbolicExpression};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FunctionVisibility {
+    Private,
+    ReadOnly,
+    Public,
+}
+
+/// The function called by a `contract-call?`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CallTarget {
+    /// `(contract-call? .contract function ...)`
+    Contract(QualifiedContractIdentifier, ClarityName),
+    /// `(contract-call? trait-ref function ...)`, where `trait-ref` is an argument of the
+    /// calling function with a trait type
+    Trait(TraitIdentifier, ClarityName),
+    /// A dynamic call whose trait isn't known from the function's signature
+    Unresolved(ClarityName),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FunctionCalls {
+    pub visibility: FunctionVisibility,
+    /// Functions of the same contract that this function calls
+    pub local_calls: BTreeSet<ClarityName>,
+    /// The `contract-call?`s this function makes
+    pub contract_calls: BTreeSet<CallTarget>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ContractCallGraph {
+    pub functions: BTreeMap<ClarityName, FunctionCalls>,
+}
+
+impl ContractCallGraph {
+    /// The functions of the contract that `function` calls, directly or indirectly, including
+    /// `function` itself
+    pub fn local_closure(&self, function: &ClarityName) -> BTreeSet<ClarityName> {
+        let mut found = BTreeSet::new();
+        let mut frontier = vec![function.clone()];
+        while let Some(next) = frontier.pop() {
+            if !found.insert(next.clone()) {
+                continue;
+            }
+            if let Some(calls) = self.functions.get(&next) {
+                frontier.extend(calls.local_calls.iter().cloned());
+            }
+        }
+        found
+    }
+
+    /// The `contract-call?`s that calling `function` can make, directly or through the
+    /// contract's other functions
+    pub fn contract_calls_of(&self, function: &ClarityName) -> BTreeSet<CallTarget> {
+        self.local_closure(function)
+            .iter()
+            .filter_map(|name| self.functions.get(name))
+            .flat_map(|calls| calls.contract_calls.iter().cloned())
+            .collect()
+    }
+
+    /// The contracts called by a literal `contract-call?` anywhere in the contract
+    pub fn called_contracts(&self) -> BTreeSet<QualifiedContractIdentifier> {
+        self.functions
+            .values()
+            .flat_map(|calls| calls.contract_calls.iter())
+            .filter_map(|target| match target {
+                CallTarget::Contract(contract, _) => Some(contract.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The private functions that no public or read-only function can reach
+    pub fn unreachable_functions(&self) -> BTreeSet<ClarityName> {
+        let reachable: BTreeSet<_> = self
+            .functions
+            .iter()
+            .filter(|(_, calls)| calls.visibility != FunctionVisibility::Private)
+            .flat_map(|(name, _)| self.local_closure(name))
+            .collect();
+        self.functions
+            .keys()
+            .filter(|name| !reachable.contains(*name))
+            .cloned()
+            .collect()
+    }
+}
+
+pub fn build_call_graph(contract_analysis: &ContractAnalysis) -> CheckResult<ContractCallGraph> {
+    let mut definitions = vec![];
+    for expression in contract_analysis.expressions.iter() {
+        let (visibility, signature, body) = match DefineFunctionsParsed::try_parse(expression)? {
+            Some(DefineFunctionsParsed::PrivateFunction { signature, body }) => {
+                (FunctionVisibility::Private, signature, body)
+            }
+            Some(DefineFunctionsParsed::ReadOnlyFunction { signature, body }) => {
+                (FunctionVisibility::ReadOnly, signature, body)
+            }
+            Some(DefineFunctionsParsed::PublicFunction { signature, body }) => {
+                (FunctionVisibility::Public, signature, body)
+            }
+            _ => continue,
+        };
+        if let Some(name) = signature.first().and_then(|name| name.match_atom()) {
+            definitions.push((name, visibility, body));
+        }
+    }
+
+    let defined: BTreeSet<_> = definitions.iter().map(|(name, ..)| *name).collect();
+    let mut call_graph = ContractCallGraph::default();
+    for (name, visibility, body) in definitions {
+        let function_type = match visibility {
+            FunctionVisibility::Private => contract_analysis.get_private_function(name),
+            FunctionVisibility::ReadOnly => contract_analysis.get_read_only_function_type(name),
+            FunctionVisibility::Public => contract_analysis.get_public_function_type(name),
+        };
+        let mut walker = CallWalker {
+            defined: &defined,
+            clarity_version: &contract_analysis.clarity_version,
+            function_type,
+            calls: FunctionCalls {
+                visibility,
+                local_calls: BTreeSet::new(),
+                contract_calls: BTreeSet::new(),
+            },
+        };
+        walker.walk(body);
+        call_graph.functions.insert(name.clone(), walker.calls);
+    }
+    Ok(call_graph)
+}
+
+struct CallWalker<'a> {
+    defined: &'a BTreeSet<&'a ClarityName>,
+    clarity_version: &'a ClarityVersion,
+    function_type: Option<&'a FunctionType>,
+    calls: FunctionCalls,
+}
+
+impl CallWalker<'_> {
+    fn walk(&mut self, expression: &SymbolicExpression) {
+        let Some(list) = expression.match_list() else {
+            return;
+        };
+        let Some((function, args)) = list.split_first() else {
+            return;
+        };
+        let Some(function) = function.match_atom() else {
+            list.iter().for_each(|expression| self.walk(expression));
+            return;
+        };
+
+        if self.defined.contains(function) {
+            self.calls.local_calls.insert(function.clone());
+        }
+        match NativeFunctions::lookup_by_name_at_version(function, self.clarity_version) {
+            Some(NativeFunctions::ContractCall) => {
+                if let Some(target) = self.call_target(args) {
+                    self.calls.contract_calls.insert(target);
+                }
+            }
+            Some(NativeFunctions::Map | NativeFunctions::Filter | NativeFunctions::Fold) => {
+                if let Some(mapped) = args.first().and_then(|arg| arg.match_atom()) {
+                    if self.defined.contains(mapped) {
+                        self.calls.local_calls.insert(mapped.clone());
+                    }
+                }
+            }
+            Some(NativeFunctions::TupleCons) => {
+                // skip the field names, which can shadow function names
+                for pair in args.iter().filter_map(|arg| arg.match_list()) {
+                    pair.iter().skip(1).for_each(|value| self.walk(value));
+                }
+                return;
+            }
+            _ => {}
+        }
+        args.iter().for_each(|expression| self.walk(expression));
+    }
+
+    fn call_target(&self, args: &[SymbolicExpression]) -> Option<CallTarget> {
+        let function_name = args.get(1)?.match_atom()?.clone();
+        match &args.first()?.expr {
+            SymbolicExpressionType::LiteralValue(Value::Principal(PrincipalData::Contract(
+                contract,
+            ))) => Some(CallTarget::Contract(contract.clone(), function_name)),
+            SymbolicExpressionType::Atom(trait_reference) => {
+                Some(match self.trait_argument(trait_reference) {
+                    Some(trait_identifier) => CallTarget::Trait(trait_identifier, function_name),
+                    None => CallTarget::Unresolved(function_name),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// The trait of the calling function's argument named `name`
+    fn trait_argument(&self, name: &ClarityName) -> Option<TraitIdentifier> {
+        let Some(FunctionType::Fixed(function)) = self.function_type else {
+            return None;
+        };
+        let arg = function.args.iter().find(|arg| &arg.name == name)?;
+        match &arg.signature {
+            TypeSignature::TraitReferenceType(trait_identifier)
+            | TypeSignature::CallableType(CallableSubtype::Trait(trait_identifier)) => {
+                Some(trait_identifier.clone())
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stacks_common::types::StacksEpochId;
+
+    use super::*;
+    use crate::vm::analysis::{type_check, CheckError};
+    use crate::vm::ast::parse;
+    use crate::vm::database::MemoryBackingStore;
+
+    fn name(name: &str) -> ClarityName {
+        ClarityName::from(name)
+    }
+
+    #[test]
+    fn test_call_graph() {
+        let epoch = StacksEpochId::Epoch21;
+        let version = ClarityVersion::Clarity2;
+        let other = QualifiedContractIdentifier::local("other").unwrap();
+        let main = QualifiedContractIdentifier::local("main").unwrap();
+        let contracts = [
+            (&other, "(define-public (pay (amount uint)) (ok amount))"),
+            (
+                &main,
+                "(define-trait token-trait ((transfer? (principal principal uint) (response bool uint))))
+                (define-private (fee (amount uint)) (/ amount u100))
+                (define-private (total (amount uint)) (+ amount (fee amount)))
+                (define-private (unused) { fee: u1 })
+                (define-private (double (x uint)) (* x u2))
+                (define-read-only (doubled (xs (list 10 uint))) (map double xs))
+                (define-public (pay (token <token-trait>) (amount uint))
+                    (contract-call? token transfer? tx-sender 'S1G2081040G2081040G2081040G208105NK8PE5 (total amount)))
+                (define-public (forward (amount uint))
+                    (contract-call? .other pay amount))",
+            ),
+        ];
+
+        let mut store = MemoryBackingStore::new();
+        let mut db = store.as_analysis_db();
+        db.execute(|db| {
+            for (contract_id, src) in contracts {
+                let mut expressions = parse(contract_id, src, version, epoch).unwrap();
+                db.test_insert_contract_hash(contract_id);
+                type_check(contract_id, &mut expressions, db, true, &epoch, &version)?;
+            }
+            Ok::<_, CheckError>(())
+        })
+        .unwrap();
+
+        // the call graph is persisted with the analysis
+        let call_graph = db
+            .execute(|db| db.load_contract(&main, &epoch))
+            .unwrap()
+            .unwrap()
+            .call_graph
+            .unwrap();
+        let token_trait = TraitIdentifier {
+            name: name("token-trait"),
+            contract_identifier: main.clone(),
+        };
+
+        assert_eq!(
+            call_graph.functions[&name("pay")].local_calls,
+            BTreeSet::from([name("total")])
+        );
+        assert_eq!(
+            call_graph.functions[&name("doubled")].local_calls,
+            BTreeSet::from([name("double")])
+        );
+        // tuple fields aren't calls
+        assert!(call_graph.functions[&name("unused")].local_calls.is_empty());
+        assert_eq!(
+            call_graph.local_closure(&name("pay")),
+            BTreeSet::from([name("pay"), name("total"), name("fee")])
+        );
+        assert_eq!(
+            call_graph.contract_calls_of(&name("pay")),
+            BTreeSet::from([CallTarget::Trait(token_trait, name("transfer?"))])
+        );
+        assert_eq!(
+            call_graph.contract_calls_of(&name("forward")),
+            BTreeSet::from([CallTarget::Contract(other.clone(), name("pay"))])
+        );
+        assert_eq!(call_graph.called_contracts(), BTreeSet::from([other]));
+        assert_eq!(
+            call_graph.unreachable_functions(),
+            BTreeSet::from([name("unused")])
+        );
+    }
+}
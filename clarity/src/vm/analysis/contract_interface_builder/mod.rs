@@ -20,12 +20,17 @@ use stacks_common::types::StacksEpochId;
 
 use crate::vm::analysis::types::ContractAnalysis;
 use crate::vm::analysis::CheckResult;
-use crate::vm::types::signatures::CallableSubtype;
+use crate::vm::contexts::ContractContext;
+use crate::vm::types::signatures::{CallableSubtype, FunctionSignature};
 use crate::vm::types::{
     FixedFunction, FunctionArg, FunctionType, TupleTypeSignature, TypeSignature,
 };
 use crate::vm::{CheckErrors, ClarityName, ClarityVersion};
 
+/// The version of the `ContractAbi` document format.  It is bumped whenever a field is removed
+///  or changes meaning; fields may be added without bumping it.
+pub const CONTRACT_ABI_VERSION: u32 = 1;
+
 pub fn build_contract_interface(
     contract_analysis: &ContractAnalysis,
 ) -> CheckResult<ContractInterface> {
@@ -41,6 +46,7 @@ pub fn build_contract_interface(
         map_types,
         fungible_tokens,
         non_fungible_tokens,
+        event_types,
         epoch: _,
         clarity_version: _,
         defined_traits: _,
@@ -50,7 +56,9 @@ pub fn build_contract_interface(
         type_map: _,
         cost_track: _,
         contract_interface: _,
+        call_graph: _,
         is_cost_contract_eligible: _,
+        source_map: _,
     } = contract_analysis;
 
     contract_interface
@@ -102,6 +110,10 @@ pub fn build_contract_interface(
             fungible_tokens,
         ));
 
+    contract_interface
+        .events
+        .append(&mut ContractInterfaceEvent::from_map(event_types));
+
     Ok(contract_interface)
 }
 
@@ -376,6 +388,23 @@ impl ContractInterfaceMap {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractInterfaceEvent {
+    pub name: String,
+    pub payload: ContractInterfaceAtomType,
+}
+
+impl ContractInterfaceEvent {
+    fn from_map(map: &BTreeMap<ClarityName, TypeSignature>) -> Vec<ContractInterfaceEvent> {
+        map.iter()
+            .map(|(name, payload_type)| ContractInterfaceEvent {
+                name: name.clone().into(),
+                payload: ContractInterfaceAtomType::from_type_signature(payload_type),
+            })
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ContractInterface {
     pub functions: Vec<ContractInterfaceFunction>,
@@ -383,6 +412,9 @@ pub struct ContractInterface {
     pub maps: Vec<ContractInterfaceMap>,
     pub fungible_tokens: Vec<ContractInterfaceFungibleTokens>,
     pub non_fungible_tokens: Vec<ContractInterfaceNonFungibleTokens>,
+    /// Only serialized for contracts that emit events with `emit-event`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<ContractInterfaceEvent>,
     pub epoch: StacksEpochId,
     pub clarity_version: ClarityVersion,
 }
@@ -395,6 +427,7 @@ impl ContractInterface {
             maps: Vec::new(),
             fungible_tokens: Vec::new(),
             non_fungible_tokens: Vec::new(),
+            events: Vec::new(),
             epoch,
             clarity_version,
         }
@@ -407,6 +440,201 @@ impl ContractInterface {
     }
 }
 
+/// A trait the contract defines
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractAbiTrait {
+    pub name: String,
+    pub functions: Vec<ContractAbiTraitFunction>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractAbiTraitFunction {
+    pub name: String,
+    pub args: Vec<ContractInterfaceAtomType>,
+    pub outputs: ContractInterfaceFunctionOutput,
+}
+
+impl ContractAbiTrait {
+    fn from_map(
+        traits: &BTreeMap<ClarityName, BTreeMap<ClarityName, FunctionSignature>>,
+    ) -> Vec<Self> {
+        traits
+            .iter()
+            .map(|(name, functions)| Self {
+                name: name.to_string(),
+                functions: functions
+                    .iter()
+                    .map(|(name, signature)| ContractAbiTraitFunction {
+                        name: name.to_string(),
+                        args: signature
+                            .args
+                            .iter()
+                            .map(ContractInterfaceAtomType::from_type_signature)
+                            .collect(),
+                        outputs: ContractInterfaceFunctionOutput {
+                            type_f: ContractInterfaceAtomType::from_type_signature(
+                                &signature.returns,
+                            ),
+                        },
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+/// A constant that holds an error: either an `err` response, or (by convention) a constant whose
+///  name starts with `err`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractAbiErrorConstant {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_f: ContractInterfaceAtomType,
+    /// The hex consensus serialization of the constant's value, if it was known
+    pub value: Option<String>,
+}
+
+impl ContractAbiErrorConstant {
+    fn is_error_constant(name: &ClarityName, type_sig: &TypeSignature) -> bool {
+        let is_err_response = matches!(
+            type_sig,
+            TypeSignature::ResponseType(types) if types.0 == TypeSignature::NoType
+        );
+        is_err_response || name.to_ascii_lowercase().starts_with("err")
+    }
+
+    fn from_map(
+        constants: &BTreeMap<ClarityName, TypeSignature>,
+        contract_context: Option<&ContractContext>,
+    ) -> Vec<Self> {
+        constants
+            .iter()
+            .filter(|(name, type_sig)| Self::is_error_constant(name, type_sig))
+            .map(|(name, type_sig)| Self {
+                name: name.to_string(),
+                type_f: ContractInterfaceAtomType::from_type_signature(type_sig),
+                value: contract_context
+                    .and_then(|context| context.variables.get(name))
+                    .and_then(|value| value.serialize_to_hex().ok()),
+            })
+            .collect()
+    }
+}
+
+/// A data var, with the most bytes its value's serialization can take (if its type is
+///  serializable)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractAbiVariable {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_f: ContractInterfaceAtomType,
+    pub max_size: Option<u32>,
+}
+
+impl ContractAbiVariable {
+    fn from_map(map: &BTreeMap<ClarityName, TypeSignature>) -> Vec<Self> {
+        map.iter()
+            .map(|(name, type_sig)| Self {
+                name: name.to_string(),
+                type_f: ContractInterfaceAtomType::from_type_signature(type_sig),
+                max_size: type_sig.max_serialized_size().ok(),
+            })
+            .collect()
+    }
+}
+
+/// A data map, with the most bytes the serializations of its keys and values can take
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractAbiMap {
+    pub name: String,
+    pub key: ContractInterfaceAtomType,
+    pub value: ContractInterfaceAtomType,
+    pub key_max_size: Option<u32>,
+    pub value_max_size: Option<u32>,
+}
+
+impl ContractAbiMap {
+    fn from_map(map: &BTreeMap<ClarityName, (TypeSignature, TypeSignature)>) -> Vec<Self> {
+        map.iter()
+            .map(|(name, (key_sig, value_sig))| Self {
+                name: name.to_string(),
+                key: ContractInterfaceAtomType::from_type_signature(key_sig),
+                value: ContractInterfaceAtomType::from_type_signature(value_sig),
+                key_max_size: key_sig.max_serialized_size().ok(),
+                value_max_size: value_sig.max_serialized_size().ok(),
+            })
+            .collect()
+    }
+}
+
+/// A stable, versioned description of a deployed contract, for tools that generate typed
+///  bindings for it.  Unlike `ContractInterface` (which is stored with the contract, and kept
+///  as it was when the contract was deployed), it only lists the functions that can be called
+///  from outside the contract, and adds the traits the contract defines and implements, its
+///  error constants, and bounds on the sizes of its persisted data.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractAbi {
+    /// See `CONTRACT_ABI_VERSION`
+    pub abi_version: u32,
+    pub contract_id: String,
+    pub epoch: StacksEpochId,
+    pub clarity_version: ClarityVersion,
+    /// The public and read-only functions
+    pub functions: Vec<ContractInterfaceFunction>,
+    /// The traits the contract declares (with `impl-trait`) that it implements
+    pub implemented_traits: Vec<String>,
+    pub defined_traits: Vec<ContractAbiTrait>,
+    pub error_constants: Vec<ContractAbiErrorConstant>,
+    pub variables: Vec<ContractAbiVariable>,
+    pub maps: Vec<ContractAbiMap>,
+    pub fungible_tokens: Vec<ContractInterfaceFungibleTokens>,
+    pub non_fungible_tokens: Vec<ContractInterfaceNonFungibleTokens>,
+    pub events: Vec<ContractInterfaceEvent>,
+}
+
+/// Build the ABI document of the contract with analysis `contract_analysis`.  The values of the
+///  error constants are taken from the contract's `contract_context`, if given.
+pub fn build_contract_abi(
+    contract_analysis: &ContractAnalysis,
+    contract_context: Option<&ContractContext>,
+) -> CheckResult<ContractAbi> {
+    let mut functions = ContractInterfaceFunction::from_map(
+        &contract_analysis.public_function_types,
+        ContractInterfaceFunctionAccess::public,
+    )?;
+    functions.append(&mut ContractInterfaceFunction::from_map(
+        &contract_analysis.read_only_function_types,
+        ContractInterfaceFunctionAccess::read_only,
+    )?);
+
+    Ok(ContractAbi {
+        abi_version: CONTRACT_ABI_VERSION,
+        contract_id: contract_analysis.contract_identifier.to_string(),
+        epoch: contract_analysis.epoch,
+        clarity_version: contract_analysis.clarity_version,
+        functions,
+        implemented_traits: contract_analysis
+            .implemented_traits
+            .iter()
+            .map(|trait_id| trait_id.to_string())
+            .collect(),
+        defined_traits: ContractAbiTrait::from_map(&contract_analysis.defined_traits),
+        error_constants: ContractAbiErrorConstant::from_map(
+            &contract_analysis.variable_types,
+            contract_context,
+        ),
+        variables: ContractAbiVariable::from_map(&contract_analysis.persisted_variable_types),
+        maps: ContractAbiMap::from_map(&contract_analysis.map_types),
+        fungible_tokens: ContractInterfaceFungibleTokens::from_set(
+            &contract_analysis.fungible_tokens,
+        ),
+        non_fungible_tokens: ContractInterfaceNonFungibleTokens::from_map(
+            &contract_analysis.non_fungible_tokens,
+        ),
+        events: ContractInterfaceEvent::from_map(&contract_analysis.event_types),
+    })
+}
+
 #[test]
 fn test_string_rename_ascii() {
     let arg = ContractInterfaceFunctionArg {
@@ -0,0 +1,296 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Cache of the dependencies used while analyzing contracts.
+//!
+//! Analyzing a contract reads the analyses of the contracts it depends on (for their traits and
+//! function types), which means loading and deserializing each of them from the side store.
+//! `DependencyGraph` keeps those analyses, keyed by the commitment to the dependency's code
+//! (its hash and deployment height), so that analyzing many contracts that share dependencies,
+//! or analyzing a contract again, reuses them.  A cached analysis is only used while the
+//! contract's commitment in the store still matches.
+//!
+//! The graph also records which contracts each analyzed contract depended on, so that
+//! `invalidate()` can find everything that has to be analyzed again when a contract changes.
+//!
+//! Costs are charged from the loaded types, as before, so using the cache doesn't change the
+//! cost of an analysis.
+
+use std::collections::{BTreeSet, HashMap};
+
+use crate::vm::analysis::ContractAnalysis;
+use crate::vm::database::clarity_store::ContractCommitment;
+use crate::vm::types::QualifiedContractIdentifier;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DependencyGraph {
+    /// Analyses loaded as dependencies, with the commitment they were loaded for
+    analyses: HashMap<QualifiedContractIdentifier, (ContractCommitment, ContractAnalysis)>,
+    /// The contracts each analyzed contract depends on
+    dependencies: HashMap<QualifiedContractIdentifier, BTreeSet<QualifiedContractIdentifier>>,
+    /// The analyzed contracts that depend on each contract
+    dependents: HashMap<QualifiedContractIdentifier, BTreeSet<QualifiedContractIdentifier>>,
+    /// The contracts loaded since `begin_analysis()`
+    loaded: BTreeSet<QualifiedContractIdentifier>,
+}
+
+impl DependencyGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The cached analysis of `contract`, if it was loaded for the same `commitment`
+    pub fn get(
+        &mut self,
+        contract: &QualifiedContractIdentifier,
+        commitment: &ContractCommitment,
+    ) -> Option<ContractAnalysis> {
+        let (cached_commitment, analysis) = self.analyses.get(contract)?;
+        if cached_commitment != commitment {
+            return None;
+        }
+        let analysis = analysis.clone();
+        self.loaded.insert(contract.clone());
+        Some(analysis)
+    }
+
+    pub fn insert(
+        &mut self,
+        contract: &QualifiedContractIdentifier,
+        commitment: ContractCommitment,
+        analysis: ContractAnalysis,
+    ) {
+        self.loaded.insert(contract.clone());
+        self.analyses
+            .insert(contract.clone(), (commitment, analysis));
+    }
+
+    /// Start recording the contracts loaded by an analysis
+    pub fn begin_analysis(&mut self) {
+        self.loaded.clear();
+    }
+
+    /// Record that `contract` depends on the contracts loaded since `begin_analysis()`
+    pub fn finish_analysis(&mut self, contract: &QualifiedContractIdentifier) {
+        let mut loaded = std::mem::take(&mut self.loaded);
+        loaded.remove(contract);
+        self.remove_dependencies(contract);
+        for dependency in loaded.iter() {
+            self.dependents
+                .entry(dependency.clone())
+                .or_default()
+                .insert(contract.clone());
+        }
+        self.dependencies.insert(contract.clone(), loaded);
+    }
+
+    fn remove_dependencies(&mut self, contract: &QualifiedContractIdentifier) {
+        let Some(dependencies) = self.dependencies.remove(contract) else {
+            return;
+        };
+        for dependency in dependencies.iter() {
+            if let Some(dependents) = self.dependents.get_mut(dependency) {
+                dependents.remove(contract);
+                if dependents.is_empty() {
+                    self.dependents.remove(dependency);
+                }
+            }
+        }
+    }
+
+    /// The contracts that `contract` depended on when it was last analyzed
+    pub fn dependencies_of(
+        &self,
+        contract: &QualifiedContractIdentifier,
+    ) -> BTreeSet<QualifiedContractIdentifier> {
+        self.dependencies.get(contract).cloned().unwrap_or_default()
+    }
+
+    /// The analyzed contracts that depend on `contract`, directly or indirectly
+    pub fn dependents_of(
+        &self,
+        contract: &QualifiedContractIdentifier,
+    ) -> BTreeSet<QualifiedContractIdentifier> {
+        let mut found = BTreeSet::new();
+        let mut frontier = vec![contract.clone()];
+        while let Some(next) = frontier.pop() {
+            let Some(dependents) = self.dependents.get(&next) else {
+                continue;
+            };
+            for dependent in dependents.iter() {
+                if dependent != contract && found.insert(dependent.clone()) {
+                    frontier.push(dependent.clone());
+                }
+            }
+        }
+        found
+    }
+
+    /// Forget `contract`'s analysis and those of every contract that depends on it, directly or
+    /// indirectly.  Returns those dependents, which must be analyzed again to be re-checked
+    /// against the current `contract`.
+    pub fn invalidate(
+        &mut self,
+        contract: &QualifiedContractIdentifier,
+    ) -> BTreeSet<QualifiedContractIdentifier> {
+        let dependents = self.dependents_of(contract);
+        self.analyses.remove(contract);
+        for dependent in dependents.iter() {
+            self.analyses.remove(dependent);
+            self.remove_dependencies(dependent);
+        }
+        dependents
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stacks_common::types::StacksEpochId;
+    use stacks_common::util::hash::Sha512Trunc256Sum;
+
+    use super::*;
+    use crate::vm::analysis::{type_check, CheckError};
+    use crate::vm::ast::parse;
+    use crate::vm::costs::LimitedCostTracker;
+    use crate::vm::database::MemoryBackingStore;
+    use crate::vm::ClarityVersion;
+
+    fn contract(name: &str) -> QualifiedContractIdentifier {
+        QualifiedContractIdentifier::local(name).unwrap()
+    }
+
+    fn commitment(hash: u8) -> ContractCommitment {
+        ContractCommitment {
+            hash: Sha512Trunc256Sum([hash; 32]),
+            block_height: 1,
+        }
+    }
+
+    fn analysis(name: &str) -> ContractAnalysis {
+        ContractAnalysis::new(
+            contract(name),
+            vec![],
+            LimitedCostTracker::new_free(),
+            StacksEpochId::Epoch21,
+            ClarityVersion::Clarity2,
+        )
+    }
+
+    fn analyze(graph: &mut DependencyGraph, name: &str, dependencies: &[&str]) {
+        graph.begin_analysis();
+        for dependency in dependencies {
+            if graph.get(&contract(dependency), &commitment(0)).is_none() {
+                graph.insert(&contract(dependency), commitment(0), analysis(dependency));
+            }
+        }
+        graph.finish_analysis(&contract(name));
+    }
+
+    #[test]
+    fn test_dependency_graph() {
+        let mut graph = DependencyGraph::new();
+        graph.insert(&contract("trait"), commitment(0), analysis("trait"));
+        assert_eq!(
+            graph.get(&contract("trait"), &commitment(0)),
+            Some(analysis("trait"))
+        );
+        // the contract was re-published
+        assert_eq!(graph.get(&contract("trait"), &commitment(1)), None);
+
+        // impl -> trait, user -> impl -> trait, other -> trait
+        analyze(&mut graph, "impl", &["trait"]);
+        analyze(&mut graph, "user", &["impl", "trait"]);
+        analyze(&mut graph, "other", &["trait"]);
+        assert_eq!(
+            graph.dependencies_of(&contract("user")),
+            BTreeSet::from([contract("impl"), contract("trait")])
+        );
+        assert_eq!(
+            graph.dependents_of(&contract("impl")),
+            BTreeSet::from([contract("user")])
+        );
+
+        assert_eq!(
+            graph.invalidate(&contract("trait")),
+            BTreeSet::from([contract("impl"), contract("user"), contract("other")])
+        );
+        assert_eq!(graph.get(&contract("trait"), &commitment(0)), None);
+        assert_eq!(graph.get(&contract("impl"), &commitment(0)), None);
+        assert!(graph.dependencies_of(&contract("user")).is_empty());
+        assert!(graph.dependents_of(&contract("trait")).is_empty());
+    }
+
+    #[test]
+    fn test_analysis_db_dependency_graph() {
+        let epoch = StacksEpochId::Epoch21;
+        let version = ClarityVersion::Clarity2;
+        let contracts = [
+            (
+                "trait",
+                "(define-trait get-trait ((get-1 (uint) (response uint uint))))",
+            ),
+            (
+                "impl-1",
+                "(impl-trait .trait.get-trait) (define-public (get-1 (x uint)) (ok x))",
+            ),
+            (
+                "impl-2",
+                "(impl-trait .trait.get-trait) (define-public (get-1 (x uint)) (ok u2))",
+            ),
+        ];
+
+        let mut store = MemoryBackingStore::new();
+        let mut db = store.as_analysis_db();
+        db.execute(|db| {
+            for (name, src) in contracts.iter() {
+                let mut expressions = parse(&contract(name), src, version, epoch).unwrap();
+                db.test_insert_contract_hash(&contract(name));
+                type_check(
+                    &contract(name),
+                    &mut expressions,
+                    db,
+                    true,
+                    &epoch,
+                    &version,
+                )?;
+            }
+            Ok::<_, CheckError>(())
+        })
+        .unwrap();
+
+        let graph = db.dependency_graph();
+        assert_eq!(
+            graph.dependencies_of(&contract("impl-1")),
+            BTreeSet::from([contract("trait")])
+        );
+        assert_eq!(
+            graph.dependents_of(&contract("trait")),
+            BTreeSet::from([contract("impl-1"), contract("impl-2")])
+        );
+
+        // the graph can be carried over to another analysis database
+        let graph = db.take_dependency_graph();
+        drop(db);
+        let mut db = store.as_analysis_db().with_dependency_graph(graph);
+        assert_eq!(
+            db.invalidate(&contract("trait")),
+            BTreeSet::from([contract("impl-1"), contract("impl-2")])
+        );
+        assert!(db
+            .dependency_graph()
+            .dependents_of(&contract("trait"))
+            .is_empty());
+    }
+}
@@ -139,6 +139,9 @@ pub enum CheckErrors {
     GetStacksBlockInfoExpectPropertyName,
     GetTenureInfoExpectPropertyName,
 
+    // emit-event
+    EmitEventExpectName,
+
     NameAlreadyUsed(String),
     ReservedWord(String),
 
@@ -416,6 +419,7 @@ impl DiagnosableError for CheckErrors {
             CheckErrors::GetBurnBlockInfoExpectPropertyName => "missing property name for burn block info introspection".into(),
             CheckErrors::GetStacksBlockInfoExpectPropertyName => "missing property name for stacks block info introspection".into(),
             CheckErrors::GetTenureInfoExpectPropertyName => "missing property name for tenure info introspection".into(),
+            CheckErrors::EmitEventExpectName => "expecting a string literal event name".into(),
             CheckErrors::NameAlreadyUsed(name) => format!("defining '{}' conflicts with previous value", name),
             CheckErrors::ReservedWord(name) => format!("{name} is a reserved word"),
             CheckErrors::NonFunctionApplication => "expecting expression of type function".into(),
@@ -0,0 +1,354 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Lints for Clarity contracts.
+//!
+//! Unlike the analysis passes, lints never reject a contract: they flag code that is legal but
+//! likely to be a mistake.  They run on a contract that has passed analysis, and take its Clarity
+//! version into account (e.g., binding a name that a later Clarity version reserves).
+
+use hashbrown::{HashMap, HashSet};
+
+use crate::vm::analysis::types::ContractAnalysis;
+use crate::vm::diagnostic::{Diagnostic, Level};
+use crate::vm::functions::define::DefineFunctionsParsed;
+use crate::vm::representations::{ClarityName, Span, SymbolicExpression};
+use crate::vm::{is_reserved, ClarityVersion};
+
+#[cfg(test)]
+mod tests;
+
+define_named_enum!(Lint {
+    UncheckedUnwrapPanic("unchecked-unwrap-panic"),
+    UnusedConstant("unused-constant"),
+    ShadowedBinding("shadowed-binding"),
+    TxSenderInTransfer("tx-sender-in-transfer"),
+});
+
+/// How a lint's findings are reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl LintLevel {
+    pub fn lookup_by_name(name: &str) -> Option<LintLevel> {
+        match name {
+            "allow" => Some(LintLevel::Allow),
+            "warn" => Some(LintLevel::Warn),
+            "deny" => Some(LintLevel::Deny),
+            _ => None,
+        }
+    }
+}
+
+/// The level of each lint.  Lints that are not set are at `LintLevel::Warn`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LintConfig {
+    levels: HashMap<Lint, LintLevel>,
+}
+
+impl LintConfig {
+    pub fn new() -> LintConfig {
+        LintConfig::default()
+    }
+
+    pub fn set_level(&mut self, lint: Lint, level: LintLevel) {
+        self.levels.insert(lint, level);
+    }
+
+    pub fn level(&self, lint: Lint) -> LintLevel {
+        self.levels.get(&lint).copied().unwrap_or(LintLevel::Warn)
+    }
+}
+
+/// A finding of a lint.  The diagnostic's level is `Level::Error` for denied lints, and
+///  `Level::Warning` otherwise.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LintDiagnostic {
+    pub lint: Lint,
+    pub diagnostic: Diagnostic,
+}
+
+impl LintDiagnostic {
+    pub fn is_denied(&self) -> bool {
+        self.diagnostic.level == Level::Error
+    }
+}
+
+const CLARITY_VERSIONS: [ClarityVersion; 4] = [
+    ClarityVersion::Clarity1,
+    ClarityVersion::Clarity2,
+    ClarityVersion::Clarity3,
+    ClarityVersion::Clarity4,
+];
+
+const UNWRAP_PANIC_FUNCTIONS: [&str; 2] = ["unwrap-panic", "unwrap-err-panic"];
+
+const TRANSFER_FUNCTIONS: [&str; 4] = [
+    "stx-transfer?",
+    "stx-transfer-memo?",
+    "ft-transfer?",
+    "nft-transfer?",
+];
+
+pub struct Linter<'a> {
+    contract_analysis: &'a ContractAnalysis,
+    config: &'a LintConfig,
+    diagnostics: Vec<LintDiagnostic>,
+}
+
+impl<'a> Linter<'a> {
+    /// Lint the contract with analysis `contract_analysis`.  Returns the findings of the lints
+    ///  that `config` does not allow, in source order.
+    pub fn run(contract_analysis: &ContractAnalysis, config: &LintConfig) -> Vec<LintDiagnostic> {
+        let mut linter = Linter {
+            contract_analysis,
+            config,
+            diagnostics: vec![],
+        };
+
+        let mut constants = vec![];
+        let mut used_names = HashSet::new();
+        for expr in contract_analysis.expressions.iter() {
+            linter.check_unwrap_panics(expr);
+            match DefineFunctionsParsed::try_parse(expr) {
+                Ok(Some(DefineFunctionsParsed::Constant { name, value })) => {
+                    constants.push((name, expr));
+                    collect_atoms(value, &mut used_names);
+                }
+                Ok(Some(DefineFunctionsParsed::PrivateFunction { signature, body }))
+                | Ok(Some(DefineFunctionsParsed::ReadOnlyFunction { signature, body }))
+                | Ok(Some(DefineFunctionsParsed::PublicFunction { signature, body })) => {
+                    for arg in signature.iter().skip(1) {
+                        if let Some(arg_name) = arg
+                            .match_list()
+                            .and_then(|arg| arg.first())
+                            .and_then(|name| name.match_atom())
+                        {
+                            linter.check_binding(arg_name, arg);
+                        }
+                    }
+                    linter.check_bindings(body);
+                    if contains_transfer(body) {
+                        linter.check_tx_sender(body);
+                    }
+                    collect_atoms(expr, &mut used_names);
+                }
+                _ => collect_atoms(expr, &mut used_names),
+            }
+        }
+        for (name, expr) in constants {
+            if !used_names.contains(name) {
+                linter.report(
+                    Lint::UnusedConstant,
+                    expr,
+                    format!("constant '{}' is never used", name),
+                    None,
+                );
+            }
+        }
+
+        let Linter {
+            mut diagnostics, ..
+        } = linter;
+        diagnostics.sort_by(|a, b| a.diagnostic.spans.cmp(&b.diagnostic.spans));
+        diagnostics
+    }
+
+    fn report(
+        &mut self,
+        lint: Lint,
+        expr: &SymbolicExpression,
+        message: String,
+        suggestion: Option<String>,
+    ) {
+        let level = match self.config.level(lint) {
+            LintLevel::Allow => return,
+            LintLevel::Warn => Level::Warning,
+            LintLevel::Deny => Level::Error,
+        };
+        let span = self
+            .contract_analysis
+            .get_span(expr.id)
+            .cloned()
+            .unwrap_or_else(Span::zero);
+        self.diagnostics.push(LintDiagnostic {
+            lint,
+            diagnostic: Diagnostic {
+                level,
+                message: format!("{} ({})", message, lint),
+                spans: vec![span],
+                suggestion,
+            },
+        });
+    }
+
+    fn check_unwrap_panics(&mut self, expr: &SymbolicExpression) {
+        let Some(list) = expr.match_list() else {
+            return;
+        };
+        if let Some(function) = function_name(list) {
+            if UNWRAP_PANIC_FUNCTIONS.contains(&function) {
+                self.report(
+                    Lint::UncheckedUnwrapPanic,
+                    expr,
+                    format!(
+                        "'{}' aborts the transaction without an error code",
+                        function
+                    ),
+                    Some("use 'unwrap!' or 'unwrap-err!' with an error response".into()),
+                );
+            }
+        }
+        for child in list.iter() {
+            self.check_unwrap_panics(child);
+        }
+    }
+
+    /// Check the names bound by `let` and `match` in `expr`
+    fn check_bindings(&mut self, expr: &SymbolicExpression) {
+        let Some(list) = expr.match_list() else {
+            return;
+        };
+        match function_name(list) {
+            Some("let") => {
+                if let Some(bindings) = list.get(1).and_then(|b| b.match_list()) {
+                    for binding in bindings.iter() {
+                        if let Some(name) = binding
+                            .match_list()
+                            .and_then(|pair| pair.first())
+                            .and_then(|name| name.match_atom())
+                        {
+                            self.check_binding(name, binding);
+                        }
+                    }
+                }
+            }
+            // (match option some-name some-branch none-branch), or
+            // (match response ok-name ok-branch err-name err-branch)
+            Some("match") => {
+                let names: &[usize] = if list.len() == 6 { &[2, 4] } else { &[2] };
+                for index in names {
+                    if let Some(name) = list.get(*index).and_then(|name| name.match_atom()) {
+                        self.check_binding(name, &list[*index]);
+                    }
+                }
+            }
+            _ => {}
+        }
+        for child in list.iter() {
+            self.check_bindings(child);
+        }
+    }
+
+    fn check_binding(&mut self, name: &ClarityName, expr: &SymbolicExpression) {
+        if self.is_defined(name) {
+            self.report(
+                Lint::ShadowedBinding,
+                expr,
+                format!("'{}' shadows a definition of this contract", name),
+                None,
+            );
+            return;
+        }
+        let version = self.contract_analysis.clarity_version;
+        if is_reserved(name, &version) {
+            return;
+        }
+        if let Some(reserved_in) = CLARITY_VERSIONS
+            .iter()
+            .find(|later| **later > version && is_reserved(name, later))
+        {
+            self.report(
+                Lint::ShadowedBinding,
+                expr,
+                format!(
+                    "'{}' is a reserved name in {}, so this contract can't move to it unchanged",
+                    name, reserved_in
+                ),
+                None,
+            );
+        }
+    }
+
+    fn is_defined(&self, name: &ClarityName) -> bool {
+        let analysis = self.contract_analysis;
+        analysis.variable_types.contains_key(name)
+            || analysis.persisted_variable_types.contains_key(name)
+            || analysis.map_types.contains_key(name)
+            || analysis.private_function_types.contains_key(name)
+            || analysis.public_function_types.contains_key(name)
+            || analysis.read_only_function_types.contains_key(name)
+            || analysis.fungible_tokens.contains(name)
+            || analysis.non_fungible_tokens.contains_key(name)
+            || analysis.defined_traits.contains_key(name)
+    }
+
+    /// Check the uses of `tx-sender` outside of `as-contract` in a function that transfers
+    ///  tokens.  Any contract the sender calls can call the function on their behalf.
+    fn check_tx_sender(&mut self, expr: &SymbolicExpression) {
+        if expr.match_atom().map(|name| name.as_str()) == Some("tx-sender") {
+            self.report(
+                Lint::TxSenderInTransfer,
+                expr,
+                "'tx-sender' is used in a function that transfers tokens".into(),
+                Some(
+                    "any contract the sender calls can act as them here: consider 'contract-caller'"
+                        .into(),
+                ),
+            );
+            return;
+        }
+        let Some(list) = expr.match_list() else {
+            return;
+        };
+        if function_name(list) == Some("as-contract") {
+            return;
+        }
+        for child in list.iter() {
+            self.check_tx_sender(child);
+        }
+    }
+}
+
+/// The name of the function that `list` applies, if it is an application
+fn function_name(list: &[SymbolicExpression]) -> Option<&str> {
+    list.first()
+        .and_then(|function| function.match_atom())
+        .map(|function| function.as_str())
+}
+
+fn collect_atoms(expr: &SymbolicExpression, atoms: &mut HashSet<ClarityName>) {
+    if let Some(name) = expr.match_atom() {
+        atoms.insert(name.clone());
+    } else if let Some(list) = expr.match_list() {
+        for child in list.iter() {
+            collect_atoms(child, atoms);
+        }
+    }
+}
+
+fn contains_transfer(expr: &SymbolicExpression) -> bool {
+    let Some(list) = expr.match_list() else {
+        return false;
+    };
+    let is_transfer = function_name(list)
+        .map(|function| TRANSFER_FUNCTIONS.contains(&function))
+        .unwrap_or(false);
+    is_transfer || list.iter().any(contains_transfer)
+}
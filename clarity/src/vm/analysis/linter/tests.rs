@@ -0,0 +1,84 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use stacks_common::types::StacksEpochId;
+
+use crate::vm::analysis::linter::{Lint, LintConfig, LintLevel, Linter};
+use crate::vm::diagnostic::Level;
+use crate::vm::tooling::mem_type_check;
+use crate::vm::ClarityVersion;
+
+fn lint(contract: &str, version: ClarityVersion, config: &LintConfig) -> Vec<(Lint, Level)> {
+    let analysis = mem_type_check(contract, version, StacksEpochId::Epoch21)
+        .unwrap()
+        .1;
+    Linter::run(&analysis, config)
+        .into_iter()
+        .map(|finding| (finding.lint, finding.diagnostic.level))
+        .collect()
+}
+
+#[test]
+fn test_lints() {
+    let contract = "
+        (define-constant ERR-UNUSED (err u1))
+        (define-constant FEE u10)
+        (define-fungible-token token)
+        (define-read-only (get-fee) FEE)
+        (define-public (pay (amount uint) (recipient principal))
+            (let ((tenure-height u1))
+                (try! (ft-transfer? token (+ amount (get-fee)) tx-sender recipient))
+                (ok (unwrap-panic (some tenure-height)))))
+        (define-private (helper)
+            (let ((get-fee u1)) get-fee))
+    ";
+
+    assert_eq!(
+        lint(contract, ClarityVersion::Clarity2, &LintConfig::new()),
+        vec![
+            (Lint::UncheckedUnwrapPanic, Level::Warning),
+            // `tenure-height` is reserved in Clarity 3
+            (Lint::ShadowedBinding, Level::Warning),
+            (Lint::TxSenderInTransfer, Level::Warning),
+            // the read-only function `get-fee`
+            (Lint::ShadowedBinding, Level::Warning),
+            (Lint::UnusedConstant, Level::Warning),
+        ]
+    );
+}
+
+#[test]
+fn test_lint_levels() {
+    let contract = "
+        (define-constant OWNER tx-sender)
+        (define-public (withdraw (amount uint) (recipient principal))
+            (as-contract (stx-transfer? amount tx-sender recipient)))
+        (define-read-only (get-one) (unwrap-panic (some u1)))
+    ";
+    let mut config = LintConfig::new();
+    config.set_level(Lint::UncheckedUnwrapPanic, LintLevel::Allow);
+    config.set_level(Lint::UnusedConstant, LintLevel::Deny);
+
+    // `tx-sender` is the contract inside `as-contract`
+    assert_eq!(
+        lint(contract, ClarityVersion::Clarity1, &config),
+        vec![(Lint::UnusedConstant, Level::Error)]
+    );
+    assert_eq!(
+        Lint::lookup_by_name("tx-sender-in-transfer"),
+        Some(Lint::TxSenderInTransfer)
+    );
+    assert_eq!(LintLevel::lookup_by_name("deny"), Some(LintLevel::Deny));
+}
@@ -16,9 +16,12 @@
 
 pub mod analysis_db;
 pub mod arithmetic_checker;
+pub mod call_graph;
 pub mod contract_interface_builder;
+pub mod dependency_graph;
 #[allow(clippy::result_large_err)]
 pub mod errors;
+pub mod linter;
 pub mod read_only_checker;
 pub mod trait_checker;
 pub mod type_checker;
@@ -28,7 +31,10 @@ use stacks_common::types::StacksEpochId;
 
 pub use self::analysis_db::AnalysisDatabase;
 use self::arithmetic_checker::ArithmeticOnlyChecker;
+use self::call_graph::build_call_graph;
+pub use self::call_graph::ContractCallGraph;
 use self::contract_interface_builder::build_contract_interface;
+pub use self::dependency_graph::DependencyGraph;
 pub use self::errors::{CheckError, CheckErrors, CheckResult};
 use self::read_only_checker::ReadOnlyChecker;
 use self::trait_checker::TraitChecker;
@@ -41,7 +47,7 @@ use crate::vm::costs::LimitedCostTracker;
 use crate::vm::database::MemoryBackingStore;
 use crate::vm::database::STORE_CONTRACT_SRC_INTERFACE;
 use crate::vm::representations::SymbolicExpression;
-use crate::vm::types::{QualifiedContractIdentifier, TypeSignature};
+use crate::vm::types::{QualifiedContractIdentifier, TypeSignature, ValueLimits};
 use crate::vm::ClarityVersion;
 
 /// Used by CLI tools like the docs generator. Not used in production
@@ -130,6 +136,12 @@ pub fn run_analysis(
     version: ClarityVersion,
     build_type_map: bool,
 ) -> Result<ContractAnalysis, (CheckError, LimitedCostTracker)> {
+    let value_limits = if cost_tracker.is_mainnet() {
+        ValueLimits::CONSENSUS
+    } else {
+        analysis_db.get_value_limits()
+    };
+    let _value_limits = value_limits.enter();
     let mut contract_analysis = ContractAnalysis::new(
         contract_identifier.clone(),
         expressions.to_vec(),
@@ -138,6 +150,7 @@ pub fn run_analysis(
         version,
     );
     let result = analysis_db.execute(|db| {
+        db.begin_dependency_tracking();
         ReadOnlyChecker::run_pass(&epoch, &mut contract_analysis, db)?;
         match epoch {
             StacksEpochId::Epoch20 | StacksEpochId::Epoch2_05 => {
@@ -148,7 +161,8 @@ pub fn run_analysis(
             | StacksEpochId::Epoch23
             | StacksEpochId::Epoch24
             | StacksEpochId::Epoch25
-            | StacksEpochId::Epoch30 => {
+            | StacksEpochId::Epoch30
+            | StacksEpochId::Epoch31 => {
                 TypeChecker2_1::run_pass(&epoch, &mut contract_analysis, db, build_type_map)
             }
             StacksEpochId::Epoch10 => {
@@ -160,6 +174,7 @@ pub fn run_analysis(
         }?;
         TraitChecker::run_pass(&epoch, &mut contract_analysis, db)?;
         ArithmeticOnlyChecker::check_contract_cost_eligible(&mut contract_analysis);
+        contract_analysis.call_graph = Some(build_call_graph(&contract_analysis)?);
 
         if STORE_CONTRACT_SRC_INTERFACE {
             let interface = build_contract_interface(&contract_analysis)?;
@@ -168,6 +183,7 @@ pub fn run_analysis(
         if save_contract {
             db.insert_contract(contract_identifier, &contract_analysis)?;
         }
+        db.finish_dependency_tracking(contract_identifier);
         Ok(())
     });
     match result {
@@ -294,7 +294,9 @@ impl<'a, 'b> ReadOnlyChecker<'a, 'b> {
             | AsContract | Begin | FetchVar | GetStxBalance | StxGetAccount | GetTokenBalance
             | GetAssetOwner | GetTokenSupply | ElementAt | IndexOf | Slice | ReplaceAt
             | BitwiseAnd | BitwiseOr | BitwiseNot | BitwiseLShift | BitwiseRShift | BitwiseXor2
-            | ElementAtAlias | IndexOfAlias => {
+            | ElementAtAlias | IndexOfAlias | Secp256k1VerifyMany | ImplementsTrait | EmitEvent
+            | BuffAnd | BuffOr | BuffXor | BuffNot | AltBn128Add | AltBn128Mul
+            | AltBn128PairingCheck | BlockVrfSeed => {
                 // Check all arguments.
                 self.check_each_expression_is_read_only(args)
             }
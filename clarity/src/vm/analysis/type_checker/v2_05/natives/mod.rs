@@ -773,7 +773,9 @@ impl TypedNativeFunction {
             | StringToUInt | IntToAscii | IntToUtf8 | GetBurnBlockInfo | StxTransferMemo
             | StxGetAccount | BitwiseAnd | BitwiseOr | BitwiseNot | BitwiseLShift
             | BitwiseRShift | BitwiseXor2 | Slice | ToConsensusBuff | FromConsensusBuff
-            | ReplaceAt | GetStacksBlockInfo | GetTenureInfo => {
+            | ReplaceAt | GetStacksBlockInfo | GetTenureInfo | Secp256k1VerifyMany
+            | ImplementsTrait | EmitEvent | BuffAnd | BuffOr | BuffXor | BuffNot | AltBn128Add
+            | AltBn128Mul | AltBn128PairingCheck | BlockVrfSeed => {
                 return Err(CheckErrors::Expects(
                     "Clarity 2+ keywords should not show up in 2.05".into(),
                 )
@@ -17,6 +17,7 @@
 use std::collections::BTreeMap;
 
 use hashbrown::{HashMap, HashSet};
+use stacks_common::types::StacksEpochId;
 
 use crate::vm::analysis::errors::{CheckError, CheckErrors, CheckResult};
 use crate::vm::analysis::type_checker::is_reserved_word;
@@ -43,10 +44,12 @@ impl TraitContext {
     pub fn new(clarity_version: ClarityVersion) -> TraitContext {
         match clarity_version {
             ClarityVersion::Clarity1 => Self::Clarity1(HashMap::new()),
-            ClarityVersion::Clarity2 | ClarityVersion::Clarity3 => Self::Clarity2 {
-                defined: HashSet::new(),
-                all: HashMap::new(),
-            },
+            ClarityVersion::Clarity2 | ClarityVersion::Clarity3 | ClarityVersion::Clarity4 => {
+                Self::Clarity2 {
+                    defined: HashSet::new(),
+                    all: HashMap::new(),
+                }
+            }
         }
     }
 
@@ -141,6 +144,7 @@ pub struct ContractContext {
     non_fungible_tokens: HashMap<ClarityName, TypeSignature>,
     traits: TraitContext,
     pub implemented_traits: HashSet<TraitIdentifier>,
+    event_types: HashMap<ClarityName, TypeSignature>,
 }
 
 impl ContractContext {
@@ -161,6 +165,7 @@ impl ContractContext {
             non_fungible_tokens: HashMap::new(),
             traits: TraitContext::new(clarity_version),
             implemented_traits: HashSet::new(),
+            event_types: HashMap::new(),
         }
     }
 
@@ -322,6 +327,26 @@ impl ContractContext {
         self.traits.get_trait(trait_id)
     }
 
+    /// Record that the contract emits the event `name` with a payload of type `payload_type`.
+    /// Each event's type is the least supertype of all the payloads it is emitted with.
+    pub fn add_event_type(
+        &mut self,
+        epoch: &StacksEpochId,
+        name: ClarityName,
+        payload_type: TypeSignature,
+    ) -> CheckResult<()> {
+        let event_type = match self.event_types.get(&name) {
+            Some(event_type) => TypeSignature::least_supertype(epoch, event_type, &payload_type)?,
+            None => payload_type,
+        };
+        self.event_types.insert(name, event_type);
+        Ok(())
+    }
+
+    pub fn get_event_type(&self, name: &str) -> Option<&TypeSignature> {
+        self.event_types.get(name)
+    }
+
     pub fn get_map_type(&self, map_name: &str) -> Option<&(TypeSignature, TypeSignature)> {
         self.map_types.get(map_name)
     }
@@ -384,5 +409,9 @@ impl ContractContext {
         for trait_identifier in self.implemented_traits.drain() {
             contract_analysis.add_implemented_trait(trait_identifier);
         }
+
+        for (name, event_type) in self.event_types.drain() {
+            contract_analysis.add_event_type(name, event_type);
+        }
     }
 }
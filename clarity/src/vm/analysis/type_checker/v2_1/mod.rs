@@ -51,7 +51,7 @@ use crate::vm::types::{
     parse_name_type_pairs, CallableData, FixedFunction, FunctionArg, FunctionType, ListData,
     ListTypeData, OptionalData, PrincipalData, QualifiedContractIdentifier, ResponseData,
     SequenceData, SequenceSubtype, StringSubtype, TraitIdentifier, TupleData, TupleTypeSignature,
-    TypeSignature, Value, MAX_TYPE_DEPTH,
+    TypeSignature, Value, ValueLimits,
 };
 use crate::vm::variables::NativeVariables;
 use crate::vm::ClarityVersion;
@@ -472,7 +472,7 @@ impl FunctionType {
 
     #[allow(clippy::only_used_in_recursion)]
     fn clarity2_principal_to_callable_type(&self, value: &Value, depth: u8) -> TypeResult {
-        if depth > MAX_TYPE_DEPTH {
+        if depth > ValueLimits::current().max_type_depth {
             return Err(CheckErrors::TypeSignatureTooDeep.into());
         }
 
@@ -738,7 +738,7 @@ fn clarity2_inner_type_check_type<T: CostTracker>(
     depth: u8,
     tracker: &mut T,
 ) -> TypeResult {
-    if depth > MAX_TYPE_DEPTH {
+    if depth > ValueLimits::current().max_type_depth {
         return Err(CheckErrors::TypeSignatureTooDeep.into());
     }
 
@@ -17,7 +17,7 @@
 use stacks_common::types::StacksEpochId;
 
 use super::{
-    check_argument_count, check_arguments_at_least, check_arguments_at_most,
+    check_argument_count, check_arguments_at_least, check_arguments_at_most, clarity2_lookup_trait,
     compute_typecheck_cost, no_type, TypeChecker, TypeResult, TypingContext,
 };
 use crate::vm::analysis::errors::{CheckError, CheckErrors, CheckResult};
@@ -27,7 +27,8 @@ use crate::vm::costs::{
     CostTracker,
 };
 use crate::vm::errors::{Error as InterpError, RuntimeErrorType};
-use crate::vm::functions::{handle_binding_list, NativeFunctions};
+use crate::vm::functions::{handle_binding_list, parse_event_name, NativeFunctions};
+use crate::vm::representations::TraitDefinition;
 use crate::vm::types::signatures::{
     CallableSubtype, FunctionArgSignature, FunctionReturnsSignature, SequenceSubtype, ASCII_40,
     UTF8_40,
@@ -36,8 +37,9 @@ use crate::vm::types::TypeSignature::SequenceType;
 use crate::vm::types::{
     BlockInfoProperty, BufferLength, BurnBlockInfoProperty, FixedFunction, FunctionArg,
     FunctionSignature, FunctionType, PrincipalData, StacksBlockInfoProperty, TenureInfoProperty,
-    TupleTypeSignature, TypeSignature, Value, BUFF_1, BUFF_20, BUFF_32, BUFF_33, BUFF_64, BUFF_65,
-    MAX_VALUE_SIZE,
+    TraitIdentifier, TupleTypeSignature, TypeSignature, Value, ValueLimits,
+    ALT_BN128_PAIRING_ENTRY, BUFF_1, BUFF_20, BUFF_32, BUFF_33, BUFF_64, BUFF_65,
+    SECP256K1_SIGNATURE_ENTRY,
 };
 use crate::vm::{ClarityName, ClarityVersion, SymbolicExpression, SymbolicExpressionType};
 
@@ -83,7 +85,7 @@ fn check_special_list_cons(
             entries_size = cur_size.checked_add(checked.size()?);
         }
         if let Some(cur_size) = entries_size {
-            if cur_size > MAX_VALUE_SIZE {
+            if cur_size > ValueLimits::current().max_value_size {
                 entries_size = None;
             }
         }
@@ -113,6 +115,25 @@ fn check_special_print(
     checker.type_check(&args[0], context)
 }
 
+fn check_special_emit_event(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(2, args)?;
+
+    let name = parse_event_name(&args[0])?;
+    let payload_type = checker.type_check(&args[1], context)?;
+    if !matches!(payload_type, TypeSignature::TupleType(_)) {
+        return Err(CheckErrors::ExpectedTuple(payload_type).into());
+    }
+
+    checker
+        .contract_context
+        .add_event_type(&checker.epoch, name, payload_type.clone())?;
+    Ok(payload_type)
+}
+
 fn check_special_as_contract(
     checker: &mut TypeChecker,
     args: &[SymbolicExpression],
@@ -242,7 +263,7 @@ pub fn check_special_tuple_cons(
                 checker,
                 var_type.type_size()?,
             )?;
-            if type_size < MAX_VALUE_SIZE {
+            if type_size < ValueLimits::current().max_value_size {
                 type_size = type_size
                     .saturating_add(var_name.len() as u32)
                     .saturating_add(var_name.len() as u32)
@@ -633,6 +654,47 @@ fn check_contract_of(
     Ok(TypeSignature::PrincipalType)
 }
 
+/// The trait named by the trait argument of `implements-trait?`: a trait identifier
+/// (`.contract.trait` or `'ADDRESS.contract.trait`) or a trait reference (`<trait>`)
+fn trait_identifier_of(expr: &SymbolicExpression) -> CheckResult<TraitIdentifier> {
+    match &expr.expr {
+        SymbolicExpressionType::Field(trait_id) => Ok(trait_id.clone()),
+        SymbolicExpressionType::TraitReference(_, TraitDefinition::Defined(trait_id))
+        | SymbolicExpressionType::TraitReference(_, TraitDefinition::Imported(trait_id)) => {
+            Ok(trait_id.clone())
+        }
+        _ => Err(CheckErrors::ExpectedTraitIdentifier.into()),
+    }
+}
+
+fn check_implements_trait(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(2, args)?;
+    // the principal can also be a trait-typed argument
+    let is_trait_reference = match &args[0].expr {
+        SymbolicExpressionType::Atom(name) => context.lookup_trait_reference_type(name).is_some(),
+        _ => false,
+    };
+    if !is_trait_reference {
+        checker.type_check_expects(&args[0], context, &TypeSignature::PrincipalType)?;
+    }
+
+    runtime_cost(ClarityCostFunction::ImplementsTrait, checker, 1)?;
+
+    let trait_id = trait_identifier_of(&args[1])?;
+    clarity2_lookup_trait(
+        checker.db,
+        Some(&checker.contract_context),
+        &trait_id,
+        &mut checker.cost_track,
+    )?;
+
+    Ok(TypeSignature::BoolType)
+}
+
 fn check_principal_of(
     checker: &mut TypeChecker,
     args: &[SymbolicExpression],
@@ -710,6 +772,108 @@ fn check_secp256k1_verify(
     Ok(TypeSignature::BoolType)
 }
 
+fn check_secp256k1_verify_many(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(1, args)?;
+    let entries_type = checker.type_check(&args[0], context)?;
+    let max_len = match entries_type {
+        SequenceType(SequenceSubtype::ListType(ref list_data)) => list_data.get_max_len(),
+        _ => return Err(CheckErrors::ExpectedSequence(entries_type).into()),
+    };
+    let expected_type = TypeSignature::list_of(SECP256K1_SIGNATURE_ENTRY.clone(), max_len)?;
+    if !expected_type.admits_type(&StacksEpochId::Epoch21, &entries_type)? {
+        return Err(CheckErrors::TypeError(expected_type, entries_type).into());
+    }
+    Ok(TypeSignature::list_of(TypeSignature::BoolType, max_len)?)
+}
+
+fn check_alt_bn128_add(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(2, args)?;
+    checker.type_check_expects(&args[0], context, &BUFF_64)?;
+    checker.type_check_expects(&args[1], context, &BUFF_64)?;
+    Ok(TypeSignature::new_response(
+        BUFF_64.clone(),
+        TypeSignature::UIntType,
+    )?)
+}
+
+fn check_alt_bn128_mul(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(2, args)?;
+    checker.type_check_expects(&args[0], context, &BUFF_64)?;
+    checker.type_check_expects(&args[1], context, &BUFF_32)?;
+    Ok(TypeSignature::new_response(
+        BUFF_64.clone(),
+        TypeSignature::UIntType,
+    )?)
+}
+
+fn check_alt_bn128_pairing_check(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(1, args)?;
+    let entries_type = checker.type_check(&args[0], context)?;
+    let max_len = match entries_type {
+        SequenceType(SequenceSubtype::ListType(ref list_data)) => list_data.get_max_len(),
+        _ => return Err(CheckErrors::ExpectedSequence(entries_type).into()),
+    };
+    let expected_type = TypeSignature::list_of(ALT_BN128_PAIRING_ENTRY.clone(), max_len)?;
+    if !expected_type.admits_type(&StacksEpochId::Epoch21, &entries_type)? {
+        return Err(CheckErrors::TypeError(expected_type, entries_type).into());
+    }
+    Ok(TypeSignature::new_response(
+        TypeSignature::BoolType,
+        TypeSignature::UIntType,
+    )?)
+}
+
+/// `buff-and`, `buff-or` and `buff-xor` return a buffer as long as the longer of their inputs
+fn check_buff_bitwise(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(2, args)?;
+    let mut max_len = 0u32;
+    for arg in args.iter() {
+        let arg_type = checker.type_check(arg, context)?;
+        match arg_type {
+            SequenceType(SequenceSubtype::BufferType(ref len)) => {
+                max_len = max_len.max(u32::from(len))
+            }
+            _ => return Err(CheckErrors::TypeError(TypeSignature::max_buffer()?, arg_type).into()),
+        }
+    }
+    Ok(SequenceType(SequenceSubtype::BufferType(
+        BufferLength::try_from(max_len)?,
+    )))
+}
+
+fn check_buff_not(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(1, args)?;
+    let arg_type = checker.type_check(&args[0], context)?;
+    match arg_type {
+        SequenceType(SequenceSubtype::BufferType(_)) => Ok(arg_type),
+        _ => Err(CheckErrors::TypeError(TypeSignature::max_buffer()?, arg_type).into()),
+    }
+}
+
 fn check_get_block_info(
     checker: &mut TypeChecker,
     args: &[SymbolicExpression],
@@ -991,6 +1155,12 @@ impl TypedNativeFunction {
             ))),
             Secp256k1Recover => Special(SpecialNativeFunction(&check_secp256k1_recover)),
             Secp256k1Verify => Special(SpecialNativeFunction(&check_secp256k1_verify)),
+            Secp256k1VerifyMany => Special(SpecialNativeFunction(&check_secp256k1_verify_many)),
+            BuffAnd | BuffOr | BuffXor => Special(SpecialNativeFunction(&check_buff_bitwise)),
+            BuffNot => Special(SpecialNativeFunction(&check_buff_not)),
+            AltBn128Add => Special(SpecialNativeFunction(&check_alt_bn128_add)),
+            AltBn128Mul => Special(SpecialNativeFunction(&check_alt_bn128_mul)),
+            AltBn128PairingCheck => Special(SpecialNativeFunction(&check_alt_bn128_pairing_check)),
             GetStxBalance => Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
                 args: vec![FunctionArg::new(
                     TypeSignature::PrincipalType,
@@ -1131,14 +1301,27 @@ impl TypedNativeFunction {
             TupleMerge => Special(SpecialNativeFunction(&check_special_merge)),
             Begin => Special(SpecialNativeFunction(&check_special_begin)),
             Print => Special(SpecialNativeFunction(&check_special_print)),
+            EmitEvent => Special(SpecialNativeFunction(&check_special_emit_event)),
             AsContract => Special(SpecialNativeFunction(&check_special_as_contract)),
             ContractCall => Special(SpecialNativeFunction(&check_contract_call)),
             ContractOf => Special(SpecialNativeFunction(&check_contract_of)),
+            ImplementsTrait => Special(SpecialNativeFunction(&check_implements_trait)),
             PrincipalOf => Special(SpecialNativeFunction(&check_principal_of)),
             GetBlockInfo => Special(SpecialNativeFunction(&check_get_block_info)),
             GetBurnBlockInfo => Special(SpecialNativeFunction(&check_get_burn_block_info)),
             GetStacksBlockInfo => Special(SpecialNativeFunction(&check_get_stacks_block_info)),
             GetTenureInfo => Special(SpecialNativeFunction(&check_get_tenure_info)),
+            BlockVrfSeed => Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
+                args: vec![FunctionArg::new(
+                    TypeSignature::UIntType,
+                    ClarityName::try_from("stacks-block-height".to_owned()).map_err(|_| {
+                        CheckErrors::Expects(
+                            "FAIL: ClarityName failed to accept default arg name".into(),
+                        )
+                    })?,
+                )],
+                returns: TypeSignature::new_option(BUFF_32.clone())?,
+            }))),
             ConsSome => Special(SpecialNativeFunction(&options::check_special_some)),
             ConsOkay => Special(SpecialNativeFunction(&options::check_special_okay)),
             ConsError => Special(SpecialNativeFunction(&options::check_special_error)),
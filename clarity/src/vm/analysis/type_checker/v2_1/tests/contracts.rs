@@ -20,7 +20,9 @@ use assert_json_diff::assert_json_eq;
 use serde_json;
 use stacks_common::types::StacksEpochId;
 
-use crate::vm::analysis::contract_interface_builder::build_contract_interface;
+use crate::vm::analysis::contract_interface_builder::{
+    build_contract_interface, CONTRACT_ABI_VERSION,
+};
 use crate::vm::analysis::errors::CheckErrors;
 use crate::vm::analysis::type_checker::v2_1::tests::mem_type_check;
 use crate::vm::analysis::{
@@ -433,7 +435,7 @@ fn test_names_tokens_contracts_interface() {
         "fungible_tokens": [],
         "non_fungible_tokens": [],
         "epoch": "Epoch21",
-        "clarity_version": "Clarity3"
+        "clarity_version": "Clarity4"
     }"#).unwrap();
 
     eprintln!("{}", test_contract_json_str);
@@ -441,6 +443,57 @@ fn test_names_tokens_contracts_interface() {
     assert_json_eq!(test_contract_json, test_contract_json_expected);
 }
 
+#[test]
+fn test_contract_abi() {
+    let contract = "
+        (define-trait transfer-trait ((transfer (uint principal) (response bool uint))))
+        (define-constant ERR-UNAUTHORIZED (err u401))
+        (define-constant err-low-balance u402)
+        (define-constant owner tx-sender)
+        (define-data-var counter uint u0)
+        (define-map balances principal uint)
+        (define-private (helper) u1)
+        (define-read-only (get-counter) (var-get counter))
+        (define-public (increment) (ok (var-set counter (+ (var-get counter) (helper)))))
+    ";
+    let contract_analysis = mem_type_check(contract).unwrap().1;
+    let abi = contract_analysis.export_abi(None).unwrap();
+    let abi_json = serde_json::to_value(&abi).unwrap();
+
+    assert_eq!(abi.abi_version, CONTRACT_ABI_VERSION);
+    // private functions can't be called from outside the contract
+    let function_names: Vec<_> = abi.functions.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(function_names, vec!["increment", "get-counter"]);
+
+    assert_json_eq!(
+        abi_json["defined_traits"].clone(),
+        serde_json::json!([{
+            "name": "transfer-trait",
+            "functions": [{
+                "name": "transfer",
+                "args": ["uint128", "principal"],
+                "outputs": { "type": { "response": { "ok": "bool", "error": "uint128" } } }
+            }]
+        }])
+    );
+    assert_json_eq!(
+        abi_json["error_constants"].clone(),
+        serde_json::json!([
+            {
+                "name": "ERR-UNAUTHORIZED",
+                "type": { "response": { "ok": "none", "error": "uint128" } },
+                "value": null
+            },
+            { "name": "err-low-balance", "type": "uint128", "value": null }
+        ])
+    );
+    assert_json_eq!(
+        abi_json["variables"].clone(),
+        serde_json::json!([{ "name": "counter", "type": "uint128", "max_size": 17 }])
+    );
+    assert_eq!(abi.maps[0].value_max_size, Some(17));
+}
+
 #[apply(test_clarity_versions)]
 fn test_names_tokens_contracts(#[case] version: ClarityVersion, #[case] epoch: StacksEpochId) {
     let tokens_contract_id = QualifiedContractIdentifier::local("tokens").unwrap();
@@ -2740,7 +2793,7 @@ fn clarity_trait_experiments_downcast_literal_2(
         })
         .unwrap_err();
     match version {
-        ClarityVersion::Clarity2 | ClarityVersion::Clarity3 => {
+        ClarityVersion::Clarity2 | ClarityVersion::Clarity3 | ClarityVersion::Clarity4 => {
             assert!(err.starts_with("ExpectedCallableType(PrincipalType)"))
         }
         ClarityVersion::Clarity1 => {
@@ -2937,7 +2990,7 @@ fn clarity_trait_experiments_trait_cast_incompatible(
                 assert!(err.starts_with("TypeError(CallableType(Trait(TraitIdentifier"))
             }
         }
-        ClarityVersion::Clarity2 | ClarityVersion::Clarity3 => {
+        ClarityVersion::Clarity2 | ClarityVersion::Clarity3 | ClarityVersion::Clarity4 => {
             assert!(err.starts_with("IncompatibleTrait"))
         }
     }
@@ -533,6 +533,208 @@ fn test_tx_sponsor() {
     }
 }
 
+#[test]
+fn test_emit_event() {
+    let contract = "(define-public (pay)
+            (begin
+                (emit-event \"transfer\" { amount: u10, memo: \"rent\" })
+                (ok (emit-event \"transfer\" { amount: u1, memo: \"hi\" }))))";
+    let (_, analysis) =
+        mem_run_analysis(contract, ClarityVersion::Clarity4, StacksEpochId::Epoch31).unwrap();
+    let event_type = analysis
+        .event_types
+        .get(&ClarityName::from("transfer"))
+        .unwrap();
+    assert_eq!(
+        "(tuple (amount uint) (memo (string-ascii 4)))",
+        &format!("{}", event_type)
+    );
+
+    let bad = [
+        "(emit-event transfer { amount: u10 })",
+        "(emit-event u1 { amount: u10 })",
+        "(emit-event \"transfer\" u10)",
+        "(emit-event \"transfer\" { amount: u10 } { amount: u11 })",
+        "(begin (emit-event \"transfer\" { amount: u10 }) (emit-event \"transfer\" { memo: u10 }))",
+    ];
+    let bad_expected = [
+        CheckErrors::EmitEventExpectName,
+        CheckErrors::EmitEventExpectName,
+        CheckErrors::ExpectedTuple(UIntType),
+        CheckErrors::IncorrectArgumentCount(2, 3),
+        CheckErrors::TypeError(
+            TypeSignature::from_string(
+                "(tuple (amount uint))",
+                ClarityVersion::Clarity4,
+                StacksEpochId::Epoch31,
+            ),
+            TypeSignature::from_string(
+                "(tuple (memo uint))",
+                ClarityVersion::Clarity4,
+                StacksEpochId::Epoch31,
+            ),
+        ),
+    ];
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(
+            expected,
+            &mem_run_analysis(bad_test, ClarityVersion::Clarity4, StacksEpochId::Epoch31)
+                .unwrap_err()
+                .err
+        );
+    }
+
+    // emit-event is only available from Clarity 4
+    assert_eq!(
+        CheckErrors::UnknownFunction("emit-event".to_string()),
+        mem_run_analysis(
+            "(emit-event \"transfer\" { amount: u10 })",
+            ClarityVersion::Clarity3,
+            StacksEpochId::Epoch31
+        )
+        .unwrap_err()
+        .err
+    );
+}
+
+#[test]
+fn test_buff_bitwise() {
+    let good = [
+        "(buff-and 0x0102 0x03)",
+        "(buff-or 0x01 0x020304)",
+        "(buff-xor 0x0102 0x0304)",
+        "(buff-not 0x010203)",
+    ];
+    let expected = [buff_type(2), buff_type(3), buff_type(2), buff_type(3)];
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &mem_run_analysis(good_test, ClarityVersion::Clarity4, StacksEpochId::Epoch31)
+                .unwrap()
+                .0
+                .unwrap()
+        );
+    }
+
+    let bad = [
+        "(buff-and 0x01 u1)",
+        "(buff-or 0x01)",
+        "(buff-not \"abc\")",
+        "(buff-xor 0x01 0x02 0x03)",
+    ];
+    let bad_expected = [
+        CheckErrors::TypeError(TypeSignature::max_buffer().unwrap(), UIntType),
+        CheckErrors::IncorrectArgumentCount(2, 1),
+        CheckErrors::TypeError(
+            TypeSignature::max_buffer().unwrap(),
+            TypeSignature::SequenceType(StringType(ASCII(3u32.try_into().unwrap()))),
+        ),
+        CheckErrors::IncorrectArgumentCount(2, 3),
+    ];
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(
+            expected,
+            &mem_run_analysis(bad_test, ClarityVersion::Clarity4, StacksEpochId::Epoch31)
+                .unwrap_err()
+                .err
+        );
+    }
+
+    // the buffer natives are only available from Clarity 4
+    assert_eq!(
+        CheckErrors::UnknownFunction("buff-not".to_string()),
+        mem_run_analysis(
+            "(buff-not 0x01)",
+            ClarityVersion::Clarity3,
+            StacksEpochId::Epoch31
+        )
+        .unwrap_err()
+        .err
+    );
+}
+
+#[test]
+fn test_alt_bn128() {
+    let point_result = TypeSignature::new_response(BUFF_64.clone(), UIntType).unwrap();
+    let good = [
+        "(alt-bn128-add? 0x01 0x02)",
+        "(alt-bn128-mul? 0x01 0x02)",
+        "(alt-bn128-pairing-check? (list { g1: 0x01, g2: 0x02 }))",
+        "(alt-bn128-pairing-check? (list))",
+    ];
+    let expected = [
+        point_result.clone(),
+        point_result,
+        TypeSignature::new_response(BoolType, UIntType).unwrap(),
+        TypeSignature::new_response(BoolType, UIntType).unwrap(),
+    ];
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &mem_run_analysis(good_test, ClarityVersion::Clarity4, StacksEpochId::Epoch31)
+                .unwrap()
+                .0
+                .unwrap()
+        );
+    }
+
+    let long_point = format!("0x{}", "00".repeat(65));
+    let bad = [
+        "(alt-bn128-mul? 0x01 u1)".to_string(),
+        "(alt-bn128-add? 0x01)".to_string(),
+        format!("(alt-bn128-add? 0x01 {long_point})"),
+        "(alt-bn128-pairing-check? 0x01)".to_string(),
+    ];
+    let bad_expected = [
+        CheckErrors::TypeError(BUFF_32.clone(), UIntType),
+        CheckErrors::IncorrectArgumentCount(2, 1),
+        CheckErrors::TypeError(BUFF_64.clone(), buff_type(65)),
+        CheckErrors::ExpectedSequence(buff_type(1)),
+    ];
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(
+            expected,
+            &mem_run_analysis(bad_test, ClarityVersion::Clarity4, StacksEpochId::Epoch31)
+                .unwrap_err()
+                .err
+        );
+    }
+}
+
+#[test]
+fn test_block_vrf_seed() {
+    assert_eq!(
+        TypeSignature::new_option(BUFF_32.clone()).unwrap(),
+        mem_run_analysis(
+            "(block-vrf-seed u1)",
+            ClarityVersion::Clarity4,
+            StacksEpochId::Epoch31
+        )
+        .unwrap()
+        .0
+        .unwrap()
+    );
+
+    let bad = [
+        ("(block-vrf-seed 1)", ClarityVersion::Clarity4),
+        ("(block-vrf-seed u1 u2)", ClarityVersion::Clarity4),
+        ("(block-vrf-seed u1)", ClarityVersion::Clarity3),
+    ];
+    let bad_expected = [
+        CheckErrors::TypeError(UIntType, IntType),
+        CheckErrors::IncorrectArgumentCount(1, 2),
+        CheckErrors::UnknownFunction("block-vrf-seed".to_string()),
+    ];
+    for ((bad_test, version), expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(
+            expected,
+            &mem_run_analysis(bad_test, *version, StacksEpochId::Epoch31)
+                .unwrap_err()
+                .err
+        );
+    }
+}
+
 #[apply(test_clarity_versions)]
 fn test_destructuring_opts(#[case] version: ClarityVersion, #[case] epoch: StacksEpochId) {
     let good = [
@@ -20,10 +20,15 @@ use hashbrown::HashMap;
 use stacks_common::types::StacksEpochId;
 
 use crate::vm::analysis::analysis_db::AnalysisDatabase;
-use crate::vm::analysis::contract_interface_builder::ContractInterface;
+use crate::vm::analysis::call_graph::ContractCallGraph;
+use crate::vm::analysis::contract_interface_builder::{
+    build_contract_abi, ContractAbi, ContractInterface,
+};
 use crate::vm::analysis::errors::{CheckErrors, CheckResult};
 use crate::vm::analysis::type_checker::contexts::TypeMap;
+use crate::vm::contexts::ContractContext;
 use crate::vm::costs::{CostTracker, ExecutionCost, LimitedCostTracker};
+use crate::vm::representations::Span;
 use crate::vm::types::signatures::FunctionSignature;
 use crate::vm::types::{FunctionType, QualifiedContractIdentifier, TraitIdentifier, TypeSignature};
 use crate::vm::{ClarityName, ClarityVersion, SymbolicExpression};
@@ -33,6 +38,47 @@ const DESERIALIZE_FAIL_MESSAGE: &str =
 const SERIALIZE_FAIL_MESSAGE: &str =
     "PANIC: Failed to deserialize bad database data in contract analysis.";
 
+/// Stores a source map as a list of `(id, start_line, start_column, end_line, end_column)`,
+///  which is much smaller than a map of `Span` objects
+mod source_map_serde {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserializer, Serializer};
+
+    use crate::vm::representations::Span;
+
+    pub fn serialize<S: Serializer>(map: &BTreeMap<u64, Span>, ser: S) -> Result<S::Ok, S::Error> {
+        ser.collect_seq(map.iter().map(|(id, span)| {
+            (
+                id,
+                span.start_line,
+                span.start_column,
+                span.end_line,
+                span.end_column,
+            )
+        }))
+    }
+
+    pub fn deserialize<'de, D>(deser: D) -> Result<BTreeMap<u64, Span>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let entries: Vec<(u64, u32, u32, u32, u32)> = serde::Deserialize::deserialize(deser)?;
+        Ok(entries
+            .into_iter()
+            .map(|(id, start_line, start_column, end_line, end_column)| {
+                let span = Span {
+                    start_line,
+                    start_column,
+                    end_line,
+                    end_column,
+                };
+                (id, span)
+            })
+            .collect())
+    }
+}
+
 pub trait AnalysisPass {
     fn run_pass(
         epoch: &StacksEpochId,
@@ -54,10 +100,20 @@ pub struct ContractAnalysis {
     pub non_fungible_tokens: BTreeMap<ClarityName, TypeSignature>,
     pub defined_traits: BTreeMap<ClarityName, BTreeMap<ClarityName, FunctionSignature>>,
     pub implemented_traits: BTreeSet<TraitIdentifier>,
+    /// The payload types of the events emitted with `emit-event`, by event name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub event_types: BTreeMap<ClarityName, TypeSignature>,
     pub contract_interface: Option<ContractInterface>,
+    /// Not recorded for contracts analyzed before the call graph was added
+    #[serde(default)]
+    pub call_graph: Option<ContractCallGraph>,
     pub is_cost_contract_eligible: bool,
     pub epoch: StacksEpochId,
     pub clarity_version: ClarityVersion,
+    /// The span of source that each expression was parsed from, by expression id (see
+    ///  `ContractAST::source_map`).  Empty for contracts analyzed before source maps were kept.
+    #[serde(default, with = "source_map_serde")]
+    pub source_map: BTreeMap<u64, Span>,
     #[serde(skip)]
     pub expressions: Vec<SymbolicExpression>,
     #[serde(skip)]
@@ -79,6 +135,7 @@ impl ContractAnalysis {
             expressions,
             type_map: None,
             contract_interface: None,
+            call_graph: None,
             private_function_types: BTreeMap::new(),
             public_function_types: BTreeMap::new(),
             read_only_function_types: BTreeMap::new(),
@@ -87,15 +144,31 @@ impl ContractAnalysis {
             persisted_variable_types: BTreeMap::new(),
             defined_traits: BTreeMap::new(),
             implemented_traits: BTreeSet::new(),
+            event_types: BTreeMap::new(),
             fungible_tokens: BTreeSet::new(),
             non_fungible_tokens: BTreeMap::new(),
             cost_track: Some(cost_track),
             is_cost_contract_eligible: false,
             epoch,
             clarity_version,
+            source_map: BTreeMap::new(),
         }
     }
 
+    /// The span of source that expression `id` was parsed from, if it is known
+    pub fn get_span(&self, id: u64) -> Option<&Span> {
+        self.source_map.get(&id)
+    }
+
+    /// Export the contract's ABI document (see `ContractAbi`).  The values of its error
+    ///  constants are only included if the contract's `contract_context` is given.
+    pub fn export_abi(
+        &self,
+        contract_context: Option<&ContractContext>,
+    ) -> CheckResult<ContractAbi> {
+        build_contract_abi(self, contract_context)
+    }
+
     #[allow(clippy::expect_used)]
     pub fn take_contract_cost_tracker(&mut self) -> LimitedCostTracker {
         self.cost_track
@@ -162,6 +235,10 @@ impl ContractAnalysis {
         self.implemented_traits.insert(trait_identifier);
     }
 
+    pub fn add_event_type(&mut self, name: ClarityName, event_type: TypeSignature) {
+        self.event_types.insert(name, event_type);
+    }
+
     pub fn get_public_function_type(&self, name: &str) -> Option<&FunctionType> {
         self.public_function_types.get(name)
     }
@@ -217,6 +294,9 @@ impl ContractAnalysis {
         for (_, nft_type) in self.non_fungible_tokens.iter_mut() {
             *nft_type = nft_type.canonicalize(epoch);
         }
+        for (_, event_type) in self.event_types.iter_mut() {
+            *event_type = event_type.canonicalize(epoch);
+        }
         for (_, trait_definition) in self.defined_traits.iter_mut() {
             for (_, function_signature) in trait_definition.iter_mut() {
                 *function_signature = function_signature.canonicalize(epoch);
@@ -17,7 +17,7 @@
 use crate::vm::ast::errors::{ParseError, ParseErrors, ParseResult};
 use crate::vm::ast::types::{BuildASTPass, ContractAST};
 use crate::vm::representations::PreSymbolicExpressionType::List;
-use crate::vm::representations::SymbolicExpressionCommon;
+use crate::vm::representations::{SymbolicExpression, SymbolicExpressionCommon};
 use crate::vm::ClarityVersion;
 
 fn inner_relabel<T: SymbolicExpressionCommon>(args: &mut [T], index: u64) -> ParseResult<u64> {
@@ -42,6 +42,17 @@ pub fn update_expression_id<T: SymbolicExpressionCommon>(exprs: &mut [T]) -> Par
     Ok(())
 }
 
+/// The ids of `exprs` and their sub-expressions, in the order that `update_expression_id()`
+///  assigns them
+fn collect_expression_ids(exprs: &[SymbolicExpression], ids: &mut Vec<u64>) {
+    for expression in exprs.iter() {
+        ids.push(expression.id);
+        if let Some(exprs) = expression.match_list() {
+            collect_expression_ids(exprs, ids);
+        }
+    }
+}
+
 pub struct ExpressionIdentifier;
 
 impl ExpressionIdentifier {
@@ -56,7 +67,19 @@ impl ExpressionIdentifier {
         contract_ast: &mut ContractAST,
         _version: ClarityVersion,
     ) -> ParseResult<()> {
+        let mut pre_expression_ids = vec![];
+        collect_expression_ids(&contract_ast.expressions, &mut pre_expression_ids);
         update_expression_id(contract_ast.expressions.as_mut_slice())?;
+
+        // the sugar expander keyed the source map by the ids of the pre-expressions
+        let mut expression_ids = vec![];
+        collect_expression_ids(&contract_ast.expressions, &mut expression_ids);
+        let pre_expression_spans = std::mem::take(&mut contract_ast.source_map);
+        for (pre_id, id) in pre_expression_ids.into_iter().zip(expression_ids) {
+            if let Some(span) = pre_expression_spans.get(&pre_id) {
+                contract_ast.source_map.insert(id, span.clone());
+            }
+        }
         Ok(())
     }
 }
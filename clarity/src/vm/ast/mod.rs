@@ -141,7 +141,9 @@ fn build_ast_typical<T: CostTracker>(
 }
 
 /// Used by developer tools only. Continues on through errors by inserting
-/// placeholders into the AST. Collects as many diagnostics as possible.
+/// placeholders into the AST (or, before epoch 2.1, by skipping the expressions
+/// that fail to parse). Collects as many diagnostics as possible, each with the
+/// span of the source it refers to where one is known.
 /// Always returns a ContractAST, a vector of diagnostics, and a boolean
 /// that indicates if the build was successful.
 #[allow(clippy::unwrap_used)]
@@ -190,16 +192,15 @@ fn inner_build_ast<T: CostTracker>(
         } else {
             parser::v2::parse_collect_diagnostics(source_code)
         }
-    } else {
-        let parse_result = match ast_rules {
+    } else if error_early {
+        let pre_expressions = match ast_rules {
             ASTRules::Typical => parse_v1_no_stack_limit(source_code),
             ASTRules::PrecheckSize => parse_v1(source_code),
-        };
-        match parse_result {
-            Ok(pre_expressions) => (pre_expressions, vec![], true),
-            Err(error) if error_early => return Err(error),
-            Err(error) => (vec![], vec![error.diagnostic], false),
-        }
+        }?;
+        (pre_expressions, vec![], true)
+    } else {
+        // collecting diagnostics always applies `ASTRules::PrecheckSize`
+        parser::v1::parse_collect_diagnostics(source_code)
     };
 
     if let Some(e) = cost_err {
@@ -328,7 +329,9 @@ mod test {
     use crate::vm::costs::{LimitedCostTracker, *};
     use crate::vm::representations::depth_traverse;
     use crate::vm::types::QualifiedContractIdentifier;
-    use crate::vm::{ClarityCostFunction, ClarityName, ClarityVersion, MAX_CALL_STACK_DEPTH};
+    use crate::vm::{
+        ClarityCostFunction, ClarityName, ClarityVersion, SymbolicExpression, MAX_CALL_STACK_DEPTH,
+    };
 
     #[derive(PartialEq, Debug)]
     struct UnitTestTracker {
@@ -621,4 +624,41 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn test_source_map() {
+        let contract = "(define-constant t { a: 1 })\n(+ 1 2)";
+        let ast = build_ast(
+            &QualifiedContractIdentifier::transient(),
+            contract,
+            &mut (),
+            ClarityVersion::Clarity2,
+            StacksEpochId::Epoch21,
+        )
+        .unwrap();
+        let span_of = |expr: &SymbolicExpression| {
+            ast.source_map.get(&expr.id).map(|span| {
+                (
+                    span.start_line,
+                    span.start_column,
+                    span.end_line,
+                    span.end_column,
+                )
+            })
+        };
+
+        let define = &ast.expressions[0];
+        assert_eq!(span_of(define), Some((1, 1, 1, 28)));
+        // `{ a: 1 }` is expanded to `(tuple (a 1))`, which keeps the span of the tuple, but
+        // the `tuple` and the pair that the expansion introduced have none
+        let tuple = &define.match_list().unwrap()[2];
+        assert_eq!(span_of(tuple), Some((1, 20, 1, 27)));
+        let tuple = tuple.match_list().unwrap();
+        assert_eq!(span_of(&tuple[0]), None);
+        assert_eq!(span_of(&tuple[1]), None);
+
+        let add = &ast.expressions[1];
+        assert_eq!(span_of(add), Some((2, 1, 2, 7)));
+        assert_eq!(span_of(&add.match_list().unwrap()[2]), Some((2, 6, 2, 6)));
+    }
 }
@@ -23,6 +23,7 @@ use stacks_common::util::hash::hex_bytes;
 
 use crate::vm::ast::errors::{ParseError, ParseErrors, ParseResult};
 use crate::vm::ast::stack_depth_checker::AST_CALL_STACK_DEPTH_BUFFER;
+use crate::vm::diagnostic::Diagnostic;
 use crate::vm::errors::{InterpreterResult as Result, RuntimeErrorType};
 use crate::vm::representations::{
     ClarityName, ContractName, PreSymbolicExpression, PreSymbolicExpressionType, MAX_STRING_LEN,
@@ -190,7 +191,13 @@ lazy_static! {
 }
 
 /// Lex the contract, permitting nesting of lists and tuples up to `max_nesting`.
-fn inner_lex(input: &str, max_nesting: u64) -> ParseResult<Vec<(LexItem, u32, u32)>> {
+/// Unless `fail_fast` is set, tokens that fail to lex are skipped and their errors are returned
+/// alongside the tokens (too-deep nesting still fails).
+fn inner_lex(
+    input: &str,
+    max_nesting: u64,
+    fail_fast: bool,
+) -> ParseResult<(Vec<(LexItem, u32, u32)>, Vec<ParseError>)> {
     let mut context = LexContext::ExpectNothing;
 
     let mut line_indices = get_lines_at(input);
@@ -198,6 +205,7 @@ fn inner_lex(input: &str, max_nesting: u64) -> ParseResult<Vec<(LexItem, u32, u3
     let mut current_line: u32 = 1;
 
     let mut result = Vec::new();
+    let mut errors = Vec::new();
     let mut munch_index = 0;
     let mut column_pos: u32 = 1;
     let mut did_match = true;
@@ -225,229 +233,275 @@ fn inner_lex(input: &str, max_nesting: u64) -> ParseResult<Vec<(LexItem, u32, u3
                 assert_eq!(whole_match.start(), 0);
                 munch_index += whole_match.end();
 
-                match context {
-                    LexContext::ExpectNothing => Ok(()),
-                    LexContext::ExpectClosing => {
-                        // expect the next lexed item to be something that typically
-                        // "closes" an atom -- i.e., whitespace or a right-parens.
-                        // this prevents an atom like 1234abc from getting split into "1234" and "abc"
-                        match matcher.handler {
-                            TokenType::RParens => Ok(()),
-                            TokenType::RCurly => Ok(()),
-                            TokenType::Whitespace => Ok(()),
-                            TokenType::Comma => Ok(()),
-                            TokenType::Colon => Ok(()),
-                            _ => Err(ParseError::new(ParseErrors::SeparatorExpected(
-                                current_slice[..whole_match.end()].to_string(),
-                            ))),
+                let token = (|| -> ParseResult<LexItem> {
+                    match context {
+                        LexContext::ExpectNothing => Ok(()),
+                        LexContext::ExpectClosing => {
+                            // expect the next lexed item to be something that typically
+                            // "closes" an atom -- i.e., whitespace or a right-parens.
+                            // this prevents an atom like 1234abc from getting split into "1234" and "abc"
+                            match matcher.handler {
+                                TokenType::RParens => Ok(()),
+                                TokenType::RCurly => Ok(()),
+                                TokenType::Whitespace => Ok(()),
+                                TokenType::Comma => Ok(()),
+                                TokenType::Colon => Ok(()),
+                                _ => Err(ParseError::new(ParseErrors::SeparatorExpected(
+                                    current_slice[..whole_match.end()].to_string(),
+                                ))),
+                            }
                         }
-                    }
-                    LexContext::ExpectClosingColon => {
-                        // handle the expected whitespace after a `:`
-                        match matcher.handler {
-                            TokenType::RParens => Ok(()),
-                            TokenType::RCurly => Ok(()),
-                            TokenType::Whitespace => Ok(()),
-                            TokenType::Comma => Ok(()),
-                            TokenType::Colon => Ok(()),
-                            _ => Err(ParseError::new(ParseErrors::SeparatorExpectedAfterColon(
-                                current_slice[..whole_match.end()].to_string(),
-                            ))),
+                        LexContext::ExpectClosingColon => {
+                            // handle the expected whitespace after a `:`
+                            match matcher.handler {
+                                TokenType::RParens => Ok(()),
+                                TokenType::RCurly => Ok(()),
+                                TokenType::Whitespace => Ok(()),
+                                TokenType::Comma => Ok(()),
+                                TokenType::Colon => Ok(()),
+                                _ => {
+                                    Err(ParseError::new(ParseErrors::SeparatorExpectedAfterColon(
+                                        current_slice[..whole_match.end()].to_string(),
+                                    )))
+                                }
+                            }
                         }
-                    }
-                }?;
-
-                // default to expect a closing
-                context = LexContext::ExpectClosing;
-
-                let token = match matcher.handler {
-                    TokenType::LParens => {
-                        context = LexContext::ExpectNothing;
-                        nesting_depth += 1;
-                        if nesting_depth > max_nesting {
-                            return Err(ParseError::new(
-                                ParseErrors::VaryExpressionStackDepthTooDeep,
-                            ));
+                    }?;
+
+                    // default to expect a closing
+                    context = LexContext::ExpectClosing;
+
+                    match matcher.handler {
+                        TokenType::LParens => {
+                            context = LexContext::ExpectNothing;
+                            nesting_depth += 1;
+                            if nesting_depth > max_nesting {
+                                return Err(ParseError::new(
+                                    ParseErrors::VaryExpressionStackDepthTooDeep,
+                                ));
+                            }
+                            Ok(LexItem::LeftParen)
                         }
-                        Ok(LexItem::LeftParen)
-                    }
-                    TokenType::RParens => {
-                        // if this underflows, the contract is invalid anyway
-                        nesting_depth = nesting_depth.saturating_sub(1);
-                        Ok(LexItem::RightParen)
-                    }
-                    TokenType::Whitespace => {
-                        context = LexContext::ExpectNothing;
-                        Ok(LexItem::Whitespace)
-                    }
-                    TokenType::Comma => {
-                        context = LexContext::ExpectNothing;
-                        Ok(LexItem::CommaSeparator)
-                    }
-                    TokenType::Colon => {
-                        // colon should not be followed directly by an item,
-                        //  e.g., {a:b} should not be legal
-                        context = LexContext::ExpectClosingColon;
-                        Ok(LexItem::ColonSeparator)
-                    }
-                    TokenType::LCurly => {
-                        context = LexContext::ExpectNothing;
-                        nesting_depth += 1;
-                        if nesting_depth > max_nesting {
-                            return Err(ParseError::new(
-                                ParseErrors::VaryExpressionStackDepthTooDeep,
-                            ));
+                        TokenType::RParens => {
+                            // if this underflows, the contract is invalid anyway
+                            nesting_depth = nesting_depth.saturating_sub(1);
+                            Ok(LexItem::RightParen)
                         }
-                        Ok(LexItem::LeftCurly)
-                    }
-                    TokenType::RCurly => {
-                        // if this underflows, the contract is invalid anyway
-                        nesting_depth = nesting_depth.saturating_sub(1);
-                        Ok(LexItem::RightCurly)
-                    }
-                    TokenType::Variable => {
-                        let value = get_value_or_err(current_slice, captures)?;
-                        if value.contains('#') {
-                            Err(ParseError::new(ParseErrors::IllegalVariableName(value)))
-                        } else {
-                            Ok(LexItem::Variable(value))
+                        TokenType::Whitespace => {
+                            context = LexContext::ExpectNothing;
+                            Ok(LexItem::Whitespace)
                         }
-                    }
-                    TokenType::UIntLiteral => {
-                        let str_value = get_value_or_err(current_slice, captures)?;
-                        let value = match str_value.parse::<u128>() {
-                            Ok(parsed) => Ok(Value::UInt(parsed)),
-                            Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingIntValue(
-                                str_value.clone(),
-                            ))),
-                        }?;
-                        Ok(LexItem::LiteralValue(str_value.len(), value))
-                    }
-                    TokenType::IntLiteral => {
-                        let str_value = get_value_or_err(current_slice, captures)?;
-                        let value = match str_value.parse::<i128>() {
-                            Ok(parsed) => Ok(Value::Int(parsed)),
-                            Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingIntValue(
-                                str_value.clone(),
-                            ))),
-                        }?;
-                        Ok(LexItem::LiteralValue(str_value.len(), value))
-                    }
-                    TokenType::FullyQualifiedContractIdentifierLiteral => {
-                        let str_value = get_value_or_err(current_slice, captures)?;
-                        let value =
-                            match PrincipalData::parse_qualified_contract_principal(&str_value) {
-                                Ok(parsed) => Ok(Value::Principal(parsed)),
+                        TokenType::Comma => {
+                            context = LexContext::ExpectNothing;
+                            Ok(LexItem::CommaSeparator)
+                        }
+                        TokenType::Colon => {
+                            // colon should not be followed directly by an item,
+                            //  e.g., {a:b} should not be legal
+                            context = LexContext::ExpectClosingColon;
+                            Ok(LexItem::ColonSeparator)
+                        }
+                        TokenType::LCurly => {
+                            context = LexContext::ExpectNothing;
+                            nesting_depth += 1;
+                            if nesting_depth > max_nesting {
+                                return Err(ParseError::new(
+                                    ParseErrors::VaryExpressionStackDepthTooDeep,
+                                ));
+                            }
+                            Ok(LexItem::LeftCurly)
+                        }
+                        TokenType::RCurly => {
+                            // if this underflows, the contract is invalid anyway
+                            nesting_depth = nesting_depth.saturating_sub(1);
+                            Ok(LexItem::RightCurly)
+                        }
+                        TokenType::Variable => {
+                            let value = get_value_or_err(current_slice, captures)?;
+                            if value.contains('#') {
+                                Err(ParseError::new(ParseErrors::IllegalVariableName(value)))
+                            } else {
+                                Ok(LexItem::Variable(value))
+                            }
+                        }
+                        TokenType::UIntLiteral => {
+                            let str_value = get_value_or_err(current_slice, captures)?;
+                            let value = match str_value.parse::<u128>() {
+                                Ok(parsed) => Ok(Value::UInt(parsed)),
+                                Err(_e) => Err(ParseError::new(
+                                    ParseErrors::FailedParsingIntValue(str_value.clone()),
+                                )),
+                            }?;
+                            Ok(LexItem::LiteralValue(str_value.len(), value))
+                        }
+                        TokenType::IntLiteral => {
+                            let str_value = get_value_or_err(current_slice, captures)?;
+                            let value = match str_value.parse::<i128>() {
+                                Ok(parsed) => Ok(Value::Int(parsed)),
+                                Err(_e) => Err(ParseError::new(
+                                    ParseErrors::FailedParsingIntValue(str_value.clone()),
+                                )),
+                            }?;
+                            Ok(LexItem::LiteralValue(str_value.len(), value))
+                        }
+                        TokenType::FullyQualifiedContractIdentifierLiteral => {
+                            let str_value = get_value_or_err(current_slice, captures)?;
+                            let value =
+                                match PrincipalData::parse_qualified_contract_principal(&str_value)
+                                {
+                                    Ok(parsed) => Ok(Value::Principal(parsed)),
+                                    Err(_e) => Err(ParseError::new(
+                                        ParseErrors::FailedParsingPrincipal(str_value.clone()),
+                                    )),
+                                }?;
+                            Ok(LexItem::LiteralValue(str_value.len(), value))
+                        }
+                        TokenType::SugaredContractIdentifierLiteral => {
+                            let str_value = get_value_or_err(current_slice, captures)?;
+                            let value = match str_value[1..].to_string().try_into() {
+                                Ok(parsed) => Ok(parsed),
                                 Err(_e) => Err(ParseError::new(
                                     ParseErrors::FailedParsingPrincipal(str_value.clone()),
                                 )),
                             }?;
-                        Ok(LexItem::LiteralValue(str_value.len(), value))
-                    }
-                    TokenType::SugaredContractIdentifierLiteral => {
-                        let str_value = get_value_or_err(current_slice, captures)?;
-                        let value = match str_value[1..].to_string().try_into() {
-                            Ok(parsed) => Ok(parsed),
-                            Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingPrincipal(
-                                str_value.clone(),
-                            ))),
-                        }?;
-                        Ok(LexItem::SugaredContractIdentifier(str_value.len(), value))
-                    }
-                    TokenType::FullyQualifiedFieldIdentifierLiteral => {
-                        let str_value = get_value_or_err(current_slice, captures)?;
-                        let value = match TraitIdentifier::parse_fully_qualified(&str_value) {
-                            Ok(parsed) => Ok(parsed),
-                            Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingField(
-                                str_value.clone(),
-                            ))),
-                        }?;
-                        Ok(LexItem::FieldIdentifier(str_value.len(), value))
-                    }
-                    TokenType::SugaredFieldIdentifierLiteral => {
-                        let str_value = get_value_or_err(current_slice, captures)?;
-                        let (contract_name, field_name) =
-                            match TraitIdentifier::parse_sugared_syntax(&str_value) {
-                                Ok((contract_name, field_name)) => Ok((contract_name, field_name)),
+                            Ok(LexItem::SugaredContractIdentifier(str_value.len(), value))
+                        }
+                        TokenType::FullyQualifiedFieldIdentifierLiteral => {
+                            let str_value = get_value_or_err(current_slice, captures)?;
+                            let value = match TraitIdentifier::parse_fully_qualified(&str_value) {
+                                Ok(parsed) => Ok(parsed),
                                 Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingField(
                                     str_value.clone(),
                                 ))),
                             }?;
-                        Ok(LexItem::SugaredFieldIdentifier(
-                            str_value.len(),
-                            contract_name,
-                            field_name,
-                        ))
-                    }
-                    TokenType::PrincipalLiteral => {
-                        let str_value = get_value_or_err(current_slice, captures)?;
-                        let value = match PrincipalData::parse_standard_principal(&str_value) {
-                            Ok(parsed) => Ok(Value::Principal(PrincipalData::Standard(parsed))),
-                            Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingPrincipal(
-                                str_value.clone(),
-                            ))),
-                        }?;
-                        Ok(LexItem::LiteralValue(str_value.len(), value))
-                    }
-                    TokenType::TraitReferenceLiteral => {
-                        let str_value = get_value_or_err(current_slice, captures)?;
-                        let data = str_value.clone().try_into().map_err(|_| {
-                            ParseError::new(ParseErrors::IllegalVariableName(str_value.to_string()))
-                        })?;
-                        Ok(LexItem::TraitReference(str_value.len(), data))
-                    }
-                    TokenType::HexStringLiteral => {
-                        let str_value = get_value_or_err(current_slice, captures)?;
-                        let byte_vec = hex_bytes(&str_value).map_err(|x| {
-                            ParseError::new(ParseErrors::FailedParsingHexValue(
-                                str_value.clone(),
-                                x.to_string(),
+                            Ok(LexItem::FieldIdentifier(str_value.len(), value))
+                        }
+                        TokenType::SugaredFieldIdentifierLiteral => {
+                            let str_value = get_value_or_err(current_slice, captures)?;
+                            let (contract_name, field_name) =
+                                match TraitIdentifier::parse_sugared_syntax(&str_value) {
+                                    Ok((contract_name, field_name)) => {
+                                        Ok((contract_name, field_name))
+                                    }
+                                    Err(_e) => Err(ParseError::new(
+                                        ParseErrors::FailedParsingField(str_value.clone()),
+                                    )),
+                                }?;
+                            Ok(LexItem::SugaredFieldIdentifier(
+                                str_value.len(),
+                                contract_name,
+                                field_name,
                             ))
-                        })?;
-                        let value = match Value::buff_from(byte_vec) {
-                            Ok(parsed) => Ok(parsed),
-                            Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingBuffer(
-                                str_value.clone(),
-                            ))),
-                        }?;
-                        Ok(LexItem::LiteralValue(str_value.len(), value))
+                        }
+                        TokenType::PrincipalLiteral => {
+                            let str_value = get_value_or_err(current_slice, captures)?;
+                            let value = match PrincipalData::parse_standard_principal(&str_value) {
+                                Ok(parsed) => Ok(Value::Principal(PrincipalData::Standard(parsed))),
+                                Err(_e) => Err(ParseError::new(
+                                    ParseErrors::FailedParsingPrincipal(str_value.clone()),
+                                )),
+                            }?;
+                            Ok(LexItem::LiteralValue(str_value.len(), value))
+                        }
+                        TokenType::TraitReferenceLiteral => {
+                            let str_value = get_value_or_err(current_slice, captures)?;
+                            let data = str_value.clone().try_into().map_err(|_| {
+                                ParseError::new(ParseErrors::IllegalVariableName(
+                                    str_value.to_string(),
+                                ))
+                            })?;
+                            Ok(LexItem::TraitReference(str_value.len(), data))
+                        }
+                        TokenType::HexStringLiteral => {
+                            let str_value = get_value_or_err(current_slice, captures)?;
+                            let byte_vec = hex_bytes(&str_value).map_err(|x| {
+                                ParseError::new(ParseErrors::FailedParsingHexValue(
+                                    str_value.clone(),
+                                    x.to_string(),
+                                ))
+                            })?;
+                            let value = match Value::buff_from(byte_vec) {
+                                Ok(parsed) => Ok(parsed),
+                                Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingBuffer(
+                                    str_value.clone(),
+                                ))),
+                            }?;
+                            Ok(LexItem::LiteralValue(str_value.len(), value))
+                        }
+                        TokenType::StringASCIILiteral => {
+                            let str_value = get_value_or_err(current_slice, captures)?;
+                            let str_value_len = str_value.len();
+                            let unescaped_str = unescape_ascii_chars(str_value, false)?;
+                            let byte_vec = unescaped_str.as_bytes().to_vec();
+
+                            let value = match Value::string_ascii_from_bytes(byte_vec) {
+                                Ok(parsed) => Ok(parsed),
+                                Err(_e) => {
+                                    Err(ParseError::new(ParseErrors::InvalidCharactersDetected))
+                                }
+                            }?;
+                            Ok(LexItem::LiteralValue(str_value_len, value))
+                        }
+                        TokenType::StringUTF8Literal => {
+                            let str_value = get_value_or_err(current_slice, captures)?;
+                            let str_value_len = str_value.len();
+                            let unescaped_str = unescape_ascii_chars(str_value, true)?;
+
+                            let value =
+                                match Value::string_utf8_from_string_utf8_literal(unescaped_str) {
+                                    Ok(parsed) => Ok(parsed),
+                                    Err(_e) => {
+                                        Err(ParseError::new(ParseErrors::InvalidCharactersDetected))
+                                    }
+                                }?;
+                            Ok(LexItem::LiteralValue(str_value_len, value))
+                        }
                     }
-                    TokenType::StringASCIILiteral => {
-                        let str_value = get_value_or_err(current_slice, captures)?;
-                        let str_value_len = str_value.len();
-                        let unescaped_str = unescape_ascii_chars(str_value, false)?;
-                        let byte_vec = unescaped_str.as_bytes().to_vec();
-
-                        let value = match Value::string_ascii_from_bytes(byte_vec) {
-                            Ok(parsed) => Ok(parsed),
-                            Err(_e) => Err(ParseError::new(ParseErrors::InvalidCharactersDetected)),
-                        }?;
-                        Ok(LexItem::LiteralValue(str_value_len, value))
+                })();
+
+                match token {
+                    Ok(token) => result.push((token, current_line, column_pos)),
+                    Err(error)
+                        if fail_fast
+                            || error.err == ParseErrors::VaryExpressionStackDepthTooDeep =>
+                    {
+                        return Err(error);
                     }
-                    TokenType::StringUTF8Literal => {
-                        let str_value = get_value_or_err(current_slice, captures)?;
-                        let str_value_len = str_value.len();
-                        let unescaped_str = unescape_ascii_chars(str_value, true)?;
-
-                        let value = match Value::string_utf8_from_string_utf8_literal(unescaped_str)
-                        {
-                            Ok(parsed) => Ok(parsed),
-                            Err(_e) => Err(ParseError::new(ParseErrors::InvalidCharactersDetected)),
-                        }?;
-                        Ok(LexItem::LiteralValue(str_value_len, value))
+                    Err(mut error) => {
+                        // skip the token
+                        let end_column = column_pos + whole_match.end() as u32 - 1;
+                        error.diagnostic.add_span(
+                            current_line,
+                            column_pos,
+                            current_line,
+                            end_column,
+                        );
+                        errors.push(error);
                     }
-                }?;
-
-                result.push((token, current_line, column_pos));
+                }
                 column_pos += whole_match.end() as u32;
                 did_match = true;
                 break;
             }
         }
+
+        if !did_match && !fail_fast {
+            // skip the character that nothing matches
+            let skipped = current_slice.chars().next().unwrap_or(' ');
+            let mut error =
+                ParseError::new(ParseErrors::FailedParsingRemainder(skipped.to_string()));
+            error
+                .diagnostic
+                .add_span(current_line, column_pos, current_line, column_pos);
+            errors.push(error);
+            munch_index += skipped.len_utf8();
+            column_pos += 1;
+            did_match = true;
+        }
     }
 
     if munch_index == input.len() {
-        Ok(result)
+        Ok((result, errors))
     } else {
         Err(ParseError::new(ParseErrors::FailedParsingRemainder(
             input[munch_index..].to_string(),
@@ -456,10 +510,12 @@ fn inner_lex(input: &str, max_nesting: u64) -> ParseResult<Vec<(LexItem, u32, u3
 }
 
 pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
-    inner_lex(
+    let (lexed, _) = inner_lex(
         input,
         AST_CALL_STACK_DEPTH_BUFFER + (MAX_CALL_STACK_DEPTH as u64) + 1,
-    )
+        true,
+    )?;
+    Ok(lexed)
 }
 
 fn unescape_ascii_chars(escaped_str: String, allow_unicode_escape: bool) -> ParseResult<String> {
@@ -508,199 +564,242 @@ fn handle_expression(
     }
 }
 
+pub fn parse_lexed(input: Vec<(LexItem, u32, u32)>) -> ParseResult<Vec<PreSymbolicExpression>> {
+    let (output_list, _) = inner_parse_lexed(input, true)?;
+    Ok(output_list)
+}
+
+/// Parse lexed tokens into expressions.  Unless `fail_fast` is set, a top-level expression
+/// that fails to parse is skipped (up to its closing parenthesis), its error is returned
+/// alongside the other expressions, and parsing continues with the next one.
 // TODO: add tests from mutation testing results #4828
 #[cfg_attr(test, mutants::skip)]
-pub fn parse_lexed(input: Vec<(LexItem, u32, u32)>) -> ParseResult<Vec<PreSymbolicExpression>> {
+fn inner_parse_lexed(
+    input: Vec<(LexItem, u32, u32)>,
+    fail_fast: bool,
+) -> ParseResult<(Vec<PreSymbolicExpression>, Vec<ParseError>)> {
     let mut parse_stack = Vec::new();
 
     let mut output_list = Vec::new();
+    let mut errors = Vec::new();
+    // while skipping an expression that failed to parse, how many of its lists and tuples
+    //  are still open
+    let mut skip_depth: usize = 0;
 
     for (item, line_pos, column_pos) in input.into_iter() {
-        match item {
-            LexItem::LeftParen => {
-                // start new list.
-                let new_list = Vec::new();
-                parse_stack.push((new_list, line_pos, column_pos, ParseContext::CollectList));
+        if skip_depth > 0 {
+            match item {
+                LexItem::LeftParen | LexItem::LeftCurly => skip_depth += 1,
+                LexItem::RightParen | LexItem::RightCurly => skip_depth -= 1,
+                _ => (),
             }
-            LexItem::RightParen => {
-                // end current list.
-                if let Some((list, start_line, start_column, parse_context)) = parse_stack.pop() {
-                    match parse_context {
-                        ParseContext::CollectList => {
-                            let checked_list: ParseResult<Vec<PreSymbolicExpression>> = list
-                                .into_iter()
-                                .map(|i| match i {
-                                    ParseStackItem::Expression(e) => Ok(e),
-                                    ParseStackItem::Colon => {
-                                        Err(ParseError::new(ParseErrors::ColonSeparatorUnexpected))
-                                    }
-                                    ParseStackItem::Comma => {
-                                        Err(ParseError::new(ParseErrors::CommaSeparatorUnexpected))
-                                    }
-                                })
-                                .collect();
-                            let checked_list = checked_list?;
-                            let mut pre_expr = PreSymbolicExpression::list(checked_list);
-                            pre_expr.set_span(start_line, start_column, line_pos, column_pos);
-                            handle_expression(&mut parse_stack, &mut output_list, pre_expr);
-                        }
-                        ParseContext::CollectTuple => {
-                            let mut error =
-                                ParseError::new(ParseErrors::ClosingTupleLiteralExpected);
-                            error.diagnostic.add_span(
-                                start_line,
-                                start_column,
-                                line_pos,
-                                column_pos,
-                            );
-                            return Err(error);
+            continue;
+        }
+
+        let parsed = (|| -> ParseResult<()> {
+            match item {
+                LexItem::LeftParen => {
+                    // start new list.
+                    let new_list = Vec::new();
+                    parse_stack.push((new_list, line_pos, column_pos, ParseContext::CollectList));
+                }
+                LexItem::RightParen => {
+                    // end current list.
+                    if let Some((list, start_line, start_column, parse_context)) = parse_stack.pop()
+                    {
+                        match parse_context {
+                            ParseContext::CollectList => {
+                                let checked_list: ParseResult<Vec<PreSymbolicExpression>> = list
+                                    .into_iter()
+                                    .map(|i| match i {
+                                        ParseStackItem::Expression(e) => Ok(e),
+                                        ParseStackItem::Colon => Err(ParseError::new(
+                                            ParseErrors::ColonSeparatorUnexpected,
+                                        )),
+                                        ParseStackItem::Comma => Err(ParseError::new(
+                                            ParseErrors::CommaSeparatorUnexpected,
+                                        )),
+                                    })
+                                    .collect();
+                                let checked_list = checked_list?;
+                                let mut pre_expr = PreSymbolicExpression::list(checked_list);
+                                pre_expr.set_span(start_line, start_column, line_pos, column_pos);
+                                handle_expression(&mut parse_stack, &mut output_list, pre_expr);
+                            }
+                            ParseContext::CollectTuple => {
+                                let mut error =
+                                    ParseError::new(ParseErrors::ClosingTupleLiteralExpected);
+                                error.diagnostic.add_span(
+                                    start_line,
+                                    start_column,
+                                    line_pos,
+                                    column_pos,
+                                );
+                                return Err(error);
+                            }
                         }
+                    } else {
+                        debug!(
+                            "Closing parenthesis expected ({}, {})",
+                            line_pos, column_pos
+                        );
+                        return Err(ParseError::new(ParseErrors::ClosingParenthesisUnexpected));
                     }
-                } else {
-                    debug!(
-                        "Closing parenthesis expected ({}, {})",
-                        line_pos, column_pos
-                    );
-                    return Err(ParseError::new(ParseErrors::ClosingParenthesisUnexpected));
                 }
-            }
-            LexItem::LeftCurly => {
-                let new_list = Vec::new();
-                parse_stack.push((new_list, line_pos, column_pos, ParseContext::CollectTuple));
-            }
-            LexItem::RightCurly => {
-                if let Some((tuple_list, start_line, start_column, parse_context)) =
-                    parse_stack.pop()
-                {
-                    match parse_context {
-                        ParseContext::CollectTuple => {
-                            let mut checked_list = Vec::new();
-                            for (index, item) in tuple_list.into_iter().enumerate() {
-                                // check that tuple items are (expr, colon, expr, comma)
-                                match index % 4 {
-                                    0 | 2 => {
-                                        if let ParseStackItem::Expression(e) = item {
-                                            checked_list.push(e);
-                                            Ok(())
-                                        } else {
-                                            Err(ParseErrors::TupleItemExpected(index))
+                LexItem::LeftCurly => {
+                    let new_list = Vec::new();
+                    parse_stack.push((new_list, line_pos, column_pos, ParseContext::CollectTuple));
+                }
+                LexItem::RightCurly => {
+                    if let Some((tuple_list, start_line, start_column, parse_context)) =
+                        parse_stack.pop()
+                    {
+                        match parse_context {
+                            ParseContext::CollectTuple => {
+                                let mut checked_list = Vec::new();
+                                for (index, item) in tuple_list.into_iter().enumerate() {
+                                    // check that tuple items are (expr, colon, expr, comma)
+                                    match index % 4 {
+                                        0 | 2 => {
+                                            if let ParseStackItem::Expression(e) = item {
+                                                checked_list.push(e);
+                                                Ok(())
+                                            } else {
+                                                Err(ParseErrors::TupleItemExpected(index))
+                                            }
                                         }
-                                    }
-                                    1 => {
-                                        if let ParseStackItem::Colon = item {
-                                            Ok(())
-                                        } else {
-                                            Err(ParseErrors::TupleColonExpected(index))
+                                        1 => {
+                                            if let ParseStackItem::Colon = item {
+                                                Ok(())
+                                            } else {
+                                                Err(ParseErrors::TupleColonExpected(index))
+                                            }
                                         }
-                                    }
-                                    3 => {
-                                        if let ParseStackItem::Comma = item {
-                                            Ok(())
-                                        } else {
-                                            Err(ParseErrors::TupleCommaExpected(index))
+                                        3 => {
+                                            if let ParseStackItem::Comma = item {
+                                                Ok(())
+                                            } else {
+                                                Err(ParseErrors::TupleCommaExpected(index))
+                                            }
                                         }
-                                    }
-                                    _ => unreachable!("More than four modulos of four."),
-                                }?;
+                                        _ => unreachable!("More than four modulos of four."),
+                                    }?;
+                                }
+                                let mut pre_expr = PreSymbolicExpression::tuple(checked_list);
+                                pre_expr.set_span(start_line, start_column, line_pos, column_pos);
+                                handle_expression(&mut parse_stack, &mut output_list, pre_expr);
+                            }
+                            ParseContext::CollectList => {
+                                let mut error =
+                                    ParseError::new(ParseErrors::ClosingParenthesisExpected);
+                                error.diagnostic.add_span(
+                                    start_line,
+                                    start_column,
+                                    line_pos,
+                                    column_pos,
+                                );
+                                return Err(error);
                             }
-                            let mut pre_expr = PreSymbolicExpression::tuple(checked_list);
-                            pre_expr.set_span(start_line, start_column, line_pos, column_pos);
-                            handle_expression(&mut parse_stack, &mut output_list, pre_expr);
-                        }
-                        ParseContext::CollectList => {
-                            let mut error =
-                                ParseError::new(ParseErrors::ClosingParenthesisExpected);
-                            error.diagnostic.add_span(
-                                start_line,
-                                start_column,
-                                line_pos,
-                                column_pos,
-                            );
-                            return Err(error);
                         }
+                    } else {
+                        debug!(
+                            "Closing tuple literal unexpected ({}, {})",
+                            line_pos, column_pos
+                        );
+                        return Err(ParseError::new(ParseErrors::ClosingTupleLiteralUnexpected));
                     }
-                } else {
-                    debug!(
-                        "Closing tuple literal unexpected ({}, {})",
-                        line_pos, column_pos
-                    );
-                    return Err(ParseError::new(ParseErrors::ClosingTupleLiteralUnexpected));
                 }
-            }
-            LexItem::Variable(value) => {
-                let end_column = column_pos + (value.len() as u32) - 1;
-                let value = value.clone().try_into().map_err(|_| {
-                    ParseError::new(ParseErrors::IllegalVariableName(value.to_string()))
-                })?;
-                let mut pre_expr = PreSymbolicExpression::atom(value);
-                pre_expr.set_span(line_pos, column_pos, line_pos, end_column);
-                handle_expression(&mut parse_stack, &mut output_list, pre_expr);
-            }
-            LexItem::LiteralValue(length, value) => {
-                let mut end_column = column_pos + (length as u32);
-                // Avoid underflows on cases like empty strings
-                if length > 0 {
-                    end_column -= 1;
+                LexItem::Variable(value) => {
+                    let end_column = column_pos + (value.len() as u32) - 1;
+                    let value = value.clone().try_into().map_err(|_| {
+                        ParseError::new(ParseErrors::IllegalVariableName(value.to_string()))
+                    })?;
+                    let mut pre_expr = PreSymbolicExpression::atom(value);
+                    pre_expr.set_span(line_pos, column_pos, line_pos, end_column);
+                    handle_expression(&mut parse_stack, &mut output_list, pre_expr);
                 }
-                let mut pre_expr = PreSymbolicExpression::atom_value(value);
-                pre_expr.set_span(line_pos, column_pos, line_pos, end_column);
-                handle_expression(&mut parse_stack, &mut output_list, pre_expr);
-            }
-            LexItem::SugaredContractIdentifier(length, value) => {
-                let mut end_column = column_pos + (length as u32);
-                // Avoid underflows on cases like empty strings
-                if length > 0 {
-                    end_column -= 1;
+                LexItem::LiteralValue(length, value) => {
+                    let mut end_column = column_pos + (length as u32);
+                    // Avoid underflows on cases like empty strings
+                    if length > 0 {
+                        end_column -= 1;
+                    }
+                    let mut pre_expr = PreSymbolicExpression::atom_value(value);
+                    pre_expr.set_span(line_pos, column_pos, line_pos, end_column);
+                    handle_expression(&mut parse_stack, &mut output_list, pre_expr);
                 }
-                let mut pre_expr = PreSymbolicExpression::sugared_contract_identifier(value);
-                pre_expr.set_span(line_pos, column_pos, line_pos, end_column);
-                handle_expression(&mut parse_stack, &mut output_list, pre_expr);
-            }
-            LexItem::SugaredFieldIdentifier(length, contract_name, name) => {
-                let mut end_column = column_pos + (length as u32);
-                // Avoid underflows on cases like empty strings
-                if length > 0 {
-                    end_column -= 1;
+                LexItem::SugaredContractIdentifier(length, value) => {
+                    let mut end_column = column_pos + (length as u32);
+                    // Avoid underflows on cases like empty strings
+                    if length > 0 {
+                        end_column -= 1;
+                    }
+                    let mut pre_expr = PreSymbolicExpression::sugared_contract_identifier(value);
+                    pre_expr.set_span(line_pos, column_pos, line_pos, end_column);
+                    handle_expression(&mut parse_stack, &mut output_list, pre_expr);
                 }
-                let mut pre_expr =
-                    PreSymbolicExpression::sugared_field_identifier(contract_name, name);
-                pre_expr.set_span(line_pos, column_pos, line_pos, end_column);
-                handle_expression(&mut parse_stack, &mut output_list, pre_expr);
-            }
-            LexItem::FieldIdentifier(length, trait_identifier) => {
-                let mut end_column = column_pos + (length as u32);
-                // Avoid underflows on cases like empty strings
-                if length > 0 {
-                    end_column -= 1;
+                LexItem::SugaredFieldIdentifier(length, contract_name, name) => {
+                    let mut end_column = column_pos + (length as u32);
+                    // Avoid underflows on cases like empty strings
+                    if length > 0 {
+                        end_column -= 1;
+                    }
+                    let mut pre_expr =
+                        PreSymbolicExpression::sugared_field_identifier(contract_name, name);
+                    pre_expr.set_span(line_pos, column_pos, line_pos, end_column);
+                    handle_expression(&mut parse_stack, &mut output_list, pre_expr);
                 }
-                let mut pre_expr = PreSymbolicExpression::field_identifier(trait_identifier);
-                pre_expr.set_span(line_pos, column_pos, line_pos, end_column);
-                handle_expression(&mut parse_stack, &mut output_list, pre_expr);
-            }
-            LexItem::TraitReference(_length, value) => {
-                let end_column = column_pos + (value.len() as u32) - 1;
-                let mut pre_expr = PreSymbolicExpression::trait_reference(value);
-                pre_expr.set_span(line_pos, column_pos, line_pos, end_column);
-                handle_expression(&mut parse_stack, &mut output_list, pre_expr);
-            }
-            LexItem::ColonSeparator => {
-                match parse_stack.last_mut() {
-                    None => return Err(ParseError::new(ParseErrors::ColonSeparatorUnexpected)),
-                    Some((ref mut list, ..)) => {
-                        list.push(ParseStackItem::Colon);
+                LexItem::FieldIdentifier(length, trait_identifier) => {
+                    let mut end_column = column_pos + (length as u32);
+                    // Avoid underflows on cases like empty strings
+                    if length > 0 {
+                        end_column -= 1;
                     }
-                };
+                    let mut pre_expr = PreSymbolicExpression::field_identifier(trait_identifier);
+                    pre_expr.set_span(line_pos, column_pos, line_pos, end_column);
+                    handle_expression(&mut parse_stack, &mut output_list, pre_expr);
+                }
+                LexItem::TraitReference(_length, value) => {
+                    let end_column = column_pos + (value.len() as u32) - 1;
+                    let mut pre_expr = PreSymbolicExpression::trait_reference(value);
+                    pre_expr.set_span(line_pos, column_pos, line_pos, end_column);
+                    handle_expression(&mut parse_stack, &mut output_list, pre_expr);
+                }
+                LexItem::ColonSeparator => {
+                    match parse_stack.last_mut() {
+                        None => return Err(ParseError::new(ParseErrors::ColonSeparatorUnexpected)),
+                        Some((ref mut list, ..)) => {
+                            list.push(ParseStackItem::Colon);
+                        }
+                    };
+                }
+                LexItem::CommaSeparator => {
+                    match parse_stack.last_mut() {
+                        None => return Err(ParseError::new(ParseErrors::CommaSeparatorUnexpected)),
+                        Some((ref mut list, ..)) => {
+                            list.push(ParseStackItem::Comma);
+                        }
+                    };
+                }
+                LexItem::Whitespace => (),
+            };
+            Ok(())
+        })();
+
+        if let Err(mut error) = parsed {
+            if fail_fast {
+                return Err(error);
             }
-            LexItem::CommaSeparator => {
-                match parse_stack.last_mut() {
-                    None => return Err(ParseError::new(ParseErrors::CommaSeparatorUnexpected)),
-                    Some((ref mut list, ..)) => {
-                        list.push(ParseStackItem::Comma);
-                    }
-                };
+            if error.diagnostic.spans.is_empty() {
+                error
+                    .diagnostic
+                    .add_span(line_pos, column_pos, line_pos, column_pos);
             }
-            LexItem::Whitespace => (),
-        };
+            errors.push(error);
+            // skip the rest of the top-level expression
+            skip_depth = parse_stack.len();
+            parse_stack.clear();
+        }
     }
 
     // check unfinished stack:
@@ -715,25 +814,66 @@ pub fn parse_lexed(input: Vec<(LexItem, u32, u32)>) -> ParseResult<Vec<PreSymbol
                 start_column
             );
         }
-        Err(error)
-    } else {
-        Ok(output_list)
+        if fail_fast {
+            return Err(error);
+        }
+        errors.push(error);
     }
+    Ok((output_list, errors))
 }
 
 pub fn parse(input: &str) -> ParseResult<Vec<PreSymbolicExpression>> {
-    let lexed = inner_lex(
+    let (lexed, _) = inner_lex(
         input,
         AST_CALL_STACK_DEPTH_BUFFER + (MAX_CALL_STACK_DEPTH as u64) + 1,
+        true,
     )?;
     parse_lexed(lexed)
 }
 
 pub fn parse_no_stack_limit(input: &str) -> ParseResult<Vec<PreSymbolicExpression>> {
-    let lexed = inner_lex(input, u64::MAX)?;
+    let (lexed, _) = inner_lex(input, u64::MAX, true)?;
     parse_lexed(lexed)
 }
 
+/// Used by developer tools only.  Parses like `parse()`, but skips the tokens and top-level
+/// expressions that fail to parse instead of stopping at the first error, so that one pass
+/// reports as many errors as possible.  Returns the expressions that parsed, a diagnostic (with
+/// its span) for each error, and whether there were no errors.
+pub fn parse_collect_diagnostics(
+    input: &str,
+) -> (Vec<PreSymbolicExpression>, Vec<Diagnostic>, bool) {
+    let (lexed, mut errors) = match inner_lex(
+        input,
+        AST_CALL_STACK_DEPTH_BUFFER + (MAX_CALL_STACK_DEPTH as u64) + 1,
+        false,
+    ) {
+        Ok(lexed) => lexed,
+        Err(error) => return (vec![], vec![error.diagnostic], false),
+    };
+    let output_list = match inner_parse_lexed(lexed, false) {
+        Ok((output_list, parse_errors)) => {
+            errors.extend(parse_errors);
+            output_list
+        }
+        Err(error) => {
+            errors.push(error);
+            vec![]
+        }
+    };
+    let success = errors.is_empty();
+    let mut diagnostics: Vec<Diagnostic> =
+        errors.into_iter().map(|error| error.diagnostic).collect();
+    // lexing errors are found before parsing errors, but are reported in source order
+    diagnostics.sort_by_key(|diagnostic| {
+        diagnostic
+            .spans
+            .first()
+            .map(|span| (span.start_line, span.start_column))
+    });
+    (output_list, diagnostics, success)
+}
+
 #[cfg(test)]
 mod test {
     use crate::vm::ast::errors::{ParseError, ParseErrors};
@@ -1264,4 +1404,56 @@ mod test {
             ParseErrors::SeparatorExpected(_)
         ));
     }
+
+    #[test]
+    fn test_parse_collect_diagnostics() {
+        let input = "(define-constant a 42g)
+(define-constant b (1 2 :))
+(define-constant c u1)
+(define-constant d u2 $)
+)";
+        // parsing stops at the first error
+        assert!(matches!(
+            ast::parser::v1::parse(input).unwrap_err().err,
+            ParseErrors::SeparatorExpected(_)
+        ));
+
+        let (exprs, diagnostics, success) = ast::parser::v1::parse_collect_diagnostics(input);
+        assert!(!success);
+        // the expressions with errors in tokens are kept without them, and the ones that
+        //  failed to parse are skipped
+        assert_eq!(exprs.len(), 3);
+        assert_eq!(
+            exprs[0].match_list().unwrap()[2].match_atom_value(),
+            Some(&Value::Int(42))
+        );
+        assert_eq!(
+            exprs[1].match_list().unwrap()[1]
+                .match_atom()
+                .unwrap()
+                .as_str(),
+            "c"
+        );
+
+        let locations: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| {
+                let span = &diagnostic.spans[0];
+                (span.start_line, span.start_column)
+            })
+            .collect();
+        assert_eq!(locations, vec![(1, 22), (2, 26), (4, 23), (5, 1)]);
+        assert_eq!(
+            diagnostics[0].message,
+            ParseError::new(ParseErrors::SeparatorExpected("g".into()))
+                .diagnostic
+                .message
+        );
+
+        let (exprs, diagnostics, success) =
+            ast::parser::v1::parse_collect_diagnostics("(define-constant c u1)");
+        assert!(success);
+        assert!(diagnostics.is_empty());
+        assert_eq!(exprs.len(), 1);
+    }
 }
@@ -21,7 +21,7 @@ use crate::vm::ast::types::{BuildASTPass, ContractAST, PreExpressionsDrain};
 use crate::vm::functions::define::{DefineFunctions, DefineFunctionsParsed};
 use crate::vm::functions::NativeFunctions;
 use crate::vm::representations::{
-    ClarityName, PreSymbolicExpression, PreSymbolicExpressionType, SymbolicExpression,
+    ClarityName, PreSymbolicExpression, PreSymbolicExpressionType, Span, SymbolicExpression,
     SymbolicExpressionType,
 };
 use crate::vm::types::{
@@ -71,6 +71,7 @@ impl SugarExpander {
 
         for pre_expr in pre_exprs_iter {
             let span = pre_expr.span().clone();
+            let pre_id = pre_expr.id;
             let mut expr = match pre_expr.pre_expr {
                 PreSymbolicExpressionType::AtomValue(content) => {
                     SymbolicExpression::literal_value(content)
@@ -144,7 +145,12 @@ impl SugarExpander {
                 }
                 PreSymbolicExpressionType::Placeholder(_) => continue,
             };
-            // expr.id will be set by the subsequent expression identifier pass.
+            // expr.id will be set by the subsequent expression identifier pass, which re-keys
+            // the source map from the pre-expression ids to the new ones.
+            expr.id = pre_id;
+            if pre_id != 0 && span != Span::ZERO {
+                contract_ast.source_map.insert(pre_id, span.clone());
+            }
             expr.copy_span(&span);
 
             #[cfg(feature = "developer-mode")]
@@ -14,12 +14,15 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::BTreeMap;
 use std::vec::Drain;
 
 use hashbrown::{HashMap, HashSet};
 
 use crate::vm::ast::errors::ParseResult;
-use crate::vm::representations::{PreSymbolicExpression, SymbolicExpression, TraitDefinition};
+use crate::vm::representations::{
+    PreSymbolicExpression, Span, SymbolicExpression, TraitDefinition,
+};
 use crate::vm::types::signatures::FunctionSignature;
 use crate::vm::types::{QualifiedContractIdentifier, TraitIdentifier};
 use crate::vm::{ClarityName, ClarityVersion};
@@ -36,6 +39,9 @@ pub struct ContractAST {
     pub top_level_expression_sorting: Option<Vec<usize>>,
     pub referenced_traits: HashMap<ClarityName, TraitDefinition>,
     pub implemented_traits: HashSet<TraitIdentifier>,
+    /// The span of source that each expression was parsed from, by expression id.  Expressions
+    ///  that the sugar expander introduced (such as the `tuple` of `{ a: 1 }`) have no entry.
+    pub source_map: BTreeMap<u64, Span>,
 }
 
 impl ContractAST {
@@ -50,6 +56,7 @@ impl ContractAST {
             top_level_expression_sorting: Some(Vec::new()),
             referenced_traits: HashMap::new(),
             implemented_traits: HashSet::new(),
+            source_map: BTreeMap::new(),
         }
     }
 
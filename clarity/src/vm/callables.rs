@@ -292,16 +292,30 @@ impl DefinedFunction {
             }
         }
 
-        let result = eval(&self.body, env, &context);
-
-        // if the error wasn't actually an error, but a function return,
-        //    pull that out and return it.
-        match result {
-            Ok(r) => Ok(r),
-            Err(e) => match e {
-                Error::ShortReturn(v) => Ok(v.into()),
-                _ => Err(e),
-            },
+        env.with_eval_hooks(|hook, env| {
+            for (name, value) in self.arguments.iter().zip(args.iter()) {
+                hook.did_bind_variable(env, name, value);
+            }
+        });
+
+        let eval_body = |env: &mut Environment| {
+            let result = eval(&self.body, env, &context);
+
+            // if the error wasn't actually an error, but a function return,
+            //    pull that out and return it.
+            match result {
+                Ok(r) => Ok(r),
+                Err(e) => match e {
+                    Error::ShortReturn(v) => Ok(v.into()),
+                    _ => Err(e),
+                },
+            }
+        };
+
+        if self.define_type == DefineType::ReadOnly {
+            env.memoize_read_only_call(&self.name, args, eval_body)
+        } else {
+            eval_body(env)
         }
     }
 
@@ -371,6 +385,14 @@ impl DefinedFunction {
         }
     }
 
+    /// Rewrite the function's body in place, given the names of its arguments
+    pub fn rewrite_body<F>(&mut self, f: F)
+    where
+        F: FnOnce(&[ClarityName], &mut SymbolicExpression),
+    {
+        f(&self.arguments, &mut self.body)
+    }
+
     #[cfg(feature = "developer-mode")]
     pub fn get_span(&self) -> Span {
         self.body.span.clone()
@@ -2,16 +2,23 @@ use std::fmt;
 
 use stacks_common::types::StacksEpochId;
 
+use crate::vm::analysis::contract_interface_builder::{
+    build_contract_interface, ContractInterface,
+};
+use crate::vm::analysis::linter::{LintConfig, Linter};
 use crate::vm::analysis::{AnalysisDatabase, CheckError, CheckErrors, ContractAnalysis};
 use crate::vm::ast::errors::{ParseError, ParseErrors};
 use crate::vm::ast::{ASTRules, ContractAST};
 use crate::vm::contexts::{AssetMap, Environment, OwnedEnvironment};
 use crate::vm::costs::{ExecutionCost, LimitedCostTracker};
 use crate::vm::database::ClarityDatabase;
+use crate::vm::diagnostic::{Diagnostic, Level};
 use crate::vm::errors::Error as InterpreterError;
 use crate::vm::events::StacksTransactionEvent;
 use crate::vm::types::{BuffData, PrincipalData, QualifiedContractIdentifier};
-use crate::vm::{analysis, ast, ClarityVersion, ContractContext, SymbolicExpression, Value};
+use crate::vm::{
+    analysis, ast, ClarityVersion, ContractContext, EvalHook, SymbolicExpression, Value,
+};
 
 #[derive(Debug)]
 pub enum Error {
@@ -95,6 +102,36 @@ impl From<ParseError> for Error {
     }
 }
 
+impl Error {
+    /// The error as a diagnostic, with the location of the error if it has one
+    pub fn diagnostic(&self) -> Diagnostic {
+        match self {
+            Error::Analysis(e) => e.diagnostic.clone(),
+            Error::Parse(e) => e.diagnostic.clone(),
+            e => Diagnostic {
+                level: Level::Error,
+                message: e.to_string(),
+                spans: vec![],
+                suggestion: None,
+            },
+        }
+    }
+}
+
+/// The outcome of deploying a contract without committing it, from
+///  `ClarityConnection::dry_run_deploy()`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeployDryRun {
+    /// Whether the deployment would succeed
+    pub okay: bool,
+    /// Why the deployment would fail, if it would, followed by the findings of the lints
+    pub diagnostics: Vec<Diagnostic>,
+    /// The cost of parsing, analyzing and initializing the contract (up to where it failed)
+    pub cost: ExecutionCost,
+    /// The contract's interface, if it passed analysis
+    pub interface: Option<ContractInterface>,
+}
+
 pub trait ClarityConnection {
     /// Do something to the underlying DB that involves only reading.
     fn with_clarity_db_readonly_owned<F, R>(&mut self, to_do: F) -> R
@@ -123,6 +160,35 @@ pub trait ClarityConnection {
         cost_track: LimitedCostTracker,
         to_do: F,
     ) -> Result<R, InterpreterError>
+    where
+        F: FnOnce(&mut Environment) -> Result<R, InterpreterError>,
+    {
+        self.with_readonly_clarity_env_and_hook(
+            mainnet,
+            chain_id,
+            clarity_version,
+            sender,
+            sponsor,
+            cost_track,
+            None,
+            to_do,
+        )
+    }
+
+    /// Like `with_readonly_clarity_env()`, but with `hook` (if given) added to the
+    /// environment's eval hooks, e.g. to trace the execution
+    #[allow(clippy::too_many_arguments)]
+    fn with_readonly_clarity_env_and_hook<F, R>(
+        &mut self,
+        mainnet: bool,
+        chain_id: u32,
+        clarity_version: ClarityVersion,
+        sender: PrincipalData,
+        sponsor: Option<PrincipalData>,
+        cost_track: LimitedCostTracker,
+        hook: Option<&mut dyn EvalHook>,
+        to_do: F,
+    ) -> Result<R, InterpreterError>
     where
         F: FnOnce(&mut Environment) -> Result<R, InterpreterError>,
     {
@@ -133,6 +199,9 @@ pub trait ClarityConnection {
             let mut vm_env = OwnedEnvironment::new_cost_limited(
                 mainnet, chain_id, clarity_db, cost_track, epoch_id,
             );
+            if let Some(hook) = hook {
+                vm_env.add_eval_hook(hook);
+            }
             let result = vm_env
                 .execute_in_env(sender, sponsor, Some(initial_context), to_do)
                 .map(|(result, _, _)| result);
@@ -147,6 +216,159 @@ pub trait ClarityConnection {
             (result, db)
         })
     }
+
+    /// Parse, analyze and initialize `contract_content` as `identifier`, as if its issuer
+    ///  deployed it, and then discard it.  The costs are charged to `cost_track`, whose limit
+    ///  bounds the work done.
+    #[allow(clippy::too_many_arguments)]
+    fn dry_run_deploy(
+        &mut self,
+        mainnet: bool,
+        chain_id: u32,
+        identifier: &QualifiedContractIdentifier,
+        clarity_version: ClarityVersion,
+        contract_content: &str,
+        ast_rules: ASTRules,
+        cost_track: LimitedCostTracker,
+    ) -> DeployDryRun {
+        let epoch_id = self.get_epoch();
+        let mut dry_run = DeployDryRun {
+            okay: false,
+            diagnostics: vec![],
+            cost: ExecutionCost::zero(),
+            interface: None,
+        };
+
+        if self.with_analysis_db_readonly(|db| db.has_contract(identifier)) {
+            dry_run
+                .diagnostics
+                .push(Diagnostic::err(&CheckErrors::ContractAlreadyExists(
+                    identifier.to_string(),
+                )));
+            return dry_run;
+        }
+
+        let (cost_track, result) = self.with_analysis_db_readonly(|db| {
+            analyze_with_tracker(
+                db,
+                cost_track,
+                identifier,
+                clarity_version,
+                contract_content,
+                ast_rules,
+                epoch_id,
+            )
+        });
+        dry_run.cost = cost_track.get_total();
+        let (contract_ast, contract_analysis) = match result {
+            Ok(analyzed) => analyzed,
+            Err(e) => {
+                dry_run.diagnostics.push(e.diagnostic());
+                return dry_run;
+            }
+        };
+        dry_run.interface = build_contract_interface(&contract_analysis).ok();
+
+        let result = self.with_readonly_clarity_env(
+            mainnet,
+            chain_id,
+            clarity_version,
+            identifier.issuer.clone().into(),
+            None,
+            cost_track,
+            |env| {
+                let result = env.initialize_contract_from_ast(
+                    identifier.clone(),
+                    clarity_version,
+                    &contract_ast,
+                    contract_content,
+                );
+                Ok((
+                    result,
+                    env.global_context.cost_track.get_total(),
+                    env.global_context.error_location.clone(),
+                ))
+            },
+        );
+        match result {
+            Ok((Ok(()), cost, _)) => {
+                dry_run.okay = true;
+                dry_run.cost = cost;
+            }
+            Ok((Err(e), cost, location)) => {
+                let mut diagnostic = Error::from(e).diagnostic();
+                // the contract has no stored analysis yet, so locate the error with this one's
+                if let Some(span) = location
+                    .filter(|location| &location.contract_identifier == identifier)
+                    .and_then(|location| {
+                        location
+                            .span
+                            .or_else(|| contract_analysis.get_span(location.expression_id).cloned())
+                    })
+                {
+                    diagnostic.spans.push(span);
+                }
+                dry_run.diagnostics.push(diagnostic);
+                dry_run.cost = cost;
+            }
+            Err(e) => dry_run.diagnostics.push(Error::from(e).diagnostic()),
+        }
+
+        dry_run.diagnostics.extend(
+            Linter::run(&contract_analysis, &LintConfig::new())
+                .into_iter()
+                .map(|lint| lint.diagnostic),
+        );
+        dry_run
+    }
+}
+
+/// Parse and analyze a smart contract, charging the costs to `cost_track`
+fn analyze_with_tracker(
+    db: &mut AnalysisDatabase,
+    mut cost_track: LimitedCostTracker,
+    identifier: &QualifiedContractIdentifier,
+    clarity_version: ClarityVersion,
+    contract_content: &str,
+    ast_rules: ASTRules,
+    epoch_id: StacksEpochId,
+) -> (
+    LimitedCostTracker,
+    Result<(ContractAST, ContractAnalysis), Error>,
+) {
+    let ast_result = ast::build_ast_with_rules(
+        identifier,
+        contract_content,
+        &mut cost_track,
+        clarity_version,
+        epoch_id,
+        ast_rules,
+    );
+
+    let mut contract_ast = match ast_result {
+        Ok(x) => x,
+        Err(e) => return (cost_track, Err(e.into())),
+    };
+
+    let result = analysis::run_analysis(
+        identifier,
+        &mut contract_ast.expressions,
+        db,
+        false,
+        cost_track,
+        epoch_id,
+        clarity_version,
+        false,
+    );
+
+    match result {
+        Ok(mut contract_analysis) => {
+            let cost_track = contract_analysis.take_contract_cost_tracker();
+            contract_analysis.source_map = contract_ast.source_map.clone();
+            (cost_track, Ok((contract_ast, contract_analysis)))
+        }
+        Err((e, cost_track)) => (cost_track, Err(e.into())),
+    }
 }
 
 pub trait TransactionConnection: ClarityConnection {
@@ -177,6 +399,13 @@ pub trait TransactionConnection: ClarityConnection {
     where
         F: FnOnce(&mut AnalysisDatabase, LimitedCostTracker) -> (LimitedCostTracker, R);
 
+    /// The budget that contract analysis (parsing and type-checking) may consume, independent
+    ///  of the execution budget of the transaction. `None` means that analysis is bounded by
+    ///  the transaction's execution budget, which is what consensus requires.
+    fn get_analysis_cost_limit(&self) -> Option<ExecutionCost> {
+        None
+    }
+
     /// Analyze a provided smart contract, but do not write the analysis to the AnalysisDatabase
     fn analyze_smart_contract(
         &mut self,
@@ -184,42 +413,59 @@ pub trait TransactionConnection: ClarityConnection {
         clarity_version: ClarityVersion,
         contract_content: &str,
         ast_rules: ASTRules,
+    ) -> Result<(ContractAST, ContractAnalysis), Error> {
+        let analysis_cost_limit = self.get_analysis_cost_limit();
+        self.analyze_smart_contract_with_limit(
+            identifier,
+            clarity_version,
+            contract_content,
+            ast_rules,
+            analysis_cost_limit,
+        )
+    }
+
+    /// Analyze a provided smart contract, but do not write the analysis to the AnalysisDatabase.
+    /// If `analysis_cost_limit` is given, the analysis may consume up to that much cost on top
+    ///  of whatever the transaction has consumed so far, instead of being bounded by the
+    ///  transaction's execution budget. The costs are still charged to the transaction.
+    fn analyze_smart_contract_with_limit(
+        &mut self,
+        identifier: &QualifiedContractIdentifier,
+        clarity_version: ClarityVersion,
+        contract_content: &str,
+        ast_rules: ASTRules,
+        analysis_cost_limit: Option<ExecutionCost>,
     ) -> Result<(ContractAST, ContractAnalysis), Error> {
         let epoch_id = self.get_epoch();
 
         self.with_analysis_db(|db, mut cost_track| {
-            let ast_result = ast::build_ast_with_rules(
-                identifier,
-                contract_content,
-                &mut cost_track,
-                clarity_version,
-                epoch_id,
-                ast_rules,
-            );
-
-            let mut contract_ast = match ast_result {
-                Ok(x) => x,
-                Err(e) => return (cost_track, Err(e.into())),
+            let execution_limit = match analysis_cost_limit.as_ref() {
+                Some(analysis_limit) if matches!(cost_track, LimitedCostTracker::Limited(_)) => {
+                    let execution_limit = cost_track.get_limit();
+                    let mut phase_limit = cost_track.get_total();
+                    if phase_limit.add(analysis_limit).is_err() {
+                        phase_limit = ExecutionCost::max_value();
+                    }
+                    cost_track.set_limit(phase_limit);
+                    Some(execution_limit)
+                }
+                _ => None,
             };
 
-            let result = analysis::run_analysis(
-                identifier,
-                &mut contract_ast.expressions,
+            let (mut cost_track, result) = analyze_with_tracker(
                 db,
-                false,
                 cost_track,
-                epoch_id,
+                identifier,
                 clarity_version,
-                false,
+                contract_content,
+                ast_rules,
+                epoch_id,
             );
 
-            match result {
-                Ok(mut contract_analysis) => {
-                    let cost_track = contract_analysis.take_contract_cost_tracker();
-                    (cost_track, Ok((contract_ast, contract_analysis)))
-                }
-                Err((e, cost_track)) => (cost_track, Err(e.into())),
+            if let Some(execution_limit) = execution_limit {
+                cost_track.set_limit(execution_limit);
             }
+            (cost_track, result)
         })
     }
 
@@ -24,6 +24,7 @@ use serde_json::json;
 use stacks_common::consts::CHAIN_ID_TESTNET;
 use stacks_common::types::chainstate::StacksBlockId;
 use stacks_common::types::StacksEpochId;
+use stacks_common::util::hash::Sha512Trunc256Sum;
 
 use super::EvalHook;
 use crate::vm::ast::{ASTRules, ContractAST};
@@ -36,17 +37,18 @@ use crate::vm::costs::{
 };
 use crate::vm::database::{
     ClarityDatabase, DataMapMetadata, DataVariableMetadata, FungibleTokenMetadata,
-    NonFungibleTokenMetadata,
+    NonFungibleTokenMetadata, ReadOnlyCallKey, ReadOnlyCallResult,
 };
 use crate::vm::errors::{
-    CheckErrors, InterpreterError, InterpreterResult as Result, RuntimeErrorType,
+    CheckErrors, ErrorLocation, InterpreterError, InterpreterResult as Result, RuntimeErrorType,
 };
 use crate::vm::events::*;
-use crate::vm::representations::{ClarityName, ContractName, SymbolicExpression};
+use crate::vm::representations::{ClarityName, ContractName, Span, SymbolicExpression};
 use crate::vm::types::signatures::FunctionSignature;
 use crate::vm::types::{
-    AssetIdentifier, BuffData, CallableData, OptionalData, PrincipalData,
-    QualifiedContractIdentifier, TraitIdentifier, TypeSignature, Value,
+    AssetIdentifier, BuffData, CallableData, ListData, OptionalData, PrincipalData,
+    QualifiedContractIdentifier, ResponseData, SequenceData, TraitIdentifier, TupleData,
+    TypeSignature, Value, ValueLimits,
 };
 use crate::vm::version::ClarityVersion;
 use crate::vm::{ast, eval, is_reserved, stx_transfer_consolidated};
@@ -203,6 +205,11 @@ pub struct GlobalContext<'a, 'hooks> {
     /// This is the chain ID of the transaction
     pub chain_id: u32,
     pub eval_hooks: Option<Vec<&'hooks mut dyn EvalHook>>,
+    /// The limits on values in effect while executing.  Always the consensus limits on mainnet.
+    pub value_limits: ValueLimits,
+    /// Where the last runtime error or cost-limit failure was raised, if the last top-level
+    ///  execution failed with one
+    pub error_location: Option<ErrorLocation>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -576,7 +583,7 @@ impl<'a, 'hooks> OwnedEnvironment<'a, 'hooks> {
         database: ClarityDatabase<'a>,
         cost_tracker: LimitedCostTracker,
         epoch_id: StacksEpochId,
-    ) -> OwnedEnvironment<'a, '_> {
+    ) -> OwnedEnvironment<'a, 'hooks> {
         OwnedEnvironment {
             context: GlobalContext::new(mainnet, chain_id, database, cost_tracker, epoch_id),
             call_stack: CallStack::new(),
@@ -611,6 +618,8 @@ impl<'a, 'hooks> OwnedEnvironment<'a, 'hooks> {
         F: FnOnce(&mut Environment) -> std::result::Result<A, E>,
     {
         assert!(self.context.is_top_level());
+        let _value_limits = self.context.value_limits.enter();
+        self.context.error_location = None;
         self.begin();
 
         let result = {
@@ -807,6 +816,11 @@ impl<'a, 'hooks> OwnedEnvironment<'a, 'hooks> {
         self.context.cost_track.get_total()
     }
 
+    /// Where the last runtime error or cost-limit failure in this environment was raised
+    pub fn error_location(&self) -> Option<&ErrorLocation> {
+        self.context.error_location.as_ref()
+    }
+
     /// Destroys this environment, returning ownership of its database reference.
     ///  If the context wasn't top-level (i.e., it had uncommitted data), return None,
     ///   because the database is not guaranteed to be in a sane state.
@@ -890,6 +904,20 @@ impl CostTracker for GlobalContext<'_, '_> {
     }
 }
 
+/// Whether `value` is or contains a trait reference
+fn contains_callable(value: &Value) -> bool {
+    match value {
+        Value::CallableContract(_) => true,
+        Value::Optional(OptionalData { data: Some(inner) }) => contains_callable(inner),
+        Value::Response(ResponseData { data, .. }) => contains_callable(data),
+        Value::Sequence(SequenceData::List(ListData { data, .. })) => {
+            data.iter().any(contains_callable)
+        }
+        Value::Tuple(TupleData { data_map, .. }) => data_map.values().any(contains_callable),
+        _ => false,
+    }
+}
+
 impl<'a, 'b, 'hooks> Environment<'a, 'b, 'hooks> {
     /// Returns an Environment value & checks the types of the contract sender, caller, and sponsor
     ///
@@ -1062,6 +1090,159 @@ impl<'a, 'b, 'hooks> Environment<'a, 'b, 'hooks> {
         &self.global_context.epoch_id
     }
 
+    /// Record that evaluating `expression` failed with `error`, if `error` has a source location
+    ///  and no location has been recorded yet.  Errors are recorded as they return from `eval()`,
+    ///  so the location is that of the innermost expression that failed.
+    pub fn record_error_location(
+        &mut self,
+        expression: &SymbolicExpression,
+        error: &crate::vm::errors::Error,
+    ) {
+        if self.global_context.error_location.is_some() || !error.has_source_location() {
+            return;
+        }
+        let contract_identifier = self.contract_context.contract_identifier.clone();
+        let span = if *expression.span() != Span::ZERO {
+            // spans are kept on the expressions themselves in developer mode
+            Some(expression.span().clone())
+        } else {
+            self.global_context
+                .database
+                .get_expression_span(&contract_identifier, expression.id)
+                .ok()
+                .flatten()
+        };
+        self.global_context.error_location = Some(ErrorLocation {
+            contract_identifier,
+            expression_id: expression.id,
+            span,
+        });
+    }
+
+    /// Call `f` on each of the eval hooks, if there are any
+    pub fn with_eval_hooks<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut dyn EvalHook, &mut Environment),
+    {
+        if let Some(mut hooks) = self.global_context.eval_hooks.take() {
+            for hook in hooks.iter_mut() {
+                f(&mut **hook, self);
+            }
+            self.global_context.eval_hooks = Some(hooks);
+        }
+    }
+
+    /// The identity of a call of the current contract's read-only function `function`, if
+    ///  calls made from here may be memoized: the store must keep a read-only call cache, and
+    ///  the call must not be observed (by eval hooks or a cost profile) or take trait
+    ///  references (which serialize as plain principals).
+    fn read_only_call_key(
+        &mut self,
+        function: &ClarityName,
+        args: &[Value],
+    ) -> Option<ReadOnlyCallKey> {
+        if self.global_context.eval_hooks.is_some()
+            || self.global_context.cost_track.is_profiling()
+            || self
+                .global_context
+                .database
+                .get_read_only_call_cache()
+                .is_none()
+            || args.iter().any(contains_callable)
+        {
+            return None;
+        }
+        let mut serialized = vec![];
+        for arg in args.iter() {
+            arg.serialize_write(&mut serialized).ok()?;
+        }
+        Some(ReadOnlyCallKey {
+            contract: self.contract_context.contract_identifier.clone(),
+            function: function.clone(),
+            args_hash: Sha512Trunc256Sum::from_data(&serialized),
+            sender: self.sender.clone(),
+            caller: self.caller.clone(),
+            sponsor: self.sponsor.clone(),
+        })
+    }
+
+    /// Evaluate the body of the current contract's read-only function `function` applied to
+    ///  `args` (by calling `eval_body`), going through the store's read-only call cache if it
+    ///  keeps one.  A memoized call charges the cost and memory that evaluating it did, unless
+    ///  that would exceed a limit, in which case it is evaluated again so that it fails in the
+    ///  same way.  Calls that fail or emit events are not memoized.
+    pub fn memoize_read_only_call<F>(
+        &mut self,
+        function: &ClarityName,
+        args: &[Value],
+        eval_body: F,
+    ) -> Result<Value>
+    where
+        F: FnOnce(&mut Self) -> Result<Value>,
+    {
+        let Some(call) = self.read_only_call_key(function, args) else {
+            return eval_body(self);
+        };
+        let memoized = self
+            .global_context
+            .database
+            .get_read_only_call_cache()
+            .and_then(|cache| cache.lookup(&call));
+        if let Some(memoized) = memoized {
+            let tracker = &mut self.global_context.cost_track;
+            let mut total = tracker.get_total();
+            let within_limits = total.add(&memoized.cost).is_ok()
+                && !total.exceeds(&tracker.get_limit())
+                && matches!(
+                    tracker.get_memory().checked_add(memoized.peak_memory),
+                    Some(peak) if peak <= tracker.get_memory_limit()
+                );
+            if within_limits {
+                tracker.add_cost(memoized.cost)?;
+                tracker.add_memory(memoized.peak_memory)?;
+                tracker.drop_memory(memoized.peak_memory - memoized.memory)?;
+                return Ok(memoized.value);
+            }
+        }
+
+        let tracker = &mut self.global_context.cost_track;
+        let cost_before = tracker.get_total();
+        let memory_before = tracker.get_memory();
+        let peak_before = tracker.get_peak_memory();
+        tracker.set_peak_memory(memory_before);
+        let events_before = self.global_context.event_count();
+        if let Some(cache) = self.global_context.database.get_read_only_call_cache() {
+            cache.begin_call();
+        }
+
+        let result = eval_body(self);
+
+        let tracker = &mut self.global_context.cost_track;
+        let peak = tracker.get_peak_memory();
+        tracker.set_peak_memory(peak.max(peak_before));
+        let outcome = match result.as_ref() {
+            Ok(value) if self.global_context.event_count() == events_before => {
+                let tracker = &self.global_context.cost_track;
+                let mut cost = tracker.get_total();
+                let memory = tracker.get_memory().checked_sub(memory_before);
+                match (cost.sub(&cost_before), memory) {
+                    (Ok(()), Some(memory)) => Some(ReadOnlyCallResult {
+                        value: value.clone(),
+                        cost,
+                        memory,
+                        peak_memory: peak.saturating_sub(memory_before).max(memory),
+                    }),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        if let Some(cache) = self.global_context.database.get_read_only_call_cache() {
+            cache.end_call(call, outcome);
+        }
+        result
+    }
+
     pub fn execute_contract(
         &mut self,
         contract: &QualifiedContractIdentifier,
@@ -1143,7 +1324,11 @@ impl<'a, 'b, 'hooks> Environment<'a, 'b, 'hooks> {
                 return Err(CheckErrors::CircularReference(vec![func_identifier.to_string()]).into())
             }
             self.call_stack.insert(&func_identifier, true);
+            self.with_eval_hooks(|hook, env| hook.will_call_contract(env, contract_identifier, tx_name, &args));
+            self.global_context.cost_track.begin_profile_contract_call(contract_identifier, tx_name);
             let res = self.execute_function_as_transaction(&func, &args, Some(&contract.contract_context), allow_private);
+            self.global_context.cost_track.end_profile_contract_call();
+            self.with_eval_hooks(|hook, env| hook.did_finish_contract_call(env, contract_identifier, tx_name, &res));
             self.call_stack.remove(&func_identifier, true)?;
 
             match res {
@@ -1312,9 +1497,15 @@ impl<'a, 'b, 'hooks> Environment<'a, 'b, 'hooks> {
                 self.global_context
                     .database
                     .insert_contract(&contract_identifier, contract)?;
-                self.global_context
+                if let Err(e) = self
+                    .global_context
                     .database
-                    .set_contract_data_size(&contract_identifier, data_size)?;
+                    .set_contract_data_size(&contract_identifier, data_size)
+                {
+                    // e.g. the contract's constants would take it over its data quota
+                    self.global_context.roll_back()?;
+                    return Err(e);
+                }
 
                 self.global_context.commit()?;
                 Ok(())
@@ -1377,6 +1568,7 @@ impl<'a, 'b, 'hooks> Environment<'a, 'b, 'hooks> {
     }
 
     pub fn push_to_event_batch(&mut self, event: StacksTransactionEvent) {
+        self.with_eval_hooks(|hook, env| hook.did_register_event(env, &event));
         if let Some(batch) = self.global_context.event_batches.last_mut() {
             batch.events.push(event);
         }
@@ -1404,6 +1596,18 @@ impl<'a, 'b, 'hooks> Environment<'a, 'b, 'hooks> {
         Ok(())
     }
 
+    pub fn register_contract_event(&mut self, name: ClarityName, payload: Value) -> Result<()> {
+        let event_data = ContractEventData {
+            contract_identifier: self.contract_context.contract_identifier.clone(),
+            name,
+            payload,
+        };
+        let event = StacksTransactionEvent::ContractEvent(event_data);
+
+        self.push_to_event_batch(event);
+        Ok(())
+    }
+
     pub fn register_stx_transfer_event(
         &mut self,
         sender: PrincipalData,
@@ -1546,7 +1750,12 @@ impl<'a, 'hooks> GlobalContext<'a, 'hooks> {
         database: ClarityDatabase<'a>,
         cost_track: LimitedCostTracker,
         epoch_id: StacksEpochId,
-    ) -> GlobalContext {
+    ) -> GlobalContext<'a, 'hooks> {
+        let value_limits = if mainnet {
+            ValueLimits::CONSENSUS
+        } else {
+            database.get_value_limits()
+        };
         GlobalContext {
             database,
             cost_track,
@@ -1557,6 +1766,8 @@ impl<'a, 'hooks> GlobalContext<'a, 'hooks> {
             epoch_id,
             chain_id,
             eval_hooks: None,
+            value_limits,
+            error_location: None,
         }
     }
 
@@ -1613,6 +1824,10 @@ impl<'a, 'hooks> GlobalContext<'a, 'hooks> {
     where
         F: FnOnce(&mut Self) -> Result<T>,
     {
+        let _value_limits = self.value_limits.enter();
+        if self.is_top_level() {
+            self.error_location = None;
+        }
         self.begin();
         let result = f(self).or_else(|e| {
             self.roll_back()?;
@@ -1665,6 +1880,14 @@ impl<'a, 'hooks> GlobalContext<'a, 'hooks> {
         self.read_only.last().cloned().unwrap_or(false)
     }
 
+    /// The number of events emitted so far that have not been rolled back
+    pub fn event_count(&self) -> usize {
+        self.event_batches
+            .iter()
+            .map(|batch| batch.events.len())
+            .sum()
+    }
+
     pub fn begin(&mut self) {
         self.asset_maps.push(AssetMap::new());
         self.event_batches.push(EventBatch::new());
@@ -1971,6 +2194,7 @@ mod test {
 
     use super::*;
     use crate::vm::callables::DefineType;
+    use crate::vm::database::{ClarityBackingStore, MemoryBackingStore};
     use crate::vm::tests::{
         test_epochs, tl_env_factory, MemoryEnvironmentGenerator, TopLevelMemoryEnvironmentGenerator,
     };
@@ -2215,4 +2439,61 @@ mod test {
             TypeSignature::CallableType(CallableSubtype::Trait(trait_id))
         );
     }
+
+    #[test]
+    fn test_memoize_read_only_calls() {
+        let epoch = StacksEpochId::Epoch21;
+        let contract_id = QualifiedContractIdentifier::local("counter").unwrap();
+        let contract = "(define-data-var count uint u1)
+            (define-read-only (get-scaled (factor uint)) (* factor (var-get count)))
+            (define-public (increment) (ok (var-set count (+ u1 (var-get count)))))";
+        let sender = PrincipalData::Standard(StandardPrincipalData::transient());
+
+        let mut plain = MemoryBackingStore::new();
+        let mut memoizing = MemoryBackingStore::new();
+        memoizing.set_memoize_read_only_calls(true);
+        for store in [&mut plain, &mut memoizing] {
+            OwnedEnvironment::new(store.as_clarity_db(), epoch)
+                .initialize_contract(contract_id.clone(), contract, None, ASTRules::PrecheckSize)
+                .unwrap();
+        }
+        let call = |store: &mut MemoryBackingStore, function: &str, args: &[Value]| {
+            let args: Vec<_> = args
+                .iter()
+                .map(|arg| SymbolicExpression::atom_value(arg.clone()))
+                .collect();
+            let mut env = OwnedEnvironment::new(store.as_clarity_db(), epoch);
+            let (value, ..) = env
+                .execute_transaction(sender.clone(), None, contract_id.clone(), function, &args)
+                .unwrap();
+            (value, env.get_cost_total())
+        };
+        let hits =
+            |store: &mut MemoryBackingStore| store.get_read_only_call_cache().unwrap().hits();
+
+        let expected = call(&mut plain, "get-scaled", &[Value::UInt(3)]);
+        assert_eq!(expected.0, Value::UInt(3));
+        for _ in 0..2 {
+            assert_eq!(
+                call(&mut memoizing, "get-scaled", &[Value::UInt(3)]),
+                expected
+            );
+        }
+        assert_eq!(hits(&mut memoizing), 1);
+
+        // other arguments are another call
+        call(&mut memoizing, "get-scaled", &[Value::UInt(4)]);
+        assert_eq!(hits(&mut memoizing), 1);
+
+        // writing the variable invalidates the call
+        call(&mut plain, "increment", &[]);
+        call(&mut memoizing, "increment", &[]);
+        let expected = call(&mut plain, "get-scaled", &[Value::UInt(3)]);
+        assert_eq!(expected.0, Value::UInt(6));
+        assert_eq!(
+            call(&mut memoizing, "get-scaled", &[Value::UInt(3)]),
+            expected
+        );
+        assert_eq!(hits(&mut memoizing), 1);
+    }
 }
@@ -154,5 +154,15 @@ define_named_enum!(ClarityCostFunction {
     BitwiseNot("cost_bitwise_not"),
     BitwiseLShift("cost_bitwise_left_shift"),
     BitwiseRShift("cost_bitwise_right_shift"),
+    BuffAnd("cost_buff_and"),
+    BuffOr("cost_buff_or"),
+    BuffXor("cost_buff_xor"),
+    BuffNot("cost_buff_not"),
+    AltBn128Add("cost_alt_bn128_add"),
+    AltBn128Mul("cost_alt_bn128_mul"),
+    AltBn128PairingCheck("cost_alt_bn128_pairing_check"),
+    BlockVrfSeed("cost_block_vrf_seed"),
+    ImplementsTrait("cost_implements_trait"),
+    EmitEvent("cost_emit_event"),
     Unimplemented("cost_unimplemented"),
 });
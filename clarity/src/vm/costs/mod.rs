@@ -26,6 +26,7 @@ use crate::boot_util::boot_code_id;
 use crate::vm::ast::ContractAST;
 use crate::vm::contexts::{ContractContext, Environment, GlobalContext, OwnedEnvironment};
 use crate::vm::costs::cost_functions::ClarityCostFunction;
+use crate::vm::costs::profile::CostProfile;
 use crate::vm::database::clarity_store::NullBackingStore;
 use crate::vm::database::ClarityDatabase;
 use crate::vm::errors::{Error, InterpreterResult};
@@ -40,6 +41,7 @@ use crate::vm::{ast, eval_all, ClarityName, SymbolicExpression, Value};
 
 pub mod constants;
 pub mod cost_functions;
+pub mod profile;
 
 type Result<T> = std::result::Result<T, CostErrors>;
 
@@ -49,6 +51,16 @@ pub const CLARITY_MEMORY_LIMIT: u64 = 100 * 1000 * 1000;
 pub const COSTS_1_NAME: &'static str = "costs";
 pub const COSTS_2_NAME: &'static str = "costs-2";
 pub const COSTS_3_NAME: &'static str = "costs-3";
+pub const COSTS_4_NAME: &'static str = "costs-4";
+
+/// A cost-function contract confirmed through the cost-voting contract in epoch 3.1 or later can
+///  define this constant (a uint) so that the boot cost functions it replaces only take effect
+///  once the burnchain reaches that height, instead of as soon as the proposal is processed.
+///
+/// This can't be enabled on existing epochs, since it changes which proposals take effect and
+///  when, so it is blocked on epoch 3.1 being scheduled.  Mainnet and testnet don't schedule it
+///  yet, so only networks with their own epoch list (e.g. regtest and tests) can use it.
+pub const COST_ACTIVATION_BURN_HEIGHT: &str = "ACTIVATION_BURN_HEIGHT";
 
 lazy_static! {
     static ref COST_TUPLE_TYPE_SIGNATURE: TypeSignature = {
@@ -183,11 +195,22 @@ impl ClarityCostFunctionReference {
     }
 }
 
+/// A confirmed replacement of a boot cost function that takes effect once the burnchain reaches
+///  `activation_burn_height` (see `COST_ACTIVATION_BURN_HEIGHT`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledCostFunction {
+    pub activation_burn_height: u32,
+    pub cost_function: ClarityCostFunction,
+    pub reference: ClarityCostFunctionReference,
+}
+
 #[derive(Debug, Clone)]
 pub struct CostStateSummary {
     pub contract_call_circuits:
         HashMap<(QualifiedContractIdentifier, ClarityName), ClarityCostFunctionReference>,
     pub cost_function_references: HashMap<ClarityCostFunction, ClarityCostFunctionReference>,
+    /// Replacements that wait for a burnchain height, in the order they were confirmed
+    pub scheduled_cost_functions: Vec<ScheduledCostFunction>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -197,6 +220,9 @@ struct SerializedCostStateSummary {
         ClarityCostFunctionReference,
     )>,
     cost_function_references: Vec<(ClarityCostFunction, ClarityCostFunctionReference)>,
+    // omitted when empty, so that summaries without scheduled replacements are stored as before
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    scheduled_cost_functions: Vec<ScheduledCostFunction>,
 }
 
 impl From<CostStateSummary> for SerializedCostStateSummary {
@@ -204,10 +230,12 @@ impl From<CostStateSummary> for SerializedCostStateSummary {
         let CostStateSummary {
             contract_call_circuits,
             cost_function_references,
+            scheduled_cost_functions,
         } = other;
         SerializedCostStateSummary {
             contract_call_circuits: contract_call_circuits.into_iter().collect(),
             cost_function_references: cost_function_references.into_iter().collect(),
+            scheduled_cost_functions,
         }
     }
 }
@@ -217,10 +245,12 @@ impl From<SerializedCostStateSummary> for CostStateSummary {
         let SerializedCostStateSummary {
             contract_call_circuits,
             cost_function_references,
+            scheduled_cost_functions,
         } = other;
         CostStateSummary {
             contract_call_circuits: contract_call_circuits.into_iter().collect(),
             cost_function_references: cost_function_references.into_iter().collect(),
+            scheduled_cost_functions,
         }
     }
 }
@@ -230,6 +260,43 @@ impl CostStateSummary {
         CostStateSummary {
             contract_call_circuits: HashMap::new(),
             cost_function_references: HashMap::new(),
+            scheduled_cost_functions: vec![],
+        }
+    }
+
+    /// Replace `cost_function` with `reference`, now or once the burnchain reaches
+    ///  `activation_burn_height`.  A replacement that takes effect now supersedes the ones
+    ///  scheduled before it.
+    fn replace_cost_function(
+        &mut self,
+        cost_function: ClarityCostFunction,
+        reference: ClarityCostFunctionReference,
+        activation_burn_height: Option<u32>,
+    ) {
+        match activation_burn_height {
+            Some(activation_burn_height) => {
+                self.scheduled_cost_functions.push(ScheduledCostFunction {
+                    activation_burn_height,
+                    cost_function,
+                    reference,
+                })
+            }
+            None => {
+                self.scheduled_cost_functions
+                    .retain(|scheduled| scheduled.cost_function != cost_function);
+                self.cost_function_references
+                    .insert(cost_function, reference);
+            }
+        }
+    }
+
+    /// Apply the scheduled replacements that have taken effect by `burn_height`
+    pub fn activate_scheduled(&mut self, burn_height: u32) {
+        for scheduled in self.scheduled_cost_functions.iter() {
+            if scheduled.activation_burn_height <= burn_height {
+                self.cost_function_references
+                    .insert(scheduled.cost_function, scheduled.reference.clone());
+            }
         }
     }
 }
@@ -245,12 +312,16 @@ pub struct TrackerData {
     limit: ExecutionCost,
     memory: u64,
     memory_limit: u64,
+    /// the most memory in use at once since the last `LimitedCostTracker::set_peak_memory()`
+    peak_memory: u64,
     /// if the cost tracker is non-free, this holds the StacksEpochId that should be used to evaluate
     ///  the Clarity cost functions. If the tracker *is* free, then those functions do not need to be
     ///  evaluated, so no epoch identifier is necessary.
     epoch: StacksEpochId,
     mainnet: bool,
     chain_id: u32,
+    /// If profiling is enabled, the breakdown of the costs charged so far
+    profile: Option<CostProfile>,
 }
 
 #[derive(Clone)]
@@ -613,7 +684,14 @@ fn load_cost_functions(
             }
         };
 
-        if target_contract == boot_code_id("costs", mainnet) {
+        // since epoch 3.1, the functions of any of the boot cost contracts can be replaced
+        let targets_boot_costs = target_contract == boot_code_id(COSTS_1_NAME, mainnet)
+            || (clarity_epoch >= StacksEpochId::Epoch31
+                && [COSTS_2_NAME, COSTS_3_NAME, COSTS_4_NAME]
+                    .iter()
+                    .any(|name| target_contract == boot_code_id(name, mainnet)));
+
+        if targets_boot_costs {
             // refering to one of the boot code cost functions
             let target = match ClarityCostFunction::lookup_by_name(&target_function) {
                 Some(ClarityCostFunction::Unimplemented) => {
@@ -630,9 +708,28 @@ fn load_cost_functions(
                     continue;
                 }
             };
-            state_summary
-                .cost_function_references
-                .insert(target, cost_func_ref);
+            let activation_burn_height = if clarity_epoch >= StacksEpochId::Epoch31 {
+                let cost_contract = clarity_db
+                    .get_contract(&cost_func_ref.contract_id)
+                    .map_err(|e| CostErrors::CostComputationFailed(e.to_string()))?;
+                match cost_contract
+                    .contract_context
+                    .lookup_variable(COST_ACTIVATION_BURN_HEIGHT)
+                {
+                    None => None,
+                    Some(Value::UInt(height)) => Some(u32::try_from(*height).unwrap_or(u32::MAX)),
+                    Some(_) => {
+                        warn!("Confirmed cost proposal invalid: activation burn height is not a uint";
+                              "confirmed_proposal_id" => confirmed_proposal,
+                              "contract_name" => %cost_func_ref.contract_id,
+                        );
+                        continue;
+                    }
+                }
+            } else {
+                None
+            };
+            state_summary.replace_cost_function(target, cost_func_ref, activation_burn_height);
         } else {
             // referring to a user-defined function
             match clarity_db
@@ -709,9 +806,11 @@ impl LimitedCostTracker {
             memory_limit: CLARITY_MEMORY_LIMIT,
             total: ExecutionCost::zero(),
             memory: 0,
+            peak_memory: 0,
             epoch,
             mainnet,
             chain_id,
+            profile: None,
         };
         assert!(clarity_db.is_stack_empty());
         cost_tracker.load_costs(clarity_db, true)?;
@@ -733,9 +832,38 @@ impl LimitedCostTracker {
             memory_limit: CLARITY_MEMORY_LIMIT,
             total: ExecutionCost::zero(),
             memory: 0,
+            peak_memory: 0,
+            epoch,
+            mainnet,
+            chain_id,
+            profile: None,
+        };
+        cost_tracker.load_costs(clarity_db, false)?;
+        Ok(Self::Limited(cost_tracker))
+    }
+
+    /// Cost tracker for a read-only evaluation that is bounded by `limits`, including its
+    /// memory limit.  Like `new_mid_block()`, this can be used while the database is in use.
+    pub fn new_read_only(
+        mainnet: bool,
+        chain_id: u32,
+        limits: &ExecutionLimits,
+        clarity_db: &mut ClarityDatabase,
+        epoch: StacksEpochId,
+    ) -> Result<LimitedCostTracker> {
+        let mut cost_tracker = TrackerData {
+            cost_function_references: HashMap::new(),
+            cost_contracts: HashMap::new(),
+            contract_call_circuits: HashMap::new(),
+            limit: limits.cost_limit(),
+            memory_limit: limits.memory,
+            total: ExecutionCost::zero(),
+            memory: 0,
+            peak_memory: 0,
             epoch,
             mainnet,
             chain_id,
+            profile: None,
         };
         cost_tracker.load_costs(clarity_db, false)?;
         Ok(Self::Limited(cost_tracker))
@@ -776,6 +904,7 @@ impl LimitedCostTracker {
             | StacksEpochId::Epoch24
             | StacksEpochId::Epoch25
             | StacksEpochId::Epoch30 => COSTS_3_NAME.to_string(),
+            StacksEpochId::Epoch31 => COSTS_4_NAME.to_string(),
         };
         Ok(result)
     }
@@ -797,18 +926,33 @@ impl TrackerData {
             self.mainnet,
         );
 
+        let mut state_summary = load_cost_functions(self.mainnet, clarity_db, apply_updates)
+            .map_err(|e| {
+                let result = clarity_db
+                    .roll_back()
+                    .map_err(|e| CostErrors::Expect(e.to_string()));
+                match result {
+                    Ok(_) => e,
+                    Err(rollback_err) => rollback_err,
+                }
+            })?;
+        if !state_summary.scheduled_cost_functions.is_empty() {
+            let burn_height = match clarity_db.get_current_burnchain_block_height() {
+                Ok(burn_height) => burn_height,
+                Err(e) => {
+                    clarity_db
+                        .roll_back()
+                        .map_err(|e| CostErrors::Expect(e.to_string()))?;
+                    return Err(CostErrors::CostComputationFailed(e.to_string()));
+                }
+            };
+            state_summary.activate_scheduled(burn_height);
+        }
         let CostStateSummary {
             contract_call_circuits,
             mut cost_function_references,
-        } = load_cost_functions(self.mainnet, clarity_db, apply_updates).map_err(|e| {
-            let result = clarity_db
-                .roll_back()
-                .map_err(|e| CostErrors::Expect(e.to_string()));
-            match result {
-                Ok(_) => e,
-                Err(rollback_err) => rollback_err,
-            }
-        })?;
+            ..
+        } = state_summary;
 
         self.contract_call_circuits = contract_call_circuits;
 
@@ -890,6 +1034,15 @@ impl LimitedCostTracker {
             Self::Free => panic!("Cannot set total on free tracker"),
         }
     }
+    #[allow(clippy::panic)]
+    pub fn set_limit(&mut self, limit: ExecutionCost) {
+        // used to temporarily apply a different budget to a phase of a transaction,
+        //  e.g. the analysis of a contract deployment.
+        match self {
+            Self::Limited(ref mut data) => data.limit = limit,
+            Self::Free => panic!("Cannot set limit on free tracker"),
+        }
+    }
     pub fn get_limit(&self) -> ExecutionCost {
         match self {
             Self::Limited(TrackerData { limit, .. }) => limit.clone(),
@@ -909,6 +1062,73 @@ impl LimitedCostTracker {
             Self::Free => u64::MAX,
         }
     }
+    /// The most memory in use at once since the last `set_peak_memory()`
+    pub fn get_peak_memory(&self) -> u64 {
+        match self {
+            Self::Limited(TrackerData { peak_memory, .. }) => *peak_memory,
+            Self::Free => 0,
+        }
+    }
+    /// Track the peak memory use from `peak` on.  Used to measure the memory used by part of an
+    ///  evaluation.
+    pub fn set_peak_memory(&mut self, peak: u64) {
+        if let Self::Limited(ref mut data) = self {
+            data.peak_memory = peak;
+        }
+    }
+    /// Whether a cost profile is being recorded
+    pub fn is_profiling(&self) -> bool {
+        match self {
+            Self::Limited(data) => data.profile.is_some(),
+            Self::Free => false,
+        }
+    }
+
+    /// Is this tracking costs on mainnet?  A free tracker doesn't know.
+    pub fn is_mainnet(&self) -> bool {
+        match self {
+            Self::Limited(TrackerData { mainnet, .. }) => *mainnet,
+            Self::Free => false,
+        }
+    }
+
+    /// Start recording a cost profile, discarding any profile recorded so far.  A free tracker
+    /// charges nothing, so it records nothing.
+    pub fn enable_profile(&mut self) {
+        if let Self::Limited(ref mut data) = self {
+            data.profile = Some(CostProfile::new());
+        }
+    }
+
+    /// Stop recording the cost profile, and return it
+    pub fn take_profile(&mut self) -> Option<CostProfile> {
+        match self {
+            Self::Limited(ref mut data) => data.profile.take(),
+            Self::Free => None,
+        }
+    }
+
+    /// Attribute the costs charged from now on to a call to `contract.function`, until the
+    /// matching `end_profile_contract_call()`
+    pub fn begin_profile_contract_call(
+        &mut self,
+        contract: &QualifiedContractIdentifier,
+        function: &str,
+    ) {
+        if let Self::Limited(ref mut data) = self {
+            if let Some(profile) = data.profile.as_mut() {
+                profile.begin_contract_call(contract, function, data.memory);
+            }
+        }
+    }
+
+    pub fn end_profile_contract_call(&mut self) {
+        if let Self::Limited(ref mut data) = self {
+            if let Some(profile) = data.profile.as_mut() {
+                profile.end_contract_call();
+            }
+        }
+    }
 }
 
 fn parse_cost(
@@ -1025,6 +1245,10 @@ fn add_cost(s: &mut TrackerData, cost: ExecutionCost) -> std::result::Result<(),
 
 fn add_memory(s: &mut TrackerData, memory: u64) -> std::result::Result<(), CostErrors> {
     s.memory = s.memory.cost_overflow_add(memory)?;
+    s.peak_memory = s.peak_memory.max(s.memory);
+    if let Some(profile) = s.profile.as_mut() {
+        profile.record_memory(s.memory);
+    }
     if s.memory > s.memory_limit {
         Err(CostErrors::MemoryBalanceExceeded(s.memory, s.memory_limit))
     } else {
@@ -1066,7 +1290,11 @@ impl CostTracker for LimitedCostTracker {
                     )))?
                     .clone();
 
-                compute_cost(data, cost_function_ref, input, data.epoch)
+                let cost = compute_cost(data, cost_function_ref, input, data.epoch)?;
+                if let Some(profile) = data.profile.as_mut() {
+                    profile.record_cost(cost_function, &cost);
+                }
+                Ok(cost)
             }
         }
     }
@@ -1329,6 +1557,49 @@ impl ExecutionCost {
     }
 }
 
+/// Budget for a single read-only evaluation.  Read-only calls can't write, so unlike an
+/// `ExecutionCost` limit this only bounds runtime, reads and memory.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, Hash)]
+pub struct ExecutionLimits {
+    pub runtime: u64,
+    pub read_count: u64,
+    pub read_length: u64,
+    pub memory: u64,
+}
+
+impl ExecutionLimits {
+    /// The largest budget: no cost limit and the default VM memory limit
+    pub fn max_value() -> ExecutionLimits {
+        Self {
+            runtime: u64::MAX,
+            read_count: u64::MAX,
+            read_length: u64::MAX,
+            memory: CLARITY_MEMORY_LIMIT,
+        }
+    }
+
+    /// Limits with the runtime and read dimensions of `cost` and the given memory limit
+    pub fn from_cost(cost: &ExecutionCost, memory: u64) -> ExecutionLimits {
+        Self {
+            runtime: cost.runtime,
+            read_count: cost.read_count,
+            read_length: cost.read_length,
+            memory,
+        }
+    }
+
+    /// The cost limit to enforce for these limits.  No writes are allowed.
+    pub fn cost_limit(&self) -> ExecutionCost {
+        ExecutionCost {
+            runtime: self.runtime,
+            read_count: self.read_count,
+            read_length: self.read_length,
+            write_length: 0,
+            write_count: 0,
+        }
+    }
+}
+
 // ONLY WORKS IF INPUT IS u64
 fn int_log2(input: u64) -> Option<u64> {
     63_u32.checked_sub(input.leading_zeros()).map(|floor_log| {
@@ -1343,6 +1614,10 @@ fn int_log2(input: u64) -> Option<u64> {
 #[cfg(test)]
 mod unit_tests {
     use super::*;
+    use crate::vm::analysis::type_check;
+    use crate::vm::ast::{parse, ASTRules};
+    use crate::vm::database::MemoryBackingStore;
+    use crate::vm::version::ClarityVersion;
 
     #[test]
     fn test_simple_overflows() {
@@ -1377,4 +1652,166 @@ mod unit_tests {
             assert_eq!(int_log2(*input).unwrap(), *expected);
         }
     }
+
+    #[test]
+    fn test_scheduled_cost_functions() {
+        let reference = |name: &str| {
+            ClarityCostFunctionReference::new(
+                QualifiedContractIdentifier::local("costs-4").unwrap(),
+                name.to_string(),
+            )
+        };
+
+        let mut summary = CostStateSummary::empty();
+        summary.replace_cost_function(ClarityCostFunction::Add, reference("add-a"), Some(100));
+        summary.replace_cost_function(ClarityCostFunction::Add, reference("add-b"), Some(200));
+        summary.replace_cost_function(ClarityCostFunction::Sub, reference("sub-a"), Some(100));
+        summary.replace_cost_function(ClarityCostFunction::Sub, reference("sub-b"), None);
+        assert_eq!(summary.scheduled_cost_functions.len(), 2);
+
+        let mut before = summary.clone();
+        before.activate_scheduled(99);
+        assert_eq!(before.cost_function_references.len(), 1);
+        assert_eq!(
+            before.cost_function_references[&ClarityCostFunction::Sub],
+            reference("sub-b")
+        );
+
+        let mut between = summary.clone();
+        between.activate_scheduled(150);
+        assert_eq!(
+            between.cost_function_references[&ClarityCostFunction::Add],
+            reference("add-a")
+        );
+        assert_eq!(
+            between.cost_function_references[&ClarityCostFunction::Sub],
+            reference("sub-b")
+        );
+
+        summary.activate_scheduled(200);
+        assert_eq!(
+            summary.cost_function_references[&ClarityCostFunction::Add],
+            reference("add-b")
+        );
+
+        // summaries without scheduled replacements serialize as before
+        let serialized =
+            serde_json::to_string(&SerializedCostStateSummary::from(CostStateSummary::empty()))
+                .unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"contract_call_circuits":[],"cost_function_references":[]}"#
+        );
+        let summary: CostStateSummary =
+            serde_json::from_str::<SerializedCostStateSummary>(&serialized)
+                .unwrap()
+                .into();
+        assert!(summary.scheduled_cost_functions.is_empty());
+    }
+
+    /// Process two confirmed proposals in `epoch`, both made by a cost contract with an
+    ///  activation burn height: one replacing `cost_add` in `costs`, the other replacing
+    ///  `cost_sub` in `costs-3`
+    fn load_proposals_at_epoch(epoch: StacksEpochId) -> CostStateSummary {
+        let cost_definer = QualifiedContractIdentifier::local("cost-definer").unwrap();
+        let cost_definer_src = "
+            (define-constant ACTIVATION_BURN_HEIGHT u100)
+            (define-read-only (cost-definition (size uint))
+                { runtime: u1, write_length: u1, write_count: u1, read_count: u1, read_length: u1 })";
+        let cost_voting_src = format!(
+            "
+            (define-data-var confirmed-proposal-count uint u2)
+            (define-map confirmed-proposals
+                {{ confirmed-id: uint }}
+                {{ function-contract: principal, function-name: (string-ascii 128),
+                   cost-function-contract: principal, cost-function-name: (string-ascii 128),
+                   confirmed-height: uint }})
+            (map-set confirmed-proposals {{ confirmed-id: u0 }}
+                {{ function-contract: '{costs}, function-name: \"cost_add\",
+                   cost-function-contract: '{cost_definer}, cost-function-name: \"cost-definition\",
+                   confirmed-height: u1 }})
+            (map-set confirmed-proposals {{ confirmed-id: u1 }}
+                {{ function-contract: '{costs_3}, function-name: \"cost_sub\",
+                   cost-function-contract: '{cost_definer}, cost-function-name: \"cost-definition\",
+                   confirmed-height: u1 }})",
+            costs = boot_code_id(COSTS_1_NAME, false),
+            costs_3 = boot_code_id(COSTS_3_NAME, false),
+        );
+
+        let mut store = MemoryBackingStore::new();
+        {
+            let mut db = store.as_clarity_db();
+            db.begin();
+            db.set_clarity_epoch_version(epoch).unwrap();
+            db.commit().unwrap();
+        }
+        for (contract_id, src) in [
+            (cost_definer, cost_definer_src.to_string()),
+            (boot_code_id("cost-voting", false), cost_voting_src),
+        ] {
+            OwnedEnvironment::new(store.as_clarity_db(), epoch)
+                .initialize_versioned_contract(
+                    contract_id.clone(),
+                    ClarityVersion::Clarity2,
+                    &src,
+                    None,
+                    ASTRules::PrecheckSize,
+                )
+                .unwrap();
+            let mut expressions =
+                parse(&contract_id, &src, ClarityVersion::Clarity2, epoch).unwrap();
+            type_check(
+                &contract_id,
+                &mut expressions,
+                &mut store.as_analysis_db(),
+                true,
+                &epoch,
+                &ClarityVersion::Clarity2,
+            )
+            .unwrap();
+        }
+
+        let mut db = store.as_clarity_db();
+        db.begin();
+        let summary = load_cost_functions(false, &mut db, true).unwrap();
+        db.commit().unwrap();
+        summary
+    }
+
+    #[test]
+    fn test_boot_cost_replacements_by_epoch() {
+        let cost_definition = ClarityCostFunctionReference::new(
+            QualifiedContractIdentifier::local("cost-definer").unwrap(),
+            "cost-definition".to_string(),
+        );
+
+        // before epoch 3.1, only `costs` can be replaced, and it is replaced right away
+        let summary = load_proposals_at_epoch(StacksEpochId::Epoch30);
+        assert!(summary.scheduled_cost_functions.is_empty());
+        assert_eq!(summary.cost_function_references.len(), 1);
+        assert_eq!(
+            summary.cost_function_references[&ClarityCostFunction::Add],
+            cost_definition
+        );
+        assert!(summary.contract_call_circuits.is_empty());
+
+        // since epoch 3.1, both replacements wait for the activation burn height
+        let summary = load_proposals_at_epoch(StacksEpochId::Epoch31);
+        assert!(summary.cost_function_references.is_empty());
+        assert_eq!(
+            summary.scheduled_cost_functions,
+            vec![
+                ScheduledCostFunction {
+                    activation_burn_height: 100,
+                    cost_function: ClarityCostFunction::Add,
+                    reference: cost_definition.clone(),
+                },
+                ScheduledCostFunction {
+                    activation_burn_height: 100,
+                    cost_function: ClarityCostFunction::Sub,
+                    reference: cost_definition,
+                },
+            ]
+        );
+    }
 }
@@ -0,0 +1,132 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Cost profiles.  When enabled on a `LimitedCostTracker`, a profile records where the cost of a
+//! transaction went: how much each cost function (i.e. each native function, plus the
+//! interpreter's own bookkeeping) charged, and how much each contract function called cost,
+//! including the calls it made.  Since costs are deterministic, so is the profile.
+//!
+//! The profile only sums costs that the tracker has already charged (and checked for overflow)
+//! so it ignores overflow errors of its own.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::vm::costs::cost_functions::ClarityCostFunction;
+use crate::vm::costs::ExecutionCost;
+use crate::vm::types::QualifiedContractIdentifier;
+
+/// The cost charged by one cost function, or by calls to one contract function
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CostProfileEntry {
+    /// Number of times the cost was charged, or the function called
+    pub count: u64,
+    pub cost: ExecutionCost,
+    /// For contract calls, the most memory in use during any one call, beyond what was in use
+    /// when it started
+    pub peak_memory: u64,
+}
+
+impl Default for CostProfileEntry {
+    fn default() -> Self {
+        CostProfileEntry {
+            count: 0,
+            cost: ExecutionCost::zero(),
+            peak_memory: 0,
+        }
+    }
+}
+
+/// A contract call that hasn't returned yet
+#[derive(Debug, Clone, PartialEq)]
+struct OpenCall {
+    key: String,
+    cost: ExecutionCost,
+    start_memory: u64,
+    peak_memory: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostProfile {
+    /// Cost charged by each cost function, keyed by its name (e.g. `cost_add`)
+    pub cost_functions: BTreeMap<String, CostProfileEntry>,
+    /// Cost of each contract function called, keyed by `<contract>.<function>`
+    pub contract_calls: BTreeMap<String, CostProfileEntry>,
+    /// The most memory in use at any point
+    pub peak_memory: u64,
+    #[serde(skip)]
+    open_calls: Vec<OpenCall>,
+}
+
+impl CostProfile {
+    pub fn new() -> CostProfile {
+        CostProfile::default()
+    }
+
+    /// Total cost recorded across all cost functions
+    pub fn total(&self) -> ExecutionCost {
+        let mut total = ExecutionCost::zero();
+        for entry in self.cost_functions.values() {
+            let _ = total.add(&entry.cost);
+        }
+        total
+    }
+
+    pub(crate) fn record_cost(&mut self, cost_function: ClarityCostFunction, cost: &ExecutionCost) {
+        let entry = self
+            .cost_functions
+            .entry(cost_function.get_name())
+            .or_default();
+        entry.count = entry.count.saturating_add(1);
+        let _ = entry.cost.add(cost);
+        for call in self.open_calls.iter_mut() {
+            let _ = call.cost.add(cost);
+        }
+    }
+
+    pub(crate) fn record_memory(&mut self, memory: u64) {
+        self.peak_memory = self.peak_memory.max(memory);
+        for call in self.open_calls.iter_mut() {
+            call.peak_memory = call.peak_memory.max(memory);
+        }
+    }
+
+    pub(crate) fn begin_contract_call(
+        &mut self,
+        contract: &QualifiedContractIdentifier,
+        function: &str,
+        memory: u64,
+    ) {
+        self.open_calls.push(OpenCall {
+            key: format!("{contract}.{function}"),
+            cost: ExecutionCost::zero(),
+            start_memory: memory,
+            peak_memory: memory,
+        });
+    }
+
+    pub(crate) fn end_contract_call(&mut self) {
+        let Some(call) = self.open_calls.pop() else {
+            return;
+        };
+        let entry = self.contract_calls.entry(call.key).or_default();
+        entry.count = entry.count.saturating_add(1);
+        let _ = entry.cost.add(&call.cost);
+        entry.peak_memory = entry
+            .peak_memory
+            .max(call.peak_memory.saturating_sub(call.start_memory));
+    }
+}
@@ -1,17 +1,23 @@
 use std::collections::BTreeMap;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::Write;
+use std::path::Path;
 
 use hashbrown::{HashMap, HashSet};
 use serde_json::Value as JsonValue;
 
 use super::functions::define::DefineFunctionsParsed;
 use super::EvalHook;
+use crate::vm::ast::ContractAST;
+use crate::vm::representations::Span;
 use crate::vm::types::QualifiedContractIdentifier;
 use crate::vm::SymbolicExpression;
 
 pub struct CoverageReporter {
     executed_lines: HashMap<QualifiedContractIdentifier, HashMap<u32, u64>>,
+    /// How many times each expression was evaluated, by expression ID.  Unlike lines, these
+    ///  are recorded without the `developer-mode` feature (which is needed for spans).
+    executed_expressions: HashMap<QualifiedContractIdentifier, HashMap<u64, u64>>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -19,34 +25,79 @@ struct ContractFileInfo {
     contract: String,
     src_file: String,
     executable_lines: Vec<u32>,
+    /// The spans in `src_file` of the executable atoms, each with the ID of the expression
+    ///  whose evaluation covers it
+    #[serde(default)]
+    executable_expressions: Vec<(u64, Span)>,
 }
 
 #[derive(Serialize, Deserialize)]
 struct CoverageFileInfo {
     coverage: HashMap<String, Vec<(u32, u64)>>,
+    #[serde(default)]
+    expressions: HashMap<String, Vec<(u64, u64)>>,
+}
+
+/// The coverage of a contract, summed over the coverage files of a report
+struct ContractCoverage {
+    info: ContractFileInfo,
+    /// Execution count of each executable line
+    lines: BTreeMap<u32, u64>,
+    /// Execution count of each executed expression
+    expressions: HashMap<u64, u64>,
+}
+
+impl ContractCoverage {
+    fn lines_hit(&self) -> u64 {
+        self.lines.values().filter(|count| **count > 0).count() as u64
+    }
+}
+
+/// How many of the executable lines of the contracts in a report were executed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CoverageSummary {
+    pub lines_hit: u64,
+    pub lines_found: u64,
+}
+
+impl CoverageSummary {
+    /// The percentage of executable lines that were executed.  100 if there are none.
+    pub fn percent(&self) -> f64 {
+        if self.lines_found == 0 {
+            return 100.0;
+        }
+        self.lines_hit as f64 * 100.0 / self.lines_found as f64
+    }
 }
 
 impl CoverageReporter {
     pub fn new() -> CoverageReporter {
         CoverageReporter {
             executed_lines: HashMap::new(),
+            executed_expressions: HashMap::new(),
         }
     }
 
-    #[cfg(not(feature = "developer-mode"))]
     pub fn report_eval(
         &mut self,
-        _expr: &SymbolicExpression,
-        _contract: &QualifiedContractIdentifier,
+        expr: &SymbolicExpression,
+        contract: &QualifiedContractIdentifier,
     ) {
+        *self
+            .executed_expressions
+            .entry(contract.clone())
+            .or_default()
+            .entry(expr.id)
+            .or_insert(0) += 1;
+        self.report_line(expr, contract);
+    }
+
+    #[cfg(not(feature = "developer-mode"))]
+    fn report_line(&mut self, _expr: &SymbolicExpression, _contract: &QualifiedContractIdentifier) {
     }
 
     #[cfg(feature = "developer-mode")]
-    pub fn report_eval(
-        &mut self,
-        expr: &SymbolicExpression,
-        contract: &QualifiedContractIdentifier,
-    ) {
+    fn report_line(&mut self, expr: &SymbolicExpression, contract: &QualifiedContractIdentifier) {
         if expr.match_list().is_some() {
             // don't count the whole list expression: wait until we've eval'ed the
             //   list components
@@ -84,8 +135,20 @@ impl CoverageReporter {
 
             coverage.insert(contract.to_string(), executed_lines);
         }
+        let mut expressions = HashMap::with_capacity(self.executed_expressions.len());
+        for (contract, execution_map) in self.executed_expressions.iter() {
+            let mut executed_expressions = execution_map
+                .iter()
+                .map(|(id, count)| (*id, *count))
+                .collect::<Vec<_>>();
+            executed_expressions.sort_by_key(|f| f.0);
+            expressions.insert(contract.to_string(), executed_expressions);
+        }
 
-        let out = CoverageFileInfo { coverage };
+        let out = CoverageFileInfo {
+            coverage,
+            expressions,
+        };
         if let Err(e) = serde_json::to_writer(f, &out) {
             error!(
                 "Failed to serialize JSON to coverage file {}: {}",
@@ -98,12 +161,15 @@ impl CoverageReporter {
         Ok(())
     }
 
-    fn executable_lines(exprs: &[SymbolicExpression]) -> Vec<u32> {
-        let mut lines = vec![];
-        let mut lines_seen = HashSet::new();
-        for expression in exprs.iter() {
-            let mut frontier = vec![expression];
-            while let Some(cur_expr) = frontier.pop() {
+    /// The executable atoms of `ast` that have a span, with their spans, identified by the
+    ///  expression whose evaluation covers them: the atom itself, or the application if the atom
+    ///  names the function applied (which is not evaluated on its own).  List expressions are
+    ///  covered through their children.
+    fn executable_expressions(ast: &ContractAST) -> Vec<(u64, Span)> {
+        let mut expressions = vec![];
+        for expression in ast.expressions.iter() {
+            let mut frontier = vec![(expression, expression.id)];
+            while let Some((cur_expr, covered_by)) = frontier.pop() {
                 // handle defines: the `define-` atom is non executable, and neither are any of the type arguments,
                 //  but the bodies of functions, the value of a constant, initial values for variables, and the
                 //  max supply of FTs
@@ -111,25 +177,25 @@ impl CoverageReporter {
                 {
                     match define_expr {
                         DefineFunctionsParsed::Constant { name: _, value } => {
-                            frontier.push(value);
+                            frontier.push((value, value.id));
                         }
                         DefineFunctionsParsed::PrivateFunction { signature: _, body }
                         | DefineFunctionsParsed::PublicFunction { signature: _, body }
                         | DefineFunctionsParsed::ReadOnlyFunction { signature: _, body } => {
-                            frontier.push(body);
+                            frontier.push((body, body.id));
                         }
                         DefineFunctionsParsed::BoundedFungibleToken {
                             name: _,
                             max_supply,
                         } => {
-                            frontier.push(max_supply);
+                            frontier.push((max_supply, max_supply.id));
                         }
                         DefineFunctionsParsed::PersistedVariable {
                             name: _,
                             data_type: _,
                             initial,
                         } => {
-                            frontier.push(initial);
+                            frontier.push((initial, initial.id));
                         }
                         DefineFunctionsParsed::NonFungibleToken { .. } => {}
                         DefineFunctionsParsed::UnboundedFungibleToken { .. } => {}
@@ -144,17 +210,29 @@ impl CoverageReporter {
 
                 if let Some(children) = cur_expr.match_list() {
                     // don't count list expressions as a whole, just their children
-                    frontier.extend(children);
-                } else {
-                    let line = cur_expr.span().start_line;
-                    if !lines_seen.contains(&line) {
-                        lines_seen.insert(line);
-                        lines.push(line);
+                    for (index, child) in children.iter().enumerate() {
+                        if index == 0 && child.match_atom().is_some() {
+                            frontier.push((child, cur_expr.id));
+                        } else {
+                            frontier.push((child, child.id));
+                        }
                     }
+                } else if let Some(span) = ast.source_map.get(&cur_expr.id) {
+                    expressions.push((covered_by, span.clone()));
                 }
             }
         }
 
+        expressions.sort_by(|a, b| a.1.cmp(&b.1));
+        expressions
+    }
+
+    fn executable_lines(expressions: &[(u64, Span)]) -> Vec<u32> {
+        let lines: HashSet<_> = expressions
+            .iter()
+            .map(|(_, span)| span.start_line)
+            .collect();
+        let mut lines: Vec<_> = lines.into_iter().collect();
         lines.sort();
         lines
     }
@@ -162,17 +240,19 @@ impl CoverageReporter {
     pub fn register_src_file<P: AsRef<std::path::Path> + Copy>(
         contract: &QualifiedContractIdentifier,
         src_file_name: &str,
-        ast: &[SymbolicExpression],
+        ast: &ContractAST,
         filename: P,
     ) -> std::io::Result<()> {
         let f = File::create(filename)?;
 
-        let executable_lines = CoverageReporter::executable_lines(ast);
+        let executable_expressions = CoverageReporter::executable_expressions(ast);
+        let executable_lines = CoverageReporter::executable_lines(&executable_expressions);
 
         let json = ContractFileInfo {
             contract: contract.to_string(),
             src_file: src_file_name.to_string(),
             executable_lines,
+            executable_expressions,
         };
 
         if let Err(e) = serde_json::to_writer(f, &json) {
@@ -186,43 +266,214 @@ impl CoverageReporter {
         Ok(())
     }
 
-    pub fn produce_lcov<P: AsRef<std::path::Path>>(
-        out_filename: &str,
+    /// Sum the coverage files' counts for each registered contract
+    fn load_coverage<P: AsRef<std::path::Path>>(
         register_files: &[P],
         coverage_files: &[P],
-    ) -> std::io::Result<()> {
-        let mut out = File::create(out_filename)?;
+    ) -> std::io::Result<Vec<ContractCoverage>> {
+        let mut coverages = Vec::with_capacity(coverage_files.len());
+        for coverage_filename in coverage_files.iter() {
+            let cov_reader = File::open(coverage_filename)?;
+            let coverage: CoverageFileInfo = serde_json::from_reader(cov_reader)?;
+            coverages.push(coverage);
+        }
 
+        let mut contracts = Vec::with_capacity(register_files.len());
         for contract_filename in register_files.iter() {
             let reader = File::open(contract_filename)?;
             let info: ContractFileInfo = serde_json::from_reader(reader)?;
-            let mut summed_coverage = BTreeMap::new();
-            for coverage_filename in coverage_files.iter() {
-                let cov_reader = File::open(coverage_filename)?;
-                let coverage: CoverageFileInfo = serde_json::from_reader(cov_reader)?;
+            let mut summed_lines = BTreeMap::new();
+            let mut expressions = HashMap::new();
+            for coverage in coverages.iter() {
                 if let Some(contract_coverage) = coverage.coverage.get(&info.contract) {
                     for (line, count) in contract_coverage.iter() {
-                        if let Some(line_count) = summed_coverage.get_mut(line) {
-                            *line_count += *count;
-                        } else {
-                            summed_coverage.insert(*line, *count);
-                        }
+                        *summed_lines.entry(*line).or_insert(0) += *count;
+                    }
+                }
+                if let Some(contract_coverage) = coverage.expressions.get(&info.contract) {
+                    for (id, count) in contract_coverage.iter() {
+                        *expressions.entry(*id).or_insert(0) += *count;
                     }
                 }
             }
-            writeln!(out, "TN:{}", &info.contract)?;
-            writeln!(out, "SF:{}", &info.src_file)?;
-            for line in info.executable_lines.iter() {
-                let count = summed_coverage.get(line).cloned().unwrap_or(0);
+
+            // lines are only recorded with the `developer-mode` feature, so derive them from
+            //  the expressions if the contract was registered with its expressions
+            let lines = if info.executable_expressions.is_empty() {
+                info.executable_lines
+                    .iter()
+                    .map(|line| (*line, summed_lines.get(line).cloned().unwrap_or(0)))
+                    .collect()
+            } else {
+                let mut lines = BTreeMap::new();
+                for (id, span) in info.executable_expressions.iter() {
+                    *lines.entry(span.start_line).or_insert(0) +=
+                        expressions.get(id).cloned().unwrap_or(0);
+                }
+                lines
+            };
+            contracts.push(ContractCoverage {
+                info,
+                lines,
+                expressions,
+            });
+        }
+        Ok(contracts)
+    }
+
+    pub fn produce_lcov<P: AsRef<std::path::Path>>(
+        out_filename: &str,
+        register_files: &[P],
+        coverage_files: &[P],
+    ) -> std::io::Result<CoverageSummary> {
+        let mut out = File::create(out_filename)?;
+        let mut summary = CoverageSummary::default();
+
+        for contract in CoverageReporter::load_coverage(register_files, coverage_files)? {
+            writeln!(out, "TN:{}", &contract.info.contract)?;
+            writeln!(out, "SF:{}", &contract.info.src_file)?;
+            for (line, count) in contract.lines.iter() {
                 writeln!(out, "DA:{},{}", line, count)?;
             }
-            writeln!(out, "LH:{}", summed_coverage.len())?;
-            writeln!(out, "LF:{}", &info.executable_lines.len())?;
+            writeln!(out, "LH:{}", contract.lines_hit())?;
+            writeln!(out, "LF:{}", contract.lines.len())?;
             writeln!(out, "end_of_record")?;
+
+            summary.lines_hit += contract.lines_hit();
+            summary.lines_found += contract.lines.len() as u64;
         }
 
-        Ok(())
+        Ok(summary)
+    }
+
+    /// Write an HTML report to `out_folder`: an `index.html` summary, and a page per contract
+    ///  with its source, where executed lines are green, lines that were not executed are
+    ///  red, and expressions that were not executed are underlined.
+    pub fn produce_html<P: AsRef<std::path::Path>>(
+        out_folder: &str,
+        register_files: &[P],
+        coverage_files: &[P],
+    ) -> std::io::Result<CoverageSummary> {
+        fs::create_dir_all(out_folder)?;
+        let mut summary = CoverageSummary::default();
+        let mut index_rows = String::new();
+
+        for contract in CoverageReporter::load_coverage(register_files, coverage_files)? {
+            let source = fs::read_to_string(&contract.info.src_file)?;
+            let page_name = format!("{}.html", contract.info.contract);
+            let mut page = File::create(Path::new(out_folder).join(&page_name))?;
+            write!(
+                page,
+                "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{}</title>{}</head><body>\n<h1>{}</h1>\n<pre>\n",
+                html_escape(&contract.info.contract),
+                HTML_STYLE,
+                html_escape(&contract.info.contract)
+            )?;
+            for (index, line) in source.lines().enumerate() {
+                let line_number = u32::try_from(index + 1).unwrap_or(u32::MAX);
+                let class = match contract.lines.get(&line_number) {
+                    Some(0) => "miss",
+                    Some(_) => "hit",
+                    None => "",
+                };
+                let count = contract
+                    .lines
+                    .get(&line_number)
+                    .map(|count| count.to_string())
+                    .unwrap_or_default();
+                writeln!(
+                    page,
+                    "<span class=\"{}\"><span class=\"count\">{:>6}</span> {:>5}  {}</span>",
+                    class,
+                    count,
+                    line_number,
+                    annotate_line(&contract, line_number, line)
+                )?;
+            }
+            writeln!(page, "</pre></body></html>")?;
+
+            let contract_summary = CoverageSummary {
+                lines_hit: contract.lines_hit(),
+                lines_found: contract.lines.len() as u64,
+            };
+            index_rows.push_str(&format!(
+                "<tr><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}/{}</td><td>{:.1}%</td></tr>\n",
+                html_escape(&page_name),
+                html_escape(&contract.info.contract),
+                html_escape(&contract.info.src_file),
+                contract_summary.lines_hit,
+                contract_summary.lines_found,
+                contract_summary.percent()
+            ));
+            summary.lines_hit += contract_summary.lines_hit;
+            summary.lines_found += contract_summary.lines_found;
+        }
+
+        let mut index = File::create(Path::new(out_folder).join("index.html"))?;
+        write!(
+            index,
+            "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Coverage</title>{}</head><body>\n<h1>Coverage: {}/{} lines ({:.1}%)</h1>\n<table>\n<tr><th>Contract</th><th>Source</th><th>Lines</th><th>Coverage</th></tr>\n{}</table>\n</body></html>\n",
+            HTML_STYLE,
+            summary.lines_hit,
+            summary.lines_found,
+            summary.percent(),
+            index_rows
+        )?;
+
+        Ok(summary)
+    }
+}
+
+const HTML_STYLE: &str = "<style>
+.hit { background-color: #dfd; }
+.miss { background-color: #fdd; }
+.unexecuted { text-decoration: underline wavy red; }
+.count { color: #888; }
+td, th { padding: 0 1em; text-align: left; }
+</style>";
+
+fn html_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escape `line` (line `line_number` of the source) for HTML, marking the expressions on it
+///  that were not executed
+fn annotate_line(contract: &ContractCoverage, line_number: u32, line: &str) -> String {
+    // (start, end) columns of the unexecuted expressions, which are atoms and so don't overlap
+    let unexecuted: Vec<_> = contract
+        .info
+        .executable_expressions
+        .iter()
+        .filter(|(id, span)| {
+            span.start_line == line_number
+                && span.end_line == line_number
+                && !contract.expressions.contains_key(id)
+        })
+        .map(|(_, span)| (span.start_column, span.end_column))
+        .collect();
+
+    let mut annotated = String::with_capacity(line.len());
+    for (index, c) in line.chars().enumerate() {
+        let column = u32::try_from(index + 1).unwrap_or(u32::MAX);
+        if unexecuted.iter().any(|(start, _)| *start == column) {
+            annotated.push_str("<span class=\"unexecuted\">");
+        }
+        annotated.push_str(&html_escape(&c.to_string()));
+        if unexecuted.iter().any(|(_, end)| *end == column) {
+            annotated.push_str("</span>");
+        }
     }
+    annotated
 }
 
 impl EvalHook for CoverageReporter {
@@ -0,0 +1,259 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Process-wide cache of deserialized contract analyses.
+//!
+//! Every contract call type-checks against the callee's analysis, which is stored in the side
+//! store as JSON.  Deserializing the analysis of a large contract (like PoX) is expensive, so
+//! when enabled, this cache keeps the most-recently-used analyses in memory.  An entry is keyed
+//! by the block in which the contract was deployed and its code hash, so it is valid on every
+//! fork that can see it.  Analyses that are still pending, or that were stored in the block
+//! being built, are never cached.
+//!
+//! The cache is disabled (capacity 0) until the node enables it.  `warm_load()` pre-populates
+//! it, so that the first calls after a restart do not pay the deserialization cost.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, PoisonError};
+
+use lazy_static::lazy_static;
+use stacks_common::types::chainstate::StacksBlockId;
+use stacks_common::util::hash::Sha512Trunc256Sum;
+
+use crate::vm::analysis::ContractAnalysis;
+use crate::vm::database::{ClarityBackingStore, RollbackWrapper};
+use crate::vm::types::QualifiedContractIdentifier;
+
+lazy_static! {
+    static ref ANALYSIS_CACHE: Mutex<AnalysisCache> = Mutex::new(AnalysisCache::new(0));
+}
+
+/// Identifies one deployment of a contract
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AnalysisCacheKey {
+    pub contract: QualifiedContractIdentifier,
+    /// The block in which the contract was deployed
+    pub deployed_at: StacksBlockId,
+    pub code_hash: Sha512Trunc256Sum,
+}
+
+/// LRU cache of contract analyses
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalysisCache {
+    /// Maximum number of analyses to keep. The cache is disabled if 0.
+    capacity: usize,
+    entries: HashMap<AnalysisCacheKey, (ContractAnalysis, u64)>,
+    /// Keys, by when they were last used
+    lru: BTreeMap<u64, AnalysisCacheKey>,
+    clock: u64,
+}
+
+impl AnalysisCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            lru: BTreeMap::new(),
+            clock: 0,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Change the capacity, evicting the least-recently-used analyses if it shrinks
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.evict();
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn evict(&mut self) {
+        while self.entries.len() > self.capacity {
+            let Some(oldest) = self.lru.keys().next().cloned() else {
+                break;
+            };
+            if let Some(evicted) = self.lru.remove(&oldest) {
+                self.entries.remove(&evicted);
+            }
+        }
+    }
+
+    pub fn get(&mut self, key: &AnalysisCacheKey) -> Option<ContractAnalysis> {
+        let now = self.tick();
+        let (analysis, last_used) = self.entries.get_mut(key)?;
+        self.lru.remove(last_used);
+        self.lru.insert(now, key.clone());
+        *last_used = now;
+        Some(analysis.clone())
+    }
+
+    pub fn insert(&mut self, key: AnalysisCacheKey, analysis: ContractAnalysis) {
+        if !self.is_enabled() {
+            return;
+        }
+        let now = self.tick();
+        if let Some((_, last_used)) = self.entries.insert(key.clone(), (analysis, now)) {
+            self.lru.remove(&last_used);
+        }
+        self.lru.insert(now, key);
+        self.evict();
+    }
+
+    /// The (distinct) contracts whose analyses were most recently used, most recent first
+    pub fn recently_used(&self, limit: usize) -> Vec<QualifiedContractIdentifier> {
+        let mut contracts = vec![];
+        for key in self.lru.values().rev() {
+            if contracts.len() >= limit {
+                break;
+            }
+            if !contracts.contains(&key.contract) {
+                contracts.push(key.contract.clone());
+            }
+        }
+        contracts
+    }
+}
+
+fn with_cache<F, R>(f: F) -> R
+where
+    F: FnOnce(&mut AnalysisCache) -> R,
+{
+    // the cache is always left consistent, so a panic elsewhere can't corrupt it
+    let mut cache = ANALYSIS_CACHE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    f(&mut cache)
+}
+
+/// Enable (or disable, if 0) the process-wide analysis cache
+pub fn set_capacity(capacity: usize) {
+    with_cache(|cache| cache.set_capacity(capacity))
+}
+
+pub fn is_enabled() -> bool {
+    with_cache(|cache| cache.is_enabled())
+}
+
+pub fn get(key: &AnalysisCacheKey) -> Option<ContractAnalysis> {
+    with_cache(|cache| cache.get(key))
+}
+
+pub fn insert(key: AnalysisCacheKey, analysis: ContractAnalysis) {
+    with_cache(|cache| cache.insert(key, analysis))
+}
+
+/// The contracts whose analyses were most recently used, most recent first
+pub fn recently_used_contracts(limit: usize) -> Vec<QualifiedContractIdentifier> {
+    with_cache(|cache| cache.recently_used(limit))
+}
+
+/// Load the analyses of `contracts` from `store` into the process-wide cache.  Contracts that
+/// don't exist in `store` are skipped.  Returns how many analyses were loaded.
+pub fn warm_load(
+    store: &mut dyn ClarityBackingStore,
+    contracts: &[QualifiedContractIdentifier],
+) -> usize {
+    if !is_enabled() {
+        return 0;
+    }
+    let mut wrapper = RollbackWrapper::new(store);
+    wrapper.nest();
+    let mut loaded = 0;
+    for contract in contracts {
+        match wrapper.get_contract_analysis(contract) {
+            Ok(Some(_)) => loaded += 1,
+            Ok(None) => {}
+            Err(e) => {
+                warn!("Failed to warm-load contract analysis"; "contract" => %contract, "error" => ?e);
+            }
+        }
+    }
+    // nothing was written
+    let _ = wrapper.rollback();
+    loaded
+}
+
+#[cfg(test)]
+mod tests {
+    use stacks_common::types::StacksEpochId;
+
+    use super::*;
+    use crate::vm::costs::LimitedCostTracker;
+    use crate::vm::ClarityVersion;
+
+    fn key(name: &str, block: u8) -> AnalysisCacheKey {
+        AnalysisCacheKey {
+            contract: QualifiedContractIdentifier::local(name).unwrap(),
+            deployed_at: StacksBlockId([block; 32]),
+            code_hash: Sha512Trunc256Sum([0; 32]),
+        }
+    }
+
+    fn analysis(name: &str) -> ContractAnalysis {
+        ContractAnalysis::new(
+            QualifiedContractIdentifier::local(name).unwrap(),
+            vec![],
+            LimitedCostTracker::new_free(),
+            StacksEpochId::Epoch21,
+            ClarityVersion::Clarity2,
+        )
+    }
+
+    #[test]
+    fn test_analysis_cache() {
+        // disabled by default
+        let mut cache = AnalysisCache::new(0);
+        cache.insert(key("a", 1), analysis("a"));
+        assert!(cache.is_empty());
+
+        let mut cache = AnalysisCache::new(2);
+        cache.insert(key("a", 1), analysis("a"));
+        cache.insert(key("b", 1), analysis("b"));
+        assert_eq!(cache.get(&key("a", 1)), Some(analysis("a")));
+        // a deployment in another block is a different entry
+        assert_eq!(cache.get(&key("a", 2)), None);
+
+        // "b" is the least-recently used
+        cache.insert(key("c", 1), analysis("c"));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&key("b", 1)), None);
+        assert_eq!(
+            cache.recently_used(10),
+            vec![
+                QualifiedContractIdentifier::local("c").unwrap(),
+                QualifiedContractIdentifier::local("a").unwrap(),
+            ]
+        );
+        assert_eq!(cache.recently_used(1).len(), 1);
+
+        cache.set_capacity(1);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get(&key("c", 1)), Some(analysis("c")));
+    }
+}
@@ -0,0 +1,335 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Per-block memoization of read-only function calls.
+//!
+//! The transactions of a block often call the same read-only functions with the same arguments
+//! (balances, oracle prices, pool parameters, ...).  When the store a block is processed against
+//! keeps a `ReadOnlyCallCache` (see `ClarityBackingStore::get_read_only_call_cache()`), each
+//! such call records the keys it read, the cost it was charged and the memory it used.  A later
+//! call of the same function with the same arguments and principals, none of whose keys have
+//! been written since, returns the recorded result and charges the recorded cost instead of
+//! evaluating the function again.  The outcome is exactly that of evaluating it, so the cache
+//! only changes how long a block takes to process.
+//!
+//! `RollbackWrapper` reports reads and writes to the cache.  A key's generation is bumped when
+//! it is written, and when one of its writes is rolled back; an entry is valid while every key
+//! it read is still at the generation it read.  Only keys that some call has read are tracked.
+
+use hashbrown::HashMap;
+use stacks_common::util::hash::Sha512Trunc256Sum;
+
+use crate::vm::costs::ExecutionCost;
+use crate::vm::representations::ClarityName;
+use crate::vm::types::{PrincipalData, QualifiedContractIdentifier};
+use crate::vm::Value;
+
+/// Most calls kept at once.  When full, the cache is emptied.
+pub const READ_ONLY_CALL_CACHE_MAX_ENTRIES: usize = 4096;
+/// Calls that read more keys than this are not memoized
+pub const READ_ONLY_CALL_CACHE_MAX_READS: usize = 256;
+
+/// A key read by a memoized call
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum ReadKey {
+    Data(String),
+    Metadata(QualifiedContractIdentifier, String),
+}
+
+/// Identifies a call: everything besides the state it reads that its outcome depends on
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReadOnlyCallKey {
+    pub contract: QualifiedContractIdentifier,
+    pub function: ClarityName,
+    /// Hash of the serializations of the arguments
+    pub args_hash: Sha512Trunc256Sum,
+    pub sender: Option<PrincipalData>,
+    pub caller: Option<PrincipalData>,
+    pub sponsor: Option<PrincipalData>,
+}
+
+/// What a call returned and used
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadOnlyCallResult {
+    pub value: Value,
+    /// The cost charged by the call
+    pub cost: ExecutionCost,
+    /// The memory in use after the call, less that in use before it
+    pub memory: u64,
+    /// The most memory in use during the call, less that in use before it
+    pub peak_memory: u64,
+}
+
+struct CacheEntry {
+    result: ReadOnlyCallResult,
+    /// The generation of each key the call read
+    reads: HashMap<ReadKey, u64>,
+}
+
+#[derive(Default)]
+pub struct ReadOnlyCallCache {
+    data_generations: HashMap<String, u64>,
+    metadata_generations: HashMap<(QualifiedContractIdentifier, String), u64>,
+    entries: HashMap<ReadOnlyCallKey, CacheEntry>,
+    /// The reads of the calls being evaluated, innermost last.  `None` once a call has read too
+    ///  many keys to be memoized.
+    recordings: Vec<Option<HashMap<ReadKey, u64>>>,
+    hits: u64,
+    misses: u64,
+}
+
+impl ReadOnlyCallCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// How many lookups found a valid entry
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// How many lookups did not
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    fn generation(&self, key: &ReadKey) -> Option<u64> {
+        match key {
+            ReadKey::Data(key) => self.data_generations.get(key).copied(),
+            ReadKey::Metadata(contract, key) => self
+                .metadata_generations
+                .get(&(contract.clone(), key.clone()))
+                .copied(),
+        }
+    }
+
+    fn record_read(&mut self, key: ReadKey) {
+        let generation = match &key {
+            ReadKey::Data(key) => *self.data_generations.entry(key.clone()).or_insert(0),
+            ReadKey::Metadata(contract, key) => *self
+                .metadata_generations
+                .entry((contract.clone(), key.clone()))
+                .or_insert(0),
+        };
+        let Some(recording) = self.recordings.last_mut() else {
+            return;
+        };
+        if let Some(reads) = recording {
+            reads.entry(key).or_insert(generation);
+            if reads.len() > READ_ONLY_CALL_CACHE_MAX_READS {
+                *recording = None;
+            }
+        }
+    }
+
+    fn is_recording(&self) -> bool {
+        matches!(self.recordings.last(), Some(Some(_)))
+    }
+
+    /// `key` was read from the data store
+    pub fn note_data_read(&mut self, key: &str) {
+        if self.is_recording() {
+            self.record_read(ReadKey::Data(key.to_string()));
+        }
+    }
+
+    /// `contract`'s metadata `key` was read
+    pub fn note_metadata_read(&mut self, contract: &QualifiedContractIdentifier, key: &str) {
+        if self.is_recording() {
+            self.record_read(ReadKey::Metadata(contract.clone(), key.to_string()));
+        }
+    }
+
+    /// `key` was written in the data store, or one of its writes was rolled back
+    pub fn note_data_write(&mut self, key: &str) {
+        if let Some(generation) = self.data_generations.get_mut(key) {
+            *generation += 1;
+        }
+    }
+
+    /// `contract`'s metadata `key` was written, or one of its writes was rolled back.  This also
+    ///  drops the calls of `contract`'s functions: metadata is only written when a contract is
+    ///  deployed, and a rolled-back deployment may be followed by another one of other code.
+    pub fn note_metadata_write(&mut self, contract: &QualifiedContractIdentifier, key: &str) {
+        if let Some(generation) = self
+            .metadata_generations
+            .get_mut(&(contract.clone(), key.to_string()))
+        {
+            *generation += 1;
+        }
+        self.entries.retain(|call, _| call.contract != *contract);
+    }
+
+    /// The recorded outcome of `call`, if none of the keys it read have been written since.  The
+    ///  call's reads count as reads of the calls being evaluated.
+    pub fn lookup(&mut self, call: &ReadOnlyCallKey) -> Option<ReadOnlyCallResult> {
+        let valid = match self.entries.get(call) {
+            Some(entry) => entry
+                .reads
+                .iter()
+                .all(|(key, generation)| self.generation(key) == Some(*generation)),
+            None => {
+                self.misses += 1;
+                return None;
+            }
+        };
+        if !valid {
+            self.entries.remove(call);
+            self.misses += 1;
+            return None;
+        }
+        self.hits += 1;
+        let entry = self.entries.get(call)?;
+        let result = entry.result.clone();
+        if let Some(recording) = self.recordings.last_mut() {
+            if let Some(reads) = recording {
+                for (key, generation) in entry.reads.iter() {
+                    reads.entry(key.clone()).or_insert(*generation);
+                }
+                if reads.len() > READ_ONLY_CALL_CACHE_MAX_READS {
+                    *recording = None;
+                }
+            }
+        }
+        Some(result)
+    }
+
+    /// Start recording the reads of a call.  Each call to this must be matched by one to
+    ///  `end_call()`.
+    pub fn begin_call(&mut self) {
+        self.recordings.push(Some(HashMap::new()));
+    }
+
+    /// Stop recording the reads of `call`, and memoize its outcome if it is given.  Its reads
+    ///  also count as reads of the enclosing call, if any.
+    pub fn end_call(&mut self, call: ReadOnlyCallKey, result: Option<ReadOnlyCallResult>) {
+        let Some(reads) = self.recordings.pop() else {
+            return;
+        };
+        if let Some(parent) = self.recordings.last_mut() {
+            match (parent.as_mut(), reads.as_ref()) {
+                (Some(parent_reads), Some(reads)) => {
+                    for (key, generation) in reads.iter() {
+                        parent_reads.entry(key.clone()).or_insert(*generation);
+                    }
+                    if parent_reads.len() > READ_ONLY_CALL_CACHE_MAX_READS {
+                        *parent = None;
+                    }
+                }
+                (Some(_), None) => *parent = None,
+                (None, _) => {}
+            }
+        }
+        let (Some(result), Some(reads)) = (result, reads) else {
+            return;
+        };
+        if self.entries.len() >= READ_ONLY_CALL_CACHE_MAX_ENTRIES {
+            // the generations recorded by calls still being evaluated must stay valid
+            if !self.recordings.is_empty() {
+                return;
+            }
+            self.entries.clear();
+            self.data_generations.clear();
+            self.metadata_generations.clear();
+        }
+        self.entries.insert(call, CacheEntry { result, reads });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(function: &str) -> ReadOnlyCallKey {
+        ReadOnlyCallKey {
+            contract: QualifiedContractIdentifier::local("oracle").unwrap(),
+            function: function.into(),
+            args_hash: Sha512Trunc256Sum([0; 32]),
+            sender: None,
+            caller: None,
+            sponsor: None,
+        }
+    }
+
+    fn result(value: u128) -> ReadOnlyCallResult {
+        ReadOnlyCallResult {
+            value: Value::UInt(value),
+            cost: ExecutionCost::runtime(10),
+            memory: 0,
+            peak_memory: 8,
+        }
+    }
+
+    #[test]
+    fn test_read_only_call_cache_invalidation() {
+        let mut cache = ReadOnlyCallCache::new();
+        assert_eq!(cache.lookup(&call("price")), None);
+
+        cache.begin_call();
+        cache.note_data_read("price-key");
+        cache.end_call(call("price"), Some(result(1)));
+        assert_eq!(cache.lookup(&call("price")), Some(result(1)));
+
+        // writes to keys the call didn't read don't matter
+        cache.note_data_write("other-key");
+        assert_eq!(cache.lookup(&call("price")), Some(result(1)));
+
+        cache.note_data_write("price-key");
+        assert_eq!(cache.lookup(&call("price")), None);
+        assert!(cache.is_empty());
+        assert_eq!((cache.hits(), cache.misses()), (2, 2));
+
+        // a nested call's reads are reads of the enclosing call, even when memoized
+        cache.begin_call();
+        cache.note_data_read("price-key");
+        cache.end_call(call("price"), Some(result(2)));
+        cache.begin_call();
+        assert_eq!(cache.lookup(&call("price")), Some(result(2)));
+        cache.end_call(call("twap"), Some(result(3)));
+        cache.note_data_write("price-key");
+        assert_eq!(cache.lookup(&call("twap")), None);
+
+        // redeploying the contract drops its calls
+        cache.begin_call();
+        cache.end_call(call("constant"), Some(result(4)));
+        assert_eq!(cache.lookup(&call("constant")), Some(result(4)));
+        cache.note_metadata_write(
+            &QualifiedContractIdentifier::local("oracle").unwrap(),
+            "abi",
+        );
+        assert_eq!(cache.lookup(&call("constant")), None);
+    }
+
+    #[test]
+    fn test_read_only_call_cache_read_limit() {
+        let mut cache = ReadOnlyCallCache::new();
+        cache.begin_call();
+        cache.begin_call();
+        for i in 0..=READ_ONLY_CALL_CACHE_MAX_READS {
+            cache.note_data_read(&format!("key-{}", i));
+        }
+        cache.end_call(call("inner"), Some(result(1)));
+        cache.end_call(call("outer"), Some(result(2)));
+        assert!(cache.is_empty());
+    }
+}
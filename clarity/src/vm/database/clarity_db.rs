@@ -14,6 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::BTreeSet;
+
 use serde_json;
 use stacks_common::address::AddressHashMode;
 use stacks_common::consts::{
@@ -30,7 +32,7 @@ use stacks_common::util::hash::{to_hex, Hash160, Sha256Sum, Sha512Trunc256Sum};
 
 use super::clarity_store::SpecialCaseHandler;
 use super::key_value_wrapper::ValueResult;
-use crate::vm::analysis::{AnalysisDatabase, ContractAnalysis};
+use crate::vm::analysis::ContractAnalysis;
 use crate::vm::ast::ASTRules;
 use crate::vm::contracts::Contract;
 use crate::vm::costs::{CostOverflowingMath, ExecutionCost};
@@ -39,23 +41,27 @@ use crate::vm::database::structures::{
     DataVariableMetadata, FungibleTokenMetadata, NonFungibleTokenMetadata, STXBalance,
     STXBalanceSnapshot, SimmedBlock,
 };
-use crate::vm::database::{ClarityBackingStore, RollbackWrapper};
+use crate::vm::database::{
+    ClarityBackingStore, ClarityStoreKey, ReadMode, ReadOnlyCallCache, RollbackWrapper,
+};
 use crate::vm::errors::{
     CheckErrors, Error, IncomparableError, InterpreterError, InterpreterResult as Result,
     RuntimeErrorType,
 };
-use crate::vm::representations::ClarityName;
+use crate::vm::optimizer;
+use crate::vm::representations::{ClarityName, Span};
 use crate::vm::types::serialization::{SerializationError, NONE_SERIALIZATION_LEN};
 use crate::vm::types::{
     byte_len_of_serialization, OptionalData, PrincipalData, QualifiedContractIdentifier,
-    SequenceData, StandardPrincipalData, TupleData, TupleTypeSignature, TypeSignature, Value, NONE,
+    SequenceData, StandardPrincipalData, TraitIdentifier, TupleData, TupleTypeSignature,
+    TypeSignature, Value, ValueLimits, NONE,
 };
 
 pub const STORE_CONTRACT_SRC_INTERFACE: bool = true;
-const TENURE_HEIGHT_KEY: &str = "_stx-data::tenure_height";
 
 pub type StacksEpoch = GenericStacksEpoch<ExecutionCost>;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
 pub enum StoreType {
     DataMap = 0x00,
@@ -465,6 +471,62 @@ impl<'a> ClarityDatabase<'a> {
         self.store.get_data::<T>(key)
     }
 
+    /// Charge the write of `key` (with a `value_size`-byte value) to `contract_identifier`'s
+    ///  storage quota and data quota, if the store enforces either
+    fn charge_storage(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+        key: &str,
+        value_size: u64,
+    ) -> Result<()> {
+        let bytes = u64::try_from(key.len())
+            .unwrap_or(u64::MAX)
+            .saturating_add(value_size);
+        self.store.charge_storage(contract_identifier, bytes)?;
+        self.add_contract_data_size(contract_identifier, bytes)
+    }
+
+    /// The number of bytes of data `contract_identifier` has stored so far: the size of its
+    ///  constants, plus every write to its data vars, maps and tokens.  This is only tracked if
+    ///  the store enforces a contract data quota.
+    pub fn get_contract_data_usage(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+    ) -> Result<u64> {
+        let key = ClarityStoreKey::ContractDataSize(contract_identifier).to_string();
+        Ok(self.get_data(&key)?.unwrap_or(0))
+    }
+
+    /// Add `bytes` to the data stored by `contract_identifier`, if the store enforces a contract
+    ///  data quota (see `ClarityBackingStore::get_contract_data_quota()`).  Fails with
+    ///  `StorageQuotaExceeded` if this would take the contract over the quota.
+    ///
+    /// Unlike the contract's size and data size, the running total is kept in the data store
+    ///  rather than with the contract's metadata: metadata can only be read back from the block
+    ///  that deployed the contract, and the total has to follow forks and rollbacks.
+    pub fn add_contract_data_size(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+        bytes: u64,
+    ) -> Result<()> {
+        let Some(quota) = self.store.get_contract_data_quota() else {
+            return Ok(());
+        };
+        let used = self
+            .get_contract_data_usage(contract_identifier)?
+            .saturating_add(bytes);
+        if used > quota {
+            return Err(RuntimeErrorType::StorageQuotaExceeded(
+                contract_identifier.clone(),
+                used,
+                quota,
+            )
+            .into());
+        }
+        let key = ClarityStoreKey::ContractDataSize(contract_identifier).to_string();
+        self.put_data(&key, &used)
+    }
+
     pub fn put_value(&mut self, key: &str, value: Value, epoch: &StacksEpochId) -> Result<()> {
         self.put_value_with_size(key, value, epoch)?;
         Ok(())
@@ -522,6 +584,19 @@ impl<'a> ClarityDatabase<'a> {
         self.store.get_data_with_proof(key)
     }
 
+    /// Fetch data, along with its Merkle proof if `mode` is `ReadMode::WithProof`.
+    /// Block processing never needs proofs, so it should use `ReadMode::Plain` (or `get_data`).
+    pub fn get_data_with_mode<T>(
+        &mut self,
+        key: &str,
+        mode: ReadMode,
+    ) -> Result<Option<(T, Option<Vec<u8>>)>>
+    where
+        T: ClarityDeserializable<T>,
+    {
+        self.store.get_data_with_mode(key, mode)
+    }
+
     pub fn make_key_for_trip(
         contract_identifier: &QualifiedContractIdentifier,
         data: StoreType,
@@ -531,11 +606,11 @@ impl<'a> ClarityDatabase<'a> {
     }
 
     pub fn make_metadata_key(data: StoreType, var_name: &str) -> String {
-        format!("vm-metadata::{}::{}", data as u8, var_name)
-    }
-
-    fn clarity_state_epoch_key() -> &'static str {
-        "vm-epoch::epoch-version"
+        ClarityStoreKey::Metadata {
+            data,
+            name: var_name,
+        }
+        .to_string()
     }
 
     pub fn make_key_for_quad(
@@ -667,16 +742,30 @@ impl<'a> ClarityDatabase<'a> {
         &mut self,
         contract_identifier: &QualifiedContractIdentifier,
     ) -> Result<Option<ContractAnalysis>> {
-        let x_opt = self
-            .store
-            .get_metadata(contract_identifier, AnalysisDatabase::storage_key())
-            // treat NoSuchContract error thrown by get_metadata as an Option::None --
-            //    the analysis will propagate that as a CheckError anyways.
-            .ok();
-        match x_opt.flatten() {
-            None => Ok(None),
-            Some(x) => ContractAnalysis::deserialize(&x).map(|out| Some(out)),
-        }
+        self.store.get_contract_analysis(contract_identifier)
+    }
+
+    /// The span of source that expression `id` of a contract was parsed from, according to the
+    /// source map of the contract's stored analysis
+    pub fn get_expression_span(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+        id: u64,
+    ) -> Result<Option<Span>> {
+        Ok(self
+            .load_contract_analysis(contract_identifier)?
+            .and_then(|analysis| analysis.get_span(id).cloned()))
+    }
+
+    /// The traits that a contract's stored analysis records it as implementing (see
+    /// `AnalysisDatabase::get_implemented_traits()`), or `None` if no analysis is stored for it
+    pub fn get_implemented_traits(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+    ) -> Result<Option<BTreeSet<TraitIdentifier>>> {
+        Ok(self
+            .load_contract_analysis(contract_identifier)?
+            .map(|analysis| analysis.implemented_traits))
     }
 
     pub fn get_contract_size(
@@ -704,7 +793,8 @@ impl<'a> ClarityDatabase<'a> {
         Ok(data_size + contract_size)
     }
 
-    /// used for adding the memory usage of `define-constant` variables.
+    /// used for adding the memory usage of `define-constant` variables.  This also counts
+    ///  towards the contract's data quota, if the store enforces one.
     pub fn set_contract_data_size(
         &mut self,
         contract_identifier: &QualifiedContractIdentifier,
@@ -722,7 +812,7 @@ impl<'a> ClarityDatabase<'a> {
 
         let key = ClarityDatabase::make_metadata_key(StoreType::Contract, "contract-data-size");
         self.insert_metadata(contract_identifier, &key, &data_size)?;
-        Ok(())
+        self.add_contract_data_size(contract_identifier, data_size)
     }
 
     pub fn insert_contract(
@@ -749,12 +839,16 @@ impl<'a> ClarityDatabase<'a> {
             .ok_or_else(|| InterpreterError::Expect(
                 "Failed to read non-consensus contract metadata, even though contract exists in MARF."
                 .into()))?;
-        data.canonicalize_types(&self.get_clarity_epoch_version()?);
+        let epoch = self.get_clarity_epoch_version()?;
+        data.canonicalize_types(&epoch);
+        if self.store.optimize_contracts() {
+            optimizer::optimize_contract(&mut data.contract_context, &epoch);
+        }
         Ok(data)
     }
 
-    pub fn ustx_liquid_supply_key() -> &'static str {
-        "_stx-data::ustx_liquid_supply"
+    pub fn ustx_liquid_supply_key() -> String {
+        ClarityStoreKey::UstxLiquidSupply.to_string()
     }
 
     /// Returns the epoch version currently applied in the stored Clarity state.
@@ -762,7 +856,7 @@ impl<'a> ClarityDatabase<'a> {
     /// The instantiation of subsequent epochs may bump up the epoch version in the clarity DB if
     /// Clarity is updated in that epoch.
     pub fn get_clarity_epoch_version(&mut self) -> Result<StacksEpochId> {
-        let out = match self.get_data(Self::clarity_state_epoch_key())? {
+        let out = match self.get_data(&ClarityStoreKey::ClarityEpoch.to_string())? {
             Some(x) => u32::try_into(x).map_err(|_| {
                 InterpreterError::Expect("Bad Clarity epoch version in stored Clarity state".into())
             })?,
@@ -773,14 +867,14 @@ impl<'a> ClarityDatabase<'a> {
 
     /// Should be called _after_ all of the epoch's initialization has been invoked
     pub fn set_clarity_epoch_version(&mut self, epoch: StacksEpochId) -> Result<()> {
-        self.put_data(Self::clarity_state_epoch_key(), &(epoch as u32))
+        self.put_data(&ClarityStoreKey::ClarityEpoch.to_string(), &(epoch as u32))
     }
 
     /// Returns the _current_ total liquid ustx
     pub fn get_total_liquid_ustx(&mut self) -> Result<u128> {
         Ok(self
             .get_value(
-                ClarityDatabase::ustx_liquid_supply_key(),
+                &ClarityDatabase::ustx_liquid_supply_key(),
                 &TypeSignature::UIntType,
                 &StacksEpochId::latest(),
             )
@@ -796,7 +890,7 @@ impl<'a> ClarityDatabase<'a> {
 
     fn set_ustx_liquid_supply(&mut self, set_to: u128) -> Result<()> {
         self.put_value(
-            ClarityDatabase::ustx_liquid_supply_key(),
+            &ClarityDatabase::ustx_liquid_supply_key(),
             Value::UInt(set_to),
             // okay to pin epoch, because ustx_liquid_supply does not need to sanitize
             &StacksEpochId::Epoch21,
@@ -834,7 +928,7 @@ impl<'a> ClarityDatabase<'a> {
             return Ok(self.get_current_block_height());
         }
 
-        self.get_data(TENURE_HEIGHT_KEY)?
+        self.get_data(&ClarityStoreKey::TenureHeight.to_string())?
             .ok_or_else(|| {
                 InterpreterError::Expect("No tenure height in stored Clarity state".into()).into()
             })
@@ -855,7 +949,7 @@ impl<'a> ClarityDatabase<'a> {
                 "Setting tenure height in Clarity state is not supported before epoch 3.0".into(),
             )));
         }
-        self.put_data(TENURE_HEIGHT_KEY, &height)
+        self.put_data(&ClarityStoreKey::TenureHeight.to_string(), &height)
     }
 
     pub fn destroy(self) -> RollbackWrapper<'a> {
@@ -1230,11 +1324,11 @@ impl<'a> ClarityDatabase<'a> {
 
 impl<'a> ClarityDatabase<'a> {
     pub fn make_microblock_pubkey_height_key(pubkey_hash: &Hash160) -> String {
-        format!("microblock-pubkey-hash::{}", pubkey_hash)
+        ClarityStoreKey::MicroblockPubkeyHash(pubkey_hash).to_string()
     }
 
     pub fn make_microblock_poison_key(height: u32) -> String {
-        format!("microblock-poison::{}", height)
+        ClarityStoreKey::MicroblockPoison(height).to_string()
     }
 
     pub fn insert_microblock_pubkey_hash_height(
@@ -1251,6 +1345,16 @@ impl<'a> ClarityDatabase<'a> {
         self.store.get_cc_special_cases_handler()
     }
 
+    pub fn get_value_limits(&self) -> ValueLimits {
+        self.store.get_value_limits()
+    }
+
+    /// The store's cache of read-only function calls, if it keeps one and reads are not
+    ///  time-shifted.  See `ReadOnlyCallCache`.
+    pub fn get_read_only_call_cache(&mut self) -> Option<&mut ReadOnlyCallCache> {
+        self.store.get_read_only_call_cache()
+    }
+
     pub fn insert_microblock_poison(
         &mut self,
         height: u32,
@@ -1423,13 +1527,14 @@ impl<'a> ClarityDatabase<'a> {
             );
         }
 
-        let key = ClarityDatabase::make_key_for_trip(
-            contract_identifier,
-            StoreType::Variable,
-            variable_name,
-        );
+        let key = ClarityStoreKey::Var {
+            contract: contract_identifier,
+            name: variable_name,
+        }
+        .to_string();
 
         let size = self.put_value_with_size(&key, value, epoch)?;
+        self.charge_storage(contract_identifier, &key, size)?;
 
         Ok(ValueResult {
             value: Value::Bool(true),
@@ -1454,11 +1559,11 @@ impl<'a> ClarityDatabase<'a> {
         variable_descriptor: &DataVariableMetadata,
         epoch: &StacksEpochId,
     ) -> Result<Value> {
-        let key = ClarityDatabase::make_key_for_trip(
-            contract_identifier,
-            StoreType::Variable,
-            variable_name,
-        );
+        let key = ClarityStoreKey::Var {
+            contract: contract_identifier,
+            name: variable_name,
+        }
+        .to_string();
 
         let result = self.get_value(&key, &variable_descriptor.value_type, epoch)?;
 
@@ -1477,11 +1582,11 @@ impl<'a> ClarityDatabase<'a> {
         variable_descriptor: &DataVariableMetadata,
         epoch: &StacksEpochId,
     ) -> Result<ValueResult> {
-        let key = ClarityDatabase::make_key_for_trip(
-            contract_identifier,
-            StoreType::Variable,
-            variable_name,
-        );
+        let key = ClarityStoreKey::Var {
+            contract: contract_identifier,
+            name: variable_name,
+        }
+        .to_string();
 
         let result = self.get_value(&key, &variable_descriptor.value_type, epoch)?;
 
@@ -1543,12 +1648,12 @@ impl<'a> ClarityDatabase<'a> {
         map_name: &str,
         key_value_serialized: &str,
     ) -> String {
-        ClarityDatabase::make_key_for_quad(
-            contract_identifier,
-            StoreType::DataMap,
-            map_name,
-            key_value_serialized,
-        )
+        ClarityStoreKey::MapEntry {
+            contract: contract_identifier,
+            map: map_name,
+            serialized_key: key_value_serialized,
+        }
+        .to_string()
     }
 
     pub fn fetch_entry_unknown_descriptor(
@@ -1764,12 +1869,12 @@ impl<'a> ClarityDatabase<'a> {
 
         let key_serialized = key_value.serialize_to_hex()?;
         let key_serialized_byte_len = byte_len_of_serialization(&key_serialized);
-        let key = ClarityDatabase::make_key_for_quad(
-            contract_identifier,
-            StoreType::DataMap,
-            map_name,
-            &key_serialized,
-        );
+        let key = ClarityStoreKey::MapEntry {
+            contract: contract_identifier,
+            map: map_name,
+            serialized_key: &key_serialized,
+        }
+        .to_string();
         let stored_type = TypeSignature::new_option(map_descriptor.value_type.clone())?;
 
         if return_if_exists && self.data_map_entry_exists(&key, &stored_type, epoch)? {
@@ -1781,6 +1886,7 @@ impl<'a> ClarityDatabase<'a> {
 
         let placed_value = Value::some(value)?;
         let placed_size = self.put_value_with_size(&key, placed_value, epoch)?;
+        self.charge_storage(contract_identifier, &key, placed_size)?;
 
         Ok(ValueResult {
             value: Value::Bool(true),
@@ -1813,12 +1919,12 @@ impl<'a> ClarityDatabase<'a> {
 
         let key_serialized = key_value.serialize_to_hex()?;
         let key_serialized_byte_len = byte_len_of_serialization(&key_serialized);
-        let key = ClarityDatabase::make_key_for_quad(
-            contract_identifier,
-            StoreType::DataMap,
-            map_name,
-            &key_serialized,
-        );
+        let key = ClarityStoreKey::MapEntry {
+            contract: contract_identifier,
+            map: map_name,
+            serialized_key: &key_serialized,
+        }
+        .to_string();
         let stored_type = TypeSignature::new_option(map_descriptor.value_type.clone())?;
         if !self.data_map_entry_exists(&key, &stored_type, epoch)? {
             return Ok(ValueResult {
@@ -1857,11 +1963,11 @@ impl<'a> ClarityDatabase<'a> {
         self.insert_metadata(contract_identifier, &key, &data)?;
 
         // total supply _is_ included in the consensus hash
-        let supply_key = ClarityDatabase::make_key_for_trip(
-            contract_identifier,
-            StoreType::CirculatingSupply,
-            token_name,
-        );
+        let supply_key = ClarityStoreKey::FtSupply {
+            contract: contract_identifier,
+            token: token_name,
+        }
+        .to_string();
         self.put_data(&supply_key, &(0_u128))?;
 
         Ok(data)
@@ -1911,11 +2017,11 @@ impl<'a> ClarityDatabase<'a> {
         amount: u128,
         descriptor: &FungibleTokenMetadata,
     ) -> Result<()> {
-        let key = ClarityDatabase::make_key_for_trip(
-            contract_identifier,
-            StoreType::CirculatingSupply,
-            token_name,
-        );
+        let key = ClarityStoreKey::FtSupply {
+            contract: contract_identifier,
+            token: token_name,
+        }
+        .to_string();
         let current_supply: u128 = self.get_data(&key)?.ok_or_else(|| {
             InterpreterError::Expect("ERROR: Clarity VM failed to track token supply.".into())
         })?;
@@ -1939,11 +2045,11 @@ impl<'a> ClarityDatabase<'a> {
         token_name: &str,
         amount: u128,
     ) -> Result<()> {
-        let key = ClarityDatabase::make_key_for_trip(
-            contract_identifier,
-            StoreType::CirculatingSupply,
-            token_name,
-        );
+        let key = ClarityStoreKey::FtSupply {
+            contract: contract_identifier,
+            token: token_name,
+        }
+        .to_string();
         let current_supply: u128 = self.get_data(&key)?.ok_or_else(|| {
             InterpreterError::Expect("ERROR: Clarity VM failed to track token supply.".into())
         })?;
@@ -1968,12 +2074,12 @@ impl<'a> ClarityDatabase<'a> {
             self.load_ft(contract_identifier, token_name)?;
         }
 
-        let key = ClarityDatabase::make_key_for_quad(
-            contract_identifier,
-            StoreType::FungibleToken,
-            token_name,
-            &principal.serialize(),
-        );
+        let key = ClarityStoreKey::FtBalance {
+            contract: contract_identifier,
+            token: token_name,
+            owner: principal,
+        }
+        .to_string();
 
         let result = self.get_data(&key)?;
         match result {
@@ -1989,13 +2095,14 @@ impl<'a> ClarityDatabase<'a> {
         principal: &PrincipalData,
         balance: u128,
     ) -> Result<()> {
-        let key = ClarityDatabase::make_key_for_quad(
-            contract_identifier,
-            StoreType::FungibleToken,
-            token_name,
-            &principal.serialize(),
-        );
-        self.put_data(&key, &balance)
+        let key = ClarityStoreKey::FtBalance {
+            contract: contract_identifier,
+            token: token_name,
+            owner: principal,
+        }
+        .to_string();
+        let size = self.put_data_with_size(&key, &balance)?;
+        self.charge_storage(contract_identifier, &key, size)
     }
 
     pub fn get_ft_supply(
@@ -2003,11 +2110,11 @@ impl<'a> ClarityDatabase<'a> {
         contract_identifier: &QualifiedContractIdentifier,
         token_name: &str,
     ) -> Result<u128> {
-        let key = ClarityDatabase::make_key_for_trip(
-            contract_identifier,
-            StoreType::CirculatingSupply,
-            token_name,
-        );
+        let key = ClarityStoreKey::FtSupply {
+            contract: contract_identifier,
+            token: token_name,
+        }
+        .to_string();
         let supply = self.get_data(&key)?.ok_or_else(|| {
             InterpreterError::Expect("ERROR: Clarity VM failed to track token supply.".into())
         })?;
@@ -2025,12 +2132,12 @@ impl<'a> ClarityDatabase<'a> {
             return Err(CheckErrors::TypeValueError(key_type.clone(), (*asset).clone()).into());
         }
 
-        let key = ClarityDatabase::make_key_for_quad(
-            contract_identifier,
-            StoreType::NonFungibleToken,
-            asset_name,
-            &asset.serialize_to_hex()?,
-        );
+        let key = ClarityStoreKey::NftOwner {
+            contract: contract_identifier,
+            asset: asset_name,
+            serialized_asset: &asset.serialize_to_hex()?,
+        }
+        .to_string();
 
         let epoch = self.get_clarity_epoch_version()?;
         let value: Option<ValueResult> = self.get_value(
@@ -2074,15 +2181,16 @@ impl<'a> ClarityDatabase<'a> {
             return Err(CheckErrors::TypeValueError(key_type.clone(), (*asset).clone()).into());
         }
 
-        let key = ClarityDatabase::make_key_for_quad(
-            contract_identifier,
-            StoreType::NonFungibleToken,
-            asset_name,
-            &asset.serialize_to_hex()?,
-        );
+        let key = ClarityStoreKey::NftOwner {
+            contract: contract_identifier,
+            asset: asset_name,
+            serialized_asset: &asset.serialize_to_hex()?,
+        }
+        .to_string();
 
         let value = Value::some(Value::Principal(principal.clone()))?;
-        self.put_value(&key, value, epoch)?;
+        let size = self.put_value_with_size(&key, value, epoch)?;
+        self.charge_storage(contract_identifier, &key, size)?;
 
         Ok(())
     }
@@ -2099,12 +2207,12 @@ impl<'a> ClarityDatabase<'a> {
             return Err(CheckErrors::TypeValueError(key_type.clone(), (*asset).clone()).into());
         }
 
-        let key = ClarityDatabase::make_key_for_quad(
-            contract_identifier,
-            StoreType::NonFungibleToken,
-            asset_name,
-            &asset.serialize_to_hex()?,
-        );
+        let key = ClarityStoreKey::NftOwner {
+            contract: contract_identifier,
+            asset: asset_name,
+            serialized_asset: &asset.serialize_to_hex()?,
+        }
+        .to_string();
 
         self.put_value(&key, Value::none(), epoch)?;
         Ok(())
@@ -2113,24 +2221,20 @@ impl<'a> ClarityDatabase<'a> {
 
 // load/store STX token state and account nonces
 impl<'a> ClarityDatabase<'a> {
-    fn make_key_for_account(principal: &PrincipalData, data: StoreType) -> String {
-        format!("vm-account::{}::{}", principal, data as u8)
-    }
-
     pub fn make_key_for_account_balance(principal: &PrincipalData) -> String {
-        ClarityDatabase::make_key_for_account(principal, StoreType::STXBalance)
+        ClarityStoreKey::StxBalance(principal).to_string()
     }
 
     pub fn make_key_for_account_nonce(principal: &PrincipalData) -> String {
-        ClarityDatabase::make_key_for_account(principal, StoreType::Nonce)
+        ClarityStoreKey::Nonce(principal).to_string()
     }
 
     pub fn make_key_for_account_stx_locked(principal: &PrincipalData) -> String {
-        ClarityDatabase::make_key_for_account(principal, StoreType::PoxSTXLockup)
+        ClarityStoreKey::StxLocked(principal).to_string()
     }
 
     pub fn make_key_for_account_unlock_height(principal: &PrincipalData) -> String {
-        ClarityDatabase::make_key_for_account(principal, StoreType::PoxUnlockHeight)
+        ClarityStoreKey::UnlockHeight(principal).to_string()
     }
 
     pub fn get_stx_balance_snapshot<'conn>(
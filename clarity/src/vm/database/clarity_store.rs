@@ -26,15 +26,15 @@ use crate::vm::contexts::GlobalContext;
 #[cfg(feature = "canonical")]
 use crate::vm::database::SqliteConnection;
 use crate::vm::database::{
-    BurnStateDB, ClarityDatabase, ClarityDeserializable, ClaritySerializable, HeadersDB,
-    NULL_BURN_STATE_DB, NULL_HEADER_DB,
+    BurnStateDB, ClarityDatabase, ClarityDeserializable, ClaritySerializable, ClarityStoreKey,
+    HeadersDB, ReadOnlyCallCache, StorageQuotaPolicy, NULL_BURN_STATE_DB, NULL_HEADER_DB,
 };
 use crate::vm::errors::{
     CheckErrors, IncomparableError, InterpreterError, InterpreterResult as Result,
     InterpreterResult, RuntimeErrorType,
 };
 use crate::vm::events::StacksTransactionEvent;
-use crate::vm::types::{PrincipalData, QualifiedContractIdentifier};
+use crate::vm::types::{PrincipalData, QualifiedContractIdentifier, ValueLimits};
 use crate::vm::Value;
 
 pub struct NullBackingStore {}
@@ -56,9 +56,23 @@ pub type SpecialCaseHandler = &'static dyn Fn(
     &Value,
 ) -> Result<()>;
 
+/// Whether a read from a backing store should also produce a Merkle proof of the value.
+/// Producing a proof requires an extra trie traversal, so only callers that serve the
+///  proof to someone else (e.g. the RPC endpoints) should ask for one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadMode {
+    /// Only fetch the value
+    Plain,
+    /// Fetch the value along with the byte representation of its Merkle proof
+    WithProof,
+}
+
 // These functions generally _do not_ return errors, rather, any errors in the underlying storage
 //    will _panic_. The rationale for this is that under no condition should the interpreter
 //    attempt to continue processing in the event of an unexpected storage error.
+//
+// The trait must stay object safe: the databases built on it hold a `&mut dyn ClarityBackingStore`,
+//    so embedders can pick a backend (memory, MARF, scratch workspace, ...) at runtime.
 pub trait ClarityBackingStore {
     /// put K-V data into the committed datastore
     fn put_all_data(&mut self, items: Vec<(String, String)>) -> Result<()>;
@@ -67,6 +81,20 @@ pub trait ClarityBackingStore {
     /// fetch K-V out of the committed datastore, along with the byte representation
     ///  of the Merkle proof for that key-value pair
     fn get_data_with_proof(&mut self, key: &str) -> Result<Option<(String, Vec<u8>)>>;
+    /// fetch K-V out of the committed datastore, along with the byte representation
+    ///  of the Merkle proof for that key-value pair if `mode` is `ReadMode::WithProof`
+    fn get_data_with_mode(
+        &mut self,
+        key: &str,
+        mode: ReadMode,
+    ) -> Result<Option<(String, Option<Vec<u8>>)>> {
+        match mode {
+            ReadMode::Plain => Ok(self.get_data(key)?.map(|value| (value, None))),
+            ReadMode::WithProof => Ok(self
+                .get_data_with_proof(key)?
+                .map(|(value, proof)| (value, Some(proof)))),
+        }
+    }
     fn has_entry(&mut self, key: &str) -> Result<bool> {
         Ok(self.get_data(key)?.is_some())
     }
@@ -94,6 +122,37 @@ pub trait ClarityBackingStore {
         None
     }
 
+    /// The policy limiting how much data each contract may write, if this store enforces one.
+    ///  See `StorageQuotaPolicy`.
+    fn get_storage_quota_policy(&mut self) -> Option<&mut dyn StorageQuotaPolicy> {
+        None
+    }
+
+    /// The limits on values that analyses and executions against this store apply, unless they
+    ///  are on mainnet.  See `ValueLimits`.
+    fn get_value_limits(&self) -> ValueLimits {
+        ValueLimits::CONSENSUS
+    }
+
+    /// The most bytes of data that each contract may store, if this store enforces a limit.
+    ///  See `ClarityDatabase::add_contract_data_size()`.
+    fn get_contract_data_quota(&self) -> Option<u64> {
+        None
+    }
+
+    /// Whether contracts loaded from this store are run through the constant-folding pass.
+    ///  This changes their execution costs, so only local tooling may turn it on.  See
+    ///  `vm::optimizer`.
+    fn optimize_contracts(&self) -> bool {
+        false
+    }
+
+    /// The cache of read-only function calls made against this store, if it keeps one.  See
+    ///  `ReadOnlyCallCache`.
+    fn get_read_only_call_cache(&mut self) -> Option<&mut ReadOnlyCallCache> {
+        None
+    }
+
     /// The contract commitment is the hash of the contract, plus the block height in
     ///   which the contract was initialized.
     fn make_contract_commitment(&mut self, contract_hash: Sha512Trunc256Sum) -> String {
@@ -144,11 +203,11 @@ pub trait ClarityBackingStore {
     }
 }
 
-// TODO: Figure out where this belongs
 pub fn make_contract_hash_key(contract: &QualifiedContractIdentifier) -> String {
-    format!("clarity-contract::{}", contract)
+    ClarityStoreKey::ContractHash(contract).to_string()
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ContractCommitment {
     pub hash: Sha512Trunc256Sum,
     pub block_height: u32,
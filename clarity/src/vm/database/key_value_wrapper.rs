@@ -21,13 +21,17 @@ use stacks_common::types::chainstate::StacksBlockId;
 use stacks_common::types::StacksEpochId;
 use stacks_common::util::hash::Sha512Trunc256Sum;
 
-use super::clarity_store::SpecialCaseHandler;
-use super::{ClarityBackingStore, ClarityDeserializable};
-use crate::vm::database::clarity_store::make_contract_hash_key;
-use crate::vm::errors::{InterpreterError, InterpreterResult};
+use super::analysis_cache::{self, AnalysisCacheKey};
+use super::clarity_store::{ContractCommitment, SpecialCaseHandler};
+use super::{
+    ClarityBackingStore, ClarityDeserializable, ClarityStoreKey, ReadMode, ReadOnlyCallCache,
+};
+use crate::vm::analysis::{AnalysisDatabase, ContractAnalysis};
+use crate::vm::errors::{InterpreterError, InterpreterResult, RuntimeErrorType};
 use crate::vm::types::serialization::SerializationError;
 use crate::vm::types::{
     QualifiedContractIdentifier, SequenceData, SequenceSubtype, TupleData, TypeSignature,
+    ValueLimits,
 };
 use crate::vm::{StacksEpoch, Value};
 
@@ -113,6 +117,8 @@ pub struct ValueResult {
 pub struct RollbackContext {
     edits: Vec<(String, RollbackValueCheck)>,
     metadata_edits: Vec<((QualifiedContractIdentifier, String), RollbackValueCheck)>,
+    /// Bytes written by each contract in this context, if the store enforces a storage quota
+    storage_usage: HashMap<QualifiedContractIdentifier, u64>,
 }
 
 pub struct RollbackWrapper<'a> {
@@ -173,6 +179,7 @@ impl RollbackWrapperPersistedLog {
         self.stack.push(RollbackContext {
             edits: Vec::new(),
             metadata_edits: Vec::new(),
+            storage_usage: HashMap::new(),
         });
     }
 }
@@ -232,13 +239,35 @@ impl<'a> RollbackWrapper<'a> {
         self.store.get_cc_special_cases_handler()
     }
 
+    pub fn get_value_limits(&self) -> ValueLimits {
+        self.store.get_value_limits()
+    }
+
+    pub fn get_contract_data_quota(&self) -> Option<u64> {
+        self.store.get_contract_data_quota()
+    }
+
+    pub fn optimize_contracts(&self) -> bool {
+        self.store.optimize_contracts()
+    }
+
     pub fn nest(&mut self) {
         self.stack.push(RollbackContext {
             edits: Vec::new(),
             metadata_edits: Vec::new(),
+            storage_usage: HashMap::new(),
         });
     }
 
+    /// The store's cache of read-only function calls, if it keeps one.  Calls made while reads
+    ///  are time-shifted (in `(at-block ..)`) see other state, so they can't use it.
+    pub fn get_read_only_call_cache(&mut self) -> Option<&mut ReadOnlyCallCache> {
+        if !self.query_pending_data {
+            return None;
+        }
+        self.store.get_read_only_call_cache()
+    }
+
     // Rollback the child's edits.
     //   this clears all edits from the child's edit queue,
     //     and removes any of those edits from the lookup map.
@@ -252,10 +281,16 @@ impl<'a> RollbackWrapper<'a> {
 
         for (key, value) in last_item.edits.drain(..) {
             rollback_lookup_map(&key, &value, &mut self.lookup_map)?;
+            if let Some(cache) = self.store.get_read_only_call_cache() {
+                cache.note_data_write(&key);
+            }
         }
 
         for (key, value) in last_item.metadata_edits.drain(..) {
             rollback_lookup_map(&key, &value, &mut self.metadata_lookup_map)?;
+            if let Some(cache) = self.store.get_read_only_call_cache() {
+                cache.note_metadata_write(&key.0, &key.1);
+            }
         }
 
         Ok(())
@@ -279,6 +314,10 @@ impl<'a> RollbackWrapper<'a> {
             for (key, value) in last_item.metadata_edits.drain(..) {
                 next_up.metadata_edits.push((key, value));
             }
+            for (contract, bytes) in last_item.storage_usage.drain() {
+                let used = next_up.storage_usage.entry(contract).or_insert(0);
+                *used = used.saturating_add(bytes);
+            }
         } else {
             // stack is empty, committing to the backing store
             let all_edits =
@@ -302,10 +341,54 @@ impl<'a> RollbackWrapper<'a> {
                     ))
                 })?;
             }
+
+            if let Some(policy) = self.store.get_storage_quota_policy() {
+                for (contract, bytes) in last_item.storage_usage.iter() {
+                    policy.record_usage(contract, *bytes);
+                }
+            }
         }
 
         Ok(())
     }
+
+    /// Charge `bytes` written by `contract` against its storage quota, if the store enforces
+    ///  one.  Fails with `StorageQuotaExceeded` (and charges nothing) if this would take the
+    ///  contract over its budget.
+    pub fn charge_storage(
+        &mut self,
+        contract: &QualifiedContractIdentifier,
+        bytes: u64,
+    ) -> InterpreterResult<()> {
+        let Some(policy) = self.store.get_storage_quota_policy() else {
+            return Ok(());
+        };
+        let Some(budget) = policy.budget(contract) else {
+            return Ok(());
+        };
+        let pending = self
+            .stack
+            .iter()
+            .filter_map(|context| context.storage_usage.get(contract))
+            .fold(0u64, |total, bytes| total.saturating_add(*bytes));
+        let used = policy
+            .used(contract)
+            .saturating_add(pending)
+            .saturating_add(bytes);
+        if used > budget {
+            return Err(
+                RuntimeErrorType::StorageQuotaExceeded(contract.clone(), used, budget).into(),
+            );
+        }
+        let current = self.stack.last_mut().ok_or_else(|| {
+            InterpreterError::Expect(
+                "ERROR: Clarity VM attempted PUT on non-nested context.".into(),
+            )
+        })?;
+        let charged = current.storage_usage.entry(contract.clone()).or_insert(0);
+        *charged = charged.saturating_add(bytes);
+        Ok(())
+    }
 }
 
 fn inner_put_data<T>(
@@ -329,6 +412,9 @@ impl<'a> RollbackWrapper<'a> {
             )
         })?;
 
+        if let Some(cache) = self.store.get_read_only_call_cache() {
+            cache.note_data_write(key);
+        }
         Ok(inner_put_data(
             &mut self.lookup_map,
             &mut current.edits,
@@ -369,6 +455,25 @@ impl<'a> RollbackWrapper<'a> {
             .transpose()
     }
 
+    /// Fetch data with `ReadMode::Plain` exactly as `get_data` does, or with
+    ///  `ReadMode::WithProof` exactly as `get_data_with_proof` does. In particular, proofs
+    ///  are only available for values already materialized in the underlying store.
+    pub fn get_data_with_mode<T>(
+        &mut self,
+        key: &str,
+        mode: ReadMode,
+    ) -> InterpreterResult<Option<(T, Option<Vec<u8>>)>>
+    where
+        T: ClarityDeserializable<T>,
+    {
+        match mode {
+            ReadMode::Plain => Ok(self.get_data(key)?.map(|value| (value, None))),
+            ReadMode::WithProof => Ok(self
+                .get_data_with_proof(key)?
+                .map(|(value, proof)| (value, Some(proof)))),
+        }
+    }
+
     pub fn get_data<T>(&mut self, key: &str) -> InterpreterResult<Option<T>>
     where
         T: ClarityDeserializable<T>,
@@ -379,6 +484,9 @@ impl<'a> RollbackWrapper<'a> {
             )
         })?;
 
+        if let Some(cache) = self.get_read_only_call_cache() {
+            cache.note_data_read(key);
+        }
         if self.query_pending_data {
             if let Some(pending_value) = self.lookup_map.get(key).and_then(|x| x.last()) {
                 // if there's pending data and we're querying pending data, return here
@@ -421,6 +529,9 @@ impl<'a> RollbackWrapper<'a> {
             )
         })?;
 
+        if let Some(cache) = self.get_read_only_call_cache() {
+            cache.note_data_read(key);
+        }
         if self.query_pending_data {
             if let Some(x) = self.lookup_map.get(key).and_then(|x| x.last()) {
                 return Ok(Some(Self::deserialize_value(x, expected, epoch)?));
@@ -450,7 +561,7 @@ impl<'a> RollbackWrapper<'a> {
         contract: &QualifiedContractIdentifier,
         content_hash: Sha512Trunc256Sum,
     ) -> InterpreterResult<()> {
-        let key = make_contract_hash_key(contract);
+        let key = ClarityStoreKey::ContractHash(contract).to_string();
         let value = self.store.make_contract_commitment(content_hash);
         self.put_data(&key, &value)
     }
@@ -467,6 +578,9 @@ impl<'a> RollbackWrapper<'a> {
             )
         })?;
 
+        if let Some(cache) = self.store.get_read_only_call_cache() {
+            cache.note_metadata_write(contract, key);
+        }
         let metadata_key = (contract.clone(), key.to_string());
 
         Ok(inner_put_data(
@@ -490,6 +604,9 @@ impl<'a> RollbackWrapper<'a> {
             )
         })?;
 
+        if let Some(cache) = self.get_read_only_call_cache() {
+            cache.note_metadata_read(contract, key);
+        }
         // This is THEORETICALLY a spurious clone, but it's hard to turn something like
         //  (&A, &B) into &(A, B).
         let metadata_key = (contract.clone(), key.to_string());
@@ -544,6 +661,9 @@ impl<'a> RollbackWrapper<'a> {
                 "ERROR: Clarity VM attempted GET on non-nested context.".into(),
             )
         })?;
+        if let Some(cache) = self.get_read_only_call_cache() {
+            cache.note_data_read(key);
+        }
         if self.query_pending_data && self.lookup_map.contains_key(key) {
             Ok(true)
         } else {
@@ -558,4 +678,67 @@ impl<'a> RollbackWrapper<'a> {
     ) -> bool {
         matches!(self.get_metadata(contract, key), Ok(Some(_)))
     }
+
+    /// The commitment to `contract`'s code (its hash, and the height at which it was deployed),
+    ///  including pending deployments.  `None` if the contract doesn't exist.
+    pub fn get_contract_commitment(
+        &mut self,
+        contract: &QualifiedContractIdentifier,
+    ) -> InterpreterResult<Option<ContractCommitment>> {
+        self.get_data(&ClarityStoreKey::ContractHash(contract).to_string())
+    }
+
+    /// The analysis cache key for `contract`, if its analysis can be cached: it must have been
+    ///  stored in a block other than the one currently being built, and not be pending.
+    fn analysis_cache_key(
+        &mut self,
+        contract: &QualifiedContractIdentifier,
+    ) -> Option<AnalysisCacheKey> {
+        if !analysis_cache::is_enabled() {
+            return None;
+        }
+        if self.query_pending_data
+            && self.metadata_lookup_map.contains_key(&(
+                contract.clone(),
+                AnalysisDatabase::storage_key().to_string(),
+            ))
+        {
+            return None;
+        }
+        let (deployed_at, code_hash) = self.store.get_contract_hash(contract).ok()?;
+        if deployed_at == self.store.get_open_chain_tip() {
+            return None;
+        }
+        Some(AnalysisCacheKey {
+            contract: contract.clone(),
+            deployed_at,
+            code_hash,
+        })
+    }
+
+    /// Load `contract`'s analysis (without canonicalizing its types), going through the
+    ///  process-wide analysis cache if it is enabled.
+    pub fn get_contract_analysis(
+        &mut self,
+        contract: &QualifiedContractIdentifier,
+    ) -> InterpreterResult<Option<ContractAnalysis>> {
+        let cache_key = self.analysis_cache_key(contract);
+        if let Some(analysis) = cache_key.as_ref().and_then(analysis_cache::get) {
+            return Ok(Some(analysis));
+        }
+        // treat NoSuchContract error thrown by get_metadata as an Option::None --
+        //    the analysis will propagate that as a CheckError anyways.
+        let Some(serialized) = self
+            .get_metadata(contract, AnalysisDatabase::storage_key())
+            .ok()
+            .flatten()
+        else {
+            return Ok(None);
+        };
+        let analysis = ContractAnalysis::deserialize(&serialized)?;
+        if let Some(cache_key) = cache_key {
+            analysis_cache::insert(cache_key, analysis.clone());
+        }
+        Ok(Some(analysis))
+    }
 }
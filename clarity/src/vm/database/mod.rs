@@ -18,22 +18,33 @@ use hashbrown::HashMap;
 #[cfg(feature = "canonical")]
 pub use sqlite::MemoryBackingStore;
 
+pub use self::call_cache::{ReadOnlyCallCache, ReadOnlyCallKey, ReadOnlyCallResult};
 pub use self::clarity_db::{
     BurnStateDB, ClarityDatabase, HeadersDB, StoreType, NULL_BURN_STATE_DB, NULL_HEADER_DB,
     STORE_CONTRACT_SRC_INTERFACE,
 };
-pub use self::clarity_store::{ClarityBackingStore, SpecialCaseHandler};
+pub use self::clarity_store::{ClarityBackingStore, ReadMode, SpecialCaseHandler};
 pub use self::key_value_wrapper::{RollbackWrapper, RollbackWrapperPersistedLog};
+pub use self::scratch_store::{ScratchStore, StateOverride};
 #[cfg(feature = "canonical")]
-pub use self::sqlite::SqliteConnection;
+pub use self::sqlite::{MaintenancePause, SqliteConnection};
+pub use self::state_diff::ClarityStateDiff;
+pub use self::storage_quota::{StorageQuota, StorageQuotaPolicy};
+pub use self::store_key::ClarityStoreKey;
 pub use self::structures::{
     ClarityDeserializable, ClaritySerializable, DataMapMetadata, DataVariableMetadata,
     FungibleTokenMetadata, NonFungibleTokenMetadata, STXBalance,
 };
 
+pub mod analysis_cache;
+pub mod call_cache;
 pub mod clarity_db;
 pub mod clarity_store;
 mod key_value_wrapper;
+mod scratch_store;
 #[cfg(feature = "canonical")]
 pub mod sqlite;
+mod state_diff;
+mod storage_quota;
+mod store_key;
 mod structures;
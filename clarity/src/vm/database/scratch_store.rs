@@ -0,0 +1,486 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Scratch workspaces over a backing store.
+//!
+//! A `ScratchStore` layers uncommitted writes over another `ClarityBackingStore`, positioned at a
+//! chosen tip.  Transactions evaluated against it see their own writes, but nothing reaches the
+//! underlying store until the workspace is materialized; dropping it discards the writes.  This
+//! lets the miner and the signer's block validation each simulate transactions against their own
+//! tentative tip, using their own (read-only) connections to the same database, without copying
+//! it.
+//!
+//! A `StateOverride` pretends that some state differs from what is stored (an account's balance,
+//! a map entry, ...).  Applied to a scratch workspace, it lets developer tooling evaluate a call
+//! against "what if" state without affecting the chain.
+
+use std::collections::HashMap;
+
+#[cfg(feature = "canonical")]
+use rusqlite::Connection;
+use stacks_common::types::chainstate::StacksBlockId;
+use stacks_common::types::StacksEpochId;
+use stacks_common::util::hash::Sha512Trunc256Sum;
+
+use crate::vm::analysis::AnalysisDatabase;
+use crate::vm::database::clarity_store::ContractCommitment;
+use crate::vm::database::{
+    BurnStateDB, ClarityBackingStore, ClarityDatabase, ClarityDeserializable, ClarityStoreKey,
+    HeadersDB, STXBalance, SpecialCaseHandler, StorageQuotaPolicy,
+};
+use crate::vm::errors::{CheckErrors, InterpreterError, InterpreterResult as Result};
+use crate::vm::representations::ClarityName;
+use crate::vm::types::{PrincipalData, QualifiedContractIdentifier, ValueLimits};
+use crate::vm::Value;
+
+/// Uncommitted writes layered over a backing store
+pub struct ScratchStore<'a> {
+    store: &'a mut dyn ClarityBackingStore,
+    /// The block the workspace was opened at
+    tip: StacksBlockId,
+    /// Whether reads are at `tip` (and so see the scratch writes), rather than time-shifted
+    at_tip: bool,
+    data: HashMap<String, String>,
+    metadata: HashMap<(QualifiedContractIdentifier, String), String>,
+}
+
+impl<'a> ScratchStore<'a> {
+    /// Open a workspace over `store`, at the tip it is currently open at
+    pub fn new(store: &'a mut dyn ClarityBackingStore) -> ScratchStore<'a> {
+        let tip = store.get_open_chain_tip();
+        ScratchStore {
+            store,
+            tip,
+            at_tip: true,
+            data: HashMap::new(),
+            metadata: HashMap::new(),
+        }
+    }
+
+    /// Open a workspace over `store`, with reads serviced from `tip`
+    pub fn at(
+        store: &'a mut dyn ClarityBackingStore,
+        tip: StacksBlockId,
+    ) -> Result<ScratchStore<'a>> {
+        store.set_block_hash(tip.clone())?;
+        Ok(ScratchStore {
+            store,
+            tip,
+            at_tip: true,
+            data: HashMap::new(),
+            metadata: HashMap::new(),
+        })
+    }
+
+    /// The block the workspace was opened at
+    pub fn tip(&self) -> &StacksBlockId {
+        &self.tip
+    }
+
+    /// How many keys (including metadata keys) have been written to the workspace
+    pub fn num_writes(&self) -> usize {
+        self.data.len() + self.metadata.len()
+    }
+
+    /// Drop the workspace's writes, keeping the workspace open
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.metadata.clear();
+    }
+
+    /// Close the workspace, dropping its writes
+    pub fn discard(self) {}
+
+    /// Close the workspace, writing its writes through to the underlying store.  This fails if
+    /// the underlying store is read-only.
+    pub fn materialize(self) -> Result<()> {
+        if !self.at_tip {
+            self.store.set_block_hash(self.tip.clone())?;
+        }
+        let mut data: Vec<_> = self.data.into_iter().collect();
+        data.sort();
+        self.store.put_all_data(data)?;
+        self.store
+            .put_all_metadata(self.metadata.into_iter().collect())
+    }
+
+    pub fn as_clarity_db<'b>(
+        &'b mut self,
+        headers_db: &'b dyn HeadersDB,
+        burn_state_db: &'b dyn BurnStateDB,
+    ) -> ClarityDatabase<'b> {
+        ClarityDatabase::new(self, headers_db, burn_state_db)
+    }
+
+    pub fn as_analysis_db(&mut self) -> AnalysisDatabase<'_> {
+        AnalysisDatabase::new(self)
+    }
+}
+
+/// State to pretend is stored
+#[derive(Debug, Clone, PartialEq)]
+pub enum StateOverride {
+    /// The account holds `amount` unlocked uSTX, and none locked
+    StxBalance {
+        principal: PrincipalData,
+        amount: u128,
+    },
+    /// The account's balance of a fungible token
+    FtBalance {
+        contract: QualifiedContractIdentifier,
+        token: ClarityName,
+        principal: PrincipalData,
+        amount: u128,
+    },
+    /// The value of a data var
+    DataVar {
+        contract: QualifiedContractIdentifier,
+        name: ClarityName,
+        value: Value,
+    },
+    /// A map entry, or its absence if `value` is `None`
+    MapEntry {
+        contract: QualifiedContractIdentifier,
+        map: ClarityName,
+        key: Value,
+        value: Option<Value>,
+    },
+}
+
+impl StateOverride {
+    /// Write the overridden state to `db`.  Fails if the token, var or map is not defined, or
+    ///  if a value is not of its declared type.
+    pub fn apply(&self, db: &mut ClarityDatabase, epoch: &StacksEpochId) -> Result<()> {
+        match self {
+            StateOverride::StxBalance { principal, amount } => db.put_data(
+                &ClarityDatabase::make_key_for_account_balance(principal),
+                &STXBalance::initial(*amount),
+            ),
+            StateOverride::FtBalance {
+                contract,
+                token,
+                principal,
+                amount,
+            } => {
+                db.load_ft(contract, token)?;
+                db.set_ft_balance(contract, token, principal, *amount)
+            }
+            StateOverride::DataVar {
+                contract,
+                name,
+                value,
+            } => {
+                let descriptor = db.load_variable(contract, name)?;
+                db.set_variable(contract, name, value.clone(), &descriptor, epoch)
+                    .map(|_| ())
+            }
+            StateOverride::MapEntry {
+                contract,
+                map,
+                key,
+                value,
+            } => {
+                let descriptor = db.load_map(contract, map)?;
+                match value {
+                    Some(value) => db.set_entry(
+                        contract,
+                        map,
+                        key.clone(),
+                        value.clone(),
+                        &descriptor,
+                        epoch,
+                    ),
+                    None => db.delete_entry(contract, map, key, &descriptor, epoch),
+                }
+                .map(|_| ())
+            }
+        }
+    }
+}
+
+impl ClarityBackingStore for ScratchStore<'_> {
+    fn put_all_data(&mut self, items: Vec<(String, String)>) -> Result<()> {
+        if !self.at_tip {
+            return Err(InterpreterError::Expect(
+                "Cannot write to a time-shifted scratch store".into(),
+            )
+            .into());
+        }
+        self.data.extend(items);
+        Ok(())
+    }
+
+    fn get_data(&mut self, key: &str) -> Result<Option<String>> {
+        if self.at_tip {
+            if let Some(value) = self.data.get(key) {
+                return Ok(Some(value.clone()));
+            }
+        }
+        self.store.get_data(key)
+    }
+
+    /// Values written to the workspace have no Merkle proof yet, so they are returned with an
+    ///  empty one.
+    fn get_data_with_proof(&mut self, key: &str) -> Result<Option<(String, Vec<u8>)>> {
+        if self.at_tip {
+            if let Some(value) = self.data.get(key) {
+                return Ok(Some((value.clone(), vec![])));
+            }
+        }
+        self.store.get_data_with_proof(key)
+    }
+
+    fn set_block_hash(&mut self, bhh: StacksBlockId) -> Result<StacksBlockId> {
+        let at_tip = bhh == self.tip;
+        let prior = self.store.set_block_hash(bhh)?;
+        self.at_tip = at_tip;
+        Ok(prior)
+    }
+
+    fn get_block_at_height(&mut self, height: u32) -> Option<StacksBlockId> {
+        self.store.get_block_at_height(height)
+    }
+
+    fn get_current_block_height(&mut self) -> u32 {
+        self.store.get_current_block_height()
+    }
+
+    fn get_open_chain_tip_height(&mut self) -> u32 {
+        self.store.get_open_chain_tip_height()
+    }
+
+    fn get_open_chain_tip(&mut self) -> StacksBlockId {
+        self.store.get_open_chain_tip()
+    }
+
+    #[cfg(feature = "canonical")]
+    fn get_side_store(&mut self) -> &Connection {
+        self.store.get_side_store()
+    }
+
+    fn get_cc_special_cases_handler(&self) -> Option<SpecialCaseHandler> {
+        self.store.get_cc_special_cases_handler()
+    }
+
+    fn get_storage_quota_policy(&mut self) -> Option<&mut dyn StorageQuotaPolicy> {
+        self.store.get_storage_quota_policy()
+    }
+
+    fn get_value_limits(&self) -> ValueLimits {
+        self.store.get_value_limits()
+    }
+
+    fn get_contract_data_quota(&self) -> Option<u64> {
+        self.store.get_contract_data_quota()
+    }
+
+    fn optimize_contracts(&self) -> bool {
+        self.store.optimize_contracts()
+    }
+
+    fn get_contract_hash(
+        &mut self,
+        contract: &QualifiedContractIdentifier,
+    ) -> Result<(StacksBlockId, Sha512Trunc256Sum)> {
+        let key = ClarityStoreKey::ContractHash(contract).to_string();
+        if !self.at_tip || !self.data.contains_key(&key) {
+            return self.store.get_contract_hash(contract);
+        }
+        // deployed in this workspace
+        let ContractCommitment { hash, block_height } = self
+            .get_data(&key)?
+            .map(|x| ContractCommitment::deserialize(&x))
+            .ok_or_else(|| CheckErrors::NoSuchContract(contract.to_string()))??;
+        let bhh = self.get_block_at_height(block_height).ok_or_else(|| {
+            InterpreterError::Expect(
+                "Should always be able to map from height to block hash when looking up contract information."
+                    .into(),
+            )
+        })?;
+        Ok((bhh, hash))
+    }
+
+    fn insert_metadata(
+        &mut self,
+        contract: &QualifiedContractIdentifier,
+        key: &str,
+        value: &str,
+    ) -> Result<()> {
+        self.metadata
+            .insert((contract.clone(), key.to_string()), value.to_string());
+        Ok(())
+    }
+
+    fn get_metadata(
+        &mut self,
+        contract: &QualifiedContractIdentifier,
+        key: &str,
+    ) -> Result<Option<String>> {
+        if let Some(value) = self.metadata.get(&(contract.clone(), key.to_string())) {
+            return Ok(Some(value.clone()));
+        }
+        self.store.get_metadata(contract, key)
+    }
+
+    fn get_metadata_manual(
+        &mut self,
+        at_height: u32,
+        contract: &QualifiedContractIdentifier,
+        key: &str,
+    ) -> Result<Option<String>> {
+        if at_height >= self.get_open_chain_tip_height() {
+            if let Some(value) = self.metadata.get(&(contract.clone(), key.to_string())) {
+                return Ok(Some(value.clone()));
+            }
+        }
+        self.store.get_metadata_manual(at_height, contract, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::database::{MemoryBackingStore, NULL_BURN_STATE_DB, NULL_HEADER_DB};
+
+    fn put(store: &mut dyn ClarityBackingStore, key: &str, value: &str) {
+        store
+            .put_all_data(vec![(key.to_string(), value.to_string())])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_scratch_store() {
+        let contract = QualifiedContractIdentifier::local("foo").unwrap();
+        let mut store = MemoryBackingStore::new();
+        put(&mut store, "a", "1");
+
+        let mut scratch = ScratchStore::new(&mut store);
+        put(&mut scratch, "a", "2");
+        put(&mut scratch, "b", "3");
+        scratch.insert_metadata(&contract, "m", "4").unwrap();
+        assert_eq!(scratch.get_data("a").unwrap(), Some("2".to_string()));
+        assert_eq!(scratch.get_data("b").unwrap(), Some("3".to_string()));
+        assert_eq!(
+            scratch.get_metadata(&contract, "m").unwrap(),
+            Some("4".to_string())
+        );
+        assert_eq!(scratch.num_writes(), 3);
+        scratch.discard();
+
+        // discarded writes never reach the store
+        assert_eq!(store.get_data("a").unwrap(), Some("1".to_string()));
+        assert_eq!(store.get_data("b").unwrap(), None);
+
+        let mut scratch = ScratchStore::new(&mut store);
+        put(&mut scratch, "b", "3");
+        scratch.materialize().unwrap();
+        assert_eq!(store.get_data("a").unwrap(), Some("1".to_string()));
+        assert_eq!(store.get_data("b").unwrap(), Some("3".to_string()));
+    }
+
+    #[test]
+    fn test_scratch_store_contract() {
+        let mut store = MemoryBackingStore::new();
+        let contract = QualifiedContractIdentifier::local("foo").unwrap();
+
+        // a contract stored in the workspace is visible to it, and only to it
+        let mut scratch = ScratchStore::new(&mut store);
+        {
+            let mut db = scratch.as_clarity_db(&NULL_HEADER_DB, &NULL_BURN_STATE_DB);
+            db.begin();
+            db.insert_contract_hash(&contract, "(define-data-var x int 1)")
+                .unwrap();
+            db.set_metadata(&contract, "m", "4").unwrap();
+            db.commit().unwrap();
+        }
+        assert!(scratch.get_contract_hash(&contract).is_ok());
+        assert_eq!(
+            scratch.get_metadata(&contract, "m").unwrap(),
+            Some("4".to_string())
+        );
+        scratch.discard();
+        assert!(store.get_contract_hash(&contract).is_err());
+    }
+
+    #[test]
+    fn test_boxed_backing_store() {
+        let mut base = MemoryBackingStore::new();
+        put(&mut base, "a", "1");
+
+        for use_scratch in [false, true] {
+            let mut store: Box<dyn ClarityBackingStore + '_> = if use_scratch {
+                Box::new(ScratchStore::new(&mut base))
+            } else {
+                Box::new(MemoryBackingStore::new())
+            };
+            put(store.as_mut(), "b", "2");
+            assert_eq!(store.get_data("b").unwrap(), Some("2".to_string()));
+            // only the workspace sees the base store's data
+            assert_eq!(store.get_data("a").unwrap().is_some(), use_scratch);
+
+            let mut db = ClarityDatabase::new(store.as_mut(), &NULL_HEADER_DB, &NULL_BURN_STATE_DB);
+            db.begin();
+            db.put_data("c", &3_u128).unwrap();
+            assert_eq!(db.get_data::<u128>("c").unwrap(), Some(3));
+            db.commit().unwrap();
+        }
+        assert_eq!(base.get_data("b").unwrap(), None);
+    }
+
+    #[test]
+    fn test_state_override() {
+        let mut store = MemoryBackingStore::new();
+        let principal = PrincipalData::from(QualifiedContractIdentifier::transient().issuer);
+        let epoch = StacksEpochId::latest();
+
+        let mut scratch = ScratchStore::new(&mut store);
+        {
+            let mut db = scratch.as_clarity_db(&NULL_HEADER_DB, &NULL_BURN_STATE_DB);
+            db.begin();
+            StateOverride::StxBalance {
+                principal: principal.clone(),
+                amount: 1000,
+            }
+            .apply(&mut db, &epoch)
+            .unwrap();
+            // there is no such contract
+            assert!(StateOverride::DataVar {
+                contract: QualifiedContractIdentifier::local("foo").unwrap(),
+                name: "x".into(),
+                value: Value::Int(1),
+            }
+            .apply(&mut db, &epoch)
+            .is_err());
+            assert_eq!(
+                db.get_account_stx_balance(&principal)
+                    .unwrap()
+                    .amount_unlocked(),
+                1000
+            );
+            db.commit().unwrap();
+        }
+        scratch.discard();
+
+        let mut db = store.as_clarity_db();
+        db.begin();
+        assert_eq!(
+            db.get_account_stx_balance(&principal)
+                .unwrap()
+                .amount_unlocked(),
+            0
+        );
+        db.roll_back().unwrap();
+    }
+}
@@ -14,6 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 use rusqlite::types::{FromSql, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
 use rusqlite::{
     params, Connection, Error as SqliteError, ErrorCode as SqliteErrorCode, OptionalExtension, Row,
@@ -24,10 +26,10 @@ use stacks_common::types::sqlite::NO_PARAMS;
 use stacks_common::util::db::tx_busy_handler;
 use stacks_common::util::hash::Sha512Trunc256Sum;
 
-use super::clarity_store::{make_contract_hash_key, ContractCommitment};
+use super::clarity_store::ContractCommitment;
 use super::{
-    ClarityBackingStore, ClarityDatabase, ClarityDeserializable, SpecialCaseHandler,
-    NULL_BURN_STATE_DB, NULL_HEADER_DB,
+    ClarityBackingStore, ClarityDatabase, ClarityDeserializable, ClarityStoreKey,
+    ReadOnlyCallCache, SpecialCaseHandler, StorageQuotaPolicy, NULL_BURN_STATE_DB, NULL_HEADER_DB,
 };
 use crate::vm::analysis::{AnalysisDatabase, CheckErrors};
 use crate::vm::contracts::Contract;
@@ -35,7 +37,7 @@ use crate::vm::costs::ExecutionCost;
 use crate::vm::errors::{
     Error, IncomparableError, InterpreterError, InterpreterResult as Result, RuntimeErrorType,
 };
-use crate::vm::types::QualifiedContractIdentifier;
+use crate::vm::types::{QualifiedContractIdentifier, ValueLimits};
 
 const SQL_FAIL_MESSAGE: &str = "PANIC: SQL Failure in Smart Contract VM.";
 
@@ -43,6 +45,21 @@ pub struct SqliteConnection {
     conn: Connection,
 }
 
+/// Number of outstanding `MaintenancePause`s in this process
+static MAINTENANCE_PAUSES: AtomicUsize = AtomicUsize::new(0);
+
+/// Holds off side-store maintenance (see `SqliteConnection::run_maintenance_step`) for as
+///  long as it is alive.
+pub struct MaintenancePause {
+    _private: (),
+}
+
+impl Drop for MaintenancePause {
+    fn drop(&mut self) {
+        MAINTENANCE_PAUSES.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
 fn sqlite_put(conn: &Connection, key: &str, value: &str) -> Result<()> {
     let params = params![key, value];
     match conn.execute("REPLACE INTO data_table (key, value) VALUES (?, ?)", params) {
@@ -84,7 +101,7 @@ pub fn sqlite_get_contract_hash(
     store: &mut dyn ClarityBackingStore,
     contract: &QualifiedContractIdentifier,
 ) -> Result<(StacksBlockId, Sha512Trunc256Sum)> {
-    let key = make_contract_hash_key(contract);
+    let key = ClarityStoreKey::ContractHash(contract).to_string();
     let contract_commitment = store
         .get_data(&key)?
         .map(|x| ContractCommitment::deserialize(&x))
@@ -145,6 +162,11 @@ impl SqliteConnection {
         sqlite_get(conn, key)
     }
 
+    /// The side-store key under which `contract_hash`'s metadata `key` is stored
+    pub fn make_metadata_key(contract_hash: &str, key: &str) -> String {
+        format!("clr-meta::{}::{}", contract_hash, key)
+    }
+
     pub fn insert_metadata(
         conn: &Connection,
         bhh: &StacksBlockId,
@@ -152,7 +174,7 @@ impl SqliteConnection {
         key: &str,
         value: &str,
     ) -> Result<()> {
-        let key = format!("clr-meta::{}::{}", contract_hash, key);
+        let key = Self::make_metadata_key(contract_hash, key);
         let params = params![bhh, key, value];
 
         if let Err(e) = conn.execute(
@@ -204,7 +226,7 @@ impl SqliteConnection {
         contract_hash: &str,
         key: &str,
     ) -> Result<Option<String>> {
-        let key = format!("clr-meta::{}::{}", contract_hash, key);
+        let key = Self::make_metadata_key(contract_hash, key);
         let params = params![bhh, key];
 
         match conn
@@ -228,36 +250,226 @@ impl SqliteConnection {
     }
 }
 
+/// Schema migrations for the Clarity side-store, in order of application. The migration at
+///  index `i` upgrades the side-store from schema version `i` to version `i + 1`. Side-stores
+///  created before the schema was versioned are at version 0; since the version 1 migration
+///  only creates tables that such side-stores already have, they are upgraded in place.
+///
+/// Append new migrations to the end of this list. Never modify an existing one, since nodes
+///  will already have applied it.
+const SIDE_STORE_MIGRATIONS: &[&[&str]] = &[
+    // version 1: the original data and metadata tables
+    &[
+        "CREATE TABLE IF NOT EXISTS data_table
+                      (key TEXT PRIMARY KEY, value TEXT)",
+        "CREATE TABLE IF NOT EXISTS metadata_table
+                      (key TEXT NOT NULL, blockhash TEXT, value TEXT,
+                       UNIQUE (key, blockhash))",
+        "CREATE INDEX IF NOT EXISTS md_blockhashes ON metadata_table(blockhash)",
+    ],
+    // version 2: the height below which a pruned node has discarded state
+    &["CREATE TABLE IF NOT EXISTS pruned_state (height INTEGER NOT NULL)"],
+];
+
+/// The schema version of the Clarity side-store that this code expects
+pub const SIDE_STORE_SCHEMA_VERSION: u32 = SIDE_STORE_MIGRATIONS.len() as u32;
+
+/// The side-store can share its database file with other tables (such as the MARF's), so its
+///  version table has a name that is specific to it.
+const SIDE_STORE_SCHEMA_VERSION_TABLE: &str =
+    "CREATE TABLE IF NOT EXISTS clarity_side_store_schema_version (version INTEGER NOT NULL)";
+
 impl SqliteConnection {
     pub fn initialize_conn(conn: &Connection) -> Result<()> {
         conn.query_row("PRAGMA journal_mode = WAL;", NO_PARAMS, |_row| Ok(()))
             .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?;
 
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS data_table
-                      (key TEXT PRIMARY KEY, value TEXT)",
-            NO_PARAMS,
-        )
-        .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?;
+        Self::migrate(conn)?;
+        Self::check_schema(conn)?;
+
+        Ok(())
+    }
 
+    /// Get the schema version of the side-store. Side-stores that predate schema versioning
+    ///  report version 0.
+    pub fn get_schema_version(conn: &Connection) -> Result<u32> {
+        let has_version_table = conn
+            .query_row(
+                "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?",
+                params!["clarity_side_store_schema_version"],
+                |_row| Ok(()),
+            )
+            .optional()
+            .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?
+            .is_some();
+        if !has_version_table {
+            return Ok(0);
+        }
+
+        let version: Option<u32> = conn
+            .query_row(
+                "SELECT version FROM clarity_side_store_schema_version",
+                NO_PARAMS,
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?;
+        Ok(version.unwrap_or(0))
+    }
+
+    /// Apply, in order, every migration that the side-store has not applied yet. Callers should
+    ///  run this in a transaction, so that a failed migration leaves the side-store untouched.
+    pub fn migrate(conn: &Connection) -> Result<()> {
+        let version = Self::get_schema_version(conn)?;
+        if version > SIDE_STORE_SCHEMA_VERSION {
+            return Err(InterpreterError::DBError(format!(
+                "Clarity side-store has schema version {version}, but only versions up to {SIDE_STORE_SCHEMA_VERSION} are supported"
+            ))
+            .into());
+        }
+        if version == SIDE_STORE_SCHEMA_VERSION {
+            return Ok(());
+        }
+
+        conn.execute(SIDE_STORE_SCHEMA_VERSION_TABLE, NO_PARAMS)
+            .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?;
+
+        for (from_version, migration) in SIDE_STORE_MIGRATIONS
+            .iter()
+            .enumerate()
+            .skip(version as usize)
+        {
+            debug!(
+                "Migrate Clarity side-store from schema {} to schema {}",
+                from_version,
+                from_version + 1
+            );
+            for statement in migration.iter() {
+                conn.execute_batch(statement)
+                    .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?;
+            }
+        }
+
+        conn.execute("DELETE FROM clarity_side_store_schema_version", NO_PARAMS)
+            .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?;
         conn.execute(
-            "CREATE TABLE IF NOT EXISTS metadata_table
-                      (key TEXT NOT NULL, blockhash TEXT, value TEXT,
-                       UNIQUE (key, blockhash))",
-            NO_PARAMS,
+            "INSERT INTO clarity_side_store_schema_version (version) VALUES (?)",
+            params![SIDE_STORE_SCHEMA_VERSION],
         )
         .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?;
 
+        Ok(())
+    }
+
+    /// Hold off side-store maintenance until the returned guard is dropped. Block processing
+    ///  holds one of these, so that maintenance only runs while the node is idle.
+    pub fn pause_maintenance() -> MaintenancePause {
+        MAINTENANCE_PAUSES.fetch_add(1, Ordering::SeqCst);
+        MaintenancePause { _private: () }
+    }
+
+    pub fn is_maintenance_paused() -> bool {
+        MAINTENANCE_PAUSES.load(Ordering::SeqCst) > 0
+    }
+
+    /// Run one bounded step of side-store maintenance: reclaim up to `max_free_pages` free
+    ///  pages, if the database uses incremental auto-vacuum, and refresh the query planner's
+    ///  statistics with a bounded `ANALYZE`. A step that starts is not interrupted by a later
+    ///  pause; `max_free_pages` bounds how long it can hold the database.
+    /// Returns `false` without doing anything if maintenance is paused.
+    pub fn run_maintenance_step(conn: &Connection, max_free_pages: u32) -> Result<bool> {
+        if Self::is_maintenance_paused() {
+            return Ok(false);
+        }
+
+        let auto_vacuum: i64 = conn
+            .query_row("PRAGMA auto_vacuum", NO_PARAMS, |row| row.get(0))
+            .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?;
+        // 2 is INCREMENTAL. Switching an existing database to it needs a full VACUUM, which
+        //  is too disruptive to do here.
+        if auto_vacuum == 2 {
+            conn.execute_batch(&format!("PRAGMA incremental_vacuum({max_free_pages})"))
+                .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?;
+        }
+
+        conn.execute_batch("PRAGMA analysis_limit = 1000; PRAGMA optimize;")
+            .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?;
+
+        Ok(true)
+    }
+
+    /// Get the height below which state has been pruned (see `prune_data_batch()`), if any
+    pub fn get_pruned_height(conn: &Connection) -> Result<Option<u32>> {
+        conn.query_row("SELECT height FROM pruned_state", NO_PARAMS, |row| {
+            row.get(0)
+        })
+        .optional()
+        .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }).into())
+    }
+
+    pub fn set_pruned_height(conn: &Connection, height: u32) -> Result<()> {
+        conn.execute("DELETE FROM pruned_state", NO_PARAMS)
+            .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?;
         conn.execute(
-            "CREATE INDEX IF NOT EXISTS md_blockhashes ON metadata_table(blockhash)",
-            NO_PARAMS,
+            "INSERT INTO pruned_state (height) VALUES (?)",
+            params![height],
         )
         .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?;
+        Ok(())
+    }
 
-        Self::check_schema(conn)?;
+    /// Number of data entries
+    pub fn count_data(conn: &Connection) -> Result<u64> {
+        conn.query_row("SELECT COUNT(*) FROM data_table", NO_PARAMS, |row| {
+            row.get::<_, i64>(0)
+        })
+        .map(|count| u64::try_from(count).unwrap_or(0))
+        .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }).into())
+    }
 
-        Ok(())
+    /// Delete the data entries whose key `is_live` rejects, among up to `limit` entries after row
+    ///  `after_rowid`. Metadata is never deleted, since contracts need it for as long as they
+    ///  exist.
+    /// Returns the number of entries deleted, and the row to continue from, or None if there are
+    ///  no more rows.
+    pub fn prune_data_batch<F: Fn(&str) -> bool>(
+        conn: &Connection,
+        after_rowid: i64,
+        limit: u32,
+        is_live: F,
+    ) -> Result<(u64, Option<i64>)> {
+        let rows = {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT rowid, key FROM data_table WHERE rowid > ?1 ORDER BY rowid LIMIT ?2",
+                )
+                .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?;
+            let rows = stmt
+                .query_map(params![after_rowid, limit], |row| {
+                    Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+                })
+                .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?;
+            rows
+        };
+
+        let last_rowid = rows.last().map(|(rowid, _)| *rowid);
+        let mut stmt = conn
+            .prepare("DELETE FROM data_table WHERE rowid = ?")
+            .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?;
+        let mut deleted = 0;
+        for (rowid, key) in rows.iter() {
+            if is_live(key) {
+                continue;
+            }
+            stmt.execute(params![rowid])
+                .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?;
+            deleted += 1;
+        }
+        Ok((deleted, last_rowid))
     }
+
     pub fn memory() -> Result<Connection> {
         let contract_db = SqliteConnection::inner_open(":memory:")?;
         SqliteConnection::initialize_conn(&contract_db)?;
@@ -268,6 +480,8 @@ impl SqliteConnection {
         SqliteConnection::check_schema(&contract_db)?;
         Ok(contract_db)
     }
+    /// Check that the side-store's tables exist and that it has been migrated to the current
+    ///  schema version.
     pub fn check_schema(conn: &Connection) -> Result<()> {
         let sql = "SELECT sql FROM sqlite_master WHERE name=?";
         let _: String = conn
@@ -276,6 +490,13 @@ impl SqliteConnection {
         let _: String = conn
             .query_row(sql, params!["metadata_table"], |row| row.get(0))
             .map_err(|x| InterpreterError::SqliteError(IncomparableError { err: x }))?;
+        let version = Self::get_schema_version(conn)?;
+        if version != SIDE_STORE_SCHEMA_VERSION {
+            return Err(InterpreterError::DBError(format!(
+                "Clarity side-store has schema version {version}, expected {SIDE_STORE_SCHEMA_VERSION}"
+            ))
+            .into());
+        }
         Ok(())
     }
 
@@ -292,6 +513,11 @@ impl SqliteConnection {
 
 pub struct MemoryBackingStore {
     side_store: Connection,
+    storage_quota: Option<Box<dyn StorageQuotaPolicy>>,
+    value_limits: ValueLimits,
+    contract_data_quota: Option<u64>,
+    optimize_contracts: bool,
+    read_only_call_cache: Option<ReadOnlyCallCache>,
 }
 
 impl Default for MemoryBackingStore {
@@ -305,7 +531,14 @@ impl MemoryBackingStore {
     pub fn new() -> MemoryBackingStore {
         let side_store = SqliteConnection::memory().unwrap();
 
-        let mut memory_marf = MemoryBackingStore { side_store };
+        let mut memory_marf = MemoryBackingStore {
+            side_store,
+            storage_quota: None,
+            value_limits: ValueLimits::CONSENSUS,
+            contract_data_quota: None,
+            optimize_contracts: false,
+            read_only_call_cache: None,
+        };
 
         memory_marf.as_clarity_db().initialize();
 
@@ -319,6 +552,32 @@ impl MemoryBackingStore {
     pub fn as_analysis_db(&mut self) -> AnalysisDatabase {
         AnalysisDatabase::new(self)
     }
+
+    /// Enforce (or stop enforcing) a storage quota on the contracts' writes
+    pub fn set_storage_quota_policy(&mut self, policy: Option<Box<dyn StorageQuotaPolicy>>) {
+        self.storage_quota = policy;
+    }
+
+    /// Analyze and execute contracts against this store with other limits on values
+    pub fn set_value_limits(&mut self, limits: ValueLimits) {
+        self.value_limits = limits;
+    }
+
+    /// Limit (or stop limiting) how much data each contract may store
+    pub fn set_contract_data_quota(&mut self, quota: Option<u64>) {
+        self.contract_data_quota = quota;
+    }
+
+    /// Run (or stop running) the contracts loaded from this store through the constant-folding
+    ///  pass
+    pub fn set_optimize_contracts(&mut self, optimize: bool) {
+        self.optimize_contracts = optimize;
+    }
+
+    /// Memoize (or stop memoizing) the read-only function calls made against this store
+    pub fn set_memoize_read_only_calls(&mut self, memoize: bool) {
+        self.read_only_call_cache = memoize.then(ReadOnlyCallCache::new);
+    }
 }
 
 impl ClarityBackingStore for MemoryBackingStore {
@@ -362,6 +621,28 @@ impl ClarityBackingStore for MemoryBackingStore {
         None
     }
 
+    fn get_storage_quota_policy(&mut self) -> Option<&mut dyn StorageQuotaPolicy> {
+        self.storage_quota
+            .as_deref_mut()
+            .map(|policy| policy as &mut dyn StorageQuotaPolicy)
+    }
+
+    fn get_value_limits(&self) -> ValueLimits {
+        self.value_limits
+    }
+
+    fn get_contract_data_quota(&self) -> Option<u64> {
+        self.contract_data_quota
+    }
+
+    fn optimize_contracts(&self) -> bool {
+        self.optimize_contracts
+    }
+
+    fn get_read_only_call_cache(&mut self) -> Option<&mut ReadOnlyCallCache> {
+        self.read_only_call_cache.as_mut()
+    }
+
     fn put_all_data(&mut self, items: Vec<(String, String)>) -> Result<()> {
         for (key, value) in items.into_iter() {
             SqliteConnection::put(self.get_side_store(), &key, &value)?;
@@ -419,3 +700,108 @@ impl FromSql for ExecutionCost {
         Ok(parsed)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrate_unversioned_side_store() {
+        let conn = SqliteConnection::inner_open(":memory:").unwrap();
+        // a side-store created before the schema was versioned
+        for statement in SIDE_STORE_MIGRATIONS[0].iter() {
+            conn.execute_batch(statement).unwrap();
+        }
+        sqlite_put(&conn, "key", "value").unwrap();
+
+        assert_eq!(SqliteConnection::get_schema_version(&conn).unwrap(), 0);
+        assert!(SqliteConnection::check_schema(&conn).is_err());
+
+        SqliteConnection::initialize_conn(&conn).unwrap();
+        assert_eq!(
+            SqliteConnection::get_schema_version(&conn).unwrap(),
+            SIDE_STORE_SCHEMA_VERSION
+        );
+        SqliteConnection::check_schema(&conn).unwrap();
+        assert_eq!(sqlite_get(&conn, "key").unwrap(), Some("value".into()));
+
+        // migrating again is a no-op
+        SqliteConnection::migrate(&conn).unwrap();
+        assert_eq!(
+            SqliteConnection::get_schema_version(&conn).unwrap(),
+            SIDE_STORE_SCHEMA_VERSION
+        );
+    }
+
+    #[test]
+    fn maintenance_pause() {
+        let conn = SqliteConnection::memory().unwrap();
+        sqlite_put(&conn, "key", "value").unwrap();
+
+        {
+            let _pause = SqliteConnection::pause_maintenance();
+            assert!(SqliteConnection::is_maintenance_paused());
+            assert!(!SqliteConnection::run_maintenance_step(&conn, 100).unwrap());
+        }
+
+        // other tests may hold a pause concurrently
+        if !SqliteConnection::is_maintenance_paused() {
+            assert!(SqliteConnection::run_maintenance_step(&conn, 100).unwrap());
+        }
+        assert_eq!(sqlite_get(&conn, "key").unwrap(), Some("value".into()));
+    }
+
+    #[test]
+    fn prune_data() {
+        let conn = SqliteConnection::memory().unwrap();
+        assert_eq!(SqliteConnection::get_pruned_height(&conn).unwrap(), None);
+        for key in ["a", "b", "c"] {
+            sqlite_put(&conn, key, "value").unwrap();
+        }
+        SqliteConnection::insert_metadata(&conn, &StacksBlockId([1; 32]), "contract", "m", "v")
+            .unwrap();
+
+        assert_eq!(SqliteConnection::count_data(&conn).unwrap(), 3);
+        assert_eq!(
+            SqliteConnection::prune_data_batch(&conn, 0, 2, |key| key == "b").unwrap(),
+            (1, Some(2))
+        );
+        assert_eq!(
+            SqliteConnection::prune_data_batch(&conn, 2, 2, |key| key == "b").unwrap(),
+            (1, Some(3))
+        );
+        assert_eq!(
+            SqliteConnection::prune_data_batch(&conn, 3, 2, |key| key == "b").unwrap(),
+            (0, None)
+        );
+        assert_eq!(SqliteConnection::count_data(&conn).unwrap(), 1);
+        SqliteConnection::set_pruned_height(&conn, 10).unwrap();
+        SqliteConnection::set_pruned_height(&conn, 20).unwrap();
+
+        assert_eq!(sqlite_get(&conn, "a").unwrap(), None);
+        assert_eq!(sqlite_get(&conn, "b").unwrap(), Some("value".into()));
+        assert_eq!(sqlite_get(&conn, "c").unwrap(), None);
+        assert_eq!(
+            SqliteConnection::get_metadata(&conn, &StacksBlockId([1; 32]), "contract", "m")
+                .unwrap(),
+            Some("v".into())
+        );
+        assert_eq!(
+            SqliteConnection::get_pruned_height(&conn).unwrap(),
+            Some(20)
+        );
+    }
+
+    #[test]
+    fn reject_newer_side_store() {
+        let conn = SqliteConnection::memory().unwrap();
+        conn.execute(
+            "UPDATE clarity_side_store_schema_version SET version = ?",
+            params![SIDE_STORE_SCHEMA_VERSION + 1],
+        )
+        .unwrap();
+
+        assert!(SqliteConnection::migrate(&conn).is_err());
+        assert!(SqliteConnection::check_schema(&conn).is_err());
+    }
+}
@@ -0,0 +1,170 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+
+use stacks_common::codec::{read_next, write_next, Error as codec_error, StacksMessageCodec};
+
+/// Every key/value written to a Clarity datastore while it was recording, e.g. while processing
+/// a block. Only the last value written to each key is kept, and keys are ordered, so the same
+/// writes always produce the same diff (and the same encoding) regardless of write order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClarityStateDiff {
+    /// MARF-indexed data, by key
+    pub data: BTreeMap<String, String>,
+    /// Contract metadata, by contract identifier and then by key
+    pub metadata: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl ClarityStateDiff {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_data(&mut self, key: &str, value: &str) {
+        self.data.insert(key.to_string(), value.to_string());
+    }
+
+    pub fn record_metadata(&mut self, contract: &str, key: &str, value: &str) {
+        self.metadata
+            .entry(contract.to_string())
+            .or_default()
+            .insert(key.to_string(), value.to_string());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty() && self.metadata.is_empty()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("FATAL: failed to serialize state diff to JSON")
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+fn write_str<W: Write>(fd: &mut W, s: &str) -> Result<(), codec_error> {
+    write_next(fd, &s.as_bytes().to_vec())
+}
+
+fn read_str<R: Read>(fd: &mut R) -> Result<String, codec_error> {
+    let bytes: Vec<u8> = read_next(fd)?;
+    String::from_utf8(bytes).map_err(|_e| {
+        codec_error::DeserializeError("Failed to parse state diff: invalid utf8".to_string())
+    })
+}
+
+fn write_map<W: Write>(fd: &mut W, map: &BTreeMap<String, String>) -> Result<(), codec_error> {
+    write_next(fd, &(map.len() as u32))?;
+    for (key, value) in map.iter() {
+        write_str(fd, key)?;
+        write_str(fd, value)?;
+    }
+    Ok(())
+}
+
+/// Keys must be strictly increasing, so every diff has exactly one encoding
+fn read_map<R: Read>(fd: &mut R) -> Result<BTreeMap<String, String>, codec_error> {
+    let len: u32 = read_next(fd)?;
+    let mut map = BTreeMap::new();
+    for _ in 0..len {
+        let key = read_str(fd)?;
+        let value = read_str(fd)?;
+        if matches!(map.keys().next_back(), Some(last) if *last >= key) {
+            return Err(codec_error::DeserializeError(
+                "Failed to parse state diff: keys are not in order".to_string(),
+            ));
+        }
+        map.insert(key, value);
+    }
+    Ok(map)
+}
+
+impl StacksMessageCodec for ClarityStateDiff {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), codec_error> {
+        write_map(fd, &self.data)?;
+        write_next(fd, &(self.metadata.len() as u32))?;
+        for (contract, entries) in self.metadata.iter() {
+            write_str(fd, contract)?;
+            write_map(fd, entries)?;
+        }
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<ClarityStateDiff, codec_error> {
+        let data = read_map(fd)?;
+        let len: u32 = read_next(fd)?;
+        let mut metadata = BTreeMap::new();
+        for _ in 0..len {
+            let contract = read_str(fd)?;
+            let entries = read_map(fd)?;
+            if matches!(metadata.keys().next_back(), Some(last) if *last >= contract) {
+                return Err(codec_error::DeserializeError(
+                    "Failed to parse state diff: contracts are not in order".to_string(),
+                ));
+            }
+            metadata.insert(contract, entries);
+        }
+        Ok(ClarityStateDiff { data, metadata })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_diff_is_deterministic() {
+        let mut diff_1 = ClarityStateDiff::new();
+        diff_1.record_data("b", "2");
+        diff_1.record_data("a", "0");
+        diff_1.record_data("a", "1");
+        diff_1.record_metadata("S1G2081040G2081040G2081040G208105NK8PE5.foo", "k", "v");
+
+        let mut diff_2 = ClarityStateDiff::new();
+        diff_2.record_metadata("S1G2081040G2081040G2081040G208105NK8PE5.foo", "k", "v");
+        diff_2.record_data("a", "1");
+        diff_2.record_data("b", "2");
+
+        assert_eq!(diff_1, diff_2);
+        assert_eq!(diff_1.serialize_to_vec(), diff_2.serialize_to_vec());
+        assert_eq!(diff_1.to_json(), diff_2.to_json());
+
+        let bytes = diff_1.serialize_to_vec();
+        assert_eq!(
+            ClarityStateDiff::consensus_deserialize(&mut &bytes[..]).unwrap(),
+            diff_1
+        );
+        assert_eq!(
+            ClarityStateDiff::from_json(&diff_1.to_json()).unwrap(),
+            diff_1
+        );
+    }
+
+    #[test]
+    fn state_diff_rejects_unordered_keys() {
+        let mut bytes = vec![];
+        write_next(&mut bytes, &2u32).unwrap();
+        for key in ["b", "a"] {
+            write_str(&mut bytes, key).unwrap();
+            write_str(&mut bytes, "value").unwrap();
+        }
+        write_next(&mut bytes, &0u32).unwrap();
+        assert!(ClarityStateDiff::consensus_deserialize(&mut &bytes[..]).is_err());
+    }
+}
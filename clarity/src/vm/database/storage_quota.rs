@@ -0,0 +1,211 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Contract-level storage quotas.
+//!
+//! A `ClarityBackingStore` can return a `StorageQuotaPolicy` from `get_storage_quota_policy()`
+//! to limit how much data each contract may write.  Every write of a data var, map entry, token
+//! balance or NFT owner is charged (key and value bytes) to the contract that defines it.  A
+//! write that would take a contract over its budget fails with
+//! `RuntimeErrorType::StorageQuotaExceeded`.
+//!
+//! Usage is counted in bytes written, not bytes stored: overwriting or deleting an entry does
+//! not give back budget.  Pending writes are tracked by the `RollbackWrapper` (so rolled back
+//! writes aren't charged), and reported to the policy with `record_usage()` when they are
+//! flushed to the store.
+//!
+//! A store can instead (or also) return a quota from `get_contract_data_quota()`.  That quota
+//! also counts the size of each contract's constants, and the running total is kept in the data
+//! store (see `ClarityDatabase::add_contract_data_size()`), so that it persists across blocks
+//! and follows forks.  The stacks-node sets it with `node.contract_data_quota`.
+//!
+//! The Stacks chain doesn't use this: it's meant for deployments that embed the Clarity VM.
+
+use std::collections::HashMap;
+
+use crate::vm::types::QualifiedContractIdentifier;
+
+/// Decides how much data each contract may write
+pub trait StorageQuotaPolicy {
+    /// How many bytes `contract` may write in total, or `None` if it's unlimited
+    fn budget(&self, contract: &QualifiedContractIdentifier) -> Option<u64>;
+    /// How many bytes `contract` has written to the store so far
+    fn used(&self, contract: &QualifiedContractIdentifier) -> u64;
+    /// `contract` wrote another `bytes` bytes to the store
+    fn record_usage(&mut self, contract: &QualifiedContractIdentifier, bytes: u64);
+}
+
+/// A `StorageQuotaPolicy` with a default budget and per-contract overrides, which keeps usage
+/// in memory
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StorageQuota {
+    /// Budget of contracts without an override.  `None` means unlimited.
+    default_budget: Option<u64>,
+    budgets: HashMap<QualifiedContractIdentifier, u64>,
+    usage: HashMap<QualifiedContractIdentifier, u64>,
+}
+
+impl StorageQuota {
+    pub fn new(default_budget: Option<u64>) -> Self {
+        Self {
+            default_budget,
+            ..Self::default()
+        }
+    }
+
+    /// Give `contract` its own budget
+    pub fn with_budget(mut self, contract: QualifiedContractIdentifier, budget: u64) -> Self {
+        self.budgets.insert(contract, budget);
+        self
+    }
+}
+
+impl StorageQuotaPolicy for StorageQuota {
+    fn budget(&self, contract: &QualifiedContractIdentifier) -> Option<u64> {
+        self.budgets.get(contract).copied().or(self.default_budget)
+    }
+
+    fn used(&self, contract: &QualifiedContractIdentifier) -> u64 {
+        self.usage.get(contract).copied().unwrap_or(0)
+    }
+
+    fn record_usage(&mut self, contract: &QualifiedContractIdentifier, bytes: u64) {
+        let used = self.usage.entry(contract.clone()).or_insert(0);
+        *used = used.saturating_add(bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stacks_common::types::StacksEpochId;
+
+    use super::*;
+    use crate::vm::ast::ASTRules;
+    use crate::vm::contexts::OwnedEnvironment;
+    use crate::vm::database::MemoryBackingStore;
+    use crate::vm::errors::{Error, RuntimeErrorType};
+    use crate::vm::types::PrincipalData;
+    use crate::vm::{SymbolicExpression, Value};
+
+    #[test]
+    fn test_storage_quota() {
+        let small = QualifiedContractIdentifier::local("small").unwrap();
+        let large = QualifiedContractIdentifier::local("large").unwrap();
+        let contract = "(define-map store uint (buff 100))
+            (define-public (put (k uint)) (ok (map-set store k 0x0102030405060708090a)))";
+
+        let mut store = MemoryBackingStore::new();
+        store.set_storage_quota_policy(Some(Box::new(
+            StorageQuota::new(Some(200)).with_budget(large.clone(), 10_000),
+        )));
+        let mut owned_env = OwnedEnvironment::new(store.as_clarity_db(), StacksEpochId::Epoch21);
+        for contract_id in [&small, &large] {
+            owned_env
+                .initialize_contract(contract_id.clone(), contract, None, ASTRules::PrecheckSize)
+                .unwrap();
+        }
+
+        let sender = PrincipalData::parse("S1G2081040G2081040G2081040G208105NK8PE5").unwrap();
+        let mut put = |contract_id: &QualifiedContractIdentifier, k: u128| {
+            owned_env.execute_transaction(
+                sender.clone(),
+                None,
+                contract_id.clone(),
+                "put",
+                &[SymbolicExpression::atom_value(Value::UInt(k))],
+            )
+        };
+
+        let mut exceeded = None;
+        for k in 0..10 {
+            if let Err(e) = put(&small, k) {
+                exceeded = Some((k, e));
+                break;
+            }
+        }
+        let (k, e) = exceeded.expect("the quota was never exceeded");
+        assert!(k > 0);
+        assert!(matches!(
+            e,
+            Error::Runtime(RuntimeErrorType::StorageQuotaExceeded(contract, used, 200), _)
+                if contract == small && used > 200
+        ));
+
+        // other contracts have their own budget
+        for k in 0..10 {
+            put(&large, k).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_contract_data_quota() {
+        let contract_id = QualifiedContractIdentifier::local("store").unwrap();
+        let contract = "(define-constant greeting \"hello\")
+            (define-map store uint (buff 100))
+            (define-public (put (k uint)) (ok (map-set store k 0x0102030405060708090a)))";
+
+        let mut store = MemoryBackingStore::new();
+        store.set_contract_data_quota(Some(300));
+        let mut owned_env = OwnedEnvironment::new(store.as_clarity_db(), StacksEpochId::Epoch21);
+        owned_env
+            .initialize_contract(contract_id.clone(), contract, None, ASTRules::PrecheckSize)
+            .unwrap();
+
+        let sender = PrincipalData::parse("S1G2081040G2081040G2081040G208105NK8PE5").unwrap();
+        let mut exceeded = None;
+        for k in 0..10 {
+            let result = owned_env.execute_transaction(
+                sender.clone(),
+                None,
+                contract_id.clone(),
+                "put",
+                &[SymbolicExpression::atom_value(Value::UInt(k))],
+            );
+            if let Err(e) = result {
+                exceeded = Some(e);
+                break;
+            }
+        }
+        assert!(matches!(
+            exceeded.expect("the quota was never exceeded"),
+            Error::Runtime(RuntimeErrorType::StorageQuotaExceeded(contract, used, 300), _)
+                if contract == contract_id && used > 300
+        ));
+        drop(owned_env);
+
+        // the failed write wasn't counted
+        let mut db = store.as_clarity_db();
+        db.begin();
+        let used = db.get_contract_data_usage(&contract_id).unwrap();
+        assert!(used > 0 && used <= 300);
+        db.roll_back().unwrap();
+
+        // a contract's constants count towards its quota too
+        store.set_contract_data_quota(Some(4));
+        let mut owned_env = OwnedEnvironment::new(store.as_clarity_db(), StacksEpochId::Epoch21);
+        let err = owned_env
+            .initialize_contract(
+                QualifiedContractIdentifier::local("constants").unwrap(),
+                "(define-constant greeting \"hello\")",
+                None,
+                ASTRules::PrecheckSize,
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Runtime(RuntimeErrorType::StorageQuotaExceeded(..), _)
+        ));
+    }
+}
@@ -0,0 +1,270 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Typed keys for the Clarity data store.
+//!
+//! Every key that the Clarity VM reads or writes through a `ClarityBackingStore` is described by
+//! a `ClarityStoreKey`.  Its `Display` implementation is the canonical string serialization, which
+//! is what ends up in the MARF (or the side store, for metadata keys), so it must never change.
+
+use std::fmt;
+
+use stacks_common::util::hash::Hash160;
+
+use crate::vm::database::{ClaritySerializable, StoreType};
+use crate::vm::types::{PrincipalData, QualifiedContractIdentifier};
+
+/// A key in the Clarity data store
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClarityStoreKey<'a> {
+    /// The commitment to a contract's code hash and the height at which it was instantiated
+    ContractHash(&'a QualifiedContractIdentifier),
+    /// The number of bytes of data a contract has stored, if the store enforces a contract data
+    /// quota
+    ContractDataSize(&'a QualifiedContractIdentifier),
+    /// A `define-data-var` variable
+    Var {
+        contract: &'a QualifiedContractIdentifier,
+        name: &'a str,
+    },
+    /// An entry in a `define-map` map.  `serialized_key` is the hex serialization of the map key.
+    MapEntry {
+        contract: &'a QualifiedContractIdentifier,
+        map: &'a str,
+        serialized_key: &'a str,
+    },
+    /// The circulating supply of a fungible token
+    FtSupply {
+        contract: &'a QualifiedContractIdentifier,
+        token: &'a str,
+    },
+    /// An account's balance of a fungible token
+    FtBalance {
+        contract: &'a QualifiedContractIdentifier,
+        token: &'a str,
+        owner: &'a PrincipalData,
+    },
+    /// The owner of a non-fungible token.  `serialized_asset` is the hex serialization of the
+    /// asset identifier.
+    NftOwner {
+        contract: &'a QualifiedContractIdentifier,
+        asset: &'a str,
+        serialized_asset: &'a str,
+    },
+    /// Contract metadata.  These keys are scoped to a contract by the side store.
+    Metadata { data: StoreType, name: &'a str },
+    /// An account's STX balance
+    StxBalance(&'a PrincipalData),
+    /// An account's nonce
+    Nonce(&'a PrincipalData),
+    /// The amount of an account's STX locked by PoX
+    StxLocked(&'a PrincipalData),
+    /// The burnchain height at which an account's locked STX unlock
+    UnlockHeight(&'a PrincipalData),
+    /// The epoch version of the stored Clarity state
+    ClarityEpoch,
+    /// The total liquid uSTX
+    UstxLiquidSupply,
+    /// The current tenure height
+    TenureHeight,
+    /// The height at which a microblock public key hash was first used
+    MicroblockPubkeyHash(&'a Hash160),
+    /// The microblock poison record reported at a given height
+    MicroblockPoison(u32),
+}
+
+impl fmt::Display for ClarityStoreKey<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClarityStoreKey::ContractHash(contract) => write!(f, "clarity-contract::{contract}"),
+            ClarityStoreKey::ContractDataSize(contract) => {
+                write!(f, "clarity-contract-data-size::{contract}")
+            }
+            ClarityStoreKey::Var { contract, name } => {
+                write!(f, "vm::{contract}::{}::{name}", StoreType::Variable as u8)
+            }
+            ClarityStoreKey::MapEntry {
+                contract,
+                map,
+                serialized_key,
+            } => write!(
+                f,
+                "vm::{contract}::{}::{map}::{serialized_key}",
+                StoreType::DataMap as u8
+            ),
+            ClarityStoreKey::FtSupply { contract, token } => write!(
+                f,
+                "vm::{contract}::{}::{token}",
+                StoreType::CirculatingSupply as u8
+            ),
+            ClarityStoreKey::FtBalance {
+                contract,
+                token,
+                owner,
+            } => write!(
+                f,
+                "vm::{contract}::{}::{token}::{}",
+                StoreType::FungibleToken as u8,
+                owner.serialize()
+            ),
+            ClarityStoreKey::NftOwner {
+                contract,
+                asset,
+                serialized_asset,
+            } => write!(
+                f,
+                "vm::{contract}::{}::{asset}::{serialized_asset}",
+                StoreType::NonFungibleToken as u8
+            ),
+            ClarityStoreKey::Metadata { data, name } => {
+                write!(f, "vm-metadata::{}::{name}", *data as u8)
+            }
+            ClarityStoreKey::StxBalance(principal) => {
+                write!(
+                    f,
+                    "vm-account::{principal}::{}",
+                    StoreType::STXBalance as u8
+                )
+            }
+            ClarityStoreKey::Nonce(principal) => {
+                write!(f, "vm-account::{principal}::{}", StoreType::Nonce as u8)
+            }
+            ClarityStoreKey::StxLocked(principal) => write!(
+                f,
+                "vm-account::{principal}::{}",
+                StoreType::PoxSTXLockup as u8
+            ),
+            ClarityStoreKey::UnlockHeight(principal) => write!(
+                f,
+                "vm-account::{principal}::{}",
+                StoreType::PoxUnlockHeight as u8
+            ),
+            ClarityStoreKey::ClarityEpoch => write!(f, "vm-epoch::epoch-version"),
+            ClarityStoreKey::UstxLiquidSupply => write!(f, "_stx-data::ustx_liquid_supply"),
+            ClarityStoreKey::TenureHeight => write!(f, "_stx-data::tenure_height"),
+            ClarityStoreKey::MicroblockPubkeyHash(pubkey_hash) => {
+                write!(f, "microblock-pubkey-hash::{pubkey_hash}")
+            }
+            ClarityStoreKey::MicroblockPoison(height) => write!(f, "microblock-poison::{height}"),
+        }
+    }
+}
+
+impl From<ClarityStoreKey<'_>> for String {
+    fn from(key: ClarityStoreKey<'_>) -> String {
+        key.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_key_serialization() {
+        let contract =
+            QualifiedContractIdentifier::parse("SP000000000000000000002Q6VF78.foo").unwrap();
+        let principal = PrincipalData::parse("SP000000000000000000002Q6VF78").unwrap();
+        let pubkey_hash = Hash160([0x01; 20]);
+
+        // these strings are consensus-critical
+        let expected = [
+            (
+                ClarityStoreKey::ContractHash(&contract),
+                "clarity-contract::SP000000000000000000002Q6VF78.foo",
+            ),
+            (
+                ClarityStoreKey::ContractDataSize(&contract),
+                "clarity-contract-data-size::SP000000000000000000002Q6VF78.foo",
+            ),
+            (
+                ClarityStoreKey::Var {
+                    contract: &contract,
+                    name: "bar",
+                },
+                "vm::SP000000000000000000002Q6VF78.foo::1::bar",
+            ),
+            (
+                ClarityStoreKey::MapEntry {
+                    contract: &contract,
+                    map: "bar",
+                    serialized_key: "0c",
+                },
+                "vm::SP000000000000000000002Q6VF78.foo::0::bar::0c",
+            ),
+            (
+                ClarityStoreKey::FtSupply {
+                    contract: &contract,
+                    token: "bar",
+                },
+                "vm::SP000000000000000000002Q6VF78.foo::3::bar",
+            ),
+            (
+                ClarityStoreKey::FtBalance {
+                    contract: &contract,
+                    token: "bar",
+                    owner: &principal,
+                },
+                r#"vm::SP000000000000000000002Q6VF78.foo::2::bar::{"Standard":[22,[0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0]]}"#,
+            ),
+            (
+                ClarityStoreKey::NftOwner {
+                    contract: &contract,
+                    asset: "bar",
+                    serialized_asset: "01",
+                },
+                "vm::SP000000000000000000002Q6VF78.foo::4::bar::01",
+            ),
+            (
+                ClarityStoreKey::Metadata {
+                    data: StoreType::Contract,
+                    name: "contract-size",
+                },
+                "vm-metadata::9::contract-size",
+            ),
+            (
+                ClarityStoreKey::StxBalance(&principal),
+                "vm-account::SP000000000000000000002Q6VF78::19",
+            ),
+            (
+                ClarityStoreKey::Nonce(&principal),
+                "vm-account::SP000000000000000000002Q6VF78::18",
+            ),
+            (
+                ClarityStoreKey::StxLocked(&principal),
+                "vm-account::SP000000000000000000002Q6VF78::20",
+            ),
+            (
+                ClarityStoreKey::UnlockHeight(&principal),
+                "vm-account::SP000000000000000000002Q6VF78::21",
+            ),
+            (ClarityStoreKey::ClarityEpoch, "vm-epoch::epoch-version"),
+            (
+                ClarityStoreKey::UstxLiquidSupply,
+                "_stx-data::ustx_liquid_supply",
+            ),
+            (ClarityStoreKey::TenureHeight, "_stx-data::tenure_height"),
+            (
+                ClarityStoreKey::MicroblockPubkeyHash(&pubkey_hash),
+                "microblock-pubkey-hash::0101010101010101010101010101010101010101",
+            ),
+            (ClarityStoreKey::MicroblockPoison(7), "microblock-poison::7"),
+        ];
+
+        for (key, expected) in expected {
+            assert_eq!(String::from(key), expected);
+        }
+    }
+}
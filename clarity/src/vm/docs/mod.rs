@@ -1187,6 +1187,128 @@ nodes configured for development (as opposed to production mining nodes), this f
     example: "(print (+ 1 2 3)) ;; Returns 6",
 };
 
+const EMIT_EVENT_API: SpecialAPI = SpecialAPI {
+    input_type: "string-ascii, tuple",
+    snippet: "emit-event \"${1:event-name}\" ${2:payload}",
+    output_type: "tuple",
+    signature: "(emit-event \"event-name\" payload)",
+    description: "The `emit-event` function emits a named event with a tuple `payload`, and returns
+the payload. Unlike `print`, which emits any value under the `print` topic, each event has a name
+(which must be a string literal that is a valid Clarity name), and the type of its payload is
+recorded in the contract's interface, so that the events a contract emits can be decoded without
+inspecting the contract's code. If a contract emits an event with the same name in several places,
+the event's type is the least supertype of all of their payloads. Event observers receive these
+events as `contract_typed_event`s, and can subscribe to them by contract and event name.",
+    example: "(emit-event \"transfer\" { amount: u100, memo: \"rent\" }) ;; Returns (tuple (amount u100) (memo \"rent\"))",
+};
+
+const BUFF_AND_API: SpecialAPI = SpecialAPI {
+    input_type: "buff, buff",
+    snippet: "buff-and ${1:buff-1} ${2:buff-2}",
+    output_type: "buff",
+    signature: "(buff-and b1 b2)",
+    description: "Returns the bitwise and of two buffers. The buffers are aligned at their last
+bytes, as if they were big-endian numbers: if one buffer is shorter than the other, it is padded
+with leading zero bytes, and the result is as long as the longer buffer.",
+    example: "(buff-and 0xff0f 0x0ff0) ;; Returns 0x0f00
+(buff-and 0x1234 0xff) ;; Returns 0x0034
+",
+};
+
+const BUFF_OR_API: SpecialAPI = SpecialAPI {
+    input_type: "buff, buff",
+    snippet: "buff-or ${1:buff-1} ${2:buff-2}",
+    output_type: "buff",
+    signature: "(buff-or b1 b2)",
+    description: "Returns the bitwise inclusive or of two buffers. The buffers are aligned at their
+last bytes, as if they were big-endian numbers: if one buffer is shorter than the other, it is
+padded with leading zero bytes, and the result is as long as the longer buffer.",
+    example: "(buff-or 0xff00 0x00ff) ;; Returns 0xffff
+(buff-or 0x1234 0xff) ;; Returns 0x12ff
+",
+};
+
+const BUFF_XOR_API: SpecialAPI = SpecialAPI {
+    input_type: "buff, buff",
+    snippet: "buff-xor ${1:buff-1} ${2:buff-2}",
+    output_type: "buff",
+    signature: "(buff-xor b1 b2)",
+    description: "Returns the bitwise exclusive or of two buffers. The buffers are aligned at their
+last bytes, as if they were big-endian numbers: if one buffer is shorter than the other, it is
+padded with leading zero bytes, and the result is as long as the longer buffer.",
+    example: "(buff-xor 0xffff 0x0ff0) ;; Returns 0xf00f
+(buff-xor 0x1234 0xff) ;; Returns 0x12cb
+",
+};
+
+const BUFF_NOT_API: SpecialAPI = SpecialAPI {
+    input_type: "buff",
+    snippet: "buff-not ${1:buff}",
+    output_type: "buff",
+    signature: "(buff-not b)",
+    description: "Returns the bitwise complement of a buffer, which has the same length as the
+buffer.",
+    example: "(buff-not 0x00ff) ;; Returns 0xff00
+(buff-not 0x) ;; Returns 0x
+",
+};
+
+const ALT_BN128_ADD_API: SpecialAPI = SpecialAPI {
+    input_type: "(buff 64), (buff 64)",
+    snippet: "alt-bn128-add? ${1:a} ${2:b}",
+    output_type: "(response (buff 64) uint)",
+    signature: "(alt-bn128-add? a b)",
+    description: "The `alt-bn128-add?` function adds two points of the G1 group of the alt-bn128
+(BN254) curve, as Ethereum's `ECADD` precompile does. A point is encoded as its 32-byte big-endian
+`x` and `y` coordinates, and the point at infinity as 64 zero bytes. It returns `(err u1)` if an
+input is not 64 bytes long or has a coordinate that is not less than the field modulus, and
+`(err u2)` if an input is not a point on the curve.",
+    example: "(alt-bn128-add? 0x00000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000002 0x00000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000002) ;; Returns (ok 0x030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd315ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4)
+(alt-bn128-add? 0x00000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000002 0x000000000000000000000000000000000000000000000000000000000000000130644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45) ;; Returns (ok 0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000)
+(alt-bn128-add? 0x00000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000002 0x01) ;; Returns (err u1)
+",
+};
+
+const ALT_BN128_MUL_API: SpecialAPI = SpecialAPI {
+    input_type: "(buff 64), (buff 32)",
+    snippet: "alt-bn128-mul? ${1:point} ${2:scalar}",
+    output_type: "(response (buff 64) uint)",
+    signature: "(alt-bn128-mul? point scalar)",
+    description: "The `alt-bn128-mul?` function multiplies a point of the G1 group of the alt-bn128
+(BN254) curve by a 32-byte big-endian scalar, as Ethereum's `ECMUL` precompile does. Points are
+encoded as for `alt-bn128-add?`. It returns `(err u1)` if the point is not 64 bytes long, the
+scalar is not 32 bytes long, or the point has a coordinate that is not less than the field
+modulus, and `(err u2)` if the point is not on the curve.",
+    example: "(alt-bn128-mul? 0x00000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000002 0x0000000000000000000000000000000000000000000000000000000000000002) ;; Returns (ok 0x030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd315ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4)
+(alt-bn128-mul? 0x00000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000003 0x0000000000000000000000000000000000000000000000000000000000000002) ;; Returns (err u2)
+",
+};
+
+const ALT_BN128_PAIRING_CHECK_API: SpecialAPI = SpecialAPI {
+    input_type: "(list { g1: (buff 64), g2: (buff 128) })",
+    snippet: "alt-bn128-pairing-check? ${1:pairs}",
+    output_type: "(response bool uint)",
+    signature: "(alt-bn128-pairing-check? pairs)",
+    description: "The `alt-bn128-pairing-check?` function checks whether the product of the
+pairings of each pair of a G1 point and a G2 point of the alt-bn128 (BN254) curve is the identity,
+as Ethereum's pairing precompile does. This is the check that verifies Groth16 and other zk-SNARK
+proofs. G1 points are encoded as for `alt-bn128-add?`. A G2 point is encoded as its `x` and `y`
+coordinates, each of which is an element `a + b*i` of the extension field written as 32-byte
+big-endian `b` then `a`; the point at infinity is 128 zero bytes. An empty list passes. It returns
+`(err u1)` if a point is not of the right length or has a coordinate that is not less than the
+field modulus, and `(err u2)` if a point is not on the curve (or, in G2, not in the prime-order
+subgroup). The cost of the check grows linearly with the number of pairs.",
+    example: "(alt-bn128-pairing-check? (list
+  { g1: 0x00000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000002,
+    g2: 0x198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c21800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa }
+  { g1: 0x000000000000000000000000000000000000000000000000000000000000000130644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45,
+    g2: 0x198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c21800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa })) ;; Returns (ok true)
+(alt-bn128-pairing-check? (list
+  { g1: 0x00000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000002,
+    g2: 0x198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c21800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa })) ;; Returns (ok false)
+",
+};
+
 const FETCH_ENTRY_API: SpecialAPI = SpecialAPI {
     input_type: "MapName, tuple",
     snippet: "map-get? ${1:map-name} ${2:key-tuple}",
@@ -1392,6 +1514,25 @@ The signature includes 64 bytes plus an optional additional recovery id (00..03)
  0x03adb8de4bfb65db2cfd6120d55c6526ae9c52e675db7e47308636534ba7786110) ;; Returns false"
 };
 
+const SECP256K1VERIFY_MANY_API: SpecialAPI = SpecialAPI {
+    input_type: "(list { message-hash: (buff 32), signature: (buff 65), public-key: (buff 33) })",
+    snippet: "secp256k1-verify-many ${1:entries})",
+    output_type: "(list bool)",
+    signature: "(secp256k1-verify-many entries)",
+    description: "The `secp256k1-verify-many` function checks a list of signatures at once. For each
+entry, it returns `true` if the `signature` of the `message-hash` was signed with the private key
+that generated the `public-key`, exactly as `secp256k1-verify` would, and `false` otherwise.
+The result is a list of `bool`s in the same order as `entries`. Each entry costs as much as a call
+to `secp256k1-verify`.",
+    example: "(secp256k1-verify-many (list
+  { message-hash: 0xde5b9eb9e7c5592930eb2e30a01369c36586d872082ed8181ee83d2a0ec20f04,
+    signature: 0x8738487ebe69b93d8e51583be8eee50bb4213fc49c767d329632730cc193b873554428fc936ca3569afc15f1c9365f6591d6251a89fee9c9ac661116824d3a1301,
+    public-key: 0x03adb8de4bfb65db2cfd6120d55c6526ae9c52e675db7e47308636534ba7786110 }
+  { message-hash: 0x0000000000000000000000000000000000000000000000000000000000000000,
+    signature: 0x0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000,
+    public-key: 0x03adb8de4bfb65db2cfd6120d55c6526ae9c52e675db7e47308636534ba7786110 })) ;; Returns (true false)"
+};
+
 const CONTRACT_CALL_API: SpecialAPI = SpecialAPI {
     input_type: "ContractName, PublicFunctionName, Arg0, ...",
     snippet: "contract-call? ${1:contract-principal} ${2:func} ${3:arg1}",
@@ -1420,6 +1561,23 @@ const CONTRACT_OF_API: SpecialAPI = SpecialAPI {
 "
 };
 
+const IMPLEMENTS_TRAIT_API: SpecialAPI = SpecialAPI {
+    input_type: "principal, TraitIdentifier",
+    output_type: "bool",
+    snippet: "implements-trait? ${1:principal} ${2:trait-identifier}",
+    signature: "(implements-trait? principal trait-identifier)",
+    description: "The `implements-trait?` function returns `true` if `principal` is a contract that
+declares that it implements the given trait with `impl-trait`, and `false` otherwise (including when
+`principal` is a standard principal, or no such contract exists). The trait can be given as a trait
+identifier (`.token-a.token-trait` or `'SPAXYA5XS51713FDTQ8H94EJ4V579CXMTRNBZKSF.token-a.token-trait`),
+or as a trait reference (`<token-a-trait>`) imported with `use-trait`. Only explicit `impl-trait`
+declarations count: a contract that has all of a trait's functions without declaring the trait is
+not reported as implementing it.",
+    example: "(implements-trait? tx-sender 'SPAXYA5XS51713FDTQ8H94EJ4V579CXMTRNBZKSF.token-a.token-trait) ;; Returns false
+(implements-trait? .tokens 'SPAXYA5XS51713FDTQ8H94EJ4V579CXMTRNBZKSF.token-a.token-trait) ;; Returns false
+"
+};
+
 const PRINCIPAL_OF_API: SpecialAPI = SpecialAPI {
     input_type: "(buff 33)",
     snippet: "principal-of? ${1:public-key}",
@@ -1877,6 +2035,23 @@ this value is less than or equal to the value for `miner-spend-total` at the sam
 "
 };
 
+const BLOCK_VRF_SEED_API: SpecialAPI = SpecialAPI {
+    input_type: "uint",
+    snippet: "block-vrf-seed ${1:stacks-block-height}",
+    output_type: "(optional (buff 32))",
+    signature: "(block-vrf-seed stacks-block-height)",
+    description: "The `block-vrf-seed` function returns the VRF seed of the tenure that the Stacks block at the given
+height is a part of. If the provided `stacks-block-height` does not correspond to an existing block prior to the current block,
+the function returns `none`.
+
+The VRF seed is produced by the miner's VRF proof over the previous seed, so unlike a block hash, a miner can't choose it
+(other than by withholding the block). It is the same for every block of a tenure, and it is known as soon as the
+tenure starts, so a contract that draws from it should commit to a height whose seed is not yet known.",
+    example: "(block-vrf-seed u0) ;; Returns (some 0xf490de2920c8a35fabeb13208852aa28c76f9be9b03a4dd2b3c075f7a26923b4)
+(block-vrf-seed u999999) ;; Returns none
+",
+};
+
 const PRINCIPAL_CONSTRUCT_API: SpecialAPI = SpecialAPI {
     input_type: "(buff 1), (buff 20), [(string-ascii 40)]",
     output_type: "(response principal { error_code: uint, value: (optional principal) })",
@@ -2586,15 +2761,26 @@ pub fn make_api_reference(function: &NativeFunctions) -> FunctionAPI {
         Keccak256 => make_for_special(&KECCAK256_API, function),
         Secp256k1Recover => make_for_special(&SECP256K1RECOVER_API, function),
         Secp256k1Verify => make_for_special(&SECP256K1VERIFY_API, function),
+        Secp256k1VerifyMany => make_for_special(&SECP256K1VERIFY_MANY_API, function),
         Print => make_for_special(&PRINT_API, function),
+        EmitEvent => make_for_special(&EMIT_EVENT_API, function),
+        BuffAnd => make_for_special(&BUFF_AND_API, function),
+        BuffOr => make_for_special(&BUFF_OR_API, function),
+        BuffXor => make_for_special(&BUFF_XOR_API, function),
+        BuffNot => make_for_special(&BUFF_NOT_API, function),
+        AltBn128Add => make_for_special(&ALT_BN128_ADD_API, function),
+        AltBn128Mul => make_for_special(&ALT_BN128_MUL_API, function),
+        AltBn128PairingCheck => make_for_special(&ALT_BN128_PAIRING_CHECK_API, function),
         ContractCall => make_for_special(&CONTRACT_CALL_API, function),
         ContractOf => make_for_special(&CONTRACT_OF_API, function),
+        ImplementsTrait => make_for_special(&IMPLEMENTS_TRAIT_API, function),
         PrincipalOf => make_for_special(&PRINCIPAL_OF_API, function),
         AsContract => make_for_special(&AS_CONTRACT_API, function),
         GetBlockInfo => make_for_special(&GET_BLOCK_INFO_API, function),
         GetBurnBlockInfo => make_for_special(&GET_BURN_BLOCK_INFO_API, function),
         GetStacksBlockInfo => make_for_special(&GET_STACKS_BLOCK_INFO_API, function),
         GetTenureInfo => make_for_special(&GET_TENURE_INFO_API, function),
+        BlockVrfSeed => make_for_special(&BLOCK_VRF_SEED_API, function),
         ConsOkay => make_for_special(&CONS_OK_API, function),
         ConsError => make_for_special(&CONS_ERR_API, function),
         ConsSome => make_for_special(&CONS_SOME_API, function),
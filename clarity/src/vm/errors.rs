@@ -29,7 +29,8 @@ pub use crate::vm::analysis::errors::{
 use crate::vm::ast::errors::ParseError;
 use crate::vm::contexts::StackTrace;
 use crate::vm::costs::CostErrors;
-use crate::vm::types::{TypeSignature, Value};
+use crate::vm::representations::Span;
+use crate::vm::types::{QualifiedContractIdentifier, TypeSignature, Value};
 
 #[derive(Debug)]
 pub struct IncomparableError<T> {
@@ -105,6 +106,9 @@ pub enum RuntimeErrorType {
     DefunctPoxContract,
     PoxAlreadyLocked,
     MetadataAlreadySet,
+    /// A contract tried to write more than its storage budget allows: (contract, bytes it would
+    ///  have written, budget)
+    StorageQuotaExceeded(QualifiedContractIdentifier, u64, u64),
 }
 
 #[derive(Debug, PartialEq)]
@@ -115,12 +119,59 @@ pub enum ShortReturnType {
 
 pub type InterpreterResult<R> = Result<R, Error>;
 
+/// Where a runtime error or a cost-limit failure was raised: the innermost expression that was
+///  being evaluated when it was
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorLocation {
+    pub contract_identifier: QualifiedContractIdentifier,
+    pub expression_id: u64,
+    /// The expression's span in the contract's source, from the source map of the contract's
+    ///  analysis.  `None` if the contract has no stored analysis yet (as while it is being
+    ///  deployed, outside of developer mode) or its analysis has no source map.
+    pub span: Option<Span>,
+}
+
+impl fmt::Display for ErrorLocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.span {
+            Some(ref span) => write!(
+                f,
+                "{}:{}:{}",
+                self.contract_identifier, span.start_line, span.start_column
+            ),
+            None => write!(
+                f,
+                "{} (expression {})",
+                self.contract_identifier, self.expression_id
+            ),
+        }
+    }
+}
+
 impl<T> PartialEq<IncomparableError<T>> for IncomparableError<T> {
     fn eq(&self, _other: &IncomparableError<T>) -> bool {
         return false;
     }
 }
 
+impl Error {
+    /// Whether this error has a location in the source of a contract: runtime errors and
+    ///  failures to stay within the cost or memory limits do, while errors that the analysis
+    ///  should have caught, interpreter failures, and short returns don't.
+    pub fn has_source_location(&self) -> bool {
+        match self {
+            Error::Runtime(..) => true,
+            Error::Unchecked(err) => matches!(
+                err,
+                CheckErrors::CostOverflow
+                    | CheckErrors::CostBalanceExceeded(..)
+                    | CheckErrors::MemoryBalanceExceeded(..)
+            ),
+            Error::Interpreter(_) | Error::ShortReturn(_) => false,
+        }
+    }
+}
+
 impl PartialEq<Error> for Error {
     fn eq(&self, other: &Error) -> bool {
         match (self, other) {
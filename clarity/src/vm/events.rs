@@ -17,10 +17,12 @@
 use serde_json::json;
 use stacks_common::codec::StacksMessageCodec;
 use stacks_common::types::chainstate::StacksAddress;
+use stacks_common::util::hash::to_hex;
 
 use super::types::serialization::SerializationError;
 use crate::vm::analysis::ContractAnalysis;
 use crate::vm::costs::ExecutionCost;
+use crate::vm::representations::ClarityName;
 use crate::vm::types::{
     AssetIdentifier, BuffData, PrincipalData, QualifiedContractIdentifier, StandardPrincipalData,
     Value,
@@ -29,6 +31,7 @@ use crate::vm::types::{
 #[derive(Debug, Clone, PartialEq)]
 pub enum StacksTransactionEvent {
     SmartContractEvent(SmartContractEventData),
+    ContractEvent(ContractEventData),
     STXEvent(STXEventType),
     NFTEvent(NFTEventType),
     FTEvent(FTEventType),
@@ -49,6 +52,13 @@ impl StacksTransactionEvent {
                 "type": "contract_event",
                 "contract_event": event_data.json_serialize()?
             }),
+            StacksTransactionEvent::ContractEvent(event_data) => json!({
+                "txid": format!("0x{:?}", txid),
+                "event_index": event_index,
+                "committed": committed,
+                "type": "contract_typed_event",
+                "contract_typed_event": event_data.json_serialize()?
+            }),
             StacksTransactionEvent::STXEvent(STXEventType::STXTransferEvent(event_data)) => json!({
                 "txid": format!("0x{:?}", txid),
                 "event_index": event_index,
@@ -363,3 +373,25 @@ impl SmartContractEventData {
         }))
     }
 }
+
+/// An event emitted with `emit-event`. Unlike a `print` event, it has a name, and its payload is
+/// a tuple whose type is recorded in the emitting contract's analysis.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContractEventData {
+    pub contract_identifier: QualifiedContractIdentifier,
+    pub name: ClarityName,
+    pub payload: Value,
+}
+
+impl ContractEventData {
+    pub fn json_serialize(&self) -> Result<serde_json::Value, SerializationError> {
+        let mut raw_payload = vec![];
+        self.payload.serialize_write(&mut raw_payload)?;
+        Ok(json!({
+            "contract_identifier": self.contract_identifier.to_string(),
+            "name": self.name.to_string(),
+            "payload": self.payload,
+            "raw_payload": format!("0x{}", to_hex(&raw_payload)),
+        }))
+    }
+}
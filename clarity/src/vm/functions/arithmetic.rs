@@ -383,6 +383,48 @@ pub fn native_bitwise_not(a: Value) -> InterpreterResult<Value> {
     type_force_unary_arithmetic!(bitwise_not, a)
 }
 
+fn buff_bytes(value: Value) -> InterpreterResult<Vec<u8>> {
+    match value {
+        Value::Sequence(SequenceData::Buffer(BuffData { data })) => Ok(data),
+        _ => Err(CheckErrors::TypeValueError(TypeSignature::max_buffer()?, value).into()),
+    }
+}
+
+/// Combine two buffers byte by byte. The buffers are aligned at their ends, and the shorter one
+/// is padded with leading zeros, so that the result is as long as the longer buffer (as if the
+/// buffers were big-endian numbers).
+fn buff_bitwise(a: Value, b: Value, op: fn(u8, u8) -> u8) -> InterpreterResult<Value> {
+    let (a, b) = (buff_bytes(a)?, buff_bytes(b)?);
+    let (longer, shorter) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let padding = longer.len() - shorter.len();
+    let result = longer
+        .iter()
+        .enumerate()
+        .map(|(i, byte)| {
+            let other = i.checked_sub(padding).map_or(0, |j| shorter[j]);
+            op(*byte, other)
+        })
+        .collect();
+    Value::buff_from(result)
+}
+
+pub fn native_buff_and(a: Value, b: Value) -> InterpreterResult<Value> {
+    buff_bitwise(a, b, |x, y| x & y)
+}
+
+pub fn native_buff_or(a: Value, b: Value) -> InterpreterResult<Value> {
+    buff_bitwise(a, b, |x, y| x | y)
+}
+
+pub fn native_buff_xor(a: Value, b: Value) -> InterpreterResult<Value> {
+    buff_bitwise(a, b, |x, y| x ^ y)
+}
+
+pub fn native_buff_not(a: Value) -> InterpreterResult<Value> {
+    let bytes = buff_bytes(a)?;
+    Value::buff_from(bytes.into_iter().map(|byte| !byte).collect())
+}
+
 // This function is 'special', because it must access the context to determine
 // the clarity version.
 fn special_geq_v1(
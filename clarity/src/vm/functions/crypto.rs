@@ -18,6 +18,7 @@ use stacks_common::address::{
     AddressHashMode, C32_ADDRESS_VERSION_MAINNET_SINGLESIG, C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
 };
 use stacks_common::types::chainstate::StacksAddress;
+use stacks_common::util::bn254::{bn254_g1_add, bn254_g1_mul, bn254_pairing_check, Bn254Error};
 use stacks_common::util::hash;
 use stacks_common::util::secp256k1::{secp256k1_recover, secp256k1_verify, Secp256k1PublicKey};
 
@@ -33,8 +34,9 @@ use crate::vm::errors::{
 use crate::vm::representations::SymbolicExpressionType::{Atom, List};
 use crate::vm::representations::{ClarityName, SymbolicExpression, SymbolicExpressionType};
 use crate::vm::types::{
-    BuffData, CharType, PrincipalData, ResponseData, SequenceData, StacksAddressExtensions,
-    TypeSignature, Value, BUFF_32, BUFF_33, BUFF_65,
+    BuffData, CharType, ListData, PrincipalData, ResponseData, SequenceData,
+    StacksAddressExtensions, TypeSignature, Value, ALT_BN128_PAIRING_ENTRY, BUFF_128, BUFF_32,
+    BUFF_33, BUFF_64, BUFF_65, SECP256K1_SIGNATURE_ENTRY,
 };
 use crate::vm::{eval, ClarityVersion, Environment, LocalContext};
 
@@ -236,3 +238,175 @@ pub fn special_secp256k1_verify(
         secp256k1_verify(message, signature, pubkey).is_ok(),
     ))
 }
+
+/// Check one entry of a `secp256k1-verify-many` list, with the same rules as `secp256k1-verify`
+fn secp256k1_verify_entry(entry: &Value) -> Result<bool> {
+    let Value::Tuple(tuple) = entry else {
+        return Err(
+            CheckErrors::TypeValueError(SECP256K1_SIGNATURE_ENTRY.clone(), entry.clone()).into(),
+        );
+    };
+
+    let message = match tuple.get("message-hash")? {
+        Value::Sequence(SequenceData::Buffer(BuffData { data })) if data.len() == 32 => data,
+        other => return Err(CheckErrors::TypeValueError(BUFF_32.clone(), other.clone()).into()),
+    };
+    let signature = match tuple.get("signature")? {
+        Value::Sequence(SequenceData::Buffer(BuffData { data })) if data.len() <= 65 => data,
+        other => return Err(CheckErrors::TypeValueError(BUFF_65.clone(), other.clone()).into()),
+    };
+    let pubkey = match tuple.get("public-key")? {
+        Value::Sequence(SequenceData::Buffer(BuffData { data })) if data.len() == 33 => data,
+        other => return Err(CheckErrors::TypeValueError(BUFF_33.clone(), other.clone()).into()),
+    };
+
+    if signature.len() < 64 || (signature.len() == 65 && signature[64] > 3) {
+        return Ok(false);
+    }
+    Ok(secp256k1_verify(message, signature, pubkey).is_ok())
+}
+
+pub fn special_secp256k1_verify_many(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    // (secp256k1-verify-many (list ...))
+    // arg0 => (list {message-hash: (buff 32), signature: (buff 65), public-key: (buff 33)})
+    check_argument_count(1, args)?;
+
+    let param0 = eval(&args[0], env, context)?;
+    let entries = match param0 {
+        Value::Sequence(SequenceData::List(ListData { ref data, .. })) => data,
+        _ => return Err(CheckErrors::ExpectedSequence(TypeSignature::type_of(&param0)?).into()),
+    };
+
+    // each signature costs the same as a `secp256k1-verify`, but none of the overhead of
+    //  applying it in a `fold` or `map`
+    let mut results = Vec::with_capacity(entries.len());
+    for entry in entries.iter() {
+        runtime_cost(ClarityCostFunction::Secp256k1verify, env, 0)?;
+        results.push(Value::Bool(secp256k1_verify_entry(entry)?));
+    }
+    Value::cons_list(results, env.epoch())
+}
+
+/// The error code that the `alt-bn128-*` natives return for invalid inputs: `u1` if an input is
+/// not an encoding of a point or scalar, `u2` if a point is not on the curve
+fn alt_bn128_error_code(error: Bn254Error) -> u128 {
+    match error {
+        Bn254Error::InvalidEncoding => 1,
+        Bn254Error::InvalidPoint => 2,
+    }
+}
+
+/// Evaluate an argument that must be a buffer of at most `expected`'s length
+fn eval_buffer_arg(
+    arg: &SymbolicExpression,
+    expected: &TypeSignature,
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Vec<u8>> {
+    let value = eval(arg, env, context)?;
+    if !expected.admits(env.epoch(), &value)? {
+        return Err(CheckErrors::TypeValueError(expected.clone(), value).into());
+    }
+    match value {
+        Value::Sequence(SequenceData::Buffer(BuffData { data })) => Ok(data),
+        _ => Err(CheckErrors::TypeValueError(expected.clone(), value).into()),
+    }
+}
+
+/// The `(response (buff 64) uint)` result of a G1 operation
+fn alt_bn128_result(result: std::result::Result<[u8; 64], Bn254Error>) -> Result<Value> {
+    match result {
+        Ok(point) => Value::okay(Value::buff_from(point.to_vec())?),
+        Err(error) => Ok(Value::err_uint(alt_bn128_error_code(error))),
+    }
+}
+
+pub fn special_alt_bn128_add(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    // (alt-bn128-add? (..))
+    // arg0 => (buff 64), arg1 => (buff 64)
+    check_argument_count(2, args)?;
+
+    runtime_cost(ClarityCostFunction::AltBn128Add, env, 0)?;
+
+    let a = eval_buffer_arg(&args[0], &BUFF_64, env, context)?;
+    let b = eval_buffer_arg(&args[1], &BUFF_64, env, context)?;
+    alt_bn128_result(bn254_g1_add(&a, &b))
+}
+
+pub fn special_alt_bn128_mul(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    // (alt-bn128-mul? (..))
+    // arg0 => (buff 64), arg1 => (buff 32)
+    check_argument_count(2, args)?;
+
+    runtime_cost(ClarityCostFunction::AltBn128Mul, env, 0)?;
+
+    let point = eval_buffer_arg(&args[0], &BUFF_64, env, context)?;
+    let scalar = eval_buffer_arg(&args[1], &BUFF_32, env, context)?;
+    alt_bn128_result(bn254_g1_mul(&point, &scalar))
+}
+
+/// The G1 and G2 points of one entry of an `alt-bn128-pairing-check?` list
+fn alt_bn128_pairing_entry(entry: &Value) -> Result<(&[u8], &[u8])> {
+    let Value::Tuple(tuple) = entry else {
+        return Err(
+            CheckErrors::TypeValueError(ALT_BN128_PAIRING_ENTRY.clone(), entry.clone()).into(),
+        );
+    };
+
+    let g1 = match tuple.get("g1")? {
+        Value::Sequence(SequenceData::Buffer(BuffData { data })) if data.len() <= 64 => {
+            data.as_slice()
+        }
+        other => return Err(CheckErrors::TypeValueError(BUFF_64.clone(), other.clone()).into()),
+    };
+    let g2 = match tuple.get("g2")? {
+        Value::Sequence(SequenceData::Buffer(BuffData { data })) if data.len() <= 128 => {
+            data.as_slice()
+        }
+        other => return Err(CheckErrors::TypeValueError(BUFF_128.clone(), other.clone()).into()),
+    };
+    Ok((g1, g2))
+}
+
+pub fn special_alt_bn128_pairing_check(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    // (alt-bn128-pairing-check? (list ...))
+    // arg0 => (list { g1: (buff 64), g2: (buff 128) })
+    check_argument_count(1, args)?;
+
+    let param0 = eval(&args[0], env, context)?;
+    let entries = match param0 {
+        Value::Sequence(SequenceData::List(ListData { ref data, .. })) => data,
+        _ => return Err(CheckErrors::ExpectedSequence(TypeSignature::type_of(&param0)?).into()),
+    };
+
+    runtime_cost(
+        ClarityCostFunction::AltBn128PairingCheck,
+        env,
+        entries.len(),
+    )?;
+
+    let pairs = entries
+        .iter()
+        .map(alt_bn128_pairing_entry)
+        .collect::<Result<Vec<_>>>()?;
+    match bn254_pairing_check(&pairs) {
+        Ok(passed) => Value::okay(Value::Bool(passed)),
+        Err(error) => Ok(Value::err_uint(alt_bn128_error_code(error))),
+    }
+}
@@ -30,7 +30,7 @@ use crate::vm::errors::{
     InterpreterResult as Result, RuntimeErrorType,
 };
 use crate::vm::functions::tuples;
-use crate::vm::representations::{SymbolicExpression, SymbolicExpressionType};
+use crate::vm::representations::{ClarityName, SymbolicExpression, SymbolicExpressionType};
 use crate::vm::types::{
     BlockInfoProperty, BuffData, BurnBlockInfoProperty, OptionalData, PrincipalData, SequenceData,
     StacksBlockInfoProperty, TenureInfoProperty, TupleData, TypeSignature, Value, BUFF_32,
@@ -464,6 +464,29 @@ pub fn special_at_block(
     result
 }
 
+/// The key and value of a map write, if there are eval hooks to report it to
+fn trace_map_write(
+    env: &Environment,
+    key: &Value,
+    value: Option<&Value>,
+) -> Option<(Value, Option<Value>)> {
+    env.global_context.eval_hooks.as_ref()?;
+    Some((key.clone(), value.cloned()))
+}
+
+/// Report a map write to the eval hooks, if it took effect
+fn report_map_write(
+    env: &mut Environment,
+    map_name: &ClarityName,
+    traced: Option<(Value, Option<Value>)>,
+    result: &Result<Value>,
+) {
+    let (Some((key, value)), Ok(Value::Bool(true))) = (traced, result) else {
+        return;
+    };
+    env.with_eval_hooks(|hook, env| hook.did_write_map(env, map_name, &key, value.as_ref()));
+}
+
 pub fn special_set_entry_v200(
     args: &[SymbolicExpression],
     env: &mut Environment,
@@ -499,10 +522,14 @@ pub fn special_set_entry_v200(
     env.add_memory(value.get_memory_use()?)?;
 
     let epoch = *env.epoch();
-    env.global_context
+    let traced = trace_map_write(env, &key, Some(&value));
+    let result = env
+        .global_context
         .database
         .set_entry(contract, map_name, key, value, data_types, &epoch)
-        .map(|data| data.value)
+        .map(|data| data.value);
+    report_map_write(env, map_name, traced, &result);
+    result
 }
 
 /// The Stacks v205 version of set_entry uses the actual stored size of the
@@ -533,6 +560,7 @@ pub fn special_set_entry_v205(
         .ok_or(CheckErrors::NoSuchMap(map_name.to_string()))?;
 
     let epoch = *env.epoch();
+    let traced = trace_map_write(env, &key, Some(&value));
     let result = env
         .global_context
         .database
@@ -547,7 +575,9 @@ pub fn special_set_entry_v205(
 
     env.add_memory(result_size)?;
 
-    result.map(|data| data.value)
+    let result = result.map(|data| data.value);
+    report_map_write(env, map_name, traced, &result);
+    result
 }
 
 pub fn special_insert_entry_v200(
@@ -585,11 +615,15 @@ pub fn special_insert_entry_v200(
     env.add_memory(value.get_memory_use()?)?;
 
     let epoch = *env.epoch();
+    let traced = trace_map_write(env, &key, Some(&value));
 
-    env.global_context
+    let result = env
+        .global_context
         .database
         .insert_entry(contract, map_name, key, value, data_types, &epoch)
-        .map(|data| data.value)
+        .map(|data| data.value);
+    report_map_write(env, map_name, traced, &result);
+    result
 }
 
 /// The Stacks v205 version of insert_entry uses the actual stored size of the
@@ -620,6 +654,7 @@ pub fn special_insert_entry_v205(
         .ok_or(CheckErrors::NoSuchMap(map_name.to_string()))?;
 
     let epoch = *env.epoch();
+    let traced = trace_map_write(env, &key, Some(&value));
     let result = env
         .global_context
         .database
@@ -634,7 +669,9 @@ pub fn special_insert_entry_v205(
 
     env.add_memory(result_size)?;
 
-    result.map(|data| data.value)
+    let result = result.map(|data| data.value);
+    report_map_write(env, map_name, traced, &result);
+    result
 }
 
 pub fn special_delete_entry_v200(
@@ -669,10 +706,14 @@ pub fn special_delete_entry_v200(
     env.add_memory(key.get_memory_use()?)?;
 
     let epoch = *env.epoch();
-    env.global_context
+    let traced = trace_map_write(env, &key, None);
+    let result = env
+        .global_context
         .database
         .delete_entry(contract, map_name, &key, data_types, &epoch)
-        .map(|data| data.value)
+        .map(|data| data.value);
+    report_map_write(env, map_name, traced, &result);
+    result
 }
 
 /// The Stacks v205 version of delete_entry uses the actual stored size of the
@@ -701,6 +742,7 @@ pub fn special_delete_entry_v205(
         .ok_or(CheckErrors::NoSuchMap(map_name.to_string()))?;
 
     let epoch = *env.epoch();
+    let traced = trace_map_write(env, &key, None);
     let result = env
         .global_context
         .database
@@ -715,7 +757,9 @@ pub fn special_delete_entry_v205(
 
     env.add_memory(result_size)?;
 
-    result.map(|data| data.value)
+    let result = result.map(|data| data.value);
+    report_map_write(env, map_name, traced, &result);
+    result
 }
 
 /// Handles the `get-block-info?` special function.
@@ -1156,3 +1200,45 @@ pub fn special_get_tenure_info(
 
     Value::some(result)
 }
+
+/// Handles the `block-vrf-seed` special function.
+/// Interprets `args` as `[block-height]`, and returns the VRF seed of the tenure of which the
+/// Stacks block at `block-height` is a part, or `none` if there is no such block prior to the
+/// current block.
+///
+/// # Errors:
+/// - CheckErrors::IncorrectArgumentCount if there isn't 1 argument.
+/// - CheckErrors::TypeValueError if `args[0]` isn't a `uint`.
+pub fn special_block_vrf_seed(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    // (block-vrf-seed block-height-uint)
+    runtime_cost(ClarityCostFunction::BlockVrfSeed, env, 0)?;
+
+    check_argument_count(1, args)?;
+
+    let height_eval = eval(&args[0], env, context)?;
+    let height_value = match height_eval {
+        Value::UInt(result) => Ok(result),
+        x => Err(CheckErrors::TypeValueError(TypeSignature::UIntType, x)),
+    }?;
+
+    let Ok(height_value) = u32::try_from(height_value) else {
+        return Ok(Value::none());
+    };
+
+    let current_height = env.global_context.database.get_current_block_height();
+    if height_value >= current_height {
+        return Ok(Value::none());
+    }
+
+    let vrf_seed = env
+        .global_context
+        .database
+        .get_block_vrf_seed(height_value)?;
+    Value::some(Value::Sequence(SequenceData::Buffer(BuffData {
+        data: vrf_seed.as_bytes().to_vec(),
+    })))
+}
@@ -25,15 +25,17 @@ use crate::vm::costs::{
     constants as cost_constants, cost_functions, runtime_cost, CostTracker, MemoryConsumer,
 };
 use crate::vm::errors::{
-    check_argument_count, check_arguments_at_least, CheckErrors, Error,
+    check_argument_count, check_arguments_at_least, CheckErrors, Error, InterpreterError,
     InterpreterResult as Result, RuntimeErrorType, ShortReturnType,
 };
 pub use crate::vm::functions::assets::stx_transfer_consolidated;
 use crate::vm::representations::SymbolicExpressionType::{Atom, List};
-use crate::vm::representations::{ClarityName, SymbolicExpression, SymbolicExpressionType};
+use crate::vm::representations::{
+    ClarityName, SymbolicExpression, SymbolicExpressionType, TraitDefinition,
+};
 use crate::vm::types::{
-    BuffData, CharType, PrincipalData, ResponseData, SequenceData, TypeSignature, Value, BUFF_32,
-    BUFF_33, BUFF_65,
+    ASCIIData, BuffData, CallableData, CharType, PrincipalData, ResponseData, SequenceData,
+    TypeSignature, Value, BUFF_32, BUFF_33, BUFF_65,
 };
 use crate::vm::Value::CallableContract;
 use crate::vm::{eval, is_reserved, Environment, LocalContext};
@@ -63,12 +65,13 @@ macro_rules! switch_on_global_epoch {
                 StacksEpochId::Epoch25 => $Epoch205Version(args, env, context),
                 // Note: We reuse 2.05 for 3.0.
                 StacksEpochId::Epoch30 => $Epoch205Version(args, env, context),
+                // Note: We reuse 2.05 for 3.1.
+                StacksEpochId::Epoch31 => $Epoch205Version(args, env, context),
             }
         }
     };
 }
 
-use super::errors::InterpreterError;
 use crate::vm::ClarityVersion;
 
 mod arithmetic;
@@ -195,6 +198,17 @@ define_versioned_named_enum_with_max!(NativeFunctions(ClarityVersion) {
     ReplaceAt("replace-at?", ClarityVersion::Clarity2, None),
     GetStacksBlockInfo("get-stacks-block-info?", ClarityVersion::Clarity3, None),
     GetTenureInfo("get-tenure-info?", ClarityVersion::Clarity3, None),
+    Secp256k1VerifyMany("secp256k1-verify-many", ClarityVersion::Clarity4, None),
+    ImplementsTrait("implements-trait?", ClarityVersion::Clarity4, None),
+    EmitEvent("emit-event", ClarityVersion::Clarity4, None),
+    BuffAnd("buff-and", ClarityVersion::Clarity4, None),
+    BuffOr("buff-or", ClarityVersion::Clarity4, None),
+    BuffXor("buff-xor", ClarityVersion::Clarity4, None),
+    BuffNot("buff-not", ClarityVersion::Clarity4, None),
+    AltBn128Add("alt-bn128-add?", ClarityVersion::Clarity4, None),
+    AltBn128Mul("alt-bn128-mul?", ClarityVersion::Clarity4, None),
+    AltBn128PairingCheck("alt-bn128-pairing-check?", ClarityVersion::Clarity4, None),
+    BlockVrfSeed("block-vrf-seed", ClarityVersion::Clarity4, None),
 });
 
 ///
@@ -409,12 +423,26 @@ pub fn lookup_reserved_functions(name: &str, version: &ClarityVersion) -> Option
             Secp256k1Verify => {
                 SpecialFunction("native_secp256k1-verify", &crypto::special_secp256k1_verify)
             }
+            Secp256k1VerifyMany => SpecialFunction(
+                "native_secp256k1-verify-many",
+                &crypto::special_secp256k1_verify_many,
+            ),
+            AltBn128Add => SpecialFunction("native_alt-bn128-add", &crypto::special_alt_bn128_add),
+            AltBn128Mul => SpecialFunction("native_alt-bn128-mul", &crypto::special_alt_bn128_mul),
+            AltBn128PairingCheck => SpecialFunction(
+                "native_alt-bn128-pairing-check",
+                &crypto::special_alt_bn128_pairing_check,
+            ),
             Print => SpecialFunction("special_print", &special_print),
+            EmitEvent => SpecialFunction("special_emit-event", &special_emit_event),
             ContractCall => {
                 SpecialFunction("special_contract-call", &database::special_contract_call)
             }
             AsContract => SpecialFunction("special_as-contract", &special_as_contract),
             ContractOf => SpecialFunction("special_contract-of", &special_contract_of),
+            ImplementsTrait => {
+                SpecialFunction("special_implements-trait", &special_implements_trait)
+            }
             PrincipalOf => SpecialFunction("special_principal-of", &crypto::special_principal_of),
             GetBlockInfo => {
                 SpecialFunction("special_get_block_info", &database::special_get_block_info)
@@ -431,6 +459,9 @@ pub fn lookup_reserved_functions(name: &str, version: &ClarityVersion) -> Option
                 "special_get_tenure_info",
                 &database::special_get_tenure_info,
             ),
+            BlockVrfSeed => {
+                SpecialFunction("special_block_vrf_seed", &database::special_block_vrf_seed)
+            }
             ConsSome => NativeFunction(
                 "native_some",
                 NativeHandle::SingleArg(&options::native_some),
@@ -563,6 +594,30 @@ pub fn lookup_reserved_functions(name: &str, version: &ClarityVersion) -> Option
                 NativeHandle::MoreArg(&arithmetic::native_bitwise_xor),
                 ClarityCostFunction::Xor,
             ),
+            BuffAnd => NativeFunction205(
+                "native_buff_and",
+                NativeHandle::DoubleArg(&arithmetic::native_buff_and),
+                ClarityCostFunction::BuffAnd,
+                &cost_input_sized_vararg,
+            ),
+            BuffOr => NativeFunction205(
+                "native_buff_or",
+                NativeHandle::DoubleArg(&arithmetic::native_buff_or),
+                ClarityCostFunction::BuffOr,
+                &cost_input_sized_vararg,
+            ),
+            BuffXor => NativeFunction205(
+                "native_buff_xor",
+                NativeHandle::DoubleArg(&arithmetic::native_buff_xor),
+                ClarityCostFunction::BuffXor,
+                &cost_input_sized_vararg,
+            ),
+            BuffNot => NativeFunction205(
+                "native_buff_not",
+                NativeHandle::SingleArg(&arithmetic::native_buff_not),
+                ClarityCostFunction::BuffNot,
+                &cost_input_sized_vararg,
+            ),
         };
         Some(callable)
     } else {
@@ -622,6 +677,43 @@ fn special_print(
     Ok(input)
 }
 
+/// The name of the event in `(emit-event "name" payload)`, which must be a string literal that is
+/// also a valid Clarity name, so that each event's payload type is known at analysis time
+pub fn parse_event_name(
+    expr: &SymbolicExpression,
+) -> std::result::Result<ClarityName, CheckErrors> {
+    let Some(Value::Sequence(SequenceData::String(CharType::ASCII(ASCIIData { data })))) =
+        expr.match_literal_value()
+    else {
+        return Err(CheckErrors::EmitEventExpectName);
+    };
+    let name = String::from_utf8(data.clone()).map_err(|_| CheckErrors::EmitEventExpectName)?;
+    ClarityName::try_from(name).map_err(|_| CheckErrors::EmitEventExpectName)
+}
+
+fn special_emit_event(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    // (emit-event "name" payload)
+    // arg0 => string literal
+    // arg1 => tuple
+    check_argument_count(2, args)?;
+
+    let name = parse_event_name(&args[0])?;
+    let payload = eval(&args[1], env, context)?;
+
+    runtime_cost(ClarityCostFunction::EmitEvent, env, payload.size()?)?;
+
+    if !matches!(payload, Value::Tuple(_)) {
+        return Err(CheckErrors::ExpectedTuple(TypeSignature::type_of(&payload)?).into());
+    }
+
+    env.register_contract_event(name, payload.clone())?;
+    Ok(payload)
+}
+
 fn special_if(
     args: &[SymbolicExpression],
     env: &mut Environment,
@@ -742,6 +834,7 @@ fn special_let(
                     inner_context.callable_contracts.insert(binding_name.clone(), trait_data.clone());
                 }
             }
+            env.with_eval_hooks(|hook, env| hook.did_bind_variable(env, binding_name, &binding_value));
             inner_context.variables.insert(binding_name.clone(), binding_value);
             Ok(())
         })?;
@@ -817,3 +910,63 @@ fn special_contract_of(
     let contract_principal = Value::Principal(PrincipalData::Contract(contract_identifier.clone()));
     Ok(contract_principal)
 }
+
+fn special_implements_trait(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    // (implements-trait? (..) .contract.trait)
+    // arg0 => principal
+    // arg1 => trait identifier or trait reference
+    check_argument_count(2, args)?;
+
+    // charged up front, since it prices the lookup of whether the contract exists
+    runtime_cost(ClarityCostFunction::ImplementsTrait, env, 0)?;
+
+    let trait_identifier = match &args[1].expr {
+        SymbolicExpressionType::Field(trait_identifier)
+        | SymbolicExpressionType::TraitReference(_, TraitDefinition::Defined(trait_identifier))
+        | SymbolicExpressionType::TraitReference(_, TraitDefinition::Imported(trait_identifier)) => {
+            trait_identifier
+        }
+        _ => return Err(CheckErrors::ExpectedTraitIdentifier.into()),
+    };
+
+    let contract_identifier = match eval(&args[0], env, context)? {
+        Value::Principal(PrincipalData::Contract(contract_identifier))
+        | CallableContract(CallableData {
+            contract_identifier,
+            ..
+        }) => contract_identifier,
+        Value::Principal(PrincipalData::Standard(_)) => return Ok(Value::Bool(false)),
+        other => {
+            return Err(CheckErrors::TypeValueError(TypeSignature::PrincipalType, other).into())
+        }
+    };
+
+    // a contract that doesn't exist doesn't implement anything
+    if !env
+        .global_context
+        .database
+        .has_contract(&contract_identifier)
+    {
+        return Ok(Value::Bool(false));
+    }
+    let contract_size = env
+        .global_context
+        .database
+        .get_contract_size(&contract_identifier)?;
+    runtime_cost(ClarityCostFunction::LoadContract, env, contract_size)?;
+
+    let implemented_traits = env
+        .global_context
+        .database
+        .get_implemented_traits(&contract_identifier)?
+        .ok_or_else(|| {
+            InterpreterError::Expect(format!(
+                "Failed to load the analysis of existing contract {contract_identifier}"
+            ))
+        })?;
+    Ok(Value::Bool(implemented_traits.contains(trait_identifier)))
+}
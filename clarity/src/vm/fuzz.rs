@@ -0,0 +1,684 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Generators and differential execution for property-based fuzzing of the Clarity VM.
+//!
+//! This module is only built with the `fuzzing` feature.  A `ValueGenerator` draws random types,
+//! random values of a given type, and random expressions that type-check as a given type, from
+//! any `Rng` (seed one, e.g. a `ChaCha8Rng`, to make failures reproducible).  `to_source()`
+//! renders a generated expression as Clarity code, which `compare_stores()` and
+//! `compare_epochs()` then run in two configurations, reporting a `Divergence` if their results
+//! differ.
+//!
+//! The generators cover every type that can be written in a contract except traits: values of
+//! trait and callable types need deployed contracts to refer to, so generating them fails.
+
+use rand::Rng;
+use stacks_common::consts::CHAIN_ID_TESTNET;
+use stacks_common::types::StacksEpochId;
+use stacks_common::util::hash::to_hex;
+
+use crate::vm::analysis::errors::CheckErrors;
+use crate::vm::ast::{build_ast_with_rules, ASTRules};
+use crate::vm::contexts::{ContractContext, GlobalContext};
+use crate::vm::costs::LimitedCostTracker;
+use crate::vm::database::{
+    ClarityBackingStore, ClarityDatabase, NULL_BURN_STATE_DB, NULL_HEADER_DB,
+};
+use crate::vm::errors::InterpreterResult;
+use crate::vm::representations::{ClarityName, SymbolicExpression, SymbolicExpressionType};
+use crate::vm::types::signatures::{SequenceSubtype, StringSubtype};
+use crate::vm::types::{
+    BufferLength, CharType, OptionalData, PrincipalData, QualifiedContractIdentifier, ResponseData,
+    SequenceData, StandardPrincipalData, StringUTF8Length, TupleData, TupleTypeSignature,
+    TypeSignature, Value,
+};
+use crate::vm::{eval_all, ClarityVersion};
+
+/// Address versions of the standard principals that the generators draw
+const PRINCIPAL_VERSIONS: [u8; 4] = [20, 21, 22, 26];
+
+/// Bounds on what the generators produce
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FuzzLimits {
+    /// Maximum length of generated buffers, strings and lists, and of the sequence types
+    pub max_sequence_len: u32,
+    /// Maximum nesting depth of generated types and expressions
+    pub max_depth: u8,
+    /// Maximum number of list items in one generated value
+    pub max_list_items: usize,
+}
+
+impl Default for FuzzLimits {
+    fn default() -> FuzzLimits {
+        FuzzLimits {
+            max_sequence_len: 8,
+            max_depth: 4,
+            max_list_items: 64,
+        }
+    }
+}
+
+/// Draws random types, values and expressions
+pub struct ValueGenerator<R: Rng> {
+    rng: R,
+    epoch: StacksEpochId,
+    limits: FuzzLimits,
+    /// List items left in the value being generated
+    list_items_left: usize,
+    /// Count of the `let` bindings generated, to keep their names distinct
+    bindings: usize,
+}
+
+impl<R: Rng> ValueGenerator<R> {
+    pub fn new(rng: R, epoch: StacksEpochId) -> ValueGenerator<R> {
+        ValueGenerator {
+            rng,
+            epoch,
+            limits: FuzzLimits::default(),
+            list_items_left: 0,
+            bindings: 0,
+        }
+    }
+
+    pub fn with_limits(mut self, limits: FuzzLimits) -> ValueGenerator<R> {
+        self.limits = limits;
+        self
+    }
+
+    pub fn rng(&mut self) -> &mut R {
+        &mut self.rng
+    }
+
+    /// A random type that values can be generated for
+    pub fn random_type(&mut self) -> InterpreterResult<TypeSignature> {
+        self.random_type_at_depth(self.limits.max_depth)
+    }
+
+    fn random_type_at_depth(&mut self, depth: u8) -> InterpreterResult<TypeSignature> {
+        let max_len = self.limits.max_sequence_len.max(1);
+        let choices = if depth == 0 { 7 } else { 11 };
+        let ty = match self.rng.gen_range(0..choices) {
+            0 => TypeSignature::IntType,
+            1 => TypeSignature::UIntType,
+            2 => TypeSignature::BoolType,
+            3 => TypeSignature::PrincipalType,
+            4 => TypeSignature::SequenceType(SequenceSubtype::BufferType(BufferLength::try_from(
+                self.rng.gen_range(1..=max_len),
+            )?)),
+            5 => TypeSignature::SequenceType(SequenceSubtype::StringType(StringSubtype::ASCII(
+                BufferLength::try_from(self.rng.gen_range(1..=max_len))?,
+            ))),
+            6 => TypeSignature::SequenceType(SequenceSubtype::StringType(StringSubtype::UTF8(
+                StringUTF8Length::try_from(self.rng.gen_range(1..=max_len))?,
+            ))),
+            7 => TypeSignature::list_of(
+                self.random_type_at_depth(depth - 1)?,
+                self.rng.gen_range(0..=max_len),
+            )?,
+            8 => TypeSignature::new_option(self.random_type_at_depth(depth - 1)?)?,
+            9 => TypeSignature::new_response(
+                self.random_type_at_depth(depth - 1)?,
+                self.random_type_at_depth(depth - 1)?,
+            )?,
+            _ => {
+                let mut fields = vec![];
+                for i in 0..self.rng.gen_range(1..=4) {
+                    fields.push((
+                        ClarityName::try_from(format!("f{i}"))?,
+                        self.random_type_at_depth(depth - 1)?,
+                    ));
+                }
+                TypeSignature::TupleType(TupleTypeSignature::try_from(fields)?)
+            }
+        };
+        Ok(ty)
+    }
+
+    fn sequence_len(&mut self, max_len: u32) -> usize {
+        self.rng
+            .gen_range(0..=max_len.min(self.limits.max_sequence_len)) as usize
+    }
+
+    fn random_int(&mut self) -> i128 {
+        match self.rng.gen_range(0..8) {
+            0 => 0,
+            1 => i128::MAX,
+            2 => i128::MIN,
+            3 => self.rng.gen_range(-16..16),
+            _ => self.rng.gen(),
+        }
+    }
+
+    fn random_uint(&mut self) -> u128 {
+        match self.rng.gen_range(0..8) {
+            0 => 0,
+            1 => u128::MAX,
+            2 => self.rng.gen_range(0..16),
+            _ => self.rng.gen(),
+        }
+    }
+
+    /// A random standard or contract principal
+    pub fn random_principal(&mut self) -> InterpreterResult<PrincipalData> {
+        let version = PRINCIPAL_VERSIONS[self.rng.gen_range(0..PRINCIPAL_VERSIONS.len())];
+        let issuer = StandardPrincipalData(version, self.rng.gen());
+        if self.rng.gen_bool(0.5) {
+            return Ok(PrincipalData::Standard(issuer));
+        }
+        let name = format!("contract-{}", self.rng.gen_range(0..100));
+        Ok(PrincipalData::Contract(QualifiedContractIdentifier::new(
+            issuer,
+            name.try_into()?,
+        )))
+    }
+
+    /// A random value of type `ty`
+    pub fn random_value(&mut self, ty: &TypeSignature) -> InterpreterResult<Value> {
+        self.list_items_left = self.limits.max_list_items;
+        self.value_of(ty)
+    }
+
+    fn value_of(&mut self, ty: &TypeSignature) -> InterpreterResult<Value> {
+        use crate::vm::types::signatures::TypeSignature::*;
+        let value = match ty {
+            IntType => Value::Int(self.random_int()),
+            UIntType => Value::UInt(self.random_uint()),
+            BoolType => Value::Bool(self.rng.gen()),
+            PrincipalType => Value::Principal(self.random_principal()?),
+            SequenceType(SequenceSubtype::BufferType(len)) => {
+                let len = self.sequence_len(u32::from(len));
+                Value::buff_from((0..len).map(|_| self.rng.gen()).collect())?
+            }
+            SequenceType(SequenceSubtype::StringType(StringSubtype::ASCII(len))) => {
+                let len = self.sequence_len(u32::from(len));
+                Value::string_ascii_from_bytes(
+                    (0..len)
+                        .map(|_| self.rng.gen_range(0x20u8..=0x7e))
+                        .collect(),
+                )?
+            }
+            SequenceType(SequenceSubtype::StringType(StringSubtype::UTF8(len))) => {
+                let len = self.sequence_len(u32::from(len));
+                let string: String = (0..len)
+                    .map(|_| {
+                        if self.rng.gen_bool(0.5) {
+                            char::from(self.rng.gen_range(0x20u8..=0x7e))
+                        } else {
+                            self.rng.gen()
+                        }
+                    })
+                    .collect();
+                Value::string_utf8_from_bytes(string.into_bytes())?
+            }
+            SequenceType(SequenceSubtype::ListType(list_type)) => {
+                let mut len = self.sequence_len(list_type.get_max_len());
+                if *list_type.get_list_item_type() == NoType {
+                    len = 0;
+                }
+                len = len.min(self.list_items_left);
+                self.list_items_left -= len;
+                let mut items = Vec::with_capacity(len);
+                for _ in 0..len {
+                    items.push(self.value_of(list_type.get_list_item_type())?);
+                }
+                Value::list_with_type(&self.epoch, items, list_type.clone())?
+            }
+            TupleType(tuple_type) => {
+                let mut fields = vec![];
+                for (name, field_type) in tuple_type.get_type_map().iter() {
+                    fields.push((name.clone(), self.value_of(field_type)?));
+                }
+                Value::Tuple(TupleData::from_data_typed(&self.epoch, fields, tuple_type)?)
+            }
+            OptionalType(inner) => {
+                if **inner == NoType || self.rng.gen_bool(0.25) {
+                    Value::none()
+                } else {
+                    Value::some(self.value_of(inner)?)?
+                }
+            }
+            ResponseType(inner) => {
+                let (ok_type, err_type) = &**inner;
+                if *err_type == NoType || (*ok_type != NoType && self.rng.gen_bool(0.5)) {
+                    Value::okay(self.value_of(ok_type)?)?
+                } else {
+                    Value::error(self.value_of(err_type)?)?
+                }
+            }
+            NoType => return Err(CheckErrors::CouldNotDetermineType.into()),
+            CallableType(_) | ListUnionType(_) | TraitReferenceType(_) => {
+                return Err(
+                    CheckErrors::Expects(format!("Cannot generate values of type {ty}")).into(),
+                )
+            }
+        };
+        Ok(value)
+    }
+
+    /// A random expression that type-checks as (a subtype of) `ty`, built from literals and
+    /// from native functions that cannot fail at runtime
+    pub fn random_expression(
+        &mut self,
+        ty: &TypeSignature,
+    ) -> InterpreterResult<SymbolicExpression> {
+        self.list_items_left = self.limits.max_list_items;
+        self.expression_of(ty, self.limits.max_depth)
+    }
+
+    fn expression_of(
+        &mut self,
+        ty: &TypeSignature,
+        depth: u8,
+    ) -> InterpreterResult<SymbolicExpression> {
+        if depth == 0 || self.rng.gen_bool(0.3) {
+            return self.literal_of(ty, depth);
+        }
+        let depth = depth - 1;
+        let expression = match (self.rng.gen_range(0..5), ty) {
+            (0, _) => call(
+                "if",
+                vec![
+                    self.expression_of(&TypeSignature::BoolType, depth)?,
+                    self.expression_of(ty, depth)?,
+                    self.expression_of(ty, depth)?,
+                ],
+            ),
+            (1, _) => call("begin", vec![self.expression_of(ty, depth)?]),
+            (2, _) => call(
+                "unwrap-panic",
+                vec![call("some", vec![self.expression_of(ty, depth)?])],
+            ),
+            (3, _) => {
+                let name = format!("v{}", self.bindings);
+                self.bindings += 1;
+                let binding =
+                    SymbolicExpression::list(vec![atom(&name), self.expression_of(ty, depth)?]);
+                call(
+                    "let",
+                    vec![SymbolicExpression::list(vec![binding]), atom(&name)],
+                )
+            }
+            (_, TypeSignature::BoolType) => match self.rng.gen_range(0..4) {
+                0 => call("not", vec![self.expression_of(ty, depth)?]),
+                1 => call(
+                    "and",
+                    vec![
+                        self.expression_of(ty, depth)?,
+                        self.expression_of(ty, depth)?,
+                    ],
+                ),
+                2 => call(
+                    "or",
+                    vec![
+                        self.expression_of(ty, depth)?,
+                        self.expression_of(ty, depth)?,
+                    ],
+                ),
+                _ => {
+                    let compared = TypeSignature::IntType;
+                    call(
+                        "is-eq",
+                        vec![
+                            self.expression_of(&compared, depth)?,
+                            self.expression_of(&compared, depth)?,
+                        ],
+                    )
+                }
+            },
+            (_, TypeSignature::IntType) | (_, TypeSignature::UIntType) => call(
+                "xor",
+                vec![
+                    self.expression_of(ty, depth)?,
+                    self.expression_of(ty, depth)?,
+                ],
+            ),
+            _ => self.literal_of(ty, depth)?,
+        };
+        Ok(expression)
+    }
+
+    /// An expression that constructs a random value of `ty`
+    fn literal_of(
+        &mut self,
+        ty: &TypeSignature,
+        depth: u8,
+    ) -> InterpreterResult<SymbolicExpression> {
+        use crate::vm::types::signatures::TypeSignature::*;
+        let depth = depth.saturating_sub(1);
+        let expression = match ty {
+            SequenceType(SequenceSubtype::ListType(list_type)) => {
+                let mut len = self.sequence_len(list_type.get_max_len());
+                if *list_type.get_list_item_type() == NoType {
+                    len = 0;
+                }
+                len = len.min(self.list_items_left);
+                self.list_items_left -= len;
+                let mut items = Vec::with_capacity(len);
+                for _ in 0..len {
+                    items.push(self.expression_of(list_type.get_list_item_type(), depth)?);
+                }
+                call("list", items)
+            }
+            TupleType(tuple_type) => {
+                let mut fields = vec![];
+                for (name, field_type) in tuple_type.get_type_map().iter() {
+                    fields.push(SymbolicExpression::list(vec![
+                        atom(name),
+                        self.expression_of(field_type, depth)?,
+                    ]));
+                }
+                call("tuple", fields)
+            }
+            OptionalType(inner) => {
+                if **inner == NoType || self.rng.gen_bool(0.25) {
+                    atom("none")
+                } else {
+                    call("some", vec![self.expression_of(inner, depth)?])
+                }
+            }
+            ResponseType(inner) => {
+                let (ok_type, err_type) = &**inner;
+                if *err_type == NoType || (*ok_type != NoType && self.rng.gen_bool(0.5)) {
+                    call("ok", vec![self.expression_of(ok_type, depth)?])
+                } else {
+                    call("err", vec![self.expression_of(err_type, depth)?])
+                }
+            }
+            _ => SymbolicExpression::literal_value(self.value_of(ty)?),
+        };
+        Ok(expression)
+    }
+}
+
+fn atom(name: &str) -> SymbolicExpression {
+    SymbolicExpression::atom(ClarityName::from(name))
+}
+
+fn call(function: &str, mut args: Vec<SymbolicExpression>) -> SymbolicExpression {
+    args.insert(0, atom(function));
+    SymbolicExpression::list(args)
+}
+
+/// Render an expression as Clarity code that parses back to it.  Values are rendered as the
+/// expressions that construct them, and strings as literals (so ASCII strings must only contain
+/// printable characters).
+pub fn to_source(expression: &SymbolicExpression) -> String {
+    match &expression.expr {
+        SymbolicExpressionType::List(items) => {
+            let items: Vec<_> = items.iter().map(to_source).collect();
+            format!("({})", items.join(" "))
+        }
+        SymbolicExpressionType::AtomValue(value) | SymbolicExpressionType::LiteralValue(value) => {
+            value_to_source(value)
+        }
+        _ => expression.to_string(),
+    }
+}
+
+/// Render a value as a Clarity expression that evaluates to it
+pub fn value_to_source(value: &Value) -> String {
+    match value {
+        Value::Int(_) | Value::UInt(_) | Value::Bool(_) => value.to_string(),
+        Value::Principal(principal) => format!("'{principal}"),
+        Value::CallableContract(callable) => format!("'{}", callable.contract_identifier),
+        Value::Optional(OptionalData { data: None }) => "none".into(),
+        Value::Optional(OptionalData { data: Some(inner) }) => {
+            format!("(some {})", value_to_source(inner))
+        }
+        Value::Response(ResponseData { committed, data }) => format!(
+            "({} {})",
+            if *committed { "ok" } else { "err" },
+            value_to_source(data)
+        ),
+        Value::Tuple(tuple) => {
+            let fields: Vec<_> = tuple
+                .data_map
+                .iter()
+                .map(|(name, value)| format!("({} {})", name, value_to_source(value)))
+                .collect();
+            format!("(tuple {})", fields.join(" "))
+        }
+        Value::Sequence(SequenceData::Buffer(buff)) => format!("0x{}", to_hex(&buff.data)),
+        Value::Sequence(SequenceData::List(list)) => {
+            let items: Vec<_> = list.data.iter().map(value_to_source).collect();
+            if items.is_empty() {
+                "(list)".into()
+            } else {
+                format!("(list {})", items.join(" "))
+            }
+        }
+        Value::Sequence(SequenceData::String(CharType::ASCII(ascii))) => {
+            let mut rendered = String::from("\"");
+            for byte in ascii.data.iter() {
+                match byte {
+                    b'"' => rendered.push_str("\\\""),
+                    b'\\' => rendered.push_str("\\\\"),
+                    b'\n' => rendered.push_str("\\n"),
+                    b'\t' => rendered.push_str("\\t"),
+                    b'\r' => rendered.push_str("\\r"),
+                    _ => rendered.push(char::from(*byte)),
+                }
+            }
+            rendered.push('"');
+            rendered
+        }
+        Value::Sequence(SequenceData::String(CharType::UTF8(utf8))) => {
+            let mut rendered = String::from("u\"");
+            for c in utf8.data.iter() {
+                let c = std::str::from_utf8(c)
+                    .ok()
+                    .and_then(|c| c.chars().next())
+                    .unwrap_or(char::REPLACEMENT_CHARACTER);
+                match c {
+                    '"' => rendered.push_str("\\\""),
+                    '\\' => rendered.push_str("\\\\"),
+                    ' '..='~' => rendered.push(c),
+                    _ => rendered.push_str(&format!("\\u{{{:x}}}", u32::from(c))),
+                }
+            }
+            rendered.push('"');
+            rendered
+        }
+    }
+}
+
+/// Run `program` as top-level code, as `execute()` does, with its state written to `store`
+pub fn execute_in_store(
+    program: &str,
+    version: ClarityVersion,
+    epoch: StacksEpochId,
+    store: &mut dyn ClarityBackingStore,
+) -> InterpreterResult<Option<Value>> {
+    let contract_id = QualifiedContractIdentifier::transient();
+    let mut contract_context = ContractContext::new(contract_id.clone(), version);
+    let database = ClarityDatabase::new(store, &NULL_HEADER_DB, &NULL_BURN_STATE_DB);
+    let mut global_context = GlobalContext::new(
+        false,
+        CHAIN_ID_TESTNET,
+        database,
+        LimitedCostTracker::new_free(),
+        epoch,
+    );
+    global_context.execute(|g| {
+        let parsed = build_ast_with_rules(
+            &contract_id,
+            program,
+            &mut (),
+            version,
+            epoch,
+            ASTRules::PrecheckSize,
+        )?
+        .expressions;
+        eval_all(&parsed, &mut contract_context, g, None)
+    })
+}
+
+/// The results of a program that evaluated differently in two configurations
+#[derive(Debug)]
+pub struct Divergence {
+    pub program: String,
+    pub left: InterpreterResult<Option<Value>>,
+    pub right: InterpreterResult<Option<Value>>,
+}
+
+fn compare_results(
+    program: &str,
+    left: InterpreterResult<Option<Value>>,
+    right: InterpreterResult<Option<Value>>,
+) -> Option<Divergence> {
+    if left == right {
+        return None;
+    }
+    Some(Divergence {
+        program: program.to_string(),
+        left,
+        right,
+    })
+}
+
+/// Run `program` against two backing stores, and report whether the results differ
+pub fn compare_stores(
+    program: &str,
+    version: ClarityVersion,
+    epoch: StacksEpochId,
+    left: &mut dyn ClarityBackingStore,
+    right: &mut dyn ClarityBackingStore,
+) -> Option<Divergence> {
+    compare_results(
+        program,
+        execute_in_store(program, version, epoch, left),
+        execute_in_store(program, version, epoch, right),
+    )
+}
+
+/// Run `program` in two epochs, each against its own backing store, and report whether the
+/// results differ
+pub fn compare_epochs(
+    program: &str,
+    version: ClarityVersion,
+    left_epoch: StacksEpochId,
+    right_epoch: StacksEpochId,
+    left: &mut dyn ClarityBackingStore,
+    right: &mut dyn ClarityBackingStore,
+) -> Option<Divergence> {
+    compare_results(
+        program,
+        execute_in_store(program, version, left_epoch, left),
+        execute_in_store(program, version, right_epoch, right),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+    use crate::vm::analysis::mem_type_check;
+    use crate::vm::database::MemoryBackingStore;
+
+    const EPOCH: StacksEpochId = StacksEpochId::Epoch21;
+
+    #[test]
+    fn test_random_values() {
+        let mut generator = ValueGenerator::new(ChaCha8Rng::seed_from_u64(0), EPOCH);
+        for _ in 0..200 {
+            let ty = generator.random_type().unwrap();
+            let value = generator.random_value(&ty).unwrap();
+            assert!(
+                ty.admits(&EPOCH, &value).unwrap(),
+                "{ty} does not admit {value}"
+            );
+
+            // the rendered value evaluates back to the same value (lists are compared by their
+            // serializations, since their type signatures may differ)
+            let source = value_to_source(&value);
+            let evaluated = execute_in_store(
+                &source,
+                ClarityVersion::Clarity2,
+                EPOCH,
+                &mut MemoryBackingStore::new(),
+            )
+            .unwrap()
+            .unwrap();
+            assert_eq!(
+                value.serialize_to_hex().unwrap(),
+                evaluated.serialize_to_hex().unwrap(),
+                "{source}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_random_expressions() {
+        let mut generator = ValueGenerator::new(ChaCha8Rng::seed_from_u64(1), EPOCH);
+        for _ in 0..100 {
+            let ty = generator.random_type().unwrap();
+            let program = to_source(&generator.random_expression(&ty).unwrap());
+
+            let checked_type = mem_type_check(&program, ClarityVersion::Clarity2, EPOCH)
+                .unwrap()
+                .0
+                .unwrap();
+            assert!(ty.admits_type(&EPOCH, &checked_type).unwrap(), "{program}");
+
+            let value = execute_in_store(
+                &program,
+                ClarityVersion::Clarity2,
+                EPOCH,
+                &mut MemoryBackingStore::new(),
+            )
+            .unwrap()
+            .unwrap();
+            assert!(ty.admits(&EPOCH, &value).unwrap(), "{program}");
+
+            assert!(compare_stores(
+                &program,
+                ClarityVersion::Clarity2,
+                EPOCH,
+                &mut MemoryBackingStore::new(),
+                &mut MemoryBackingStore::new(),
+            )
+            .is_none());
+            assert!(compare_epochs(
+                &program,
+                ClarityVersion::Clarity2,
+                StacksEpochId::Epoch21,
+                StacksEpochId::Epoch25,
+                &mut MemoryBackingStore::new(),
+                &mut MemoryBackingStore::new(),
+            )
+            .is_none());
+        }
+    }
+
+    #[test]
+    fn test_divergence() {
+        let divergence = compare_stores(
+            "(define-data-var n int 0) (var-set n 1) (var-get n)",
+            ClarityVersion::Clarity2,
+            EPOCH,
+            &mut MemoryBackingStore::new(),
+            &mut MemoryBackingStore::new(),
+        );
+        assert!(divergence.is_none());
+
+        let divergence =
+            compare_results("(+ 1 1)", Ok(Some(Value::Int(2))), Ok(Some(Value::Int(3)))).unwrap();
+        assert_eq!(divergence.program, "(+ 1 1)");
+        assert_eq!(divergence.right, Ok(Some(Value::Int(3))));
+    }
+}
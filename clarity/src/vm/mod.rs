@@ -38,6 +38,11 @@ pub mod docs;
 pub mod version;
 
 pub mod coverage;
+pub mod optimizer;
+pub mod tracer;
+
+#[cfg(feature = "fuzzing")]
+pub mod fuzz;
 
 pub mod events;
 
@@ -76,6 +81,7 @@ pub use crate::vm::database::clarity_db::StacksEpoch;
 use crate::vm::errors::{
     CheckErrors, Error, InterpreterError, InterpreterResult as Result, RuntimeErrorType,
 };
+use crate::vm::events::StacksTransactionEvent;
 use crate::vm::functions::define::DefineResult;
 pub use crate::vm::functions::stx_transfer_consolidated;
 pub use crate::vm::representations::{
@@ -163,6 +169,44 @@ pub trait EvalHook {
 
     // Called upon completion of the execution
     fn did_complete(&mut self, _result: core::result::Result<&mut ExecutionResult, String>);
+
+    // Called after a variable is bound, by a `let` or to a function argument
+    fn did_bind_variable(&mut self, _env: &mut Environment, _name: &ClarityName, _value: &Value) {}
+
+    // Called before a public or read-only function of a contract is called, either by a
+    // transaction or by `contract-call?`
+    fn will_call_contract(
+        &mut self,
+        _env: &mut Environment,
+        _contract: &QualifiedContractIdentifier,
+        _function: &str,
+        _args: &[Value],
+    ) {
+    }
+
+    // Called after a contract call returns
+    fn did_finish_contract_call(
+        &mut self,
+        _env: &mut Environment,
+        _contract: &QualifiedContractIdentifier,
+        _function: &str,
+        _res: &core::result::Result<Value, crate::vm::errors::Error>,
+    ) {
+    }
+
+    // Called when an event is registered, e.g. an asset transfer or a `print`
+    fn did_register_event(&mut self, _env: &mut Environment, _event: &StacksTransactionEvent) {}
+
+    // Called after a map entry is set or inserted (`value` is `Some`), or deleted (`value` is
+    // `None`)
+    fn did_write_map(
+        &mut self,
+        _env: &mut Environment,
+        _map: &ClarityName,
+        _key: &Value,
+        _value: Option<&Value>,
+    ) {
+    }
 }
 
 fn lookup_variable(name: &str, context: &LocalContext, env: &mut Environment) -> Result<Value> {
@@ -356,6 +400,10 @@ pub fn eval<'a>(
         env.global_context.eval_hooks = Some(eval_hooks);
     }
 
+    if let Err(ref e) = res {
+        env.record_error_location(exp, e);
+    }
+
     res
 }
 
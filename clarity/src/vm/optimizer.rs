@@ -0,0 +1,471 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Constant folding and dead-branch elimination for analyzed contracts.
+//!
+//! `optimize_contract()` rewrites the bodies of a contract's functions: references to the
+//! contract's constants (and to `true`, `false` and `none`) are replaced with their values, calls
+//! to pure native functions whose arguments are all values are replaced with their results, and
+//! `if`, `and`, `or` and `match` expressions whose outcome is already known are reduced to the
+//! branch that would be taken.  Expressions whose evaluation fails are left alone, so that they
+//! still fail the same way at runtime.
+//!
+//! The pass runs on contracts that have already been analyzed, and it doesn't change what they
+//! evaluate to, but it does change what they cost: the folded expressions are no longer charged
+//! for.  A node must therefore never apply it.  It is meant for local tooling that replays
+//! blocks or runs contracts and doesn't care about costs.  A store opts in by returning true from
+//! `ClarityBackingStore::optimize_contracts()`, and `ClarityDatabase::get_contract()` then
+//! optimizes each contract as it is loaded.
+
+use hashbrown::HashMap;
+use stacks_common::types::StacksEpochId;
+
+use crate::vm::callables::{CallableType, NativeHandle};
+use crate::vm::contexts::ContractContext;
+use crate::vm::functions::{lookup_reserved_functions, NativeFunctions};
+use crate::vm::representations::{
+    ClarityName, SymbolicExpression, SymbolicExpressionCommon, SymbolicExpressionType,
+};
+use crate::vm::types::{OptionalData, ResponseData, TypeSignature, Value};
+use crate::vm::variables::NativeVariables;
+use crate::vm::ClarityVersion;
+
+/// Fold the constant expressions in the bodies of `contract_context`'s functions
+pub fn optimize_contract(contract_context: &mut ContractContext, epoch: &StacksEpochId) {
+    let clarity_version = *contract_context.get_clarity_version();
+    let ContractContext {
+        variables,
+        functions,
+        ..
+    } = contract_context;
+    for function in functions.values_mut() {
+        function.rewrite_body(|arguments, body| {
+            let mut folder = ConstantFolder {
+                clarity_version,
+                epoch: *epoch,
+                constants: variables,
+                bound: arguments.to_vec(),
+            };
+            folder.fold(body);
+        });
+    }
+}
+
+struct ConstantFolder<'a> {
+    clarity_version: ClarityVersion,
+    epoch: StacksEpochId,
+    constants: &'a HashMap<ClarityName, Value>,
+    /// The local variables in scope, which shadow the contract's constants
+    bound: Vec<ClarityName>,
+}
+
+/// The value of `expr`, if it is one
+fn constant(expr: &SymbolicExpression) -> Option<&Value> {
+    expr.match_atom_value()
+        .or_else(|| expr.match_literal_value())
+}
+
+/// Replace `expr` with `new`, which keeps `expr`'s id and span if it is a new expression
+fn replace(expr: &mut SymbolicExpression, mut new: SymbolicExpression) {
+    if new.id == 0 {
+        new.id = expr.id;
+        new.copy_span(expr.span());
+    }
+    *expr = new;
+}
+
+/// `(let ((name value)) body)`
+fn bind(name: ClarityName, value: Value, body: SymbolicExpression) -> Option<SymbolicExpression> {
+    let binding = SymbolicExpression::list(vec![
+        SymbolicExpression::atom(name),
+        SymbolicExpression::atom_value(value),
+    ]);
+    Some(SymbolicExpression::list(vec![
+        SymbolicExpression::atom(ClarityName::try_from(NativeFunctions::Let.get_name()).ok()?),
+        SymbolicExpression::list(vec![binding]),
+        body,
+    ]))
+}
+
+/// Apply a native function that doesn't need an environment
+fn apply_native(handle: &NativeHandle, mut values: Vec<Value>) -> Option<Value> {
+    match handle {
+        NativeHandle::SingleArg(function) if values.len() == 1 => function(values.pop()?).ok(),
+        NativeHandle::DoubleArg(function) if values.len() == 2 => {
+            let second = values.pop()?;
+            let first = values.pop()?;
+            function(first, second).ok()
+        }
+        NativeHandle::MoreArg(function) => function(values).ok(),
+        _ => None,
+    }
+}
+
+/// `and` (`short_circuit` false) or `or` (`short_circuit` true) of the leading constant arguments
+fn fold_bool_op(args: &[SymbolicExpression], short_circuit: bool) -> Option<Value> {
+    if args.is_empty() {
+        return None;
+    }
+    for arg in args.iter() {
+        match constant(arg)? {
+            Value::Bool(value) if *value == short_circuit => return Some(Value::Bool(*value)),
+            Value::Bool(_) => {}
+            _ => return None,
+        }
+    }
+    Some(Value::Bool(!short_circuit))
+}
+
+fn fold_comparison(args: &[SymbolicExpression], native: NativeFunctions) -> Option<Value> {
+    let [a, b] = args else {
+        return None;
+    };
+    let ordering = match (constant(a)?, constant(b)?) {
+        (Value::Int(a), Value::Int(b)) => a.cmp(b),
+        (Value::UInt(a), Value::UInt(b)) => a.cmp(b),
+        _ => return None,
+    };
+    let result = match native {
+        NativeFunctions::CmpGeq => ordering.is_ge(),
+        NativeFunctions::CmpLeq => ordering.is_le(),
+        NativeFunctions::CmpLess => ordering.is_lt(),
+        NativeFunctions::CmpGreater => ordering.is_gt(),
+        _ => return None,
+    };
+    Some(Value::Bool(result))
+}
+
+impl ConstantFolder<'_> {
+    fn fold(&mut self, expr: &mut SymbolicExpression) {
+        let folded = match expr.expr {
+            SymbolicExpressionType::Atom(ref name) => {
+                self.fold_atom(name).map(SymbolicExpression::atom_value)
+            }
+            SymbolicExpressionType::List(ref mut children) => self.fold_list(children),
+            _ => None,
+        };
+        if let Some(folded) = folded {
+            replace(expr, folded);
+        }
+    }
+
+    fn fold_all(&mut self, exprs: &mut [SymbolicExpression]) {
+        for expr in exprs.iter_mut() {
+            self.fold(expr);
+        }
+    }
+
+    /// Fold `expr` with `name` bound
+    fn fold_bound(&mut self, name: &ClarityName, expr: &mut SymbolicExpression) {
+        self.bound.push(name.clone());
+        self.fold(expr);
+        self.bound.pop();
+    }
+
+    fn fold_atom(&self, name: &ClarityName) -> Option<Value> {
+        if let Some(variable) =
+            NativeVariables::lookup_by_name_at_version(name, &self.clarity_version)
+        {
+            return match variable {
+                NativeVariables::NativeTrue => Some(Value::Bool(true)),
+                NativeVariables::NativeFalse => Some(Value::Bool(false)),
+                NativeVariables::NativeNone => Some(Value::none()),
+                _ => None,
+            };
+        }
+        if self.bound.contains(name) {
+            return None;
+        }
+        let value = self.constants.get(name)?.clone();
+        let expected = TypeSignature::type_of(&value).ok()?;
+        Value::sanitize_value(&self.epoch, &expected, value).map(|(value, _)| value)
+    }
+
+    fn fold_list(&mut self, children: &mut [SymbolicExpression]) -> Option<SymbolicExpression> {
+        let (function, args) = children.split_first_mut()?;
+        let name = function.match_atom()?;
+        let Some(native) = NativeFunctions::lookup_by_name_at_version(name, &self.clarity_version)
+        else {
+            // a call to one of the contract's functions
+            self.fold_all(args);
+            return None;
+        };
+
+        use crate::vm::functions::NativeFunctions::*;
+        match native {
+            If => {
+                self.fold_all(args);
+                let [condition, then_branch, else_branch] = args else {
+                    return None;
+                };
+                match constant(condition)? {
+                    Value::Bool(true) => Some(then_branch.clone()),
+                    Value::Bool(false) => Some(else_branch.clone()),
+                    _ => None,
+                }
+            }
+            And | Or => {
+                self.fold_all(args);
+                fold_bool_op(args, native == Or).map(SymbolicExpression::atom_value)
+            }
+            CmpGeq | CmpLeq | CmpLess | CmpGreater => {
+                self.fold_all(args);
+                fold_comparison(args, native).map(SymbolicExpression::atom_value)
+            }
+            Let => {
+                self.fold_let(args);
+                None
+            }
+            Match => self.fold_match(args),
+            TupleCons => {
+                for pair in args.iter_mut() {
+                    if let SymbolicExpressionType::List(ref mut pair) = pair.expr {
+                        if let [_, value] = pair.as_mut_slice() {
+                            self.fold(value);
+                        }
+                    }
+                }
+                None
+            }
+            Asserts | Print | AsMaxLen | Append | Concat | ListCons | Slice | ReplaceAt
+            | Secp256k1Recover | Secp256k1Verify | Secp256k1VerifyMany | PrincipalConstruct
+            | PrincipalDestruct | IsStandard | StxTransfer | StxTransferMemo | StxBurn
+            | StxGetAccount | GetStxBalance | AsContract | AtBlock | PrincipalOf => {
+                self.fold_all(args);
+                None
+            }
+            // the first argument is a name
+            FetchVar | SetVar | FetchEntry | SetEntry | InsertEntry | DeleteEntry | MintAsset
+            | MintToken | TransferAsset | TransferToken | BurnAsset | BurnToken
+            | GetTokenBalance | GetAssetOwner | GetTokenSupply | TupleGet | Map | Filter | Fold
+            | GetBlockInfo | GetBurnBlockInfo | GetStacksBlockInfo | GetTenureInfo
+            | FromConsensusBuff | EmitEvent => {
+                if let Some((_, rest)) = args.split_first_mut() {
+                    self.fold_all(rest);
+                }
+                None
+            }
+            // the first two arguments are the contract and the function
+            ContractCall => {
+                if let Some(rest) = args.get_mut(2..) {
+                    self.fold_all(rest);
+                }
+                None
+            }
+            _ => match lookup_reserved_functions(name, &self.clarity_version)? {
+                CallableType::NativeFunction(_, handle, _)
+                | CallableType::NativeFunction205(_, handle, _, _) => {
+                    self.fold_all(args);
+                    let values = args
+                        .iter()
+                        .map(|arg| constant(arg).cloned())
+                        .collect::<Option<Vec<_>>>()?;
+                    apply_native(&handle, values).map(SymbolicExpression::atom_value)
+                }
+                // other special functions take arguments that aren't expressions
+                _ => None,
+            },
+        }
+    }
+
+    fn fold_let(&mut self, args: &mut [SymbolicExpression]) {
+        let Some((bindings, body)) = args.split_first_mut() else {
+            return;
+        };
+        let Some(bindings) = bindings.match_list_mut() else {
+            return;
+        };
+        let depth = self.bound.len();
+        for binding in bindings.iter_mut() {
+            let Some([name, value]) = binding.match_list_mut() else {
+                self.bound.truncate(depth);
+                return;
+            };
+            self.fold(value);
+            let Some(name) = name.match_atom() else {
+                self.bound.truncate(depth);
+                return;
+            };
+            self.bound.push(name.clone());
+        }
+        self.fold_all(body);
+        self.bound.truncate(depth);
+    }
+
+    fn fold_match(&mut self, args: &mut [SymbolicExpression]) -> Option<SymbolicExpression> {
+        let (input, branches) = args.split_first_mut()?;
+        self.fold(input);
+        match branches {
+            [some_name, some_branch, none_branch] => {
+                let some_name = some_name.match_atom()?.clone();
+                self.fold_bound(&some_name, some_branch);
+                self.fold(none_branch);
+                match constant(input)? {
+                    Value::Optional(OptionalData { data: Some(value) }) => {
+                        bind(some_name, (**value).clone(), some_branch.clone())
+                    }
+                    Value::Optional(OptionalData { data: None }) => Some(none_branch.clone()),
+                    _ => None,
+                }
+            }
+            [ok_name, ok_branch, err_name, err_branch] => {
+                let ok_name = ok_name.match_atom()?.clone();
+                let err_name = err_name.match_atom()?.clone();
+                self.fold_bound(&ok_name, ok_branch);
+                self.fold_bound(&err_name, err_branch);
+                match constant(input)? {
+                    Value::Response(ResponseData { committed, data }) => {
+                        if *committed {
+                            bind(ok_name, (**data).clone(), ok_branch.clone())
+                        } else {
+                            bind(err_name, (**data).clone(), err_branch.clone())
+                        }
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::ast::{parse, ASTRules};
+    use crate::vm::contexts::{Environment, LocalContext, OwnedEnvironment};
+    use crate::vm::database::MemoryBackingStore;
+    use crate::vm::errors::Error;
+    use crate::vm::types::{PrincipalData, QualifiedContractIdentifier};
+    use crate::vm::{EvalHook, ExecutionResult};
+
+    fn fold(src: &str) -> String {
+        let constants = [("TWO", Value::Int(2)), ("FEE", Value::UInt(10))]
+            .into_iter()
+            .map(|(name, value)| (ClarityName::try_from(name.to_string()).unwrap(), value))
+            .collect();
+        let mut exprs = parse(
+            &QualifiedContractIdentifier::transient(),
+            src,
+            ClarityVersion::Clarity2,
+            StacksEpochId::Epoch21,
+        )
+        .unwrap();
+        let mut folder = ConstantFolder {
+            clarity_version: ClarityVersion::Clarity2,
+            epoch: StacksEpochId::Epoch21,
+            constants: &constants,
+            bound: vec![],
+        };
+        folder.fold(&mut exprs[0]);
+        exprs[0].to_string()
+    }
+
+    #[test]
+    fn test_constant_folding() {
+        assert_eq!(fold("(+ 1 TWO)"), "3");
+        assert_eq!(fold("(+ x (* TWO 3))"), "( + x 6 )");
+        assert_eq!(fold("(if (> TWO 1) (ok FEE) (err u1))"), "(ok u10)");
+        assert_eq!(fold("(or false (< TWO 3))"), "true");
+        assert_eq!(
+            fold("(and true (> x 1) false)"),
+            "( and true ( > x 1 ) false )"
+        );
+        assert_eq!(
+            fold("(match (some TWO) value (+ value 1) 0)"),
+            "( let ( ( value 2 ) ) ( + value 1 ) )"
+        );
+        assert_eq!(
+            fold("(match (err FEE) value 0 code (to-int code))"),
+            "( let ( ( code u10 ) ) ( to-int code ) )"
+        );
+        // names of data vars are not expressions
+        assert_eq!(fold("(var-get TWO)"), "( var-get TWO )");
+        // local variables shadow constants
+        assert_eq!(
+            fold("(let ((x TWO) (TWO 5)) (+ x TWO))"),
+            "( let ( ( x 2 ) ( TWO 5 ) ) ( + x TWO ) )"
+        );
+        // errors are left for the runtime
+        assert_eq!(fold("(/ TWO 0)"), "( / 2 0 )");
+    }
+
+    #[derive(Default)]
+    struct EvalCounter {
+        evals: usize,
+    }
+
+    impl EvalHook for EvalCounter {
+        fn will_begin_eval(
+            &mut self,
+            _: &mut Environment,
+            _: &LocalContext,
+            _: &SymbolicExpression,
+        ) {
+            self.evals += 1;
+        }
+
+        fn did_finish_eval(
+            &mut self,
+            _: &mut Environment,
+            _: &LocalContext,
+            _: &SymbolicExpression,
+            _: &Result<Value, Error>,
+        ) {
+        }
+
+        fn did_complete(&mut self, _: Result<&mut ExecutionResult, String>) {}
+    }
+
+    #[test]
+    fn test_optimized_contract() {
+        let contract_id = QualifiedContractIdentifier::local("fees").unwrap();
+        let contract = "(define-constant FEE u10)
+             (define-constant LIMIT u1000)
+             (define-public (pay (amount uint))
+               (if (> (* FEE u2) LIMIT)
+                   (err u1)
+                   (ok (+ amount (* FEE u2)))))";
+        let sender = PrincipalData::parse("S1G2081040G2081040G2081040G208105NK8PE5").unwrap();
+        let run = |optimize: bool| {
+            let mut store = MemoryBackingStore::new();
+            store.set_optimize_contracts(optimize);
+            let mut counter = EvalCounter::default();
+            let mut owned_env =
+                OwnedEnvironment::new(store.as_clarity_db(), StacksEpochId::Epoch21);
+            owned_env
+                .initialize_contract(contract_id.clone(), contract, None, ASTRules::PrecheckSize)
+                .unwrap();
+            owned_env.add_eval_hook(&mut counter);
+            let (result, ..) = owned_env
+                .execute_transaction(
+                    sender.clone(),
+                    None,
+                    contract_id.clone(),
+                    "pay",
+                    &[SymbolicExpression::atom_value(Value::UInt(5))],
+                )
+                .unwrap();
+            drop(owned_env);
+            (result, counter.evals)
+        };
+
+        let (result, evals) = run(false);
+        let (optimized_result, optimized_evals) = run(true);
+        assert_eq!(result, Value::okay(Value::UInt(25)).unwrap());
+        assert_eq!(optimized_result, result);
+        assert!(optimized_evals < evals);
+    }
+}
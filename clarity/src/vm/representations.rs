@@ -184,8 +184,8 @@ pub enum PreSymbolicExpressionType {
 pub struct PreSymbolicExpression {
     pub pre_expr: PreSymbolicExpressionType,
     pub id: u64,
-
-    #[cfg(feature = "developer-mode")]
+    /// Always recorded, so that the source map of a contract can be built from its
+    ///  pre-expressions whether or not spans are kept on its `SymbolicExpression`s
     pub span: Span,
 }
 
@@ -224,7 +224,6 @@ impl SymbolicExpressionCommon for SymbolicExpression {
 }
 
 impl PreSymbolicExpression {
-    #[cfg(feature = "developer-mode")]
     fn cons() -> PreSymbolicExpression {
         PreSymbolicExpression {
             id: 0,
@@ -232,15 +231,7 @@ impl PreSymbolicExpression {
             pre_expr: PreSymbolicExpressionType::AtomValue(Value::Bool(false)),
         }
     }
-    #[cfg(not(feature = "developer-mode"))]
-    fn cons() -> PreSymbolicExpression {
-        PreSymbolicExpression {
-            id: 0,
-            pre_expr: PreSymbolicExpressionType::AtomValue(Value::Bool(false)),
-        }
-    }
 
-    #[cfg(feature = "developer-mode")]
     pub fn set_span(&mut self, start_line: u32, start_column: u32, end_line: u32, end_column: u32) {
         self.span = Span {
             start_line,
@@ -250,34 +241,14 @@ impl PreSymbolicExpression {
         }
     }
 
-    #[cfg(not(feature = "developer-mode"))]
-    pub fn set_span(
-        &mut self,
-        _start_line: u32,
-        _start_column: u32,
-        _end_line: u32,
-        _end_column: u32,
-    ) {
-    }
-
-    #[cfg(feature = "developer-mode")]
     pub fn copy_span(&mut self, src: &Span) {
         self.span = src.clone();
     }
 
-    #[cfg(not(feature = "developer-mode"))]
-    pub fn copy_span(&mut self, _src: &Span) {}
-
-    #[cfg(feature = "developer-mode")]
     pub fn span(&self) -> &Span {
         &self.span
     }
 
-    #[cfg(not(feature = "developer-mode"))]
-    pub fn span(&self) -> &Span {
-        &Span::ZERO
-    }
-
     pub fn sugared_contract_identifier(val: ContractName) -> PreSymbolicExpression {
         PreSymbolicExpression {
             pre_expr: PreSymbolicExpressionType::SugaredContractIdentifier(val),
@@ -19,9 +19,12 @@ use rstest::rstest;
 use stacks_common::types::chainstate::BlockHeaderHash;
 use stacks_common::types::StacksEpochId;
 
+use crate::vm::analysis::run_analysis;
 use crate::vm::ast::errors::ParseErrors;
-use crate::vm::ast::ASTRules;
-use crate::vm::contexts::Environment;
+use crate::vm::ast::{build_ast, ASTRules};
+use crate::vm::contexts::{Environment, OwnedEnvironment};
+use crate::vm::costs::LimitedCostTracker;
+use crate::vm::database::MemoryBackingStore;
 use crate::vm::errors::{CheckErrors, Error, RuntimeErrorType};
 use crate::vm::tests::{
     env_factory, execute, is_committed, is_err_code_i128 as is_err_code, symbols_from_values,
@@ -32,7 +35,10 @@ use crate::vm::types::{
     OptionalData, PrincipalData, QualifiedContractIdentifier, ResponseData, StandardPrincipalData,
     TypeSignature, Value,
 };
-use crate::vm::{execute as vm_execute, ClarityVersion, ContractContext};
+use crate::vm::{
+    execute as vm_execute, ClarityName, ClarityVersion, ContractContext, EvalHook, ExecutionResult,
+    LocalContext, SymbolicExpression,
+};
 
 const FACTORIAL_CONTRACT: &str = "(define-map factorials { id: int } { current: int, index: int })
          (define-private (init-factorial (id int) (factorial int))
@@ -738,6 +744,122 @@ fn test_simple_contract_call(epoch: StacksEpochId, mut env_factory: MemoryEnviro
     }
 }
 
+/// Records the binding and contract-call callbacks it receives
+#[derive(Default)]
+struct RecordingHook {
+    events: Vec<String>,
+}
+
+impl EvalHook for RecordingHook {
+    fn will_begin_eval(
+        &mut self,
+        _env: &mut Environment,
+        _context: &LocalContext,
+        _expr: &SymbolicExpression,
+    ) {
+    }
+
+    fn did_finish_eval(
+        &mut self,
+        _env: &mut Environment,
+        _context: &LocalContext,
+        _expr: &SymbolicExpression,
+        _res: &core::result::Result<Value, Error>,
+    ) {
+    }
+
+    fn did_complete(&mut self, _result: core::result::Result<&mut ExecutionResult, String>) {}
+
+    fn did_bind_variable(&mut self, _env: &mut Environment, name: &ClarityName, _value: &Value) {
+        self.events.push(format!("bind {name}"));
+    }
+
+    fn will_call_contract(
+        &mut self,
+        _env: &mut Environment,
+        contract: &QualifiedContractIdentifier,
+        function: &str,
+        _args: &[Value],
+    ) {
+        self.events
+            .push(format!("call {}.{function}", contract.name));
+    }
+
+    fn did_finish_contract_call(
+        &mut self,
+        _env: &mut Environment,
+        contract: &QualifiedContractIdentifier,
+        function: &str,
+        res: &core::result::Result<Value, Error>,
+    ) {
+        let res = res.as_ref().expect("contract call failed");
+        self.events
+            .push(format!("return {}.{function} {res}", contract.name));
+    }
+}
+
+#[apply(test_epochs)]
+fn test_eval_hooks(epoch: StacksEpochId, mut env_factory: MemoryEnvironmentGenerator) {
+    let contract_2 = "(define-public (proxy-compute)
+            (let ((id 8008))
+              (contract-call? .factorial-contract compute id)))";
+
+    let mut hook = RecordingHook::default();
+    {
+        let mut owned_env = env_factory.get_env(epoch);
+        owned_env.add_eval_hook(&mut hook);
+
+        let mut placeholder_context = ContractContext::new(
+            QualifiedContractIdentifier::transient(),
+            ClarityVersion::Clarity2,
+        );
+        let mut env = owned_env.get_exec_environment(
+            Some(get_principal().expect_principal().unwrap()),
+            None,
+            &mut placeholder_context,
+        );
+        env.initialize_contract(
+            QualifiedContractIdentifier::local("factorial-contract").unwrap(),
+            FACTORIAL_CONTRACT,
+            ASTRules::PrecheckSize,
+        )
+        .unwrap();
+        env.initialize_contract(
+            QualifiedContractIdentifier::local("proxy-compute").unwrap(),
+            contract_2,
+            ASTRules::PrecheckSize,
+        )
+        .unwrap();
+        env.execute_contract(
+            &QualifiedContractIdentifier::local("proxy-compute").unwrap(),
+            "proxy-compute",
+            &[],
+            false,
+        )
+        .unwrap();
+    }
+
+    assert_eq!(
+        hook.events,
+        vec![
+            // deploying the factorial contract calls `init-factorial` twice
+            "bind id",
+            "bind factorial",
+            "bind id",
+            "bind factorial",
+            "call proxy-compute.proxy-compute",
+            "bind id",
+            "call factorial-contract.compute",
+            "bind id",
+            "bind entry",
+            "bind current",
+            "bind index",
+            "return factorial-contract.compute (ok false)",
+            "return proxy-compute.proxy-compute (ok false)",
+        ]
+    );
+}
+
 #[apply(test_epochs)]
 fn test_aborts(epoch: StacksEpochId, mut env_factory: MemoryEnvironmentGenerator) {
     let mut owned_env = env_factory.get_env(epoch);
@@ -1183,3 +1305,63 @@ fn test_eval_with_non_existing_contract(
     owned_env.commit().unwrap();
     assert!(owned_env.destruct().is_some());
 }
+
+#[test]
+fn test_error_location() {
+    let epoch = StacksEpochId::Epoch21;
+    let version = ClarityVersion::Clarity1;
+    let contract_id = QualifiedContractIdentifier::local("divider").unwrap();
+    let contract = "(define-read-only (divide (a uint) (b uint))
+  (ok (/ a b)))";
+
+    let mut store = MemoryBackingStore::new();
+    let mut owned_env = OwnedEnvironment::new(store.as_clarity_db(), epoch);
+    owned_env
+        .initialize_contract(contract_id.clone(), contract, None, ASTRules::PrecheckSize)
+        .unwrap();
+    drop(owned_env);
+
+    let contract_ast = build_ast(&contract_id, contract, &mut (), version, epoch).unwrap();
+    let mut analysis_db = store.as_analysis_db();
+    let mut analysis = run_analysis(
+        &contract_id,
+        &contract_ast.expressions,
+        &mut analysis_db,
+        false,
+        LimitedCostTracker::new_free(),
+        epoch,
+        version,
+        false,
+    )
+    .unwrap();
+    analysis.source_map = contract_ast.source_map.clone();
+    analysis_db
+        .execute(|db| db.insert_contract(&contract_id, &analysis))
+        .unwrap();
+    drop(analysis_db);
+
+    let mut owned_env = OwnedEnvironment::new(store.as_clarity_db(), epoch);
+    let divide = |owned_env: &mut OwnedEnvironment, b: u128| {
+        owned_env.execute_transaction(
+            get_principal_as_principal_data(),
+            None,
+            contract_id.clone(),
+            "divide",
+            &symbols_from_values(vec![Value::UInt(1), Value::UInt(b)]),
+        )
+    };
+    assert_eq!(
+        divide(&mut owned_env, 0).unwrap_err(),
+        RuntimeErrorType::DivisionByZero.into()
+    );
+    // the innermost expression that failed was `(/ a b)`
+    let location = owned_env.error_location().unwrap();
+    assert_eq!(location.contract_identifier, contract_id);
+    let span = location.span.as_ref().unwrap();
+    assert_eq!((span.start_line, span.start_column), (2, 7));
+    assert_eq!(location.to_string(), format!("{contract_id}:2:7"));
+
+    // the location is forgotten when the next execution begins
+    divide(&mut owned_env, 1).unwrap();
+    assert_eq!(owned_env.error_location(), None);
+}
@@ -98,6 +98,14 @@ macro_rules! clarity_template {
                 (StacksEpochId::Epoch23, ClarityVersion::Clarity3) => (),
                 (StacksEpochId::Epoch24, ClarityVersion::Clarity3) => (),
                 (StacksEpochId::Epoch25, ClarityVersion::Clarity3) => (),
+                (StacksEpochId::Epoch20, ClarityVersion::Clarity4) => (),
+                (StacksEpochId::Epoch2_05, ClarityVersion::Clarity4) => (),
+                (StacksEpochId::Epoch21, ClarityVersion::Clarity4) => (),
+                (StacksEpochId::Epoch22, ClarityVersion::Clarity4) => (),
+                (StacksEpochId::Epoch23, ClarityVersion::Clarity4) => (),
+                (StacksEpochId::Epoch24, ClarityVersion::Clarity4) => (),
+                (StacksEpochId::Epoch25, ClarityVersion::Clarity4) => (),
+                (StacksEpochId::Epoch30, ClarityVersion::Clarity4) => (),
                 // this will lead to a compile time failure if a pair is left out
                 //  of the clarity_template! macro list
                 $((StacksEpochId::$epoch, ClarityVersion::$clarity))|* => (),
@@ -122,6 +130,7 @@ epochs_template! {
     Epoch24,
     Epoch25,
     Epoch30,
+    Epoch31,
 }
 
 clarity_template! {
@@ -140,6 +149,10 @@ clarity_template! {
     (Epoch30, Clarity1),
     (Epoch30, Clarity2),
     (Epoch30, Clarity3),
+    (Epoch31, Clarity1),
+    (Epoch31, Clarity2),
+    (Epoch31, Clarity3),
+    (Epoch31, Clarity4),
 }
 
 #[cfg(test)]
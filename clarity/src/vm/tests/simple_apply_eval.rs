@@ -472,6 +472,146 @@ fn test_secp256k1() {
         .for_each(|(program, expectation)| assert_eq!(expectation.clone(), execute(program)));
 }
 
+#[test]
+fn test_secp256k1_verify_many() {
+    let program = "(secp256k1-verify-many (list
+        { message-hash: 0xde5b9eb9e7c5592930eb2e30a01369c36586d872082ed8181ee83d2a0ec20f04, signature: 0x8738487ebe69b93d8e51583be8eee50bb4213fc49c767d329632730cc193b873554428fc936ca3569afc15f1c9365f6591d6251a89fee9c9ac661116824d3a1301, public-key: 0x03adb8de4bfb65db2cfd6120d55c6526ae9c52e675db7e47308636534ba7786110 }
+        { message-hash: 0xde5b9eb9e7c5592930eb2e30a01369c36586d872082ed8181ee83d2a0ec20f04, signature: 0x8738487ebe69b93d8e51583be8eee50bb4213fc49c767d329632730cc193b873554428fc936ca3569afc15f1c9365f6591d6251a89fee9c9ac661116824d3a13, public-key: 0x03adb8de4bfb65db2cfd6120d55c6526ae9c52e675db7e47308636534ba7786110 }
+        { message-hash: 0x0000000000000000000000000000000000000000000000000000000000000000, signature: 0x0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000, public-key: 0x03adb8de4bfb65db2cfd6120d55c6526ae9c52e675db7e47308636534ba7786110 }
+        { message-hash: 0xde5b9eb9e7c5592930eb2e30a01369c36586d872082ed8181ee83d2a0ec20f04, signature: 0x8738487ebe69b93d8e51583be8eee50bb4213fc49c767d329632730cc193b873554428fc936ca3569afc15f1c9365f6591d6251a89fee9c9ac661116824d3a1305, public-key: 0x03adb8de4bfb65db2cfd6120d55c6526ae9c52e675db7e47308636534ba7786110 }))";
+
+    let expected = Value::cons_list_unsanitized(vec![
+        Value::Bool(true),
+        Value::Bool(true),
+        Value::Bool(false),
+        Value::Bool(false),
+    ])
+    .unwrap();
+    assert_eq!(
+        Some(expected),
+        execute_with_parameters(
+            program,
+            ClarityVersion::Clarity4,
+            StacksEpochId::Epoch31,
+            ASTRules::PrecheckSize,
+            false
+        )
+        .unwrap()
+    );
+
+    // not available before Clarity 4
+    assert!(execute_with_parameters(
+        program,
+        ClarityVersion::Clarity3,
+        StacksEpochId::Epoch31,
+        ASTRules::PrecheckSize,
+        false
+    )
+    .is_err());
+}
+
+#[test]
+fn test_buff_bitwise() {
+    let tests = [
+        ("(buff-and 0xff0f 0x0ff0)", "0f00"),
+        ("(buff-or 0xff00 0x00ff)", "ffff"),
+        ("(buff-xor 0xffff 0x0ff0)", "f00f"),
+        ("(buff-not 0x00ff)", "ff00"),
+        // the shorter buffer is padded with leading zeros
+        ("(buff-and 0x1234 0xff)", "0034"),
+        ("(buff-or 0xff 0x1234)", "12ff"),
+        ("(buff-xor 0x1234 0xff)", "12cb"),
+        ("(buff-and 0x 0x1234)", "0000"),
+        ("(buff-not 0x)", ""),
+    ];
+    for (program, expected) in tests.iter() {
+        assert_eq!(
+            Some(Value::buff_from(hex_bytes(expected).unwrap()).unwrap()),
+            execute_with_parameters(
+                program,
+                ClarityVersion::Clarity4,
+                StacksEpochId::Epoch31,
+                ASTRules::PrecheckSize,
+                false
+            )
+            .unwrap()
+        );
+    }
+
+    // not available before Clarity 4
+    assert!(execute_with_parameters(
+        "(buff-not 0x00ff)",
+        ClarityVersion::Clarity3,
+        StacksEpochId::Epoch31,
+        ASTRules::PrecheckSize,
+        false
+    )
+    .is_err());
+}
+
+#[test]
+fn test_alt_bn128() {
+    let g1 = "0x00000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000002";
+    let g1_neg = "0x000000000000000000000000000000000000000000000000000000000000000130644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45";
+    let g2 = "0x198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c21800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa";
+    let double = "030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd315ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4";
+    let double = Value::okay(Value::buff_from(hex_bytes(double).unwrap()).unwrap()).unwrap();
+    let two = format!("0x{}02", "0".repeat(62));
+    let off_curve = format!("{}3", &g1[..g1.len() - 1]);
+
+    let tests = [
+        (format!("(alt-bn128-add? {g1} {g1})"), double.clone()),
+        (format!("(alt-bn128-mul? {g1} {two})"), double),
+        (
+            format!("(alt-bn128-add? {g1} {g1_neg})"),
+            Value::okay(Value::buff_from(vec![0; 64]).unwrap()).unwrap(),
+        ),
+        (format!("(alt-bn128-add? {g1} 0x01)"), Value::err_uint(1)),
+        (format!("(alt-bn128-mul? {g1} 0x02)"), Value::err_uint(1)),
+        (format!("(alt-bn128-add? {g1} {off_curve})"), Value::err_uint(2)),
+        (
+            format!("(alt-bn128-pairing-check? (list {{ g1: {g1}, g2: {g2} }} {{ g1: {g1_neg}, g2: {g2} }}))"),
+            Value::okay_true(),
+        ),
+        (
+            format!("(alt-bn128-pairing-check? (list {{ g1: {g1}, g2: {g2} }}))"),
+            Value::okay(Value::Bool(false)).unwrap(),
+        ),
+        (
+            format!("(alt-bn128-pairing-check? (list {{ g1: {off_curve}, g2: {g2} }}))"),
+            Value::err_uint(2),
+        ),
+        (
+            "(alt-bn128-pairing-check? (list))".to_string(),
+            Value::okay_true(),
+        ),
+    ];
+    for (program, expected) in tests.iter() {
+        assert_eq!(
+            Some(expected.clone()),
+            execute_with_parameters(
+                program,
+                ClarityVersion::Clarity4,
+                StacksEpochId::Epoch31,
+                ASTRules::PrecheckSize,
+                false
+            )
+            .unwrap(),
+            "{program}"
+        );
+    }
+
+    // not available before Clarity 4
+    assert!(execute_with_parameters(
+        &format!("(alt-bn128-add? {g1} {g1})"),
+        ClarityVersion::Clarity3,
+        StacksEpochId::Epoch31,
+        ASTRules::PrecheckSize,
+        false
+    )
+    .is_err());
+}
+
 #[test]
 fn test_principal_of_fix() {
     // There is a bug with principal-of in Clarity1. The address returned is always testnet. In Clarity2, we fix this.
@@ -17,7 +17,11 @@
 use stacks_common::types::StacksEpochId;
 
 use super::MemoryEnvironmentGenerator;
-use crate::vm::ast::ASTRules;
+#[cfg(test)]
+use crate::vm::analysis::type_check;
+use crate::vm::ast::{parse, ASTRules};
+use crate::vm::contexts::OwnedEnvironment;
+use crate::vm::database::MemoryBackingStore;
 use crate::vm::errors::{CheckErrors, Error};
 use crate::vm::tests::{
     env_factory, execute, symbols_from_values, test_clarity_versions, test_epochs,
@@ -2145,3 +2149,146 @@ fn test_pass_principal_literal_to_trait(
         );
     }
 }
+
+#[test]
+fn test_implements_trait() {
+    let epoch = StacksEpochId::Epoch31;
+    let version = ClarityVersion::Clarity4;
+    let contracts = [
+        (
+            "defining-trait",
+            "(define-trait trait-1 ((get-1 (uint) (response uint uint))))",
+        ),
+        (
+            "implementing-contract",
+            "(impl-trait .defining-trait.trait-1)
+            (define-public (get-1 (x uint)) (ok x))",
+        ),
+        // has the trait's function, but doesn't declare the trait
+        (
+            "conforming-contract",
+            "(define-public (get-1 (x uint)) (ok x))",
+        ),
+        (
+            "checking-contract",
+            "(use-trait trait-1 .defining-trait.trait-1)
+            (define-read-only (check (who principal))
+                (implements-trait? who .defining-trait.trait-1))
+            (define-read-only (check-reference (who principal))
+                (implements-trait? who <trait-1>))
+            (define-read-only (check-callable (contract <trait-1>))
+                (implements-trait? contract <trait-1>))",
+        ),
+    ];
+
+    // the analyses are stored with the contracts, so each contract is deployed before it is
+    //  analyzed
+    let mut store = MemoryBackingStore::new();
+    for (name, src) in contracts.iter() {
+        let contract_id = QualifiedContractIdentifier::local(name).unwrap();
+        OwnedEnvironment::new(store.as_clarity_db(), epoch)
+            .initialize_versioned_contract(
+                contract_id.clone(),
+                version,
+                src,
+                None,
+                ASTRules::PrecheckSize,
+            )
+            .unwrap();
+        let mut expressions = parse(&contract_id, src, version, epoch).unwrap();
+        type_check(
+            &contract_id,
+            &mut expressions,
+            &mut store.as_analysis_db(),
+            true,
+            &epoch,
+            &version,
+        )
+        .unwrap();
+    }
+
+    let mut owned_env = OwnedEnvironment::new(store.as_clarity_db(), epoch);
+
+    let checking_contract = QualifiedContractIdentifier::local("checking-contract").unwrap();
+    let sender = execute("'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR")
+        .expect_principal()
+        .unwrap();
+    let mut check = |function: &str, who: Value| {
+        owned_env
+            .execute_transaction(
+                sender.clone(),
+                None,
+                checking_contract.clone(),
+                function,
+                &symbols_from_values(vec![who]),
+            )
+            .unwrap()
+            .0
+    };
+    let contract = |name: &str| {
+        Value::from(PrincipalData::Contract(
+            QualifiedContractIdentifier::local(name).unwrap(),
+        ))
+    };
+
+    for function in ["check", "check-reference"] {
+        assert_eq!(
+            check(function, contract("implementing-contract")),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            check(function, contract("conforming-contract")),
+            Value::Bool(false)
+        );
+        assert_eq!(
+            check(function, contract("no-such-contract")),
+            Value::Bool(false)
+        );
+        assert_eq!(
+            check(function, Value::from(sender.clone())),
+            Value::Bool(false)
+        );
+    }
+    assert_eq!(
+        check("check-callable", contract("implementing-contract")),
+        Value::Bool(true)
+    );
+    assert_eq!(
+        check("check-callable", contract("conforming-contract")),
+        Value::Bool(false)
+    );
+}
+
+#[test]
+fn test_implements_trait_unknown_trait() {
+    let epoch = StacksEpochId::Epoch31;
+    let contract_id = QualifiedContractIdentifier::local("checking-contract").unwrap();
+    let src = "(define-read-only (check (who principal))
+        (implements-trait? who .no-such-contract.trait-1))";
+
+    let mut store = MemoryBackingStore::new();
+    let mut expressions = parse(&contract_id, src, ClarityVersion::Clarity4, epoch).unwrap();
+    let err = type_check(
+        &contract_id,
+        &mut expressions,
+        &mut store.as_analysis_db(),
+        false,
+        &epoch,
+        &ClarityVersion::Clarity4,
+    )
+    .unwrap_err();
+    assert!(matches!(err.err, CheckErrors::NoSuchContract(_)));
+
+    // not available before Clarity 4
+    let mut expressions = parse(&contract_id, src, ClarityVersion::Clarity3, epoch).unwrap();
+    let err = type_check(
+        &contract_id,
+        &mut expressions,
+        &mut store.as_analysis_db(),
+        false,
+        &epoch,
+        &ClarityVersion::Clarity3,
+    )
+    .unwrap_err();
+    assert!(matches!(err.err, CheckErrors::UnknownFunction(_)));
+}
@@ -0,0 +1,491 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! An `EvalHook` that records what the interpreter did: each contract function called and what
+//! it returned, each function application evaluated and its result, each variable bound, and
+//! each asset moved and map entry written.
+//!
+//! A tracer can be cloned, and the clones share the same trace, so one clone can be handed to
+//! the VM while another is kept to read the trace afterwards.  `write_json_lines()` writes the
+//! trace out as one JSON object per line, e.g. for `clarity-cli execute --trace`.
+
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::vm::contexts::{Environment, LocalContext};
+use crate::vm::errors::Error;
+use crate::vm::events::{FTEventType, NFTEventType, STXEventType, StacksTransactionEvent};
+use crate::vm::representations::{ClarityName, SymbolicExpression};
+use crate::vm::types::QualifiedContractIdentifier;
+use crate::vm::{EvalHook, ExecutionResult, Value};
+
+/// One step of a trace.  `depth` is the nesting depth of function applications.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TraceEntry {
+    /// A public or read-only contract function was called
+    ContractCall {
+        depth: u32,
+        contract: String,
+        function: String,
+        args: Vec<String>,
+    },
+    /// A contract function returned (`result`) or failed (`error`)
+    ContractReturn {
+        depth: u32,
+        contract: String,
+        function: String,
+        result: Option<String>,
+        error: Option<String>,
+    },
+    /// A function application was evaluated
+    Eval {
+        depth: u32,
+        contract: String,
+        expr_id: u64,
+        line: u32,
+        function: String,
+        result: Option<String>,
+        error: Option<String>,
+    },
+    /// A variable was bound by a `let`, or to a function argument
+    Bind {
+        depth: u32,
+        name: String,
+        value: String,
+    },
+    /// STX or a token was transferred, minted, burned or (for STX) locked.  `asset` is `stx`
+    /// or the token's identifier; `value` is the id of a non-fungible token.
+    AssetMove {
+        depth: u32,
+        asset: String,
+        action: String,
+        sender: Option<String>,
+        recipient: Option<String>,
+        amount: Option<String>,
+        value: Option<String>,
+    },
+    /// A map entry was set or inserted, or deleted (`value` is `None`)
+    MapWrite {
+        depth: u32,
+        contract: String,
+        map: String,
+        key: String,
+        value: Option<String>,
+    },
+}
+
+/// The `AssetMove` entry for `event`, if it moved an asset
+fn asset_move(depth: u32, event: &StacksTransactionEvent) -> Option<TraceEntry> {
+    let stx = || "stx".to_string();
+    let (asset, action, sender, recipient, amount, value) = match event {
+        StacksTransactionEvent::STXEvent(STXEventType::STXTransferEvent(data)) => (
+            stx(),
+            "transfer",
+            Some(data.sender.to_string()),
+            Some(data.recipient.to_string()),
+            Some(data.amount.to_string()),
+            None,
+        ),
+        StacksTransactionEvent::STXEvent(STXEventType::STXMintEvent(data)) => (
+            stx(),
+            "mint",
+            None,
+            Some(data.recipient.to_string()),
+            Some(data.amount.to_string()),
+            None,
+        ),
+        StacksTransactionEvent::STXEvent(STXEventType::STXBurnEvent(data)) => (
+            stx(),
+            "burn",
+            Some(data.sender.to_string()),
+            None,
+            Some(data.amount.to_string()),
+            None,
+        ),
+        StacksTransactionEvent::STXEvent(STXEventType::STXLockEvent(data)) => (
+            stx(),
+            "lock",
+            Some(data.locked_address.to_string()),
+            None,
+            Some(data.locked_amount.to_string()),
+            None,
+        ),
+        StacksTransactionEvent::FTEvent(FTEventType::FTTransferEvent(data)) => (
+            data.asset_identifier.to_string(),
+            "transfer",
+            Some(data.sender.to_string()),
+            Some(data.recipient.to_string()),
+            Some(data.amount.to_string()),
+            None,
+        ),
+        StacksTransactionEvent::FTEvent(FTEventType::FTMintEvent(data)) => (
+            data.asset_identifier.to_string(),
+            "mint",
+            None,
+            Some(data.recipient.to_string()),
+            Some(data.amount.to_string()),
+            None,
+        ),
+        StacksTransactionEvent::FTEvent(FTEventType::FTBurnEvent(data)) => (
+            data.asset_identifier.to_string(),
+            "burn",
+            Some(data.sender.to_string()),
+            None,
+            Some(data.amount.to_string()),
+            None,
+        ),
+        StacksTransactionEvent::NFTEvent(NFTEventType::NFTTransferEvent(data)) => (
+            data.asset_identifier.to_string(),
+            "transfer",
+            Some(data.sender.to_string()),
+            Some(data.recipient.to_string()),
+            None,
+            Some(data.value.to_string()),
+        ),
+        StacksTransactionEvent::NFTEvent(NFTEventType::NFTMintEvent(data)) => (
+            data.asset_identifier.to_string(),
+            "mint",
+            None,
+            Some(data.recipient.to_string()),
+            None,
+            Some(data.value.to_string()),
+        ),
+        StacksTransactionEvent::NFTEvent(NFTEventType::NFTBurnEvent(data)) => (
+            data.asset_identifier.to_string(),
+            "burn",
+            Some(data.sender.to_string()),
+            None,
+            None,
+            Some(data.value.to_string()),
+        ),
+        StacksTransactionEvent::SmartContractEvent(_)
+        | StacksTransactionEvent::ContractEvent(_) => return None,
+    };
+    Some(TraceEntry::AssetMove {
+        depth,
+        asset,
+        action: action.to_string(),
+        sender,
+        recipient,
+        amount,
+        value,
+    })
+}
+
+#[derive(Debug, Default)]
+struct TraceState {
+    entries: Vec<TraceEntry>,
+    depth: u32,
+}
+
+/// Records a trace of execution
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionTracer {
+    state: Arc<Mutex<TraceState>>,
+}
+
+fn split_result(res: &Result<Value, Error>) -> (Option<String>, Option<String>) {
+    match res {
+        Ok(value) => (Some(value.to_string()), None),
+        Err(e) => (None, Some(e.to_string())),
+    }
+}
+
+impl ExecutionTracer {
+    pub fn new() -> ExecutionTracer {
+        ExecutionTracer::default()
+    }
+
+    /// The trace recorded so far, by this tracer and its clones
+    pub fn entries(&self) -> Vec<TraceEntry> {
+        match self.state.lock() {
+            Ok(state) => state.entries.clone(),
+            Err(_) => vec![],
+        }
+    }
+
+    /// Write the trace recorded so far as JSON lines, one entry per line
+    pub fn write_json_lines<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        for entry in self.entries() {
+            serde_json::to_writer(&mut *out, &entry)?;
+            out.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    fn with_state<F: FnOnce(&mut TraceState)>(&self, f: F) {
+        // a poisoned trace is incomplete, but there's no reason to fail execution over it
+        if let Ok(mut state) = self.state.lock() {
+            f(&mut state)
+        }
+    }
+}
+
+impl EvalHook for ExecutionTracer {
+    fn will_begin_eval(
+        &mut self,
+        _env: &mut Environment,
+        _context: &LocalContext,
+        expr: &SymbolicExpression,
+    ) {
+        if expr.match_list().is_some() {
+            self.with_state(|state| state.depth = state.depth.saturating_add(1));
+        }
+    }
+
+    fn did_finish_eval(
+        &mut self,
+        env: &mut Environment,
+        _context: &LocalContext,
+        expr: &SymbolicExpression,
+        res: &Result<Value, Error>,
+    ) {
+        let Some(list) = expr.match_list() else {
+            return;
+        };
+        let function = list
+            .first()
+            .and_then(|head| head.match_atom())
+            .map(|name| name.to_string())
+            .unwrap_or_default();
+        let (result, error) = split_result(res);
+        let contract = env.contract_context.contract_identifier.to_string();
+        self.with_state(|state| {
+            state.entries.push(TraceEntry::Eval {
+                depth: state.depth,
+                contract,
+                expr_id: expr.id,
+                line: expr.span().start_line,
+                function,
+                result,
+                error,
+            });
+            state.depth = state.depth.saturating_sub(1);
+        });
+    }
+
+    fn did_complete(&mut self, _result: Result<&mut ExecutionResult, String>) {}
+
+    fn did_bind_variable(&mut self, _env: &mut Environment, name: &ClarityName, value: &Value) {
+        self.with_state(|state| {
+            state.entries.push(TraceEntry::Bind {
+                depth: state.depth,
+                name: name.to_string(),
+                value: value.to_string(),
+            })
+        });
+    }
+
+    fn will_call_contract(
+        &mut self,
+        _env: &mut Environment,
+        contract: &QualifiedContractIdentifier,
+        function: &str,
+        args: &[Value],
+    ) {
+        self.with_state(|state| {
+            state.entries.push(TraceEntry::ContractCall {
+                depth: state.depth,
+                contract: contract.to_string(),
+                function: function.to_string(),
+                args: args.iter().map(|arg| arg.to_string()).collect(),
+            })
+        });
+    }
+
+    fn did_finish_contract_call(
+        &mut self,
+        _env: &mut Environment,
+        contract: &QualifiedContractIdentifier,
+        function: &str,
+        res: &Result<Value, Error>,
+    ) {
+        let (result, error) = split_result(res);
+        self.with_state(|state| {
+            state.entries.push(TraceEntry::ContractReturn {
+                depth: state.depth,
+                contract: contract.to_string(),
+                function: function.to_string(),
+                result,
+                error,
+            })
+        });
+    }
+
+    fn did_register_event(&mut self, _env: &mut Environment, event: &StacksTransactionEvent) {
+        self.with_state(|state| {
+            if let Some(entry) = asset_move(state.depth, event) {
+                state.entries.push(entry);
+            }
+        });
+    }
+
+    fn did_write_map(
+        &mut self,
+        env: &mut Environment,
+        map: &ClarityName,
+        key: &Value,
+        value: Option<&Value>,
+    ) {
+        let contract = env.contract_context.contract_identifier.to_string();
+        self.with_state(|state| {
+            state.entries.push(TraceEntry::MapWrite {
+                depth: state.depth,
+                contract,
+                map: map.to_string(),
+                key: key.to_string(),
+                value: value.map(|value| value.to_string()),
+            })
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stacks_common::types::StacksEpochId;
+
+    use super::*;
+    use crate::vm::ast::ASTRules;
+    use crate::vm::contexts::OwnedEnvironment;
+    use crate::vm::database::MemoryBackingStore;
+    use crate::vm::types::PrincipalData;
+
+    #[test]
+    fn test_execution_tracer() {
+        let contract_id = QualifiedContractIdentifier::local("foo").unwrap();
+        let mut store = MemoryBackingStore::new();
+        let mut owned_env = OwnedEnvironment::new(store.as_clarity_db(), StacksEpochId::Epoch21);
+        owned_env
+            .initialize_contract(
+                contract_id.clone(),
+                "(define-read-only (add-one (x int)) (let ((y (+ x 1))) y))",
+                None,
+                ASTRules::PrecheckSize,
+            )
+            .unwrap();
+
+        let tracer = ExecutionTracer::new();
+        let mut hook = tracer.clone();
+        owned_env.add_eval_hook(&mut hook);
+        let sender = PrincipalData::parse("S1G2081040G2081040G2081040G208105NK8PE5").unwrap();
+        let (result, _, _) = owned_env
+            .execute_transaction(
+                sender,
+                None,
+                contract_id.clone(),
+                "add-one",
+                &[SymbolicExpression::atom_value(Value::Int(1))],
+            )
+            .unwrap();
+        assert_eq!(result, Value::Int(2));
+        drop(owned_env);
+
+        let entries = tracer.entries();
+        let call = entries.first().unwrap();
+        assert_eq!(
+            call,
+            &TraceEntry::ContractCall {
+                depth: 0,
+                contract: contract_id.to_string(),
+                function: "add-one".into(),
+                args: vec!["1".into()],
+            }
+        );
+        assert!(entries.contains(&TraceEntry::Bind {
+            depth: 1,
+            name: "y".into(),
+            value: "2".into(),
+        }));
+        assert!(entries.iter().any(|entry| matches!(
+            entry,
+            TraceEntry::Eval { function, result: Some(result), depth: 2, .. }
+                if function == "+" && result == "2"
+        )));
+        assert!(matches!(
+            entries.last().unwrap(),
+            TraceEntry::ContractReturn { depth: 0, result: Some(result), .. } if result == "2"
+        ));
+    }
+
+    #[test]
+    fn test_execution_tracer_writes() {
+        let contract_id = QualifiedContractIdentifier::local("foo").unwrap();
+        let mut store = MemoryBackingStore::new();
+        let mut owned_env = OwnedEnvironment::new(store.as_clarity_db(), StacksEpochId::Epoch21);
+        owned_env
+            .initialize_contract(
+                contract_id.clone(),
+                "(define-fungible-token tok)
+                 (define-map counts int int)
+                 (define-public (go (x int))
+                   (begin
+                     (map-set counts x (+ x 1))
+                     (map-delete counts (+ x 1))
+                     (ft-mint? tok u10 tx-sender)))",
+                None,
+                ASTRules::PrecheckSize,
+            )
+            .unwrap();
+
+        let tracer = ExecutionTracer::new();
+        let mut hook = tracer.clone();
+        owned_env.add_eval_hook(&mut hook);
+        let sender = PrincipalData::parse("S1G2081040G2081040G2081040G208105NK8PE5").unwrap();
+        owned_env
+            .execute_transaction(
+                sender.clone(),
+                None,
+                contract_id.clone(),
+                "go",
+                &[SymbolicExpression::atom_value(Value::Int(1))],
+            )
+            .unwrap();
+        drop(owned_env);
+
+        let entries = tracer.entries();
+        assert!(entries.contains(&TraceEntry::MapWrite {
+            depth: 2,
+            contract: contract_id.to_string(),
+            map: "counts".into(),
+            key: "1".into(),
+            value: Some("2".into()),
+        }));
+        // nothing was deleted, so nothing was written
+        assert!(!entries
+            .iter()
+            .any(|entry| matches!(entry, TraceEntry::MapWrite { value: None, .. })));
+        assert!(entries.contains(&TraceEntry::AssetMove {
+            depth: 2,
+            asset: format!("{contract_id}::tok"),
+            action: "mint".into(),
+            sender: None,
+            recipient: Some(sender.to_string()),
+            amount: Some("10".into()),
+            value: None,
+        }));
+
+        let mut out = vec![];
+        tracer.write_json_lines(&mut out).unwrap();
+        let lines: Vec<TraceEntry> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines, entries);
+    }
+}
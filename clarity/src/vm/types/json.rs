@@ -0,0 +1,363 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! JSON representation of Clarity values, for clients that construct or inspect values without
+//! implementing the consensus serialization.
+//!
+//! | Clarity type                 | JSON                                               |
+//! |------------------------------|----------------------------------------------------|
+//! | `int`, `uint`                | decimal string, e.g. `"-12"`                       |
+//! | `bool`                       | `true` or `false`                                  |
+//! | `(buff n)`                   | hex string with a `0x` prefix, e.g. `"0x00ff"`     |
+//! | `(string-ascii n)`           | string                                             |
+//! | `(string-utf8 n)`            | string                                             |
+//! | `principal`, trait reference | principal string, e.g. `"SP000...000.pox-4"`       |
+//! | `(list n t)`                 | array                                              |
+//! | `(tuple ...)`                | object, with one member per field                  |
+//! | `(optional t)`               | `null` for `none`, otherwise the value             |
+//! | `(response t e)`             | `{"ok": value}` or `{"err": value}`                |
+//!
+//! Integers are strings because they are 128 bits wide, but JSON integers are accepted too.  A
+//! buffer's `0x` prefix is optional.  Since `null` would be ambiguous for a nested optional, a
+//! `some` whose value is itself an optional is written as `{"some": value}`.
+//!
+//! The representation is untyped, so reading a value requires its type.
+
+use serde_json::{Map, Value as JSONValue};
+use stacks_common::types::StacksEpochId;
+use stacks_common::util::hash::{hex_bytes, to_hex};
+
+use super::serialization::SerializationError;
+use crate::vm::types::signatures::CallableSubtype;
+use crate::vm::types::{
+    CallableData, CharType, OptionalData, PrincipalData, ResponseData, SequenceData,
+    SequenceSubtype, StringSubtype, TupleData, TypeSignature, Value,
+};
+
+/// The epoch whose typing rules values read from JSON are checked against
+const JSON_TYPE_CHECK_EPOCH: StacksEpochId = StacksEpochId::Epoch21;
+
+/// A value that can't be constructed
+fn bad_value(e: impl std::fmt::Display) -> SerializationError {
+    SerializationError::DeserializationError(e.to_string())
+}
+
+/// The single member of a `{"<tag>": value}` object, and its tag
+fn tagged(json: &JSONValue) -> Option<(&str, &JSONValue)> {
+    let object = json.as_object()?;
+    if object.len() != 1 {
+        return None;
+    }
+    object
+        .iter()
+        .next()
+        .map(|(tag, value)| (tag.as_str(), value))
+}
+
+fn integer_from_json<T: std::str::FromStr>(json: &JSONValue) -> Option<T> {
+    match json {
+        JSONValue::String(s) => s.parse().ok(),
+        // only integers print without a decimal point or exponent
+        JSONValue::Number(n) => n.to_string().parse().ok(),
+        _ => None,
+    }
+}
+
+impl Value {
+    /// The JSON representation of this value
+    pub fn to_json(&self) -> JSONValue {
+        match self {
+            Value::Int(i) => JSONValue::String(i.to_string()),
+            Value::UInt(u) => JSONValue::String(u.to_string()),
+            Value::Bool(b) => JSONValue::Bool(*b),
+            Value::Sequence(SequenceData::Buffer(buff)) => {
+                JSONValue::String(format!("0x{}", to_hex(&buff.data)))
+            }
+            Value::Sequence(SequenceData::String(CharType::ASCII(s))) => {
+                JSONValue::String(String::from_utf8_lossy(&s.data).into_owned())
+            }
+            Value::Sequence(SequenceData::String(CharType::UTF8(s))) => {
+                JSONValue::String(String::from_utf8_lossy(&s.data.concat()).into_owned())
+            }
+            Value::Sequence(SequenceData::List(list)) => {
+                JSONValue::Array(list.data.iter().map(Value::to_json).collect())
+            }
+            Value::Principal(principal) => JSONValue::String(principal.to_string()),
+            Value::CallableContract(CallableData {
+                contract_identifier,
+                ..
+            }) => JSONValue::String(contract_identifier.to_string()),
+            Value::Tuple(tuple) => JSONValue::Object(
+                tuple
+                    .data_map
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.to_json()))
+                    .collect(),
+            ),
+            Value::Optional(OptionalData { data: None }) => JSONValue::Null,
+            Value::Optional(OptionalData { data: Some(value) }) => match **value {
+                Value::Optional(_) => {
+                    let mut object = Map::new();
+                    object.insert("some".into(), value.to_json());
+                    JSONValue::Object(object)
+                }
+                _ => value.to_json(),
+            },
+            Value::Response(ResponseData { committed, data }) => {
+                let mut object = Map::new();
+                let tag = if *committed { "ok" } else { "err" };
+                object.insert(tag.into(), data.to_json());
+                JSONValue::Object(object)
+            }
+        }
+    }
+
+    /// Read a value of type `expected` from its JSON representation
+    pub fn try_from_json(
+        json: &JSONValue,
+        expected: &TypeSignature,
+    ) -> Result<Value, SerializationError> {
+        let unexpected = || SerializationError::DeserializeExpected(expected.clone());
+        let value = match expected {
+            TypeSignature::NoType => {
+                return Err(SerializationError::DeserializationError(
+                    "Cannot construct a value of no type".into(),
+                ))
+            }
+            TypeSignature::IntType => Value::Int(integer_from_json(json).ok_or_else(unexpected)?),
+            TypeSignature::UIntType => Value::UInt(integer_from_json(json).ok_or_else(unexpected)?),
+            TypeSignature::BoolType => Value::Bool(json.as_bool().ok_or_else(unexpected)?),
+            TypeSignature::SequenceType(SequenceSubtype::BufferType(_)) => {
+                let hex = json.as_str().ok_or_else(unexpected)?;
+                let bytes = hex_bytes(hex.strip_prefix("0x").unwrap_or(hex)).map_err(bad_value)?;
+                Value::buff_from(bytes).map_err(bad_value)?
+            }
+            TypeSignature::SequenceType(SequenceSubtype::StringType(StringSubtype::ASCII(_))) => {
+                let s = json.as_str().ok_or_else(unexpected)?;
+                Value::string_ascii_from_bytes(s.as_bytes().to_vec()).map_err(bad_value)?
+            }
+            TypeSignature::SequenceType(SequenceSubtype::StringType(StringSubtype::UTF8(_))) => {
+                let s = json.as_str().ok_or_else(unexpected)?;
+                Value::string_utf8_from_bytes(s.as_bytes().to_vec()).map_err(bad_value)?
+            }
+            TypeSignature::SequenceType(SequenceSubtype::ListType(list_type)) => {
+                let items = json
+                    .as_array()
+                    .ok_or_else(unexpected)?
+                    .iter()
+                    .map(|item| Value::try_from_json(item, list_type.get_list_item_type()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                return Value::list_with_type(&JSON_TYPE_CHECK_EPOCH, items, list_type.clone())
+                    .map_err(|_| unexpected());
+            }
+            TypeSignature::PrincipalType => {
+                let s = json.as_str().ok_or_else(unexpected)?;
+                Value::Principal(PrincipalData::parse(s).map_err(bad_value)?)
+            }
+            TypeSignature::CallableType(CallableSubtype::Trait(trait_identifier))
+            | TypeSignature::TraitReferenceType(trait_identifier) => {
+                let s = json.as_str().ok_or_else(unexpected)?;
+                let PrincipalData::Contract(contract_identifier) =
+                    PrincipalData::parse_qualified_contract_principal(s).map_err(bad_value)?
+                else {
+                    return Err(unexpected());
+                };
+                Value::CallableContract(CallableData {
+                    contract_identifier,
+                    trait_identifier: Some(trait_identifier.clone()),
+                })
+            }
+            TypeSignature::CallableType(CallableSubtype::Principal(_))
+            | TypeSignature::ListUnionType(_) => {
+                let s = json.as_str().ok_or_else(unexpected)?;
+                Value::Principal(
+                    PrincipalData::parse_qualified_contract_principal(s).map_err(bad_value)?,
+                )
+            }
+            TypeSignature::TupleType(tuple_type) => {
+                let object = json.as_object().ok_or_else(unexpected)?;
+                let type_map = tuple_type.get_type_map();
+                if object.len() != type_map.len() {
+                    return Err(unexpected());
+                }
+                let fields = type_map
+                    .iter()
+                    .map(|(name, field_type)| {
+                        let field = object.get(name.as_str()).ok_or_else(unexpected)?;
+                        Ok((name.clone(), Value::try_from_json(field, field_type)?))
+                    })
+                    .collect::<Result<Vec<_>, SerializationError>>()?;
+                return TupleData::from_data_typed(&JSON_TYPE_CHECK_EPOCH, fields, tuple_type)
+                    .map(Value::from)
+                    .map_err(|_| unexpected());
+            }
+            TypeSignature::OptionalType(inner_type) => {
+                if json.is_null() {
+                    return Ok(Value::none());
+                }
+                let inner = if let TypeSignature::OptionalType(_) = **inner_type {
+                    match tagged(json) {
+                        Some(("some", inner)) => inner,
+                        _ => return Err(unexpected()),
+                    }
+                } else {
+                    json
+                };
+                return Value::some(Value::try_from_json(inner, inner_type)?).map_err(bad_value);
+            }
+            TypeSignature::ResponseType(response_type) => {
+                let (ok_type, err_type) = response_type.as_ref();
+                return match tagged(json) {
+                    Some(("ok", inner)) => Value::okay(Value::try_from_json(inner, ok_type)?),
+                    Some(("err", inner)) => Value::error(Value::try_from_json(inner, err_type)?),
+                    _ => return Err(unexpected()),
+                }
+                .map_err(bad_value);
+            }
+        };
+        // check the bounds of the type, e.g. the length of a buffer
+        if !expected
+            .admits(&JSON_TYPE_CHECK_EPOCH, &value)
+            .map_err(bad_value)?
+        {
+            return Err(unexpected());
+        }
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::vm::types::QualifiedContractIdentifier;
+    use crate::vm::ClarityVersion;
+
+    fn parse_type(type_str: &str) -> TypeSignature {
+        let expr = &crate::vm::ast::parse(
+            &QualifiedContractIdentifier::transient(),
+            type_str,
+            ClarityVersion::Clarity2,
+            StacksEpochId::Epoch21,
+        )
+        .unwrap()[0];
+        TypeSignature::parse_type_repr(StacksEpochId::Epoch21, expr, &mut ()).unwrap()
+    }
+
+    fn parse_value(value_str: &str) -> Value {
+        crate::vm::execute_v2(value_str).unwrap().unwrap()
+    }
+
+    fn check_roundtrip(value_str: &str, type_str: &str, expected: JSONValue) {
+        let value = parse_value(value_str);
+        assert_eq!(value.to_json(), expected, "{value_str}");
+        assert_eq!(
+            Value::try_from_json(&expected, &parse_type(type_str)).unwrap(),
+            value,
+            "{value_str}"
+        );
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        check_roundtrip(
+            "-170141183460469231731687303715884105728",
+            "int",
+            json!("-170141183460469231731687303715884105728"),
+        );
+        check_roundtrip(
+            "u340282366920938463463374607431768211455",
+            "uint",
+            json!("340282366920938463463374607431768211455"),
+        );
+        check_roundtrip("true", "bool", json!(true));
+        check_roundtrip("0x00ff", "(buff 4)", json!("0x00ff"));
+        check_roundtrip("\"hello\"", "(string-ascii 10)", json!("hello"));
+        check_roundtrip("u\"caf\\u{e9}\"", "(string-utf8 10)", json!("café"));
+        check_roundtrip(
+            "'SP000000000000000000002Q6VF78",
+            "principal",
+            json!("SP000000000000000000002Q6VF78"),
+        );
+        check_roundtrip(
+            "'SP000000000000000000002Q6VF78.pox-4",
+            "principal",
+            json!("SP000000000000000000002Q6VF78.pox-4"),
+        );
+        check_roundtrip("(list 1 2 3)", "(list 5 int)", json!(["1", "2", "3"]));
+        check_roundtrip(
+            "{ a: 1, b: (some u2), c: none }",
+            "{ a: int, b: (optional uint), c: (optional int) }",
+            json!({"a": "1", "b": "2", "c": null}),
+        );
+        check_roundtrip(
+            "(some (some 1))",
+            "(optional (optional int))",
+            json!({"some": "1"}),
+        );
+        check_roundtrip(
+            "(some none)",
+            "(optional (optional int))",
+            json!({"some": null}),
+        );
+        check_roundtrip("none", "(optional (optional int))", json!(null));
+        check_roundtrip("(ok u1)", "(response uint int)", json!({"ok": "1"}));
+        check_roundtrip(
+            "(err (list 0x01))",
+            "(response uint (list 2 (buff 1)))",
+            json!({"err": ["0x01"]}),
+        );
+    }
+
+    #[test]
+    fn test_json_lenient_input() {
+        assert_eq!(
+            Value::try_from_json(&json!(-5), &TypeSignature::IntType).unwrap(),
+            Value::Int(-5)
+        );
+        assert_eq!(
+            Value::try_from_json(&json!("00ff"), &parse_type("(buff 2)")).unwrap(),
+            parse_value("0x00ff")
+        );
+    }
+
+    #[test]
+    fn test_json_type_errors() {
+        let bad = [
+            (json!(1.5), "int"),
+            (json!("-1"), "uint"),
+            (json!("1"), "bool"),
+            (json!("0x000000"), "(buff 2)"),
+            (json!("0xzz"), "(buff 2)"),
+            (json!("toolong"), "(string-ascii 2)"),
+            (json!("caf\u{e9}"), "(string-ascii 10)"),
+            (json!("SP000000000000000000002Q6VF78"), "(buff 2)"),
+            (json!("not-a-principal"), "principal"),
+            (json!(["1", "2", "3"]), "(list 2 int)"),
+            (json!({"a": "1"}), "{ a: int, b: int }"),
+            (json!({"a": "1", "b": "2", "c": "3"}), "{ a: int, b: int }"),
+            (json!("1"), "(optional (optional int))"),
+            (json!({"ok": "1", "err": "2"}), "(response int int)"),
+            (json!({"okay": "1"}), "(response int int)"),
+        ];
+        for (json, type_str) in bad {
+            assert!(
+                Value::try_from_json(&json, &parse_type(type_str)).is_err(),
+                "{json} as {type_str}"
+            );
+        }
+    }
+}
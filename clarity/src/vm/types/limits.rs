@@ -0,0 +1,178 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Configurable limits on the size and depth of Clarity values.
+//!
+//! On the Stacks chain these limits are consensus rules (`ValueLimits::CONSENSUS`, which is
+//! `MAX_VALUE_SIZE` and `MAX_TYPE_DEPTH`).  Deployments that embed the Clarity VM can raise
+//! them by returning other limits from `ClarityBackingStore::get_value_limits()`.  The store's
+//! limits apply for the whole of each analysis (`run_analysis()`) and each execution
+//! (`GlobalContext::execute()` and `OwnedEnvironment::execute_in_env()`), so that type
+//! signatures, values and their serializations are all checked against the same limits.
+//! Mainnet contexts always use the consensus limits.
+//!
+//! Value and type constructors are not passed a context, so the limits in effect are kept in a
+//! thread-local, which `ValueLimits::current()` reads.  Outside of an analysis or execution,
+//! they are the consensus limits.
+
+use std::cell::Cell;
+
+use crate::vm::types::{MAX_TYPE_DEPTH, MAX_VALUE_SIZE};
+
+thread_local! {
+    static CURRENT_VALUE_LIMITS: Cell<ValueLimits> = Cell::new(ValueLimits::CONSENSUS);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValueLimits {
+    /// Maximum size of a value (and of the values a type signature admits), in bytes
+    pub max_value_size: u32,
+    /// Maximum nesting depth of a type signature
+    pub max_type_depth: u8,
+    /// Maximum length of a list
+    pub max_list_length: u32,
+}
+
+impl Default for ValueLimits {
+    fn default() -> Self {
+        Self::CONSENSUS
+    }
+}
+
+impl ValueLimits {
+    /// The Stacks chain's limits.  Every list entry is at least a byte, so the value size
+    /// already limits list lengths to `MAX_VALUE_SIZE`.
+    pub const CONSENSUS: ValueLimits = ValueLimits {
+        max_value_size: MAX_VALUE_SIZE,
+        max_type_depth: MAX_TYPE_DEPTH,
+        max_list_length: MAX_VALUE_SIZE,
+    };
+
+    /// The limits in effect on this thread
+    pub fn current() -> ValueLimits {
+        CURRENT_VALUE_LIMITS.with(|limits| limits.get())
+    }
+
+    /// Bound on the length of a value's serialization
+    pub fn max_serialized_size(&self) -> u32 {
+        self.max_value_size.saturating_mul(2)
+    }
+
+    /// Put these limits in effect on this thread until the returned scope is dropped
+    #[must_use]
+    pub fn enter(self) -> ValueLimitsScope {
+        let previous = CURRENT_VALUE_LIMITS.with(|limits| limits.replace(self));
+        ValueLimitsScope { previous }
+    }
+
+    /// Run `f` with these limits in effect
+    pub fn with<F, R>(self, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        let _scope = self.enter();
+        f()
+    }
+}
+
+/// Restores the previous limits when dropped
+pub struct ValueLimitsScope {
+    previous: ValueLimits,
+}
+
+impl Drop for ValueLimitsScope {
+    fn drop(&mut self) {
+        CURRENT_VALUE_LIMITS.with(|limits| limits.set(self.previous));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stacks_common::consts::{CHAIN_ID_MAINNET, CHAIN_ID_TESTNET};
+    use stacks_common::types::StacksEpochId;
+
+    use super::*;
+    use crate::vm::analysis::type_check;
+    use crate::vm::ast::{parse, ASTRules};
+    use crate::vm::contexts::OwnedEnvironment;
+    use crate::vm::database::MemoryBackingStore;
+    use crate::vm::types::{ListTypeData, QualifiedContractIdentifier, TypeSignature, Value};
+    use crate::vm::ClarityVersion;
+
+    const RAISED: ValueLimits = ValueLimits {
+        max_value_size: 4 * MAX_VALUE_SIZE,
+        max_type_depth: 64,
+        max_list_length: 2 * MAX_VALUE_SIZE,
+    };
+
+    #[test]
+    fn test_value_limits_scope() {
+        let raised = RAISED;
+        let long_list = || ListTypeData::new_list(TypeSignature::BoolType, MAX_VALUE_SIZE + 1);
+        let big_buffer = || Value::buff_from(vec![0; MAX_VALUE_SIZE as usize + 1]);
+
+        assert!(long_list().is_err());
+        assert!(big_buffer().is_err());
+        raised.with(|| {
+            assert_eq!(ValueLimits::current(), raised);
+            assert!(long_list().is_ok());
+            assert!(big_buffer().is_ok());
+
+            // scopes nest
+            ValueLimits::CONSENSUS.with(|| assert!(big_buffer().is_err()));
+            assert!(big_buffer().is_ok());
+        });
+        assert_eq!(ValueLimits::current(), ValueLimits::CONSENSUS);
+        assert!(long_list().is_err());
+    }
+
+    #[test]
+    fn test_store_value_limits() {
+        let epoch = StacksEpochId::Epoch21;
+        let version = ClarityVersion::Clarity2;
+        let contract_id = QualifiedContractIdentifier::local("big").unwrap();
+        let contract = "(define-data-var big (buff 2000000) 0x00)";
+        let analyze = |store: &mut MemoryBackingStore| {
+            let mut expressions = parse(&contract_id, contract, version, epoch).unwrap();
+            type_check(
+                &contract_id,
+                &mut expressions,
+                &mut store.as_analysis_db(),
+                false,
+                &epoch,
+                &version,
+            )
+        };
+        let initialize = |store: &mut MemoryBackingStore, mainnet: bool| {
+            let chain_id = if mainnet {
+                CHAIN_ID_MAINNET
+            } else {
+                CHAIN_ID_TESTNET
+            };
+            OwnedEnvironment::new_free(mainnet, chain_id, store.as_clarity_db(), epoch)
+                .initialize_contract(contract_id.clone(), contract, None, ASTRules::PrecheckSize)
+        };
+
+        let mut store = MemoryBackingStore::new();
+        assert!(analyze(&mut store).is_err());
+        assert!(initialize(&mut store, false).is_err());
+
+        store.set_value_limits(RAISED);
+        analyze(&mut store).unwrap();
+        // mainnet always uses the consensus limits
+        assert!(initialize(&mut store, true).is_err());
+        initialize(&mut store, false).unwrap();
+    }
+}
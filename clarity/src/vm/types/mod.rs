@@ -14,6 +14,9 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+#[allow(clippy::result_large_err)]
+pub mod json;
+pub mod limits;
 #[allow(clippy::result_large_err)]
 pub mod serialization;
 #[allow(clippy::result_large_err)]
@@ -36,11 +39,12 @@ use crate::vm::errors::{
 use crate::vm::representations::{
     ClarityName, ContractName, SymbolicExpression, SymbolicExpressionType,
 };
+pub use crate::vm::types::limits::ValueLimits;
 pub use crate::vm::types::signatures::{
     parse_name_type_pairs, AssetIdentifier, BufferLength, FixedFunction, FunctionArg,
     FunctionSignature, FunctionType, ListTypeData, SequenceSubtype, StringSubtype,
-    StringUTF8Length, TupleTypeSignature, TypeSignature, BUFF_1, BUFF_20, BUFF_21, BUFF_32,
-    BUFF_33, BUFF_64, BUFF_65,
+    StringUTF8Length, TupleTypeSignature, TypeSignature, ALT_BN128_PAIRING_ENTRY, BUFF_1, BUFF_128,
+    BUFF_20, BUFF_21, BUFF_32, BUFF_33, BUFF_64, BUFF_65, SECP256K1_SIGNATURE_ENTRY,
 };
 use crate::vm::ClarityVersion;
 
@@ -859,9 +863,10 @@ pub const NONE: Value = Value::Optional(OptionalData { data: None });
 
 impl Value {
     pub fn some(data: Value) -> Result<Value> {
-        if data.size()? + WRAPPER_VALUE_SIZE > MAX_VALUE_SIZE {
+        let limits = ValueLimits::current();
+        if data.size()? + WRAPPER_VALUE_SIZE > limits.max_value_size {
             Err(CheckErrors::ValueTooLarge.into())
-        } else if data.depth()? + 1 > MAX_TYPE_DEPTH {
+        } else if data.depth()? + 1 > limits.max_type_depth {
             Err(CheckErrors::TypeSignatureTooDeep.into())
         } else {
             Ok(Value::Optional(OptionalData {
@@ -896,9 +901,10 @@ impl Value {
     }
 
     pub fn okay(data: Value) -> Result<Value> {
-        if data.size()? + WRAPPER_VALUE_SIZE > MAX_VALUE_SIZE {
+        let limits = ValueLimits::current();
+        if data.size()? + WRAPPER_VALUE_SIZE > limits.max_value_size {
             Err(CheckErrors::ValueTooLarge.into())
-        } else if data.depth()? + 1 > MAX_TYPE_DEPTH {
+        } else if data.depth()? + 1 > limits.max_type_depth {
             Err(CheckErrors::TypeSignatureTooDeep.into())
         } else {
             Ok(Value::Response(ResponseData {
@@ -909,9 +915,10 @@ impl Value {
     }
 
     pub fn error(data: Value) -> Result<Value> {
-        if data.size()? + WRAPPER_VALUE_SIZE > MAX_VALUE_SIZE {
+        let limits = ValueLimits::current();
+        if data.size()? + WRAPPER_VALUE_SIZE > limits.max_value_size {
             Err(CheckErrors::ValueTooLarge.into())
-        } else if data.depth()? + 1 > MAX_TYPE_DEPTH {
+        } else if data.depth()? + 1 > limits.max_type_depth {
             Err(CheckErrors::TypeSignatureTooDeep.into())
         } else {
             Ok(Value::Response(ResponseData {
@@ -37,7 +37,7 @@ use crate::vm::types::{
     byte_len_of_serialization, BufferLength, CallableData, CharType, OptionalData, PrincipalData,
     QualifiedContractIdentifier, ResponseData, SequenceData, SequenceSubtype,
     StandardPrincipalData, StringSubtype, StringUTF8Length, TupleData, TypeSignature, Value,
-    BOUND_VALUE_SERIALIZATION_BYTES, MAX_TYPE_DEPTH, MAX_VALUE_SIZE,
+    ValueLimits,
 };
 
 /// Errors that may occur in serialization or deserialization
@@ -539,10 +539,11 @@ impl Value {
         expected_type: Option<&TypeSignature>,
         sanitize: bool,
     ) -> Result<(Value, u64), SerializationError> {
+        let max_serialized_size = u64::from(ValueLimits::current().max_serialized_size());
         let bound_value_serialization_bytes = if sanitize && expected_type.is_some() {
-            SANITIZATION_READ_BOUND
+            cmp::max(SANITIZATION_READ_BOUND, max_serialized_size)
         } else {
-            BOUND_VALUE_SERIALIZATION_BYTES as u64
+            max_serialized_size
         };
         let mut bound_reader = BoundReader::from_reader(r, bound_value_serialization_bytes);
         let value = Value::inner_deserialize_read(&mut bound_reader, expected_type, sanitize)?;
@@ -581,13 +582,14 @@ impl Value {
         use super::PrincipalData::*;
         use super::Value::*;
 
+        let limits = ValueLimits::current();
         let mut stack = vec![DeserializeStackItem::TopLevel {
             expected_type: top_expected_type.cloned(),
         }];
 
         while !stack.is_empty() {
             let depth_check = if sanitize {
-                MAX_TYPE_DEPTH as usize
+                limits.max_type_depth as usize
             } else {
                 UNSANITIZED_DEPTH_CHECK
             };
@@ -715,7 +717,7 @@ impl Value {
                     r.read_exact(&mut len)?;
                     let len = u32::from_be_bytes(len);
 
-                    if len > MAX_VALUE_SIZE {
+                    if len > limits.max_list_length {
                         return Err("Illegal list type".into());
                     }
 
@@ -765,7 +767,7 @@ impl Value {
                     let len = u32::from_be_bytes(len);
                     let expected_len = u64::from(len);
 
-                    if len > MAX_VALUE_SIZE {
+                    if len > limits.max_value_size {
                         return Err(SerializationError::DeserializationError(
                             "Illegal tuple type".to_string(),
                         ));
@@ -1179,6 +1181,86 @@ impl Value {
         })?;
         Ok(counter.count)
     }
+
+    /// The length of this value's consensus serialization, computed from its structure instead
+    ///  of by serializing it, so that callers can reject oversized values (e.g. before
+    ///  `put_value()`, or when parsing arguments) without paying for a serialization that would
+    ///  only fail.  This is exact for every value that the `Value` constructors admit, and it
+    ///  saturates instead of failing.
+    pub fn estimate_serialized_size(&self) -> u64 {
+        // a type prefix, plus a length prefix for sequences and tuples
+        const PREFIX_LEN: u64 = 1;
+        const LENGTH_LEN: u64 = 4;
+        // a version byte and a hash160
+        const STANDARD_PRINCIPAL_LEN: u64 = 21;
+        let name_len = |name: &str| 1 + name.len() as u64;
+
+        let body_len = match self {
+            Value::Int(_) | Value::UInt(_) => 16,
+            Value::Bool(_) | Value::Optional(OptionalData { data: None }) => 0,
+            Value::Principal(PrincipalData::Standard(_)) => STANDARD_PRINCIPAL_LEN,
+            Value::Principal(PrincipalData::Contract(contract_identifier))
+            | Value::CallableContract(CallableData {
+                contract_identifier,
+                ..
+            }) => STANDARD_PRINCIPAL_LEN + name_len(&contract_identifier.name),
+            Value::Optional(OptionalData { data: Some(value) }) => value.estimate_serialized_size(),
+            Value::Response(response) => response.data.estimate_serialized_size(),
+            Value::Sequence(SequenceData::List(list)) => {
+                list.data.iter().fold(LENGTH_LEN, |len, item| {
+                    len.saturating_add(item.estimate_serialized_size())
+                })
+            }
+            Value::Sequence(SequenceData::Buffer(buffer)) => LENGTH_LEN + buffer.data.len() as u64,
+            Value::Sequence(SequenceData::String(CharType::ASCII(string))) => {
+                LENGTH_LEN + string.data.len() as u64
+            }
+            Value::Sequence(SequenceData::String(CharType::UTF8(string))) => string
+                .data
+                .iter()
+                .fold(LENGTH_LEN, |len, c| len.saturating_add(c.len() as u64)),
+            Value::Tuple(tuple) => tuple
+                .data_map
+                .iter()
+                .fold(LENGTH_LEN, |len, (name, value)| {
+                    len.saturating_add(name_len(name))
+                        .saturating_add(value.estimate_serialized_size())
+                }),
+        };
+        PREFIX_LEN.saturating_add(body_len)
+    }
+
+    /// The depth of this value's type (what `depth()` returns), found without constructing the
+    ///  type: lists and tuples already carry theirs.
+    pub fn type_depth(&self) -> u8 {
+        match self {
+            Value::Optional(OptionalData { data: None }) => 2,
+            Value::Optional(OptionalData { data: Some(value) }) => {
+                value.type_depth().saturating_add(1)
+            }
+            Value::Response(response) => response.data.type_depth().saturating_add(1),
+            Value::Sequence(SequenceData::List(list)) => list
+                .type_signature
+                .get_list_item_type()
+                .depth()
+                .saturating_add(1),
+            Value::Tuple(tuple) => tuple.type_signature.max_depth().saturating_add(1),
+            _ => 1,
+        }
+    }
+
+    /// Check that this value is within `limits`: that its type is no deeper than
+    ///  `max_type_depth`, and that its serialization is no longer than `max_serialized_size()`
+    ///  (the most that deserialization will read).
+    pub fn check_limits(&self, limits: &ValueLimits) -> Result<(), CheckErrors> {
+        if self.type_depth() > limits.max_type_depth {
+            return Err(CheckErrors::TypeSignatureTooDeep);
+        }
+        if self.estimate_serialized_size() > u64::from(limits.max_serialized_size()) {
+            return Err(CheckErrors::ValueTooLarge);
+        }
+        Ok(())
+    }
 }
 
 /// A writer that just counts the bytes written
@@ -1412,6 +1494,11 @@ pub mod tests {
             v.serialize_to_hex().unwrap().len() as u32 / 2,
             "serialized_size() should return the byte length of the serialization (half the length of the hex encoding)",
         );
+        assert_eq!(
+            v.estimate_serialized_size(),
+            u64::from(v.serialized_size().unwrap())
+        );
+        assert_eq!(v.type_depth(), v.depth().unwrap());
     }
 
     fn test_deser_u32_helper(num: u32) {
@@ -2223,4 +2310,28 @@ pub mod tests {
         test_bad_expectation(contract_p2, TypeSignature::BoolType);
         test_bad_expectation(standard_p, TypeSignature::BoolType);
     }
+
+    #[test]
+    fn test_check_limits() {
+        let deep = (0..10).fold(Value::Int(1), |value, _| Value::some(value).unwrap());
+        assert_eq!(deep.type_depth(), 11);
+        let shallow = ValueLimits {
+            max_type_depth: 10,
+            ..ValueLimits::CONSENSUS
+        };
+        assert_eq!(
+            deep.check_limits(&shallow),
+            Err(CheckErrors::TypeSignatureTooDeep)
+        );
+        assert_eq!(deep.check_limits(&ValueLimits::CONSENSUS), Ok(()));
+
+        let buff = Value::buff_from(vec![0; 1000]).unwrap();
+        assert_eq!(buff.estimate_serialized_size(), 1005);
+        let small = ValueLimits {
+            max_value_size: 400,
+            ..ValueLimits::CONSENSUS
+        };
+        assert_eq!(buff.check_limits(&small), Err(CheckErrors::ValueTooLarge));
+        assert_eq!(buff.check_limits(&ValueLimits::CONSENSUS), Ok(()));
+    }
 }
@@ -36,8 +36,7 @@ use crate::vm::representations::{
 };
 use crate::vm::types::{
     CharType, PrincipalData, QualifiedContractIdentifier, SequenceData, SequencedValue,
-    StandardPrincipalData, TraitIdentifier, Value, MAX_TYPE_DEPTH, MAX_VALUE_SIZE,
-    WRAPPER_VALUE_SIZE,
+    StandardPrincipalData, TraitIdentifier, Value, ValueLimits, WRAPPER_VALUE_SIZE,
 };
 
 type Result<R> = std::result::Result<R, CheckErrors>;
@@ -241,6 +240,35 @@ lazy_static! {
             BufferLength::try_from(16u32).expect("BUG: Legal Clarity buffer length marked invalid"),
         ))
     };
+    pub static ref BUFF_128: TypeSignature = {
+        #[allow(clippy::expect_used)]
+        SequenceType(SequenceSubtype::BufferType(
+            BufferLength::try_from(128u32).expect("BUG: Legal Clarity buffer length marked invalid"),
+        ))
+    };
+    /// An entry of the list checked by `secp256k1-verify-many`
+    pub static ref SECP256K1_SIGNATURE_ENTRY: TypeSignature = {
+        #[allow(clippy::expect_used)]
+        TupleType(
+            TupleTypeSignature::try_from(vec![
+                ("message-hash".into(), BUFF_32.clone()),
+                ("signature".into(), BUFF_65.clone()),
+                ("public-key".into(), BUFF_33.clone()),
+            ])
+            .expect("BUG: Legal Clarity tuple type marked invalid"),
+        )
+    };
+    /// An entry of the list checked by `alt-bn128-pairing-check?`
+    pub static ref ALT_BN128_PAIRING_ENTRY: TypeSignature = {
+        #[allow(clippy::expect_used)]
+        TupleType(
+            TupleTypeSignature::try_from(vec![
+                ("g1".into(), BUFF_64.clone()),
+                ("g2".into(), BUFF_128.clone()),
+            ])
+            .expect("BUG: Legal Clarity tuple type marked invalid"),
+        )
+    };
 }
 
 pub const ASCII_40: TypeSignature = SequenceType(SequenceSubtype::StringType(
@@ -410,7 +438,7 @@ impl From<BufferLength> for u32 {
 impl TryFrom<u32> for BufferLength {
     type Error = CheckErrors;
     fn try_from(data: u32) -> Result<BufferLength> {
-        if data > MAX_VALUE_SIZE {
+        if data > ValueLimits::current().max_value_size {
             Err(CheckErrors::ValueTooLarge)
         } else {
             Ok(BufferLength(data))
@@ -421,7 +449,7 @@ impl TryFrom<u32> for BufferLength {
 impl TryFrom<usize> for BufferLength {
     type Error = CheckErrors;
     fn try_from(data: usize) -> Result<BufferLength> {
-        if data > (MAX_VALUE_SIZE as usize) {
+        if data > (ValueLimits::current().max_value_size as usize) {
             Err(CheckErrors::ValueTooLarge)
         } else {
             Ok(BufferLength(data as u32))
@@ -432,7 +460,7 @@ impl TryFrom<usize> for BufferLength {
 impl TryFrom<i128> for BufferLength {
     type Error = CheckErrors;
     fn try_from(data: i128) -> Result<BufferLength> {
-        if data > (MAX_VALUE_SIZE as i128) {
+        if data > (ValueLimits::current().max_value_size as i128) {
             Err(CheckErrors::ValueTooLarge)
         } else if data < 0 {
             Err(CheckErrors::ValueOutOfBounds)
@@ -460,7 +488,7 @@ impl TryFrom<u32> for StringUTF8Length {
         let len = data
             .checked_mul(4)
             .ok_or_else(|| CheckErrors::ValueTooLarge)?;
-        if len > MAX_VALUE_SIZE {
+        if len > ValueLimits::current().max_value_size {
             Err(CheckErrors::ValueTooLarge)
         } else {
             Ok(StringUTF8Length(data))
@@ -474,7 +502,7 @@ impl TryFrom<usize> for StringUTF8Length {
         let len = data
             .checked_mul(4)
             .ok_or_else(|| CheckErrors::ValueTooLarge)?;
-        if len > (MAX_VALUE_SIZE as usize) {
+        if len > (ValueLimits::current().max_value_size as usize) {
             Err(CheckErrors::ValueTooLarge)
         } else {
             Ok(StringUTF8Length(data as u32))
@@ -488,7 +516,7 @@ impl TryFrom<i128> for StringUTF8Length {
         let len = data
             .checked_mul(4)
             .ok_or_else(|| CheckErrors::ValueTooLarge)?;
-        if len > (MAX_VALUE_SIZE as i128) {
+        if len > (ValueLimits::current().max_value_size as i128) {
             Err(CheckErrors::ValueTooLarge)
         } else if data < 0 {
             Err(CheckErrors::ValueOutOfBounds)
@@ -500,10 +528,14 @@ impl TryFrom<i128> for StringUTF8Length {
 
 impl ListTypeData {
     pub fn new_list(entry_type: TypeSignature, max_len: u32) -> Result<ListTypeData> {
+        let limits = ValueLimits::current();
         let would_be_depth = 1 + entry_type.depth();
-        if would_be_depth > MAX_TYPE_DEPTH {
+        if would_be_depth > limits.max_type_depth {
             return Err(CheckErrors::TypeSignatureTooDeep);
         }
+        if max_len > limits.max_list_length {
+            return Err(CheckErrors::ValueTooLarge);
+        }
 
         let list_data = ListTypeData {
             entry_type: Box::new(entry_type),
@@ -512,7 +544,7 @@ impl ListTypeData {
         let would_be_size = list_data
             .inner_size()?
             .ok_or_else(|| CheckErrors::ValueTooLarge)?;
-        if would_be_size > MAX_VALUE_SIZE {
+        if would_be_size > limits.max_value_size {
             Err(CheckErrors::ValueTooLarge)
         } else {
             Ok(list_data)
@@ -544,9 +576,10 @@ impl TypeSignature {
     pub fn new_option(inner_type: TypeSignature) -> Result<TypeSignature> {
         let new_size = WRAPPER_VALUE_SIZE + inner_type.size()?;
         let new_depth = 1 + inner_type.depth();
-        if new_size > MAX_VALUE_SIZE {
+        let limits = ValueLimits::current();
+        if new_size > limits.max_value_size {
             Err(CheckErrors::ValueTooLarge)
-        } else if new_depth > MAX_TYPE_DEPTH {
+        } else if new_depth > limits.max_type_depth {
             Err(CheckErrors::TypeSignatureTooDeep)
         } else {
             Ok(OptionalType(Box::new(inner_type)))
@@ -557,9 +590,10 @@ impl TypeSignature {
         let new_size = WRAPPER_VALUE_SIZE + cmp::max(ok_type.size()?, err_type.size()?);
         let new_depth = 1 + cmp::max(ok_type.depth(), err_type.depth());
 
-        if new_size > MAX_VALUE_SIZE {
+        let limits = ValueLimits::current();
+        if new_size > limits.max_value_size {
             Err(CheckErrors::ValueTooLarge)
-        } else if new_depth > MAX_TYPE_DEPTH {
+        } else if new_depth > limits.max_type_depth {
             Err(CheckErrors::TypeSignatureTooDeep)
         } else {
             Ok(ResponseType(Box::new((ok_type, err_type))))
@@ -587,13 +621,24 @@ impl TypeSignature {
             | StacksEpochId::Epoch23
             | StacksEpochId::Epoch24
             | StacksEpochId::Epoch25
-            | StacksEpochId::Epoch30 => self.admits_type_v2_1(other),
+            | StacksEpochId::Epoch30
+            | StacksEpochId::Epoch31 => self.admits_type_v2_1(other),
             StacksEpochId::Epoch10 => {
                 return Err(CheckErrors::Expects("epoch 1.0 not supported".into()))
             }
         }
     }
 
+    /// Can a value of type `other` be used where this type is expected in `epoch`?  This is the
+    /// subtyping relation of the type checker, for use outside of analysis (e.g. to validate the
+    /// arguments of a contract call before sending it).  Both types are canonicalized for
+    /// `epoch` first, and types that can't be compared (such as `NoType`) are not admitted.
+    pub fn admits_type_signature(&self, other: &TypeSignature, epoch: &StacksEpochId) -> bool {
+        self.canonicalize(epoch)
+            .admits_type(epoch, &other.canonicalize(epoch))
+            .unwrap_or(false)
+    }
+
     pub fn admits_type_v2_0(&self, other: &TypeSignature) -> Result<bool> {
         match self {
             SequenceType(SequenceSubtype::ListType(ref my_list_type)) => {
@@ -800,7 +845,8 @@ impl TypeSignature {
             | StacksEpochId::Epoch23
             | StacksEpochId::Epoch24
             | StacksEpochId::Epoch25
-            | StacksEpochId::Epoch30 => self.canonicalize_v2_1(),
+            | StacksEpochId::Epoch30
+            | StacksEpochId::Epoch31 => self.canonicalize_v2_1(),
         }
     }
 
@@ -900,8 +946,9 @@ impl TryFrom<BTreeMap<ClarityName, TypeSignature>> for TupleTypeSignature {
         if type_map.is_empty() {
             return Err(CheckErrors::EmptyTuplesNotAllowed);
         }
+        let limits = ValueLimits::current();
         for child_sig in type_map.values() {
-            if (1 + child_sig.depth()) > MAX_TYPE_DEPTH {
+            if (1 + child_sig.depth()) > limits.max_type_depth {
                 return Err(CheckErrors::TypeSignatureTooDeep);
             }
         }
@@ -910,7 +957,7 @@ impl TryFrom<BTreeMap<ClarityName, TypeSignature>> for TupleTypeSignature {
         let would_be_size = result
             .inner_size()?
             .ok_or_else(|| CheckErrors::ValueTooLarge)?;
-        if would_be_size > MAX_VALUE_SIZE {
+        if would_be_size > limits.max_value_size {
             Err(CheckErrors::ValueTooLarge)
         } else {
             Ok(result)
@@ -1011,6 +1058,20 @@ impl FunctionSignature {
         Ok(true)
     }
 
+    /// Can a function of type `other` implement this trait function in `epoch`?  Its arguments
+    /// must admit this function's arguments, and this function's return type must admit its
+    /// return type, as in `ContractAnalysis::check_trait_compliance()`.
+    pub fn compatible_for_trait(&self, other: &FunctionSignature, epoch: &StacksEpochId) -> bool {
+        let expected = self.canonicalize(epoch);
+        let other = other.canonicalize(epoch);
+        expected
+            .check_args_trait_compliance(epoch, other.args)
+            .unwrap_or(false)
+            && expected
+                .returns
+                .admits_type_signature(&other.returns, epoch)
+    }
+
     pub fn canonicalize(&self, epoch: &StacksEpochId) -> FunctionSignature {
         let canonicalized_args = self
             .args
@@ -1065,8 +1126,9 @@ impl TypeSignature {
     }
 
     pub fn max_string_ascii() -> Result<TypeSignature> {
+        let max_value_size = ValueLimits::current().max_value_size;
         Ok(SequenceType(SequenceSubtype::StringType(
-            StringSubtype::ASCII(BufferLength::try_from(MAX_VALUE_SIZE).map_err(|_| {
+            StringSubtype::ASCII(BufferLength::try_from(max_value_size).map_err(|_| {
                 CheckErrors::Expects(
                     "FAIL: Max Clarity Value Size is no longer realizable in ASCII Type".into(),
                 )
@@ -1075,8 +1137,9 @@ impl TypeSignature {
     }
 
     pub fn max_string_utf8() -> Result<TypeSignature> {
+        let max_value_size = ValueLimits::current().max_value_size;
         Ok(SequenceType(SequenceSubtype::StringType(
-            StringSubtype::UTF8(StringUTF8Length::try_from(MAX_VALUE_SIZE / 4).map_err(|_| {
+            StringSubtype::UTF8(StringUTF8Length::try_from(max_value_size / 4).map_err(|_| {
                 CheckErrors::Expects(
                     "FAIL: Max Clarity Value Size is no longer realizable in UTF8 Type".into(),
                 )
@@ -1085,8 +1148,9 @@ impl TypeSignature {
     }
 
     pub fn max_buffer() -> Result<TypeSignature> {
+        let max_value_size = ValueLimits::current().max_value_size;
         Ok(SequenceType(SequenceSubtype::BufferType(
-            BufferLength::try_from(MAX_VALUE_SIZE).map_err(|_| {
+            BufferLength::try_from(max_value_size).map_err(|_| {
                 CheckErrors::Expects(
                     "FAIL: Max Clarity Value Size is no longer realizable in Buffer Type".into(),
                 )
@@ -1158,7 +1222,8 @@ impl TypeSignature {
             | StacksEpochId::Epoch23
             | StacksEpochId::Epoch24
             | StacksEpochId::Epoch25
-            | StacksEpochId::Epoch30 => Self::least_supertype_v2_1(a, b),
+            | StacksEpochId::Epoch30
+            | StacksEpochId::Epoch31 => Self::least_supertype_v2_1(a, b),
             StacksEpochId::Epoch10 => {
                 return Err(CheckErrors::Expects("epoch 1.0 not supported".into()))
             }
@@ -1840,7 +1905,7 @@ impl ListTypeData {
             .and_then(|x| x.checked_add(self.type_size()?));
         match total_size {
             Some(total_size) => {
-                if total_size > MAX_VALUE_SIZE {
+                if total_size > ValueLimits::current().max_value_size {
                     Ok(None)
                 } else {
                     Ok(Some(total_size))
@@ -1852,7 +1917,7 @@ impl ListTypeData {
 
     fn type_size(&self) -> Option<u32> {
         let total_size = self.entry_type.inner_type_size()?.checked_add(4 + 1)?; // 1 byte for Type enum, 4 for max_len.
-        if total_size > MAX_VALUE_SIZE {
+        if total_size > ValueLimits::current().max_value_size {
             None
         } else {
             Some(total_size)
@@ -1874,7 +1939,7 @@ impl TupleTypeSignature {
                 .checked_add(name.len() as u32)?;
         }
 
-        if type_map_size > MAX_VALUE_SIZE {
+        if type_map_size > ValueLimits::current().max_value_size {
             None
         } else {
             Some(type_map_size)
@@ -1887,7 +1952,7 @@ impl TupleTypeSignature {
         })
     }
 
-    fn max_depth(&self) -> u8 {
+    pub fn max_depth(&self) -> u8 {
         let mut max = 0;
         for (_name, type_signature) in self.type_map.iter() {
             max = cmp::max(max, type_signature.depth())
@@ -1921,7 +1986,7 @@ impl TupleTypeSignature {
             };
         }
 
-        if total_size > MAX_VALUE_SIZE {
+        if total_size > ValueLimits::current().max_value_size {
             Ok(None)
         } else {
             Ok(Some(total_size))
@@ -2673,4 +2738,68 @@ mod test {
             );
         }
     }
+
+    #[test]
+    fn test_admits_type_signature() {
+        let epoch = StacksEpochId::Epoch21;
+        let parse = |repr| TypeSignature::from_string(repr, ClarityVersion::Clarity2, epoch);
+
+        let admitted = [
+            ("(list 5 uint)", "(list 3 uint)"),
+            ("(string-ascii 10)", "(string-ascii 10)"),
+            ("(optional (buff 32))", "(optional (buff 20))"),
+            ("(response int uint)", "(response int uint)"),
+            (
+                "(tuple (a int) (b (buff 4)))",
+                "(tuple (a int) (b (buff 2)))",
+            ),
+        ];
+        for (expected, actual) in admitted {
+            assert!(parse(expected).admits_type_signature(&parse(actual), &epoch));
+        }
+
+        assert!(!parse("(list 3 uint)").admits_type_signature(&parse("(list 5 uint)"), &epoch));
+        assert!(!parse("int").admits_type_signature(&parse("uint"), &epoch));
+        assert!(
+            !parse("(string-ascii 10)").admits_type_signature(&parse("(string-utf8 10)"), &epoch)
+        );
+        // `NoType` can't be compared
+        assert!(!TypeSignature::NoType.admits_type_signature(&parse("int"), &epoch));
+        // epoch 1.0 has no type checker
+        assert!(!parse("int").admits_type_signature(&parse("int"), &StacksEpochId::Epoch10));
+    }
+
+    #[test]
+    fn test_compatible_for_trait() {
+        let epoch = StacksEpochId::Epoch21;
+        let parse = |repr| TypeSignature::from_string(repr, ClarityVersion::Clarity2, epoch);
+        let expected = FunctionSignature {
+            args: vec![parse("(buff 10)"), parse("uint")],
+            returns: parse("(response (list 5 uint) uint)"),
+        };
+
+        // wider arguments and a narrower return type are fine
+        let compatible = FunctionSignature {
+            args: vec![parse("(buff 20)"), parse("uint")],
+            returns: parse("(response (list 2 uint) uint)"),
+        };
+        assert!(expected.compatible_for_trait(&compatible, &epoch));
+        assert!(expected.compatible_for_trait(&expected, &epoch));
+
+        let narrower_arg = FunctionSignature {
+            args: vec![parse("(buff 5)"), parse("uint")],
+            returns: expected.returns.clone(),
+        };
+        let wider_return = FunctionSignature {
+            args: expected.args.clone(),
+            returns: parse("(response (list 10 uint) uint)"),
+        };
+        let missing_arg = FunctionSignature {
+            args: vec![parse("(buff 10)")],
+            returns: expected.returns.clone(),
+        };
+        for incompatible in [narrower_arg, wider_return, missing_arg] {
+            assert!(!expected.compatible_for_trait(&incompatible, &epoch));
+        }
+    }
 }
@@ -10,6 +10,7 @@ pub enum ClarityVersion {
     Clarity1,
     Clarity2,
     Clarity3,
+    Clarity4,
 }
 
 impl fmt::Display for ClarityVersion {
@@ -18,13 +19,14 @@ impl fmt::Display for ClarityVersion {
             ClarityVersion::Clarity1 => write!(f, "Clarity 1"),
             ClarityVersion::Clarity2 => write!(f, "Clarity 2"),
             ClarityVersion::Clarity3 => write!(f, "Clarity 3"),
+            ClarityVersion::Clarity4 => write!(f, "Clarity 4"),
         }
     }
 }
 
 impl ClarityVersion {
     pub fn latest() -> ClarityVersion {
-        ClarityVersion::Clarity3
+        ClarityVersion::Clarity4
     }
     pub fn default_for_epoch(epoch_id: StacksEpochId) -> ClarityVersion {
         match epoch_id {
@@ -40,6 +42,7 @@ impl ClarityVersion {
             StacksEpochId::Epoch24 => ClarityVersion::Clarity2,
             StacksEpochId::Epoch25 => ClarityVersion::Clarity2,
             StacksEpochId::Epoch30 => ClarityVersion::Clarity3,
+            StacksEpochId::Epoch31 => ClarityVersion::Clarity4,
         }
     }
 }
@@ -55,9 +58,11 @@ impl FromStr for ClarityVersion {
             Ok(ClarityVersion::Clarity2)
         } else if s == "clarity3" {
             Ok(ClarityVersion::Clarity3)
+        } else if s == "clarity4" {
+            Ok(ClarityVersion::Clarity4)
         } else {
             Err(RuntimeErrorType::ParseError(
-                "Invalid clarity version. Valid versions are: Clarity1, Clarity2, Clarity3."
+                "Invalid clarity version. Valid versions are: Clarity1, Clarity2, Clarity3, Clarity4."
                     .to_string(),
             )
             .into())
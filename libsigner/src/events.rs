@@ -540,6 +540,8 @@ fn process_new_burn_block_event<T: SignerEventTrait>(
     Ok(event)
 }
 
+/// Parse the signer set and message ID out of the name of a signers' StackerDB contract
+/// (`signers-<signer set>-<message ID>`)
 pub fn get_signers_db_signer_set_message_id(name: &str) -> Option<(u32, u32)> {
     // Splitting the string by '-'
     let parts: Vec<&str> = name.split('-').collect();
@@ -162,31 +162,7 @@ pub fn decode_http_response(payload: &[u8]) -> Result<(HashMap<String, String>,
                     "No HTTP version given".to_string(),
                 ));
             }
-            let mut headers: HashMap<String, String> = HashMap::new();
-            for i in 0..resp.headers.len() {
-                let value = String::from_utf8(resp.headers[i].value.to_vec()).map_err(|_e| {
-                    RPCError::MalformedResponse("Invalid HTTP header value: not utf-8".to_string())
-                })?;
-                if !value.is_ascii() {
-                    return Err(RPCError::MalformedResponse(
-                        "Invalid HTTP response: header value is not ASCII-US".to_string(),
-                    ));
-                }
-                if value.len() > MAX_HTTP_HEADER_LEN {
-                    return Err(RPCError::MalformedResponse(
-                        "Invalid HTTP response: header value is too big".to_string(),
-                    ));
-                }
-
-                let key = resp.headers[i].name.to_string().to_lowercase();
-                if headers.contains_key(&key) {
-                    return Err(RPCError::MalformedResponse(format!(
-                        "Invalid HTTP respuest: duplicate header \"{}\"",
-                        key
-                    )));
-                }
-                headers.insert(key, value);
-            }
+            let headers = decode_http_response_headers(resp.headers)?;
             (headers, body_offset)
         } else {
             return Err(RPCError::Deserialize(
@@ -197,6 +173,38 @@ pub fn decode_http_response(payload: &[u8]) -> Result<(HashMap<String, String>,
     Ok((headers, body_offset))
 }
 
+/// Decode HTTP response headers into a table keyed by lowercase header name
+fn decode_http_response_headers(
+    resp_headers: &[httparse::Header],
+) -> Result<HashMap<String, String>, RPCError> {
+    let mut headers: HashMap<String, String> = HashMap::new();
+    for header in resp_headers.iter() {
+        let value = String::from_utf8(header.value.to_vec()).map_err(|_e| {
+            RPCError::MalformedResponse("Invalid HTTP header value: not utf-8".to_string())
+        })?;
+        if !value.is_ascii() {
+            return Err(RPCError::MalformedResponse(
+                "Invalid HTTP response: header value is not ASCII-US".to_string(),
+            ));
+        }
+        if value.len() > MAX_HTTP_HEADER_LEN {
+            return Err(RPCError::MalformedResponse(
+                "Invalid HTTP response: header value is too big".to_string(),
+            ));
+        }
+
+        let key = header.name.to_string().to_lowercase();
+        if headers.contains_key(&key) {
+            return Err(RPCError::MalformedResponse(format!(
+                "Invalid HTTP respuest: duplicate header \"{}\"",
+                key
+            )));
+        }
+        headers.insert(key, value);
+    }
+    Ok(headers)
+}
+
 /// Decode an HTTP body, given the headers.
 pub fn decode_http_body(headers: &HashMap<String, String>, mut buf: &[u8]) -> io::Result<Vec<u8>> {
     let chunked = if let Some(val) = headers.get("transfer-encoding") {
@@ -230,23 +238,7 @@ pub fn run_http_request<S: Read + Write>(
     content_type: Option<&str>,
     payload: &[u8],
 ) -> Result<Vec<u8>, RPCError> {
-    let content_length_hdr = if !payload.is_empty() {
-        format!("Content-Length: {}\r\n", payload.len())
-    } else {
-        "".to_string()
-    };
-
-    let req_txt = if let Some(content_type) = content_type {
-        format!(
-            "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Type: {}\r\n{}User-Agent: libsigner/0.1\r\nAccept: */*\r\n\r\n",
-            verb, path, host, content_type, content_length_hdr
-        )
-    } else {
-        format!(
-            "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n{}User-Agent: libsigner/0.1\r\nAccept: */*\r\n\r\n",
-            verb, path, host, content_length_hdr
-        )
-    };
+    let req_txt = format_http_request(host, verb, path, content_type, payload.len(), false);
     debug!("HTTP request\n{}", &req_txt);
 
     sock.write_all(req_txt.as_bytes())?;
@@ -266,3 +258,216 @@ pub fn run_http_request<S: Read + Write>(
 
     decode_http_body(&headers, &buf[body_offset..]).map_err(|e| e.into())
 }
+
+/// Format the request line and headers of an HTTP request.  If `keep_alive` is false, the server
+/// is asked to close the connection once it has replied.
+fn format_http_request(
+    host: &str,
+    verb: &str,
+    path: &str,
+    content_type: Option<&str>,
+    content_length: usize,
+    keep_alive: bool,
+) -> String {
+    let content_length_hdr = if content_length > 0 {
+        format!("Content-Length: {}\r\n", content_length)
+    } else {
+        "".to_string()
+    };
+    let connection = if keep_alive { "keep-alive" } else { "close" };
+
+    if let Some(content_type) = content_type {
+        format!(
+            "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: {}\r\nContent-Type: {}\r\n{}User-Agent: libsigner/0.1\r\nAccept: */*\r\n\r\n",
+            verb, path, host, connection, content_type, content_length_hdr
+        )
+    } else {
+        format!(
+            "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: {}\r\n{}User-Agent: libsigner/0.1\r\nAccept: */*\r\n\r\n",
+            verb, path, host, connection, content_length_hdr
+        )
+    }
+}
+
+/// An HTTP request to send with `run_pipelined_http_requests`
+#[derive(Debug, Clone)]
+pub struct HttpRequest<'a> {
+    pub verb: &'a str,
+    pub path: String,
+    pub content_type: Option<&'a str>,
+    pub payload: &'a [u8],
+}
+
+/// An HTTP response read from a connection that may carry further responses
+#[derive(Debug)]
+pub struct HttpResponse {
+    pub code: u16,
+    /// The body, decoded if it was chunked
+    pub body: Vec<u8>,
+    /// Whether the connection can carry another request
+    pub keep_alive: bool,
+}
+
+/// Decode the status line and headers at the start of `payload`, if they are all there.
+/// Returns the status code, the table of headers, the offset of the body, and whether the
+/// server will keep the connection open.
+fn decode_http_response_head(
+    payload: &[u8],
+) -> Result<Option<(u16, HashMap<String, String>, usize, bool)>, RPCError> {
+    let mut headers_buf = [httparse::EMPTY_HEADER; MAX_HTTP_HEADERS];
+    let mut resp = httparse::Response::new(&mut headers_buf);
+    let body_offset = match resp.parse(payload) {
+        Ok(httparse::Status::Complete(body_offset)) => body_offset,
+        Ok(httparse::Status::Partial) => return Ok(None),
+        Err(_) => {
+            return Err(RPCError::Deserialize(
+                "Failed to decode HTTP headers".to_string(),
+            ))
+        }
+    };
+    let code = resp
+        .code
+        .ok_or_else(|| RPCError::MalformedResponse("No HTTP status code returned".to_string()))?;
+    let version = resp
+        .version
+        .ok_or_else(|| RPCError::MalformedResponse("No HTTP version given".to_string()))?;
+    if version != 0 && version != 1 {
+        return Err(RPCError::MalformedResponse(format!(
+            "Unrecognized HTTP code {}",
+            version
+        )));
+    }
+    let headers = decode_http_response_headers(resp.headers)?;
+    // HTTP/1.0 connections are closed unless the server says otherwise
+    let keep_alive = match headers.get("connection") {
+        Some(value) if value.eq_ignore_ascii_case("close") => false,
+        Some(value) if value.eq_ignore_ascii_case("keep-alive") => true,
+        _ => version == 1,
+    };
+    Ok(Some((code, headers, body_offset, keep_alive)))
+}
+
+/// Read one HTTP response from `sock`, without reading past its end, so that the connection can
+/// carry further responses.  `buf` holds any bytes of this response that were already read from
+/// `sock`, and on return holds any bytes that were read past its end.
+pub fn read_http_response<S: Read>(
+    sock: &mut S,
+    buf: &mut Vec<u8>,
+) -> Result<HttpResponse, RPCError> {
+    let mut read_buf = [0u8; 4096];
+    let (code, headers, body_offset, mut keep_alive) = loop {
+        if let Some(head) = decode_http_response_head(buf)? {
+            break head;
+        }
+        if buf.len() > MAX_HTTP_HEADERS * MAX_HTTP_HEADER_LEN {
+            return Err(RPCError::MalformedResponse(
+                "Invalid HTTP response: headers are too big".to_string(),
+            ));
+        }
+        let nr = sock.read(&mut read_buf)?;
+        if nr == 0 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        buf.extend_from_slice(&read_buf[0..nr]);
+    };
+    let mut rest = buf.split_off(body_offset);
+    buf.clear();
+
+    let chunked = matches!(headers.get("transfer-encoding"), Some(val) if val == "chunked");
+    let content_length = headers
+        .get("content-length")
+        .map(|val| {
+            val.parse::<usize>().map_err(|_| {
+                RPCError::MalformedResponse("Invalid HTTP response: bad Content-Length".to_string())
+            })
+        })
+        .transpose()?;
+
+    let body = if chunked {
+        let mut leftover = io::Cursor::new(rest);
+        let mut state = HttpChunkedTransferReaderState::new(MAX_MESSAGE_LEN.into());
+        let mut body = vec![];
+        {
+            let mut fd = (&mut leftover).chain(&mut *sock);
+            while !state.is_eof() {
+                let (decoded, consumed) = state.do_read(&mut fd, &mut read_buf)?;
+                if consumed == 0 {
+                    return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+                }
+                body.extend_from_slice(&read_buf[0..decoded]);
+            }
+        }
+        let position = usize::try_from(leftover.position())
+            .expect("infallible: cursor position exceeds the buffer length");
+        *buf = leftover.into_inner().split_off(position);
+        body
+    } else if let Some(content_length) = content_length {
+        if content_length > usize::try_from(MAX_MESSAGE_LEN).unwrap_or(usize::MAX) {
+            return Err(RPCError::MalformedResponse(
+                "Invalid HTTP response: body is too big".to_string(),
+            ));
+        }
+        while rest.len() < content_length {
+            let nr = sock.read(&mut read_buf)?;
+            if nr == 0 {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+            }
+            rest.extend_from_slice(&read_buf[0..nr]);
+        }
+        *buf = rest.split_off(content_length);
+        rest
+    } else {
+        // the body runs until the server closes the connection
+        sock.read_to_end(&mut rest)?;
+        keep_alive = false;
+        rest
+    };
+
+    Ok(HttpResponse {
+        code,
+        body,
+        keep_alive,
+    })
+}
+
+/// Send `requests` over `sock` without waiting for replies, then read the replies in order,
+/// appending each to `results`: the body of a 200 reply, or `RPCError::HttpError` otherwise.
+/// Returns whether `sock` can carry further requests.  If the server closes the connection
+/// early, the requests without a reply in `results` were not answered and must be resent.
+pub fn run_pipelined_http_requests<S: Read + Write>(
+    sock: &mut S,
+    host: &str,
+    requests: &[HttpRequest],
+    results: &mut Vec<Result<Vec<u8>, RPCError>>,
+) -> Result<bool, RPCError> {
+    let mut req_bytes = vec![];
+    for request in requests.iter() {
+        let req_txt = format_http_request(
+            host,
+            request.verb,
+            &request.path,
+            request.content_type,
+            request.payload.len(),
+            true,
+        );
+        debug!("HTTP request\n{}", &req_txt);
+        req_bytes.extend_from_slice(req_txt.as_bytes());
+        req_bytes.extend_from_slice(request.payload);
+    }
+    sock.write_all(&req_bytes)?;
+
+    let mut buf = vec![];
+    for _ in requests.iter() {
+        let response = read_http_response(sock, &mut buf)?;
+        if response.code == 200 {
+            results.push(Ok(response.body));
+        } else {
+            results.push(Err(RPCError::HttpError(response.code.into())));
+        }
+        if !response.keep_alive {
+            return Ok(false);
+        }
+    }
+    // anything more from the server is unexpected
+    Ok(buf.is_empty())
+}
@@ -56,11 +56,11 @@ use lazy_static::lazy_static;
 
 pub use crate::error::{EventError, RPCError};
 pub use crate::events::{
-    BlockProposal, EventReceiver, EventStopSignaler, SignerEvent, SignerEventReceiver,
-    SignerEventTrait, SignerStopSignaler,
+    get_signers_db_signer_set_message_id, BlockProposal, EventReceiver, EventStopSignaler,
+    SignerEvent, SignerEventReceiver, SignerEventTrait, SignerStopSignaler,
 };
 pub use crate::runloop::{RunningSigner, Signer, SignerRunLoop};
-pub use crate::session::{SignerSession, StackerDBSession};
+pub use crate::session::{ConnectionPool, ConnectionPoolStats, SignerSession, StackerDBSession};
 pub use crate::signer_set::{Error as ParseSignerEntriesError, SignerEntries};
 
 /// A trait for message slots used for signer communication
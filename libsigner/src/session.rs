@@ -16,6 +16,8 @@
 
 use std::net::{SocketAddr, TcpStream};
 use std::str;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use clarity::vm::types::QualifiedContractIdentifier;
 use libstackerdb::{
@@ -26,7 +28,7 @@ use libstackerdb::{
 use stacks_common::codec::StacksMessageCodec;
 
 use crate::error::RPCError;
-use crate::http::run_http_request;
+use crate::http::{run_http_request, run_pipelined_http_requests, HttpRequest};
 
 /// Trait for connecting to and querying a signer Stacker DB replica
 pub trait SignerSession {
@@ -94,6 +96,102 @@ pub trait SignerSession {
     }
 }
 
+/// Counts of the connections a `ConnectionPool` handed out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConnectionPoolStats {
+    /// Connections newly opened
+    pub opened: u64,
+    /// Idle connections reused
+    pub reused: u64,
+}
+
+#[derive(Debug)]
+struct IdleConnection {
+    host: String,
+    sock: TcpStream,
+    idle_since: Instant,
+}
+
+#[derive(Debug)]
+struct ConnectionPoolState {
+    idle: Vec<IdleConnection>,
+    max_idle: usize,
+    idle_timeout: Duration,
+    stats: ConnectionPoolStats,
+}
+
+/// A pool of idle keep-alive connections to Stacks nodes.  Clones share the same pool, so one
+/// pool can serve every `StackerDBSession` of a signer.
+#[derive(Debug, Clone)]
+pub struct ConnectionPool {
+    state: Arc<Mutex<ConnectionPoolState>>,
+}
+
+impl ConnectionPool {
+    /// Keep up to `max_idle` connections open, each for up to `idle_timeout` since its last use
+    pub fn new(max_idle: usize, idle_timeout: Duration) -> ConnectionPool {
+        ConnectionPool {
+            state: Arc::new(Mutex::new(ConnectionPoolState {
+                idle: vec![],
+                max_idle,
+                idle_timeout,
+                stats: ConnectionPoolStats::default(),
+            })),
+        }
+    }
+
+    /// Take the most recently used idle connection to `host`, or open a new one.
+    /// Returns the connection and whether it was reused.
+    fn checkout(&self, host: &str) -> Result<(TcpStream, bool), RPCError> {
+        {
+            let mut state = self
+                .state
+                .lock()
+                .expect("FATAL: connection pool lock poisoned");
+            let idle_timeout = state.idle_timeout;
+            state
+                .idle
+                .retain(|conn| conn.idle_since.elapsed() < idle_timeout);
+            if let Some(index) = state.idle.iter().rposition(|conn| conn.host == host) {
+                let conn = state.idle.remove(index);
+                state.stats.reused = state.stats.reused.saturating_add(1);
+                return Ok((conn.sock, true));
+            }
+            state.stats.opened = state.stats.opened.saturating_add(1);
+        }
+        debug!("connect to {}", host);
+        Ok((TcpStream::connect(host)?, false))
+    }
+
+    /// Return a connection that can carry further requests to the pool
+    fn checkin(&self, host: &str, sock: TcpStream) {
+        let mut state = self
+            .state
+            .lock()
+            .expect("FATAL: connection pool lock poisoned");
+        if state.max_idle == 0 {
+            return;
+        }
+        if state.idle.len() >= state.max_idle {
+            // the oldest connection goes first
+            state.idle.remove(0);
+        }
+        state.idle.push(IdleConnection {
+            host: host.to_string(),
+            sock,
+            idle_since: Instant::now(),
+        });
+    }
+
+    /// The number of connections opened and reused so far
+    pub fn stats(&self) -> ConnectionPoolStats {
+        self.state
+            .lock()
+            .expect("FATAL: connection pool lock poisoned")
+            .stats
+    }
+}
+
 /// signer session for a stackerdb instance
 #[derive(Debug)]
 pub struct StackerDBSession {
@@ -103,6 +201,8 @@ pub struct StackerDBSession {
     pub stackerdb_contract_id: QualifiedContractIdentifier,
     /// connection to the replica
     sock: Option<TcpStream>,
+    /// keep-alive connections shared with other sessions, if any
+    pool: Option<ConnectionPool>,
 }
 
 impl StackerDBSession {
@@ -112,9 +212,16 @@ impl StackerDBSession {
             host: host.to_owned(),
             stackerdb_contract_id,
             sock: None,
+            pool: None,
         }
     }
 
+    /// Send requests over keep-alive connections from `pool`, and pipeline queries for several
+    /// chunks over one connection, instead of opening a connection per request
+    pub fn set_connection_pool(&mut self, pool: ConnectionPool) {
+        self.pool = Some(pool);
+    }
+
     /// connect or reconnect to the node
     fn connect_or_reconnect(&mut self) -> Result<(), RPCError> {
         debug!("connect to {}", &self.host);
@@ -153,10 +260,138 @@ impl StackerDBSession {
         content_type: Option<&str>,
         payload: &[u8],
     ) -> Result<Vec<u8>, RPCError> {
+        if let Some(pool) = self.pool.clone() {
+            let request = HttpRequest {
+                verb,
+                path: path.to_string(),
+                content_type,
+                payload,
+            };
+            return self
+                .pooled_rpc_requests(&pool, &[request])?
+                .pop()
+                .ok_or(RPCError::NotConnected)?;
+        }
         self.with_socket(|session, sock| {
             run_http_request(sock, &session.host, verb, path, content_type, payload)
         })?
     }
+
+    /// send HTTP GET requests for each of `paths` and receive the replies, in order.
+    /// With a connection pool, the requests are pipelined over one connection.  Without one,
+    /// requests stop at the first error other than a 404.
+    fn rpc_get_requests(
+        &mut self,
+        paths: Vec<String>,
+    ) -> Result<Vec<Result<Vec<u8>, RPCError>>, RPCError> {
+        if let Some(pool) = self.pool.clone() {
+            let requests: Vec<_> = paths
+                .into_iter()
+                .map(|path| HttpRequest {
+                    verb: "GET",
+                    path,
+                    content_type: None,
+                    payload: &[],
+                })
+                .collect();
+            return self.pooled_rpc_requests(&pool, &requests);
+        }
+        let mut results = vec![];
+        for path in paths.iter() {
+            let result = self.rpc_request("GET", path, None, &[]);
+            let stop = match &result {
+                Ok(_) | Err(RPCError::HttpError(404)) => false,
+                Err(_) => true,
+            };
+            results.push(result);
+            if stop {
+                break;
+            }
+        }
+        Ok(results)
+    }
+
+    /// Send `requests` over pooled connections, and receive their replies in order.  A reused
+    /// connection that fails before any reply may have been closed by the node while idle, so
+    /// its requests are retried on another connection.
+    fn pooled_rpc_requests(
+        &mut self,
+        pool: &ConnectionPool,
+        requests: &[HttpRequest],
+    ) -> Result<Vec<Result<Vec<u8>, RPCError>>, RPCError> {
+        let mut results = Vec::with_capacity(requests.len());
+        while results.len() < requests.len() {
+            let (mut sock, reused) = pool.checkout(&self.host)?;
+            let answered = results.len();
+            match run_pipelined_http_requests(
+                &mut sock,
+                &self.host,
+                &requests[answered..],
+                &mut results,
+            ) {
+                Ok(true) => pool.checkin(&self.host, sock),
+                Ok(false) => {}
+                Err(e) => {
+                    if results.len() == answered && !reused {
+                        return Err(e);
+                    }
+                    debug!(
+                        "Pooled connection to {} failed after {} of {} replies: {:?}",
+                        &self.host,
+                        results.len(),
+                        requests.len(),
+                        &e
+                    );
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    /// query the replica for the latest chunks of slots in any of its StackerDBs, given as
+    /// (contract, slot ID) pairs.  With a connection pool, the queries are pipelined over one
+    /// connection, so this takes a single round trip.
+    pub fn get_latest_chunks_in(
+        &mut self,
+        slots: &[(QualifiedContractIdentifier, u32)],
+    ) -> Result<Vec<Option<Vec<u8>>>, RPCError> {
+        let mut payloads = vec![];
+        let paths = slots
+            .iter()
+            .map(|(contract_id, slot_id)| {
+                stackerdb_get_chunk_path(contract_id.clone(), *slot_id, None)
+            })
+            .collect();
+        for ((contract_id, _), result) in slots.iter().zip(self.rpc_get_requests(paths)?) {
+            let limit = if contract_id.name.starts_with("signer") {
+                SIGNERS_STACKERDB_CHUNK_SIZE
+            } else {
+                usize::try_from(STACKERDB_MAX_CHUNK_SIZE)
+                    .expect("infallible: StackerDB chunk size exceeds usize::MAX")
+            };
+            let chunk = match result {
+                Ok(body_bytes) => {
+                    // Verify that the chunk is not too large
+                    if body_bytes.len() > limit {
+                        None
+                    } else {
+                        Some(body_bytes)
+                    }
+                }
+                Err(RPCError::HttpError(code)) => {
+                    if code != 404 {
+                        return Err(RPCError::HttpError(code));
+                    }
+                    None
+                }
+                Err(e) => {
+                    return Err(e);
+                }
+            };
+            payloads.push(chunk);
+        }
+        Ok(payloads)
+    }
 }
 
 impl SignerSession for StackerDBSession {
@@ -189,14 +424,19 @@ impl SignerSession for StackerDBSession {
         &mut self,
         slots_and_versions: &[(u32, u32)],
     ) -> Result<Vec<Option<Vec<u8>>>, RPCError> {
+        let paths = slots_and_versions
+            .iter()
+            .map(|(slot_id, slot_version)| {
+                stackerdb_get_chunk_path(
+                    self.stackerdb_contract_id.clone(),
+                    *slot_id,
+                    Some(*slot_version),
+                )
+            })
+            .collect();
         let mut payloads = vec![];
-        for (slot_id, slot_version) in slots_and_versions.iter() {
-            let path = stackerdb_get_chunk_path(
-                self.stackerdb_contract_id.clone(),
-                *slot_id,
-                Some(*slot_version),
-            );
-            let chunk = match self.rpc_request("GET", &path, None, &[]) {
+        for result in self.rpc_get_requests(paths)? {
+            let chunk = match result {
                 Ok(body_bytes) => Some(body_bytes),
                 Err(RPCError::HttpError(code)) => {
                     if code != 404 {
@@ -215,37 +455,11 @@ impl SignerSession for StackerDBSession {
 
     /// query the replica for zero or more latest chunks
     fn get_latest_chunks(&mut self, slot_ids: &[u32]) -> Result<Vec<Option<Vec<u8>>>, RPCError> {
-        let mut payloads = vec![];
-        let limit = if self.stackerdb_contract_id.name.starts_with("signer") {
-            SIGNERS_STACKERDB_CHUNK_SIZE
-        } else {
-            usize::try_from(STACKERDB_MAX_CHUNK_SIZE)
-                .expect("infallible: StackerDB chunk size exceeds usize::MAX")
-        };
-        for slot_id in slot_ids.iter() {
-            let path = stackerdb_get_chunk_path(self.stackerdb_contract_id.clone(), *slot_id, None);
-            let chunk = match self.rpc_request("GET", &path, None, &[]) {
-                Ok(body_bytes) => {
-                    // Verify that the chunk is not too large
-                    if body_bytes.len() > limit {
-                        None
-                    } else {
-                        Some(body_bytes)
-                    }
-                }
-                Err(RPCError::HttpError(code)) => {
-                    if code != 404 {
-                        return Err(RPCError::HttpError(code));
-                    }
-                    None
-                }
-                Err(e) => {
-                    return Err(e);
-                }
-            };
-            payloads.push(chunk);
-        }
-        Ok(payloads)
+        let slots: Vec<_> = slot_ids
+            .iter()
+            .map(|slot_id| (self.stackerdb_contract_id.clone(), *slot_id))
+            .collect();
+        self.get_latest_chunks_in(&slots)
     }
 
     /// upload a chunk
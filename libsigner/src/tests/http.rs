@@ -21,7 +21,10 @@ use hashbrown::HashMap;
 use stacks_common::util::chunked_encoding::*;
 
 use crate::error::{EventError, RPCError};
-use crate::http::{decode_http_body, decode_http_request, decode_http_response, run_http_request};
+use crate::http::{
+    decode_http_body, decode_http_request, decode_http_response, read_http_response,
+    run_http_request, run_pipelined_http_requests, HttpRequest,
+};
 
 #[test]
 fn test_decode_http_request_ok() {
@@ -342,3 +345,111 @@ fn test_run_http_request_no_body() {
         assert_eq!(result_plain.len(), 0);
     }
 }
+
+#[test]
+fn test_read_http_response_keep_alive() {
+    let replies = [
+        "HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello",
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n",
+        "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n6\r\n world\r\n0\r\n\r\n",
+        "HTTP/1.1 200 OK\r\nConnection: close\r\n\r\nbye",
+    ];
+    let mut msock = MockHTTPSocket::new(replies.concat());
+    let mut buf = vec![];
+
+    let response = read_http_response(&mut msock, &mut buf).unwrap();
+    assert_eq!(
+        (response.code, response.body.as_slice()),
+        (200, "hello".as_bytes())
+    );
+    assert!(response.keep_alive);
+    // the rest of the replies were read too, and are kept for the next call
+    assert!(buf.starts_with(b"HTTP/1.1 404"));
+
+    let response = read_http_response(&mut msock, &mut buf).unwrap();
+    assert_eq!((response.code, response.body.len()), (404, 0));
+    assert!(response.keep_alive);
+
+    let response = read_http_response(&mut msock, &mut buf).unwrap();
+    assert_eq!(response.body, "hello world".as_bytes());
+    assert!(response.keep_alive);
+    assert!(buf.starts_with(b"HTTP/1.1 200 OK\r\nConnection: close"));
+
+    // a reply without a length runs until the connection closes
+    let response = read_http_response(&mut msock, &mut buf).unwrap();
+    assert_eq!(response.body, "bye".as_bytes());
+    assert!(!response.keep_alive);
+    assert!(buf.is_empty());
+
+    // truncated replies are errors
+    for reply in [
+        "HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nhello",
+        "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n",
+        "HTTP/1.1 200 OK\r\nContent-Le",
+    ] {
+        let mut msock = MockHTTPSocket::new(reply.to_string());
+        assert!(read_http_response(&mut msock, &mut vec![]).is_err());
+    }
+
+    // HTTP/1.0 connections close unless the server keeps them open
+    let mut msock = MockHTTPSocket::new("HTTP/1.0 200 OK\r\nContent-Length: 0\r\n\r\n".into());
+    assert!(
+        !read_http_response(&mut msock, &mut vec![])
+            .unwrap()
+            .keep_alive
+    );
+    let mut msock = MockHTTPSocket::new(
+        "HTTP/1.0 200 OK\r\nConnection: keep-alive\r\nContent-Length: 0\r\n\r\n".into(),
+    );
+    assert!(
+        read_http_response(&mut msock, &mut vec![])
+            .unwrap()
+            .keep_alive
+    );
+}
+
+#[test]
+fn test_run_pipelined_http_requests() {
+    let requests: Vec<_> = ["/a", "/b", "/c"]
+        .iter()
+        .map(|path| HttpRequest {
+            verb: "GET",
+            path: path.to_string(),
+            content_type: None,
+            payload: &[],
+        })
+        .collect();
+
+    let mut msock = MockHTTPSocket::new(
+        [
+            "HTTP/1.1 200 OK\r\nContent-Length: 1\r\n\r\na",
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n",
+            "HTTP/1.1 200 OK\r\nContent-Length: 1\r\n\r\nc",
+        ]
+        .concat(),
+    );
+    let mut results = vec![];
+    assert!(
+        run_pipelined_http_requests(&mut msock, "127.0.0.1:20443", &requests, &mut results)
+            .unwrap()
+    );
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].as_ref().unwrap(), b"a");
+    assert!(matches!(results[1], Err(RPCError::HttpError(404))));
+    assert_eq!(results[2].as_ref().unwrap(), b"c");
+    // all the requests were sent up front, asking to keep the connection open
+    let request_txt = str::from_utf8(&msock.request).unwrap();
+    assert_eq!(request_txt.matches("Connection: keep-alive\r\n").count(), 3);
+    assert!(request_txt.starts_with("GET /a HTTP/1.1\r\n"));
+
+    // the server closes the connection after the first reply
+    let mut msock = MockHTTPSocket::new(
+        "HTTP/1.1 200 OK\r\nConnection: close\r\nContent-Length: 1\r\n\r\na".into(),
+    );
+    let mut results = vec![];
+    assert!(
+        !run_pipelined_http_requests(&mut msock, "127.0.0.1:20443", &requests, &mut results)
+            .unwrap()
+    );
+    assert_eq!(results.len(), 1);
+}
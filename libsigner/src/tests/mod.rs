@@ -18,7 +18,7 @@ mod http;
 
 use std::fmt::Debug;
 use std::io::{Read, Write};
-use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
 use std::sync::mpsc::{channel, Receiver, Sender};
 use std::time::Duration;
 use std::{mem, thread};
@@ -43,7 +43,10 @@ use stacks_common::util::sleep_ms;
 
 use crate::events::{SignerEvent, SignerEventTrait};
 use crate::v0::messages::{BlockRejection, SignerMessage};
-use crate::{BlockProposal, Signer, SignerEventReceiver, SignerRunLoop};
+use crate::{
+    BlockProposal, ConnectionPool, ConnectionPoolStats, Signer, SignerEventReceiver, SignerRunLoop,
+    SignerSession, StackerDBSession,
+};
 
 /// Simple runloop implementation.  It receives `max_events` events and returns `events` from the
 /// last call to `run_one_pass` as its final state.
@@ -237,3 +240,75 @@ fn test_status_endpoint() {
     assert_eq!(sent_events, accepted_events);
     mock_stacks_node.join().unwrap();
 }
+
+/// Read one HTTP request without a body from `sock`
+fn read_test_http_request(sock: &mut TcpStream) -> Option<String> {
+    let mut request = vec![];
+    let mut byte = [0u8; 1];
+    while !request.ends_with(b"\r\n\r\n") {
+        if sock.read(&mut byte).ok()? == 0 {
+            return None;
+        }
+        request.push(byte[0]);
+    }
+    String::from_utf8(request).ok()
+}
+
+#[test]
+fn test_stackerdb_session_connection_pool() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let host = listener.local_addr().unwrap().to_string();
+
+    // a node that serves any number of requests over a single connection
+    let server = thread::spawn(move || {
+        let (mut sock, _) = listener.accept().unwrap();
+        let mut num_requests = 0;
+        while let Some(request) = read_test_http_request(&mut sock) {
+            assert!(request.contains("Connection: keep-alive\r\n"));
+            num_requests += 1;
+            let reply = if request.contains("/1 ") {
+                "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_string()
+            } else {
+                "HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nchunk".to_string()
+            };
+            sock.write_all(reply.as_bytes()).unwrap();
+        }
+        num_requests
+    });
+
+    let pool = ConnectionPool::new(4, Duration::from_secs(60));
+    let contract = boot_code_id(SIGNERS_NAME, false);
+    let mut session = StackerDBSession::new(&host, contract.clone());
+    session.set_connection_pool(pool.clone());
+    let mut other_session = StackerDBSession::new(&host, contract.clone());
+    other_session.set_connection_pool(pool.clone());
+
+    assert_eq!(
+        session.get_latest_chunks(&[0, 1, 2]).unwrap(),
+        vec![Some(b"chunk".to_vec()), None, Some(b"chunk".to_vec())]
+    );
+    assert_eq!(
+        other_session.get_latest_chunk(3).unwrap(),
+        Some(b"chunk".to_vec())
+    );
+    // slots in different StackerDBs share the connection too
+    let other_contract = boot_code_id("signers-0-1", false);
+    assert_eq!(
+        session
+            .get_latest_chunks_in(&[(contract, 0), (other_contract, 1)])
+            .unwrap(),
+        vec![Some(b"chunk".to_vec()), None]
+    );
+    assert_eq!(
+        pool.stats(),
+        ConnectionPoolStats {
+            opened: 1,
+            reused: 2
+        }
+    );
+
+    drop(pool);
+    drop(session);
+    drop(other_session);
+    assert_eq!(server.join().unwrap(), 6);
+}
@@ -61,6 +61,7 @@ use stacks_common::codec::{
     StacksMessageCodec,
 };
 use stacks_common::consts::SIGNER_SLOTS_PER_USER;
+use stacks_common::types::StacksPublicKeyBuffer;
 use stacks_common::util::hash::Sha512Trunc256Sum;
 use tiny_http::{
     Method as HttpMethod, Request as HttpRequest, Response as HttpResponse, Server as HttpServer,
@@ -78,7 +79,9 @@ define_u8_enum!(
 ///  the contract index in the signers contracts (i.e., X in signers-0-X)
 MessageSlotID {
     /// Block Response message from signers
-    BlockResponse = 1
+    BlockResponse = 1,
+    /// Key rotation announcement from signers
+    KeyRotation = 2
 });
 
 define_u8_enum!(
@@ -119,7 +122,9 @@ SignerMessageTypePrefix {
     /// Mock block signature message from Epoch 2.5 signers
     MockSignature = 4,
     /// Mock block message from Epoch 2.5 miners
-    MockBlock = 5
+    MockBlock = 5,
+    /// Key rotation announcement from signers
+    KeyRotation = 6
 });
 
 #[cfg_attr(test, mutants::skip)]
@@ -165,6 +170,7 @@ impl From<&SignerMessage> for SignerMessageTypePrefix {
             SignerMessage::MockProposal(_) => SignerMessageTypePrefix::MockProposal,
             SignerMessage::MockSignature(_) => SignerMessageTypePrefix::MockSignature,
             SignerMessage::MockBlock(_) => SignerMessageTypePrefix::MockBlock,
+            SignerMessage::KeyRotation(_) => SignerMessageTypePrefix::KeyRotation,
         }
     }
 }
@@ -184,6 +190,8 @@ pub enum SignerMessage {
     MockProposal(MockProposal),
     /// A mock block from the epoch 2.5 miners
     MockBlock(MockBlock),
+    /// A signer's announcement that it will sign with a new key
+    KeyRotation(KeyRotation),
 }
 
 impl SignerMessage {
@@ -198,6 +206,7 @@ impl SignerMessage {
             | Self::MockProposal(_)
             | Self::MockBlock(_) => None,
             Self::BlockResponse(_) | Self::MockSignature(_) => Some(MessageSlotID::BlockResponse), // Mock signature uses the same slot as block response since its exclusively for epoch 2.5 testing
+            Self::KeyRotation(_) => Some(MessageSlotID::KeyRotation),
         }
     }
 }
@@ -214,6 +223,7 @@ impl StacksMessageCodec for SignerMessage {
             SignerMessage::MockSignature(signature) => signature.consensus_serialize(fd),
             SignerMessage::MockProposal(message) => message.consensus_serialize(fd),
             SignerMessage::MockBlock(block) => block.consensus_serialize(fd),
+            SignerMessage::KeyRotation(rotation) => rotation.consensus_serialize(fd),
         }?;
         Ok(())
     }
@@ -247,6 +257,10 @@ impl StacksMessageCodec for SignerMessage {
                 let block = StacksMessageCodec::consensus_deserialize(fd)?;
                 SignerMessage::MockBlock(block)
             }
+            SignerMessageTypePrefix::KeyRotation => {
+                let rotation = StacksMessageCodec::consensus_deserialize(fd)?;
+                SignerMessage::KeyRotation(rotation)
+            }
         };
         Ok(message)
     }
@@ -521,6 +535,133 @@ impl StacksMessageCodec for MockBlock {
     }
 }
 
+/// A signer's announcement that, from `activation_reward_cycle` on, it signs with
+/// `next_public_key`. It is signed by both the current key and the next key, so that the
+/// other signers and miners know the current key holder owns the next key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyRotation {
+    /// The public key the signer will sign with
+    pub next_public_key: StacksPublicKeyBuffer,
+    /// The first reward cycle signed with the next key
+    pub activation_reward_cycle: u64,
+    /// The chain id
+    pub chain_id: u32,
+    /// The signature across the announcement by the current key
+    signature: MessageSignature,
+    /// The signature across the announcement by the next key
+    next_key_signature: MessageSignature,
+    /// Signer message metadata
+    pub metadata: SignerMessageMetadata,
+}
+
+impl KeyRotation {
+    /// Create a new key rotation announcement, signed by both the current and the next key
+    pub fn new(
+        current_private_key: &StacksPrivateKey,
+        next_private_key: &StacksPrivateKey,
+        activation_reward_cycle: u64,
+        mainnet: bool,
+    ) -> Self {
+        let chain_id = if mainnet {
+            CHAIN_ID_MAINNET
+        } else {
+            CHAIN_ID_TESTNET
+        };
+        let mut rotation = Self {
+            next_public_key: StacksPublicKeyBuffer::from_public_key(
+                &StacksPublicKey::from_private(next_private_key),
+            ),
+            activation_reward_cycle,
+            chain_id,
+            signature: MessageSignature::empty(),
+            next_key_signature: MessageSignature::empty(),
+            metadata: SignerMessageMetadata::default(),
+        };
+        rotation
+            .sign(current_private_key, next_private_key)
+            .expect("Failed to sign KeyRotation");
+        rotation
+    }
+
+    /// The signature hash for the key rotation
+    pub fn hash(&self) -> Sha256Sum {
+        let domain_tuple = make_structured_data_domain("key-rotation", "1.0.0", self.chain_id);
+        let data_tuple = Value::Tuple(
+            TupleData::from_data(vec![
+                (
+                    "next-public-key".into(),
+                    Value::buff_from(self.next_public_key.as_bytes().into()).unwrap(),
+                ),
+                (
+                    "activation-reward-cycle".into(),
+                    Value::UInt(self.activation_reward_cycle.into()),
+                ),
+            ])
+            .expect("Error creating signature hash"),
+        );
+        structured_data_message_hash(data_tuple, domain_tuple)
+    }
+
+    /// Sign the key rotation with both keys and set the internal signature fields
+    fn sign(
+        &mut self,
+        current_private_key: &StacksPrivateKey,
+        next_private_key: &StacksPrivateKey,
+    ) -> Result<(), String> {
+        let signature_hash = self.hash();
+        self.signature = current_private_key.sign(signature_hash.as_bytes())?;
+        self.next_key_signature = next_private_key.sign(signature_hash.as_bytes())?;
+        Ok(())
+    }
+
+    /// Verify the key rotation against the provided current signer public key, and that it
+    /// was also signed by the next key
+    pub fn verify(&self, public_key: &StacksPublicKey) -> Result<bool, String> {
+        if self.signature == MessageSignature::empty()
+            || self.next_key_signature == MessageSignature::empty()
+        {
+            return Ok(false);
+        }
+        let signature_hash = self.hash();
+        let next_public_key = self.next_public_key.to_public_key()?;
+        Ok(public_key
+            .verify(&signature_hash.0, &self.signature)
+            .map_err(|e| e.to_string())?
+            && next_public_key
+                .verify(&signature_hash.0, &self.next_key_signature)
+                .map_err(|e| e.to_string())?)
+    }
+}
+
+impl StacksMessageCodec for KeyRotation {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), CodecError> {
+        write_next(fd, &self.next_public_key)?;
+        write_next(fd, &self.activation_reward_cycle)?;
+        write_next(fd, &self.chain_id)?;
+        write_next(fd, &self.signature)?;
+        write_next(fd, &self.next_key_signature)?;
+        write_next(fd, &self.metadata)?;
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, CodecError> {
+        let next_public_key = read_next::<StacksPublicKeyBuffer, _>(fd)?;
+        let activation_reward_cycle = read_next::<u64, _>(fd)?;
+        let chain_id = read_next::<u32, _>(fd)?;
+        let signature = read_next::<MessageSignature, _>(fd)?;
+        let next_key_signature = read_next::<MessageSignature, _>(fd)?;
+        let metadata = read_next::<SignerMessageMetadata, _>(fd)?;
+        Ok(Self {
+            next_public_key,
+            activation_reward_cycle,
+            chain_id,
+            signature,
+            next_key_signature,
+            metadata,
+        })
+    }
+}
+
 define_u8_enum!(
 /// Enum representing the reject code type prefix
 RejectCodeTypePrefix {
@@ -535,7 +676,11 @@ RejectCodeTypePrefix {
     /// The block was rejected due to a mismatch with expected sortition view
     SortitionViewMismatch = 4,
     /// The block was rejected due to a testing directive
-    TestingDirective = 5
+    TestingDirective = 5,
+    /// The block was rejected because it violates the signer's block policy
+    PolicyViolation = 6,
+    /// The block was rejected because the signer already signed a conflicting block
+    ConflictingBlock = 7
 });
 
 impl TryFrom<u8> for RejectCodeTypePrefix {
@@ -556,10 +701,34 @@ impl From<&RejectCode> for RejectCodeTypePrefix {
             RejectCode::NoSortitionView => RejectCodeTypePrefix::NoSortitionView,
             RejectCode::SortitionViewMismatch => RejectCodeTypePrefix::SortitionViewMismatch,
             RejectCode::TestingDirective => RejectCodeTypePrefix::TestingDirective,
+            RejectCode::PolicyViolation(_) => RejectCodeTypePrefix::PolicyViolation,
+            RejectCode::ConflictingBlock => RejectCodeTypePrefix::ConflictingBlock,
         }
     }
 }
 
+define_u8_enum!(
+/// The rules of a signer's block policy, which an operator can configure to reject
+/// otherwise valid blocks
+PolicyRule {
+    /// The block contains too many transactions calling a contract
+    MaxContractTxs = 0,
+    /// The block leaves out pending transactions calling a contract
+    RequiredContractTxs = 1,
+    /// The block is empty while the mempool is busy
+    EmptyBlockUnderLoad = 2,
+    /// The block's transactions don't follow the required ordering rule
+    TxOrdering = 3
+});
+
+impl TryFrom<u8> for PolicyRule {
+    type Error = CodecError;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Self::from_u8(value)
+            .ok_or_else(|| CodecError::DeserializeError(format!("Unknown policy rule: {value}")))
+    }
+}
+
 /// This enum is used to supply a `reason_code` for block rejections
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum RejectCode {
@@ -575,6 +744,11 @@ pub enum RejectCode {
     SortitionViewMismatch,
     /// The block was rejected due to a testing directive
     TestingDirective,
+    /// The block violates a rule of the signer's block policy
+    PolicyViolation(PolicyRule),
+    /// The signer already signed a different block at the same tenure and height, which has
+    /// not been globally rejected
+    ConflictingBlock,
 }
 
 define_u8_enum!(
@@ -923,11 +1097,13 @@ impl StacksMessageCodec for RejectCode {
         // Do not do a single match here as we may add other variants in the future and don't want to miss adding it
         match self {
             RejectCode::ValidationFailed(code) => write_next(fd, &(*code as u8))?,
+            RejectCode::PolicyViolation(rule) => write_next(fd, &rule.to_u8())?,
             RejectCode::ConnectivityIssues
             | RejectCode::RejectedInPriorRound
             | RejectCode::NoSortitionView
             | RejectCode::SortitionViewMismatch
-            | RejectCode::TestingDirective => {
+            | RejectCode::TestingDirective
+            | RejectCode::ConflictingBlock => {
                 // No additional data to serialize / deserialize
             }
         };
@@ -951,6 +1127,10 @@ impl StacksMessageCodec for RejectCode {
             RejectCodeTypePrefix::NoSortitionView => RejectCode::NoSortitionView,
             RejectCodeTypePrefix::SortitionViewMismatch => RejectCode::SortitionViewMismatch,
             RejectCodeTypePrefix::TestingDirective => RejectCode::TestingDirective,
+            RejectCodeTypePrefix::PolicyViolation => {
+                RejectCode::PolicyViolation(PolicyRule::try_from(read_next::<u8, _>(fd)?)?)
+            }
+            RejectCodeTypePrefix::ConflictingBlock => RejectCode::ConflictingBlock,
         };
         Ok(code)
     }
@@ -981,10 +1161,23 @@ impl std::fmt::Display for RejectCode {
             RejectCode::TestingDirective => {
                 write!(f, "The block was rejected due to a testing directive.")
             }
+            RejectCode::PolicyViolation(rule) => {
+                write!(f, "The block violates the signer's {rule:?} policy.")
+            }
+            RejectCode::ConflictingBlock => write!(
+                f,
+                "The signer already signed a different block at the same tenure and height."
+            ),
         }
     }
 }
 
+impl From<KeyRotation> for SignerMessage {
+    fn from(rotation: KeyRotation) -> Self {
+        Self::KeyRotation(rotation)
+    }
+}
+
 impl From<BlockResponse> for SignerMessage {
     fn from(block_response: BlockResponse) -> Self {
         Self::BlockResponse(block_response)
@@ -1037,6 +1230,22 @@ mod test {
         let deserialized_code = read_next::<RejectCode, _>(&mut &serialized_code[..])
             .expect("Failed to deserialize RejectCode");
         assert_eq!(code, deserialized_code);
+
+        let code = RejectCode::ConflictingBlock;
+        let serialized_code = code.serialize_to_vec();
+        assert_eq!(serialized_code, vec![7u8]);
+        let deserialized_code = read_next::<RejectCode, _>(&mut &serialized_code[..])
+            .expect("Failed to deserialize RejectCode");
+        assert_eq!(code, deserialized_code);
+
+        for rule in PolicyRule::ALL {
+            let code = RejectCode::PolicyViolation(*rule);
+            let serialized_code = code.serialize_to_vec();
+            let deserialized_code = read_next::<RejectCode, _>(&mut &serialized_code[..])
+                .expect("Failed to deserialize RejectCode");
+            assert_eq!(code, deserialized_code);
+        }
+        assert!(read_next::<RejectCode, _>(&mut &[6u8, 4u8][..]).is_err());
     }
 
     #[test]
@@ -1238,6 +1447,44 @@ mod test {
         assert_eq!(mock_block, deserialized_data);
     }
 
+    #[test]
+    fn verify_and_serde_key_rotation() {
+        let current_private_key = StacksPrivateKey::new();
+        let next_private_key = StacksPrivateKey::new();
+        let rotation = KeyRotation::new(&current_private_key, &next_private_key, 12, false);
+        assert_eq!(
+            rotation.next_public_key.to_public_key().unwrap(),
+            StacksPublicKey::from_private(&next_private_key)
+        );
+        assert!(rotation
+            .verify(&StacksPublicKey::from_private(&current_private_key))
+            .expect("Failed to verify KeyRotation"));
+        assert!(!rotation
+            .verify(&StacksPublicKey::from_private(&StacksPrivateKey::new()))
+            .expect("Failed to verify KeyRotation"));
+
+        // the next key must have signed it too
+        let mut unproven = rotation.clone();
+        unproven.next_key_signature = MessageSignature::empty();
+        assert!(!unproven
+            .verify(&StacksPublicKey::from_private(&current_private_key))
+            .expect("Failed to verify KeyRotation"));
+        let mut swapped = rotation.clone();
+        swapped.next_public_key = StacksPublicKeyBuffer::from_public_key(
+            &StacksPublicKey::from_private(&StacksPrivateKey::new()),
+        );
+        assert!(!swapped
+            .verify(&StacksPublicKey::from_private(&current_private_key))
+            .expect("Failed to verify KeyRotation"));
+
+        let message = SignerMessage::KeyRotation(rotation);
+        assert_eq!(message.msg_id(), Some(MessageSlotID::KeyRotation));
+        let serialized_message = message.serialize_to_vec();
+        let deserialized_message = read_next::<SignerMessage, _>(&mut &serialized_message[..])
+            .expect("Failed to deserialize KeyRotation");
+        assert_eq!(message, deserialized_message);
+    }
+
     #[test]
     fn test_backwards_compatibility() {
         let block_rejected_hex = "010100000050426c6f636b206973206e6f7420612074656e7572652d737461727420626c6f636b2c20616e642068617320616e20756e7265636f676e697a65642074656e75726520636f6e73656e7375732068617368000691f95f84b7045f7dce7757052caa986ef042cb58f7df5031a3b5b5d0e3dda63e80000000006fb349212e1a1af1a3c712878d5159b5ec14636adb6f70be00a6da4ad4f88a9934d8a9abb229620dd8e0f225d63401e36c64817fb29e6c05591dcbe95c512df3";
@@ -34,7 +34,7 @@ use crate::deps_common::bitcoin::network::serialize::{
     self, serialize, BitcoinHash, SimpleDecoder, SimpleEncoder,
 };
 use crate::deps_common::bitcoin::util::hash::Sha256dHash;
-use crate::util::hash::to_hex;
+use crate::util::hash::{to_hex, Sha256Sum};
 
 /// A reference to a transaction output
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
@@ -447,6 +447,63 @@ impl Transaction {
         Sha256dHash::from_data(&raw_vec)
     }
 
+    /// Computes the BIP341 signature hash of a key-path spend of the given input, with
+    /// SIGHASH_DEFAULT and no annex.  `prevouts` are the outputs that all of the inputs spend,
+    /// in input order.
+    ///
+    /// # Panics
+    /// Panics if `prevouts` doesn't have an output for each input, or if `input_index` is out of
+    /// range.
+    pub fn taproot_key_spend_signature_hash(
+        &self,
+        input_index: usize,
+        prevouts: &[TxOut],
+    ) -> Sha256Sum {
+        assert_eq!(
+            prevouts.len(),
+            self.input.len(),
+            "FATAL: need one prevout per input"
+        );
+        assert!(input_index < self.input.len(), "FATAL: no such input");
+
+        let mut prevouts_bytes = vec![];
+        let mut amounts_bytes = vec![];
+        let mut script_pubkeys_bytes = vec![];
+        let mut sequences_bytes = vec![];
+        for (inp, prevout) in self.input.iter().zip(prevouts.iter()) {
+            prevouts_bytes.append(
+                &mut serialize(&inp.previous_output).expect("FATAL: failed to encode outpoint"),
+            );
+            amounts_bytes.extend_from_slice(&prevout.value.to_le_bytes());
+            script_pubkeys_bytes.append(
+                &mut serialize(&prevout.script_pubkey)
+                    .expect("FATAL: failed to encode script pubkey"),
+            );
+            sequences_bytes.extend_from_slice(&inp.sequence.to_le_bytes());
+        }
+        let mut outputs_bytes = vec![];
+        for output in self.output.iter() {
+            outputs_bytes.append(&mut serialize(output).expect("FATAL: failed to encode output"));
+        }
+
+        let mut raw_vec = vec![];
+        // sighash epoch and hash type (SIGHASH_DEFAULT)
+        raw_vec.extend_from_slice(&[0x00, 0x00]);
+        raw_vec.extend_from_slice(&self.version.to_le_bytes());
+        raw_vec.extend_from_slice(&self.lock_time.to_le_bytes());
+        raw_vec.extend_from_slice(Sha256Sum::from_data(&prevouts_bytes).as_bytes());
+        raw_vec.extend_from_slice(Sha256Sum::from_data(&amounts_bytes).as_bytes());
+        raw_vec.extend_from_slice(Sha256Sum::from_data(&script_pubkeys_bytes).as_bytes());
+        raw_vec.extend_from_slice(Sha256Sum::from_data(&sequences_bytes).as_bytes());
+        raw_vec.extend_from_slice(Sha256Sum::from_data(&outputs_bytes).as_bytes());
+        // spend type: key path, no annex
+        raw_vec.push(0x00);
+        raw_vec.extend_from_slice(&(input_index as u32).to_le_bytes());
+
+        let tag = Sha256Sum::from_data(b"TapSighash");
+        Sha256Sum::from_data(&[&tag.0[..], &tag.0[..], &raw_vec[..]].concat())
+    }
+
     /// Gets the "weight" of this transaction, as defined by BIP141. For transactions with an empty
     /// witness, this is simply the consensus-serialized size times 4. For transactions with a
     /// witness, this is the non-witness consensus-serialized size multiplied by 3 plus the
@@ -81,6 +81,7 @@ pub enum StacksEpochId {
     Epoch24 = 0x02019,
     Epoch25 = 0x0201a,
     Epoch30 = 0x03000,
+    Epoch31 = 0x03001,
 }
 
 #[derive(Debug)]
@@ -91,7 +92,7 @@ pub enum MempoolCollectionBehavior {
 
 impl StacksEpochId {
     pub fn latest() -> StacksEpochId {
-        StacksEpochId::Epoch30
+        StacksEpochId::Epoch31
     }
 
     /// In this epoch, how should the mempool perform garbage collection?
@@ -105,7 +106,9 @@ impl StacksEpochId {
             | StacksEpochId::Epoch23
             | StacksEpochId::Epoch24
             | StacksEpochId::Epoch25 => MempoolCollectionBehavior::ByStacksHeight,
-            StacksEpochId::Epoch30 => MempoolCollectionBehavior::ByReceiveTime,
+            StacksEpochId::Epoch30 | StacksEpochId::Epoch31 => {
+                MempoolCollectionBehavior::ByReceiveTime
+            }
         }
     }
 
@@ -120,7 +123,7 @@ impl StacksEpochId {
             | StacksEpochId::Epoch22
             | StacksEpochId::Epoch23
             | StacksEpochId::Epoch24 => false,
-            StacksEpochId::Epoch25 | StacksEpochId::Epoch30 => true,
+            StacksEpochId::Epoch25 | StacksEpochId::Epoch30 | StacksEpochId::Epoch31 => true,
         }
     }
 
@@ -134,7 +137,10 @@ impl StacksEpochId {
             | StacksEpochId::Epoch21
             | StacksEpochId::Epoch22
             | StacksEpochId::Epoch23 => false,
-            StacksEpochId::Epoch24 | StacksEpochId::Epoch25 | StacksEpochId::Epoch30 => true,
+            StacksEpochId::Epoch24
+            | StacksEpochId::Epoch25
+            | StacksEpochId::Epoch30
+            | StacksEpochId::Epoch31 => true,
         }
     }
 
@@ -150,7 +156,7 @@ impl StacksEpochId {
             | StacksEpochId::Epoch23
             | StacksEpochId::Epoch24
             | StacksEpochId::Epoch25 => false,
-            StacksEpochId::Epoch30 => true,
+            StacksEpochId::Epoch30 | StacksEpochId::Epoch31 => true,
         }
     }
 
@@ -166,7 +172,7 @@ impl StacksEpochId {
             | StacksEpochId::Epoch23
             | StacksEpochId::Epoch24
             | StacksEpochId::Epoch25 => false,
-            StacksEpochId::Epoch30 => true,
+            StacksEpochId::Epoch30 | StacksEpochId::Epoch31 => true,
         }
     }
 
@@ -181,7 +187,7 @@ impl StacksEpochId {
             | StacksEpochId::Epoch23
             | StacksEpochId::Epoch24
             | StacksEpochId::Epoch25 => false,
-            StacksEpochId::Epoch30 => true,
+            StacksEpochId::Epoch30 | StacksEpochId::Epoch31 => true,
         }
     }
 
@@ -212,7 +218,7 @@ impl StacksEpochId {
             | StacksEpochId::Epoch23
             | StacksEpochId::Epoch24
             | StacksEpochId::Epoch25 => 0,
-            StacksEpochId::Epoch30 => MINING_COMMITMENT_FREQUENCY_NAKAMOTO,
+            StacksEpochId::Epoch30 | StacksEpochId::Epoch31 => MINING_COMMITMENT_FREQUENCY_NAKAMOTO,
         }
     }
 
@@ -248,7 +254,9 @@ impl StacksEpochId {
             | StacksEpochId::Epoch23
             | StacksEpochId::Epoch24
             | StacksEpochId::Epoch25 => false,
-            StacksEpochId::Epoch30 => cur_reward_cycle > first_epoch30_reward_cycle,
+            StacksEpochId::Epoch30 | StacksEpochId::Epoch31 => {
+                cur_reward_cycle > first_epoch30_reward_cycle
+            }
         }
     }
 }
@@ -265,6 +273,7 @@ impl std::fmt::Display for StacksEpochId {
             StacksEpochId::Epoch24 => write!(f, "2.4"),
             StacksEpochId::Epoch25 => write!(f, "2.5"),
             StacksEpochId::Epoch30 => write!(f, "3.0"),
+            StacksEpochId::Epoch31 => write!(f, "3.1"),
         }
     }
 }
@@ -283,6 +292,7 @@ impl TryFrom<u32> for StacksEpochId {
             x if x == StacksEpochId::Epoch24 as u32 => Ok(StacksEpochId::Epoch24),
             x if x == StacksEpochId::Epoch25 as u32 => Ok(StacksEpochId::Epoch25),
             x if x == StacksEpochId::Epoch30 as u32 => Ok(StacksEpochId::Epoch30),
+            x if x == StacksEpochId::Epoch31 as u32 => Ok(StacksEpochId::Epoch31),
             _ => Err("Invalid epoch"),
         }
     }
@@ -0,0 +1,188 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Operations on the BN254 (alt-bn128) pairing-friendly curve, with the encodings of Ethereum's
+//! precompiles (EIP-196 and EIP-197), so that proofs and keys made for those verify unchanged.
+//!
+//! * A G1 point is 64 bytes: its big-endian `x` and `y` coordinates.
+//! * A G2 point is 128 bytes: `x` then `y`, each an element `a + b*i` of the quadratic extension
+//!   field written as `b` then `a`.
+//! * The point at infinity is all zeros, in either group.
+//! * A scalar is a 32-byte big-endian integer.
+
+use substrate_bn::{pairing_batch, AffineG1, AffineG2, Fq, Fq2, Fr, Group, Gt, G1, G2};
+
+pub const BN254_G1_POINT_LEN: usize = 64;
+pub const BN254_G2_POINT_LEN: usize = 128;
+pub const BN254_SCALAR_LEN: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bn254Error {
+    /// An input has the wrong length, or a coordinate is not less than the field modulus
+    InvalidEncoding,
+    /// A point is not on the curve (or, in G2, not in the prime-order subgroup)
+    InvalidPoint,
+}
+
+fn read_fq(bytes: &[u8]) -> Result<Fq, Bn254Error> {
+    Fq::from_slice(bytes).map_err(|_| Bn254Error::InvalidEncoding)
+}
+
+fn read_g1(bytes: &[u8]) -> Result<G1, Bn254Error> {
+    if bytes.len() != BN254_G1_POINT_LEN {
+        return Err(Bn254Error::InvalidEncoding);
+    }
+    let x = read_fq(&bytes[0..32])?;
+    let y = read_fq(&bytes[32..64])?;
+    if x == Fq::zero() && y == Fq::zero() {
+        return Ok(G1::zero());
+    }
+    AffineG1::new(x, y)
+        .map(G1::from)
+        .map_err(|_| Bn254Error::InvalidPoint)
+}
+
+fn read_g2(bytes: &[u8]) -> Result<G2, Bn254Error> {
+    if bytes.len() != BN254_G2_POINT_LEN {
+        return Err(Bn254Error::InvalidEncoding);
+    }
+    let x = Fq2::new(read_fq(&bytes[32..64])?, read_fq(&bytes[0..32])?);
+    let y = Fq2::new(read_fq(&bytes[96..128])?, read_fq(&bytes[64..96])?);
+    if x == Fq2::zero() && y == Fq2::zero() {
+        return Ok(G2::zero());
+    }
+    AffineG2::new(x, y)
+        .map(G2::from)
+        .map_err(|_| Bn254Error::InvalidPoint)
+}
+
+fn write_g1(point: G1) -> Result<[u8; BN254_G1_POINT_LEN], Bn254Error> {
+    let mut bytes = [0u8; BN254_G1_POINT_LEN];
+    if let Some(affine) = AffineG1::from_jacobian(point) {
+        affine
+            .x()
+            .to_big_endian(&mut bytes[0..32])
+            .map_err(|_| Bn254Error::InvalidEncoding)?;
+        affine
+            .y()
+            .to_big_endian(&mut bytes[32..64])
+            .map_err(|_| Bn254Error::InvalidEncoding)?;
+    }
+    Ok(bytes)
+}
+
+/// Add two G1 points
+pub fn bn254_g1_add(a: &[u8], b: &[u8]) -> Result<[u8; BN254_G1_POINT_LEN], Bn254Error> {
+    write_g1(read_g1(a)? + read_g1(b)?)
+}
+
+/// Multiply a G1 point by a scalar (which need not be reduced modulo the group order)
+pub fn bn254_g1_mul(point: &[u8], scalar: &[u8]) -> Result<[u8; BN254_G1_POINT_LEN], Bn254Error> {
+    if scalar.len() != BN254_SCALAR_LEN {
+        return Err(Bn254Error::InvalidEncoding);
+    }
+    let scalar = Fr::from_slice(scalar).map_err(|_| Bn254Error::InvalidEncoding)?;
+    write_g1(read_g1(point)? * scalar)
+}
+
+/// Check that the product of the pairings of each `(G1, G2)` pair is the identity.  An empty
+/// list of pairs passes.
+pub fn bn254_pairing_check(pairs: &[(&[u8], &[u8])]) -> Result<bool, Bn254Error> {
+    let mut points = Vec::with_capacity(pairs.len());
+    for (g1, g2) in pairs.iter() {
+        let (g1, g2) = (read_g1(g1)?, read_g2(g2)?);
+        // the pairing with the point at infinity is the identity
+        if !g1.is_zero() && !g2.is_zero() {
+            points.push((g1, g2));
+        }
+    }
+    if points.is_empty() {
+        return Ok(true);
+    }
+    Ok(pairing_batch(&points) == Gt::one())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::hash::hex_bytes;
+
+    const G1_GENERATOR: &str = "00000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000002";
+    const G1_GENERATOR_NEG: &str = "000000000000000000000000000000000000000000000000000000000000000130644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45";
+    const G1_DOUBLE: &str = "030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd315ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4";
+    const G2_GENERATOR: &str = "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c21800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa";
+
+    #[test]
+    fn test_bn254_g1_arithmetic() {
+        let g = hex_bytes(G1_GENERATOR).unwrap();
+        let zero = [0u8; 64];
+        let mut two = [0u8; 32];
+        two[31] = 2;
+
+        assert_eq!(
+            bn254_g1_add(&g, &g).unwrap().to_vec(),
+            hex_bytes(G1_DOUBLE).unwrap()
+        );
+        assert_eq!(
+            bn254_g1_mul(&g, &two).unwrap().to_vec(),
+            hex_bytes(G1_DOUBLE).unwrap()
+        );
+        assert_eq!(bn254_g1_add(&g, &zero).unwrap().to_vec(), g);
+        assert_eq!(
+            bn254_g1_add(&g, &hex_bytes(G1_GENERATOR_NEG).unwrap()).unwrap(),
+            zero
+        );
+        assert_eq!(bn254_g1_mul(&g, &[0u8; 32]).unwrap(), zero);
+
+        // (1, 3) is not on the curve
+        let mut off_curve = g.clone();
+        off_curve[63] = 3;
+        assert_eq!(bn254_g1_add(&g, &off_curve), Err(Bn254Error::InvalidPoint));
+        // coordinates must be less than the field modulus
+        assert_eq!(
+            bn254_g1_add(&g, &[0xff; 64]),
+            Err(Bn254Error::InvalidEncoding)
+        );
+        assert_eq!(bn254_g1_add(&g, &g[..63]), Err(Bn254Error::InvalidEncoding));
+        assert_eq!(
+            bn254_g1_mul(&g, &two[..31]),
+            Err(Bn254Error::InvalidEncoding)
+        );
+    }
+
+    #[test]
+    fn test_bn254_pairing_check() {
+        let g1 = hex_bytes(G1_GENERATOR).unwrap();
+        let g1 = g1.as_slice();
+        let g1_neg = hex_bytes(G1_GENERATOR_NEG).unwrap();
+        let g1_neg = g1_neg.as_slice();
+        let g2 = hex_bytes(G2_GENERATOR).unwrap();
+        let g2 = g2.as_slice();
+
+        assert!(bn254_pairing_check(&[]).unwrap());
+        // e(G1, G2) * e(-G1, G2) = 1
+        assert!(bn254_pairing_check(&[(g1, g2), (g1_neg, g2)]).unwrap());
+        assert!(!bn254_pairing_check(&[(g1, g2), (g1, g2)]).unwrap());
+        // pairings with the point at infinity are the identity
+        assert!(bn254_pairing_check(&[(&[0u8; 64][..], g2), (g1, &[0u8; 128][..])]).unwrap());
+
+        let mut off_curve = g2.to_vec();
+        off_curve[127] ^= 1;
+        assert_eq!(
+            bn254_pairing_check(&[(g1, off_curve.as_slice())]),
+            Err(Bn254Error::InvalidPoint)
+        );
+    }
+}
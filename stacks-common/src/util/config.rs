@@ -0,0 +1,276 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Loading of TOML configuration files, shared by the node and the signer.
+//!
+//! A config file is deserialized into a typed struct, whose defaults are supplied by its `serde`
+//! attributes.  Before that, any field can be overridden by an environment variable named after
+//! its path: the program's prefix, followed by the path's components in upper case, separated
+//! by `__`.  For example, with the prefix `STACKS_CONFIG__`, `STACKS_CONFIG__NODE__RPC_BIND`
+//! overrides `node.rpc_bind`.  The value of the variable is parsed as a TOML value, or taken as
+//! a string if it isn't one.
+//!
+//! Every error, whether it comes from parsing or from validating the loaded config, carries the
+//! path of the offending field.
+
+use std::{env, error, fmt};
+
+use serde::de::DeserializeOwned;
+use toml::value::Table;
+use toml::Value;
+
+/// An invalid configuration field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigFieldError {
+    /// The dotted path of the field, e.g. `node.rpc_bind`.  Empty if the error isn't specific
+    /// to a field (e.g. a TOML syntax error).
+    pub path: String,
+    pub message: String,
+}
+
+impl ConfigFieldError {
+    pub fn new(path: &str, message: &str) -> ConfigFieldError {
+        ConfigFieldError {
+            path: path.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    /// The field has a value that could not be interpreted
+    pub fn invalid_value(path: &str, value: &str, reason: &str) -> ConfigFieldError {
+        ConfigFieldError::new(path, &format!("invalid value `{value}`: {reason}"))
+    }
+
+    /// Convert a TOML deserialization error, whose message names the offending key
+    fn from_toml(e: toml::de::Error) -> ConfigFieldError {
+        let message = e.to_string();
+        let Some((before, rest)) = message.split_once(" for key `") else {
+            return ConfigFieldError::new("", &message);
+        };
+        let Some((path, after)) = rest.split_once('`') else {
+            return ConfigFieldError::new("", &message);
+        };
+        ConfigFieldError::new(path, &format!("{before}{after}"))
+    }
+}
+
+impl fmt::Display for ConfigFieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+impl error::Error for ConfigFieldError {}
+
+/// Fail with an error on `path` unless `condition` holds
+pub fn check(condition: bool, path: &str, message: &str) -> Result<(), ConfigFieldError> {
+    if condition {
+        Ok(())
+    } else {
+        Err(ConfigFieldError::new(path, message))
+    }
+}
+
+/// The path of the field that the environment variable `name` overrides, if any
+fn env_override_path(env_prefix: &str, name: &str) -> Option<Vec<String>> {
+    let path: Vec<_> = name
+        .strip_prefix(env_prefix)?
+        .split("__")
+        .map(|component| component.to_lowercase())
+        .collect();
+    if path.iter().any(|component| component.is_empty()) {
+        return None;
+    }
+    Some(path)
+}
+
+/// Interpret an environment variable's value as a TOML value, or else as a string
+fn env_override_value(value: &str) -> Value {
+    toml::from_str::<Table>(&format!("value = {value}"))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| Value::String(value.to_string()))
+}
+
+fn set_field(root: &mut Table, path: &[String], value: Value) -> Result<(), ConfigFieldError> {
+    let Some((field, parents)) = path.split_last() else {
+        return Ok(());
+    };
+    let mut table = root;
+    for (i, parent) in parents.iter().enumerate() {
+        let child = table
+            .entry(parent.clone())
+            .or_insert_with(|| Value::Table(Table::new()));
+        let Value::Table(child) = child else {
+            return Err(ConfigFieldError::new(
+                &path[..=i].join("."),
+                "cannot override a field of a non-table value",
+            ));
+        };
+        table = child;
+    }
+    table.insert(field.clone(), value);
+    Ok(())
+}
+
+/// Load a config of type `T` from the TOML `content`, applying the overrides in `vars` whose
+/// names start with `env_prefix`.
+pub fn load_toml_with_vars<T, I>(
+    content: &str,
+    env_prefix: &str,
+    vars: I,
+) -> Result<T, ConfigFieldError>
+where
+    T: DeserializeOwned,
+    I: IntoIterator<Item = (String, String)>,
+{
+    let mut overrides: Vec<_> = vars
+        .into_iter()
+        .filter_map(|(name, value)| Some((env_override_path(env_prefix, &name)?, value)))
+        .collect();
+    if overrides.is_empty() {
+        // deserialize directly, so that errors point at the right line
+        return toml::from_str(content).map_err(ConfigFieldError::from_toml);
+    }
+    // apply overrides in a deterministic order
+    overrides.sort();
+
+    let mut root: Table = toml::from_str(content).map_err(ConfigFieldError::from_toml)?;
+    for (path, value) in overrides {
+        set_field(&mut root, &path, env_override_value(&value))?;
+    }
+    let content = toml::to_string(&Value::Table(root))
+        .map_err(|e| ConfigFieldError::new("", &e.to_string()))?;
+    toml::from_str(&content).map_err(ConfigFieldError::from_toml)
+}
+
+/// Load a config of type `T` from the TOML `content`, applying overrides from the environment
+/// variables whose names start with `env_prefix`.
+pub fn load_toml<T: DeserializeOwned>(
+    content: &str,
+    env_prefix: &str,
+) -> Result<T, ConfigFieldError> {
+    load_toml_with_vars(content, env_prefix, env::vars())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct TestConfig {
+        name: String,
+        #[serde(default)]
+        node: TestNodeConfig,
+    }
+
+    #[derive(Debug, Default, PartialEq, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct TestNodeConfig {
+        port: Option<u16>,
+        #[serde(default)]
+        miner: bool,
+    }
+
+    fn vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_load_toml() {
+        let content = "name = \"a\"\n[node]\nport = 1\n";
+        let config: TestConfig = load_toml_with_vars(content, "TEST__", vec![]).unwrap();
+        assert_eq!(config.name, "a");
+        assert_eq!(config.node.port, Some(1));
+        assert!(!config.node.miner);
+
+        // overrides take precedence, and can add fields and tables
+        let config: TestConfig = load_toml_with_vars(
+            content,
+            "TEST__",
+            vars(&[
+                ("TEST__NAME", "b"),
+                ("TEST__NODE__PORT", "2"),
+                ("TEST__NODE__MINER", "true"),
+                ("OTHER__NAME", "c"),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(config.name, "b");
+        assert_eq!(config.node.port, Some(2));
+        assert!(config.node.miner);
+
+        let config: TestConfig =
+            load_toml_with_vars("", "TEST__", vars(&[("TEST__NAME", "\"c\"")])).unwrap();
+        assert_eq!(config.name, "c");
+        assert_eq!(config.node, TestNodeConfig::default());
+    }
+
+    #[test]
+    fn test_load_toml_errors() {
+        let err = load_toml_with_vars::<TestConfig, _>(
+            "name = \"a\"\n[node]\nport = \"x\"\n",
+            "TEST__",
+            vec![],
+        )
+        .unwrap_err();
+        assert_eq!(err.path, "node.port");
+        assert!(err.to_string().starts_with("node.port: invalid type"));
+
+        let err = load_toml_with_vars::<TestConfig, _>(
+            "name = \"a\"\n[node]\nunknown = 1\n",
+            "TEST__",
+            vec![],
+        )
+        .unwrap_err();
+        assert_eq!(err.path, "node");
+        assert!(err.message.starts_with("unknown field `unknown`"));
+
+        let err = load_toml_with_vars::<TestConfig, _>("name = ", "TEST__", vec![]).unwrap_err();
+        assert_eq!(err.path, "");
+
+        // overridden fields are checked too
+        let err = load_toml_with_vars::<TestConfig, _>(
+            "name = \"a\"",
+            "TEST__",
+            vars(&[("TEST__NODE__PORT", "100000")]),
+        )
+        .unwrap_err();
+        assert_eq!(err.path, "node.port");
+
+        let err = load_toml_with_vars::<TestConfig, _>(
+            "name = \"a\"",
+            "TEST__",
+            vars(&[("TEST__NAME__FIRST", "b")]),
+        )
+        .unwrap_err();
+        assert_eq!(err.path, "name");
+
+        assert_eq!(
+            check(false, "node.port", "must be set")
+                .unwrap_err()
+                .to_string(),
+            "node.port: must be set"
+        );
+        assert!(check(true, "node.port", "must be set").is_ok());
+    }
+}
@@ -18,7 +18,9 @@
 pub mod log;
 #[macro_use]
 pub mod macros;
+pub mod bn254;
 pub mod chunked_encoding;
+pub mod config;
 pub mod db;
 pub mod hash;
 pub mod pair;
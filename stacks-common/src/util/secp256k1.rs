@@ -20,8 +20,9 @@ use secp256k1::ecdsa::{
     Signature as LibSecp256k1Signature,
 };
 use secp256k1::{
-    constants as LibSecp256k1Constants, Error as LibSecp256k1Error, Message as LibSecp256k1Message,
-    PublicKey as LibSecp256k1PublicKey, Secp256k1, SecretKey as LibSecp256k1PrivateKey,
+    constants as LibSecp256k1Constants, Error as LibSecp256k1Error, KeyPair as LibSecp256k1KeyPair,
+    Message as LibSecp256k1Message, PublicKey as LibSecp256k1PublicKey, Scalar, Secp256k1,
+    SecretKey as LibSecp256k1PrivateKey, XOnlyPublicKey as LibSecp256k1XOnlyPublicKey,
 };
 use serde::de::{Deserialize, Error as de_Error};
 use serde::ser::Error as ser_Error;
@@ -167,6 +168,23 @@ impl Secp256k1PublicKey {
         self.compressed = value;
     }
 
+    /// The x-only form of this key, as used in taproot descriptors
+    pub fn to_x_only_bytes(&self) -> [u8; 32] {
+        LibSecp256k1XOnlyPublicKey::from(self.key).serialize()
+    }
+
+    /// The output key of a taproot output that this key can spend by the key path, with no
+    /// script path (BIP-86)
+    pub fn to_taproot_output_key(&self) -> Result<[u8; 32], &'static str> {
+        _secp256k1.with(|ctx| {
+            let internal_key = LibSecp256k1XOnlyPublicKey::from(self.key);
+            let (output_key, _parity) = internal_key
+                .add_tweak(ctx, &taproot_tweak(&internal_key)?)
+                .map_err(|_e| "Invalid taproot tweak")?;
+            Ok(output_key.serialize())
+        })
+    }
+
     /// recover message and signature to public key (will be compressed)
     pub fn recover_to_pubkey(
         msg: &[u8],
@@ -347,6 +365,34 @@ impl Secp256k1PrivateKey {
     pub fn as_slice(&self) -> &[u8; 32] {
         self.key.as_ref()
     }
+
+    /// Make a BIP-340 signature of the taproot signature hash `sighash` with the tweaked key of
+    /// a BIP-86 key-path spend, for the witness of an input that spends
+    /// `to_taproot_output_key()`
+    pub fn sign_taproot(&self, sighash: &[u8]) -> Result<[u8; 64], &'static str> {
+        _secp256k1.with(|ctx| {
+            let msg = LibSecp256k1Message::from_slice(sighash).map_err(|_e| {
+                "Invalid message: failed to decode sighash: must be a 32-byte hash"
+            })?;
+            let keypair = LibSecp256k1KeyPair::from_secret_key(ctx, &self.key);
+            let (internal_key, _parity) = keypair.x_only_public_key();
+            let tweaked = keypair
+                .add_xonly_tweak(ctx, &taproot_tweak(&internal_key)?)
+                .map_err(|_e| "Invalid taproot tweak")?;
+            let sig = ctx.sign_schnorr_no_aux_rand(&msg, &tweaked);
+            let mut sig_bytes = [0u8; 64];
+            sig_bytes.copy_from_slice(sig.as_ref());
+            Ok(sig_bytes)
+        })
+    }
+}
+
+/// The BIP-341 tweak of an internal key with no script tree
+fn taproot_tweak(internal_key: &LibSecp256k1XOnlyPublicKey) -> Result<Scalar, &'static str> {
+    let tag = Sha256Sum::from_data(b"TapTweak");
+    let tweak =
+        Sha256Sum::from_data(&[&tag.0[..], &tag.0[..], &internal_key.serialize()[..]].concat());
+    Scalar::from_be_bytes(tweak.0).map_err(|_e| "Taproot tweak out of range")
 }
 
 impl PrivateKey for Secp256k1PrivateKey {
@@ -645,6 +691,39 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_taproot_key_spend() {
+        // BIP-86 test vector: first receiving address of account 0
+        let internal_key = Secp256k1PublicKey::from_hex(
+            "02cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115",
+        )
+        .unwrap();
+        assert_eq!(
+            to_hex(&internal_key.to_x_only_bytes()),
+            "cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115"
+        );
+        assert_eq!(
+            to_hex(&internal_key.to_taproot_output_key().unwrap()),
+            "a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c"
+        );
+
+        // signatures verify against the output key
+        let privk = Secp256k1PrivateKey::new();
+        let output_key = Secp256k1PublicKey::from_private(&privk)
+            .to_taproot_output_key()
+            .unwrap();
+        let sighash = Sha256Sum::from_data(b"taproot sighash");
+        let sig = privk.sign_taproot(sighash.as_bytes()).unwrap();
+
+        let secp = Secp256k1::verification_only();
+        let msg = LibSecp256k1Message::from_slice(sighash.as_bytes()).unwrap();
+        let output_key = secp256k1::XOnlyPublicKey::from_slice(&output_key).unwrap();
+        let sig = secp256k1::schnorr::Signature::from_slice(&sig).unwrap();
+        secp.verify_schnorr(&sig, &msg, &output_key).unwrap();
+
+        assert!(privk.sign_taproot(&[0u8; 31]).is_err());
+    }
+
     #[test]
     #[ignore]
     fn test_verify_benchmark_roundtrip() {
@@ -14,6 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 use std::io::{self, Read};
+#[cfg(feature = "monitor_subscription")]
+use std::net::SocketAddr;
 use std::path::PathBuf;
 
 use blockstack_lib::chainstate::stacks::address::PoxAddress;
@@ -75,6 +77,58 @@ pub enum Command {
     VerifyVote(VerifyVoteArgs),
     /// Verify signer signatures by checking stackerdb slots contain the correct data
     MonitorSigners(MonitorSignersArgs),
+    /// Summarize the reasons the node's stacker-db rejected this signer's chunks
+    ChunkRejections(ChunkRejectionsArgs),
+    /// Encrypt a Stacks private key, read from stdin, into a keystore file for
+    /// `stacks_private_key_file`. The password is read from `STACKS_SIGNER_KEYSTORE_PASSWORD`.
+    EncryptKey(EncryptKeyArgs),
+    /// Inspect or write the slots of a stacker-db instance, using the signer's own stacker-db
+    /// client, e.g. to debug stuck slots and wrong-version errors
+    #[command(subcommand)]
+    Stackerdb(StackerDBCommand),
+}
+
+/// Subcommands of the stackerdb command
+#[derive(clap::Subcommand, Debug)]
+pub enum StackerDBCommand {
+    /// Print the metadata and the hex encoded data of the latest chunk in a slot
+    Get(StackerDBGetArgs),
+    /// Sign and write a chunk to a slot, and print the node's acknowledgement
+    Put(StackerDBPutArgs),
+    /// Print the metadata of every slot
+    ListSlots(StackerDBArgs),
+}
+
+/// Arguments for the stackerdb get command
+#[derive(Parser, Debug, Clone)]
+pub struct StackerDBGetArgs {
+    /// The base arguments
+    #[clap(flatten)]
+    pub db_args: StackerDBArgs,
+    /// The slot ID to get
+    #[arg(long)]
+    pub slot_id: u32,
+}
+
+/// Arguments for the stackerdb put command
+#[derive(Parser, Debug, Clone)]
+pub struct StackerDBPutArgs {
+    /// The base arguments
+    #[clap(flatten)]
+    pub db_args: StackerDBArgs,
+    /// The Stacks private key to use in hexademical format
+    #[arg(short, long, value_parser = parse_private_key)]
+    pub private_key: StacksPrivateKey,
+    /// The slot ID to write
+    #[arg(long)]
+    pub slot_id: u32,
+    /// The slot version to write. Defaults to one past the slot's current version on the node.
+    #[arg(long)]
+    pub slot_version: Option<u32>,
+    /// The data to upload
+    #[arg(required = false, value_parser = parse_data)]
+    // See PutChunkArgs::data
+    pub data: alloc::vec::Vec<u8>,
 }
 
 /// Basic arguments for all cyrptographic and stacker-db functionality
@@ -248,6 +302,33 @@ pub struct MonitorSignersArgs {
     /// Max age in seconds before a signer message is considered stale.
     #[arg(long, short, default_value = "1200")]
     pub max_age: u64,
+    /// Instead of polling, receive new signer messages as the node pushes them to this
+    /// address. The node needs an event observer for it with the `stackerdb` events key.
+    #[cfg(feature = "monitor_subscription")]
+    #[arg(long)]
+    pub subscribe: Option<SocketAddr>,
+}
+
+#[derive(Parser, Debug, Clone)]
+/// Arguments for the ChunkRejections command
+pub struct ChunkRejectionsArgs {
+    /// Path to signer config file
+    #[arg(long, short, value_name = "FILE")]
+    pub config: PathBuf,
+    /// Only include rejections received in the last this many seconds
+    #[arg(long, short, default_value = "86400")]
+    pub since: u64,
+}
+
+#[derive(Parser, Debug, Clone)]
+/// Arguments for the EncryptKey command
+pub struct EncryptKeyArgs {
+    /// Path of the keystore file to create
+    #[arg(long, short, value_name = "FILE")]
+    pub output: PathBuf,
+    /// The number of PBKDF2 iterations (default: 600000)
+    #[arg(long)]
+    pub iterations: Option<u32>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
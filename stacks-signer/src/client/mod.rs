@@ -14,10 +14,19 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+/// The session manager for the signers' StackerDBs across reward cycles
+pub(crate) mod sessions;
 /// The stacker db module for communicating with the stackerdb contract
 pub(crate) mod stackerdb;
 /// The stacks node client module for communicating with the stacks node
 pub(crate) mod stacks_client;
+/// The subscription module for receiving the chunks the stacks node pushes
+#[cfg(feature = "monitor_subscription")]
+pub(crate) mod subscription;
+/// The throttle module for deduplicating and rate limiting StackerDB writes
+pub(crate) mod throttle;
+/// The transactions module for building, submitting and tracking the signer's transactions
+pub(crate) mod transactions;
 
 use std::time::Duration;
 
@@ -25,11 +34,16 @@ use clarity::vm::errors::Error as ClarityError;
 use clarity::vm::types::serialization::SerializationError;
 use libsigner::RPCError;
 use libstackerdb::Error as StackerDBError;
+pub use sessions::*;
 use slog::slog_debug;
 pub use stackerdb::*;
 pub use stacks_client::*;
 use stacks_common::codec::Error as CodecError;
 use stacks_common::debug;
+#[cfg(feature = "monitor_subscription")]
+pub use subscription::*;
+pub use throttle::*;
+pub use transactions::*;
 
 /// Backoff timer initial interval in milliseconds
 const BACKOFF_INITIAL_INTERVAL: u64 = 128;
@@ -62,6 +76,9 @@ pub enum ClientError {
     /// Failed to build and sign a new Stacks transaction.
     #[error("Failed to generate transaction from a transaction signer: {0}")]
     TransactionGenerationFailure(String),
+    /// The stacks node did not admit a transaction to its mempool
+    #[error("Stacks node rejected the transaction: {0:?}")]
+    TransactionRejected(TransactionRejection),
     /// Stacks node client request failed
     #[error("Stacks node client request failed: {0}")]
     RequestFailure(reqwest::StatusCode),
@@ -95,6 +112,9 @@ pub enum ClientError {
     /// An RPC libsigner error occurred
     #[error("A libsigner RPC error occurred: {0}")]
     RPCError(#[from] RPCError),
+    /// Failed to receive the chunks pushed by the stacks node
+    #[error("Stacker-db subscription failed: {0}")]
+    SubscriptionError(String),
 }
 
 /// Retry a function F with an exponential backoff and notification on transient failure
@@ -104,6 +124,7 @@ where
     E: std::fmt::Debug,
 {
     let notify = |err, dur| {
+        crate::monitoring::increment_node_rpc_errors();
         debug!(
             "Failed to connect to stacks node and/or deserialize its response: {err:?}. Next attempt in {dur:?}"
         );
@@ -406,6 +427,7 @@ pub(crate) mod tests {
             },
             signer_slot_ids,
             stacks_private_key: config.stacks_private_key,
+            key_rotation: config.key_rotation.clone(),
             node_host: config.node_host.to_string(),
             mainnet: config.network.is_mainnet(),
             db_path: config.db_path.clone(),
@@ -413,6 +435,10 @@ pub(crate) mod tests {
             block_proposal_timeout: config.block_proposal_timeout,
             tenure_last_block_proposal_timeout: config.tenure_last_block_proposal_timeout,
             block_proposal_validation_timeout: config.block_proposal_validation_timeout,
+            stackerdb_connection_pool: config.new_stackerdb_connection_pool(),
+            stackerdb_throttle: config.stackerdb_throttle_config(),
+            transaction_config: config.transaction_config(),
+            block_policy: config.block_policy.clone(),
         }
     }
 
@@ -0,0 +1,168 @@
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::time::{Duration, Instant};
+
+use clarity::vm::types::QualifiedContractIdentifier;
+use hashbrown::HashMap;
+use libsigner::{ConnectionPool, MessageSlotID, StackerDBSession};
+
+/// A session and when it was last handed out
+#[derive(Debug)]
+struct CachedSession {
+    session: StackerDBSession,
+    last_used: Instant,
+}
+
+/// The sessions to the signers' StackerDBs on one node, for any reward cycle and message ID.
+/// A session is created the first time it is asked for, and kept until it is expired.
+#[derive(Debug)]
+pub struct StackerDBSessions<M: MessageSlotID> {
+    /// The node the sessions talk to
+    host: String,
+    /// Whether the node is a mainnet node
+    is_mainnet: bool,
+    /// The keep-alive connections the sessions share, if any
+    pool: Option<ConnectionPool>,
+    /// The sessions created so far, by reward cycle and message ID
+    sessions: HashMap<(u64, M), CachedSession>,
+}
+
+impl<M: MessageSlotID> StackerDBSessions<M> {
+    /// Manage sessions to the StackerDBs on `host`
+    pub fn new(host: &str, is_mainnet: bool) -> Self {
+        Self {
+            host: host.to_string(),
+            is_mainnet,
+            pool: None,
+            sessions: HashMap::new(),
+        }
+    }
+
+    /// Send every session's requests, including those of sessions created later, over
+    /// keep-alive connections from `pool`
+    pub fn set_connection_pool(&mut self, pool: ConnectionPool) {
+        for cached in self.sessions.values_mut() {
+            cached.session.set_connection_pool(pool.clone());
+        }
+        self.pool = Some(pool);
+    }
+
+    /// The node the sessions talk to
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// The StackerDB contract holding the messages with `msg_id` in `reward_cycle`
+    pub fn contract_id(&self, reward_cycle: u64, msg_id: &M) -> QualifiedContractIdentifier {
+        msg_id.stacker_db_contract(self.is_mainnet, reward_cycle)
+    }
+
+    /// The session for the messages with `msg_id` in `reward_cycle`, created if there is none
+    pub fn get_or_create(&mut self, reward_cycle: u64, msg_id: &M) -> &mut StackerDBSession {
+        let contract_id = self.contract_id(reward_cycle, msg_id);
+        let cached = self
+            .sessions
+            .entry((reward_cycle, *msg_id))
+            .or_insert_with(|| {
+                let mut session = StackerDBSession::new(&self.host, contract_id);
+                if let Some(pool) = &self.pool {
+                    session.set_connection_pool(pool.clone());
+                }
+                CachedSession {
+                    session,
+                    last_used: Instant::now(),
+                }
+            });
+        cached.last_used = Instant::now();
+        &mut cached.session
+    }
+
+    /// The session for the messages with `msg_id` in `reward_cycle`, if it has been created
+    pub fn get(&self, reward_cycle: u64, msg_id: &M) -> Option<&StackerDBSession> {
+        self.sessions
+            .get(&(reward_cycle, *msg_id))
+            .map(|cached| &cached.session)
+    }
+
+    /// The number of sessions
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    /// Whether there are no sessions
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    /// Drop the sessions for reward cycles before `reward_cycle`. Returns how many were dropped.
+    pub fn expire_cycles_before(&mut self, reward_cycle: u64) -> usize {
+        let num_sessions = self.sessions.len();
+        self.sessions
+            .retain(|(session_cycle, _), _| *session_cycle >= reward_cycle);
+        num_sessions - self.sessions.len()
+    }
+
+    /// Drop the sessions that have not been used for `max_idle`. Returns how many were dropped.
+    pub fn expire_idle(&mut self, max_idle: Duration) -> usize {
+        let num_sessions = self.sessions.len();
+        self.sessions
+            .retain(|_, cached| cached.last_used.elapsed() < max_idle);
+        num_sessions - self.sessions.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libsigner::v0::messages::MessageSlotID as V0MessageSlotID;
+
+    use super::*;
+
+    #[test]
+    fn sessions_are_created_cached_and_expired() {
+        let mut sessions = StackerDBSessions::new("127.0.0.1:20443", false);
+        assert!(sessions.is_empty());
+        assert!(sessions.get(10, &V0MessageSlotID::BlockResponse).is_none());
+
+        let contract_id = sessions
+            .get_or_create(10, &V0MessageSlotID::BlockResponse)
+            .stackerdb_contract_id
+            .clone();
+        assert_eq!(
+            contract_id,
+            V0MessageSlotID::BlockResponse.stacker_db_contract(false, 10)
+        );
+        sessions.get_or_create(10, &V0MessageSlotID::BlockResponse);
+        assert_eq!(sessions.len(), 1);
+
+        // sessions for the next cycle use the other signer set's contracts
+        let next_contract_id = sessions
+            .get_or_create(11, &V0MessageSlotID::BlockResponse)
+            .stackerdb_contract_id
+            .clone();
+        assert_ne!(contract_id, next_contract_id);
+        sessions.get_or_create(12, &V0MessageSlotID::BlockResponse);
+        assert_eq!(sessions.len(), 3);
+
+        assert_eq!(sessions.expire_cycles_before(11), 1);
+        assert!(sessions.get(10, &V0MessageSlotID::BlockResponse).is_none());
+        assert!(sessions.get(11, &V0MessageSlotID::BlockResponse).is_some());
+        assert!(sessions.get(12, &V0MessageSlotID::BlockResponse).is_some());
+
+        assert_eq!(sessions.expire_idle(Duration::from_secs(3600)), 0);
+        assert_eq!(sessions.expire_idle(Duration::ZERO), 2);
+        assert!(sessions.is_empty());
+    }
+}
@@ -14,16 +14,23 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 //
+use std::time::{Duration, Instant};
+
 use blockstack_lib::net::api::poststackerdbchunk::StackerDBErrorCodes;
 use clarity::codec::read_next;
-use hashbrown::HashMap;
-use libsigner::{MessageSlotID, SignerMessage, SignerSession, StackerDBSession};
-use libstackerdb::{StackerDBChunkAckData, StackerDBChunkData};
+use hashbrown::{HashMap, HashSet};
+use libsigner::{ConnectionPool, MessageSlotID, SignerMessage, SignerSession, StackerDBSession};
+use libstackerdb::{SlotMetadata, StackerDBChunkAckData, StackerDBChunkData};
+use serde::{Deserialize, Serialize};
 use slog::{slog_debug, slog_warn};
 use stacks_common::types::chainstate::StacksPrivateKey;
+use stacks_common::util::get_epoch_time_secs;
 use stacks_common::{debug, warn};
 
-use crate::client::{retry_with_exponential_backoff, ClientError};
+use crate::client::{
+    retry_with_exponential_backoff, ClientError, MessageThrottle, MessageThrottleConfig,
+    StackerDBSessions,
+};
 use crate::config::SignerConfig;
 
 /// The signer StackerDB slot ID, purposefully wrapped to prevent conflation with SignerID
@@ -36,12 +43,54 @@ impl std::fmt::Display for SignerSlotID {
     }
 }
 
+/// A chunk that the node's StackerDB refused to store
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkRejection {
+    /// The message ID of the slot written to
+    pub msg_id: String,
+    /// The slot written to
+    pub slot_id: u32,
+    /// The slot version we attempted to write
+    pub slot_version: u32,
+    /// The slot version the node expected, if it said
+    pub expected_version: Option<u32>,
+    /// The node's error code, if any
+    pub code: Option<u32>,
+    /// The node's explanation, if any
+    pub reason: Option<String>,
+    /// The node that rejected the chunk
+    pub node: String,
+    /// When the rejection was received (epoch time in seconds)
+    pub received_time: u64,
+}
+
+impl ChunkRejection {
+    /// The name of the rejection's error code, for grouping rejections by reason
+    pub fn code_name(&self) -> String {
+        match self.code.map(StackerDBErrorCodes::from_code) {
+            Some(Some(code)) => format!("{code:?}"),
+            Some(None) => "Unknown".into(),
+            None => "None".into(),
+        }
+    }
+}
+
+/// The next version to write to one of the signer's slots
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlotVersion {
+    /// The message ID of the slot
+    pub msg_id: String,
+    /// The slot
+    pub slot_id: u32,
+    /// The next version to write to the slot
+    pub slot_version: u32,
+}
+
 /// The StackerDB client for communicating with the .signers contract
 #[derive(Debug)]
 pub struct StackerDB<M: MessageSlotID + std::cmp::Eq> {
-    /// The stacker-db sessions for each signer set and message type.
-    /// Maps message ID to the DB session.
-    signers_message_stackerdb_sessions: HashMap<M, StackerDBSession>,
+    /// The stacker-db sessions for each reward cycle and message type, created as they are used
+    sessions: StackerDBSessions<M>,
     /// The private key used in all stacks node communications
     stacks_private_key: StacksPrivateKey,
     /// A map of a message ID to last chunk version for each session
@@ -50,6 +99,15 @@ pub struct StackerDB<M: MessageSlotID + std::cmp::Eq> {
     signer_slot_id: SignerSlotID,
     /// The reward cycle of the connecting signer
     reward_cycle: u64,
+    /// Rejected chunks that have not yet been taken by `take_chunk_rejections`
+    chunk_rejections: Vec<ChunkRejection>,
+    /// The message IDs whose slot versions changed since the last `take_updated_slot_versions`
+    updated_slot_versions: HashSet<M>,
+    /// Whether to ask the node for the version of a slot we have no version for, which is
+    /// set once versions are restored from a previous run
+    query_slot_versions: bool,
+    /// Deduplicates and rate limits the chunks we write
+    throttle: MessageThrottle<M>,
 }
 
 impl<M: MessageSlotID + 'static> From<&SignerConfig> for StackerDB<M> {
@@ -61,6 +119,8 @@ impl<M: MessageSlotID + 'static> From<&SignerConfig> for StackerDB<M> {
             config.reward_cycle,
             config.signer_slot_id,
         )
+        .with_connection_pool(&config.stackerdb_connection_pool)
+        .with_throttle(config.stackerdb_throttle)
     }
 }
 
@@ -73,19 +133,56 @@ impl<M: MessageSlotID + 'static> StackerDB<M> {
         reward_cycle: u64,
         signer_slot_id: SignerSlotID,
     ) -> Self {
-        let mut signers_message_stackerdb_sessions = HashMap::new();
-        for msg_id in M::all() {
-            let session =
-                StackerDBSession::new(host, msg_id.stacker_db_contract(is_mainnet, reward_cycle));
-            signers_message_stackerdb_sessions.insert(*msg_id, session);
-        }
-
         Self {
-            signers_message_stackerdb_sessions,
+            sessions: StackerDBSessions::new(host, is_mainnet),
             stacks_private_key,
             slot_versions: HashMap::new(),
             signer_slot_id,
             reward_cycle,
+            chunk_rejections: vec![],
+            updated_slot_versions: HashSet::new(),
+            query_slot_versions: false,
+            throttle: MessageThrottle::new(MessageThrottleConfig::default()),
+        }
+    }
+
+    /// Send every session's requests over keep-alive connections from `pool`
+    pub fn with_connection_pool(mut self, pool: &ConnectionPool) -> Self {
+        self.sessions.set_connection_pool(pool.clone());
+        self
+    }
+
+    /// Deduplicate and rate limit the chunks we write according to `config`
+    pub fn with_throttle(mut self, config: MessageThrottleConfig) -> Self {
+        self.throttle = MessageThrottle::new(config);
+        self
+    }
+
+    /// Switch to writing to slot `signer_slot_id` of `reward_cycle`'s StackerDBs, e.g. when
+    /// the signer moves on to the next reward cycle. The sessions of the previous cycle are
+    /// kept, so that its messages can still be read, and those of earlier cycles are dropped.
+    /// Slot versions are per reward cycle, so the updated versions should be taken with
+    /// `take_updated_slot_versions` first.
+    pub fn hand_over(&mut self, reward_cycle: u64, signer_slot_id: SignerSlotID) {
+        if reward_cycle == self.reward_cycle {
+            self.signer_slot_id = signer_slot_id;
+            return;
+        }
+        self.sessions
+            .expire_cycles_before(reward_cycle.saturating_sub(1));
+        self.reward_cycle = reward_cycle;
+        self.signer_slot_id = signer_slot_id;
+        self.slot_versions.clear();
+        self.updated_slot_versions.clear();
+        self.query_slot_versions = false;
+        self.throttle.clear_sent();
+    }
+
+    /// Drop the sessions that have not been used for `max_idle`
+    pub fn expire_idle_sessions(&mut self, max_idle: Duration) {
+        let num_expired = self.sessions.expire_idle(max_idle);
+        if num_expired > 0 {
+            debug!("Expired {num_expired} idle stackerdb sessions");
         }
     }
 
@@ -104,71 +201,75 @@ impl<M: MessageSlotID + 'static> StackerDB<M> {
     }
 
     /// Sends message (as a raw msg ID and bytes) to the .signers stacker-db with an
-    /// exponential backoff retry
+    /// exponential backoff retry. A message identical to the last one the stacker-db accepted
+    /// in the slot, within the dedupe window, is not sent again.
     pub fn send_message_bytes_with_retry(
         &mut self,
         msg_id: &M,
         message_bytes: Vec<u8>,
     ) -> Result<StackerDBChunkAckData, ClientError> {
         let slot_id = self.signer_slot_id;
+        if let Some(ack) =
+            self.throttle
+                .duplicate_ack(msg_id, slot_id, &message_bytes, Instant::now())
+        {
+            debug!("Skipping a message already in stackerdb slot ID {slot_id} with message ID {msg_id:?}");
+            crate::monitoring::increment_stackerdb_messages_deduplicated();
+            return Ok(ack.clone());
+        }
         loop {
-            let mut slot_version = if let Some(versions) = self.slot_versions.get_mut(msg_id) {
-                if let Some(version) = versions.get(&slot_id) {
-                    *version
-                } else {
-                    versions.insert(slot_id, 0);
-                    1
+            let known_version = self
+                .slot_versions
+                .get(msg_id)
+                .and_then(|versions| versions.get(&slot_id))
+                .copied();
+            let mut slot_version = match known_version {
+                Some(version) => version,
+                None => {
+                    let version = self.initial_slot_version(msg_id, slot_id);
+                    self.set_slot_version(msg_id, slot_id, version);
+                    version
                 }
-            } else {
-                let mut versions = HashMap::new();
-                versions.insert(slot_id, 0);
-                self.slot_versions.insert(*msg_id, versions);
-                1
-            };
-
-            let mut chunk = StackerDBChunkData::new(slot_id.0, slot_version, message_bytes.clone());
-            chunk.sign(&self.stacks_private_key)?;
-
-            let Some(session) = self.signers_message_stackerdb_sessions.get_mut(msg_id) else {
-                panic!("FATAL: would loop forever trying to send a message with ID {msg_id:?}, for which we don't have a session");
             };
 
-            debug!(
-                "Sending a chunk to stackerdb slot ID {slot_id} with version {slot_version} and message ID {msg_id:?} to contract {:?}!\n{chunk:?}",
-                &session.stackerdb_contract_id
-            );
+            self.wait_for_write(msg_id);
+            let session = self.sessions.get_or_create(self.reward_cycle, msg_id);
+            let chunk_ack = put_chunk_with_retry(
+                session,
+                &self.stacks_private_key,
+                slot_id.0,
+                slot_version,
+                message_bytes.clone(),
+            )?;
 
-            let send_request = || session.put_chunk(&chunk).map_err(backoff::Error::transient);
-            let chunk_ack: StackerDBChunkAckData = retry_with_exponential_backoff(send_request)?;
-
-            if let Some(versions) = self.slot_versions.get_mut(msg_id) {
-                // NOTE: per the above, this is always executed
-                versions.insert(slot_id, slot_version.saturating_add(1));
-            } else {
-                return Err(ClientError::NotConnected);
-            }
+            self.set_slot_version(msg_id, slot_id, slot_version.saturating_add(1));
 
+            crate::monitoring::increment_stackerdb_chunks_sent(chunk_ack.accepted);
             if chunk_ack.accepted {
                 debug!("Chunk accepted by stackerdb: {chunk_ack:?}");
+                self.throttle.record_sent(
+                    msg_id,
+                    slot_id,
+                    &message_bytes,
+                    &chunk_ack,
+                    Instant::now(),
+                );
                 return Ok(chunk_ack);
             } else {
                 warn!("Chunk rejected by stackerdb: {chunk_ack:?}");
+                self.record_chunk_rejection(msg_id, slot_id, slot_version, &chunk_ack);
             }
             if let Some(code) = chunk_ack.code {
                 match StackerDBErrorCodes::from_code(code) {
                     Some(StackerDBErrorCodes::DataAlreadyExists) => {
+                        crate::monitoring::increment_stackerdb_version_conflicts();
                         if let Some(slot_metadata) = chunk_ack.metadata {
                             warn!("Failed to send message to stackerdb due to wrong version number. Attempted {}. Expected {}. Retrying...", slot_version, slot_metadata.slot_version);
                             slot_version = slot_metadata.slot_version;
                         } else {
                             warn!("Failed to send message to stackerdb due to wrong version number. Attempted {}. Expected unknown version number. Incrementing and retrying...", slot_version);
                         }
-                        if let Some(versions) = self.slot_versions.get_mut(msg_id) {
-                            // NOTE: per the above, this is always executed
-                            versions.insert(slot_id, slot_version.saturating_add(1));
-                        } else {
-                            return Err(ClientError::NotConnected);
-                        }
+                        self.set_slot_version(msg_id, slot_id, slot_version.saturating_add(1));
                     }
                     _ => {
                         warn!("Failed to send message to stackerdb: {:?}", chunk_ack);
@@ -183,18 +284,125 @@ impl<M: MessageSlotID + 'static> StackerDB<M> {
         }
     }
 
+    /// Wait until the rate limit of `msg_id` allows another chunk write
+    fn wait_for_write(&mut self, msg_id: &M) {
+        while let Err(wait) = self.throttle.take_write(msg_id, Instant::now()) {
+            debug!("Rate limiting stackerdb writes with message ID {msg_id:?} for {wait:?}");
+            crate::monitoring::increment_stackerdb_writes_rate_limited();
+            std::thread::sleep(wait);
+        }
+    }
+
+    /// The version to first write to a slot we have no version for.  If our versions were
+    /// restored, the slot was never written in the previous runs we know of, so ask the node
+    /// for its version in case it was (e.g. before the signer's database was wiped).
+    fn initial_slot_version(&mut self, msg_id: &M, slot_id: SignerSlotID) -> u32 {
+        if !self.query_slot_versions {
+            return 1;
+        }
+        let session = self.sessions.get_or_create(self.reward_cycle, msg_id);
+        match next_slot_version(session, slot_id.0) {
+            Ok(slot_version) => slot_version,
+            Err(e) => {
+                warn!("Failed to query the version of stackerdb slot ID {slot_id} with message ID {msg_id:?}: {e:?}");
+                1
+            }
+        }
+    }
+
+    /// Set the next version to write to a slot
+    fn set_slot_version(&mut self, msg_id: &M, slot_id: SignerSlotID, slot_version: u32) {
+        self.slot_versions
+            .entry(*msg_id)
+            .or_default()
+            .insert(slot_id, slot_version);
+        self.updated_slot_versions.insert(*msg_id);
+    }
+
+    /// Restore the slot versions persisted by a previous run.  Slots without a version are
+    /// looked up on the node before they are first written.
+    pub fn restore_slot_versions(&mut self, versions: &[SlotVersion]) {
+        for version in versions {
+            let Some(msg_id) = M::all()
+                .iter()
+                .find(|msg_id| format!("{msg_id:?}") == version.msg_id)
+            else {
+                warn!(
+                    "Ignoring the persisted version of a slot with unknown message ID {}",
+                    version.msg_id
+                );
+                continue;
+            };
+            self.slot_versions
+                .entry(*msg_id)
+                .or_default()
+                .insert(SignerSlotID(version.slot_id), version.slot_version);
+        }
+        self.query_slot_versions = true;
+    }
+
+    /// Take the slot versions that changed since the last call, to be persisted
+    pub fn take_updated_slot_versions(&mut self) -> Vec<SlotVersion> {
+        let mut updated = vec![];
+        for msg_id in std::mem::take(&mut self.updated_slot_versions) {
+            let Some(versions) = self.slot_versions.get(&msg_id) else {
+                continue;
+            };
+            for (slot_id, slot_version) in versions.iter() {
+                updated.push(SlotVersion {
+                    msg_id: format!("{msg_id:?}"),
+                    slot_id: slot_id.0,
+                    slot_version: *slot_version,
+                });
+            }
+        }
+        updated
+    }
+
+    /// Record a rejected chunk, for the metrics and for `take_chunk_rejections`
+    fn record_chunk_rejection(
+        &mut self,
+        msg_id: &M,
+        slot_id: SignerSlotID,
+        slot_version: u32,
+        chunk_ack: &StackerDBChunkAckData,
+    ) {
+        let rejection = ChunkRejection {
+            msg_id: format!("{msg_id:?}"),
+            slot_id: slot_id.0,
+            slot_version,
+            expected_version: chunk_ack
+                .metadata
+                .as_ref()
+                .map(|metadata| metadata.slot_version),
+            code: chunk_ack.code,
+            reason: chunk_ack.reason.clone(),
+            node: self.sessions.host().to_string(),
+            received_time: get_epoch_time_secs(),
+        };
+        crate::monitoring::increment_stackerdb_chunk_rejections(&rejection.code_name());
+        self.chunk_rejections.push(rejection);
+    }
+
+    /// Take the chunks rejected since the last call
+    pub fn take_chunk_rejections(&mut self) -> Vec<ChunkRejection> {
+        std::mem::take(&mut self.chunk_rejections)
+    }
+
     /// Get all signer messages from stackerdb for the given slot IDs
     pub fn get_messages<T: SignerMessage<M>>(
         session: &mut StackerDBSession,
         slot_ids: &[u32],
     ) -> Result<Vec<T>, ClientError> {
         let mut messages = vec![];
+        let timer = crate::monitoring::new_stackerdb_request_timer("get_latest_chunks");
         let send_request = || {
             session
                 .get_latest_chunks(slot_ids)
                 .map_err(backoff::Error::transient)
         };
         let chunk_ack = retry_with_exponential_backoff(send_request)?;
+        timer.stop_and_record();
         for (i, chunk) in chunk_ack.iter().enumerate() {
             let Some(data) = chunk else {
                 continue;
@@ -211,6 +419,57 @@ impl<M: MessageSlotID + 'static> StackerDB<M> {
         Ok(messages)
     }
 
+    /// Get the latest message in every message slot of the given signers, in one round trip to
+    /// the node.  The messages are keyed by signer, in the order of `M::all()`.
+    pub fn get_all_latest_messages<T: SignerMessage<M>>(
+        &mut self,
+        signer_ids: &[SignerSlotID],
+    ) -> Result<HashMap<SignerSlotID, Vec<T>>, ClientError> {
+        let mut slots = vec![];
+        for msg_id in M::all() {
+            let contract_id = self.sessions.contract_id(self.reward_cycle, msg_id);
+            for signer_id in signer_ids {
+                slots.push((*signer_id, contract_id.clone(), signer_id.0));
+            }
+        }
+        // every session talks to the same node, so any of them can fetch all the slots
+        let Some(msg_id) = M::all().first() else {
+            return Err(ClientError::NotConnected);
+        };
+        let session = self.sessions.get_or_create(self.reward_cycle, msg_id);
+        let contract_slots: Vec<_> = slots
+            .iter()
+            .map(|(_, contract_id, slot_id)| (contract_id.clone(), *slot_id))
+            .collect();
+        let timer = crate::monitoring::new_stackerdb_request_timer("get_latest_chunks");
+        let send_request = || {
+            session
+                .get_latest_chunks_in(&contract_slots)
+                .map_err(backoff::Error::transient)
+        };
+        let chunks = retry_with_exponential_backoff(send_request)?;
+        timer.stop_and_record();
+
+        let mut messages: HashMap<SignerSlotID, Vec<T>> = HashMap::new();
+        for ((signer_id, ..), chunk) in slots.iter().zip(chunks) {
+            let Some(data) = chunk else {
+                continue;
+            };
+            let Ok(message) = read_next::<T, _>(&mut &data[..]) else {
+                if !data.is_empty() {
+                    warn!("Failed to deserialize chunk data into a SignerMessage");
+                    debug!(
+                        "slot #{signer_id}: Failed chunk ({}): {data:?}",
+                        &data.len()
+                    );
+                }
+                continue;
+            };
+            messages.entry(*signer_id).or_default().push(message);
+        }
+        Ok(messages)
+    }
+
     /// Retrieve the signer set this stackerdb client is attached to
     pub fn get_signer_set(&self) -> u32 {
         u32::try_from(self.reward_cycle % 2).expect("FATAL: reward cycle % 2 exceeds u32::MAX")
@@ -221,27 +480,101 @@ impl<M: MessageSlotID + 'static> StackerDB<M> {
         self.signer_slot_id
     }
 
-    /// Get the session corresponding to the given message ID if it exists
-    pub fn get_session_mut(&mut self, msg_id: &M) -> Option<&mut StackerDBSession> {
-        self.signers_message_stackerdb_sessions.get_mut(msg_id)
+    /// Get the session corresponding to the given message ID in the current reward cycle
+    pub fn get_session_mut(&mut self, msg_id: &M) -> &mut StackerDBSession {
+        self.sessions.get_or_create(self.reward_cycle, msg_id)
+    }
+
+    /// Get the session corresponding to the given message ID in any reward cycle, e.g. to read
+    /// the messages of the previous or next cycle's signers
+    pub fn get_cycle_session_mut(
+        &mut self,
+        reward_cycle: u64,
+        msg_id: &M,
+    ) -> &mut StackerDBSession {
+        self.sessions.get_or_create(reward_cycle, msg_id)
     }
 }
 
+/// Sign a chunk of `data` for version `slot_version` of `slot_id`, and write it to `session`'s
+/// StackerDB with an exponential backoff retry
+pub fn put_chunk_with_retry(
+    session: &mut StackerDBSession,
+    private_key: &StacksPrivateKey,
+    slot_id: u32,
+    slot_version: u32,
+    data: Vec<u8>,
+) -> Result<StackerDBChunkAckData, ClientError> {
+    let mut chunk = StackerDBChunkData::new(slot_id, slot_version, data);
+    chunk.sign(private_key)?;
+    debug!(
+        "Sending a chunk to stackerdb slot ID {slot_id} with version {slot_version} to contract {:?}!\n{chunk:?}",
+        &session.stackerdb_contract_id
+    );
+    let timer = crate::monitoring::new_stackerdb_request_timer("put_chunk");
+    let send_request = || session.put_chunk(&chunk).map_err(backoff::Error::transient);
+    let chunk_ack = retry_with_exponential_backoff(send_request)?;
+    timer.stop_and_record();
+    Ok(chunk_ack)
+}
+
+/// The metadata of every slot of `session`'s StackerDB, with an exponential backoff retry
+pub fn list_slots_with_retry(
+    session: &mut StackerDBSession,
+) -> Result<Vec<SlotMetadata>, ClientError> {
+    let timer = crate::monitoring::new_stackerdb_request_timer("list_chunks");
+    let send_request = || session.list_chunks().map_err(backoff::Error::transient);
+    let metadata = retry_with_exponential_backoff(send_request)?;
+    timer.stop_and_record();
+    Ok(metadata)
+}
+
+/// The latest chunk in `slot_id` of `session`'s StackerDB, if it was ever written, with an
+/// exponential backoff retry
+pub fn get_latest_chunk_with_retry(
+    session: &mut StackerDBSession,
+    slot_id: u32,
+) -> Result<Option<Vec<u8>>, ClientError> {
+    let timer = crate::monitoring::new_stackerdb_request_timer("get_latest_chunks");
+    let send_request = || {
+        session
+            .get_latest_chunk(slot_id)
+            .map_err(backoff::Error::transient)
+    };
+    let chunk = retry_with_exponential_backoff(send_request)?;
+    timer.stop_and_record();
+    Ok(chunk)
+}
+
+/// The version to write next to `slot_id` of `session`'s StackerDB, according to the node: one
+/// past the slot's current version, or 1 if it was never written
+pub fn next_slot_version(session: &mut StackerDBSession, slot_id: u32) -> Result<u32, ClientError> {
+    Ok(session
+        .list_chunks()?
+        .iter()
+        .find(|slot| slot.slot_id == slot_id)
+        .map_or(1, |slot| slot.slot_version.saturating_add(1)))
+}
+
 #[cfg(test)]
 mod tests {
     use std::thread::spawn;
     use std::time::Duration;
 
     use blockstack_lib::chainstate::nakamoto::{NakamotoBlock, NakamotoBlockHeader};
+    use clarity::codec::StacksMessageCodec;
     use clarity::util::hash::{MerkleTree, Sha512Trunc256Sum};
     use clarity::util::secp256k1::MessageSignature;
     use libsigner::v0::messages::{
-        BlockRejection, BlockResponse, RejectCode, SignerMessage, SignerMessageMetadata,
+        BlockRejection, BlockResponse, MessageSlotID, RejectCode, SignerMessage,
+        SignerMessageMetadata,
     };
     use rand::{thread_rng, RngCore};
 
     use super::*;
-    use crate::client::tests::{generate_signer_config, mock_server_from_config, write_response};
+    use crate::client::tests::{
+        generate_signer_config, mock_server_from_config, mock_server_random, write_response,
+    };
     use crate::config::{build_signer_config_tomls, GlobalConfig, Network};
 
     #[test]
@@ -305,4 +638,285 @@ mod tests {
         write_response(mock_server, response_bytes.as_slice());
         assert_eq!(ack, sender_thread.join().unwrap());
     }
+
+    #[test]
+    fn rejected_chunk_should_be_recorded() {
+        let signer_config = build_signer_config_tomls(
+            &[StacksPrivateKey::new()],
+            "localhost:20444",
+            Some(Duration::from_millis(128)),
+            &Network::Testnet,
+            "1234",
+            16,
+            3000,
+            Some(100_000),
+            None,
+            Some(9000),
+            None,
+        );
+        let config = GlobalConfig::load_from_str(&signer_config[0]).unwrap();
+        let signer_config = generate_signer_config(&config, 5);
+        let mut stackerdb = StackerDB::<MessageSlotID>::from(&signer_config);
+        assert!(stackerdb.take_chunk_rejections().is_empty());
+
+        let ack = StackerDBChunkAckData {
+            accepted: false,
+            reason: Some("No such slot".into()),
+            metadata: None,
+            code: Some(StackerDBErrorCodes::NoSuchSlot.code()),
+        };
+        let mock_server = mock_server_from_config(&config);
+        let sender_thread = spawn(move || {
+            let result =
+                stackerdb.send_message_bytes_with_retry(&MessageSlotID::BlockResponse, vec![1]);
+            (stackerdb, result)
+        });
+        let mut response_bytes = b"HTTP/1.1 200 OK\n\n".to_vec();
+        let payload = serde_json::to_string(&ack).expect("Failed to serialize ack");
+        response_bytes.extend(payload.as_bytes());
+        std::thread::sleep(Duration::from_millis(500));
+        write_response(mock_server, response_bytes.as_slice());
+        let (mut stackerdb, result) = sender_thread.join().unwrap();
+        assert!(matches!(result, Err(ClientError::PutChunkRejected(_))));
+
+        let rejections = stackerdb.take_chunk_rejections();
+        assert_eq!(rejections.len(), 1);
+        assert_eq!(rejections[0].msg_id, "BlockResponse");
+        assert_eq!(rejections[0].slot_version, 1);
+        assert_eq!(rejections[0].code_name(), "NoSuchSlot");
+        assert_eq!(rejections[0].reason.as_deref(), Some("No such slot"));
+        assert_eq!(rejections[0].node, config.node_host);
+        assert!(stackerdb.take_chunk_rejections().is_empty());
+    }
+
+    #[test]
+    fn restored_slot_versions_should_be_used() {
+        let signer_config = build_signer_config_tomls(
+            &[StacksPrivateKey::new()],
+            "localhost:20445",
+            Some(Duration::from_millis(128)),
+            &Network::Testnet,
+            "1234",
+            16,
+            3000,
+            Some(100_000),
+            None,
+            Some(9000),
+            None,
+        );
+        let config = GlobalConfig::load_from_str(&signer_config[0]).unwrap();
+        let signer_config = generate_signer_config(&config, 5);
+        let slot_id = signer_config.signer_slot_id.0;
+        let mut stackerdb = StackerDB::<MessageSlotID>::from(&signer_config);
+        stackerdb.restore_slot_versions(&[
+            SlotVersion {
+                msg_id: "BlockResponse".into(),
+                slot_id,
+                slot_version: 5,
+            },
+            SlotVersion {
+                msg_id: "NoSuchMessage".into(),
+                slot_id,
+                slot_version: 9,
+            },
+        ]);
+        assert!(stackerdb.take_updated_slot_versions().is_empty());
+
+        let ack = StackerDBChunkAckData {
+            accepted: true,
+            reason: None,
+            metadata: None,
+            code: None,
+        };
+        let mock_server = mock_server_from_config(&config);
+        let sender_thread = spawn(move || {
+            let result =
+                stackerdb.send_message_bytes_with_retry(&MessageSlotID::BlockResponse, vec![1]);
+            (stackerdb, result)
+        });
+        let mut response_bytes = b"HTTP/1.1 200 OK\n\n".to_vec();
+        let payload = serde_json::to_string(&ack).expect("Failed to serialize ack");
+        response_bytes.extend(payload.as_bytes());
+        std::thread::sleep(Duration::from_millis(500));
+        let request_bytes = write_response(mock_server, response_bytes.as_slice());
+        let (mut stackerdb, result) = sender_thread.join().unwrap();
+        assert_eq!(result.unwrap(), ack);
+
+        let request = String::from_utf8_lossy(&request_bytes);
+        assert!(request.contains("\"slot_version\":5"));
+        assert_eq!(
+            stackerdb.take_updated_slot_versions(),
+            vec![SlotVersion {
+                msg_id: "BlockResponse".into(),
+                slot_id,
+                slot_version: 6,
+            }]
+        );
+        assert!(stackerdb.take_updated_slot_versions().is_empty());
+
+        // the slot already holds the message, so it is not written again
+        let result =
+            stackerdb.send_message_bytes_with_retry(&MessageSlotID::BlockResponse, vec![1]);
+        assert_eq!(result.unwrap(), ack);
+        assert!(stackerdb.take_updated_slot_versions().is_empty());
+    }
+
+    #[test]
+    fn hand_over_should_switch_reward_cycle() {
+        let signer_config = build_signer_config_tomls(
+            &[StacksPrivateKey::new()],
+            "localhost:20447",
+            Some(Duration::from_millis(128)),
+            &Network::Testnet,
+            "1234",
+            16,
+            3000,
+            Some(100_000),
+            None,
+            Some(9000),
+            None,
+        );
+        let config = GlobalConfig::load_from_str(&signer_config[0]).unwrap();
+        let signer_config = generate_signer_config(&config, 5);
+        let reward_cycle = signer_config.reward_cycle;
+        let slot_id = signer_config.signer_slot_id.0;
+        let mut stackerdb = StackerDB::<MessageSlotID>::from(&signer_config);
+        stackerdb.restore_slot_versions(&[SlotVersion {
+            msg_id: "BlockResponse".into(),
+            slot_id,
+            slot_version: 5,
+        }]);
+        let contract_id = stackerdb
+            .get_session_mut(&MessageSlotID::BlockResponse)
+            .stackerdb_contract_id
+            .clone();
+
+        stackerdb.hand_over(reward_cycle + 1, SignerSlotID(slot_id + 1));
+        assert_eq!(stackerdb.get_signer_slot_id(), SignerSlotID(slot_id + 1));
+        assert_ne!(
+            stackerdb.get_signer_set(),
+            u32::try_from(reward_cycle % 2).unwrap()
+        );
+        assert!(stackerdb.slot_versions.is_empty());
+        assert_eq!(
+            stackerdb
+                .get_session_mut(&MessageSlotID::BlockResponse)
+                .stackerdb_contract_id,
+            MessageSlotID::BlockResponse.stacker_db_contract(false, reward_cycle + 1)
+        );
+        // the previous cycle's session is kept, but not earlier ones
+        assert_eq!(stackerdb.sessions.len(), 2);
+        assert_eq!(
+            stackerdb
+                .get_cycle_session_mut(reward_cycle, &MessageSlotID::BlockResponse)
+                .stackerdb_contract_id,
+            contract_id
+        );
+        stackerdb.hand_over(reward_cycle + 2, SignerSlotID(slot_id));
+        assert!(stackerdb
+            .sessions
+            .get(reward_cycle, &MessageSlotID::BlockResponse)
+            .is_none());
+        assert!(stackerdb
+            .sessions
+            .get(reward_cycle + 1, &MessageSlotID::BlockResponse)
+            .is_some());
+    }
+
+    #[test]
+    fn get_all_latest_messages_should_succeed() {
+        let signer_config = build_signer_config_tomls(
+            &[StacksPrivateKey::new()],
+            "localhost:20446",
+            Some(Duration::from_millis(128)),
+            &Network::Testnet,
+            "1234",
+            16,
+            3000,
+            Some(100_000),
+            None,
+            Some(9000),
+            None,
+        );
+        let config = GlobalConfig::load_from_str(&signer_config[0]).unwrap();
+        let signer_config = generate_signer_config(&config, 5);
+        let mut stackerdb = StackerDB::<MessageSlotID>::from(&signer_config);
+
+        let block_reject = BlockRejection {
+            reason: "Did not like it".into(),
+            reason_code: RejectCode::RejectedInPriorRound,
+            signer_signature_hash: NakamotoBlockHeader::empty().signer_signature_hash(),
+            chain_id: thread_rng().next_u32(),
+            signature: MessageSignature::empty(),
+            metadata: SignerMessageMetadata::empty(),
+        };
+        let signer_message = SignerMessage::BlockResponse(BlockResponse::Rejected(block_reject));
+        let mock_server = mock_server_from_config(&config);
+        let reader_thread = spawn(move || {
+            stackerdb
+                .get_all_latest_messages::<SignerMessage>(&[SignerSlotID(3)])
+                .unwrap()
+        });
+        // the block response slot holds the message, and the key rotation slot is empty
+        let message_bytes = signer_message.serialize_to_vec();
+        let mut response_bytes = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n",
+            message_bytes.len()
+        )
+        .into_bytes();
+        response_bytes.extend(message_bytes);
+        response_bytes.extend(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n");
+        std::thread::sleep(Duration::from_millis(500));
+        let request_bytes = write_response(mock_server, response_bytes.as_slice());
+        let messages = reader_thread.join().unwrap();
+        assert_eq!(
+            messages,
+            HashMap::from([(SignerSlotID(3), vec![signer_message])])
+        );
+
+        let request = String::from_utf8_lossy(&request_bytes);
+        assert!(request.starts_with("GET /v2/stackerdb/"));
+        assert!(request.contains("-1/3 HTTP/1.1"));
+    }
+
+    #[test]
+    fn put_chunk_should_use_next_slot_version() {
+        let (mock_server, mock_server_addr) = mock_server_random();
+        let contract_id = MessageSlotID::BlockResponse.stacker_db_contract(false, 10);
+        let mut session = StackerDBSession::new(&mock_server_addr.to_string(), contract_id);
+        let slots = vec![SlotMetadata {
+            slot_id: 3,
+            slot_version: 7,
+            data_hash: Sha512Trunc256Sum([0u8; 32]),
+            signature: MessageSignature::empty(),
+        }];
+        let mut response_bytes = b"HTTP/1.1 200 OK\n\n".to_vec();
+        response_bytes.extend(serde_json::to_string(&slots).unwrap().as_bytes());
+        let listener = mock_server.try_clone().unwrap();
+        let server_thread = spawn(move || {
+            write_response(listener.try_clone().unwrap(), &response_bytes);
+            write_response(listener, &response_bytes);
+        });
+        assert_eq!(next_slot_version(&mut session, 3).unwrap(), 8);
+        assert_eq!(next_slot_version(&mut session, 4).unwrap(), 1);
+        server_thread.join().unwrap();
+
+        let ack = StackerDBChunkAckData {
+            accepted: true,
+            reason: None,
+            metadata: None,
+            code: None,
+        };
+        let mut response_bytes = b"HTTP/1.1 200 OK\n\n".to_vec();
+        response_bytes.extend(serde_json::to_string(&ack).unwrap().as_bytes());
+        let private_key = StacksPrivateKey::new();
+        let sender_thread = spawn(move || {
+            put_chunk_with_retry(&mut session, &private_key, 3, 8, vec![1, 2, 3]).unwrap()
+        });
+        let request_bytes = write_response(mock_server, &response_bytes);
+        assert_eq!(sender_thread.join().unwrap(), ack);
+
+        let request = String::from_utf8_lossy(&request_bytes);
+        assert!(request.starts_with("POST /v2/stackerdb/"));
+    }
 }
@@ -17,6 +17,7 @@ use std::collections::{HashMap, VecDeque};
 use std::fmt::Display;
 use std::time::{Duration, Instant};
 
+use blockstack_lib::burnchains::Txid;
 use blockstack_lib::chainstate::nakamoto::NakamotoBlock;
 use blockstack_lib::chainstate::stacks::boot::{NakamotoSignerEntry, SIGNERS_NAME};
 use blockstack_lib::chainstate::stacks::db::StacksBlockHeaderTypes;
@@ -30,12 +31,14 @@ use blockstack_lib::net::api::get_tenures_fork_info::{
     TenureForkingInfo, RPC_TENURE_FORKING_INFO_PATH,
 };
 use blockstack_lib::net::api::getaccount::AccountEntryResponse;
+use blockstack_lib::net::api::getmempooltxs::MemPoolTxsResponse;
 use blockstack_lib::net::api::getpoxinfo::RPCPoxInfoData;
 use blockstack_lib::net::api::getsortition::{SortitionInfo, RPC_SORTITION_INFO_PATH};
 use blockstack_lib::net::api::getstackers::GetStackersResponse;
 use blockstack_lib::net::api::postblock::StacksBlockAcceptedData;
 use blockstack_lib::net::api::postblock_proposal::NakamotoBlockProposal;
 use blockstack_lib::net::api::postblock_v3;
+use blockstack_lib::net::api::postfeerate::{FeeRateEstimateRequestBody, RPCFeeEstimateResponse};
 use blockstack_lib::util_lib::boot::boot_code_id;
 use clarity::vm::types::{PrincipalData, QualifiedContractIdentifier};
 use clarity::vm::{ClarityName, ContractName, Value as ClarityValue};
@@ -50,6 +53,7 @@ use stacks_common::types::chainstate::{
     ConsensusHash, StacksAddress, StacksPrivateKey, StacksPublicKey,
 };
 use stacks_common::types::StacksEpochId;
+use stacks_common::util::hash::to_hex;
 use stacks_common::{debug, warn};
 
 use super::SignerSlotID;
@@ -83,6 +87,23 @@ struct GetStackersErrorResp {
     err_msg: String,
 }
 
+/// The stacks node's reason for not admitting a transaction to its mempool
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TransactionRejection {
+    /// The reason code, e.g. `FeeTooLow` or `BadNonce`
+    pub reason: String,
+    /// The details of the rejection, e.g. the fee or nonce the node expected
+    #[serde(default)]
+    pub reason_data: Option<serde_json::Value>,
+}
+
+impl TransactionRejection {
+    /// The fee or nonce the node expected, for `FeeTooLow` and `BadNonce` rejections
+    pub fn expected(&self) -> Option<u64> {
+        self.reason_data.as_ref()?.get("expected")?.as_u64()
+    }
+}
+
 /// Result from fetching current and last sortition:
 ///  two sortition infos
 pub struct CurrentAndLastSortition {
@@ -566,6 +587,92 @@ impl StacksClient {
         Ok(account_entry)
     }
 
+    /// Get the first `limit` transactions in the stacks node's mempool, optionally only those
+    /// calling `contract`. The mempool may still hold transactions that have been mined.
+    pub fn get_mempool_txs(
+        &self,
+        contract: Option<&QualifiedContractIdentifier>,
+        limit: u64,
+    ) -> Result<MemPoolTxsResponse, ClientError> {
+        debug!("stacks_node_client: Getting mempool transactions...");
+        let timer_label = format!("{}/v2/mempool/txs", self.http_origin);
+        let timer = crate::monitoring::new_rpc_call_timer(&timer_label, &self.http_origin);
+        let send_request = || {
+            self.stacks_node_client
+                .get(self.mempool_txs_path(contract, limit))
+                .send()
+                .map_err(backoff::Error::transient)
+        };
+        let response = retry_with_exponential_backoff(send_request)?;
+        timer.stop_and_record();
+        if !response.status().is_success() {
+            return Err(ClientError::RequestFailure(response.status()));
+        }
+        let mempool_txs = response.json::<MemPoolTxsResponse>()?;
+        Ok(mempool_txs)
+    }
+
+    /// Estimate the fees of a transaction with `payload` and a length of `estimated_len` bytes
+    pub fn get_fee_estimate(
+        &self,
+        payload: &TransactionPayload,
+        estimated_len: Option<u64>,
+    ) -> Result<RPCFeeEstimateResponse, ClientError> {
+        debug!("stacks_node_client: Getting fee estimate...");
+        let body = FeeRateEstimateRequestBody {
+            estimated_len,
+            transaction_payload: to_hex(&payload.serialize_to_vec()),
+        };
+        let timer =
+            crate::monitoring::new_rpc_call_timer(&self.fee_estimate_path(), &self.http_origin);
+        let send_request = || {
+            self.stacks_node_client
+                .post(self.fee_estimate_path())
+                .header("Content-Type", "application/json")
+                .json(&body)
+                .send()
+                .map_err(backoff::Error::transient)
+        };
+        let response = retry_with_exponential_backoff(send_request)?;
+        timer.stop_and_record();
+        if !response.status().is_success() {
+            return Err(ClientError::RequestFailure(response.status()));
+        }
+        let fee_estimate = response.json::<RPCFeeEstimateResponse>()?;
+        Ok(fee_estimate)
+    }
+
+    /// Submit a signed transaction to the stacks node's mempool. If the node does not admit
+    /// it, the error is a `ClientError::TransactionRejected` with the node's reason.
+    pub fn submit_transaction(&self, tx: &StacksTransaction) -> Result<Txid, ClientError> {
+        debug!("stacks_node_client: Submitting transaction...";
+            "txid" => %tx.txid(),
+            "nonce" => tx.get_origin_nonce(),
+            "fee" => tx.get_tx_fee(),
+        );
+        let timer =
+            crate::monitoring::new_rpc_call_timer(&self.transactions_path(), &self.http_origin);
+        let send_request = || {
+            self.stacks_node_client
+                .post(self.transactions_path())
+                .header("Content-Type", "application/octet-stream")
+                .body(tx.serialize_to_vec())
+                .send()
+                .map_err(backoff::Error::transient)
+        };
+        let response = retry_with_exponential_backoff(send_request)?;
+        timer.stop_and_record();
+        if response.status() == reqwest::StatusCode::BAD_REQUEST {
+            let rejection = response.json::<TransactionRejection>()?;
+            return Err(ClientError::TransactionRejected(rejection));
+        }
+        if !response.status().is_success() {
+            return Err(ClientError::RequestFailure(response.status()));
+        }
+        let txid = response.json::<Txid>()?;
+        Ok(txid)
+    }
+
     /// Post a block to the stacks-node, retry forever on errors.
     ///
     /// In tests, this panics if the retry takes longer than 30 seconds.
@@ -714,6 +821,28 @@ impl StacksClient {
         format!("{}/v2/accounts/{stacks_address}?proof=0", self.http_origin)
     }
 
+    fn fee_estimate_path(&self) -> String {
+        format!("{}/v2/fees/transaction", self.http_origin)
+    }
+
+    fn transactions_path(&self) -> String {
+        format!("{}/v2/transactions", self.http_origin)
+    }
+
+    fn mempool_txs_path(
+        &self,
+        contract: Option<&QualifiedContractIdentifier>,
+        limit: u64,
+    ) -> String {
+        match contract {
+            Some(contract) => format!(
+                "{}/v2/mempool/txs?contract={contract}&limit={limit}",
+                self.http_origin
+            ),
+            None => format!("{}/v2/mempool/txs?limit={limit}", self.http_origin),
+        }
+    }
+
     fn reward_set_path(&self, reward_cycle: u64) -> String {
         format!("{}/v3/stacker_set/{reward_cycle}", self.http_origin)
     }
@@ -759,6 +888,28 @@ impl StacksClient {
         Ok(unsigned_tx)
     }
 
+    /// Create a stacks transaction from the signer's address for a modifying contract call,
+    /// without a fee
+    pub fn build_unsigned_signer_contract_call(
+        &self,
+        contract_addr: &StacksAddress,
+        contract_name: ContractName,
+        function_name: ClarityName,
+        function_args: &[ClarityValue],
+        nonce: u64,
+    ) -> Result<StacksTransaction, ClientError> {
+        Self::build_unsigned_contract_call_transaction(
+            contract_addr,
+            contract_name,
+            function_name,
+            function_args,
+            &self.stacks_private_key,
+            self.tx_version,
+            self.chain_id,
+            nonce,
+        )
+    }
+
     /// Sign an unsigned transaction
     pub fn sign_transaction(
         &self,
@@ -1158,6 +1309,28 @@ mod tests {
         assert_eq!(reduced_peer_info.server_version, peer_info.server_version);
     }
 
+    #[test]
+    fn get_mempool_txs_should_succeed() {
+        let mock = MockServerClient::new();
+        let contract =
+            QualifiedContractIdentifier::parse("ST000000000000000000002AMW42H.sbtc-deposit")
+                .unwrap();
+        let expected = MemPoolTxsResponse {
+            txs: vec![],
+            next_page_id: None,
+        };
+        let h = spawn(move || mock.client.get_mempool_txs(Some(&contract), 10));
+        let response = format!(
+            "HTTP/1.1 200 OK\n\n{}",
+            serde_json::to_string(&expected).unwrap()
+        );
+        let request = write_response(mock.server, response.as_bytes());
+        assert!(String::from_utf8_lossy(&request).starts_with(
+            "GET /v2/mempool/txs?contract=ST000000000000000000002AMW42H.sbtc-deposit&limit=10 "
+        ));
+        assert_eq!(h.join().unwrap().unwrap(), expected);
+    }
+
     #[test]
     fn get_reward_set_should_succeed() {
         let mock = MockServerClient::new();
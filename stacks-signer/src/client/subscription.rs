@@ -0,0 +1,212 @@
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use blockstack_lib::chainstate::stacks::boot::SIGNERS_NAME;
+use blockstack_lib::chainstate::stacks::events::StackerDBChunksEvent;
+use clarity::codec::read_next;
+use libsigner::{get_signers_db_signer_set_message_id, MessageSlotID, SignerMessage};
+use libstackerdb::StackerDBChunkData;
+use slog::{slog_debug, slog_warn};
+use stacks_common::{debug, warn};
+use tiny_http::{Method as HttpMethod, Response as HttpResponse, Server as HttpServer};
+
+use crate::client::{ClientError, SignerSlotID};
+
+/// Chunks written to one of the signers' StackerDBs
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignerChunks {
+    /// The signer set the StackerDB belongs to (the reward cycle modulo 2)
+    pub signer_set: u32,
+    /// The message ID of the StackerDB
+    pub msg_id: u32,
+    /// The newly written chunks
+    pub chunks: Vec<StackerDBChunkData>,
+}
+
+impl SignerChunks {
+    /// The messages in the chunks that deserialize, with the slots they were written to
+    pub fn messages<M: MessageSlotID, T: SignerMessage<M>>(&self) -> Vec<(SignerSlotID, T)> {
+        self.chunks
+            .iter()
+            .filter_map(|chunk| {
+                let message = read_next::<T, _>(&mut &chunk.data[..]).ok()?;
+                Some((SignerSlotID(chunk.slot_id), message))
+            })
+            .collect()
+    }
+}
+
+/// A subscription to the chunks written to the signers' StackerDBs. The node's event
+/// dispatcher pushes them to an event observer endpoint as they are written, so they do not
+/// need to be polled for. The node must have an event observer for the endpoint with the
+/// `stackerdb` events key.
+pub struct StackerDBSubscription {
+    /// The event observer endpoint
+    http_server: HttpServer,
+    /// The address the endpoint is bound to
+    local_addr: SocketAddr,
+}
+
+impl StackerDBSubscription {
+    /// Listen for the node's events on `endpoint`
+    pub fn bind(endpoint: SocketAddr) -> Result<Self, ClientError> {
+        let http_server = HttpServer::http(endpoint).map_err(|e| {
+            ClientError::SubscriptionError(format!("Failed to bind to {endpoint}: {e}"))
+        })?;
+        let local_addr = http_server
+            .server_addr()
+            .to_ip()
+            .ok_or_else(|| ClientError::SubscriptionError("Not bound to an IP address".into()))?;
+        Ok(Self {
+            http_server,
+            local_addr,
+        })
+    }
+
+    /// The address the subscription is listening on
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Wait up to `timeout` for the node to push chunks written to one of the signers'
+    /// StackerDBs. Every other event the node sends is acknowledged and ignored.
+    pub fn next_chunks(&self, timeout: Duration) -> Result<Option<SignerChunks>, ClientError> {
+        let Some(mut request) = self
+            .http_server
+            .recv_timeout(timeout)
+            .map_err(|e| ClientError::SubscriptionError(format!("{e:?}")))?
+        else {
+            return Ok(None);
+        };
+        if request.method() != &HttpMethod::Post || request.url() != "/stackerdb_chunks" {
+            // `/new_block` and the like are expected, but not of interest
+            debug!("{}: ignoring event {}", self.local_addr, request.url());
+            Self::ack(request);
+            return Ok(None);
+        }
+        let mut body = vec![];
+        let read_result = request.as_reader().read_to_end(&mut body);
+        Self::ack(request);
+        read_result.map_err(|e| {
+            ClientError::SubscriptionError(format!("Failed to read event body: {e:?}"))
+        })?;
+        let event: StackerDBChunksEvent = serde_json::from_slice(&body).map_err(|e| {
+            ClientError::SubscriptionError(format!("Could not decode event body: {e:?}"))
+        })?;
+        Ok(Self::signer_chunks(event))
+    }
+
+    /// The chunks in `event`, if it is for one of the signers' StackerDBs
+    fn signer_chunks(event: StackerDBChunksEvent) -> Option<SignerChunks> {
+        if !event.contract_id.is_boot() || !event.contract_id.name.starts_with(SIGNERS_NAME) {
+            return None;
+        }
+        let Some((signer_set, msg_id)) =
+            get_signers_db_signer_set_message_id(event.contract_id.name.as_str())
+        else {
+            return None;
+        };
+        Some(SignerChunks {
+            signer_set,
+            msg_id,
+            chunks: event.modified_slots,
+        })
+    }
+
+    fn ack(request: tiny_http::Request) {
+        if let Err(e) = request.respond(HttpResponse::empty(200u16)) {
+            warn!("Failed to respond to event: {e:?}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+
+    use blockstack_lib::util_lib::boot::boot_code_id;
+    use libsigner::v0::messages::{
+        MessageSlotID as V0MessageSlotID, SignerMessage as V0SignerMessage,
+    };
+
+    use super::*;
+
+    fn post_event(addr: SocketAddr, path: &str, body: &str) {
+        let mut sock = TcpStream::connect(addr).unwrap();
+        let request = format!(
+            "POST {path} HTTP/1.1\r\nHost: {addr}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        sock.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        sock.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"));
+    }
+
+    #[test]
+    fn pushed_signer_chunks_are_received() {
+        let subscription = StackerDBSubscription::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+        let addr = subscription.local_addr();
+        assert_eq!(
+            subscription.next_chunks(Duration::from_millis(10)).unwrap(),
+            None
+        );
+
+        let chunk = StackerDBChunkData::new(3, 7, vec![0xff]);
+        let signers_event = StackerDBChunksEvent {
+            contract_id: boot_code_id("signers-1-1", false),
+            modified_slots: vec![chunk.clone()],
+        };
+        let miners_event = StackerDBChunksEvent {
+            contract_id: boot_code_id("miners", false),
+            modified_slots: vec![chunk.clone()],
+        };
+        let sender = std::thread::spawn(move || {
+            post_event(addr, "/new_block", "{}");
+            post_event(
+                addr,
+                "/stackerdb_chunks",
+                &serde_json::to_string(&miners_event).unwrap(),
+            );
+            post_event(
+                addr,
+                "/stackerdb_chunks",
+                &serde_json::to_string(&signers_event).unwrap(),
+            );
+        });
+
+        let timeout = Duration::from_secs(5);
+        assert_eq!(subscription.next_chunks(timeout).unwrap(), None);
+        assert_eq!(subscription.next_chunks(timeout).unwrap(), None);
+        let chunks = subscription.next_chunks(timeout).unwrap().unwrap();
+        sender.join().unwrap();
+        assert_eq!(
+            chunks,
+            SignerChunks {
+                signer_set: 1,
+                msg_id: 1,
+                chunks: vec![chunk],
+            }
+        );
+        // the chunk holds no valid message
+        assert!(chunks
+            .messages::<V0MessageSlotID, V0SignerMessage>()
+            .is_empty());
+    }
+}
@@ -0,0 +1,259 @@
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::time::{Duration, Instant};
+
+use hashbrown::HashMap;
+use libsigner::MessageSlotID;
+use libstackerdb::StackerDBChunkAckData;
+use stacks_common::util::hash::Sha512Trunc256Sum;
+
+use crate::client::SignerSlotID;
+
+/// Limits on how often the signer writes to its StackerDB slots
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct MessageThrottleConfig {
+    /// How long a message the StackerDB accepted is remembered, so that sending it again to
+    /// the same slot is skipped. Zero disables deduplication.
+    pub dedupe_window: Duration,
+    /// The most chunk writes per second for each message ID. Zero disables rate limiting.
+    pub max_writes_per_sec: u32,
+    /// The most chunk writes for each message ID in a burst
+    pub write_burst: u32,
+}
+
+/// The last message the StackerDB accepted in a slot
+#[derive(Debug)]
+struct SentMessage {
+    /// The hash of the message bytes
+    hash: Sha512Trunc256Sum,
+    /// When the message was accepted
+    sent_at: Instant,
+    /// The StackerDB's acknowledgement of the message
+    ack: StackerDBChunkAckData,
+}
+
+/// A token bucket, holding up to `capacity` tokens and refilled at `rate` tokens per second
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(capacity: f64, now: Instant) -> Self {
+        Self {
+            tokens: capacity,
+            last_refill: now,
+        }
+    }
+
+    /// Take a token, if there is one. Otherwise, return how long until there is one.
+    fn take(&mut self, capacity: f64, rate: f64, now: Instant) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * rate).min(capacity);
+        self.last_refill = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / rate))
+        }
+    }
+}
+
+/// Deduplicates and rate limits the messages the signer writes to its StackerDB slots, so
+/// that retries do not flood the node with writes or burn slot versions
+#[derive(Debug)]
+pub struct MessageThrottle<M: MessageSlotID> {
+    config: MessageThrottleConfig,
+    /// The last accepted message in each slot
+    sent: HashMap<(M, SignerSlotID), SentMessage>,
+    /// The rate limit of each message ID
+    buckets: HashMap<M, TokenBucket>,
+}
+
+impl<M: MessageSlotID> MessageThrottle<M> {
+    /// Throttle messages according to `config`
+    pub fn new(config: MessageThrottleConfig) -> Self {
+        Self {
+            config,
+            sent: HashMap::new(),
+            buckets: HashMap::new(),
+        }
+    }
+
+    /// The acknowledgement of `message_bytes`, if they are what the StackerDB last accepted in
+    /// `slot_id` for `msg_id`, within the dedupe window
+    pub fn duplicate_ack(
+        &self,
+        msg_id: &M,
+        slot_id: SignerSlotID,
+        message_bytes: &[u8],
+        now: Instant,
+    ) -> Option<&StackerDBChunkAckData> {
+        let sent = self.sent.get(&(*msg_id, slot_id))?;
+        if now.saturating_duration_since(sent.sent_at) >= self.config.dedupe_window {
+            return None;
+        }
+        if sent.hash != Sha512Trunc256Sum::from_data(message_bytes) {
+            return None;
+        }
+        Some(&sent.ack)
+    }
+
+    /// Remember that the StackerDB accepted `message_bytes` in `slot_id` for `msg_id`
+    pub fn record_sent(
+        &mut self,
+        msg_id: &M,
+        slot_id: SignerSlotID,
+        message_bytes: &[u8],
+        ack: &StackerDBChunkAckData,
+        now: Instant,
+    ) {
+        if self.config.dedupe_window.is_zero() {
+            return;
+        }
+        self.sent.insert(
+            (*msg_id, slot_id),
+            SentMessage {
+                hash: Sha512Trunc256Sum::from_data(message_bytes),
+                sent_at: now,
+                ack: ack.clone(),
+            },
+        );
+    }
+
+    /// Forget the messages sent so far, e.g. because the slots now belong to another reward
+    /// cycle
+    pub fn clear_sent(&mut self) {
+        self.sent.clear();
+    }
+
+    /// Take a chunk write for `msg_id` out of its rate limit. If the limit is reached, returns
+    /// how long to wait before trying again.
+    pub fn take_write(&mut self, msg_id: &M, now: Instant) -> Result<(), Duration> {
+        if self.config.max_writes_per_sec == 0 {
+            return Ok(());
+        }
+        let rate = f64::from(self.config.max_writes_per_sec);
+        let capacity = f64::from(self.config.write_burst.max(1));
+        self.buckets
+            .entry(*msg_id)
+            .or_insert_with(|| TokenBucket::new(capacity, now))
+            .take(capacity, rate, now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libsigner::v0::messages::MessageSlotID as V0MessageSlotID;
+
+    use super::*;
+
+    fn ack() -> StackerDBChunkAckData {
+        StackerDBChunkAckData {
+            accepted: true,
+            reason: None,
+            metadata: None,
+            code: None,
+        }
+    }
+
+    #[test]
+    fn identical_messages_are_deduplicated_within_window() {
+        let mut throttle = MessageThrottle::new(MessageThrottleConfig {
+            dedupe_window: Duration::from_secs(10),
+            ..MessageThrottleConfig::default()
+        });
+        let msg_id = V0MessageSlotID::BlockResponse;
+        let now = Instant::now();
+        assert!(throttle
+            .duplicate_ack(&msg_id, SignerSlotID(1), &[1, 2], now)
+            .is_none());
+
+        throttle.record_sent(&msg_id, SignerSlotID(1), &[1, 2], &ack(), now);
+        assert_eq!(
+            throttle.duplicate_ack(&msg_id, SignerSlotID(1), &[1, 2], now),
+            Some(&ack())
+        );
+        // another slot, other bytes, or after the window
+        assert!(throttle
+            .duplicate_ack(&msg_id, SignerSlotID(2), &[1, 2], now)
+            .is_none());
+        assert!(throttle
+            .duplicate_ack(&msg_id, SignerSlotID(1), &[1, 3], now)
+            .is_none());
+        assert!(throttle
+            .duplicate_ack(
+                &msg_id,
+                SignerSlotID(1),
+                &[1, 2],
+                now + Duration::from_secs(10)
+            )
+            .is_none());
+
+        // a different message replaces the slot's contents, so the first may be sent again
+        throttle.record_sent(&msg_id, SignerSlotID(1), &[1, 3], &ack(), now);
+        assert!(throttle
+            .duplicate_ack(&msg_id, SignerSlotID(1), &[1, 2], now)
+            .is_none());
+
+        throttle.clear_sent();
+        assert!(throttle
+            .duplicate_ack(&msg_id, SignerSlotID(1), &[1, 3], now)
+            .is_none());
+
+        // disabled
+        let mut throttle = MessageThrottle::new(MessageThrottleConfig::default());
+        throttle.record_sent(&msg_id, SignerSlotID(1), &[1, 2], &ack(), now);
+        assert!(throttle
+            .duplicate_ack(&msg_id, SignerSlotID(1), &[1, 2], now)
+            .is_none());
+    }
+
+    #[test]
+    fn writes_are_rate_limited() {
+        let mut throttle = MessageThrottle::new(MessageThrottleConfig {
+            max_writes_per_sec: 2,
+            write_burst: 3,
+            ..MessageThrottleConfig::default()
+        });
+        let msg_id = V0MessageSlotID::BlockResponse;
+        let now = Instant::now();
+        for _ in 0..3 {
+            assert_eq!(throttle.take_write(&msg_id, now), Ok(()));
+        }
+        assert_eq!(
+            throttle.take_write(&msg_id, now),
+            Err(Duration::from_millis(500))
+        );
+        let later = now + Duration::from_millis(500);
+        assert_eq!(throttle.take_write(&msg_id, later), Ok(()));
+        assert!(throttle.take_write(&msg_id, later).is_err());
+        // the bucket does not fill past the burst
+        let much_later = later + Duration::from_secs(60);
+        for _ in 0..3 {
+            assert_eq!(throttle.take_write(&msg_id, much_later), Ok(()));
+        }
+        assert!(throttle.take_write(&msg_id, much_later).is_err());
+
+        // disabled
+        let mut throttle = MessageThrottle::new(MessageThrottleConfig::default());
+        for _ in 0..100 {
+            assert_eq!(throttle.take_write(&msg_id, now), Ok(()));
+        }
+    }
+}
@@ -0,0 +1,472 @@
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use blockstack_lib::burnchains::Txid;
+use blockstack_lib::chainstate::stacks::StacksTransaction;
+use clarity::vm::{ClarityName, ContractName, Value as ClarityValue};
+use slog::{slog_debug, slog_info, slog_warn};
+use stacks_common::types::chainstate::StacksAddress;
+use stacks_common::{debug, info, warn};
+
+use crate::client::{ClientError, StacksClient, TransactionRejection};
+
+/// How many times a transaction is resubmitted after the node rejects it, before giving up
+const MAX_SUBMIT_ATTEMPTS: u32 = 5;
+
+/// Fee and replacement settings for the transactions the signer sends
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransactionConfig {
+    /// The lowest fee (in microSTX) to pay, e.g. when the node cannot estimate fees
+    pub min_fee: u64,
+    /// The highest fee (in microSTX) to pay, including after fee bumps
+    pub max_fee: u64,
+    /// The percentage a fee is raised by to replace a transaction
+    pub fee_bump_percent: u64,
+    /// How long a transaction may stay unconfirmed before it is replaced with a higher fee
+    pub replace_after: Duration,
+}
+
+/// A transaction that was admitted to the mempool, but not yet confirmed
+#[derive(Debug, Clone)]
+struct PendingTransaction {
+    /// The transaction without a fee or signature, to sign again with a higher fee
+    unsigned_tx: StacksTransaction,
+    /// The ID of the transaction as submitted
+    txid: Txid,
+    /// The fee the transaction pays
+    fee: u64,
+    /// When the transaction was submitted
+    submitted_at: Instant,
+}
+
+/// How to resubmit a transaction the node rejected
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Resubmission {
+    /// With a higher fee
+    Fee(u64),
+    /// With another nonce
+    Nonce(u64),
+}
+
+/// Builds, fee-estimates, signs and submits the signer's own Stacks transactions, and tracks
+/// them until they are confirmed. Nonces are taken from a local cache, which is reconciled with
+/// the node's account nonce, so that several transactions can be pending at once.
+/// Transactions that stay unconfirmed are replaced with a higher fee.
+#[derive(Debug)]
+pub struct TransactionManager {
+    stacks_client: StacksClient,
+    config: TransactionConfig,
+    /// The nonce of the next transaction, if it is known
+    next_nonce: Option<u64>,
+    /// The pending transactions, by nonce
+    pending: BTreeMap<u64, PendingTransaction>,
+}
+
+impl TransactionManager {
+    /// Send the transactions of `stacks_client`'s signer according to `config`
+    pub fn new(stacks_client: StacksClient, config: TransactionConfig) -> Self {
+        Self {
+            stacks_client,
+            config,
+            next_nonce: None,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// The IDs of the transactions that are not yet confirmed, in nonce order
+    pub fn pending_txids(&self) -> Vec<Txid> {
+        self.pending.values().map(|pending| pending.txid).collect()
+    }
+
+    /// Reconcile the nonce cache with the signer's account nonce on the node, and forget the
+    /// pending transactions it confirmed. Returns the nonce of the next transaction.
+    pub fn reconcile_nonce(&mut self) -> Result<u64, ClientError> {
+        let address = *self.stacks_client.get_signer_address();
+        let account_nonce = self.stacks_client.get_account_entry(&address)?.nonce;
+        Ok(self.apply_account_nonce(account_nonce))
+    }
+
+    fn apply_account_nonce(&mut self, account_nonce: u64) -> u64 {
+        self.pending = self.pending.split_off(&account_nonce);
+        let next_nonce = self
+            .pending
+            .keys()
+            .next_back()
+            .map_or(account_nonce, |nonce| nonce.saturating_add(1))
+            .max(account_nonce);
+        self.next_nonce = Some(next_nonce);
+        next_nonce
+    }
+
+    /// Build, sign and submit a call to `function_name` of `contract_addr.contract_name` with
+    /// `function_args`, paying the node's estimated fee
+    pub fn send_contract_call(
+        &mut self,
+        contract_addr: &StacksAddress,
+        contract_name: ContractName,
+        function_name: ClarityName,
+        function_args: &[ClarityValue],
+    ) -> Result<Txid, ClientError> {
+        let nonce = match self.next_nonce {
+            Some(nonce) => nonce,
+            None => self.reconcile_nonce()?,
+        };
+        let unsigned_tx = self.stacks_client.build_unsigned_signer_contract_call(
+            contract_addr,
+            contract_name,
+            function_name,
+            function_args,
+            nonce,
+        )?;
+        let fee = self.estimate_fee(&unsigned_tx);
+        self.submit(unsigned_tx, nonce, fee, true)
+    }
+
+    /// Replace the pending transactions that have not been confirmed within the configured
+    /// time with a higher fee. Returns the IDs of the replacements.
+    pub fn replace_stale_transactions(&mut self) -> Result<Vec<Txid>, ClientError> {
+        self.reconcile_nonce()?;
+        let stale: Vec<_> = self
+            .pending
+            .iter()
+            .filter(|(_, pending)| pending.submitted_at.elapsed() >= self.config.replace_after)
+            .map(|(nonce, pending)| (*nonce, pending.clone()))
+            .collect();
+        let mut replacements = vec![];
+        for (nonce, pending) in stale {
+            let fee = self.bump_fee(pending.fee);
+            if fee <= pending.fee {
+                warn!("Transaction is unconfirmed, but its fee cannot be raised further";
+                    "txid" => %pending.txid,
+                    "nonce" => nonce,
+                    "fee" => pending.fee,
+                );
+                continue;
+            }
+            info!("Replacing unconfirmed transaction with a higher fee";
+                "txid" => %pending.txid,
+                "nonce" => nonce,
+                "fee" => pending.fee,
+                "new_fee" => fee,
+            );
+            match self.submit(pending.unsigned_tx, nonce, fee, false) {
+                Ok(txid) => replacements.push(txid),
+                Err(e) => warn!("Failed to replace transaction {}: {e:?}", pending.txid),
+            }
+        }
+        Ok(replacements)
+    }
+
+    /// The fee to pay for `unsigned_tx`: the node's middle estimate, within the configured
+    /// bounds
+    fn estimate_fee(&self, unsigned_tx: &StacksTransaction) -> u64 {
+        let estimate = self
+            .stacks_client
+            .get_fee_estimate(&unsigned_tx.payload, Some(unsigned_tx.tx_len()));
+        let fee = match estimate {
+            Ok(estimate) => estimate
+                .estimations
+                .get(1)
+                .map_or(self.config.min_fee, |estimation| estimation.fee),
+            Err(e) => {
+                debug!("Failed to estimate transaction fee, using the minimum fee: {e:?}");
+                self.config.min_fee
+            }
+        };
+        fee.clamp(self.config.min_fee, self.config.max_fee)
+    }
+
+    /// `fee` raised by the configured percentage, but by at least 1 microSTX and at most to
+    /// the maximum fee
+    fn bump_fee(&self, fee: u64) -> u64 {
+        let bump = fee.saturating_mul(self.config.fee_bump_percent) / 100;
+        fee.saturating_add(bump.max(1)).min(self.config.max_fee)
+    }
+
+    /// How to resubmit a transaction with `nonce` and `fee` after the node's `rejection`, if
+    /// it can be. The nonce is only changed if `may_change_nonce`.
+    fn resubmission(
+        &self,
+        rejection: &TransactionRejection,
+        nonce: u64,
+        fee: u64,
+        may_change_nonce: bool,
+    ) -> Option<Resubmission> {
+        match rejection.reason.as_str() {
+            "FeeTooLow" => {
+                let expected = rejection.expected().unwrap_or(fee);
+                let new_fee = self.bump_fee(fee.max(expected));
+                if new_fee > fee && new_fee >= expected {
+                    Some(Resubmission::Fee(new_fee))
+                } else {
+                    None
+                }
+            }
+            // another transaction of ours is in the mempool with this nonce
+            "ConflictingNonceInMempool" if may_change_nonce => {
+                Some(Resubmission::Nonce(nonce.saturating_add(1)))
+            }
+            "BadNonce" if may_change_nonce => rejection
+                .expected()
+                .filter(|expected| *expected > nonce)
+                .map(Resubmission::Nonce),
+            _ => None,
+        }
+    }
+
+    /// Sign `unsigned_tx` with `nonce` and `fee`, and submit it. If the node rejects it for its
+    /// fee (or nonce, if `may_change_nonce`), it is resubmitted with a better one.
+    fn submit(
+        &mut self,
+        unsigned_tx: StacksTransaction,
+        mut nonce: u64,
+        mut fee: u64,
+        may_change_nonce: bool,
+    ) -> Result<Txid, ClientError> {
+        let mut attempts = 0;
+        loop {
+            let mut tx = unsigned_tx.clone();
+            tx.set_origin_nonce(nonce);
+            tx.set_tx_fee(fee);
+            let signed_tx = self.stacks_client.sign_transaction(tx)?;
+            let rejection = match self.stacks_client.submit_transaction(&signed_tx) {
+                Ok(txid) => {
+                    debug!("Transaction admitted to the mempool";
+                        "txid" => %txid,
+                        "nonce" => nonce,
+                        "fee" => fee,
+                    );
+                    self.pending.insert(
+                        nonce,
+                        PendingTransaction {
+                            unsigned_tx,
+                            txid,
+                            fee,
+                            submitted_at: Instant::now(),
+                        },
+                    );
+                    let next_nonce = self.next_nonce.unwrap_or(0).max(nonce.saturating_add(1));
+                    self.next_nonce = Some(next_nonce);
+                    return Ok(txid);
+                }
+                Err(ClientError::TransactionRejected(rejection)) => rejection,
+                Err(e) => return Err(e),
+            };
+            attempts += 1;
+            let resubmission = self.resubmission(&rejection, nonce, fee, may_change_nonce);
+            let Some(resubmission) = resubmission.filter(|_| attempts < MAX_SUBMIT_ATTEMPTS) else {
+                return Err(ClientError::TransactionRejected(rejection));
+            };
+            warn!("Transaction rejected by the stacks node, resubmitting";
+                "reason" => %rejection.reason,
+                "reason_data" => ?rejection.reason_data,
+                "nonce" => nonce,
+                "fee" => fee,
+                "resubmission" => ?resubmission,
+            );
+            match resubmission {
+                Resubmission::Fee(new_fee) => fee = new_fee,
+                Resubmission::Nonce(new_nonce) => nonce = new_nonce,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread::spawn;
+
+    use blockstack_lib::chainstate::stacks::boot::SIGNERS_VOTING_NAME;
+    use blockstack_lib::net::api::getaccount::AccountEntryResponse;
+    use blockstack_lib::net::api::postfeerate::{RPCFeeEstimate, RPCFeeEstimateResponse};
+    use clarity::vm::costs::ExecutionCost;
+
+    use super::*;
+    use crate::client::tests::{write_response, MockServerClient};
+
+    fn config() -> TransactionConfig {
+        TransactionConfig {
+            min_fee: 1_000,
+            max_fee: 10_000,
+            fee_bump_percent: 25,
+            replace_after: Duration::from_secs(60),
+        }
+    }
+
+    fn rejection(reason: &str, expected: Option<u64>) -> TransactionRejection {
+        TransactionRejection {
+            reason: reason.into(),
+            reason_data: expected.map(|expected| serde_json::json!({ "expected": expected })),
+        }
+    }
+
+    fn json_response<T: serde::Serialize>(status: &str, body: &T) -> String {
+        format!(
+            "HTTP/1.1 {status}\n\n{}",
+            serde_json::to_string(body).unwrap()
+        )
+    }
+
+    #[test]
+    fn rejected_transactions_are_resubmitted() {
+        let mock = MockServerClient::new();
+        let manager = TransactionManager::new(mock.client, config());
+        assert_eq!(manager.bump_fee(1_000), 1_250);
+        assert_eq!(manager.bump_fee(3), 4);
+        assert_eq!(manager.bump_fee(9_000), 10_000);
+
+        assert_eq!(
+            manager.resubmission(&rejection("FeeTooLow", Some(2_000)), 5, 1_000, true),
+            Some(Resubmission::Fee(2_500))
+        );
+        assert_eq!(
+            manager.resubmission(&rejection("FeeTooLow", Some(20_000)), 5, 1_000, true),
+            None
+        );
+        assert_eq!(
+            manager.resubmission(
+                &rejection("ConflictingNonceInMempool", None),
+                5,
+                1_000,
+                true
+            ),
+            Some(Resubmission::Nonce(6))
+        );
+        assert_eq!(
+            manager.resubmission(
+                &rejection("ConflictingNonceInMempool", None),
+                5,
+                1_000,
+                false
+            ),
+            None
+        );
+        assert_eq!(
+            manager.resubmission(&rejection("BadNonce", Some(7)), 5, 1_000, true),
+            Some(Resubmission::Nonce(7))
+        );
+        assert_eq!(
+            manager.resubmission(&rejection("NotEnoughFunds", None), 5, 1_000, true),
+            None
+        );
+    }
+
+    #[test]
+    fn nonces_are_reconciled_with_the_node() {
+        let mock = MockServerClient::new();
+        let mut manager = TransactionManager::new(mock.client.clone(), config());
+        let unsigned_tx = mock
+            .client
+            .build_unsigned_signer_contract_call(
+                &StacksAddress::burn_address(false),
+                SIGNERS_VOTING_NAME.into(),
+                "vote-for-aggregate-public-key".into(),
+                &[],
+                0,
+            )
+            .unwrap();
+        for (nonce, txid) in [(3, Txid([3; 32])), (4, Txid([4; 32]))] {
+            manager.pending.insert(
+                nonce,
+                PendingTransaction {
+                    unsigned_tx: unsigned_tx.clone(),
+                    txid,
+                    fee: 1_000,
+                    submitted_at: Instant::now(),
+                },
+            );
+        }
+        assert_eq!(manager.apply_account_nonce(2), 5);
+        assert_eq!(manager.pending_txids(), vec![Txid([3; 32]), Txid([4; 32])]);
+        // the transaction with nonce 3 was confirmed
+        assert_eq!(manager.apply_account_nonce(4), 5);
+        assert_eq!(manager.pending_txids(), vec![Txid([4; 32])]);
+        // every transaction was confirmed, and others were sent from the same account
+        assert_eq!(manager.apply_account_nonce(8), 8);
+        assert!(manager.pending_txids().is_empty());
+    }
+
+    #[test]
+    fn contract_call_should_be_estimated_and_resubmitted() {
+        let mock = MockServerClient::new();
+        let mut manager = TransactionManager::new(mock.client, config());
+        let server = mock.server;
+        let h = spawn(move || {
+            let txid = manager
+                .send_contract_call(
+                    &StacksAddress::burn_address(false),
+                    SIGNERS_VOTING_NAME.into(),
+                    "vote-for-aggregate-public-key".into(),
+                    &[ClarityValue::UInt(1)],
+                )
+                .unwrap();
+            (manager, txid)
+        });
+
+        let account = AccountEntryResponse {
+            balance: "0x0".into(),
+            locked: "0x0".into(),
+            unlock_height: 0,
+            nonce: 7,
+            balance_proof: None,
+            nonce_proof: None,
+        };
+        let request = write_response(
+            server.try_clone().unwrap(),
+            json_response("200 OK", &account).as_bytes(),
+        );
+        assert!(String::from_utf8_lossy(&request).starts_with("GET /v2/accounts/"));
+
+        let estimate = RPCFeeEstimateResponse {
+            estimated_cost: ExecutionCost::zero(),
+            estimated_cost_scalar: 1,
+            estimations: [500, 1_500, 2_500]
+                .into_iter()
+                .map(|fee| RPCFeeEstimate { fee_rate: 1.0, fee })
+                .collect(),
+            cost_scalar_change_by_byte: 0.0,
+            fee_rate_bands: None,
+        };
+        let request = write_response(
+            server.try_clone().unwrap(),
+            json_response("200 OK", &estimate).as_bytes(),
+        );
+        assert!(String::from_utf8_lossy(&request).starts_with("POST /v2/fees/transaction "));
+
+        let too_low = serde_json::json!({
+            "txid": "00",
+            "error": "transaction rejected",
+            "reason": "FeeTooLow",
+            "reason_data": { "expected": 1_800, "actual": 1_500 },
+        });
+        let request = write_response(
+            server.try_clone().unwrap(),
+            json_response("400 Bad Request", &too_low).as_bytes(),
+        );
+        assert!(String::from_utf8_lossy(&request).starts_with("POST /v2/transactions "));
+
+        let txid = Txid([9; 32]);
+        write_response(server, json_response("200 OK", &txid).as_bytes());
+        let (manager, submitted_txid) = h.join().unwrap();
+        assert_eq!(submitted_txid, txid);
+        assert_eq!(manager.next_nonce, Some(8));
+        let pending = &manager.pending[&7];
+        assert_eq!(pending.txid, txid);
+        assert_eq!(pending.fee, 2_250);
+    }
+}
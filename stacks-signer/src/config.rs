@@ -22,22 +22,39 @@ use std::time::Duration;
 
 use blockstack_lib::chainstate::stacks::TransactionVersion;
 use clarity::util::hash::to_hex;
-use libsigner::SignerEntries;
+use libsigner::{ConnectionPool, SignerEntries};
 use serde::Deserialize;
 use stacks_common::address::{
     C32_ADDRESS_VERSION_MAINNET_SINGLESIG, C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
 };
 use stacks_common::consts::{CHAIN_ID_MAINNET, CHAIN_ID_TESTNET};
 use stacks_common::types::chainstate::{StacksAddress, StacksPrivateKey, StacksPublicKey};
+use stacks_common::util::config::load_toml;
 use stacks_common::util::hash::Hash160;
 
-use crate::client::SignerSlotID;
+use crate::client::{MessageThrottleConfig, SignerSlotID, TransactionConfig};
+use crate::keystore::{run_key_command, Keystore, KEYSTORE_PASSWORD_ENV};
+use crate::policy::{BlockPolicy, RawBlockPolicy};
 
 const EVENT_TIMEOUT_MS: u64 = 5000;
 const BLOCK_PROPOSAL_TIMEOUT_MS: u64 = 600_000;
 const BLOCK_PROPOSAL_VALIDATION_TIMEOUT_MS: u64 = 120_000;
 const DEFAULT_FIRST_PROPOSAL_BURN_BLOCK_TIMING_SECS: u64 = 60;
 const DEFAULT_TENURE_LAST_BLOCK_PROPOSAL_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_STACKERDB_MAX_IDLE_CONNECTIONS: usize = 4;
+/// Shorter than the node's default 15-second HTTP idle timeout, so that the signer rarely reuses
+/// a connection the node is about to close
+const DEFAULT_STACKERDB_IDLE_TIMEOUT_MS: u64 = 10_000;
+const DEFAULT_STACKERDB_DEDUPE_WINDOW_MS: u64 = 60_000;
+const DEFAULT_STACKERDB_MAX_WRITES_PER_SEC: u32 = 10;
+const DEFAULT_STACKERDB_WRITE_BURST: u32 = 20;
+const DEFAULT_TX_MIN_FEE_USTX: u64 = 1_000;
+const DEFAULT_TX_MAX_FEE_USTX: u64 = 1_000_000;
+const DEFAULT_TX_FEE_BUMP_PERCENT: u64 = 25;
+const DEFAULT_TX_REPLACE_AFTER_SECS: u64 = 600;
+/// Prefix of the environment variables that override config file fields, e.g.
+/// `STACKS_SIGNER_CONFIG__NODE_HOST` overrides `node_host`
+pub const CONFIG_ENV_PREFIX: &str = "STACKS_SIGNER_CONFIG__";
 
 #[derive(thiserror::Error, Debug)]
 /// An error occurred parsing the provided configuration
@@ -119,6 +136,8 @@ pub struct SignerConfig {
     pub signer_slot_ids: Vec<SignerSlotID>,
     /// The private key for this signer
     pub stacks_private_key: StacksPrivateKey,
+    /// The signer's staged key rotation, if any
+    pub key_rotation: Option<KeyRotationConfig>,
     /// The node host for this signer
     pub node_host: String,
     /// Whether this signer is running on mainnet or not
@@ -135,6 +154,23 @@ pub struct SignerConfig {
     pub tenure_last_block_proposal_timeout: Duration,
     /// How much time to wait for a block proposal validation response before marking the block invalid
     pub block_proposal_validation_timeout: Duration,
+    /// Keep-alive connections to the node's StackerDB, shared by all of the signer's sessions
+    pub stackerdb_connection_pool: ConnectionPool,
+    /// How the signer's StackerDB writes are deduplicated and rate limited
+    pub stackerdb_throttle: MessageThrottleConfig,
+    /// Fee and replacement settings for the transactions the signer sends
+    pub transaction_config: TransactionConfig,
+    /// The rules blocks must follow before the signer signs them
+    pub block_policy: BlockPolicy,
+}
+
+/// A staged rotation of the signer's private key
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyRotationConfig {
+    /// The private key the signer rotates to
+    pub next_private_key: StacksPrivateKey,
+    /// The first reward cycle the signer signs with the next private key
+    pub activation_reward_cycle: u64,
 }
 
 /// The parsed configuration for the signer
@@ -148,6 +184,8 @@ pub struct GlobalConfig {
     pub stacks_private_key: StacksPrivateKey,
     /// The signer's Stacks address
     pub stacks_address: StacksAddress,
+    /// The signer's staged key rotation, if any
+    pub key_rotation: Option<KeyRotationConfig>,
     /// The network to use. One of "mainnet" or "testnet".
     pub network: Network,
     /// The time to wait for a response from the stacker-db instance
@@ -171,6 +209,29 @@ pub struct GlobalConfig {
     /// How long to wait for a response from a block proposal validation response from the node
     /// before marking that block as invalid and rejecting it
     pub block_proposal_validation_timeout: Duration,
+    /// The most idle keep-alive connections to the node's StackerDB to keep open. 0 opens a new
+    /// connection for every request.
+    pub stackerdb_max_idle_connections: usize,
+    /// How long an idle StackerDB connection is kept open
+    pub stackerdb_idle_timeout: Duration,
+    /// How long a message the StackerDB accepted is remembered, so that sending it again to the
+    /// same slot is skipped. Zero disables deduplication.
+    pub stackerdb_dedupe_window: Duration,
+    /// The most StackerDB chunk writes per second for each message ID. Zero disables rate
+    /// limiting.
+    pub stackerdb_max_writes_per_sec: u32,
+    /// The most StackerDB chunk writes for each message ID in a burst
+    pub stackerdb_write_burst: u32,
+    /// The lowest fee (in microSTX) the signer pays for its transactions
+    pub tx_min_fee: u64,
+    /// The highest fee (in microSTX) the signer pays for its transactions
+    pub tx_max_fee: u64,
+    /// The percentage a transaction's fee is raised by to replace it
+    pub tx_fee_bump_percent: u64,
+    /// How long a transaction may stay unconfirmed before it is replaced with a higher fee
+    pub tx_replace_after: Duration,
+    /// The rules blocks must follow before the signer signs them
+    pub block_policy: BlockPolicy,
 }
 
 /// Internal struct for loading up the config file
@@ -182,7 +243,24 @@ struct RawConfigFile {
     pub endpoint: String,
     /// The hex representation of the signer's Stacks private key used for communicating
     /// with the Stacks Node, including writing to the Stacker DB instance.
-    pub stacks_private_key: String,
+    pub stacks_private_key: Option<String>,
+    /// The path to a keystore file holding the encrypted private key, instead of
+    /// `stacks_private_key`. Its password is read from `STACKS_SIGNER_KEYSTORE_PASSWORD`.
+    pub stacks_private_key_file: Option<String>,
+    /// A command (program and arguments) that prints the hex private key, instead of
+    /// `stacks_private_key`, e.g. to read it from an OS keychain or a KMS
+    pub stacks_private_key_command: Option<Vec<String>>,
+    /// The hex representation of the private key the signer rotates to at
+    /// `key_rotation_reward_cycle`
+    pub next_stacks_private_key: Option<String>,
+    /// The path to a keystore file holding the encrypted next private key, instead of
+    /// `next_stacks_private_key`. Its password is read from `STACKS_SIGNER_KEYSTORE_PASSWORD`.
+    pub next_stacks_private_key_file: Option<String>,
+    /// A command (program and arguments) that prints the hex next private key, instead of
+    /// `next_stacks_private_key`
+    pub next_stacks_private_key_command: Option<Vec<String>>,
+    /// The first reward cycle the signer signs with its next private key
+    pub key_rotation_reward_cycle: Option<u64>,
     /// The network to use. One of "mainnet" or "testnet".
     pub network: Network,
     /// The time to wait (in millisecs) for a response from the stacker-db instance
@@ -206,13 +284,36 @@ struct RawConfigFile {
     /// How long to wait (in millisecs) for a response from a block proposal validation response from the node
     /// before marking that block as invalid and rejecting it
     pub block_proposal_validation_timeout_ms: Option<u64>,
+    /// The most idle keep-alive connections to the node's StackerDB to keep open. 0 opens a new
+    /// connection for every request.
+    pub stackerdb_max_idle_connections: Option<usize>,
+    /// How long (in millisecs) an idle StackerDB connection is kept open
+    pub stackerdb_idle_timeout_ms: Option<u64>,
+    /// How long (in millisecs) a message the StackerDB accepted is remembered, so that sending
+    /// it again to the same slot is skipped. 0 disables deduplication.
+    pub stackerdb_dedupe_window_ms: Option<u64>,
+    /// The most StackerDB chunk writes per second for each message ID. 0 disables rate limiting.
+    pub stackerdb_max_writes_per_sec: Option<u32>,
+    /// The most StackerDB chunk writes for each message ID in a burst
+    pub stackerdb_write_burst: Option<u32>,
+    /// The lowest fee (in microSTX) the signer pays for its transactions
+    pub tx_min_fee_ustx: Option<u64>,
+    /// The highest fee (in microSTX) the signer pays for its transactions
+    pub tx_max_fee_ustx: Option<u64>,
+    /// The percentage a transaction's fee is raised by to replace it
+    pub tx_fee_bump_percent: Option<u64>,
+    /// How long (in secs) a transaction may stay unconfirmed before it is replaced with a higher
+    /// fee
+    pub tx_replace_after_secs: Option<u64>,
+    /// The rules blocks must follow before the signer signs them
+    pub block_policy: Option<RawBlockPolicy>,
 }
 
 impl RawConfigFile {
     /// load the config from a string
     pub fn load_from_str(data: &str) -> Result<Self, ConfigError> {
-        let config: Self =
-            toml::from_str(data).map_err(|e| ConfigError::ParseError(format!("{e:?}")))?;
+        let config: Self = load_toml(data, CONFIG_ENV_PREFIX)
+            .map_err(|e| ConfigError::ParseError(e.to_string()))?;
         Ok(config)
     }
     /// load the config from a file and parse it
@@ -220,6 +321,92 @@ impl RawConfigFile {
     pub fn load_from_file(path: &str) -> Result<Self, ConfigError> {
         Self::try_from(&PathBuf::from(path))
     }
+
+    /// Get the private key from whichever one of `stacks_private_key`,
+    /// `stacks_private_key_file` or `stacks_private_key_command` is set.  A keystore file is
+    /// decrypted with the password that `keystore_password` returns.
+    fn load_stacks_private_key(
+        &self,
+        keystore_password: &dyn Fn() -> Option<String>,
+    ) -> Result<StacksPrivateKey, ConfigError> {
+        load_private_key(
+            "stacks_private_key",
+            &self.stacks_private_key,
+            &self.stacks_private_key_file,
+            &self.stacks_private_key_command,
+            keystore_password,
+        )?
+        .ok_or_else(|| {
+            ConfigError::InvalidConfig(
+                "Exactly one of stacks_private_key, stacks_private_key_file or stacks_private_key_command must be set".to_string(),
+            )
+        })
+    }
+
+    /// Get the staged key rotation from whichever one of `next_stacks_private_key`,
+    /// `next_stacks_private_key_file` or `next_stacks_private_key_command` is set, and
+    /// `key_rotation_reward_cycle`
+    fn load_key_rotation(
+        &self,
+        keystore_password: &dyn Fn() -> Option<String>,
+    ) -> Result<Option<KeyRotationConfig>, ConfigError> {
+        let next_private_key = load_private_key(
+            "next_stacks_private_key",
+            &self.next_stacks_private_key,
+            &self.next_stacks_private_key_file,
+            &self.next_stacks_private_key_command,
+            keystore_password,
+        )?;
+        match (next_private_key, self.key_rotation_reward_cycle) {
+            (Some(next_private_key), Some(activation_reward_cycle)) => Ok(Some(KeyRotationConfig {
+                next_private_key,
+                activation_reward_cycle,
+            })),
+            (None, None) => Ok(None),
+            _ => Err(ConfigError::InvalidConfig(
+                "key_rotation_reward_cycle must be set if and only if a next stacks private key is set".to_string(),
+            )),
+        }
+    }
+}
+
+/// The keystore password in the `STACKS_SIGNER_KEYSTORE_PASSWORD` environment variable
+fn keystore_password_from_env() -> Option<String> {
+    std::env::var(KEYSTORE_PASSWORD_ENV).ok()
+}
+
+/// Get the private key from whichever one of `{field}`, `{field}_file` or `{field}_command`
+/// is set, if any
+fn load_private_key(
+    field: &str,
+    key_hex: &Option<String>,
+    key_file: &Option<String>,
+    key_command: &Option<Vec<String>>,
+    keystore_password: &dyn Fn() -> Option<String>,
+) -> Result<Option<StacksPrivateKey>, ConfigError> {
+    match (key_hex, key_file, key_command) {
+        (None, None, None) => Ok(None),
+        (Some(key_hex), None, None) => StacksPrivateKey::from_hex(key_hex)
+            .map(Some)
+            .map_err(|e| ConfigError::BadField(field.to_string(), e.into())),
+        (None, Some(path), None) => {
+            let password = keystore_password().ok_or_else(|| {
+                ConfigError::InvalidConfig(format!(
+                    "{field}_file is set, but {KEYSTORE_PASSWORD_ENV} is not"
+                ))
+            })?;
+            Keystore::load(&PathBuf::from(path))
+                .and_then(|keystore| keystore.decrypt(&password))
+                .map(Some)
+                .map_err(|e| ConfigError::BadField(format!("{field}_file"), e.to_string()))
+        }
+        (None, None, Some(command)) => run_key_command(command)
+            .map(Some)
+            .map_err(|e| ConfigError::BadField(format!("{field}_command"), e.to_string())),
+        _ => Err(ConfigError::InvalidConfig(format!(
+            "Only one of {field}, {field}_file or {field}_command may be set"
+        ))),
+    }
 }
 
 impl TryFrom<&PathBuf> for RawConfigFile {
@@ -251,12 +438,12 @@ impl TryFrom<RawConfigFile> for GlobalConfig {
                 ConfigError::BadField("endpoint".to_string(), raw_data.endpoint.clone())
             })?;
 
-        let stacks_private_key = StacksPrivateKey::from_hex(&raw_data.stacks_private_key)
-            .map_err(|e| ConfigError::BadField("stacks_private_key".to_string(), e.into()))?;
+        let stacks_private_key = raw_data.load_stacks_private_key(&keystore_password_from_env)?;
         let stacks_public_key = StacksPublicKey::from_private(&stacks_private_key);
         let signer_hash = Hash160::from_data(stacks_public_key.to_bytes_compressed().as_slice());
         let stacks_address =
             StacksAddress::p2pkh_from_hash(raw_data.network.is_mainnet(), signer_hash);
+        let key_rotation = raw_data.load_key_rotation(&keystore_password_from_env)?;
         let event_timeout =
             Duration::from_millis(raw_data.event_timeout_ms.unwrap_or(EVENT_TIMEOUT_MS));
         let first_proposal_burn_block_timing = Duration::from_secs(
@@ -297,11 +484,41 @@ impl TryFrom<RawConfigFile> for GlobalConfig {
                 .unwrap_or(BLOCK_PROPOSAL_VALIDATION_TIMEOUT_MS),
         );
 
+        let stackerdb_idle_timeout = Duration::from_millis(
+            raw_data
+                .stackerdb_idle_timeout_ms
+                .unwrap_or(DEFAULT_STACKERDB_IDLE_TIMEOUT_MS),
+        );
+
+        let stackerdb_dedupe_window = Duration::from_millis(
+            raw_data
+                .stackerdb_dedupe_window_ms
+                .unwrap_or(DEFAULT_STACKERDB_DEDUPE_WINDOW_MS),
+        );
+
+        let tx_min_fee = raw_data.tx_min_fee_ustx.unwrap_or(DEFAULT_TX_MIN_FEE_USTX);
+        let tx_max_fee = raw_data.tx_max_fee_ustx.unwrap_or(DEFAULT_TX_MAX_FEE_USTX);
+        if tx_min_fee > tx_max_fee {
+            return Err(ConfigError::BadField(
+                "tx_min_fee_ustx".to_string(),
+                format!("{tx_min_fee} (must not exceed tx_max_fee_ustx {tx_max_fee})"),
+            ));
+        }
+
+        let tx_replace_after = Duration::from_secs(
+            raw_data
+                .tx_replace_after_secs
+                .unwrap_or(DEFAULT_TX_REPLACE_AFTER_SECS),
+        );
+
+        let block_policy = BlockPolicy::try_from(raw_data.block_policy.unwrap_or_default())?;
+
         Ok(Self {
             node_host: raw_data.node_host,
             endpoint,
             stacks_private_key,
             stacks_address,
+            key_rotation,
             network: raw_data.network,
             event_timeout,
             auth_password: raw_data.auth_password,
@@ -312,6 +529,24 @@ impl TryFrom<RawConfigFile> for GlobalConfig {
             chain_id: raw_data.chain_id,
             tenure_last_block_proposal_timeout,
             block_proposal_validation_timeout,
+            stackerdb_max_idle_connections: raw_data
+                .stackerdb_max_idle_connections
+                .unwrap_or(DEFAULT_STACKERDB_MAX_IDLE_CONNECTIONS),
+            stackerdb_idle_timeout,
+            stackerdb_dedupe_window,
+            stackerdb_max_writes_per_sec: raw_data
+                .stackerdb_max_writes_per_sec
+                .unwrap_or(DEFAULT_STACKERDB_MAX_WRITES_PER_SEC),
+            stackerdb_write_burst: raw_data
+                .stackerdb_write_burst
+                .unwrap_or(DEFAULT_STACKERDB_WRITE_BURST),
+            tx_min_fee,
+            tx_max_fee,
+            tx_fee_bump_percent: raw_data
+                .tx_fee_bump_percent
+                .unwrap_or(DEFAULT_TX_FEE_BUMP_PERCENT),
+            tx_replace_after,
+            block_policy,
         })
     }
 }
@@ -366,6 +601,44 @@ Metrics endpoint: {metrics_endpoint}
         )
     }
 
+    /// Create the pool of StackerDB connections that the signer's sessions share
+    pub fn new_stackerdb_connection_pool(&self) -> ConnectionPool {
+        ConnectionPool::new(
+            self.stackerdb_max_idle_connections,
+            self.stackerdb_idle_timeout,
+        )
+    }
+
+    /// How the signer's StackerDB writes are deduplicated and rate limited
+    pub fn stackerdb_throttle_config(&self) -> MessageThrottleConfig {
+        MessageThrottleConfig {
+            dedupe_window: self.stackerdb_dedupe_window,
+            max_writes_per_sec: self.stackerdb_max_writes_per_sec,
+            write_burst: self.stackerdb_write_burst,
+        }
+    }
+
+    /// Fee and replacement settings for the transactions the signer sends
+    pub fn transaction_config(&self) -> TransactionConfig {
+        TransactionConfig {
+            min_fee: self.tx_min_fee,
+            max_fee: self.tx_max_fee,
+            fee_bump_percent: self.tx_fee_bump_percent,
+            replace_after: self.tx_replace_after,
+        }
+    }
+
+    /// The private key the signer signs with in `reward_cycle`: the next key from the key
+    /// rotation's activation reward cycle on, and the current key before it
+    pub fn stacks_private_key_for_cycle(&self, reward_cycle: u64) -> StacksPrivateKey {
+        match &self.key_rotation {
+            Some(rotation) if reward_cycle >= rotation.activation_reward_cycle => {
+                rotation.next_private_key
+            }
+            _ => self.stacks_private_key,
+        }
+    }
+
     /// Get the chain ID for the network
     pub fn to_chain_id(&self) -> u32 {
         self.chain_id.unwrap_or(match self.network {
@@ -483,6 +756,9 @@ chain_id = {chain_id}
 
 #[cfg(test)]
 mod tests {
+    use blockstack_lib::chainstate::nakamoto::ordering::TxOrderingRule;
+    use clarity::vm::types::QualifiedContractIdentifier;
+
     use super::*;
 
     #[test]
@@ -624,4 +900,352 @@ db_path = ":memory:"
         let global_config = GlobalConfig::try_from(config).unwrap();
         assert_eq!(global_config.to_chain_id(), 0x80000100);
     }
+
+    #[test]
+    fn test_stackerdb_connection_pool_config() {
+        let base_toml = r#"
+stacks_private_key = "2de4e77aab89c0c2570bb8bb90824f5cf2a5204a975905fee450ff9dad0fcf2801"
+node_host = "localhost"
+endpoint = "localhost:30000"
+network = "mainnet"
+auth_password = "abcd"
+db_path = ":memory:"
+"#;
+        let config = GlobalConfig::load_from_str(base_toml).unwrap();
+        assert_eq!(
+            config.stackerdb_max_idle_connections,
+            DEFAULT_STACKERDB_MAX_IDLE_CONNECTIONS
+        );
+        assert_eq!(
+            config.stackerdb_idle_timeout,
+            Duration::from_millis(DEFAULT_STACKERDB_IDLE_TIMEOUT_MS)
+        );
+
+        let config_toml = format!(
+            r#"{base_toml}
+stackerdb_max_idle_connections = 0
+stackerdb_idle_timeout_ms = 500
+"#
+        );
+        let config = GlobalConfig::load_from_str(&config_toml).unwrap();
+        assert_eq!(config.stackerdb_max_idle_connections, 0);
+        assert_eq!(config.stackerdb_idle_timeout, Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_stackerdb_throttle_config() {
+        let base_toml = r#"
+stacks_private_key = "2de4e77aab89c0c2570bb8bb90824f5cf2a5204a975905fee450ff9dad0fcf2801"
+node_host = "localhost"
+endpoint = "localhost:30000"
+network = "mainnet"
+auth_password = "abcd"
+db_path = ":memory:"
+"#;
+        let config = GlobalConfig::load_from_str(base_toml).unwrap();
+        assert_eq!(
+            config.stackerdb_throttle_config(),
+            MessageThrottleConfig {
+                dedupe_window: Duration::from_millis(DEFAULT_STACKERDB_DEDUPE_WINDOW_MS),
+                max_writes_per_sec: DEFAULT_STACKERDB_MAX_WRITES_PER_SEC,
+                write_burst: DEFAULT_STACKERDB_WRITE_BURST,
+            }
+        );
+
+        let config_toml = format!(
+            r#"{base_toml}
+stackerdb_dedupe_window_ms = 0
+stackerdb_max_writes_per_sec = 0
+stackerdb_write_burst = 5
+"#
+        );
+        let config = GlobalConfig::load_from_str(&config_toml).unwrap();
+        assert_eq!(
+            config.stackerdb_throttle_config(),
+            MessageThrottleConfig {
+                dedupe_window: Duration::ZERO,
+                max_writes_per_sec: 0,
+                write_burst: 5,
+            }
+        );
+    }
+
+    #[test]
+    fn test_transaction_config() {
+        let base_toml = r#"
+stacks_private_key = "2de4e77aab89c0c2570bb8bb90824f5cf2a5204a975905fee450ff9dad0fcf2801"
+node_host = "localhost"
+endpoint = "localhost:30000"
+network = "mainnet"
+auth_password = "abcd"
+db_path = ":memory:"
+"#;
+        let config = GlobalConfig::load_from_str(base_toml).unwrap();
+        assert_eq!(
+            config.transaction_config(),
+            TransactionConfig {
+                min_fee: DEFAULT_TX_MIN_FEE_USTX,
+                max_fee: DEFAULT_TX_MAX_FEE_USTX,
+                fee_bump_percent: DEFAULT_TX_FEE_BUMP_PERCENT,
+                replace_after: Duration::from_secs(DEFAULT_TX_REPLACE_AFTER_SECS),
+            }
+        );
+
+        let config_toml = format!(
+            r#"{base_toml}
+tx_min_fee_ustx = 200
+tx_max_fee_ustx = 5000
+tx_fee_bump_percent = 50
+tx_replace_after_secs = 30
+"#
+        );
+        let config = GlobalConfig::load_from_str(&config_toml).unwrap();
+        assert_eq!(
+            config.transaction_config(),
+            TransactionConfig {
+                min_fee: 200,
+                max_fee: 5000,
+                fee_bump_percent: 50,
+                replace_after: Duration::from_secs(30),
+            }
+        );
+
+        let config_toml = format!(
+            r#"{base_toml}
+tx_min_fee_ustx = 6000
+tx_max_fee_ustx = 5000
+"#
+        );
+        assert!(matches!(
+            GlobalConfig::load_from_str(&config_toml),
+            Err(ConfigError::BadField(field, _)) if field == "tx_min_fee_ustx"
+        ));
+    }
+
+    #[test]
+    fn test_key_rotation_config() {
+        let base_toml = r#"
+stacks_private_key = "2de4e77aab89c0c2570bb8bb90824f5cf2a5204a975905fee450ff9dad0fcf2801"
+node_host = "localhost"
+endpoint = "localhost:30000"
+network = "mainnet"
+auth_password = "abcd"
+db_path = ":memory:"
+"#;
+        let config = GlobalConfig::load_from_str(base_toml).unwrap();
+        assert_eq!(config.key_rotation, None);
+        assert_eq!(
+            config.stacks_private_key_for_cycle(100),
+            config.stacks_private_key
+        );
+
+        let next_private_key = StacksPrivateKey::new();
+        let config_toml = format!(
+            r#"{base_toml}
+next_stacks_private_key = "{}"
+key_rotation_reward_cycle = 10
+"#,
+            next_private_key.to_hex()
+        );
+        let config = GlobalConfig::load_from_str(&config_toml).unwrap();
+        assert_eq!(
+            config.key_rotation,
+            Some(KeyRotationConfig {
+                next_private_key,
+                activation_reward_cycle: 10,
+            })
+        );
+        assert_eq!(
+            config.stacks_private_key_for_cycle(9),
+            config.stacks_private_key
+        );
+        assert_eq!(config.stacks_private_key_for_cycle(10), next_private_key);
+        assert_eq!(config.stacks_private_key_for_cycle(11), next_private_key);
+
+        // the next key and the reward cycle go together
+        let config_toml = format!(
+            r#"{base_toml}
+next_stacks_private_key = "{}"
+"#,
+            next_private_key.to_hex()
+        );
+        assert!(matches!(
+            GlobalConfig::load_from_str(&config_toml),
+            Err(ConfigError::InvalidConfig(_))
+        ));
+        let config_toml = format!(
+            r#"{base_toml}
+key_rotation_reward_cycle = 10
+"#
+        );
+        assert!(matches!(
+            GlobalConfig::load_from_str(&config_toml),
+            Err(ConfigError::InvalidConfig(_))
+        ));
+
+        // at most one source of the next key
+        let config_toml = format!(
+            r#"{base_toml}
+next_stacks_private_key = "{}"
+next_stacks_private_key_command = ["echo", "{}"]
+key_rotation_reward_cycle = 10
+"#,
+            next_private_key.to_hex(),
+            next_private_key.to_hex()
+        );
+        assert!(matches!(
+            GlobalConfig::load_from_str(&config_toml),
+            Err(ConfigError::InvalidConfig(_))
+        ));
+        let config_toml = format!(
+            r#"{base_toml}
+next_stacks_private_key = "not a key"
+key_rotation_reward_cycle = 10
+"#
+        );
+        assert!(matches!(
+            GlobalConfig::load_from_str(&config_toml),
+            Err(ConfigError::BadField(field, _)) if field == "next_stacks_private_key"
+        ));
+    }
+
+    #[test]
+    fn test_block_policy_config() {
+        let base_toml = r#"
+stacks_private_key = "2de4e77aab89c0c2570bb8bb90824f5cf2a5204a975905fee450ff9dad0fcf2801"
+node_host = "localhost"
+endpoint = "localhost:30000"
+network = "mainnet"
+auth_password = "abcd"
+db_path = ":memory:"
+"#;
+        let config = GlobalConfig::load_from_str(base_toml).unwrap();
+        assert!(config.block_policy.is_empty());
+
+        let config_toml = format!(
+            r#"{base_toml}
+[block_policy]
+required_contract_txs = ["SP000000000000000000002Q6VF78.sbtc-deposit"]
+required_contract_txs_max_pending_secs = 60
+reject_empty_blocks_mempool_size = 100
+tx_ordering = "salted-txid"
+
+[[block_policy.max_contract_txs]]
+contract = "SP000000000000000000002Q6VF78.pox-4"
+max_txs = 5
+"#
+        );
+        let config = GlobalConfig::load_from_str(&config_toml).unwrap();
+        let policy = config.block_policy;
+        assert_eq!(
+            policy.max_contract_txs,
+            vec![(
+                QualifiedContractIdentifier::parse("SP000000000000000000002Q6VF78.pox-4").unwrap(),
+                5
+            )]
+        );
+        assert_eq!(
+            policy.required_contract_txs,
+            vec![
+                QualifiedContractIdentifier::parse("SP000000000000000000002Q6VF78.sbtc-deposit")
+                    .unwrap()
+            ]
+        );
+        assert_eq!(
+            policy.required_contract_txs_max_pending,
+            Duration::from_secs(60)
+        );
+        assert_eq!(policy.reject_empty_blocks_mempool_size, Some(100));
+        assert_eq!(policy.tx_ordering, Some(TxOrderingRule::SaltedTxid));
+
+        let config_toml = format!(
+            r#"{base_toml}
+[block_policy]
+required_contract_txs = ["sbtc-deposit"]
+"#
+        );
+        assert!(GlobalConfig::load_from_str(&config_toml).is_err());
+    }
+
+    #[test]
+    fn test_private_key_sources() {
+        let sk_hex = "2de4e77aab89c0c2570bb8bb90824f5cf2a5204a975905fee450ff9dad0fcf2801";
+        let expected_addr = "SP1286C62P3TAWVQV2VM2CEGTRBQZSZ6MHMS9RW05";
+        let base_toml = r#"
+node_host = "localhost"
+endpoint = "localhost:30000"
+network = "mainnet"
+auth_password = "abcd"
+db_path = ":memory:"
+"#;
+
+        // no key, or more than one key source
+        assert!(matches!(
+            GlobalConfig::load_from_str(base_toml),
+            Err(ConfigError::InvalidConfig(_))
+        ));
+        let config_toml = format!(
+            r#"{base_toml}
+stacks_private_key = "{sk_hex}"
+stacks_private_key_command = ["echo", "{sk_hex}"]
+"#
+        );
+        assert!(matches!(
+            GlobalConfig::load_from_str(&config_toml),
+            Err(ConfigError::InvalidConfig(_))
+        ));
+
+        // a key command
+        if cfg!(unix) {
+            let config_toml = format!(
+                r#"{base_toml}
+stacks_private_key_command = ["echo", "{sk_hex}"]
+"#
+            );
+            let config = GlobalConfig::load_from_str(&config_toml).unwrap();
+            assert_eq!(config.stacks_address.to_string(), expected_addr);
+
+            let config_toml = format!(
+                r#"{base_toml}
+stacks_private_key_command = ["false"]
+"#
+            );
+            assert!(matches!(
+                GlobalConfig::load_from_str(&config_toml),
+                Err(ConfigError::BadField(..))
+            ));
+        }
+
+        // a keystore file
+        let dir = std::env::temp_dir().join(format!("signer-keystore-{}", rand::random::<u64>()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("signer.keystore");
+        let private_key = StacksPrivateKey::from_hex(sk_hex).unwrap();
+        Keystore::encrypt(&private_key, "hunter2", 10)
+            .unwrap()
+            .save(&path)
+            .unwrap();
+        let config_toml = format!(
+            r#"{base_toml}
+stacks_private_key_file = "{}"
+"#,
+            path.display()
+        );
+        let raw_config = RawConfigFile::load_from_str(&config_toml).unwrap();
+        assert_eq!(
+            raw_config
+                .load_stacks_private_key(&|| Some("hunter2".into()))
+                .unwrap(),
+            private_key
+        );
+        assert!(matches!(
+            raw_config.load_stacks_private_key(&|| Some("hunter3".into())),
+            Err(ConfigError::BadField(..))
+        ));
+        assert!(matches!(
+            raw_config.load_stacks_private_key(&|| None),
+            Err(ConfigError::InvalidConfig(_))
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -0,0 +1,299 @@
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Sources of the signer's Stacks private key other than a plaintext hex string in the config
+//! file.
+//!
+//! A keystore file holds the private key encrypted with AES-256-GCM, under a key derived from a
+//! password with PBKDF2-HMAC-SHA256.  The password is read from the
+//! `STACKS_SIGNER_KEYSTORE_PASSWORD` environment variable, and keystores are created with the
+//! `encrypt-key` command.
+//!
+//! A key command is an external program that prints the hex private key on stdout.  This is how
+//! the signer gets its key from an OS keychain (e.g. `security find-generic-password -w` on
+//! macOS, or `secret-tool lookup` on Linux), or from a cloud KMS that holds the key encrypted
+//! (e.g. a script that calls `aws kms decrypt`).
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use hmac::Hmac;
+use pbkdf2::pbkdf2;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use stacks_common::types::chainstate::StacksPrivateKey;
+use stacks_common::util::hash::{hex_bytes, to_hex};
+
+/// The environment variable holding the password of the keystore file
+pub const KEYSTORE_PASSWORD_ENV: &str = "STACKS_SIGNER_KEYSTORE_PASSWORD";
+/// The number of PBKDF2 iterations for new keystores
+pub const DEFAULT_PBKDF2_ITERATIONS: u32 = 600_000;
+
+const KEYSTORE_VERSION: u32 = 1;
+const KEYSTORE_KDF: &str = "pbkdf2-hmac-sha256";
+const KEYSTORE_CIPHER: &str = "aes-256-gcm";
+/// Binds the ciphertext to its use, so it can't be passed off as some other encrypted value
+const KEYSTORE_AAD: &[u8] = b"stacks-signer-keystore-v1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+#[derive(thiserror::Error, Debug)]
+/// An error occurred loading the signer's private key
+pub enum KeystoreError {
+    /// Error reading or writing the keystore file
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// The keystore file is not a valid keystore
+    #[error("Malformed keystore: {0}")]
+    Malformed(String),
+    /// The keystore could not be decrypted
+    #[error("Failed to decrypt keystore: wrong password or corrupted file")]
+    Decrypt,
+    /// The key command failed
+    #[error("Key command failed: {0}")]
+    Command(String),
+    /// The decrypted or fetched key is not a valid private key
+    #[error("Invalid private key: {0}")]
+    InvalidKey(String),
+}
+
+/// A Stacks private key encrypted under a password
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Keystore {
+    /// The keystore format version
+    pub version: u32,
+    /// The key derivation function
+    pub kdf: String,
+    /// The number of PBKDF2 iterations
+    pub iterations: u32,
+    /// The hex PBKDF2 salt
+    pub salt: String,
+    /// The cipher
+    pub cipher: String,
+    /// The hex AES-GCM nonce
+    pub nonce: String,
+    /// The hex ciphertext of the hex private key
+    pub ciphertext: String,
+}
+
+/// Derive a 32-byte key from `password` with PBKDF2-HMAC-SHA256
+fn pbkdf2_hmac_sha256(password: &[u8], salt: &[u8], iterations: u32) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    pbkdf2::<Hmac<Sha256>>(password, salt, iterations, &mut key)
+        .expect("HMAC accepts keys of any length");
+    key
+}
+
+impl Keystore {
+    /// Encrypt `private_key` under `password`
+    pub fn encrypt(
+        private_key: &StacksPrivateKey,
+        password: &str,
+        iterations: u32,
+    ) -> Result<Self, KeystoreError> {
+        if iterations == 0 {
+            return Err(KeystoreError::Malformed(
+                "iterations must be positive".into(),
+            ));
+        }
+        let mut salt = [0u8; SALT_LEN];
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let key = pbkdf2_hmac_sha256(password.as_bytes(), &salt, iterations);
+        let key_hex = private_key.to_hex();
+        let payload = Payload {
+            msg: key_hex.as_bytes(),
+            aad: KEYSTORE_AAD,
+        };
+        let ciphertext = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))
+            .encrypt(Nonce::from_slice(&nonce), payload)
+            .map_err(|_| KeystoreError::Malformed("failed to encrypt private key".into()))?;
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            kdf: KEYSTORE_KDF.into(),
+            iterations,
+            salt: to_hex(&salt),
+            cipher: KEYSTORE_CIPHER.into(),
+            nonce: to_hex(&nonce),
+            ciphertext: to_hex(&ciphertext),
+        })
+    }
+
+    /// Decrypt the private key with `password`
+    pub fn decrypt(&self, password: &str) -> Result<StacksPrivateKey, KeystoreError> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(KeystoreError::Malformed(format!(
+                "unsupported version {}",
+                self.version
+            )));
+        }
+        if self.kdf != KEYSTORE_KDF || self.cipher != KEYSTORE_CIPHER {
+            return Err(KeystoreError::Malformed(format!(
+                "unsupported kdf {} or cipher {}",
+                self.kdf, self.cipher
+            )));
+        }
+        if self.iterations == 0 {
+            return Err(KeystoreError::Malformed(
+                "iterations must be positive".into(),
+            ));
+        }
+        let hex_field = |name: &str, value: &str| {
+            hex_bytes(value).map_err(|_| KeystoreError::Malformed(format!("{name} is not hex")))
+        };
+        let salt = hex_field("salt", &self.salt)?;
+        let nonce = hex_field("nonce", &self.nonce)?;
+        let ciphertext = hex_field("ciphertext", &self.ciphertext)?;
+        if nonce.len() != NONCE_LEN {
+            return Err(KeystoreError::Malformed(format!(
+                "nonce must be {NONCE_LEN} bytes"
+            )));
+        }
+
+        let key = pbkdf2_hmac_sha256(password.as_bytes(), &salt, self.iterations);
+        let payload = Payload {
+            msg: &ciphertext,
+            aad: KEYSTORE_AAD,
+        };
+        let plaintext = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))
+            .decrypt(Nonce::from_slice(&nonce), payload)
+            .map_err(|_| KeystoreError::Decrypt)?;
+        let key_hex = std::str::from_utf8(&plaintext).map_err(|_| KeystoreError::Decrypt)?;
+        StacksPrivateKey::from_hex(key_hex).map_err(|e| KeystoreError::InvalidKey(e.into()))
+    }
+
+    /// Read a keystore file
+    pub fn load(path: &Path) -> Result<Self, KeystoreError> {
+        let data = fs::read_to_string(path)?;
+        serde_json::from_str(&data).map_err(|e| KeystoreError::Malformed(e.to_string()))
+    }
+
+    /// Write the keystore to a new file, readable only by its owner
+    pub fn save(&self, path: &Path) -> Result<(), KeystoreError> {
+        let data = serde_json::to_string_pretty(self)
+            .map_err(|e| KeystoreError::Malformed(e.to_string()))?;
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(path)?;
+        file.write_all(data.as_bytes())?;
+        file.sync_all()?;
+        Ok(())
+    }
+}
+
+/// Run `command` and parse the hex private key it prints on stdout.  The key is never included
+/// in errors.
+pub fn run_key_command(command: &[String]) -> Result<StacksPrivateKey, KeystoreError> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| KeystoreError::Command("the command is empty".into()))?;
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| KeystoreError::Command(format!("failed to run {program}: {e}")))?;
+    if !output.status.success() {
+        return Err(KeystoreError::Command(format!(
+            "{program} exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    let key_hex = std::str::from_utf8(&output.stdout)
+        .map_err(|_| KeystoreError::InvalidKey(format!("{program} printed invalid utf8")))?;
+    StacksPrivateKey::from_hex(key_hex.trim())
+        .map_err(|_| KeystoreError::InvalidKey(format!("{program} did not print a hex key")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY_HEX: &str = "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01";
+
+    #[test]
+    fn test_pbkdf2_hmac_sha256() {
+        // RFC 7914, section 11
+        assert_eq!(
+            to_hex(&pbkdf2_hmac_sha256(b"passwd", b"salt", 1)),
+            "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc"
+        );
+    }
+
+    #[test]
+    fn test_keystore_round_trip() {
+        let private_key = StacksPrivateKey::from_hex(KEY_HEX).unwrap();
+        let keystore = Keystore::encrypt(&private_key, "correct horse", 10).unwrap();
+        assert!(!keystore.ciphertext.contains(KEY_HEX));
+        assert_eq!(keystore.decrypt("correct horse").unwrap(), private_key);
+        assert!(matches!(
+            keystore.decrypt("wrong horse"),
+            Err(KeystoreError::Decrypt)
+        ));
+
+        let mut tampered = keystore.clone();
+        tampered.iterations = 11;
+        assert!(matches!(
+            tampered.decrypt("correct horse"),
+            Err(KeystoreError::Decrypt)
+        ));
+        let mut tampered = keystore.clone();
+        tampered.version = 2;
+        assert!(matches!(
+            tampered.decrypt("correct horse"),
+            Err(KeystoreError::Malformed(_))
+        ));
+
+        // saved keystores are never overwritten
+        let dir = std::env::temp_dir().join(format!("keystore-test-{}", rand::random::<u64>()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("signer.keystore");
+        keystore.save(&path).unwrap();
+        assert_eq!(Keystore::load(&path).unwrap(), keystore);
+        assert!(keystore.save(&path).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_key_command() {
+        let private_key = StacksPrivateKey::from_hex(KEY_HEX).unwrap();
+        assert_eq!(
+            run_key_command(&["echo".into(), KEY_HEX.into()]).unwrap(),
+            private_key
+        );
+        assert!(matches!(
+            run_key_command(&[]),
+            Err(KeystoreError::Command(_))
+        ));
+        assert!(matches!(
+            run_key_command(&["false".into()]),
+            Err(KeystoreError::Command(_))
+        ));
+        let err = run_key_command(&["echo".into(), "not-a-key".into()]).unwrap_err();
+        assert!(matches!(err, KeystoreError::InvalidKey(_)));
+        assert!(!err.to_string().contains("not-a-key"));
+    }
+}
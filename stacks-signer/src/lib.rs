@@ -29,10 +29,14 @@ pub mod cli;
 pub mod client;
 /// The configuration module for the signer
 pub mod config;
+/// Encrypted and external storage of the signer's private key
+pub mod keystore;
 /// The signer monitor for observing signer behaviours in the network
 pub mod monitor_signers;
 /// The monitoring server for the signer
 pub mod monitoring;
+/// Operator-configurable rules that blocks must follow before the signer signs them
+pub mod policy;
 /// The primary runloop for the signer
 pub mod runloop;
 /// The signer state module
@@ -30,21 +30,28 @@ use std::io::{self, Write};
 
 use blockstack_lib::util_lib::signed_structured_data::pox4::make_pox_4_signer_key_signature;
 use clap::Parser;
-use clarity::types::chainstate::StacksPublicKey;
+use clarity::types::chainstate::{StacksPrivateKey, StacksPublicKey};
 use clarity::util::sleep_ms;
 use libsigner::{SignerSession, VERSION_STRING};
 use libstackerdb::StackerDBChunkData;
 use slog::{slog_debug, slog_error};
+use stacks_common::util::get_epoch_time_secs;
 use stacks_common::util::hash::to_hex;
 use stacks_common::util::secp256k1::MessageSignature;
 use stacks_common::{debug, error};
 use stacks_signer::cli::{
-    Cli, Command, GenerateStackingSignatureArgs, GenerateVoteArgs, GetChunkArgs,
-    GetLatestChunkArgs, MonitorSignersArgs, PutChunkArgs, RunSignerArgs, StackerDBArgs,
+    ChunkRejectionsArgs, Cli, Command, EncryptKeyArgs, GenerateStackingSignatureArgs,
+    GenerateVoteArgs, GetChunkArgs, GetLatestChunkArgs, MonitorSignersArgs, PutChunkArgs,
+    RunSignerArgs, StackerDBArgs, StackerDBCommand, StackerDBGetArgs, StackerDBPutArgs,
     VerifyVoteArgs,
 };
+use stacks_signer::client::{
+    get_latest_chunk_with_retry, list_slots_with_retry, next_slot_version, put_chunk_with_retry,
+};
 use stacks_signer::config::GlobalConfig;
+use stacks_signer::keystore::{Keystore, DEFAULT_PBKDF2_ITERATIONS, KEYSTORE_PASSWORD_ENV};
 use stacks_signer::monitor_signers::SignerMonitor;
+use stacks_signer::signerdb::SignerDb;
 use stacks_signer::utils::stackerdb_session;
 use stacks_signer::v0::SpawnedSigner;
 use tracing_subscriber::prelude::*;
@@ -97,6 +104,47 @@ fn handle_put_chunk(args: PutChunkArgs) {
     println!("{}", serde_json::to_string(&chunk_ack).unwrap());
 }
 
+fn handle_stackerdb_get(args: StackerDBGetArgs) {
+    debug!("Getting stackerdb slot...");
+    let mut session = stackerdb_session(&args.db_args.host, args.db_args.contract);
+    let metadata = list_slots_with_retry(&mut session)
+        .unwrap()
+        .into_iter()
+        .find(|slot| slot.slot_id == args.slot_id);
+    let data = get_latest_chunk_with_retry(&mut session, args.slot_id).unwrap();
+    let slot = serde_json::json!({
+        "slot_id": args.slot_id,
+        "metadata": metadata,
+        "data": data.map(|data| to_hex(&data)),
+    });
+    println!("{}", serde_json::to_string_pretty(&slot).unwrap());
+}
+
+fn handle_stackerdb_put(args: StackerDBPutArgs) {
+    debug!("Putting stackerdb chunk...");
+    let mut session = stackerdb_session(&args.db_args.host, args.db_args.contract);
+    let slot_version = match args.slot_version {
+        Some(slot_version) => slot_version,
+        None => next_slot_version(&mut session, args.slot_id).unwrap(),
+    };
+    let chunk_ack = put_chunk_with_retry(
+        &mut session,
+        &args.private_key,
+        args.slot_id,
+        slot_version,
+        args.data,
+    )
+    .unwrap();
+    println!("{}", serde_json::to_string_pretty(&chunk_ack).unwrap());
+}
+
+fn handle_stackerdb_list_slots(args: StackerDBArgs) {
+    debug!("Listing stackerdb slots...");
+    let mut session = stackerdb_session(&args.host, args.contract);
+    let slots = list_slots_with_retry(&mut session).unwrap();
+    println!("{}", serde_json::to_string_pretty(&slots).unwrap());
+}
+
 fn handle_run(args: RunSignerArgs) {
     debug!("Running signer...");
     let config = GlobalConfig::try_from(&args.config).unwrap();
@@ -198,6 +246,31 @@ fn handle_monitor_signers(args: MonitorSignersArgs) {
     }
 }
 
+fn handle_chunk_rejections(args: ChunkRejectionsArgs) {
+    let config = GlobalConfig::try_from(&args.config).unwrap();
+    let signer_db = SignerDb::new(&config.db_path).unwrap();
+    let since = get_epoch_time_secs().saturating_sub(args.since);
+    let summary = signer_db.get_chunk_rejection_summary(since).unwrap();
+    println!("{}", serde_json::to_string_pretty(&summary).unwrap());
+}
+
+fn handle_encrypt_key(args: EncryptKeyArgs) {
+    let password = std::env::var(KEYSTORE_PASSWORD_ENV)
+        .unwrap_or_else(|_| panic!("{KEYSTORE_PASSWORD_ENV} must be set"));
+    let mut key_hex = String::new();
+    io::stdin().read_line(&mut key_hex).unwrap();
+    let private_key =
+        StacksPrivateKey::from_hex(key_hex.trim()).expect("stdin is not a hex private key");
+    let iterations = args.iterations.unwrap_or(DEFAULT_PBKDF2_ITERATIONS);
+    let keystore = Keystore::encrypt(&private_key, &password, iterations).unwrap();
+    keystore.save(&args.output).unwrap();
+    println!(
+        "Wrote keystore for public key {} to {}",
+        to_hex(&StacksPublicKey::from_private(&private_key).to_bytes_compressed()),
+        args.output.display()
+    );
+}
+
 fn main() {
     let cli = Cli::parse();
 
@@ -237,6 +310,21 @@ fn main() {
         Command::MonitorSigners(args) => {
             handle_monitor_signers(args);
         }
+        Command::ChunkRejections(args) => {
+            handle_chunk_rejections(args);
+        }
+        Command::EncryptKey(args) => {
+            handle_encrypt_key(args);
+        }
+        Command::Stackerdb(StackerDBCommand::Get(args)) => {
+            handle_stackerdb_get(args);
+        }
+        Command::Stackerdb(StackerDBCommand::Put(args)) => {
+            handle_stackerdb_put(args);
+        }
+        Command::Stackerdb(StackerDBCommand::ListSlots(args)) => {
+            handle_stackerdb_list_slots(args);
+        }
     }
 }
 
@@ -14,6 +14,8 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::HashMap;
+#[cfg(feature = "monitor_subscription")]
+use std::time::{Duration, Instant};
 
 use clarity::codec::read_next;
 use clarity::types::chainstate::{StacksAddress, StacksPrivateKey, StacksPublicKey};
@@ -25,6 +27,8 @@ use slog::{slog_info, slog_warn};
 use stacks_common::{info, warn};
 
 use crate::cli::MonitorSignersArgs;
+#[cfg(feature = "monitor_subscription")]
+use crate::client::StackerDBSubscription;
 use crate::client::{ClientError, SignerSlotID, StacksClient};
 use crate::utils::stackerdb_session;
 
@@ -219,6 +223,50 @@ impl SignerMonitor {
         );
     }
 
+    #[cfg(feature = "monitor_subscription")]
+    /// Until `interval` has passed, apply the block responses the node pushes for the current
+    /// signer set to `chunks`, the latest chunk of each of the signers' slots
+    fn receive_pushed_chunks(
+        &self,
+        subscription: &StackerDBSubscription,
+        interval: Duration,
+        chunks: &mut [Option<Vec<u8>>],
+    ) {
+        let reward_cycle = self
+            .cycle_state
+            .reward_cycle
+            .expect("BUG: reward cycle not set");
+        let signer_set =
+            u32::try_from(reward_cycle % 2).expect("FATAL: reward cycle % 2 exceeds u32::MAX");
+        let deadline = Instant::now() + interval;
+        while let Some(timeout) = deadline.checked_duration_since(Instant::now()) {
+            let signer_chunks = match subscription.next_chunks(timeout) {
+                Ok(Some(signer_chunks)) => signer_chunks,
+                Ok(None) => continue,
+                Err(e) => {
+                    warn!("Failed to receive pushed signer messages: {e}");
+                    continue;
+                }
+            };
+            if signer_chunks.signer_set != signer_set
+                || signer_chunks.msg_id != MessageSlotID::BlockResponse.to_u32()
+            {
+                continue;
+            }
+            for chunk in signer_chunks.chunks {
+                let Some(index) = self
+                    .cycle_state
+                    .slot_ids
+                    .iter()
+                    .position(|slot_id| *slot_id == chunk.slot_id)
+                else {
+                    continue;
+                };
+                chunks[index] = Some(chunk.data);
+            }
+        }
+    }
+
     /// Start monitoring the signers stackerdb slots for expected new messages
     pub fn start(&mut self) -> Result<(), ClientError> {
         self.refresh_state()?;
@@ -235,6 +283,21 @@ impl SignerMonitor {
             self.args.interval, self.args.max_age
         );
         let mut session = stackerdb_session(&self.args.host, contract);
+        #[cfg(feature = "monitor_subscription")]
+        let subscription = self
+            .args
+            .subscribe
+            .map(StackerDBSubscription::bind)
+            .transpose()?;
+        #[cfg(feature = "monitor_subscription")]
+        if let Some(subscription) = &subscription {
+            info!(
+                "Receiving signer messages pushed to {}. The latest messages are only queried when the reward cycle changes.",
+                subscription.local_addr()
+            );
+        }
+        // When subscribed, the latest chunks as updated by the node's pushes
+        let mut pushed_chunks = None;
         info!("Confirming messages for {nmb_signers} registered signers";
             "signer_addresses" => self.cycle_state.signers_addresses.values().map(|addr| format!("{addr}")).collect::<Vec<_>>().join(", ")
         );
@@ -260,12 +323,18 @@ impl SignerMonitor {
                 // Clear the last messages and signer last update times.
                 last_messages.clear();
                 last_updates.clear();
+                pushed_chunks = None;
             }
-            let new_messages: Vec<_> = session
-                .get_latest_chunks(&self.cycle_state.slot_ids)?
-                .into_iter()
+            let chunks = match pushed_chunks.take() {
+                Some(chunks) => chunks,
+                None => session.get_latest_chunks(&self.cycle_state.slot_ids)?,
+            };
+            let new_messages: Vec<_> = chunks
+                .iter()
                 .map(|chunk_opt| {
-                    chunk_opt.and_then(|data| read_next::<SignerMessage, _>(&mut &data[..]).ok())
+                    chunk_opt
+                        .as_ref()
+                        .and_then(|data| read_next::<SignerMessage, _>(&mut &data[..]).ok())
                 })
                 .collect();
 
@@ -325,6 +394,14 @@ impl SignerMonitor {
                 self.print_stale_signers(&stale_signers);
                 self.print_unexpected_messages(&unexpected_messages);
             }
+            #[cfg(feature = "monitor_subscription")]
+            if let Some(subscription) = &subscription {
+                let mut chunks = chunks;
+                let interval = Duration::from_secs(self.args.interval);
+                self.receive_pushed_chunks(subscription, interval, &mut chunks);
+                pushed_chunks = Some(chunks);
+                continue;
+            }
             sleep_ms(interval_ms);
         }
     }
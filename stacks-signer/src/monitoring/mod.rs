@@ -14,6 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::time::Duration;
+
 #[cfg(feature = "monitoring_prom")]
 use ::prometheus::HistogramTimer;
 #[cfg(feature = "monitoring_prom")]
@@ -78,6 +80,70 @@ pub fn increment_block_proposals_received() {
     prometheus::BLOCK_PROPOSALS_RECEIVED.inc();
 }
 
+/// Increment the number of chunks rejected by the node's StackerDB, by error code
+#[allow(unused_variables)]
+pub fn increment_stackerdb_chunk_rejections(code: &str) {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::STACKERDB_CHUNK_REJECTIONS
+        .with_label_values(&[code])
+        .inc();
+}
+
+/// Increment the number of chunks sent to the node's StackerDB
+#[allow(unused_variables)]
+pub fn increment_stackerdb_chunks_sent(accepted: bool) {
+    #[cfg(feature = "monitoring_prom")]
+    {
+        let label_value = if accepted { "accepted" } else { "rejected" };
+        prometheus::STACKERDB_CHUNKS_SENT
+            .with_label_values(&[label_value])
+            .inc();
+    }
+}
+
+/// Increment the number of chunks rejected by the node's StackerDB because the slot already
+/// had a newer version
+#[allow(unused_variables)]
+pub fn increment_stackerdb_version_conflicts() {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::STACKERDB_VERSION_CONFLICTS.inc();
+}
+
+/// Increment the number of messages not sent because the StackerDB slot already held them
+#[allow(unused_variables)]
+pub fn increment_stackerdb_messages_deduplicated() {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::STACKERDB_MESSAGES_DEDUPLICATED.inc();
+}
+
+/// Increment the number of chunk writes delayed by the StackerDB write rate limit
+#[allow(unused_variables)]
+pub fn increment_stackerdb_writes_rate_limited() {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::STACKERDB_WRITES_RATE_LIMITED.inc();
+}
+
+/// Increment the number of block proposals that the signer sent its own decision on
+#[allow(unused_variables)]
+pub fn increment_signing_rounds_participated() {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::SIGNING_ROUNDS_PARTICIPATED.inc();
+}
+
+/// Increment the number of failed requests to the Stacks node
+#[allow(unused_variables)]
+pub fn increment_node_rpc_errors() {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::NODE_RPC_ERRORS.inc();
+}
+
+/// Record how long the node took to respond to a block proposal validation request
+#[allow(unused_variables)]
+pub fn observe_block_validation_latency(latency: Duration) {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::BLOCK_VALIDATION_LATENCIES_HISTOGRAM.observe(latency.as_secs_f64());
+}
+
 /// Update the stx balance of the signer
 #[allow(unused_variables)]
 pub fn update_signer_stx_balance(balance: i64) {
@@ -110,6 +176,21 @@ pub fn new_rpc_call_timer(full_path: &str, origin: &str) -> HistogramTimer {
     histogram.start_timer()
 }
 
+/// Start a new timer of a StackerDB request.  `operation` is e.g. `put_chunk`, and a request
+/// includes its retries.
+#[cfg(feature = "monitoring_prom")]
+pub fn new_stackerdb_request_timer(operation: &str) -> HistogramTimer {
+    prometheus::STACKERDB_REQUEST_LATENCIES_HISTOGRAM
+        .with_label_values(&[operation])
+        .start_timer()
+}
+
+/// Stop and record the no-op timer.
+#[cfg(not(feature = "monitoring_prom"))]
+pub fn new_stackerdb_request_timer(_operation: &str) -> NoOpTimer {
+    NoOpTimer
+}
+
 /// NoOp timer uses for monitoring when the monitoring feature is not enabled.
 pub struct NoOpTimer;
 impl NoOpTimer {
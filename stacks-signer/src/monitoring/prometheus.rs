@@ -16,9 +16,9 @@
 
 use lazy_static::lazy_static;
 use prometheus::{
-    gather, histogram_opts, opts, register_histogram_vec, register_int_counter,
-    register_int_counter_vec, register_int_gauge, Encoder, HistogramVec, IntCounter, IntCounterVec,
-    IntGauge, TextEncoder,
+    gather, histogram_opts, opts, register_histogram, register_histogram_vec, register_int_counter,
+    register_int_counter_vec, register_int_gauge, Encoder, Histogram, HistogramVec, IntCounter,
+    IntCounterVec, IntGauge, TextEncoder,
 };
 
 lazy_static! {
@@ -44,6 +44,43 @@ lazy_static! {
         "The number of block proposals received by the signer"
     ))
     .unwrap();
+    pub static ref STACKERDB_CHUNK_REJECTIONS: IntCounterVec = register_int_counter_vec!(
+        "stacks_signer_stackerdb_chunk_rejections",
+        "The number of chunks rejected by the node's StackerDB. `code` is the name of the StackerDB error code",
+        &["code"]
+    )
+    .unwrap();
+    pub static ref STACKERDB_CHUNKS_SENT: IntCounterVec = register_int_counter_vec!(
+        "stacks_signer_stackerdb_chunks_sent",
+        "The number of chunks sent to the node's StackerDB. `result` is either 'accepted' or 'rejected'",
+        &["result"]
+    )
+    .unwrap();
+    pub static ref STACKERDB_VERSION_CONFLICTS: IntCounter = register_int_counter!(opts!(
+        "stacks_signer_stackerdb_version_conflicts",
+        "The number of chunks rejected by the node's StackerDB because the slot already had a newer version"
+    ))
+    .unwrap();
+    pub static ref STACKERDB_MESSAGES_DEDUPLICATED: IntCounter = register_int_counter!(opts!(
+        "stacks_signer_stackerdb_messages_deduplicated",
+        "The number of messages not sent because the StackerDB slot already held them"
+    ))
+    .unwrap();
+    pub static ref STACKERDB_WRITES_RATE_LIMITED: IntCounter = register_int_counter!(opts!(
+        "stacks_signer_stackerdb_writes_rate_limited",
+        "The number of chunk writes delayed by the StackerDB write rate limit"
+    ))
+    .unwrap();
+    pub static ref SIGNING_ROUNDS_PARTICIPATED: IntCounter = register_int_counter!(opts!(
+        "stacks_signer_signing_rounds_participated",
+        "The number of block proposals that the signer sent its own decision on"
+    ))
+    .unwrap();
+    pub static ref NODE_RPC_ERRORS: IntCounter = register_int_counter!(opts!(
+        "stacks_signer_node_rpc_errors",
+        "The number of failed requests to the Stacks node, including those that were then retried"
+    ))
+    .unwrap();
     pub static ref CURRENT_REWARD_CYCLE: IntGauge = register_int_gauge!(opts!(
         "stacks_signer_current_reward_cycle",
         "The current reward cycle"
@@ -62,6 +99,15 @@ lazy_static! {
         "Time (seconds) measuring round-trip RPC call latency to the Stacks node"
         // Will use DEFAULT_BUCKETS = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0] by default
     ), &["path"]).unwrap();
+    pub static ref STACKERDB_REQUEST_LATENCIES_HISTOGRAM: HistogramVec = register_histogram_vec!(histogram_opts!(
+        "stacks_signer_stackerdb_request_latencies_histogram",
+        "Time (seconds) measuring StackerDB request latency to the Stacks node, including retries. `operation` is 'put_chunk' or 'get_latest_chunks'"
+    ), &["operation"]).unwrap();
+    pub static ref BLOCK_VALIDATION_LATENCIES_HISTOGRAM: Histogram = register_histogram!(histogram_opts!(
+        "stacks_signer_block_validation_latencies_histogram",
+        "Time (seconds) between submitting a block proposal to the Stacks node for validation and receiving its response",
+        vec![0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 20.0, 30.0, 60.0, 120.0]
+    )).unwrap();
 }
 
 pub fn gather_metrics_string() -> String {
@@ -0,0 +1,510 @@
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::time::Duration;
+
+use blockstack_lib::burnchains::Txid;
+use blockstack_lib::chainstate::nakamoto::ordering::TxOrderingRule;
+use blockstack_lib::chainstate::nakamoto::NakamotoBlock;
+use blockstack_lib::chainstate::stacks::TransactionPayload;
+use blockstack_lib::net::api::getmempooltxs::MAX_MEMPOOL_TXS_PAGE_LIMIT;
+use clarity::vm::types::QualifiedContractIdentifier;
+use libsigner::v0::messages::PolicyRule;
+use serde::Deserialize;
+use stacks_common::types::chainstate::StacksAddress;
+use stacks_common::types::Address;
+
+use crate::client::{ClientError, StacksClient};
+use crate::config::ConfigError;
+
+/// The `[block_policy]` table of the config file
+#[derive(Deserialize, Debug, Default)]
+pub struct RawBlockPolicy {
+    /// The most transactions calling a contract that a block may contain
+    pub max_contract_txs: Option<Vec<RawContractTxLimit>>,
+    /// Contracts (e.g. sBTC's) whose pending calls blocks must include once they have waited
+    /// `required_contract_txs_max_pending_secs` in the mempool
+    pub required_contract_txs: Option<Vec<String>>,
+    /// How long (in secs) a call to one of the `required_contract_txs` contracts may be left
+    /// out of blocks
+    pub required_contract_txs_max_pending_secs: Option<u64>,
+    /// Reject empty blocks while the mempool holds at least this many transactions
+    pub reject_empty_blocks_mempool_size: Option<u64>,
+    /// The name of the transaction-ordering rule that blocks must follow
+    pub tx_ordering: Option<String>,
+}
+
+/// An entry of `block_policy.max_contract_txs`
+#[derive(Deserialize, Debug)]
+pub struct RawContractTxLimit {
+    /// The contract
+    pub contract: String,
+    /// The most transactions calling it that a block may contain
+    pub max_txs: u64,
+}
+
+/// The default for `block_policy.required_contract_txs_max_pending_secs`
+const DEFAULT_REQUIRED_CONTRACT_TXS_MAX_PENDING_SECS: u64 = 120;
+
+/// The rules, besides being valid, that the operator requires of blocks before the signer
+/// signs them. A signer without a `[block_policy]` has no rules.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BlockPolicy {
+    /// The most transactions calling each contract that a block may contain
+    pub max_contract_txs: Vec<(QualifiedContractIdentifier, u64)>,
+    /// Contracts whose pending calls blocks must include once they have waited
+    /// `required_contract_txs_max_pending` in the mempool
+    pub required_contract_txs: Vec<QualifiedContractIdentifier>,
+    /// How long a call to one of the `required_contract_txs` contracts may be left out of blocks
+    pub required_contract_txs_max_pending: Duration,
+    /// Reject empty blocks while the mempool holds at least this many transactions
+    pub reject_empty_blocks_mempool_size: Option<u64>,
+    /// The transaction-ordering rule that every miner's blocks must follow, since miners don't
+    /// advertise one (see `miner.tx_ordering_commitment`)
+    pub tx_ordering: Option<TxOrderingRule>,
+}
+
+impl TryFrom<RawBlockPolicy> for BlockPolicy {
+    type Error = ConfigError;
+
+    fn try_from(raw: RawBlockPolicy) -> Result<Self, Self::Error> {
+        let parse_contract = |field: &str, contract: &str| {
+            QualifiedContractIdentifier::parse(contract).map_err(|_| {
+                ConfigError::BadField(format!("block_policy.{field}"), contract.to_string())
+            })
+        };
+        let max_contract_txs = raw
+            .max_contract_txs
+            .unwrap_or_default()
+            .iter()
+            .map(|limit| {
+                Ok((
+                    parse_contract("max_contract_txs", &limit.contract)?,
+                    limit.max_txs,
+                ))
+            })
+            .collect::<Result<_, ConfigError>>()?;
+        let required_contract_txs = raw
+            .required_contract_txs
+            .unwrap_or_default()
+            .iter()
+            .map(|contract| parse_contract("required_contract_txs", contract))
+            .collect::<Result<_, ConfigError>>()?;
+        if let Some(mempool_size) = raw.reject_empty_blocks_mempool_size {
+            // the signer counts the mempool's transactions with a single page
+            if mempool_size == 0 || mempool_size > MAX_MEMPOOL_TXS_PAGE_LIMIT {
+                return Err(ConfigError::BadField(
+                    "block_policy.reject_empty_blocks_mempool_size".to_string(),
+                    mempool_size.to_string(),
+                ));
+            }
+        }
+        let tx_ordering = raw
+            .tx_ordering
+            .map(|name| {
+                TxOrderingRule::from_name(&name).ok_or_else(|| {
+                    ConfigError::BadField("block_policy.tx_ordering".to_string(), name)
+                })
+            })
+            .transpose()?;
+        Ok(Self {
+            max_contract_txs,
+            required_contract_txs,
+            required_contract_txs_max_pending: Duration::from_secs(
+                raw.required_contract_txs_max_pending_secs
+                    .unwrap_or(DEFAULT_REQUIRED_CONTRACT_TXS_MAX_PENDING_SECS),
+            ),
+            reject_empty_blocks_mempool_size: raw.reject_empty_blocks_mempool_size,
+            tx_ordering,
+        })
+    }
+}
+
+/// A rule of the block policy that a block breaks
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyViolation {
+    /// The rule
+    pub rule: PolicyRule,
+    /// How the block breaks it
+    pub reason: String,
+}
+
+impl Display for PolicyViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.reason)
+    }
+}
+
+/// What the block policy needs to know about the node's mempool
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MempoolView {
+    /// The mineable calls to the `required_contract_txs` contracts that have been pending for
+    /// longer than allowed
+    pub overdue_txs: Vec<(QualifiedContractIdentifier, Txid)>,
+    /// How many transactions the mempool holds, counted up to
+    /// `reject_empty_blocks_mempool_size`
+    pub num_txs: u64,
+}
+
+impl BlockPolicy {
+    /// Whether any rules are configured
+    pub fn is_empty(&self) -> bool {
+        self.max_contract_txs.is_empty()
+            && self.required_contract_txs.is_empty()
+            && self.reject_empty_blocks_mempool_size.is_none()
+            && self.tx_ordering.is_none()
+    }
+
+    /// Check `block` against the rules, asking the node about its mempool if the rules depend
+    /// on it
+    pub fn evaluate(
+        &self,
+        stacks_client: &StacksClient,
+        block: &NakamotoBlock,
+        now_secs: u64,
+    ) -> Result<Option<PolicyViolation>, ClientError> {
+        if self.is_empty() {
+            return Ok(None);
+        }
+        let mempool = self.fetch_mempool_view(stacks_client, block, now_secs)?;
+        Ok(self.check_block(block, &mempool))
+    }
+
+    /// Check `block` against the rules, given the state of the mempool
+    pub fn check_block(
+        &self,
+        block: &NakamotoBlock,
+        mempool: &MempoolView,
+    ) -> Option<PolicyViolation> {
+        let mut contract_calls: HashMap<QualifiedContractIdentifier, u64> = HashMap::new();
+        for tx in block.txs.iter() {
+            if let TransactionPayload::ContractCall(call) = &tx.payload {
+                *contract_calls
+                    .entry(call.to_clarity_contract_id())
+                    .or_default() += 1;
+            }
+        }
+        for (contract, max_txs) in self.max_contract_txs.iter() {
+            let num_txs = contract_calls.get(contract).copied().unwrap_or(0);
+            if num_txs > *max_txs {
+                return Some(PolicyViolation {
+                    rule: PolicyRule::MaxContractTxs,
+                    reason: format!(
+                        "Block contains {num_txs} transactions calling {contract}, more than the allowed {max_txs}"
+                    ),
+                });
+            }
+        }
+
+        let missing: Vec<_> = mempool
+            .overdue_txs
+            .iter()
+            .filter(|(_, txid)| !block.txs.iter().any(|tx| &tx.txid() == txid))
+            .collect();
+        if let Some((contract, txid)) = missing.first() {
+            return Some(PolicyViolation {
+                rule: PolicyRule::RequiredContractTxs,
+                reason: format!(
+                    "Block leaves out {} transactions calling required contracts that have been pending for over {}s, including {txid} calling {contract}",
+                    missing.len(),
+                    self.required_contract_txs_max_pending.as_secs()
+                ),
+            });
+        }
+
+        if let Some(rule) = self.tx_ordering {
+            if let Err(e) = block.check_tx_ordering(rule) {
+                return Some(PolicyViolation {
+                    rule: PolicyRule::TxOrdering,
+                    reason: e.to_string(),
+                });
+            }
+        }
+
+        if let Some(mempool_size) = self.reject_empty_blocks_mempool_size {
+            if Self::is_empty_block(block) && mempool.num_txs >= mempool_size {
+                return Some(PolicyViolation {
+                    rule: PolicyRule::EmptyBlockUnderLoad,
+                    reason: format!(
+                        "Block is empty while the mempool holds at least {mempool_size} transactions"
+                    ),
+                });
+            }
+        }
+        None
+    }
+
+    /// Whether `block` contains nothing but tenure changes and coinbases
+    fn is_empty_block(block: &NakamotoBlock) -> bool {
+        block.txs.iter().all(|tx| {
+            matches!(
+                tx.payload,
+                TransactionPayload::TenureChange(..) | TransactionPayload::Coinbase(..)
+            )
+        })
+    }
+
+    /// Ask the node about the parts of its mempool that the rules depend on
+    fn fetch_mempool_view(
+        &self,
+        stacks_client: &StacksClient,
+        block: &NakamotoBlock,
+        now_secs: u64,
+    ) -> Result<MempoolView, ClientError> {
+        let mut mempool = MempoolView::default();
+        let mut account_nonces: HashMap<String, u64> = HashMap::new();
+        for contract in self.required_contract_txs.iter() {
+            let pending =
+                stacks_client.get_mempool_txs(Some(contract), MAX_MEMPOOL_TXS_PAGE_LIMIT)?;
+            for entry in pending.txs {
+                let pending_secs = now_secs.saturating_sub(entry.accept_time);
+                if pending_secs <= self.required_contract_txs_max_pending.as_secs() {
+                    continue;
+                }
+                // The mempool keeps transactions after they are mined, and a transaction
+                // can only be mined once its predecessors are, so only the origin account's
+                // next transaction is overdue
+                let nonce = match account_nonces.get(&entry.origin_address) {
+                    Some(nonce) => *nonce,
+                    None => {
+                        let Some(address) = StacksAddress::from_string(&entry.origin_address)
+                        else {
+                            continue;
+                        };
+                        let nonce = stacks_client.get_account_entry(&address)?.nonce;
+                        account_nonces.insert(entry.origin_address.clone(), nonce);
+                        nonce
+                    }
+                };
+                if entry.origin_nonce == nonce {
+                    mempool.overdue_txs.push((contract.clone(), entry.txid));
+                }
+            }
+        }
+        if let Some(mempool_size) = self.reject_empty_blocks_mempool_size {
+            // The mempool only matters if the block is empty
+            if Self::is_empty_block(block) {
+                mempool.num_txs = stacks_client
+                    .get_mempool_txs(None, mempool_size)?
+                    .txs
+                    .len()
+                    .try_into()
+                    .unwrap_or(u64::MAX);
+            }
+        }
+        Ok(mempool)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use blockstack_lib::chainstate::nakamoto::NakamotoBlockHeader;
+    use blockstack_lib::chainstate::stacks::{
+        CoinbasePayload, StacksTransaction, TransactionAnchorMode, TransactionAuth,
+        TransactionContractCall, TransactionPostConditionMode, TransactionSpendingCondition,
+        TransactionVersion,
+    };
+    use clarity::vm::{ClarityName, ContractName};
+    use stacks_common::types::chainstate::{StacksPrivateKey, StacksPublicKey};
+
+    use super::*;
+
+    fn sbtc_contract() -> QualifiedContractIdentifier {
+        QualifiedContractIdentifier::parse("ST000000000000000000002AMW42H.sbtc-deposit").unwrap()
+    }
+
+    fn make_tx(payload: TransactionPayload, nonce: u64) -> StacksTransaction {
+        let private_key = StacksPrivateKey::new();
+        let mut spending_condition = TransactionSpendingCondition::new_singlesig_p2pkh(
+            StacksPublicKey::from_private(&private_key),
+        )
+        .unwrap();
+        spending_condition.set_nonce(nonce);
+        let mut tx = StacksTransaction::new(
+            TransactionVersion::Testnet,
+            TransactionAuth::Standard(spending_condition),
+            payload,
+        );
+        tx.anchor_mode = TransactionAnchorMode::Any;
+        tx.post_condition_mode = TransactionPostConditionMode::Allow;
+        tx
+    }
+
+    fn contract_call(contract: &QualifiedContractIdentifier, nonce: u64) -> StacksTransaction {
+        make_tx(
+            TransactionPayload::ContractCall(TransactionContractCall {
+                address: StacksAddress::from(contract.issuer.clone()),
+                contract_name: ContractName::from(contract.name.as_str()),
+                function_name: ClarityName::from("complete-deposit"),
+                function_args: vec![],
+            }),
+            nonce,
+        )
+    }
+
+    fn coinbase() -> StacksTransaction {
+        make_tx(
+            TransactionPayload::Coinbase(CoinbasePayload([0u8; 32]), None, None),
+            0,
+        )
+    }
+
+    fn make_block(txs: Vec<StacksTransaction>) -> NakamotoBlock {
+        NakamotoBlock {
+            header: NakamotoBlockHeader::empty(),
+            txs,
+        }
+    }
+
+    #[test]
+    fn parse_block_policy() {
+        assert_eq!(
+            BlockPolicy::try_from(RawBlockPolicy::default()).unwrap(),
+            BlockPolicy {
+                required_contract_txs_max_pending: Duration::from_secs(
+                    DEFAULT_REQUIRED_CONTRACT_TXS_MAX_PENDING_SECS
+                ),
+                ..BlockPolicy::default()
+            }
+        );
+
+        let raw = RawBlockPolicy {
+            max_contract_txs: Some(vec![RawContractTxLimit {
+                contract: "not a contract".into(),
+                max_txs: 1,
+            }]),
+            ..RawBlockPolicy::default()
+        };
+        assert!(matches!(
+            BlockPolicy::try_from(raw),
+            Err(ConfigError::BadField(field, _)) if field == "block_policy.max_contract_txs"
+        ));
+
+        let raw = RawBlockPolicy {
+            reject_empty_blocks_mempool_size: Some(MAX_MEMPOOL_TXS_PAGE_LIMIT + 1),
+            ..RawBlockPolicy::default()
+        };
+        assert!(BlockPolicy::try_from(raw).is_err());
+    }
+
+    #[test]
+    fn max_contract_txs_rule() {
+        let contract = sbtc_contract();
+        let policy = BlockPolicy {
+            max_contract_txs: vec![(contract.clone(), 2)],
+            ..BlockPolicy::default()
+        };
+        let mempool = MempoolView::default();
+        let block = make_block(vec![
+            contract_call(&contract, 0),
+            contract_call(&contract, 0),
+        ]);
+        assert_eq!(policy.check_block(&block, &mempool), None);
+
+        let block = make_block(vec![
+            contract_call(&contract, 0),
+            contract_call(&contract, 0),
+            contract_call(&contract, 0),
+        ]);
+        let violation = policy.check_block(&block, &mempool).unwrap();
+        assert_eq!(violation.rule, PolicyRule::MaxContractTxs);
+    }
+
+    #[test]
+    fn required_contract_txs_rule() {
+        let contract = sbtc_contract();
+        let policy = BlockPolicy {
+            required_contract_txs: vec![contract.clone()],
+            ..BlockPolicy::default()
+        };
+        let overdue_tx = contract_call(&contract, 3);
+        let mempool = MempoolView {
+            overdue_txs: vec![(contract.clone(), overdue_tx.txid())],
+            num_txs: 0,
+        };
+        assert_eq!(
+            policy.check_block(&make_block(vec![coinbase(), overdue_tx]), &mempool),
+            None
+        );
+        let violation = policy
+            .check_block(
+                &make_block(vec![coinbase(), contract_call(&contract, 4)]),
+                &mempool,
+            )
+            .unwrap();
+        assert_eq!(violation.rule, PolicyRule::RequiredContractTxs);
+        assert_eq!(
+            policy.check_block(&make_block(vec![coinbase()]), &MempoolView::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn empty_block_under_load_rule() {
+        let policy = BlockPolicy {
+            reject_empty_blocks_mempool_size: Some(10),
+            ..BlockPolicy::default()
+        };
+        let quiet = MempoolView {
+            overdue_txs: vec![],
+            num_txs: 9,
+        };
+        let busy = MempoolView {
+            overdue_txs: vec![],
+            num_txs: 10,
+        };
+        let empty_block = make_block(vec![coinbase()]);
+        assert_eq!(policy.check_block(&empty_block, &quiet), None);
+        let violation = policy.check_block(&empty_block, &busy).unwrap();
+        assert_eq!(violation.rule, PolicyRule::EmptyBlockUnderLoad);
+
+        let block = make_block(vec![coinbase(), contract_call(&sbtc_contract(), 0)]);
+        assert_eq!(policy.check_block(&block, &busy), None);
+    }
+
+    #[test]
+    fn tx_ordering_rule() {
+        let rule = TxOrderingRule::SaltedTxid;
+        let policy = BlockPolicy {
+            tx_ordering: Some(rule),
+            ..BlockPolicy::default()
+        };
+        let mempool = MempoolView::default();
+        let contract = sbtc_contract();
+        let mut block = make_block(vec![
+            coinbase(),
+            contract_call(&contract, 0),
+            contract_call(&contract, 0),
+        ]);
+        block.txs = rule.order_transactions(&block.header.parent_block_id, block.txs);
+        assert_eq!(policy.check_block(&block, &mempool), None);
+
+        block.txs.swap(1, 2);
+        let violation = policy.check_block(&block, &mempool).unwrap();
+        assert_eq!(violation.rule, PolicyRule::TxOrdering);
+        assert_eq!(BlockPolicy::default().check_block(&block, &mempool), None);
+
+        let raw = RawBlockPolicy {
+            tx_ordering: Some("fee-rate".into()),
+            ..RawBlockPolicy::default()
+        };
+        assert!(matches!(
+            BlockPolicy::try_from(raw),
+            Err(ConfigError::BadField(field, _)) if field == "block_policy.tx_ordering"
+        ));
+    }
+}
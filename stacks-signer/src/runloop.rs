@@ -19,8 +19,9 @@ use std::time::Duration;
 
 use clarity::codec::StacksMessageCodec;
 use hashbrown::HashMap;
-use libsigner::{SignerEntries, SignerEvent, SignerRunLoop};
+use libsigner::{ConnectionPool, SignerEntries, SignerEvent, SignerRunLoop};
 use slog::{slog_debug, slog_error, slog_info, slog_warn};
+use stacks_common::types::chainstate::{StacksAddress, StacksPublicKey};
 use stacks_common::{debug, error, info, warn};
 
 use crate::chainstate::SortitionsView;
@@ -181,12 +182,15 @@ where
     pub current_reward_cycle_info: Option<RewardCycleInfo>,
     /// Cache sortitin data from `stacks-node`
     pub sortition_state: Option<SortitionsView>,
+    /// Keep-alive connections to the node's StackerDB, shared by every reward cycle's signer
+    pub stackerdb_connection_pool: ConnectionPool,
 }
 
 impl<Signer: SignerTrait<T>, T: StacksMessageCodec + Clone + Send + Debug> RunLoop<Signer, T> {
     /// Create a new signer runloop from the provided configuration
     pub fn new(config: GlobalConfig) -> Self {
         let stacks_client = StacksClient::from(&config);
+        let stackerdb_connection_pool = config.new_stackerdb_connection_pool();
         Self {
             config,
             stacks_client,
@@ -194,6 +198,7 @@ impl<Signer: SignerTrait<T>, T: StacksMessageCodec + Clone + Send + Debug> RunLo
             state: State::Uninitialized,
             current_reward_cycle_info: None,
             sortition_state: None,
+            stackerdb_connection_pool,
         }
     }
     /// Get the registered signers for a specific reward cycle
@@ -254,7 +259,13 @@ impl<Signer: SignerTrait<T>, T: StacksMessageCodec + Clone + Send + Debug> RunLo
                 warn!("Error while fetching stackerdb slots {reward_cycle}: {e:?}");
                 e
             })?;
-        let current_addr = self.stacks_client.get_signer_address();
+        // A staged key rotation changes the key, and so the address, the signer is registered
+        // with from its activation reward cycle on
+        let stacks_private_key = self.config.stacks_private_key_for_cycle(reward_cycle);
+        let current_addr = &StacksAddress::p2pkh(
+            self.config.network.is_mainnet(),
+            &StacksPublicKey::from_private(&stacks_private_key),
+        );
 
         let Some(signer_slot_id) = signer_slot_ids.get(current_addr) else {
             warn!(
@@ -278,13 +289,18 @@ impl<Signer: SignerTrait<T>, T: StacksMessageCodec + Clone + Send + Debug> RunLo
             signer_entries,
             signer_slot_ids: signer_slot_ids.into_values().collect(),
             first_proposal_burn_block_timing: self.config.first_proposal_burn_block_timing,
-            stacks_private_key: self.config.stacks_private_key,
+            stacks_private_key,
+            key_rotation: self.config.key_rotation.clone(),
             node_host: self.config.node_host.to_string(),
             mainnet: self.config.network.is_mainnet(),
             db_path: self.config.db_path.clone(),
             block_proposal_timeout: self.config.block_proposal_timeout,
             tenure_last_block_proposal_timeout: self.config.tenure_last_block_proposal_timeout,
             block_proposal_validation_timeout: self.config.block_proposal_validation_timeout,
+            stackerdb_connection_pool: self.stackerdb_connection_pool.clone(),
+            stackerdb_throttle: self.config.stackerdb_throttle_config(),
+            transaction_config: self.config.transaction_config(),
+            block_policy: self.config.block_policy.clone(),
         }))
     }
 
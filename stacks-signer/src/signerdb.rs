@@ -21,12 +21,13 @@ use std::time::SystemTime;
 use blockstack_lib::chainstate::nakamoto::NakamotoBlock;
 use blockstack_lib::util_lib::db::{
     query_row, query_rows, sqlite_open, table_exists, tx_begin_immediate, u64_to_sql,
-    Error as DBError,
+    Error as DBError, FromColumn, FromRow,
 };
 use clarity::types::chainstate::{BurnchainHeaderHash, StacksAddress};
+use libsigner::v0::messages::BlockResponse;
 use libsigner::BlockProposal;
 use rusqlite::{
-    params, Connection, Error as SqliteError, OpenFlags, OptionalExtension, Transaction,
+    params, Connection, Error as SqliteError, OpenFlags, OptionalExtension, Row, Transaction,
 };
 use serde::{Deserialize, Serialize};
 use slog::{slog_debug, slog_error};
@@ -37,6 +38,8 @@ use stacks_common::util::hash::Sha512Trunc256Sum;
 use stacks_common::util::secp256k1::MessageSignature;
 use stacks_common::{debug, define_u8_enum, error};
 
+use crate::client::{ChunkRejection, SlotVersion};
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 /// A vote across the signer set for a block
 pub struct NakamotoBlockVote {
@@ -276,6 +279,90 @@ impl BlockInfo {
     }
 }
 
+/// An action the signer has committed to but may not have completed yet.
+/// Intents are written to the signer DB before the signer acts on them and
+/// removed once the action has completed, so that a signer that crashes in
+/// between can resume where it left off instead of staying silent.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum SignerIntent {
+    /// Publish the given block response to the signers' StackerDB
+    PublishBlockResponse(BlockResponse),
+}
+
+impl SignerIntent {
+    /// Return the signer signature hash of the block this intent is about
+    pub fn signer_signature_hash(&self) -> Sha512Trunc256Sum {
+        match self {
+            SignerIntent::PublishBlockResponse(BlockResponse::Accepted(accepted)) => {
+                accepted.signer_signature_hash
+            }
+            SignerIntent::PublishBlockResponse(BlockResponse::Rejected(rejected)) => {
+                rejected.signer_signature_hash
+            }
+        }
+    }
+}
+
+/// The chunk rejections with the same message ID and error code, summarized
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkRejectionSummary {
+    /// The message ID of the slots written to
+    pub msg_id: String,
+    /// The name of the node's error code
+    pub code: String,
+    /// How many chunks were rejected
+    pub count: u64,
+    /// When the latest rejection was received (epoch time in seconds)
+    pub last_received_time: u64,
+    /// The node's explanation of the latest rejection, if any
+    pub last_reason: Option<String>,
+    /// The node that sent the latest rejection
+    pub last_node: String,
+}
+
+impl FromRow<ChunkRejectionSummary> for ChunkRejectionSummary {
+    fn from_row(row: &Row) -> Result<ChunkRejectionSummary, DBError> {
+        Ok(ChunkRejectionSummary {
+            msg_id: row.get("msg_id")?,
+            code: row.get("code_name")?,
+            count: u64::from_column(row, "count")?,
+            last_received_time: u64::from_column(row, "last_received_time")?,
+            last_reason: row.get("reason")?,
+            last_node: row.get("node")?,
+        })
+    }
+}
+
+/// A block proposal submitted to the node for validation, whose response is outstanding
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubmittedBlockProposal {
+    /// The block proposal
+    pub block_proposal: BlockProposal,
+    /// When the proposal was submitted (epoch time in seconds)
+    pub submitted_time: u64,
+}
+
+impl FromRow<SubmittedBlockProposal> for SubmittedBlockProposal {
+    fn from_row(row: &Row) -> Result<SubmittedBlockProposal, DBError> {
+        let block_proposal: String = row.get("block_proposal")?;
+        Ok(SubmittedBlockProposal {
+            block_proposal: serde_json::from_str(&block_proposal)
+                .map_err(DBError::SerializationError)?,
+            submitted_time: u64::from_column(row, "submitted_time")?,
+        })
+    }
+}
+
+impl FromRow<SlotVersion> for SlotVersion {
+    fn from_row(row: &Row) -> Result<SlotVersion, DBError> {
+        Ok(SlotVersion {
+            msg_id: row.get("msg_id")?,
+            slot_id: row.get("slot_id")?,
+            slot_version: row.get("slot_version")?,
+        })
+    }
+}
+
 /// This struct manages a SQLite database connection
 /// for the signer.
 #[derive(Debug)]
@@ -342,6 +429,73 @@ CREATE TABLE IF NOT EXISTS burn_blocks (
     received_time INTEGER NOT NULL
 ) STRICT";
 
+static CREATE_SIGNER_INTENTS_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS signer_intents (
+    reward_cycle INTEGER NOT NULL,
+    -- The sighash of the block that the intent acts upon
+    signer_signature_hash TEXT NOT NULL,
+    -- JSON-serialized SignerIntent
+    intent TEXT NOT NULL,
+    -- Time at which the intent was logged (epoch time in seconds)
+    created_time INTEGER NOT NULL,
+    PRIMARY KEY (reward_cycle, signer_signature_hash)
+) STRICT;"#;
+
+static CREATE_SIGNED_BLOCKS_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS signed_blocks (
+    -- The tenure and height of the signed block.  Signing two different blocks at the same
+    -- tenure and height is equivocation, unless the earlier one was globally rejected.
+    consensus_hash TEXT NOT NULL,
+    stacks_height INTEGER NOT NULL,
+    signer_signature_hash TEXT NOT NULL,
+    reward_cycle INTEGER NOT NULL,
+    -- Time at which the block was first signed (epoch time in seconds)
+    signed_time INTEGER NOT NULL,
+    PRIMARY KEY (consensus_hash, stacks_height, signer_signature_hash)
+) STRICT;"#;
+
+static CREATE_CHUNK_REJECTIONS_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS chunk_rejections (
+    reward_cycle INTEGER NOT NULL,
+    msg_id TEXT NOT NULL,
+    slot_id INTEGER NOT NULL,
+    -- The slot version we attempted to write, and the one the node expected (if it said)
+    slot_version INTEGER NOT NULL,
+    expected_version INTEGER,
+    -- The node's error code, if any, and its name
+    code INTEGER,
+    code_name TEXT NOT NULL,
+    reason TEXT,
+    node TEXT NOT NULL,
+    -- Time at which the rejection was received (epoch time in seconds)
+    received_time INTEGER NOT NULL
+) STRICT;"#;
+
+static CREATE_SLOT_VERSIONS_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS stackerdb_slot_versions (
+    reward_cycle INTEGER NOT NULL,
+    msg_id TEXT NOT NULL,
+    slot_id INTEGER NOT NULL,
+    -- The next version to write to the slot
+    slot_version INTEGER NOT NULL,
+    PRIMARY KEY (reward_cycle, msg_id, slot_id)
+) STRICT;"#;
+
+static CREATE_SUBMITTED_BLOCK_PROPOSALS_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS submitted_block_proposals (
+    -- A signer has at most one block proposal awaiting validation at a time
+    reward_cycle INTEGER PRIMARY KEY,
+    signer_signature_hash TEXT NOT NULL,
+    -- JSON-serialized BlockProposal
+    block_proposal TEXT NOT NULL,
+    -- Time at which the proposal was submitted to the node (epoch time in seconds)
+    submitted_time INTEGER NOT NULL
+) STRICT;"#;
+
+static CREATE_INDEXES_5: &str = r#"
+CREATE INDEX IF NOT EXISTS chunk_rejections_received_time ON chunk_rejections(received_time);
+"#;
+
 static CREATE_DB_CONFIG: &str = "
     CREATE TABLE db_config(
         version INTEGER NOT NULL
@@ -431,9 +585,35 @@ static SCHEMA_4: &[&str] = &[
     "INSERT OR REPLACE INTO db_config (version) VALUES (4);",
 ];
 
+static SCHEMA_5: &[&str] = &[
+    CREATE_SIGNER_INTENTS_TABLE,
+    "INSERT OR REPLACE INTO db_config (version) VALUES (5);",
+];
+
+static SCHEMA_6: &[&str] = &[
+    CREATE_SIGNED_BLOCKS_TABLE,
+    "INSERT OR REPLACE INTO db_config (version) VALUES (6);",
+];
+
+static SCHEMA_7: &[&str] = &[
+    CREATE_CHUNK_REJECTIONS_TABLE,
+    CREATE_INDEXES_5,
+    "INSERT OR REPLACE INTO db_config (version) VALUES (7);",
+];
+
+static SCHEMA_8: &[&str] = &[
+    CREATE_SLOT_VERSIONS_TABLE,
+    "INSERT OR REPLACE INTO db_config (version) VALUES (8);",
+];
+
+static SCHEMA_9: &[&str] = &[
+    CREATE_SUBMITTED_BLOCK_PROPOSALS_TABLE,
+    "INSERT OR REPLACE INTO db_config (version) VALUES (9);",
+];
+
 impl SignerDb {
     /// The current schema version used in this build of the signer binary.
-    pub const SCHEMA_VERSION: u32 = 4;
+    pub const SCHEMA_VERSION: u32 = 9;
 
     /// Create a new `SignerState` instance.
     /// This will create a new SQLite database at the given path
@@ -519,6 +699,76 @@ impl SignerDb {
         Ok(())
     }
 
+    /// Migrate from schema 4 to schema 5
+    fn schema_5_migration(tx: &Transaction) -> Result<(), DBError> {
+        if Self::get_schema_version(tx)? >= 5 {
+            // no migration necessary
+            return Ok(());
+        }
+
+        for statement in SCHEMA_5.iter() {
+            tx.execute_batch(statement)?;
+        }
+
+        Ok(())
+    }
+
+    /// Migrate from schema 5 to schema 6
+    fn schema_6_migration(tx: &Transaction) -> Result<(), DBError> {
+        if Self::get_schema_version(tx)? >= 6 {
+            // no migration necessary
+            return Ok(());
+        }
+
+        for statement in SCHEMA_6.iter() {
+            tx.execute_batch(statement)?;
+        }
+
+        Ok(())
+    }
+
+    /// Migrate from schema 6 to schema 7
+    fn schema_7_migration(tx: &Transaction) -> Result<(), DBError> {
+        if Self::get_schema_version(tx)? >= 7 {
+            // no migration necessary
+            return Ok(());
+        }
+
+        for statement in SCHEMA_7.iter() {
+            tx.execute_batch(statement)?;
+        }
+
+        Ok(())
+    }
+
+    /// Migrate from schema 7 to schema 8
+    fn schema_8_migration(tx: &Transaction) -> Result<(), DBError> {
+        if Self::get_schema_version(tx)? >= 8 {
+            // no migration necessary
+            return Ok(());
+        }
+
+        for statement in SCHEMA_8.iter() {
+            tx.execute_batch(statement)?;
+        }
+
+        Ok(())
+    }
+
+    /// Migrate from schema 8 to schema 9
+    fn schema_9_migration(tx: &Transaction) -> Result<(), DBError> {
+        if Self::get_schema_version(tx)? >= 9 {
+            // no migration necessary
+            return Ok(());
+        }
+
+        for statement in SCHEMA_9.iter() {
+            tx.execute_batch(statement)?;
+        }
+
+        Ok(())
+    }
+
     /// Either instantiate a new database, or migrate an existing one
     /// If the detected version of the existing database is 0 (i.e., a pre-migration
     /// logic DB, the DB will be dropped).
@@ -531,7 +781,12 @@ impl SignerDb {
                 1 => Self::schema_2_migration(&sql_tx)?,
                 2 => Self::schema_3_migration(&sql_tx)?,
                 3 => Self::schema_4_migration(&sql_tx)?,
-                4 => break,
+                4 => Self::schema_5_migration(&sql_tx)?,
+                5 => Self::schema_6_migration(&sql_tx)?,
+                6 => Self::schema_7_migration(&sql_tx)?,
+                7 => Self::schema_8_migration(&sql_tx)?,
+                8 => Self::schema_9_migration(&sql_tx)?,
+                9 => break,
                 x => return Err(DBError::Other(format!(
                     "Database schema is newer than supported by this binary. Expected version = {}, Database version = {x}",
                     Self::SCHEMA_VERSION,
@@ -859,6 +1114,240 @@ impl SignerDb {
             BlockState::try_from(state.as_str()).map_err(|_| DBError::Corruption)?,
         ))
     }
+
+    /// Record an intent before acting on it.
+    /// Replaces any outstanding intent for the same block.
+    pub fn log_intent(&self, reward_cycle: u64, intent: &SignerIntent) -> Result<(), DBError> {
+        let hash = intent.signer_signature_hash();
+        let intent_json = serde_json::to_string(intent).map_err(DBError::SerializationError)?;
+        debug!("Logging signer intent.";
+            "reward_cycle" => reward_cycle,
+            "signer_sighash" => %hash,
+        );
+        self.db.execute(
+            "INSERT OR REPLACE INTO signer_intents (reward_cycle, signer_signature_hash, intent, created_time) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                u64_to_sql(reward_cycle)?,
+                hash.to_string(),
+                intent_json,
+                u64_to_sql(get_epoch_time_secs())?,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Remove the intent for the given block once it has been acted upon
+    pub fn complete_intent(
+        &self,
+        reward_cycle: u64,
+        block_sighash: &Sha512Trunc256Sum,
+    ) -> Result<(), DBError> {
+        debug!("Completing signer intent.";
+            "reward_cycle" => reward_cycle,
+            "signer_sighash" => %block_sighash,
+        );
+        self.db.execute(
+            "DELETE FROM signer_intents WHERE reward_cycle = ?1 AND signer_signature_hash = ?2",
+            params![u64_to_sql(reward_cycle)?, block_sighash.to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// Record that we signed the given block
+    pub fn record_signed_block(&self, block_info: &BlockInfo) -> Result<(), DBError> {
+        let hash = block_info.signer_signature_hash();
+        debug!("Recording signed block.";
+            "reward_cycle" => block_info.reward_cycle,
+            "signer_sighash" => %hash,
+            "consensus_hash" => %block_info.block.header.consensus_hash,
+            "stacks_height" => block_info.block.header.chain_length,
+        );
+        self.db.execute(
+            "INSERT OR IGNORE INTO signed_blocks (consensus_hash, stacks_height, signer_signature_hash, reward_cycle, signed_time) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                block_info.block.header.consensus_hash.to_hex(),
+                u64_to_sql(block_info.block.header.chain_length)?,
+                hash.to_string(),
+                u64_to_sql(block_info.reward_cycle)?,
+                u64_to_sql(get_epoch_time_secs())?,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Get the sighashes of any other blocks we signed at the same tenure and height as the
+    /// given block that have not been globally rejected.  Any of them could still be accepted,
+    /// so signing the given block as well would be a double-sign.
+    pub fn get_conflicting_signed_blocks(
+        &self,
+        block_info: &BlockInfo,
+    ) -> Result<Vec<Sha512Trunc256Sum>, DBError> {
+        let qry = "SELECT s.signer_signature_hash FROM signed_blocks s LEFT JOIN blocks b ON b.reward_cycle = s.reward_cycle AND b.signer_signature_hash = s.signer_signature_hash WHERE s.consensus_hash = ?1 AND s.stacks_height = ?2 AND s.signer_signature_hash != ?3 AND (b.block_info IS NULL OR json_extract(b.block_info, '$.state') != ?4) ORDER BY s.signed_time ASC";
+        let args = params![
+            block_info.block.header.consensus_hash.to_hex(),
+            u64_to_sql(block_info.block.header.chain_length)?,
+            block_info.signer_signature_hash().to_string(),
+            BlockState::GloballyRejected.to_string(),
+        ];
+        let hashes_txt: Vec<String> = query_rows(&self.db, qry, args)?;
+        hashes_txt
+            .into_iter()
+            .map(|hash_txt| Sha512Trunc256Sum::from_hex(&hash_txt).map_err(|_| DBError::Corruption))
+            .collect()
+    }
+
+    /// Remove the intent for the given block once it has been acted upon, along with every
+    /// intent in the reward cycle that was logged before it.  Those were for the same StackerDB
+    /// slot, which now holds the later message, so publishing them would only overwrite it with
+    /// a stale one.
+    pub fn complete_intents_through(
+        &self,
+        reward_cycle: u64,
+        block_sighash: &Sha512Trunc256Sum,
+    ) -> Result<(), DBError> {
+        debug!("Completing signer intent and the intents logged before it.";
+            "reward_cycle" => reward_cycle,
+            "signer_sighash" => %block_sighash,
+        );
+        self.db.execute(
+            "DELETE FROM signer_intents WHERE reward_cycle = ?1 AND rowid <= (SELECT rowid FROM signer_intents WHERE reward_cycle = ?1 AND signer_signature_hash = ?2)",
+            params![u64_to_sql(reward_cycle)?, block_sighash.to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// Remove the intents for reward cycles before `min_reward_cycle`, and those logged before
+    /// `min_created_time` (epoch time in seconds).  Returns how many were removed.
+    pub fn prune_intents(
+        &self,
+        min_reward_cycle: u64,
+        min_created_time: u64,
+    ) -> Result<usize, DBError> {
+        let removed = self.db.execute(
+            "DELETE FROM signer_intents WHERE reward_cycle < ?1 OR created_time < ?2",
+            params![u64_to_sql(min_reward_cycle)?, u64_to_sql(min_created_time)?],
+        )?;
+        Ok(removed)
+    }
+
+    /// Get all outstanding intents for the given reward cycle, oldest first
+    pub fn get_pending_intents(&self, reward_cycle: u64) -> Result<Vec<SignerIntent>, DBError> {
+        let qry = "SELECT intent FROM signer_intents WHERE reward_cycle = ?1 ORDER BY created_time ASC, rowid ASC";
+        let args = params![u64_to_sql(reward_cycle)?];
+        let intents_txt: Vec<String> = query_rows(&self.db, qry, args)?;
+        intents_txt
+            .into_iter()
+            .map(|intent_txt| {
+                serde_json::from_str(&intent_txt).map_err(DBError::SerializationError)
+            })
+            .collect()
+    }
+
+    /// Record a chunk that the node's StackerDB rejected
+    pub fn record_chunk_rejection(
+        &self,
+        reward_cycle: u64,
+        rejection: &ChunkRejection,
+    ) -> Result<(), DBError> {
+        self.db.execute(
+            "INSERT INTO chunk_rejections (reward_cycle, msg_id, slot_id, slot_version, expected_version, code, code_name, reason, node, received_time) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                u64_to_sql(reward_cycle)?,
+                rejection.msg_id,
+                rejection.slot_id,
+                rejection.slot_version,
+                rejection.expected_version,
+                rejection.code,
+                rejection.code_name(),
+                rejection.reason,
+                rejection.node,
+                u64_to_sql(rejection.received_time)?,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Summarize the chunk rejections received since `since` (epoch time in seconds), by
+    /// message ID and error code, most frequent first
+    pub fn get_chunk_rejection_summary(
+        &self,
+        since: u64,
+    ) -> Result<Vec<ChunkRejectionSummary>, DBError> {
+        // SQLite takes the bare columns from the row with the latest time
+        let qry = "SELECT msg_id, code_name, COUNT(*) AS count, MAX(received_time) AS last_received_time, reason, node FROM chunk_rejections WHERE received_time >= ?1 GROUP BY msg_id, code_name ORDER BY count DESC, msg_id ASC, code_name ASC";
+        query_rows(&self.db, qry, params![u64_to_sql(since)?])
+    }
+
+    /// Set the next version to write to one of the signer's StackerDB slots
+    pub fn set_slot_version(
+        &self,
+        reward_cycle: u64,
+        version: &SlotVersion,
+    ) -> Result<(), DBError> {
+        self.db.execute(
+            "INSERT OR REPLACE INTO stackerdb_slot_versions (reward_cycle, msg_id, slot_id, slot_version) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                u64_to_sql(reward_cycle)?,
+                version.msg_id,
+                version.slot_id,
+                version.slot_version,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Record the block proposal submitted to the node for validation in the given reward
+    /// cycle, replacing any earlier one
+    pub fn set_submitted_block_proposal(
+        &self,
+        reward_cycle: u64,
+        submitted: &SubmittedBlockProposal,
+    ) -> Result<(), DBError> {
+        let block_proposal = serde_json::to_string(&submitted.block_proposal)
+            .map_err(DBError::SerializationError)?;
+        self.db.execute(
+            "INSERT OR REPLACE INTO submitted_block_proposals (reward_cycle, signer_signature_hash, block_proposal, submitted_time) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                u64_to_sql(reward_cycle)?,
+                submitted
+                    .block_proposal
+                    .block
+                    .header
+                    .signer_signature_hash()
+                    .to_string(),
+                block_proposal,
+                u64_to_sql(submitted.submitted_time)?,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Get the block proposal awaiting validation in the given reward cycle, if any
+    pub fn get_submitted_block_proposal(
+        &self,
+        reward_cycle: u64,
+    ) -> Result<Option<SubmittedBlockProposal>, DBError> {
+        query_row(
+            &self.db,
+            "SELECT block_proposal, submitted_time FROM submitted_block_proposals WHERE reward_cycle = ?1",
+            params![u64_to_sql(reward_cycle)?],
+        )
+    }
+
+    /// Forget the block proposal awaiting validation in the given reward cycle
+    pub fn clear_submitted_block_proposal(&self, reward_cycle: u64) -> Result<(), DBError> {
+        self.db.execute(
+            "DELETE FROM submitted_block_proposals WHERE reward_cycle = ?1",
+            params![u64_to_sql(reward_cycle)?],
+        )?;
+        Ok(())
+    }
+
+    /// Get the next versions to write to the signer's StackerDB slots in the given reward cycle
+    pub fn get_slot_versions(&self, reward_cycle: u64) -> Result<Vec<SlotVersion>, DBError> {
+        let qry = "SELECT msg_id, slot_id, slot_version FROM stackerdb_slot_versions WHERE reward_cycle = ?1 ORDER BY msg_id ASC, slot_id ASC";
+        query_rows(&self.db, qry, params![u64_to_sql(reward_cycle)?])
+    }
 }
 
 fn try_deserialize<T>(s: Option<String>) -> Result<Option<T>, DBError>
@@ -1094,6 +1583,87 @@ mod tests {
             .is_none());
     }
 
+    #[test]
+    fn log_and_complete_intents() {
+        let db_path = tmp_db_path();
+        let db = SignerDb::new(db_path).expect("Failed to create signer db");
+        let reward_cycle = 42;
+        let hash_1 = Sha512Trunc256Sum([0x01; 32]);
+        let hash_2 = Sha512Trunc256Sum([0x02; 32]);
+        let intent_1 = SignerIntent::PublishBlockResponse(BlockResponse::accepted(
+            hash_1,
+            MessageSignature([0x11; 65]),
+        ));
+        let intent_2 = SignerIntent::PublishBlockResponse(BlockResponse::accepted(
+            hash_2,
+            MessageSignature([0x22; 65]),
+        ));
+
+        assert!(db.get_pending_intents(reward_cycle).unwrap().is_empty());
+
+        db.log_intent(reward_cycle, &intent_1).unwrap();
+        db.log_intent(reward_cycle, &intent_2).unwrap();
+        // Logging the same intent twice does not duplicate it
+        db.log_intent(reward_cycle, &intent_2).unwrap();
+
+        let pending = db.get_pending_intents(reward_cycle).unwrap();
+        assert_eq!(pending.len(), 2);
+        assert!(pending.contains(&intent_1));
+        assert!(pending.contains(&intent_2));
+        assert!(db.get_pending_intents(reward_cycle + 1).unwrap().is_empty());
+
+        db.complete_intent(reward_cycle, &hash_1).unwrap();
+        assert_eq!(
+            db.get_pending_intents(reward_cycle).unwrap(),
+            vec![intent_2]
+        );
+
+        db.complete_intent(reward_cycle, &hash_2).unwrap();
+        assert!(db.get_pending_intents(reward_cycle).unwrap().is_empty());
+    }
+
+    #[test]
+    fn complete_and_prune_intents() {
+        let db_path = tmp_db_path();
+        let db = SignerDb::new(db_path).expect("Failed to create signer db");
+        let reward_cycle = 42;
+        let intents: Vec<_> = (1..=3)
+            .map(|i| {
+                SignerIntent::PublishBlockResponse(BlockResponse::accepted(
+                    Sha512Trunc256Sum([i; 32]),
+                    MessageSignature([i; 65]),
+                ))
+            })
+            .collect();
+        for intent in intents.iter() {
+            db.log_intent(reward_cycle, intent).unwrap();
+        }
+        db.log_intent(reward_cycle + 1, &intents[0]).unwrap();
+
+        // Publishing the second response supersedes the first, but not the third
+        db.complete_intents_through(reward_cycle, &intents[1].signer_signature_hash())
+            .unwrap();
+        assert_eq!(
+            db.get_pending_intents(reward_cycle).unwrap(),
+            vec![intents[2].clone()]
+        );
+        // An intent that is no longer pending removes nothing
+        db.complete_intents_through(reward_cycle, &intents[1].signer_signature_hash())
+            .unwrap();
+        assert_eq!(db.get_pending_intents(reward_cycle).unwrap().len(), 1);
+        assert_eq!(db.get_pending_intents(reward_cycle + 1).unwrap().len(), 1);
+
+        // Intents of earlier reward cycles are pruned
+        assert_eq!(db.prune_intents(reward_cycle + 1, 0).unwrap(), 1);
+        assert!(db.get_pending_intents(reward_cycle).unwrap().is_empty());
+        assert_eq!(db.get_pending_intents(reward_cycle + 1).unwrap().len(), 1);
+
+        // And so are old intents
+        assert_eq!(db.prune_intents(0, 0).unwrap(), 0);
+        assert_eq!(db.prune_intents(0, get_epoch_time_secs() + 1).unwrap(), 1);
+        assert!(db.get_pending_intents(reward_cycle + 1).unwrap().is_empty());
+    }
+
     #[test]
     fn test_has_unprocessed_blocks() {
         let db_path = tmp_db_path();
@@ -1267,6 +1837,63 @@ mod tests {
         assert!(block.check_state(BlockState::GloballyRejected));
     }
 
+    #[test]
+    fn signed_block_conflicts() {
+        let db_path = tmp_db_path();
+        let mut db = SignerDb::new(db_path).expect("Failed to create signer db");
+
+        let (block_info_1, _) = create_block_override(|b| {
+            b.block.header.miner_signature = MessageSignature([0x01; 65]);
+        });
+        let (mut block_info_2, _) = create_block_override(|b| {
+            b.block.header.miner_signature = MessageSignature([0x02; 65]);
+        });
+        let (block_info_3, _) = create_block_override(|b| {
+            b.block.header.miner_signature = MessageSignature([0x03; 65]);
+            b.block.header.chain_length = 1;
+        });
+        let hash_2 = block_info_2.signer_signature_hash();
+
+        db.insert_block(&block_info_2).unwrap();
+        db.record_signed_block(&block_info_2).unwrap();
+        // re-recording the same block is a no-op
+        db.record_signed_block(&block_info_2).unwrap();
+
+        assert!(db
+            .get_conflicting_signed_blocks(&block_info_2)
+            .unwrap()
+            .is_empty());
+        // an undecided block conflicts
+        assert_eq!(
+            db.get_conflicting_signed_blocks(&block_info_1).unwrap(),
+            vec![hash_2]
+        );
+        // different height, so no conflict
+        assert!(db
+            .get_conflicting_signed_blocks(&block_info_3)
+            .unwrap()
+            .is_empty());
+
+        // so does a globally accepted block
+        let (mut accepted_block_info_2, _) = create_block_override(|b| {
+            b.block.header.miner_signature = MessageSignature([0x02; 65]);
+        });
+        accepted_block_info_2.mark_globally_accepted().unwrap();
+        db.insert_block(&accepted_block_info_2).unwrap();
+        assert_eq!(
+            db.get_conflicting_signed_blocks(&block_info_1).unwrap(),
+            vec![hash_2]
+        );
+
+        // a globally rejected block no longer conflicts
+        block_info_2.mark_globally_rejected().unwrap();
+        db.insert_block(&block_info_2).unwrap();
+        assert!(db
+            .get_conflicting_signed_blocks(&block_info_1)
+            .unwrap()
+            .is_empty());
+    }
+
     #[test]
     fn test_get_canonical_tip() {
         let db_path = tmp_db_path();
@@ -1307,4 +1934,130 @@ mod tests {
 
         assert_eq!(db.get_canonical_tip().unwrap().unwrap(), block_info_2);
     }
+
+    #[test]
+    fn test_chunk_rejection_summary() {
+        let db_path = tmp_db_path();
+        let db = SignerDb::new(db_path).expect("Failed to create signer db");
+        let rejection =
+            |msg_id: &str, code: Option<u32>, node: &str, received_time: u64| ChunkRejection {
+                msg_id: msg_id.into(),
+                slot_id: 1,
+                slot_version: 2,
+                expected_version: None,
+                code,
+                reason: Some(format!("rejected at {received_time}")),
+                node: node.into(),
+                received_time,
+            };
+        let rejections = [
+            rejection("BlockResponse", Some(0), "node-a", 100),
+            rejection("BlockResponse", Some(0), "node-b", 300),
+            rejection("BlockResponse", Some(0), "node-a", 200),
+            rejection("MockSignature", Some(2), "node-a", 250),
+            rejection("MockSignature", None, "node-a", 260),
+            rejection("BlockResponse", Some(1), "node-a", 10),
+        ];
+        for rejection in rejections.iter() {
+            db.record_chunk_rejection(42, rejection).unwrap();
+        }
+
+        let summary = db.get_chunk_rejection_summary(100).unwrap();
+        assert_eq!(summary.len(), 3);
+        assert_eq!(
+            summary[0],
+            ChunkRejectionSummary {
+                msg_id: "BlockResponse".into(),
+                code: "DataAlreadyExists".into(),
+                count: 3,
+                last_received_time: 300,
+                last_reason: Some("rejected at 300".into()),
+                last_node: "node-b".into(),
+            }
+        );
+        assert_eq!(
+            (summary[1].msg_id.as_str(), summary[1].code.as_str()),
+            ("MockSignature", "BadSigner")
+        );
+        assert_eq!(
+            (summary[2].msg_id.as_str(), summary[2].code.as_str()),
+            ("MockSignature", "None")
+        );
+
+        assert!(db.get_chunk_rejection_summary(301).unwrap().is_empty());
+        assert_eq!(db.get_chunk_rejection_summary(0).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_slot_versions() {
+        let db_path = tmp_db_path();
+        let db = SignerDb::new(db_path).expect("Failed to create signer db");
+        let version = |msg_id: &str, slot_id: u32, slot_version: u32| SlotVersion {
+            msg_id: msg_id.into(),
+            slot_id,
+            slot_version,
+        };
+        assert!(db.get_slot_versions(42).unwrap().is_empty());
+
+        db.set_slot_version(42, &version("MockSignature", 1, 3))
+            .unwrap();
+        db.set_slot_version(42, &version("BlockResponse", 1, 7))
+            .unwrap();
+        db.set_slot_version(43, &version("BlockResponse", 1, 2))
+            .unwrap();
+        // the latest version replaces the earlier one
+        db.set_slot_version(42, &version("BlockResponse", 1, 8))
+            .unwrap();
+
+        assert_eq!(
+            db.get_slot_versions(42).unwrap(),
+            vec![
+                version("BlockResponse", 1, 8),
+                version("MockSignature", 1, 3)
+            ]
+        );
+        assert_eq!(
+            db.get_slot_versions(43).unwrap(),
+            vec![version("BlockResponse", 1, 2)]
+        );
+    }
+
+    #[test]
+    fn test_submitted_block_proposal() {
+        let db_path = tmp_db_path();
+        let db = SignerDb::new(&db_path).expect("Failed to create signer db");
+        let (_, block_proposal) = create_block();
+        let (_, other_block_proposal) = create_block_override(|b| {
+            b.block.header.chain_length = 2;
+        });
+        assert_eq!(db.get_submitted_block_proposal(42).unwrap(), None);
+
+        let submitted = SubmittedBlockProposal {
+            block_proposal,
+            submitted_time: 100,
+        };
+        db.set_submitted_block_proposal(42, &submitted).unwrap();
+        assert_eq!(
+            db.get_submitted_block_proposal(42).unwrap(),
+            Some(submitted)
+        );
+        assert_eq!(db.get_submitted_block_proposal(43).unwrap(), None);
+
+        // a later submission replaces the earlier one, and survives a restart
+        let other_submitted = SubmittedBlockProposal {
+            block_proposal: other_block_proposal,
+            submitted_time: 200,
+        };
+        db.set_submitted_block_proposal(42, &other_submitted)
+            .unwrap();
+        drop(db);
+        let db = SignerDb::new(&db_path).expect("Failed to reopen signer db");
+        assert_eq!(
+            db.get_submitted_block_proposal(42).unwrap(),
+            Some(other_submitted)
+        );
+
+        db.clear_submitted_block_proposal(42).unwrap();
+        assert_eq!(db.get_submitted_block_proposal(42).unwrap(), None);
+    }
 }
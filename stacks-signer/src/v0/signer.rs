@@ -27,23 +27,33 @@ use clarity::types::{PrivateKey, StacksEpochId};
 use clarity::util::hash::MerkleHashFunc;
 use clarity::util::secp256k1::Secp256k1PublicKey;
 use libsigner::v0::messages::{
-    BlockAccepted, BlockRejection, BlockResponse, MessageSlotID, MockProposal, MockSignature,
-    RejectCode, SignerMessage,
+    BlockAccepted, BlockRejection, BlockResponse, KeyRotation, MessageSlotID, MockProposal,
+    MockSignature, RejectCode, SignerMessage,
 };
 use libsigner::{BlockProposal, SignerEvent};
+use libstackerdb::StackerDBChunkAckData;
 use slog::{slog_debug, slog_error, slog_info, slog_warn};
 use stacks_common::types::chainstate::StacksAddress;
 use stacks_common::util::get_epoch_time_secs;
+use stacks_common::util::hash::Sha512Trunc256Sum;
 use stacks_common::util::secp256k1::MessageSignature;
 use stacks_common::{debug, error, info, warn};
 
 use crate::chainstate::{ProposalEvalConfig, SortitionsView};
-use crate::client::{SignerSlotID, StackerDB, StacksClient};
-use crate::config::SignerConfig;
+use crate::client::{ClientError, SignerSlotID, StackerDB, StacksClient};
+use crate::config::{KeyRotationConfig, SignerConfig};
+use crate::policy::BlockPolicy;
 use crate::runloop::SignerResult;
-use crate::signerdb::{BlockInfo, BlockState, SignerDb};
+use crate::signerdb::{BlockInfo, BlockState, SignerDb, SignerIntent, SubmittedBlockProposal};
 use crate::Signer as SignerTrait;
 
+/// How often the intents that were not completed are retried
+const PENDING_INTENT_RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long an intent is retried before it is dropped.  By then, the block it is about has
+/// been decided on without us, and publishing our response would overwrite a newer one.
+const PENDING_INTENT_MAX_AGE: Duration = Duration::from_secs(600);
+
 #[cfg(any(test, feature = "testing"))]
 /// A global variable that can be used to reject all block proposals if the signer's public key is in the provided list
 pub static TEST_REJECT_ALL_BLOCK_PROPOSAL: std::sync::Mutex<
@@ -87,11 +97,21 @@ pub struct Signer {
     pub signer_db: SignerDb,
     /// Configuration for proposal evaluation
     pub proposal_config: ProposalEvalConfig,
+    /// The rules blocks must follow before this signer signs them
+    pub block_policy: BlockPolicy,
     /// How long to wait for a block proposal validation response to arrive before
     /// marking a submitted block as invalid
     pub block_proposal_validation_timeout: Duration,
     /// The current submitted block proposal and its submission time
     pub submitted_block_proposal: Option<(BlockProposal, Instant)>,
+    /// Whether the block proposal submission left over from a previous run has been resumed
+    pub resumed_submitted_block_proposal: bool,
+    /// When the pending intents were last retried
+    pub last_intent_retry: Option<Instant>,
+    /// The signer's staged key rotation, if any
+    key_rotation: Option<KeyRotationConfig>,
+    /// Whether the key rotation has been announced to the other signers
+    pub announced_key_rotation: bool,
 }
 
 impl std::fmt::Display for Signer {
@@ -134,6 +154,18 @@ impl SignerTrait<SignerMessage> for Signer {
         if event_parity == Some(other_signer_parity) {
             return;
         }
+        if self
+            .last_intent_retry
+            .map_or(true, |last| last.elapsed() >= PENDING_INTENT_RETRY_INTERVAL)
+        {
+            self.last_intent_retry = Some(Instant::now());
+            self.replay_pending_intents(current_reward_cycle);
+        }
+        if !self.resumed_submitted_block_proposal {
+            self.resumed_submitted_block_proposal = true;
+            self.resume_submitted_block_proposal(stacks_client);
+        }
+        self.announce_key_rotation();
         self.check_submitted_block_proposal();
         debug!("{self}: Processing event: {event:?}");
         let Some(event) = event else {
@@ -262,13 +294,20 @@ impl SignerTrait<SignerMessage> for Signer {
 
 impl From<SignerConfig> for Signer {
     fn from(signer_config: SignerConfig) -> Self {
-        let stackerdb = StackerDB::from(&signer_config);
+        let mut stackerdb = StackerDB::from(&signer_config);
         debug!(
             "Reward cycle #{} Signer #{}",
             signer_config.reward_cycle, signer_config.signer_id,
         );
         let signer_db =
             SignerDb::new(&signer_config.db_path).expect("Failed to connect to signer Db");
+        match signer_db.get_slot_versions(signer_config.reward_cycle) {
+            Ok(versions) => stackerdb.restore_slot_versions(&versions),
+            Err(e) => warn!(
+                "Reward cycle #{} Signer #{}: Failed to load stackerdb slot versions: {e:?}",
+                signer_config.reward_cycle, signer_config.signer_id,
+            ),
+        }
         let proposal_config = ProposalEvalConfig::from(&signer_config);
 
         Self {
@@ -282,7 +321,12 @@ impl From<SignerConfig> for Signer {
             reward_cycle: signer_config.reward_cycle,
             signer_db,
             proposal_config,
+            block_policy: signer_config.block_policy,
             submitted_block_proposal: None,
+            resumed_submitted_block_proposal: false,
+            last_intent_retry: None,
+            key_rotation: signer_config.key_rotation,
+            announced_key_rotation: false,
             block_proposal_validation_timeout: signer_config.block_proposal_validation_timeout,
         }
     }
@@ -295,6 +339,15 @@ impl Signer {
     fn determine_response(&self, block_info: &BlockInfo) -> Option<BlockResponse> {
         let valid = block_info.valid?;
         let response = if valid {
+            if let Err(reject_code) = self.guard_block_signature(block_info) {
+                let reject_code = reject_code?;
+                return Some(BlockResponse::rejected(
+                    block_info.signer_signature_hash(),
+                    reject_code,
+                    &self.private_key,
+                    self.mainnet,
+                ));
+            }
             debug!("{self}: Accepting block {}", block_info.block.block_id());
             let signature = self
                 .private_key
@@ -313,6 +366,223 @@ impl Signer {
         Some(response)
     }
 
+    /// Check that signing this block would not equivocate, i.e. that we have not already signed a
+    /// different block at the same tenure and height that has not been globally rejected, and
+    /// record that we are signing it.
+    /// Returns `Err(Some(code))` if the block must be rejected with `code`, and `Err(None)` if
+    /// the signer db could not be read or written, in which case the block is neither signed
+    /// nor rejected.
+    fn guard_block_signature(&self, block_info: &BlockInfo) -> Result<(), Option<RejectCode>> {
+        let conflicts = match self.signer_db.get_conflicting_signed_blocks(block_info) {
+            Ok(conflicts) => conflicts,
+            Err(e) => {
+                error!("{self}: Failed to check signer db for conflicting signed blocks. Refusing to sign block: {e:?}";
+                    "signer_sighash" => %block_info.signer_signature_hash(),
+                );
+                return Err(None);
+            }
+        };
+        if !conflicts.is_empty() {
+            error!("{self}: Refusing to sign block: already signed a different block at the same tenure and height that has not been globally rejected.";
+                "signer_sighash" => %block_info.signer_signature_hash(),
+                "block_id" => %block_info.block.block_id(),
+                "consensus_hash" => %block_info.block.header.consensus_hash,
+                "stacks_height" => block_info.block.header.chain_length,
+                "conflicting_sighashes" => ?conflicts,
+            );
+            return Err(Some(RejectCode::ConflictingBlock));
+        }
+        if let Err(e) = self.signer_db.record_signed_block(block_info) {
+            error!("{self}: Failed to record signed block in signer db. Refusing to sign block: {e:?}";
+                "signer_sighash" => %block_info.signer_signature_hash(),
+            );
+            return Err(None);
+        }
+        Ok(())
+    }
+
+    /// Send a block response to the signers' StackerDB.
+    /// The response is written to the intent log first and only removed once the
+    /// StackerDB has accepted it, so that it is retried if the send fails or we crash in
+    /// between.  Accepting it also supersedes the responses still pending from before it.
+    fn send_block_response(
+        &mut self,
+        block_response: BlockResponse,
+    ) -> Result<StackerDBChunkAckData, ClientError> {
+        let intent = SignerIntent::PublishBlockResponse(block_response.clone());
+        if let Err(e) = self.signer_db.log_intent(self.reward_cycle, &intent) {
+            warn!("{self}: Failed to log block response intent: {e:?}");
+        }
+        let result = self
+            .stackerdb
+            .send_message_with_retry::<SignerMessage>(block_response.into());
+        self.record_chunk_rejections();
+        self.persist_slot_versions();
+        let ack = result?;
+        if let Err(e) = self
+            .signer_db
+            .complete_intents_through(self.reward_cycle, &intent.signer_signature_hash())
+        {
+            warn!("{self}: Failed to complete block response intent: {e:?}");
+        }
+        Ok(ack)
+    }
+
+    /// Act on any intents that were logged but never completed, either because sending failed
+    /// or because the signer stopped in between.  Intents of reward cycles that are over, and
+    /// those too old to still matter, are dropped instead.
+    fn replay_pending_intents(&mut self, current_reward_cycle: u64) {
+        // The previous reward cycle's signer may still be signing
+        let min_created_time =
+            get_epoch_time_secs().saturating_sub(PENDING_INTENT_MAX_AGE.as_secs());
+        match self
+            .signer_db
+            .prune_intents(current_reward_cycle.saturating_sub(1), min_created_time)
+        {
+            Ok(0) => {}
+            Ok(pruned) => info!("{self}: Dropped {pruned} stale pending intents"),
+            Err(e) => warn!("{self}: Failed to prune pending intents: {e:?}"),
+        }
+        let intents = match self.signer_db.get_pending_intents(self.reward_cycle) {
+            Ok(intents) => intents,
+            Err(e) => {
+                error!("{self}: Failed to load pending intents from signer db: {e:?}");
+                return;
+            }
+        };
+        for intent in intents {
+            let signer_signature_hash = intent.signer_signature_hash();
+            let reached_consensus = self
+                .signer_db
+                .block_lookup(self.reward_cycle, &signer_signature_hash)
+                .ok()
+                .flatten()
+                .map(|block_info| block_info.has_reached_consensus())
+                .unwrap_or(false);
+            if reached_consensus {
+                // The rest of the signer set has already decided on this block. Nothing to resume.
+                debug!("{self}: Dropping pending intent for a block that has already reached consensus";
+                    "signer_sighash" => %signer_signature_hash,
+                );
+                if let Err(e) = self
+                    .signer_db
+                    .complete_intent(self.reward_cycle, &signer_signature_hash)
+                {
+                    warn!("{self}: Failed to complete block response intent: {e:?}");
+                }
+                continue;
+            }
+            match intent {
+                SignerIntent::PublishBlockResponse(block_response) => {
+                    info!("{self}: Resuming publication of a block response from the intent log";
+                        "signer_sighash" => %signer_signature_hash,
+                    );
+                    let accepted = matches!(block_response, BlockResponse::Accepted(..));
+                    match self.send_block_response(block_response) {
+                        Ok(_) => {
+                            crate::monitoring::increment_block_responses_sent(accepted);
+                        }
+                        Err(e) => {
+                            warn!("{self}: Failed to send block response to stacker-db: {e:?}",);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Resume the validation of a block proposal that was awaiting the node's response when the
+    /// signer last stopped. The response was lost with the signer, so the proposal is submitted
+    /// again, unless the block has been decided on since.
+    fn resume_submitted_block_proposal(&mut self, stacks_client: &StacksClient) {
+        let submitted = match self
+            .signer_db
+            .get_submitted_block_proposal(self.reward_cycle)
+        {
+            Ok(Some(submitted)) => submitted,
+            Ok(None) => return,
+            Err(e) => {
+                error!("{self}: Failed to load submitted block proposal from signer db: {e:?}");
+                return;
+            }
+        };
+        let block_proposal = submitted.block_proposal;
+        let signer_signature_hash = block_proposal.block.header.signer_signature_hash();
+        let undecided = self
+            .signer_db
+            .block_lookup(self.reward_cycle, &signer_signature_hash)
+            .ok()
+            .flatten()
+            .map(|block_info| {
+                !block_info.is_locally_finalized() && !block_info.has_reached_consensus()
+            })
+            .unwrap_or(false);
+        if !undecided {
+            debug!("{self}: Dropping submitted block proposal for a block that has already been decided on";
+                "signer_sighash" => %signer_signature_hash,
+            );
+            if let Err(e) = self
+                .signer_db
+                .clear_submitted_block_proposal(self.reward_cycle)
+            {
+                warn!("{self}: Failed to clear submitted block proposal: {e:?}");
+            }
+            return;
+        }
+        info!(
+            "{self}: Resuming validation of a block proposal submitted before the signer stopped";
+            "signer_sighash" => %signer_signature_hash,
+            "block_id" => %block_proposal.block.block_id(),
+            "submitted_time" => submitted.submitted_time,
+        );
+        if let Err(e) = stacks_client.submit_block_for_validation(block_proposal.block.clone()) {
+            // Still track it, so that the block is rejected once the validation times out
+            // instead of the signer staying silent about it
+            warn!("{self}: Failed to resubmit block for validation: {e:?}");
+        }
+        self.track_submitted_block_proposal(block_proposal);
+    }
+
+    /// Track a block proposal as awaiting validation by the node. It is also recorded in the
+    /// signer db, so that its validation is resumed if the signer restarts before the node
+    /// responds.
+    fn track_submitted_block_proposal(&mut self, block_proposal: BlockProposal) {
+        let submitted = SubmittedBlockProposal {
+            block_proposal: block_proposal.clone(),
+            submitted_time: get_epoch_time_secs(),
+        };
+        if let Err(e) = self
+            .signer_db
+            .set_submitted_block_proposal(self.reward_cycle, &submitted)
+        {
+            warn!("{self}: Failed to record submitted block proposal: {e:?}");
+        }
+        self.submitted_block_proposal = Some((block_proposal, Instant::now()));
+    }
+
+    /// Stop tracking the block proposal awaiting validation, if it is for the given block
+    fn untrack_submitted_block_proposal(&mut self, signer_signature_hash: &Sha512Trunc256Sum) {
+        if !self
+            .submitted_block_proposal
+            .as_ref()
+            .map(|(proposal, _)| {
+                &proposal.block.header.signer_signature_hash() == signer_signature_hash
+            })
+            .unwrap_or(false)
+        {
+            return;
+        }
+        if let Some((_, submission_time)) = self.submitted_block_proposal.take() {
+            crate::monitoring::observe_block_validation_latency(submission_time.elapsed());
+        }
+        if let Err(e) = self
+            .signer_db
+            .clear_submitted_block_proposal(self.reward_cycle)
+        {
+            warn!("{self}: Failed to clear submitted block proposal: {e:?}");
+        }
+    }
+
     /// Handle block proposal messages submitted to signers stackerdb
     fn handle_block_proposal(
         &mut self,
@@ -350,10 +620,7 @@ impl Signer {
             // Submit a proposal response to the .signers contract for miners
             debug!("{self}: Broadcasting a block response to stacks node: {block_response:?}");
             let accepted = matches!(block_response, BlockResponse::Accepted(..));
-            match self
-                .stackerdb
-                .send_message_with_retry::<SignerMessage>(block_response.into())
-            {
+            match self.send_block_response(block_response) {
                 Ok(_) => {
                     crate::monitoring::increment_block_responses_sent(accepted);
                 }
@@ -443,6 +710,9 @@ impl Signer {
             ))
         };
 
+        let block_response = block_response
+            .or_else(|| self.check_block_policy(stacks_client, &block_proposal.block));
+
         #[cfg(any(test, feature = "testing"))]
         let block_response =
             self.test_reject_block_proposal(block_proposal, &mut block_info, block_response);
@@ -453,9 +723,7 @@ impl Signer {
                 warn!("{self}: Failed to mark block as locally rejected: {e:?}",);
             };
             debug!("{self}: Broadcasting a block response to stacks node: {block_response:?}");
-            let res = self
-                .stackerdb
-                .send_message_with_retry::<SignerMessage>(block_response.into());
+            let res = self.send_block_response(block_response);
 
             match res {
                 Err(e) => warn!("{self}: Failed to send block rejection to stacker-db: {e:?}"),
@@ -463,7 +731,10 @@ impl Signer {
                     "{self}: Block rejection not accepted by stacker-db: {:?}",
                     ack.reason
                 ),
-                Ok(_) => debug!("{self}: Block rejection accepted by stacker-db"),
+                Ok(_) => {
+                    debug!("{self}: Block rejection accepted by stacker-db");
+                    crate::monitoring::increment_signing_rounds_participated();
+                }
             }
         } else {
             // Just in case check if the last block validation submission timed out.
@@ -479,8 +750,7 @@ impl Signer {
                 );
                 match stacks_client.submit_block_for_validation(block_info.block.clone()) {
                     Ok(_) => {
-                        self.submitted_block_proposal =
-                            Some((block_proposal.clone(), Instant::now()));
+                        self.track_submitted_block_proposal(block_proposal.clone());
                     }
                     Err(e) => {
                         warn!("{self}: Failed to submit block for validation: {e:?}");
@@ -501,6 +771,54 @@ impl Signer {
         }
     }
 
+    /// Check the block against the operator's block policy. Returns our rejection if it
+    /// breaks one of the rules, or if the node could not be asked about its mempool.
+    fn check_block_policy(
+        &self,
+        stacks_client: &StacksClient,
+        block: &NakamotoBlock,
+    ) -> Option<BlockResponse> {
+        let signer_signature_hash = block.header.signer_signature_hash();
+        let evaluation = self
+            .block_policy
+            .evaluate(stacks_client, block, get_epoch_time_secs());
+        let (reject_code, reason) = match evaluation {
+            Ok(None) => return None,
+            Ok(Some(violation)) => {
+                warn!(
+                    "{self}: Block proposal violates the block policy";
+                    "signer_sighash" => %signer_signature_hash,
+                    "block_id" => %block.block_id(),
+                    "rule" => ?violation.rule,
+                    "reason" => %violation,
+                );
+                (
+                    RejectCode::PolicyViolation(violation.rule),
+                    violation.reason,
+                )
+            }
+            Err(e) => {
+                warn!(
+                    "{self}: Error checking block proposal against the block policy: {e:?}";
+                    "signer_sighash" => %signer_signature_hash,
+                    "block_id" => %block.block_id(),
+                );
+                let reject_code = RejectCode::ConnectivityIssues;
+                let reason = reject_code.to_string();
+                (reject_code, reason)
+            }
+        };
+        let mut rejection = BlockRejection::new(
+            signer_signature_hash,
+            reject_code,
+            &self.private_key,
+            self.mainnet,
+        );
+        // the reason is not covered by the rejection's signature
+        rejection.reason = reason;
+        Some(BlockResponse::Rejected(rejection))
+    }
+
     /// Handle block response messages from a signer
     fn handle_block_response(
         &mut self,
@@ -524,16 +842,7 @@ impl Signer {
     ) -> Option<BlockResponse> {
         crate::monitoring::increment_block_validation_responses(true);
         let signer_signature_hash = block_validate_ok.signer_signature_hash;
-        if self
-            .submitted_block_proposal
-            .as_ref()
-            .map(|(proposal, _)| {
-                proposal.block.header.signer_signature_hash() == signer_signature_hash
-            })
-            .unwrap_or(false)
-        {
-            self.submitted_block_proposal = None;
-        }
+        self.untrack_submitted_block_proposal(&signer_signature_hash);
         // For mutability reasons, we need to take the block_info out of the map and add it back after processing
         let mut block_info = match self
             .signer_db
@@ -556,6 +865,26 @@ impl Signer {
                 return None;
             }
         };
+        if let Err(reject_code) = self.guard_block_signature(&block_info) {
+            let reject_code = reject_code?;
+            if let Err(e) = block_info.mark_locally_rejected() {
+                if !block_info.has_reached_consensus() {
+                    warn!("{self}: Failed to mark block as locally rejected: {e:?}",);
+                    return None;
+                }
+            }
+            let block_rejection = BlockRejection::new(
+                signer_signature_hash,
+                reject_code,
+                &self.private_key,
+                self.mainnet,
+            );
+            self.signer_db
+                .insert_block(&block_info)
+                .unwrap_or_else(|e| self.handle_insert_block_error(e));
+            self.handle_block_rejection(&block_rejection);
+            return Some(BlockResponse::Rejected(block_rejection));
+        }
         if let Err(e) = block_info.mark_locally_accepted(false) {
             if !block_info.has_reached_consensus() {
                 warn!("{self}: Failed to mark block as locally accepted: {e:?}",);
@@ -584,16 +913,7 @@ impl Signer {
     ) -> Option<BlockResponse> {
         crate::monitoring::increment_block_validation_responses(false);
         let signer_signature_hash = block_validate_reject.signer_signature_hash;
-        if self
-            .submitted_block_proposal
-            .as_ref()
-            .map(|(proposal, _)| {
-                proposal.block.header.signer_signature_hash() == signer_signature_hash
-            })
-            .unwrap_or(false)
-        {
-            self.submitted_block_proposal = None;
-        }
+        self.untrack_submitted_block_proposal(&signer_signature_hash);
         let mut block_info = match self
             .signer_db
             .block_lookup(self.reward_cycle, &signer_signature_hash)
@@ -656,12 +976,10 @@ impl Signer {
             "{self}: Broadcasting a block response to stacks node: {response:?}";
         );
         let accepted = matches!(response, BlockResponse::Accepted(..));
-        match self
-            .stackerdb
-            .send_message_with_retry::<SignerMessage>(response.into())
-        {
+        match self.send_block_response(response) {
             Ok(_) => {
                 crate::monitoring::increment_block_responses_sent(accepted);
+                crate::monitoring::increment_signing_rounds_participated();
             }
             Err(e) => {
                 warn!("{self}: Failed to send block rejection to stacker-db: {e:?}",);
@@ -681,6 +999,12 @@ impl Signer {
             self.submitted_block_proposal = Some((block_proposal, block_submission));
             return;
         }
+        if let Err(e) = self
+            .signer_db
+            .clear_submitted_block_proposal(self.reward_cycle)
+        {
+            warn!("{self}: Failed to clear submitted block proposal: {e:?}");
+        }
         let signature_sighash = block_proposal.block.header.signer_signature_hash();
         // For mutability reasons, we need to take the block_info out of the map and add it back after processing
         let mut block_info = match self
@@ -727,9 +1051,7 @@ impl Signer {
             warn!("{self}: Failed to mark block as locally rejected: {e:?}",);
         };
         debug!("{self}: Broadcasting a block response to stacks node: {rejection:?}");
-        let res = self
-            .stackerdb
-            .send_message_with_retry::<SignerMessage>(rejection.into());
+        let res = self.send_block_response(rejection);
 
         match res {
             Err(e) => warn!("{self}: Failed to send block rejection to stacker-db: {e:?}"),
@@ -737,7 +1059,10 @@ impl Signer {
                 "{self}: Block rejection not accepted by stacker-db: {:?}",
                 ack.reason
             ),
-            Ok(_) => debug!("{self}: Block rejection accepted by stacker-db"),
+            Ok(_) => {
+                debug!("{self}: Block rejection accepted by stacker-db");
+                crate::monitoring::increment_signing_rounds_participated();
+            }
         }
         self.signer_db
             .insert_block(&block_info)
@@ -850,15 +1175,8 @@ impl Signer {
             error!("{self}: Failed to update block state: {e:?}",);
             panic!("{self} Failed to update block state: {e}");
         }
-        if self
-            .submitted_block_proposal
-            .as_ref()
-            .map(|(proposal, _)| &proposal.block.header.signer_signature_hash() == block_hash)
-            .unwrap_or(false)
-        {
-            // Consensus reached! No longer bother tracking its validation submission to the node as we are too late to participate in the decision anyway.
-            self.submitted_block_proposal = None;
-        }
+        // Consensus reached! No longer bother tracking its validation submission to the node as we are too late to participate in the decision anyway.
+        self.untrack_submitted_block_proposal(block_hash);
     }
 
     /// Handle an observed signature from another signer
@@ -1001,15 +1319,8 @@ impl Signer {
             }
         }
         self.broadcast_signed_block(stacks_client, block_info.block, &addrs_to_sigs);
-        if self
-            .submitted_block_proposal
-            .as_ref()
-            .map(|(proposal, _)| &proposal.block.header.signer_signature_hash() == block_hash)
-            .unwrap_or(false)
-        {
-            // Consensus reached! No longer bother tracking its validation submission to the node as we are too late to participate in the decision anyway.
-            self.submitted_block_proposal = None;
-        }
+        // Consensus reached! No longer bother tracking its validation submission to the node as we are too late to participate in the decision anyway.
+        self.untrack_submitted_block_proposal(block_hash);
     }
 
     fn broadcast_signed_block(
@@ -1120,6 +1431,66 @@ impl Signer {
         {
             warn!("{self}: Failed to send mock signature to stacker-db: {e:?}",);
         }
+        self.record_chunk_rejections();
+        self.persist_slot_versions();
+    }
+
+    /// Announce the staged key rotation to the other signers of this reward cycle, if it
+    /// activates in a later reward cycle and has not been announced yet.  If the announcement
+    /// fails, it is retried on the next event.
+    fn announce_key_rotation(&mut self) {
+        if self.announced_key_rotation {
+            return;
+        }
+        let Some(rotation) = &self.key_rotation else {
+            return;
+        };
+        if self.reward_cycle >= rotation.activation_reward_cycle {
+            return;
+        }
+        let key_rotation = KeyRotation::new(
+            &self.private_key,
+            &rotation.next_private_key,
+            rotation.activation_reward_cycle,
+            self.mainnet,
+        );
+        info!(
+            "{self}: Announcing key rotation";
+            "next_public_key" => key_rotation.next_public_key.to_hex(),
+            "activation_reward_cycle" => key_rotation.activation_reward_cycle
+        );
+        match self
+            .stackerdb
+            .send_message_with_retry::<SignerMessage>(key_rotation.into())
+        {
+            Ok(ack) if ack.accepted => self.announced_key_rotation = true,
+            Ok(ack) => warn!("{self}: Key rotation announcement rejected by stacker-db: {ack:?}"),
+            Err(e) => warn!("{self}: Failed to announce key rotation to stacker-db: {e:?}"),
+        }
+        self.record_chunk_rejections();
+        self.persist_slot_versions();
+    }
+
+    /// Write the chunks that the StackerDB rejected to the signer db
+    fn record_chunk_rejections(&mut self) {
+        for rejection in self.stackerdb.take_chunk_rejections() {
+            if let Err(e) = self
+                .signer_db
+                .record_chunk_rejection(self.reward_cycle, &rejection)
+            {
+                warn!("{self}: Failed to record chunk rejection: {e:?}");
+            }
+        }
+    }
+
+    /// Write the StackerDB slot versions that changed to the signer db, so that a restarted
+    /// signer continues from them
+    fn persist_slot_versions(&mut self) {
+        for version in self.stackerdb.take_updated_slot_versions() {
+            if let Err(e) = self.signer_db.set_slot_version(self.reward_cycle, &version) {
+                warn!("{self}: Failed to persist stackerdb slot version: {e:?}");
+            }
+        }
     }
 
     /// Helper for logging insert_block error
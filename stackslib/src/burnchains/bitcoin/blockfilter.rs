@@ -0,0 +1,281 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! BIP158 compact block filters.  A block's basic filter is a Golomb-coded set of the scripts
+//! that its transactions spend and pay to (except for `OP_RETURN` outputs), so a node that keeps
+//! no transaction index can still tell which blocks may touch a given script before fetching
+//! them.  Filters have false positives, but no false negatives.
+
+use std::hash::Hasher;
+
+use siphasher::sip::SipHasher; // this is SipHash-2-4
+use stacks_common::deps_common::bitcoin::util::hash::Sha256dHash;
+
+use crate::burnchains::bitcoin::Error as btc_error;
+
+/// The Golomb-Rice parameter of basic filters
+pub const BASIC_FILTER_P: u8 = 19;
+/// The inverse false-positive rate of basic filters
+pub const BASIC_FILTER_M: u64 = 784931;
+
+/// A block's BIP158 basic filter
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockFilter {
+    /// The SipHash key, from the first 16 bytes of the block hash
+    k0: u64,
+    k1: u64,
+    /// The number of items in the set
+    n: u64,
+    /// The Golomb-Rice coded deltas of the sorted, hashed items
+    data: Vec<u8>,
+}
+
+/// Reads the bits of a byte string, most-significant first
+struct BitReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, offset: 0 }
+    }
+
+    fn read_bit(&mut self) -> Result<bool, btc_error> {
+        let byte = self
+            .data
+            .get(self.offset / 8)
+            .ok_or(btc_error::InvalidByteSequence)?;
+        let bit = (byte >> (7 - self.offset % 8)) & 1 == 1;
+        self.offset += 1;
+        Ok(bit)
+    }
+
+    fn read_bits(&mut self, count: u8) -> Result<u64, btc_error> {
+        let mut value = 0;
+        for _ in 0..count {
+            value = (value << 1) | u64::from(self.read_bit()?);
+        }
+        Ok(value)
+    }
+
+    fn read_golomb_rice(&mut self, p: u8) -> Result<u64, btc_error> {
+        let mut quotient: u64 = 0;
+        while self.read_bit()? {
+            quotient = quotient
+                .checked_add(1)
+                .ok_or(btc_error::InvalidByteSequence)?;
+        }
+        let remainder = self.read_bits(p)?;
+        quotient
+            .checked_shl(u32::from(p))
+            .map(|q| q | remainder)
+            .ok_or(btc_error::InvalidByteSequence)
+    }
+}
+
+/// Decode a Bitcoin CompactSize integer, returning it and the number of bytes it took
+fn read_compact_size(bytes: &[u8]) -> Result<(u64, usize), btc_error> {
+    let read_le = |len: usize| -> Result<u64, btc_error> {
+        let field = bytes
+            .get(1..1 + len)
+            .ok_or(btc_error::InvalidByteSequence)?;
+        Ok(field
+            .iter()
+            .rev()
+            .fold(0u64, |acc, byte| (acc << 8) | u64::from(*byte)))
+    };
+    match bytes.first() {
+        None => Err(btc_error::InvalidByteSequence),
+        Some(0xfd) => Ok((read_le(2)?, 3)),
+        Some(0xfe) => Ok((read_le(4)?, 5)),
+        Some(0xff) => Ok((read_le(8)?, 9)),
+        Some(n) => Ok((u64::from(*n), 1)),
+    }
+}
+
+impl BlockFilter {
+    /// Decode the basic filter of the block with hash `block_hash`
+    pub fn from_bytes(block_hash: &Sha256dHash, bytes: &[u8]) -> Result<BlockFilter, btc_error> {
+        let (n, n_len) = read_compact_size(bytes)?;
+        let mut k0_bytes = [0u8; 8];
+        let mut k1_bytes = [0u8; 8];
+        k0_bytes.copy_from_slice(&block_hash.0[0..8]);
+        k1_bytes.copy_from_slice(&block_hash.0[8..16]);
+        Ok(BlockFilter {
+            k0: u64::from_le_bytes(k0_bytes),
+            k1: u64::from_le_bytes(k1_bytes),
+            n,
+            data: bytes[n_len..].to_vec(),
+        })
+    }
+
+    /// The number of items in the filter
+    pub fn len(&self) -> u64 {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Hash an item into the filter's range, `[0, N * M)`
+    fn hash_to_range(&self, item: &[u8]) -> u64 {
+        let mut hasher = SipHasher::new_with_keys(self.k0, self.k1);
+        hasher.write(item);
+        let range = u128::from(self.n) * u128::from(BASIC_FILTER_M);
+        // the product of a u64 and a value < 2^64, shifted down by 64 bits, fits in a u64
+        u64::try_from((u128::from(hasher.finish()) * range) >> 64)
+            .expect("BUG: hash exceeds filter range")
+    }
+
+    /// Does the filter match any of `items`?  This can be a false positive, but never a false
+    /// negative.
+    pub fn match_any(&self, items: &[&[u8]]) -> Result<bool, btc_error> {
+        if self.n == 0 || items.is_empty() {
+            return Ok(false);
+        }
+        let mut queries: Vec<u64> = items.iter().map(|item| self.hash_to_range(item)).collect();
+        queries.sort_unstable();
+
+        let mut reader = BitReader::new(&self.data);
+        let mut value: u64 = 0;
+        let mut queries_iter = queries.into_iter().peekable();
+        for _ in 0..self.n {
+            value = value
+                .checked_add(reader.read_golomb_rice(BASIC_FILTER_P)?)
+                .ok_or(btc_error::InvalidByteSequence)?;
+            while let Some(query) = queries_iter.peek() {
+                if *query < value {
+                    queries_iter.next();
+                } else if *query == value {
+                    return Ok(true);
+                } else {
+                    break;
+                }
+            }
+            if queries_iter.peek().is_none() {
+                return Ok(false);
+            }
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes bits most-significant first
+    struct BitWriter {
+        data: Vec<u8>,
+        offset: usize,
+    }
+
+    impl BitWriter {
+        fn write_bit(&mut self, bit: bool) {
+            if self.offset % 8 == 0 {
+                self.data.push(0);
+            }
+            if bit {
+                let last = self.data.last_mut().unwrap();
+                *last |= 1 << (7 - self.offset % 8);
+            }
+            self.offset += 1;
+        }
+
+        fn write_golomb_rice(&mut self, value: u64, p: u8) {
+            for _ in 0..(value >> p) {
+                self.write_bit(true);
+            }
+            self.write_bit(false);
+            for i in (0..p).rev() {
+                self.write_bit((value >> i) & 1 == 1);
+            }
+        }
+    }
+
+    /// Build the basic filter of `items` for the block `block_hash`
+    fn make_filter(block_hash: &Sha256dHash, items: &[&[u8]]) -> Vec<u8> {
+        assert!(items.len() < 0xfd);
+        let n = items.len() as u64;
+        let hasher = BlockFilter::from_bytes(block_hash, &[n as u8]).unwrap();
+        let mut values: Vec<u64> = items.iter().map(|i| hasher.hash_to_range(i)).collect();
+        values.sort_unstable();
+        values.dedup();
+
+        let mut writer = BitWriter {
+            data: vec![],
+            offset: 0,
+        };
+        let mut last = 0;
+        for value in values.iter() {
+            writer.write_golomb_rice(value - last, BASIC_FILTER_P);
+            last = *value;
+        }
+        let mut bytes = vec![values.len() as u8];
+        bytes.append(&mut writer.data);
+        bytes
+    }
+
+    #[test]
+    fn test_compact_size() {
+        assert_eq!(read_compact_size(&[0x00]).unwrap(), (0, 1));
+        assert_eq!(read_compact_size(&[0xfc]).unwrap(), (0xfc, 1));
+        assert_eq!(read_compact_size(&[0xfd, 0x34, 0x12]).unwrap(), (0x1234, 3));
+        assert_eq!(
+            read_compact_size(&[0xfe, 0x78, 0x56, 0x34, 0x12]).unwrap(),
+            (0x12345678, 5)
+        );
+        assert!(read_compact_size(&[]).is_err());
+        assert!(read_compact_size(&[0xfd, 0x34]).is_err());
+    }
+
+    #[test]
+    fn test_match_any() {
+        let block_hash = Sha256dHash([0x11; 32]);
+        let scripts: Vec<Vec<u8>> = (0..20u8).map(|i| vec![0x76, 0xa9, 0x14, i]).collect();
+        let items: Vec<&[u8]> = scripts.iter().map(|s| s.as_slice()).collect();
+        let filter =
+            BlockFilter::from_bytes(&block_hash, &make_filter(&block_hash, &items)).unwrap();
+        assert_eq!(filter.len(), 20);
+
+        for item in items.iter() {
+            assert!(filter.match_any(&[item]).unwrap());
+        }
+        assert!(filter.match_any(&[&[0x00, 0x14, 0xff], items[7]]).unwrap());
+        assert!(!filter.match_any(&[&[0x00, 0x14, 0xff]]).unwrap());
+        assert!(!filter.match_any(&[]).unwrap());
+
+        // the same filter under a different block's key doesn't match
+        let other_hash = Sha256dHash([0x22; 32]);
+        let other =
+            BlockFilter::from_bytes(&other_hash, &make_filter(&block_hash, &items)).unwrap();
+        assert!(!items
+            .iter()
+            .all(|item| other.match_any(&[item]).unwrap_or(false)));
+
+        // the empty filter matches nothing
+        let empty = BlockFilter::from_bytes(&block_hash, &[0x00]).unwrap();
+        assert!(empty.is_empty());
+        assert!(!empty.match_any(&items).unwrap());
+
+        // a truncated filter is an error
+        let mut truncated = make_filter(&block_hash, &items);
+        truncated.truncate(2);
+        let truncated = BlockFilter::from_bytes(&block_hash, &truncated).unwrap();
+        assert!(truncated.match_any(&[&[0x00, 0x14, 0xff]]).is_err());
+    }
+}
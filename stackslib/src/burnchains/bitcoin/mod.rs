@@ -33,11 +33,13 @@ use crate::util_lib::db::Error as db_error;
 
 pub mod address;
 pub mod bits;
+pub mod blockfilter;
 pub mod blocks;
 pub mod indexer;
 pub mod keys;
 pub mod messages;
 pub mod network;
+pub mod op_builder;
 pub mod spv;
 
 pub type PeerMessage = stacks_common::deps_common::bitcoin::network::message::NetworkMessage;
@@ -0,0 +1,888 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Construction of the Bitcoin transactions that carry burnchain operations.
+//!
+//! `BurnOpBuilder` turns a `BlockstackOperationType` and a set of spendable outputs into a
+//! signed transaction, laid out the way the burnchain parser expects it: the `OP_RETURN` output
+//! with the magic bytes and the serialized operation first, then the operation's own outputs
+//! (block commit payouts, the `PreStx` output, the recipient of a `TransferStx`, ...), then the
+//! change.  Fees are charged per serialized byte, as the node does.
+//!
+//! Inputs are spent in the order they are given, until they cover the outputs and the fee.
+//! Operations that must be funded by a `PreStx` output (`StackStx`, `TransferStx`, `DelegateStx`
+//! and `VoteForAggregateKey`) need that output to come first.  All inputs signal
+//! replace-by-fee, and `build_replacement()` makes a transaction that replaces an unconfirmed
+//! one under the BIP-125 rules.
+//!
+//! The builder doesn't talk to a Bitcoin node: a `BurnOpBroadcaster` sends the transactions it
+//! makes.  Wallets that hold their own keys use `build_unsigned()` instead, and sign the
+//! resulting transaction from its PSBT (BIP-174) encoding.
+
+use std::{error, fmt};
+
+use stacks_common::codec::{Error as codec_error, StacksMessageCodec};
+use stacks_common::deps_common::bitcoin::blockdata::opcodes;
+use stacks_common::deps_common::bitcoin::blockdata::script::{Builder, Script};
+use stacks_common::deps_common::bitcoin::blockdata::transaction::{
+    OutPoint, Transaction, TxIn, TxOut,
+};
+use stacks_common::deps_common::bitcoin::network::encodable::VarInt;
+use stacks_common::deps_common::bitcoin::network::serialize::serialize;
+use stacks_common::deps_common::bitcoin::util::hash::Sha256dHash;
+use stacks_common::util::hash::{to_hex, Hash160};
+use stacks_common::util::secp256k1::{MessageSignature, Secp256k1PrivateKey, Secp256k1PublicKey};
+
+use crate::burnchains::bitcoin::address::{LegacyBitcoinAddress, SegwitBitcoinAddress};
+use crate::burnchains::{MagicBytes, PrivateKey, PublicKey, Txid};
+use crate::chainstate::burn::operations::BlockstackOperationType;
+use crate::chainstate::stacks::address::PoxAddress;
+
+/// Outputs worth less than this aren't relayed by Bitcoin nodes
+pub const DUST_UTXO_LIMIT: u64 = 5500;
+/// Fee rate (sats/byte) that a replacement must pay on top of the fee of the transaction it
+/// replaces (BIP-125's incremental relay fee)
+pub const INCREMENTAL_RELAY_FEE_RATE: u64 = 1;
+/// Size of the largest operation that spends a `PreStx` output.  The `PreStx` output is worth
+/// enough to pay for it at the `PreStx`'s fee rate.
+pub const STACKING_OP_ESTIMATED_SIZE: u64 = 250;
+/// Largest signature data of a p2pkh input: the pushes of a DER signature with its sighash type,
+/// and of an uncompressed public key
+const P2PKH_SCRIPT_SIG_MAX_SIZE: u64 = 1 + 73 + 1 + 65;
+/// Largest witness of a p2wpkh input: the item count, and a DER signature with its sighash type
+/// and a compressed public key, with their lengths
+const P2WPKH_WITNESS_MAX_SIZE: u64 = 1 + 1 + 73 + 1 + 33;
+
+const PSBT_MAGIC: &[u8] = b"psbt\xff";
+const PSBT_GLOBAL_UNSIGNED_TX: u8 = 0x00;
+const PSBT_IN_NON_WITNESS_UTXO: u8 = 0x00;
+const PSBT_IN_WITNESS_UTXO: u8 = 0x01;
+const PSBT_SEPARATOR: u8 = 0x00;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The operation can't be sent in a transaction
+    InvalidOperation(String),
+    /// The inputs are worth less than the outputs and the fee
+    InsufficientFunds { required: u64, available: u64 },
+    /// The operation failed to serialize
+    SerializationError(codec_error),
+    /// An input couldn't be signed
+    SigningError(String),
+    /// The transaction couldn't be sent to the Bitcoin node
+    BroadcastError(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::InvalidOperation(ref msg) => write!(f, "Invalid operation: {}", msg),
+            Error::InsufficientFunds {
+                required,
+                available,
+            } => write!(
+                f,
+                "Insufficient funds: {} sats required, {} available",
+                required, available
+            ),
+            Error::SerializationError(ref e) => fmt::Display::fmt(e, f),
+            Error::SigningError(ref msg) => write!(f, "Failed to sign input: {}", msg),
+            Error::BroadcastError(ref msg) => write!(f, "Failed to broadcast: {}", msg),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn cause(&self) -> Option<&dyn error::Error> {
+        match *self {
+            Error::SerializationError(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<codec_error> for Error {
+    fn from(e: codec_error) -> Error {
+        Error::SerializationError(e)
+    }
+}
+
+/// An output that can be spent by the key that signs the operation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Utxo {
+    pub txid: Sha256dHash,
+    pub vout: u32,
+    pub script_pub_key: Script,
+    pub amount: u64,
+}
+
+impl Utxo {
+    fn is_p2wpkh(&self) -> bool {
+        let script = self.script_pub_key.as_bytes();
+        script.len() == 22 && script[0..2] == [0x00, 0x14]
+    }
+}
+
+/// What a transaction spends
+#[derive(Debug, Clone, PartialEq)]
+pub struct BurnOpFees {
+    /// Serialized size of the transaction, in bytes
+    pub tx_size: u64,
+    /// Fee rate the fee was computed at, in sats/byte
+    pub fee_rate: u64,
+    /// Fee paid to the Bitcoin miner
+    pub fee: u64,
+    /// Value of the operation's outputs (burns, payouts and dust outputs)
+    pub outputs: u64,
+    /// Value returned to the signer
+    pub change: u64,
+}
+
+impl BurnOpFees {
+    /// Everything the transaction spends apart from the change
+    pub fn total_spent(&self) -> u64 {
+        self.outputs + self.fee
+    }
+}
+
+/// A signed transaction that carries a burnchain operation
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignedBurnOp {
+    pub op: BlockstackOperationType,
+    pub tx: Transaction,
+    pub txid: Txid,
+    /// The outputs the transaction spends, in input order
+    pub inputs: Vec<Utxo>,
+    pub fees: BurnOpFees,
+}
+
+impl SignedBurnOp {
+    pub fn serialize(&self) -> Vec<u8> {
+        serialize(&self.tx).expect("BUG: failed to serialize to a vec")
+    }
+
+    pub fn to_hex(&self) -> String {
+        to_hex(&self.serialize())
+    }
+}
+
+/// An unsigned transaction that carries a burnchain operation, for a wallet to sign
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnsignedBurnOp {
+    pub op: BlockstackOperationType,
+    pub tx: Transaction,
+    /// The outputs the transaction spends, in input order
+    pub inputs: Vec<Utxo>,
+    /// The fees, where `tx_size` is the largest size that the signed transaction can have
+    pub fees: BurnOpFees,
+}
+
+impl UnsignedBurnOp {
+    /// Encode the transaction as a PSBT.  p2wpkh inputs carry the output they spend.  Other
+    /// inputs carry the transaction whose output they spend, which must be in `previous_txs`.
+    pub fn to_psbt(&self, previous_txs: &[Transaction]) -> Result<Vec<u8>, Error> {
+        let mut psbt = PSBT_MAGIC.to_vec();
+        write_psbt_entry(
+            &mut psbt,
+            &[PSBT_GLOBAL_UNSIGNED_TX],
+            &serialize(&self.tx).expect("BUG: failed to serialize to a vec"),
+        );
+        psbt.push(PSBT_SEPARATOR);
+
+        for utxo in self.inputs.iter() {
+            let previous_tx = previous_txs.iter().find(|tx| tx.txid() == utxo.txid);
+            if let Some(previous_tx) = previous_tx {
+                write_psbt_entry(
+                    &mut psbt,
+                    &[PSBT_IN_NON_WITNESS_UTXO],
+                    &serialize(previous_tx).expect("BUG: failed to serialize to a vec"),
+                );
+            } else if !utxo.is_p2wpkh() {
+                return Err(Error::InvalidOperation(format!(
+                    "missing the transaction that input {}:{} spends",
+                    utxo.txid.be_hex_string(),
+                    utxo.vout
+                )));
+            }
+            if utxo.is_p2wpkh() {
+                let spent_output = TxOut {
+                    value: utxo.amount,
+                    script_pubkey: utxo.script_pub_key.clone(),
+                };
+                write_psbt_entry(
+                    &mut psbt,
+                    &[PSBT_IN_WITNESS_UTXO],
+                    &serialize(&spent_output).expect("BUG: failed to serialize to a vec"),
+                );
+            }
+            psbt.push(PSBT_SEPARATOR);
+        }
+
+        // nothing is known about the outputs
+        for _ in self.tx.output.iter() {
+            psbt.push(PSBT_SEPARATOR);
+        }
+        Ok(psbt)
+    }
+
+    /// Encode the transaction as a base64 PSBT, as Bitcoin Core's RPCs take it
+    pub fn to_psbt_base64(&self, previous_txs: &[Transaction]) -> Result<String, Error> {
+        Ok(base64::encode(self.to_psbt(previous_txs)?))
+    }
+}
+
+/// Append a PSBT key-value pair to `psbt`
+fn write_psbt_entry(psbt: &mut Vec<u8>, key: &[u8], value: &[u8]) {
+    psbt.extend(serialize(&VarInt(key.len() as u64)).expect("BUG: failed to serialize to a vec"));
+    psbt.extend_from_slice(key);
+    psbt.extend(serialize(&VarInt(value.len() as u64)).expect("BUG: failed to serialize to a vec"));
+    psbt.extend_from_slice(value);
+}
+
+/// How `BurnOpBuilder::assemble()` finishes the transactions it makes
+enum Finish<'a> {
+    /// Sign them, paying change to the signer
+    Sign {
+        signing_key: &'a Secp256k1PrivateKey,
+        public_key: &'a Secp256k1PublicKey,
+    },
+    /// Leave them unsigned, paying change to `change_script_pubkey`
+    Unsigned { change_script_pubkey: &'a Script },
+}
+
+/// Sends signed burnchain operations to the Bitcoin network
+pub trait BurnOpBroadcaster {
+    fn broadcast(&self, op: &SignedBurnOp) -> Result<Txid, Error>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BurnOpBuilder {
+    magic_bytes: MagicBytes,
+    segwit_change: bool,
+}
+
+impl BurnOpBuilder {
+    pub fn new(magic_bytes: MagicBytes) -> BurnOpBuilder {
+        BurnOpBuilder {
+            magic_bytes,
+            segwit_change: false,
+        }
+    }
+
+    /// Pay change to a p2wpkh output instead of a p2pkh output.  Only valid from epoch 2.1 on.
+    pub fn with_segwit_change(mut self, segwit_change: bool) -> BurnOpBuilder {
+        self.segwit_change = segwit_change;
+        self
+    }
+
+    /// The outputs that carry `op`, before the change output
+    pub fn op_outputs(
+        &self,
+        op: &BlockstackOperationType,
+        fee_rate: u64,
+    ) -> Result<Vec<TxOut>, Error> {
+        let mut op_bytes = self.magic_bytes.as_bytes().to_vec();
+        let op_return_value = match op {
+            BlockstackOperationType::LeaderBlockCommit(ref commit) => {
+                commit.consensus_serialize(&mut op_bytes)?;
+                if commit.sunset_burn > 0 {
+                    commit.sunset_burn.max(DUST_UTXO_LIMIT)
+                } else {
+                    0
+                }
+            }
+            BlockstackOperationType::LeaderKeyRegister(ref op) => {
+                op.consensus_serialize(&mut op_bytes)?;
+                0
+            }
+            BlockstackOperationType::PreStx(ref op) => {
+                op.consensus_serialize(&mut op_bytes)?;
+                0
+            }
+            BlockstackOperationType::StackStx(ref op) => {
+                op.consensus_serialize(&mut op_bytes)?;
+                0
+            }
+            BlockstackOperationType::TransferStx(ref op) => {
+                op.consensus_serialize(&mut op_bytes)?;
+                0
+            }
+            BlockstackOperationType::DelegateStx(ref op) => {
+                op.consensus_serialize(&mut op_bytes)?;
+                0
+            }
+            BlockstackOperationType::VoteForAggregateKey(ref op) => {
+                op.consensus_serialize(&mut op_bytes)?;
+                0
+            }
+        };
+
+        let mut outputs = vec![TxOut {
+            value: op_return_value,
+            script_pubkey: Builder::new()
+                .push_opcode(opcodes::All::OP_RETURN)
+                .push_slice(&op_bytes)
+                .into_script(),
+        }];
+
+        match op {
+            BlockstackOperationType::LeaderBlockCommit(ref commit) => {
+                if commit.commit_outs.is_empty() {
+                    return Err(Error::InvalidOperation(
+                        "block commit has no commit outputs".into(),
+                    ));
+                }
+                let amount_per_output = commit.burn_fee / commit.commit_outs.len() as u64;
+                for commit_to in commit.commit_outs.iter() {
+                    outputs.push(commit_to.to_bitcoin_tx_out(amount_per_output));
+                }
+            }
+            BlockstackOperationType::PreStx(ref op) => {
+                outputs.push(
+                    PoxAddress::Standard(op.output.clone(), None)
+                        .to_bitcoin_tx_out(Self::pre_stx_output_value(fee_rate)),
+                );
+            }
+            BlockstackOperationType::StackStx(ref op) => {
+                outputs.push(op.reward_addr.to_bitcoin_tx_out(DUST_UTXO_LIMIT));
+            }
+            BlockstackOperationType::TransferStx(ref op) => {
+                outputs.push(
+                    PoxAddress::Standard(op.recipient.clone(), None)
+                        .to_bitcoin_tx_out(DUST_UTXO_LIMIT),
+                );
+            }
+            BlockstackOperationType::DelegateStx(ref op) => {
+                outputs.push(
+                    PoxAddress::Standard(op.delegate_to.clone(), None)
+                        .to_bitcoin_tx_out(DUST_UTXO_LIMIT),
+                );
+            }
+            BlockstackOperationType::LeaderKeyRegister(_)
+            | BlockstackOperationType::VoteForAggregateKey(_) => {}
+        }
+        Ok(outputs)
+    }
+
+    /// Value of a `PreStx` output, which funds the stacking operation that spends it
+    pub fn pre_stx_output_value(fee_rate: u64) -> u64 {
+        DUST_UTXO_LIMIT + STACKING_OP_ESTIMATED_SIZE * fee_rate
+    }
+
+    /// Block commits and key registrations must have a change output
+    fn requires_change(op: &BlockstackOperationType) -> bool {
+        matches!(
+            op,
+            BlockstackOperationType::LeaderBlockCommit(_)
+                | BlockstackOperationType::LeaderKeyRegister(_)
+        )
+    }
+
+    /// What sending `op` at `fee_rate` would spend, if signed by the owner of `public_key`
+    pub fn estimate_fees(
+        &self,
+        op: &BlockstackOperationType,
+        utxos: &[Utxo],
+        fee_rate: u64,
+        public_key: &Secp256k1PublicKey,
+    ) -> Result<BurnOpFees, Error> {
+        // signatures made by any key have the same size
+        let dummy_key = Secp256k1PrivateKey::from_seed(&[0u8; 32]);
+        let finish = Finish::Sign {
+            signing_key: &dummy_key,
+            public_key,
+        };
+        let (_, _, fees) = self.assemble(op, &[], utxos, fee_rate, (0, 0), &finish)?;
+        Ok(fees)
+    }
+
+    /// Build and sign a transaction that sends `op` at `fee_rate`, spending `utxos` (in order)
+    pub fn build(
+        &self,
+        op: BlockstackOperationType,
+        utxos: &[Utxo],
+        fee_rate: u64,
+        private_key: &Secp256k1PrivateKey,
+    ) -> Result<SignedBurnOp, Error> {
+        let public_key = Secp256k1PublicKey::from_private(private_key);
+        let finish = Finish::Sign {
+            signing_key: private_key,
+            public_key: &public_key,
+        };
+        let (tx, inputs, fees) = self.assemble(&op, &[], utxos, fee_rate, (0, 0), &finish)?;
+        Ok(SignedBurnOp {
+            op,
+            txid: Txid::from_bitcoin_tx_hash(&tx.txid()),
+            tx,
+            inputs,
+            fees,
+        })
+    }
+
+    /// Build an unsigned transaction that sends `op` at `fee_rate`, spending `utxos` (in order)
+    /// and paying change to `change_script_pubkey`.  Only p2pkh and p2wpkh outputs can be spent.
+    /// The fee covers the largest signatures that the inputs can need, so the signed transaction
+    /// pays at least `fee_rate`.
+    pub fn build_unsigned(
+        &self,
+        op: BlockstackOperationType,
+        utxos: &[Utxo],
+        fee_rate: u64,
+        change_script_pubkey: &Script,
+    ) -> Result<UnsignedBurnOp, Error> {
+        let finish = Finish::Unsigned {
+            change_script_pubkey,
+        };
+        let (tx, inputs, fees) = self.assemble(&op, &[], utxos, fee_rate, (0, 0), &finish)?;
+        Ok(UnsignedBurnOp {
+            op,
+            tx,
+            inputs,
+            fees,
+        })
+    }
+
+    /// Build and sign a transaction that replaces `previous` while it is unconfirmed, sending `op`
+    /// (which may differ from `previous.op`) instead.  The replacement spends all of `previous`'s
+    /// inputs, plus as many of `extra_utxos` as it needs, and pays at least `fee_rate` and at
+    /// least `previous`'s fee plus the incremental relay fee for its own size.
+    pub fn build_replacement(
+        &self,
+        previous: &SignedBurnOp,
+        op: BlockstackOperationType,
+        extra_utxos: &[Utxo],
+        fee_rate: u64,
+        private_key: &Secp256k1PrivateKey,
+    ) -> Result<SignedBurnOp, Error> {
+        let public_key = Secp256k1PublicKey::from_private(private_key);
+        let fee_rate = fee_rate.max(previous.fees.fee_rate + INCREMENTAL_RELAY_FEE_RATE);
+        let finish = Finish::Sign {
+            signing_key: private_key,
+            public_key: &public_key,
+        };
+        let (tx, inputs, fees) = self.assemble(
+            &op,
+            &previous.inputs,
+            extra_utxos,
+            fee_rate,
+            (previous.fees.fee, INCREMENTAL_RELAY_FEE_RATE),
+            &finish,
+        )?;
+        Ok(SignedBurnOp {
+            op,
+            txid: Txid::from_bitcoin_tx_hash(&tx.txid()),
+            tx,
+            inputs,
+            fees,
+        })
+    }
+
+    /// Select inputs and make a transaction that sends `op`.  All of `required` are spent, and
+    /// then `optional` in order until the outputs and the fee are covered.  The fee is the larger
+    /// of `fee_rate` per byte and `min_fee.0` plus `min_fee.1` per byte.
+    fn assemble(
+        &self,
+        op: &BlockstackOperationType,
+        required: &[Utxo],
+        optional: &[Utxo],
+        fee_rate: u64,
+        min_fee: (u64, u64),
+        finish: &Finish,
+    ) -> Result<(Transaction, Vec<Utxo>, BurnOpFees), Error> {
+        let op_outputs = self.op_outputs(op, fee_rate)?;
+        let outputs_value: u64 = op_outputs.iter().map(|output| output.value).sum();
+        let change_reserve = if Self::requires_change(op) {
+            DUST_UTXO_LIMIT
+        } else {
+            0
+        };
+        let available: u64 = required
+            .iter()
+            .chain(optional.iter())
+            .map(|utxo| utxo.amount)
+            .sum();
+
+        // Each pass learns the size of the transaction with the inputs it selected.  Passes only
+        // add inputs, so this terminates once the fee is covered or the inputs run out.
+        let mut fee = min_fee.0;
+        loop {
+            let target = outputs_value + fee + change_reserve;
+            let mut inputs = required.to_vec();
+            let mut total: u64 = inputs.iter().map(|utxo| utxo.amount).sum();
+            for utxo in optional.iter() {
+                if total >= target && !inputs.is_empty() {
+                    break;
+                }
+                total += utxo.amount;
+                inputs.push(utxo.clone());
+            }
+            if total < target || inputs.is_empty() {
+                return Err(Error::InsufficientFunds {
+                    required: target,
+                    available,
+                });
+            }
+
+            let change = total - outputs_value - fee;
+            let (tx, tx_size) = match *finish {
+                Finish::Sign {
+                    signing_key,
+                    public_key,
+                } => {
+                    let tx = self.sign_tx(&op_outputs, &inputs, change, signing_key, public_key)?;
+                    let tx_size = serialize(&tx)
+                        .expect("BUG: failed to serialize to a vec")
+                        .len() as u64;
+                    (tx, tx_size)
+                }
+                Finish::Unsigned {
+                    change_script_pubkey,
+                } => {
+                    let change_output = (change >= DUST_UTXO_LIMIT).then(|| TxOut {
+                        value: change,
+                        script_pubkey: change_script_pubkey.clone(),
+                    });
+                    let tx = Self::unsigned_tx(&op_outputs, &inputs, change_output);
+                    let tx_size = serialize(&tx)
+                        .expect("BUG: failed to serialize to a vec")
+                        .len() as u64
+                        + Self::max_signatures_size(&inputs);
+                    (tx, tx_size)
+                }
+            };
+            let required_fee = (tx_size * fee_rate).max(min_fee.0 + tx_size * min_fee.1);
+            if fee >= required_fee {
+                // change below the dust limit is left to the Bitcoin miner
+                let change = if change >= DUST_UTXO_LIMIT { change } else { 0 };
+                let fees = BurnOpFees {
+                    tx_size,
+                    fee_rate,
+                    fee: total - outputs_value - change,
+                    outputs: outputs_value,
+                    change,
+                };
+                return Ok((tx, inputs, fees));
+            }
+            fee = required_fee;
+        }
+    }
+
+    /// The transaction with `outputs` and `change_output`, spending `inputs`, without signatures
+    fn unsigned_tx(
+        outputs: &[TxOut],
+        inputs: &[Utxo],
+        change_output: Option<TxOut>,
+    ) -> Transaction {
+        let mut output = outputs.to_vec();
+        output.extend(change_output);
+        let input = inputs
+            .iter()
+            .map(|utxo| TxIn {
+                previous_output: OutPoint {
+                    txid: utxo.txid,
+                    vout: utxo.vout,
+                },
+                script_sig: Script::new(),
+                sequence: 0xFFFFFFFD, // allow RBF
+                witness: vec![],
+            })
+            .collect();
+        Transaction {
+            input,
+            output,
+            version: 1,
+            lock_time: 0,
+        }
+    }
+
+    /// The most that signing a transaction that spends `inputs` can add to its size
+    fn max_signatures_size(inputs: &[Utxo]) -> u64 {
+        let has_witness = inputs.iter().any(|utxo| utxo.is_p2wpkh());
+        // the segwit marker and flag
+        let mut size = if has_witness { 2 } else { 0 };
+        for utxo in inputs.iter() {
+            size += if utxo.is_p2wpkh() {
+                P2WPKH_WITNESS_MAX_SIZE
+            } else if has_witness {
+                // an empty witness
+                P2PKH_SCRIPT_SIG_MAX_SIZE + 1
+            } else {
+                P2PKH_SCRIPT_SIG_MAX_SIZE
+            };
+        }
+        size
+    }
+
+    /// Sign a transaction with `outputs` and a change output worth `change`, spending `inputs`
+    fn sign_tx(
+        &self,
+        outputs: &[TxOut],
+        inputs: &[Utxo],
+        change: u64,
+        signing_key: &Secp256k1PrivateKey,
+        public_key: &Secp256k1PublicKey,
+    ) -> Result<Transaction, Error> {
+        let mut compressed_key = public_key.clone();
+        compressed_key.set_compressed(true);
+        let change_output = (change >= DUST_UTXO_LIMIT).then(|| {
+            if self.segwit_change {
+                let hash = Hash160::from_data(&compressed_key.to_bytes());
+                SegwitBitcoinAddress::to_p2wpkh_tx_out(&hash.0, change)
+            } else {
+                let hash = Hash160::from_data(&public_key.to_bytes());
+                LegacyBitcoinAddress::to_p2pkh_tx_out(&hash, change)
+            }
+        });
+        let mut tx = Self::unsigned_tx(outputs, inputs, change_output);
+
+        let sig_hash_all = 0x01;
+        for (i, utxo) in inputs.iter().enumerate() {
+            let sig_hash = if utxo.is_p2wpkh() {
+                tx.segwit_signature_hash(i, &utxo.script_pub_key, utxo.amount, sig_hash_all)
+            } else {
+                tx.signature_hash(i, &utxo.script_pub_key, sig_hash_all)
+            };
+            let signature = signing_key
+                .sign(sig_hash.as_bytes())
+                .map_err(|e| Error::SigningError(e.into()))?;
+            let sig_der = Self::to_der(&signature)?;
+            let sig_bytes = [&sig_der[..], &[sig_hash_all as u8][..]].concat();
+
+            if utxo.is_p2wpkh() {
+                tx.input[i].witness = vec![sig_bytes, compressed_key.to_bytes()];
+            } else {
+                tx.input[i].script_sig = Builder::new()
+                    .push_slice(&sig_bytes)
+                    .push_slice(&public_key.to_bytes())
+                    .into_script();
+            }
+        }
+        Ok(tx)
+    }
+
+    fn to_der(signature: &MessageSignature) -> Result<Vec<u8>, Error> {
+        let recoverable = signature
+            .to_secp256k1_recoverable()
+            .ok_or_else(|| Error::SigningError("invalid recoverable signature".into()))?;
+        Ok(recoverable.to_standard().serialize_der().to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stacks_common::types::chainstate::{BurnchainHeaderHash, StacksAddress};
+    use stacks_common::types::StacksEpochId;
+    use stacks_common::util::vrf::{VRFPrivateKey, VRFPublicKey};
+
+    use super::*;
+    use crate::burnchains::bitcoin::blocks::BitcoinBlockParser;
+    use crate::burnchains::bitcoin::BitcoinNetworkType;
+    use crate::burnchains::{BurnchainBlockHeader, BurnchainTransaction};
+    use crate::chainstate::burn::operations::{DelegateStxOp, LeaderKeyRegisterOp, PreStxOp};
+
+    fn utxo(public_key: &Secp256k1PublicKey, n: u8, amount: u64, segwit: bool) -> Utxo {
+        let mut public_key = public_key.clone();
+        if segwit {
+            public_key.set_compressed(true);
+        }
+        let hash = Hash160::from_data(&public_key.to_bytes());
+        let script_pub_key = if segwit {
+            SegwitBitcoinAddress::to_p2wpkh_tx_out(&hash.0, amount).script_pubkey
+        } else {
+            LegacyBitcoinAddress::to_p2pkh_tx_out(&hash, amount).script_pubkey
+        };
+        Utxo {
+            txid: Sha256dHash([n; 32]),
+            vout: n as u32,
+            script_pub_key,
+            amount,
+        }
+    }
+
+    #[test]
+    fn test_build_key_register() {
+        let magic_bytes = MagicBytes([105, 100]); // "id"
+        let builder = BurnOpBuilder::new(magic_bytes.clone());
+        let private_key = Secp256k1PrivateKey::from_seed(&[1u8; 32]);
+        let public_key = Secp256k1PublicKey::from_private(&private_key);
+        let vrf_key = VRFPublicKey::from_private(&VRFPrivateKey::new());
+        let op = BlockstackOperationType::LeaderKeyRegister(LeaderKeyRegisterOp::new(&vrf_key));
+        let utxos = vec![
+            utxo(&public_key, 1, 6_000, false),
+            utxo(&public_key, 2, 20_000, false),
+        ];
+
+        let estimate = builder.estimate_fees(&op, &utxos, 10, &public_key).unwrap();
+        let signed = builder.build(op.clone(), &utxos, 10, &private_key).unwrap();
+        assert_eq!(signed.fees.tx_size, signed.serialize().len() as u64);
+        assert!(signed.fees.fee >= signed.fees.tx_size * 10);
+        // signatures differ in size by a few bytes at most
+        assert!(estimate.fee.abs_diff(signed.fees.fee) <= 30);
+        // the first input doesn't cover the fee and the change
+        assert_eq!(signed.inputs, utxos);
+        assert_eq!(
+            signed.fees.fee + signed.fees.change,
+            utxos.iter().map(|u| u.amount).sum::<u64>()
+        );
+
+        // the transaction parses back into the operation
+        let parser = BitcoinBlockParser::new(BitcoinNetworkType::Testnet, magic_bytes.clone());
+        let btc_tx = parser
+            .parse_tx(&signed.tx, 1, StacksEpochId::Epoch25)
+            .unwrap();
+        assert_eq!(btc_tx.txid, signed.txid);
+        let header = BurnchainBlockHeader {
+            block_height: 100,
+            block_hash: BurnchainHeaderHash([0x11; 32]),
+            parent_block_hash: BurnchainHeaderHash([0x10; 32]),
+            num_txs: 2,
+            timestamp: 0,
+        };
+        let parsed =
+            LeaderKeyRegisterOp::from_tx(&header, &BurnchainTransaction::Bitcoin(btc_tx)).unwrap();
+        assert_eq!(parsed.public_key, vrf_key);
+
+        // not enough to pay for the change output
+        let err = builder
+            .build(op, &[utxo(&public_key, 3, 6_000, false)], 10, &private_key)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::InsufficientFunds {
+                available: 6_000,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_build_replacement() {
+        let builder = BurnOpBuilder::new(MagicBytes([105, 100])).with_segwit_change(true);
+        let private_key = Secp256k1PrivateKey::from_seed(&[2u8; 32]);
+        let public_key = Secp256k1PublicKey::from_private(&private_key);
+        let op = BlockstackOperationType::PreStx(PreStxOp {
+            output: StacksAddress::burn_address(false),
+            txid: Txid([0u8; 32]),
+            vtxindex: 0,
+            block_height: 0,
+            burn_header_hash: BurnchainHeaderHash::zero(),
+        });
+        let utxos = vec![
+            utxo(&public_key, 1, 50_000, true),
+            utxo(&public_key, 2, 50_000, true),
+        ];
+
+        let signed = builder.build(op.clone(), &utxos, 5, &private_key).unwrap();
+        assert_eq!(signed.inputs, utxos[0..1].to_vec());
+        assert_eq!(
+            signed.tx.output[1].value,
+            BurnOpBuilder::pre_stx_output_value(5)
+        );
+        assert!(signed
+            .tx
+            .input
+            .iter()
+            .all(|input| input.sequence < 0xFFFFFFFE));
+
+        // a replacement spends the same inputs and pays more, even if asked for a lower rate
+        let replacement = builder
+            .build_replacement(&signed, op, &utxos[1..], 1, &private_key)
+            .unwrap();
+        assert_ne!(replacement.txid, signed.txid);
+        assert_eq!(replacement.inputs, signed.inputs);
+        assert_eq!(replacement.fees.fee_rate, 6);
+        assert!(
+            replacement.fees.fee
+                >= signed.fees.fee + replacement.fees.tx_size * INCREMENTAL_RELAY_FEE_RATE
+        );
+    }
+
+    #[test]
+    fn test_build_unsigned() {
+        let builder = BurnOpBuilder::new(MagicBytes([105, 100]));
+        let private_key = Secp256k1PrivateKey::from_seed(&[3u8; 32]);
+        let public_key = Secp256k1PublicKey::from_private(&private_key);
+        let op = BlockstackOperationType::DelegateStx(DelegateStxOp {
+            sender: StacksAddress::burn_address(false),
+            delegate_to: StacksAddress::burn_address(false),
+            reward_addr: None,
+            delegated_ustx: 1_000_000,
+            until_burn_height: None,
+            txid: Txid([0u8; 32]),
+            vtxindex: 0,
+            block_height: 0,
+            burn_header_hash: BurnchainHeaderHash::zero(),
+        });
+
+        // the pre-stx output, and a legacy output from a previous transaction
+        let mut funding = utxo(&public_key, 0, 50_000, false);
+        let previous_tx = Transaction {
+            version: 1,
+            lock_time: 0,
+            input: vec![],
+            output: vec![TxOut {
+                value: funding.amount,
+                script_pubkey: funding.script_pub_key.clone(),
+            }],
+        };
+        funding.txid = previous_tx.txid();
+        let utxos = vec![utxo(&public_key, 1, 6_000, true), funding];
+        let change_script = utxos[1].script_pub_key.clone();
+
+        let unsigned = builder
+            .build_unsigned(op.clone(), &utxos, 10, &change_script)
+            .unwrap();
+        assert_eq!(unsigned.inputs, utxos);
+        assert!(unsigned
+            .tx
+            .input
+            .iter()
+            .all(|input| input.witness.is_empty()
+                && input.script_sig.is_empty()
+                && input.sequence < 0xFFFFFFFE));
+        assert_eq!(
+            unsigned.tx.output.last().unwrap().script_pubkey,
+            change_script
+        );
+
+        // the signed transaction is no bigger than estimated, and has the same outputs
+        let signed = builder.build(op, &utxos, 10, &private_key).unwrap();
+        assert_eq!(signed.inputs, unsigned.inputs);
+        assert_eq!(signed.tx.output.len(), unsigned.tx.output.len());
+        assert_eq!(signed.tx.output[..2], unsigned.tx.output[..2]);
+        assert!(signed.fees.tx_size <= unsigned.fees.tx_size);
+        assert!(unsigned.fees.fee >= unsigned.fees.tx_size * 10);
+
+        // legacy inputs need the transaction they spend from
+        assert!(unsigned.to_psbt(&[]).is_err());
+        let psbt = unsigned.to_psbt(&[previous_tx.clone()]).unwrap();
+        let unsigned_tx = serialize(&unsigned.tx).unwrap();
+        let mut expected_prefix = b"psbt\xff\x01\x00".to_vec();
+        expected_prefix.extend(serialize(&VarInt(unsigned_tx.len() as u64)).unwrap());
+        expected_prefix.extend(unsigned_tx);
+        expected_prefix.push(PSBT_SEPARATOR);
+        assert!(psbt.starts_with(&expected_prefix));
+        let previous_tx_bytes = serialize(&previous_tx).unwrap();
+        assert!(psbt
+            .windows(previous_tx_bytes.len())
+            .any(|window| window == previous_tx_bytes.as_slice()));
+        // one separator per output
+        assert!(psbt.ends_with(&vec![PSBT_SEPARATOR; unsigned.tx.output.len() + 1]));
+        assert!(unsigned
+            .to_psbt_base64(&[previous_tx])
+            .unwrap()
+            .starts_with("cHNidP8"));
+    }
+}
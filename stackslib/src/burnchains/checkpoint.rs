@@ -0,0 +1,374 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Burnchain indexer checkpoints.  A checkpoint is a consistent copy of the SPV headers DB and
+//! the burnchain DB, taken while the node runs.  Restoring one before a reindex (or after a crash
+//! that damaged the burnchain state) means that only the Bitcoin headers and blocks after the
+//! checkpoint need to be downloaded and parsed again.
+//!
+//! The sortition DB and the chainstate are derived from the burnchain DB, and are not part of a
+//! checkpoint.  Restore a checkpoint only into a node whose sortition DB and chainstate are being
+//! rebuilt, or are no further along than the checkpoint.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rusqlite::OpenFlags;
+use stacks_common::types::chainstate::BurnchainHeaderHash;
+use stacks_common::types::sqlite::NO_PARAMS;
+use stacks_common::util::get_epoch_time_secs;
+
+use crate::burnchains::db::BurnchainDB;
+use crate::burnchains::Error;
+use crate::util_lib::db::{query_row, sqlite_open, Error as db_error};
+
+/// The name of a checkpoint's metadata file
+pub const CHECKPOINT_METADATA_FILE: &str = "checkpoint.json";
+/// The name of a checkpoint's copy of the SPV headers DB
+pub const CHECKPOINT_HEADERS_FILE: &str = "headers.sqlite";
+/// The name of a checkpoint's copy of the burnchain DB
+pub const CHECKPOINT_BURNCHAIN_DB_FILE: &str = "burnchain.sqlite";
+
+/// What a checkpoint holds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BurnchainCheckpoint {
+    /// The height of the burnchain DB's canonical tip
+    pub block_height: u64,
+    /// The hash of the burnchain DB's canonical tip
+    pub block_hash: BurnchainHeaderHash,
+    /// The height of the highest SPV header
+    pub headers_height: u64,
+    /// When the checkpoint was taken, in seconds since the epoch
+    pub created_at: u64,
+}
+
+/// Copy a SQLite DB to `dest` as of one transaction, even while it is being written to
+fn copy_db(src: &str, dest: &Path) -> Result<(), Error> {
+    let conn = sqlite_open(src, OpenFlags::SQLITE_OPEN_READ_ONLY, false)?;
+    let dest = dest.to_str().ok_or_else(|| {
+        Error::FSError(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "Invalid checkpoint path",
+        ))
+    })?;
+    conn.execute("VACUUM INTO ?1", [dest])?;
+    // the copy starts out with a rollback journal; switch it to WAL mode like the original, since
+    //  read-only connections can't
+    sqlite_open(dest, OpenFlags::SQLITE_OPEN_READ_WRITE, false)?;
+    Ok(())
+}
+
+/// Replace `dest` with `src`, along with any write-ahead log that belonged to the old `dest`
+fn replace_db(src: &Path, dest: &str) -> Result<(), Error> {
+    for suffix in ["-wal", "-shm"] {
+        let path = format!("{dest}{suffix}");
+        if fs::metadata(&path).is_ok() {
+            fs::remove_file(&path).map_err(Error::FSError)?;
+        }
+    }
+    if let Some(parent) = Path::new(dest).parent() {
+        fs::create_dir_all(parent).map_err(Error::FSError)?;
+    }
+    let staged = format!("{dest}.restore");
+    fs::copy(src, &staged).map_err(Error::FSError)?;
+    fs::rename(&staged, dest).map_err(Error::FSError)
+}
+
+fn get_headers_height(headers_path: &Path) -> Result<u64, Error> {
+    let conn = sqlite_open(headers_path, OpenFlags::SQLITE_OPEN_READ_ONLY, false)?;
+    let height: Option<u64> = query_row(
+        &conn,
+        "SELECT IFNULL(MAX(height),0) FROM headers",
+        NO_PARAMS,
+    )?;
+    Ok(height.unwrap_or(0))
+}
+
+impl BurnchainCheckpoint {
+    /// Take a checkpoint of the SPV headers DB at `headers_path` and the burnchain DB at
+    /// `burnchain_db_path`, into the directory `checkpoint_dir`, which must not exist.  The
+    /// directory only appears once the checkpoint is complete.
+    pub fn create(
+        headers_path: &str,
+        burnchain_db_path: &str,
+        checkpoint_dir: &Path,
+    ) -> Result<BurnchainCheckpoint, Error> {
+        if fs::metadata(checkpoint_dir).is_ok() {
+            return Err(Error::FSError(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("{} already exists", checkpoint_dir.display()),
+            )));
+        }
+        let staging_dir = checkpoint_dir.with_extension("tmp");
+        if fs::metadata(&staging_dir).is_ok() {
+            fs::remove_dir_all(&staging_dir).map_err(Error::FSError)?;
+        }
+        fs::create_dir_all(&staging_dir).map_err(Error::FSError)?;
+
+        // copy the burnchain DB first, so the headers cover every block in it
+        let tip = BurnchainDB::open(burnchain_db_path, false)?.get_canonical_chain_tip()?;
+        copy_db(
+            burnchain_db_path,
+            &staging_dir.join(CHECKPOINT_BURNCHAIN_DB_FILE),
+        )?;
+        let headers_copy = staging_dir.join(CHECKPOINT_HEADERS_FILE);
+        copy_db(headers_path, &headers_copy)?;
+
+        let headers_height = get_headers_height(&headers_copy)?;
+        if headers_height < tip.block_height {
+            return Err(Error::MissingHeaders);
+        }
+
+        let checkpoint = BurnchainCheckpoint {
+            block_height: tip.block_height,
+            block_hash: tip.block_hash,
+            headers_height,
+            created_at: get_epoch_time_secs(),
+        };
+        let metadata = serde_json::to_vec_pretty(&checkpoint)
+            .map_err(|e| Error::DBError(db_error::SerializationError(e)))?;
+        fs::write(staging_dir.join(CHECKPOINT_METADATA_FILE), metadata).map_err(Error::FSError)?;
+        fs::rename(&staging_dir, checkpoint_dir).map_err(Error::FSError)?;
+
+        info!(
+            "Created burnchain checkpoint";
+            "path" => %checkpoint_dir.display(),
+            "block_height" => checkpoint.block_height,
+            "block_hash" => %checkpoint.block_hash,
+            "headers_height" => checkpoint.headers_height,
+        );
+        Ok(checkpoint)
+    }
+
+    /// Load the metadata of the checkpoint in `checkpoint_dir`
+    pub fn load(checkpoint_dir: &Path) -> Result<BurnchainCheckpoint, Error> {
+        let metadata =
+            fs::read(checkpoint_dir.join(CHECKPOINT_METADATA_FILE)).map_err(Error::FSError)?;
+        serde_json::from_slice(&metadata)
+            .map_err(|e| Error::DBError(db_error::SerializationError(e)))
+    }
+
+    /// Restore the checkpoint in `checkpoint_dir` to the SPV headers DB at `headers_path` and
+    /// the burnchain DB at `burnchain_db_path`.  Existing DBs are only replaced if `overwrite`
+    /// is set.  The node must not be running.
+    pub fn restore(
+        checkpoint_dir: &Path,
+        headers_path: &str,
+        burnchain_db_path: &str,
+        overwrite: bool,
+    ) -> Result<BurnchainCheckpoint, Error> {
+        let checkpoint = BurnchainCheckpoint::load(checkpoint_dir)?;
+        let burnchain_db_copy = checkpoint_dir.join(CHECKPOINT_BURNCHAIN_DB_FILE);
+        let headers_copy = checkpoint_dir.join(CHECKPOINT_HEADERS_FILE);
+
+        // make sure the checkpoint is what its metadata says it is
+        let burnchain_db_copy_str = burnchain_db_copy.to_str().ok_or_else(|| {
+            Error::FSError(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "Invalid checkpoint path",
+            ))
+        })?;
+        let tip = BurnchainDB::open(burnchain_db_copy_str, false)?.get_canonical_chain_tip()?;
+        if tip.block_height != checkpoint.block_height || tip.block_hash != checkpoint.block_hash {
+            return Err(Error::UnknownBlock(tip.block_hash));
+        }
+        if get_headers_height(&headers_copy)? != checkpoint.headers_height {
+            return Err(Error::MissingHeaders);
+        }
+
+        if !overwrite {
+            for path in [headers_path, burnchain_db_path] {
+                if fs::metadata(path).is_ok() {
+                    return Err(Error::FSError(std::io::Error::new(
+                        std::io::ErrorKind::AlreadyExists,
+                        format!("{path} already exists"),
+                    )));
+                }
+            }
+        }
+
+        replace_db(&headers_copy, headers_path)?;
+        replace_db(&burnchain_db_copy, burnchain_db_path)?;
+
+        info!(
+            "Restored burnchain checkpoint";
+            "path" => %checkpoint_dir.display(),
+            "block_height" => checkpoint.block_height,
+            "block_hash" => %checkpoint.block_hash,
+            "headers_height" => checkpoint.headers_height,
+        );
+        Ok(checkpoint)
+    }
+}
+
+/// The checkpoints in the subdirectories of `root`, by burnchain block height, ascending.
+/// Subdirectories that don't hold a complete checkpoint are ignored.
+pub fn list_checkpoints(root: &Path) -> Result<Vec<(PathBuf, BurnchainCheckpoint)>, Error> {
+    if fs::metadata(root).is_err() {
+        return Ok(vec![]);
+    }
+    let mut checkpoints = vec![];
+    for entry in fs::read_dir(root).map_err(Error::FSError)? {
+        let path = entry.map_err(Error::FSError)?.path();
+        if let Ok(checkpoint) = BurnchainCheckpoint::load(&path) {
+            checkpoints.push((path, checkpoint));
+        }
+    }
+    checkpoints.sort_by_key(|(_, checkpoint)| checkpoint.block_height);
+    Ok(checkpoints)
+}
+
+/// Delete all but the `retain` highest checkpoints in `root`
+pub fn prune_checkpoints(root: &Path, retain: usize) -> Result<(), Error> {
+    let checkpoints = list_checkpoints(root)?;
+    let num_to_delete = checkpoints.len().saturating_sub(retain);
+    for (path, checkpoint) in checkpoints.into_iter().take(num_to_delete) {
+        debug!(
+            "Delete burnchain checkpoint";
+            "path" => %path.display(),
+            "block_height" => checkpoint.block_height,
+        );
+        fs::remove_dir_all(&path).map_err(Error::FSError)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::burnchains::bitcoin::spv::SpvClient;
+    use crate::burnchains::bitcoin::BitcoinNetworkType;
+    use crate::burnchains::Burnchain;
+
+    /// Make a fresh test directory at `dir`, with an empty SPV headers DB and burnchain DB in
+    /// its `node` subdirectory
+    fn make_burnchain_state(dir: &Path) -> (String, String) {
+        if fs::metadata(dir).is_ok() {
+            fs::remove_dir_all(dir).unwrap();
+        }
+        let node_dir = dir.join("node");
+        fs::create_dir_all(&node_dir).unwrap();
+
+        let headers_path = node_dir
+            .join("headers.sqlite")
+            .to_str()
+            .unwrap()
+            .to_string();
+        let burnchain_db_path = node_dir
+            .join("burnchain.sqlite")
+            .to_str()
+            .unwrap()
+            .to_string();
+        SpvClient::new(
+            &headers_path,
+            0,
+            None,
+            BitcoinNetworkType::Regtest,
+            true,
+            false,
+        )
+        .unwrap();
+        let burnchain = Burnchain::regtest(node_dir.to_str().unwrap());
+        BurnchainDB::connect(&burnchain_db_path, &burnchain, true).unwrap();
+        (headers_path, burnchain_db_path)
+    }
+
+    #[test]
+    fn test_create_and_restore_checkpoint() {
+        let dir = PathBuf::from("/tmp/stacks-node-tests/test_burnchain_checkpoint");
+        let (headers_path, burnchain_db_path) = make_burnchain_state(&dir);
+        let tip = BurnchainDB::open(&burnchain_db_path, false)
+            .unwrap()
+            .get_canonical_chain_tip()
+            .unwrap();
+
+        let checkpoints_dir = dir.join("checkpoints");
+        let checkpoint_dir = checkpoints_dir.join(tip.block_height.to_string());
+        let checkpoint =
+            BurnchainCheckpoint::create(&headers_path, &burnchain_db_path, &checkpoint_dir)
+                .unwrap();
+        assert_eq!(checkpoint.block_height, tip.block_height);
+        assert_eq!(checkpoint.block_hash, tip.block_hash);
+        assert_eq!(
+            BurnchainCheckpoint::load(&checkpoint_dir).unwrap(),
+            checkpoint
+        );
+
+        // a checkpoint isn't overwritten
+        assert!(
+            BurnchainCheckpoint::create(&headers_path, &burnchain_db_path, &checkpoint_dir)
+                .is_err()
+        );
+
+        // restore into a fresh directory
+        let restored_dir = dir.join("restored");
+        let restored_headers_path = restored_dir.join("headers.sqlite");
+        let restored_burnchain_db_path = restored_dir.join("burnchain").join("burnchain.sqlite");
+        let restored = BurnchainCheckpoint::restore(
+            &checkpoint_dir,
+            restored_headers_path.to_str().unwrap(),
+            restored_burnchain_db_path.to_str().unwrap(),
+            false,
+        )
+        .unwrap();
+        assert_eq!(restored, checkpoint);
+        let restored_tip = BurnchainDB::open(restored_burnchain_db_path.to_str().unwrap(), false)
+            .unwrap()
+            .get_canonical_chain_tip()
+            .unwrap();
+        assert_eq!(restored_tip, tip);
+
+        // existing DBs are only replaced on request
+        assert!(BurnchainCheckpoint::restore(
+            &checkpoint_dir,
+            restored_headers_path.to_str().unwrap(),
+            restored_burnchain_db_path.to_str().unwrap(),
+            false,
+        )
+        .is_err());
+        BurnchainCheckpoint::restore(
+            &checkpoint_dir,
+            restored_headers_path.to_str().unwrap(),
+            restored_burnchain_db_path.to_str().unwrap(),
+            true,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_list_and_prune_checkpoints() {
+        let dir = PathBuf::from("/tmp/stacks-node-tests/test_burnchain_checkpoint_prune");
+        let (headers_path, burnchain_db_path) = make_burnchain_state(&dir);
+        let checkpoints_dir = dir.join("checkpoints");
+        assert!(list_checkpoints(&checkpoints_dir).unwrap().is_empty());
+
+        for name in ["a", "b", "c"] {
+            BurnchainCheckpoint::create(
+                &headers_path,
+                &burnchain_db_path,
+                &checkpoints_dir.join(name),
+            )
+            .unwrap();
+        }
+        // an incomplete checkpoint is ignored
+        fs::create_dir_all(checkpoints_dir.join("d.tmp")).unwrap();
+        assert_eq!(list_checkpoints(&checkpoints_dir).unwrap().len(), 3);
+
+        prune_checkpoints(&checkpoints_dir, 1).unwrap();
+        assert_eq!(list_checkpoints(&checkpoints_dir).unwrap().len(), 1);
+        prune_checkpoints(&checkpoints_dir, 0).unwrap();
+        assert!(list_checkpoints(&checkpoints_dir).unwrap().is_empty());
+    }
+}
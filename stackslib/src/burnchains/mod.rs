@@ -54,6 +54,7 @@ use crate::util_lib::db::Error as db_error;
 pub mod affirmation;
 pub mod bitcoin;
 pub mod burnchain;
+pub mod checkpoint;
 pub mod db;
 pub mod indexer;
 
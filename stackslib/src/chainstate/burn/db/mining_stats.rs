@@ -0,0 +1,248 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Mining statistics.  How much a miner should commit depends on how much the other miners
+//! commit.  These queries summarize the block commits and winners of a fork's recent sortitions,
+//! so that mining pools can size their commits without scraping the burnchain themselves.
+
+use std::collections::HashMap;
+
+use rusqlite::Connection;
+
+use crate::chainstate::burn::db::sortdb::SortitionDB;
+use crate::chainstate::burn::BlockSnapshot;
+use crate::util_lib::db::Error as db_error;
+
+/// The number of sortitions summarized by default (about a day of Bitcoin blocks)
+pub const DEFAULT_MINING_STATS_WINDOW: u64 = 144;
+/// The most sortitions that can be summarized at once
+pub const MAX_MINING_STATS_WINDOW: u64 = 2016;
+
+/// The distribution of a set of burn amounts, in satoshis
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BurnDistribution {
+    pub count: u64,
+    pub min: u64,
+    pub p25: u64,
+    pub median: u64,
+    pub p75: u64,
+    pub max: u64,
+    pub mean: u64,
+}
+
+impl BurnDistribution {
+    pub fn from_burns(mut burns: Vec<u64>) -> BurnDistribution {
+        burns.sort_unstable();
+        let percentile = |p: usize| {
+            burns
+                .get(burns.len().saturating_sub(1) * p / 100)
+                .copied()
+                .unwrap_or(0)
+        };
+        let total: u128 = burns.iter().map(|burn| u128::from(*burn)).sum();
+        let count = u64::try_from(burns.len()).expect("FATAL: more than u64::MAX burns");
+        let mean = if count > 0 {
+            u64::try_from(total / u128::from(count)).expect("FATAL: mean burn exceeds u64::MAX")
+        } else {
+            0
+        };
+        BurnDistribution {
+            count,
+            min: percentile(0),
+            p25: percentile(25),
+            median: percentile(50),
+            p75: percentile(75),
+            max: percentile(100),
+            mean,
+        }
+    }
+}
+
+/// How one miner fared in the summarized sortitions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MinerWinStats {
+    /// The apparent sender of the miner's commits.  This is not authenticated.
+    pub miner: String,
+    pub commits: u64,
+    pub wins: u64,
+    /// The total burn of the miner's commits
+    pub total_burn: u64,
+    /// `wins / commits`
+    pub win_rate: f64,
+}
+
+/// The chance that a commit would have won the summarized sortitions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WinProbability {
+    pub burn_fee: u64,
+    pub probability: f64,
+}
+
+/// A summary of a fork's recent sortitions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MiningStats {
+    /// The burn block height of the first summarized sortition
+    pub start_burn_block_height: u64,
+    /// The burn block height of the last summarized sortition
+    pub end_burn_block_height: u64,
+    pub num_sortitions: u64,
+    /// How many of the sortitions chose a winner
+    pub num_winning_sortitions: u64,
+    /// The burn of each block commit
+    pub commit_burns: BurnDistribution,
+    /// The total burn of each sortition's block commits
+    pub sortition_burns: BurnDistribution,
+    /// The miners that committed, by wins and then by total burn, descending
+    pub miners: Vec<MinerWinStats>,
+    /// If asked for, the chance that a given commit would have won
+    pub expected_win_probability: Option<WinProbability>,
+}
+
+/// Estimate the probability that a commit of `burn_fee` wins a sortition, given the total burn of
+/// the other commits in each of a set of past sortitions.  This is the commit's average share of
+/// those sortitions' burn.  It ignores that a miner's sortition weight is capped by the median of
+/// its recent commits, and that commits can miss their intended burn block, so it overestimates
+/// the chances of a miner whose commits vary.
+pub fn expected_win_probability(sortition_burns: &[u64], burn_fee: u64) -> f64 {
+    if sortition_burns.is_empty() || burn_fee == 0 {
+        return 0.0;
+    }
+    let total: f64 = sortition_burns
+        .iter()
+        .map(|burn| burn_fee as f64 / (burn_fee as f64 + *burn as f64))
+        .sum();
+    total / sortition_burns.len() as f64
+}
+
+impl SortitionDB {
+    /// Summarize the block commits and winners of the last `window` sortitions of the fork that
+    /// ends at `tip`, including `tip`.  If `burn_fee` is given, also estimate the chance that a
+    /// commit of that amount would have won them.
+    pub fn get_mining_stats(
+        conn: &Connection,
+        tip: &BlockSnapshot,
+        window: u64,
+        burn_fee: Option<u64>,
+    ) -> Result<MiningStats, db_error> {
+        let mut miners: HashMap<String, MinerWinStats> = HashMap::new();
+        let mut commit_burns = vec![];
+        let mut sortition_burns = vec![];
+        let mut num_winning_sortitions = 0;
+
+        let mut cursor = Some(tip.clone());
+        let mut start_burn_block_height = tip.block_height;
+        while let Some(sn) = cursor.take() {
+            start_burn_block_height = sn.block_height;
+            let commits = SortitionDB::get_block_commits_by_block(conn, &sn.sortition_id)?;
+            let mut sortition_burn: u64 = 0;
+            for commit in commits.iter() {
+                let won = sn.sortition && commit.txid == sn.winning_block_txid;
+                let stats = miners
+                    .entry(commit.apparent_sender.to_string())
+                    .or_insert_with(|| MinerWinStats {
+                        miner: commit.apparent_sender.to_string(),
+                        commits: 0,
+                        wins: 0,
+                        total_burn: 0,
+                        win_rate: 0.0,
+                    });
+                stats.commits += 1;
+                stats.wins += u64::from(won);
+                stats.total_burn = stats.total_burn.saturating_add(commit.burn_fee);
+                commit_burns.push(commit.burn_fee);
+                sortition_burn = sortition_burn.saturating_add(commit.burn_fee);
+            }
+            sortition_burns.push(sortition_burn);
+            if sn.sortition {
+                num_winning_sortitions += 1;
+            }
+
+            let num_sortitions = u64::try_from(sortition_burns.len()).unwrap_or(u64::MAX);
+            if num_sortitions >= window || sn.parent_sortition_id == sn.sortition_id {
+                break;
+            }
+            cursor = SortitionDB::get_block_snapshot(conn, &sn.parent_sortition_id)?;
+        }
+
+        let mut miners: Vec<_> = miners
+            .into_values()
+            .map(|mut stats| {
+                stats.win_rate = stats.wins as f64 / stats.commits as f64;
+                stats
+            })
+            .collect();
+        miners.sort_by(|a, b| {
+            b.wins
+                .cmp(&a.wins)
+                .then(b.total_burn.cmp(&a.total_burn))
+                .then(a.miner.cmp(&b.miner))
+        });
+
+        let expected_win_probability = burn_fee.map(|burn_fee| WinProbability {
+            burn_fee,
+            probability: expected_win_probability(&sortition_burns, burn_fee),
+        });
+        Ok(MiningStats {
+            start_burn_block_height,
+            end_burn_block_height: tip.block_height,
+            num_sortitions: u64::try_from(sortition_burns.len()).unwrap_or(u64::MAX),
+            num_winning_sortitions,
+            commit_burns: BurnDistribution::from_burns(commit_burns),
+            sortition_burns: BurnDistribution::from_burns(sortition_burns),
+            miners,
+            expected_win_probability,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_burn_distribution() {
+        let dist = BurnDistribution::from_burns(vec![]);
+        assert_eq!(dist.count, 0);
+        assert_eq!(dist.max, 0);
+        assert_eq!(dist.mean, 0);
+
+        let dist = BurnDistribution::from_burns(vec![500, 100, 400, 200, 300]);
+        assert_eq!(
+            dist,
+            BurnDistribution {
+                count: 5,
+                min: 100,
+                p25: 200,
+                median: 300,
+                p75: 400,
+                max: 500,
+                mean: 300,
+            }
+        );
+
+        let dist = BurnDistribution::from_burns(vec![u64::MAX, u64::MAX]);
+        assert_eq!(dist.mean, u64::MAX);
+    }
+
+    #[test]
+    fn test_expected_win_probability() {
+        assert_eq!(expected_win_probability(&[], 100), 0.0);
+        assert_eq!(expected_win_probability(&[100], 0), 0.0);
+        // no competition
+        assert_eq!(expected_win_probability(&[0, 0], 100), 1.0);
+        // half of one sortition and a quarter of another
+        assert_eq!(expected_win_probability(&[100, 300], 100), 0.375);
+    }
+}
@@ -56,8 +56,8 @@ use crate::chainstate::coordinator::comm::{
     ArcCounterCoordinatorNotices, CoordinatorEvents, CoordinatorNotices, CoordinatorReceivers,
 };
 use crate::chainstate::stacks::address::PoxAddress;
-use crate::chainstate::stacks::boot::{POX_3_NAME, POX_4_NAME};
 use crate::chainstate::stacks::db::accounts::MinerReward;
+use crate::chainstate::stacks::db::reorgs::StacksReorg;
 use crate::chainstate::stacks::db::{
     ChainStateBootData, ClarityTx, MinerRewardInfo, StacksChainState, StacksEpochReceipt,
     StacksHeaderInfo,
@@ -195,6 +195,10 @@ pub trait BlockEventDispatcher {
         burns: u64,
         reward_recipients: Vec<PoxAddress>,
     );
+
+    /// called whenever the canonical Stacks tip moves to a block
+    ///  that does not descend from the previous tip.
+    fn announce_reorg(&self, _reorg: &StacksReorg) {}
 }
 
 pub struct ChainsCoordinatorConfig {
@@ -323,14 +327,12 @@ impl<'a, T: BlockEventDispatcher> RewardSetProvider for OnChainRewardSetProvider
         sortdb: &SortitionDB,
         block_id: &StacksBlockId,
     ) -> Result<RewardSet, Error> {
-        let cur_epoch = SortitionDB::get_stacks_epoch(sortdb.conn(), cycle_start_burn_height)?
-            .unwrap_or_else(|| panic!("FATAL: no epoch for burn height {cycle_start_burn_height}"));
         let cycle = burnchain
             .block_height_to_reward_cycle(cycle_start_burn_height)
             .expect("FATAL: no reward cycle for burn height");
         // `self.get_reward_set_nakamoto` reads the reward set from data written during
         //   updates to .signers
-        // `self.get_reward_set_epoch2` reads the reward set from the `.pox-*` contract
+        // `chainstate.compute_reward_set` computes the reward set from the `.pox-*` contract
         //
         //  Data **cannot** be read from `.signers` in epoch 2.5 because the write occurs
         //   in the first block of the prepare phase, but the PoX anchor block is *before*
@@ -352,14 +354,7 @@ impl<'a, T: BlockEventDispatcher> RewardSetProvider for OnChainRewardSetProvider
             None => false,
         };
 
-        let reward_set = self.get_reward_set_epoch2(
-            cycle_start_burn_height,
-            chainstate,
-            burnchain,
-            sortdb,
-            block_id,
-            cur_epoch,
-        )?;
+        let reward_set = chainstate.compute_reward_set(burnchain, sortdb, cycle, block_id)?;
 
         if is_nakamoto_reward_set {
             if reward_set.signers.is_none() || reward_set.signers == Some(vec![]) {
@@ -382,98 +377,6 @@ impl<'a, T: BlockEventDispatcher> RewardSetProvider for OnChainRewardSetProvider
     }
 }
 
-impl<'a, T: BlockEventDispatcher> OnChainRewardSetProvider<'a, T> {
-    fn get_reward_set_epoch2(
-        &self,
-        // Todo: `current_burn_height` is a misleading name: should be the `cycle_start_burn_height`
-        current_burn_height: u64,
-        chainstate: &mut StacksChainState,
-        burnchain: &Burnchain,
-        sortdb: &SortitionDB,
-        block_id: &StacksBlockId,
-        cur_epoch: StacksEpoch,
-    ) -> Result<RewardSet, Error> {
-        match cur_epoch.epoch_id {
-            StacksEpochId::Epoch10
-            | StacksEpochId::Epoch20
-            | StacksEpochId::Epoch2_05
-            | StacksEpochId::Epoch21 => {
-                // Epochs 1.0 - 2.1 compute reward sets
-            }
-            StacksEpochId::Epoch22 | StacksEpochId::Epoch23 => {
-                info!("PoX reward cycle defaulting to burn in Epochs 2.2 and 2.3");
-                return Ok(RewardSet::empty());
-            }
-            StacksEpochId::Epoch24 => {
-                // Epoch 2.4 computes reward sets, but *only* if PoX-3 is active
-                if burnchain
-                    .pox_constants
-                    .active_pox_contract(current_burn_height)
-                    != POX_3_NAME
-                {
-                    // Note: this should not happen in mainnet or testnet, because the no reward cycle start height
-                    //        exists between Epoch 2.4's instantiation height and the pox-3 activation height.
-                    //  However, this *will* happen in testing if Epoch 2.4's instantiation height is set == a reward cycle
-                    //   start height
-                    info!("PoX reward cycle defaulting to burn in Epoch 2.4 because cycle start is before PoX-3 activation");
-                    return Ok(RewardSet::empty());
-                }
-            }
-            StacksEpochId::Epoch25 | StacksEpochId::Epoch30 => {
-                // Epoch 2.5 and 3.0 compute reward sets, but *only* if PoX-4 is active
-                if burnchain
-                    .pox_constants
-                    .active_pox_contract(current_burn_height)
-                    != POX_4_NAME
-                {
-                    // Note: this should not happen in mainnet or testnet, because the no reward cycle start height
-                    //        exists between Epoch 2.5's instantiation height and the pox-4 activation height.
-                    //  However, this *will* happen in testing if Epoch 2.5's instantiation height is set == a reward cycle
-                    //   start height
-                    info!("PoX reward cycle defaulting to burn in Epoch 2.5 because cycle start is before PoX-4 activation");
-                    return Ok(RewardSet::empty());
-                }
-            }
-        };
-
-        let registered_addrs =
-            chainstate.get_reward_addresses(burnchain, sortdb, current_burn_height, block_id)?;
-
-        let liquid_ustx = chainstate.get_liquid_ustx(block_id);
-
-        let (threshold, participation) = StacksChainState::get_reward_threshold_and_participation(
-            &burnchain.pox_constants,
-            &registered_addrs[..],
-            liquid_ustx,
-        );
-
-        if !burnchain
-            .pox_constants
-            .enough_participation(participation, liquid_ustx)
-        {
-            info!("PoX reward cycle did not have enough participation. Defaulting to burn";
-                  "burn_height" => current_burn_height,
-                  "participation" => participation,
-                  "liquid_ustx" => liquid_ustx,
-                  "registered_addrs" => registered_addrs.len());
-            return Ok(RewardSet::empty());
-        } else {
-            info!("PoX reward cycle threshold computed";
-                  "burn_height" => current_burn_height,
-                  "threshold" => threshold,
-                  "participation" => participation,
-                  "liquid_ustx" => liquid_ustx,
-                  "registered_addrs" => registered_addrs.len());
-        }
-
-        Ok(StacksChainState::make_reward_set(
-            threshold,
-            registered_addrs,
-            cur_epoch.epoch_id,
-        ))
-    }
-}
-
 impl<
         'a,
         T: BlockEventDispatcher,
@@ -547,6 +450,7 @@ impl<
 
         loop {
             let bits = comms.wait_on();
+            let stacks_tip = inst.canonical_stacks_tip();
             if inst.in_subsequent_nakamoto_reward_cycle() {
                 debug!("Coordinator: in subsequent Nakamoto reward cycle");
                 if !inst.handle_comms_nakamoto(bits, miner_status.clone()) {
@@ -566,6 +470,48 @@ impl<
                     return;
                 }
             }
+            inst.handle_stacks_reorg(stacks_tip);
+        }
+    }
+
+    /// The canonical Stacks tip, if it can be read
+    fn canonical_stacks_tip(&self) -> Option<StacksBlockId> {
+        match SortitionDB::get_canonical_stacks_chain_tip_hash(self.sortition_db.conn()) {
+            Ok((ch, bhh)) => Some(StacksBlockId::new(&ch, &bhh)),
+            Err(e) => {
+                warn!("Failed to load the canonical Stacks tip: {:?}", &e);
+                None
+            }
+        }
+    }
+
+    /// Record and announce a reorg, if the canonical Stacks tip moved from `old_tip` to a block
+    /// that does not descend from it
+    fn handle_stacks_reorg(&mut self, old_tip: Option<StacksBlockId>) {
+        let (Some(old_tip), Some(new_tip)) = (old_tip, self.canonical_stacks_tip()) else {
+            return;
+        };
+        let reorg = match self.chain_state_db.detect_reorg(&old_tip, &new_tip) {
+            Ok(Some(reorg)) => reorg,
+            Ok(None) => return,
+            Err(e) => {
+                warn!("Failed to check for a Stacks reorg: {:?}", &e);
+                return;
+            }
+        };
+        info!(
+            "Stacks chain reorg";
+            "old_tip" => %reorg.old_tip,
+            "new_tip" => %reorg.new_tip,
+            "fork_point" => %reorg.fork_point,
+            "depth" => reorg.depth(),
+            "orphaned_txs" => reorg.orphaned_txids.len()
+        );
+        if let Err(e) = self.chain_state_db.record_reorg(&reorg) {
+            warn!("Failed to record Stacks reorg: {:?}", &e);
+        }
+        if let Some(dispatcher) = self.dispatcher {
+            dispatcher.announce_reorg(&reorg);
         }
     }
 
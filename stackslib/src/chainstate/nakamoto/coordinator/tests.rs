@@ -979,7 +979,7 @@ fn block_info_tests(use_primary_testnet: bool) {
                     tip_block_id: &StacksBlockId| {
         let contract_id = match version {
             ClarityVersion::Clarity1 => &clar1_contract_id,
-            ClarityVersion::Clarity2 => panic!(),
+            ClarityVersion::Clarity2 | ClarityVersion::Clarity4 => panic!(),
             ClarityVersion::Clarity3 => &clar3_contract_id,
         };
         peer.with_db_state(|sortdb, chainstate, _, _| {
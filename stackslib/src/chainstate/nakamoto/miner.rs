@@ -51,6 +51,7 @@ use crate::chainstate::burn::db::sortdb::{
 use crate::chainstate::burn::operations::*;
 use crate::chainstate::burn::*;
 use crate::chainstate::coordinator::OnChainRewardSetProvider;
+use crate::chainstate::nakamoto::ordering::TxOrderingRule;
 use crate::chainstate::nakamoto::{
     MaturedMinerRewards, NakamotoBlock, NakamotoBlockHeader, NakamotoChainState, SetupBlockResult,
 };
@@ -590,6 +591,11 @@ impl NakamotoBlockBuilder {
         }
 
         builder.soft_limit = soft_limit;
+        let tx_ordering = settings.tx_ordering;
+
+        // required transactions are mined right after the tenure transactions, ahead of the
+        // mempool walk, so that each one is either in this block or rejected with a reason
+        let required_txs = mempool.get_pending_required_txs()?;
 
         let initial_txs: Vec<_> = [
             tenure_info.tenure_change_tx.clone(),
@@ -597,6 +603,7 @@ impl NakamotoBlockBuilder {
         ]
         .into_iter()
         .filter_map(|x| x)
+        .chain(required_txs.iter().cloned())
         .collect();
 
         // TODO: update this mempool check to prioritize signer vote transactions over other transactions
@@ -618,6 +625,23 @@ impl NakamotoBlockBuilder {
             }
         };
 
+        for required_tx in required_txs.iter() {
+            let txid = required_tx.txid();
+            let reason = tx_events.iter().find_map(|event| match event {
+                TransactionEvent::ProcessingError(e) if e.txid == txid => Some(&e.error),
+                TransactionEvent::Skipped(e) if e.txid == txid => Some(&e.error),
+                TransactionEvent::Problematic(e) if e.txid == txid => Some(&e.error),
+                _ => None,
+            });
+            if let Some(reason) = reason {
+                info!("Miner: rejecting required transaction";
+                    "txid" => %txid,
+                    "reason" => %reason
+                );
+                mempool.reject_required_tx(&txid, reason)?;
+            }
+        }
+
         if blocked {
             debug!(
                 "Miner: block transaction selection aborted (child of {})",
@@ -630,10 +654,33 @@ impl NakamotoBlockBuilder {
             return Err(Error::NoTransactionsToMine);
         }
 
-        // save the block so we can build microblocks off of it
-        let block = builder.mine_nakamoto_block(&mut tenure_tx);
-        let size = builder.bytes_so_far;
-        let consumed = builder.tenure_finish(tenure_tx)?;
+        let (block, size, consumed) = match tx_ordering {
+            None => {
+                // save the block so we can build microblocks off of it
+                let block = builder.mine_nakamoto_block(&mut tenure_tx);
+                let size = builder.bytes_so_far;
+                let consumed = builder.tenure_finish(tenure_tx)?;
+                (block, size, consumed)
+            }
+            Some(rule) => {
+                // the miner committed to an ordering, so rebuild the block from the selected
+                // transactions in that order
+                let selected_txs = mem::take(&mut builder.txs);
+                tenure_tx.rollback_block();
+                drop(miner_tenure_info);
+                Self::build_ordered_nakamoto_block(
+                    &mut chainstate,
+                    burn_dbconn,
+                    parent_stacks_header,
+                    tenure_id_consensus_hash,
+                    total_burn,
+                    &tenure_info,
+                    signer_bitvec_len,
+                    rule,
+                    selected_txs,
+                )?
+            }
+        };
 
         let ts_end = get_epoch_time_ms();
 
@@ -657,6 +704,72 @@ impl NakamotoBlockBuilder {
         Ok((block, consumed, size, tx_events))
     }
 
+    /// Build a block that commits to `rule` from transactions that were already selected for it.
+    /// Transactions are replayed in the committed order.  If one no longer applies, it and the
+    /// later transactions from the same origin are left out, which keeps the rest in order.
+    /// Returns the block, its size, and its execution cost.
+    fn build_ordered_nakamoto_block(
+        chainstate: &mut StacksChainState,
+        burn_dbconn: &SortitionHandleConn,
+        parent_stacks_header: &StacksHeaderInfo,
+        tenure_id_consensus_hash: &ConsensusHash,
+        total_burn: u64,
+        tenure_info: &NakamotoTenureInfo,
+        signer_bitvec_len: u16,
+        rule: TxOrderingRule,
+        selected_txs: Vec<StacksTransaction>,
+    ) -> Result<(NakamotoBlock, u64, ExecutionCost), Error> {
+        let mut builder = NakamotoBlockBuilder::new(
+            parent_stacks_header,
+            tenure_id_consensus_hash,
+            total_burn,
+            tenure_info.tenure_change_tx(),
+            tenure_info.coinbase_tx(),
+            signer_bitvec_len,
+            None,
+        )?;
+
+        let mut miner_tenure_info =
+            builder.load_tenure_info(chainstate, burn_dbconn, tenure_info.cause())?;
+        let mut tenure_tx = builder.tenure_begin(burn_dbconn, &mut miner_tenure_info)?;
+
+        let ordered_txs =
+            rule.order_transactions(&parent_stacks_header.index_block_hash(), selected_txs);
+        let mut dropped_origins = HashSet::new();
+        for tx in ordered_txs.iter() {
+            let origin = tx.origin_address();
+            if dropped_origins.contains(&origin) {
+                continue;
+            }
+            let result = builder.try_mine_tx_with_len(
+                &mut tenure_tx,
+                tx,
+                tx.tx_len(),
+                &BlockLimitFunction::NO_LIMIT_HIT,
+                ASTRules::PrecheckSize,
+            );
+            if !matches!(result, TransactionResult::Success(..)) {
+                debug!("Miner: leaving transaction out of ordered block";
+                    "txid" => %tx.txid(),
+                    "origin" => %origin,
+                    "rule" => %rule,
+                    "result" => ?result
+                );
+                dropped_origins.insert(origin);
+            }
+        }
+
+        if builder.txs.is_empty() {
+            tenure_tx.rollback_block();
+            return Err(Error::NoTransactionsToMine);
+        }
+
+        let block = builder.mine_nakamoto_block(&mut tenure_tx);
+        let size = builder.bytes_so_far;
+        let consumed = builder.tenure_finish(tenure_tx)?;
+        Ok((block, size, consumed))
+    }
+
     pub fn get_bytes_so_far(&self) -> u64 {
         self.bytes_so_far
     }
@@ -119,6 +119,8 @@ use crate::{chainstate, monitoring};
 pub mod coordinator;
 pub mod keys;
 pub mod miner;
+pub mod ordering;
+pub mod prevalidate;
 pub mod shadow;
 pub mod signer_set;
 pub mod staging_blocks;
@@ -4642,6 +4644,13 @@ impl NakamotoChainState {
 
         let new_block_id = new_tip.index_block_hash();
         chainstate_tx.log_transactions_processed(&new_block_id, &tx_receipts);
+        StacksChainState::store_block_costs(
+            chainstate_tx.tx.tx(),
+            &new_block_id,
+            new_tip.stacks_block_height,
+            &block_execution_cost,
+            &tx_receipts,
+        )?;
 
         let reward_cycle = pox_constants.block_height_to_reward_cycle(
             first_block_height.into(),
@@ -0,0 +1,145 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction-ordering commitments.  A miner can commit to ordering the transactions of a block
+//! by a deterministic rule instead of by its own choice, so that it can't reorder them to extract
+//! value from their senders.  The commitment is a matter of signer policy, not of consensus.
+//!
+//! Nothing advertises the rule yet: the block header has no optional fields to record it in, and
+//! adding one would need an epoch-gated change to the header's serialization.  Instead, signers
+//! that require a rule apply their locally configured rule to every miner's block proposals, and
+//! miners have to be configured to match.  Advertising the rule per block, and validating it in
+//! block acceptance, are out of scope until such an epoch exists.
+//!
+//! The only rule so far is `salted-txid`: after the block's tenure-change and coinbase
+//! transactions, transactions are ordered by the SHA512/256 hash of the block's parent block ID
+//! and their txid.  Each account's transactions stay in nonce order, so the block is the merge of
+//! the accounts' transaction sequences that always takes the head with the lowest hash next.
+//! The salt isn't known until the parent block is, so senders can't grind their txids to land
+//! ahead of others for long.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+
+use stacks_common::types::chainstate::StacksBlockId;
+use stacks_common::util::hash::Sha512Trunc256Sum;
+
+use crate::burnchains::Txid;
+use crate::chainstate::nakamoto::NakamotoBlock;
+use crate::chainstate::stacks::{Error as ChainstateError, StacksTransaction, TransactionPayload};
+
+/// The names of the ordering rules
+pub const TX_ORDERING_RULES: &[&str] = &["salted-txid"];
+
+/// A deterministic transaction-ordering rule that a block can commit to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxOrderingRule {
+    /// Order by the hash of the parent block ID and the txid, keeping each account's nonce order
+    SaltedTxid,
+}
+
+impl fmt::Display for TxOrderingRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl TxOrderingRule {
+    /// The name that selects this rule in the miner config
+    pub fn name(&self) -> &'static str {
+        match self {
+            TxOrderingRule::SaltedTxid => "salted-txid",
+        }
+    }
+
+    /// The rule called `name`
+    pub fn from_name(name: &str) -> Option<TxOrderingRule> {
+        match name {
+            "salted-txid" => Some(TxOrderingRule::SaltedTxid),
+            _ => None,
+        }
+    }
+
+    /// The key that orders a transaction in a block built on `parent_block_id`
+    fn sort_key(&self, parent_block_id: &StacksBlockId, txid: &Txid) -> [u8; 32] {
+        match self {
+            TxOrderingRule::SaltedTxid => {
+                Sha512Trunc256Sum::from_data(&[&parent_block_id.0[..], &txid.0[..]].concat()).0
+            }
+        }
+    }
+
+    /// Order the transactions of a block built on `parent_block_id` by this rule.  The leading
+    /// tenure-change and coinbase transactions stay first, and each account's transactions keep
+    /// their relative order.
+    pub fn order_transactions(
+        &self,
+        parent_block_id: &StacksBlockId,
+        mut txs: Vec<StacksTransaction>,
+    ) -> Vec<StacksTransaction> {
+        let num_tenure_txs = txs
+            .iter()
+            .take_while(|tx| {
+                matches!(
+                    tx.payload,
+                    TransactionPayload::TenureChange(..) | TransactionPayload::Coinbase(..)
+                )
+            })
+            .count();
+        let rest = txs.split_off(num_tenure_txs);
+
+        let mut queue_ids = HashMap::new();
+        let mut queues: Vec<VecDeque<StacksTransaction>> = vec![];
+        for tx in rest.into_iter() {
+            let queue_id = *queue_ids.entry(tx.origin_address()).or_insert_with(|| {
+                queues.push(VecDeque::new());
+                queues.len() - 1
+            });
+            queues[queue_id].push_back(tx);
+        }
+
+        // always take the queue head with the lowest key next
+        let mut heads = BTreeMap::new();
+        for (queue_id, queue) in queues.iter().enumerate() {
+            if let Some(tx) = queue.front() {
+                heads.insert(self.sort_key(parent_block_id, &tx.txid()), queue_id);
+            }
+        }
+        while let Some(head) = heads.keys().next().cloned() {
+            let queue_id = heads.remove(&head).expect("BUG: head is missing");
+            let tx = queues[queue_id]
+                .pop_front()
+                .expect("BUG: queue head is missing");
+            txs.push(tx);
+            if let Some(next_tx) = queues[queue_id].front() {
+                heads.insert(self.sort_key(parent_block_id, &next_tx.txid()), queue_id);
+            }
+        }
+        txs
+    }
+}
+
+impl NakamotoBlock {
+    /// Verify that the block's transactions follow the ordering `rule`
+    pub fn check_tx_ordering(&self, rule: TxOrderingRule) -> Result<(), ChainstateError> {
+        let ordered = rule.order_transactions(&self.header.parent_block_id, self.txs.clone());
+        if ordered != self.txs {
+            return Err(ChainstateError::InvalidStacksBlock(format!(
+                "Transactions violate the ordering rule {rule}"
+            )));
+        }
+        Ok(())
+    }
+}
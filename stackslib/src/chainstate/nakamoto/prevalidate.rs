@@ -0,0 +1,238 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Pre-validation of proposed Nakamoto blocks.  A proposed block is checked and executed atop its
+//! parent the same way it would be if it were appended, but nothing it writes is kept.  Instead of
+//! stopping at the first problem, every transaction that can't be mined and every header field
+//! that doesn't match the block that the node computes is reported, so that signers can tell
+//! miners exactly what is wrong with a block.
+
+use std::fmt;
+
+use clarity::vm::ast::ASTRules;
+use clarity::vm::costs::ExecutionCost;
+use stacks_common::types::chainstate::TrieHash;
+
+use crate::chainstate::burn::db::sortdb::SortitionHandleConn;
+use crate::chainstate::nakamoto::miner::NakamotoBlockBuilder;
+use crate::chainstate::nakamoto::{NakamotoBlock, NakamotoChainState};
+use crate::chainstate::stacks::db::{StacksChainState, StacksHeaderInfo};
+use crate::chainstate::stacks::miner::{BlockBuilder, BlockLimitFunction, TransactionResult};
+use crate::chainstate::stacks::{Error as ChainstateError, TransactionPayload};
+
+/// A way in which a proposed block is invalid
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum BlockViolation {
+    /// A transaction can't be mined
+    Transaction { tx_index: u32, reason: String },
+    /// A header field doesn't match the one computed by executing the block
+    Header {
+        field: String,
+        expected: String,
+        computed: String,
+    },
+}
+
+impl fmt::Display for BlockViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BlockViolation::Transaction { reason, .. } => write!(f, "{reason}"),
+            BlockViolation::Header {
+                field,
+                expected,
+                computed,
+            } => write!(
+                f,
+                "Header {field} is {expected}, but the node computed {computed}"
+            ),
+        }
+    }
+}
+
+/// The outcome of executing a proposed block atop its parent
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockPrevalidation {
+    /// Everything wrong with the block, in block order.  The block is valid if this is empty.
+    pub violations: Vec<BlockViolation>,
+    /// The execution cost of the block's mineable transactions
+    pub cost: ExecutionCost,
+    /// The size of the block's mineable transactions
+    pub size: u64,
+    /// The state root that executing the block's mineable transactions produces
+    pub state_index_root: TrieHash,
+}
+
+impl BlockPrevalidation {
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+impl NakamotoChainState {
+    /// DO NOT CALL FROM CONSENSUS CODE
+    ///
+    /// Check and execute `block` atop `parent_header` without appending it.  `burn_dbconn` must
+    /// be open at the block's burn view, and `expected_burn` is what the block's tenure spent.
+    ///
+    /// The stateless checks run first, and their failure is returned as an error since there is
+    /// nothing worth executing.  Otherwise every transaction is executed, and the transactions
+    /// that can't be mined and the header fields that don't match the block built from the rest
+    /// are returned as violations.
+    pub fn prevalidate_block(
+        chainstate: &mut StacksChainState,
+        burn_dbconn: &SortitionHandleConn,
+        parent_header: &StacksHeaderInfo,
+        block: &NakamotoBlock,
+        expected_burn: u64,
+    ) -> Result<BlockPrevalidation, ChainstateError> {
+        Self::validate_normal_nakamoto_block_burnchain(
+            chainstate.nakamoto_blocks_db(),
+            burn_dbconn,
+            Some(expected_burn),
+            block,
+            chainstate.mainnet,
+            chainstate.chain_id,
+        )?;
+
+        let tenure_change = block
+            .txs
+            .iter()
+            .find(|tx| matches!(tx.payload, TransactionPayload::TenureChange(..)));
+        let coinbase = block
+            .txs
+            .iter()
+            .find(|tx| matches!(tx.payload, TransactionPayload::Coinbase(..)));
+        let tenure_cause = tenure_change.and_then(|tx| match &tx.payload {
+            TransactionPayload::TenureChange(tc) => Some(tc.cause),
+            _ => None,
+        });
+
+        let mut builder = NakamotoBlockBuilder::new(
+            parent_header,
+            &block.header.consensus_hash,
+            block.header.burn_spent,
+            tenure_change,
+            coinbase,
+            block.header.pox_treatment.len(),
+            None,
+        )?;
+
+        let mut miner_tenure_info =
+            builder.load_tenure_info(chainstate, burn_dbconn, tenure_cause)?;
+        let mut tenure_tx = builder.tenure_begin(burn_dbconn, &mut miner_tenure_info)?;
+
+        let mut violations = vec![];
+        for (i, tx) in block.txs.iter().enumerate() {
+            let tx_len = tx.tx_len();
+            let tx_result = builder.try_mine_tx_with_len(
+                &mut tenure_tx,
+                tx,
+                tx_len,
+                &BlockLimitFunction::NO_LIMIT_HIT,
+                ASTRules::PrecheckSize,
+            );
+            let reason = match tx_result {
+                TransactionResult::Success(_) => continue,
+                TransactionResult::Skipped(s) => format!("tx {i} skipped: {}", s.error),
+                TransactionResult::ProcessingError(e) => {
+                    format!("Error processing tx {i}: {}", e.error)
+                }
+                TransactionResult::Problematic(p) => format!("Problematic tx {i}: {}", p.error),
+            };
+            warn!(
+                "Proposed block has an invalid transaction";
+                "reason" => %reason,
+                "tx" => ?tx,
+            );
+            violations.push(BlockViolation::Transaction {
+                tx_index: u32::try_from(i).expect("FATAL: more than u32::MAX transactions"),
+                reason,
+            });
+        }
+
+        let mut computed_block = builder.mine_nakamoto_block(&mut tenure_tx);
+        let size = builder.get_bytes_so_far();
+        let cost = builder.tenure_finish(tenure_tx)?;
+        let state_index_root = computed_block.header.state_index_root.clone();
+
+        // Clone the fields that the builder doesn't compute from the proposed block.  Its
+        // signatures, timestamp and transaction-ordering commitment have already been validated
+        // by `validate_normal_nakamoto_block_burnchain()` (or are up to the caller to check).
+        computed_block.header.miner_signature = block.header.miner_signature.clone();
+        computed_block.header.signer_signature = block.header.signer_signature.clone();
+        computed_block.header.timestamp = block.header.timestamp;
+        computed_block.header.version = block.header.version;
+
+        let proposed_header = &block.header;
+        let computed_header = &computed_block.header;
+        let mut header_violation = |field: &str, expected: String, computed: String| {
+            if expected != computed {
+                violations.push(BlockViolation::Header {
+                    field: field.into(),
+                    expected,
+                    computed,
+                });
+            }
+        };
+        header_violation(
+            "chain_length",
+            proposed_header.chain_length.to_string(),
+            computed_header.chain_length.to_string(),
+        );
+        header_violation(
+            "burn_spent",
+            proposed_header.burn_spent.to_string(),
+            computed_header.burn_spent.to_string(),
+        );
+        header_violation(
+            "tx_merkle_root",
+            proposed_header.tx_merkle_root.to_string(),
+            computed_header.tx_merkle_root.to_string(),
+        );
+        header_violation(
+            "state_index_root",
+            proposed_header.state_index_root.to_string(),
+            computed_header.state_index_root.to_string(),
+        );
+        header_violation(
+            "pox_treatment",
+            format!("{:?}", &proposed_header.pox_treatment),
+            format!("{:?}", &computed_header.pox_treatment),
+        );
+
+        let expected_block_header_hash = proposed_header.block_hash();
+        let computed_block_header_hash = computed_header.block_hash();
+        if computed_block_header_hash != expected_block_header_hash
+            && !violations
+                .iter()
+                .any(|violation| matches!(violation, BlockViolation::Header { .. }))
+        {
+            // some other field differs
+            violations.push(BlockViolation::Header {
+                field: "block_hash".into(),
+                expected: expected_block_header_hash.to_string(),
+                computed: computed_block_header_hash.to_string(),
+            });
+        }
+
+        Ok(BlockPrevalidation {
+            violations,
+            cost,
+            size,
+            state_index_root,
+        })
+    }
+}
@@ -466,31 +466,21 @@ impl NakamotoSigners {
         block_id: &StacksBlockId,
         reward_cycle: u64,
     ) -> Result<HashMap<StacksAddress, u64>, ChainstateError> {
-        let signers_opt = chainstate
-            .eval_boot_code_read_only(
-                sortdb,
-                block_id,
-                SIGNERS_NAME,
-                &format!("(get-signers u{})", reward_cycle),
-            )?
-            .expect_optional()?;
+        let signer_set_opt = chainstate
+            .with_boot_maps(sortdb, block_id, |maps| maps.get_signer_set(reward_cycle))?;
         let mut signers = HashMap::new();
-        if let Some(signers_list) = signers_opt {
-            for signer in signers_list.expect_list()? {
-                let signer_tuple = signer.expect_tuple()?;
-                let principal_data = signer_tuple.get("signer")?.clone().expect_principal()?;
-                let signer_address = if let PrincipalData::Standard(signer) = principal_data {
-                    signer.into()
-                } else {
-                    panic!(
-                        "FATAL: Signer returned from get-signers is not a standard principal: {:?}",
-                        principal_data
-                    );
-                };
-                let weight = u64::try_from(signer_tuple.get("weight")?.to_owned().expect_u128()?)
-                    .expect("FATAL: Signer weight greater than a u64::MAX");
-                signers.insert(signer_address, weight);
-            }
+        for entry in signer_set_opt.unwrap_or_default() {
+            let signer_address = if let PrincipalData::Standard(signer) = entry.signer {
+                signer.into()
+            } else {
+                panic!(
+                    "FATAL: Signer returned from get-signers is not a standard principal: {:?}",
+                    entry.signer
+                );
+            };
+            let weight =
+                u64::try_from(entry.weight).expect("FATAL: Signer weight greater than a u64::MAX");
+            signers.insert(signer_address, weight);
         }
         if signers.is_empty() {
             error!(
@@ -61,6 +61,7 @@ use crate::chainstate::coordinator::tests::{
 };
 use crate::chainstate::nakamoto::coordinator::tests::boot_nakamoto;
 use crate::chainstate::nakamoto::miner::NakamotoBlockBuilder;
+use crate::chainstate::nakamoto::ordering::TxOrderingRule;
 use crate::chainstate::nakamoto::signer_set::NakamotoSigners;
 use crate::chainstate::nakamoto::staging_blocks::{
     NakamotoBlockObtainMethod, NakamotoStagingBlocksConnRef,
@@ -80,6 +81,7 @@ use crate::chainstate::stacks::db::{
     ChainstateBNSNamespace, StacksAccount, StacksBlockHeaderTypes, StacksChainState,
     StacksHeaderInfo,
 };
+use crate::chainstate::stacks::tests::{make_user_coinbase, make_user_stacks_transfer};
 use crate::chainstate::stacks::{
     CoinbasePayload, Error as ChainstateError, StacksBlock, StacksBlockHeader, StacksTransaction,
     StacksTransactionSigner, TenureChangeCause, TenureChangePayload, TokenTransferMemo,
@@ -3054,6 +3056,68 @@ fn filter_one_transaction_per_signer_duplicate_nonces() {
     assert!(filtered_txs.contains(&txs.first().expect("failed to get first tx")));
 }
 
+#[test]
+fn test_salted_txid_ordering_commitment() {
+    let sender_1 = StacksPrivateKey::new();
+    let sender_2 = StacksPrivateKey::new();
+    let recipient = StacksAddress::burn_address(false).to_account_principal();
+    let parent_block_id = StacksBlockId([0x11; 32]);
+    let rule = TxOrderingRule::SaltedTxid;
+
+    let coinbase_tx = make_user_coinbase(&sender_1, 0, 0);
+    let sender_1_txs: Vec<_> = (1..5)
+        .map(|nonce| make_user_stacks_transfer(&sender_1, nonce, 1, &recipient, 1))
+        .collect();
+    let sender_2_txs: Vec<_> = (0..4)
+        .map(|nonce| make_user_stacks_transfer(&sender_2, nonce, 1, &recipient, 1))
+        .collect();
+
+    let mut txs = vec![coinbase_tx.clone()];
+    txs.extend(sender_1_txs.iter().cloned());
+    txs.extend(sender_2_txs.iter().cloned());
+    let ordered = rule.order_transactions(&parent_block_id, txs.clone());
+
+    // the coinbase stays first, and each sender's transactions stay in nonce order
+    assert_eq!(ordered.len(), txs.len());
+    assert_eq!(ordered[0], coinbase_tx);
+    for sender_txs in [&sender_1_txs, &sender_2_txs] {
+        let origin = sender_txs[0].origin_address();
+        let ordered_sender_txs: Vec<_> = ordered[1..]
+            .iter()
+            .filter(|tx| tx.origin_address() == origin)
+            .cloned()
+            .collect();
+        assert_eq!(&ordered_sender_txs, sender_txs);
+    }
+
+    // the order doesn't depend on the order the transactions were selected in
+    let mut interleaved = vec![coinbase_tx.clone()];
+    for (tx_1, tx_2) in sender_1_txs.iter().zip(sender_2_txs.iter()) {
+        interleaved.push(tx_2.clone());
+        interleaved.push(tx_1.clone());
+    }
+    assert_eq!(
+        rule.order_transactions(&parent_block_id, interleaved),
+        ordered
+    );
+
+    // the committed order doesn't change the block header
+    let mut header = NakamotoBlockHeader::empty();
+    header.parent_block_id = parent_block_id;
+    let mut block = NakamotoBlock {
+        header: header.clone(),
+        txs: ordered.clone(),
+    };
+    block.check_tx_ordering(rule).unwrap();
+    assert_eq!(block.header, header);
+
+    let swap_idx = (1..ordered.len() - 1)
+        .find(|i| ordered[*i].origin_address() != ordered[*i + 1].origin_address())
+        .unwrap();
+    block.txs.swap(swap_idx, swap_idx + 1);
+    block.check_tx_ordering(rule).unwrap_err();
+}
+
 pub mod nakamoto_block_signatures {
     use super::*;
 
@@ -0,0 +1,569 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Chainstate snapshots let a new node start from another node's chainstate instead of
+//! processing every block since genesis.
+//!
+//! A snapshot is a directory holding a signed manifest and a content-addressed object store.
+//! The manifest lists every file of the node's burnchain DBs, sortition DB, chainstate DBs and
+//! MARFs with its SHA-256 hash, and the Bitcoin block (and canonical Stacks tip) the state was
+//! exported at. Each file is stored once, under `objects/<sha256>`. SQLite databases are copied
+//! with `VACUUM INTO`, so the snapshot holds a consistent, compacted copy of each of them.
+//!
+//! The snapshot ID is the SHA-256 hash of the manifest's contents, and is what the exporter
+//! signs. A node only restores snapshots signed by a key it trusts, and checks every file's
+//! hash as it is restored. Once restored, the node picks up the burnchain and the Stacks chain
+//! from the snapshot's anchor.
+
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::{error, fmt, fs, io};
+
+use rusqlite::OpenFlags;
+use sha2::{Digest, Sha256};
+use stacks_common::types::chainstate::{BlockHeaderHash, BurnchainHeaderHash, ConsensusHash};
+use stacks_common::types::{PrivateKey, PublicKey};
+use stacks_common::util::hash::Sha256Sum;
+use stacks_common::util::secp256k1::{MessageSignature, Secp256k1PrivateKey, Secp256k1PublicKey};
+
+use crate::chainstate::burn::db::sortdb::SortitionDB;
+use crate::util_lib::db::{sqlite_open, Error as db_error};
+
+/// Version of the snapshot format
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+/// Name of the manifest file in a snapshot directory
+pub const SNAPSHOT_MANIFEST_FILE: &str = "manifest.json";
+/// Name of the object store in a snapshot directory
+const SNAPSHOT_OBJECTS_DIR: &str = "objects";
+
+/// The files and directories of a node's working directory that a snapshot holds
+pub const SNAPSHOT_ROOTS: &[&str] = &["burnchain", "chainstate", "headers.sqlite"];
+/// The files and directories under `SNAPSHOT_ROOTS` that are local to a node
+const SNAPSHOT_EXCLUDED: &[&str] = &[
+    "chainstate/mempool.sqlite",
+    "chainstate/estimates",
+    "chainstate/fee_estimator_scalar_rate.sqlite",
+];
+/// Suffixes of SQLite's temporary files, whose contents `VACUUM INTO` already includes
+const SQLITE_TEMP_SUFFIXES: &[&str] = &["-wal", "-shm", "-journal"];
+/// The first bytes of every SQLite database file
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+#[derive(Debug)]
+pub enum Error {
+    /// I/O error
+    IOError(io::Error),
+    /// Database error
+    DBError(db_error),
+    /// The manifest is missing, malformed or of an unsupported version
+    BadManifest(String),
+    /// The snapshot is for a different network
+    WrongNetwork,
+    /// The snapshot is not signed by a trusted key
+    UntrustedSigner(Secp256k1PublicKey),
+    /// The manifest's signature does not match its contents
+    BadSignature,
+    /// A restored file does not match the manifest
+    ObjectMismatch(String),
+    /// The node already has chainstate
+    AlreadyExists(PathBuf),
+    /// The restored state is not at the manifest's anchor
+    AnchorMismatch,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::IOError(e) => fmt::Display::fmt(e, f),
+            Error::DBError(e) => fmt::Display::fmt(e, f),
+            Error::BadManifest(msg) => write!(f, "Bad snapshot manifest: {msg}"),
+            Error::WrongNetwork => write!(f, "Snapshot is for a different network"),
+            Error::UntrustedSigner(key) => {
+                write!(f, "Snapshot is signed by an untrusted key {}", key.to_hex())
+            }
+            Error::BadSignature => write!(f, "Snapshot signature does not match its manifest"),
+            Error::ObjectMismatch(path) => write!(f, "Snapshot file {path} is corrupt"),
+            Error::AlreadyExists(path) => write!(f, "{} already exists", path.display()),
+            Error::AnchorMismatch => write!(f, "Restored state is not at the snapshot's anchor"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn cause(&self) -> Option<&dyn error::Error> {
+        match self {
+            Error::IOError(e) => Some(e),
+            Error::DBError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::IOError(e)
+    }
+}
+
+impl From<db_error> for Error {
+    fn from(e: db_error) -> Self {
+        Error::DBError(e)
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(e: rusqlite::Error) -> Self {
+        Error::DBError(db_error::SqliteError(e))
+    }
+}
+
+/// The Bitcoin block, and the canonical Stacks tip as of that block, that a snapshot was
+/// taken at
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotAnchor {
+    pub burn_block_height: u64,
+    pub burn_header_hash: BurnchainHeaderHash,
+    pub consensus_hash: ConsensusHash,
+    pub stacks_block_hash: BlockHeaderHash,
+    pub stacks_block_height: u64,
+}
+
+impl SnapshotAnchor {
+    /// The anchor of the state in `sortdb`
+    pub fn load(sortdb: &SortitionDB) -> Result<SnapshotAnchor, db_error> {
+        let burn_tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn())?;
+        let (consensus_hash, stacks_block_hash, stacks_block_height) =
+            SortitionDB::get_canonical_stacks_chain_tip_hash_and_height(sortdb.conn())?;
+        Ok(SnapshotAnchor {
+            burn_block_height: burn_tip.block_height,
+            burn_header_hash: burn_tip.burn_header_hash,
+            consensus_hash,
+            stacks_block_hash,
+            stacks_block_height,
+        })
+    }
+}
+
+/// A file of a snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotFile {
+    /// Path relative to the node's working directory, with `/` separators
+    pub path: String,
+    pub size: u64,
+    pub sha256: Sha256Sum,
+}
+
+/// The signed part of a snapshot manifest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotContents {
+    pub version: u32,
+    pub mainnet: bool,
+    pub chain_id: u32,
+    pub anchor: SnapshotAnchor,
+    /// Sorted by path
+    pub files: Vec<SnapshotFile>,
+}
+
+impl SnapshotContents {
+    /// The content address of the snapshot
+    pub fn snapshot_id(&self) -> Sha256Sum {
+        let bytes = serde_json::to_vec(self).expect("FATAL: failed to serialize snapshot contents");
+        Sha256Sum::from_data(&bytes)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub contents: SnapshotContents,
+    pub signer: Secp256k1PublicKey,
+    pub signature: MessageSignature,
+}
+
+impl SnapshotManifest {
+    /// Read the manifest of the snapshot in `snapshot_dir`
+    pub fn load(snapshot_dir: &Path) -> Result<SnapshotManifest, Error> {
+        let bytes = fs::read(snapshot_dir.join(SNAPSHOT_MANIFEST_FILE))?;
+        let manifest: SnapshotManifest = serde_json::from_slice(&bytes)
+            .map_err(|e| Error::BadManifest(format!("failed to parse: {e}")))?;
+        if manifest.contents.version != SNAPSHOT_FORMAT_VERSION {
+            return Err(Error::BadManifest(format!(
+                "unsupported version {}",
+                manifest.contents.version
+            )));
+        }
+        Ok(manifest)
+    }
+
+    /// Check that the manifest is signed by one of `trusted_signers`
+    pub fn verify(&self, trusted_signers: &[Secp256k1PublicKey]) -> Result<(), Error> {
+        if !trusted_signers.contains(&self.signer) {
+            return Err(Error::UntrustedSigner(self.signer));
+        }
+        let snapshot_id = self.contents.snapshot_id();
+        match self.signer.verify(snapshot_id.as_bytes(), &self.signature) {
+            Ok(true) => Ok(()),
+            _ => Err(Error::BadSignature),
+        }
+    }
+}
+
+/// Export the chainstate of the node whose working directory (for its network mode) is
+/// `node_dir` to a new snapshot in `snapshot_dir`, signed with `signing_key`. The node must not
+/// be running.
+pub fn export_snapshot(
+    node_dir: &Path,
+    snapshot_dir: &Path,
+    mainnet: bool,
+    chain_id: u32,
+    anchor: SnapshotAnchor,
+    signing_key: &Secp256k1PrivateKey,
+) -> Result<SnapshotManifest, Error> {
+    if snapshot_dir.join(SNAPSHOT_MANIFEST_FILE).exists() {
+        return Err(Error::AlreadyExists(snapshot_dir.to_path_buf()));
+    }
+    let objects_dir = snapshot_dir.join(SNAPSHOT_OBJECTS_DIR);
+    fs::create_dir_all(&objects_dir)?;
+
+    let mut paths = vec![];
+    for root in SNAPSHOT_ROOTS.iter() {
+        collect_files(node_dir, Path::new(root), &mut paths)?;
+    }
+    paths.sort();
+
+    let mut files = Vec::with_capacity(paths.len());
+    let staging_path = objects_dir.join("staging");
+    for path in paths.into_iter() {
+        let _ = fs::remove_file(&staging_path);
+        let source_path = node_dir.join(&path);
+        if is_sqlite_db(&source_path)? {
+            let conn = sqlite_open(&source_path, OpenFlags::SQLITE_OPEN_READ_WRITE, false)?;
+            let staging_path_str = staging_path
+                .to_str()
+                .ok_or_else(|| Error::BadManifest("non-UTF-8 snapshot path".into()))?;
+            conn.execute("VACUUM INTO ?1", [staging_path_str])?;
+        } else {
+            fs::copy(&source_path, &staging_path)?;
+        }
+        let (sha256, size) = hash_file(&staging_path)?;
+        let object_path = objects_dir.join(sha256.to_hex());
+        if object_path.exists() {
+            fs::remove_file(&staging_path)?;
+        } else {
+            fs::rename(&staging_path, &object_path)?;
+        }
+        debug!("Exported snapshot file"; "path" => &path, "sha256" => %sha256, "size" => size);
+        files.push(SnapshotFile { path, size, sha256 });
+    }
+
+    let contents = SnapshotContents {
+        version: SNAPSHOT_FORMAT_VERSION,
+        mainnet,
+        chain_id,
+        anchor,
+        files,
+    };
+    let signature = signing_key
+        .sign(contents.snapshot_id().as_bytes())
+        .map_err(|e| Error::BadManifest(format!("failed to sign: {e}")))?;
+    let manifest = SnapshotManifest {
+        contents,
+        signer: Secp256k1PublicKey::from_private(signing_key),
+        signature,
+    };
+    let bytes = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| Error::BadManifest(format!("failed to serialize: {e}")))?;
+    fs::write(snapshot_dir.join(SNAPSHOT_MANIFEST_FILE), bytes)?;
+    Ok(manifest)
+}
+
+/// Restore the snapshot in `snapshot_dir` into the node working directory (for its network
+/// mode) `node_dir`, which must not have any chainstate yet. The snapshot must be for the
+/// node's network and signed by one of `trusted_signers`. The caller should check the restored
+/// sortition DB against the manifest's anchor, and `discard_restored_snapshot` if it does not
+/// match. Nothing is left behind if the restore fails.
+pub fn restore_snapshot(
+    snapshot_dir: &Path,
+    node_dir: &Path,
+    mainnet: bool,
+    chain_id: u32,
+    trusted_signers: &[Secp256k1PublicKey],
+) -> Result<SnapshotManifest, Error> {
+    let manifest = SnapshotManifest::load(snapshot_dir)?;
+    if manifest.contents.mainnet != mainnet || manifest.contents.chain_id != chain_id {
+        return Err(Error::WrongNetwork);
+    }
+    manifest.verify(trusted_signers)?;
+    for root in SNAPSHOT_ROOTS.iter() {
+        let path = node_dir.join(root);
+        if path.exists() {
+            return Err(Error::AlreadyExists(path));
+        }
+    }
+
+    let objects_dir = snapshot_dir.join(SNAPSHOT_OBJECTS_DIR);
+    let res = manifest.contents.files.iter().try_for_each(|file| {
+        let dest_path = node_dir.join(checked_relative_path(&file.path)?);
+        if let Some(parent) = dest_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let object_path = objects_dir.join(file.sha256.to_hex());
+        let (sha256, size) = copy_and_hash(&object_path, &dest_path)?;
+        if sha256 != file.sha256 || size != file.size {
+            return Err(Error::ObjectMismatch(file.path.clone()));
+        }
+        Ok(())
+    });
+    if let Err(e) = res {
+        discard_restored_snapshot(node_dir);
+        return Err(e);
+    }
+    Ok(manifest)
+}
+
+/// Delete the chainstate restored from a snapshot into `node_dir`
+pub fn discard_restored_snapshot(node_dir: &Path) {
+    for root in SNAPSHOT_ROOTS.iter() {
+        let path = node_dir.join(root);
+        let res = if path.is_dir() {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        };
+        if let Err(e) = res {
+            if e.kind() != io::ErrorKind::NotFound {
+                warn!("Failed to discard restored snapshot data at {path:?}: {e:?}");
+            }
+        }
+    }
+}
+
+/// Add the snapshotted files at or below `rel_path` in `node_dir` to `paths`
+fn collect_files(node_dir: &Path, rel_path: &Path, paths: &mut Vec<String>) -> Result<(), Error> {
+    let rel_path_str = rel_path
+        .to_str()
+        .ok_or_else(|| Error::BadManifest(format!("non-UTF-8 path {rel_path:?}")))?
+        .replace(std::path::MAIN_SEPARATOR, "/");
+    if SNAPSHOT_EXCLUDED.contains(&rel_path_str.as_str())
+        || SQLITE_TEMP_SUFFIXES
+            .iter()
+            .any(|suffix| rel_path_str.ends_with(suffix))
+    {
+        return Ok(());
+    }
+    let path = node_dir.join(rel_path);
+    let Ok(metadata) = fs::metadata(&path) else {
+        return Ok(());
+    };
+    if metadata.is_dir() {
+        for entry in fs::read_dir(&path)? {
+            collect_files(node_dir, &rel_path.join(entry?.file_name()), paths)?;
+        }
+    } else if metadata.is_file() {
+        paths.push(rel_path_str);
+    }
+    Ok(())
+}
+
+/// The path of a snapshot file, if it stays within the node's working directory
+fn checked_relative_path(path: &str) -> Result<PathBuf, Error> {
+    let rel_path = PathBuf::from(path);
+    let in_roots = SNAPSHOT_ROOTS
+        .iter()
+        .any(|root| path == *root || path.starts_with(&format!("{root}/")));
+    let normal = rel_path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+    if !in_roots || !normal {
+        return Err(Error::BadManifest(format!("invalid file path {path}")));
+    }
+    Ok(rel_path)
+}
+
+fn is_sqlite_db(path: &Path) -> Result<bool, Error> {
+    let mut header = [0u8; SQLITE_HEADER.len()];
+    let mut file = fs::File::open(path)?;
+    match file.read_exact(&mut header) {
+        Ok(()) => Ok(&header == SQLITE_HEADER),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn hash_file(path: &Path) -> Result<(Sha256Sum, u64), Error> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let size = io::copy(&mut file, &mut hasher)?;
+    Ok((Sha256Sum(hasher.finalize().into()), size))
+}
+
+/// Copy `from` to `to`, and return the hash and size of what was copied
+fn copy_and_hash(from: &Path, to: &Path) -> Result<(Sha256Sum, u64), Error> {
+    let mut reader = fs::File::open(from)?;
+    let mut writer = fs::File::create(to)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 1 << 20];
+    let mut size = 0;
+    loop {
+        let nread = reader.read(&mut buf)?;
+        if nread == 0 {
+            break;
+        }
+        hasher.update(&buf[..nread]);
+        writer.write_all(&buf[..nread])?;
+        size += nread as u64;
+    }
+    writer.sync_all()?;
+    Ok((Sha256Sum(hasher.finalize().into()), size))
+}
+
+#[cfg(test)]
+mod test {
+    use rusqlite::Connection;
+
+    use super::*;
+
+    fn anchor() -> SnapshotAnchor {
+        SnapshotAnchor {
+            burn_block_height: 100,
+            burn_header_hash: BurnchainHeaderHash([0x01; 32]),
+            consensus_hash: ConsensusHash([0x02; 20]),
+            stacks_block_hash: BlockHeaderHash([0x03; 32]),
+            stacks_block_height: 50,
+        }
+    }
+
+    fn make_node_dir(path: &Path) {
+        let _ = fs::remove_dir_all(path);
+        fs::create_dir_all(path.join("chainstate/vm")).unwrap();
+        fs::create_dir_all(path.join("burnchain")).unwrap();
+
+        let conn = Connection::open(path.join("chainstate/vm/index.sqlite")).unwrap();
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             CREATE TABLE data(value TEXT);
+             INSERT INTO data VALUES ('hello');",
+        )
+        .unwrap();
+        // the same contents as another file
+        fs::write(path.join("chainstate/vm/index.sqlite.blobs"), b"blobs").unwrap();
+        fs::write(path.join("burnchain/blobs"), b"blobs").unwrap();
+        // local to the node
+        fs::write(path.join("chainstate/mempool.sqlite"), b"mempool").unwrap();
+    }
+
+    #[test]
+    fn test_export_restore_snapshot() {
+        let base = PathBuf::from("/tmp/stacks-node-tests/test_export_restore_snapshot");
+        let node_dir = base.join("node");
+        let snapshot_dir = base.join("snapshot");
+        let restore_dir = base.join("restore");
+        let _ = fs::remove_dir_all(&base);
+        make_node_dir(&node_dir);
+
+        let signing_key = Secp256k1PrivateKey::new();
+        let signer = Secp256k1PublicKey::from_private(&signing_key);
+        let manifest = export_snapshot(
+            &node_dir,
+            &snapshot_dir,
+            false,
+            0x80000000,
+            anchor(),
+            &signing_key,
+        )
+        .unwrap();
+
+        let paths: Vec<_> = manifest
+            .contents
+            .files
+            .iter()
+            .map(|file| file.path.as_str())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "burnchain/blobs",
+                "chainstate/vm/index.sqlite",
+                "chainstate/vm/index.sqlite.blobs"
+            ]
+        );
+        // identical files are stored once
+        assert_eq!(
+            fs::read_dir(snapshot_dir.join(SNAPSHOT_OBJECTS_DIR))
+                .unwrap()
+                .count(),
+            2
+        );
+        assert_eq!(SnapshotManifest::load(&snapshot_dir).unwrap(), manifest);
+
+        // only snapshots for this network, signed by a trusted key, are restored
+        let other_signer = Secp256k1PublicKey::from_private(&Secp256k1PrivateKey::new());
+        assert!(matches!(
+            restore_snapshot(&snapshot_dir, &restore_dir, true, 1, &[signer]),
+            Err(Error::WrongNetwork)
+        ));
+        assert!(matches!(
+            restore_snapshot(
+                &snapshot_dir,
+                &restore_dir,
+                false,
+                0x80000000,
+                &[other_signer]
+            ),
+            Err(Error::UntrustedSigner(_))
+        ));
+
+        restore_snapshot(&snapshot_dir, &restore_dir, false, 0x80000000, &[signer]).unwrap();
+        let conn = Connection::open(restore_dir.join("chainstate/vm/index.sqlite")).unwrap();
+        let value: String = conn
+            .query_row("SELECT value FROM data", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(value, "hello");
+        assert_eq!(
+            fs::read(restore_dir.join("burnchain/blobs")).unwrap(),
+            b"blobs"
+        );
+        assert!(!restore_dir.join("chainstate/mempool.sqlite").exists());
+
+        // a node with chainstate is not overwritten
+        assert!(matches!(
+            restore_snapshot(&snapshot_dir, &restore_dir, false, 0x80000000, &[signer]),
+            Err(Error::AlreadyExists(_))
+        ));
+
+        // a corrupt object is detected, and nothing is left behind
+        discard_restored_snapshot(&restore_dir);
+        let blobs_sha256 = manifest.contents.files[0].sha256.to_hex();
+        fs::write(
+            snapshot_dir.join(SNAPSHOT_OBJECTS_DIR).join(blobs_sha256),
+            b"corrupt",
+        )
+        .unwrap();
+        assert!(matches!(
+            restore_snapshot(&snapshot_dir, &restore_dir, false, 0x80000000, &[signer]),
+            Err(Error::ObjectMismatch(_))
+        ));
+        for root in SNAPSHOT_ROOTS.iter() {
+            assert!(!restore_dir.join(root).exists());
+        }
+
+        // a tampered manifest is rejected
+        let mut tampered = manifest.clone();
+        tampered.contents.anchor.burn_block_height += 1;
+        assert!(matches!(
+            tampered.verify(&[signer]),
+            Err(Error::BadSignature)
+        ));
+    }
+}
@@ -605,6 +605,11 @@ impl StacksBlock {
                 error!("Versioned smart contracts not supported before Stacks 2.1");
                 return false;
             }
+            if *version_opt == Some(ClarityVersion::Clarity4) && epoch_id < StacksEpochId::Epoch31 {
+                // not supported
+                error!("Clarity 4 contracts not supported before Stacks 3.1"; "txid" => %tx.txid());
+                return false;
+            }
         }
         if let TransactionPayload::TenureChange(..) = &tx.payload {
             if epoch_id < StacksEpochId::Epoch30 {
@@ -1716,6 +1721,7 @@ mod test {
             StacksEpochId::Epoch24,
             StacksEpochId::Epoch25,
             StacksEpochId::Epoch30,
+            StacksEpochId::Epoch31,
         ];
         let get_tx_root = |txs: &Vec<StacksTransaction>| {
             let txid_vecs = txs.iter().map(|tx| tx.txid().as_bytes().to_vec()).collect();
@@ -2037,6 +2043,17 @@ mod test {
                 Some(ClarityVersion::Clarity1),
             ),
         );
+        let tx_clarity4_smart_contract = StacksTransaction::new(
+            TransactionVersion::Testnet,
+            origin_auth.clone(),
+            TransactionPayload::SmartContract(
+                TransactionSmartContract {
+                    name: ContractName::try_from("hello-world").unwrap(),
+                    code_body: StacksString::from_str("(print \"hello world\")").unwrap(),
+                },
+                Some(ClarityVersion::Clarity4),
+            ),
+        );
 
         let tenure_change_payload = TenureChangePayload {
             tenure_consensus_hash: ConsensusHash([0x01; 20]),
@@ -2064,6 +2081,7 @@ mod test {
         let no_coinbase = vec![tx_transfer.clone()];
         let coinbase_contract = vec![tx_coinbase_contract.clone()];
         let versioned_contract = vec![tx_versioned_smart_contract.clone()];
+        let clarity4_contract = vec![tx_clarity4_smart_contract.clone()];
         let nakamoto_coinbase = vec![tx_coinbase_proof.clone()];
         let tenure_change_tx = vec![tx_tenure_change.clone()];
         let nakamoto_txs = vec![tx_coinbase_proof.clone(), tx_tenure_change.clone()];
@@ -2094,6 +2112,14 @@ mod test {
             header.clone(),
             None,
         );
+        verify_block_epoch_validation(
+            &clarity4_contract,
+            Some(tx_coinbase.clone()),
+            Some(tx_coinbase_proof.clone()),
+            StacksEpochId::Epoch31,
+            header.clone(),
+            None,
+        );
         verify_block_epoch_validation(
             &coinbase_contract,
             None,
@@ -0,0 +1,185 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Typed accessors for the boot contract state that the node reads directly: PoX-4 stacking
+//! state, the `.signers` signer sets, and `.signers-voting` aggregate key votes.  Each accessor
+//! builds the map key and decodes the map value the same way the contract's own read-only
+//! functions would, so callers never hand-build tuple keys.
+
+use clarity::vm::database::ClarityDatabase;
+use clarity::vm::errors::{InterpreterError, InterpreterResult};
+use clarity::vm::types::{PrincipalData, TupleData, Value};
+use stacks_common::types::StacksEpochId;
+
+use crate::chainstate::stacks::address::PoxAddress;
+use crate::chainstate::stacks::boot::{
+    POX_4_NAME, SIGNERS_NAME, SIGNERS_PK_LEN, SIGNERS_VOTING_NAME,
+};
+use crate::util_lib::boot::boot_code_id;
+
+/// An entry in PoX-4's `stacking-state` map
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoxStackingState {
+    pub pox_addr: PoxAddress,
+    pub lock_period: u128,
+    pub first_reward_cycle: u128,
+    pub reward_set_indexes: Vec<u128>,
+    pub delegated_to: Option<PrincipalData>,
+}
+
+/// A member of a reward cycle's signer set, from `.signers`' `cycle-signer-set` map
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignerSetEntry {
+    pub signer: PrincipalData,
+    pub weight: u128,
+}
+
+/// A signer's aggregate key vote, from `.signers-voting`'s `votes` map
+#[derive(Debug, Clone, PartialEq)]
+pub struct AggregateKeyVote {
+    pub aggregate_public_key: Vec<u8>,
+    pub signer_weight: u128,
+}
+
+/// Read-only typed view of the boot contracts' data maps
+pub struct BootMaps<'a, 'b> {
+    db: &'a mut ClarityDatabase<'b>,
+    mainnet: bool,
+    epoch: StacksEpochId,
+}
+
+impl<'a, 'b> BootMaps<'a, 'b> {
+    pub fn new(db: &'a mut ClarityDatabase<'b>, mainnet: bool, epoch: StacksEpochId) -> Self {
+        Self { db, mainnet, epoch }
+    }
+
+    /// Look up `key` in the boot contract `contract_name`'s map `map_name`
+    fn fetch(
+        &mut self,
+        contract_name: &str,
+        map_name: &str,
+        key: &Value,
+    ) -> InterpreterResult<Option<Value>> {
+        let contract_id = boot_code_id(contract_name, self.mainnet);
+        self.db
+            .fetch_entry_unknown_descriptor(&contract_id, map_name, key, &self.epoch)?
+            .expect_optional()
+    }
+
+    fn make_tuple(fields: Vec<(&str, Value)>) -> InterpreterResult<Value> {
+        let fields = fields
+            .into_iter()
+            .map(|(name, value)| (name.into(), value))
+            .collect();
+        Ok(Value::Tuple(TupleData::from_data(fields)?))
+    }
+
+    fn get_field(tuple: &TupleData, name: &str) -> InterpreterResult<Value> {
+        Ok(tuple.get(name)?.clone())
+    }
+
+    /// Get `stacker`'s entry in PoX-4's `stacking-state` map.  Unlike PoX-4's `get-stacker-info`,
+    /// this returns the entry even if its lock has expired.
+    pub fn get_stacking_state(
+        &mut self,
+        stacker: &PrincipalData,
+    ) -> InterpreterResult<Option<PoxStackingState>> {
+        let key = Self::make_tuple(vec![("stacker", Value::Principal(stacker.clone()))])?;
+        let Some(value) = self.fetch(POX_4_NAME, "stacking-state", &key)? else {
+            return Ok(None);
+        };
+        let tuple = value.expect_tuple()?;
+        let pox_addr_value = Self::get_field(&tuple, "pox-addr")?;
+        let pox_addr =
+            PoxAddress::try_from_pox_tuple(self.mainnet, &pox_addr_value).ok_or_else(|| {
+                InterpreterError::Expect(format!(
+                    "Invalid PoX address in stacking-state for {stacker}"
+                ))
+            })?;
+        let reward_set_indexes = Self::get_field(&tuple, "reward-set-indexes")?
+            .expect_list()?
+            .into_iter()
+            .map(|index| index.expect_u128())
+            .collect::<InterpreterResult<_>>()?;
+        let delegated_to = Self::get_field(&tuple, "delegated-to")?
+            .expect_optional()?
+            .map(|delegate| delegate.expect_principal())
+            .transpose()?;
+        Ok(Some(PoxStackingState {
+            pox_addr,
+            lock_period: Self::get_field(&tuple, "lock-period")?.expect_u128()?,
+            first_reward_cycle: Self::get_field(&tuple, "first-reward-cycle")?.expect_u128()?,
+            reward_set_indexes,
+            delegated_to,
+        }))
+    }
+
+    /// Get the signer set for `reward_cycle`, as stored in `.signers`' `cycle-signer-set` map
+    pub fn get_signer_set(
+        &mut self,
+        reward_cycle: u64,
+    ) -> InterpreterResult<Option<Vec<SignerSetEntry>>> {
+        let key = Value::UInt(reward_cycle.into());
+        let Some(value) = self.fetch(SIGNERS_NAME, "cycle-signer-set", &key)? else {
+            return Ok(None);
+        };
+        let signers = value
+            .expect_list()?
+            .into_iter()
+            .map(|entry| {
+                let tuple = entry.expect_tuple()?;
+                Ok(SignerSetEntry {
+                    signer: Self::get_field(&tuple, "signer")?.expect_principal()?,
+                    weight: Self::get_field(&tuple, "weight")?.expect_u128()?,
+                })
+            })
+            .collect::<InterpreterResult<_>>()?;
+        Ok(Some(signers))
+    }
+
+    /// Get `signer`'s vote in `round` of `reward_cycle`'s aggregate key vote
+    pub fn get_aggregate_key_vote(
+        &mut self,
+        reward_cycle: u64,
+        round: u64,
+        signer: &PrincipalData,
+    ) -> InterpreterResult<Option<AggregateKeyVote>> {
+        let key = Self::make_tuple(vec![
+            ("reward-cycle", Value::UInt(reward_cycle.into())),
+            ("round", Value::UInt(round.into())),
+            ("signer", Value::Principal(signer.clone())),
+        ])?;
+        let Some(value) = self.fetch(SIGNERS_VOTING_NAME, "votes", &key)? else {
+            return Ok(None);
+        };
+        let tuple = value.expect_tuple()?;
+        Ok(Some(AggregateKeyVote {
+            aggregate_public_key: Self::get_field(&tuple, "aggregate-public-key")?
+                .expect_buff(SIGNERS_PK_LEN)?,
+            signer_weight: Self::get_field(&tuple, "signer-weight")?.expect_u128()?,
+        }))
+    }
+
+    /// Get the aggregate public key approved for `reward_cycle`, if any
+    pub fn get_approved_aggregate_key(
+        &mut self,
+        reward_cycle: u64,
+    ) -> InterpreterResult<Option<Vec<u8>>> {
+        let key = Value::UInt(reward_cycle.into());
+        self.fetch(SIGNERS_VOTING_NAME, "aggregate-public-keys", &key)?
+            .map(|value| value.expect_buff(SIGNERS_PK_LEN))
+            .transpose()
+    }
+}
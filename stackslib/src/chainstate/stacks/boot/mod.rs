@@ -49,6 +49,7 @@ use crate::burnchains::bitcoin::address::BitcoinAddress;
 use crate::burnchains::{Address, Burnchain, PoxConstants};
 use crate::chainstate::burn::db::sortdb::SortitionDB;
 use crate::chainstate::stacks::address::{PoxAddress, StacksAddressExtensions};
+use crate::chainstate::stacks::boot::maps::BootMaps;
 use crate::chainstate::stacks::db::{StacksChainState, StacksDBConn};
 use crate::chainstate::stacks::index::marf::MarfConnection;
 use crate::chainstate::stacks::Error;
@@ -68,6 +69,7 @@ pub const BOOT_CODE_LOCKUP: &'static str = std::include_str!("lockup.clar");
 pub const BOOT_CODE_COSTS: &'static str = std::include_str!("costs.clar");
 pub const BOOT_CODE_COSTS_2: &'static str = std::include_str!("costs-2.clar");
 pub const BOOT_CODE_COSTS_3: &'static str = std::include_str!("costs-3.clar");
+pub const BOOT_CODE_COSTS_4: &'static str = std::include_str!("costs-4.clar");
 pub const BOOT_CODE_COSTS_2_TESTNET: &'static str = std::include_str!("costs-2-testnet.clar");
 pub const BOOT_CODE_COST_VOTING_MAINNET: &'static str = std::include_str!("cost-voting.clar");
 pub const BOOT_CODE_BNS: &'static str = std::include_str!("bns.clar");
@@ -96,6 +98,7 @@ pub const SIGNERS_VOTING_BODY: &'static str = std::include_str!("signers-voting.
 pub const COSTS_1_NAME: &'static str = "costs";
 pub const COSTS_2_NAME: &'static str = "costs-2";
 pub const COSTS_3_NAME: &'static str = "costs-3";
+pub const COSTS_4_NAME: &'static str = "costs-4";
 /// This contract name is used in testnet **only** to lookup an initial
 ///  setting for the pox-4 aggregate key. This contract should contain a `define-read-only`
 ///  function called `aggregate-key` with zero arguments which returns a (buff 33)
@@ -105,6 +108,7 @@ pub const BOOT_TEST_POX_4_AGG_KEY_FNAME: &'static str = "aggregate-key";
 pub const MINERS_NAME: &'static str = "miners";
 
 pub mod docs;
+pub mod maps;
 
 lazy_static! {
     pub static ref BOOT_CODE_POX_MAINNET: String =
@@ -153,6 +157,62 @@ fn make_testnet_cost_voting() -> String {
         )
 }
 
+/// A contract instantiated by the boot code address outside of any transaction
+#[derive(Debug, Clone, PartialEq)]
+pub struct BootContract {
+    pub name: ContractName,
+    pub code: String,
+    /// `None` for the default Clarity version of the epoch it is instantiated in
+    pub clarity_version: Option<ClarityVersion>,
+}
+
+impl BootContract {
+    pub fn new(name: &str, code: &str, clarity_version: Option<ClarityVersion>) -> BootContract {
+        BootContract {
+            name: ContractName::try_from(name.to_string())
+                .expect("FATAL: invalid boot-code contract name"),
+            code: code.to_string(),
+            clarity_version,
+        }
+    }
+}
+
+/// Supplies the boot contracts of a network, so that networks other than the Stacks mainnet and
+///  testnet (e.g. subnets and appchains) can instantiate their own.
+///
+/// The node's own logic calls some boot contracts by name (`.pox`, `.costs`, `.lockup`, ...),
+///  so a network that replaces them must keep their names and interfaces.  The contracts that
+///  each epoch transition instantiates (such as `.pox-2` in 2.1) are always instantiated; a
+///  provider can only add to them.
+pub trait BootContractProvider: Send + Sync {
+    /// The contracts instantiated in the genesis block, in order
+    fn genesis_contracts(&self, mainnet: bool) -> Vec<BootContract>;
+
+    /// The contracts to instantiate, in order, in the first block of `epoch` (after those that
+    ///  its transition instantiates).  A contract is thus activated at the burnchain height at
+    ///  which the network's epoch list starts `epoch`.
+    fn epoch_contracts(&self, _mainnet: bool, _epoch: StacksEpochId) -> Vec<BootContract> {
+        vec![]
+    }
+}
+
+/// The boot contracts of the Stacks mainnet and testnet
+pub struct StacksBootContracts;
+
+impl BootContractProvider for StacksBootContracts {
+    fn genesis_contracts(&self, mainnet: bool) -> Vec<BootContract> {
+        let boot_code = if mainnet {
+            *STACKS_BOOT_CODE_MAINNET
+        } else {
+            *STACKS_BOOT_CODE_TESTNET
+        };
+        boot_code
+            .iter()
+            .map(|(name, code)| BootContract::new(name, code, None))
+            .collect()
+    }
+}
+
 pub fn make_contract_id(addr: &StacksAddress, name: &str) -> QualifiedContractIdentifier {
     QualifiedContractIdentifier::new(
         StandardPrincipalData::from(addr.clone()),
@@ -609,6 +669,30 @@ impl StacksChainState {
             .map_err(Error::ClarityError)
     }
 
+    /// Read boot contract state at `stacks_block_id` through the typed accessors in `BootMaps`
+    pub fn with_boot_maps<F, R>(
+        &mut self,
+        sortdb: &SortitionDB,
+        stacks_block_id: &StacksBlockId,
+        to_do: F,
+    ) -> Result<R, Error>
+    where
+        F: FnOnce(&mut BootMaps) -> InterpreterResult<R>,
+    {
+        let iconn = sortdb.index_handle_at_block(self, stacks_block_id)?;
+        let ro_index = self.state_index.reopen_readonly()?;
+        let headers_db = HeadersDBConn(StacksDBConn::new(&ro_index, ()));
+        let mainnet = self.mainnet;
+        let mut connection = self
+            .clarity_state
+            .read_only_connection_checked(stacks_block_id, &headers_db, &iconn)
+            .map_err(Error::ClarityError)?;
+        let epoch = connection.get_epoch();
+        let result = connection
+            .with_clarity_db_readonly(|db| to_do(&mut BootMaps::new(db, mainnet, epoch)))?;
+        Ok(result)
+    }
+
     pub fn get_liquid_ustx(&mut self, stacks_block_id: &StacksBlockId) -> u128 {
         let mut connection = self.clarity_state.read_only_connection(
             stacks_block_id,
@@ -1342,6 +1426,95 @@ impl StacksChainState {
         }
     }
 
+    /// Compute the PoX reward set of `reward_cycle` from the stacking state as of `block_id`:
+    /// the reward addresses, each repeated once per reward slot it claims, and (from PoX-4 on)
+    /// the signers and their weights.  The reward set is empty if the cycle's epoch does not pay
+    /// PoX rewards, or if too little was stacked for it.
+    ///
+    /// This is the calculation that the chains coordinator performs when it processes a
+    /// reward cycle's anchor block, so `block_id` should be the anchor block to reproduce it.
+    pub fn compute_reward_set(
+        &mut self,
+        burnchain: &Burnchain,
+        sortdb: &SortitionDB,
+        reward_cycle: u64,
+        block_id: &StacksBlockId,
+    ) -> Result<RewardSet, Error> {
+        let cycle_start_height = burnchain.reward_cycle_to_block_height(reward_cycle);
+        let epoch_id = SortitionDB::get_stacks_epoch(sortdb.conn(), cycle_start_height)?
+            .ok_or(Error::PoxNoRewardCycle)?
+            .epoch_id;
+        let active_pox_contract = burnchain
+            .pox_constants
+            .active_pox_contract(cycle_start_height);
+        match epoch_id {
+            StacksEpochId::Epoch10
+            | StacksEpochId::Epoch20
+            | StacksEpochId::Epoch2_05
+            | StacksEpochId::Epoch21 => {
+                // Epochs 1.0 - 2.1 compute reward sets
+            }
+            StacksEpochId::Epoch22 | StacksEpochId::Epoch23 => {
+                info!("PoX reward cycle defaulting to burn in Epochs 2.2 and 2.3");
+                return Ok(RewardSet::empty());
+            }
+            StacksEpochId::Epoch24 => {
+                // Epoch 2.4 computes reward sets, but *only* if PoX-3 is active
+                if active_pox_contract != POX_3_NAME {
+                    // Note: this should not happen in mainnet or testnet, because the no reward cycle start height
+                    //        exists between Epoch 2.4's instantiation height and the pox-3 activation height.
+                    //  However, this *will* happen in testing if Epoch 2.4's instantiation height is set == a reward cycle
+                    //   start height
+                    info!("PoX reward cycle defaulting to burn in Epoch 2.4 because cycle start is before PoX-3 activation");
+                    return Ok(RewardSet::empty());
+                }
+            }
+            StacksEpochId::Epoch25 | StacksEpochId::Epoch30 | StacksEpochId::Epoch31 => {
+                // Epoch 2.5 and 3.x compute reward sets, but *only* if PoX-4 is active
+                if active_pox_contract != POX_4_NAME {
+                    // Note: this should not happen in mainnet or testnet, because the no reward cycle start height
+                    //        exists between Epoch 2.5's instantiation height and the pox-4 activation height.
+                    //  However, this *will* happen in testing if Epoch 2.5's instantiation height is set == a reward cycle
+                    //   start height
+                    info!("PoX reward cycle defaulting to burn in Epoch 2.5 because cycle start is before PoX-4 activation");
+                    return Ok(RewardSet::empty());
+                }
+            }
+        };
+
+        let registered_addrs =
+            self.get_reward_addresses_in_cycle(burnchain, sortdb, reward_cycle, block_id)?;
+
+        let liquid_ustx = self.get_liquid_ustx(block_id);
+
+        let (threshold, participation) = Self::get_reward_threshold_and_participation(
+            &burnchain.pox_constants,
+            &registered_addrs[..],
+            liquid_ustx,
+        );
+
+        if !burnchain
+            .pox_constants
+            .enough_participation(participation, liquid_ustx)
+        {
+            info!("PoX reward cycle did not have enough participation. Defaulting to burn";
+                  "burn_height" => cycle_start_height,
+                  "participation" => participation,
+                  "liquid_ustx" => liquid_ustx,
+                  "registered_addrs" => registered_addrs.len());
+            return Ok(RewardSet::empty());
+        } else {
+            info!("PoX reward cycle threshold computed";
+                  "burn_height" => cycle_start_height,
+                  "threshold" => threshold,
+                  "participation" => participation,
+                  "liquid_ustx" => liquid_ustx,
+                  "registered_addrs" => registered_addrs.len());
+        }
+
+        Ok(Self::make_reward_set(threshold, registered_addrs, epoch_id))
+    }
+
     /// Get the aggregate public key for a given reward cycle from pox 4
     pub fn get_aggregate_public_key_pox_4(
         &mut self,
@@ -1349,26 +1522,13 @@ impl StacksChainState {
         block_id: &StacksBlockId,
         reward_cycle: u64,
     ) -> Result<Option<Vec<u8>>, Error> {
-        let aggregate_public_key_opt = self
-            .eval_boot_code_read_only(
-                sortdb,
-                block_id,
-                SIGNERS_VOTING_NAME,
-                &format!("(get-approved-aggregate-key u{})", reward_cycle),
-            )?
-            .expect_optional()?;
+        let aggregate_public_key = self.with_boot_maps(sortdb, block_id, |maps| {
+            maps.get_approved_aggregate_key(reward_cycle)
+        })?;
         debug!(
             "Aggregate public key for reward cycle {} is {:?}",
-            reward_cycle, aggregate_public_key_opt
+            reward_cycle, aggregate_public_key
         );
-
-        let aggregate_public_key = match aggregate_public_key_opt {
-            Some(value) => {
-                // A point should have 33 bytes exactly.
-                Some(value.expect_buff(33)?)
-            }
-            None => None,
-        };
         Ok(aggregate_public_key)
     }
 }
@@ -3432,6 +3592,22 @@ pub mod test {
                     );
                     assert_eq!(reward_addrs[0].1, 1024 * POX_THRESHOLD_STEPS_USTX);
 
+                    // the computed reward set only pays Alice, and has no signers in PoX-1
+                    let reward_set = with_sortdb(&mut peer, |ref mut chainstate, ref sortdb| {
+                        chainstate.compute_reward_set(
+                            &burnchain,
+                            sortdb,
+                            cur_reward_cycle as u64,
+                            &tip_index_block,
+                        )
+                    })
+                    .unwrap();
+                    assert!(reward_set
+                        .rewarded_addresses
+                        .iter()
+                        .all(|addr| addr.hash160() == key_to_stacks_addr(&alice).bytes));
+                    assert!(reward_set.signers.is_none());
+
                     // Lock-up is consistent with stacker state
                     let alice_account = get_account(&mut peer, &key_to_stacks_addr(&alice).into());
                     assert_eq!(alice_account.stx_balance.amount_unlocked(), 0);
@@ -3448,6 +3624,17 @@ pub mod test {
                 } else {
                     // no reward addresses
                     assert_eq!(reward_addrs.len(), 0);
+
+                    let reward_set = with_sortdb(&mut peer, |ref mut chainstate, ref sortdb| {
+                        chainstate.compute_reward_set(
+                            &burnchain,
+                            sortdb,
+                            cur_reward_cycle as u64,
+                            &tip_index_block,
+                        )
+                    })
+                    .unwrap();
+                    assert!(reward_set.rewarded_addresses.is_empty());
                 }
             }
         }
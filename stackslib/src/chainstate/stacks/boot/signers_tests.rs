@@ -49,7 +49,9 @@ use crate::chainstate::stacks::boot::pox_4_tests::{
 use crate::chainstate::stacks::boot::test::{
     instantiate_pox_peer_with_epoch, key_to_stacks_addr, make_pox_4_lockup, with_sortdb,
 };
-use crate::chainstate::stacks::boot::{NakamotoSignerEntry, SIGNERS_NAME, SIGNERS_VOTING_NAME};
+use crate::chainstate::stacks::boot::{
+    NakamotoSignerEntry, POX_4_NAME, SIGNERS_NAME, SIGNERS_VOTING_NAME,
+};
 use crate::chainstate::stacks::db::StacksChainState;
 use crate::chainstate::stacks::index::marf::MarfConnection;
 use crate::chainstate::stacks::{
@@ -344,6 +346,90 @@ fn signers_db_get_slots() {
     }
 }
 
+#[test]
+fn signers_boot_maps() {
+    let stacker_1 = TestStacker::from_seed(&[3, 4]);
+    let stacker_2 = TestStacker::from_seed(&[5, 6]);
+
+    let (mut peer, _, latest_block_id, reward_cycle) = prepare_signers_test(
+        function_name!(),
+        vec![],
+        &[stacker_1.clone(), stacker_2.clone()],
+        None,
+    );
+    let reward_cycle = u64::try_from(reward_cycle).unwrap();
+
+    // the typed accessors agree with the contracts' own read-only functions
+    let expected_signers = readonly_call(
+        &mut peer,
+        &latest_block_id,
+        SIGNERS_NAME.into(),
+        "get-signers".into(),
+        vec![Value::UInt(reward_cycle.into())],
+    )
+    .expect_optional()
+    .unwrap()
+    .unwrap()
+    .expect_list()
+    .unwrap();
+    let stacker_1_addr = key_to_stacks_addr(&stacker_1.stacker_private_key);
+    let expected_stacker_info = readonly_call(
+        &mut peer,
+        &latest_block_id,
+        POX_4_NAME.into(),
+        "get-stacker-info".into(),
+        vec![PrincipalData::from(stacker_1_addr.clone()).into()],
+    )
+    .expect_optional()
+    .unwrap()
+    .unwrap()
+    .expect_tuple()
+    .unwrap();
+
+    let (signer_set, stacking_state, no_stacking_state, aggregate_key) =
+        with_sortdb(&mut peer, |chainstate, sortdb| {
+            chainstate
+                .with_boot_maps(sortdb, &latest_block_id, |maps| {
+                    Ok((
+                        maps.get_signer_set(reward_cycle)?,
+                        maps.get_stacking_state(&stacker_1_addr.clone().into())?,
+                        maps.get_stacking_state(&boot_code_addr(false).into())?,
+                        maps.get_approved_aggregate_key(reward_cycle + 1)?,
+                    ))
+                })
+                .unwrap()
+        });
+
+    let signer_set = signer_set.unwrap();
+    assert_eq!(signer_set.len(), 2);
+    for (entry, expected) in signer_set.iter().zip(expected_signers.into_iter()) {
+        let expected = expected.expect_tuple().unwrap();
+        assert_eq!(
+            Value::Principal(entry.signer.clone()),
+            *expected.get("signer").unwrap()
+        );
+        assert_eq!(Value::UInt(entry.weight), *expected.get("weight").unwrap());
+    }
+
+    let stacking_state = stacking_state.unwrap();
+    assert_eq!(
+        Value::Tuple(stacking_state.pox_addr.as_clarity_tuple().unwrap()),
+        *expected_stacker_info.get("pox-addr").unwrap()
+    );
+    assert_eq!(
+        Value::UInt(stacking_state.lock_period),
+        *expected_stacker_info.get("lock-period").unwrap()
+    );
+    assert_eq!(
+        Value::UInt(stacking_state.first_reward_cycle),
+        *expected_stacker_info.get("first-reward-cycle").unwrap()
+    );
+    assert!(stacking_state.delegated_to.is_none());
+
+    assert!(no_stacking_state.is_none());
+    assert!(aggregate_key.is_none());
+}
+
 pub fn prepare_signers_test<'a>(
     test_name: &str,
     initial_balances: Vec<(PrincipalData, u64)>,
@@ -0,0 +1,189 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Execution cost telemetry.  The cost of every accepted block is kept in the chainstate DB,
+//! one column per cost dimension and with the cost of each of its transactions, so that capacity
+//! planning and proposals to change the block limits can be based on what blocks really use.
+
+use clarity::vm::costs::ExecutionCost;
+use rusqlite::Row;
+use stacks_common::types::chainstate::StacksBlockId;
+
+use crate::burnchains::Txid;
+use crate::chainstate::stacks::db::*;
+use crate::chainstate::stacks::events::StacksTransactionReceipt;
+use crate::chainstate::stacks::Error;
+use crate::util_lib::db::{query_row, u64_to_sql, DBConn, Error as db_error, FromColumn, FromRow};
+
+/// The execution cost of one transaction of a block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionCost {
+    pub txid: Txid,
+    /// The index of the transaction in its block
+    pub tx_index: u32,
+    pub cost: ExecutionCost,
+}
+
+/// The execution cost of an accepted block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockCosts {
+    pub index_block_hash: StacksBlockId,
+    pub block_height: u64,
+    /// The cost of the block, as recorded in its header
+    pub block_cost: ExecutionCost,
+    /// The cost of each of the block's transactions, in the order they were processed
+    pub tx_costs: Vec<TransactionCost>,
+}
+
+impl FromRow<BlockCosts> for BlockCosts {
+    fn from_row<'a>(row: &'a Row) -> Result<BlockCosts, db_error> {
+        let index_block_hash = StacksBlockId::from_column(row, "index_block_hash")?;
+        let block_height = u64::from_column(row, "block_height")?;
+        let block_cost = ExecutionCost {
+            write_length: u64::from_column(row, "write_length")?,
+            write_count: u64::from_column(row, "write_count")?,
+            read_length: u64::from_column(row, "read_length")?,
+            read_count: u64::from_column(row, "read_count")?,
+            runtime: u64::from_column(row, "runtime")?,
+        };
+        let tx_costs_json: String = row.get_unwrap("tx_costs");
+        let tx_costs =
+            serde_json::from_str(&tx_costs_json).map_err(db_error::SerializationError)?;
+        Ok(BlockCosts {
+            index_block_hash,
+            block_height,
+            block_cost,
+            tx_costs,
+        })
+    }
+}
+
+impl StacksChainState {
+    /// Store the execution costs of the accepted block `index_block_hash`, given the receipts
+    /// of its transactions
+    pub fn store_block_costs(
+        tx: &DBConn,
+        index_block_hash: &StacksBlockId,
+        block_height: u64,
+        block_cost: &ExecutionCost,
+        receipts: &[StacksTransactionReceipt],
+    ) -> Result<(), Error> {
+        let tx_costs: Vec<_> = receipts
+            .iter()
+            .map(|receipt| TransactionCost {
+                txid: receipt.transaction.txid(),
+                tx_index: receipt.tx_index,
+                cost: receipt.execution_cost.clone(),
+            })
+            .collect();
+        let tx_costs_json =
+            serde_json::to_string(&tx_costs).expect("FATAL: failed to encode transaction costs");
+        tx.execute(
+            "INSERT OR REPLACE INTO block_costs
+                (index_block_hash, block_height, write_length, write_count, read_length, read_count, runtime, tx_costs)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                index_block_hash,
+                u64_to_sql(block_height)?,
+                u64_to_sql(block_cost.write_length)?,
+                u64_to_sql(block_cost.write_count)?,
+                u64_to_sql(block_cost.read_length)?,
+                u64_to_sql(block_cost.read_count)?,
+                u64_to_sql(block_cost.runtime)?,
+                tx_costs_json
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Get the execution costs of the accepted block `index_block_hash`.
+    /// Returns None for blocks accepted before costs were recorded.
+    pub fn get_block_costs(
+        conn: &DBConn,
+        index_block_hash: &StacksBlockId,
+    ) -> Result<Option<BlockCosts>, Error> {
+        let costs = query_row(
+            conn,
+            "SELECT * FROM block_costs WHERE index_block_hash = ?1",
+            params![index_block_hash],
+        )?;
+        Ok(costs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use stacks_common::types::StacksEpochId;
+
+    use super::*;
+    use crate::chainstate::stacks::db::test::*;
+    use crate::chainstate::stacks::test::make_codec_test_block;
+    use crate::core::CHAIN_ID_TESTNET;
+
+    #[test]
+    fn test_store_and_load_block_costs() {
+        let mut chainstate =
+            instantiate_chainstate(false, CHAIN_ID_TESTNET, "test_store_and_load_block_costs");
+        let block = make_codec_test_block(3, StacksEpochId::Epoch25);
+        let receipts: Vec<_> = block
+            .txs
+            .iter()
+            .enumerate()
+            .map(|(i, tx)| {
+                let mut receipt = StacksTransactionReceipt::from_coinbase(tx.clone());
+                receipt.tx_index = i as u32;
+                receipt.execution_cost = ExecutionCost {
+                    write_length: i as u64,
+                    write_count: 1,
+                    read_length: 2 * i as u64,
+                    read_count: 2,
+                    runtime: 100 * i as u64,
+                };
+                receipt
+            })
+            .collect();
+        let block_cost = ExecutionCost {
+            write_length: 3,
+            write_count: 3,
+            read_length: 6,
+            read_count: 6,
+            runtime: 300,
+        };
+        let block_id = StacksBlockId([0x01; 32]);
+
+        assert!(
+            StacksChainState::get_block_costs(chainstate.db(), &block_id)
+                .unwrap()
+                .is_none()
+        );
+
+        let tx = chainstate.db_tx_begin().unwrap();
+        StacksChainState::store_block_costs(&tx, &block_id, 5, &block_cost, &receipts).unwrap();
+        tx.commit().unwrap();
+
+        let costs = StacksChainState::get_block_costs(chainstate.db(), &block_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(costs.index_block_hash, block_id);
+        assert_eq!(costs.block_height, 5);
+        assert_eq!(costs.block_cost, block_cost);
+        assert_eq!(costs.tx_costs.len(), block.txs.len());
+        for (i, (tx_cost, receipt)) in costs.tx_costs.iter().zip(receipts.iter()).enumerate() {
+            assert_eq!(tx_cost.txid, block.txs[i].txid());
+            assert_eq!(tx_cost.tx_index, i as u32);
+            assert_eq!(tx_cost.cost, receipt.execution_cost);
+        }
+    }
+}
@@ -36,7 +36,7 @@ use clarity::vm::types::{
 use rand::{thread_rng, Rng, RngCore};
 use rusqlite::types::ToSql;
 use rusqlite::{
-    params, Connection, DatabaseName, Error as sqlite_error, OptionalExtension, Params,
+    params, Connection, DatabaseName, Error as sqlite_error, OpenFlags, OptionalExtension, Params,
 };
 use serde::Serialize;
 use serde_json::json;
@@ -79,7 +79,7 @@ use crate::net::relay::Relayer;
 use crate::net::{BlocksInvData, Error as net_error};
 use crate::util_lib::boot::boot_code_id;
 use crate::util_lib::db::{
-    query_count, query_int, query_row, query_row_columns, query_row_panic, query_rows,
+    query_count, query_int, query_row, query_row_columns, query_row_panic, query_rows, sqlite_open,
     tx_busy_handler, u64_to_sql, DBConn, Error as db_error, FromColumn, FromRow,
 };
 use crate::util_lib::signed_structured_data::pox4::Pox4SignatureTopic;
@@ -97,6 +97,21 @@ pub struct StagingMicroblock {
     pub block_data: Vec<u8>,
 }
 
+/// What `StacksChainState::prune_fork_data` deleted
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ForkDataPruneSummary {
+    pub staging_blocks_deleted: u64,
+    pub microblocks_deleted: u64,
+}
+
+/// Schema of a microblock archive, which holds the data of the microblocks that
+/// `StacksChainState::archive_microblocks` moved out of the chainstate
+const MICROBLOCK_ARCHIVE_SCHEMA: &'static str = r#"
+    CREATE TABLE IF NOT EXISTS microblocks(
+        block_hash TEXT PRIMARY KEY NOT NULL,
+        block_data BLOB NOT NULL
+    );"#;
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct StagingBlock {
     pub consensus_hash: ConsensusHash,
@@ -2377,6 +2392,211 @@ impl StacksChainState {
         Ok(())
     }
 
+    /// Delete the staging data of the epoch 2.x blocks below `below_height` that never joined the
+    /// chain: staging blocks that were never processed or were orphaned (and their block files),
+    /// and the microblocks that were orphaned or built on them (along with their invalidated
+    /// copies).  Nothing that a processed block or a fork above `below_height` needs is deleted,
+    /// but the node forgets that the deleted blocks were invalid.
+    pub fn prune_fork_data(&mut self, below_height: u64) -> Result<ForkDataPruneSummary, Error> {
+        let blocks_path = self.blocks_path.clone();
+        let tx = self.db_tx_begin()?;
+        let height_arg = u64_to_sql(below_height)?;
+
+        let sql = "SELECT m.microblock_hash FROM staging_microblocks m
+                   JOIN staging_blocks b
+                     ON m.consensus_hash = b.consensus_hash
+                    AND m.anchored_block_hash = b.anchored_block_hash
+                   WHERE b.height < ?1 AND (m.orphaned = 1 OR b.processed = 0 OR b.orphaned = 1)";
+        let microblock_hashes = query_row_columns::<BlockHeaderHash, _>(
+            &tx,
+            sql,
+            params![height_arg],
+            "microblock_hash",
+        )?;
+
+        let sql = "SELECT consensus_hash, anchored_block_hash FROM staging_blocks
+                   WHERE height < ?1 AND (processed = 0 OR orphaned = 1)";
+        let mut stmt = tx.prepare(sql).map_err(db_error::SqliteError)?;
+        let stale_blocks = stmt
+            .query_and_then(params![height_arg], |row| {
+                let consensus_hash = ConsensusHash::from_column(row, "consensus_hash")?;
+                let block_hash = BlockHeaderHash::from_column(row, "anchored_block_hash")?;
+                Ok((consensus_hash, block_hash))
+            })
+            .map_err(db_error::SqliteError)?
+            .collect::<Result<Vec<_>, db_error>>()?;
+        drop(stmt);
+
+        tx.execute(
+            "DELETE FROM staging_microblocks WHERE orphaned = 1 AND EXISTS (
+                SELECT 1 FROM staging_blocks b
+                WHERE b.consensus_hash = staging_microblocks.consensus_hash
+                  AND b.anchored_block_hash = staging_microblocks.anchored_block_hash
+                  AND b.height < ?1)",
+            params![height_arg],
+        )?;
+        for (consensus_hash, block_hash) in stale_blocks.iter() {
+            let args = params![consensus_hash, block_hash];
+            tx.execute(
+                "DELETE FROM staging_microblocks WHERE consensus_hash = ?1 AND anchored_block_hash = ?2",
+                args,
+            )?;
+            tx.execute(
+                "DELETE FROM staging_blocks WHERE consensus_hash = ?1 AND anchored_block_hash = ?2",
+                args,
+            )?;
+        }
+        // a microblock's data is shared by every fork that has it
+        let mut microblocks_deleted = 0;
+        for microblock_hash in microblock_hashes.iter() {
+            let args = params![microblock_hash];
+            let still_staged = query_row::<i64, _>(
+                &tx,
+                "SELECT 1 FROM staging_microblocks WHERE microblock_hash = ?1",
+                args,
+            )?
+            .is_some();
+            if still_staged {
+                continue;
+            }
+            tx.execute(
+                "DELETE FROM staging_microblocks_data WHERE block_hash = ?1",
+                args,
+            )?;
+            tx.execute(
+                "DELETE FROM invalidated_microblocks_data WHERE block_hash = ?1",
+                args,
+            )?;
+            microblocks_deleted += 1;
+        }
+        tx.commit()?;
+
+        // only delete block files once the rows are gone
+        for (consensus_hash, block_hash) in stale_blocks.iter() {
+            let block_path =
+                StacksChainState::get_block_path(&blocks_path, consensus_hash, block_hash)?;
+            if let Err(e) = fs::remove_file(&block_path) {
+                if e.kind() != io::ErrorKind::NotFound {
+                    warn!("Failed to delete pruned block file {block_path}: {e:?}");
+                }
+            }
+        }
+
+        Ok(ForkDataPruneSummary {
+            staging_blocks_deleted: stale_blocks.len() as u64,
+            microblocks_deleted,
+        })
+    }
+
+    /// Move the data of up to `max_microblocks` processed microblocks whose parent anchored blocks
+    /// are below `below_height` to the microblock archive at `archive_path`, creating it if need
+    /// be.  The microblocks' staging info stays in the chainstate, so the node still knows that
+    /// they were processed, but it can no longer load or serve them.
+    /// Returns the number of microblocks archived.
+    pub fn archive_microblocks(
+        &mut self,
+        archive_path: &Path,
+        below_height: u64,
+        max_microblocks: u64,
+    ) -> Result<u64, Error> {
+        // a microblock's data is shared by every fork that has it, so only archive it once no
+        // fork at or above `below_height` still needs it
+        let sql = "SELECT DISTINCT m.microblock_hash FROM staging_microblocks m
+                   JOIN staging_blocks b
+                     ON m.consensus_hash = b.consensus_hash
+                    AND m.anchored_block_hash = b.anchored_block_hash
+                   JOIN staging_microblocks_data d ON d.block_hash = m.microblock_hash
+                   WHERE b.height < ?1 AND m.processed = 1 AND m.orphaned = 0
+                     AND NOT EXISTS (
+                       SELECT 1 FROM staging_microblocks m2
+                       JOIN staging_blocks b2
+                         ON m2.consensus_hash = b2.consensus_hash
+                        AND m2.anchored_block_hash = b2.anchored_block_hash
+                       WHERE m2.microblock_hash = m.microblock_hash
+                         AND m2.orphaned = 0
+                         AND (m2.processed = 0 OR b2.height >= ?1))
+                   LIMIT ?2";
+        let microblock_hashes = query_row_columns::<BlockHeaderHash, _>(
+            self.db(),
+            sql,
+            params![u64_to_sql(below_height)?, u64_to_sql(max_microblocks)?],
+            "microblock_hash",
+        )?;
+        if microblock_hashes.is_empty() {
+            return Ok(0);
+        }
+
+        // copy the data to the archive before deleting it here, so a crash in between only
+        // leaves a copy behind
+        let mut archive = sqlite_open(
+            archive_path,
+            OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_CREATE,
+            false,
+        )
+        .map_err(db_error::SqliteError)?;
+        archive
+            .execute_batch(MICROBLOCK_ARCHIVE_SCHEMA)
+            .map_err(db_error::SqliteError)?;
+        let archive_tx = archive.transaction().map_err(db_error::SqliteError)?;
+        for microblock_hash in microblock_hashes.iter() {
+            let Some(block_data) =
+                StacksChainState::load_staging_microblock_bytes(self.db(), microblock_hash)?
+            else {
+                continue;
+            };
+            archive_tx.execute(
+                "INSERT OR REPLACE INTO microblocks (block_hash, block_data) VALUES (?1, ?2)",
+                params![microblock_hash, block_data],
+            )?;
+        }
+        archive_tx.commit().map_err(db_error::SqliteError)?;
+
+        let archived_at = u64_to_sql(get_epoch_time_secs())?;
+        let tx = self.db_tx_begin()?;
+        for microblock_hash in microblock_hashes.iter() {
+            tx.execute(
+                "DELETE FROM staging_microblocks_data WHERE block_hash = ?1",
+                params![microblock_hash],
+            )?;
+            tx.execute(
+                "INSERT OR REPLACE INTO archived_microblocks (microblock_hash, archived_at) VALUES (?1, ?2)",
+                params![microblock_hash, archived_at],
+            )?;
+        }
+        tx.commit()?;
+
+        Ok(microblock_hashes.len() as u64)
+    }
+
+    /// Has this microblock's data been moved to the microblock archive?
+    pub fn is_microblock_archived(
+        blocks_conn: &DBConn,
+        microblock_hash: &BlockHeaderHash,
+    ) -> Result<bool, Error> {
+        let sql = "SELECT 1 FROM archived_microblocks WHERE microblock_hash = ?1";
+        let archived = query_row::<i64, _>(blocks_conn, sql, params![microblock_hash])?.is_some();
+        Ok(archived)
+    }
+
+    /// Load a microblock from the microblock archive at `archive_path`
+    pub fn load_archived_microblock(
+        archive_path: &Path,
+        microblock_hash: &BlockHeaderHash,
+    ) -> Result<Option<StacksMicroblock>, Error> {
+        let archive = sqlite_open(archive_path, OpenFlags::SQLITE_OPEN_READ_ONLY, false)
+            .map_err(db_error::SqliteError)?;
+        let Some(block_data) = StacksChainState::inner_load_staging_block_bytes(
+            &archive,
+            "microblocks",
+            microblock_hash,
+        )?
+        else {
+            return Ok(None);
+        };
+        let microblock = StacksMicroblock::consensus_deserialize(&mut &block_data[..])?;
+        Ok(Some(microblock))
+    }
+
     /// Clear out a staging block -- mark it as processed.
     /// Mark its children as attachable.
     /// Idempotent.
@@ -4098,49 +4318,74 @@ impl StacksChainState {
                 );
                 // this assertion failing means that the _parent_ block was invalid: this is bad and should panic.
                 assert!(current_epoch < sortition_epoch.epoch_id, "The SortitionDB believes the epoch is earlier than this Stacks block's parent: sortition db epoch = {}, current epoch = {}", sortition_epoch.epoch_id, current_epoch);
-                // time for special cases:
-                match current_epoch {
-                    StacksEpochId::Epoch10 => {
-                        panic!("Clarity VM believes it was running in 1.0: pre-Clarity.")
-                    }
-                    StacksEpochId::Epoch20 => {
-                        receipts.push(clarity_tx.block.initialize_epoch_2_05()?);
-                        current_epoch = StacksEpochId::Epoch2_05;
-                    }
-                    StacksEpochId::Epoch2_05 => {
-                        receipts.append(&mut clarity_tx.block.initialize_epoch_2_1()?);
-                        current_epoch = StacksEpochId::Epoch21;
-                    }
-                    StacksEpochId::Epoch21 => {
-                        receipts.append(&mut clarity_tx.block.initialize_epoch_2_2()?);
-                        current_epoch = StacksEpochId::Epoch22;
-                    }
-                    StacksEpochId::Epoch22 => {
-                        receipts.append(&mut clarity_tx.block.initialize_epoch_2_3()?);
-                        current_epoch = StacksEpochId::Epoch23;
-                    }
-                    StacksEpochId::Epoch23 => {
-                        receipts.append(&mut clarity_tx.block.initialize_epoch_2_4()?);
-                        current_epoch = StacksEpochId::Epoch24;
-                    }
-                    StacksEpochId::Epoch24 => {
-                        receipts.append(&mut clarity_tx.block.initialize_epoch_2_5()?);
-                        current_epoch = StacksEpochId::Epoch25;
-                    }
-                    StacksEpochId::Epoch25 => {
-                        receipts.append(&mut clarity_tx.block.initialize_epoch_3_0()?);
-                        current_epoch = StacksEpochId::Epoch30;
-                    }
-                    StacksEpochId::Epoch30 => {
-                        panic!("No defined transition from Epoch30 forward")
-                    }
-                }
+                let (next_epoch, mut transition_receipts) =
+                    StacksChainState::apply_builtin_epoch_transition(
+                        &mut clarity_tx.block,
+                        current_epoch,
+                    )?;
+                receipts.append(&mut transition_receipts);
+                current_epoch = next_epoch;
+                receipts.append(
+                    &mut clarity_tx
+                        .block
+                        .initialize_epoch_boot_contracts(current_epoch)?,
+                );
             }
         }
 
         Ok((applied, receipts))
     }
 
+    /// Apply the built-in transition out of `epoch` to the Clarity state.
+    /// Return the epoch it transitions to, and the receipts of the contracts it instantiates.
+    pub fn apply_builtin_epoch_transition(
+        clarity_block: &mut ClarityBlockConnection,
+        epoch: StacksEpochId,
+    ) -> Result<(StacksEpochId, Vec<StacksTransactionReceipt>), Error> {
+        // time for special cases:
+        let transition = match epoch {
+            StacksEpochId::Epoch10 => {
+                panic!("Clarity VM believes it was running in 1.0: pre-Clarity.")
+            }
+            StacksEpochId::Epoch20 => (
+                StacksEpochId::Epoch2_05,
+                vec![clarity_block.initialize_epoch_2_05()?],
+            ),
+            StacksEpochId::Epoch2_05 => (
+                StacksEpochId::Epoch21,
+                clarity_block.initialize_epoch_2_1()?,
+            ),
+            StacksEpochId::Epoch21 => (
+                StacksEpochId::Epoch22,
+                clarity_block.initialize_epoch_2_2()?,
+            ),
+            StacksEpochId::Epoch22 => (
+                StacksEpochId::Epoch23,
+                clarity_block.initialize_epoch_2_3()?,
+            ),
+            StacksEpochId::Epoch23 => (
+                StacksEpochId::Epoch24,
+                clarity_block.initialize_epoch_2_4()?,
+            ),
+            StacksEpochId::Epoch24 => (
+                StacksEpochId::Epoch25,
+                clarity_block.initialize_epoch_2_5()?,
+            ),
+            StacksEpochId::Epoch25 => (
+                StacksEpochId::Epoch30,
+                clarity_block.initialize_epoch_3_0()?,
+            ),
+            StacksEpochId::Epoch30 => (
+                StacksEpochId::Epoch31,
+                clarity_block.initialize_epoch_3_1()?,
+            ),
+            StacksEpochId::Epoch31 => {
+                panic!("No defined transition from Epoch31 forward")
+            }
+        };
+        Ok(transition)
+    }
+
     // TODO: add tests from mutation testing results #4856
     // Or keep the skip and remove the comment
     #[cfg_attr(test, mutants::skip)]
@@ -4586,9 +4831,14 @@ impl StacksChainState {
         let mut fees = 0u128;
         let mut burns = 0u128;
         let mut receipts = vec![];
-        for tx in block_txs.iter() {
-            let (tx_fee, mut tx_receipt) =
-                StacksChainState::process_transaction(clarity_tx, tx, false, ast_rules)?;
+        let first_invalid = StacksChainState::precheck_block_transactions(clarity_tx, block_txs);
+        for (i, tx) in block_txs.iter().enumerate() {
+            let (tx_fee, mut tx_receipt) = if Some(i) == first_invalid {
+                // fails with the precheck's error
+                StacksChainState::process_transaction(clarity_tx, tx, false, ast_rules)?
+            } else {
+                StacksChainState::process_prechecked_transaction(clarity_tx, tx, false, ast_rules)?
+            };
             fees = fees.checked_add(u128::from(tx_fee)).expect("Fee overflow");
             tx_receipt.tx_index = tx_index;
             burns = burns
@@ -4942,7 +5192,7 @@ impl StacksChainState {
                     )?;
                 Ok((stack_ops, transfer_ops, delegate_ops, vec![]))
             }
-            StacksEpochId::Epoch25 | StacksEpochId::Epoch30 => {
+            StacksEpochId::Epoch25 | StacksEpochId::Epoch30 | StacksEpochId::Epoch31 => {
                 // TODO: sbtc ops in epoch 3.0
                 StacksChainState::get_stacking_and_transfer_and_delegate_burn_ops_v210(
                     chainstate_tx,
@@ -5033,7 +5283,7 @@ impl StacksChainState {
                     pox_reward_cycle,
                     pox_start_cycle_info,
                 ),
-                StacksEpochId::Epoch25 | StacksEpochId::Epoch30 => {
+                StacksEpochId::Epoch25 | StacksEpochId::Epoch30 | StacksEpochId::Epoch31 => {
                     Self::handle_pox_cycle_start_pox_4(
                         clarity_tx,
                         pox_reward_cycle,
@@ -5860,6 +6110,13 @@ impl StacksChainState {
         .expect("FATAL: failed to advance chain tip");
 
         chainstate_tx.log_transactions_processed(&new_tip.index_block_hash(), &tx_receipts);
+        StacksChainState::store_block_costs(
+            chainstate_tx.tx.tx(),
+            &new_tip.index_block_hash(),
+            new_tip.stacks_block_height,
+            &block_execution_cost,
+            &tx_receipts,
+        )?;
 
         // store the reward set calculated during this block if it happened
         // NOTE: miner and proposal evaluation should not invoke this because
@@ -9672,6 +9929,231 @@ pub mod test {
         }
     }
 
+    #[test]
+    fn stacks_db_prune_fork_data() {
+        let mut chainstate = instantiate_chainstate(false, 0x80000000, function_name!());
+        let privk = StacksPrivateKey::from_hex(
+            "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
+        )
+        .unwrap();
+
+        // a rejected block deep in the chain, and a pending block near the tip
+        let mut old_block = make_empty_coinbase_block(&privk);
+        old_block.header.total_work.work = 1;
+        let mut new_block = make_empty_coinbase_block(&privk);
+        new_block.header.total_work.work = 10;
+
+        let blocks = [
+            (ConsensusHash([2u8; 20]), old_block),
+            (ConsensusHash([3u8; 20]), new_block),
+        ];
+        let mut microblocks = vec![];
+        for (consensus_hash, block) in blocks.iter() {
+            let mut mblocks = make_sample_microblock_stream(&privk, &block.block_hash());
+            mblocks.truncate(3);
+            for mblock in mblocks.iter() {
+                store_staging_microblock(
+                    &mut chainstate,
+                    consensus_hash,
+                    &block.block_hash(),
+                    mblock,
+                );
+            }
+            store_staging_block(
+                &mut chainstate,
+                consensus_hash,
+                block,
+                &ConsensusHash([1u8; 20]),
+                1,
+                2,
+            );
+            microblocks.push(mblocks);
+        }
+        set_block_processed(
+            &mut chainstate,
+            &blocks[0].0,
+            &blocks[0].1.block_hash(),
+            false,
+        );
+
+        let summary = chainstate.prune_fork_data(5).unwrap();
+        assert_eq!(summary.staging_blocks_deleted, 1);
+        assert_eq!(summary.microblocks_deleted, 3);
+
+        // the old block and its microblocks are gone
+        let block_path = StacksChainState::get_block_path(
+            &chainstate.blocks_path,
+            &blocks[0].0,
+            &blocks[0].1.block_hash(),
+        )
+        .unwrap();
+        assert!(!Path::new(&block_path).exists());
+        for mblock in microblocks[0].iter() {
+            assert!(StacksChainState::load_staging_microblock(
+                &chainstate.db(),
+                &blocks[0].0,
+                &blocks[0].1.block_hash(),
+                &mblock.block_hash()
+            )
+            .unwrap()
+            .is_none());
+        }
+        let remaining: i64 = query_count(
+            &chainstate.db(),
+            "SELECT COUNT(*) FROM staging_microblocks",
+            NO_PARAMS,
+        )
+        .unwrap();
+        assert_eq!(remaining, 3);
+
+        // the pending block and its microblocks are still there
+        assert_block_staging_not_processed(&mut chainstate, &blocks[1].0, &blocks[1].1);
+        for mblock in microblocks[1].iter() {
+            assert!(StacksChainState::load_staging_microblock(
+                &chainstate.db(),
+                &blocks[1].0,
+                &blocks[1].1.block_hash(),
+                &mblock.block_hash()
+            )
+            .unwrap()
+            .is_some());
+        }
+
+        // pruning again is a no-op
+        let summary = chainstate.prune_fork_data(5).unwrap();
+        assert_eq!(summary, ForkDataPruneSummary::default());
+    }
+
+    #[test]
+    fn stacks_db_archive_microblocks() {
+        let mut chainstate = instantiate_chainstate(false, 0x80000000, function_name!());
+        let privk = StacksPrivateKey::from_hex(
+            "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
+        )
+        .unwrap();
+        let archive_path = PathBuf::from(format!("{}/microblocks.sqlite", &chainstate.root_path));
+
+        // an old and a recent block, each with a microblock stream confirmed by a child
+        let mut streams = vec![];
+        for (i, height) in [(0u8, 1u64), (1, 10)] {
+            let consensus_hash = ConsensusHash([2 + 2 * i; 20]);
+            let child_consensus_hash = ConsensusHash([3 + 2 * i; 20]);
+
+            let mut block = make_empty_coinbase_block(&privk);
+            block.header.total_work.work = height;
+            let mut mblocks = make_sample_microblock_stream(&privk, &block.block_hash());
+            mblocks.truncate(3);
+
+            let mut child_block = make_empty_coinbase_block(&privk);
+            child_block.header.total_work.work = height + 1;
+            child_block.header.parent_block = block.block_hash();
+            child_block.header.parent_microblock = mblocks.last().unwrap().block_hash();
+            child_block.header.parent_microblock_sequence = mblocks.last().unwrap().header.sequence;
+
+            for mblock in mblocks.iter() {
+                store_staging_microblock(
+                    &mut chainstate,
+                    &consensus_hash,
+                    &block.block_hash(),
+                    mblock,
+                );
+            }
+            store_staging_block(
+                &mut chainstate,
+                &consensus_hash,
+                &block,
+                &ConsensusHash([1u8; 20]),
+                1,
+                2,
+            );
+            store_staging_block(
+                &mut chainstate,
+                &child_consensus_hash,
+                &child_block,
+                &consensus_hash,
+                1,
+                2,
+            );
+            set_block_processed(&mut chainstate, &consensus_hash, &block.block_hash(), true);
+            set_block_processed(
+                &mut chainstate,
+                &child_consensus_hash,
+                &child_block.block_hash(),
+                true,
+            );
+            set_microblocks_processed(
+                &mut chainstate,
+                &child_consensus_hash,
+                &child_block.block_hash(),
+                &mblocks.last().unwrap().block_hash(),
+            );
+            streams.push((consensus_hash, block.block_hash(), mblocks));
+        }
+
+        // archiving is done in batches
+        assert_eq!(
+            chainstate.archive_microblocks(&archive_path, 5, 2).unwrap(),
+            2
+        );
+        assert_eq!(
+            chainstate
+                .archive_microblocks(&archive_path, 5, 100)
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            chainstate
+                .archive_microblocks(&archive_path, 5, 100)
+                .unwrap(),
+            0
+        );
+
+        // the old stream moved to the archive, but its staging info is kept
+        let (consensus_hash, block_hash, mblocks) = &streams[0];
+        for mblock in mblocks.iter() {
+            let microblock_hash = mblock.block_hash();
+            assert!(
+                StacksChainState::is_microblock_archived(chainstate.db(), &microblock_hash)
+                    .unwrap()
+            );
+            assert_eq!(
+                StacksChainState::load_archived_microblock(&archive_path, &microblock_hash)
+                    .unwrap(),
+                Some(mblock.clone())
+            );
+            let staging_microblock = StacksChainState::load_staging_microblock(
+                chainstate.db(),
+                consensus_hash,
+                block_hash,
+                &microblock_hash,
+            )
+            .unwrap()
+            .unwrap();
+            assert!(staging_microblock.processed);
+            assert!(staging_microblock.block_data.is_empty());
+        }
+
+        // the recent stream is still served from the chainstate
+        let (consensus_hash, block_hash, mblocks) = &streams[1];
+        for mblock in mblocks.iter() {
+            assert!(!StacksChainState::is_microblock_archived(
+                chainstate.db(),
+                &mblock.block_hash()
+            )
+            .unwrap());
+        }
+        assert_eq!(
+            StacksChainState::load_processed_microblock_stream_fork(
+                chainstate.db(),
+                consensus_hash,
+                block_hash,
+                &mblocks.last().unwrap().block_hash(),
+            )
+            .unwrap(),
+            Some(mblocks.clone())
+        );
+    }
+
     #[test]
     fn stacks_db_drop_staging_microblocks() {
         let mut chainstate = instantiate_chainstate(false, 0x80000000, function_name!());
@@ -0,0 +1,417 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Epoch transition dry runs.  Before an epoch is rolled out, its transition can be rehearsed
+//! against a node's chain state: the built-in transitions and boot contracts of every epoch up to
+//! it are applied atop a block in a new block that is then rolled back, and everything that fails
+//! is reported.  Boot contracts are instantiated for free by the real transition, but the dry run
+//! meters them, so that one whose instantiation wouldn't fit in a block is reported too.
+
+use clarity::vm::ast::ASTRules;
+use clarity::vm::costs::{ExecutionCost, LimitedCostTracker};
+use clarity::vm::database::BurnStateDB;
+use clarity::vm::Value;
+use stacks_common::types::chainstate::StacksBlockId;
+use stacks_common::types::StacksEpochId;
+
+use crate::chainstate::stacks::boot::{BootContract, BootContractProvider};
+use crate::chainstate::stacks::db::{ClarityTx, StacksChainState, StacksHeaderInfo};
+use crate::chainstate::stacks::{
+    Error, StacksTransaction, TransactionPayload, TransactionSmartContract, TransactionVersion,
+    MINER_BLOCK_CONSENSUS_HASH, MINER_BLOCK_HEADER_HASH,
+};
+use crate::clarity_vm::clarity::{ClarityConnection, Error as clarity_error};
+use crate::core::StacksEpoch;
+use crate::util_lib::boot::{boot_code_addr, boot_code_tx_auth};
+use crate::util_lib::strings::StacksString;
+
+/// Something that went wrong while rehearsing an epoch transition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum EpochDryRunFailure {
+    /// The built-in transition out of `from_epoch` failed, so no later epoch was rehearsed
+    Transition {
+        from_epoch: StacksEpochId,
+        error: String,
+    },
+    /// A boot contract failed to parse or type-check
+    Analysis {
+        epoch: StacksEpochId,
+        contract: String,
+        error: String,
+    },
+    /// A boot contract failed while it was initialized
+    Runtime {
+        epoch: StacksEpochId,
+        contract: String,
+        error: String,
+    },
+    /// Instantiating a boot contract costs more than the target epoch's block limit
+    CostLimitExceeded {
+        epoch: StacksEpochId,
+        contract: String,
+        cost: ExecutionCost,
+        limit: ExecutionCost,
+    },
+}
+
+/// A boot contract that the dry run tried to instantiate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EpochDryRunContract {
+    /// The epoch whose first block instantiates the contract
+    pub epoch: StacksEpochId,
+    pub name: String,
+    /// The cost of analyzing and initializing the contract
+    pub cost: ExecutionCost,
+}
+
+/// The outcome of rehearsing an epoch transition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EpochDryRunReport {
+    /// The block atop which the transition was applied
+    pub parent_block_id: StacksBlockId,
+    /// The epoch of the parent block's Clarity state
+    pub from_epoch: StacksEpochId,
+    pub to_epoch: StacksEpochId,
+    /// The boot contracts instantiated by the transition, in order
+    pub contracts: Vec<EpochDryRunContract>,
+    pub failures: Vec<EpochDryRunFailure>,
+}
+
+impl EpochDryRunReport {
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl StacksChainState {
+    /// Rehearse the transition to `target_epoch` atop the block `parent`.  Every built-in
+    /// transition between the parent's epoch and `target_epoch` is applied, and the boot
+    /// contracts that the network's `BootContractProvider` adds in each of those epochs are
+    /// instantiated, followed by `extra_contracts` (the ones being rehearsed) in `target_epoch`.
+    /// `burn_dbconn` must be open at the parent's burn view.
+    ///
+    /// Nothing is written: the block that the transition is applied in is rolled back.
+    pub fn epoch_transition_dry_run(
+        &mut self,
+        burn_dbconn: &dyn BurnStateDB,
+        parent: &StacksHeaderInfo,
+        target_epoch: &StacksEpoch,
+        extra_contracts: &[BootContract],
+    ) -> Result<EpochDryRunReport, Error> {
+        let provider = self.clarity_state.get_boot_contract_provider().clone();
+        let mainnet = self.mainnet;
+        let mut clarity_tx = self.block_begin(
+            burn_dbconn,
+            &parent.consensus_hash,
+            &parent.anchored_header.block_hash(),
+            &MINER_BLOCK_CONSENSUS_HASH,
+            &MINER_BLOCK_HEADER_HASH,
+        );
+
+        let mut report = EpochDryRunReport {
+            parent_block_id: parent.index_block_hash(),
+            from_epoch: StacksEpochId::Epoch10,
+            to_epoch: target_epoch.epoch_id,
+            contracts: vec![],
+            failures: vec![],
+        };
+        let result = Self::inner_epoch_transition_dry_run(
+            &mut clarity_tx,
+            mainnet,
+            provider.as_ref(),
+            target_epoch,
+            extra_contracts,
+            &mut report,
+        );
+        clarity_tx.rollback_block();
+        result.map(|_| report)
+    }
+
+    /// Apply the transitions and instantiate the boot contracts of a dry run in `clarity_tx`
+    fn inner_epoch_transition_dry_run(
+        clarity_tx: &mut ClarityTx,
+        mainnet: bool,
+        provider: &dyn BootContractProvider,
+        target_epoch: &StacksEpoch,
+        extra_contracts: &[BootContract],
+        report: &mut EpochDryRunReport,
+    ) -> Result<(), Error> {
+        let from_epoch =
+            clarity_tx.with_clarity_db_readonly(|db| db.get_clarity_epoch_version())?;
+        report.from_epoch = from_epoch;
+        if from_epoch > target_epoch.epoch_id {
+            report.failures.push(EpochDryRunFailure::Transition {
+                from_epoch,
+                error: format!("Chain state is already in epoch {from_epoch}"),
+            });
+            return Ok(());
+        }
+
+        let mut current_epoch = from_epoch;
+        let mut contracts = vec![];
+        loop {
+            if current_epoch == target_epoch.epoch_id {
+                contracts.extend_from_slice(extra_contracts);
+            }
+            for contract in contracts.drain(..) {
+                Self::dry_run_boot_contract(
+                    clarity_tx,
+                    current_epoch,
+                    &contract,
+                    &target_epoch.block_limit,
+                    report,
+                )?;
+            }
+            if current_epoch >= target_epoch.epoch_id {
+                return Ok(());
+            }
+
+            match Self::apply_builtin_epoch_transition(clarity_tx.connection(), current_epoch) {
+                Ok((next_epoch, _receipts)) => {
+                    debug!("Dry run: applied epoch transition";
+                        "new_epoch_id" => %next_epoch,
+                        "old_epoch_id" => %current_epoch
+                    );
+                    current_epoch = next_epoch;
+                    contracts = provider.epoch_contracts(mainnet, current_epoch);
+                }
+                Err(e) => {
+                    report.failures.push(EpochDryRunFailure::Transition {
+                        from_epoch: current_epoch,
+                        error: e.to_string(),
+                    });
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Instantiate `contract` as a boot contract of `epoch`, metering its cost, and record the
+    /// outcome in `report`
+    fn dry_run_boot_contract(
+        clarity_tx: &mut ClarityTx,
+        epoch: StacksEpochId,
+        contract: &BootContract,
+        block_limit: &ExecutionCost,
+        report: &mut EpochDryRunReport,
+    ) -> Result<(), Error> {
+        let mainnet = clarity_tx.config.mainnet;
+        let chain_id = clarity_tx.config.chain_id;
+        let name = contract.name.to_string();
+
+        let Some(code_body) = StacksString::from_str(&contract.code) else {
+            report.failures.push(EpochDryRunFailure::Analysis {
+                epoch,
+                contract: name,
+                error: "Contract code is not a valid Stacks string".into(),
+            });
+            return Ok(());
+        };
+        let tx_version = if mainnet {
+            TransactionVersion::Mainnet
+        } else {
+            TransactionVersion::Testnet
+        };
+        let contract_tx = StacksTransaction::new(
+            tx_version,
+            boot_code_tx_auth(boot_code_addr(mainnet)),
+            TransactionPayload::SmartContract(
+                TransactionSmartContract {
+                    name: contract.name.clone(),
+                    code_body,
+                },
+                contract.clarity_version,
+            ),
+        );
+        let boot_code_account = clarity_tx.connection().get_boot_code_account()?;
+
+        // meter the contract without a limit, so that it is instantiated whatever it costs
+        let cost_tracker = clarity_tx.connection().as_transaction(|tx_conn| {
+            tx_conn.with_clarity_db(|db| {
+                Ok(LimitedCostTracker::new(
+                    mainnet,
+                    chain_id,
+                    ExecutionCost::max_value(),
+                    db,
+                    epoch,
+                )
+                .expect("FAIL: problem instantiating cost tracking"))
+            })
+        })?;
+        let (result, _) = clarity_tx.with_temporary_cost_tracker(cost_tracker, |clarity_tx| {
+            clarity_tx.connection().as_transaction(|tx_conn| {
+                StacksChainState::process_transaction_payload(
+                    tx_conn,
+                    &contract_tx,
+                    &boot_code_account,
+                    ASTRules::PrecheckSize,
+                )
+            })
+        });
+
+        let receipt = match result {
+            Ok(receipt) => receipt,
+            Err(Error::ClarityError(
+                e @ (clarity_error::Parse(..) | clarity_error::Analysis(..)),
+            )) => {
+                report.failures.push(EpochDryRunFailure::Analysis {
+                    epoch,
+                    contract: name,
+                    error: e.to_string(),
+                });
+                return Ok(());
+            }
+            Err(e) => {
+                report.failures.push(EpochDryRunFailure::Runtime {
+                    epoch,
+                    contract: name,
+                    error: e.to_string(),
+                });
+                return Ok(());
+            }
+        };
+
+        report.contracts.push(EpochDryRunContract {
+            epoch,
+            name: name.clone(),
+            cost: receipt.execution_cost.clone(),
+        });
+        if receipt.contract_analysis.is_none() {
+            report.failures.push(EpochDryRunFailure::Analysis {
+                epoch,
+                contract: name,
+                error: receipt.vm_error.unwrap_or_default(),
+            });
+            return Ok(());
+        }
+        if receipt.result != Value::okay_true() || receipt.post_condition_aborted {
+            let error = receipt
+                .vm_error
+                .clone()
+                .unwrap_or_else(|| format!("Initialization returned {}", &receipt.result));
+            report.failures.push(EpochDryRunFailure::Runtime {
+                epoch,
+                contract: name,
+                error,
+            });
+            return Ok(());
+        }
+        if receipt.execution_cost.exceeds(block_limit) {
+            report.failures.push(EpochDryRunFailure::CostLimitExceeded {
+                epoch,
+                contract: name,
+                cost: receipt.execution_cost,
+                limit: block_limit.clone(),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use clarity::vm::test_util::TEST_BURN_STATE_DB;
+    use clarity::vm::types::QualifiedContractIdentifier;
+
+    use super::*;
+    use crate::chainstate::stacks::db::test::*;
+    use crate::core::{BLOCK_LIMIT_MAINNET_205, CHAIN_ID_TESTNET, PEER_VERSION_EPOCH_2_05};
+    use crate::util_lib::boot::boot_code_id;
+
+    #[test]
+    fn test_epoch_transition_dry_run() {
+        let mut chainstate =
+            instantiate_chainstate(false, CHAIN_ID_TESTNET, "test_epoch_transition_dry_run");
+        let genesis = StacksChainState::get_genesis_header_info(chainstate.db()).unwrap();
+        let mut target_epoch = StacksEpoch {
+            epoch_id: StacksEpochId::Epoch2_05,
+            start_height: 0,
+            end_height: u64::MAX,
+            block_limit: BLOCK_LIMIT_MAINNET_205.clone(),
+            network_epoch: PEER_VERSION_EPOCH_2_05,
+        };
+        let contracts = vec![
+            BootContract::new("dry-run-ok", "(define-data-var counter uint u0)", None),
+            BootContract::new("dry-run-bad", "(define-read-only (f) (+ u1 1))", None),
+        ];
+
+        let report = chainstate
+            .epoch_transition_dry_run(&TEST_BURN_STATE_DB, &genesis, &target_epoch, &contracts)
+            .unwrap();
+        assert_eq!(report.parent_block_id, genesis.index_block_hash());
+        assert_eq!(report.from_epoch, StacksEpochId::Epoch20);
+        assert_eq!(report.to_epoch, StacksEpochId::Epoch2_05);
+        assert_eq!(report.contracts.len(), 2);
+        assert_eq!(report.contracts[0].name, "dry-run-ok");
+        assert_eq!(report.contracts[0].epoch, StacksEpochId::Epoch2_05);
+        assert!(report.contracts[0].cost.runtime > 0);
+        assert_eq!(report.failures.len(), 1);
+        assert!(matches!(
+            &report.failures[0],
+            EpochDryRunFailure::Analysis { contract, .. } if contract == "dry-run-bad"
+        ));
+
+        // a block limit that the contract doesn't fit in is reported
+        target_epoch.block_limit = ExecutionCost {
+            write_length: 1,
+            write_count: 1,
+            read_length: 1,
+            read_count: 1,
+            runtime: 1,
+        };
+        let report = chainstate
+            .epoch_transition_dry_run(
+                &TEST_BURN_STATE_DB,
+                &genesis,
+                &target_epoch,
+                &contracts[..1],
+            )
+            .unwrap();
+        assert_eq!(report.failures.len(), 1);
+        assert!(matches!(
+            &report.failures[0],
+            EpochDryRunFailure::CostLimitExceeded { contract, .. } if contract == "dry-run-ok"
+        ));
+
+        // nothing was kept
+        let mut clarity_tx = chainstate.block_begin(
+            &TEST_BURN_STATE_DB,
+            &genesis.consensus_hash,
+            &genesis.anchored_header.block_hash(),
+            &MINER_BLOCK_CONSENSUS_HASH,
+            &MINER_BLOCK_HEADER_HASH,
+        );
+        let epoch = clarity_tx
+            .with_clarity_db_readonly(|db| db.get_clarity_epoch_version())
+            .unwrap();
+        assert_eq!(epoch, StacksEpochId::Epoch20);
+        let contract_id = QualifiedContractIdentifier::new(
+            boot_code_addr(false).into(),
+            contracts[0].name.clone(),
+        );
+        assert!(
+            StacksChainState::get_contract(&mut clarity_tx, &contract_id)
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            StacksChainState::get_contract(&mut clarity_tx, &boot_code_id("costs-2", false))
+                .unwrap()
+                .is_none()
+        );
+        clarity_tx.rollback_block();
+    }
+}
@@ -19,6 +19,7 @@ use std::collections::{BTreeMap, HashSet};
 use std::io::prelude::*;
 use std::ops::{Deref, DerefMut};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::{fmt, fs, io};
 
 use clarity::vm::analysis::analysis_db::AnalysisDatabase;
@@ -26,12 +27,12 @@ use clarity::vm::analysis::run_analysis;
 use clarity::vm::ast::ASTRules;
 use clarity::vm::clarity::TransactionConnection;
 use clarity::vm::contexts::OwnedEnvironment;
-use clarity::vm::costs::{ExecutionCost, LimitedCostTracker};
+use clarity::vm::costs::{ExecutionCost, ExecutionLimits, LimitedCostTracker};
 use clarity::vm::database::{
     BurnStateDB, ClarityDatabase, HeadersDB, STXBalance, SqliteConnection, NULL_BURN_STATE_DB,
 };
 use clarity::vm::events::*;
-use clarity::vm::representations::{ClarityName, ContractName};
+use clarity::vm::representations::ClarityName;
 use clarity::vm::types::TupleData;
 use clarity::vm::{SymbolicExpression, Value};
 use lazy_static::lazy_static;
@@ -76,7 +77,7 @@ use crate::chainstate::stacks::{
 };
 use crate::clarity_vm::clarity::{
     ClarityBlockConnection, ClarityConnection, ClarityInstance, ClarityReadOnlyConnection,
-    Error as clarity_error, PreCommitClarityBlock,
+    Error as clarity_error, PreCommitClarityBlock, SimulatedCallResult, SimulatedContractCall,
 };
 use crate::clarity_vm::database::marf::MarfedKV;
 use crate::clarity_vm::database::HeadersDBConn;
@@ -91,9 +92,12 @@ use crate::util_lib::db::{
 };
 
 pub mod accounts;
+pub mod block_costs;
 pub mod blocks;
 pub mod contracts;
+pub mod epoch_dry_run;
 pub mod headers;
+pub mod reorgs;
 pub mod transactions;
 pub mod unconfirmed;
 
@@ -307,6 +311,7 @@ impl DBConfig {
             StacksEpochId::Epoch24 => version_u32 >= 3 && version_u32 <= 8,
             StacksEpochId::Epoch25 => version_u32 >= 3 && version_u32 <= 8,
             StacksEpochId::Epoch30 => version_u32 >= 3 && version_u32 <= 8,
+            StacksEpochId::Epoch31 => version_u32 >= 3 && version_u32 <= 8,
         }
     }
 }
@@ -680,7 +685,7 @@ impl<'a> DerefMut for ChainstateTx<'a> {
     }
 }
 
-pub const CHAINSTATE_VERSION: &'static str = "8";
+pub const CHAINSTATE_VERSION: &'static str = "11";
 
 const CHAINSTATE_INITIAL_SCHEMA: &'static [&'static str] = &[
     "PRAGMA foreign_keys = ON;",
@@ -879,6 +884,58 @@ const CHAINSTATE_SCHEMA_3: &'static [&'static str] = &[
     "#,
 ];
 
+const CHAINSTATE_SCHEMA_4: &'static [&'static str] = &[
+    // table of the most recent reorgs of the canonical Stacks chain, so they can be served to
+    // RPC clients after the event was sent.
+    r#"
+    CREATE TABLE stacks_reorgs(
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        old_tip TEXT NOT NULL,
+        new_tip TEXT NOT NULL,
+        fork_point TEXT NOT NULL,
+        detected_at INTEGER NOT NULL,
+        -- this is the JSON-encoded StacksReorg
+        data TEXT NOT NULL
+    );"#,
+    r#"
+    UPDATE db_config SET version = "9";
+    "#,
+];
+
+const CHAINSTATE_SCHEMA_5: &'static [&'static str] = &[
+    // microblocks whose data was moved to the microblock archive, so RPC requests for them can
+    // be told apart from requests for microblocks the node never had.
+    r#"
+    CREATE TABLE archived_microblocks(
+        microblock_hash TEXT PRIMARY KEY NOT NULL,
+        archived_at INTEGER NOT NULL
+    );"#,
+    r#"
+    UPDATE db_config SET version = "10";
+    "#,
+];
+
+const CHAINSTATE_SCHEMA_6: &'static [&'static str] = &[
+    // the execution cost of each accepted block, one column per dimension so that it can be
+    // aggregated in SQL, along with the cost of each of its transactions.
+    r#"
+    CREATE TABLE block_costs(
+        index_block_hash TEXT PRIMARY KEY NOT NULL,
+        block_height INTEGER NOT NULL,
+        write_length INTEGER NOT NULL,
+        write_count INTEGER NOT NULL,
+        read_length INTEGER NOT NULL,
+        read_count INTEGER NOT NULL,
+        runtime INTEGER NOT NULL,
+        -- this is the JSON-encoded list of TransactionCosts
+        tx_costs TEXT NOT NULL
+    );"#,
+    "CREATE INDEX block_costs_by_height ON block_costs(block_height);",
+    r#"
+    UPDATE db_config SET version = "11";
+    "#,
+];
+
 const CHAINSTATE_INDEXES: &'static [&'static str] = &[
     "CREATE INDEX IF NOT EXISTS index_block_hash_to_primary_key ON block_headers(index_block_hash,consensus_hash,block_hash);",
     "CREATE INDEX IF NOT EXISTS block_headers_hash_index ON block_headers(block_hash,block_height);",
@@ -967,6 +1024,8 @@ pub struct ChainStateBootData {
         Option<Box<dyn FnOnce() -> Box<dyn Iterator<Item = ChainstateBNSNamespace>>>>,
     pub get_bulk_initial_names:
         Option<Box<dyn FnOnce() -> Box<dyn Iterator<Item = ChainstateBNSName>>>>,
+    /// Supplies the boot contracts, if not those of the Stacks mainnet and testnet
+    pub boot_contract_provider: Option<Arc<dyn BootContractProvider>>,
 }
 
 impl ChainStateBootData {
@@ -986,6 +1045,7 @@ impl ChainStateBootData {
             get_bulk_initial_balances: None,
             get_bulk_initial_namespaces: None,
             get_bulk_initial_names: None,
+            boot_contract_provider: None,
         }
     }
 }
@@ -1134,6 +1194,30 @@ impl StacksChainState {
                         tx.execute_batch(cmd)?;
                     }
                 }
+                "8" => {
+                    info!(
+                        "Migrating chainstate schema from version 8 to 9: adds stacks_reorgs table"
+                    );
+                    for cmd in CHAINSTATE_SCHEMA_4.iter() {
+                        tx.execute_batch(cmd)?;
+                    }
+                }
+                "9" => {
+                    info!(
+                        "Migrating chainstate schema from version 9 to 10: adds archived_microblocks table"
+                    );
+                    for cmd in CHAINSTATE_SCHEMA_5.iter() {
+                        tx.execute_batch(cmd)?;
+                    }
+                }
+                "10" => {
+                    info!(
+                        "Migrating chainstate schema from version 10 to 11: adds block_costs table"
+                    );
+                    for cmd in CHAINSTATE_SCHEMA_6.iter() {
+                        tx.execute_batch(cmd)?;
+                    }
+                }
                 _ => {
                     error!(
                         "Invalid chain state database: expected version = {}, got {}",
@@ -1284,6 +1368,11 @@ impl StacksChainState {
         let mut initial_liquid_ustx = 0u128;
         let mut receipts = vec![];
 
+        let boot_code = chainstate
+            .clarity_state
+            .get_boot_contract_provider()
+            .genesis_contracts(mainnet);
+
         {
             let mut clarity_tx = chainstate.genesis_block_begin(
                 &NULL_BURN_STATE_DB,
@@ -1292,26 +1381,20 @@ impl StacksChainState {
                 &FIRST_BURNCHAIN_CONSENSUS_HASH,
                 &FIRST_STACKS_BLOCK_HASH,
             );
-            let boot_code = if mainnet {
-                *boot::STACKS_BOOT_CODE_MAINNET
-            } else {
-                *boot::STACKS_BOOT_CODE_TESTNET
-            };
-            for (boot_code_name, boot_code_contract) in boot_code.iter() {
+            for boot_contract in boot_code.into_iter() {
                 debug!(
                     "Instantiate boot code contract '{}' ({} bytes)...",
-                    boot_code_name,
-                    boot_code_contract.len()
+                    &boot_contract.name,
+                    boot_contract.code.len()
                 );
 
                 let smart_contract = TransactionPayload::SmartContract(
                     TransactionSmartContract {
-                        name: ContractName::try_from(boot_code_name.to_string())
-                            .expect("FATAL: invalid boot-code contract name"),
-                        code_body: StacksString::from_str(boot_code_contract)
+                        name: boot_contract.name,
+                        code_body: StacksString::from_str(&boot_contract.code)
                             .expect("FATAL: invalid boot code body"),
                     },
-                    None,
+                    boot_contract.clarity_version,
                 );
 
                 let boot_code_smart_contract = StacksTransaction::new(
@@ -1882,6 +1965,15 @@ impl StacksChainState {
             marf_opts: marf_opts,
         };
 
+        if let Some(provider) = boot_data
+            .as_ref()
+            .and_then(|boot_data| boot_data.boot_contract_provider.clone())
+        {
+            chainstate
+                .clarity_state
+                .set_boot_contract_provider(provider);
+        }
+
         let mut receipts = vec![];
         match (init_required, boot_data) {
             (true, Some(boot_data)) => {
@@ -1978,6 +2070,26 @@ impl StacksChainState {
         )
     }
 
+    /// Checked eval-read-only, bounded by `limits`
+    pub fn eval_read_only_with_limits(
+        &mut self,
+        burn_dbconn: &dyn BurnStateDB,
+        parent_id_bhh: &StacksBlockId,
+        contract: &QualifiedContractIdentifier,
+        code: &str,
+        limits: &ExecutionLimits,
+    ) -> Result<Value, clarity_error> {
+        self.clarity_state.eval_read_only_with_limits(
+            parent_id_bhh,
+            &HeadersDBConn(StacksDBConn::new(&self.state_index, ())),
+            burn_dbconn,
+            contract,
+            code,
+            ASTRules::PrecheckSize,
+            limits,
+        )
+    }
+
     /// Execute a public function in `contract` from a read-only DB context
     ///  Any mutations that occur will be rolled-back before returning, regardless of
     ///  an okay or error result.
@@ -2021,6 +2133,22 @@ impl StacksChainState {
         Ok(result)
     }
 
+    /// Simulate `call` on top of `parent_id_bhh`, with its state overrides applied.  Nothing is
+    ///  written to the chainstate.
+    pub fn simulate_contract_call(
+        &mut self,
+        burn_dbconn: &dyn BurnStateDB,
+        parent_id_bhh: &StacksBlockId,
+        call: &SimulatedContractCall,
+    ) -> Result<SimulatedCallResult, clarity_error> {
+        self.clarity_state.simulate_contract_call(
+            parent_id_bhh,
+            &HeadersDBConn(StacksDBConn::new(&self.state_index, ())),
+            burn_dbconn,
+            call,
+        )
+    }
+
     pub fn db(&self) -> &DBConn {
         self.state_index.sqlite_conn()
     }
@@ -2735,6 +2863,7 @@ impl StacksChainState {
 pub mod test {
     use std::{env, fs};
 
+    use clarity::vm::representations::ContractName;
     use clarity::vm::test_util::TEST_BURN_STATE_DB;
     use stx_genesis::GenesisData;
 
@@ -2781,6 +2910,7 @@ pub mod test {
             get_bulk_initial_balances: None,
             get_bulk_initial_names: None,
             get_bulk_initial_namespaces: None,
+            boot_contract_provider: None,
         };
 
         StacksChainState::open_and_exec(mainnet, chain_id, &path, Some(&mut boot_data), None)
@@ -2823,6 +2953,65 @@ pub mod test {
         }
     }
 
+    struct AppchainBootContracts;
+
+    impl BootContractProvider for AppchainBootContracts {
+        fn genesis_contracts(&self, mainnet: bool) -> Vec<BootContract> {
+            let mut contracts = StacksBootContracts.genesis_contracts(mainnet);
+            contracts.push(BootContract::new(
+                "appchain-registry",
+                "(define-data-var operator principal tx-sender)",
+                None,
+            ));
+            contracts
+        }
+    }
+
+    #[test]
+    fn test_instantiate_chainstate_with_boot_contract_provider() {
+        let path = chainstate_path(function_name!());
+        if fs::metadata(&path).is_ok() {
+            fs::remove_dir_all(&path).unwrap();
+        }
+
+        let mut boot_data = ChainStateBootData {
+            initial_balances: vec![],
+            post_flight_callback: None,
+            first_burnchain_block_hash: BurnchainHeaderHash::zero(),
+            first_burnchain_block_height: 0,
+            first_burnchain_block_timestamp: 0,
+            pox_constants: PoxConstants::testnet_default(),
+            get_bulk_initial_lockups: None,
+            get_bulk_initial_balances: None,
+            get_bulk_initial_names: None,
+            get_bulk_initial_namespaces: None,
+            boot_contract_provider: Some(Arc::new(AppchainBootContracts)),
+        };
+        let mut chainstate =
+            StacksChainState::open_and_exec(false, 0x80000000, &path, Some(&mut boot_data), None)
+                .unwrap()
+                .0;
+
+        let mut conn = chainstate.block_begin(
+            &TEST_BURN_STATE_DB,
+            &FIRST_BURNCHAIN_CONSENSUS_HASH,
+            &FIRST_STACKS_BLOCK_HASH,
+            &MINER_BLOCK_CONSENSUS_HASH,
+            &MINER_BLOCK_HEADER_HASH,
+        );
+        let registry_id = QualifiedContractIdentifier::new(
+            boot_code_test_addr().into(),
+            ContractName::try_from("appchain-registry".to_string()).unwrap(),
+        );
+        assert!(StacksChainState::get_contract(&mut conn, &registry_id)
+            .unwrap()
+            .is_some());
+        let pox_id = boot_code_id("pox", false);
+        assert!(StacksChainState::get_contract(&mut conn, &pox_id)
+            .unwrap()
+            .is_some());
+    }
+
     #[test]
     fn test_chainstate_sampled_genesis_consistency() {
         // Test root hash for the test chainstate data set
@@ -2875,6 +3064,7 @@ pub mod test {
                         }),
                 )
             })),
+            boot_contract_provider: None,
         };
 
         let path = chainstate_path(function_name!());
@@ -2965,6 +3155,7 @@ pub mod test {
                         }),
                 )
             })),
+            boot_contract_provider: None,
         };
 
         let path = chainstate_path(function_name!());
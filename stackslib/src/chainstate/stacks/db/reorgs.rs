@@ -0,0 +1,345 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Detection of reorgs of the canonical Stacks chain.  A reorg happens when the canonical tip
+//! switches to a block that does not descend from the previous tip.  The blocks of the old fork
+//! above the fork point are orphaned, and so are those of their transactions which the new fork
+//! does not also contain.  Recent reorgs are kept in the chainstate DB so they can be queried.
+
+use std::collections::HashSet;
+
+use stacks_common::types::chainstate::StacksBlockId;
+use stacks_common::util::get_epoch_time_secs;
+
+use crate::burnchains::Txid;
+use crate::chainstate::nakamoto::NakamotoChainState;
+use crate::chainstate::stacks::db::*;
+use crate::chainstate::stacks::Error;
+use crate::util_lib::db::{query_rows, u64_to_sql, DBConn, Error as db_error};
+
+/// Reorgs deeper than this are not reported; the orphaned fork would be too expensive to walk
+pub const MAX_REORG_DEPTH: u64 = 1024;
+
+/// How many reorgs the chainstate DB keeps
+pub const MAX_RECORDED_REORGS: u64 = 256;
+
+/// A switch of the canonical Stacks chain tip to a block that does not descend from the
+/// previous tip
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StacksReorg {
+    pub old_tip: StacksBlockId,
+    pub old_tip_height: u64,
+    pub new_tip: StacksBlockId,
+    pub new_tip_height: u64,
+    /// The highest block that both forks contain
+    pub fork_point: StacksBlockId,
+    pub fork_point_height: u64,
+    /// The blocks of the old fork above the fork point, highest first
+    pub orphaned_blocks: Vec<StacksBlockId>,
+    /// The transactions of the orphaned blocks that the new fork does not contain.  Only
+    /// anchored transactions are considered in epoch 2.x.
+    pub orphaned_txids: Vec<Txid>,
+    /// When the reorg was detected, in seconds since the Unix epoch
+    pub detected_at: u64,
+}
+
+impl StacksReorg {
+    /// How many blocks were orphaned
+    pub fn depth(&self) -> u64 {
+        self.orphaned_blocks.len() as u64
+    }
+}
+
+impl StacksChainState {
+    /// Find the ancestor of `tip` at `height`
+    fn get_reorg_ancestor(
+        &self,
+        tip: &StacksBlockId,
+        height: u64,
+    ) -> Result<Option<StacksBlockId>, Error> {
+        self.index_conn()
+            .get_ancestor_block_hash(height, tip)
+            .map_err(Error::DBError)
+    }
+
+    /// Load the txids of a processed block.  Blocks whose data is no longer stored (for
+    /// example, because it was pruned) have no txids.
+    fn get_reorg_block_txids(&self, block_id: &StacksBlockId) -> Result<Vec<Txid>, Error> {
+        let Some(header) = NakamotoChainState::get_block_header(self.db(), block_id)? else {
+            return Ok(vec![]);
+        };
+        let txs = if header.anchored_header.as_stacks_nakamoto().is_some() {
+            self.nakamoto_blocks_db()
+                .get_nakamoto_block(block_id)?
+                .map(|(block, _)| block.txs)
+        } else {
+            match StacksChainState::load_block(
+                &self.blocks_path,
+                &header.consensus_hash,
+                &header.anchored_header.block_hash(),
+            ) {
+                Ok(block) => block.map(|block| block.txs),
+                Err(e) => {
+                    debug!("Could not load block {block_id} to find orphaned txids: {e:?}");
+                    None
+                }
+            }
+        };
+        Ok(txs.unwrap_or_default().iter().map(|tx| tx.txid()).collect())
+    }
+
+    /// Determine whether or not moving the canonical tip from `old_tip` to `new_tip` is a reorg.
+    /// Returns Ok(None) if `new_tip` descends from `old_tip`, if either tip is unknown, or if
+    /// the reorg is deeper than `MAX_REORG_DEPTH`.
+    pub fn detect_reorg(
+        &self,
+        old_tip: &StacksBlockId,
+        new_tip: &StacksBlockId,
+    ) -> Result<Option<StacksReorg>, Error> {
+        if old_tip == new_tip {
+            return Ok(None);
+        }
+        let (Some(old_header), Some(new_header)) = (
+            NakamotoChainState::get_block_header(self.db(), old_tip)?,
+            NakamotoChainState::get_block_header(self.db(), new_tip)?,
+        ) else {
+            return Ok(None);
+        };
+        let old_tip_height = old_header.stacks_block_height;
+        let new_tip_height = new_header.stacks_block_height;
+
+        // walk back from the old tip until we reach a block that the new fork also contains
+        let mut orphaned_blocks = vec![];
+        let mut cursor = old_tip.clone();
+        let mut cursor_height = old_tip_height;
+        loop {
+            if cursor_height <= new_tip_height
+                && self.get_reorg_ancestor(new_tip, cursor_height)?.as_ref() == Some(&cursor)
+            {
+                break;
+            }
+            if orphaned_blocks.len() as u64 >= MAX_REORG_DEPTH || cursor_height == 0 {
+                warn!(
+                    "Stacks reorg is too deep to report";
+                    "old_tip" => %old_tip,
+                    "new_tip" => %new_tip,
+                    "max_depth" => MAX_REORG_DEPTH
+                );
+                return Ok(None);
+            }
+            let Some(parent) = self.get_reorg_ancestor(&cursor, cursor_height - 1)? else {
+                warn!(
+                    "Could not find the parent of {cursor} at height {}",
+                    cursor_height - 1
+                );
+                return Ok(None);
+            };
+            orphaned_blocks.push(cursor);
+            cursor = parent;
+            cursor_height -= 1;
+        }
+
+        if orphaned_blocks.is_empty() {
+            // the new tip descends from the old tip
+            return Ok(None);
+        }
+
+        let mut adopted_txids = HashSet::new();
+        for height in (cursor_height + 1)..=new_tip_height {
+            if let Some(block_id) = self.get_reorg_ancestor(new_tip, height)? {
+                adopted_txids.extend(self.get_reorg_block_txids(&block_id)?);
+            }
+        }
+        let mut orphaned_txids = vec![];
+        for block_id in orphaned_blocks.iter() {
+            for txid in self.get_reorg_block_txids(block_id)? {
+                if !adopted_txids.contains(&txid) {
+                    orphaned_txids.push(txid);
+                }
+            }
+        }
+
+        Ok(Some(StacksReorg {
+            old_tip: old_tip.clone(),
+            old_tip_height,
+            new_tip: new_tip.clone(),
+            new_tip_height,
+            fork_point: cursor,
+            fork_point_height: cursor_height,
+            orphaned_blocks,
+            orphaned_txids,
+            detected_at: get_epoch_time_secs(),
+        }))
+    }
+
+    /// Store a reorg, and forget the oldest ones beyond `MAX_RECORDED_REORGS`
+    pub fn record_reorg(&mut self, reorg: &StacksReorg) -> Result<(), Error> {
+        let data = serde_json::to_string(reorg).expect("FATAL: failed to encode reorg");
+        let tx = self.db_tx_begin()?;
+        tx.execute(
+            "INSERT INTO stacks_reorgs (old_tip,new_tip,fork_point,detected_at,data) VALUES (?1,?2,?3,?4,?5)",
+            params![
+                reorg.old_tip,
+                reorg.new_tip,
+                reorg.fork_point,
+                u64_to_sql(reorg.detected_at)?,
+                data
+            ],
+        )?;
+        tx.execute(
+            "DELETE FROM stacks_reorgs WHERE id NOT IN (SELECT id FROM stacks_reorgs ORDER BY id DESC LIMIT ?1)",
+            params![u64_to_sql(MAX_RECORDED_REORGS)?],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Get up to `limit` of the most recent reorgs, most recent first
+    pub fn get_recent_reorgs(conn: &DBConn, limit: u64) -> Result<Vec<StacksReorg>, Error> {
+        let rows: Vec<String> = query_rows(
+            conn,
+            "SELECT data FROM stacks_reorgs ORDER BY id DESC LIMIT ?1",
+            params![u64_to_sql(limit)?],
+        )?;
+        rows.iter()
+            .map(|data| {
+                serde_json::from_str(data)
+                    .map_err(|e| Error::DBError(db_error::SerializationError(e)))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use clarity::vm::costs::ExecutionCost;
+    use stacks_common::types::chainstate::BurnchainHeaderHash;
+    use stacks_common::util::hash::{Hash160, Sha512Trunc256Sum};
+
+    use super::*;
+    use crate::chainstate::burn::ConsensusHash;
+    use crate::chainstate::stacks::db::test::*;
+    use crate::core::CHAIN_ID_TESTNET;
+
+    /// Add a child of `parent` to the headers DB.  Siblings are told apart by `salt`.
+    fn extend(
+        chainstate: &mut StacksChainState,
+        parent: &StacksHeaderInfo,
+        salt: u8,
+    ) -> StacksHeaderInfo {
+        let mut child = parent.clone();
+        let mut header = parent.anchored_header.as_stacks_epoch2().unwrap().clone();
+        header.parent_block = parent.anchored_header.block_hash();
+        header.microblock_pubkey_hash =
+            Hash160::from_data(&[&header.microblock_pubkey_hash.0[..], &[salt][..]].concat());
+        header.total_work.work += 1;
+        child.anchored_header = header.into();
+        child.microblock_tail = None;
+        child.stacks_block_height = parent.stacks_block_height + 1;
+        child.consensus_hash =
+            ConsensusHash(Hash160::from_data(&child.anchored_header.block_hash().0).0);
+        child.burn_header_hash =
+            BurnchainHeaderHash(Sha512Trunc256Sum::from_data(&child.consensus_hash.0).0);
+        child.burn_header_height = parent.burn_header_height + 1;
+
+        let reward = MinerPaymentSchedule::genesis(false);
+        let mut tx = chainstate.index_tx_begin();
+        let child = StacksChainState::advance_tip(
+            &mut tx,
+            parent.anchored_header.as_stacks_epoch2().unwrap(),
+            &parent.consensus_hash,
+            child.anchored_header.as_stacks_epoch2().unwrap(),
+            &child.consensus_hash,
+            &child.burn_header_hash,
+            child.burn_header_height,
+            child.burn_header_timestamp,
+            None,
+            &reward,
+            None,
+            &ExecutionCost::zero(),
+            123,
+            false,
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+            parent.anchored_header.height() + 1,
+        )
+        .unwrap();
+        tx.commit().unwrap();
+        child
+    }
+
+    #[test]
+    fn test_detect_and_record_reorgs() {
+        let mut chainstate =
+            instantiate_chainstate(false, CHAIN_ID_TESTNET, "test_detect_and_record_reorgs");
+
+        let genesis = StacksHeaderInfo::regtest_genesis();
+        let common = extend(&mut chainstate, &genesis, 0);
+        let a1 = extend(&mut chainstate, &common, 1);
+        let a2 = extend(&mut chainstate, &a1, 1);
+        let b1 = extend(&mut chainstate, &common, 2);
+        let b2 = extend(&mut chainstate, &b1, 2);
+        let b3 = extend(&mut chainstate, &b2, 2);
+
+        // extending the tip is not a reorg
+        assert!(chainstate
+            .detect_reorg(&a1.index_block_hash(), &a2.index_block_hash())
+            .unwrap()
+            .is_none());
+        assert!(chainstate
+            .detect_reorg(&a2.index_block_hash(), &a2.index_block_hash())
+            .unwrap()
+            .is_none());
+
+        // switching from a2 to b3 orphans a2 and a1
+        let reorg = chainstate
+            .detect_reorg(&a2.index_block_hash(), &b3.index_block_hash())
+            .unwrap()
+            .unwrap();
+        assert_eq!(reorg.old_tip_height, a2.stacks_block_height);
+        assert_eq!(reorg.new_tip_height, b3.stacks_block_height);
+        assert_eq!(reorg.fork_point, common.index_block_hash());
+        assert_eq!(reorg.fork_point_height, common.stacks_block_height);
+        assert_eq!(
+            reorg.orphaned_blocks,
+            vec![a2.index_block_hash(), a1.index_block_hash()]
+        );
+        assert_eq!(reorg.depth(), 2);
+
+        // switching back to a lower tip is a reorg too
+        let reorg_back = chainstate
+            .detect_reorg(&b3.index_block_hash(), &a1.index_block_hash())
+            .unwrap()
+            .unwrap();
+        assert_eq!(reorg_back.fork_point, common.index_block_hash());
+        assert_eq!(
+            reorg_back.orphaned_blocks,
+            vec![
+                b3.index_block_hash(),
+                b2.index_block_hash(),
+                b1.index_block_hash()
+            ]
+        );
+
+        chainstate.record_reorg(&reorg).unwrap();
+        chainstate.record_reorg(&reorg_back).unwrap();
+        let recent = StacksChainState::get_recent_reorgs(chainstate.db(), 10).unwrap();
+        assert_eq!(recent, vec![reorg_back.clone(), reorg.clone()]);
+        let recent = StacksChainState::get_recent_reorgs(chainstate.db(), 1).unwrap();
+        assert_eq!(recent, vec![reorg_back]);
+    }
+}
@@ -14,7 +14,6 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::{HashMap, HashSet};
 use std::io::prelude::*;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
@@ -25,7 +24,7 @@ use clarity::vm::analysis::types::ContractAnalysis;
 use clarity::vm::ast::errors::ParseErrors;
 use clarity::vm::ast::ASTRules;
 use clarity::vm::clarity::TransactionConnection;
-use clarity::vm::contexts::{AssetMap, AssetMapEntry, Environment};
+use clarity::vm::contexts::{AssetMap, Environment};
 use clarity::vm::contracts::Contract;
 use clarity::vm::costs::cost_functions::ClarityCostFunction;
 use clarity::vm::costs::{cost_functions, runtime_cost, CostTracker, ExecutionCost};
@@ -34,15 +33,18 @@ use clarity::vm::errors::Error as InterpreterError;
 use clarity::vm::representations::{ClarityName, ContractName};
 use clarity::vm::types::serialization::SerializationError as ClaritySerializationError;
 use clarity::vm::types::{
-    AssetIdentifier, BuffData, PrincipalData, QualifiedContractIdentifier, SequenceData,
-    StacksAddressExtensions as ClarityStacksAddressExt, StandardPrincipalData, TupleData,
-    TypeSignature, Value,
+    BuffData, PrincipalData, QualifiedContractIdentifier, SequenceData,
+    StacksAddressExtensions as ClarityStacksAddressExt, TupleData, TypeSignature, Value,
 };
+use rayon::prelude::*;
 use stacks_common::util::hash::to_hex;
 
 use crate::chainstate::burn::db::sortdb::*;
 use crate::chainstate::nakamoto::NakamotoChainState;
 use crate::chainstate::stacks::db::*;
+use crate::chainstate::stacks::post_conditions::{
+    check_post_conditions_in_asset_map, PostConditionError,
+};
 use crate::chainstate::stacks::{Error, StacksMicroblockHeader, *};
 use crate::clarity_vm::clarity::{
     ClarityBlockConnection, ClarityConnection, ClarityInstance, ClarityTransactionConnection,
@@ -52,33 +54,6 @@ use crate::net::Error as net_error;
 use crate::util_lib::db::{query_count, query_rows, DBConn, Error as db_error};
 use crate::util_lib::strings::{StacksString, VecDisplay};
 
-/// This is a safe-to-hash Clarity value
-#[derive(PartialEq, Eq)]
-struct HashableClarityValue(Value);
-
-impl TryFrom<Value> for HashableClarityValue {
-    type Error = InterpreterError;
-
-    fn try_from(value: Value) -> Result<Self, Self::Error> {
-        // check that serialization _will_ be successful when hashed
-        let _bytes = value.serialize_to_vec().map_err(|_| {
-            InterpreterError::Interpreter(clarity::vm::errors::InterpreterError::Expect(
-                "Failed to serialize asset in NFT during post-condition checks".into(),
-            ))
-        })?;
-        Ok(Self(value))
-    }
-}
-
-impl std::hash::Hash for HashableClarityValue {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        #[allow(clippy::unwrap_used)]
-        // this unwrap is safe _as long as_ TryFrom<Value> was used as a constructor
-        let bytes = self.0.serialize_to_vec().unwrap();
-        bytes.hash(state);
-    }
-}
-
 impl StacksTransactionReceipt {
     pub fn from_stx_transfer(
         tx: StacksTransaction,
@@ -570,6 +545,24 @@ impl StacksChainState {
         Ok(())
     }
 
+    /// Run `process_transaction_precheck()` on each of a block's transactions, in parallel.
+    /// Returns the index of the first transaction (in block order) that fails the precheck, if
+    ///  any.  Transactions before it don't need to be prechecked again.
+    ///
+    /// Only these stateless checks run in parallel.  Executing the transactions themselves stays
+    ///  serial, in block order: they all write to the block's one MARF transaction, so scheduling
+    ///  independent transactions on a thread pool by their contract footprints is out of scope.
+    pub fn precheck_block_transactions(
+        clarity_tx: &ClarityTx,
+        block_txs: &[StacksTransaction],
+    ) -> Option<usize> {
+        let epoch_id = clarity_tx.get_epoch();
+        let config = &clarity_tx.config;
+        block_txs.par_iter().position_first(|tx| {
+            StacksChainState::process_transaction_precheck(config, tx, epoch_id).is_err()
+        })
+    }
+
     /// Apply a post-conditions check.
     /// Return true if they all pass.
     /// Return false if at least one fails.
@@ -580,187 +573,19 @@ impl StacksChainState {
         asset_map: &AssetMap,
         txid: Txid,
     ) -> Result<bool, InterpreterError> {
-        let mut checked_fungible_assets: HashMap<PrincipalData, HashSet<AssetIdentifier>> =
-            HashMap::new();
-        let mut checked_nonfungible_assets: HashMap<
-            PrincipalData,
-            HashMap<AssetIdentifier, HashSet<HashableClarityValue>>,
-        > = HashMap::new();
-        let allow_unchecked_assets = *post_condition_mode == TransactionPostConditionMode::Allow;
-
-        for postcond in post_conditions {
-            match postcond {
-                TransactionPostCondition::STX(
-                    ref principal,
-                    ref condition_code,
-                    ref amount_sent_condition,
-                ) => {
-                    let account_principal = principal.to_principal_data(&origin_account.principal);
-
-                    let amount_transferred = asset_map.get_stx(&account_principal).unwrap_or(0);
-                    let amount_burned = asset_map.get_stx_burned(&account_principal).unwrap_or(0);
-
-                    let amount_sent = amount_transferred
-                        .checked_add(amount_burned)
-                        .expect("FATAL: sent waaaaay too much STX");
-
-                    if !condition_code.check(u128::from(*amount_sent_condition), amount_sent) {
-                        info!(
-                            "Post-condition check failure on STX owned by {}: {:?} {:?} {}",
-                            account_principal, amount_sent_condition, condition_code, amount_sent; "txid" => %txid
-                        );
-                        return Ok(false);
-                    }
-
-                    if let Some(ref mut asset_ids) =
-                        checked_fungible_assets.get_mut(&account_principal)
-                    {
-                        if amount_transferred > 0 {
-                            asset_ids.insert(AssetIdentifier::STX());
-                        }
-                        if amount_burned > 0 {
-                            asset_ids.insert(AssetIdentifier::STX_burned());
-                        }
-                    } else {
-                        let mut h = HashSet::new();
-                        if amount_transferred > 0 {
-                            h.insert(AssetIdentifier::STX());
-                        }
-                        if amount_burned > 0 {
-                            h.insert(AssetIdentifier::STX_burned());
-                        }
-                        checked_fungible_assets.insert(account_principal, h);
-                    }
-                }
-                TransactionPostCondition::Fungible(
-                    ref principal,
-                    ref asset_info,
-                    ref condition_code,
-                    ref amount_sent_condition,
-                ) => {
-                    let account_principal = principal.to_principal_data(&origin_account.principal);
-                    let asset_id = AssetIdentifier {
-                        contract_identifier: QualifiedContractIdentifier::new(
-                            StandardPrincipalData::from(asset_info.contract_address.clone()),
-                            asset_info.contract_name.clone(),
-                        ),
-                        asset_name: asset_info.asset_name.clone(),
-                    };
-
-                    let amount_sent = asset_map
-                        .get_fungible_tokens(&account_principal, &asset_id)
-                        .unwrap_or(0);
-                    if !condition_code.check(u128::from(*amount_sent_condition), amount_sent) {
-                        info!("Post-condition check failure on fungible asset {} owned by {}: {} {:?} {}", &asset_id, account_principal, amount_sent_condition, condition_code, amount_sent; "txid" => %txid);
-                        return Ok(false);
-                    }
-
-                    if let Some(ref mut asset_ids) =
-                        checked_fungible_assets.get_mut(&account_principal)
-                    {
-                        asset_ids.insert(asset_id);
-                    } else {
-                        let mut h = HashSet::new();
-                        h.insert(asset_id);
-                        checked_fungible_assets.insert(account_principal, h);
-                    }
-                }
-                TransactionPostCondition::Nonfungible(
-                    ref principal,
-                    ref asset_info,
-                    ref asset_value,
-                    ref condition_code,
-                ) => {
-                    let account_principal = principal.to_principal_data(&origin_account.principal);
-                    let asset_id = AssetIdentifier {
-                        contract_identifier: QualifiedContractIdentifier::new(
-                            StandardPrincipalData::from(asset_info.contract_address.clone()),
-                            asset_info.contract_name.clone(),
-                        ),
-                        asset_name: asset_info.asset_name.clone(),
-                    };
-
-                    let empty_assets = vec![];
-                    let assets_sent = asset_map
-                        .get_nonfungible_tokens(&account_principal, &asset_id)
-                        .unwrap_or(&empty_assets);
-                    if !condition_code.check(asset_value, assets_sent) {
-                        info!("Post-condition check failure on non-fungible asset {} owned by {}: {:?} {:?}", &asset_id, account_principal, &asset_value, condition_code; "txid" => %txid);
-                        return Ok(false);
-                    }
-
-                    if let Some(ref mut asset_id_map) =
-                        checked_nonfungible_assets.get_mut(&account_principal)
-                    {
-                        if let Some(ref mut asset_values) = asset_id_map.get_mut(&asset_id) {
-                            asset_values.insert(asset_value.clone().try_into()?);
-                        } else {
-                            let mut asset_set = HashSet::new();
-                            asset_set.insert(asset_value.clone().try_into()?);
-                            asset_id_map.insert(asset_id, asset_set);
-                        }
-                    } else {
-                        let mut asset_id_map = HashMap::new();
-                        let mut asset_set = HashSet::new();
-                        asset_set.insert(asset_value.clone().try_into()?);
-                        asset_id_map.insert(asset_id, asset_set);
-                        checked_nonfungible_assets.insert(account_principal, asset_id_map);
-                    }
-                }
-            }
-        }
-
-        if !allow_unchecked_assets {
-            // make sure every asset transferred is covered by a postcondition
-            let asset_map_copy = (*asset_map).clone();
-            let mut all_assets_sent = asset_map_copy.to_table();
-            for (principal, mut assets) in all_assets_sent.drain() {
-                for (asset_identifier, asset_entry) in assets.drain() {
-                    match asset_entry {
-                        AssetMapEntry::Asset(values) => {
-                            // this is a NFT
-                            if let Some(ref checked_nft_asset_map) =
-                                checked_nonfungible_assets.get(&principal)
-                            {
-                                if let Some(ref nfts) = checked_nft_asset_map.get(&asset_identifier)
-                                {
-                                    // each value must be covered
-                                    for v in values {
-                                        if !nfts.contains(&v.clone().try_into()?) {
-                                            info!("Post-condition check failure: Non-fungible asset {} value {:?} was moved by {} but not checked", &asset_identifier, &v, &principal; "txid" => %txid);
-                                            return Ok(false);
-                                        }
-                                    }
-                                } else {
-                                    // no values covered
-                                    info!("Post-condition check failure: No checks for non-fungible asset type {} moved by {}", &asset_identifier, &principal; "txid" => %txid);
-                                    return Ok(false);
-                                }
-                            } else {
-                                // no NFT for this principal
-                                info!("Post-condition check failure: No checks for any non-fungible assets, but moved {} by {}", &asset_identifier, &principal; "txid" => %txid);
-                                return Ok(false);
-                            }
-                        }
-                        _ => {
-                            // This is STX or a fungible token
-                            if let Some(ref checked_ft_asset_ids) =
-                                checked_fungible_assets.get(&principal)
-                            {
-                                if !checked_ft_asset_ids.contains(&asset_identifier) {
-                                    info!("Post-condition check failure: checks did not cover transfer of {} by {}", &asset_identifier, &principal; "txid" => %txid);
-                                    return Ok(false);
-                                }
-                            } else {
-                                info!("Post-condition check failure: No checks for fungible token type {} moved by {}", &asset_identifier, &principal; "txid" => %txid);
-                                return Ok(false);
-                            }
-                        }
-                    }
-                }
+        match check_post_conditions_in_asset_map(
+            asset_map,
+            post_condition_mode,
+            post_conditions,
+            &origin_account.principal,
+        ) {
+            Ok(()) => Ok(true),
+            Err(PostConditionError::Interpreter(e)) => Err(e),
+            Err(failure) => {
+                info!("Post-condition check failure: {}", failure; "txid" => %txid);
+                Ok(false)
             }
         }
-        return Ok(true);
     }
 
     /// Given two microblock headers, were they signed by the same key?
@@ -1477,10 +1302,20 @@ impl StacksChainState {
         quiet: bool,
         ast_rules: ASTRules,
     ) -> Result<(u64, StacksTransactionReceipt), Error> {
-        debug!("Process transaction {} ({})", tx.txid(), tx.payload.name());
         let epoch = clarity_block.get_epoch();
-
         StacksChainState::process_transaction_precheck(&clarity_block.config, tx, epoch)?;
+        StacksChainState::process_prechecked_transaction(clarity_block, tx, quiet, ast_rules)
+    }
+
+    /// Process a transaction that has passed `process_transaction_precheck()`
+    pub fn process_prechecked_transaction(
+        clarity_block: &mut ClarityTx,
+        tx: &StacksTransaction,
+        quiet: bool,
+        ast_rules: ASTRules,
+    ) -> Result<(u64, StacksTransactionReceipt), Error> {
+        debug!("Process transaction {} ({})", tx.txid(), tx.payload.name());
+        let epoch = clarity_block.get_epoch();
 
         // what version of Clarity did the transaction caller want? And, is it valid now?
         let clarity_version = StacksChainState::get_tx_clarity_version(clarity_block, tx)?;
@@ -1826,6 +1661,89 @@ pub mod test {
         }
     }
 
+    #[test]
+    fn precheck_block_transactions_matches_serial_precheck() {
+        let mut chainstate = instantiate_chainstate(false, 0x80000000, function_name!());
+
+        let privk = StacksPrivateKey::from_hex(
+            "6d430bb91222408e7706c9001cfaeb91b08c2be6d5ac95779ab52c6b431950e001",
+        )
+        .unwrap();
+        let auth = TransactionAuth::from_p2pkh(&privk).unwrap();
+        let recv_addr = StacksAddress {
+            version: 1,
+            bytes: Hash160([0xff; 20]),
+        };
+        let make_tx = |nonce: u64, version: TransactionVersion, chain_id: u32| {
+            let mut tx_stx_transfer = StacksTransaction::new(
+                version,
+                auth.clone(),
+                TransactionPayload::TokenTransfer(
+                    recv_addr.into(),
+                    123,
+                    TokenTransferMemo([0u8; 34]),
+                ),
+            );
+            tx_stx_transfer.chain_id = chain_id;
+            tx_stx_transfer.post_condition_mode = TransactionPostConditionMode::Allow;
+            tx_stx_transfer.set_tx_fee(0);
+            tx_stx_transfer.set_origin_nonce(nonce);
+
+            let mut signer = StacksTransactionSigner::new(&tx_stx_transfer);
+            signer.sign_origin(&privk).unwrap();
+            signer.get_tx().unwrap()
+        };
+        let valid_txs: Vec<_> = (0..16)
+            .map(|nonce| make_tx(nonce, TransactionVersion::Testnet, 0x80000000))
+            .collect();
+
+        let conn = chainstate.block_begin(
+            ALL_BURN_DBS[0],
+            &FIRST_BURNCHAIN_CONSENSUS_HASH,
+            &FIRST_STACKS_BLOCK_HASH,
+            &ConsensusHash([1; 20]),
+            &BlockHeaderHash([1; 32]),
+        );
+        let epoch_id = conn.get_epoch();
+
+        let mut blocks = vec![valid_txs.clone()];
+        for invalid_at in [vec![0], vec![15], vec![3, 9], vec![12, 5, 14]] {
+            let mut block_txs = valid_txs.clone();
+            for (i, index) in invalid_at.into_iter().enumerate() {
+                block_txs[index] = if i % 2 == 0 {
+                    make_tx(index as u64, TransactionVersion::Testnet, 0x00000001)
+                } else {
+                    make_tx(index as u64, TransactionVersion::Mainnet, 0x80000000)
+                };
+            }
+            blocks.push(block_txs);
+        }
+
+        for block_txs in blocks.iter() {
+            let serial_first_invalid = block_txs.iter().position(|tx| {
+                StacksChainState::process_transaction_precheck(&conn.config, tx, epoch_id).is_err()
+            });
+            assert_eq!(
+                StacksChainState::precheck_block_transactions(&conn, block_txs),
+                serial_first_invalid
+            );
+        }
+        assert_eq!(
+            StacksChainState::precheck_block_transactions(&conn, &blocks[0]),
+            None
+        );
+        assert_eq!(
+            StacksChainState::precheck_block_transactions(&conn, &blocks[3]),
+            Some(3)
+        );
+        assert_eq!(
+            StacksChainState::precheck_block_transactions(&conn, &blocks[4]),
+            Some(5)
+        );
+
+        conn.commit_block();
+    }
+
     #[test]
     fn process_token_transfer_stx_transaction_invalid() {
         let mut chainstate = instantiate_chainstate(false, 0x80000000, function_name!());
@@ -8725,6 +8643,7 @@ pub mod test {
                     StacksEpochId::Epoch24 => self.get_stacks_epoch(5),
                     StacksEpochId::Epoch25 => self.get_stacks_epoch(6),
                     StacksEpochId::Epoch30 => self.get_stacks_epoch(7),
+                    StacksEpochId::Epoch31 => self.get_stacks_epoch(8),
                 }
             }
             fn get_pox_payout_addrs(
@@ -37,6 +37,8 @@ use crate::chainstate::stacks::index::trie::Trie;
 use crate::chainstate::stacks::index::{
     ClarityMarfTrieId, Error, MARFValue, MarfTrieId, TrieHashExtension, TrieLeaf, TrieMerkleProof,
 };
+use crate::clarity_vm::database::audit::MarfReadAuditConfig;
+use crate::clarity_vm::database::encryption::SideStoreCipher;
 use crate::util_lib::db::Error as db_error;
 
 pub const BLOCK_HASH_TO_HEIGHT_MAPPING_KEY: &str = "__MARF_BLOCK_HASH_TO_HEIGHT";
@@ -71,6 +73,18 @@ pub struct MARFOpenOpts {
     pub external_blobs: bool,
     /// unconditionally do a DB migration (used for testing)
     pub force_db_migrate: bool,
+    /// if set, encrypt the values of the Clarity side-store (only used by `MarfedKV`)
+    pub side_store_cipher: Option<SideStoreCipher>,
+    /// if set, log a proof of every read made while processing a block (only used by `MarfedKV`)
+    pub read_audit: Option<MarfReadAuditConfig>,
+    /// if set, the most bytes of data that each contract may store (only used by `MarfedKV`)
+    pub contract_data_quota: Option<u64>,
+    /// if set, run the contracts loaded from the store through the constant-folding pass.  This
+    /// changes execution costs, so it is only for local tooling (only used by `MarfedKV`)
+    pub optimize_contracts: bool,
+    /// if set, memoize the read-only function calls made while processing each block (only used
+    /// by `MarfedKV`)
+    pub memoize_read_only_calls: bool,
 }
 
 impl MARFOpenOpts {
@@ -80,6 +94,11 @@ impl MARFOpenOpts {
             cache_strategy: "noop".to_string(),
             external_blobs: false,
             force_db_migrate: false,
+            side_store_cipher: None,
+            read_audit: None,
+            contract_data_quota: None,
+            optimize_contracts: false,
+            memoize_read_only_calls: false,
         }
     }
 
@@ -93,6 +112,11 @@ impl MARFOpenOpts {
             cache_strategy: cache_strategy.to_string(),
             external_blobs,
             force_db_migrate: false,
+            side_store_cipher: None,
+            read_audit: None,
+            contract_data_quota: None,
+            optimize_contracts: false,
+            memoize_read_only_calls: false,
         }
     }
 
@@ -494,6 +518,25 @@ impl<'a, T: MarfTrieId> MarfTransaction<'a, T> {
         Ok(())
     }
 
+    /// Insert a batch of path/value pairs, for state whose keys are only known by their paths
+    pub fn insert_raw_batch(
+        &mut self,
+        paths: &[TriePath],
+        values: Vec<MARFValue>,
+    ) -> Result<(), Error> {
+        if self.storage.readonly() {
+            return Err(Error::ReadOnlyError);
+        }
+        assert_eq!(paths.len(), values.len());
+
+        let block_hash = match self.open_chain_tip {
+            None => Err(Error::WriteNotBegunError),
+            Some(WriteChainTip { ref block_hash, .. }) => Ok(block_hash.clone()),
+        }?;
+
+        MARF::inner_insert_raw_batch(&mut self.storage, &block_hash, paths, values)
+    }
+
     /// Begin extending the MARF to an unconfirmed trie.  The resulting trie will have a block hash
     /// equal to MARF::make_unconfirmed_block_hash(chain_tip) to avoid collision
     /// and block hash reuse.
@@ -1115,6 +1158,13 @@ impl<T: MarfTrieId> MARF<T> {
         Ok(MARF::from_storage(file_storage))
     }
 
+    /// Instantiate a read-only MARF using a TrieFileStorage instance, from the given path on disk.
+    /// The MARF must already exist.
+    pub fn from_path_readonly(path: &str, open_opts: MARFOpenOpts) -> Result<MARF<T>, Error> {
+        let file_storage = TrieFileStorage::open_readonly(path, open_opts)?;
+        Ok(MARF::from_storage(file_storage))
+    }
+
     /// Instantiate an unconfirmed MARF using a TrieFileStorage instance, from the given path on disk.
     /// This will have the side-effect of instantiating a new fork table from the tries encoded on
     /// disk. Performant code should call this method sparingly.
@@ -1152,6 +1202,116 @@ impl<T: MarfTrieId> MARF<T> {
         result.map(|option_result| option_result.map(|leaf| leaf.data))
     }
 
+    /// Pass the value of every leaf in `block_hash`'s trie to `visit`.  If `follow_backptrs` is
+    /// set, this is every value in the state as of `block_hash`; otherwise, it is only the values
+    /// written in that block.  A value is visited once for every leaf that holds it.
+    pub fn collect_leaf_values<F: FnMut(MARFValue)>(
+        storage: &mut TrieStorageConnection<T>,
+        block_hash: &T,
+        follow_backptrs: bool,
+        visit: &mut F,
+    ) -> Result<(), Error> {
+        let (cur_block_hash, cur_block_id) = storage.get_cur_block_and_id();
+
+        let result = MARF::inner_collect_leaf_values(storage, block_hash, follow_backptrs, visit);
+
+        // restore
+        storage.open_block_maybe_id(&cur_block_hash, cur_block_id)?;
+
+        result
+    }
+
+    fn inner_collect_leaf_values<F: FnMut(MARFValue)>(
+        storage: &mut TrieStorageConnection<T>,
+        block_hash: &T,
+        follow_backptrs: bool,
+        visit: &mut F,
+    ) -> Result<(), Error> {
+        storage.open_block(block_hash)?;
+        let block_id = storage.get_cur_block_identifier()?;
+        let mut pending = vec![(block_hash.clone(), block_id, storage.root_trieptr())];
+        while let Some((node_block_hash, node_block_id, ptr)) = pending.pop() {
+            storage.open_block_known_id(&node_block_hash, node_block_id)?;
+            let node = storage.read_nodetype_nohash(&ptr)?;
+            if let TrieNodeType::Leaf(leaf) = node {
+                visit(leaf.data);
+                continue;
+            }
+            for child in node.ptrs() {
+                if child.id() == TrieNodeID::Empty as u8 {
+                    continue;
+                }
+                if !is_backptr(child.id()) {
+                    pending.push((node_block_hash.clone(), node_block_id, *child));
+                } else if follow_backptrs {
+                    let back_block_id = child.back_block();
+                    let back_block_hash = storage.get_block_from_local_id(back_block_id)?.clone();
+                    pending.push((back_block_hash, back_block_id, child.from_backptr()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the path and value of every leaf in the state as of `block_hash`, in no particular
+    /// order.
+    pub fn collect_leaves(
+        storage: &mut TrieStorageConnection<T>,
+        block_hash: &T,
+    ) -> Result<Vec<(TriePath, MARFValue)>, Error> {
+        let (cur_block_hash, cur_block_id) = storage.get_cur_block_and_id();
+
+        let result = MARF::inner_collect_leaves(storage, block_hash);
+
+        // restore
+        storage.open_block_maybe_id(&cur_block_hash, cur_block_id)?;
+
+        result
+    }
+
+    fn inner_collect_leaves(
+        storage: &mut TrieStorageConnection<T>,
+        block_hash: &T,
+    ) -> Result<Vec<(TriePath, MARFValue)>, Error> {
+        let mut leaves = vec![];
+        storage.open_block(block_hash)?;
+        let block_id = storage.get_cur_block_identifier()?;
+        // each node's path is the path to it, then its own (compressed) path segment
+        let mut pending = vec![(block_hash.clone(), block_id, storage.root_trieptr(), vec![])];
+        while let Some((node_block_hash, node_block_id, ptr, mut path)) = pending.pop() {
+            storage.open_block_known_id(&node_block_hash, node_block_id)?;
+            let node = storage.read_nodetype_nohash(&ptr)?;
+            path.extend_from_slice(node.path_bytes());
+            if let TrieNodeType::Leaf(leaf) = node {
+                let path = TriePath::from_bytes(&path).ok_or_else(|| {
+                    Error::CorruptionError(format!("Leaf path has {} bytes", path.len()))
+                })?;
+                leaves.push((path, leaf.data));
+                continue;
+            }
+            for child in node.ptrs() {
+                if child.id() == TrieNodeID::Empty as u8 {
+                    continue;
+                }
+                let mut child_path = path.clone();
+                child_path.push(child.chr());
+                if !is_backptr(child.id()) {
+                    pending.push((node_block_hash.clone(), node_block_id, *child, child_path));
+                } else {
+                    let back_block_id = child.back_block();
+                    let back_block_hash = storage.get_block_from_local_id(back_block_id)?.clone();
+                    pending.push((
+                        back_block_hash,
+                        back_block_id,
+                        child.from_backptr(),
+                        child_path,
+                    ));
+                }
+            }
+        }
+        Ok(leaves)
+    }
+
     pub fn get_block_height_miner_tip(
         storage: &mut TrieStorageConnection<T>,
         block_hash: &T,
@@ -1245,24 +1405,34 @@ impl<T: MarfTrieId> MARF<T> {
         keys: &Vec<String>,
         values: Vec<MARFValue>,
     ) -> Result<(), Error> {
-        assert_eq!(keys.len(), values.len());
+        let paths: Vec<_> = keys.iter().map(|key| TriePath::from_key(key)).collect();
+        MARF::inner_insert_raw_batch(conn, block_hash, &paths, values)
+    }
 
-        if keys.len() == 0 {
+    /// Insert a batch of path/value pairs (see `MARF::inner_insert_batch()`)
+    fn inner_insert_raw_batch(
+        conn: &mut TrieStorageTransaction<T>,
+        block_hash: &T,
+        paths: &[TriePath],
+        values: Vec<MARFValue>,
+    ) -> Result<(), Error> {
+        assert_eq!(paths.len(), values.len());
+
+        if paths.len() == 0 {
             return Ok(());
         }
 
         let (cur_block_hash, cur_block_id) = conn.get_cur_block_and_id();
 
-        let last = keys.len() - 1;
+        let last = paths.len() - 1;
         let mut progress = 0;
-        let eta_enabled = keys.len() > 10_000;
-        let mut result = keys[0..last]
+        let eta_enabled = paths.len() > 10_000;
+        let mut result = paths[0..last]
             .iter()
             .enumerate()
             .zip(values[0..last].iter())
-            .try_for_each(|((index, key), value)| {
+            .try_for_each(|((index, path), value)| {
                 let marf_leaf = TrieLeaf::from_value(&[], value.clone());
-                let path = TriePath::from_key(key);
 
                 if eta_enabled {
                     let updated_progress = 100 * index / last;
@@ -1274,14 +1444,13 @@ impl<T: MarfTrieId> MARF<T> {
                         );
                     }
                 }
-                MARF::insert_leaf_in_batch(conn, block_hash, &path, &marf_leaf)
+                MARF::insert_leaf_in_batch(conn, block_hash, path, &marf_leaf)
             });
 
         if result.is_ok() {
             // last insert updates the root with the skiplist hash
             let marf_leaf = TrieLeaf::from_value(&[], values[last].clone());
-            let path = TriePath::from_key(&keys[last]);
-            result = MARF::insert_leaf(conn, block_hash, &path, &marf_leaf);
+            result = MARF::insert_leaf(conn, block_hash, &paths[last], &marf_leaf);
         }
 
         // restore
@@ -1509,6 +1678,16 @@ impl<T: MarfTrieId> MARF<T> {
         self.storage.sqlite_tx()
     }
 
+    /// Is this MARF's storage read-only?
+    pub fn readonly(&self) -> bool {
+        self.storage.readonly()
+    }
+
+    /// Was this MARF opened to read and write unconfirmed tries?
+    pub fn unconfirmed(&self) -> bool {
+        self.storage.unconfirmed()
+    }
+
     /// Reopen storage read-only
     pub fn reopen_storage_readonly(&self) -> Result<TrieFileStorage<T>, Error> {
         self.storage.reopen_readonly()
@@ -1535,6 +1714,27 @@ impl<T: MarfTrieId> MARF<T> {
         })
     }
 
+    /// Pass the value of every leaf in `block_hash`'s trie to `visit` (see
+    /// `MARF::collect_leaf_values()`)
+    pub fn get_leaf_values<F: FnMut(MARFValue)>(
+        &mut self,
+        block_hash: &T,
+        follow_backptrs: bool,
+        visit: &mut F,
+    ) -> Result<(), Error> {
+        MARF::collect_leaf_values(
+            &mut self.storage.connection(),
+            block_hash,
+            follow_backptrs,
+            visit,
+        )
+    }
+
+    /// Get every leaf in the state as of `block_hash` (see `MARF::collect_leaves()`)
+    pub fn get_leaves(&mut self, block_hash: &T) -> Result<Vec<(TriePath, MARFValue)>, Error> {
+        MARF::collect_leaves(&mut self.storage.connection(), block_hash)
+    }
+
     /// Get the root trie hash at a particular block
     pub fn get_root_hash_at(&mut self, block_hash: &T) -> Result<TrieHash, Error> {
         self.storage.connection().get_root_hash_at(block_hash)
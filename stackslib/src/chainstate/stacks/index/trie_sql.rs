@@ -442,6 +442,17 @@ pub fn read_all_block_hashes_and_roots<T: MarfTrieId>(
     rows.collect()
 }
 
+/// Get the hashes of all tries, and whether each is unconfirmed
+pub fn read_all_block_hashes<T: MarfTrieId>(conn: &Connection) -> Result<Vec<(T, bool)>, Error> {
+    let mut s = conn.prepare("SELECT block_hash, unconfirmed FROM marf_data ORDER BY block_id")?;
+    let rows = s.query_and_then(NO_PARAMS, |row| {
+        let block_hash: T = row.get("block_hash")?;
+        let unconfirmed: bool = row.get("unconfirmed")?;
+        Ok((block_hash, unconfirmed))
+    })?;
+    rows.collect()
+}
+
 /// Read a node's hash from a sqlite-stored blob, given the block ID
 pub fn read_node_hash_bytes<W: Write>(
     conn: &Connection,
@@ -45,6 +45,7 @@ use crate::chainstate::burn::db::sortdb::{
 };
 use crate::chainstate::burn::operations::*;
 use crate::chainstate::burn::*;
+use crate::chainstate::nakamoto::ordering::TxOrderingRule;
 use crate::chainstate::stacks::address::StacksAddressExtensions;
 use crate::chainstate::stacks::db::blocks::{MemPoolRejection, SetupBlockResult};
 use crate::chainstate::stacks::db::transactions::{
@@ -195,6 +196,8 @@ pub struct BlockBuilderSettings {
     pub miner_status: Arc<Mutex<MinerStatus>>,
     /// Should the builder attempt to confirm any parent microblocks
     pub confirm_microblocks: bool,
+    /// The transaction-ordering rule that Nakamoto blocks commit to, if any
+    pub tx_ordering: Option<TxOrderingRule>,
 }
 
 impl BlockBuilderSettings {
@@ -206,6 +209,7 @@ impl BlockBuilderSettings {
             mempool_settings: MemPoolWalkSettings::default(),
             miner_status: Arc::new(Mutex::new(MinerStatus::make_ready(0))),
             confirm_microblocks: true,
+            tx_ordering: None,
         }
     }
 
@@ -217,6 +221,7 @@ impl BlockBuilderSettings {
             mempool_settings: MemPoolWalkSettings::zero(),
             miner_status: Arc::new(Mutex::new(MinerStatus::make_ready(0))),
             confirm_microblocks: true,
+            tx_ordering: None,
         }
     }
 }
@@ -2287,10 +2292,19 @@ impl StacksBlockBuilder {
             let mut loop_result = Ok(());
             while block_limit_hit != BlockLimitFunction::LIMIT_REACHED {
                 let mut num_considered = 0;
+                let mut walk_settings = mempool_settings.clone();
+                if walk_settings.skip_txs_over_tenure_budget {
+                    // the tenure's cost so far includes the blocks mined earlier in the tenure
+                    let mut tenure_budget = block_limit.clone();
+                    if tenure_budget.sub(&epoch_tx.cost_so_far()).is_err() {
+                        tenure_budget = ExecutionCost::zero();
+                    }
+                    walk_settings.tenure_budget = Some(tenure_budget);
+                }
                 let intermediate_result = mempool.iterate_candidates(
                     epoch_tx,
                     &mut tx_events,
-                    mempool_settings.clone(),
+                    walk_settings,
                     |epoch_tx, to_consider, estimator| {
                         // first, have we been preempted?
                         blocked = (*settings.miner_status.lock().expect("FATAL: mutex poisoned"))
@@ -68,6 +68,8 @@ pub mod db;
 pub mod events;
 pub mod index;
 pub mod miner;
+pub mod multisig;
+pub mod post_conditions;
 pub mod transaction;
 
 #[cfg(test)]
@@ -131,6 +133,8 @@ pub enum Error {
     /// This error indicates a Epoch2 block attempted to build off of a Nakamoto block.
     InvalidChildOfNakomotoBlock,
     NoRegisteredSigners(u64),
+    /// The microblock's data was moved to the microblock archive
+    MicroblockArchived(BlockHeaderHash),
 }
 
 impl From<marf_error> for Error {
@@ -232,6 +236,9 @@ impl fmt::Display for Error {
             Error::NotInSameFork => {
                 write!(f, "The supplied block identifiers are not in the same fork")
             }
+            Error::MicroblockArchived(ref hash) => {
+                write!(f, "Microblock {hash} has been archived")
+            }
         }
     }
 }
@@ -277,6 +284,7 @@ impl error::Error for Error {
             Error::ExpectedTenureChange => None,
             Error::NoRegisteredSigners(_) => None,
             Error::NotInSameFork => None,
+            Error::MicroblockArchived(_) => None,
         }
     }
 }
@@ -322,6 +330,7 @@ impl Error {
             Error::ExpectedTenureChange => "ExpectedTenureChange",
             Error::NoRegisteredSigners(_) => "NoRegisteredSigners",
             Error::NotInSameFork => "NotInSameFork",
+            Error::MicroblockArchived(_) => "MicroblockArchived",
         }
     }
 
@@ -0,0 +1,458 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Assembly of contract deployments authorized by an m-of-n multisig account, such as one
+//! controlled by a DAO.
+//!
+//! The deployment uses an order-independent P2SH spending condition, so each signer signs the
+//! same sighash and signatures can be collected in any order, from signers who never see each
+//! other's signatures.  Every signer builds the same `MultisigDeployment` (or receives its
+//! `unsigned_tx()`), calls `sign()`, and hands the signature to whoever assembles the
+//! transaction with `add_signature()` and `finish()`.  If the deployment is sponsored, the
+//! sponsor signs last with `finish_sponsored()`.
+//!
+//! Order-independent multisig transactions are only valid in epoch 3.0 and later.
+
+use std::collections::{BTreeMap, HashSet};
+
+use clarity::vm::types::QualifiedContractIdentifier;
+use clarity::vm::ClarityVersion;
+use stacks_common::types::chainstate::StacksAddress;
+use stacks_common::util::secp256k1::MessageSignature;
+
+use crate::burnchains::Txid;
+use crate::chainstate::stacks::{
+    StacksPrivateKey, StacksPublicKey, StacksTransaction, StacksTransactionSigner, TransactionAuth,
+    TransactionAuthFlags, TransactionPayload, TransactionPublicKeyEncoding,
+    TransactionSpendingCondition, TransactionVersion,
+};
+use crate::net::Error as net_error;
+
+/// A contract deployment that is being signed by an m-of-n multisig account
+#[derive(Debug, Clone, PartialEq)]
+pub struct MultisigDeployment {
+    /// The deployment, without any signatures or public keys
+    tx: StacksTransaction,
+    /// The multisig account's public keys, in the order that determines its address
+    public_keys: Vec<StacksPublicKey>,
+    /// Signatures collected so far, by the index of the signer's public key
+    signatures: BTreeMap<usize, MessageSignature>,
+}
+
+impl MultisigDeployment {
+    /// Start a deployment of `code_body` as `contract_name`, authorized by `signatures_required`
+    /// of `public_keys`.  The nonce and fee default to 0.
+    pub fn new(
+        mainnet: bool,
+        chain_id: u32,
+        signatures_required: u16,
+        public_keys: Vec<StacksPublicKey>,
+        contract_name: &str,
+        code_body: &str,
+        clarity_version: Option<ClarityVersion>,
+    ) -> Result<MultisigDeployment, net_error> {
+        if signatures_required == 0 || usize::from(signatures_required) > public_keys.len() {
+            return Err(net_error::SigningError(format!(
+                "Cannot require {signatures_required} signatures from {} public keys",
+                public_keys.len()
+            )));
+        }
+        let mut distinct = HashSet::new();
+        if !public_keys
+            .iter()
+            .all(|public_key| distinct.insert(public_key.to_bytes_compressed()))
+        {
+            return Err(net_error::SigningError(
+                "Duplicate public key in multisig account".to_string(),
+            ));
+        }
+
+        let origin = TransactionSpendingCondition::new_multisig_order_independent_p2sh(
+            signatures_required,
+            public_keys.clone(),
+        )
+        .ok_or_else(|| net_error::SigningError("Failed to derive multisig address".to_string()))?;
+        let payload =
+            TransactionPayload::new_smart_contract(contract_name, code_body, clarity_version)
+                .ok_or_else(|| {
+                    net_error::SigningError(format!("Invalid contract name: {contract_name}"))
+                })?;
+        let version = if mainnet {
+            TransactionVersion::Mainnet
+        } else {
+            TransactionVersion::Testnet
+        };
+
+        let mut tx = StacksTransaction::new(version, TransactionAuth::Standard(origin), payload);
+        tx.chain_id = chain_id;
+        Ok(MultisigDeployment {
+            tx,
+            public_keys,
+            signatures: BTreeMap::new(),
+        })
+    }
+
+    /// What each signature commits to: the sighash, and the multisig account's fee and nonce
+    fn signed_fields(&self) -> (Txid, u64, u64) {
+        let origin = self.tx.auth.origin();
+        (self.sighash(), origin.tx_fee(), origin.nonce())
+    }
+
+    /// Apply a change to the unsigned transaction.  Signatures collected so far are discarded
+    /// if it changes what the signers sign.
+    fn update<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut StacksTransaction),
+    {
+        let signed_fields = self.signed_fields();
+        f(&mut self.tx);
+        if self.signed_fields() != signed_fields {
+            self.signatures.clear();
+        }
+    }
+
+    /// Set the multisig account's nonce
+    pub fn set_nonce(&mut self, nonce: u64) {
+        self.update(|tx| tx.set_origin_nonce(nonce));
+    }
+
+    /// Set the fee.  If the deployment is sponsored, the sponsor pays it.
+    pub fn set_fee(&mut self, fee: u64) {
+        self.update(|tx| tx.set_tx_fee(fee));
+    }
+
+    /// Make the deployment sponsored (or not).  The fee is preserved.
+    pub fn set_sponsored(&mut self, sponsored: bool) {
+        if self.is_sponsored() == sponsored {
+            return;
+        }
+        self.update(|tx| {
+            let fee = tx.get_tx_fee();
+            let mut origin = tx.auth.origin().clone();
+            if sponsored {
+                origin.set_tx_fee(0);
+                tx.auth = TransactionAuth::Sponsored(
+                    origin,
+                    TransactionSpendingCondition::new_initial_sighash(),
+                );
+            } else {
+                tx.auth = TransactionAuth::Standard(origin);
+            }
+            tx.set_tx_fee(fee);
+        });
+    }
+
+    pub fn is_sponsored(&self) -> bool {
+        self.tx.auth.is_sponsored()
+    }
+
+    /// The deployment, without any signatures.  This is what each signer signs.
+    pub fn unsigned_tx(&self) -> &StacksTransaction {
+        &self.tx
+    }
+
+    /// The multisig account's address
+    pub fn address(&self) -> StacksAddress {
+        self.tx.origin_address()
+    }
+
+    /// The identifier that the contract will have once deployed
+    pub fn contract_id(&self) -> QualifiedContractIdentifier {
+        let contract_name = match &self.tx.payload {
+            TransactionPayload::SmartContract(contract, _) => contract.name.clone(),
+            _ => unreachable!("BUG: multisig deployment is not a smart contract"),
+        };
+        QualifiedContractIdentifier::new(self.address().into(), contract_name)
+    }
+
+    /// The sighash that every signer signs
+    pub fn sighash(&self) -> Txid {
+        let mut tx = self.tx.clone();
+        tx.auth = tx.auth.into_initial_sighash_auth();
+        tx.txid()
+    }
+
+    pub fn public_keys(&self) -> &[StacksPublicKey] {
+        &self.public_keys
+    }
+
+    pub fn signatures_required(&self) -> u16 {
+        self.tx.auth.origin().signatures_required()
+    }
+
+    pub fn signatures_collected(&self) -> usize {
+        self.signatures.len()
+    }
+
+    /// The public keys that have not signed yet
+    pub fn missing_signers(&self) -> Vec<StacksPublicKey> {
+        self.public_keys
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !self.signatures.contains_key(i))
+            .map(|(_, public_key)| public_key.clone())
+            .collect()
+    }
+
+    /// Have enough signatures been collected to finish the deployment?
+    pub fn is_complete(&self) -> bool {
+        self.signatures.len() >= usize::from(self.signatures_required())
+    }
+
+    fn signer_index(&self, public_key: &StacksPublicKey) -> Option<usize> {
+        let public_key = public_key.to_bytes_compressed();
+        self.public_keys
+            .iter()
+            .position(|signer| signer.to_bytes_compressed() == public_key)
+    }
+
+    /// Sign the deployment with one of the multisig account's private keys
+    pub fn sign(&self, privk: &StacksPrivateKey) -> Result<MessageSignature, net_error> {
+        if self
+            .signer_index(&StacksPublicKey::from_private(privk))
+            .is_none()
+        {
+            return Err(net_error::SigningError(
+                "Private key is not a signer of the multisig account".to_string(),
+            ));
+        }
+        let (sighash, tx_fee, nonce) = self.signed_fields();
+        let (signature, _) = TransactionSpendingCondition::next_signature(
+            &sighash,
+            &TransactionAuthFlags::AuthStandard,
+            tx_fee,
+            nonce,
+            privk,
+        )?;
+        Ok(signature)
+    }
+
+    /// Add a signer's signature.  Returns the index of the signer's public key.
+    pub fn add_signature(&mut self, signature: MessageSignature) -> Result<usize, net_error> {
+        let (sighash, tx_fee, nonce) = self.signed_fields();
+        let (public_key, _) = TransactionSpendingCondition::next_verification(
+            &sighash,
+            &TransactionAuthFlags::AuthStandard,
+            tx_fee,
+            nonce,
+            &TransactionPublicKeyEncoding::Compressed,
+            &signature,
+        )?;
+        let index = self.signer_index(&public_key).ok_or_else(|| {
+            net_error::VerifyingError(format!(
+                "Signature is not from a signer of the multisig account: {}",
+                public_key.to_hex()
+            ))
+        })?;
+        if self.signatures.contains_key(&index) {
+            return Err(net_error::VerifyingError(format!(
+                "Duplicate signature from {}",
+                public_key.to_hex()
+            )));
+        }
+        self.signatures.insert(index, signature);
+        Ok(index)
+    }
+
+    /// Produce the signed deployment.  If it is sponsored, it still needs to be signed by the
+    /// sponsor.  Only the required number of signatures are included, which keeps the
+    /// transaction small.
+    pub fn finish(&self) -> Result<StacksTransaction, net_error> {
+        if !self.is_complete() {
+            return Err(net_error::SigningError(format!(
+                "Not enough signatures. Got {}, expected {}",
+                self.signatures.len(),
+                self.signatures_required()
+            )));
+        }
+
+        let mut tx = self.tx.clone();
+        let (TransactionAuth::Standard(origin) | TransactionAuth::Sponsored(origin, _)) =
+            &mut tx.auth;
+        let TransactionSpendingCondition::OrderIndependentMultisig(origin) = origin else {
+            unreachable!("BUG: multisig deployment has a different spending condition");
+        };
+        let mut signatures_needed = usize::from(origin.signatures_required);
+        for (i, public_key) in self.public_keys.iter().enumerate() {
+            match self.signatures.get(&i) {
+                Some(signature) if signatures_needed > 0 => {
+                    let key_encoding = if public_key.compressed() {
+                        TransactionPublicKeyEncoding::Compressed
+                    } else {
+                        TransactionPublicKeyEncoding::Uncompressed
+                    };
+                    origin.push_signature(key_encoding, signature.clone());
+                    signatures_needed -= 1;
+                }
+                _ => origin.push_public_key(public_key.clone()),
+            }
+        }
+
+        tx.verify_origin()?;
+        Ok(tx)
+    }
+
+    /// Produce the signed deployment, sponsored by the (single-sig) `sponsor_privk` account
+    pub fn finish_sponsored(
+        &self,
+        sponsor_privk: &StacksPrivateKey,
+        sponsor_nonce: u64,
+    ) -> Result<StacksTransaction, net_error> {
+        if !self.is_sponsored() {
+            return Err(net_error::SigningError(
+                "Multisig deployment is not sponsored".to_string(),
+            ));
+        }
+        let tx = self.finish()?;
+
+        let mut sponsor = TransactionSpendingCondition::new_singlesig_p2pkh(
+            StacksPublicKey::from_private(sponsor_privk),
+        )
+        .ok_or_else(|| net_error::SigningError("Invalid sponsor key".to_string()))?;
+        sponsor.set_nonce(sponsor_nonce);
+        sponsor.set_tx_fee(tx.get_tx_fee());
+
+        let mut signer = StacksTransactionSigner::new_sponsor(&tx, sponsor)
+            .map_err(|e| net_error::SigningError(e.to_string()))?;
+        signer.sign_sponsor(sponsor_privk)?;
+        let tx = signer.get_tx().ok_or_else(|| {
+            net_error::SigningError("Sponsor signature is incomplete".to_string())
+        })?;
+        tx.verify()?;
+        Ok(tx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::CHAIN_ID_TESTNET;
+
+    const CODE: &str = "(define-public (hello) (ok true))";
+
+    fn signers() -> (Vec<StacksPrivateKey>, Vec<StacksPublicKey>) {
+        let privks: Vec<_> = (0..3).map(|_| StacksPrivateKey::new()).collect();
+        let pubks = privks.iter().map(StacksPublicKey::from_private).collect();
+        (privks, pubks)
+    }
+
+    #[test]
+    fn test_multisig_deployment() {
+        let (privks, pubks) = signers();
+        let outsider = StacksPrivateKey::new();
+
+        assert!(MultisigDeployment::new(
+            false,
+            CHAIN_ID_TESTNET,
+            4,
+            pubks.clone(),
+            "dao",
+            CODE,
+            None
+        )
+        .is_err());
+        assert!(MultisigDeployment::new(
+            false,
+            CHAIN_ID_TESTNET,
+            0,
+            pubks.clone(),
+            "dao",
+            CODE,
+            None
+        )
+        .is_err());
+        assert!(MultisigDeployment::new(
+            false,
+            CHAIN_ID_TESTNET,
+            2,
+            vec![pubks[0].clone(), pubks[0].clone()],
+            "dao",
+            CODE,
+            None
+        )
+        .is_err());
+
+        let mut deployment =
+            MultisigDeployment::new(false, CHAIN_ID_TESTNET, 2, pubks.clone(), "dao", CODE, None)
+                .unwrap();
+        deployment.set_nonce(3);
+        deployment.set_fee(1000);
+        assert_eq!(
+            deployment.contract_id(),
+            QualifiedContractIdentifier::new(deployment.address().into(), "dao".into())
+        );
+
+        // signatures can be collected in any order
+        let sig_2 = deployment.sign(&privks[2]).unwrap();
+        assert_eq!(deployment.add_signature(sig_2.clone()).unwrap(), 2);
+        assert!(deployment.add_signature(sig_2).is_err());
+        assert!(deployment.sign(&outsider).is_err());
+        assert!(deployment.finish().is_err());
+
+        // a signature over something else is rejected
+        let mut other = deployment.clone();
+        other.set_nonce(4);
+        assert_eq!(other.signatures_collected(), 0);
+        assert!(deployment
+            .add_signature(other.sign(&privks[0]).unwrap())
+            .is_err());
+
+        deployment
+            .add_signature(deployment.sign(&privks[0]).unwrap())
+            .unwrap();
+        assert!(deployment.is_complete());
+        assert_eq!(deployment.missing_signers(), vec![pubks[1].clone()]);
+
+        let tx = deployment.finish().unwrap();
+        tx.verify().unwrap();
+        assert_eq!(tx.origin_address(), deployment.address());
+        assert_eq!(tx.get_origin_nonce(), 3);
+        assert_eq!(tx.get_tx_fee(), 1000);
+        assert_eq!(tx.auth.origin().num_signatures(), 2);
+        assert!(deployment.finish_sponsored(&outsider, 0).is_err());
+    }
+
+    #[test]
+    fn test_sponsored_multisig_deployment() {
+        let (privks, pubks) = signers();
+        let sponsor = StacksPrivateKey::new();
+
+        let mut deployment =
+            MultisigDeployment::new(false, CHAIN_ID_TESTNET, 2, pubks, "dao", CODE, None).unwrap();
+        deployment.set_sponsored(true);
+        for privk in privks.iter() {
+            if !deployment.is_complete() {
+                deployment
+                    .add_signature(deployment.sign(privk).unwrap())
+                    .unwrap();
+            }
+        }
+
+        // the signers don't commit to the sponsor's fee
+        deployment.set_fee(500);
+        assert!(deployment.is_complete());
+
+        let tx = deployment.finish_sponsored(&sponsor, 7).unwrap();
+        tx.verify().unwrap();
+        assert_eq!(tx.origin_address(), deployment.address());
+        assert_eq!(tx.get_sponsor_nonce(), Some(7));
+        assert_eq!(tx.get_tx_fee(), 500);
+
+        // but they do commit to being sponsored
+        deployment.set_sponsored(false);
+        assert_eq!(deployment.signatures_collected(), 0);
+        assert_eq!(deployment.unsigned_tx().get_tx_fee(), 500);
+    }
+}
@@ -0,0 +1,453 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Evaluation of transaction post-conditions.
+//!
+//! A node checks a transaction's post-conditions against the assets that the transaction moved
+//! while it ran (its `AssetMap`).  Wallets and signers can run the same check before a
+//! transaction is broadcast, against the events of a simulated run: `check_post_conditions()`
+//! rebuilds the asset map from those events.  Only the events that the VM records as asset
+//! movements count: STX transfers and burns, and fungible and non-fungible token transfers and
+//! burns, each attributed to its sender.  Mints, locks and printed values are ignored.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use clarity::vm::contexts::{AssetMap, AssetMapEntry};
+use clarity::vm::errors::Error as InterpreterError;
+use clarity::vm::events::{FTEventType, NFTEventType, STXEventType, StacksTransactionEvent};
+use clarity::vm::types::{
+    AssetIdentifier, PrincipalData, QualifiedContractIdentifier, StandardPrincipalData, Value,
+};
+
+use crate::chainstate::stacks::{
+    AssetInfo, TransactionPostCondition, TransactionPostConditionMode,
+};
+
+/// This is a safe-to-hash Clarity value
+#[derive(PartialEq, Eq)]
+struct HashableClarityValue(Value);
+
+impl TryFrom<Value> for HashableClarityValue {
+    type Error = InterpreterError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        // check that serialization _will_ be successful when hashed
+        let _bytes = value.serialize_to_vec().map_err(|_| {
+            InterpreterError::Interpreter(clarity::vm::errors::InterpreterError::Expect(
+                "Failed to serialize asset in NFT during post-condition checks".into(),
+            ))
+        })?;
+        Ok(Self(value))
+    }
+}
+
+impl std::hash::Hash for HashableClarityValue {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        #[allow(clippy::unwrap_used)]
+        // this unwrap is safe _as long as_ TryFrom<Value> was used as a constructor
+        let bytes = self.0.serialize_to_vec().unwrap();
+        bytes.hash(state);
+    }
+}
+
+/// Why a transaction's post-conditions are not satisfied
+#[derive(Debug)]
+pub enum PostConditionError {
+    /// The post-condition at `index` in the transaction's list does not hold for what
+    /// `principal` sent of `asset`
+    NotMet {
+        index: usize,
+        principal: PrincipalData,
+        asset: AssetIdentifier,
+    },
+    /// In `Deny` mode, `principal` sent some of `asset` that no post-condition covers
+    Unchecked {
+        principal: PrincipalData,
+        asset: AssetIdentifier,
+    },
+    /// The asset movements could not be evaluated
+    Interpreter(InterpreterError),
+}
+
+impl fmt::Display for PostConditionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PostConditionError::NotMet {
+                index,
+                principal,
+                asset,
+            } => write!(
+                f,
+                "post-condition {} on {} sent by {} is not met",
+                index, asset, principal
+            ),
+            PostConditionError::Unchecked { principal, asset } => write!(
+                f,
+                "{} was sent by {} but no post-condition covers it",
+                asset, principal
+            ),
+            PostConditionError::Interpreter(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<InterpreterError> for PostConditionError {
+    fn from(e: InterpreterError) -> PostConditionError {
+        PostConditionError::Interpreter(e)
+    }
+}
+
+fn asset_identifier(asset_info: &AssetInfo) -> AssetIdentifier {
+    AssetIdentifier {
+        contract_identifier: QualifiedContractIdentifier::new(
+            StandardPrincipalData::from(asset_info.contract_address.clone()),
+            asset_info.contract_name.clone(),
+        ),
+        asset_name: asset_info.asset_name.clone(),
+    }
+}
+
+/// Rebuild the asset map of a transaction from the events it emitted
+pub fn asset_map_from_events(
+    events: &[StacksTransactionEvent],
+) -> Result<AssetMap, PostConditionError> {
+    let mut asset_map = AssetMap::new();
+    for event in events.iter() {
+        match event {
+            StacksTransactionEvent::STXEvent(STXEventType::STXTransferEvent(data)) => {
+                asset_map.add_stx_transfer(&data.sender, data.amount)?
+            }
+            StacksTransactionEvent::STXEvent(STXEventType::STXBurnEvent(data)) => {
+                asset_map.add_stx_burn(&data.sender, data.amount)?
+            }
+            StacksTransactionEvent::FTEvent(FTEventType::FTTransferEvent(data)) => asset_map
+                .add_token_transfer(&data.sender, data.asset_identifier.clone(), data.amount)?,
+            StacksTransactionEvent::FTEvent(FTEventType::FTBurnEvent(data)) => asset_map
+                .add_token_transfer(&data.sender, data.asset_identifier.clone(), data.amount)?,
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTTransferEvent(data)) => asset_map
+                .add_asset_transfer(
+                    &data.sender,
+                    data.asset_identifier.clone(),
+                    data.value.clone(),
+                ),
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTBurnEvent(data)) => asset_map
+                .add_asset_transfer(
+                    &data.sender,
+                    data.asset_identifier.clone(),
+                    data.value.clone(),
+                ),
+            _ => {}
+        }
+    }
+    Ok(asset_map)
+}
+
+/// Check that the assets a transaction sent, as recorded by its `events`, satisfy its
+/// post-conditions.  `origin` is the transaction's origin account, which
+/// `PostConditionPrincipal::Origin` refers to.
+pub fn check_post_conditions(
+    events: &[StacksTransactionEvent],
+    mode: &TransactionPostConditionMode,
+    conditions: &[TransactionPostCondition],
+    origin: &PrincipalData,
+) -> Result<(), PostConditionError> {
+    let asset_map = asset_map_from_events(events)?;
+    check_post_conditions_in_asset_map(&asset_map, mode, conditions, origin)
+}
+
+/// Check that the assets in `asset_map` satisfy a transaction's post-conditions
+pub fn check_post_conditions_in_asset_map(
+    asset_map: &AssetMap,
+    mode: &TransactionPostConditionMode,
+    conditions: &[TransactionPostCondition],
+    origin: &PrincipalData,
+) -> Result<(), PostConditionError> {
+    let mut checked_fungible_assets: HashMap<PrincipalData, HashSet<AssetIdentifier>> =
+        HashMap::new();
+    let mut checked_nonfungible_assets: HashMap<
+        PrincipalData,
+        HashMap<AssetIdentifier, HashSet<HashableClarityValue>>,
+    > = HashMap::new();
+
+    for (index, postcond) in conditions.iter().enumerate() {
+        match postcond {
+            TransactionPostCondition::STX(
+                ref principal,
+                ref condition_code,
+                ref amount_sent_condition,
+            ) => {
+                let account_principal = principal.to_principal_data(origin);
+
+                let amount_transferred = asset_map.get_stx(&account_principal).unwrap_or(0);
+                let amount_burned = asset_map.get_stx_burned(&account_principal).unwrap_or(0);
+
+                let amount_sent = amount_transferred
+                    .checked_add(amount_burned)
+                    .expect("FATAL: sent waaaaay too much STX");
+
+                if !condition_code.check(u128::from(*amount_sent_condition), amount_sent) {
+                    return Err(PostConditionError::NotMet {
+                        index,
+                        principal: account_principal,
+                        asset: AssetIdentifier::STX(),
+                    });
+                }
+
+                let asset_ids = checked_fungible_assets
+                    .entry(account_principal)
+                    .or_default();
+                if amount_transferred > 0 {
+                    asset_ids.insert(AssetIdentifier::STX());
+                }
+                if amount_burned > 0 {
+                    asset_ids.insert(AssetIdentifier::STX_burned());
+                }
+            }
+            TransactionPostCondition::Fungible(
+                ref principal,
+                ref asset_info,
+                ref condition_code,
+                ref amount_sent_condition,
+            ) => {
+                let account_principal = principal.to_principal_data(origin);
+                let asset_id = asset_identifier(asset_info);
+
+                let amount_sent = asset_map
+                    .get_fungible_tokens(&account_principal, &asset_id)
+                    .unwrap_or(0);
+                if !condition_code.check(u128::from(*amount_sent_condition), amount_sent) {
+                    return Err(PostConditionError::NotMet {
+                        index,
+                        principal: account_principal,
+                        asset: asset_id,
+                    });
+                }
+
+                checked_fungible_assets
+                    .entry(account_principal)
+                    .or_default()
+                    .insert(asset_id);
+            }
+            TransactionPostCondition::Nonfungible(
+                ref principal,
+                ref asset_info,
+                ref asset_value,
+                ref condition_code,
+            ) => {
+                let account_principal = principal.to_principal_data(origin);
+                let asset_id = asset_identifier(asset_info);
+
+                let empty_assets = vec![];
+                let assets_sent = asset_map
+                    .get_nonfungible_tokens(&account_principal, &asset_id)
+                    .unwrap_or(&empty_assets);
+                if !condition_code.check(asset_value, assets_sent) {
+                    return Err(PostConditionError::NotMet {
+                        index,
+                        principal: account_principal,
+                        asset: asset_id,
+                    });
+                }
+
+                checked_nonfungible_assets
+                    .entry(account_principal)
+                    .or_default()
+                    .entry(asset_id)
+                    .or_default()
+                    .insert(asset_value.clone().try_into()?);
+            }
+        }
+    }
+
+    if *mode == TransactionPostConditionMode::Allow {
+        return Ok(());
+    }
+
+    // make sure every asset transferred is covered by a postcondition
+    for (principal, assets) in asset_map.clone().to_table() {
+        for (asset_identifier, asset_entry) in assets {
+            let covered = match asset_entry {
+                AssetMapEntry::Asset(values) => {
+                    // this is a NFT: each value must be covered
+                    match checked_nonfungible_assets
+                        .get(&principal)
+                        .and_then(|checked| checked.get(&asset_identifier))
+                    {
+                        Some(nfts) => {
+                            let mut covered = true;
+                            for v in values {
+                                if !nfts.contains(&v.try_into()?) {
+                                    covered = false;
+                                    break;
+                                }
+                            }
+                            covered
+                        }
+                        None => false,
+                    }
+                }
+                // This is STX or a fungible token
+                _ => checked_fungible_assets
+                    .get(&principal)
+                    .map_or(false, |checked| checked.contains(&asset_identifier)),
+            };
+            if !covered {
+                return Err(PostConditionError::Unchecked {
+                    principal,
+                    asset: asset_identifier,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use clarity::vm::events::{FTTransferEventData, NFTTransferEventData, STXTransferEventData};
+    use clarity::vm::types::BuffData;
+    use stacks_common::address::C32_ADDRESS_VERSION_TESTNET_SINGLESIG;
+    use stacks_common::types::chainstate::StacksAddress;
+    use stacks_common::util::hash::Hash160;
+
+    use super::*;
+    use crate::chainstate::stacks::{
+        FungibleConditionCode, NonfungibleConditionCode, PostConditionPrincipal,
+    };
+
+    #[test]
+    fn test_check_post_conditions_from_events() {
+        let contract_address =
+            StacksAddress::new(C32_ADDRESS_VERSION_TESTNET_SINGLESIG, Hash160([1; 20]));
+        let origin = PrincipalData::from(StacksAddress::new(
+            C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+            Hash160([2; 20]),
+        ));
+        let recipient = PrincipalData::from(StacksAddress::new(
+            C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+            Hash160([3; 20]),
+        ));
+        let asset_info = |name: &str| AssetInfo {
+            contract_address: contract_address.clone(),
+            contract_name: "tokens".into(),
+            asset_name: name.into(),
+        };
+        let ft_id = asset_identifier(&asset_info("ft"));
+        let nft_id = asset_identifier(&asset_info("nft"));
+
+        let events = vec![
+            StacksTransactionEvent::STXEvent(STXEventType::STXTransferEvent(
+                STXTransferEventData {
+                    sender: origin.clone(),
+                    recipient: recipient.clone(),
+                    amount: 100,
+                    memo: BuffData::empty(),
+                },
+            )),
+            StacksTransactionEvent::FTEvent(FTEventType::FTTransferEvent(FTTransferEventData {
+                asset_identifier: ft_id.clone(),
+                sender: origin.clone(),
+                recipient: recipient.clone(),
+                amount: 10,
+            })),
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTTransferEvent(
+                NFTTransferEventData {
+                    asset_identifier: nft_id.clone(),
+                    sender: origin.clone(),
+                    recipient: recipient.clone(),
+                    value: Value::UInt(1),
+                },
+            )),
+        ];
+
+        let stx_condition = TransactionPostCondition::STX(
+            PostConditionPrincipal::Origin,
+            FungibleConditionCode::SentEq,
+            100,
+        );
+        let ft_condition = TransactionPostCondition::Fungible(
+            PostConditionPrincipal::Origin,
+            asset_info("ft"),
+            FungibleConditionCode::SentLe,
+            10,
+        );
+        let nft_condition = TransactionPostCondition::Nonfungible(
+            PostConditionPrincipal::Origin,
+            asset_info("nft"),
+            Value::UInt(1),
+            NonfungibleConditionCode::Sent,
+        );
+        let all_conditions = [
+            stx_condition.clone(),
+            ft_condition.clone(),
+            nft_condition.clone(),
+        ];
+
+        check_post_conditions(
+            &events,
+            &TransactionPostConditionMode::Deny,
+            &all_conditions,
+            &origin,
+        )
+        .unwrap();
+
+        // unchecked assets are only allowed in allow mode
+        check_post_conditions(
+            &events,
+            &TransactionPostConditionMode::Allow,
+            &[stx_condition.clone()],
+            &origin,
+        )
+        .unwrap();
+        match check_post_conditions(
+            &events,
+            &TransactionPostConditionMode::Deny,
+            &[stx_condition.clone(), nft_condition.clone()],
+            &origin,
+        ) {
+            Err(PostConditionError::Unchecked { principal, asset }) => {
+                assert_eq!(principal, origin);
+                assert_eq!(asset, ft_id);
+            }
+            result => panic!("Expected an unchecked asset, got {:?}", result),
+        }
+
+        // the failing condition is reported by its index
+        let too_little = TransactionPostCondition::Fungible(
+            PostConditionPrincipal::Origin,
+            asset_info("ft"),
+            FungibleConditionCode::SentLt,
+            10,
+        );
+        match check_post_conditions(
+            &events,
+            &TransactionPostConditionMode::Allow,
+            &[stx_condition, too_little],
+            &origin,
+        ) {
+            Err(PostConditionError::NotMet {
+                index,
+                principal,
+                asset,
+            }) => {
+                assert_eq!(index, 1);
+                assert_eq!(principal, origin);
+                assert_eq!(asset, ft_id);
+            }
+            result => panic!("Expected an unmet post-condition, got {:?}", result),
+        }
+    }
+}
@@ -1447,6 +1447,7 @@ pub fn instantiate_and_exec(
         get_bulk_initial_balances: None,
         get_bulk_initial_names: None,
         get_bulk_initial_namespaces: None,
+        boot_contract_provider: None,
     };
 
     StacksChainState::open_and_exec(mainnet, chain_id, &path, Some(&mut boot_data), None)
@@ -0,0 +1,676 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Calibration of the Clarity cost functions against the wall-clock time of the natives they
+//! price, for use by the `clarity-bench` binary whenever the cost functions are re-tuned.
+//!
+//! Each native is applied to inputs of several sizes, in an in-memory chainstate with the boot
+//! contracts (and so the boot cost functions) installed.  A measurement evaluates the whole
+//! expression, including looking up its arguments, and compares the time that takes to the
+//! runtime cost assessed for evaluating it.  Time per unit of runtime cost should be about the
+//! same for every native and every input size: a native that takes much longer per unit than
+//! the median is underpriced, and one whose time per unit grows (or shrinks) with its input is
+//! priced with the wrong slope.
+
+use std::cmp::Ordering;
+use std::time::{Duration, Instant};
+
+use clarity::vm::ast::ASTRules;
+use clarity::vm::contexts::{LocalContext, OwnedEnvironment};
+use clarity::vm::costs::cost_functions::ClarityCostFunction;
+use clarity::vm::costs::{ExecutionCost, LimitedCostTracker};
+use clarity::vm::errors::Error;
+use clarity::vm::types::{PrincipalData, QualifiedContractIdentifier};
+use clarity::vm::{eval, ClarityVersion, ContractContext, SymbolicExpression};
+use serde::Serialize;
+
+use crate::clarity_cli::{
+    consume_arg, default_chain_id, install_boot_code, parse, CLIHeadersDB, DEFAULT_CLI_EPOCH,
+};
+use crate::clarity_vm::database::MemoryBackingStore;
+
+/// Number of evaluations timed per input size, unless given
+pub const DEFAULT_BENCH_ITERATIONS: u32 = 200;
+/// How far (as a ratio) a native's time per unit of cost may be from the median before it is
+///  reported as mispriced, unless given
+pub const DEFAULT_BENCH_THRESHOLD: f64 = 2.0;
+
+const SIZES: &[u64] = &[1, 16, 256, 2048];
+const FIXED_SIZE: &[u64] = &[1];
+
+/// A native to measure, at each of `sizes`
+struct NativeBench {
+    native: &'static str,
+    cost_function: ClarityCostFunction,
+    sizes: &'static [u64],
+    /// Definitions of the inputs of the given size
+    setup: fn(u64) -> String,
+    /// The expression to measure, applying the native to the inputs of the given size
+    expr: fn(u64) -> String,
+}
+
+fn buff(size: u64) -> String {
+    format!("0x{}", "ab".repeat(size as usize))
+}
+
+fn list_of(item: &str, size: u64) -> String {
+    format!("(list{})", format!(" {item}").repeat(size as usize))
+}
+
+/// `(function x x ...)` with `size` arguments
+fn apply_to_x(function: &str, size: u64) -> String {
+    format!("({function}{})", " x".repeat(size as usize))
+}
+
+fn buff_setup(size: u64) -> String {
+    format!("(define-constant b {})", buff(size))
+}
+
+fn int_list_setup(size: u64) -> String {
+    format!("(define-constant l {})", list_of("1", size))
+}
+
+const NATIVE_BENCHES: &[NativeBench] = &[
+    NativeBench {
+        native: "+",
+        cost_function: ClarityCostFunction::Add,
+        sizes: SIZES,
+        setup: |_| "(define-constant x 1)".into(),
+        expr: |size| apply_to_x("+", size),
+    },
+    NativeBench {
+        native: "-",
+        cost_function: ClarityCostFunction::Sub,
+        sizes: SIZES,
+        setup: |_| "(define-constant x 1)".into(),
+        expr: |size| apply_to_x("-", size),
+    },
+    NativeBench {
+        native: "*",
+        cost_function: ClarityCostFunction::Mul,
+        sizes: SIZES,
+        setup: |_| "(define-constant x 1)".into(),
+        expr: |size| apply_to_x("*", size),
+    },
+    NativeBench {
+        native: "is-eq",
+        cost_function: ClarityCostFunction::Eq,
+        sizes: SIZES,
+        setup: |_| "(define-constant x 0x0102030405060708)".into(),
+        expr: |size| apply_to_x("is-eq", size),
+    },
+    NativeBench {
+        native: "sha256",
+        cost_function: ClarityCostFunction::Sha256,
+        sizes: SIZES,
+        setup: buff_setup,
+        expr: |_| "(sha256 b)".into(),
+    },
+    NativeBench {
+        native: "sha512",
+        cost_function: ClarityCostFunction::Sha512,
+        sizes: SIZES,
+        setup: buff_setup,
+        expr: |_| "(sha512 b)".into(),
+    },
+    NativeBench {
+        native: "keccak256",
+        cost_function: ClarityCostFunction::Keccak256,
+        sizes: SIZES,
+        setup: buff_setup,
+        expr: |_| "(keccak256 b)".into(),
+    },
+    NativeBench {
+        native: "hash160",
+        cost_function: ClarityCostFunction::Hash160,
+        sizes: SIZES,
+        setup: buff_setup,
+        expr: |_| "(hash160 b)".into(),
+    },
+    NativeBench {
+        native: "concat",
+        cost_function: ClarityCostFunction::Concat,
+        sizes: SIZES,
+        setup: buff_setup,
+        expr: |_| "(concat b b)".into(),
+    },
+    NativeBench {
+        native: "to-consensus-buff?",
+        cost_function: ClarityCostFunction::ToConsensusBuff,
+        sizes: SIZES,
+        setup: buff_setup,
+        expr: |_| "(to-consensus-buff? b)".into(),
+    },
+    NativeBench {
+        native: "len",
+        cost_function: ClarityCostFunction::Len,
+        sizes: SIZES,
+        setup: int_list_setup,
+        expr: |_| "(len l)".into(),
+    },
+    NativeBench {
+        native: "element-at?",
+        cost_function: ClarityCostFunction::ElementAt,
+        sizes: SIZES,
+        setup: int_list_setup,
+        expr: |_| "(element-at? l u0)".into(),
+    },
+    NativeBench {
+        native: "index-of?",
+        cost_function: ClarityCostFunction::IndexOf,
+        sizes: SIZES,
+        setup: int_list_setup,
+        // not found, so every item is compared
+        expr: |_| "(index-of? l 0)".into(),
+    },
+    NativeBench {
+        native: "append",
+        cost_function: ClarityCostFunction::Append,
+        sizes: SIZES,
+        setup: int_list_setup,
+        expr: |_| "(append l 1)".into(),
+    },
+    NativeBench {
+        native: "as-max-len?",
+        cost_function: ClarityCostFunction::AsMaxLen,
+        sizes: SIZES,
+        setup: int_list_setup,
+        expr: |_| "(as-max-len? l u2048)".into(),
+    },
+    NativeBench {
+        native: "map",
+        cost_function: ClarityCostFunction::Map,
+        sizes: SIZES,
+        setup: int_list_setup,
+        expr: |_| "(map + l l)".into(),
+    },
+    NativeBench {
+        native: "fold",
+        cost_function: ClarityCostFunction::Fold,
+        sizes: SIZES,
+        setup: int_list_setup,
+        expr: |_| "(fold + l 0)".into(),
+    },
+    NativeBench {
+        native: "filter",
+        cost_function: ClarityCostFunction::Filter,
+        sizes: SIZES,
+        setup: |size| format!("(define-constant l {})", list_of("true", size)),
+        expr: |_| "(filter not l)".into(),
+    },
+    NativeBench {
+        native: "var-get",
+        cost_function: ClarityCostFunction::FetchVar,
+        sizes: SIZES,
+        setup: |size| format!("(define-data-var v (buff 2048) {})", buff(size)),
+        expr: |_| "(var-get v)".into(),
+    },
+    NativeBench {
+        native: "var-set",
+        cost_function: ClarityCostFunction::SetVar,
+        sizes: SIZES,
+        setup: |size| format!("(define-data-var v (buff 2048) 0x) {}", buff_setup(size)),
+        expr: |_| "(var-set v b)".into(),
+    },
+    NativeBench {
+        native: "map-get?",
+        cost_function: ClarityCostFunction::FetchEntry,
+        sizes: SIZES,
+        setup: |size| {
+            format!(
+                "(define-map m int (buff 2048)) (map-insert m 1 {})",
+                buff(size)
+            )
+        },
+        expr: |_| "(map-get? m 1)".into(),
+    },
+    NativeBench {
+        native: "map-set",
+        cost_function: ClarityCostFunction::SetEntry,
+        sizes: SIZES,
+        setup: |size| format!("(define-map m int (buff 2048)) {}", buff_setup(size)),
+        expr: |_| "(map-set m 1 b)".into(),
+    },
+    NativeBench {
+        native: "secp256k1-recover?",
+        cost_function: ClarityCostFunction::Secp256k1recover,
+        sizes: FIXED_SIZE,
+        setup: |_| {
+            format!(
+                "(define-constant h {}) (define-constant sig {})",
+                buff(32),
+                buff(65)
+            )
+        },
+        expr: |_| "(secp256k1-recover? h sig)".into(),
+    },
+    NativeBench {
+        native: "secp256k1-verify",
+        cost_function: ClarityCostFunction::Secp256k1verify,
+        sizes: FIXED_SIZE,
+        setup: |_| {
+            format!(
+                "(define-constant h {}) (define-constant sig {}) (define-constant key {})",
+                buff(32),
+                buff(65),
+                buff(33)
+            )
+        },
+        expr: |_| "(secp256k1-verify h sig key)".into(),
+    },
+];
+
+/// What to measure
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalibrationConfig {
+    /// Evaluations timed per input size
+    pub iterations: u32,
+    /// The natives to measure (all of them if empty)
+    pub natives: Vec<String>,
+    pub threshold: f64,
+    pub mainnet: bool,
+}
+
+impl Default for CalibrationConfig {
+    fn default() -> Self {
+        CalibrationConfig {
+            iterations: DEFAULT_BENCH_ITERATIONS,
+            natives: vec![],
+            threshold: DEFAULT_BENCH_THRESHOLD,
+            mainnet: true,
+        }
+    }
+}
+
+/// One native applied to inputs of one size
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NativeMeasurement {
+    pub size: u64,
+    /// The runtime cost assessed for one evaluation
+    pub runtime: u64,
+    /// The wall-clock time of one evaluation
+    pub nanos: u64,
+    pub nanos_per_runtime: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CalibrationVerdict {
+    Calibrated,
+    /// Takes longer per unit of cost than the natives overall, or more so as its input grows
+    Underpriced,
+    /// Takes less time per unit of cost than the natives overall, or less so as its input grows
+    Overpriced,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NativeCalibration {
+    pub native: String,
+    pub cost_function: String,
+    pub measurements: Vec<NativeMeasurement>,
+    /// Time per unit of cost over all of the native's measurements
+    pub nanos_per_runtime: f64,
+    /// `nanos_per_runtime` relative to the median over all natives
+    pub relative_to_median: f64,
+    /// Time per unit of cost at the largest input size relative to that at the smallest
+    pub scaling: f64,
+    pub verdict: CalibrationVerdict,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CalibrationReport {
+    pub epoch: String,
+    pub iterations: u32,
+    pub threshold: f64,
+    /// The median time per unit of cost over all natives, which the others are compared to
+    pub median_nanos_per_runtime: f64,
+    pub natives: Vec<NativeCalibration>,
+}
+
+fn ratio(nanos: u64, runtime: u64) -> f64 {
+    nanos as f64 / runtime.max(1) as f64
+}
+
+fn median(values: &[f64]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    match sorted.len() {
+        0 => 0.0,
+        len if len % 2 == 1 => sorted[len / 2],
+        len => (sorted[len / 2 - 1] + sorted[len / 2]) / 2.0,
+    }
+}
+
+fn load_contract_context(
+    vm_env: &mut OwnedEnvironment,
+    contract_id: &QualifiedContractIdentifier,
+) -> Result<ContractContext, String> {
+    vm_env
+        .execute_in_env(contract_id.issuer.clone().into(), None, None, |env| {
+            env.global_context.database.get_contract(contract_id)
+        })
+        .map(|(contract, ..)| contract.contract_context)
+        .map_err(|e| format!("Failed to load {contract_id}: {e}"))
+}
+
+/// The runtime cost assessed for evaluating `expr` in `contract_id` once, and the time it takes
+///  to evaluate it `iterations` times
+fn measure(
+    store: &mut MemoryBackingStore,
+    mainnet: bool,
+    contract_id: &QualifiedContractIdentifier,
+    expr: &SymbolicExpression,
+    iterations: u32,
+) -> Result<(u64, Duration), String> {
+    let chain_id = default_chain_id(mainnet);
+    let sender: PrincipalData = contract_id.issuer.clone().into();
+
+    let runtime = {
+        let mut db = store.as_clarity_db();
+        let cost_track = LimitedCostTracker::new(
+            mainnet,
+            chain_id,
+            ExecutionCost::max_value(),
+            &mut db,
+            DEFAULT_CLI_EPOCH,
+        )
+        .map_err(|e| format!("Failed to load the cost functions: {e:?}"))?;
+        let mut vm_env = OwnedEnvironment::new_cost_limited(
+            mainnet,
+            chain_id,
+            db,
+            cost_track,
+            DEFAULT_CLI_EPOCH,
+        );
+        let context = load_contract_context(&mut vm_env, contract_id)?;
+        let before = vm_env.get_cost_total();
+        vm_env
+            .execute_in_env(sender.clone(), None, Some(context), |env| {
+                eval(expr, env, &LocalContext::new())
+            })
+            .map_err(|e| format!("Failed to evaluate {expr}: {e}"))?;
+        vm_env.get_cost_total().runtime - before.runtime
+    };
+
+    let mut vm_env =
+        OwnedEnvironment::new_free(mainnet, chain_id, store.as_clarity_db(), DEFAULT_CLI_EPOCH);
+    let context = load_contract_context(&mut vm_env, contract_id)?;
+    let (elapsed, ..) = vm_env
+        .execute_in_env(sender, None, Some(context), |env| {
+            // warm up
+            eval(expr, env, &LocalContext::new())?;
+            let start = Instant::now();
+            for _ in 0..iterations {
+                eval(expr, env, &LocalContext::new())?;
+            }
+            Ok::<_, Error>(start.elapsed())
+        })
+        .map_err(|e| format!("Failed to evaluate {expr}: {e}"))?;
+    Ok((runtime, elapsed))
+}
+
+/// Measure the natives in `config`, and compare their time per unit of cost
+pub fn run_calibration(config: &CalibrationConfig) -> Result<CalibrationReport, String> {
+    let benches: Vec<_> = NATIVE_BENCHES
+        .iter()
+        .filter(|bench| {
+            config.natives.is_empty() || config.natives.iter().any(|n| n == bench.native)
+        })
+        .collect();
+    if let Some(unknown) = config.natives.iter().find(|n| {
+        !NATIVE_BENCHES
+            .iter()
+            .any(|bench| bench.native == n.as_str())
+    }) {
+        return Err(format!("No benchmark for native '{unknown}'"));
+    }
+
+    let header_db = CLIHeadersDB::new_memory(config.mainnet);
+    let mut store = MemoryBackingStore::new();
+    install_boot_code(&header_db, &mut store);
+    let clarity_version = ClarityVersion::default_for_epoch(DEFAULT_CLI_EPOCH);
+    let iterations = config.iterations.max(1);
+
+    let mut natives = vec![];
+    for (index, bench) in benches.iter().enumerate() {
+        let mut measurements = vec![];
+        for size in bench.sizes.iter() {
+            let contract_id = QualifiedContractIdentifier::local(&format!("bench-{index}-{size}"))
+                .map_err(|e| e.to_string())?;
+            OwnedEnvironment::new_free(
+                config.mainnet,
+                default_chain_id(config.mainnet),
+                store.as_clarity_db(),
+                DEFAULT_CLI_EPOCH,
+            )
+            .initialize_versioned_contract(
+                contract_id.clone(),
+                clarity_version,
+                &(bench.setup)(*size),
+                None,
+                ASTRules::PrecheckSize,
+            )
+            .map_err(|e| format!("Failed to set up '{}' at size {size}: {e}", bench.native))?;
+            let expr_source = (bench.expr)(*size);
+            let expr = parse(&contract_id, &expr_source, clarity_version)
+                .map_err(|e| format!("Failed to parse {expr_source}: {e}"))?
+                .into_iter()
+                .next()
+                .ok_or_else(|| format!("Empty benchmark for '{}'", bench.native))?;
+
+            let (runtime, elapsed) =
+                measure(&mut store, config.mainnet, &contract_id, &expr, iterations)?;
+            let nanos =
+                u64::try_from(elapsed.as_nanos() / u128::from(iterations)).unwrap_or(u64::MAX);
+            debug!("Measured native";
+                   "native" => bench.native,
+                   "size" => size,
+                   "runtime" => runtime,
+                   "nanos" => nanos);
+            measurements.push(NativeMeasurement {
+                size: *size,
+                runtime,
+                nanos,
+                nanos_per_runtime: ratio(nanos, runtime),
+            });
+        }
+        let total_nanos = measurements.iter().map(|m| m.nanos).sum();
+        let total_runtime = measurements.iter().map(|m| m.runtime).sum();
+        let scaling = match (measurements.first(), measurements.last()) {
+            (Some(first), Some(last)) if first.nanos_per_runtime > 0.0 => {
+                last.nanos_per_runtime / first.nanos_per_runtime
+            }
+            _ => 1.0,
+        };
+        natives.push(NativeCalibration {
+            native: bench.native.to_string(),
+            cost_function: bench.cost_function.get_name(),
+            measurements,
+            nanos_per_runtime: ratio(total_nanos, total_runtime),
+            relative_to_median: 1.0,
+            scaling,
+            verdict: CalibrationVerdict::Calibrated,
+        });
+    }
+
+    let median_nanos_per_runtime = median(
+        &natives
+            .iter()
+            .map(|native| native.nanos_per_runtime)
+            .collect::<Vec<_>>(),
+    );
+    let threshold = config.threshold.max(1.0);
+    for native in natives.iter_mut() {
+        native.relative_to_median = if median_nanos_per_runtime > 0.0 {
+            native.nanos_per_runtime / median_nanos_per_runtime
+        } else {
+            1.0
+        };
+        native.verdict = if native.relative_to_median > threshold || native.scaling > threshold {
+            CalibrationVerdict::Underpriced
+        } else if native.relative_to_median < 1.0 / threshold || native.scaling < 1.0 / threshold {
+            CalibrationVerdict::Overpriced
+        } else {
+            CalibrationVerdict::Calibrated
+        };
+    }
+
+    Ok(CalibrationReport {
+        epoch: DEFAULT_CLI_EPOCH.to_string(),
+        iterations,
+        threshold,
+        median_nanos_per_runtime,
+        natives,
+    })
+}
+
+fn print_usage(invoked_by: &str) {
+    eprintln!(
+        "Usage: {invoked_by} [--iterations N] [--native NAME]... [--threshold RATIO] [--testnet]
+
+Measures the wall-clock time of the Clarity natives on inputs of several sizes, compares it to
+the runtime cost assessed by the boot cost functions, and prints a calibration report.
+
+  --iterations N     evaluations timed per input size (default: {DEFAULT_BENCH_ITERATIONS})
+  --native NAME      only measure this native (can be given more than once)
+  --threshold RATIO  how far a native's time per unit of cost may be from the median before it
+                     is reported as mispriced (default: {DEFAULT_BENCH_THRESHOLD})
+  --testnet          use the testnet boot contracts
+"
+    );
+}
+
+pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_json::Value>) {
+    let mut argv = args.to_vec();
+    if consume_arg(&mut argv, &["-h", "--help"], false)
+        .ok()
+        .flatten()
+        .is_some()
+    {
+        print_usage(invoked_by);
+        return (0, None);
+    }
+
+    let mut config = CalibrationConfig::default();
+    match consume_arg(&mut argv, &["--iterations"], true) {
+        Ok(Some(iterations)) => match iterations.parse() {
+            Ok(iterations) => config.iterations = iterations,
+            Err(_) => {
+                eprintln!("Invalid --iterations: {iterations}");
+                return (1, None);
+            }
+        },
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("{e}");
+            print_usage(invoked_by);
+            return (1, None);
+        }
+    }
+    match consume_arg(&mut argv, &["--threshold"], true) {
+        Ok(Some(threshold)) => match threshold.parse() {
+            Ok(threshold) => config.threshold = threshold,
+            Err(_) => {
+                eprintln!("Invalid --threshold: {threshold}");
+                return (1, None);
+            }
+        },
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("{e}");
+            print_usage(invoked_by);
+            return (1, None);
+        }
+    }
+    loop {
+        match consume_arg(&mut argv, &["--native"], true) {
+            Ok(Some(native)) => config.natives.push(native),
+            Ok(None) => break,
+            Err(e) => {
+                eprintln!("{e}");
+                print_usage(invoked_by);
+                return (1, None);
+            }
+        }
+    }
+    if let Ok(Some(_)) = consume_arg(&mut argv, &["--testnet"], false) {
+        config.mainnet = false;
+    }
+    if !argv.is_empty() {
+        eprintln!("Unexpected arguments: {}", argv.join(" "));
+        print_usage(invoked_by);
+        return (1, None);
+    }
+
+    match run_calibration(&config) {
+        Ok(report) => (0, Some(serde_json::to_value(report).unwrap())),
+        Err(e) => {
+            eprintln!("{e}");
+            (1, None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calibration_report() {
+        let config = CalibrationConfig {
+            iterations: 2,
+            natives: vec!["+".into(), "sha256".into(), "secp256k1-verify".into()],
+            ..CalibrationConfig::default()
+        };
+        let report = run_calibration(&config).unwrap();
+        assert_eq!(report.iterations, 2);
+        let natives: Vec<_> = report
+            .natives
+            .iter()
+            .map(|native| (native.native.as_str(), native.cost_function.as_str()))
+            .collect();
+        assert_eq!(
+            natives,
+            vec![
+                ("+", "cost_add"),
+                ("sha256", "cost_sha256"),
+                ("secp256k1-verify", "cost_secp256k1verify")
+            ]
+        );
+        assert_eq!(report.natives[0].measurements.len(), SIZES.len());
+        assert_eq!(report.natives[2].measurements.len(), 1);
+        for native in report.natives.iter() {
+            for measurement in native.measurements.iter() {
+                assert!(measurement.runtime > 0);
+            }
+        }
+        // more arguments cost more
+        let add = &report.natives[0].measurements;
+        assert!(add.last().unwrap().runtime > add.first().unwrap().runtime);
+
+        let config = CalibrationConfig {
+            natives: vec!["no-such-native".into()],
+            ..CalibrationConfig::default()
+        };
+        assert!(run_calibration(&config).is_err());
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(&[]), 0.0);
+        assert_eq!(median(&[3.0, 1.0, 2.0]), 2.0);
+        assert_eq!(median(&[4.0, 1.0, 2.0, 3.0]), 2.5);
+    }
+}
@@ -15,16 +15,24 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::ffi::OsStr;
-use std::io::{Read, Write};
+use std::io::Read;
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::{env, fs, io, process};
 
 use clarity::vm::coverage::CoverageReporter;
+use clarity::vm::tracer::ExecutionTracer;
 use lazy_static::lazy_static;
 use rand::Rng;
 use rusqlite::types::ToSql;
 use rusqlite::{Connection, OpenFlags, Row, Transaction};
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
 use serde::Serialize;
 use serde_json::json;
 use stacks_common::address::c32::c32_address;
@@ -48,18 +56,23 @@ use crate::chainstate::stacks::index::storage::TrieFileStorage;
 use crate::chainstate::stacks::index::{ClarityMarfTrieId, MarfTrieId};
 use crate::clarity::vm::analysis::contract_interface_builder::build_contract_interface;
 use crate::clarity::vm::analysis::errors::{CheckError, CheckResult};
+use crate::clarity::vm::analysis::linter::{Lint, LintConfig, LintLevel, Linter};
 use crate::clarity::vm::analysis::{AnalysisDatabase, ContractAnalysis};
 use crate::clarity::vm::ast::{build_ast_with_rules, ASTRules};
-use crate::clarity::vm::contexts::{AssetMap, GlobalContext, OwnedEnvironment};
+use crate::clarity::vm::contexts::{
+    AssetMap, CallStack, Environment, GlobalContext, LocalContext, OwnedEnvironment,
+};
 use crate::clarity::vm::costs::{ExecutionCost, LimitedCostTracker};
 use crate::clarity::vm::database::{
     BurnStateDB, ClarityDatabase, HeadersDB, STXBalance, SqliteConnection, NULL_BURN_STATE_DB,
 };
 use crate::clarity::vm::errors::{Error, InterpreterResult, RuntimeErrorType};
+use crate::clarity::vm::functions::define::DefineFunctionsParsed;
+use crate::clarity::vm::functions::NativeFunctions;
 use crate::clarity::vm::types::{OptionalData, PrincipalData, QualifiedContractIdentifier};
 use crate::clarity::vm::{
-    analysis, ast, eval_all, ClarityVersion, ContractContext, ContractName, SymbolicExpression,
-    SymbolicExpressionType, Value,
+    analysis, ast, eval, eval_all, ClarityVersion, ContractContext, ContractName,
+    SymbolicExpression, SymbolicExpressionType, Value,
 };
 use crate::clarity_vm::database::marf::{MarfedKV, WritableMarfStore};
 use crate::clarity_vm::database::MemoryBackingStore;
@@ -113,15 +126,18 @@ where command is one of:
 
   initialize         to initialize a local VM state database.
   check              to typecheck a potential contract definition.
+  lint               to typecheck a potential contract definition and run the lints on it.
   launch             to launch a initialize a new contract in the local state database.
   eval               to evaluate (in read-only mode) a program in a given contract context.
   eval_at_chaintip   like `eval`, but does not advance to a new block.
   eval_at_block      like `eval_at_chaintip`, but accepts a index-block-hash to evaluate at,
                      must be passed eval string via stdin.
   eval_raw           to typecheck and evaluate an expression without a contract or database context.
-  repl               to typecheck and evaluate expressions in a stdin/stdout loop.
+  repl               to typecheck and evaluate expressions and definitions in an interactive session.
   execute            to execute a public function of a defined contract.
   generate_address   to generate a random Stacks public address for testing purposes.
+  make_lcov          to write an lcov report of the coverage recorded with `--c`.
+  make_coverage_html to write an HTML report of the coverage recorded with `--c`.
 ",
         invoked_by
     );
@@ -150,7 +166,7 @@ struct EvalInput {
     content: String,
 }
 
-fn parse(
+pub(crate) fn parse(
     contract_identifier: &QualifiedContractIdentifier,
     source_code: &str,
     clarity_version: ClarityVersion,
@@ -167,7 +183,7 @@ fn parse(
     Ok(ast.expressions)
 }
 
-trait ClarityStorage {
+pub(crate) trait ClarityStorage {
     fn get_clarity_db<'a>(
         &'a mut self,
         headers_db: &'a dyn HeadersDB,
@@ -401,7 +417,7 @@ where
     result
 }
 
-fn default_chain_id(mainnet: bool) -> u32 {
+pub(crate) fn default_chain_id(mainnet: bool) -> u32 {
     let chain_id = if mainnet {
         CHAIN_ID_MAINNET
     } else {
@@ -415,6 +431,7 @@ fn with_env_costs<F, R>(
     header_db: &CLIHeadersDB,
     marf: &mut WritableMarfStore,
     coverage: Option<&mut CoverageReporter>,
+    tracer: Option<&mut ExecutionTracer>,
     f: F,
 ) -> (R, ExecutionCost)
 where
@@ -443,6 +460,9 @@ where
     if let Some(coverage) = coverage {
         vm_env.add_eval_hook(coverage);
     }
+    if let Some(tracer) = tracer {
+        vm_env.add_eval_hook(tracer);
+    }
     let result = f(&mut vm_env);
     let cost = vm_env.get_cost_total();
     (result, cost)
@@ -476,6 +496,16 @@ pub fn vm_execute(program: &str, clarity_version: ClarityVersion) -> Result<Opti
     })
 }
 
+/// Write the trace of an execution to `trace_file`, one JSON object per line
+fn save_trace(trace_file: Option<String>, tracer: Option<ExecutionTracer>) {
+    if let (Some(trace_file), Some(tracer)) = (trace_file, tracer) {
+        let mut file = fs::File::create(&trace_file).expect("Failed to create trace file");
+        tracer
+            .write_json_lines(&mut file)
+            .expect("Failed to write trace file");
+    }
+}
+
 fn save_coverage(
     coverage_folder: Option<String>,
     coverage: Option<CoverageReporter>,
@@ -497,7 +527,246 @@ fn save_coverage(
     }
 }
 
-struct CLIHeadersDB {
+const REPL_HELP: &str = "Enter Clarity expressions and definitions to evaluate them, or one of:
+
+  ::set_tx_sender <principal>   to evaluate the following expressions as <principal>.
+  ::advance_chain_tip [count]   to move the chain tip `count` (default 1) blocks ahead.
+  ::help                        to print this message.";
+
+/// Whether `input` has no unclosed lists, tuples or strings, so that the REPL can evaluate it
+fn is_complete_input(input: &str) -> bool {
+    let mut depth: i64 = 0;
+    let mut in_string = false;
+    let mut chars = input.chars();
+    while let Some(c) = chars.next() {
+        if in_string {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            // a comment runs to the end of the line
+            ';' => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '(' | '{' => depth += 1,
+            ')' | '}' => depth -= 1,
+            _ => {}
+        }
+    }
+    !in_string && depth <= 0
+}
+
+/// The state of a `repl` session.  Its definitions are made in a transient contract, and its
+///  writes persist in an in-memory store for the rest of the session.
+struct ReplSession {
+    marf: MemoryBackingStore,
+    mainnet: bool,
+    clarity_version: ClarityVersion,
+    contract_context: ContractContext,
+    /// The inputs that made definitions, which later inputs are type-checked along with
+    definitions: String,
+    tx_sender: PrincipalData,
+}
+
+impl ReplSession {
+    fn new(mainnet: bool) -> ReplSession {
+        let clarity_version = ClarityVersion::default_for_epoch(DEFAULT_CLI_EPOCH);
+        let contract_id = QualifiedContractIdentifier::transient();
+        ReplSession {
+            marf: MemoryBackingStore::new(),
+            mainnet,
+            clarity_version,
+            tx_sender: contract_id.issuer.clone().into(),
+            contract_context: ContractContext::new(contract_id, clarity_version),
+            definitions: String::new(),
+        }
+    }
+
+    /// Evaluate a complete input, returning what to print
+    fn handle_input(&mut self, input: &str) -> Option<String> {
+        if let Some(command) = input.trim().strip_prefix("::") {
+            return Some(self.run_command(command));
+        }
+        match self.eval(input) {
+            Ok(value) => value.map(|value| value.to_string()),
+            Err(message) => Some(message),
+        }
+    }
+
+    fn run_command(&mut self, command: &str) -> String {
+        let words: Vec<_> = command.split_whitespace().collect();
+        match words.as_slice() {
+            ["set_tx_sender", principal] => match PrincipalData::parse(principal) {
+                Ok(principal) => {
+                    self.tx_sender = principal;
+                    format!("tx-sender is now {}", self.tx_sender)
+                }
+                Err(error) => format!("Invalid principal:\n{}", error),
+            },
+            ["advance_chain_tip"] | ["advance_chain_tip", _] => {
+                let count = match words.get(1).map(|count| count.parse::<u32>()) {
+                    None => 1,
+                    Some(Ok(count)) => count,
+                    Some(Err(error)) => return format!("Invalid block count:\n{}", error),
+                };
+                let tip_height = self.marf.advance_chain_tip(count);
+                format!("block-height is now {}", tip_height)
+            }
+            ["help"] => REPL_HELP.to_string(),
+            _ => format!(
+                "Unknown command '::{}'. Type ::help for the list of commands.",
+                command.trim()
+            ),
+        }
+    }
+
+    /// Type-check `input` along with the session's definitions, and evaluate it.  Returns the
+    ///  value of its last expression, if that is not a definition.
+    fn eval(&mut self, input: &str) -> Result<Option<Value>, String> {
+        let contract_id = self.contract_context.contract_identifier.clone();
+        let expressions = parse(&contract_id, input, self.clarity_version)
+            .map_err(|error| format!("Parse error:\n{}", error))?;
+
+        let source = format!("{}\n{}", self.definitions, input);
+        let mut contract_ast = parse(&contract_id, &source, self.clarity_version)
+            .map_err(|error| format!("Parse error:\n{}", error))?;
+        run_analysis_free(
+            &contract_id,
+            &mut contract_ast,
+            &mut MemoryBackingStore::new(),
+            false,
+        )
+        .map_err(|(error, _)| format!("Type check error:\n{}", error))?;
+
+        let mut global_context = GlobalContext::new(
+            self.mainnet,
+            default_chain_id(self.mainnet),
+            self.marf.as_clarity_db(),
+            LimitedCostTracker::new_free(),
+            DEFAULT_CLI_EPOCH,
+        );
+        let contract_context = &mut self.contract_context;
+        let tx_sender = &self.tx_sender;
+        let mut defines = false;
+        let result = global_context
+            .execute(|global_context| {
+                let mut result = None;
+                for expression in expressions.iter() {
+                    if DefineFunctionsParsed::try_parse(expression)?.is_some() {
+                        defines = true;
+                        eval_all(
+                            std::slice::from_ref(expression),
+                            contract_context,
+                            global_context,
+                            None,
+                        )?;
+                        result = None;
+                    } else {
+                        let mut call_stack = CallStack::new();
+                        let mut env = Environment::new(
+                            global_context,
+                            contract_context,
+                            &mut call_stack,
+                            Some(tx_sender.clone()),
+                            Some(tx_sender.clone()),
+                            None,
+                        );
+                        result = Some(eval(expression, &mut env, &LocalContext::new())?);
+                    }
+                }
+                Ok(result)
+            })
+            .map_err(|error: Error| format!("Execution error:\n{}", error))?;
+
+        if defines {
+            self.definitions.push_str(input);
+            self.definitions.push('\n');
+        }
+        Ok(result)
+    }
+
+    /// The names to complete: the session's functions, the native functions, and the commands
+    fn completions(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .contract_context
+            .functions
+            .keys()
+            .map(|name| name.to_string())
+            .collect();
+        names.extend(
+            NativeFunctions::ALL_NAMES
+                .iter()
+                .filter(|name| {
+                    NativeFunctions::lookup_by_name_at_version(name, &self.clarity_version)
+                        .is_some()
+                })
+                .map(|name| name.to_string()),
+        );
+        names.extend(
+            ["::set_tx_sender", "::advance_chain_tip", "::help"]
+                .iter()
+                .map(|command| command.to_string()),
+        );
+        names.sort();
+        names
+    }
+}
+
+/// Tab-completion of the REPL's names
+#[derive(Default)]
+struct ReplHelper {
+    completions: Vec<String>,
+}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let start = line[..pos]
+            .rfind(|c: char| c == ' ' || c == '\t' || c == '(' || c == ')')
+            .map(|index| index + 1)
+            .unwrap_or(0);
+        let prefix = &line[start..pos];
+        if prefix.is_empty() {
+            return Ok((pos, vec![]));
+        }
+        let candidates = self
+            .completions
+            .iter()
+            .filter(|name| name.starts_with(prefix))
+            .cloned()
+            .collect();
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+pub(crate) struct CLIHeadersDB {
     db_path: String,
     conn: Connection,
 }
@@ -826,7 +1095,7 @@ struct InitialAllocation {
     amount: u64,
 }
 
-fn consume_arg(
+pub(crate) fn consume_arg(
     args: &mut Vec<String>,
     argnames: &[&str],
     has_optarg: bool,
@@ -865,7 +1134,7 @@ fn consume_arg(
 }
 
 /// This function uses Clarity1 to parse the boot code.
-fn install_boot_code<C: ClarityStorage>(header_db: &CLIHeadersDB, marf: &mut C) {
+pub(crate) fn install_boot_code<C: ClarityStorage>(header_db: &CLIHeadersDB, marf: &mut C) {
     let mainnet = header_db.is_mainnet();
     let boot_code = if mainnet {
         *STACKS_BOOT_CODE_MAINNET_2_1
@@ -1252,75 +1521,176 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
             }
             (0, Some(result))
         }
-        "repl" => {
+        "lint" => {
+            if args.len() < 2 {
+                eprintln!(
+                    "Usage: {} {} [program-file.clar] [--contract_id CONTRACT_ID] [--allow LINT] [--warn LINT] [--deny LINT] [--testnet]",
+                    invoked_by, args[0]
+                );
+                eprintln!("Lints: {}", Lint::ALL_NAMES.join(", "));
+                panic_test!();
+            }
+
             let mut argv: Vec<String> = args.into_iter().map(|x| x.clone()).collect();
+            let contract_id = if let Ok(optarg) = consume_arg(&mut argv, &["--contract_id"], true) {
+                optarg
+                    .map(|optarg_str| {
+                        friendly_expect(
+                            QualifiedContractIdentifier::parse(&optarg_str),
+                            &format!("Error parsing contract identifier '{}", &optarg_str),
+                        )
+                    })
+                    .unwrap_or(QualifiedContractIdentifier::transient())
+            } else {
+                eprintln!("Expected argument for --contract-id");
+                panic_test!();
+            };
+
+            // each of --allow, --warn and --deny may be given more than once
+            let mut config = LintConfig::new();
+            for level_name in ["allow", "warn", "deny"] {
+                let level = LintLevel::lookup_by_name(level_name).expect("BUG: unknown lint level");
+                let switch = format!("--{}", level_name);
+                loop {
+                    let lint_name = match consume_arg(&mut argv, &[switch.as_str()], true) {
+                        Ok(Some(lint_name)) => lint_name,
+                        Ok(None) => break,
+                        Err(_) => {
+                            eprintln!("Expected a lint name for {}", &switch);
+                            panic_test!();
+                        }
+                    };
+                    let lint = friendly_expect_opt(
+                        Lint::lookup_by_name(&lint_name),
+                        &format!(
+                            "Unknown lint '{}'. Lints: {}",
+                            &lint_name,
+                            Lint::ALL_NAMES.join(", ")
+                        ),
+                    );
+                    config.set_level(lint, level);
+                }
+            }
+
             let mainnet = if let Ok(Some(_)) = consume_arg(&mut argv, &["--testnet"], false) {
                 false
             } else {
                 true
             };
-            let mut marf = MemoryBackingStore::new();
-            let mut vm_env = OwnedEnvironment::new_free(
-                mainnet,
-                default_chain_id(mainnet),
-                marf.as_clarity_db(),
-                DEFAULT_CLI_EPOCH,
-            );
-            let mut placeholder_context = ContractContext::new(
-                QualifiedContractIdentifier::transient(),
-                ClarityVersion::Clarity2,
+
+            let content: String = if &argv[1] == "-" {
+                let mut buffer = String::new();
+                friendly_expect(
+                    io::stdin().read_to_string(&mut buffer),
+                    "Error reading from stdin.",
+                );
+                buffer
+            } else {
+                friendly_expect(
+                    fs::read_to_string(&argv[1]),
+                    &format!("Error reading file: {}", argv[1]),
+                )
+            };
+
+            // parse here rather than with parse(), to keep the source map for the lints' spans
+            let clarity_version = ClarityVersion::default_for_epoch(DEFAULT_CLI_EPOCH);
+            let ast = friendly_expect(
+                build_ast_with_rules(
+                    &contract_id,
+                    &content,
+                    &mut (),
+                    clarity_version,
+                    DEFAULT_CLI_EPOCH,
+                    ASTRules::PrecheckSize,
+                ),
+                "Failed to parse program",
             );
-            let mut exec_env = vm_env.get_exec_environment(None, None, &mut placeholder_context);
-            let mut analysis_marf = MemoryBackingStore::new();
+            let mut expressions = ast.expressions;
 
-            let contract_id = QualifiedContractIdentifier::transient();
+            let header_db = CLIHeadersDB::new_memory(mainnet);
+            let mut analysis_marf = MemoryBackingStore::new();
+            install_boot_code(&header_db, &mut analysis_marf);
+            let mut contract_analysis = match run_analysis(
+                &contract_id,
+                &mut expressions,
+                &header_db,
+                &mut analysis_marf,
+                false,
+            ) {
+                Ok(contract_analysis) => contract_analysis,
+                Err((e, _)) => {
+                    let result = json!({
+                        "message": "Checks failed.",
+                        "error": {
+                            "analysis": serde_json::to_value(&e.diagnostic).unwrap(),
+                        }
+                    });
+                    return (1, Some(result));
+                }
+            };
+            contract_analysis.source_map = ast.source_map;
 
-            let mut stdout = io::stdout();
+            let lints = Linter::run(&contract_analysis, &config);
+            let denied = lints.iter().any(|lint| lint.is_denied());
+            let result = json!({
+                "message": if denied { "Lints failed." } else { "Lints passed." },
+                "lints": serde_json::to_value(&lints).unwrap(),
+            });
+            (if denied { 1 } else { 0 }, Some(result))
+        }
+        "repl" => {
+            let mut argv: Vec<String> = args.into_iter().map(|x| x.clone()).collect();
+            let mainnet = if let Ok(Some(_)) = consume_arg(&mut argv, &["--testnet"], false) {
+                false
+            } else {
+                true
+            };
+            let mut session = ReplSession::new(mainnet);
+            let mut editor = match Editor::<ReplHelper, DefaultHistory>::new() {
+                Ok(editor) => editor,
+                Err(error) => {
+                    eprintln!("Failed to open the terminal:\n{}", error);
+                    panic_test!();
+                }
+            };
+            editor.set_helper(Some(ReplHelper {
+                completions: session.completions(),
+            }));
 
+            // an input may span several lines, until its lists and strings are closed
+            let mut content = String::new();
             loop {
-                let content: String = {
-                    let mut buffer = String::new();
-                    stdout.write(b"> ").unwrap_or_else(|e| {
-                        panic!("Failed to write stdout prompt string:\n{}", e);
-                    });
-                    stdout.flush().unwrap_or_else(|e| {
-                        panic!("Failed to flush stdout prompt string:\n{}", e);
-                    });
-                    match io::stdin().read_line(&mut buffer) {
-                        Ok(_) => buffer,
-                        Err(error) => {
-                            eprintln!("Error reading from stdin:\n{}", error);
-                            panic_test!();
-                        }
+                let prompt = if content.is_empty() { "> " } else { "... " };
+                match editor.readline(prompt) {
+                    Ok(line) => {
+                        content.push_str(&line);
+                        content.push('\n');
                     }
-                };
-
-                let mut ast = match parse(&contract_id, &content, ClarityVersion::Clarity2) {
-                    Ok(val) => val,
-                    Err(error) => {
-                        println!("Parse error:\n{}", error);
+                    Err(ReadlineError::Interrupted) => {
+                        content.clear();
                         continue;
                     }
-                };
-
-                match run_analysis_free(&contract_id, &mut ast, &mut analysis_marf, true) {
-                    Ok(_) => (),
-                    Err((error, _)) => {
-                        println!("Type check error:\n{}", error);
-                        continue;
+                    Err(ReadlineError::Eof) => return (0, None),
+                    Err(error) => {
+                        eprintln!("Error reading from stdin:\n{}", error);
+                        panic_test!();
                     }
                 }
+                if !is_complete_input(&content) {
+                    continue;
+                }
+                let input = std::mem::take(&mut content);
+                if input.trim().is_empty() {
+                    continue;
+                }
+                let _ = editor.add_history_entry(input.trim());
 
-                let eval_result =
-                    match exec_env.eval_raw_with_rules(&content, ASTRules::PrecheckSize) {
-                        Ok(val) => val,
-                        Err(error) => {
-                            println!("Execution error:\n{}", error);
-                            continue;
-                        }
-                    };
-
-                println!("{}", eval_result);
+                if let Some(output) = session.handle_input(&input) {
+                    println!("{}", output);
+                }
+                if let Some(helper) = editor.helper_mut() {
+                    helper.completions = session.completions();
+                }
             }
         }
         "eval_raw" => {
@@ -1392,6 +1762,11 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
             } else {
                 false
             };
+            let coverage_folder = if let Ok(covarg) = consume_arg(&mut argv, &["--c"], true) {
+                covarg
+            } else {
+                None
+            };
 
             let evalInput = get_eval_input(invoked_by, &argv);
             let vm_filename = if argv.len() == 3 { &argv[2] } else { &argv[3] };
@@ -1406,10 +1781,20 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                 QualifiedContractIdentifier::transient(),
                 ClarityVersion::Clarity2,
             );
+            let mut coverage = if coverage_folder.is_some() {
+                Some(CoverageReporter::new())
+            } else {
+                None
+            };
 
             let (_, _, result_and_cost) = in_block(header_db, marf_kv, |header_db, mut marf| {
-                let result_and_cost =
-                    with_env_costs(mainnet, &header_db, &mut marf, None, |vm_env| {
+                let result_and_cost = with_env_costs(
+                    mainnet,
+                    &header_db,
+                    &mut marf,
+                    coverage.as_mut(),
+                    None,
+                    |vm_env| {
                         vm_env
                             .get_exec_environment(None, None, &mut placeholder_context)
                             .eval_read_only_with_rules(
@@ -1417,9 +1802,11 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                                 &evalInput.content,
                                 ASTRules::PrecheckSize,
                             )
-                    });
+                    },
+                );
                 (header_db, marf, result_and_cost)
             });
+            save_coverage(coverage_folder, coverage, "eval");
 
             match result_and_cost {
                 (Ok(result), cost) => {
@@ -1486,6 +1873,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                     &header_db,
                     &mut marf,
                     coverage.as_mut(),
+                    None,
                     |vm_env| {
                         vm_env
                             .get_exec_environment(None, None, &mut placeholder_context)
@@ -1573,7 +1961,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
             );
             let result_and_cost = at_block(chain_tip, marf_kv, |mut marf| {
                 let result_and_cost =
-                    with_env_costs(mainnet, &header_db, &mut marf, None, |vm_env| {
+                    with_env_costs(mainnet, &header_db, &mut marf, None, None, |vm_env| {
                         vm_env
                             .get_exec_environment(None, None, &mut placeholder_context)
                             .eval_read_only_with_rules(
@@ -1655,12 +2043,16 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
             );
 
             // TODO: Add --clarity_version as command line argument
-            let mut ast = friendly_expect(
-                parse(
+            let contract_ast = friendly_expect(
+                build_ast_with_rules(
                     &contract_identifier,
                     &contract_content,
+                    &mut (),
                     ClarityVersion::Clarity2,
-                ),
+                    DEFAULT_CLI_EPOCH,
+                    ASTRules::PrecheckSize,
+                )
+                .map_err(|e| RuntimeErrorType::ASTError(e)),
                 "Failed to parse program.",
             );
 
@@ -1671,11 +2063,12 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                 CoverageReporter::register_src_file(
                     &contract_identifier,
                     contract_src_file,
-                    &ast,
+                    &contract_ast,
                     &coverage_file,
                 )
                 .expect("Coverage reference file generation failure");
             }
+            let mut ast = contract_ast.expressions;
 
             // let header_db = CLIHeadersDB::new(vm_filename, false);
 
@@ -1704,6 +2097,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                                 &header_db,
                                 &mut marf,
                                 coverage.as_mut(),
+                                None,
                                 |vm_env| {
                                     vm_env.initialize_versioned_contract(
                                         contract_identifier,
@@ -1784,9 +2178,14 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
             } else {
                 false
             };
+            let trace_file = if let Ok(tracearg) = consume_arg(&mut argv, &["--trace"], true) {
+                tracearg
+            } else {
+                None
+            };
 
             if argv.len() < 5 {
-                eprintln!("Usage: {} {} [--costs] [--assets] [vm-state.db] [contract-identifier] [public-function-name] [sender-address] [args...]", invoked_by, argv[0]);
+                eprintln!("Usage: {} {} [--costs] [--assets] [--trace trace.jsonl] [vm-state.db] [contract-identifier] [public-function-name] [sender-address] [args...]", invoked_by, argv[0]);
                 panic_test!();
             }
 
@@ -1836,12 +2235,14 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
             } else {
                 None
             };
+            let mut tracer = trace_file.as_ref().map(|_| ExecutionTracer::new());
             let (_, _, result_and_cost) = in_block(header_db, marf_kv, |header_db, mut marf| {
                 let result_and_cost = with_env_costs(
                     mainnet,
                     &header_db,
                     &mut marf,
                     coverage.as_mut(),
+                    tracer.as_mut(),
                     |vm_env| {
                         vm_env.execute_transaction(
                             sender,
@@ -1855,6 +2256,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                 let (result, cost) = result_and_cost;
                 (header_db, marf, (result, cost))
             });
+            save_trace(trace_file, tracer);
 
             match result_and_cost {
                 (Ok((x, asset_map, events)), cost) => {
@@ -1916,11 +2318,27 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                 }
             }
         }
-        "make_lcov" => {
+        "make_lcov" | "make_coverage_html" => {
+            let mut argv: Vec<String> = args.into_iter().map(|x| x.clone()).collect();
+            let fail_under = match consume_arg(&mut argv, &["--fail_under"], true) {
+                Ok(Some(percent)) => Some(friendly_expect(
+                    percent.parse::<f64>(),
+                    "Failed to parse --fail_under percentage.",
+                )),
+                _ => None,
+            };
+            if argv.len() != 3 {
+                eprintln!(
+                    "Usage: {} {} [--fail_under PERCENT] [coverage-folder] [output]",
+                    invoked_by, argv[0]
+                );
+                panic_test!();
+            }
+
             let mut register_files = vec![];
             let mut coverage_files = vec![];
-            let coverage_folder = &args[1];
-            let lcov_output_file = &args[2];
+            let coverage_folder = &argv[1];
+            let output = &argv[2];
             for folder_entry in
                 fs::read_dir(coverage_folder).expect("Failed to read the coverage folder")
             {
@@ -1935,9 +2353,30 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                     }
                 }
             }
-            CoverageReporter::produce_lcov(lcov_output_file, &register_files, &coverage_files)
-                .expect("Failed to produce an lcov output");
-            (0, None)
+            let summary = if argv[0] == "make_lcov" {
+                CoverageReporter::produce_lcov(output, &register_files, &coverage_files)
+                    .expect("Failed to produce an lcov output")
+            } else {
+                CoverageReporter::produce_html(output, &register_files, &coverage_files)
+                    .expect("Failed to produce an HTML coverage report")
+            };
+
+            let result = json!({
+                "lines_hit": summary.lines_hit,
+                "lines_found": summary.lines_found,
+                "percent": summary.percent(),
+            });
+            match fail_under {
+                Some(percent) if summary.percent() < percent => {
+                    eprintln!(
+                        "Coverage {:.2}% is below the required {:.2}%",
+                        summary.percent(),
+                        percent
+                    );
+                    (1, Some(result))
+                }
+                _ => (0, Some(result)),
+            }
         }
         _ => {
             print_usage(invoked_by);
@@ -2202,6 +2641,36 @@ mod test {
         assert!(result["events"].as_array().unwrap().len() == 0);
         assert_eq!(result["output"], json!({"UInt": 1000}));
 
+        eprintln!("execute tokens with trace");
+        let trace_file = format!("{}.trace.jsonl", &db_name);
+        let invoked = invoke_command(
+            "test",
+            &[
+                "execute".to_string(),
+                "--trace".to_string(),
+                trace_file.clone(),
+                db_name.clone(),
+                "S1G2081040G2081040G2081040G208105NK8PE5.tokens".to_string(),
+                "mint!".to_string(),
+                "SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR".to_string(),
+                "u100".to_string(),
+            ],
+        );
+
+        let exit = invoked.0;
+        assert_eq!(exit, 0);
+        let trace: Vec<serde_json::Value> = fs::read_to_string(&trace_file)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(trace.first().unwrap()["event"], json!("contract_call"));
+        assert_eq!(trace.first().unwrap()["function"], json!("mint!"));
+        assert!(trace
+            .iter()
+            .any(|entry| entry["event"] == json!("map_write")));
+        assert_eq!(trace.last().unwrap()["event"], json!("contract_return"));
+
         eprintln!("eval tokens");
         let invoked = invoke_command(
             "test",
@@ -2315,6 +2784,182 @@ mod test {
         assert!(result["costs"] != json!(null));
     }
 
+    #[test]
+    fn test_lint() {
+        let invoked = invoke_command(
+            "test",
+            &[
+                "lint".to_string(),
+                "../sample-contracts/tokens-ft.clar".to_string(),
+            ],
+        );
+        let exit = invoked.0;
+        let result = invoked.1.unwrap();
+
+        assert_eq!(exit, 0);
+        let lints = result["lints"].as_array().unwrap();
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0]["lint"], "TxSenderInTransfer");
+        assert_eq!(lints[0]["diagnostic"]["level"], "Warning");
+        assert_eq!(lints[0]["diagnostic"]["spans"][0]["start_line"], 9);
+
+        let invoked = invoke_command(
+            "test",
+            &[
+                "lint".to_string(),
+                "../sample-contracts/tokens-ft.clar".to_string(),
+                "--deny".to_string(),
+                "tx-sender-in-transfer".to_string(),
+            ],
+        );
+        let exit = invoked.0;
+        let result = invoked.1.unwrap();
+
+        assert_eq!(exit, 1);
+        assert_eq!(result["lints"][0]["diagnostic"]["level"], "Error");
+    }
+
+    #[test]
+    fn test_coverage_reports() {
+        let id = rand::thread_rng().gen::<i32>();
+        let db_name = format!("/tmp/db_{}", id);
+        let clar_name = format!("/tmp/test-coverage_{}.clar", id);
+        let coverage_folder = format!("/tmp/coverage_{}", id);
+        let lcov_name = format!("/tmp/coverage_{}.info", id);
+        let html_folder = format!("/tmp/coverage_html_{}", id);
+        fs::create_dir_all(&coverage_folder).unwrap();
+        fs::write(
+            &clar_name,
+            "(define-public (covered (x uint))
+  (ok (+ x u1)))
+(define-public (uncovered (x uint))
+  (ok (- x u1)))
+",
+        )
+        .unwrap();
+
+        invoke_command("test", &["initialize".to_string(), db_name.clone()]);
+        let invoked = invoke_command(
+            "test",
+            &[
+                "launch".to_string(),
+                "--c".to_string(),
+                coverage_folder.clone(),
+                "S1G2081040G2081040G2081040G208105NK8PE5.coverage".to_string(),
+                clar_name.clone(),
+                db_name.clone(),
+            ],
+        );
+        assert_eq!(invoked.0, 0);
+        let invoked = invoke_command(
+            "test",
+            &[
+                "execute".to_string(),
+                "--c".to_string(),
+                coverage_folder.clone(),
+                db_name.clone(),
+                "S1G2081040G2081040G2081040G208105NK8PE5.coverage".to_string(),
+                "covered".to_string(),
+                "SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR".to_string(),
+                "u1".to_string(),
+            ],
+        );
+        assert_eq!(invoked.0, 0);
+
+        let invoked = invoke_command(
+            "test",
+            &[
+                "make_lcov".to_string(),
+                coverage_folder.clone(),
+                lcov_name.clone(),
+            ],
+        );
+        assert_eq!(invoked.0, 0);
+        assert_eq!(
+            invoked.1.unwrap(),
+            json!({ "lines_hit": 1, "lines_found": 2, "percent": 50.0 })
+        );
+        let lcov = fs::read_to_string(&lcov_name).unwrap();
+        assert!(lcov.contains(&format!("SF:{}\nDA:2,4\nDA:4,0\nLH:1\nLF:2\n", clar_name)));
+
+        let invoked = invoke_command(
+            "test",
+            &[
+                "make_lcov".to_string(),
+                "--fail_under".to_string(),
+                "75".to_string(),
+                coverage_folder.clone(),
+                lcov_name,
+            ],
+        );
+        assert_eq!(invoked.0, 1);
+
+        let invoked = invoke_command(
+            "test",
+            &[
+                "make_coverage_html".to_string(),
+                coverage_folder,
+                html_folder.clone(),
+            ],
+        );
+        assert_eq!(invoked.0, 0);
+        let index = fs::read_to_string(format!("{}/index.html", html_folder)).unwrap();
+        assert!(index.contains("1/2 lines (50.0%)"));
+        let page = fs::read_to_string(format!(
+            "{}/S1G2081040G2081040G2081040G208105NK8PE5.coverage.html",
+            html_folder
+        ))
+        .unwrap();
+        assert!(page.contains(
+            "<span class=\"miss\"><span class=\"count\">     0</span>     4    (<span class=\"unexecuted\">ok</span>"
+        ));
+    }
+
+    #[test]
+    fn test_repl_session() {
+        let mut session = ReplSession::new(false);
+
+        assert_eq!(
+            session.handle_input(
+                "(define-data-var counter uint u0)
+                 (define-public (increment)
+                   (ok (var-set counter (+ (var-get counter) u1))))"
+            ),
+            None
+        );
+        // the definitions persist for the rest of the session
+        assert_eq!(
+            session.handle_input("(increment)"),
+            Some("(ok true)".into())
+        );
+        assert_eq!(session.handle_input("(var-get counter)"), Some("u1".into()));
+        assert!(session
+            .handle_input("(decrement)")
+            .unwrap()
+            .starts_with("Type check error"));
+        assert!(session.completions().contains(&"increment".to_string()));
+
+        session.handle_input("::set_tx_sender ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM");
+        assert_eq!(
+            session.handle_input("tx-sender"),
+            Some("ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM".into())
+        );
+        assert_eq!(
+            session.handle_input("::advance_chain_tip 5"),
+            Some("block-height is now 5".into())
+        );
+        assert_eq!(session.handle_input("block-height"), Some("u5".into()));
+        assert!(session
+            .handle_input("::mine")
+            .unwrap()
+            .starts_with("Unknown command"));
+
+        assert!(is_complete_input("(+ 1 2)"));
+        assert!(!is_complete_input("(define-read-only (get)\n"));
+        assert!(!is_complete_input("(print \"(\")\n(print \")"));
+        assert!(is_complete_input("(+ 1 ;; (\n 2)"));
+    }
+
     #[test]
     fn test_assets() {
         let db_name = format!("/tmp/db_{}", rand::thread_rng().gen::<i32>());
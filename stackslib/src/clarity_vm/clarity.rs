@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::sync::Arc;
 use std::{error, fmt, thread};
 
 use clarity::vm::analysis::errors::{CheckError, CheckErrors};
@@ -23,18 +24,20 @@ use clarity::vm::ast::{ASTRules, ContractAST};
 use clarity::vm::clarity::TransactionConnection;
 pub use clarity::vm::clarity::{ClarityConnection, Error};
 use clarity::vm::contexts::{AssetMap, Environment, OwnedEnvironment};
-use clarity::vm::costs::{CostTracker, ExecutionCost, LimitedCostTracker};
+use clarity::vm::costs::profile::CostProfile;
+use clarity::vm::costs::{CostTracker, ExecutionCost, ExecutionLimits, LimitedCostTracker};
 use clarity::vm::database::{
-    BurnStateDB, ClarityDatabase, HeadersDB, RollbackWrapper, RollbackWrapperPersistedLog,
-    STXBalance, SqliteConnection, NULL_BURN_STATE_DB, NULL_HEADER_DB,
+    BurnStateDB, ClarityDatabase, ClarityStateDiff, HeadersDB, RollbackWrapper,
+    RollbackWrapperPersistedLog, STXBalance, ScratchStore, SqliteConnection, StateOverride,
+    NULL_BURN_STATE_DB, NULL_HEADER_DB,
 };
 use clarity::vm::errors::Error as InterpreterError;
-use clarity::vm::representations::SymbolicExpression;
+use clarity::vm::representations::{ClarityName, SymbolicExpression};
 use clarity::vm::types::{
     AssetIdentifier, BuffData, OptionalData, PrincipalData, QualifiedContractIdentifier, TupleData,
     TypeSignature, Value,
 };
-use clarity::vm::{analysis, ast, ClarityVersion, ContractName};
+use clarity::vm::{analysis, ast, ClarityVersion, ContractName, EvalHook};
 use stacks_common::consts::{CHAIN_ID_TESTNET, SIGNER_SLOTS_PER_USER};
 use stacks_common::types::chainstate::{
     BlockHeaderHash, BurnchainHeaderHash, SortitionId, StacksAddress, StacksBlockId, TrieHash,
@@ -45,12 +48,13 @@ use stacks_common::util::secp256k1::MessageSignature;
 use crate::burnchains::{Burnchain, PoxConstants};
 use crate::chainstate::nakamoto::signer_set::NakamotoSigners;
 use crate::chainstate::stacks::boot::{
-    BOOT_CODE_COSTS, BOOT_CODE_COSTS_2, BOOT_CODE_COSTS_2_TESTNET, BOOT_CODE_COSTS_3,
+    BootContractProvider, StacksBootContracts, BOOT_CODE_COSTS, BOOT_CODE_COSTS_2,
+    BOOT_CODE_COSTS_2_TESTNET, BOOT_CODE_COSTS_3, BOOT_CODE_COSTS_4,
     BOOT_CODE_COST_VOTING_TESTNET as BOOT_CODE_COST_VOTING, BOOT_CODE_POX_TESTNET,
     BOOT_TEST_POX_4_AGG_KEY_CONTRACT, BOOT_TEST_POX_4_AGG_KEY_FNAME, COSTS_2_NAME, COSTS_3_NAME,
-    MINERS_NAME, POX_2_MAINNET_CODE, POX_2_NAME, POX_2_TESTNET_CODE, POX_3_MAINNET_CODE,
-    POX_3_NAME, POX_3_TESTNET_CODE, POX_4_CODE, POX_4_NAME, SIGNERS_BODY, SIGNERS_DB_0_BODY,
-    SIGNERS_DB_1_BODY, SIGNERS_NAME, SIGNERS_VOTING_BODY, SIGNERS_VOTING_NAME,
+    COSTS_4_NAME, MINERS_NAME, POX_2_MAINNET_CODE, POX_2_NAME, POX_2_TESTNET_CODE,
+    POX_3_MAINNET_CODE, POX_3_NAME, POX_3_TESTNET_CODE, POX_4_CODE, POX_4_NAME, SIGNERS_BODY,
+    SIGNERS_DB_0_BODY, SIGNERS_DB_1_BODY, SIGNERS_NAME, SIGNERS_VOTING_BODY, SIGNERS_VOTING_NAME,
 };
 use crate::chainstate::stacks::db::{StacksAccount, StacksChainState};
 use crate::chainstate::stacks::events::{StacksTransactionEvent, StacksTransactionReceipt};
@@ -63,6 +67,7 @@ use crate::chainstate::stacks::{
     TransactionVersion,
 };
 use crate::clarity_vm::database::marf::{MarfedKV, ReadOnlyMarfStore, WritableMarfStore};
+use crate::clarity_vm::database::snapshot::ClarityStateSnapshot;
 use crate::core::{StacksEpoch, StacksEpochId, FIRST_STACKS_BLOCK_ID, GENESIS_EPOCH};
 use crate::util_lib::boot::{boot_code_acc, boot_code_addr, boot_code_id, boot_code_tx_auth};
 use crate::util_lib::db::Error as DatabaseError;
@@ -94,6 +99,12 @@ pub struct ClarityInstance {
     datastore: MarfedKV,
     mainnet: bool,
     chain_id: u32,
+    /// Optional budget for contract analysis, independent of the block's execution budget.
+    /// Only meant for private networks and appchains -- setting this changes which contract
+    ///  deployments are valid, so nodes on a public network must leave it unset.
+    analysis_cost_limit: Option<ExecutionCost>,
+    /// Supplies the contracts instantiated at genesis and at epoch transitions
+    boot_contract_provider: Arc<dyn BootContractProvider>,
 }
 
 ///
@@ -123,6 +134,10 @@ pub struct ClarityBlockConnection<'a, 'b> {
     mainnet: bool,
     chain_id: u32,
     epoch: StacksEpochId,
+    analysis_cost_limit: Option<ExecutionCost>,
+    boot_contract_provider: Arc<dyn BootContractProvider>,
+    /// Hook called while evaluating the block's transactions, e.g. to trace them
+    eval_hook: Option<Box<dyn EvalHook>>,
 }
 
 ///
@@ -139,6 +154,8 @@ pub struct ClarityTransactionConnection<'a, 'b> {
     mainnet: bool,
     chain_id: u32,
     epoch: StacksEpochId,
+    analysis_cost_limit: Option<ExecutionCost>,
+    eval_hook: Option<&'a mut dyn EvalHook>,
 }
 
 pub struct ClarityReadOnlyConnection<'a> {
@@ -148,6 +165,30 @@ pub struct ClarityReadOnlyConnection<'a> {
     epoch: StacksEpochId,
 }
 
+/// A public function call to simulate with `ClarityInstance::simulate_contract_call()`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulatedContractCall {
+    pub sender: PrincipalData,
+    pub sponsor: Option<PrincipalData>,
+    pub contract: QualifiedContractIdentifier,
+    pub function: ClarityName,
+    pub args: Vec<Value>,
+    /// State to pretend is stored, applied in order before the call
+    pub overrides: Vec<StateOverride>,
+    /// The most the call may cost, or `None` for no limit
+    pub cost_limit: Option<ExecutionCost>,
+}
+
+/// The outcome of a simulated call
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulatedCallResult {
+    /// The function's return value.  If it is an `err` response, the call's writes and events
+    ///  were rolled back, as they would be in a transaction.
+    pub result: Value,
+    pub events: Vec<StacksTransactionEvent>,
+    pub cost: ExecutionCost,
+}
+
 impl From<ChainstateError> for Error {
     fn from(e: ChainstateError) -> Self {
         match e {
@@ -191,6 +232,9 @@ impl<'a, 'b> ClarityBlockConnection<'a, 'b> {
             mainnet: false,
             chain_id: CHAIN_ID_TESTNET,
             epoch: epoch,
+            analysis_cost_limit: None,
+            boot_contract_provider: Arc::new(StacksBootContracts),
+            eval_hook: None,
         }
     }
 
@@ -219,6 +263,28 @@ impl<'a, 'b> ClarityBlockConnection<'a, 'b> {
         }
     }
 
+    /// Start recording a breakdown of the costs charged by the transactions processed from now
+    /// on.  See `ClarityTransactionConnection::enable_cost_profile()`.
+    pub fn enable_cost_profile(&mut self) {
+        if let Some(ref mut track) = self.cost_track {
+            track.enable_profile();
+        }
+    }
+
+    /// Stop recording the cost breakdown, and return what was recorded since
+    /// `enable_cost_profile()`
+    pub fn take_cost_profile(&mut self) -> Option<CostProfile> {
+        self.cost_track
+            .as_mut()
+            .and_then(|track| track.take_profile())
+    }
+
+    /// Set (or clear) a hook to be called while evaluating the transactions processed from now
+    /// on, e.g. an `ExecutionTracer`
+    pub fn set_eval_hook(&mut self, hook: Option<Box<dyn EvalHook>>) {
+        self.eval_hook = hook;
+    }
+
     /// Returns the block limit for the block being created.
     pub fn block_limit(&self) -> Option<ExecutionCost> {
         match self.cost_track {
@@ -234,9 +300,31 @@ impl ClarityInstance {
             datastore,
             mainnet,
             chain_id,
+            analysis_cost_limit: None,
+            boot_contract_provider: Arc::new(StacksBootContracts),
         }
     }
 
+    /// Set the budget that contract analysis may consume, independent of the block's
+    ///  execution budget. See `TransactionConnection::analyze_smart_contract_with_limit()`.
+    pub fn set_analysis_cost_limit(&mut self, analysis_cost_limit: Option<ExecutionCost>) {
+        self.analysis_cost_limit = analysis_cost_limit;
+    }
+
+    pub fn get_analysis_cost_limit(&self) -> Option<&ExecutionCost> {
+        self.analysis_cost_limit.as_ref()
+    }
+
+    /// Set the provider of the boot contracts, for networks other than the Stacks mainnet and
+    ///  testnet. See `BootContractProvider`.
+    pub fn set_boot_contract_provider(&mut self, provider: Arc<dyn BootContractProvider>) {
+        self.boot_contract_provider = provider;
+    }
+
+    pub fn get_boot_contract_provider(&self) -> &Arc<dyn BootContractProvider> {
+        &self.boot_contract_provider
+    }
+
     pub fn with_marf<F, R>(&mut self, f: F) -> R
     where
         F: FnOnce(&mut MARF<StacksBlockId>) -> R,
@@ -302,6 +390,9 @@ impl ClarityInstance {
             mainnet: self.mainnet,
             chain_id: self.chain_id,
             epoch: epoch.epoch_id,
+            analysis_cost_limit: self.analysis_cost_limit.clone(),
+            boot_contract_provider: self.boot_contract_provider.clone(),
+            eval_hook: None,
         }
     }
 
@@ -326,6 +417,9 @@ impl ClarityInstance {
             mainnet: self.mainnet,
             chain_id: self.chain_id,
             epoch,
+            analysis_cost_limit: self.analysis_cost_limit.clone(),
+            boot_contract_provider: self.boot_contract_provider.clone(),
+            eval_hook: None,
         }
     }
 
@@ -352,6 +446,9 @@ impl ClarityInstance {
             mainnet: self.mainnet,
             chain_id: self.chain_id,
             epoch,
+            analysis_cost_limit: self.analysis_cost_limit.clone(),
+            boot_contract_provider: self.boot_contract_provider.clone(),
+            eval_hook: None,
         };
 
         let use_mainnet = self.mainnet;
@@ -448,6 +545,9 @@ impl ClarityInstance {
             mainnet: self.mainnet,
             chain_id: self.chain_id,
             epoch,
+            analysis_cost_limit: self.analysis_cost_limit.clone(),
+            boot_contract_provider: self.boot_contract_provider.clone(),
+            eval_hook: None,
         };
 
         let use_mainnet = self.mainnet;
@@ -556,6 +656,9 @@ impl ClarityInstance {
             mainnet: self.mainnet,
             chain_id: self.chain_id,
             epoch: epoch.epoch_id,
+            analysis_cost_limit: self.analysis_cost_limit.clone(),
+            boot_contract_provider: self.boot_contract_provider.clone(),
+            eval_hook: None,
         }
     }
 
@@ -601,6 +704,11 @@ impl ClarityInstance {
         datastore.trie_exists_for_block(bhh)
     }
 
+    /// Has the state at `bhh` been pruned (see `MarfedKV::prune_state()`)?
+    pub fn is_pruned_at(&mut self, bhh: &StacksBlockId) -> Result<bool, Error> {
+        self.datastore.is_pruned_at(bhh).map_err(Error::from)
+    }
+
     /// Evaluate program read-only at `at_block`. This will be evaluated in the Stacks epoch that
     ///  was active *during* the evaluation of `at_block`
     pub fn eval_read_only(
@@ -611,6 +719,50 @@ impl ClarityInstance {
         contract: &QualifiedContractIdentifier,
         program: &str,
         ast_rules: ASTRules,
+    ) -> Result<Value, Error> {
+        self.inner_eval_read_only(
+            at_block,
+            header_db,
+            burn_state_db,
+            contract,
+            program,
+            ast_rules,
+            None,
+        )
+    }
+
+    /// Evaluate program read-only at `at_block`, like `eval_read_only()`, but bounded by
+    ///  `limits` instead of running for free.
+    pub fn eval_read_only_with_limits(
+        &mut self,
+        at_block: &StacksBlockId,
+        header_db: &dyn HeadersDB,
+        burn_state_db: &dyn BurnStateDB,
+        contract: &QualifiedContractIdentifier,
+        program: &str,
+        ast_rules: ASTRules,
+        limits: &ExecutionLimits,
+    ) -> Result<Value, Error> {
+        self.inner_eval_read_only(
+            at_block,
+            header_db,
+            burn_state_db,
+            contract,
+            program,
+            ast_rules,
+            Some(limits),
+        )
+    }
+
+    fn inner_eval_read_only(
+        &mut self,
+        at_block: &StacksBlockId,
+        header_db: &dyn HeadersDB,
+        burn_state_db: &dyn BurnStateDB,
+        contract: &QualifiedContractIdentifier,
+        program: &str,
+        ast_rules: ASTRules,
+        limits: Option<&ExecutionLimits>,
     ) -> Result<Value, Error> {
         let mut read_only_conn = self.datastore.begin_read_only(Some(at_block));
         let mut clarity_db = read_only_conn.as_clarity_db(header_db, burn_state_db);
@@ -621,12 +773,95 @@ impl ClarityInstance {
             result
         }?;
 
-        let mut env = OwnedEnvironment::new_free(self.mainnet, self.chain_id, clarity_db, epoch_id);
+        let mut env = match limits {
+            Some(limits) => {
+                let cost_track = LimitedCostTracker::new_read_only(
+                    self.mainnet,
+                    self.chain_id,
+                    limits,
+                    &mut clarity_db,
+                    epoch_id,
+                )
+                .map_err(InterpreterError::from)?;
+                OwnedEnvironment::new_cost_limited(
+                    self.mainnet,
+                    self.chain_id,
+                    clarity_db,
+                    cost_track,
+                    epoch_id,
+                )
+            }
+            None => OwnedEnvironment::new_free(self.mainnet, self.chain_id, clarity_db, epoch_id),
+        };
         env.eval_read_only_with_rules(contract, program, ast_rules)
             .map(|(x, _, _)| x)
             .map_err(Error::from)
     }
 
+    /// Simulate `call` at `at_block`, as if it were a contract-call transaction in the block
+    ///  after it, with the call's state overrides applied first.  The call and the overrides
+    ///  are evaluated in a scratch workspace, so nothing is written to the chainstate.  This
+    ///  will be evaluated in the Stacks epoch that was active *during* the evaluation of
+    ///  `at_block`.
+    pub fn simulate_contract_call(
+        &mut self,
+        at_block: &StacksBlockId,
+        header_db: &dyn HeadersDB,
+        burn_state_db: &dyn BurnStateDB,
+        call: &SimulatedContractCall,
+    ) -> Result<SimulatedCallResult, Error> {
+        let mut read_only_conn = self.datastore.begin_read_only_checked(Some(at_block))?;
+        let mut scratch = ScratchStore::at(&mut read_only_conn, at_block.clone())?;
+        let mut clarity_db = scratch.as_clarity_db(header_db, burn_state_db);
+        let epoch_id = {
+            clarity_db.begin();
+            let result = clarity_db.get_clarity_epoch_version();
+            clarity_db.roll_back()?;
+            result
+        }?;
+
+        clarity_db.begin();
+        for state_override in call.overrides.iter() {
+            state_override.apply(&mut clarity_db, &epoch_id)?;
+        }
+        clarity_db.commit()?;
+
+        let cost_track = LimitedCostTracker::new(
+            self.mainnet,
+            self.chain_id,
+            call.cost_limit
+                .clone()
+                .unwrap_or_else(ExecutionCost::max_value),
+            &mut clarity_db,
+            epoch_id,
+        )
+        .map_err(InterpreterError::from)?;
+        let mut env = OwnedEnvironment::new_cost_limited(
+            self.mainnet,
+            self.chain_id,
+            clarity_db,
+            cost_track,
+            epoch_id,
+        );
+        let args: Vec<_> = call
+            .args
+            .iter()
+            .map(|arg| SymbolicExpression::atom_value(arg.clone()))
+            .collect();
+        let (result, _, events) = env.execute_transaction(
+            call.sender.clone(),
+            call.sponsor.clone(),
+            call.contract.clone(),
+            &call.function,
+            &args,
+        )?;
+        Ok(SimulatedCallResult {
+            result,
+            events,
+            cost: env.get_cost_total(),
+        })
+    }
+
     pub fn destroy(self) -> MarfedKV {
         self.datastore
     }
@@ -789,7 +1024,7 @@ impl<'a, 'b> ClarityBlockConnection<'a, 'b> {
     }
 
     /// Get the boot code account
-    fn get_boot_code_account(&mut self) -> Result<StacksAccount, Error> {
+    pub fn get_boot_code_account(&mut self) -> Result<StacksAccount, Error> {
         let boot_code_address = boot_code_addr(self.mainnet);
         let boot_code_nonce = self.with_clarity_db_readonly(|db| {
             db.get_account_nonce(&boot_code_address.clone().into())
@@ -1524,6 +1759,163 @@ impl<'a, 'b> ClarityBlockConnection<'a, 'b> {
         })
     }
 
+    pub fn initialize_epoch_3_1(&mut self) -> Result<Vec<StacksTransactionReceipt>, Error> {
+        // use the `using!` statement to ensure that the old cost_tracker is placed
+        //  back in all branches after initialization
+        using!(self.cost_track, "cost tracker", |old_cost_tracker| {
+            // epoch initialization is *free*.
+            // NOTE: this also means that cost functions won't be evaluated.
+            self.cost_track.replace(LimitedCostTracker::new_free());
+
+            let mainnet = self.mainnet;
+
+            // get the boot code account information
+            //  for processing the costs-4 contract initialization
+            let tx_version = if mainnet {
+                TransactionVersion::Mainnet
+            } else {
+                TransactionVersion::Testnet
+            };
+
+            let boot_code_address = boot_code_addr(mainnet);
+
+            let boot_code_auth = boot_code_tx_auth(boot_code_address.clone());
+
+            let boot_code_nonce = self.with_clarity_db_readonly(|db| {
+                db.get_account_nonce(&boot_code_address.clone().into())
+                    .expect("FATAL: Failed to boot account nonce")
+            });
+
+            let boot_code_account = StacksAccount {
+                principal: PrincipalData::Standard(boot_code_address.into()),
+                nonce: boot_code_nonce,
+                stx_balance: STXBalance::zero(),
+            };
+
+            /////////////////// .costs-4 ////////////////////////
+            let payload = TransactionPayload::SmartContract(
+                TransactionSmartContract {
+                    name: ContractName::try_from(COSTS_4_NAME)
+                        .expect("FATAL: invalid boot-code contract name"),
+                    code_body: StacksString::from_str(BOOT_CODE_COSTS_4)
+                        .expect("FATAL: invalid boot code body"),
+                },
+                None,
+            );
+
+            let costs_4_contract_tx =
+                StacksTransaction::new(tx_version.clone(), boot_code_auth.clone(), payload);
+
+            self.epoch = StacksEpochId::Epoch31;
+            let costs_4_initialization_receipt = self.as_transaction(|tx_conn| {
+                // bump the epoch in the Clarity DB
+                tx_conn
+                    .with_clarity_db(|db| {
+                        db.set_clarity_epoch_version(StacksEpochId::Epoch31)?;
+                        Ok(())
+                    })
+                    .unwrap();
+
+                // require 3.1 rules henceforth in this connection as well
+                tx_conn.epoch = StacksEpochId::Epoch31;
+
+                // initialize with a synthetic transaction
+                debug!("Instantiate .costs-4 contract");
+                StacksChainState::process_transaction_payload(
+                    tx_conn,
+                    &costs_4_contract_tx,
+                    &boot_code_account,
+                    ASTRules::PrecheckSize,
+                )
+                .expect("FATAL: Failed to process costs-4 contract initialization")
+            });
+
+            if costs_4_initialization_receipt.result != Value::okay_true()
+                || costs_4_initialization_receipt.post_condition_aborted
+            {
+                panic!(
+                    "FATAL: Failure processing Costs 4 contract initialization: {:#?}",
+                    &costs_4_initialization_receipt
+                );
+            }
+
+            debug!("Epoch 3.1 initialized");
+            (old_cost_tracker, Ok(vec![costs_4_initialization_receipt]))
+        })
+    }
+
+    /// Instantiate the contracts that the boot contract provider adds in `epoch`, once the
+    ///  transition to `epoch` has been applied
+    pub fn initialize_epoch_boot_contracts(
+        &mut self,
+        epoch: StacksEpochId,
+    ) -> Result<Vec<StacksTransactionReceipt>, Error> {
+        let contracts = self
+            .boot_contract_provider
+            .epoch_contracts(self.mainnet, epoch);
+        if contracts.is_empty() {
+            return Ok(vec![]);
+        }
+
+        using!(self.cost_track, "cost tracker", |old_cost_tracker| {
+            // like the epoch's own boot contracts, these are instantiated for free
+            self.cost_track.replace(LimitedCostTracker::new_free());
+
+            let mainnet = self.mainnet;
+            let tx_version = if mainnet {
+                TransactionVersion::Mainnet
+            } else {
+                TransactionVersion::Testnet
+            };
+            let boot_code_address = boot_code_addr(mainnet);
+            let boot_code_auth = boot_code_tx_auth(boot_code_address.clone());
+            let boot_code_nonce = self.with_clarity_db_readonly(|db| {
+                db.get_account_nonce(&boot_code_address.clone().into())
+                    .expect("FATAL: Failed to boot account nonce")
+            });
+            let boot_code_account = StacksAccount {
+                principal: PrincipalData::Standard(boot_code_address.into()),
+                nonce: boot_code_nonce,
+                stx_balance: STXBalance::zero(),
+            };
+
+            let mut receipts = vec![];
+            for contract in contracts.into_iter() {
+                let payload = TransactionPayload::SmartContract(
+                    TransactionSmartContract {
+                        name: contract.name.clone(),
+                        code_body: StacksString::from_str(&contract.code)
+                            .expect("FATAL: invalid boot code body"),
+                    },
+                    contract.clarity_version,
+                );
+                let contract_tx =
+                    StacksTransaction::new(tx_version.clone(), boot_code_auth.clone(), payload);
+
+                let receipt = self.as_transaction(|tx_conn| {
+                    debug!("Instantiate boot contract {}", &contract.name);
+                    StacksChainState::process_transaction_payload(
+                        tx_conn,
+                        &contract_tx,
+                        &boot_code_account,
+                        ASTRules::PrecheckSize,
+                    )
+                    .expect("FATAL: Failed to process boot contract initialization")
+                });
+                if receipt.result != Value::okay_true() || receipt.post_condition_aborted {
+                    panic!(
+                        "FATAL: Failure processing boot contract {} initialization: {:#?}",
+                        &contract.name, &receipt
+                    );
+                }
+                receipts.push(receipt);
+            }
+
+            debug!("Epoch {} boot contracts initialized", epoch);
+            (old_cost_tracker, Ok(receipts))
+        })
+    }
+
     pub fn start_transaction_processing<'c>(&'c mut self) -> ClarityTransactionConnection<'c, 'a> {
         let store = &mut self.datastore;
         let cost_track = &mut self.cost_track;
@@ -1531,6 +1923,10 @@ impl<'a, 'b> ClarityBlockConnection<'a, 'b> {
         let burn_state_db = self.burn_state_db;
         let mainnet = self.mainnet;
         let chain_id = self.chain_id;
+        let eval_hook = self
+            .eval_hook
+            .as_deref_mut()
+            .map(|hook| hook as &mut dyn EvalHook);
         let mut log = RollbackWrapperPersistedLog::new();
         log.nest();
         ClarityTransactionConnection {
@@ -1542,6 +1938,8 @@ impl<'a, 'b> ClarityBlockConnection<'a, 'b> {
             mainnet,
             chain_id,
             epoch: self.epoch,
+            analysis_cost_limit: self.analysis_cost_limit.clone(),
+            eval_hook,
         }
     }
 
@@ -1587,6 +1985,22 @@ impl<'a, 'b> ClarityBlockConnection<'a, 'b> {
         self.datastore.seal()
     }
 
+    /// Start recording every key/value committed to this block's datastore from now on
+    pub fn record_state_diff(&mut self) {
+        self.datastore.record_state_diff();
+    }
+
+    /// Stop recording, and get every key/value committed since `record_state_diff()`.
+    /// Returns None if the block was not being recorded.
+    pub fn take_state_diff(&mut self) -> Option<ClarityStateDiff> {
+        self.datastore.take_state_diff()
+    }
+
+    /// Write the Clarity state in `snapshot` to this block (see `WritableMarfStore::import_state()`)
+    pub fn import_state_snapshot(&mut self, snapshot: &ClarityStateSnapshot) -> Result<(), Error> {
+        self.datastore.import_state(snapshot).map_err(Error::from)
+    }
+
     pub fn destruct(self) -> WritableMarfStore<'a> {
         self.datastore
     }
@@ -1686,6 +2100,9 @@ impl<'a, 'b> TransactionConnection for ClarityTransactionConnection<'a, 'b> {
                     cost_track,
                     self.epoch,
                 );
+                if let Some(hook) = self.eval_hook.as_deref_mut() {
+                    vm_env.add_eval_hook(hook);
+                }
                 let result = to_do(&mut vm_env);
                 let (mut db, cost_track) = vm_env
                     .destruct()
@@ -1715,6 +2132,10 @@ impl<'a, 'b> TransactionConnection for ClarityTransactionConnection<'a, 'b> {
         })
     }
 
+    fn get_analysis_cost_limit(&self) -> Option<ExecutionCost> {
+        self.analysis_cost_limit.clone()
+    }
+
     fn with_analysis_db<F, R>(&mut self, to_do: F) -> R
     where
         F: FnOnce(&mut AnalysisDatabase, LimitedCostTracker) -> (LimitedCostTracker, R),
@@ -1769,6 +2190,22 @@ impl<'a, 'b> ClarityTransactionConnection<'a, 'b> {
         }
     }
 
+    /// Start recording a breakdown of the costs charged by this transaction, by cost function
+    /// and by contract call.  Blocks opened with a free cost tracker record nothing.
+    pub fn enable_cost_profile(&mut self) {
+        if let Some(ref mut track) = self.cost_track {
+            track.enable_profile();
+        }
+    }
+
+    /// Stop recording the cost breakdown, and return what was recorded since
+    /// `enable_cost_profile()`
+    pub fn take_cost_profile(&mut self) -> Option<CostProfile> {
+        self.cost_track
+            .as_mut()
+            .and_then(|track| track.take_profile())
+    }
+
     /// Evaluate a poison-microblock transaction
     pub fn run_poison_microblock(
         &mut self,
@@ -1882,17 +2319,25 @@ impl<'a, 'b> ClarityTransactionConnection<'a, 'b> {
 #[cfg(test)]
 mod tests {
     use std::fs;
+    use std::path::PathBuf;
 
     use clarity::vm::analysis::errors::CheckErrors;
-    use clarity::vm::database::{ClarityBackingStore, STXBalance};
+    use clarity::vm::database::{ClarityBackingStore, STXBalance, StoreType};
     use clarity::vm::test_util::{TEST_BURN_STATE_DB, TEST_HEADER_DB};
+    use clarity::vm::tracer::{ExecutionTracer, TraceEntry};
     use clarity::vm::types::{StandardPrincipalData, Value};
+    use stacks_common::codec::StacksMessageCodec;
     use stacks_common::consts::CHAIN_ID_TESTNET;
     use stacks_common::types::chainstate::ConsensusHash;
     use stacks_common::types::sqlite::NO_PARAMS;
+    use stacks_common::util::hash::hex_bytes;
 
     use super::*;
-    use crate::chainstate::stacks::index::ClarityMarfTrieId;
+    use crate::chainstate::stacks::index::marf::MARFOpenOpts;
+    use crate::chainstate::stacks::index::node::TriePath;
+    use crate::chainstate::stacks::index::{ClarityMarfTrieId, MARFValue, TrieMerkleProof};
+    use crate::clarity_vm::database::audit::{MarfReadAuditConfig, MarfReadAuditLog};
+    use crate::clarity_vm::database::encryption::SideStoreCipher;
     use crate::clarity_vm::database::marf::MarfedKV;
     use crate::core::{PEER_VERSION_EPOCH_1_0, PEER_VERSION_EPOCH_2_0, PEER_VERSION_EPOCH_2_05};
 
@@ -1951,10 +2396,11 @@ mod tests {
     }
 
     #[test]
-    pub fn test_initialize_contract_tx_sender_contract_caller() {
+    pub fn analysis_cost_limit_test() {
         let marf = MarfedKV::temporary();
         let mut clarity_instance = ClarityInstance::new(false, CHAIN_ID_TESTNET, marf);
         let contract_identifier = QualifiedContractIdentifier::local("foo").unwrap();
+        let contract = "(define-public (foo (x int) (y int)) (ok (+ x y)))";
 
         clarity_instance
             .begin_test_genesis_block(
@@ -1965,6 +2411,12 @@ mod tests {
             )
             .commit_block();
 
+        // a budget too small to parse the contract makes the analysis fail
+        clarity_instance.set_analysis_cost_limit(Some(ExecutionCost {
+            runtime: 1,
+            ..ExecutionCost::zero()
+        }));
+
         {
             let mut conn = clarity_instance.begin_block(
                 &StacksBlockId([0 as u8; 32]),
@@ -1973,49 +2425,51 @@ mod tests {
                 &TEST_BURN_STATE_DB,
             );
 
-            // S1G2081040G2081040G2081040G208105NK8PE5 is the transient address
-            let contract = "
-                (begin 
-                    (asserts! (is-eq tx-sender 'S1G2081040G2081040G2081040G208105NK8PE5)
-                        (err tx-sender))
+            conn.as_transaction(|tx| {
+                tx.analyze_smart_contract(
+                    &contract_identifier,
+                    ClarityVersion::Clarity1,
+                    contract,
+                    ASTRules::PrecheckSize,
+                )
+            })
+            .unwrap_err();
 
-                    (asserts! (is-eq contract-caller 'S1G2081040G2081040G2081040G208105NK8PE5)
-                        (err contract-caller))
-                )";
+            conn.commit_block();
+        }
 
-            conn.as_transaction(|conn| {
-                let (ct_ast, ct_analysis) = conn
-                    .analyze_smart_contract(
-                        &contract_identifier,
-                        ClarityVersion::Clarity1,
-                        &contract,
-                        ASTRules::PrecheckSize,
-                    )
-                    .unwrap();
-                conn.initialize_smart_contract(
+        // with no analysis budget, the analysis is bounded by the block limit again
+        clarity_instance.set_analysis_cost_limit(None);
+
+        {
+            let mut conn = clarity_instance.begin_block(
+                &StacksBlockId([1 as u8; 32]),
+                &StacksBlockId([2 as u8; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            );
+
+            conn.as_transaction(|tx| {
+                tx.analyze_smart_contract(
                     &contract_identifier,
                     ClarityVersion::Clarity1,
-                    &ct_ast,
-                    &contract,
-                    None,
-                    |_, _| false,
+                    contract,
+                    ASTRules::PrecheckSize,
                 )
-                .unwrap();
-                conn.save_analysis(&contract_identifier, &ct_analysis)
-                    .unwrap();
-            });
+            })
+            .unwrap();
 
             conn.commit_block();
         }
     }
 
     #[test]
-    pub fn tx_rollback() {
+    pub fn state_diff_test() {
         let marf = MarfedKV::temporary();
         let mut clarity_instance = ClarityInstance::new(false, CHAIN_ID_TESTNET, marf);
-
         let contract_identifier = QualifiedContractIdentifier::local("foo").unwrap();
-        let contract = "(define-public (foo (x int) (y int)) (ok (+ x y)))";
+        let contract = "(define-data-var counter int 0)
+            (define-public (bump) (ok (var-set counter (+ 1 (var-get counter)))))";
 
         clarity_instance
             .begin_test_genesis_block(
@@ -2026,13 +2480,751 @@ mod tests {
             )
             .commit_block();
 
-        {
-            let mut conn = clarity_instance.begin_block(
-                &StacksBlockId([0 as u8; 32]),
-                &StacksBlockId([1 as u8; 32]),
-                &TEST_HEADER_DB,
-                &TEST_BURN_STATE_DB,
-            );
+        let mut conn = clarity_instance.begin_block(
+            &StacksBlockId([0 as u8; 32]),
+            &StacksBlockId([1 as u8; 32]),
+            &TEST_HEADER_DB,
+            &TEST_BURN_STATE_DB,
+        );
+        assert!(conn.take_state_diff().is_none());
+        conn.record_state_diff();
+
+        conn.as_transaction(|conn| {
+            let (ct_ast, ct_analysis) = conn
+                .analyze_smart_contract(
+                    &contract_identifier,
+                    ClarityVersion::Clarity1,
+                    &contract,
+                    ASTRules::PrecheckSize,
+                )
+                .unwrap();
+            conn.initialize_smart_contract(
+                &contract_identifier,
+                ClarityVersion::Clarity1,
+                &ct_ast,
+                &contract,
+                None,
+                |_, _| false,
+            )
+            .unwrap();
+            conn.save_analysis(&contract_identifier, &ct_analysis)
+                .unwrap();
+        });
+
+        let diff_after_deploy = conn.take_state_diff().unwrap();
+        assert!(diff_after_deploy
+            .metadata
+            .contains_key(&contract_identifier.to_string()));
+        conn.record_state_diff();
+
+        conn.as_transaction(|conn| {
+            conn.run_contract_call(
+                &StandardPrincipalData::transient().into(),
+                None,
+                &contract_identifier,
+                "bump",
+                &[],
+                |_, _| false,
+            )
+            .unwrap();
+        });
+
+        // only the data var changed, and the diff holds its final value
+        let diff = conn.take_state_diff().unwrap();
+        assert!(diff.metadata.is_empty());
+        assert_eq!(diff.data.len(), 1);
+        let (key, value) = diff.data.iter().next().unwrap();
+        assert!(key.contains("counter"));
+        assert_eq!(
+            Value::try_deserialize_hex_untyped(value).unwrap(),
+            Value::Int(1)
+        );
+        assert!(conn.take_state_diff().is_none());
+
+        conn.commit_block();
+    }
+
+    #[test]
+    pub fn test_read_replica() {
+        let test_name = "/tmp/clarity_test_read_replica";
+        if fs::metadata(test_name).is_ok() {
+            fs::remove_dir_all(test_name).unwrap();
+        }
+
+        // replicas never create a MARF
+        assert!(MarfedKV::open_replica(test_name, None, None).is_err());
+
+        let marf = MarfedKV::open(test_name, None, None).unwrap();
+        assert!(!marf.is_replica());
+        let mut clarity_instance = ClarityInstance::new(false, CHAIN_ID_TESTNET, marf);
+        let contract_identifier = QualifiedContractIdentifier::local("foo").unwrap();
+        let contract = "(define-data-var bar int 1)
+            (define-public (set-bar (x int)) (ok (var-set bar x)))";
+
+        clarity_instance
+            .begin_test_genesis_block(
+                &StacksBlockId::sentinel(),
+                &StacksBlockId([0 as u8; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            )
+            .commit_block();
+
+        {
+            let mut conn = clarity_instance.begin_block(
+                &StacksBlockId([0 as u8; 32]),
+                &StacksBlockId([1 as u8; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            );
+            conn.as_transaction(|conn| {
+                let (ct_ast, ct_analysis) = conn
+                    .analyze_smart_contract(
+                        &contract_identifier,
+                        ClarityVersion::Clarity1,
+                        &contract,
+                        ASTRules::PrecheckSize,
+                    )
+                    .unwrap();
+                conn.initialize_smart_contract(
+                    &contract_identifier,
+                    ClarityVersion::Clarity1,
+                    &ct_ast,
+                    &contract,
+                    None,
+                    |_, _| false,
+                )
+                .unwrap();
+                conn.save_analysis(&contract_identifier, &ct_analysis)
+                    .unwrap();
+            });
+            conn.commit_block();
+        }
+
+        let replica = MarfedKV::open_replica(test_name, None, None).unwrap();
+        assert!(replica.is_replica());
+        let mut replica_instance = ClarityInstance::new(false, CHAIN_ID_TESTNET, replica);
+
+        let read_bar = |instance: &mut ClarityInstance, block: u8| {
+            instance
+                .eval_read_only(
+                    &StacksBlockId([block; 32]),
+                    &TEST_HEADER_DB,
+                    &TEST_BURN_STATE_DB,
+                    &contract_identifier,
+                    "(var-get bar)",
+                    ASTRules::PrecheckSize,
+                )
+                .unwrap()
+        };
+        assert_eq!(read_bar(&mut replica_instance, 1), Value::Int(1));
+
+        // the writer keeps going while the replica is open
+        {
+            let mut conn = clarity_instance.begin_block(
+                &StacksBlockId([1 as u8; 32]),
+                &StacksBlockId([2 as u8; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            );
+            conn.as_transaction(|conn| {
+                conn.run_contract_call(
+                    &StandardPrincipalData::transient().into(),
+                    None,
+                    &contract_identifier,
+                    "set-bar",
+                    &[Value::Int(2)],
+                    |_, _| false,
+                )
+                .unwrap();
+            });
+
+            // uncommitted writes are not visible to the replica
+            assert_eq!(read_bar(&mut replica_instance, 1), Value::Int(1));
+            conn.commit_block();
+        }
+
+        // the replica still sees each block's own state, and sees the new block
+        assert_eq!(read_bar(&mut replica_instance, 1), Value::Int(1));
+        assert_eq!(read_bar(&mut replica_instance, 2), Value::Int(2));
+        assert_eq!(read_bar(&mut clarity_instance, 2), Value::Int(2));
+    }
+
+    #[test]
+    pub fn test_side_store_encryption() {
+        let test_name = "/tmp/clarity_test_side_store_encryption";
+        if fs::metadata(test_name).is_ok() {
+            fs::remove_dir_all(test_name).unwrap();
+        }
+
+        let key_1 = "01".repeat(32);
+        let key_2 = "02".repeat(32);
+        let marf_opts = |key: &str, previous: &[String]| {
+            let mut opts = MARFOpenOpts::default();
+            opts.side_store_cipher = Some(SideStoreCipher::from_hex(key, previous).unwrap());
+            Some(opts)
+        };
+        let contract_identifier = QualifiedContractIdentifier::local("foo").unwrap();
+        let contract = "(define-data-var bar int 1)";
+        let read_bar = |marf: MarfedKV| {
+            let mut clarity_instance = ClarityInstance::new(false, CHAIN_ID_TESTNET, marf);
+            clarity_instance
+                .eval_read_only(
+                    &StacksBlockId([1 as u8; 32]),
+                    &TEST_HEADER_DB,
+                    &TEST_BURN_STATE_DB,
+                    &contract_identifier,
+                    "(var-get bar)",
+                    ASTRules::PrecheckSize,
+                )
+                .unwrap()
+        };
+
+        let marf = MarfedKV::open(test_name, None, marf_opts(&key_1, &[])).unwrap();
+        let mut clarity_instance = ClarityInstance::new(false, CHAIN_ID_TESTNET, marf);
+        clarity_instance
+            .begin_test_genesis_block(
+                &StacksBlockId::sentinel(),
+                &StacksBlockId([0 as u8; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            )
+            .commit_block();
+        {
+            let mut conn = clarity_instance.begin_block(
+                &StacksBlockId([0 as u8; 32]),
+                &StacksBlockId([1 as u8; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            );
+            conn.as_transaction(|conn| {
+                let (ct_ast, ct_analysis) = conn
+                    .analyze_smart_contract(
+                        &contract_identifier,
+                        ClarityVersion::Clarity1,
+                        &contract,
+                        ASTRules::PrecheckSize,
+                    )
+                    .unwrap();
+                conn.initialize_smart_contract(
+                    &contract_identifier,
+                    ClarityVersion::Clarity1,
+                    &ct_ast,
+                    &contract,
+                    None,
+                    |_, _| false,
+                )
+                .unwrap();
+                conn.save_analysis(&contract_identifier, &ct_analysis)
+                    .unwrap();
+            });
+            conn.commit_block();
+        }
+
+        // nothing is stored in plaintext
+        let marf = clarity_instance.destroy();
+        for table in ["data_table", "metadata_table"] {
+            let plaintext: u32 = marf
+                .sql_conn()
+                .query_row(
+                    &format!("SELECT COUNT(*) FROM {table} WHERE value NOT LIKE 'enc1:%'"),
+                    NO_PARAMS,
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(plaintext, 0);
+        }
+        assert_eq!(read_bar(marf), Value::Int(1));
+
+        // encrypted values can't be read without the key
+        let mut marf = MarfedKV::open(test_name, None, None).unwrap();
+        assert!(marf
+            .begin_read_only(Some(&StacksBlockId([1 as u8; 32])))
+            .get_contract_hash(&contract_identifier)
+            .is_err());
+
+        // rotate to a new key
+        let mut marf =
+            MarfedKV::open(test_name, None, marf_opts(&key_2, &[key_1.clone()])).unwrap();
+        assert!(marf.reencrypt_side_store().unwrap() > 0);
+        assert_eq!(marf.reencrypt_side_store().unwrap(), 0);
+
+        // the old key is no longer needed
+        let marf = MarfedKV::open(test_name, None, marf_opts(&key_2, &[])).unwrap();
+        assert_eq!(read_bar(marf), Value::Int(1));
+    }
+
+    #[test]
+    pub fn test_marf_read_audit() {
+        let test_name = "/tmp/clarity_test_marf_read_audit";
+        if fs::metadata(test_name).is_ok() {
+            fs::remove_dir_all(test_name).unwrap();
+        }
+        let audit_path = PathBuf::from(test_name).join("audit.log");
+        let mut marf_opts = MARFOpenOpts::default();
+        marf_opts.read_audit = Some(MarfReadAuditConfig::new(audit_path.clone()));
+
+        let marf = MarfedKV::open(test_name, None, Some(marf_opts)).unwrap();
+        let mut clarity_instance = ClarityInstance::new(false, CHAIN_ID_TESTNET, marf);
+        let contract_identifier = QualifiedContractIdentifier::local("foo").unwrap();
+        let contract = "(define-data-var bar int 1)
+            (define-public (set-bar (x int)) (ok (var-set bar x)))";
+        let bar_key =
+            ClarityDatabase::make_key_for_trip(&contract_identifier, StoreType::Variable, "bar");
+
+        clarity_instance
+            .begin_test_genesis_block(
+                &StacksBlockId::sentinel(),
+                &StacksBlockId([0 as u8; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            )
+            .commit_block();
+        {
+            let mut conn = clarity_instance.begin_block(
+                &StacksBlockId([0 as u8; 32]),
+                &StacksBlockId([1 as u8; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            );
+            conn.as_transaction(|conn| {
+                let (ct_ast, ct_analysis) = conn
+                    .analyze_smart_contract(
+                        &contract_identifier,
+                        ClarityVersion::Clarity1,
+                        &contract,
+                        ASTRules::PrecheckSize,
+                    )
+                    .unwrap();
+                conn.initialize_smart_contract(
+                    &contract_identifier,
+                    ClarityVersion::Clarity1,
+                    &ct_ast,
+                    &contract,
+                    None,
+                    |_, _| false,
+                )
+                .unwrap();
+                conn.save_analysis(&contract_identifier, &ct_analysis)
+                    .unwrap();
+            });
+            conn.commit_block();
+        }
+        {
+            let mut conn = clarity_instance.begin_block(
+                &StacksBlockId([1 as u8; 32]),
+                &StacksBlockId([2 as u8; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            );
+            let read_bar = |conn: &mut ClarityBlockConnection| {
+                conn.as_transaction(|conn| {
+                    conn.eval_read_only(&contract_identifier, "(var-get bar)")
+                        .unwrap()
+                })
+            };
+            assert_eq!(read_bar(&mut conn), Value::Int(1));
+            conn.as_transaction(|conn| {
+                conn.run_contract_call(
+                    &contract_identifier.issuer.clone().into(),
+                    None,
+                    &contract_identifier,
+                    "set-bar",
+                    &[Value::Int(2)],
+                    |_, _| false,
+                )
+                .unwrap();
+            });
+            assert_eq!(read_bar(&mut conn), Value::Int(2));
+            conn.commit_block();
+        }
+
+        let records = MarfReadAuditLog::read_records(&audit_path).unwrap();
+        let bar_reads: Vec<_> = records
+            .iter()
+            .filter(|record| record.block == StacksBlockId([2 as u8; 32]) && record.key == bar_key)
+            .collect();
+        assert_eq!(bar_reads.len(), 2);
+
+        // the first read is proven in the parent block
+        let mut marf = clarity_instance.destroy();
+        let root_to_block = marf
+            .get_marf()
+            .borrow_storage_backend()
+            .read_root_to_block_table()
+            .unwrap();
+        let proven = bar_reads[0];
+        assert_eq!(proven.read_at, StacksBlockId([2 as u8; 32]));
+        assert_eq!(proven.proven_at, Some(StacksBlockId([1 as u8; 32])));
+        let proof: TrieMerkleProof<StacksBlockId> = TrieMerkleProof::consensus_deserialize(
+            &mut &hex_bytes(proven.proof.as_ref().unwrap()).unwrap()[..],
+        )
+        .unwrap();
+        let value = MARFValue::from_hex(proven.value_hash.as_ref().unwrap()).unwrap();
+        assert!(proof.verify(
+            &TriePath::from_key(&bar_key),
+            &value,
+            proven.root_hash.as_ref().unwrap(),
+            &root_to_block
+        ));
+
+        // the second read is of a value written by the block itself
+        let written = bar_reads[1];
+        assert_ne!(written.value_hash, proven.value_hash);
+        assert!(written.value_hash.is_some());
+        assert!(written.proof.is_none());
+    }
+
+    #[test]
+    pub fn test_prune_state() {
+        let test_name = "/tmp/clarity_test_prune_state";
+        if fs::metadata(test_name).is_ok() {
+            fs::remove_dir_all(test_name).unwrap();
+        }
+
+        let marf = MarfedKV::open(test_name, None, None).unwrap();
+        let mut clarity_instance = ClarityInstance::new(false, CHAIN_ID_TESTNET, marf);
+        let contract_identifier = QualifiedContractIdentifier::local("foo").unwrap();
+        let contract = "(define-data-var bar int 1)
+            (define-public (set-bar (x int)) (ok (var-set bar x)))";
+
+        clarity_instance
+            .begin_test_genesis_block(
+                &StacksBlockId::sentinel(),
+                &StacksBlockId([0 as u8; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            )
+            .commit_block();
+
+        {
+            let mut conn = clarity_instance.begin_block(
+                &StacksBlockId([0 as u8; 32]),
+                &StacksBlockId([1 as u8; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            );
+            conn.as_transaction(|conn| {
+                let (ct_ast, ct_analysis) = conn
+                    .analyze_smart_contract(
+                        &contract_identifier,
+                        ClarityVersion::Clarity1,
+                        &contract,
+                        ASTRules::PrecheckSize,
+                    )
+                    .unwrap();
+                conn.initialize_smart_contract(
+                    &contract_identifier,
+                    ClarityVersion::Clarity1,
+                    &ct_ast,
+                    &contract,
+                    None,
+                    |_, _| false,
+                )
+                .unwrap();
+                conn.save_analysis(&contract_identifier, &ct_analysis)
+                    .unwrap();
+            });
+            conn.commit_block();
+        }
+
+        // bar is set to the block's height in each block
+        for block in 2..=4 {
+            let mut conn = clarity_instance.begin_block(
+                &StacksBlockId([block - 1; 32]),
+                &StacksBlockId([block; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            );
+            conn.as_transaction(|conn| {
+                conn.run_contract_call(
+                    &StandardPrincipalData::transient().into(),
+                    None,
+                    &contract_identifier,
+                    "set-bar",
+                    &[Value::Int(block.into())],
+                    |_, _| false,
+                )
+                .unwrap();
+            });
+            conn.commit_block();
+        }
+
+        let tip = StacksBlockId([4; 32]);
+        let mut pruner = MarfedKV::open_unconfirmed(test_name, None, None).unwrap();
+        // nothing to prune yet
+        assert_eq!(pruner.prune_state(&tip, 10).unwrap(), None);
+        assert!(!clarity_instance
+            .is_pruned_at(&StacksBlockId([1; 32]))
+            .unwrap());
+
+        let summary = pruner.prune_state(&tip, 2).unwrap().unwrap();
+        assert_eq!(summary.pruned_height, 2);
+        // at least the value that bar only had at height 1
+        assert!(summary.values_deleted > 0);
+        assert_eq!(pruner.prune_state(&tip, 2).unwrap(), None);
+
+        assert!(clarity_instance
+            .is_pruned_at(&StacksBlockId([1; 32]))
+            .unwrap());
+        for block in 2..=4 {
+            assert!(!clarity_instance
+                .is_pruned_at(&StacksBlockId([block; 32]))
+                .unwrap());
+            let bar = clarity_instance
+                .eval_read_only(
+                    &StacksBlockId([block; 32]),
+                    &TEST_HEADER_DB,
+                    &TEST_BURN_STATE_DB,
+                    &contract_identifier,
+                    "(var-get bar)",
+                    ASTRules::PrecheckSize,
+                )
+                .unwrap();
+            assert_eq!(bar, Value::Int(block.into()));
+        }
+    }
+
+    #[test]
+    pub fn test_clarity_state_snapshot() {
+        let test_name = "/tmp/clarity_test_clarity_state_snapshot";
+        let import_name = "/tmp/clarity_test_clarity_state_snapshot_import";
+        for path in [test_name, import_name] {
+            if fs::metadata(path).is_ok() {
+                fs::remove_dir_all(path).unwrap();
+            }
+        }
+
+        let marf = MarfedKV::open(test_name, None, None).unwrap();
+        let mut clarity_instance = ClarityInstance::new(false, CHAIN_ID_TESTNET, marf);
+        let contract_identifier = QualifiedContractIdentifier::local("foo").unwrap();
+        let contract = "(define-data-var bar int 1)
+            (define-map baz int int)
+            (define-public (set-bar (x int)) (begin (map-set baz x x) (ok (var-set bar x))))";
+
+        clarity_instance
+            .begin_test_genesis_block(
+                &StacksBlockId::sentinel(),
+                &StacksBlockId([0 as u8; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            )
+            .commit_block();
+
+        {
+            let mut conn = clarity_instance.begin_block(
+                &StacksBlockId([0 as u8; 32]),
+                &StacksBlockId([1 as u8; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            );
+            conn.as_transaction(|conn| {
+                let (ct_ast, ct_analysis) = conn
+                    .analyze_smart_contract(
+                        &contract_identifier,
+                        ClarityVersion::Clarity1,
+                        &contract,
+                        ASTRules::PrecheckSize,
+                    )
+                    .unwrap();
+                conn.initialize_smart_contract(
+                    &contract_identifier,
+                    ClarityVersion::Clarity1,
+                    &ct_ast,
+                    &contract,
+                    None,
+                    |_, _| false,
+                )
+                .unwrap();
+                conn.save_analysis(&contract_identifier, &ct_analysis)
+                    .unwrap();
+                conn.run_contract_call(
+                    &StandardPrincipalData::transient().into(),
+                    None,
+                    &contract_identifier,
+                    "set-bar",
+                    &[Value::Int(2)],
+                    |_, _| false,
+                )
+                .unwrap();
+            });
+            conn.commit_block();
+        }
+
+        let mut exporter = MarfedKV::open(test_name, None, None).unwrap();
+        let snapshot = exporter.export_state(&StacksBlockId([1; 32])).unwrap();
+        assert_eq!(snapshot.block_height, 1);
+        let mut bytes = vec![];
+        snapshot.write_to(&mut bytes).unwrap();
+        // exports are deterministic
+        let mut reexported = vec![];
+        exporter
+            .export_state(&StacksBlockId([1; 32]))
+            .unwrap()
+            .write_to(&mut reexported)
+            .unwrap();
+        assert_eq!(bytes, reexported);
+        let snapshot = ClarityStateSnapshot::read_from(bytes.as_slice()).unwrap();
+
+        // import at a different height than the state was exported at
+        let marf = MarfedKV::open(import_name, None, None).unwrap();
+        let mut import_instance = ClarityInstance::new(false, CHAIN_ID_TESTNET, marf);
+        import_instance
+            .begin_test_genesis_block(
+                &StacksBlockId::sentinel(),
+                &StacksBlockId([0 as u8; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            )
+            .commit_block();
+        for block in 1..=2 {
+            let mut conn = import_instance.begin_block(
+                &StacksBlockId([block - 1; 32]),
+                &StacksBlockId([block; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            );
+            if block == 2 {
+                conn.import_state_snapshot(&snapshot).unwrap();
+            }
+            conn.commit_block();
+        }
+
+        let eval = |instance: &mut ClarityInstance, block: u8, code: &str| {
+            instance
+                .eval_read_only(
+                    &StacksBlockId([block; 32]),
+                    &TEST_HEADER_DB,
+                    &TEST_BURN_STATE_DB,
+                    &contract_identifier,
+                    code,
+                    ASTRules::PrecheckSize,
+                )
+                .unwrap()
+        };
+        assert_eq!(
+            eval(&mut import_instance, 2, "(var-get bar)"),
+            Value::Int(2)
+        );
+        assert_eq!(
+            eval(&mut import_instance, 2, "(map-get? baz 2)"),
+            Value::some(Value::Int(2)).unwrap()
+        );
+
+        // the imported contract can be called
+        {
+            let mut conn = import_instance.begin_block(
+                &StacksBlockId([2; 32]),
+                &StacksBlockId([3; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            );
+            conn.as_transaction(|conn| {
+                conn.run_contract_call(
+                    &StandardPrincipalData::transient().into(),
+                    None,
+                    &contract_identifier,
+                    "set-bar",
+                    &[Value::Int(3)],
+                    |_, _| false,
+                )
+                .unwrap();
+            });
+            conn.commit_block();
+        }
+        assert_eq!(
+            eval(&mut import_instance, 3, "(var-get bar)"),
+            Value::Int(3)
+        );
+        assert_eq!(
+            eval(&mut clarity_instance, 1, "(var-get bar)"),
+            Value::Int(2)
+        );
+    }
+
+    #[test]
+    pub fn test_initialize_contract_tx_sender_contract_caller() {
+        let marf = MarfedKV::temporary();
+        let mut clarity_instance = ClarityInstance::new(false, CHAIN_ID_TESTNET, marf);
+        let contract_identifier = QualifiedContractIdentifier::local("foo").unwrap();
+
+        clarity_instance
+            .begin_test_genesis_block(
+                &StacksBlockId::sentinel(),
+                &StacksBlockId([0 as u8; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            )
+            .commit_block();
+
+        {
+            let mut conn = clarity_instance.begin_block(
+                &StacksBlockId([0 as u8; 32]),
+                &StacksBlockId([1 as u8; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            );
+
+            // S1G2081040G2081040G2081040G208105NK8PE5 is the transient address
+            let contract = "
+                (begin 
+                    (asserts! (is-eq tx-sender 'S1G2081040G2081040G2081040G208105NK8PE5)
+                        (err tx-sender))
+
+                    (asserts! (is-eq contract-caller 'S1G2081040G2081040G2081040G208105NK8PE5)
+                        (err contract-caller))
+                )";
+
+            conn.as_transaction(|conn| {
+                let (ct_ast, ct_analysis) = conn
+                    .analyze_smart_contract(
+                        &contract_identifier,
+                        ClarityVersion::Clarity1,
+                        &contract,
+                        ASTRules::PrecheckSize,
+                    )
+                    .unwrap();
+                conn.initialize_smart_contract(
+                    &contract_identifier,
+                    ClarityVersion::Clarity1,
+                    &ct_ast,
+                    &contract,
+                    None,
+                    |_, _| false,
+                )
+                .unwrap();
+                conn.save_analysis(&contract_identifier, &ct_analysis)
+                    .unwrap();
+            });
+
+            conn.commit_block();
+        }
+    }
+
+    #[test]
+    pub fn tx_rollback() {
+        let marf = MarfedKV::temporary();
+        let mut clarity_instance = ClarityInstance::new(false, CHAIN_ID_TESTNET, marf);
+
+        let contract_identifier = QualifiedContractIdentifier::local("foo").unwrap();
+        let contract = "(define-public (foo (x int) (y int)) (ok (+ x y)))";
+
+        clarity_instance
+            .begin_test_genesis_block(
+                &StacksBlockId::sentinel(),
+                &StacksBlockId([0 as u8; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            )
+            .commit_block();
+
+        {
+            let mut conn = clarity_instance.begin_block(
+                &StacksBlockId([0 as u8; 32]),
+                &StacksBlockId([1 as u8; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            );
 
             {
                 let mut tx = conn.start_transaction_processing();
@@ -2681,6 +3873,204 @@ mod tests {
         }
     }
 
+    #[test]
+    pub fn test_cost_profile() {
+        let marf = MarfedKV::temporary();
+        let mut clarity_instance = ClarityInstance::new(false, CHAIN_ID_TESTNET, marf);
+        let sender = StandardPrincipalData::transient().into();
+        let foo_id = QualifiedContractIdentifier::local("foo").unwrap();
+        let bar_id = QualifiedContractIdentifier::local("bar").unwrap();
+
+        clarity_instance
+            .begin_test_genesis_block(
+                &StacksBlockId::sentinel(),
+                &StacksBlockId([0 as u8; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            )
+            .commit_block();
+
+        {
+            let mut conn = clarity_instance.begin_block(
+                &StacksBlockId([0 as u8; 32]),
+                &StacksBlockId([1 as u8; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            );
+            for (contract_id, contract) in [
+                (&foo_id, "(define-public (add-one (a int)) (ok (+ a 1)))"),
+                (
+                    &bar_id,
+                    "(define-public (add-two (a int))
+                       (contract-call? .foo add-one (+ a 1)))",
+                ),
+            ] {
+                conn.as_transaction(|conn| {
+                    let (ct_ast, ct_analysis) = conn
+                        .analyze_smart_contract(
+                            contract_id,
+                            ClarityVersion::Clarity1,
+                            contract,
+                            ASTRules::PrecheckSize,
+                        )
+                        .unwrap();
+                    conn.initialize_smart_contract(
+                        contract_id,
+                        ClarityVersion::Clarity1,
+                        &ct_ast,
+                        contract,
+                        None,
+                        |_, _| false,
+                    )
+                    .unwrap();
+                    conn.save_analysis(contract_id, &ct_analysis).unwrap();
+                });
+            }
+            conn.commit_block();
+        }
+
+        let mut conn = clarity_instance.begin_block(
+            &StacksBlockId([1 as u8; 32]),
+            &StacksBlockId([2 as u8; 32]),
+            &TEST_HEADER_DB,
+            &TEST_BURN_STATE_DB,
+        );
+        let profile = conn.as_transaction(|tx| {
+            tx.enable_cost_profile();
+            let (result, ..) = tx
+                .run_contract_call(
+                    &sender,
+                    None,
+                    &bar_id,
+                    "add-two",
+                    &[Value::Int(1)],
+                    |_, _| false,
+                )
+                .unwrap();
+            assert_eq!(result, Value::okay(Value::Int(3)).unwrap());
+            let profile = tx.take_cost_profile().unwrap();
+            assert!(tx.take_cost_profile().is_none());
+            assert!(profile.total().runtime <= tx.cost_so_far().runtime);
+            profile
+        });
+        conn.commit_block();
+
+        // both `+`s are charged
+        assert_eq!(profile.cost_functions["cost_add"].count, 2);
+        assert!(profile.cost_functions["cost_add"].cost.runtime > 0);
+
+        // each call is recorded once, and the outer call's cost includes the inner call's
+        let outer = &profile.contract_calls[&format!("{bar_id}.add-two")];
+        let inner = &profile.contract_calls[&format!("{foo_id}.add-one")];
+        assert_eq!(outer.count, 1);
+        assert_eq!(inner.count, 1);
+        assert!(inner.cost.runtime > 0);
+        assert!(outer.cost.runtime > inner.cost.runtime);
+        assert!(profile.peak_memory > 0);
+    }
+
+    #[test]
+    pub fn test_block_eval_hook() {
+        let marf = MarfedKV::temporary();
+        let mut clarity_instance = ClarityInstance::new(false, CHAIN_ID_TESTNET, marf);
+        let sender = StandardPrincipalData::transient().into();
+        let contract_id = QualifiedContractIdentifier::local("foo").unwrap();
+        let contract = "(define-public (add-one (a int)) (ok (+ a 1)))";
+
+        clarity_instance
+            .begin_test_genesis_block(
+                &StacksBlockId::sentinel(),
+                &StacksBlockId([0 as u8; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            )
+            .commit_block();
+
+        let mut conn = clarity_instance.begin_block(
+            &StacksBlockId([0 as u8; 32]),
+            &StacksBlockId([1 as u8; 32]),
+            &TEST_HEADER_DB,
+            &TEST_BURN_STATE_DB,
+        );
+        conn.as_transaction(|conn| {
+            let (ct_ast, ct_analysis) = conn
+                .analyze_smart_contract(
+                    &contract_id,
+                    ClarityVersion::Clarity1,
+                    contract,
+                    ASTRules::PrecheckSize,
+                )
+                .unwrap();
+            conn.initialize_smart_contract(
+                &contract_id,
+                ClarityVersion::Clarity1,
+                &ct_ast,
+                contract,
+                None,
+                |_, _| false,
+            )
+            .unwrap();
+            conn.save_analysis(&contract_id, &ct_analysis).unwrap();
+        });
+
+        let tracer = ExecutionTracer::new();
+        conn.set_eval_hook(Some(Box::new(tracer.clone())));
+        conn.enable_cost_profile();
+        conn.as_transaction(|tx| {
+            tx.run_contract_call(
+                &sender,
+                None,
+                &contract_id,
+                "add-one",
+                &[Value::Int(1)],
+                |_, _| false,
+            )
+            .unwrap();
+        });
+        conn.set_eval_hook(None);
+        let profile = conn.take_cost_profile().unwrap();
+
+        // transactions run after the hook is cleared aren't traced
+        conn.as_transaction(|tx| {
+            tx.run_contract_call(
+                &sender,
+                None,
+                &contract_id,
+                "add-one",
+                &[Value::Int(2)],
+                |_, _| false,
+            )
+            .unwrap();
+        });
+        conn.commit_block();
+
+        let entries = tracer.entries();
+        assert_eq!(
+            entries.first().unwrap(),
+            &TraceEntry::ContractCall {
+                depth: 0,
+                contract: contract_id.to_string(),
+                function: "add-one".into(),
+                args: vec!["1".into()],
+            }
+        );
+        assert!(matches!(
+            entries.last().unwrap(),
+            TraceEntry::ContractReturn { result: Some(result), .. } if result == "(ok 2)"
+        ));
+        assert_eq!(
+            entries
+                .iter()
+                .filter(|entry| matches!(entry, TraceEntry::ContractCall { .. }))
+                .count(),
+            1
+        );
+        assert_eq!(
+            profile.contract_calls[&format!("{contract_id}.add-one")].count,
+            1
+        );
+    }
+
     #[test]
     pub fn test_block_limit() {
         let marf = MarfedKV::temporary();
@@ -2871,4 +4261,227 @@ mod tests {
             conn.commit_block();
         }
     }
+
+    #[test]
+    pub fn test_eval_read_only_with_limits() {
+        let marf = MarfedKV::temporary();
+        let mut clarity_instance = ClarityInstance::new(false, CHAIN_ID_TESTNET, marf);
+        let contract_identifier = QualifiedContractIdentifier::local("foo").unwrap();
+        let contract = "
+            (define-read-only (expand)
+              (let ((list1 (list 1 2 3 4 5 6 7 8 9 10)))
+                (let ((list2 (concat list1 list1)))
+                  (let ((list3 (concat list2 list2)))
+                    (len (concat list3 list3))))))
+            ";
+
+        clarity_instance
+            .begin_test_genesis_block(
+                &StacksBlockId::sentinel(),
+                &StacksBlockId([0 as u8; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            )
+            .commit_block();
+        {
+            let mut conn = clarity_instance.begin_block(
+                &StacksBlockId([0 as u8; 32]),
+                &StacksBlockId([1 as u8; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            );
+            conn.as_transaction(|conn| {
+                let (ct_ast, ct_analysis) = conn
+                    .analyze_smart_contract(
+                        &contract_identifier,
+                        ClarityVersion::Clarity1,
+                        &contract,
+                        ASTRules::PrecheckSize,
+                    )
+                    .unwrap();
+                conn.initialize_smart_contract(
+                    &contract_identifier,
+                    ClarityVersion::Clarity1,
+                    &ct_ast,
+                    &contract,
+                    None,
+                    |_, _| false,
+                )
+                .unwrap();
+                conn.save_analysis(&contract_identifier, &ct_analysis)
+                    .unwrap();
+            });
+            conn.commit_block();
+        }
+
+        let mut eval_expand = |limits: &ExecutionLimits| {
+            clarity_instance.eval_read_only_with_limits(
+                &StacksBlockId([1 as u8; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+                &contract_identifier,
+                "(expand)",
+                ASTRules::PrecheckSize,
+                limits,
+            )
+        };
+
+        assert_eq!(
+            eval_expand(&ExecutionLimits::max_value()).unwrap(),
+            Value::UInt(80)
+        );
+
+        let mut limits = ExecutionLimits::max_value();
+        limits.memory = 100;
+        assert!(matches!(
+            eval_expand(&limits).unwrap_err(),
+            Error::Interpreter(InterpreterError::Unchecked(
+                CheckErrors::MemoryBalanceExceeded(_, 100)
+            ))
+        ));
+
+        // enough to parse the program, but not to evaluate it
+        let mut limits = ExecutionLimits::max_value();
+        limits.runtime = 100_000;
+        assert!(matches!(
+            eval_expand(&limits).unwrap_err(),
+            Error::CostError(_, limit) if limit.runtime == 100_000
+        ));
+    }
+
+    #[test]
+    pub fn test_simulate_contract_call() {
+        let marf = MarfedKV::temporary();
+        let mut clarity_instance = ClarityInstance::new(false, CHAIN_ID_TESTNET, marf);
+        let contract_identifier = QualifiedContractIdentifier::local("foo").unwrap();
+        let sender = PrincipalData::from(StandardPrincipalData::transient());
+        let contract = "
+            (define-data-var paused bool true)
+            (define-map allowed principal bool)
+            (define-public (deposit (amount uint))
+              (begin
+                (asserts! (not (var-get paused)) (err u1))
+                (asserts! (default-to false (map-get? allowed tx-sender)) (err u2))
+                (try! (stx-transfer? amount tx-sender (as-contract tx-sender)))
+                (var-set paused true)
+                (ok (stx-get-balance tx-sender))))
+            ";
+
+        clarity_instance
+            .begin_test_genesis_block(
+                &StacksBlockId::sentinel(),
+                &StacksBlockId([0 as u8; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            )
+            .commit_block();
+        {
+            let mut conn = clarity_instance.begin_block(
+                &StacksBlockId([0 as u8; 32]),
+                &StacksBlockId([1 as u8; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+            );
+            conn.as_transaction(|conn| {
+                let (ct_ast, ct_analysis) = conn
+                    .analyze_smart_contract(
+                        &contract_identifier,
+                        ClarityVersion::Clarity1,
+                        &contract,
+                        ASTRules::PrecheckSize,
+                    )
+                    .unwrap();
+                conn.initialize_smart_contract(
+                    &contract_identifier,
+                    ClarityVersion::Clarity1,
+                    &ct_ast,
+                    &contract,
+                    None,
+                    |_, _| false,
+                )
+                .unwrap();
+                conn.save_analysis(&contract_identifier, &ct_analysis)
+                    .unwrap();
+            });
+            conn.commit_block();
+        }
+
+        let mut call = SimulatedContractCall {
+            sender: sender.clone(),
+            sponsor: None,
+            contract: contract_identifier.clone(),
+            function: "deposit".into(),
+            args: vec![Value::UInt(100)],
+            overrides: vec![],
+            cost_limit: None,
+        };
+        let mut simulate = |call: &SimulatedContractCall| {
+            clarity_instance.simulate_contract_call(
+                &StacksBlockId([1 as u8; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB,
+                call,
+            )
+        };
+
+        let simulated = simulate(&call).unwrap();
+        assert_eq!(simulated.result, Value::error(Value::UInt(1)).unwrap());
+        assert!(simulated.events.is_empty());
+
+        call.overrides = vec![
+            StateOverride::DataVar {
+                contract: contract_identifier.clone(),
+                name: "paused".into(),
+                value: Value::Bool(false),
+            },
+            StateOverride::MapEntry {
+                contract: contract_identifier.clone(),
+                map: "allowed".into(),
+                key: sender.clone().into(),
+                value: Some(Value::Bool(true)),
+            },
+            StateOverride::StxBalance {
+                principal: sender.clone(),
+                amount: 1000,
+            },
+        ];
+        let simulated = simulate(&call).unwrap();
+        assert_eq!(simulated.result, Value::okay(Value::UInt(900)).unwrap());
+        assert_eq!(simulated.events.len(), 1);
+        assert!(simulated.cost.runtime > 0);
+
+        // overrides must match the contract's definitions
+        let mut bad_call = call.clone();
+        bad_call.overrides[0] = StateOverride::DataVar {
+            contract: contract_identifier.clone(),
+            name: "paused".into(),
+            value: Value::UInt(0),
+        };
+        assert!(simulate(&bad_call).is_err());
+
+        let mut limited_call = call.clone();
+        limited_call.cost_limit = Some(ExecutionCost {
+            runtime: 100,
+            ..ExecutionCost::max_value()
+        });
+        assert!(matches!(
+            simulate(&limited_call).unwrap_err(),
+            Error::CostError(..)
+        ));
+
+        // the simulations didn't change the chainstate
+        assert_eq!(
+            clarity_instance
+                .eval_read_only(
+                    &StacksBlockId([1 as u8; 32]),
+                    &TEST_HEADER_DB,
+                    &TEST_BURN_STATE_DB,
+                    &contract_identifier,
+                    "(var-get paused)",
+                    ASTRules::PrecheckSize,
+                )
+                .unwrap(),
+            Value::Bool(true)
+        );
+    }
 }
@@ -0,0 +1,388 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A corpus of contracts and call sequences modeled on real mainnet workloads (fungible
+//! tokens, NFT marketplaces, AMMs and name registries), with the names, constants and
+//! principals anonymized. It is used to check that interpreter, analysis and storage
+//! optimizations help representative workloads, and not just microbenchmarks.
+
+use std::time::{Duration, Instant};
+
+use clarity::vm::ast::ASTRules;
+use clarity::vm::clarity::TransactionConnection;
+use clarity::vm::test_util::{TEST_BURN_STATE_DB_21, TEST_HEADER_DB};
+use clarity::vm::types::{
+    PrincipalData, QualifiedContractIdentifier, ResponseData, StandardPrincipalData,
+};
+use clarity::vm::{ClarityVersion, ContractName, Value};
+use stacks_common::address::C32_ADDRESS_VERSION_TESTNET_SINGLESIG;
+use stacks_common::consts::CHAIN_ID_TESTNET;
+use stacks_common::types::chainstate::StacksBlockId;
+use stacks_common::util::hash::Hash160;
+
+use crate::chainstate::stacks::index::ClarityMarfTrieId;
+use crate::clarity_vm::clarity::ClarityInstance;
+use crate::clarity_vm::database::marf::MarfedKV;
+
+/// A public or read-only function call made against a corpus contract
+pub struct CorpusCall {
+    /// Index of the calling principal (see `corpus_principal`)
+    pub sender: u8,
+    pub function: &'static str,
+    pub args: fn() -> Vec<Value>,
+}
+
+/// A contract in the corpus, along with the calls that exercise it
+pub struct CorpusContract {
+    pub name: &'static str,
+    pub clarity_version: ClarityVersion,
+    pub source: &'static str,
+    pub calls: &'static [CorpusCall],
+}
+
+/// Time spent on one corpus contract, summed over every iteration of a run
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorpusReport {
+    pub name: &'static str,
+    pub analysis: Duration,
+    pub initialization: Duration,
+    pub calls: Duration,
+    pub call_count: usize,
+}
+
+/// Anonymized principal used by the corpus. Principal 0 deploys every contract.
+pub fn corpus_principal(index: u8) -> StandardPrincipalData {
+    StandardPrincipalData(
+        C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+        [index.wrapping_add(1); 20],
+    )
+}
+
+fn principal_arg(index: u8) -> Value {
+    Value::Principal(PrincipalData::Standard(corpus_principal(index)))
+}
+
+fn ascii_arg(s: &str) -> Value {
+    Value::string_ascii_from_bytes(s.as_bytes().to_vec()).expect("FATAL: invalid corpus string")
+}
+
+fn buff_arg(bytes: &[u8]) -> Value {
+    Value::buff_from(bytes.to_vec()).expect("FATAL: invalid corpus buffer")
+}
+
+const REGISTRY_SALT: &[u8] = &[0x5a; 16];
+
+/// The preorder hash `registry.clar` expects for `name`
+fn registry_preorder_arg(name: &str) -> Value {
+    let mut preimage = ascii_arg(name)
+        .serialize_to_vec()
+        .expect("FATAL: failed to serialize corpus name");
+    preimage.extend_from_slice(REGISTRY_SALT);
+    buff_arg(Hash160::from_data(&preimage).as_bytes())
+}
+
+/// The corpus, in deployment order
+pub static CORPUS: &[CorpusContract] = &[
+    CorpusContract {
+        name: "token",
+        clarity_version: ClarityVersion::Clarity1,
+        source: include_str!("contracts/token.clar"),
+        calls: &[
+            CorpusCall {
+                sender: 0,
+                function: "mint",
+                args: || vec![Value::UInt(1_000_000), principal_arg(1)],
+            },
+            CorpusCall {
+                sender: 0,
+                function: "mint",
+                args: || vec![Value::UInt(1_000_000), principal_arg(2)],
+            },
+            CorpusCall {
+                sender: 1,
+                function: "transfer",
+                args: || {
+                    vec![
+                        Value::UInt(2_500),
+                        principal_arg(1),
+                        principal_arg(2),
+                        Value::none(),
+                    ]
+                },
+            },
+            CorpusCall {
+                sender: 2,
+                function: "transfer",
+                args: || {
+                    vec![
+                        Value::UInt(100),
+                        principal_arg(2),
+                        principal_arg(1),
+                        Value::some(buff_arg(&[0x01; 34])).expect("FATAL: invalid corpus memo"),
+                    ]
+                },
+            },
+            CorpusCall {
+                sender: 1,
+                function: "get-balance",
+                args: || vec![principal_arg(1)],
+            },
+            CorpusCall {
+                sender: 1,
+                function: "get-total-supply",
+                args: Vec::new,
+            },
+        ],
+    },
+    CorpusContract {
+        name: "nft-market",
+        clarity_version: ClarityVersion::Clarity1,
+        source: include_str!("contracts/nft-market.clar"),
+        calls: &[
+            CorpusCall {
+                sender: 0,
+                function: "mint",
+                args: || vec![principal_arg(1)],
+            },
+            CorpusCall {
+                sender: 0,
+                function: "mint",
+                args: || vec![principal_arg(1)],
+            },
+            CorpusCall {
+                sender: 1,
+                function: "list-token",
+                args: || vec![Value::UInt(1), Value::UInt(500)],
+            },
+            CorpusCall {
+                sender: 2,
+                function: "purchase",
+                args: || vec![Value::UInt(1), Value::UInt(500)],
+            },
+            CorpusCall {
+                sender: 1,
+                function: "list-token",
+                args: || vec![Value::UInt(2), Value::UInt(750)],
+            },
+            CorpusCall {
+                sender: 1,
+                function: "unlist-token",
+                args: || vec![Value::UInt(2)],
+            },
+            CorpusCall {
+                sender: 2,
+                function: "get-owner",
+                args: || vec![Value::UInt(1)],
+            },
+        ],
+    },
+    CorpusContract {
+        name: "swap",
+        clarity_version: ClarityVersion::Clarity2,
+        source: include_str!("contracts/swap.clar"),
+        calls: &[
+            CorpusCall {
+                sender: 1,
+                function: "faucet",
+                args: || vec![Value::UInt(1_000_000), Value::UInt(1_000_000)],
+            },
+            CorpusCall {
+                sender: 1,
+                function: "add-liquidity",
+                args: || vec![Value::UInt(100_000), Value::UInt(400_000)],
+            },
+            CorpusCall {
+                sender: 2,
+                function: "faucet",
+                args: || vec![Value::UInt(10_000), Value::UInt(10_000)],
+            },
+            CorpusCall {
+                sender: 2,
+                function: "swap-x-for-y",
+                args: || vec![Value::UInt(1_000), Value::UInt(1)],
+            },
+            CorpusCall {
+                sender: 2,
+                function: "swap-y-for-x",
+                args: || vec![Value::UInt(4_000), Value::UInt(1)],
+            },
+            CorpusCall {
+                sender: 1,
+                function: "add-liquidity",
+                args: || vec![Value::UInt(1_000), Value::UInt(4_000)],
+            },
+            CorpusCall {
+                sender: 2,
+                function: "get-reserves",
+                args: Vec::new,
+            },
+        ],
+    },
+    CorpusContract {
+        name: "registry",
+        clarity_version: ClarityVersion::Clarity2,
+        source: include_str!("contracts/registry.clar"),
+        calls: &[
+            CorpusCall {
+                sender: 1,
+                function: "preorder",
+                args: || vec![registry_preorder_arg("corpus-name-1")],
+            },
+            CorpusCall {
+                sender: 1,
+                function: "register",
+                args: || {
+                    vec![
+                        ascii_arg("corpus-name-1"),
+                        buff_arg(REGISTRY_SALT),
+                        buff_arg(&[0x11; 20]),
+                    ]
+                },
+            },
+            CorpusCall {
+                sender: 1,
+                function: "update-zonefile",
+                args: || vec![ascii_arg("corpus-name-1"), buff_arg(&[0x22; 20])],
+            },
+            CorpusCall {
+                sender: 2,
+                function: "resolve",
+                args: || vec![ascii_arg("corpus-name-1")],
+            },
+        ],
+    },
+];
+
+/// Deploy and exercise the whole corpus `iterations` times, each time in a fresh chainstate,
+///  and report the time spent per contract. Fails if any deployment or call fails, or if any
+///  call returns an `err` response, since the timings would then not be representative.
+pub fn run_corpus(iterations: usize) -> Result<Vec<CorpusReport>, String> {
+    let mut reports: Vec<_> = CORPUS
+        .iter()
+        .map(|contract| CorpusReport {
+            name: contract.name,
+            analysis: Duration::ZERO,
+            initialization: Duration::ZERO,
+            calls: Duration::ZERO,
+            call_count: 0,
+        })
+        .collect();
+
+    for _ in 0..iterations {
+        let marf = MarfedKV::temporary();
+        let mut clarity_instance = ClarityInstance::new(false, CHAIN_ID_TESTNET, marf);
+        clarity_instance
+            .begin_test_genesis_block_2_1(
+                &StacksBlockId::sentinel(),
+                &StacksBlockId([0; 32]),
+                &TEST_HEADER_DB,
+                &TEST_BURN_STATE_DB_21,
+            )
+            .commit_block();
+
+        let mut conn = clarity_instance.begin_block(
+            &StacksBlockId([0; 32]),
+            &StacksBlockId([1; 32]),
+            &TEST_HEADER_DB,
+            &TEST_BURN_STATE_DB_21,
+        );
+
+        for (contract, report) in CORPUS.iter().zip(reports.iter_mut()) {
+            let contract_name = ContractName::try_from(contract.name.to_string())
+                .map_err(|e| format!("Invalid corpus contract name {}: {e:?}", contract.name))?;
+            let contract_id = QualifiedContractIdentifier::new(corpus_principal(0), contract_name);
+
+            conn.as_transaction(|tx| {
+                let start = Instant::now();
+                let (ast, analysis) = tx
+                    .analyze_smart_contract(
+                        &contract_id,
+                        contract.clarity_version,
+                        contract.source,
+                        ASTRules::PrecheckSize,
+                    )
+                    .map_err(|e| format!("Failed to analyze {contract_id}: {e:?}"))?;
+                report.analysis += start.elapsed();
+
+                let start = Instant::now();
+                tx.initialize_smart_contract(
+                    &contract_id,
+                    contract.clarity_version,
+                    &ast,
+                    contract.source,
+                    None,
+                    |_, _| false,
+                )
+                .map_err(|e| format!("Failed to initialize {contract_id}: {e:?}"))?;
+                tx.save_analysis(&contract_id, &analysis)
+                    .map_err(|e| format!("Failed to save analysis of {contract_id}: {e:?}"))?;
+                report.initialization += start.elapsed();
+                Ok::<_, String>(())
+            })?;
+
+            for call in contract.calls.iter() {
+                let sender = PrincipalData::Standard(corpus_principal(call.sender));
+                let args = (call.args)();
+
+                let start = Instant::now();
+                let (result, ..) = conn
+                    .as_transaction(|tx| {
+                        tx.run_contract_call(
+                            &sender,
+                            None,
+                            &contract_id,
+                            call.function,
+                            &args,
+                            |_, _| false,
+                        )
+                    })
+                    .map_err(|e| {
+                        format!("Failed to call {contract_id}.{}: {e:?}", call.function)
+                    })?;
+                report.calls += start.elapsed();
+                report.call_count += 1;
+
+                if let Value::Response(ResponseData {
+                    committed: false, ..
+                }) = result
+                {
+                    return Err(format!(
+                        "Call to {contract_id}.{} returned {result}",
+                        call.function
+                    ));
+                }
+            }
+        }
+
+        conn.commit_block();
+    }
+
+    Ok(reports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corpus_runs_cleanly() {
+        let reports = run_corpus(1).unwrap();
+        assert_eq!(reports.len(), CORPUS.len());
+        for (report, contract) in reports.iter().zip(CORPUS.iter()) {
+            assert_eq!(report.name, contract.name);
+            assert_eq!(report.call_count, contract.calls.len());
+        }
+    }
+}
@@ -0,0 +1,231 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Optional audit log of the Clarity state read while processing blocks.
+//!
+//! When `MARFOpenOpts::read_audit` is set, every `get` that `MarfedKV` serves to a block being
+//! processed is appended to the audit file as a `MarfReadRecord`, one JSON object per line,
+//! together with a MARF proof of the value that was read.  This makes it possible to check,
+//! after the fact, exactly which state a node read when it accepted a block: each proof can be
+//! verified against the trie root hash of the block it was made for.
+//!
+//! The block being processed has no root hash until it is sealed, so reads from it are proven
+//! against its parent instead.  A value that was written by the block itself has no such proof,
+//! and is recorded without one.  Keys that were not found are recorded without a proof too (the
+//! MARF has no proofs of absence).
+//!
+//! The file is rotated once it would grow past `max_file_size`: `audit.log` becomes
+//! `audit.log.1`, `audit.log.1` becomes `audit.log.2`, and so on, keeping at most `max_files`
+//! files in total.
+
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use lazy_static::lazy_static;
+use stacks_common::types::chainstate::{StacksBlockId, TrieHash};
+
+/// Default size at which the audit file is rotated, in bytes
+pub const DEFAULT_READ_AUDIT_MAX_FILE_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Default number of audit files to keep, including the one being written
+pub const DEFAULT_READ_AUDIT_MAX_FILES: u32 = 8;
+
+lazy_static! {
+    /// Every `MarfedKV` that audits to the same path shares one log, so that rotation
+    ///  isn't raced between them
+    static ref READ_AUDIT_LOGS: Mutex<HashMap<PathBuf, Arc<Mutex<MarfReadAuditLog>>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Where and how to write the read audit log
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarfReadAuditConfig {
+    pub path: PathBuf,
+    /// Rotate the file once it would grow past this many bytes
+    pub max_file_size: u64,
+    /// Number of files to keep, including the one being written
+    pub max_files: u32,
+}
+
+impl MarfReadAuditConfig {
+    pub fn new(path: PathBuf) -> MarfReadAuditConfig {
+        MarfReadAuditConfig {
+            path,
+            max_file_size: DEFAULT_READ_AUDIT_MAX_FILE_SIZE,
+            max_files: DEFAULT_READ_AUDIT_MAX_FILES,
+        }
+    }
+}
+
+/// One read from the MARF while processing a block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarfReadRecord {
+    /// The block being processed
+    pub block: StacksBlockId,
+    /// The block whose state was read (differs from `block` for `at-block` reads)
+    pub read_at: StacksBlockId,
+    pub key: String,
+    /// Hex of the MARF value that was read (the hash of the side-store value), if the key was
+    /// found
+    pub value_hash: Option<String>,
+    /// The block that `proof` proves `value_hash` in
+    pub proven_at: Option<StacksBlockId>,
+    /// The trie root hash of `proven_at`, which `proof` verifies against
+    pub root_hash: Option<TrieHash>,
+    /// Hex of the serialized `TrieMerkleProof`
+    pub proof: Option<String>,
+}
+
+/// An append-only, rotating file of `MarfReadRecord`s
+#[derive(Debug)]
+pub struct MarfReadAuditLog {
+    config: MarfReadAuditConfig,
+    file: File,
+    /// Bytes written to `file` so far
+    size: u64,
+}
+
+impl MarfReadAuditLog {
+    pub fn open(config: MarfReadAuditConfig) -> io::Result<MarfReadAuditLog> {
+        if let Some(dir) = config.path.parent() {
+            if !dir.as_os_str().is_empty() {
+                fs::create_dir_all(dir)?;
+            }
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&config.path)?;
+        let size = file.metadata()?.len();
+        Ok(MarfReadAuditLog { config, file, size })
+    }
+
+    /// Get the log for `config.path`, opening it if no other store in this process has yet
+    pub fn shared(config: &MarfReadAuditConfig) -> io::Result<Arc<Mutex<MarfReadAuditLog>>> {
+        let mut logs = READ_AUDIT_LOGS
+            .lock()
+            .expect("FATAL: read audit log registry poisoned");
+        if let Some(log) = logs.get(&config.path) {
+            return Ok(log.clone());
+        }
+        let log = Arc::new(Mutex::new(MarfReadAuditLog::open(config.clone())?));
+        logs.insert(config.path.clone(), log.clone());
+        Ok(log)
+    }
+
+    /// The path of the `index`-th rotated file (0 is the file being written)
+    pub fn rotated_path(path: &Path, index: u32) -> PathBuf {
+        if index == 0 {
+            return path.to_path_buf();
+        }
+        let mut rotated = path.as_os_str().to_owned();
+        rotated.push(format!(".{index}"));
+        PathBuf::from(rotated)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        let path = &self.config.path;
+        let max_files = self.config.max_files.max(1);
+        for index in (0..max_files).rev() {
+            let from = Self::rotated_path(path, index);
+            if !from.exists() {
+                continue;
+            }
+            if index + 1 >= max_files {
+                fs::remove_file(&from)?;
+            } else {
+                fs::rename(&from, Self::rotated_path(path, index + 1))?;
+            }
+        }
+        self.file = OpenOptions::new().create(true).append(true).open(path)?;
+        self.size = 0;
+        Ok(())
+    }
+
+    pub fn record(&mut self, record: &MarfReadRecord) -> io::Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        let len = u64::try_from(line.len()).unwrap_or(u64::MAX);
+        if self.size > 0 && self.size.saturating_add(len) > self.config.max_file_size {
+            self.rotate()?;
+        }
+        self.file.write_all(&line)?;
+        self.size = self.size.saturating_add(len);
+        Ok(())
+    }
+
+    /// Read back the records in a (possibly rotated) audit file
+    pub fn read_records(path: &Path) -> io::Result<Vec<MarfReadRecord>> {
+        fs::read_to_string(path)?
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_str(line).map_err(io::Error::from))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(key: &str) -> MarfReadRecord {
+        MarfReadRecord {
+            block: StacksBlockId([1; 32]),
+            read_at: StacksBlockId([1; 32]),
+            key: key.to_string(),
+            value_hash: None,
+            proven_at: None,
+            root_hash: None,
+            proof: None,
+        }
+    }
+
+    #[test]
+    fn test_read_audit_log_rotation() {
+        let dir = std::env::temp_dir().join(format!(
+            "test_read_audit_log_rotation-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("audit.log");
+
+        let record_len = serde_json::to_vec(&record("key-0")).unwrap().len() as u64 + 1;
+        let mut config = MarfReadAuditConfig::new(path.clone());
+        config.max_file_size = 2 * record_len;
+        config.max_files = 3;
+        let mut log = MarfReadAuditLog::open(config).unwrap();
+        for i in 0..7 {
+            log.record(&record(&format!("key-{i}"))).unwrap();
+        }
+
+        let keys = |index| -> Vec<String> {
+            MarfReadAuditLog::read_records(&MarfReadAuditLog::rotated_path(&path, index))
+                .unwrap()
+                .into_iter()
+                .map(|record| record.key)
+                .collect()
+        };
+        assert_eq!(keys(0), vec!["key-6"]);
+        assert_eq!(keys(1), vec!["key-4", "key-5"]);
+        assert_eq!(keys(2), vec!["key-2", "key-3"]);
+        assert!(!MarfReadAuditLog::rotated_path(&path, 3).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
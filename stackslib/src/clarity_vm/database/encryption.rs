@@ -0,0 +1,481 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Optional encryption at rest for the Clarity side-store.
+//!
+//! When a `SideStoreCipher` is configured, `MarfedKV` encrypts every value it writes to the
+//! side-store's `data_table` and `metadata_table` with AES-256-GCM, and decrypts values as it
+//! reads them.  Row keys are left as-is: data keys are value hashes, and metadata keys are
+//! contract identifiers and metadata names.  Each value is bound to its row key, so encrypted
+//! values can't be moved between rows.
+//!
+//! An encrypted value is stored as `enc1:<key id>:<hex nonce and ciphertext>`, where the key id
+//! identifies which key encrypted it.  This lets a side-store hold values encrypted under several
+//! keys at once, which is what makes key rotation possible: the node is restarted with a new
+//! current key and the old key listed as a previous key, and `reencrypt_side_store_batch()`
+//! rewrites the old values under the new key.  Plaintext values, e.g. from before encryption was
+//! turned on, are read as-is and rewritten the same way, unless the cipher requires encrypted
+//! values, in which case reading a plaintext value is an error.  Once the side-store has been
+//! fully re-encrypted, requiring encrypted values stops plaintext that was written to the
+//! side-store behind the node's back from being trusted.
+
+use std::fmt;
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use clarity::vm::errors::{IncomparableError, InterpreterError, InterpreterResult};
+use rand::RngCore;
+use rusqlite::{params, Connection};
+use stacks_common::util::hash::{hex_bytes, to_hex, Sha256Sum};
+
+/// Length of a side-store encryption key, in bytes
+pub const SIDE_STORE_KEY_LEN: usize = 32;
+
+/// Length of an AES-GCM nonce, in bytes
+const NONCE_LEN: usize = 12;
+
+/// Prefix of every encrypted side-store value
+const ENCRYPTED_VALUE_PREFIX: &str = "enc1:";
+
+#[derive(Clone, PartialEq)]
+struct SideStoreKey {
+    /// hex of the first 4 bytes of the key's sha256
+    id: String,
+    key: [u8; SIDE_STORE_KEY_LEN],
+}
+
+impl SideStoreKey {
+    fn from_hex(key_hex: &str) -> Result<SideStoreKey, String> {
+        let bytes = hex_bytes(key_hex)
+            .map_err(|_| "Side-store encryption key is not a hex string".to_string())?;
+        let key: [u8; SIDE_STORE_KEY_LEN] = bytes
+            .try_into()
+            .map_err(|_| format!("Side-store encryption key must be {SIDE_STORE_KEY_LEN} bytes"))?;
+        let id = to_hex(&Sha256Sum::from_data(&key).as_bytes()[0..4]);
+        Ok(SideStoreKey { id, key })
+    }
+
+    fn aead(&self) -> Aes256Gcm {
+        Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&self.key))
+    }
+}
+
+/// The keys used to encrypt and decrypt side-store values.  Values are always encrypted with the
+/// current key; the previous keys are only used to read values that haven't been re-encrypted
+/// yet.
+#[derive(Clone, PartialEq)]
+pub struct SideStoreCipher {
+    current: SideStoreKey,
+    previous: Vec<SideStoreKey>,
+    /// Refuse to read plaintext values
+    require_encrypted: bool,
+}
+
+/// Never log the keys themselves
+impl fmt::Debug for SideStoreCipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SideStoreCipher")
+            .field("current", &self.current.id)
+            .field(
+                "previous",
+                &self.previous.iter().map(|k| &k.id).collect::<Vec<_>>(),
+            )
+            .field("require_encrypted", &self.require_encrypted)
+            .finish()
+    }
+}
+
+impl SideStoreCipher {
+    /// Instantiate from hex-encoded 32-byte keys
+    pub fn from_hex(current: &str, previous: &[String]) -> Result<SideStoreCipher, String> {
+        let current = SideStoreKey::from_hex(current)?;
+        let mut keys: Vec<SideStoreKey> = vec![];
+        for key_hex in previous.iter() {
+            let key = SideStoreKey::from_hex(key_hex)?;
+            if key.id == current.id || keys.iter().any(|k| k.id == key.id) {
+                return Err(format!("Duplicate side-store encryption key {}", &key.id));
+            }
+            keys.push(key);
+        }
+        Ok(SideStoreCipher {
+            current,
+            previous: keys,
+            require_encrypted: false,
+        })
+    }
+
+    /// Refuse to read plaintext values, once the side-store has been fully re-encrypted
+    pub fn with_require_encrypted(mut self, require_encrypted: bool) -> SideStoreCipher {
+        self.require_encrypted = require_encrypted;
+        self
+    }
+
+    /// Are plaintext values refused?
+    pub fn requires_encrypted(&self) -> bool {
+        self.require_encrypted
+    }
+
+    /// Identifier of the key that new values are encrypted with
+    pub fn key_id(&self) -> &str {
+        &self.current.id
+    }
+
+    fn get_key(&self, key_id: &str) -> Option<&SideStoreKey> {
+        std::iter::once(&self.current)
+            .chain(self.previous.iter())
+            .find(|key| key.id == key_id)
+    }
+
+    /// Encrypt `value`, to be stored under `row_key`, with the current key
+    pub fn encrypt(&self, row_key: &str, value: &str) -> InterpreterResult<String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let payload = Payload {
+            msg: value.as_bytes(),
+            aad: row_key.as_bytes(),
+        };
+        let ciphertext = self
+            .current
+            .aead()
+            .encrypt(Nonce::from_slice(&nonce), payload)
+            .map_err(|_| {
+                InterpreterError::DBError(format!("Failed to encrypt side-store value {row_key}"))
+            })?;
+        Ok(format!(
+            "{ENCRYPTED_VALUE_PREFIX}{}:{}{}",
+            &self.current.id,
+            to_hex(&nonce),
+            to_hex(&ciphertext)
+        ))
+    }
+
+    /// Decrypt `value`, stored under `row_key`, which must be encrypted with one of this
+    /// cipher's keys
+    pub fn decrypt(&self, row_key: &str, value: &str) -> InterpreterResult<String> {
+        let fail = |reason: &str| {
+            InterpreterError::DBError(format!(
+                "Failed to decrypt side-store value {row_key}: {reason}"
+            ))
+        };
+        let (key_id, sealed) = value
+            .strip_prefix(ENCRYPTED_VALUE_PREFIX)
+            .and_then(|rest| rest.split_once(':'))
+            .ok_or_else(|| fail("not an encrypted value"))?;
+        let key = self
+            .get_key(key_id)
+            .ok_or_else(|| fail(&format!("unknown key {key_id}")))?;
+        let sealed = hex_bytes(sealed).map_err(|_| fail("not a hex string"))?;
+        if sealed.len() < NONCE_LEN {
+            return Err(fail("too short").into());
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let payload = Payload {
+            msg: ciphertext,
+            aad: row_key.as_bytes(),
+        };
+        let plaintext = key
+            .aead()
+            .decrypt(Nonce::from_slice(nonce), payload)
+            .map_err(|_| fail("authentication failed"))?;
+        String::from_utf8(plaintext).map_err(|_| fail("invalid utf8").into())
+    }
+
+    /// Is `value` encrypted with the current key?
+    pub fn is_current(&self, value: &str) -> bool {
+        matches!(
+            value
+                .strip_prefix(ENCRYPTED_VALUE_PREFIX)
+                .and_then(|rest| rest.split_once(':')),
+            Some((key_id, _)) if key_id == self.current.id
+        )
+    }
+}
+
+/// Prepare `value` to be stored under `row_key`: encrypt it if there's a cipher
+pub fn encrypt_side_store_value(
+    cipher: Option<&SideStoreCipher>,
+    row_key: &str,
+    value: &str,
+) -> InterpreterResult<String> {
+    match cipher {
+        Some(cipher) => cipher.encrypt(row_key, value),
+        None => Ok(value.to_string()),
+    }
+}
+
+/// Recover the value stored under `row_key`.  Plaintext values are returned as-is, unless the
+/// cipher requires encrypted values.  Encrypted values can't be read without a cipher holding
+/// their key.
+pub fn decrypt_side_store_value(
+    cipher: Option<&SideStoreCipher>,
+    row_key: &str,
+    value: String,
+) -> InterpreterResult<String> {
+    if !value.starts_with(ENCRYPTED_VALUE_PREFIX) {
+        if matches!(cipher, Some(cipher) if cipher.requires_encrypted()) {
+            return Err(InterpreterError::DBError(format!(
+                "Side-store value {row_key} is not encrypted, but encrypted values are required"
+            ))
+            .into());
+        }
+        return Ok(value);
+    }
+    match cipher {
+        Some(cipher) => cipher.decrypt(row_key, &value),
+        None => Err(InterpreterError::DBError(format!(
+            "Side-store value {row_key} is encrypted, but no side-store encryption key is configured"
+        ))
+        .into()),
+    }
+}
+
+/// The side-store tables whose values are encrypted
+pub const ENCRYPTED_SIDE_STORE_TABLES: &[&str] = &["data_table", "metadata_table"];
+
+/// Re-encrypt up to `limit` values of `table` that aren't encrypted with `cipher`'s current key,
+/// starting after row `after_rowid`.
+/// Returns the number of values rewritten, and the row to continue from, or None if there are
+/// no more rows.
+pub fn reencrypt_side_store_batch(
+    conn: &Connection,
+    cipher: &SideStoreCipher,
+    table: &str,
+    after_rowid: i64,
+    limit: u32,
+) -> InterpreterResult<(u64, Option<i64>)> {
+    let sql_err = |err| InterpreterError::SqliteError(IncomparableError { err });
+    let rows = {
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT rowid, key, value FROM {table} WHERE rowid > ?1 ORDER BY rowid LIMIT ?2"
+            ))
+            .map_err(sql_err)?;
+        let rows = stmt
+            .query_map(params![after_rowid, limit], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })
+            .map_err(sql_err)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(sql_err)?;
+        rows
+    };
+
+    let last_rowid = rows.last().map(|(rowid, ..)| *rowid);
+    let mut rewritten = 0;
+    for (rowid, key, value) in rows.into_iter() {
+        if cipher.is_current(&value) {
+            continue;
+        }
+        // plaintext is migrated even if the cipher requires encrypted values
+        let plaintext = if value.starts_with(ENCRYPTED_VALUE_PREFIX) {
+            cipher.decrypt(&key, &value)?
+        } else {
+            value
+        };
+        let value = cipher.encrypt(&key, &plaintext)?;
+        conn.execute(
+            &format!("UPDATE {table} SET value = ?1 WHERE rowid = ?2"),
+            params![value, rowid],
+        )
+        .map_err(sql_err)?;
+        rewritten += 1;
+    }
+    Ok((rewritten, last_rowid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY_1: &str = "0101010101010101010101010101010101010101010101010101010101010101";
+    const KEY_2: &str = "0202020202020202020202020202020202020202020202020202020202020202";
+
+    #[test]
+    fn side_store_cipher_round_trip() {
+        let cipher = SideStoreCipher::from_hex(KEY_1, &[]).unwrap();
+        let sealed = cipher.encrypt("row", "hello world").unwrap();
+        assert!(cipher.is_current(&sealed));
+        assert!(!sealed.contains("hello"));
+        assert_eq!(cipher.decrypt("row", &sealed).unwrap(), "hello world");
+        assert_eq!(
+            decrypt_side_store_value(Some(&cipher), "row", sealed.clone()).unwrap(),
+            "hello world"
+        );
+
+        // values are bound to their row
+        assert!(cipher.decrypt("other-row", &sealed).is_err());
+
+        // plaintext is passed through, but ciphertext can't be read without the key
+        assert_eq!(
+            decrypt_side_store_value(None, "row", "plain".to_string()).unwrap(),
+            "plain"
+        );
+        assert!(decrypt_side_store_value(None, "row", sealed.clone()).is_err());
+        let other = SideStoreCipher::from_hex(KEY_2, &[]).unwrap();
+        assert!(other.decrypt("row", &sealed).is_err());
+
+        // keys are not logged
+        assert!(!format!("{cipher:?}").contains(KEY_1));
+    }
+
+    #[test]
+    fn side_store_cipher_rejects_tampered_values() {
+        let cipher = SideStoreCipher::from_hex(KEY_1, &[]).unwrap();
+        let sealed = cipher.encrypt("row", "hello world").unwrap();
+        let (prefix, hex) = sealed.rsplit_once(':').unwrap();
+        let mut bytes = hex_bytes(hex).unwrap();
+
+        // flipping any bit of the nonce, ciphertext or tag fails authentication
+        for ix in [0, NONCE_LEN, bytes.len() - 1] {
+            bytes[ix] ^= 0x01;
+            let tampered = format!("{prefix}:{}", to_hex(&bytes));
+            assert!(cipher.decrypt("row", &tampered).is_err());
+            assert!(decrypt_side_store_value(Some(&cipher), "row", tampered).is_err());
+            bytes[ix] ^= 0x01;
+        }
+
+        // so does truncating it
+        let truncated = format!("{prefix}:{}", to_hex(&bytes[..bytes.len() - 1]));
+        assert!(cipher.decrypt("row", &truncated).is_err());
+        let truncated = format!("{prefix}:{}", to_hex(&bytes[..NONCE_LEN - 1]));
+        assert!(cipher.decrypt("row", &truncated).is_err());
+
+        // and claiming another key
+        let relabeled = sealed.replacen(cipher.key_id(), "00000000", 1);
+        assert!(cipher.decrypt("row", &relabeled).is_err());
+
+        // the untouched value still decrypts
+        assert_eq!(
+            cipher
+                .decrypt("row", &format!("{prefix}:{}", to_hex(&bytes)))
+                .unwrap(),
+            "hello world"
+        );
+    }
+
+    #[test]
+    fn side_store_cipher_can_require_encrypted_values() {
+        let cipher = SideStoreCipher::from_hex(KEY_1, &[])
+            .unwrap()
+            .with_require_encrypted(true);
+        assert!(decrypt_side_store_value(Some(&cipher), "row", "plain".to_string()).is_err());
+        let sealed = cipher.encrypt("row", "hello world").unwrap();
+        assert_eq!(
+            decrypt_side_store_value(Some(&cipher), "row", sealed).unwrap(),
+            "hello world"
+        );
+
+        // plaintext can still be migrated
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute(
+            "CREATE TABLE data_table (key TEXT PRIMARY KEY, value TEXT)",
+            params![],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO data_table (key, value) VALUES ('a', 'value-a')",
+            params![],
+        )
+        .unwrap();
+        assert_eq!(
+            reencrypt_side_store_batch(&conn, &cipher, "data_table", 0, 10).unwrap(),
+            (1, Some(1))
+        );
+        let value: String = conn
+            .query_row(
+                "SELECT value FROM data_table WHERE key = 'a'",
+                params![],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(
+            decrypt_side_store_value(Some(&cipher), "a", value).unwrap(),
+            "value-a"
+        );
+    }
+
+    #[test]
+    fn side_store_cipher_rejects_bad_keys() {
+        assert!(SideStoreCipher::from_hex("not hex", &[]).is_err());
+        assert!(SideStoreCipher::from_hex("0101", &[]).is_err());
+        assert!(SideStoreCipher::from_hex(KEY_1, &[KEY_1.to_string()]).is_err());
+        assert!(SideStoreCipher::from_hex(KEY_1, &["0101".to_string()]).is_err());
+    }
+
+    #[test]
+    fn side_store_key_rotation() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute(
+            "CREATE TABLE data_table (key TEXT PRIMARY KEY, value TEXT)",
+            params![],
+        )
+        .unwrap();
+
+        let old_cipher = SideStoreCipher::from_hex(KEY_1, &[]).unwrap();
+        let new_cipher = SideStoreCipher::from_hex(KEY_2, &[KEY_1.to_string()]).unwrap();
+        let rows = [
+            ("a", old_cipher.encrypt("a", "value-a").unwrap()),
+            ("b", "value-b".to_string()),
+            ("c", new_cipher.encrypt("c", "value-c").unwrap()),
+        ];
+        for (key, value) in rows.iter() {
+            conn.execute(
+                "INSERT INTO data_table (key, value) VALUES (?1, ?2)",
+                params![key, value],
+            )
+            .unwrap();
+        }
+
+        // the new cipher can read everything
+        for (key, value) in rows.iter() {
+            assert_eq!(
+                decrypt_side_store_value(Some(&new_cipher), key, value.clone()).unwrap(),
+                format!("value-{key}")
+            );
+        }
+
+        let (rewritten, next) =
+            reencrypt_side_store_batch(&conn, &new_cipher, "data_table", 0, 2).unwrap();
+        assert_eq!(rewritten, 2);
+        let (rewritten, next) =
+            reencrypt_side_store_batch(&conn, &new_cipher, "data_table", next.unwrap(), 2).unwrap();
+        assert_eq!(rewritten, 0);
+        assert_eq!(
+            reencrypt_side_store_batch(&conn, &new_cipher, "data_table", next.unwrap(), 2).unwrap(),
+            (0, None)
+        );
+
+        // everything is now readable with only the new key
+        let only_new = SideStoreCipher::from_hex(KEY_2, &[]).unwrap();
+        for (key, _) in rows.iter() {
+            let value: String = conn
+                .query_row(
+                    "SELECT value FROM data_table WHERE key = ?1",
+                    params![key],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert!(only_new.is_current(&value));
+            assert_eq!(
+                only_new.decrypt(key, &value).unwrap(),
+                format!("value-{key}")
+            );
+        }
+    }
+}
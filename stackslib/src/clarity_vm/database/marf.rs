@@ -1,32 +1,134 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
 
 use clarity::util::hash::Sha512Trunc256Sum;
 use clarity::vm::analysis::AnalysisDatabase;
+use clarity::vm::database::clarity_store::ContractCommitment;
 use clarity::vm::database::sqlite::{
     sqlite_get_contract_hash, sqlite_get_metadata, sqlite_get_metadata_manual,
     sqlite_insert_metadata,
 };
 use clarity::vm::database::{
-    BurnStateDB, ClarityBackingStore, ClarityDatabase, HeadersDB, SpecialCaseHandler,
+    BurnStateDB, ClarityBackingStore, ClarityDatabase, ClarityDeserializable, ClarityStateDiff,
+    ClarityStoreKey, HeadersDB, MaintenancePause, ReadOnlyCallCache, SpecialCaseHandler,
     SqliteConnection,
 };
 use clarity::vm::errors::{
     IncomparableError, InterpreterError, InterpreterResult, RuntimeErrorType,
 };
 use clarity::vm::types::QualifiedContractIdentifier;
-use rusqlite::Connection;
+use rusqlite::{params, Connection};
 use stacks_common::codec::StacksMessageCodec;
 use stacks_common::types::chainstate::{BlockHeaderHash, StacksBlockId, TrieHash};
+use stacks_common::types::sqlite::NO_PARAMS;
 
 use crate::chainstate::stacks::index::marf::{MARFOpenOpts, MarfConnection, MarfTransaction, MARF};
 use crate::chainstate::stacks::index::{
-    ClarityMarfTrieId, Error, MARFValue, MarfTrieId, TrieMerkleProof,
+    trie_sql, ClarityMarfTrieId, Error, MARFValue, MarfTrieId, TrieMerkleProof,
 };
+use crate::clarity_vm::database::audit::{MarfReadAuditLog, MarfReadRecord};
+use crate::clarity_vm::database::encryption::{
+    decrypt_side_store_value, encrypt_side_store_value, reencrypt_side_store_batch,
+    SideStoreCipher, ENCRYPTED_SIDE_STORE_TABLES,
+};
+use crate::clarity_vm::database::snapshot::ClarityStateSnapshot;
 use crate::clarity_vm::special::handle_contract_call_special_cases;
 use crate::core::{FIRST_BURNCHAIN_CONSENSUS_HASH, FIRST_STACKS_BLOCK_HASH};
+use crate::util_lib::bloom::{BloomFilter, BloomNodeHasher};
 use crate::util_lib::db::{Error as DatabaseError, IndexDBConn};
 
+/// Number of side-store values `MarfedKV::reencrypt_side_store()` rewrites per transaction
+const REENCRYPT_BATCH_SIZE: u32 = 1000;
+
+/// Number of side-store values `MarfedKV::prune_state()` considers per transaction
+const PRUNE_BATCH_SIZE: u32 = 1000;
+
+/// Most values that `MarfedKV::prune_state()` sizes its filter of live values for, which bounds
+///  the filter to about 60 MiB.  Beyond this many values, more dead values pass for live ones
+///  and are kept.
+const LIVE_VALUES_FILTER_MAX_ITEMS: u32 = 50_000_000;
+
+/// Share of dead values that pass for live ones in `MarfedKV::prune_state()`, and are kept
+const LIVE_VALUES_FILTER_ERROR_RATE: f64 = 0.01;
+
+/// Encrypt contract metadata if there's a cipher.  The metadata's side-store key is the
+///  associated data, so each value is bound to its contract and key.
+fn encrypt_metadata_value(
+    cipher: Option<&SideStoreCipher>,
+    contract: &QualifiedContractIdentifier,
+    key: &str,
+    value: &str,
+) -> InterpreterResult<String> {
+    let row_key = SqliteConnection::make_metadata_key(&contract.to_string(), key);
+    encrypt_side_store_value(cipher, &row_key, value)
+}
+
+fn decrypt_metadata_value(
+    cipher: Option<&SideStoreCipher>,
+    contract: &QualifiedContractIdentifier,
+    key: &str,
+    value: Option<String>,
+) -> InterpreterResult<Option<String>> {
+    let row_key = SqliteConnection::make_metadata_key(&contract.to_string(), key);
+    value
+        .map(|value| decrypt_side_store_value(cipher, &row_key, value))
+        .transpose()
+}
+
+/// The outcome of a `MarfedKV::prune_state()` pass
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatePruneSummary {
+    /// State is only available at this height and above
+    pub pruned_height: u32,
+    /// Number of side-store values deleted
+    pub values_deleted: u64,
+}
+
+fn marf_failure(e: Error) -> InterpreterError {
+    InterpreterError::MarfFailure(e.to_string())
+}
+
+/// Append a committed block's reads to the read audit log.  The block is valid either way, so
+///  failing to log them is not an error.
+fn log_audited_reads(
+    read_audit: &Mutex<MarfReadAuditLog>,
+    block: &StacksBlockId,
+    audited_reads: &[MarfReadRecord],
+) {
+    let Ok(mut log) = read_audit.lock() else {
+        error!(
+            "MARF read audit log poisoned; not logging reads of {}",
+            block
+        );
+        return;
+    };
+    for record in audited_reads.iter() {
+        if let Err(e) = log.record(record) {
+            error!("Failed to log MARF reads of {}: {:?}", block, &e);
+            return;
+        }
+    }
+}
+
+fn open_read_audit(
+    marf_opts: Option<&MARFOpenOpts>,
+) -> InterpreterResult<Option<Arc<Mutex<MarfReadAuditLog>>>> {
+    marf_opts
+        .and_then(|opts| opts.read_audit.as_ref())
+        .map(|config| {
+            MarfReadAuditLog::shared(config).map_err(|e| {
+                InterpreterError::Expect(format!(
+                    "Failed to open MARF read audit log {}: {e}",
+                    config.path.display()
+                ))
+                .into()
+            })
+        })
+        .transpose()
+}
+
 /// The MarfedKV struct is used to wrap a MARF data structure and side-storage
 ///   for use as a K/V store for ClarityDB or the AnalysisDB.
 /// The Clarity VM and type checker do not "know" to begin/commit the block they are currently processing:
@@ -37,6 +139,16 @@ use crate::util_lib::db::{Error as DatabaseError, IndexDBConn};
 pub struct MarfedKV {
     chain_tip: StacksBlockId,
     marf: MARF<StacksBlockId>,
+    /// if set, side-store values are encrypted at rest
+    side_store_cipher: Option<SideStoreCipher>,
+    /// if set, reads made while processing a block are logged with their proofs
+    read_audit: Option<Arc<Mutex<MarfReadAuditLog>>>,
+    /// if set, the most bytes of data that each contract may store
+    contract_data_quota: Option<u64>,
+    /// whether contracts are run through the constant-folding pass as they are loaded
+    optimize_contracts: bool,
+    /// whether the read-only function calls made while processing a block are memoized
+    memoize_read_only_calls: bool,
 }
 
 impl MarfedKV {
@@ -68,7 +180,7 @@ impl MarfedKV {
         };
 
         if SqliteConnection::check_schema(&marf.sqlite_conn()).is_ok() {
-            // no need to initialize
+            // no need to initialize or migrate
             return Ok(marf);
         }
 
@@ -88,13 +200,34 @@ impl MarfedKV {
         miner_tip: Option<&StacksBlockId>,
         marf_opts: Option<MARFOpenOpts>,
     ) -> InterpreterResult<MarfedKV> {
+        let side_store_cipher = marf_opts
+            .as_ref()
+            .and_then(|opts| opts.side_store_cipher.clone());
+        let read_audit = open_read_audit(marf_opts.as_ref())?;
+        let contract_data_quota = marf_opts.as_ref().and_then(|opts| opts.contract_data_quota);
+        let optimize_contracts = marf_opts
+            .as_ref()
+            .map(|opts| opts.optimize_contracts)
+            .unwrap_or(false);
+        let memoize_read_only_calls = marf_opts
+            .as_ref()
+            .map(|opts| opts.memoize_read_only_calls)
+            .unwrap_or(false);
         let marf = MarfedKV::setup_db(path_str, false, marf_opts)?;
         let chain_tip = match miner_tip {
             Some(miner_tip) => miner_tip.clone(),
             None => StacksBlockId::sentinel(),
         };
 
-        Ok(MarfedKV { marf, chain_tip })
+        Ok(MarfedKV {
+            marf,
+            chain_tip,
+            side_store_cipher,
+            read_audit,
+            contract_data_quota,
+            optimize_contracts,
+            memoize_read_only_calls,
+        })
     }
 
     pub fn open_unconfirmed(
@@ -102,13 +235,356 @@ impl MarfedKV {
         miner_tip: Option<&StacksBlockId>,
         marf_opts: Option<MARFOpenOpts>,
     ) -> InterpreterResult<MarfedKV> {
+        let side_store_cipher = marf_opts
+            .as_ref()
+            .and_then(|opts| opts.side_store_cipher.clone());
+        let read_audit = open_read_audit(marf_opts.as_ref())?;
+        let contract_data_quota = marf_opts.as_ref().and_then(|opts| opts.contract_data_quota);
+        let optimize_contracts = marf_opts
+            .as_ref()
+            .map(|opts| opts.optimize_contracts)
+            .unwrap_or(false);
+        let memoize_read_only_calls = marf_opts
+            .as_ref()
+            .map(|opts| opts.memoize_read_only_calls)
+            .unwrap_or(false);
         let marf = MarfedKV::setup_db(path_str, true, marf_opts)?;
         let chain_tip = match miner_tip {
             Some(miner_tip) => miner_tip.clone(),
             None => StacksBlockId::sentinel(),
         };
 
-        Ok(MarfedKV { marf, chain_tip })
+        Ok(MarfedKV {
+            marf,
+            chain_tip,
+            side_store_cipher,
+            read_audit,
+            contract_data_quota,
+            optimize_contracts,
+            memoize_read_only_calls,
+        })
+    }
+
+    /// Open an existing MARF and side-store as a read-only replica, e.g. from a second process
+    /// serving read-only contract calls while the node keeps writing to the same files.
+    ///
+    /// Replicas only support `begin_read_only()`.  A read-only store opened at a block sees that
+    /// block's state and nothing else: committed tries and their side-store entries are never
+    /// modified, and the writer commits each block's trie and side-store entries atomically, so
+    /// concurrent writes can't be observed mid-way.  Blocks committed after the replica was
+    /// opened become readable as soon as they are committed.
+    ///
+    /// Fails if the MARF does not exist, or if its side-store is not at the current schema
+    /// version (replicas never create or migrate anything; the node does that when it starts).
+    pub fn open_replica(
+        path_str: &str,
+        miner_tip: Option<&StacksBlockId>,
+        marf_opts: Option<MARFOpenOpts>,
+    ) -> InterpreterResult<MarfedKV> {
+        let mut path = PathBuf::from(path_str);
+        path.push("marf.sqlite");
+        let marf_path = path
+            .to_str()
+            .ok_or_else(|| InterpreterError::BadFileName)?
+            .to_string();
+
+        let mut marf_opts = marf_opts.unwrap_or(MARFOpenOpts::default());
+        marf_opts.external_blobs = true;
+        let side_store_cipher = marf_opts.side_store_cipher.clone();
+        let contract_data_quota = marf_opts.contract_data_quota;
+        let optimize_contracts = marf_opts.optimize_contracts;
+        let memoize_read_only_calls = marf_opts.memoize_read_only_calls;
+
+        let marf: MARF<StacksBlockId> = MARF::from_path_readonly(&marf_path, marf_opts)
+            .map_err(|err| InterpreterError::MarfFailure(err.to_string()))?;
+        SqliteConnection::check_schema(marf.sqlite_conn())?;
+
+        let chain_tip = match miner_tip {
+            Some(miner_tip) => miner_tip.clone(),
+            None => StacksBlockId::sentinel(),
+        };
+
+        Ok(MarfedKV {
+            marf,
+            chain_tip,
+            side_store_cipher,
+            read_audit: None,
+            contract_data_quota,
+            optimize_contracts,
+            memoize_read_only_calls,
+        })
+    }
+
+    /// Was this opened with `open_replica()`?
+    pub fn is_replica(&self) -> bool {
+        self.marf.readonly()
+    }
+
+    /// Rewrite every side-store value that isn't encrypted with the current side-store key, e.g.
+    ///  after rotating keys or turning on encryption for an existing side-store.  Values are
+    ///  rewritten in batches, each in its own transaction, so this can be interrupted and re-run.
+    /// Returns the number of values rewritten.
+    pub fn reencrypt_side_store(&mut self) -> InterpreterResult<u64> {
+        let cipher = self.side_store_cipher.as_ref().ok_or_else(|| {
+            InterpreterError::Expect("No side-store encryption key is configured".into())
+        })?;
+        let mut rewritten = 0;
+        for table in ENCRYPTED_SIDE_STORE_TABLES.iter() {
+            let mut after_rowid = Some(0);
+            while let Some(rowid) = after_rowid {
+                let tx = self
+                    .marf
+                    .storage_tx()
+                    .map_err(|err| InterpreterError::DBError(err.to_string()))?;
+                let (count, next_rowid) =
+                    reencrypt_side_store_batch(&tx, cipher, table, rowid, REENCRYPT_BATCH_SIZE)?;
+                tx.commit()
+                    .map_err(|err| InterpreterError::SqliteError(IncomparableError { err }))?;
+                rewritten += count;
+                after_rowid = next_rowid;
+            }
+            debug!("Re-encrypted side-store table"; "table" => table, "rewritten" => rewritten);
+        }
+        Ok(rewritten)
+    }
+
+    /// Discard the side-store values that are only needed to read state more than `keep_blocks`
+    ///  blocks below `tip`, for nodes that only serve recent state.  Headers, contract metadata
+    ///  and MARF tries are kept: newer tries share nodes with older ones and commit to their
+    ///  ancestors' root hashes, so tries can't be dropped without rewriting the MARF.  Pruning
+    ///  the tries is out of scope.
+    ///
+    /// Values still reachable from the state at the new pruned height, or written in any later
+    ///  block on any fork, are kept, so forks above the pruned height can still be processed.
+    ///  Unconfirmed tries are kept too, which needs a MARF opened with `open_unconfirmed()` if
+    ///  there are any.  The reachable values are recorded in a bloom filter of bounded size, so
+    ///  a few dead values are kept as well.
+    ///
+    /// The reachable set is computed without holding the database, and the pruned height is only
+    ///  raised if no block was written in the meantime.  Values are then deleted in batches, each
+    ///  in its own transaction; if a block is written between two batches, the pass stops, and
+    ///  the next pass to a higher height deletes the rest.
+    ///
+    /// Returns `None` if nothing was pruned.
+    pub fn prune_state(
+        &mut self,
+        tip: &StacksBlockId,
+        keep_blocks: u32,
+    ) -> InterpreterResult<Option<StatePruneSummary>> {
+        let data_version = Self::get_data_version(self.marf.sqlite_conn())?;
+        let tip_height = self
+            .marf
+            .get_block_height_of(tip, tip)
+            .map_err(marf_failure)?
+            .ok_or_else(|| InterpreterError::Expect(format!("No height for chain tip {tip}")))?;
+        let Some(pruned_height) = tip_height.checked_sub(keep_blocks) else {
+            return Ok(None);
+        };
+        if let Some(prior_height) = SqliteConnection::get_pruned_height(self.marf.sqlite_conn())? {
+            if prior_height >= pruned_height {
+                return Ok(None);
+            }
+        }
+        let oldest_block = self
+            .marf
+            .get_bhh_at_height(tip, pruned_height)
+            .map_err(marf_failure)?
+            .ok_or_else(|| {
+                InterpreterError::Expect(format!("No block at height {pruned_height} from {tip}"))
+            })?;
+
+        let num_values = SqliteConnection::count_data(self.marf.sqlite_conn())?;
+        let mut live_values = BloomFilter::new(
+            LIVE_VALUES_FILTER_ERROR_RATE,
+            u32::try_from(num_values)
+                .unwrap_or(u32::MAX)
+                .clamp(1, LIVE_VALUES_FILTER_MAX_ITEMS),
+            BloomNodeHasher::new_random(),
+        );
+        let mut add_live_value = |value: MARFValue| {
+            live_values.insert_raw(&value.0);
+        };
+        self.marf
+            .get_leaf_values(&oldest_block, true, &mut add_live_value)
+            .map_err(marf_failure)?;
+        let block_hashes: Vec<(StacksBlockId, bool)> =
+            trie_sql::read_all_block_hashes(self.marf.sqlite_conn()).map_err(marf_failure)?;
+        for (block_hash, unconfirmed) in block_hashes {
+            if unconfirmed {
+                if !self.marf.unconfirmed() {
+                    debug!("Not pruning state: unconfirmed trie {block_hash} can't be read");
+                    return Ok(None);
+                }
+            } else {
+                let height = self
+                    .marf
+                    .get_block_height_of(&block_hash, &block_hash)
+                    .map_err(marf_failure)?;
+                if matches!(height, Some(height) if height <= pruned_height) {
+                    continue;
+                }
+            }
+            self.marf
+                .get_leaf_values(&block_hash, false, &mut add_live_value)
+                .map_err(marf_failure)?;
+        }
+
+        // state below the pruned height is refused from now on, so it doesn't matter how much of
+        //  it has been deleted yet
+        let tx = self
+            .marf
+            .storage_tx()
+            .map_err(|err| InterpreterError::DBError(err.to_string()))?;
+        if SqliteConnection::is_maintenance_paused() || Self::get_data_version(&tx)? != data_version
+        {
+            debug!("Not pruning state: a block was written while finding live values");
+            return Ok(None);
+        }
+        SqliteConnection::set_pruned_height(&tx, pruned_height)?;
+        tx.commit()
+            .map_err(|err| InterpreterError::SqliteError(IncomparableError { err }))?;
+
+        let mut values_deleted = 0;
+        let mut after_rowid = Some(0);
+        while let Some(rowid) = after_rowid {
+            let tx = self
+                .marf
+                .storage_tx()
+                .map_err(|err| InterpreterError::DBError(err.to_string()))?;
+            // a new block may have rewritten a value that isn't live yet
+            if SqliteConnection::is_maintenance_paused()
+                || Self::get_data_version(&tx)? != data_version
+            {
+                debug!("Stopped pruning state: a block was written while deleting values");
+                break;
+            }
+            let (count, next_rowid) =
+                SqliteConnection::prune_data_batch(&tx, rowid, PRUNE_BATCH_SIZE, |side_key| {
+                    // keep anything that isn't a value hash
+                    MARFValue::from_hex(side_key)
+                        .map_or(true, |value| live_values.contains_raw(&value.0))
+                })?;
+            tx.commit()
+                .map_err(|err| InterpreterError::SqliteError(IncomparableError { err }))?;
+            values_deleted += count;
+            after_rowid = next_rowid;
+        }
+
+        Ok(Some(StatePruneSummary {
+            pruned_height,
+            values_deleted,
+        }))
+    }
+
+    /// Is the state at `bhh` unavailable, because it was discarded by `prune_state()`?
+    pub fn is_pruned_at(&mut self, bhh: &StacksBlockId) -> InterpreterResult<bool> {
+        let Some(pruned_height) = SqliteConnection::get_pruned_height(self.marf.sqlite_conn())?
+        else {
+            return Ok(false);
+        };
+        let height = match self.marf.get_block_height_of(bhh, bhh) {
+            Ok(height) => height,
+            Err(Error::NotFoundError) => None,
+            Err(e) => return Err(marf_failure(e).into()),
+        };
+        Ok(matches!(height, Some(height) if height < pruned_height))
+    }
+
+    /// Export the entire Clarity state as of `block`: every key/value that has a value in the
+    ///  side-store, and the metadata of every contract deployed in `block`'s fork.
+    pub fn export_state(
+        &mut self,
+        block: &StacksBlockId,
+    ) -> InterpreterResult<ClarityStateSnapshot> {
+        if self.is_pruned_at(block)? {
+            return Err(
+                InterpreterError::Expect(format!("State at {block} has been pruned")).into(),
+            );
+        }
+        let block_height = self
+            .marf
+            .get_block_height_of(block, block)
+            .map_err(marf_failure)?
+            .ok_or_else(|| InterpreterError::Expect(format!("No height for block {block}")))?;
+
+        let leaves = self.marf.get_leaves(block).map_err(marf_failure)?;
+        let mut data = Vec::with_capacity(leaves.len());
+        for (path, marf_value) in leaves.into_iter() {
+            let side_key = marf_value.to_hex();
+            // MARF-internal keys have no side-store value
+            let Some(value) = SqliteConnection::get(self.marf.sqlite_conn(), &side_key)? else {
+                continue;
+            };
+            let value =
+                decrypt_side_store_value(self.side_store_cipher.as_ref(), &side_key, value)?;
+            data.push((path, value));
+        }
+
+        let mut stmt = self
+            .marf
+            .sqlite_conn()
+            .prepare(
+                "SELECT key, blockhash, value FROM metadata_table WHERE key LIKE 'clr-meta::%'",
+            )
+            .map_err(|err| InterpreterError::SqliteError(IncomparableError { err }))?;
+        let rows: Vec<(String, String, String)> = stmt
+            .query_map(NO_PARAMS, |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .and_then(|rows| rows.collect())
+            .map_err(|err| InterpreterError::SqliteError(IncomparableError { err }))?;
+        drop(stmt);
+        // whether each block that has metadata is in `block`'s fork
+        let mut in_fork = HashMap::new();
+        let mut metadata = vec![];
+        for (row_key, blockhash, value) in rows.into_iter() {
+            let Some((contract, key)) = row_key
+                .strip_prefix("clr-meta::")
+                .and_then(|contract_key| contract_key.split_once("::"))
+            else {
+                continue;
+            };
+            let is_in_fork = match in_fork.get(&blockhash) {
+                Some(is_in_fork) => *is_in_fork,
+                None => {
+                    let is_in_fork = match StacksBlockId::from_hex(&blockhash) {
+                        Ok(bhh) => match self.marf.get_block_height_of(&bhh, block) {
+                            Ok(height) => height.is_some(),
+                            Err(Error::NotFoundError) => false,
+                            Err(e) => return Err(marf_failure(e).into()),
+                        },
+                        Err(_) => false,
+                    };
+                    in_fork.insert(blockhash.clone(), is_in_fork);
+                    is_in_fork
+                }
+            };
+            if !is_in_fork {
+                continue;
+            }
+            let contract_id = QualifiedContractIdentifier::parse(contract)?;
+            let value = decrypt_metadata_value(
+                self.side_store_cipher.as_ref(),
+                &contract_id,
+                key,
+                Some(value),
+            )?
+            .unwrap_or_default();
+            metadata.push((contract.to_string(), key.to_string(), value));
+        }
+
+        let mut snapshot = ClarityStateSnapshot {
+            block_id: block.clone(),
+            block_height,
+            data,
+            metadata,
+        };
+        snapshot.sort();
+        Ok(snapshot)
+    }
+
+    /// SQLite's `data_version`, which changes whenever another connection commits a write
+    fn get_data_version(conn: &Connection) -> InterpreterResult<i64> {
+        conn.query_row("PRAGMA data_version", NO_PARAMS, |row| row.get(0))
+            .map_err(|err| InterpreterError::SqliteError(IncomparableError { err }).into())
     }
 
     // used by benchmarks
@@ -139,7 +615,15 @@ impl MarfedKV {
 
         let chain_tip = StacksBlockId::sentinel();
 
-        MarfedKV { marf, chain_tip }
+        MarfedKV {
+            marf,
+            chain_tip,
+            side_store_cipher: None,
+            read_audit: None,
+            contract_data_quota: None,
+            optimize_contracts: false,
+            memoize_read_only_calls: false,
+        }
     }
 
     pub fn begin_read_only<'a>(
@@ -161,6 +645,7 @@ impl MarfedKV {
         ReadOnlyMarfStore {
             chain_tip,
             marf: &mut self.marf,
+            side_store_cipher: self.side_store_cipher.as_ref(),
         }
     }
 
@@ -183,6 +668,7 @@ impl MarfedKV {
         Ok(ReadOnlyMarfStore {
             chain_tip,
             marf: &mut self.marf,
+            side_store_cipher: self.side_store_cipher.as_ref(),
         })
     }
 
@@ -221,6 +707,15 @@ impl MarfedKV {
         WritableMarfStore {
             chain_tip,
             marf: tx,
+            _maintenance_pause: SqliteConnection::pause_maintenance(),
+            state_diff: None,
+            side_store_cipher: self.side_store_cipher.as_ref(),
+            read_audit: self.read_audit.as_deref(),
+            parent_tip: current.clone(),
+            audited_reads: vec![],
+            contract_data_quota: self.contract_data_quota,
+            optimize_contracts: self.optimize_contracts,
+            read_only_call_cache: self.memoize_read_only_calls.then(ReadOnlyCallCache::new),
         }
     }
 
@@ -246,6 +741,16 @@ impl MarfedKV {
         WritableMarfStore {
             chain_tip,
             marf: tx,
+            _maintenance_pause: SqliteConnection::pause_maintenance(),
+            state_diff: None,
+            side_store_cipher: self.side_store_cipher.as_ref(),
+            // unconfirmed state isn't consensus-critical
+            read_audit: None,
+            parent_tip: current.clone(),
+            audited_reads: vec![],
+            contract_data_quota: self.contract_data_quota,
+            optimize_contracts: self.optimize_contracts,
+            read_only_call_cache: self.memoize_read_only_calls.then(ReadOnlyCallCache::new),
         }
     }
 
@@ -273,11 +778,29 @@ impl MarfedKV {
 pub struct WritableMarfStore<'a> {
     chain_tip: StacksBlockId,
     marf: MarfTransaction<'a, StacksBlockId>,
+    /// side-store maintenance must not compete with block processing
+    _maintenance_pause: MaintenancePause,
+    /// if recording, every key/value written so far
+    state_diff: Option<ClarityStateDiff>,
+    side_store_cipher: Option<&'a SideStoreCipher>,
+    /// if set, the reads made by this block are logged here when it is committed
+    read_audit: Option<&'a Mutex<MarfReadAuditLog>>,
+    /// the block this one is built on, which reads from the open block are proven against
+    parent_tip: StacksBlockId,
+    /// reads made so far, if auditing
+    audited_reads: Vec<MarfReadRecord>,
+    /// if set, the most bytes of data that each contract may store
+    contract_data_quota: Option<u64>,
+    /// whether contracts are run through the constant-folding pass as they are loaded
+    optimize_contracts: bool,
+    /// if set, the read-only function calls made in this block are memoized here
+    read_only_call_cache: Option<ReadOnlyCallCache>,
 }
 
 pub struct ReadOnlyMarfStore<'a> {
     chain_tip: StacksBlockId,
     marf: &'a mut MARF<StacksBlockId>,
+    side_store_cipher: Option<&'a SideStoreCipher>,
 }
 
 impl<'a> ReadOnlyMarfStore<'a> {
@@ -417,6 +940,7 @@ impl<'a> ClarityBackingStore for ReadOnlyMarfStore<'a> {
                             side_key
                         ))
                     })?;
+                let data = decrypt_side_store_value(self.side_store_cipher, &side_key, data)?;
                 Ok((data, proof.serialize_to_vec()))
             })
             .transpose()
@@ -441,13 +965,14 @@ impl<'a> ClarityBackingStore for ReadOnlyMarfStore<'a> {
             .map(|marf_value| {
                 let side_key = marf_value.to_hex();
                 trace!("MarfedKV get side-key for {:?}: {:?}", key, &side_key);
-                SqliteConnection::get(self.get_side_store(), &side_key)?.ok_or_else(|| {
-                    InterpreterError::Expect(format!(
-                        "ERROR: MARF contained value_hash not found in side storage: {}",
-                        side_key
-                    ))
-                    .into()
-                })
+                let data =
+                    SqliteConnection::get(self.get_side_store(), &side_key)?.ok_or_else(|| {
+                        InterpreterError::Expect(format!(
+                            "ERROR: MARF contained value_hash not found in side storage: {}",
+                            side_key
+                        ))
+                    })?;
+                decrypt_side_store_value(self.side_store_cipher, &side_key, data)
             })
             .transpose()
     }
@@ -470,7 +995,8 @@ impl<'a> ClarityBackingStore for ReadOnlyMarfStore<'a> {
         key: &str,
         value: &str,
     ) -> InterpreterResult<()> {
-        sqlite_insert_metadata(self, contract, key, value)
+        let value = encrypt_metadata_value(self.side_store_cipher, contract, key, value)?;
+        sqlite_insert_metadata(self, contract, key, &value)
     }
 
     fn get_metadata(
@@ -478,7 +1004,8 @@ impl<'a> ClarityBackingStore for ReadOnlyMarfStore<'a> {
         contract: &QualifiedContractIdentifier,
         key: &str,
     ) -> InterpreterResult<Option<String>> {
-        sqlite_get_metadata(self, contract, key)
+        let value = sqlite_get_metadata(self, contract, key)?;
+        decrypt_metadata_value(self.side_store_cipher, contract, key, value)
     }
 
     fn get_metadata_manual(
@@ -487,7 +1014,8 @@ impl<'a> ClarityBackingStore for ReadOnlyMarfStore<'a> {
         contract: &QualifiedContractIdentifier,
         key: &str,
     ) -> InterpreterResult<Option<String>> {
-        sqlite_get_metadata_manual(self, at_height, contract, key)
+        let value = sqlite_get_metadata_manual(self, at_height, contract, key)?;
+        decrypt_metadata_value(self.side_store_cipher, contract, key, value)
     }
 }
 
@@ -508,6 +1036,17 @@ impl<'a> WritableMarfStore<'a> {
         self.marf.drop_current();
     }
 
+    /// Start recording every key/value written to this store, discarding anything recorded so
+    /// far
+    pub fn record_state_diff(&mut self) {
+        self.state_diff = Some(ClarityStateDiff::new());
+    }
+
+    /// Stop recording writes, and get what was recorded
+    pub fn take_state_diff(&mut self) -> Option<ClarityStateDiff> {
+        self.state_diff.take()
+    }
+
     pub fn rollback_unconfirmed(self) -> InterpreterResult<()> {
         debug!("Drop unconfirmed MARF trie {}", &self.chain_tip);
         SqliteConnection::drop_metadata(self.marf.sqlite_tx(), &self.chain_tip)?;
@@ -515,14 +1054,86 @@ impl<'a> WritableMarfStore<'a> {
         Ok(())
     }
 
-    pub fn commit_to(self, final_bhh: &StacksBlockId) -> InterpreterResult<()> {
+    /// Record a read of `key` at the current chain tip, with a proof of `marf_value` if there
+    /// is one
+    fn audit_read(&mut self, key: &str, marf_value: Option<&MARFValue>) {
+        let block = self
+            .marf
+            .get_open_chain_tip()
+            .cloned()
+            .unwrap_or_else(|| self.chain_tip.clone());
+        let mut record = MarfReadRecord {
+            block: block.clone(),
+            read_at: self.chain_tip.clone(),
+            key: key.to_string(),
+            value_hash: marf_value.map(|value| value.to_hex()),
+            proven_at: None,
+            root_hash: None,
+            proof: None,
+        };
+        if let Some(marf_value) = marf_value {
+            // the open block has no root hash yet, so prove its values in its parent
+            let proven_at = if self.chain_tip == block {
+                self.parent_tip.clone()
+            } else {
+                self.chain_tip.clone()
+            };
+            match self.marf.get_with_proof(&proven_at, key) {
+                Ok(Some((proven_value, proof))) if &proven_value == marf_value => {
+                    match self.marf.get_root_hash_at(&proven_at) {
+                        Ok(root_hash) => {
+                            record.root_hash = Some(root_hash);
+                            record.proof = Some(proof.to_hex());
+                            record.proven_at = Some(proven_at);
+                        }
+                        Err(e) => {
+                            warn!(
+                                "Failed to get root hash of {} for read audit: {:?}",
+                                &proven_at, &e
+                            );
+                        }
+                    }
+                }
+                // written by the open block
+                Ok(_) | Err(Error::NotFoundError) => {}
+                Err(e) => {
+                    warn!(
+                        "Failed to prove read of {:?} at {} for read audit: {:?}",
+                        key, &proven_at, &e
+                    );
+                }
+            }
+        }
+        self.audited_reads.push(record);
+    }
+
+    /// The reads made by this block, now that it is known as `final_bhh`
+    fn take_audited_reads(&mut self, final_bhh: &StacksBlockId) -> Vec<MarfReadRecord> {
+        let open_tip = self.marf.get_open_chain_tip().cloned();
+        let mut audited_reads = std::mem::take(&mut self.audited_reads);
+        for record in audited_reads.iter_mut() {
+            if Some(&record.read_at) == open_tip.as_ref() {
+                record.read_at = final_bhh.clone();
+            }
+            record.block = final_bhh.clone();
+        }
+        audited_reads
+    }
+
+    pub fn commit_to(mut self, final_bhh: &StacksBlockId) -> InterpreterResult<()> {
         debug!("commit_to({})", final_bhh);
+        let audited_reads = self.take_audited_reads(final_bhh);
+        let read_audit = self.read_audit;
         SqliteConnection::commit_metadata_to(self.marf.sqlite_tx(), &self.chain_tip, final_bhh)?;
 
         let _ = self.marf.commit_to(final_bhh).map_err(|e| {
             error!("Failed to commit to MARF block {}: {:?}", &final_bhh, &e);
             InterpreterError::Expect("Failed to commit to MARF block".into())
         })?;
+
+        if let Some(read_audit) = read_audit {
+            log_audited_reads(read_audit, final_bhh, &audited_reads);
+        }
         Ok(())
     }
 
@@ -565,6 +1176,53 @@ impl<'a> WritableMarfStore<'a> {
         Ok(())
     }
 
+    /// Write the state in `snapshot` to the open block (see `MarfedKV::export_state()`).  Keys
+    ///  that the block already has are overwritten, and so is the metadata of contracts that it
+    ///  already has.  The commitment of each contract with metadata in the snapshot is rewritten
+    ///  to this block's height, which is where its metadata is now stored.
+    pub fn import_state(&mut self, snapshot: &ClarityStateSnapshot) -> InterpreterResult<()> {
+        let mut paths = Vec::with_capacity(snapshot.data.len());
+        let mut values = Vec::with_capacity(snapshot.data.len());
+        for (path, value) in snapshot.data.iter() {
+            let marf_value = MARFValue::from_value(value);
+            let side_key = marf_value.to_hex();
+            let side_value = encrypt_side_store_value(self.side_store_cipher, &side_key, value)?;
+            SqliteConnection::put(self.get_side_store(), &side_key, &side_value)?;
+            paths.push(path.clone());
+            values.push(marf_value);
+        }
+        self.marf
+            .insert_raw_batch(&paths, values)
+            .map_err(marf_failure)?;
+
+        let bhh = self.get_open_chain_tip();
+        let mut commitments = vec![];
+        for (contract, key, value) in snapshot.metadata.iter() {
+            let contract_id = QualifiedContractIdentifier::parse(contract)?;
+            let row_key = SqliteConnection::make_metadata_key(contract, key);
+            self.get_side_store()
+                .execute(
+                    "DELETE FROM metadata_table WHERE blockhash = ? AND key = ?",
+                    params![bhh, row_key],
+                )
+                .map_err(|err| InterpreterError::SqliteError(IncomparableError { err }))?;
+            self.insert_metadata(&contract_id, key, value)?;
+            if commitments.last() != Some(&contract_id) {
+                commitments.push(contract_id);
+            }
+        }
+        let mut items = vec![];
+        for contract_id in commitments.into_iter() {
+            let key = ClarityStoreKey::ContractHash(&contract_id).to_string();
+            let Some(commitment) = self.get_data(&key)? else {
+                continue;
+            };
+            let ContractCommitment { hash, .. } = ContractCommitment::deserialize(&commitment)?;
+            items.push((key, self.make_contract_commitment(hash)));
+        }
+        self.put_all_data(items)
+    }
+
     pub fn seal(&mut self) -> TrieHash {
         self.marf.seal().expect("FATAL: failed to .seal() MARF")
     }
@@ -601,9 +1259,22 @@ impl<'a> ClarityBackingStore for WritableMarfStore<'a> {
         Some(&handle_contract_call_special_cases)
     }
 
+    fn get_contract_data_quota(&self) -> Option<u64> {
+        self.contract_data_quota
+    }
+
+    fn optimize_contracts(&self) -> bool {
+        self.optimize_contracts
+    }
+
+    fn get_read_only_call_cache(&mut self) -> Option<&mut ReadOnlyCallCache> {
+        self.read_only_call_cache.as_mut()
+    }
+
     fn get_data(&mut self, key: &str) -> InterpreterResult<Option<String>> {
         trace!("MarfedKV get: {:?} tip={}", key, &self.chain_tip);
-        self.marf
+        let marf_value = self
+            .marf
             .get(&self.chain_tip, key)
             .or_else(|e| match e {
                 Error::NotFoundError => {
@@ -616,17 +1287,24 @@ impl<'a> ClarityBackingStore for WritableMarfStore<'a> {
                 }
                 _ => Err(e),
             })
-            .map_err(|_| InterpreterError::Expect("ERROR: Unexpected MARF Failure on GET".into()))?
+            .map_err(|_| {
+                InterpreterError::Expect("ERROR: Unexpected MARF Failure on GET".into())
+            })?;
+        if self.read_audit.is_some() {
+            self.audit_read(key, marf_value.as_ref());
+        }
+        marf_value
             .map(|marf_value| {
                 let side_key = marf_value.to_hex();
                 trace!("MarfedKV get side-key for {:?}: {:?}", key, &side_key);
-                SqliteConnection::get(self.marf.sqlite_tx(), &side_key)?.ok_or_else(|| {
-                    InterpreterError::Expect(format!(
-                        "ERROR: MARF contained value_hash not found in side storage: {}",
-                        side_key
-                    ))
-                    .into()
-                })
+                let data =
+                    SqliteConnection::get(self.marf.sqlite_tx(), &side_key)?.ok_or_else(|| {
+                        InterpreterError::Expect(format!(
+                            "ERROR: MARF contained value_hash not found in side storage: {}",
+                            side_key
+                        ))
+                    })?;
+                decrypt_side_store_value(self.side_store_cipher, &side_key, data)
             })
             .transpose()
     }
@@ -648,6 +1326,7 @@ impl<'a> ClarityBackingStore for WritableMarfStore<'a> {
                             side_key
                         ))
                     })?;
+                let data = decrypt_side_store_value(self.side_store_cipher, &side_key, data)?;
                 Ok((data, proof.serialize_to_vec()))
             })
             .transpose()
@@ -722,7 +1401,12 @@ impl<'a> ClarityBackingStore for WritableMarfStore<'a> {
         for (key, value) in items.into_iter() {
             trace!("MarfedKV put '{}' = '{}'", &key, &value);
             let marf_value = MARFValue::from_value(&value);
-            SqliteConnection::put(self.get_side_store(), &marf_value.to_hex(), &value)?;
+            let side_key = marf_value.to_hex();
+            let side_value = encrypt_side_store_value(self.side_store_cipher, &side_key, &value)?;
+            SqliteConnection::put(self.get_side_store(), &side_key, &side_value)?;
+            if let Some(state_diff) = self.state_diff.as_mut() {
+                state_diff.record_data(&key, &value);
+            }
             keys.push(key);
             values.push(marf_value);
         }
@@ -744,7 +1428,11 @@ impl<'a> ClarityBackingStore for WritableMarfStore<'a> {
         key: &str,
         value: &str,
     ) -> InterpreterResult<()> {
-        sqlite_insert_metadata(self, contract, key, value)
+        if let Some(state_diff) = self.state_diff.as_mut() {
+            state_diff.record_metadata(&contract.to_string(), key, value);
+        }
+        let value = encrypt_metadata_value(self.side_store_cipher, contract, key, value)?;
+        sqlite_insert_metadata(self, contract, key, &value)
     }
 
     fn get_metadata(
@@ -752,7 +1440,8 @@ impl<'a> ClarityBackingStore for WritableMarfStore<'a> {
         contract: &QualifiedContractIdentifier,
         key: &str,
     ) -> InterpreterResult<Option<String>> {
-        sqlite_get_metadata(self, contract, key)
+        let value = sqlite_get_metadata(self, contract, key)?;
+        decrypt_metadata_value(self.side_store_cipher, contract, key, value)
     }
 
     fn get_metadata_manual(
@@ -761,6 +1450,7 @@ impl<'a> ClarityBackingStore for WritableMarfStore<'a> {
         contract: &QualifiedContractIdentifier,
         key: &str,
     ) -> InterpreterResult<Option<String>> {
-        sqlite_get_metadata_manual(self, at_height, contract, key)
+        let value = sqlite_get_metadata_manual(self, at_height, contract, key)?;
+        decrypt_metadata_value(self.side_store_cipher, contract, key, value)
     }
 }
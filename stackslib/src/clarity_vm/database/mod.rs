@@ -39,7 +39,10 @@ use crate::clarity_vm::special::handle_contract_call_special_cases;
 use crate::core::{StacksEpoch, StacksEpochId};
 use crate::util_lib::db::{DBConn, Error as DBError, FromColumn, FromRow};
 
+pub mod audit;
+pub mod encryption;
 pub mod marf;
+pub mod snapshot;
 
 pub trait GetTenureStartId {
     fn get_tenure_block_id(
@@ -1191,13 +1194,18 @@ impl BurnStateDB for SortitionHandleConn<'_> {
 
 pub struct MemoryBackingStore {
     side_store: Connection,
+    /// Height of the open chain tip
+    chain_tip_height: u32,
 }
 
 impl MemoryBackingStore {
     pub fn new() -> MemoryBackingStore {
         let side_store = SqliteConnection::memory().unwrap();
 
-        let mut memory_marf = MemoryBackingStore { side_store };
+        let mut memory_marf = MemoryBackingStore {
+            side_store,
+            chain_tip_height: 0,
+        };
 
         memory_marf.as_clarity_db().initialize();
 
@@ -1221,6 +1229,22 @@ impl MemoryBackingStore {
     pub fn as_analysis_db<'a>(&'a mut self) -> AnalysisDatabase<'a> {
         AnalysisDatabase::new(self)
     }
+
+    /// Open the store at a chain tip `count` blocks after the current one.  Returns the new
+    ///  tip's height.
+    pub fn advance_chain_tip(&mut self, count: u32) -> u32 {
+        self.chain_tip_height = self.chain_tip_height.saturating_add(count);
+        self.chain_tip_height
+    }
+
+    /// The made-up id of the block at `height`.  The genesis block's id is the sentinel.
+    fn block_id_at_height(height: u32) -> StacksBlockId {
+        let mut id = StacksBlockId::sentinel();
+        for (byte, height_byte) in id.0[28..].iter_mut().zip(height.to_be_bytes()) {
+            *byte ^= height_byte;
+        }
+        id
+    }
 }
 
 impl ClarityBackingStore for MemoryBackingStore {
@@ -1241,23 +1265,23 @@ impl ClarityBackingStore for MemoryBackingStore {
     }
 
     fn get_block_at_height(&mut self, height: u32) -> Option<StacksBlockId> {
-        if height == 0 {
-            Some(StacksBlockId::sentinel())
+        if height <= self.chain_tip_height {
+            Some(MemoryBackingStore::block_id_at_height(height))
         } else {
             None
         }
     }
 
     fn get_open_chain_tip(&mut self) -> StacksBlockId {
-        StacksBlockId::sentinel()
+        MemoryBackingStore::block_id_at_height(self.chain_tip_height)
     }
 
     fn get_open_chain_tip_height(&mut self) -> u32 {
-        0
+        self.chain_tip_height
     }
 
     fn get_current_block_height(&mut self) -> u32 {
-        0
+        self.chain_tip_height
     }
 
     fn get_cc_special_cases_handler(&self) -> Option<SpecialCaseHandler> {
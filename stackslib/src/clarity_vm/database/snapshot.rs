@@ -0,0 +1,210 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Snapshots of the entire Clarity state at a block, for seeding test networks with a copy of
+//! another network's state (e.g., a forked mainnet).
+//!
+//! A snapshot holds every key/value in the MARF as of the block (contracts, data vars, maps,
+//! balances, ...) and the metadata of every contract deployed in its fork.  MARF keys are only
+//! stored as the hashes of their paths, so data is exported by path, and imported at the same
+//! paths with `MarfTransaction::insert_raw_batch()`.  MARF-internal keys (block heights and
+//! hashes) have no value in the side-store, and are not exported.
+//!
+//! The archive is one JSON object per line: a header, then the data sorted by path, then the
+//! metadata sorted by contract and key.  The same state always yields the same bytes.
+//!
+//! An imported contract's commitment is rewritten to the height of the block it is imported
+//! into, since its metadata is stored at that block.  Nothing else is remapped: heights and
+//! block hashes that contracts stored as data still refer to the chain the state came from, and
+//! principals keep their address versions.
+
+use std::io::{self, BufRead, Write};
+
+use stacks_common::types::chainstate::StacksBlockId;
+
+use crate::chainstate::stacks::index::node::TriePath;
+
+/// Version of the archive format written by `ClarityStateSnapshot::write_to()`
+pub const CLARITY_STATE_SNAPSHOT_VERSION: u32 = 1;
+
+/// The Clarity state at a block
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClarityStateSnapshot {
+    /// The block the state was exported at
+    pub block_id: StacksBlockId,
+    pub block_height: u32,
+    /// The path and value of every key, sorted by path
+    pub data: Vec<(TriePath, String)>,
+    /// The contract, key and value of every metadata entry, sorted by contract and key
+    pub metadata: Vec<(String, String, String)>,
+}
+
+/// One line of the archive
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum SnapshotRecord {
+    Header {
+        version: u32,
+        block_id: StacksBlockId,
+        block_height: u32,
+    },
+    Data {
+        path: String,
+        value: String,
+    },
+    Metadata {
+        contract: String,
+        key: String,
+        value: String,
+    },
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+impl ClarityStateSnapshot {
+    /// Sort the data and metadata, so that the archive is deterministic
+    pub fn sort(&mut self) {
+        self.data.sort();
+        self.metadata.sort();
+    }
+
+    pub fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let mut write_record = |record: SnapshotRecord| -> io::Result<()> {
+            serde_json::to_writer(&mut *w, &record)?;
+            w.write_all(b"\n")
+        };
+        write_record(SnapshotRecord::Header {
+            version: CLARITY_STATE_SNAPSHOT_VERSION,
+            block_id: self.block_id.clone(),
+            block_height: self.block_height,
+        })?;
+        for (path, value) in self.data.iter() {
+            write_record(SnapshotRecord::Data {
+                path: path.to_hex(),
+                value: value.clone(),
+            })?;
+        }
+        for (contract, key, value) in self.metadata.iter() {
+            write_record(SnapshotRecord::Metadata {
+                contract: contract.clone(),
+                key: key.clone(),
+                value: value.clone(),
+            })?;
+        }
+        w.flush()
+    }
+
+    pub fn read_from<R: BufRead>(r: R) -> io::Result<ClarityStateSnapshot> {
+        let mut lines = r
+            .lines()
+            .filter(|line| !matches!(line, Ok(line) if line.is_empty()));
+        let header = lines
+            .next()
+            .ok_or_else(|| invalid_data("Empty Clarity state snapshot".into()))??;
+        let (block_id, block_height) = match serde_json::from_str(&header)? {
+            SnapshotRecord::Header {
+                version,
+                block_id,
+                block_height,
+            } => {
+                if version != CLARITY_STATE_SNAPSHOT_VERSION {
+                    return Err(invalid_data(format!(
+                        "Unsupported Clarity state snapshot version {version}"
+                    )));
+                }
+                (block_id, block_height)
+            }
+            _ => {
+                return Err(invalid_data(
+                    "Clarity state snapshot does not start with a header".into(),
+                ))
+            }
+        };
+        let mut snapshot = ClarityStateSnapshot {
+            block_id,
+            block_height,
+            data: vec![],
+            metadata: vec![],
+        };
+        for line in lines {
+            match serde_json::from_str(&line?)? {
+                SnapshotRecord::Header { .. } => {
+                    return Err(invalid_data(
+                        "Clarity state snapshot has more than one header".into(),
+                    ))
+                }
+                SnapshotRecord::Data { path, value } => {
+                    let path = TriePath::from_hex(&path)
+                        .map_err(|e| invalid_data(format!("Invalid path {path}: {e:?}")))?;
+                    snapshot.data.push((path, value));
+                }
+                SnapshotRecord::Metadata {
+                    contract,
+                    key,
+                    value,
+                } => snapshot.metadata.push((contract, key, value)),
+            }
+        }
+        Ok(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clarity_state_snapshot_archive() {
+        let mut snapshot = ClarityStateSnapshot {
+            block_id: StacksBlockId([7; 32]),
+            block_height: 12,
+            data: vec![
+                (TriePath::from_key("b"), "02".into()),
+                (TriePath::from_key("a"), "01".into()),
+            ],
+            metadata: vec![
+                (
+                    "S1G2081040G2081040G2081040G208105NK8PE5.foo".into(),
+                    "b".into(),
+                    "2".into(),
+                ),
+                (
+                    "S1G2081040G2081040G2081040G208105NK8PE5.foo".into(),
+                    "a".into(),
+                    "1".into(),
+                ),
+            ],
+        };
+        snapshot.sort();
+
+        let mut bytes = vec![];
+        snapshot.write_to(&mut bytes).unwrap();
+        let read = ClarityStateSnapshot::read_from(bytes.as_slice()).unwrap();
+        assert_eq!(read, snapshot);
+        let mut rewritten = vec![];
+        read.write_to(&mut rewritten).unwrap();
+        assert_eq!(rewritten, bytes);
+
+        assert!(ClarityStateSnapshot::read_from(&b""[..]).is_err());
+        let data_first = bytes
+            .split(|b| *b == b'\n')
+            .skip(1)
+            .collect::<Vec<_>>()
+            .join(&b'\n');
+        assert!(ClarityStateSnapshot::read_from(data_first.as_slice()).is_err());
+    }
+}
@@ -6,5 +6,9 @@ pub mod special;
 /// Stacks blockchain specific Clarity database implementations and wrappers
 pub mod database;
 
+/// Benchmark corpus of contracts modeled on mainnet workloads
+#[cfg(feature = "bench-corpus")]
+pub mod corpus;
+
 #[cfg(test)]
 mod tests;
@@ -170,6 +170,17 @@ pub fn get_simple_test(function: &NativeFunctions) -> &'static str {
         ReplaceAt => "(replace-at? list-bar u0 5)",
         GetStacksBlockInfo => "(get-block-info? time u1)",
         GetTenureInfo => "(get-block-info? time u1)",
+        BlockVrfSeed => "(block-vrf-seed u1000)",
+        Secp256k1VerifyMany => "(secp256k1-verify-many (list { message-hash: 0xde5b9eb9e7c5592930eb2e30a01369c36586d872082ed8181ee83d2a0ec20f04, signature: 0x8738487ebe69b93d8e51583be8eee50bb4213fc49c767d329632730cc193b873554428fc936ca3569afc15f1c9365f6591d6251a89fee9c9ac661116824d3a1301, public-key: 0x03adb8de4bfb65db2cfd6120d55c6526ae9c52e675db7e47308636534ba7786110 }))",
+        ImplementsTrait => "(implements-trait? tx-sender .contract-trait.trait-1)",
+        EmitEvent => r#"(emit-event "transfer" { amount: u1 })"#,
+        BuffAnd => "(buff-and 0xff0f 0x0ff0)",
+        BuffOr => "(buff-or 0xff00 0x00ff)",
+        BuffXor => "(buff-xor 0xffff 0x0ff0)",
+        BuffNot => "(buff-not 0x00ff)",
+        AltBn128Add => "(alt-bn128-add? 0x00000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000002 0x00000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000002)",
+        AltBn128Mul => "(alt-bn128-mul? 0x00000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000002 0x0000000000000000000000000000000000000000000000000000000000000002)",
+        AltBn128PairingCheck => "(alt-bn128-pairing-check? (list { g1: 0x00000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000002, g2: 0x198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c21800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa }))",
     }
 }
 
@@ -221,6 +232,15 @@ where
         tip = next_block.clone();
     }
 
+    if epoch >= StacksEpochId::Epoch31 {
+        let next_block = StacksBlockId([0x31 as u8; 32]);
+        let mut clarity_conn =
+            clarity_instance.begin_block(&tip, &next_block, &TEST_HEADER_DB, &TEST_BURN_STATE_DB);
+        clarity_conn.initialize_epoch_3_1().unwrap();
+        clarity_conn.commit_block();
+        tip = next_block.clone();
+    }
+
     let mut marf_kv = clarity_instance.destroy();
 
     let mut store = marf_kv.begin(&tip, &StacksBlockId([3 as u8; 32]));
@@ -993,6 +1013,10 @@ fn epoch_21_test_all(use_mainnet: bool) {
 
         for (ix, f) in NativeFunctions::ALL.iter().enumerate() {
             // Note: Include Clarity2 functions for Epoch21.
+            //  The Clarity4 functions are priced by costs-4, which is only instantiated in Epoch31.
+            if f.get_min_version() == ClarityVersion::Clarity4 {
+                continue;
+            }
             let test = get_simple_test(f);
             let cost = test_program_cost(test, ClarityVersion::Clarity2, &mut owned_env, ix + 1);
             assert!(cost.exceeds(&baseline));
@@ -1010,6 +1034,36 @@ fn epoch_21_test_all_testnet() {
     epoch_21_test_all(false)
 }
 
+// test each individual cost function can be correctly invoked as
+//  Clarity code executes in Epoch 3.1
+fn epoch_31_test_all(use_mainnet: bool) {
+    with_owned_env(StacksEpochId::Epoch31, use_mainnet, |mut owned_env| {
+        setup_cost_tracked_test(use_mainnet, ClarityVersion::Clarity4, &mut owned_env);
+
+        let baseline = test_program_cost("1", ClarityVersion::Clarity4, &mut owned_env, 0);
+
+        for (ix, f) in NativeFunctions::ALL.iter().enumerate() {
+            // Note: Only the Clarity4 functions, which are priced by costs-4.
+            if f.get_min_version() == ClarityVersion::Clarity4 {
+                let test = get_simple_test(f);
+                let cost =
+                    test_program_cost(test, ClarityVersion::Clarity4, &mut owned_env, ix + 1);
+                assert!(cost.exceeds(&baseline));
+            }
+        }
+    })
+}
+
+#[test]
+fn epoch_31_test_all_mainnet() {
+    epoch_31_test_all(true)
+}
+
+#[test]
+fn epoch_31_test_all_testnet() {
+    epoch_31_test_all(false)
+}
+
 fn test_cost_contract_short_circuits(use_mainnet: bool, clarity_version: ClarityVersion) {
     let marf_kv = MarfedKV::temporary();
     let chain_id = test_only_mainnet_to_chain_id(use_mainnet);
@@ -135,6 +135,53 @@ fn test_emit_print_ok() {
     };
 }
 
+#[test]
+fn test_emit_contract_event_ok() {
+    let contract = "(define-public (emit-event-ok)
+            (begin
+                (emit-event \"transfer\" { amount: u10, memo: \"rent\" })
+                (ok u1)))";
+
+    let (value, mut events) = helper_execute_epoch(
+        contract,
+        "emit-event-ok",
+        None,
+        StacksEpochId::Epoch31,
+        false,
+    );
+    assert_eq!(value, Value::okay(Value::UInt(1)).unwrap());
+    assert_eq!(events.len(), 1);
+    match events.pop() {
+        Some(StacksTransactionEvent::ContractEvent(data)) => {
+            let contract_id = QualifiedContractIdentifier::local("contract").unwrap();
+            assert_eq!(data.contract_identifier, contract_id);
+            assert_eq!(data.name.as_str(), "transfer");
+            assert_eq!(data.payload, execute("{ amount: u10, memo: \"rent\" }"));
+            let json = data.json_serialize().unwrap();
+            assert_eq!(json["name"], "transfer");
+        }
+        _ => panic!("assertion failed"),
+    };
+}
+
+#[test]
+fn test_emit_contract_event_nok() {
+    let contract = "(define-public (emit-event-nok)
+            (begin
+                (emit-event \"transfer\" { amount: u10 })
+                (err u1)))";
+
+    let (value, events) = helper_execute_epoch(
+        contract,
+        "emit-event-nok",
+        None,
+        StacksEpochId::Epoch31,
+        false,
+    );
+    assert_eq!(value, Value::error(Value::UInt(1)).unwrap());
+    assert_eq!(events.len(), 0);
+}
+
 #[test]
 fn test_emit_print_nok() {
     let contract = "(define-public (emit-event-nok)
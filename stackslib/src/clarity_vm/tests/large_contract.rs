@@ -40,7 +40,9 @@ use stacks_common::types::chainstate::{BlockHeaderHash, StacksBlockId};
 use stacks_common::types::StacksEpochId;
 use stacks_common::util::hash::hex_bytes;
 
-use crate::chainstate::stacks::boot::{BOOT_CODE_COSTS, BOOT_CODE_COSTS_2, BOOT_CODE_COSTS_3};
+use crate::chainstate::stacks::boot::{
+    BOOT_CODE_COSTS, BOOT_CODE_COSTS_2, BOOT_CODE_COSTS_3, BOOT_CODE_COSTS_4,
+};
 use crate::chainstate::stacks::index::ClarityMarfTrieId;
 use crate::clarity_vm::clarity::{ClarityBlockConnection, ClarityInstance, Error as ClarityError};
 use crate::clarity_vm::database::marf::MarfedKV;
@@ -187,6 +189,25 @@ fn test_simple_token_system(#[case] version: ClarityVersion, #[case] epoch: Stac
                 )
                 .unwrap();
             }
+            StacksEpochId::Epoch31 => {
+                let (ast, _analysis) = tx
+                    .analyze_smart_contract(
+                        &boot_code_id("costs-4", false),
+                        ClarityVersion::Clarity2,
+                        BOOT_CODE_COSTS_4,
+                        ASTRules::PrecheckSize,
+                    )
+                    .unwrap();
+                tx.initialize_smart_contract(
+                    &boot_code_id("costs-4", false),
+                    ClarityVersion::Clarity2,
+                    &ast,
+                    BOOT_CODE_COSTS_4,
+                    None,
+                    |_, _| false,
+                )
+                .unwrap();
+            }
             _ => panic!("Epoch {} not covered.", &epoch),
         }
     });
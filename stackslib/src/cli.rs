@@ -22,28 +22,39 @@ use std::time::Instant;
 use std::{env, fs, io, process, thread};
 
 use clarity::types::chainstate::SortitionId;
+use clarity::vm::ast::ASTRules;
+use clarity::vm::contexts::AssetMap;
+use clarity::vm::costs::profile::CostProfile;
+use clarity::vm::events::{FTEventType, NFTEventType, STXEventType, StacksTransactionEvent};
+use clarity::vm::tracer::{ExecutionTracer, TraceEntry};
+use clarity::vm::Value;
 use db::blocks::DummyEventDispatcher;
 use db::ChainstateTx;
 use regex::Regex;
-use rusqlite::{Connection, OpenFlags};
+use rusqlite::{params, Connection, OpenFlags};
+use serde_json::json;
 use stacks_common::types::chainstate::{BlockHeaderHash, BurnchainHeaderHash, StacksBlockId};
 use stacks_common::types::sqlite::NO_PARAMS;
 
 use crate::burnchains::db::BurnchainDB;
-use crate::burnchains::PoxConstants;
+use crate::burnchains::{PoxConstants, Txid};
 use crate::chainstate::burn::db::sortdb::{
     get_ancestor_sort_id, SortitionDB, SortitionHandle, SortitionHandleContext,
 };
 use crate::chainstate::burn::{BlockSnapshot, ConsensusHash};
 use crate::chainstate::coordinator::OnChainRewardSetProvider;
+use crate::chainstate::nakamoto::miner::NakamotoBlockBuilder;
 use crate::chainstate::nakamoto::{NakamotoBlock, NakamotoChainState};
 use crate::chainstate::stacks::db::blocks::StagingBlock;
 use crate::chainstate::stacks::db::{StacksBlockHeaderTypes, StacksChainState, StacksHeaderInfo};
+use crate::chainstate::stacks::events::StacksTransactionReceipt;
+use crate::chainstate::stacks::index::marf::MARFOpenOpts;
 use crate::chainstate::stacks::miner::*;
 use crate::chainstate::stacks::{Error as ChainstateError, *};
 use crate::clarity_vm::clarity::ClarityInstance;
+use crate::clarity_vm::database::marf::MarfedKV;
 use crate::core::*;
-use crate::util_lib::db::IndexDBTx;
+use crate::util_lib::db::{query_row_columns, IndexDBTx};
 
 /// Can be used with CLI commands to support non-mainnet chainstate
 /// Allows integration testing of these functions
@@ -117,7 +128,19 @@ const STACKS_CHAIN_CONFIG_DEFAULT_MAINNET: LazyCell<StacksChainConfig> =
 ///
 /// Arguments:
 ///  - `argv`: Args in CLI format: `<command-name> [args...]`
+///
+/// With `--optimize`, contracts are run through the constant-folding pass as they are loaded.
+/// This makes replay faster, but changes execution costs, so blocks whose validity depends on
+/// their costs may not replay the same way.
 pub fn command_replay_block(argv: &[String], conf: Option<&StacksChainConfig>) {
+    let mut argv = argv.to_vec();
+    let optimize = match argv.iter().position(|arg| arg == "--optimize") {
+        Some(ix) => {
+            argv.remove(ix);
+            true
+        }
+        None => false,
+    };
     let print_help_and_exit = || -> ! {
         let n = &argv[0];
         eprintln!("Usage:");
@@ -126,6 +149,8 @@ pub fn command_replay_block(argv: &[String], conf: Option<&StacksChainConfig>) {
         eprintln!("  {n} <database-path> index-range <start-block> <end-block>");
         eprintln!("  {n} <database-path> range <start-block> <end-block>");
         eprintln!("  {n} <database-path> <first|last> <block-count>");
+        eprintln!("Options:");
+        eprintln!("  --optimize  Fold constants in contracts while replaying (changes costs)");
         process::exit(1);
     };
     let start = Instant::now();
@@ -185,7 +210,7 @@ pub fn command_replay_block(argv: &[String], conf: Option<&StacksChainConfig>) {
         if i % 100 == 0 {
             println!("Checked {i}...");
         }
-        replay_staging_block(db_path, index_block_hash, conf);
+        replay_staging_block(db_path, index_block_hash, conf, optimize);
     }
     println!("Finished. run_time_seconds = {}", start.elapsed().as_secs());
 }
@@ -369,11 +394,178 @@ pub fn command_replay_mock_mining(argv: &[String], conf: Option<&StacksChainConf
     }
 }
 
+/// Replay a single transaction with `replay_transaction()`, and print its result, its cost
+/// profile and its execution trace (as JSON, one trace entry per line)
+/// Terminates on error using `process::exit()`
+///
+/// Arguments:
+///  - `argv`: Args in CLI format: `<command-name> [args...]`
+///  - `conf`: Optional config for running on non-mainnet chainstate
+pub fn command_replay_transaction(argv: &[String], conf: Option<&StacksChainConfig>) {
+    let (txid, replay) = replay_transaction_from_args(argv, conf);
+
+    println!(
+        "Transaction {txid} (index {} of block {})",
+        replay.tx_index, replay.block_id
+    );
+    println!("Result: {}", replay.receipt.result);
+    if let Some(vm_error) = replay.receipt.vm_error.as_ref() {
+        println!("VM error: {vm_error}");
+    }
+    println!(
+        "Post-condition aborted: {}",
+        replay.receipt.post_condition_aborted
+    );
+    println!(
+        "Execution cost: {}",
+        serde_json::to_string(&replay.receipt.execution_cost).unwrap()
+    );
+    if let Some(cost_profile) = replay.cost_profile.as_ref() {
+        println!(
+            "Cost profile: {}",
+            serde_json::to_string(cost_profile).unwrap()
+        );
+    }
+    println!("Trace:");
+    for entry in replay.trace.iter() {
+        println!("{}", serde_json::to_string(entry).unwrap());
+    }
+}
+
+/// Replay the transaction given by `argv` (see `command_replay_transaction()`)
+/// Terminates on error using `process::exit()`
+fn replay_transaction_from_args(
+    argv: &[String],
+    conf: Option<&StacksChainConfig>,
+) -> (Txid, TransactionReplay) {
+    let print_help_and_exit = || -> ! {
+        let n = &argv[0];
+        eprintln!("Usage:");
+        eprintln!("  {n} <database-path> <txid> [<index-block-hash>]");
+        process::exit(1);
+    };
+
+    let db_path = argv.get(1).unwrap_or_else(|| print_help_and_exit());
+    let txid = argv
+        .get(2)
+        .map(|txid| Txid::from_hex(txid).unwrap_or_else(|_| print_help_and_exit()))
+        .unwrap_or_else(|| print_help_and_exit());
+    let block_id = argv.get(3).map(|block_id| {
+        StacksBlockId::from_hex(block_id).unwrap_or_else(|_| print_help_and_exit())
+    });
+
+    let default_conf = STACKS_CHAIN_CONFIG_DEFAULT_MAINNET;
+    let conf = conf.unwrap_or(&default_conf);
+
+    let chain_state_path = format!("{db_path}/chainstate/");
+    let sort_db_path = format!("{db_path}/burnchain/sortition");
+
+    let mainnet = conf.chain_id == CHAIN_ID_MAINNET;
+    let (mut chainstate, _) =
+        StacksChainState::open(mainnet, conf.chain_id, &chain_state_path, None).unwrap();
+
+    let sortdb = SortitionDB::connect(
+        &sort_db_path,
+        conf.first_block_height,
+        &conf.first_burn_header_hash,
+        conf.first_burn_header_timestamp,
+        &conf.epochs,
+        conf.pox_constants.clone(),
+        None,
+        true,
+    )
+    .unwrap();
+
+    let replay = match replay_transaction(&sortdb, &mut chainstate, &txid, block_id.as_ref()) {
+        Ok(replay) => replay,
+        Err(e) => {
+            eprintln!("Failed to replay transaction {txid}: {e}");
+            process::exit(1);
+        }
+    };
+
+    (txid, replay)
+}
+
+/// Replay a single transaction with `replay_transaction()`, and print a JSON report of its
+/// result, events, asset map, cost breakdown and execution trace
+/// Terminates on error using `process::exit()`
+///
+/// Arguments:
+///  - `argv`: Args in CLI format: `<command-name> [args...]`
+///  - `conf`: Optional config for running on non-mainnet chainstate
+pub fn command_replay_tx(argv: &[String], conf: Option<&StacksChainConfig>) {
+    let (txid, replay) = replay_transaction_from_args(argv, conf);
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&replay.to_json(&txid)).unwrap()
+    );
+}
+
+/// Export the Clarity state at a block to a file, which a dev-net node can be seeded with (see
+/// `ClarityStateSnapshot`)
+/// Terminates on error using `process::exit()`
+///
+/// Arguments:
+///  - `argv`: Args in CLI format: `<command-name> [args...]`
+pub fn command_export_clarity_state(argv: &[String]) {
+    let print_help_and_exit = || -> ! {
+        let n = &argv[0];
+        eprintln!("Usage:");
+        eprintln!("  {n} <database-path> <index-block-hash> <output-file>");
+        process::exit(1);
+    };
+
+    let db_path = argv.get(1).unwrap_or_else(|| print_help_and_exit());
+    let block_id = argv
+        .get(2)
+        .map(|block_id| StacksBlockId::from_hex(block_id).unwrap_or_else(|_| print_help_and_exit()))
+        .unwrap_or_else(|| print_help_and_exit());
+    let output_path = argv.get(3).unwrap_or_else(|| print_help_and_exit());
+
+    let clarity_path =
+        StacksChainState::vm_state_index_root_path(PathBuf::from(format!("{db_path}/chainstate/")));
+    let mut marf = MarfedKV::open(
+        clarity_path
+            .to_str()
+            .expect("FATAL: non-UTF-8 character in filename"),
+        None,
+        None,
+    )
+    .unwrap_or_else(|e| {
+        eprintln!(
+            "Failed to open Clarity state at {}: {e}",
+            clarity_path.display()
+        );
+        process::exit(1);
+    });
+
+    let snapshot = marf.export_state(&block_id).unwrap_or_else(|e| {
+        eprintln!("Failed to export Clarity state at {block_id}: {e}");
+        process::exit(1);
+    });
+    let mut file = io::BufWriter::new(fs::File::create(output_path).unwrap_or_else(|e| {
+        eprintln!("Failed to create {output_path}: {e}");
+        process::exit(1);
+    }));
+    snapshot.write_to(&mut file).unwrap_or_else(|e| {
+        eprintln!("Failed to write {output_path}: {e}");
+        process::exit(1);
+    });
+    println!(
+        "Exported {} keys and {} metadata entries at {block_id} (height {}) to {output_path}",
+        snapshot.data.len(),
+        snapshot.metadata.len(),
+        snapshot.block_height
+    );
+}
+
 /// Fetch and process a `StagingBlock` from database and call `replay_block()` to validate
 fn replay_staging_block(
     db_path: &str,
     index_block_hash_hex: &str,
     conf: Option<&StacksChainConfig>,
+    optimize: bool,
 ) {
     let block_id = StacksBlockId::from_hex(index_block_hash_hex).unwrap();
     let chain_state_path = format!("{db_path}/chainstate/");
@@ -385,8 +577,12 @@ fn replay_staging_block(
     let conf = conf.unwrap_or(&default_conf);
 
     let mainnet = conf.chain_id == CHAIN_ID_MAINNET;
+    let marf_opts = optimize.then(|| MARFOpenOpts {
+        optimize_contracts: true,
+        ..MARFOpenOpts::default()
+    });
     let (mut chainstate, _) =
-        StacksChainState::open(mainnet, conf.chain_id, &chain_state_path, None).unwrap();
+        StacksChainState::open(mainnet, conf.chain_id, &chain_state_path, marf_opts).unwrap();
 
     let mut sortdb = SortitionDB::connect(
         &sort_db_path,
@@ -940,3 +1136,227 @@ fn replay_block_nakamoto(
 
     Ok(())
 }
+
+/// The outcome of replaying a single transaction with `replay_transaction()`
+#[derive(Debug)]
+pub struct TransactionReplay {
+    /// The block the transaction was mined in
+    pub block_id: StacksBlockId,
+    /// The transaction's position in the block
+    pub tx_index: usize,
+    pub receipt: StacksTransactionReceipt,
+    /// Breakdown of the transaction's cost.  `None` if the block doesn't track costs.
+    pub cost_profile: Option<CostProfile>,
+    /// Everything the Clarity VM did while executing the transaction
+    pub trace: Vec<TraceEntry>,
+    /// The assets each principal sent or burned, which the transaction's post-conditions were
+    /// checked against
+    pub asset_map: AssetMap,
+}
+
+impl TransactionReplay {
+    /// Whether the transaction's state changes (and so its events) were committed
+    pub fn committed(&self) -> bool {
+        !self.receipt.post_condition_aborted
+            && matches!(&self.receipt.result, Value::Response(response) if response.committed)
+    }
+
+    /// The replay as a JSON report
+    pub fn to_json(&self, txid: &Txid) -> serde_json::Value {
+        let committed = self.committed();
+        let events: Vec<_> = self
+            .receipt
+            .events
+            .iter()
+            .enumerate()
+            .map(|(event_index, event)| {
+                event
+                    .json_serialize(event_index, txid, committed)
+                    .unwrap_or_else(|e| json!({ "error": format!("{e}") }))
+            })
+            .collect();
+        json!({
+            "txid": txid.to_hex(),
+            "block_id": self.block_id.to_hex(),
+            "tx_index": self.tx_index,
+            "result": self.receipt.result.to_string(),
+            "committed": committed,
+            "vm_error": self.receipt.vm_error,
+            "post_condition_aborted": self.receipt.post_condition_aborted,
+            "stx_burned": self.receipt.stx_burned.to_string(),
+            "events": events,
+            "asset_map": self.asset_map.to_json(),
+            "cost": {
+                "total": self.receipt.execution_cost,
+                "profile": self.cost_profile,
+            },
+            "trace": self.trace,
+        })
+    }
+}
+
+/// The asset map of a transaction with events `events`: the STX, tokens and NFTs each principal
+/// transferred or burned.  Mints don't count.
+fn asset_map_from_events(events: &[StacksTransactionEvent]) -> Result<AssetMap, ChainstateError> {
+    let mut asset_map = AssetMap::new();
+    for event in events.iter() {
+        match event {
+            StacksTransactionEvent::STXEvent(STXEventType::STXTransferEvent(data)) => {
+                asset_map.add_stx_transfer(&data.sender, data.amount)?
+            }
+            StacksTransactionEvent::STXEvent(STXEventType::STXBurnEvent(data)) => {
+                asset_map.add_stx_burn(&data.sender, data.amount)?
+            }
+            StacksTransactionEvent::FTEvent(FTEventType::FTTransferEvent(data)) => asset_map
+                .add_token_transfer(&data.sender, data.asset_identifier.clone(), data.amount)?,
+            StacksTransactionEvent::FTEvent(FTEventType::FTBurnEvent(data)) => asset_map
+                .add_token_transfer(&data.sender, data.asset_identifier.clone(), data.amount)?,
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTTransferEvent(data)) => asset_map
+                .add_asset_transfer(
+                    &data.sender,
+                    data.asset_identifier.clone(),
+                    data.value.clone(),
+                ),
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTBurnEvent(data)) => asset_map
+                .add_asset_transfer(
+                    &data.sender,
+                    data.asset_identifier.clone(),
+                    data.value.clone(),
+                ),
+            _ => {}
+        }
+    }
+    Ok(asset_map)
+}
+
+/// Find the blocks that mined `txid`, according to the transaction log.  The log is only
+/// written if the node runs with `STACKS_TRANSACTION_LOG` set.
+fn get_logged_transaction_blocks(
+    chainstate: &StacksChainState,
+    txid: &Txid,
+) -> Result<Vec<StacksBlockId>, ChainstateError> {
+    let sql = "SELECT index_block_hash FROM transactions WHERE txid = ?1";
+    query_row_columns(chainstate.db(), sql, params![txid], "index_block_hash")
+        .map_err(ChainstateError::DBError)
+}
+
+/// Re-execute the historical transaction `txid`, with execution tracing and cost profiling
+/// enabled.  The transaction's block is re-mined on top of its parent's state, up to and
+/// including the transaction, and then rolled back, so the chainstate is not modified.
+///
+/// If `block_id` isn't given, the block is looked up in the transaction log.  Only Nakamoto
+/// blocks can be replayed.
+pub fn replay_transaction(
+    sortdb: &SortitionDB,
+    chainstate: &mut StacksChainState,
+    txid: &Txid,
+    block_id: Option<&StacksBlockId>,
+) -> Result<TransactionReplay, ChainstateError> {
+    let block_id = match block_id {
+        Some(block_id) => block_id.clone(),
+        None => {
+            let mut block_ids = get_logged_transaction_blocks(chainstate, txid)?;
+            if block_ids.len() > 1 {
+                warn!("Transaction was mined in several forks; replaying the first one";
+                      "txid" => %txid, "block_ids" => ?block_ids);
+            }
+            if block_ids.is_empty() {
+                return Err(ChainstateError::InvalidStacksTransaction(
+                    format!("Transaction {txid} is not in the transaction log"),
+                    false,
+                ));
+            }
+            block_ids.swap_remove(0)
+        }
+    };
+    let Some((block, _)) = chainstate
+        .nakamoto_blocks_db()
+        .get_nakamoto_block(&block_id)?
+    else {
+        if NakamotoChainState::get_block_header(chainstate.db(), &block_id)?.is_some() {
+            return Err(ChainstateError::InvalidStacksBlock(
+                "Epoch 2.x blocks not supported yet".into(),
+            ));
+        }
+        return Err(ChainstateError::NoSuchBlockError);
+    };
+    let Some(tx_index) = block.txs.iter().position(|tx| tx.txid() == *txid) else {
+        return Err(ChainstateError::InvalidStacksTransaction(
+            format!("Transaction {txid} is not in block {block_id}"),
+            false,
+        ));
+    };
+
+    let parent_header =
+        NakamotoChainState::get_block_header(chainstate.db(), &block.header.parent_block_id)?
+            .ok_or(ChainstateError::NoSuchBlockError)?;
+    let burn_view = NakamotoChainState::get_block_burn_view(sortdb, &block, &parent_header)?;
+    let burn_view_sn = SortitionDB::get_block_snapshot_consensus(sortdb.conn(), &burn_view)?
+        .ok_or(ChainstateError::NoSuchBlockError)?;
+    let burn_dbconn = sortdb.index_handle(&burn_view_sn.sortition_id);
+
+    let tenure_change = block
+        .txs
+        .iter()
+        .find(|tx| matches!(tx.payload, TransactionPayload::TenureChange(..)));
+    let coinbase = block
+        .txs
+        .iter()
+        .find(|tx| matches!(tx.payload, TransactionPayload::Coinbase(..)));
+    let tenure_cause = tenure_change.and_then(|tx| match &tx.payload {
+        TransactionPayload::TenureChange(tc) => Some(tc.cause),
+        _ => None,
+    });
+    let mut builder = NakamotoBlockBuilder::new(
+        &parent_header,
+        &block.header.consensus_hash,
+        block.header.burn_spent,
+        tenure_change,
+        coinbase,
+        block.header.pox_treatment.len(),
+        None,
+    )?;
+    let mut miner_tenure_info = builder.load_tenure_info(chainstate, &burn_dbconn, tenure_cause)?;
+    let mut tenure_tx = builder.tenure_begin(&burn_dbconn, &mut miner_tenure_info)?;
+
+    let tracer = ExecutionTracer::new();
+    let mut result = None;
+    for (i, tx) in block.txs[..=tx_index].iter().enumerate() {
+        if i == tx_index {
+            tenure_tx
+                .connection()
+                .set_eval_hook(Some(Box::new(tracer.clone())));
+            tenure_tx.connection().enable_cost_profile();
+        }
+        let tx_result = builder.try_mine_tx_with_len(
+            &mut tenure_tx,
+            tx,
+            tx.tx_len(),
+            &BlockLimitFunction::NO_LIMIT_HIT,
+            ASTRules::PrecheckSize,
+        );
+        let receipt = match tx_result {
+            TransactionResult::Success(s) => s.receipt,
+            TransactionResult::Skipped(s) => return Err(s.error),
+            TransactionResult::ProcessingError(e) => return Err(e.error),
+            TransactionResult::Problematic(p) => return Err(p.error),
+        };
+        if i == tx_index {
+            tenure_tx.connection().set_eval_hook(None);
+            let cost_profile = tenure_tx.connection().take_cost_profile();
+            result = Some((receipt, cost_profile));
+        }
+    }
+    tenure_tx.rollback_block();
+
+    let (receipt, cost_profile) = result.ok_or(ChainstateError::NoTransactionsToMine)?;
+    let asset_map = asset_map_from_events(&receipt.events)?;
+    Ok(TransactionReplay {
+        block_id,
+        tx_index,
+        receipt,
+        cost_profile,
+        trace: tracer.entries(),
+        asset_map,
+    })
+}
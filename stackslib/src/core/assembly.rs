@@ -0,0 +1,116 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Block-assembly strategies decide the order in which the miner's mempool walk considers
+//! transactions.  The walk draws its candidates from three sources: the retry cache (transactions
+//! whose nonces were too high when they were first seen), the transactions with a fee-rate
+//! estimate (highest fee rate first), and the transactions without one.  A strategy picks which
+//! source to draw from next, and whether to follow an account's chain of nonces once one of its
+//! transactions is mined.
+//!
+//! Strategies are selected by name with the `miner.block_assembly_strategy` option.
+
+use std::fmt;
+use std::sync::Arc;
+
+/// A source of candidate transactions for the mempool walk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CandidateSource {
+    /// Transactions whose nonces were too high when they were considered
+    RetryCache,
+    /// Transactions with a fee-rate estimate, highest fee rate first
+    FeeRate,
+    /// Transactions without a fee-rate estimate
+    NoEstimate,
+}
+
+/// How the miner orders the transactions it considers for a block
+pub trait BlockAssemblyStrategy: Send + Sync + fmt::Debug {
+    /// The name that selects this strategy in the miner config
+    fn name(&self) -> &'static str;
+
+    /// The sources to draw the next candidate from, in order of preference.  `pick_no_estimate`
+    ///  is sampled for each candidate from `consider_no_estimate_tx_prob`.
+    fn candidate_sources(&self, pick_no_estimate: bool) -> Vec<CandidateSource>;
+
+    /// Whether to consider an account's transaction with the next nonce as soon as one of its
+    ///  transactions is mined, ahead of every other candidate
+    fn follow_nonce_chains(&self) -> bool {
+        false
+    }
+}
+
+/// Marker for strategies whose ordering resists miner-extractable value: where a transaction
+///  lands in the block can't be bought beyond its fee rate, or influenced by its contents.
+pub trait MevResistantOrdering: BlockAssemblyStrategy {}
+
+/// Consider the retry cache first, then the transactions by fee rate (or, with
+///  `consider_no_estimate_tx_prob`, a transaction without an estimate).  The default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GreedyFeeRate;
+
+impl BlockAssemblyStrategy for GreedyFeeRate {
+    fn name(&self) -> &'static str {
+        "fee-rate"
+    }
+
+    fn candidate_sources(&self, pick_no_estimate: bool) -> Vec<CandidateSource> {
+        if pick_no_estimate {
+            vec![
+                CandidateSource::RetryCache,
+                CandidateSource::NoEstimate,
+                CandidateSource::FeeRate,
+            ]
+        } else {
+            vec![
+                CandidateSource::RetryCache,
+                CandidateSource::FeeRate,
+                CandidateSource::NoEstimate,
+            ]
+        }
+    }
+}
+
+/// Like `GreedyFeeRate`, but once an account's transaction is mined, its transaction with the
+///  next nonce is considered right away.  Chains of transactions from one account are mined
+///  together instead of waiting for a later pass over the retry cache.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NonceChainAware;
+
+impl BlockAssemblyStrategy for NonceChainAware {
+    fn name(&self) -> &'static str {
+        "nonce-chain"
+    }
+
+    fn candidate_sources(&self, pick_no_estimate: bool) -> Vec<CandidateSource> {
+        GreedyFeeRate.candidate_sources(pick_no_estimate)
+    }
+
+    fn follow_nonce_chains(&self) -> bool {
+        true
+    }
+}
+
+/// The names of the built-in strategies
+pub const BLOCK_ASSEMBLY_STRATEGIES: &[&str] = &["fee-rate", "nonce-chain"];
+
+/// The built-in strategy called `name`
+pub fn block_assembly_strategy(name: &str) -> Option<Arc<dyn BlockAssemblyStrategy>> {
+    match name {
+        "fee-rate" => Some(Arc::new(GreedyFeeRate)),
+        "nonce-chain" => Some(Arc::new(NonceChainAware)),
+        _ => None,
+    }
+}
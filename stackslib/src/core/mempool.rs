@@ -21,10 +21,11 @@ use std::io::{Read, Write};
 use std::ops::{Deref, DerefMut};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime};
 use std::{fs, io};
 
-use clarity::vm::types::PrincipalData;
+use clarity::vm::types::{PrincipalData, QualifiedContractIdentifier, StandardPrincipalData};
 use rand::distributions::Uniform;
 use rand::prelude::Distribution;
 use rusqlite::types::ToSql;
@@ -55,6 +56,7 @@ use crate::chainstate::stacks::{
     Error as ChainstateError, StacksBlock, StacksMicroblock, StacksTransaction, TransactionPayload,
 };
 use crate::clarity_vm::clarity::ClarityConnection;
+use crate::core::assembly::{BlockAssemblyStrategy, CandidateSource, GreedyFeeRate};
 use crate::core::{
     ExecutionCost, StacksEpochId, FIRST_BURNCHAIN_CONSENSUS_HASH, FIRST_STACKS_BLOCK_HASH,
 };
@@ -435,6 +437,95 @@ pub struct MemPoolTxInfoPartial {
     pub sponsor_nonce: u64,
 }
 
+/// Kinds of transaction payload that a mempool listing can be filtered by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemPoolPayloadType {
+    TokenTransfer,
+    ContractCall,
+    SmartContract,
+    PoisonMicroblock,
+    Coinbase,
+    TenureChange,
+}
+
+impl MemPoolPayloadType {
+    pub fn of(payload: &TransactionPayload) -> MemPoolPayloadType {
+        match payload {
+            TransactionPayload::TokenTransfer(..) => MemPoolPayloadType::TokenTransfer,
+            TransactionPayload::ContractCall(..) => MemPoolPayloadType::ContractCall,
+            TransactionPayload::SmartContract(..) => MemPoolPayloadType::SmartContract,
+            TransactionPayload::PoisonMicroblock(..) => MemPoolPayloadType::PoisonMicroblock,
+            TransactionPayload::Coinbase(..) => MemPoolPayloadType::Coinbase,
+            TransactionPayload::TenureChange(..) => MemPoolPayloadType::TenureChange,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MemPoolPayloadType::TokenTransfer => "token_transfer",
+            MemPoolPayloadType::ContractCall => "contract_call",
+            MemPoolPayloadType::SmartContract => "smart_contract",
+            MemPoolPayloadType::PoisonMicroblock => "poison_microblock",
+            MemPoolPayloadType::Coinbase => "coinbase",
+            MemPoolPayloadType::TenureChange => "tenure_change",
+        }
+    }
+}
+
+impl FromStr for MemPoolPayloadType {
+    type Err = String;
+    fn from_str(s: &str) -> Result<MemPoolPayloadType, String> {
+        let payload_type = match s {
+            "token_transfer" => MemPoolPayloadType::TokenTransfer,
+            "contract_call" => MemPoolPayloadType::ContractCall,
+            "smart_contract" => MemPoolPayloadType::SmartContract,
+            "poison_microblock" => MemPoolPayloadType::PoisonMicroblock,
+            "coinbase" => MemPoolPayloadType::Coinbase,
+            "tenure_change" => MemPoolPayloadType::TenureChange,
+            _ => return Err(format!("Unknown payload type '{}'", s)),
+        };
+        Ok(payload_type)
+    }
+}
+
+/// Which transactions to list from the mempool. Unset fields match every transaction.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemPoolTxFilter {
+    pub origin: Option<StacksAddress>,
+    /// Matches calls to this contract, as well as its deployment
+    pub contract: Option<QualifiedContractIdentifier>,
+    pub payload_type: Option<MemPoolPayloadType>,
+    pub min_fee: Option<u64>,
+    pub max_fee: Option<u64>,
+}
+
+impl MemPoolTxFilter {
+    /// Does this transaction pass the filters that can't be evaluated in SQL?
+    fn matches_payload(&self, tx: &StacksTransaction) -> bool {
+        if let Some(payload_type) = self.payload_type {
+            if MemPoolPayloadType::of(&tx.payload) != payload_type {
+                return false;
+            }
+        }
+        if let Some(contract) = self.contract.as_ref() {
+            let matches_contract = match &tx.payload {
+                TransactionPayload::ContractCall(call) => {
+                    call.to_clarity_contract_id() == *contract
+                }
+                TransactionPayload::SmartContract(smart_contract, _) => {
+                    contract.issuer == StandardPrincipalData::from(tx.origin_address())
+                        && contract.name == smart_contract.name
+                }
+                _ => false,
+            };
+            if !matches_contract {
+                return false;
+            }
+        }
+        true
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct MemPoolTxMetadata {
     pub txid: Txid,
@@ -540,6 +631,15 @@ pub struct MemPoolWalkSettings {
     /// What percentage of the remaining cost limit should we consume before stopping the walk
     /// None means we consume the entire cost limit ASAP
     pub tenure_cost_limit_per_block_percentage: Option<u8>,
+    /// Skip transactions whose estimated cost can't fit in the rest of the tenure's budget,
+    /// instead of trying to mine them
+    pub skip_txs_over_tenure_budget: bool,
+    /// The tenure's remaining budget when the walk starts.  Set by the block builder for each
+    /// walk when `skip_txs_over_tenure_budget` is set; the walk deducts the cost of the
+    /// transactions it mines.
+    pub tenure_budget: Option<ExecutionCost>,
+    /// How to order the transactions considered for the block
+    pub block_assembly_strategy: Arc<dyn BlockAssemblyStrategy>,
 }
 
 impl Default for MemPoolWalkSettings {
@@ -558,6 +658,9 @@ impl Default for MemPoolWalkSettings {
             .collect(),
             filter_origins: HashSet::new(),
             tenure_cost_limit_per_block_percentage: None,
+            skip_txs_over_tenure_budget: false,
+            tenure_budget: None,
+            block_assembly_strategy: Arc::new(GreedyFeeRate),
         }
     }
 }
@@ -577,6 +680,9 @@ impl MemPoolWalkSettings {
             .collect(),
             filter_origins: HashSet::new(),
             tenure_cost_limit_per_block_percentage: None,
+            skip_txs_over_tenure_budget: false,
+            tenure_budget: None,
+            block_assembly_strategy: Arc::new(GreedyFeeRate),
         }
     }
 }
@@ -820,6 +926,30 @@ const MEMPOOL_SCHEMA_7_TIME_ESTIMATES: &'static [&'static str] = &[
     "#,
 ];
 
+const MEMPOOL_SCHEMA_8_REQUIRED_TXS: &'static [&'static str] = &[
+    r#"
+    CREATE TABLE required_txs(
+        set_id TEXT NOT NULL,
+        -- position of the transaction in its set
+        set_index INTEGER NOT NULL,
+        txid TEXT NOT NULL,
+        tx BLOB NOT NULL,
+        submit_time INTEGER NOT NULL,
+        -- ALLOW NULL; the block that included the transaction, once the signers accepted it
+        included_in TEXT,
+        -- ALLOW NULL; why the miner could not mine the transaction
+        rejection TEXT,
+        PRIMARY KEY (set_id, txid)
+    );
+    "#,
+    r#"
+    CREATE INDEX required_txs_by_txid ON required_txs(txid);
+    "#,
+    r#"
+    INSERT INTO schema_version (version) VALUES (8)
+    "#,
+];
+
 const MEMPOOL_INDEXES: &'static [&'static str] = &[
     "CREATE INDEX IF NOT EXISTS by_txid ON mempool(txid);",
     "CREATE INDEX IF NOT EXISTS by_height ON mempool(height);",
@@ -1307,6 +1437,9 @@ impl MemPoolDB {
                     MemPoolDB::instantiate_schema_7(tx)?;
                 }
                 7 => {
+                    MemPoolDB::instantiate_required_txs(tx)?;
+                }
+                8 => {
                     break;
                 }
                 _ => {
@@ -1393,6 +1526,16 @@ impl MemPoolDB {
         Ok(())
     }
 
+    /// Add the required transactions table
+    #[cfg_attr(test, mutants::skip)]
+    fn instantiate_required_txs(tx: &DBTx) -> Result<(), db_error> {
+        for sql_exec in MEMPOOL_SCHEMA_8_REQUIRED_TXS {
+            tx.execute_batch(sql_exec)?;
+        }
+
+        Ok(())
+    }
+
     #[cfg_attr(test, mutants::skip)]
     pub fn db_path(chainstate_root_path: &str) -> Result<String, db_error> {
         let mut path = PathBuf::from(chainstate_root_path);
@@ -1649,6 +1792,11 @@ impl MemPoolDB {
         let mut rng = rand::thread_rng();
         let mut candidate_cache = CandidateCache::new(settings.candidate_retry_cache_size);
         let mut nonce_cache = NonceCache::new(settings.nonce_cache_size);
+        let mut tenure_budget = settings.tenure_budget.clone();
+        let strategy = settings.block_assembly_strategy.clone();
+        // candidates that follow a just-mined transaction, for strategies that follow nonce chains
+        let mut nonce_chain: VecDeque<MemPoolTxInfoPartial> = VecDeque::new();
+        let epoch_id = clarity_tx.get_epoch();
 
         // set of (address, nonce) to store after the inner loop completes.  This will be done in a
         // single transaction.  This cannot grow to more than `settings.nonce_cache_size` entries.
@@ -1688,48 +1836,46 @@ impl MemPoolDB {
                 break MempoolIterationStopReason::DeadlineReached;
             }
 
-            let start_with_no_estimate =
+            let pick_no_estimate =
                 tx_consideration_sampler.sample(&mut rng) < settings.consider_no_estimate_tx_prob;
 
-            // First, try to read from the retry list
-            let (candidate, update_estimate) = match candidate_cache.next() {
-                Some(tx) => {
-                    let update_estimate = tx.fee_rate.is_none();
-                    (tx, update_estimate)
+            // A transaction following one that was just mined goes first; otherwise, draw from
+            // the sources in the strategy's order of preference.
+            let mut next_candidate = nonce_chain.pop_front().map(|tx| {
+                let update_estimate = tx.fee_rate.is_none();
+                (tx, update_estimate)
+            });
+            for source in strategy.candidate_sources(pick_no_estimate) {
+                if next_candidate.is_some() {
+                    break;
                 }
-                None => {
-                    // When the retry list is empty, read from the mempool db,
-                    // randomly selecting from either the null fee-rate transactions
-                    // or those with fee-rate estimates.
-                    let opt_tx = if start_with_no_estimate {
-                        null_iterator
+                next_candidate = match source {
+                    CandidateSource::RetryCache => candidate_cache.next().map(|tx| {
+                        let update_estimate = tx.fee_rate.is_none();
+                        (tx, update_estimate)
+                    }),
+                    CandidateSource::FeeRate => {
+                        match fee_iterator.next().map_err(|err| Error::SqliteError(err))? {
+                            Some(row) => Some((MemPoolTxInfoPartial::from_row(row)?, false)),
+                            None => None,
+                        }
+                    }
+                    CandidateSource::NoEstimate => {
+                        match null_iterator
                             .next()
                             .map_err(|err| Error::SqliteError(err))?
-                    } else {
-                        fee_iterator.next().map_err(|err| Error::SqliteError(err))?
-                    };
-                    match opt_tx {
-                        Some(row) => (MemPoolTxInfoPartial::from_row(row)?, start_with_no_estimate),
-                        None => {
-                            // If the selected iterator is empty, check the other
-                            match if start_with_no_estimate {
-                                fee_iterator.next().map_err(|err| Error::SqliteError(err))?
-                            } else {
-                                null_iterator
-                                    .next()
-                                    .map_err(|err| Error::SqliteError(err))?
-                            } {
-                                Some(row) => (
-                                    MemPoolTxInfoPartial::from_row(row)?,
-                                    !start_with_no_estimate,
-                                ),
-                                None => {
-                                    debug!("No more transactions to consider in mempool");
-                                    break MempoolIterationStopReason::NoMoreCandidates;
-                                }
-                            }
+                        {
+                            Some(row) => Some((MemPoolTxInfoPartial::from_row(row)?, true)),
+                            None => None,
                         }
                     }
+                };
+            }
+            let (candidate, update_estimate) = match next_candidate {
+                Some(next_candidate) => next_candidate,
+                None => {
+                    debug!("No more transactions to consider in mempool");
+                    break MempoolIterationStopReason::NoMoreCandidates;
                 }
             };
 
@@ -1850,6 +1996,22 @@ impl MemPoolDB {
                 continue;
             }
 
+            if let Some(budget) = tenure_budget.as_ref() {
+                // transactions without an estimate are tried, since they may well fit
+                if let Ok(estimated_cost) = self
+                    .cost_estimator
+                    .estimate_cost(&tx_info.tx.payload, &epoch_id)
+                {
+                    if estimated_cost.exceeds(budget) {
+                        debug!("Will skip mempool tx, since its estimated cost exceeds the remaining tenure budget";
+                               "txid" => %tx_info.tx.txid(),
+                               "estimated_cost" => %estimated_cost,
+                               "tenure_budget" => %budget);
+                        continue;
+                    }
+                }
+            }
+
             let consider = ConsiderTransaction {
                 tx: tx_info,
                 update_estimate,
@@ -1870,7 +2032,13 @@ impl MemPoolDB {
             match todo(clarity_tx, &consider, self.cost_estimator.as_mut())? {
                 Some(tx_event) => {
                     match tx_event {
-                        TransactionEvent::Success(_) => {
+                        TransactionEvent::Success(ref success) => {
+                            if let Some(budget) = tenure_budget.as_mut() {
+                                if budget.sub(&success.execution_cost).is_err() {
+                                    *budget = ExecutionCost::zero();
+                                }
+                            }
+
                             // Bump nonces in the cache for the executed transaction
                             let stored = nonce_cache.update(
                                 consider.tx.metadata.origin_address,
@@ -1901,6 +2069,16 @@ impl MemPoolDB {
                                     );
                                 }
                             }
+
+                            if strategy.follow_nonce_chains() {
+                                if let Some(next_tx) = Self::get_next_nonce_candidate(
+                                    self.conn(),
+                                    &consider.tx.metadata.origin_address,
+                                    consider.tx.metadata.origin_nonce + 1,
+                                )? {
+                                    nonce_chain.push_back(next_tx);
+                                }
+                            }
                             output_events.push(tx_event);
                         }
                         TransactionEvent::Skipped(_) => {
@@ -1971,6 +2149,26 @@ impl MemPoolDB {
         query_row(conn, "SELECT * FROM mempool WHERE txid = ?1", params![txid])
     }
 
+    /// The candidate from `origin_address` with `origin_nonce` and the highest fee rate, if any
+    fn get_next_nonce_candidate(
+        conn: &DBConn,
+        origin_address: &StacksAddress,
+        origin_nonce: u64,
+    ) -> Result<Option<MemPoolTxInfoPartial>, db_error> {
+        let sql = "
+            SELECT txid, origin_nonce, origin_address, sponsor_nonce, sponsor_address, fee_rate
+            FROM mempool
+            WHERE origin_address = ?1 AND origin_nonce = ?2
+            ORDER BY fee_rate DESC
+            LIMIT 1
+            ";
+        query_row(
+            conn,
+            sql,
+            params![origin_address.to_string(), u64_to_sql(origin_nonce)?],
+        )
+    }
+
     /// Get all transactions across all tips
     #[cfg(test)]
     pub fn get_all_txs(conn: &DBConn) -> Result<Vec<MemPoolTxInfo>, db_error> {
@@ -2012,6 +2210,56 @@ impl MemPoolDB {
         Ok(rows)
     }
 
+    /// List up to `limit` transactions that pass `filter`, in txid order, starting after the txid
+    /// `page_id` if given.  At most `max_scan` transactions are examined.
+    /// Returns the transactions, along with the `page_id` to resume from if the listing was cut
+    /// short.
+    pub fn list_txs(
+        conn: &DBConn,
+        filter: &MemPoolTxFilter,
+        page_id: Option<&Txid>,
+        limit: u64,
+        max_scan: u64,
+    ) -> Result<(Vec<MemPoolTxInfo>, Option<Txid>), db_error> {
+        let sql = "SELECT * FROM mempool WHERE (?1 IS NULL OR origin_address = ?1) AND tx_fee >= ?2 AND tx_fee <= ?3 AND (?4 IS NULL OR txid > ?4) ORDER BY txid ASC LIMIT ?5";
+        let args = params![
+            filter.origin.as_ref().map(|origin| origin.to_string()),
+            u64_to_sql(filter.min_fee.unwrap_or(0))?,
+            u64_to_sql(
+                filter
+                    .max_fee
+                    .unwrap_or(i64::MAX as u64)
+                    .min(i64::MAX as u64)
+            )?,
+            page_id,
+            u64_to_sql(max_scan)?,
+        ];
+        let mut stmt = conn.prepare(sql)?;
+        let mut rows = stmt.query(args)?;
+
+        let mut txs = vec![];
+        let mut num_scanned = 0;
+        let mut last_txid = None;
+        while let Some(row) = rows.next()? {
+            let txinfo = MemPoolTxInfo::from_row(row)?;
+            num_scanned += 1;
+            last_txid = Some(txinfo.metadata.txid.clone());
+            if filter.matches_payload(&txinfo.tx) {
+                txs.push(txinfo);
+                if txs.len() as u64 >= limit {
+                    break;
+                }
+            }
+        }
+
+        let next_page_id = if txs.len() as u64 >= limit || num_scanned >= max_scan {
+            last_txid
+        } else {
+            None
+        };
+        Ok((txs, next_page_id))
+    }
+
     /// Get a transaction's metadata, given address and nonce, and whether the address is used as a sponsor or an origin.
     /// Faster than getting the MemPoolTxInfo, since no deserialization will be needed.
     /// Used to see if there exists a transaction with this info, so as to implement replace-by-fee
@@ -0,0 +1,175 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Required transaction sets.  A coordinator (such as the signers' sBTC coordinator) can hand the
+//! miner a set of transactions that must not be left to the mempool walk.  The miner tries every
+//! pending required transaction at the start of its next block, ahead of the mempool, and records
+//! the outcome: the block that included the transaction once the signers accept it, or the reason
+//! it could not be mined.  The coordinator polls the set's status until every transaction has one.
+
+use rusqlite::{params, Row};
+use stacks_common::codec::StacksMessageCodec;
+use stacks_common::types::chainstate::StacksBlockId;
+use stacks_common::types::sqlite::NO_PARAMS;
+use stacks_common::util::get_epoch_time_secs;
+use stacks_common::util::hash::Sha512Trunc256Sum;
+
+use crate::burnchains::Txid;
+use crate::chainstate::stacks::StacksTransaction;
+use crate::core::mempool::MemPoolDB;
+use crate::util_lib::db::{query_rows, u64_to_sql, Error as db_error, FromColumn, FromRow};
+
+/// The most transactions a required set can hold
+pub const MAX_REQUIRED_TXS_PER_SET: usize = 64;
+
+/// What has become of a required transaction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status")]
+pub enum RequiredTxStatus {
+    /// The miner has not mined a block with the transaction, nor failed to mine it
+    Pending,
+    /// The transaction is in a block that the signers accepted
+    Included { block_id: StacksBlockId },
+    /// The miner could not mine the transaction
+    Rejected { reason: String },
+}
+
+/// A transaction of a required set, and its status
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RequiredTx {
+    pub txid: Txid,
+    #[serde(flatten)]
+    pub status: RequiredTxStatus,
+}
+
+impl FromRow<RequiredTx> for RequiredTx {
+    fn from_row<'a>(row: &'a Row) -> Result<RequiredTx, db_error> {
+        let txid = Txid::from_column(row, "txid")?;
+        let included_in: Option<StacksBlockId> = row.get("included_in")?;
+        let rejection: Option<String> = row.get("rejection")?;
+        let status = match (included_in, rejection) {
+            (Some(block_id), _) => RequiredTxStatus::Included { block_id },
+            (None, Some(reason)) => RequiredTxStatus::Rejected { reason },
+            (None, None) => RequiredTxStatus::Pending,
+        };
+        Ok(RequiredTx { txid, status })
+    }
+}
+
+/// A required transaction set, with the status of each of its transactions in set order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RequiredTxSet {
+    pub set_id: Sha512Trunc256Sum,
+    pub txs: Vec<RequiredTx>,
+}
+
+impl RequiredTxSet {
+    /// The ID of the set of `txs`, which is the hash of their txids in order
+    pub fn make_set_id(txs: &[StacksTransaction]) -> Sha512Trunc256Sum {
+        let txids: Vec<u8> = txs.iter().flat_map(|tx| tx.txid().0).collect();
+        Sha512Trunc256Sum::from_data(&txids)
+    }
+
+    /// Whether every transaction of the set has been included or rejected
+    pub fn is_settled(&self) -> bool {
+        self.txs
+            .iter()
+            .all(|tx| tx.status != RequiredTxStatus::Pending)
+    }
+}
+
+impl MemPoolDB {
+    /// Store a set of transactions that the miner must include or reject in its next block.
+    /// Submitting the same set again has no effect.  Returns the set's ID.
+    pub fn submit_required_tx_set(
+        &mut self,
+        txs: &[StacksTransaction],
+    ) -> Result<Sha512Trunc256Sum, db_error> {
+        let set_id = RequiredTxSet::make_set_id(txs);
+        let now = get_epoch_time_secs();
+        let sql = "INSERT OR IGNORE INTO required_txs (set_id, set_index, txid, tx, submit_time) VALUES (?1, ?2, ?3, ?4, ?5)";
+        let mempool_tx = self.tx_begin()?;
+        for (i, tx) in txs.iter().enumerate() {
+            let set_index = u32::try_from(i).map_err(|_| db_error::ParseError)?;
+            mempool_tx.execute(
+                sql,
+                params![
+                    set_id,
+                    set_index,
+                    tx.txid(),
+                    tx.serialize_to_vec(),
+                    u64_to_sql(now)?
+                ],
+            )?;
+        }
+        mempool_tx.commit()?;
+        Ok(set_id)
+    }
+
+    /// Get a required transaction set.  Returns None if there is no such set.
+    pub fn get_required_tx_set(
+        &self,
+        set_id: &Sha512Trunc256Sum,
+    ) -> Result<Option<RequiredTxSet>, db_error> {
+        let sql = "SELECT txid, included_in, rejection FROM required_txs WHERE set_id = ?1 ORDER BY set_index ASC";
+        let txs: Vec<RequiredTx> = query_rows(self.conn(), sql, params![set_id])?;
+        if txs.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(RequiredTxSet {
+            set_id: set_id.clone(),
+            txs,
+        }))
+    }
+
+    /// Get the pending required transactions, in the order they were submitted
+    pub fn get_pending_required_txs(&self) -> Result<Vec<StacksTransaction>, db_error> {
+        let sql = "SELECT tx FROM required_txs WHERE included_in IS NULL AND rejection IS NULL
+                   GROUP BY txid ORDER BY MIN(submit_time) ASC, MIN(rowid) ASC";
+        let mut stmt = self.conn().prepare(sql)?;
+        let mut rows = stmt.query(NO_PARAMS)?;
+        let mut txs = vec![];
+        while let Some(row) = rows.next()? {
+            let tx_bytes: Vec<u8> = row.get(0)?;
+            let tx = StacksTransaction::consensus_deserialize(&mut &tx_bytes[..])
+                .map_err(|_| db_error::ParseError)?;
+            txs.push(tx);
+        }
+        Ok(txs)
+    }
+
+    /// Record that the signers accepted `block_id`, which includes the pending required
+    /// transactions among `txids`
+    pub fn mark_required_txs_included(
+        &mut self,
+        block_id: &StacksBlockId,
+        txids: &[Txid],
+    ) -> Result<(), db_error> {
+        let sql = "UPDATE required_txs SET included_in = ?1 WHERE txid = ?2 AND included_in IS NULL AND rejection IS NULL";
+        let mempool_tx = self.tx_begin()?;
+        for txid in txids.iter() {
+            mempool_tx.execute(sql, params![block_id, txid])?;
+        }
+        mempool_tx.commit()?;
+        Ok(())
+    }
+
+    /// Record that the miner could not mine the pending required transaction `txid`
+    pub fn reject_required_tx(&mut self, txid: &Txid, reason: &str) -> Result<(), db_error> {
+        let sql = "UPDATE required_txs SET rejection = ?1 WHERE txid = ?2 AND included_in IS NULL AND rejection IS NULL";
+        self.db.execute(sql, params![reason, txid])?;
+        Ok(())
+    }
+}
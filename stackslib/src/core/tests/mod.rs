@@ -15,6 +15,7 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 use std::time::Duration;
 use std::{cmp, io};
 
@@ -60,11 +61,15 @@ use crate::chainstate::stacks::{
     TransactionVersion, C32_ADDRESS_VERSION_MAINNET_SINGLESIG,
     C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
 };
+use crate::core::assembly::{GreedyFeeRate, NonceChainAware};
 use crate::core::mempool::{
     db_get_all_nonces, MemPoolSyncData, MemPoolWalkSettings, MemPoolWalkTxTypes, TxTag,
     BLOOM_COUNTER_DEPTH, BLOOM_COUNTER_ERROR_RATE, MAX_BLOOM_COUNTER_TXS,
 };
+use crate::core::required_txs::RequiredTxStatus;
 use crate::core::{FIRST_BURNCHAIN_CONSENSUS_HASH, FIRST_STACKS_BLOCK_HASH};
+use crate::cost_estimates::metrics::UnitMetric;
+use crate::cost_estimates::{CostEstimator, EstimatorError};
 use crate::net::Error as NetError;
 use crate::util_lib::bloom::test::setup_bloom_counter;
 use crate::util_lib::bloom::*;
@@ -2839,3 +2844,394 @@ fn test_filter_txs_by_type() {
         },
     );
 }
+
+/// Estimates the same cost for every transaction
+struct FixedCostEstimator(ExecutionCost);
+
+impl CostEstimator for FixedCostEstimator {
+    fn notify_event(
+        &mut self,
+        _tx: &TransactionPayload,
+        _actual_cost: &ExecutionCost,
+        _block_limit: &ExecutionCost,
+        _evaluated_epoch: &StacksEpochId,
+    ) -> Result<(), EstimatorError> {
+        Ok(())
+    }
+
+    fn estimate_cost(
+        &self,
+        _tx: &TransactionPayload,
+        _evaluated_epoch: &StacksEpochId,
+    ) -> Result<ExecutionCost, EstimatorError> {
+        Ok(self.0.clone())
+    }
+}
+
+#[test]
+fn test_iterate_candidates_tenure_budget() {
+    let tx_cost = ExecutionCost {
+        write_length: 10,
+        write_count: 1,
+        read_length: 10,
+        read_count: 1,
+        runtime: 1000,
+    };
+    let mut chainstate = instantiate_chainstate(false, 0x80000000, function_name!());
+    let chainstate_path = chainstate_path(function_name!());
+    let mut mempool = MemPoolDB::open(
+        false,
+        0x80000000,
+        &chainstate_path,
+        Box::new(FixedCostEstimator(tx_cost.clone())),
+        Box::new(UnitMetric),
+    )
+    .unwrap();
+
+    let addr = StacksAddress {
+        version: 1,
+        bytes: Hash160([0xff; 20]),
+    };
+    let block_height = 10;
+
+    let b_1 = make_block(
+        &mut chainstate,
+        ConsensusHash([0x1; 20]),
+        &(
+            FIRST_BURNCHAIN_CONSENSUS_HASH.clone(),
+            FIRST_STACKS_BLOCK_HASH.clone(),
+        ),
+        1,
+        1,
+    );
+    let b_2 = make_block(&mut chainstate, ConsensusHash([0x2; 20]), &b_1, 2, 2);
+
+    let mut mempool_tx = mempool.tx_begin().unwrap();
+    for _ in 0..10 {
+        let pk = StacksPrivateKey::new();
+        let mut tx = StacksTransaction {
+            version: TransactionVersion::Testnet,
+            chain_id: 0x80000000,
+            auth: TransactionAuth::from_p2pkh(&pk).unwrap(),
+            anchor_mode: TransactionAnchorMode::Any,
+            post_condition_mode: TransactionPostConditionMode::Allow,
+            post_conditions: vec![],
+            payload: TransactionPayload::TokenTransfer(
+                addr.to_account_principal(),
+                123,
+                TokenTransferMemo([0u8; 34]),
+            ),
+        };
+        tx.set_tx_fee(1000);
+        tx.set_origin_nonce(0);
+
+        let txid = tx.txid();
+        let tx_bytes = tx.serialize_to_vec();
+        let origin_addr = tx.origin_address();
+        let origin_nonce = tx.get_origin_nonce();
+        let sponsor_addr = tx.sponsor_address().unwrap_or(origin_addr.clone());
+        let sponsor_nonce = tx.get_sponsor_nonce().unwrap_or(origin_nonce);
+        let tx_fee = tx.get_tx_fee();
+
+        MemPoolDB::try_add_tx(
+            &mut mempool_tx,
+            &mut chainstate,
+            &b_2.0,
+            &b_2.1,
+            true,
+            txid,
+            tx_bytes,
+            tx_fee,
+            block_height as u64,
+            &origin_addr,
+            origin_nonce,
+            &sponsor_addr,
+            sponsor_nonce,
+            None,
+        )
+        .unwrap();
+    }
+    mempool_tx.commit().unwrap();
+
+    // room for exactly three transactions
+    let mut tenure_budget = tx_cost.clone();
+    tenure_budget.multiply(3).unwrap();
+
+    let mut mempool_settings = MemPoolWalkSettings::default();
+    mempool_settings.skip_txs_over_tenure_budget = true;
+    mempool_settings.tenure_budget = Some(tenure_budget);
+
+    let mut tx_events = Vec::new();
+    chainstate.with_read_only_clarity_tx(
+        &TEST_BURN_STATE_DB,
+        &StacksBlockHeader::make_index_block_hash(&b_2.0, &b_2.1),
+        |clarity_conn| {
+            let mut count_txs = 0;
+            mempool
+                .iterate_candidates::<_, ChainstateError, _>(
+                    clarity_conn,
+                    &mut tx_events,
+                    mempool_settings.clone(),
+                    |_, available_tx, _| {
+                        count_txs += 1;
+                        Ok(Some(
+                            TransactionResult::success(
+                                &available_tx.tx.tx,
+                                available_tx.tx.metadata.tx_fee,
+                                StacksTransactionReceipt::from_stx_transfer(
+                                    available_tx.tx.tx.clone(),
+                                    vec![],
+                                    Value::okay(Value::Bool(true)).unwrap(),
+                                    tx_cost.clone(),
+                                ),
+                            )
+                            .convert_to_event(),
+                        ))
+                    },
+                )
+                .unwrap();
+            // the rest are skipped without being tried
+            assert_eq!(count_txs, 3);
+        },
+    );
+
+    // without a budget, every transaction is tried
+    mempool.reset_nonce_cache().unwrap();
+    mempool_settings.tenure_budget = None;
+    chainstate.with_read_only_clarity_tx(
+        &TEST_BURN_STATE_DB,
+        &StacksBlockHeader::make_index_block_hash(&b_2.0, &b_2.1),
+        |clarity_conn| {
+            let mut count_txs = 0;
+            mempool
+                .iterate_candidates::<_, ChainstateError, _>(
+                    clarity_conn,
+                    &mut tx_events,
+                    mempool_settings.clone(),
+                    |_, available_tx, _| {
+                        count_txs += 1;
+                        Ok(Some(
+                            TransactionResult::success(
+                                &available_tx.tx.tx,
+                                available_tx.tx.metadata.tx_fee,
+                                StacksTransactionReceipt::from_stx_transfer(
+                                    available_tx.tx.tx.clone(),
+                                    vec![],
+                                    Value::okay(Value::Bool(true)).unwrap(),
+                                    tx_cost.clone(),
+                                ),
+                            )
+                            .convert_to_event(),
+                        ))
+                    },
+                )
+                .unwrap();
+            assert_eq!(count_txs, 10);
+        },
+    );
+}
+
+#[test]
+fn test_iterate_candidates_block_assembly_strategy() {
+    let mut chainstate = instantiate_chainstate(false, 0x80000000, function_name!());
+    let chainstate_path = chainstate_path(function_name!());
+    let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+
+    let addr = StacksAddress {
+        version: 1,
+        bytes: Hash160([0xff; 20]),
+    };
+    let block_height = 10;
+
+    let b_1 = make_block(
+        &mut chainstate,
+        ConsensusHash([0x1; 20]),
+        &(
+            FIRST_BURNCHAIN_CONSENSUS_HASH.clone(),
+            FIRST_STACKS_BLOCK_HASH.clone(),
+        ),
+        1,
+        1,
+    );
+    let b_2 = make_block(&mut chainstate, ConsensusHash([0x2; 20]), &b_1, 2, 2);
+
+    // alice's first transaction, bob's, then alice's second
+    let alice = StacksPrivateKey::new();
+    let bob = StacksPrivateKey::new();
+    let mut txids = vec![];
+    let mut mempool_tx = mempool.tx_begin().unwrap();
+    for (pk, nonce) in [(&alice, 0), (&bob, 0), (&alice, 1)] {
+        let mut tx = StacksTransaction {
+            version: TransactionVersion::Testnet,
+            chain_id: 0x80000000,
+            auth: TransactionAuth::from_p2pkh(pk).unwrap(),
+            anchor_mode: TransactionAnchorMode::Any,
+            post_condition_mode: TransactionPostConditionMode::Allow,
+            post_conditions: vec![],
+            payload: TransactionPayload::TokenTransfer(
+                addr.to_account_principal(),
+                123,
+                TokenTransferMemo([0u8; 34]),
+            ),
+        };
+        tx.set_tx_fee(1000);
+        tx.set_origin_nonce(nonce);
+
+        let txid = tx.txid();
+        let tx_bytes = tx.serialize_to_vec();
+        let origin_addr = tx.origin_address();
+        let origin_nonce = tx.get_origin_nonce();
+        let sponsor_addr = tx.sponsor_address().unwrap_or(origin_addr.clone());
+        let sponsor_nonce = tx.get_sponsor_nonce().unwrap_or(origin_nonce);
+        let tx_fee = tx.get_tx_fee();
+
+        MemPoolDB::try_add_tx(
+            &mut mempool_tx,
+            &mut chainstate,
+            &b_2.0,
+            &b_2.1,
+            true,
+            txid.clone(),
+            tx_bytes,
+            tx_fee,
+            block_height as u64,
+            &origin_addr,
+            origin_nonce,
+            &sponsor_addr,
+            sponsor_nonce,
+            None,
+        )
+        .unwrap();
+        txids.push(txid);
+    }
+    mempool_tx.commit().unwrap();
+
+    let mut mempool_settings = MemPoolWalkSettings::default();
+    mempool_settings.consider_no_estimate_tx_prob = 100;
+
+    let mut mined_order = |mempool: &mut MemPoolDB, settings: &MemPoolWalkSettings| {
+        mempool.reset_nonce_cache().unwrap();
+        let mut mined = vec![];
+        let mut tx_events = Vec::new();
+        chainstate.with_read_only_clarity_tx(
+            &TEST_BURN_STATE_DB,
+            &StacksBlockHeader::make_index_block_hash(&b_2.0, &b_2.1),
+            |clarity_conn| {
+                mempool
+                    .iterate_candidates::<_, ChainstateError, _>(
+                        clarity_conn,
+                        &mut tx_events,
+                        settings.clone(),
+                        |_, available_tx, _| {
+                            mined.push(available_tx.tx.tx.txid());
+                            Ok(Some(
+                                TransactionResult::success(
+                                    &available_tx.tx.tx,
+                                    available_tx.tx.metadata.tx_fee,
+                                    StacksTransactionReceipt::from_stx_transfer(
+                                        available_tx.tx.tx.clone(),
+                                        vec![],
+                                        Value::okay(Value::Bool(true)).unwrap(),
+                                        ExecutionCost::zero(),
+                                    ),
+                                )
+                                .convert_to_event(),
+                            ))
+                        },
+                    )
+                    .unwrap();
+            },
+        );
+        mined
+    };
+
+    // by default, bob's transaction is mined before alice's second
+    mempool_settings.block_assembly_strategy = Arc::new(GreedyFeeRate);
+    assert_eq!(
+        mined_order(&mut mempool, &mempool_settings),
+        vec![txids[0].clone(), txids[1].clone(), txids[2].clone()]
+    );
+
+    // following nonce chains mines alice's transactions together
+    mempool_settings.block_assembly_strategy = Arc::new(NonceChainAware);
+    assert_eq!(
+        mined_order(&mut mempool, &mempool_settings),
+        vec![txids[0].clone(), txids[2].clone(), txids[1].clone()]
+    );
+}
+
+#[test]
+fn mempool_required_tx_sets() {
+    let _chainstate = instantiate_chainstate(false, 0x80000000, function_name!());
+    let chainstate_path = chainstate_path(function_name!());
+    let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+
+    let txs = codec_all_transactions(
+        &TransactionVersion::Testnet,
+        0x80000000,
+        &TransactionAnchorMode::Any,
+        &TransactionPostConditionMode::Allow,
+        StacksEpochId::latest(),
+    );
+    let set_1 = txs[0..3].to_vec();
+    let set_2 = txs[2..5].to_vec();
+
+    let set_id_1 = mempool.submit_required_tx_set(&set_1).unwrap();
+    let set_id_2 = mempool.submit_required_tx_set(&set_2).unwrap();
+    assert_ne!(set_id_1, set_id_2);
+
+    // resubmitting a set changes nothing
+    assert_eq!(mempool.submit_required_tx_set(&set_1).unwrap(), set_id_1);
+    assert!(mempool
+        .get_required_tx_set(&Sha512Trunc256Sum([0x11; 32]))
+        .unwrap()
+        .is_none());
+
+    // the shared transaction is only pending once
+    let pending = mempool.get_pending_required_txs().unwrap();
+    assert_eq!(pending, txs[0..5].to_vec());
+
+    mempool
+        .mark_required_txs_included(&StacksBlockId([0x01; 32]), &[txs[0].txid(), txs[2].txid()])
+        .unwrap();
+    mempool
+        .reject_required_tx(&txs[3].txid(), "bad nonce")
+        .unwrap();
+
+    // a rejected transaction can't be included later
+    mempool
+        .mark_required_txs_included(&StacksBlockId([0x02; 32]), &[txs[3].txid()])
+        .unwrap();
+
+    let set = mempool.get_required_tx_set(&set_id_1).unwrap().unwrap();
+    assert!(!set.is_settled());
+    assert_eq!(
+        set.txs
+            .iter()
+            .map(|tx| tx.status.clone())
+            .collect::<Vec<_>>(),
+        vec![
+            RequiredTxStatus::Included {
+                block_id: StacksBlockId([0x01; 32])
+            },
+            RequiredTxStatus::Pending,
+            RequiredTxStatus::Included {
+                block_id: StacksBlockId([0x01; 32])
+            },
+        ]
+    );
+
+    let set = mempool.get_required_tx_set(&set_id_2).unwrap().unwrap();
+    assert_eq!(set.txs[1].txid, txs[3].txid());
+    assert_eq!(
+        set.txs[1].status,
+        RequiredTxStatus::Rejected {
+            reason: "bad nonce".into()
+        }
+    );
+
+    assert_eq!(
+        mempool.get_pending_required_txs().unwrap(),
+        vec![txs[1].clone(), txs[4].clone()]
+    );
+}
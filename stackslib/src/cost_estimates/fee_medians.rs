@@ -12,7 +12,10 @@ use rusqlite::{
 use serde_json::Value as JsonValue;
 
 use super::metrics::{CostMetric, PROPORTION_RESOLUTION};
-use super::{EstimatorError, FeeEstimator, FeeRateEstimate};
+use super::{
+    EstimatorError, FeeEstimateKind, FeeEstimator, FeeRateBand, FeeRateBands, FeeRateEstimate,
+    FEE_RATE_PERCENTILES,
+};
 use crate::chainstate::stacks::db::StacksEpochReceipt;
 use crate::chainstate::stacks::events::TransactionOrigin;
 use crate::chainstate::stacks::TransactionPayload;
@@ -29,6 +32,13 @@ CREATE TABLE median_fee_estimator (
     low NUMBER NOT NULL
 )";
 
+const CREATE_BANDS_TABLE: &'static str = "
+CREATE TABLE median_fee_estimator_bands (
+    measure_key INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,
+    fee_rates TEXT NOT NULL
+)";
+
 const MINIMUM_TX_FEE_RATE: f64 = 1f64;
 
 /// FeeRateEstimator with the following properties:
@@ -40,6 +50,10 @@ const MINIMUM_TX_FEE_RATE: f64 = 1f64;
 /// 3) "Pad" the block, so that any unused spaces is considered to have an associated fee rate of
 ///    1f, the minimum. Ignoring the amount of empty space leads to over-estimates because it
 ///    ignores the fact that there was still space in the block.
+///
+/// It also keeps the `FEE_RATE_PERCENTILES` of the fee rates paid by each kind of transaction in
+/// each of the last `window_size` blocks that had that kind, without padding, for
+/// `get_rate_bands()`.
 pub struct WeightedMedianFeeRateEstimator<M: CostMetric> {
     db: Connection,
     /// We only look back `window_size` fee rates when averaging past estimates.
@@ -92,6 +106,9 @@ impl<M: CostMetric> WeightedMedianFeeRateEstimator<M> {
         if !Self::db_already_instantiated(tx)? {
             tx.execute(CREATE_TABLE, NO_PARAMS)?;
         }
+        if !table_exists(tx, "median_fee_estimator_bands")? {
+            tx.execute(CREATE_BANDS_TABLE, NO_PARAMS)?;
+        }
 
         Ok(())
     }
@@ -151,6 +168,80 @@ impl<M: CostMetric> WeightedMedianFeeRateEstimator<M> {
         })
     }
 
+    fn get_rate_bands_from_sql(
+        conn: &Connection,
+        kind: FeeEstimateKind,
+        window_size: u32,
+    ) -> Result<FeeRateBands, EstimatorError> {
+        let sql = "SELECT fee_rates FROM median_fee_estimator_bands WHERE kind = ?1
+                   ORDER BY measure_key DESC LIMIT ?2";
+        let mut stmt = conn.prepare(sql).expect("SQLite failure");
+        let results = stmt
+            .query_and_then::<_, SqliteError, _, _>(params![kind.as_str(), window_size], |row| {
+                let fee_rates: String = row.get("fee_rates")?;
+                Ok(fee_rates)
+            })
+            .expect("SQLite failure");
+
+        // one list per percentile, of its value in each block
+        let mut measures =
+            vec![Vec::with_capacity(window_size as usize); FEE_RATE_PERCENTILES.len()];
+        for result in results {
+            let fee_rates: Vec<f64> = serde_json::from_str(&result.expect("SQLite failure"))
+                .expect("Corrupt fee rate bands");
+            for (measure, fee_rate) in measures.iter_mut().zip(fee_rates) {
+                measure.push(fee_rate);
+            }
+        }
+
+        let sample_blocks = measures[0].len();
+        if sample_blocks == 0 {
+            return Err(EstimatorError::NoEstimateAvailable);
+        }
+
+        // the value at quantile `q` of the sorted, non-empty `values`
+        fn nearest_rank(values: &[f64], q: f64) -> f64 {
+            values[((values.len() - 1) as f64 * q).round() as usize]
+        }
+
+        let bands = FEE_RATE_PERCENTILES
+            .iter()
+            .zip(measures.iter_mut())
+            .map(|(percentile, measure)| {
+                measure.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+                FeeRateBand {
+                    percentile: *percentile,
+                    fee_rate: nearest_rank(measure, 0.5),
+                    fee_rate_lower: nearest_rank(measure, 0.1),
+                    fee_rate_upper: nearest_rank(measure, 0.9),
+                }
+            })
+            .collect();
+
+        Ok(FeeRateBands {
+            kind,
+            sample_blocks: sample_blocks as u32,
+            bands,
+        })
+    }
+
+    fn update_bands(&mut self, kind: FeeEstimateKind, fee_rates: Vec<f64>) {
+        let tx = tx_begin_immediate_sqlite(&mut self.db).expect("SQLite failure");
+        let insert_sql = "INSERT INTO median_fee_estimator_bands (kind, fee_rates) VALUES (?, ?)";
+        let deletion_sql = "DELETE FROM median_fee_estimator_bands
+                            WHERE kind = ?1 AND measure_key NOT IN (
+                               SELECT measure_key FROM median_fee_estimator_bands
+                               WHERE kind = ?1
+                               ORDER BY measure_key DESC LIMIT ?2 )";
+        let fee_rates =
+            serde_json::to_string(&fee_rates).expect("FATAL: failed to encode fee rates");
+        tx.execute(insert_sql, params![kind.as_str(), fee_rates])
+            .expect("SQLite failure");
+        tx.execute(deletion_sql, params![kind.as_str(), self.window_size])
+            .expect("SQLite failure");
+        tx.commit().expect("SQLite failure");
+    }
+
     fn update_estimate(&mut self, new_measure: FeeRateEstimate) {
         let tx = tx_begin_immediate_sqlite(&mut self.db).expect("SQLite failure");
         let insert_sql = "INSERT INTO median_fee_estimator
@@ -213,12 +304,48 @@ impl<M: CostMetric> FeeEstimator for WeightedMedianFeeRateEstimator<M> {
             self.update_estimate(block_estimate);
         }
 
+        // Bands are per kind, and only cover what the kind's transactions paid.
+        let target_percentiles: Vec<f64> = FEE_RATE_PERCENTILES
+            .iter()
+            .map(|percentile| f64::from(*percentile) / 100f64)
+            .collect();
+        for kind in FeeEstimateKind::ALL {
+            let mut kind_fee_rates: Vec<FeeRateAndWeight> = receipt
+                .tx_receipts
+                .iter()
+                .filter(|tx_receipt| match tx_receipt.transaction {
+                    TransactionOrigin::Stacks(ref tx) => {
+                        FeeEstimateKind::from_payload(&tx.payload) == Some(kind)
+                    }
+                    TransactionOrigin::Burn(_) => false,
+                })
+                .filter_map(|tx_receipt| {
+                    fee_rate_and_weight_from_receipt(&self.metric, &tx_receipt, block_limit)
+                })
+                .filter(|rate_and_weight| rate_and_weight.weight > 0)
+                .collect();
+            if kind_fee_rates.is_empty() {
+                continue;
+            }
+            kind_fee_rates.sort_by(|a, b| {
+                a.fee_rate
+                    .partial_cmp(&b.fee_rate)
+                    .unwrap_or(Ordering::Equal)
+            });
+            let fee_rates = weighted_percentiles(&kind_fee_rates, &target_percentiles);
+            self.update_bands(kind, fee_rates);
+        }
+
         Ok(())
     }
 
     fn get_rate_estimates(&self) -> Result<FeeRateEstimate, EstimatorError> {
         Self::get_rate_estimates_from_sql(&self.db, self.window_size)
     }
+
+    fn get_rate_bands(&self, kind: FeeEstimateKind) -> Result<FeeRateBands, EstimatorError> {
+        Self::get_rate_bands_from_sql(&self.db, kind, self.window_size)
+    }
 }
 
 /// Computes a `FeeRateEstimate` based on `sorted_fee_rates` using a "weighted percentile" method
@@ -230,6 +357,23 @@ impl<M: CostMetric> FeeEstimator for WeightedMedianFeeRateEstimator<M> {
 pub fn fee_rate_estimate_from_sorted_weighted_fees(
     sorted_fee_rates: &[FeeRateAndWeight],
 ) -> FeeRateEstimate {
+    let values_at_target_percentiles = weighted_percentiles(sorted_fee_rates, &[0.05, 0.5, 0.95]);
+
+    FeeRateEstimate {
+        high: values_at_target_percentiles[2],
+        middle: values_at_target_percentiles[1],
+        low: values_at_target_percentiles[0],
+    }
+}
+
+/// The values of `sorted_fee_rates` at each of `target_percentiles` (as fractions), using the
+/// "weighted percentile" method of `fee_rate_estimate_from_sorted_weighted_fees()`.
+///
+/// `sorted_fee_rates` must be non-empty, with a positive total weight.
+pub fn weighted_percentiles(
+    sorted_fee_rates: &[FeeRateAndWeight],
+    target_percentiles: &[f64],
+) -> Vec<f64> {
     assert!(!sorted_fee_rates.is_empty());
 
     let mut total_weight = 0f64;
@@ -249,10 +393,9 @@ pub fn fee_rate_estimate_from_sorted_weighted_fees(
     }
     assert_eq!(percentiles.len(), sorted_fee_rates.len());
 
-    let target_percentiles = vec![0.05, 0.5, 0.95];
     let mut fees_index = 0; // index into `sorted_fee_rates`
     let mut values_at_target_percentiles = Vec::new();
-    for target_percentile in target_percentiles {
+    for target_percentile in target_percentiles.iter().copied() {
         while fees_index < percentiles.len() && percentiles[fees_index] < target_percentile {
             fees_index += 1;
         }
@@ -270,12 +413,7 @@ pub fn fee_rate_estimate_from_sorted_weighted_fees(
         };
         values_at_target_percentiles.push(v);
     }
-
-    FeeRateEstimate {
-        high: values_at_target_percentiles[2],
-        middle: values_at_target_percentiles[1],
-        low: values_at_target_percentiles[0],
-    }
+    values_at_target_percentiles
 }
 
 /// If the weights in `working_rates` do not add up to `full_block_weight`, add a new entry **in
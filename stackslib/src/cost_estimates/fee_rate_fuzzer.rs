@@ -3,7 +3,7 @@ use rand::distributions::{Distribution, Uniform};
 use rand::rngs::StdRng;
 use rand::{thread_rng, RngCore, SeedableRng};
 
-use super::{EstimatorError, FeeEstimator, FeeRateEstimate};
+use super::{EstimatorError, FeeEstimateKind, FeeEstimator, FeeRateBands, FeeRateEstimate};
 use crate::chainstate::stacks::db::StacksEpochReceipt;
 
 /// The FeeRateFuzzer wraps an underlying FeeEstimator. It passes `notify_block` calls to the
@@ -86,4 +86,10 @@ impl<T: FeeEstimator> FeeEstimator for FeeRateFuzzer<T> {
         let underlying_estimate = self.underlying.get_rate_estimates()?;
         Ok(self.fuzz_estimate(underlying_estimate))
     }
+
+    /// Just passes the bands of `underlying` through: they describe what was paid, so aren't
+    /// fuzzed.
+    fn get_rate_bands(&self, kind: FeeEstimateKind) -> Result<FeeRateBands, EstimatorError> {
+        self.underlying.get_rate_bands(kind)
+    }
 }
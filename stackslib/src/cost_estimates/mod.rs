@@ -49,6 +49,11 @@ pub trait FeeEstimator {
     ) -> Result<(), EstimatorError>;
     /// Get the current estimates for fee rate
     fn get_rate_estimates(&self) -> Result<FeeRateEstimate, EstimatorError>;
+    /// Get the percentiles of the fee rates paid by recently mined transactions of `kind`.
+    /// Estimators that don't track fee rates by kind have no estimate.
+    fn get_rate_bands(&self, _kind: FeeEstimateKind) -> Result<FeeRateBands, EstimatorError> {
+        Err(EstimatorError::NoEstimateAvailable)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
@@ -102,6 +107,68 @@ impl Add for FeeRateEstimate {
     }
 }
 
+/// The kinds of transactions whose fee rates are estimated separately
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeEstimateKind {
+    TokenTransfer,
+    ContractCall,
+    SmartContract,
+}
+
+impl FeeEstimateKind {
+    pub const ALL: [FeeEstimateKind; 3] = [
+        FeeEstimateKind::TokenTransfer,
+        FeeEstimateKind::ContractCall,
+        FeeEstimateKind::SmartContract,
+    ];
+
+    /// The kind of a transaction with `payload`, or `None` if it doesn't compete for block space
+    /// by fee rate
+    pub fn from_payload(payload: &TransactionPayload) -> Option<FeeEstimateKind> {
+        match payload {
+            TransactionPayload::TokenTransfer(..) => Some(FeeEstimateKind::TokenTransfer),
+            TransactionPayload::ContractCall(..) => Some(FeeEstimateKind::ContractCall),
+            TransactionPayload::SmartContract(..) => Some(FeeEstimateKind::SmartContract),
+            TransactionPayload::PoisonMicroblock(..)
+            | TransactionPayload::Coinbase(..)
+            | TransactionPayload::TenureChange(..) => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeeEstimateKind::TokenTransfer => "token_transfer",
+            FeeEstimateKind::ContractCall => "contract_call",
+            FeeEstimateKind::SmartContract => "smart_contract",
+        }
+    }
+}
+
+/// The percentiles of fee rates reported in `FeeRateBands`
+pub const FEE_RATE_PERCENTILES: [u8; 5] = [5, 25, 50, 75, 95];
+
+/// A percentile of the fee rates paid by recently mined transactions
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct FeeRateBand {
+    pub percentile: u8,
+    /// The median of this percentile over recent blocks
+    pub fee_rate: f64,
+    /// The range this percentile fell in for the middle 80% of recent blocks
+    pub fee_rate_lower: f64,
+    pub fee_rate_upper: f64,
+}
+
+/// The percentiles of the fee rates paid by recently mined transactions of one kind
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct FeeRateBands {
+    pub kind: FeeEstimateKind,
+    /// The number of recent blocks with transactions of this kind that the bands are taken from
+    pub sample_blocks: u32,
+    /// One band for each of `FEE_RATE_PERCENTILES`
+    pub bands: Vec<FeeRateBand>,
+}
+
 /// Given a cost estimator and a scalar metric, estimate the fee rate for
 ///  the provided transaction
 pub fn estimate_fee_rate<CE: CostEstimator + ?Sized, CM: CostMetric + ?Sized>(
@@ -23,7 +23,9 @@ use crate::cost_estimates::fee_medians::{
 };
 use crate::cost_estimates::metrics::{CostMetric, ProportionalDotProduct};
 use crate::cost_estimates::tests::common::*;
-use crate::cost_estimates::{EstimatorError, FeeEstimator, FeeRateEstimate};
+use crate::cost_estimates::{
+    EstimatorError, FeeEstimateKind, FeeEstimator, FeeRateEstimate, FEE_RATE_PERCENTILES,
+};
 
 /// Returns true iff `b` is within `0.1%` of `a`.
 fn is_close_f64(a: f64, b: f64) -> bool {
@@ -385,3 +387,50 @@ fn test_fee_rate_estimate_100() {
         }
     );
 }
+
+/// The bands of a kind take each percentile's median over the window of blocks that had the
+/// kind, with the 10th and 90th percentile blocks as its range.
+#[test]
+fn test_fee_rate_bands() {
+    let metric = ProportionalDotProduct::new(10_000);
+    let mut estimator = instantiate_test_db(metric);
+
+    assert_eq!(
+        estimator.get_rate_bands(FeeEstimateKind::ContractCall),
+        Err(EstimatorError::NoEstimateAvailable)
+    );
+
+    for fee_rate in 1..=6 {
+        let receipt = make_block_receipt(vec![make_dummy_cc_tx(
+            fee_rate * tenth_operation_cost_basis,
+            &tenth_operation_cost,
+        )]);
+        estimator
+            .notify_block(&receipt, &block_limit)
+            .expect("Should be able to process block");
+    }
+    // blocks without contract calls don't count towards their window
+    estimator
+        .notify_block(&make_block_receipt(vec![]), &block_limit)
+        .expect("Should be able to process an empty block");
+
+    let bands = estimator
+        .get_rate_bands(FeeEstimateKind::ContractCall)
+        .expect("Should have bands for contract calls");
+    assert_eq!(bands.kind, FeeEstimateKind::ContractCall);
+    // the window holds the last 5 blocks, with fee rates 2 to 6
+    assert_eq!(bands.sample_blocks, 5);
+    assert_eq!(bands.bands.len(), FEE_RATE_PERCENTILES.len());
+    for (band, percentile) in bands.bands.iter().zip(FEE_RATE_PERCENTILES) {
+        // with one transaction per block, every percentile is its fee rate
+        assert_eq!(band.percentile, percentile);
+        assert!(is_close_f64(band.fee_rate, 4f64));
+        assert!(is_close_f64(band.fee_rate_lower, 2f64));
+        assert!(is_close_f64(band.fee_rate_upper, 6f64));
+    }
+
+    assert_eq!(
+        estimator.get_rate_bands(FeeEstimateKind::TokenTransfer),
+        Err(EstimatorError::NoEstimateAvailable)
+    );
+}
@@ -59,6 +59,7 @@ pub extern crate libstackerdb;
 pub mod chainstate;
 
 pub mod burnchains;
+pub mod clarity_bench;
 pub mod clarity_cli;
 /// A high level library for interacting with the Clarity vm
 pub mod clarity_vm;
@@ -37,10 +37,12 @@ use std::fs::File;
 use std::io::prelude::*;
 use std::io::BufReader;
 use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
 use std::time::Duration;
 use std::{env, fs, io, process, thread};
 
 use blockstack_lib::burnchains::bitcoin::{spv, BitcoinNetworkType};
+use blockstack_lib::burnchains::checkpoint::{list_checkpoints, BurnchainCheckpoint};
 use blockstack_lib::burnchains::db::{BurnchainBlockData, BurnchainDB};
 use blockstack_lib::burnchains::{Address, Burnchain, PoxConstants};
 use blockstack_lib::chainstate::burn::db::sortdb::{
@@ -54,6 +56,7 @@ use blockstack_lib::chainstate::nakamoto::shadow::{
     process_shadow_block, shadow_chainstate_repair,
 };
 use blockstack_lib::chainstate::nakamoto::{NakamotoBlock, NakamotoChainState};
+use blockstack_lib::chainstate::stacks::boot::BootContract;
 use blockstack_lib::chainstate::stacks::db::blocks::{DummyEventDispatcher, StagingBlock};
 use blockstack_lib::chainstate::stacks::db::{
     ChainStateBootData, StacksBlockHeaderTypes, StacksChainState,
@@ -1015,6 +1018,31 @@ simulating a miner.
         process::exit(0);
     }
 
+    #[cfg(feature = "bench-corpus")]
+    if argv[1] == "bench-corpus" {
+        let iterations = match argv.get(2) {
+            Some(iterations) => iterations.parse().unwrap_or_else(|_| {
+                eprintln!("Usage: {} bench-corpus [iterations]", argv[0]);
+                process::exit(1);
+            }),
+            None => 10,
+        };
+        let reports = blockstack_lib::clarity_vm::corpus::run_corpus(iterations)
+            .unwrap_or_else(|e| panic!("Corpus run failed: {}", e));
+        println!("contract,analysis_us,initialization_us,calls_us,call_count");
+        for report in reports.iter() {
+            println!(
+                "{},{},{},{},{}",
+                report.name,
+                report.analysis.as_micros(),
+                report.initialization.as_micros(),
+                report.calls.as_micros(),
+                report.call_count
+            );
+        }
+        process::exit(0);
+    }
+
     if argv[1] == "exec_program" {
         if argv.len() < 3 {
             eprintln!("Usage: {} exec_program [program-file.clar]", argv[0]);
@@ -1282,6 +1310,148 @@ simulating a miner.
         process::exit(0);
     }
 
+    if argv[1] == "epoch-dry-run" {
+        if argv.len() < 5 {
+            eprintln!(
+                "Usage: {} epoch-dry-run CHAINSTATE_DIR NETWORK EPOCH [CONTRACT_NAME=CONTRACT_FILE...]
+
+Rehearse the transition to the configured epoch EPOCH (e.g. 3.0) atop the canonical Stacks tip,
+instantiating each CONTRACT_FILE as a boot contract of EPOCH after the ones its transition
+instantiates.  Nothing is written to the chainstate.  Prints a JSON report of the contracts
+instantiated and of every failure, and exits with status 1 if there were any failures.
+",
+                &argv[0]
+            );
+            process::exit(1);
+        }
+
+        let chainstate_dir = argv[2].as_str();
+        let network = argv[3].as_str();
+        let epoch_name = argv[4].as_str();
+        let contracts: Vec<_> = argv[5..]
+            .iter()
+            .map(|arg| {
+                let (name, path) = arg
+                    .split_once('=')
+                    .unwrap_or_else(|| panic!("Expected CONTRACT_NAME=CONTRACT_FILE, got {arg}"));
+                let code = fs::read_to_string(path)
+                    .unwrap_or_else(|e| panic!("Failed to read {path}: {e:?}"));
+                BootContract::new(name, &code, None)
+            })
+            .collect();
+
+        check_shadow_network(network);
+        let (sort_db, mut chain_state) = open_nakamoto_chainstate_dbs(chainstate_dir, network);
+
+        let target_epoch = SortitionDB::get_stacks_epochs(sort_db.conn())
+            .unwrap()
+            .into_iter()
+            .find(|epoch| epoch.epoch_id.to_string() == epoch_name)
+            .unwrap_or_else(|| panic!("Epoch {epoch_name} is not configured for {network}"));
+        let header = NakamotoChainState::get_canonical_block_header(chain_state.db(), &sort_db)
+            .unwrap()
+            .unwrap();
+        let burn_dbconn = sort_db
+            .index_handle_at_block(&chain_state, &header.index_block_hash())
+            .unwrap();
+
+        let report = chain_state
+            .epoch_transition_dry_run(&burn_dbconn, &header, &target_epoch, &contracts)
+            .unwrap();
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        if !report.is_ok() {
+            process::exit(1);
+        }
+        process::exit(0);
+    }
+
+    if argv[1] == "burnchain-checkpoint" {
+        let usage = format!(
+            "Usage: {} burnchain-checkpoint create BURNCHAIN_DIR CHECKPOINT_DIR
+       {} burnchain-checkpoint restore CHECKPOINT_DIR BURNCHAIN_DIR [--overwrite]
+       {} burnchain-checkpoint list CHECKPOINTS_DIR
+
+BURNCHAIN_DIR is the node's burnchain state directory, e.g. WORKING_DIR/mainnet, which holds
+headers.sqlite and burnchain/burnchain.sqlite.  `create` copies them into the new directory
+CHECKPOINT_DIR, and is safe while the node runs.  `restore` copies a checkpoint back, and must only
+be run while the node is stopped.  It replaces existing state only with --overwrite.  `list` prints
+the checkpoints in CHECKPOINTS_DIR.
+",
+            &argv[0], &argv[0], &argv[0]
+        );
+        if argv.len() < 4 {
+            eprintln!("{usage}");
+            process::exit(1);
+        }
+        let state_paths = |dir: &str| {
+            let dir = PathBuf::from(dir);
+            let headers_path = dir.join("headers.sqlite");
+            let burnchain_db_path = dir.join("burnchain").join("burnchain.sqlite");
+            (
+                headers_path.to_str().unwrap().to_string(),
+                burnchain_db_path.to_str().unwrap().to_string(),
+            )
+        };
+
+        let checkpoint = match (argv[2].as_str(), argv.len()) {
+            ("create", 5) => {
+                let (headers_path, burnchain_db_path) = state_paths(&argv[3]);
+                BurnchainCheckpoint::create(
+                    &headers_path,
+                    &burnchain_db_path,
+                    &PathBuf::from(&argv[4]),
+                )
+            }
+            ("restore", 5) | ("restore", 6) => {
+                let overwrite = match argv.get(5).map(|arg| arg.as_str()) {
+                    None => false,
+                    Some("--overwrite") => true,
+                    Some(_) => {
+                        eprintln!("{usage}");
+                        process::exit(1);
+                    }
+                };
+                let (headers_path, burnchain_db_path) = state_paths(&argv[4]);
+                BurnchainCheckpoint::restore(
+                    &PathBuf::from(&argv[3]),
+                    &headers_path,
+                    &burnchain_db_path,
+                    overwrite,
+                )
+            }
+            ("list", 4) => {
+                let checkpoints = list_checkpoints(&PathBuf::from(&argv[3])).unwrap_or_else(|e| {
+                    eprintln!("Failed to list checkpoints: {e:?}");
+                    process::exit(1);
+                });
+                for (path, checkpoint) in checkpoints {
+                    println!(
+                        "{}\t{}\t{}\t{}",
+                        path.display(),
+                        checkpoint.block_height,
+                        checkpoint.block_hash,
+                        checkpoint.created_at
+                    );
+                }
+                process::exit(0);
+            }
+            _ => {
+                eprintln!("{usage}");
+                process::exit(1);
+            }
+        };
+        match checkpoint {
+            Ok(checkpoint) => {
+                println!("{}", serde_json::to_string_pretty(&checkpoint).unwrap());
+                process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("Failed to {} checkpoint: {e:?}", &argv[2]);
+                process::exit(1);
+            }
+        }
+    }
+
     if argv[1] == "make-shadow-block" {
         if argv.len() < 5 {
             eprintln!(
@@ -1491,6 +1661,7 @@ simulating a miner.
             get_bulk_initial_balances: None,
             get_bulk_initial_namespaces: None,
             get_bulk_initial_names: None,
+            boot_contract_provider: None,
         };
 
         let (mut new_chainstate, _) = StacksChainState::open_and_exec(
@@ -1756,6 +1927,21 @@ simulating a miner.
         process::exit(0);
     }
 
+    if argv[1] == "replay-transaction" {
+        cli::command_replay_transaction(&argv[1..], None);
+        process::exit(0);
+    }
+
+    if argv[1] == "replay-tx" {
+        cli::command_replay_tx(&argv[1..], None);
+        process::exit(0);
+    }
+
+    if argv[1] == "export-clarity-state" {
+        cli::command_export_clarity_state(&argv[1..]);
+        process::exit(0);
+    }
+
     if argv.len() < 4 {
         eprintln!("Usage: {} blockchain network working_dir", argv[0]);
         process::exit(1);
@@ -190,6 +190,22 @@ pub fn increment_errors_emitted_counter() {
     prometheus::ERRORS_EMITTED_COUNTER.inc();
 }
 
+pub fn increment_bitcoind_failover_counter() {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::BITCOIND_FAILOVER_COUNTER.inc();
+}
+
+pub fn increment_bitcoind_divergence_counter() {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::BITCOIND_DIVERGENCE_COUNTER.inc();
+}
+
+#[allow(unused_variables)]
+pub fn update_bitcoind_healthy_count(value: i64) {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::BITCOIND_HEALTHY_GAUGE.set(value);
+}
+
 fn txid_tracking_db(chainstate_root_path: &str) -> Result<DBConn, DatabaseError> {
     let mut path = PathBuf::from(chainstate_root_path);
 
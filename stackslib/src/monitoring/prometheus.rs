@@ -94,6 +94,21 @@ lazy_static! {
         "Total number of error logs emitted by node"
     )).unwrap();
 
+    pub static ref BITCOIND_FAILOVER_COUNTER: IntCounter = register_int_counter!(opts!(
+        "stacks_node_bitcoind_failovers_total",
+        "Total number of times the node switched to a different bitcoind"
+    )).unwrap();
+
+    pub static ref BITCOIND_DIVERGENCE_COUNTER: IntCounter = register_int_counter!(opts!(
+        "stacks_node_bitcoind_divergences_total",
+        "Total number of health checks that found bitcoinds with different blocks at the same height"
+    )).unwrap();
+
+    pub static ref BITCOIND_HEALTHY_GAUGE: IntGauge = register_int_gauge!(opts!(
+        "stacks_node_bitcoind_healthy",
+        "Number of configured bitcoinds that passed the last health check"
+    )).unwrap();
+
     pub static ref LAST_BLOCK_READ_COUNT: Gauge = register_gauge!(opts!(
         "stacks_node_last_block_read_count",
         "`execution_cost_read_count` for the last block observed."
@@ -19,18 +19,19 @@ use std::io::{Read, Write};
 use clarity::vm::analysis::CheckErrors;
 use clarity::vm::ast::parser::v1::CLARITY_NAME_REGEX;
 use clarity::vm::clarity::ClarityConnection;
-use clarity::vm::costs::{ExecutionCost, LimitedCostTracker};
+use clarity::vm::costs::{ExecutionLimits, LimitedCostTracker};
 use clarity::vm::database::{ClarityDatabase, STXBalance, StoreType};
 use clarity::vm::errors::Error::Unchecked;
 use clarity::vm::errors::{Error as ClarityRuntimeError, InterpreterError};
 use clarity::vm::representations::{
     CONTRACT_NAME_REGEX_STRING, PRINCIPAL_DATA_REGEX_STRING, STANDARD_PRINCIPAL_REGEX_STRING,
 };
+use clarity::vm::tracer::{ExecutionTracer, TraceEntry};
 use clarity::vm::types::{
     PrincipalData, QualifiedContractIdentifier, StandardPrincipalData,
     BOUND_VALUE_SERIALIZATION_HEX,
 };
-use clarity::vm::{ClarityName, ClarityVersion, ContractName, SymbolicExpression, Value};
+use clarity::vm::{ClarityName, ClarityVersion, ContractName, EvalHook, SymbolicExpression, Value};
 use regex::{Captures, Regex};
 use stacks_common::types::chainstate::{StacksAddress, StacksBlockId};
 use stacks_common::types::net::PeerHost;
@@ -52,6 +53,8 @@ use crate::net::httpcore::{
     StacksHttpRequest, StacksHttpResponse,
 };
 use crate::net::p2p::PeerNetwork;
+use crate::net::ratelimit::RPCRateLimitClass;
+use crate::net::rpccache::RPCResponseCacheKey;
 use crate::net::{Error as NetError, StacksNodeState, TipRequest};
 use crate::util_lib::boot::boot_code_id;
 use crate::util_lib::db::Error as DBError;
@@ -73,12 +76,18 @@ pub struct CallReadOnlyResponse {
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cause: Option<String>,
+    /// The execution trace, if one was requested (with `?trace=1`) and the node allows it
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace: Option<Vec<TraceEntry>>,
 }
 
 #[derive(Clone)]
 pub struct RPCCallReadOnlyRequestHandler {
     maximum_call_argument_size: u32,
-    read_only_call_limit: ExecutionCost,
+    read_only_call_limits: ExecutionLimits,
+    /// Whether a caller may ask for the call's execution trace
+    allow_trace: bool,
 
     /// Runtime fields
     pub contract_identifier: Option<QualifiedContractIdentifier>,
@@ -89,10 +98,15 @@ pub struct RPCCallReadOnlyRequestHandler {
 }
 
 impl RPCCallReadOnlyRequestHandler {
-    pub fn new(maximum_call_argument_size: u32, read_only_call_limit: ExecutionCost) -> Self {
+    pub fn new(
+        maximum_call_argument_size: u32,
+        read_only_call_limits: ExecutionLimits,
+        allow_trace: bool,
+    ) -> Self {
         Self {
             maximum_call_argument_size,
-            read_only_call_limit,
+            read_only_call_limits,
+            allow_trace,
             contract_identifier: None,
             function: None,
             sender: None,
@@ -188,6 +202,11 @@ impl RPCRequestHandler for RPCCallReadOnlyRequestHandler {
         self.arguments = None;
     }
 
+    /// Read-only calls evaluate Clarity code, so they are limited separately from plain reads
+    fn rate_limit_class(&self) -> RPCRateLimitClass {
+        RPCRateLimitClass::ReadOnlyCall
+    }
+
     /// Make the response
     fn try_handle_request(
         &mut self,
@@ -220,6 +239,32 @@ impl RPCRequestHandler for RPCCallReadOnlyRequestHandler {
             .take()
             .ok_or(NetError::SendError("Missing `arguments`".into()))?;
 
+        let want_trace = contents.get_query_arg("trace").map(String::as_str) == Some("1");
+        let tracer = (self.allow_trace && want_trace).then(ExecutionTracer::new);
+
+        // the same call at the same confirmed block always gets the same response (traces
+        // aren't cached)
+        let cache_key = match contents.tip_request() {
+            TipRequest::UseLatestUnconfirmedTip => None,
+            _ if tracer.is_some() => None,
+            _ => self.make_cache_key(
+                &tip,
+                &contract_identifier,
+                &function,
+                &sender,
+                sponsor.as_ref(),
+                &arguments,
+            ),
+        };
+        if let Some(body) = cache_key
+            .as_ref()
+            .and_then(|key| node.get_cached_rpc_response(key))
+        {
+            let mut preamble = HttpResponsePreamble::ok_json(&preamble);
+            preamble.set_canonical_stacks_tip_height(Some(node.canonical_stacks_tip_height()));
+            return Ok((preamble, HttpResponseContents::from_ram(body)));
+        }
+
         // run the read-only call
         let data_resp =
             node.with_node_state(|_network, sortdb, chainstate, _mempool, _rpc_args| {
@@ -230,9 +275,7 @@ impl RPCRequestHandler for RPCCallReadOnlyRequestHandler {
 
                 let mainnet = chainstate.mainnet;
                 let chain_id = chainstate.chain_id;
-                let mut cost_limit = self.read_only_call_limit.clone();
-                cost_limit.write_length = 0;
-                cost_limit.write_count = 0;
+                let limits = self.read_only_call_limits.clone();
 
                 chainstate.maybe_read_only_clarity_tx(
                     &sortdb.index_handle_at_block(chainstate, &tip)?,
@@ -241,8 +284,8 @@ impl RPCRequestHandler for RPCCallReadOnlyRequestHandler {
                         let epoch = clarity_tx.get_epoch();
                         let cost_track = clarity_tx
                             .with_clarity_db_readonly(|clarity_db| {
-                                LimitedCostTracker::new_mid_block(
-                                    mainnet, chain_id, cost_limit, clarity_db, epoch,
+                                LimitedCostTracker::new_read_only(
+                                    mainnet, chain_id, &limits, clarity_db, epoch,
                                 )
                             })
                             .map_err(|_| {
@@ -260,13 +303,15 @@ impl RPCRequestHandler for RPCCallReadOnlyRequestHandler {
                                 )))
                             })?;
 
-                        clarity_tx.with_readonly_clarity_env(
+                        let mut hook = tracer.clone();
+                        clarity_tx.with_readonly_clarity_env_and_hook(
                             mainnet,
                             chain_id,
                             clarity_version,
                             sender,
                             sponsor,
                             cost_track,
+                            hook.as_mut().map(|hook| hook as &mut dyn EvalHook),
                             |env| {
                                 // we want to execute any function as long as no actual writes are made as
                                 // opposed to be limited to purely calling `define-read-only` functions,
@@ -287,6 +332,7 @@ impl RPCRequestHandler for RPCCallReadOnlyRequestHandler {
             });
 
         // decode the response
+        let trace = tracer.map(|tracer| tracer.entries());
         let data_resp = match data_resp {
             Ok(Some(Ok(data))) => {
                 let hex_result = data
@@ -297,6 +343,7 @@ impl RPCRequestHandler for RPCCallReadOnlyRequestHandler {
                     okay: true,
                     result: Some(format!("0x{}", hex_result)),
                     cause: None,
+                    trace,
                 }
             }
             Ok(Some(Err(e))) => match e {
@@ -307,12 +354,14 @@ impl RPCRequestHandler for RPCCallReadOnlyRequestHandler {
                         okay: false,
                         result: None,
                         cause: Some("NotReadOnly".to_string()),
+                        trace,
                     }
                 }
                 _ => CallReadOnlyResponse {
                     okay: false,
                     result: None,
                     cause: Some(e.to_string()),
+                    trace,
                 },
             },
             Ok(None) | Err(_) => {
@@ -328,10 +377,43 @@ impl RPCRequestHandler for RPCCallReadOnlyRequestHandler {
         let mut preamble = HttpResponsePreamble::ok_json(&preamble);
         preamble.set_canonical_stacks_tip_height(Some(node.canonical_stacks_tip_height()));
         let body = HttpResponseContents::try_from_json(&data_resp)?;
+        if let (Some(cache_key), HttpResponseContents::RAM(bytes)) = (cache_key, &body) {
+            node.cache_rpc_response(cache_key, bytes.clone());
+        }
         Ok((preamble, body))
     }
 }
 
+impl RPCCallReadOnlyRequestHandler {
+    /// Identify a call for the response cache.
+    /// Returns None if an argument can't be serialized, in which case the call isn't cached.
+    fn make_cache_key(
+        &self,
+        tip: &StacksBlockId,
+        contract_identifier: &QualifiedContractIdentifier,
+        function: &ClarityName,
+        sender: &PrincipalData,
+        sponsor: Option<&PrincipalData>,
+        arguments: &[Value],
+    ) -> Option<RPCResponseCacheKey> {
+        let arguments = arguments
+            .iter()
+            .map(|arg| arg.serialize_to_hex().ok())
+            .collect::<Option<Vec<_>>>()?;
+        let sponsor = sponsor
+            .map(|sponsor| sponsor.to_string())
+            .unwrap_or_default();
+        Some(RPCResponseCacheKey {
+            endpoint: self.metrics_identifier().to_string(),
+            params: format!(
+                "{contract_identifier}/{function}/{sender}/{sponsor}/{}",
+                arguments.join(",")
+            ),
+            tip: tip.clone(),
+        })
+    }
+}
+
 /// Decode the HTTP response
 impl HttpResponse for RPCCallReadOnlyRequestHandler {
     fn try_parse_response(
@@ -356,6 +438,36 @@ impl StacksHttpRequest {
         function_args: Vec<Value>,
         tip_req: TipRequest,
     ) -> StacksHttpRequest {
+        StacksHttpRequest::new_callreadonlyfunction_with_trace(
+            host,
+            contract_addr,
+            contract_name,
+            sender,
+            sponsor,
+            function_name,
+            function_args,
+            tip_req,
+            false,
+        )
+    }
+
+    /// Make a new request to run a read-only function, optionally asking for its execution trace
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_callreadonlyfunction_with_trace(
+        host: PeerHost,
+        contract_addr: StacksAddress,
+        contract_name: ContractName,
+        sender: PrincipalData,
+        sponsor: Option<PrincipalData>,
+        function_name: ClarityName,
+        function_args: Vec<Value>,
+        tip_req: TipRequest,
+        trace: bool,
+    ) -> StacksHttpRequest {
+        let mut contents = HttpRequestContents::new().for_tip(tip_req);
+        if trace {
+            contents = contents.query_arg("trace".into(), "1".into());
+        }
         StacksHttpRequest::new_for_peer(
             host,
             "POST".into(),
@@ -363,7 +475,7 @@ impl StacksHttpRequest {
                 "/v2/contracts/call-read/{}/{}/{}",
                 &contract_addr, &contract_name, &function_name
             ),
-            HttpRequestContents::new().for_tip(tip_req).payload_json(
+            contents.payload_json(
                 serde_json::to_value(CallReadOnlyRequestBody {
                     sender: sender.to_string(),
                     sponsor: sponsor.map(|s| s.to_string()),
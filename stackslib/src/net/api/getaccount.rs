@@ -18,7 +18,7 @@ use std::io::{Read, Write};
 
 use clarity::vm::clarity::ClarityConnection;
 use clarity::vm::costs::LimitedCostTracker;
-use clarity::vm::database::{ClarityDatabase, STXBalance};
+use clarity::vm::database::{ClarityDatabase, ReadMode, STXBalance};
 use clarity::vm::representations::PRINCIPAL_DATA_REGEX_STRING;
 use clarity::vm::types::{PrincipalData, StandardPrincipalData};
 use clarity::vm::ClarityVersion;
@@ -142,7 +142,12 @@ impl RPCRequestHandler for RPCGetAccountRequestHandler {
             .account
             .take()
             .ok_or(NetError::SendError("Missing `account`".into()))?;
-        let with_proof = contents.get_with_proof();
+        let read_mode = contents.get_read_mode();
+        // an absent entry is reported with an empty proof if a proof was requested
+        let missing_proof: Option<String> = match read_mode {
+            ReadMode::WithProof => Some("".into()),
+            ReadMode::Plain => None,
+        };
 
         let account_opt_res =
             node.with_node_state(|_network, sortdb, chainstate, _mempool, _rpc_args| {
@@ -157,38 +162,20 @@ impl RPCRequestHandler for RPCGetAccountRequestHandler {
                             let v1_unlock_height = clarity_db.get_v1_unlock_height();
                             let v2_unlock_height = clarity_db.get_v2_unlock_height().ok()?;
                             let v3_unlock_height = clarity_db.get_v3_unlock_height().ok()?;
-                            let (balance, balance_proof) = if with_proof {
-                                clarity_db
-                                    .get_data_with_proof::<STXBalance>(&key)
-                                    .ok()
-                                    .flatten()
-                                    .map(|(a, b)| (a, Some(format!("0x{}", to_hex(&b)))))
-                                    .unwrap_or_else(|| (STXBalance::zero(), Some("".into())))
-                            } else {
-                                clarity_db
-                                    .get_data::<STXBalance>(&key)
-                                    .ok()
-                                    .flatten()
-                                    .map(|a| (a, None))
-                                    .unwrap_or_else(|| (STXBalance::zero(), None))
-                            };
+                            let (balance, balance_proof) = clarity_db
+                                .get_data_with_mode::<STXBalance>(&key, read_mode)
+                                .ok()
+                                .flatten()
+                                .map(|(a, b)| (a, b.map(|b| format!("0x{}", to_hex(&b)))))
+                                .unwrap_or_else(|| (STXBalance::zero(), missing_proof.clone()));
 
                             let key = ClarityDatabase::make_key_for_account_nonce(&account);
-                            let (nonce, nonce_proof) = if with_proof {
-                                clarity_db
-                                    .get_data_with_proof(&key)
-                                    .ok()
-                                    .flatten()
-                                    .map(|(a, b)| (a, Some(format!("0x{}", to_hex(&b)))))
-                                    .unwrap_or_else(|| (0, Some("".into())))
-                            } else {
-                                clarity_db
-                                    .get_data(&key)
-                                    .ok()
-                                    .flatten()
-                                    .map(|a| (a, None))
-                                    .unwrap_or_else(|| (0, None))
-                            };
+                            let (nonce, nonce_proof) = clarity_db
+                                .get_data_with_mode(&key, read_mode)
+                                .ok()
+                                .flatten()
+                                .map(|(a, b)| (a, b.map(|b| format!("0x{}", to_hex(&b)))))
+                                .unwrap_or_else(|| (0, missing_proof.clone()));
 
                             let unlocked = balance
                                 .get_available_balance_at_burn_block(
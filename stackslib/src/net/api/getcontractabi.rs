@@ -16,7 +16,7 @@
 
 use std::io::{Read, Write};
 
-use clarity::vm::analysis::contract_interface_builder::ContractInterface;
+use clarity::vm::analysis::contract_interface_builder::{ContractAbi, ContractInterface};
 use clarity::vm::ast::parser::v1::CLARITY_NAME_REGEX;
 use clarity::vm::clarity::ClarityConnection;
 use clarity::vm::costs::LimitedCostTracker;
@@ -130,17 +130,31 @@ impl RPCRequestHandler for RPCGetContractAbiRequestHandler {
             }
         };
 
+        // `?abi=1` asks for the contract's ABI document instead of its stored interface
+        let want_abi = contents.get_query_arg("abi").map(String::as_str) == Some("1");
+
         let data_resp =
             node.with_node_state(|_network, sortdb, chainstate, _mempool, _rpc_args| {
                 chainstate.maybe_read_only_clarity_tx(
                     &sortdb.index_handle_at_block(chainstate, &tip)?,
                     &tip,
                     |clarity_tx| {
+                        if want_abi {
+                            return clarity_tx.with_clarity_db_readonly(|db| {
+                                let analysis =
+                                    db.load_contract_analysis(&contract_identifier).ok()??;
+                                let contract = db.get_contract(&contract_identifier).ok()?;
+                                let abi =
+                                    analysis.export_abi(Some(&contract.contract_context)).ok()?;
+                                serde_json::to_value(abi).ok()
+                            });
+                        }
                         let epoch = clarity_tx.get_epoch();
                         clarity_tx.with_analysis_db_readonly(|db| {
                             db.load_contract(&contract_identifier, &epoch)
                                 .ok()?
-                                .map(|contract| contract.contract_interface)
+                                .and_then(|contract| contract.contract_interface)
+                                .and_then(|interface| serde_json::to_value(interface).ok())
                         })
                     },
                 )
@@ -180,7 +194,8 @@ impl HttpResponse for RPCGetContractAbiRequestHandler {
         preamble: &HttpResponsePreamble,
         body: &[u8],
     ) -> Result<HttpResponsePayload, Error> {
-        let contract_src: ContractInterface = parse_json(preamble, body)?;
+        // either a `ContractInterface` or a `ContractAbi`, depending on the request
+        let contract_src: serde_json::Value = parse_json(preamble, body)?;
         Ok(HttpResponsePayload::try_from_json(contract_src)?)
     }
 }
@@ -204,6 +219,27 @@ impl StacksHttpRequest {
         )
         .expect("FATAL: failed to construct request from infallible data")
     }
+
+    /// Make a new request for a contract's ABI document (see `ContractAbi`)
+    pub fn new_getcontractabi_document(
+        host: PeerHost,
+        contract_addr: StacksAddress,
+        contract_name: ContractName,
+        tip_req: TipRequest,
+    ) -> StacksHttpRequest {
+        StacksHttpRequest::new_for_peer(
+            host,
+            "GET".into(),
+            format!(
+                "/v2/contracts/interface/{}/{}",
+                &contract_addr, &contract_name
+            ),
+            HttpRequestContents::new()
+                .for_tip(tip_req)
+                .query_arg("abi".into(), "1".into()),
+        )
+        .expect("FATAL: failed to construct request from infallible data")
+    }
 }
 
 impl StacksHttpResponse {
@@ -214,4 +250,12 @@ impl StacksHttpResponse {
             .map_err(|_e| NetError::DeserializeError("Failed to load from JSON".to_string()))?;
         Ok(resp)
     }
+
+    pub fn decode_contract_abi_document_response(self) -> Result<ContractAbi, NetError> {
+        let contents = self.get_http_payload_ok()?;
+        let contents_json: serde_json::Value = contents.try_into()?;
+        let resp: ContractAbi = serde_json::from_value(contents_json)
+            .map_err(|_e| NetError::DeserializeError("Failed to load from JSON".to_string()))?;
+        Ok(resp)
+    }
 }
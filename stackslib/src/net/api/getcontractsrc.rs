@@ -18,8 +18,8 @@ use std::io::{Read, Write};
 
 use clarity::vm::clarity::ClarityConnection;
 use clarity::vm::costs::LimitedCostTracker;
-use clarity::vm::database::clarity_store::{make_contract_hash_key, ContractCommitment};
-use clarity::vm::database::{ClarityDatabase, STXBalance, StoreType};
+use clarity::vm::database::clarity_store::ContractCommitment;
+use clarity::vm::database::{ClarityDatabase, ClarityStoreKey, STXBalance, StoreType};
 use clarity::vm::representations::{
     CONTRACT_NAME_REGEX_STRING, PRINCIPAL_DATA_REGEX_STRING, STANDARD_PRINCIPAL_REGEX_STRING,
 };
@@ -136,7 +136,7 @@ impl RPCRequestHandler for RPCGetContractSrcRequestHandler {
                 return error_resp.try_into_contents().map_err(NetError::from);
             }
         };
-        let with_proof = contents.get_with_proof();
+        let read_mode = contents.get_read_mode();
 
         let data_resp =
             node.with_node_state(|_network, sortdb, chainstate, _mempool, _rpc_args| {
@@ -146,18 +146,16 @@ impl RPCRequestHandler for RPCGetContractSrcRequestHandler {
                     |clarity_tx| {
                         clarity_tx.with_clarity_db_readonly(|db| {
                             let source = db.get_contract_src(&contract_identifier)?;
-                            let contract_commit_key = make_contract_hash_key(&contract_identifier);
-                            let (contract_commit, proof) = if with_proof {
-                                db.get_data_with_proof::<ContractCommitment>(&contract_commit_key)
-                                    .ok()
-                                    .flatten()
-                                    .map(|(a, b)| (a, Some(format!("0x{}", to_hex(&b)))))?
-                            } else {
-                                db.get_data::<ContractCommitment>(&contract_commit_key)
-                                    .ok()
-                                    .flatten()
-                                    .map(|a| (a, None))?
-                            };
+                            let contract_commit_key =
+                                ClarityStoreKey::ContractHash(&contract_identifier).to_string();
+                            let (contract_commit, proof) = db
+                                .get_data_with_mode::<ContractCommitment>(
+                                    &contract_commit_key,
+                                    read_mode,
+                                )
+                                .ok()
+                                .flatten()
+                                .map(|(a, b)| (a, b.map(|b| format!("0x{}", to_hex(&b)))))?;
 
                             let publish_height = contract_commit.block_height;
                             Some(ContractSrcResponse {
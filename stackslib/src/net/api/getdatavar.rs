@@ -19,7 +19,7 @@ use std::io::{Read, Write};
 use clarity::vm::ast::parser::v1::CLARITY_NAME_REGEX;
 use clarity::vm::clarity::ClarityConnection;
 use clarity::vm::costs::LimitedCostTracker;
-use clarity::vm::database::{ClarityDatabase, STXBalance, StoreType};
+use clarity::vm::database::{ClarityDatabase, ClarityStoreKey, STXBalance, StoreType};
 use clarity::vm::representations::{
     CONTRACT_NAME_REGEX_STRING, PRINCIPAL_DATA_REGEX_STRING, STANDARD_PRINCIPAL_REGEX_STRING,
 };
@@ -146,12 +146,12 @@ impl RPCRequestHandler for RPCGetDataVarRequestHandler {
             }
         };
 
-        let with_proof = contents.get_with_proof();
-        let key = ClarityDatabase::make_key_for_trip(
-            &contract_identifier,
-            StoreType::Variable,
-            &var_name,
-        );
+        let read_mode = contents.get_read_mode();
+        let key = ClarityStoreKey::Var {
+            contract: &contract_identifier,
+            name: &var_name,
+        }
+        .to_string();
 
         let data_opt = node.with_node_state(|_network, sortdb, chainstate, _mempool, _rpc_args| {
             chainstate.maybe_read_only_clarity_tx(
@@ -159,19 +159,11 @@ impl RPCRequestHandler for RPCGetDataVarRequestHandler {
                 &tip,
                 |clarity_tx| {
                     clarity_tx.with_clarity_db_readonly(|clarity_db| {
-                        let (value_hex, marf_proof): (String, _) = if with_proof {
-                            clarity_db
-                                .get_data_with_proof(&key)
-                                .ok()
-                                .flatten()
-                                .map(|(a, b)| (a, Some(format!("0x{}", to_hex(&b)))))?
-                        } else {
-                            clarity_db
-                                .get_data(&key)
-                                .ok()
-                                .flatten()
-                                .map(|a| (a, None))?
-                        };
+                        let (value_hex, marf_proof): (String, _) = clarity_db
+                            .get_data_with_mode(&key, read_mode)
+                            .ok()
+                            .flatten()
+                            .map(|(a, b)| (a, b.map(|b| format!("0x{}", to_hex(&b)))))?;
 
                         let data = format!("0x{}", value_hex);
                         Some(DataVarResponse { data, marf_proof })
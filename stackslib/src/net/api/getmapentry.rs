@@ -19,7 +19,7 @@ use std::io::{Read, Write};
 use clarity::vm::ast::parser::v1::CLARITY_NAME_REGEX;
 use clarity::vm::clarity::ClarityConnection;
 use clarity::vm::costs::LimitedCostTracker;
-use clarity::vm::database::{ClarityDatabase, STXBalance, StoreType};
+use clarity::vm::database::{ClarityDatabase, ReadMode, STXBalance, StoreType};
 use clarity::vm::representations::{
     CONTRACT_NAME_REGEX_STRING, PRINCIPAL_DATA_REGEX_STRING, STANDARD_PRINCIPAL_REGEX_STRING,
 };
@@ -173,7 +173,7 @@ impl RPCRequestHandler for RPCGetMapEntryRequestHandler {
                 return error_resp.try_into_contents().map_err(NetError::from);
             }
         };
-        let with_proof = contents.get_with_proof();
+        let read_mode = contents.get_read_mode();
         let key =
             ClarityDatabase::make_key_for_data_map_entry(&contract_identifier, &map_name, &key)
                 .map_err(|e| NetError::SerializeError(format!("{:?}", &e)))?;
@@ -188,27 +188,19 @@ impl RPCRequestHandler for RPCGetMapEntryRequestHandler {
                     &tip,
                     |clarity_tx| {
                         clarity_tx.with_clarity_db_readonly(|clarity_db| {
-                            let (value_hex, marf_proof): (String, _) = if with_proof {
-                                clarity_db
-                                    .get_data_with_proof(&key)
-                                    .ok()
-                                    .flatten()
-                                    .map(|(a, b)| (a, Some(format!("0x{}", to_hex(&b)))))
-                                    .unwrap_or_else(|| {
-                                        test_debug!("No value for '{}' in {}", &key, tip);
-                                        (none_response, Some("".into()))
-                                    })
-                            } else {
-                                clarity_db
-                                    .get_data(&key)
-                                    .ok()
-                                    .flatten()
-                                    .map(|a| (a, None))
-                                    .unwrap_or_else(|| {
-                                        test_debug!("No value for '{}' in {}", &key, tip);
-                                        (none_response, None)
-                                    })
-                            };
+                            let (value_hex, marf_proof): (String, _) = clarity_db
+                                .get_data_with_mode(&key, read_mode)
+                                .ok()
+                                .flatten()
+                                .map(|(a, b)| (a, b.map(|b| format!("0x{}", to_hex(&b)))))
+                                .unwrap_or_else(|| {
+                                    test_debug!("No value for '{}' in {}", &key, tip);
+                                    let marf_proof = match read_mode {
+                                        ReadMode::WithProof => Some("".into()),
+                                        ReadMode::Plain => None,
+                                    };
+                                    (none_response, marf_proof)
+                                });
 
                             let data = format!("0x{}", value_hex);
                             MapEntryResponse { data, marf_proof }
@@ -0,0 +1,326 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::str::FromStr;
+
+use clarity::vm::types::QualifiedContractIdentifier;
+use regex::{Captures, Regex};
+use stacks_common::codec::StacksMessageCodec;
+use stacks_common::types::chainstate::StacksAddress;
+use stacks_common::types::net::PeerHost;
+use stacks_common::types::Address;
+use stacks_common::util::hash::to_hex;
+
+use crate::burnchains::Txid;
+use crate::core::mempool::{MemPoolDB, MemPoolPayloadType, MemPoolTxFilter, MemPoolTxInfo};
+use crate::net::http::{
+    parse_json, Error, HttpRequest, HttpRequestContents, HttpRequestPreamble, HttpResponse,
+    HttpResponseContents, HttpResponsePayload, HttpResponsePreamble, HttpServerError,
+};
+use crate::net::httpcore::{
+    HttpPreambleExtensions, RPCRequestHandler, StacksHttpRequest, StacksHttpResponse,
+};
+use crate::net::{Error as NetError, StacksNodeState};
+
+/// Number of transactions returned per page if the client doesn't say
+pub const DEFAULT_MEMPOOL_TXS_PAGE_LIMIT: u64 = 50;
+/// Maximum number of transactions returned per page
+pub const MAX_MEMPOOL_TXS_PAGE_LIMIT: u64 = 200;
+/// Maximum number of mempool transactions examined per request, so that a selective filter
+/// can't make a single request scan the whole mempool
+pub const MAX_MEMPOOL_TXS_SCAN: u64 = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemPoolTxEntry {
+    pub txid: Txid,
+    /// hex-encoded transaction
+    pub tx: String,
+    pub origin_address: String,
+    pub origin_nonce: u64,
+    pub sponsor_address: String,
+    pub sponsor_nonce: u64,
+    pub tx_fee: u64,
+    pub len: u64,
+    pub accept_time: u64,
+}
+
+impl From<MemPoolTxInfo> for MemPoolTxEntry {
+    fn from(txinfo: MemPoolTxInfo) -> Self {
+        Self {
+            txid: txinfo.metadata.txid,
+            tx: to_hex(&txinfo.tx.serialize_to_vec()),
+            origin_address: txinfo.metadata.origin_address.to_string(),
+            origin_nonce: txinfo.metadata.origin_nonce,
+            sponsor_address: txinfo.metadata.sponsor_address.to_string(),
+            sponsor_nonce: txinfo.metadata.sponsor_nonce,
+            tx_fee: txinfo.metadata.tx_fee,
+            len: txinfo.metadata.len,
+            accept_time: txinfo.metadata.accept_time,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemPoolTxsResponse {
+    pub txs: Vec<MemPoolTxEntry>,
+    /// Pass this as `page_id` to get the next page.  `None` if there are no more transactions.
+    pub next_page_id: Option<Txid>,
+}
+
+#[derive(Clone)]
+pub struct RPCGetMemPoolTxsRequestHandler {
+    pub filter: Option<MemPoolTxFilter>,
+    pub page_id: Option<Txid>,
+    pub limit: Option<u64>,
+}
+
+impl RPCGetMemPoolTxsRequestHandler {
+    pub fn new() -> Self {
+        Self {
+            filter: None,
+            page_id: None,
+            limit: None,
+        }
+    }
+}
+
+/// Decode the HTTP request
+impl HttpRequest for RPCGetMemPoolTxsRequestHandler {
+    fn verb(&self) -> &'static str {
+        "GET"
+    }
+
+    fn path_regex(&self) -> Regex {
+        Regex::new(r#"^/v2/mempool/txs$"#).unwrap()
+    }
+
+    fn metrics_identifier(&self) -> &str {
+        "/v2/mempool/txs"
+    }
+
+    /// Try to decode this request.
+    /// There's nothing to load here, so just make sure the request is well-formed.
+    fn try_parse_request(
+        &mut self,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        query: Option<&str>,
+        _body: &[u8],
+    ) -> Result<HttpRequestContents, Error> {
+        if preamble.get_content_length() != 0 {
+            return Err(Error::DecodeError(
+                "Invalid Http request: expected 0-length body for GetMemPoolTxs".to_string(),
+            ));
+        }
+
+        let req_contents = HttpRequestContents::new().query_string(query);
+        let origin = req_contents
+            .get_query_arg("origin")
+            .map(|origin| {
+                StacksAddress::from_string(origin).ok_or_else(|| {
+                    Error::DecodeError("Failed to parse origin= query parameter".to_string())
+                })
+            })
+            .transpose()?;
+        let contract = req_contents
+            .get_query_arg("contract")
+            .map(|contract| QualifiedContractIdentifier::parse(contract))
+            .transpose()
+            .map_err(|e| {
+                Error::DecodeError(format!(
+                    "Failed to parse contract= query parameter: {:?}",
+                    &e
+                ))
+            })?;
+        let payload_type = req_contents
+            .get_query_arg("payload_type")
+            .map(|payload_type| MemPoolPayloadType::from_str(payload_type))
+            .transpose()
+            .map_err(|e| {
+                Error::DecodeError(format!(
+                    "Failed to parse payload_type= query parameter: {e}"
+                ))
+            })?;
+        let min_fee = req_contents
+            .get_query_arg("min_fee")
+            .map(|min_fee| min_fee.parse::<u64>())
+            .transpose()
+            .map_err(|e| {
+                Error::DecodeError(format!(
+                    "Failed to parse min_fee= query parameter: {:?}",
+                    &e
+                ))
+            })?;
+        let max_fee = req_contents
+            .get_query_arg("max_fee")
+            .map(|max_fee| max_fee.parse::<u64>())
+            .transpose()
+            .map_err(|e| {
+                Error::DecodeError(format!(
+                    "Failed to parse max_fee= query parameter: {:?}",
+                    &e
+                ))
+            })?;
+        let page_id = req_contents
+            .get_query_arg("page_id")
+            .map(|page_id| Txid::from_hex(page_id))
+            .transpose()
+            .map_err(|e| {
+                Error::DecodeError(format!(
+                    "Failed to parse page_id= query parameter: {:?}",
+                    &e
+                ))
+            })?;
+        let limit = req_contents
+            .get_query_arg("limit")
+            .map(|limit| limit.parse::<u64>())
+            .transpose()
+            .map_err(|e| {
+                Error::DecodeError(format!("Failed to parse limit= query parameter: {:?}", &e))
+            })?;
+
+        if matches!(limit, Some(limit) if limit == 0 || limit > MAX_MEMPOOL_TXS_PAGE_LIMIT) {
+            return Err(Error::DecodeError(format!(
+                "Invalid Http request: limit must be between 1 and {}",
+                MAX_MEMPOOL_TXS_PAGE_LIMIT
+            )));
+        }
+
+        self.filter = Some(MemPoolTxFilter {
+            origin,
+            contract,
+            payload_type,
+            min_fee,
+            max_fee,
+        });
+        self.page_id = page_id;
+        self.limit = limit;
+
+        Ok(req_contents)
+    }
+}
+
+impl RPCRequestHandler for RPCGetMemPoolTxsRequestHandler {
+    /// Reset internal state
+    fn restart(&mut self) {
+        self.filter = None;
+        self.page_id = None;
+        self.limit = None;
+    }
+
+    /// Make the response
+    fn try_handle_request(
+        &mut self,
+        preamble: HttpRequestPreamble,
+        _contents: HttpRequestContents,
+        node: &mut StacksNodeState,
+    ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        let filter = self
+            .filter
+            .take()
+            .ok_or(NetError::SendError("`filter` not set".into()))?;
+        let page_id = self.page_id.take();
+        let limit = self.limit.take().unwrap_or(DEFAULT_MEMPOOL_TXS_PAGE_LIMIT);
+
+        let txs_res = node.with_node_state(|_network, _sortdb, _chainstate, mempool, _rpc_args| {
+            MemPoolDB::list_txs(
+                mempool.conn(),
+                &filter,
+                page_id.as_ref(),
+                limit,
+                MAX_MEMPOOL_TXS_SCAN,
+            )
+        });
+
+        let (txs, next_page_id) = match txs_res {
+            Ok(txs) => txs,
+            Err(e) => {
+                return StacksHttpResponse::new_error(
+                    &preamble,
+                    &HttpServerError::new(format!("Failed to list mempool transactions: {:?}", &e)),
+                )
+                .try_into_contents()
+                .map_err(NetError::from);
+            }
+        };
+
+        let response = MemPoolTxsResponse {
+            txs: txs.into_iter().map(MemPoolTxEntry::from).collect(),
+            next_page_id,
+        };
+
+        let mut preamble = HttpResponsePreamble::ok_json(&preamble);
+        preamble.set_canonical_stacks_tip_height(Some(node.canonical_stacks_tip_height()));
+        let body = HttpResponseContents::try_from_json(&response)?;
+        Ok((preamble, body))
+    }
+}
+
+/// Decode the HTTP response
+impl HttpResponse for RPCGetMemPoolTxsRequestHandler {
+    fn try_parse_response(
+        &self,
+        preamble: &HttpResponsePreamble,
+        body: &[u8],
+    ) -> Result<HttpResponsePayload, Error> {
+        let response: MemPoolTxsResponse = parse_json(preamble, body)?;
+        Ok(HttpResponsePayload::try_from_json(response)?)
+    }
+}
+
+impl StacksHttpRequest {
+    /// Make a new request to list mempool transactions
+    pub fn new_getmempooltxs(
+        host: PeerHost,
+        filter: &MemPoolTxFilter,
+        page_id: Option<&Txid>,
+        limit: Option<u64>,
+    ) -> StacksHttpRequest {
+        let mut contents = HttpRequestContents::new();
+        if let Some(origin) = filter.origin.as_ref() {
+            contents = contents.query_arg("origin".into(), origin.to_string());
+        }
+        if let Some(contract) = filter.contract.as_ref() {
+            contents = contents.query_arg("contract".into(), contract.to_string());
+        }
+        if let Some(payload_type) = filter.payload_type.as_ref() {
+            contents = contents.query_arg("payload_type".into(), payload_type.as_str().into());
+        }
+        if let Some(min_fee) = filter.min_fee {
+            contents = contents.query_arg("min_fee".into(), min_fee.to_string());
+        }
+        if let Some(max_fee) = filter.max_fee {
+            contents = contents.query_arg("max_fee".into(), max_fee.to_string());
+        }
+        if let Some(page_id) = page_id {
+            contents = contents.query_arg("page_id".into(), page_id.to_hex());
+        }
+        if let Some(limit) = limit {
+            contents = contents.query_arg("limit".into(), limit.to_string());
+        }
+        StacksHttpRequest::new_for_peer(host, "GET".into(), "/v2/mempool/txs".into(), contents)
+            .expect("FATAL: failed to construct request from infallible data")
+    }
+}
+
+impl StacksHttpResponse {
+    pub fn decode_getmempooltxs(self) -> Result<MemPoolTxsResponse, NetError> {
+        let contents = self.get_http_payload_ok()?;
+        let response_json: serde_json::Value = contents.try_into()?;
+        let response: MemPoolTxsResponse = serde_json::from_value(response_json)
+            .map_err(|_e| Error::DecodeError("Failed to decode JSON".to_string()))?;
+        Ok(response)
+    }
+}
@@ -29,9 +29,9 @@ use {serde, serde_json};
 use crate::chainstate::stacks::db::StacksChainState;
 use crate::chainstate::stacks::{Error as ChainError, StacksBlockHeader, StacksMicroblock};
 use crate::net::http::{
-    parse_bytes, Error, HttpBadRequest, HttpChunkGenerator, HttpContentType, HttpNotFound,
-    HttpRequest, HttpRequestContents, HttpRequestPreamble, HttpResponse, HttpResponseContents,
-    HttpResponsePayload, HttpResponsePreamble, HttpServerError, HttpVersion,
+    parse_bytes, Error, HttpBadRequest, HttpChunkGenerator, HttpContentType, HttpGone,
+    HttpNotFound, HttpRequest, HttpRequestContents, HttpRequestPreamble, HttpResponse,
+    HttpResponseContents, HttpResponsePayload, HttpResponsePreamble, HttpServerError, HttpVersion,
 };
 use crate::net::httpcore::{
     request, HttpRequestContentsExtensions, RPCRequestHandler, StacksHttp, StacksHttpRequest,
@@ -78,6 +78,13 @@ impl StacksIndexedMicroblockStream {
         )?
         .ok_or(ChainError::NoSuchBlockError)?;
 
+        // a stream is archived along with its parent anchored block, so checking its tail is
+        // enough
+        if StacksChainState::is_microblock_archived(&chainstate.db(), &mblock_info.microblock_hash)?
+        {
+            return Err(ChainError::MicroblockArchived(mblock_info.microblock_hash));
+        }
+
         let parent_index_block_hash = StacksBlockHeader::make_index_block_hash(
             &mblock_info.consensus_hash,
             &mblock_info.anchored_block_hash,
@@ -172,6 +179,17 @@ impl RPCRequestHandler for RPCMicroblocksIndexedRequestHandler {
                 .try_into_contents()
                 .map_err(NetError::from)
             }
+            Err(ChainError::MicroblockArchived(_)) => {
+                return StacksHttpResponse::new_error(
+                    &preamble,
+                    &HttpGone::new(format!(
+                        "Archived: microblock {:?} is no longer available\n",
+                        &tail_microblock_id
+                    )),
+                )
+                .try_into_contents()
+                .map_err(NetError::from)
+            }
             Err(e) => {
                 // nope -- error trying to check
                 let msg = format!("Failed to load microblock: {:?}\n", &e);
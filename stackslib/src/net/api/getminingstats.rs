@@ -0,0 +1,197 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use regex::{Captures, Regex};
+use stacks_common::types::net::PeerHost;
+
+use crate::chainstate::burn::db::mining_stats::{
+    MiningStats, DEFAULT_MINING_STATS_WINDOW, MAX_MINING_STATS_WINDOW,
+};
+use crate::chainstate::burn::db::sortdb::SortitionDB;
+use crate::net::http::{
+    parse_json, Error, HttpRequest, HttpRequestContents, HttpRequestPreamble, HttpResponse,
+    HttpResponseContents, HttpResponsePayload, HttpResponsePreamble, HttpServerError,
+};
+use crate::net::httpcore::{RPCRequestHandler, StacksHttpRequest, StacksHttpResponse};
+use crate::net::{Error as NetError, StacksNodeState};
+
+#[derive(Clone)]
+pub struct RPCGetMiningStatsRequestHandler {
+    pub window: Option<u64>,
+    pub burn_fee: Option<u64>,
+}
+
+impl RPCGetMiningStatsRequestHandler {
+    pub fn new() -> Self {
+        Self {
+            window: None,
+            burn_fee: None,
+        }
+    }
+}
+
+/// Decode the HTTP request
+impl HttpRequest for RPCGetMiningStatsRequestHandler {
+    fn verb(&self) -> &'static str {
+        "GET"
+    }
+
+    fn path_regex(&self) -> Regex {
+        Regex::new(r#"^/v3/sortitions/mining_stats$"#).unwrap()
+    }
+
+    fn metrics_identifier(&self) -> &str {
+        "/v3/sortitions/mining_stats"
+    }
+
+    /// Try to decode this request.
+    /// There's nothing to load here, so just make sure the request is well-formed.
+    fn try_parse_request(
+        &mut self,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        query: Option<&str>,
+        _body: &[u8],
+    ) -> Result<HttpRequestContents, Error> {
+        if preamble.get_content_length() != 0 {
+            return Err(Error::DecodeError(
+                "Invalid Http request: expected 0-length body for GetMiningStats".to_string(),
+            ));
+        }
+
+        let req_contents = HttpRequestContents::new().query_string(query);
+        let window = req_contents
+            .get_query_arg("window")
+            .map(|window| window.parse::<u64>())
+            .transpose()
+            .map_err(|e| {
+                Error::DecodeError(format!("Failed to parse window= query parameter: {:?}", &e))
+            })?;
+        let burn_fee = req_contents
+            .get_query_arg("burn_fee")
+            .map(|burn_fee| burn_fee.parse::<u64>())
+            .transpose()
+            .map_err(|e| {
+                Error::DecodeError(format!(
+                    "Failed to parse burn_fee= query parameter: {:?}",
+                    &e
+                ))
+            })?;
+
+        if matches!(window, Some(window) if window == 0 || window > MAX_MINING_STATS_WINDOW) {
+            return Err(Error::DecodeError(format!(
+                "Invalid Http request: window must be between 1 and {}",
+                MAX_MINING_STATS_WINDOW
+            )));
+        }
+
+        self.window = window;
+        self.burn_fee = burn_fee;
+
+        Ok(req_contents)
+    }
+}
+
+impl RPCRequestHandler for RPCGetMiningStatsRequestHandler {
+    /// Reset internal state
+    fn restart(&mut self) {
+        self.window = None;
+        self.burn_fee = None;
+    }
+
+    /// Make the response
+    fn try_handle_request(
+        &mut self,
+        preamble: HttpRequestPreamble,
+        _contents: HttpRequestContents,
+        node: &mut StacksNodeState,
+    ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        let window = self.window.take().unwrap_or(DEFAULT_MINING_STATS_WINDOW);
+        let burn_fee = self.burn_fee.take();
+
+        let stats_res =
+            node.with_node_state(|network, sortdb, _chainstate, _mempool, _rpc_args| {
+                SortitionDB::get_mining_stats(
+                    sortdb.conn(),
+                    &network.burnchain_tip,
+                    window,
+                    burn_fee,
+                )
+            });
+
+        let stats = match stats_res {
+            Ok(stats) => stats,
+            Err(e) => {
+                return StacksHttpResponse::new_error(
+                    &preamble,
+                    &HttpServerError::new(format!("Failed to load mining stats: {:?}", &e)),
+                )
+                .try_into_contents()
+                .map_err(NetError::from);
+            }
+        };
+
+        let preamble = HttpResponsePreamble::ok_json(&preamble);
+        let body = HttpResponseContents::try_from_json(&stats)?;
+        Ok((preamble, body))
+    }
+}
+
+/// Decode the HTTP response
+impl HttpResponse for RPCGetMiningStatsRequestHandler {
+    fn try_parse_response(
+        &self,
+        preamble: &HttpResponsePreamble,
+        body: &[u8],
+    ) -> Result<HttpResponsePayload, Error> {
+        let stats: MiningStats = parse_json(preamble, body)?;
+        Ok(HttpResponsePayload::try_from_json(stats)?)
+    }
+}
+
+impl StacksHttpRequest {
+    /// Make a new request for the mining stats of the last `window` sortitions, and optionally
+    /// for the chance that a commit of `burn_fee` would have won them
+    pub fn new_get_mining_stats(
+        host: PeerHost,
+        window: Option<u64>,
+        burn_fee: Option<u64>,
+    ) -> StacksHttpRequest {
+        let mut contents = HttpRequestContents::new();
+        if let Some(window) = window {
+            contents = contents.query_arg("window".into(), window.to_string());
+        }
+        if let Some(burn_fee) = burn_fee {
+            contents = contents.query_arg("burn_fee".into(), burn_fee.to_string());
+        }
+        StacksHttpRequest::new_for_peer(
+            host,
+            "GET".into(),
+            "/v3/sortitions/mining_stats".into(),
+            contents,
+        )
+        .expect("FATAL: failed to construct request from infallible data")
+    }
+}
+
+impl StacksHttpResponse {
+    pub fn decode_mining_stats(self) -> Result<MiningStats, NetError> {
+        let contents = self.get_http_payload_ok()?;
+        let response_json: serde_json::Value = contents.try_into()?;
+        let stats: MiningStats = serde_json::from_value(response_json)
+            .map_err(|_e| Error::DecodeError("Failed to decode JSON".to_string()))?;
+        Ok(stats)
+    }
+}
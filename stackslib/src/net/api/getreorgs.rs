@@ -0,0 +1,171 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use regex::{Captures, Regex};
+use stacks_common::types::net::PeerHost;
+
+use crate::chainstate::stacks::db::reorgs::{StacksReorg, MAX_RECORDED_REORGS};
+use crate::chainstate::stacks::db::StacksChainState;
+use crate::net::http::{
+    parse_json, Error, HttpRequest, HttpRequestContents, HttpRequestPreamble, HttpResponse,
+    HttpResponseContents, HttpResponsePayload, HttpResponsePreamble, HttpServerError,
+};
+use crate::net::httpcore::{
+    HttpPreambleExtensions, RPCRequestHandler, StacksHttpRequest, StacksHttpResponse,
+};
+use crate::net::{Error as NetError, StacksNodeState};
+
+/// Number of reorgs returned if the client doesn't say
+pub const DEFAULT_REORGS_LIMIT: u64 = 20;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReorgsResponse {
+    /// The most recent reorgs of the canonical Stacks chain, most recent first
+    pub reorgs: Vec<StacksReorg>,
+}
+
+#[derive(Clone)]
+pub struct RPCGetReorgsRequestHandler {
+    pub limit: Option<u64>,
+}
+
+impl RPCGetReorgsRequestHandler {
+    pub fn new() -> Self {
+        Self { limit: None }
+    }
+}
+
+/// Decode the HTTP request
+impl HttpRequest for RPCGetReorgsRequestHandler {
+    fn verb(&self) -> &'static str {
+        "GET"
+    }
+
+    fn path_regex(&self) -> Regex {
+        Regex::new(r#"^/v3/reorgs$"#).unwrap()
+    }
+
+    fn metrics_identifier(&self) -> &str {
+        "/v3/reorgs"
+    }
+
+    /// Try to decode this request.
+    /// There's nothing to load here, so just make sure the request is well-formed.
+    fn try_parse_request(
+        &mut self,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        query: Option<&str>,
+        _body: &[u8],
+    ) -> Result<HttpRequestContents, Error> {
+        if preamble.get_content_length() != 0 {
+            return Err(Error::DecodeError(
+                "Invalid Http request: expected 0-length body for GetReorgs".to_string(),
+            ));
+        }
+
+        let req_contents = HttpRequestContents::new().query_string(query);
+        let limit = req_contents
+            .get_query_arg("limit")
+            .map(|limit| limit.parse::<u64>())
+            .transpose()
+            .map_err(|e| {
+                Error::DecodeError(format!("Failed to parse limit= query parameter: {:?}", &e))
+            })?;
+
+        if matches!(limit, Some(limit) if limit == 0 || limit > MAX_RECORDED_REORGS) {
+            return Err(Error::DecodeError(format!(
+                "Invalid Http request: limit must be between 1 and {}",
+                MAX_RECORDED_REORGS
+            )));
+        }
+
+        self.limit = limit;
+        Ok(req_contents)
+    }
+}
+
+impl RPCRequestHandler for RPCGetReorgsRequestHandler {
+    /// Reset internal state
+    fn restart(&mut self) {
+        self.limit = None;
+    }
+
+    /// Make the response
+    fn try_handle_request(
+        &mut self,
+        preamble: HttpRequestPreamble,
+        _contents: HttpRequestContents,
+        node: &mut StacksNodeState,
+    ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        let limit = self.limit.take().unwrap_or(DEFAULT_REORGS_LIMIT);
+
+        let reorgs_res =
+            node.with_node_state(|_network, _sortdb, chainstate, _mempool, _rpc_args| {
+                StacksChainState::get_recent_reorgs(chainstate.db(), limit)
+            });
+
+        let reorgs = match reorgs_res {
+            Ok(reorgs) => reorgs,
+            Err(e) => {
+                return StacksHttpResponse::new_error(
+                    &preamble,
+                    &HttpServerError::new(format!("Failed to load reorgs: {:?}", &e)),
+                )
+                .try_into_contents()
+                .map_err(NetError::from);
+            }
+        };
+
+        let mut preamble = HttpResponsePreamble::ok_json(&preamble);
+        preamble.set_canonical_stacks_tip_height(Some(node.canonical_stacks_tip_height()));
+        let body = HttpResponseContents::try_from_json(&ReorgsResponse { reorgs })?;
+        Ok((preamble, body))
+    }
+}
+
+/// Decode the HTTP response
+impl HttpResponse for RPCGetReorgsRequestHandler {
+    fn try_parse_response(
+        &self,
+        preamble: &HttpResponsePreamble,
+        body: &[u8],
+    ) -> Result<HttpResponsePayload, Error> {
+        let response: ReorgsResponse = parse_json(preamble, body)?;
+        Ok(HttpResponsePayload::try_from_json(response)?)
+    }
+}
+
+impl StacksHttpRequest {
+    /// Make a new request for the most recent reorgs
+    pub fn new_getreorgs(host: PeerHost, limit: Option<u64>) -> StacksHttpRequest {
+        let mut contents = HttpRequestContents::new();
+        if let Some(limit) = limit {
+            contents = contents.query_arg("limit".into(), limit.to_string());
+        }
+        StacksHttpRequest::new_for_peer(host, "GET".into(), "/v3/reorgs".into(), contents)
+            .expect("FATAL: failed to construct request from infallible data")
+    }
+}
+
+impl StacksHttpResponse {
+    pub fn decode_getreorgs(self) -> Result<ReorgsResponse, NetError> {
+        let contents = self.get_http_payload_ok()?;
+        let response_json: serde_json::Value = contents.try_into()?;
+        let response: ReorgsResponse = serde_json::from_value(response_json)
+            .map_err(|_e| Error::DecodeError("Failed to decode JSON".to_string()))?;
+        Ok(response)
+    }
+}
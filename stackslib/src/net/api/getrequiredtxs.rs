@@ -0,0 +1,158 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use regex::{Captures, Regex};
+use stacks_common::types::net::PeerHost;
+use stacks_common::util::hash::Sha512Trunc256Sum;
+
+use crate::core::required_txs::RequiredTxSet;
+use crate::net::http::{
+    parse_json, Error, HttpNotFound, HttpRequest, HttpRequestContents, HttpRequestPreamble,
+    HttpResponse, HttpResponseContents, HttpResponsePayload, HttpResponsePreamble, HttpServerError,
+};
+use crate::net::httpcore::{
+    HttpPreambleExtensions, RPCRequestHandler, StacksHttpRequest, StacksHttpResponse,
+};
+use crate::net::{Error as NetError, StacksNodeState};
+
+#[derive(Clone)]
+pub struct RPCGetRequiredTxsRequestHandler {
+    pub set_id: Option<Sha512Trunc256Sum>,
+}
+
+impl RPCGetRequiredTxsRequestHandler {
+    pub fn new() -> Self {
+        Self { set_id: None }
+    }
+}
+
+/// Decode the HTTP request
+impl HttpRequest for RPCGetRequiredTxsRequestHandler {
+    fn verb(&self) -> &'static str {
+        "GET"
+    }
+
+    fn path_regex(&self) -> Regex {
+        Regex::new(r#"^/v3/miner/required_txs/(?P<set_id>[0-9a-f]{64})$"#).unwrap()
+    }
+
+    fn metrics_identifier(&self) -> &str {
+        "/v3/miner/required_txs/:set_id"
+    }
+
+    /// Try to decode this request.
+    /// There's nothing to load here, so just make sure the request is well-formed.
+    fn try_parse_request(
+        &mut self,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _body: &[u8],
+    ) -> Result<HttpRequestContents, Error> {
+        if preamble.get_content_length() != 0 {
+            return Err(Error::DecodeError(
+                "Invalid Http request: expected 0-length body for GetRequiredTxs".to_string(),
+            ));
+        }
+
+        let set_id = captures
+            .name("set_id")
+            .ok_or_else(|| Error::Http(404, "Missing `set_id`".into()))
+            .and_then(|set_id| {
+                Sha512Trunc256Sum::from_hex(set_id.as_str())
+                    .map_err(|_e| Error::Http(400, "Failed to decode `set_id`".into()))
+            })?;
+        self.set_id = Some(set_id);
+
+        Ok(HttpRequestContents::new().query_string(query))
+    }
+}
+
+impl RPCRequestHandler for RPCGetRequiredTxsRequestHandler {
+    /// Reset internal state
+    fn restart(&mut self) {
+        self.set_id = None;
+    }
+
+    /// Make the response
+    fn try_handle_request(
+        &mut self,
+        preamble: HttpRequestPreamble,
+        _contents: HttpRequestContents,
+        node: &mut StacksNodeState,
+    ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        let set_id = self
+            .set_id
+            .take()
+            .ok_or(NetError::SendError("Missing `set_id`".into()))?;
+
+        let set_res = node.with_node_state(|_network, _sortdb, _chainstate, mempool, _rpc_args| {
+            mempool.get_required_tx_set(&set_id)
+        });
+
+        let set = match set_res {
+            Ok(Some(set)) => set,
+            Ok(None) => {
+                return StacksHttpResponse::new_error(
+                    &preamble,
+                    &HttpNotFound::new(format!("No required transaction set {set_id}\n")),
+                )
+                .try_into_contents()
+                .map_err(NetError::from);
+            }
+            Err(e) => {
+                return StacksHttpResponse::new_error(
+                    &preamble,
+                    &HttpServerError::new(format!(
+                        "Failed to load required transaction set: {:?}",
+                        &e
+                    )),
+                )
+                .try_into_contents()
+                .map_err(NetError::from);
+            }
+        };
+
+        let mut preamble = HttpResponsePreamble::ok_json(&preamble);
+        preamble.set_canonical_stacks_tip_height(Some(node.canonical_stacks_tip_height()));
+        let body = HttpResponseContents::try_from_json(&set)?;
+        Ok((preamble, body))
+    }
+}
+
+/// Decode the HTTP response
+impl HttpResponse for RPCGetRequiredTxsRequestHandler {
+    fn try_parse_response(
+        &self,
+        preamble: &HttpResponsePreamble,
+        body: &[u8],
+    ) -> Result<HttpResponsePayload, Error> {
+        let set: RequiredTxSet = parse_json(preamble, body)?;
+        Ok(HttpResponsePayload::try_from_json(set)?)
+    }
+}
+
+impl StacksHttpRequest {
+    /// Make a new request for the status of a required transaction set
+    pub fn new_get_required_txs(host: PeerHost, set_id: &Sha512Trunc256Sum) -> StacksHttpRequest {
+        StacksHttpRequest::new_for_peer(
+            host,
+            "GET".into(),
+            format!("/v3/miner/required_txs/{set_id}"),
+            HttpRequestContents::new(),
+        )
+        .expect("FATAL: failed to construct request from infallible data")
+    }
+}
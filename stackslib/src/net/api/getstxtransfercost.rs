@@ -117,6 +117,7 @@ impl RPCRequestHandler for RPCGetStxTransferCostRequestHandler {
                         estimated_cost,
                         estimated_len,
                         stacks_epoch,
+                        None,
                     )?
                     .estimations;
                 if estimations.len() != 3 {
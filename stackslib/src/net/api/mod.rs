@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use clarity::vm::costs::ExecutionCost;
+use clarity::vm::costs::{ExecutionCost, ExecutionLimits};
 use stacks_common::codec::read_next;
 use stacks_common::types::chainstate::{
     BlockHeaderHash, BurnchainHeaderHash, ConsensusHash, SortitionId, StacksBlockId,
@@ -43,6 +43,7 @@ pub mod getattachmentsinv;
 pub mod getblock;
 pub mod getblock_v3;
 pub mod getblockbyheight;
+pub mod getblockcosts;
 pub mod getconstantval;
 pub mod getcontractabi;
 pub mod getcontractsrc;
@@ -51,11 +52,15 @@ pub mod getheaders;
 pub mod getinfo;
 pub mod getistraitimplemented;
 pub mod getmapentry;
+pub mod getmempooltxs;
 pub mod getmicroblocks_confirmed;
 pub mod getmicroblocks_indexed;
 pub mod getmicroblocks_unconfirmed;
+pub mod getminingstats;
 pub mod getneighbors;
 pub mod getpoxinfo;
+pub mod getreorgs;
+pub mod getrequiredtxs;
 pub mod getsigner;
 pub mod getsortition;
 pub mod getstackerdbchunk;
@@ -71,9 +76,11 @@ pub mod postblock;
 pub mod postblock_proposal;
 #[warn(unused_imports)]
 pub mod postblock_v3;
+pub mod postdryrundeploy;
 pub mod postfeerate;
 pub mod postmempoolquery;
 pub mod postmicroblock;
+pub mod postrequiredtxs;
 pub mod poststackerdbchunk;
 pub mod posttransaction;
 
@@ -86,7 +93,11 @@ impl StacksHttp {
     pub fn register_rpc_methods(&mut self) {
         self.register_rpc_endpoint(callreadonly::RPCCallReadOnlyRequestHandler::new(
             self.maximum_call_argument_size,
-            self.read_only_call_limit.clone(),
+            ExecutionLimits::from_cost(
+                &self.read_only_call_limit,
+                self.read_only_call_memory_limit,
+            ),
+            self.read_only_call_trace,
         ));
         self.register_rpc_endpoint(getaccount::RPCGetAccountRequestHandler::new());
         self.register_rpc_endpoint(getattachment::RPCGetAttachmentRequestHandler::new());
@@ -94,6 +105,7 @@ impl StacksHttp {
         self.register_rpc_endpoint(getblock::RPCBlocksRequestHandler::new());
         self.register_rpc_endpoint(getblock_v3::RPCNakamotoBlockRequestHandler::new());
         self.register_rpc_endpoint(getblockbyheight::RPCNakamotoBlockByHeightRequestHandler::new());
+        self.register_rpc_endpoint(getblockcosts::RPCGetBlockCostsRequestHandler::new());
         self.register_rpc_endpoint(getconstantval::RPCGetConstantValRequestHandler::new());
         self.register_rpc_endpoint(getcontractabi::RPCGetContractAbiRequestHandler::new());
         self.register_rpc_endpoint(getcontractsrc::RPCGetContractSrcRequestHandler::new());
@@ -104,6 +116,7 @@ impl StacksHttp {
             getistraitimplemented::RPCGetIsTraitImplementedRequestHandler::new(),
         );
         self.register_rpc_endpoint(getmapentry::RPCGetMapEntryRequestHandler::new());
+        self.register_rpc_endpoint(getmempooltxs::RPCGetMemPoolTxsRequestHandler::new());
         self.register_rpc_endpoint(
             getmicroblocks_confirmed::RPCMicroblocksConfirmedRequestHandler::new(),
         );
@@ -113,10 +126,13 @@ impl StacksHttp {
         self.register_rpc_endpoint(
             getmicroblocks_unconfirmed::RPCMicroblocksUnconfirmedRequestHandler::new(),
         );
+        self.register_rpc_endpoint(getminingstats::RPCGetMiningStatsRequestHandler::new());
         self.register_rpc_endpoint(getneighbors::RPCNeighborsRequestHandler::new());
         self.register_rpc_endpoint(getstxtransfercost::RPCGetStxTransferCostRequestHandler::new());
         self.register_rpc_endpoint(getstackerdbchunk::RPCGetStackerDBChunkRequestHandler::new());
         self.register_rpc_endpoint(getpoxinfo::RPCPoxInfoRequestHandler::new());
+        self.register_rpc_endpoint(getreorgs::RPCGetReorgsRequestHandler::new());
+        self.register_rpc_endpoint(getrequiredtxs::RPCGetRequiredTxsRequestHandler::new());
         self.register_rpc_endpoint(
             getstackerdbmetadata::RPCGetStackerDBMetadataRequestHandler::new(),
         );
@@ -140,9 +156,13 @@ impl StacksHttp {
         self.register_rpc_endpoint(postblock_v3::RPCPostBlockRequestHandler::new(
             self.auth_token.clone(),
         ));
+        self.register_rpc_endpoint(postdryrundeploy::RPCDryRunDeployRequestHandler::new());
         self.register_rpc_endpoint(postfeerate::RPCPostFeeRateRequestHandler::new());
         self.register_rpc_endpoint(postmempoolquery::RPCMempoolQueryRequestHandler::new());
         self.register_rpc_endpoint(postmicroblock::RPCPostMicroblockRequestHandler::new());
+        self.register_rpc_endpoint(postrequiredtxs::RPCPostRequiredTxsRequestHandler::new(
+            self.auth_token.clone(),
+        ));
         self.register_rpc_endpoint(poststackerdbchunk::RPCPostStackerDBChunkRequestHandler::new());
         self.register_rpc_endpoint(posttransaction::RPCPostTransactionRequestHandler::new());
     }
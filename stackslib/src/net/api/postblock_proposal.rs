@@ -26,7 +26,7 @@ use stacks_common::codec::{
 };
 use stacks_common::consts::CHAIN_ID_MAINNET;
 use stacks_common::types::chainstate::{
-    BlockHeaderHash, BurnchainHeaderHash, ConsensusHash, StacksBlockId, StacksPublicKey,
+    BlockHeaderHash, BurnchainHeaderHash, ConsensusHash, StacksBlockId, StacksPublicKey, TrieHash,
 };
 use stacks_common::types::net::PeerHost;
 use stacks_common::types::StacksPublicKeyBuffer;
@@ -38,6 +38,7 @@ use crate::burnchains::affirmation::AffirmationMap;
 use crate::burnchains::Txid;
 use crate::chainstate::burn::db::sortdb::{SortitionDB, SortitionHandleConn};
 use crate::chainstate::nakamoto::miner::NakamotoBlockBuilder;
+use crate::chainstate::nakamoto::prevalidate::{BlockPrevalidation, BlockViolation};
 use crate::chainstate::nakamoto::{NakamotoBlock, NakamotoChainState};
 use crate::chainstate::stacks::db::blocks::MINIMUM_TX_FEE_RATE_PER_BYTE;
 use crate::chainstate::stacks::db::{StacksBlockHeaderTypes, StacksChainState};
@@ -57,6 +58,7 @@ use crate::net::httpcore::{
     StacksHttpRequest, StacksHttpResponse,
 };
 use crate::net::p2p::PeerNetwork;
+use crate::net::ratelimit::RPCRateLimitClass;
 use crate::net::relay::Relayer;
 use crate::net::{
     Attachment, BlocksData, BlocksDatum, Error as NetError, StacksMessageType, StacksNodeState,
@@ -105,12 +107,16 @@ pub struct BlockValidateReject {
     pub signer_signature_hash: Sha512Trunc256Sum,
     pub reason: String,
     pub reason_code: ValidateRejectCode,
+    /// Everything wrong with the block, if it could be executed
+    #[serde(default)]
+    pub violations: Vec<BlockViolation>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct BlockValidateRejectReason {
     pub reason: String,
     pub reason_code: ValidateRejectCode,
+    pub violations: Vec<BlockViolation>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -134,6 +140,7 @@ where
         Self {
             reason: format!("Chainstate Error: {ce}"),
             reason_code: ValidateRejectCode::ChainstateError,
+            violations: vec![],
         }
     }
 }
@@ -145,6 +152,9 @@ pub struct BlockValidateOk {
     pub signer_signature_hash: Sha512Trunc256Sum,
     pub cost: ExecutionCost,
     pub size: u64,
+    /// The state root computed by executing the block
+    #[serde(default)]
+    pub state_index_root: TrieHash,
 }
 
 /// This enum is used for serializing the response to block
@@ -191,6 +201,7 @@ impl NakamotoBlockProposal {
                             signer_signature_hash: self.block.header.signer_signature_hash(),
                             reason_code: reason.reason_code,
                             reason: reason.reason,
+                            violations: reason.violations,
                         });
                 receiver.notify_proposal_result(result);
             })
@@ -214,6 +225,7 @@ impl NakamotoBlockProposal {
         .map_err(|e| BlockValidateRejectReason {
             reason_code: ValidateRejectCode::ChainstateError,
             reason: format!("Failed to query highest block in tenure ID: {:?}", &e),
+            violations: vec![],
         })?
         else {
             warn!(
@@ -224,6 +236,7 @@ impl NakamotoBlockProposal {
             return Err(BlockValidateRejectReason {
                 reason_code: ValidateRejectCode::NoSuchTenure,
                 reason: "Block is not a tenure-start block, and has an unrecognized tenure consensus hash".into(),
+                violations: vec![],
             });
         };
         let Some(parent_header) =
@@ -231,6 +244,7 @@ impl NakamotoBlockProposal {
                 |e| BlockValidateRejectReason {
                     reason_code: ValidateRejectCode::ChainstateError,
                     reason: format!("Failed to query block header by block ID: {:?}", &e),
+                    violations: vec![],
                 },
             )?
         else {
@@ -242,6 +256,7 @@ impl NakamotoBlockProposal {
             return Err(BlockValidateRejectReason {
                 reason_code: ValidateRejectCode::UnknownParent,
                 reason: "Block has no parent".into(),
+                violations: vec![],
             });
         };
         if parent_header.anchored_header.height() != highest_header.anchored_header.height() {
@@ -255,6 +270,7 @@ impl NakamotoBlockProposal {
             return Err(BlockValidateRejectReason {
                 reason_code: ValidateRejectCode::InvalidBlock,
                 reason: "Block is not higher than the highest block in its tenure".into(),
+                violations: vec![],
             });
         }
         Ok(())
@@ -276,6 +292,7 @@ impl NakamotoBlockProposal {
             return Err(BlockValidateRejectReason {
                 reason_code: ValidateRejectCode::NonCanonicalTenure,
                 reason: "Tenure consensus hash is not on the canonical Bitcoin fork".into(),
+                violations: vec![],
             });
         }
         Ok(())
@@ -297,6 +314,7 @@ impl NakamotoBlockProposal {
                 .map_err(|_| BlockValidateRejectReason {
                     reason_code: ValidateRejectCode::InvalidBlock,
                     reason: "Block is not well-formed".into(),
+                    violations: vec![],
                 })?;
 
         if !is_tenure_start {
@@ -317,6 +335,7 @@ impl NakamotoBlockProposal {
             .ok_or_else(|| BlockValidateRejectReason {
                 reason_code: ValidateRejectCode::UnknownParent,
                 reason: "No parent block".into(),
+                violations: vec![],
             })?;
 
             Self::check_block_builds_on_highest_block_in_tenure(
@@ -371,6 +390,7 @@ impl NakamotoBlockProposal {
             return Err(BlockValidateRejectReason {
                 reason_code: ValidateRejectCode::InvalidBlock,
                 reason: "Wrong network/chain_id".into(),
+                violations: vec![],
             });
         }
 
@@ -385,6 +405,7 @@ impl NakamotoBlockProposal {
         .ok_or_else(|| BlockValidateRejectReason {
             reason_code: ValidateRejectCode::InvalidBlock,
             reason: "Invalid parent block".into(),
+            violations: vec![],
         })?;
 
         let burn_view_consensus_hash =
@@ -394,9 +415,9 @@ impl NakamotoBlockProposal {
                 .ok_or_else(|| BlockValidateRejectReason {
                     reason_code: ValidateRejectCode::NoSuchTenure,
                     reason: "Failed to find sortition for block tenure".to_string(),
+                    violations: vec![],
                 })?;
 
-        let burn_dbconn: SortitionHandleConn = sortdb.index_handle(&sort_tip.sortition_id);
         let mut db_handle = sortdb.index_handle(&sort_tip.sortition_id);
 
         // (For the signer)
@@ -410,9 +431,9 @@ impl NakamotoBlockProposal {
         // get the burnchain tokens spent for this block. There must be a record of this (i.e.
         // there must be a block-commit for this), or otherwise this block doesn't correspond to
         // any burnchain chainstate.
-        let expected_burn_opt =
-            NakamotoChainState::get_expected_burns(&mut db_handle, chainstate.db(), &self.block)?;
-        if expected_burn_opt.is_none() {
+        let Some(expected_burn) =
+            NakamotoChainState::get_expected_burns(&mut db_handle, chainstate.db(), &self.block)?
+        else {
             warn!(
                 "Rejected block proposal";
                 "reason" => "Failed to find parent expected burns",
@@ -420,21 +441,10 @@ impl NakamotoBlockProposal {
             return Err(BlockValidateRejectReason {
                 reason_code: ValidateRejectCode::UnknownParent,
                 reason: "Failed to find parent expected burns".into(),
+                violations: vec![],
             });
         };
 
-        // Static validation checks
-        NakamotoChainState::validate_normal_nakamoto_block_burnchain(
-            chainstate.nakamoto_blocks_db(),
-            &db_handle,
-            expected_burn_opt,
-            &self.block,
-            mainnet,
-            self.chain_id,
-        )?;
-
-        // Validate txs against chainstate
-
         // Validate the block's timestamp. It must be:
         // - Greater than the parent block's timestamp
         // - At most 15 seconds into the future
@@ -451,6 +461,7 @@ impl NakamotoBlockProposal {
                 return Err(BlockValidateRejectReason {
                     reason_code: ValidateRejectCode::InvalidBlock,
                     reason: "Block timestamp is not greater than parent block".into(),
+                    violations: vec![],
                 });
             }
         }
@@ -464,119 +475,67 @@ impl NakamotoBlockProposal {
             return Err(BlockValidateRejectReason {
                 reason_code: ValidateRejectCode::InvalidBlock,
                 reason: "Block timestamp is too far into the future".into(),
+                violations: vec![],
             });
         }
 
-        let tenure_change = self
-            .block
-            .txs
-            .iter()
-            .find(|tx| matches!(tx.payload, TransactionPayload::TenureChange(..)));
-        let coinbase = self
-            .block
-            .txs
-            .iter()
-            .find(|tx| matches!(tx.payload, TransactionPayload::Coinbase(..)));
-        let tenure_cause = tenure_change.and_then(|tx| match &tx.payload {
-            TransactionPayload::TenureChange(tc) => Some(tc.cause),
-            _ => None,
-        });
-
-        let mut builder = NakamotoBlockBuilder::new(
+        // Static validation checks, and validation of txs against chainstate
+        let prevalidation = NakamotoChainState::prevalidate_block(
+            chainstate,
+            &db_handle,
             &parent_stacks_header,
-            &self.block.header.consensus_hash,
-            self.block.header.burn_spent,
-            tenure_change,
-            coinbase,
-            self.block.header.pox_treatment.len(),
-            None,
+            &self.block,
+            expected_burn,
         )?;
 
-        let mut miner_tenure_info =
-            builder.load_tenure_info(chainstate, &burn_dbconn, tenure_cause)?;
-        let mut tenure_tx = builder.tenure_begin(&burn_dbconn, &mut miner_tenure_info)?;
-
-        for (i, tx) in self.block.txs.iter().enumerate() {
-            let tx_len = tx.tx_len();
-            let tx_result = builder.try_mine_tx_with_len(
-                &mut tenure_tx,
-                &tx,
-                tx_len,
-                &BlockLimitFunction::NO_LIMIT_HIT,
-                ASTRules::PrecheckSize,
-            );
-            let err = match tx_result {
-                TransactionResult::Success(_) => Ok(()),
-                TransactionResult::Skipped(s) => Err(format!("tx {i} skipped: {}", s.error)),
-                TransactionResult::ProcessingError(e) => {
-                    Err(format!("Error processing tx {i}: {}", e.error))
-                }
-                TransactionResult::Problematic(p) => {
-                    Err(format!("Problematic tx {i}: {}", p.error))
+        if let Some(violation) = prevalidation.violations.first() {
+            let (reason, reason_code) = match violation {
+                BlockViolation::Transaction { reason, .. } => {
+                    (reason.clone(), ValidateRejectCode::BadTransaction)
                 }
+                BlockViolation::Header { .. } => (
+                    "Block hash is not as expected".to_string(),
+                    ValidateRejectCode::BadBlockHash,
+                ),
             };
-            if let Err(reason) = err {
-                warn!(
-                    "Rejected block proposal";
-                    "reason" => %reason,
-                    "tx" => ?tx,
-                );
-                return Err(BlockValidateRejectReason {
-                    reason,
-                    reason_code: ValidateRejectCode::BadTransaction,
-                });
-            }
-        }
-
-        let mut block = builder.mine_nakamoto_block(&mut tenure_tx);
-        let size = builder.get_bytes_so_far();
-        let cost = builder.tenure_finish(tenure_tx)?;
-
-        // Clone signatures from block proposal
-        // These have already been validated by `validate_nakamoto_block_burnchain()``
-        block.header.miner_signature = self.block.header.miner_signature.clone();
-        block.header.signer_signature = self.block.header.signer_signature.clone();
-
-        // Clone the timestamp from the block proposal, which has already been validated
-        block.header.timestamp = self.block.header.timestamp;
-
-        // Assuming `tx_merkle_root` has been checked we don't need to hash the whole block
-        let expected_block_header_hash = self.block.header.block_hash();
-        let computed_block_header_hash = block.header.block_hash();
-
-        if computed_block_header_hash != expected_block_header_hash {
             warn!(
                 "Rejected block proposal";
-                "reason" => "Block hash is not as expected",
-                "expected_block_header_hash" => %expected_block_header_hash,
-                "computed_block_header_hash" => %computed_block_header_hash,
-                //"expected_block" => %serde_json::to_string(&serde_json::to_value(&self.block).unwrap()).unwrap(),
-                //"computed_block" => %serde_json::to_string(&serde_json::to_value(&block).unwrap()).unwrap(),
+                "reason" => %reason,
+                "violations" => ?prevalidation.violations,
             );
             return Err(BlockValidateRejectReason {
-                reason: "Block hash is not as expected".into(),
-                reason_code: ValidateRejectCode::BadBlockHash,
+                reason,
+                reason_code,
+                violations: prevalidation.violations,
             });
         }
 
+        let BlockPrevalidation {
+            cost,
+            size,
+            state_index_root,
+            ..
+        } = prevalidation;
+
         info!(
             "Participant: validated anchored block";
-            "block_header_hash" => %computed_block_header_hash,
-            "height" => block.header.chain_length,
-            "tx_count" => block.txs.len(),
-            "parent_stacks_block_id" => %block.header.parent_block_id,
+            "block_header_hash" => %self.block.header.block_hash(),
+            "height" => self.block.header.chain_length,
+            "tx_count" => self.block.txs.len(),
+            "parent_stacks_block_id" => %self.block.header.parent_block_id,
             "block_size" => size,
             "execution_cost" => %cost,
             "validation_time_ms" => time_elapsed(),
-            "tx_fees_microstacks" => block.txs.iter().fold(0, |agg: u64, tx| {
+            "tx_fees_microstacks" => self.block.txs.iter().fold(0, |agg: u64, tx| {
                 agg.saturating_add(tx.get_tx_fee())
             })
         );
 
         Ok(BlockValidateOk {
-            signer_signature_hash: block.header.signer_signature_hash(),
+            signer_signature_hash: self.block.header.signer_signature_hash(),
             cost,
             size,
+            state_index_root,
         })
     }
 }
@@ -684,6 +643,11 @@ impl RPCRequestHandler for RPCBlockProposalRequestHandler {
         self.block_proposal = None
     }
 
+    /// Block proposals are charged to the credential that authorizes them
+    fn rate_limit_class(&self) -> RPCRateLimitClass {
+        RPCRateLimitClass::Authenticated
+    }
+
     /// Make the response
     fn try_handle_request(
         &mut self,
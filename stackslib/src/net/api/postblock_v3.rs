@@ -29,6 +29,7 @@ use crate::net::http::{
 use crate::net::httpcore::{
     HttpPreambleExtensions, RPCRequestHandler, StacksHttpRequest, StacksHttpResponse,
 };
+use crate::net::ratelimit::RPCRateLimitClass;
 use crate::net::relay::Relayer;
 use crate::net::{Error as NetError, NakamotoBlocksData, StacksMessageType, StacksNodeState};
 
@@ -145,6 +146,11 @@ impl RPCRequestHandler for RPCPostBlockRequestHandler {
         self.broadcast = None;
     }
 
+    /// Blocks posted with an authorization token are charged to it
+    fn rate_limit_class(&self) -> RPCRateLimitClass {
+        RPCRateLimitClass::Authenticated
+    }
+
     /// Make the response
     fn try_handle_request(
         &mut self,
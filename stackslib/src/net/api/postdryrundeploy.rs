@@ -0,0 +1,277 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use clarity::vm::clarity::{ClarityConnection, DeployDryRun};
+use clarity::vm::costs::LimitedCostTracker;
+use clarity::vm::types::{QualifiedContractIdentifier, StandardPrincipalData};
+use clarity::vm::{ClarityVersion, ContractName};
+use regex::{Captures, Regex};
+use stacks_common::types::chainstate::StacksAddress;
+use stacks_common::types::net::PeerHost;
+use stacks_common::types::Address;
+
+use crate::chainstate::burn::db::sortdb::SortitionDB;
+use crate::chainstate::stacks::MAX_TRANSACTION_LEN;
+use crate::net::http::{
+    parse_json, Error, HttpContentType, HttpNotFound, HttpRequest, HttpRequestContents,
+    HttpRequestPreamble, HttpResponse, HttpResponseContents, HttpResponsePayload,
+    HttpResponsePreamble, HttpServerError,
+};
+use crate::net::httpcore::{
+    HttpPreambleExtensions, HttpRequestContentsExtensions, RPCRequestHandler, StacksHttpRequest,
+    StacksHttpResponse,
+};
+use crate::net::ratelimit::RPCRateLimitClass;
+use crate::net::{Error as NetError, StacksNodeState, TipRequest};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DryRunDeployRequestBody {
+    /// The address that would deploy the contract
+    pub deployer: String,
+    pub contract_name: String,
+    pub source_code: String,
+    /// The Clarity version to deploy the contract with (the tip's default if not given)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clarity_version: Option<ClarityVersion>,
+}
+
+#[derive(Clone)]
+pub struct RPCDryRunDeployRequestHandler {
+    pub contract_identifier: Option<QualifiedContractIdentifier>,
+    pub source_code: Option<String>,
+    pub clarity_version: Option<ClarityVersion>,
+}
+
+impl RPCDryRunDeployRequestHandler {
+    pub fn new() -> Self {
+        Self {
+            contract_identifier: None,
+            source_code: None,
+            clarity_version: None,
+        }
+    }
+}
+
+/// Decode the HTTP request
+impl HttpRequest for RPCDryRunDeployRequestHandler {
+    fn verb(&self) -> &'static str {
+        "POST"
+    }
+
+    fn path_regex(&self) -> Regex {
+        Regex::new(r#"^/v2/contracts/dry-run-deploy$"#).unwrap()
+    }
+
+    fn metrics_identifier(&self) -> &str {
+        "/v2/contracts/dry-run-deploy"
+    }
+
+    /// Try to decode this request.
+    fn try_parse_request(
+        &mut self,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        query: Option<&str>,
+        body: &[u8],
+    ) -> Result<HttpRequestContents, Error> {
+        let content_len = preamble.get_content_length();
+        if !(content_len > 0 && content_len < MAX_TRANSACTION_LEN) {
+            return Err(Error::DecodeError(format!(
+                "Invalid Http request: invalid body length for DryRunDeploy ({})",
+                content_len
+            )));
+        }
+
+        if preamble.content_type != Some(HttpContentType::JSON) {
+            return Err(Error::DecodeError(
+                "Invalid content-type: expected application/json".to_string(),
+            ));
+        }
+
+        let body: DryRunDeployRequestBody = serde_json::from_slice(body)
+            .map_err(|_e| Error::DecodeError("Failed to parse JSON body".into()))?;
+
+        let deployer = StacksAddress::from_string(&body.deployer)
+            .ok_or_else(|| Error::DecodeError("Failed to parse deployer address".into()))?;
+        let contract_name = ContractName::try_from(body.contract_name)
+            .map_err(|_e| Error::DecodeError("Failed to parse contract name".into()))?;
+
+        self.contract_identifier = Some(QualifiedContractIdentifier::new(
+            StandardPrincipalData::from(deployer),
+            contract_name,
+        ));
+        self.source_code = Some(body.source_code);
+        self.clarity_version = body.clarity_version;
+
+        Ok(HttpRequestContents::new().query_string(query))
+    }
+}
+
+/// Handle the HTTP request
+impl RPCRequestHandler for RPCDryRunDeployRequestHandler {
+    /// Reset internal state
+    fn restart(&mut self) {
+        self.contract_identifier = None;
+        self.source_code = None;
+        self.clarity_version = None;
+    }
+
+    /// Dry runs evaluate Clarity code without committing it, like read-only calls
+    fn rate_limit_class(&self) -> RPCRateLimitClass {
+        RPCRateLimitClass::ReadOnlyCall
+    }
+
+    /// Make the response
+    fn try_handle_request(
+        &mut self,
+        preamble: HttpRequestPreamble,
+        contents: HttpRequestContents,
+        node: &mut StacksNodeState,
+    ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        let tip = match node.load_stacks_chain_tip(&preamble, &contents) {
+            Ok(tip) => tip,
+            Err(error_resp) => {
+                return error_resp.try_into_contents().map_err(NetError::from);
+            }
+        };
+
+        let contract_identifier = self
+            .contract_identifier
+            .take()
+            .ok_or(NetError::SendError("Missing `contract_identifier`".into()))?;
+        let source_code = self
+            .source_code
+            .take()
+            .ok_or(NetError::SendError("Missing `source_code`".into()))?;
+        let clarity_version = self.clarity_version.take();
+
+        let data_resp = node.with_node_state(|network, sortdb, chainstate, _mempool, _rpc_args| {
+            let mainnet = chainstate.mainnet;
+            let chain_id = chainstate.chain_id;
+            let ast_rules = network.ast_rules;
+
+            chainstate.maybe_read_only_clarity_tx(
+                &sortdb.index_handle_at_block(chainstate, &tip)?,
+                &tip,
+                |clarity_tx| {
+                    let epoch_id = clarity_tx.get_epoch();
+                    // the deployment may cost as much as a whole block
+                    let block_limit =
+                        SortitionDB::get_stacks_epoch_by_epoch_id(sortdb.conn(), &epoch_id)
+                            .ok()
+                            .flatten()
+                            .map(|epoch| epoch.block_limit)
+                            .ok_or_else(|| format!("Failed to load Stacks epoch {epoch_id}"))?;
+                    let cost_track = clarity_tx
+                        .with_clarity_db_readonly(|clarity_db| {
+                            LimitedCostTracker::new(
+                                mainnet,
+                                chain_id,
+                                block_limit,
+                                clarity_db,
+                                epoch_id,
+                            )
+                        })
+                        .map_err(|e| format!("Failed to load the cost functions: {e:?}"))?;
+                    let clarity_version = clarity_version
+                        .unwrap_or_else(|| ClarityVersion::default_for_epoch(epoch_id));
+
+                    Ok::<_, String>(clarity_tx.dry_run_deploy(
+                        mainnet,
+                        chain_id,
+                        &contract_identifier,
+                        clarity_version,
+                        &source_code,
+                        ast_rules,
+                        cost_track,
+                    ))
+                },
+            )
+        });
+
+        let data_resp = match data_resp {
+            Ok(Some(Ok(dry_run))) => dry_run,
+            Ok(Some(Err(msg))) => {
+                return StacksHttpResponse::new_error(&preamble, &HttpServerError::new(msg))
+                    .try_into_contents()
+                    .map_err(NetError::from);
+            }
+            Ok(None) | Err(_) => {
+                return StacksHttpResponse::new_error(
+                    &preamble,
+                    &HttpNotFound::new("Chain tip not found".to_string()),
+                )
+                .try_into_contents()
+                .map_err(NetError::from);
+            }
+        };
+
+        let mut preamble = HttpResponsePreamble::ok_json(&preamble);
+        preamble.set_canonical_stacks_tip_height(Some(node.canonical_stacks_tip_height()));
+        let body = HttpResponseContents::try_from_json(&data_resp)?;
+        Ok((preamble, body))
+    }
+}
+
+/// Decode the HTTP response
+impl HttpResponse for RPCDryRunDeployRequestHandler {
+    fn try_parse_response(
+        &self,
+        preamble: &HttpResponsePreamble,
+        body: &[u8],
+    ) -> Result<HttpResponsePayload, Error> {
+        let dry_run: DeployDryRun = parse_json(preamble, body)?;
+        Ok(HttpResponsePayload::try_from_json(dry_run)?)
+    }
+}
+
+impl StacksHttpRequest {
+    /// Make a new request to deploy a contract without committing it
+    pub fn new_dry_run_deploy(
+        host: PeerHost,
+        deployer: StacksAddress,
+        contract_name: ContractName,
+        source_code: String,
+        clarity_version: Option<ClarityVersion>,
+        tip_req: TipRequest,
+    ) -> StacksHttpRequest {
+        StacksHttpRequest::new_for_peer(
+            host,
+            "POST".into(),
+            "/v2/contracts/dry-run-deploy".into(),
+            HttpRequestContents::new().for_tip(tip_req).payload_json(
+                serde_json::to_value(DryRunDeployRequestBody {
+                    deployer: deployer.to_string(),
+                    contract_name: contract_name.to_string(),
+                    source_code,
+                    clarity_version,
+                })
+                .expect("FATAL: failed to encode infallible data"),
+            ),
+        )
+        .expect("FATAL: failed to construct request from infallible data")
+    }
+}
+
+impl StacksHttpResponse {
+    pub fn decode_dry_run_deploy_response(self) -> Result<DeployDryRun, NetError> {
+        let contents = self.get_http_payload_ok()?;
+        let contents_json: serde_json::Value = contents.try_into()?;
+        let resp: DeployDryRun = serde_json::from_value(contents_json)
+            .map_err(|_e| NetError::DeserializeError("Failed to load from JSON".to_string()))?;
+        Ok(resp)
+    }
+}
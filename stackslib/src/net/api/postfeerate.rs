@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::cmp;
 use std::io::{Read, Write};
 
 use clarity::vm::costs::ExecutionCost;
@@ -36,7 +37,9 @@ use crate::chainstate::stacks::TransactionPayload;
 use crate::core::mempool::MemPoolDB;
 use crate::core::StacksEpoch;
 use crate::cost_estimates::metrics::CostMetric;
-use crate::cost_estimates::{CostEstimator, FeeEstimator, FeeRateEstimate};
+use crate::cost_estimates::{
+    CostEstimator, FeeEstimateKind, FeeEstimator, FeeRateBands, FeeRateEstimate,
+};
 use crate::net::http::{
     parse_json, Error, HttpBadRequest, HttpContentType, HttpNotFound, HttpRequest,
     HttpRequestContents, HttpRequestPreamble, HttpResponse, HttpResponseContents,
@@ -81,12 +84,61 @@ impl RPCFeeEstimate {
     }
 }
 
+/// The fee for a percentile of the fee rates recently paid by the transaction's kind
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCFeePercentile {
+    pub percentile: u8,
+    pub fee_rate: f64,
+    pub fee: u64,
+    /// The range this percentile fell in for the middle 80% of recent blocks
+    pub fee_rate_lower: f64,
+    pub fee_rate_upper: f64,
+    pub fee_lower: u64,
+    pub fee_upper: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCFeeRateBands {
+    pub kind: FeeEstimateKind,
+    /// The number of recent blocks the percentiles are taken from
+    pub sample_blocks: u32,
+    pub percentiles: Vec<RPCFeePercentile>,
+}
+
+impl RPCFeeRateBands {
+    pub fn estimate_fees(scalar: u64, bands: FeeRateBands, minimum_fee: u64) -> RPCFeeRateBands {
+        let fee = |fee_rate: f64| cmp::max((fee_rate * (scalar as f64)) as u64, minimum_fee);
+        RPCFeeRateBands {
+            kind: bands.kind,
+            sample_blocks: bands.sample_blocks,
+            percentiles: bands
+                .bands
+                .into_iter()
+                .map(|band| RPCFeePercentile {
+                    percentile: band.percentile,
+                    fee_rate: band.fee_rate,
+                    fee: fee(band.fee_rate),
+                    fee_rate_lower: band.fee_rate_lower,
+                    fee_rate_upper: band.fee_rate_upper,
+                    fee_lower: fee(band.fee_rate_lower),
+                    fee_upper: fee(band.fee_rate_upper),
+                })
+                .collect(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RPCFeeEstimateResponse {
     pub estimated_cost: ExecutionCost,
     pub estimated_cost_scalar: u64,
     pub estimations: Vec<RPCFeeEstimate>,
     pub cost_scalar_change_by_byte: f64,
+    /// Percentiles of the fee rates recently paid by transactions of the same kind, if the fee
+    /// estimator tracks them
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_rate_bands: Option<RPCFeeRateBands>,
 }
 
 #[derive(Clone)]
@@ -104,7 +156,8 @@ impl RPCPostFeeRateRequestHandler {
     }
 
     /// Estimate a transaction fee, given its execution cost estimation and length estimation
-    /// and cost estimators.
+    /// and cost estimators.  If the transaction's `kind` is given, the fees for the percentiles
+    /// of the fee rates recently paid by that kind are included too.
     /// Returns Ok(fee structure) on success
     /// Returns Err(HTTP response) on error
     pub fn estimate_tx_fee_from_cost_and_length(
@@ -114,6 +167,7 @@ impl RPCPostFeeRateRequestHandler {
         estimated_cost: ExecutionCost,
         estimated_len: u64,
         stacks_epoch: StacksEpoch,
+        kind: Option<FeeEstimateKind>,
     ) -> Result<RPCFeeEstimateResponse, StacksHttpResponse> {
         let scalar_cost =
             metric.from_cost_and_len(&estimated_cost, &stacks_epoch.block_limit, estimated_len);
@@ -137,11 +191,16 @@ impl RPCPostFeeRateRequestHandler {
             }
         }
 
+        let fee_rate_bands = kind
+            .and_then(|kind| fee_estimator.get_rate_bands(kind).ok())
+            .map(|bands| RPCFeeRateBands::estimate_fees(scalar_cost, bands, minimum_fee));
+
         Ok(RPCFeeEstimateResponse {
             estimated_cost,
             estimations,
             estimated_cost_scalar: scalar_cost,
             cost_scalar_change_by_byte: metric.change_per_byte(),
+            fee_rate_bands,
         })
     }
 }
@@ -258,6 +317,7 @@ impl RPCRequestHandler for RPCPostFeeRateRequestHandler {
                         estimated_cost,
                         estimated_len,
                         stacks_epoch,
+                        FeeEstimateKind::from_payload(&tx),
                     )
                 } else {
                     debug!("Fee and cost estimation not configured on this stacks node");
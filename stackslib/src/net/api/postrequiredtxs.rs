@@ -0,0 +1,265 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashSet;
+
+use regex::{Captures, Regex};
+use stacks_common::codec::{StacksMessageCodec, MAX_PAYLOAD_LEN};
+use stacks_common::types::net::PeerHost;
+use stacks_common::util::hash::{hex_bytes, to_hex};
+
+use crate::chainstate::stacks::StacksTransaction;
+use crate::core::required_txs::{RequiredTxSet, MAX_REQUIRED_TXS_PER_SET};
+use crate::net::http::{
+    parse_json, Error, HttpBadRequest, HttpContentType, HttpRequest, HttpRequestContents,
+    HttpRequestPreamble, HttpResponse, HttpResponseContents, HttpResponsePayload,
+    HttpResponsePreamble, HttpServerError,
+};
+use crate::net::httpcore::{
+    HttpPreambleExtensions, HttpRequestContentsExtensions, RPCRequestHandler, StacksHttpRequest,
+    StacksHttpResponse,
+};
+use crate::net::ratelimit::RPCRateLimitClass;
+use crate::net::{Error as NetError, StacksNodeState};
+
+/// The body of a request to submit a required transaction set
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RequiredTxSetRequestBody {
+    /// The hex-encoded transactions, in the order the miner should try them
+    pub txs: Vec<String>,
+}
+
+#[derive(Clone)]
+pub struct RPCPostRequiredTxsRequestHandler {
+    pub txs: Option<Vec<StacksTransaction>>,
+    pub auth: Option<String>,
+}
+
+impl RPCPostRequiredTxsRequestHandler {
+    pub fn new(auth: Option<String>) -> Self {
+        Self { txs: None, auth }
+    }
+
+    /// Decode a JSON-encoded required transaction set
+    fn parse_json(body: &[u8]) -> Result<Vec<StacksTransaction>, Error> {
+        let body: RequiredTxSetRequestBody = serde_json::from_slice(body)
+            .map_err(|e| Error::DecodeError(format!("Failed to parse body: {e}")))?;
+        if body.txs.is_empty() {
+            return Err(Error::DecodeError(
+                "Required transaction set is empty".to_string(),
+            ));
+        }
+        if body.txs.len() > MAX_REQUIRED_TXS_PER_SET {
+            return Err(Error::DecodeError(format!(
+                "Required transaction set has more than {MAX_REQUIRED_TXS_PER_SET} transactions"
+            )));
+        }
+        let mut txids = HashSet::new();
+        let mut txs = vec![];
+        for tx_hex in body.txs.iter() {
+            let tx_bytes = hex_bytes(tx_hex)
+                .map_err(|_e| Error::DecodeError("Failed to parse transaction hex".to_string()))?;
+            let tx = StacksTransaction::consensus_deserialize(&mut &tx_bytes[..]).map_err(|e| {
+                Error::DecodeError(format!("Failed to deserialize transaction: {e}"))
+            })?;
+            if !txids.insert(tx.txid()) {
+                return Err(Error::DecodeError(format!(
+                    "Transaction {} appears more than once",
+                    tx.txid()
+                )));
+            }
+            txs.push(tx);
+        }
+        Ok(txs)
+    }
+}
+
+/// Decode the HTTP request
+impl HttpRequest for RPCPostRequiredTxsRequestHandler {
+    fn verb(&self) -> &'static str {
+        "POST"
+    }
+
+    fn path_regex(&self) -> Regex {
+        Regex::new(r#"^/v3/miner/required_txs$"#).unwrap()
+    }
+
+    fn metrics_identifier(&self) -> &str {
+        "/v3/miner/required_txs"
+    }
+
+    /// Try to decode this request.
+    fn try_parse_request(
+        &mut self,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        query: Option<&str>,
+        body: &[u8],
+    ) -> Result<HttpRequestContents, Error> {
+        // If no authorization is set, then the required transactions endpoint is not enabled
+        let Some(password) = &self.auth else {
+            return Err(Error::Http(400, "Bad Request.".into()));
+        };
+        let Some(auth_header) = preamble.headers.get("authorization") else {
+            return Err(Error::Http(401, "Unauthorized".into()));
+        };
+        if auth_header != password {
+            return Err(Error::Http(401, "Unauthorized".into()));
+        }
+        if preamble.get_content_length() == 0 {
+            return Err(Error::DecodeError(
+                "Invalid Http request: expected non-zero-length body for required transactions"
+                    .to_string(),
+            ));
+        }
+        if preamble.get_content_length() > MAX_PAYLOAD_LEN {
+            return Err(Error::DecodeError(
+                "Invalid Http request: required transactions body is too big".to_string(),
+            ));
+        }
+
+        let txs = match preamble.content_type {
+            Some(HttpContentType::JSON) => Self::parse_json(body)?,
+            Some(_) => {
+                return Err(Error::DecodeError(
+                    "Wrong Content-Type for required transactions; expected application/json"
+                        .to_string(),
+                ))
+            }
+            None => {
+                return Err(Error::DecodeError(
+                    "Missing Content-Type for required transactions".to_string(),
+                ))
+            }
+        };
+
+        self.txs = Some(txs);
+        Ok(HttpRequestContents::new().query_string(query))
+    }
+}
+
+impl RPCRequestHandler for RPCPostRequiredTxsRequestHandler {
+    /// Reset internal state
+    fn restart(&mut self) {
+        self.txs = None;
+    }
+
+    /// Required transaction sets are charged to the credential that authorizes them
+    fn rate_limit_class(&self) -> RPCRateLimitClass {
+        RPCRateLimitClass::Authenticated
+    }
+
+    /// Make the response
+    fn try_handle_request(
+        &mut self,
+        preamble: HttpRequestPreamble,
+        _contents: HttpRequestContents,
+        node: &mut StacksNodeState,
+    ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        let txs = self
+            .txs
+            .take()
+            .ok_or(NetError::SendError("`txs` not set".into()))?;
+
+        let set_res = node.with_node_state(|_network, _sortdb, chainstate, mempool, _rpc_args| {
+            if let Some(tx) = txs.iter().find(|tx| tx.chain_id != chainstate.chain_id) {
+                return Err(StacksHttpResponse::new_error(
+                    &preamble,
+                    &HttpBadRequest::new(format!(
+                        "Transaction {} is for chain ID {}, not {}",
+                        tx.txid(),
+                        tx.chain_id,
+                        chainstate.chain_id
+                    )),
+                ));
+            }
+            mempool
+                .submit_required_tx_set(&txs)
+                .and_then(|set_id| mempool.get_required_tx_set(&set_id))
+                .map_err(|e| {
+                    StacksHttpResponse::new_error(
+                        &preamble,
+                        &HttpServerError::new(format!(
+                            "Failed to store required transactions: {:?}",
+                            &e
+                        )),
+                    )
+                })
+        });
+
+        let set = match set_res {
+            Ok(Some(set)) => set,
+            Ok(None) => {
+                return StacksHttpResponse::new_error(
+                    &preamble,
+                    &HttpServerError::new("Required transaction set was not stored".into()),
+                )
+                .try_into_contents()
+                .map_err(NetError::from);
+            }
+            Err(response) => {
+                return response.try_into_contents().map_err(NetError::from);
+            }
+        };
+
+        let mut preamble = HttpResponsePreamble::ok_json(&preamble);
+        preamble.set_canonical_stacks_tip_height(Some(node.canonical_stacks_tip_height()));
+        let body = HttpResponseContents::try_from_json(&set)?;
+        Ok((preamble, body))
+    }
+}
+
+/// Decode the HTTP response
+impl HttpResponse for RPCPostRequiredTxsRequestHandler {
+    fn try_parse_response(
+        &self,
+        preamble: &HttpResponsePreamble,
+        body: &[u8],
+    ) -> Result<HttpResponsePayload, Error> {
+        let set: RequiredTxSet = parse_json(preamble, body)?;
+        Ok(HttpResponsePayload::try_from_json(set)?)
+    }
+}
+
+impl StacksHttpRequest {
+    /// Make a new request to submit a required transaction set
+    pub fn new_post_required_txs(host: PeerHost, txs: &[StacksTransaction]) -> StacksHttpRequest {
+        let body = RequiredTxSetRequestBody {
+            txs: txs
+                .iter()
+                .map(|tx| to_hex(&tx.serialize_to_vec()))
+                .collect(),
+        };
+        StacksHttpRequest::new_for_peer(
+            host,
+            "POST".into(),
+            "/v3/miner/required_txs".into(),
+            HttpRequestContents::new().payload_json(
+                serde_json::to_value(body).expect("FATAL: failed to encode required transactions"),
+            ),
+        )
+        .expect("FATAL: failed to construct request from infallible data")
+    }
+}
+
+impl StacksHttpResponse {
+    pub fn decode_required_tx_set(self) -> Result<RequiredTxSet, NetError> {
+        let contents = self.get_http_payload_ok()?;
+        let response_json: serde_json::Value = contents.try_into()?;
+        let set: RequiredTxSet = serde_json::from_value(response_json)
+            .map_err(|_e| Error::DecodeError("Failed to decode JSON".to_string()))?;
+        Ok(set)
+    }
+}
@@ -16,6 +16,8 @@
 
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 
+use clarity::vm::costs::{ExecutionLimits, CLARITY_MEMORY_LIMIT};
+use clarity::vm::tracer::TraceEntry;
 use clarity::vm::types::{PrincipalData, QualifiedContractIdentifier, StacksAddressExtensions};
 use clarity::vm::{ClarityName, ContractName};
 use stacks_common::codec::StacksMessageCodec;
@@ -60,8 +62,11 @@ fn test_try_parse_request() {
     debug!("Request:\n{}\n", std::str::from_utf8(&bytes).unwrap());
 
     let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
-    let mut handler =
-        callreadonly::RPCCallReadOnlyRequestHandler::new(4096, BLOCK_LIMIT_MAINNET_21);
+    let mut handler = callreadonly::RPCCallReadOnlyRequestHandler::new(
+        4096,
+        ExecutionLimits::from_cost(&BLOCK_LIMIT_MAINNET_21, CLARITY_MEMORY_LIMIT),
+        false,
+    );
     let mut parsed_request = http
         .handle_try_parse_request(
             &mut handler,
@@ -184,6 +189,37 @@ fn test_try_make_response() {
     );
     requests.push(request);
 
+    // query confirmed tip again (served from the response cache)
+    let request = StacksHttpRequest::new_callreadonlyfunction(
+        addr.into(),
+        StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R").unwrap(),
+        "hello-world".try_into().unwrap(),
+        StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+            .unwrap()
+            .to_account_principal(),
+        None,
+        "ro-confirmed".try_into().unwrap(),
+        vec![],
+        TipRequest::UseLatestAnchoredTip,
+    );
+    requests.push(request);
+
+    // query confirmed tip with a trace (not served from the response cache)
+    let request = StacksHttpRequest::new_callreadonlyfunction_with_trace(
+        addr.into(),
+        StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R").unwrap(),
+        "hello-world".try_into().unwrap(),
+        StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+            .unwrap()
+            .to_account_principal(),
+        None,
+        "ro-confirmed".try_into().unwrap(),
+        vec![],
+        TipRequest::UseLatestAnchoredTip,
+        true,
+    );
+    requests.push(request);
+
     let mut responses = test_rpc(function_name!(), requests);
 
     // confirmed tip
@@ -203,6 +239,7 @@ fn test_try_make_response() {
     assert!(resp.okay);
     assert!(resp.result.is_some());
     assert!(resp.cause.is_none());
+    assert!(resp.trace.is_none());
 
     // u1
     assert_eq!(resp.result.unwrap(), "0x0100000000000000000000000000000001");
@@ -270,4 +307,30 @@ fn test_try_make_response() {
 
     let (preamble, payload) = response.destruct();
     assert_eq!(preamble.status_code, 404);
+
+    // confirmed tip, again
+    let response = responses.remove(0);
+    assert_eq!(
+        response.preamble().get_canonical_stacks_tip_height(),
+        Some(1)
+    );
+
+    let resp = response.decode_call_readonly_response().unwrap();
+    assert!(resp.okay);
+    assert_eq!(resp.result.unwrap(), "0x0100000000000000000000000000000001");
+
+    // confirmed tip, traced
+    let response = responses.remove(0);
+    let resp = response.decode_call_readonly_response().unwrap();
+    assert!(resp.okay);
+    assert_eq!(resp.result.unwrap(), "0x0100000000000000000000000000000001");
+    let trace = resp.trace.unwrap();
+    assert!(matches!(
+        trace.first().unwrap(),
+        TraceEntry::ContractCall { function, .. } if function == "ro-confirmed"
+    ));
+    assert!(matches!(
+        trace.last().unwrap(),
+        TraceEntry::ContractReturn { result: Some(result), .. } if result == "u1"
+    ));
 }
@@ -116,6 +116,15 @@ fn test_try_make_response() {
     );
     requests.push(request);
 
+    // query the ABI document
+    let request = StacksHttpRequest::new_getcontractabi_document(
+        addr.into(),
+        StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R").unwrap(),
+        "hello-world".try_into().unwrap(),
+        TipRequest::UseLatestAnchoredTip,
+    );
+    requests.push(request);
+
     let mut responses = test_rpc(function_name!(), requests);
 
     // latest data
@@ -155,4 +164,26 @@ fn test_try_make_response() {
 
     let (preamble, body) = response.destruct();
     assert_eq!(preamble.status_code, 404);
+
+    // ABI document
+    let response = responses.remove(0);
+    debug!(
+        "Response:\n{}\n",
+        std::str::from_utf8(&response.try_serialize().unwrap()).unwrap()
+    );
+
+    let abi = response.decode_contract_abi_document_response().unwrap();
+    assert_eq!(abi.abi_version, 1);
+    assert_eq!(
+        abi.contract_id,
+        "ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R.hello-world"
+    );
+    assert_eq!(abi.defined_traits.len(), 2);
+    let bar = abi.variables.iter().find(|v| v.name == "bar").unwrap();
+    assert_eq!(bar.max_size, Some(17));
+    let test_map = abi.maps.iter().find(|m| m.name == "test-map").unwrap();
+    assert_eq!(
+        (test_map.key_max_size, test_map.value_max_size),
+        (Some(17), Some(17))
+    );
 }
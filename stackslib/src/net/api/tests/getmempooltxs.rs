@@ -0,0 +1,215 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use clarity::vm::types::QualifiedContractIdentifier;
+use stacks_common::types::chainstate::{StacksAddress, StacksPublicKey};
+use stacks_common::util::hash::Hash160;
+
+use super::TestRPC;
+use crate::burnchains::Txid;
+use crate::core::mempool::{MemPoolPayloadType, MemPoolTxFilter};
+use crate::net::api::*;
+use crate::net::connection::ConnectionOptions;
+use crate::net::http::HttpRequestContents;
+use crate::net::httpcore::{RPCRequestHandler, StacksHttp, StacksHttpRequest};
+use crate::net::ProtocolFamily;
+
+#[test]
+fn test_try_parse_request() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let mut http = StacksHttp::new(addr.clone(), &ConnectionOptions::default());
+
+    let filter = MemPoolTxFilter {
+        origin: Some(StacksAddress {
+            version: 26,
+            bytes: Hash160([0x11; 20]),
+        }),
+        contract: Some(
+            QualifiedContractIdentifier::parse("ST000000000000000000002AMW42H.pox-4").unwrap(),
+        ),
+        payload_type: Some(MemPoolPayloadType::ContractCall),
+        min_fee: Some(1),
+        max_fee: Some(1000),
+    };
+    let request = StacksHttpRequest::new_getmempooltxs(
+        addr.into(),
+        &filter,
+        Some(&Txid([0x22; 32])),
+        Some(10),
+    );
+    let bytes = request.try_serialize().unwrap();
+
+    debug!("Request:\n{}\n", std::str::from_utf8(&bytes).unwrap());
+
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut handler = getmempooltxs::RPCGetMemPoolTxsRequestHandler::new();
+    let mut parsed_request = http
+        .handle_try_parse_request(
+            &mut handler,
+            &parsed_preamble.expect_request(),
+            &bytes[offset..],
+        )
+        .unwrap();
+
+    assert_eq!(handler.filter, Some(filter));
+    assert_eq!(handler.page_id, Some(Txid([0x22; 32])));
+    assert_eq!(handler.limit, Some(10));
+
+    // parsed request consumes headers that would not be in a constructed reqeuest
+    parsed_request.clear_headers();
+    let (preamble, contents) = parsed_request.destruct();
+
+    assert_eq!(&preamble, request.preamble());
+
+    handler.restart();
+    assert!(handler.filter.is_none());
+    assert!(handler.page_id.is_none());
+    assert!(handler.limit.is_none());
+
+    // malformed and out-of-range parameters are rejected
+    for (key, value) in [
+        ("payload_type", "nonsense"),
+        ("min_fee", "-1"),
+        ("limit", "0"),
+        ("limit", "201"),
+    ] {
+        let request = StacksHttpRequest::new_for_peer(
+            addr.into(),
+            "GET".into(),
+            "/v2/mempool/txs".into(),
+            HttpRequestContents::new().query_arg(key.into(), value.into()),
+        )
+        .unwrap();
+        let bytes = request.try_serialize().unwrap();
+        let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+        let mut handler = getmempooltxs::RPCGetMemPoolTxsRequestHandler::new();
+        assert!(http
+            .handle_try_parse_request(
+                &mut handler,
+                &parsed_preamble.expect_request(),
+                &bytes[offset..],
+            )
+            .is_err());
+    }
+}
+
+#[test]
+fn test_try_make_response() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+
+    let rpc_test = TestRPC::setup(function_name!());
+    let origin = StacksAddress::p2pkh(false, &StacksPublicKey::from_private(&rpc_test.privk2));
+    let mut mempool_txids = rpc_test.mempool_txids.clone();
+    // listed in the order of their hex encodings, which is not `Txid`'s `Ord`
+    mempool_txids.sort_by_key(|txid| txid.to_hex());
+
+    let mut requests = vec![];
+
+    // first page of everything
+    let request = StacksHttpRequest::new_getmempooltxs(
+        addr.into(),
+        &MemPoolTxFilter::default(),
+        None,
+        Some(4),
+    );
+    requests.push(request);
+
+    // the rest of everything
+    let request = StacksHttpRequest::new_getmempooltxs(
+        addr.into(),
+        &MemPoolTxFilter::default(),
+        Some(&mempool_txids[3]),
+        None,
+    );
+    requests.push(request);
+
+    // all of the origin's token transfers
+    let request = StacksHttpRequest::new_getmempooltxs(
+        addr.into(),
+        &MemPoolTxFilter {
+            origin: Some(origin.clone()),
+            payload_type: Some(MemPoolPayloadType::TokenTransfer),
+            min_fee: Some(1000),
+            max_fee: Some(1000),
+            ..MemPoolTxFilter::default()
+        },
+        None,
+        None,
+    );
+    requests.push(request);
+
+    // no contract calls
+    let request = StacksHttpRequest::new_getmempooltxs(
+        addr.into(),
+        &MemPoolTxFilter {
+            payload_type: Some(MemPoolPayloadType::ContractCall),
+            ..MemPoolTxFilter::default()
+        },
+        None,
+        None,
+    );
+    requests.push(request);
+
+    // no txs pay this much
+    let request = StacksHttpRequest::new_getmempooltxs(
+        addr.into(),
+        &MemPoolTxFilter {
+            min_fee: Some(1001),
+            ..MemPoolTxFilter::default()
+        },
+        None,
+        None,
+    );
+    requests.push(request);
+
+    let mut responses = rpc_test.run(requests);
+
+    let response = responses.remove(0);
+    debug!(
+        "Response:\n{}\n",
+        std::str::from_utf8(&response.try_serialize().unwrap()).unwrap()
+    );
+    let resp = response.decode_getmempooltxs().unwrap();
+    let txids: Vec<_> = resp.txs.iter().map(|tx| tx.txid.clone()).collect();
+    assert_eq!(txids, mempool_txids[0..4]);
+    assert_eq!(resp.next_page_id, Some(mempool_txids[3].clone()));
+
+    let response = responses.remove(0);
+    let resp = response.decode_getmempooltxs().unwrap();
+    let txids: Vec<_> = resp.txs.iter().map(|tx| tx.txid.clone()).collect();
+    assert_eq!(txids, mempool_txids[4..]);
+    assert_eq!(resp.next_page_id, None);
+
+    let response = responses.remove(0);
+    let resp = response.decode_getmempooltxs().unwrap();
+    assert_eq!(resp.txs.len(), 10);
+    for tx in resp.txs.iter() {
+        assert_eq!(tx.origin_address, origin.to_string());
+        assert_eq!(tx.tx_fee, 1000);
+    }
+    assert_eq!(resp.next_page_id, None);
+
+    let response = responses.remove(0);
+    let resp = response.decode_getmempooltxs().unwrap();
+    assert!(resp.txs.is_empty());
+    assert_eq!(resp.next_page_id, None);
+
+    let response = responses.remove(0);
+    let resp = response.decode_getmempooltxs().unwrap();
+    assert!(resp.txs.is_empty());
+    assert_eq!(resp.next_page_id, None);
+}
@@ -15,6 +15,7 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
 
 use clarity::vm::types::{PrincipalData, QualifiedContractIdentifier, StacksAddressExtensions};
 use clarity::vm::{ClarityName, ContractName, Value};
@@ -291,4 +292,21 @@ fn test_stream_indexed_microblocks() {
             assert_eq!(confirmed_mblocks[j], mblocks[j])
         }
     }
+
+    // archived microblocks can't be streamed
+    let archive_path = PathBuf::from(format!("{}/microblocks.sqlite", &chainstate.root_path));
+    let archived = chainstate
+        .archive_microblocks(&archive_path, 1_000_000, 1_000)
+        .unwrap();
+    assert_eq!(archived, mblocks.len() as u64);
+
+    let tail_microblock_hash = mblocks.last().unwrap().block_hash();
+    let microblock_index_header =
+        StacksBlockHeader::make_index_block_hash(&consensus_hash, &tail_microblock_hash);
+    match StacksIndexedMicroblockStream::new(&chainstate, &microblock_index_header) {
+        Err(chainstate_error::MicroblockArchived(microblock_hash)) => {
+            assert_eq!(microblock_hash, tail_microblock_hash)
+        }
+        res => panic!("Expected the microblocks to be archived, got {res:?}"),
+    }
 }
@@ -0,0 +1,118 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use super::TestRPC;
+use crate::chainstate::burn::db::mining_stats::MAX_MINING_STATS_WINDOW;
+use crate::net::api::*;
+use crate::net::connection::ConnectionOptions;
+use crate::net::httpcore::{RPCRequestHandler, StacksHttp, StacksHttpRequest};
+use crate::net::ProtocolFamily;
+
+#[test]
+fn test_try_parse_request() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let mut http = StacksHttp::new(addr.clone(), &ConnectionOptions::default());
+
+    let request = StacksHttpRequest::new_get_mining_stats(addr.into(), Some(10), Some(20_000));
+    let bytes = request.try_serialize().unwrap();
+
+    debug!("Request:\n{}\n", std::str::from_utf8(&bytes).unwrap());
+
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut handler = getminingstats::RPCGetMiningStatsRequestHandler::new();
+    let mut parsed_request = http
+        .handle_try_parse_request(
+            &mut handler,
+            &parsed_preamble.expect_request(),
+            &bytes[offset..],
+        )
+        .unwrap();
+
+    assert_eq!(handler.window, Some(10));
+    assert_eq!(handler.burn_fee, Some(20_000));
+
+    // parsed request consumes headers that would not be in a constructed reqeuest
+    parsed_request.clear_headers();
+    let (preamble, contents) = parsed_request.destruct();
+
+    assert_eq!(&preamble, request.preamble());
+
+    handler.restart();
+    assert!(handler.window.is_none());
+    assert!(handler.burn_fee.is_none());
+
+    // the window is bounded
+    let request = StacksHttpRequest::new_get_mining_stats(
+        addr.into(),
+        Some(MAX_MINING_STATS_WINDOW + 1),
+        None,
+    );
+    let bytes = request.try_serialize().unwrap();
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut handler = getminingstats::RPCGetMiningStatsRequestHandler::new();
+    assert!(http
+        .handle_try_parse_request(
+            &mut handler,
+            &parsed_preamble.expect_request(),
+            &bytes[offset..],
+        )
+        .is_err());
+}
+
+#[test]
+fn test_try_make_response() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+
+    let rpc_test = TestRPC::setup(function_name!());
+
+    let mut requests = vec![];
+
+    // default window, with a win probability
+    let request = StacksHttpRequest::new_get_mining_stats(addr.into(), None, Some(1_000));
+    requests.push(request);
+
+    // just the tip
+    let request = StacksHttpRequest::new_get_mining_stats(addr.into(), Some(1), None);
+    requests.push(request);
+
+    let mut responses = rpc_test.run(requests);
+
+    let response = responses.remove(0);
+    debug!(
+        "Response:\n{}\n",
+        std::str::from_utf8(&response.try_serialize().unwrap()).unwrap()
+    );
+    let stats = response.decode_mining_stats().unwrap();
+    assert!(stats.num_sortitions > 1);
+    assert!(stats.num_winning_sortitions > 0);
+    assert!(!stats.miners.is_empty());
+    let total_wins: u64 = stats.miners.iter().map(|miner| miner.wins).sum();
+    assert_eq!(total_wins, stats.num_winning_sortitions);
+    assert_eq!(
+        stats.commit_burns.count,
+        stats.miners.iter().map(|miner| miner.commits).sum::<u64>()
+    );
+    let win_probability = stats.expected_win_probability.unwrap();
+    assert_eq!(win_probability.burn_fee, 1_000);
+    assert!(win_probability.probability > 0.0 && win_probability.probability <= 1.0);
+
+    let response = responses.remove(0);
+    let stats = response.decode_mining_stats().unwrap();
+    assert_eq!(stats.num_sortitions, 1);
+    assert_eq!(stats.start_burn_block_height, stats.end_burn_block_height);
+    assert!(stats.expected_win_probability.is_none());
+}
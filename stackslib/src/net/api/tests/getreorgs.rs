@@ -0,0 +1,128 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use stacks_common::types::chainstate::StacksBlockId;
+
+use super::TestRPC;
+use crate::burnchains::Txid;
+use crate::chainstate::stacks::db::reorgs::StacksReorg;
+use crate::net::api::*;
+use crate::net::connection::ConnectionOptions;
+use crate::net::http::HttpRequestContents;
+use crate::net::httpcore::{RPCRequestHandler, StacksHttp, StacksHttpRequest};
+use crate::net::ProtocolFamily;
+
+#[test]
+fn test_try_parse_request() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let mut http = StacksHttp::new(addr.clone(), &ConnectionOptions::default());
+
+    let request = StacksHttpRequest::new_getreorgs(addr.into(), Some(10));
+    let bytes = request.try_serialize().unwrap();
+
+    debug!("Request:\n{}\n", std::str::from_utf8(&bytes).unwrap());
+
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut handler = getreorgs::RPCGetReorgsRequestHandler::new();
+    let mut parsed_request = http
+        .handle_try_parse_request(
+            &mut handler,
+            &parsed_preamble.expect_request(),
+            &bytes[offset..],
+        )
+        .unwrap();
+
+    assert_eq!(handler.limit, Some(10));
+
+    // parsed request consumes headers that would not be in a constructed reqeuest
+    parsed_request.clear_headers();
+    let (preamble, contents) = parsed_request.destruct();
+
+    assert_eq!(&preamble, request.preamble());
+
+    handler.restart();
+    assert!(handler.limit.is_none());
+
+    // malformed and out-of-range limits are rejected
+    for value in ["nonsense", "0", "257"] {
+        let request = StacksHttpRequest::new_for_peer(
+            addr.into(),
+            "GET".into(),
+            "/v3/reorgs".into(),
+            HttpRequestContents::new().query_arg("limit".into(), value.into()),
+        )
+        .unwrap();
+        let bytes = request.try_serialize().unwrap();
+        let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+        let mut handler = getreorgs::RPCGetReorgsRequestHandler::new();
+        assert!(http
+            .handle_try_parse_request(
+                &mut handler,
+                &parsed_preamble.expect_request(),
+                &bytes[offset..],
+            )
+            .is_err());
+    }
+}
+
+#[test]
+fn test_try_make_response() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+
+    let mut rpc_test = TestRPC::setup(function_name!());
+    let reorgs: Vec<_> = (0..3u8)
+        .map(|i| StacksReorg {
+            old_tip: StacksBlockId([i; 32]),
+            old_tip_height: 10,
+            new_tip: StacksBlockId([i + 0x10; 32]),
+            new_tip_height: 11,
+            fork_point: StacksBlockId([i + 0x20; 32]),
+            fork_point_height: 9,
+            orphaned_blocks: vec![StacksBlockId([i; 32])],
+            orphaned_txids: vec![Txid([i; 32])],
+            detected_at: 1000 + u64::from(i),
+        })
+        .collect();
+    for reorg in reorgs.iter() {
+        rpc_test.peer_1.chainstate().record_reorg(reorg).unwrap();
+    }
+
+    let mut requests = vec![];
+
+    // all of the reorgs
+    let request = StacksHttpRequest::new_getreorgs(addr.into(), None);
+    requests.push(request);
+
+    // only the most recent one
+    let request = StacksHttpRequest::new_getreorgs(addr.into(), Some(1));
+    requests.push(request);
+
+    let mut responses = rpc_test.run(requests);
+
+    let response = responses.remove(0);
+    debug!(
+        "Response:\n{}\n",
+        std::str::from_utf8(&response.try_serialize().unwrap()).unwrap()
+    );
+    let resp = response.decode_getreorgs().unwrap();
+    let expected: Vec<_> = reorgs.iter().rev().cloned().collect();
+    assert_eq!(resp.reorgs, expected);
+
+    let response = responses.remove(0);
+    let resp = response.decode_getreorgs().unwrap();
+    assert_eq!(resp.reorgs, vec![reorgs[2].clone()]);
+}
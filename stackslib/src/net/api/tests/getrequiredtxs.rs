@@ -0,0 +1,106 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use stacks_common::types::chainstate::StacksBlockId;
+use stacks_common::util::hash::Sha512Trunc256Sum;
+
+use super::TestRPC;
+use crate::core::required_txs::RequiredTxStatus;
+use crate::net::api::*;
+use crate::net::connection::ConnectionOptions;
+use crate::net::httpcore::{RPCRequestHandler, StacksHttp, StacksHttpRequest};
+use crate::net::ProtocolFamily;
+
+#[test]
+fn test_try_parse_request() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let mut http = StacksHttp::new(addr.clone(), &ConnectionOptions::default());
+
+    let set_id = Sha512Trunc256Sum([0x11; 32]);
+    let request = StacksHttpRequest::new_get_required_txs(addr.into(), &set_id);
+    let bytes = request.try_serialize().unwrap();
+
+    debug!("Request:\n{}\n", std::str::from_utf8(&bytes).unwrap());
+
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut handler = getrequiredtxs::RPCGetRequiredTxsRequestHandler::new();
+    let mut parsed_request = http
+        .handle_try_parse_request(
+            &mut handler,
+            &parsed_preamble.expect_request(),
+            &bytes[offset..],
+        )
+        .unwrap();
+
+    assert_eq!(handler.set_id, Some(set_id));
+
+    // parsed request consumes headers that would not be in a constructed reqeuest
+    parsed_request.clear_headers();
+    let (preamble, contents) = parsed_request.destruct();
+
+    assert_eq!(&preamble, request.preamble());
+
+    handler.restart();
+    assert!(handler.set_id.is_none());
+}
+
+#[test]
+fn test_try_make_response() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+
+    let mut rpc_test = TestRPC::setup(function_name!());
+    let txs = rpc_test.sendable_txs[0..2].to_vec();
+    let block_id = StacksBlockId([0x01; 32]);
+    let set_id = {
+        let mempool = rpc_test.peer_1.mempool.as_mut().unwrap();
+        let set_id = mempool.submit_required_tx_set(&txs).unwrap();
+        mempool
+            .mark_required_txs_included(&block_id, &[txs[0].txid()])
+            .unwrap();
+        set_id
+    };
+
+    let mut requests = vec![];
+
+    // a known set
+    let request = StacksHttpRequest::new_get_required_txs(addr.into(), &set_id);
+    requests.push(request);
+
+    // an unknown set
+    let request =
+        StacksHttpRequest::new_get_required_txs(addr.into(), &Sha512Trunc256Sum([0x11; 32]));
+    requests.push(request);
+
+    let mut responses = rpc_test.run(requests);
+
+    let response = responses.remove(0);
+    debug!(
+        "Response:\n{}\n",
+        std::str::from_utf8(&response.try_serialize().unwrap()).unwrap()
+    );
+    let set = response.decode_required_tx_set().unwrap();
+    assert_eq!(set.set_id, set_id);
+    assert_eq!(set.txs[0].txid, txs[0].txid());
+    assert_eq!(set.txs[0].status, RequiredTxStatus::Included { block_id });
+    assert_eq!(set.txs[1].txid, txs[1].txid());
+    assert_eq!(set.txs[1].status, RequiredTxStatus::Pending);
+    assert!(!set.is_settled());
+
+    let response = responses.remove(0);
+    let (preamble, body) = response.destruct();
+    assert_eq!(preamble.status_code, 404);
+}
@@ -44,6 +44,7 @@ use crate::core::MemPoolDB;
 use crate::net::api::{prefix_hex, prefix_opt_hex};
 use crate::net::db::PeerDB;
 use crate::net::httpcore::{StacksHttpRequest, StacksHttpResponse};
+use crate::net::ratelimit::RPCRateLimiter;
 use crate::net::relay::Relayer;
 use crate::net::rpc::ConversationHttp;
 use crate::net::test::{TestEventObserver, TestPeer, TestPeerConfig};
@@ -61,6 +62,7 @@ mod getattachmentsinv;
 mod getblock;
 mod getblock_v3;
 mod getblockbyheight;
+mod getblockcosts;
 mod getconstantval;
 mod getcontractabi;
 mod getcontractsrc;
@@ -69,11 +71,15 @@ mod getheaders;
 mod getinfo;
 mod getistraitimplemented;
 mod getmapentry;
+mod getmempooltxs;
 mod getmicroblocks_confirmed;
 mod getmicroblocks_indexed;
 mod getmicroblocks_unconfirmed;
+mod getminingstats;
 mod getneighbors;
 mod getpoxinfo;
+mod getreorgs;
+mod getrequiredtxs;
 mod getsigner;
 mod getsortition;
 mod getstackerdbchunk;
@@ -87,9 +93,11 @@ mod liststackerdbreplicas;
 mod postblock;
 mod postblock_proposal;
 mod postblock_v3;
+mod postdryrundeploy;
 mod postfeerate;
 mod postmempoolquery;
 mod postmicroblock;
+mod postrequiredtxs;
 mod poststackerdbchunk;
 mod posttransaction;
 
@@ -273,6 +281,8 @@ impl<'a> TestRPC<'a> {
         };
         peer_1_config.connection_opts.maximum_call_argument_size = 4096;
         peer_1_config.connection_opts.auth_token = Some("password".to_string());
+        peer_1_config.connection_opts.rpc_response_cache_size = 16;
+        peer_1_config.connection_opts.read_only_call_trace = true;
 
         peer_2_config.connection_opts.read_only_call_limit = ExecutionCost {
             write_length: 0,
@@ -283,6 +293,8 @@ impl<'a> TestRPC<'a> {
         };
         peer_2_config.connection_opts.maximum_call_argument_size = 4096;
         peer_2_config.connection_opts.auth_token = Some("password".to_string());
+        peer_2_config.connection_opts.rpc_response_cache_size = 16;
+        peer_2_config.connection_opts.read_only_call_trace = true;
 
         // stacker DBs get initialized thru reconfiguration when the above block gets processed
         peer_1_config.add_stacker_db(
@@ -995,7 +1007,9 @@ impl<'a> TestRPC<'a> {
                     &rpc_args,
                     false,
                 );
-                convo_1.chat(&mut node_state).unwrap();
+                convo_1
+                    .chat(&mut node_state, &mut RPCRateLimiter::default())
+                    .unwrap();
             }
 
             peer_1.sortdb = Some(peer_1_sortdb);
@@ -1039,7 +1053,9 @@ impl<'a> TestRPC<'a> {
                     &rpc_args,
                     false,
                 );
-                convo_2.chat(&mut node_state).unwrap();
+                convo_2
+                    .chat(&mut node_state, &mut RPCRateLimiter::default())
+                    .unwrap();
             }
 
             peer_2.sortdb = Some(peer_2_sortdb);
@@ -1086,7 +1102,9 @@ impl<'a> TestRPC<'a> {
                     &rpc_args,
                     false,
                 );
-                convo_1.chat(&mut node_state).unwrap();
+                convo_1
+                    .chat(&mut node_state, &mut RPCRateLimiter::default())
+                    .unwrap();
             }
 
             convo_1.try_flush().unwrap();
@@ -37,6 +37,7 @@ use super::TestRPC;
 use crate::chainstate::burn::db::sortdb::SortitionDB;
 use crate::chainstate::burn::BlockSnapshot;
 use crate::chainstate::nakamoto::miner::NakamotoBlockBuilder;
+use crate::chainstate::nakamoto::prevalidate::BlockViolation;
 use crate::chainstate::nakamoto::{NakamotoBlock, NakamotoBlockHeader, NakamotoChainState};
 use crate::chainstate::stacks::db::StacksChainState;
 use crate::chainstate::stacks::miner::{BlockBuilder, BlockLimitFunction};
@@ -317,6 +318,7 @@ fn test_try_make_response() {
     // Increment the timestamp by 1 to ensure it is different from the previous block
     block.header.timestamp += 1;
     rpc_test.peer_1.miner.sign_nakamoto_block(&mut block);
+    let state_index_root = block.header.state_index_root.clone();
 
     // post the valid block proposal
     let proposal = NakamotoBlockProposal {
@@ -374,6 +376,27 @@ fn test_try_make_response() {
     request.add_header("authorization".into(), "password".into());
     requests.push(request);
 
+    // Restore the timestamp, but commit to the wrong state root
+    block.header.timestamp -= 10000;
+    block.header.state_index_root = TrieHash([0x11; 32]);
+    rpc_test.peer_1.miner.sign_nakamoto_block(&mut block);
+
+    // post the invalid block proposal
+    let proposal = NakamotoBlockProposal {
+        block: block.clone(),
+        chain_id: 0x80000000,
+    };
+
+    let mut request = StacksHttpRequest::new_for_peer(
+        rpc_test.peer_1.to_peer_host(),
+        "POST".into(),
+        "/v3/block_proposal".into(),
+        HttpRequestContents::new().payload_json(serde_json::to_value(proposal).unwrap()),
+    )
+    .expect("failed to construct request");
+    request.add_header("authorization".into(), "password".into());
+    requests.push(request);
+
     // execute the requests
     let observer = ProposalTestObserver::new();
     let proposal_observer = Arc::clone(&observer.proposal_observer);
@@ -393,7 +416,7 @@ fn test_try_make_response() {
             .lock()
             .unwrap()
             .len()
-            < 3
+            < 4
         {
             std::thread::sleep(std::time::Duration::from_secs(1));
         } else {
@@ -405,7 +428,7 @@ fn test_try_make_response() {
     let mut results = observer.results.lock().unwrap();
 
     let result = results.remove(0);
-    assert!(result.is_ok());
+    assert_eq!(result.unwrap().state_index_root, state_index_root);
 
     let result = results.remove(0);
     match result {
@@ -432,4 +455,24 @@ fn test_try_make_response() {
             assert_eq!(reason, "Block timestamp is too far into the future");
         }
     }
+
+    let result = results.remove(0);
+    match result {
+        Ok(_) => panic!("expected error"),
+        Err(postblock_proposal::BlockValidateReject {
+            reason_code,
+            violations,
+            ..
+        }) => {
+            assert_eq!(reason_code, ValidateRejectCode::BadBlockHash);
+            assert_eq!(
+                violations,
+                vec![BlockViolation::Header {
+                    field: "state_index_root".into(),
+                    expected: TrieHash([0x11; 32]).to_string(),
+                    computed: state_index_root.to_string(),
+                }]
+            );
+        }
+    }
 }
@@ -0,0 +1,212 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use clarity::vm::diagnostic::Level;
+use clarity::vm::types::QualifiedContractIdentifier;
+use clarity::vm::ClarityVersion;
+use stacks_common::types::chainstate::{StacksAddress, StacksBlockId};
+use stacks_common::types::Address;
+
+use super::test_rpc;
+use crate::net::api::*;
+use crate::net::connection::ConnectionOptions;
+use crate::net::httpcore::{
+    HttpPreambleExtensions, HttpRequestContentsExtensions, RPCRequestHandler, StacksHttp,
+    StacksHttpRequest,
+};
+use crate::net::{ProtocolFamily, TipRequest};
+
+#[test]
+fn test_try_parse_request() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let mut http = StacksHttp::new(addr.clone(), &ConnectionOptions::default());
+
+    let request = StacksHttpRequest::new_dry_run_deploy(
+        addr.into(),
+        StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R").unwrap(),
+        "dry-run".try_into().unwrap(),
+        "(define-read-only (get-one) u1)".into(),
+        Some(ClarityVersion::Clarity2),
+        TipRequest::SpecificTip(StacksBlockId([0x22; 32])),
+    );
+    assert_eq!(
+        request.contents().tip_request(),
+        TipRequest::SpecificTip(StacksBlockId([0x22; 32]))
+    );
+
+    let bytes = request.try_serialize().unwrap();
+
+    debug!("Request:\n{}\n", std::str::from_utf8(&bytes).unwrap());
+
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut handler = postdryrundeploy::RPCDryRunDeployRequestHandler::new();
+    let mut parsed_request = http
+        .handle_try_parse_request(
+            &mut handler,
+            &parsed_preamble.expect_request(),
+            &bytes[offset..],
+        )
+        .unwrap();
+
+    assert_eq!(
+        handler.contract_identifier,
+        Some(
+            QualifiedContractIdentifier::parse("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R.dry-run")
+                .unwrap()
+        )
+    );
+    assert_eq!(
+        handler.source_code.as_deref(),
+        Some("(define-read-only (get-one) u1)")
+    );
+    assert_eq!(handler.clarity_version, Some(ClarityVersion::Clarity2));
+
+    // parsed request consumes headers that would not be in a constructed reqeuest
+    parsed_request.clear_headers();
+    let (preamble, contents) = parsed_request.destruct();
+
+    assert_eq!(&preamble, request.preamble());
+
+    handler.restart();
+    assert!(handler.contract_identifier.is_none());
+    assert!(handler.source_code.is_none());
+    assert!(handler.clarity_version.is_none());
+}
+
+#[test]
+fn test_try_make_response() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let deployer = StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R").unwrap();
+
+    let mut requests = vec![];
+
+    // a contract that deploys, and calls a deployed contract
+    let request = StacksHttpRequest::new_dry_run_deploy(
+        addr.into(),
+        deployer.clone(),
+        "dry-run".try_into().unwrap(),
+        "(define-constant unused u1)
+         (define-data-var counter uint u0)
+         (define-public (get-bar) (contract-call? .hello-world get-bar))
+         (define-read-only (get-counter) (var-get counter))"
+            .into(),
+        None,
+        TipRequest::UseLatestAnchoredTip,
+    );
+    requests.push(request);
+
+    // a contract that fails analysis
+    let request = StacksHttpRequest::new_dry_run_deploy(
+        addr.into(),
+        deployer.clone(),
+        "dry-run".try_into().unwrap(),
+        "(define-read-only (get-one) u1)\n(define-read-only (get-two) (+ u1 1))".into(),
+        None,
+        TipRequest::UseLatestAnchoredTip,
+    );
+    requests.push(request);
+
+    // a contract that fails to initialize
+    let request = StacksHttpRequest::new_dry_run_deploy(
+        addr.into(),
+        deployer.clone(),
+        "dry-run".try_into().unwrap(),
+        "(define-constant one (unwrap-panic (if true none (some u1))))".into(),
+        None,
+        TipRequest::UseLatestAnchoredTip,
+    );
+    requests.push(request);
+
+    // a contract that already exists
+    let request = StacksHttpRequest::new_dry_run_deploy(
+        addr.into(),
+        deployer.clone(),
+        "hello-world".try_into().unwrap(),
+        "(define-read-only (get-one) u1)".into(),
+        None,
+        TipRequest::UseLatestAnchoredTip,
+    );
+    requests.push(request);
+
+    // non-existent tip
+    let request = StacksHttpRequest::new_dry_run_deploy(
+        addr.into(),
+        deployer.clone(),
+        "dry-run".try_into().unwrap(),
+        "(define-read-only (get-one) u1)".into(),
+        None,
+        TipRequest::SpecificTip(StacksBlockId([0x11; 32])),
+    );
+    requests.push(request);
+
+    let mut responses = test_rpc(function_name!(), requests);
+
+    // deploys
+    let response = responses.remove(0);
+    debug!(
+        "Response:\n{}\n",
+        std::str::from_utf8(&response.try_serialize().unwrap()).unwrap()
+    );
+    assert_eq!(
+        response.preamble().get_canonical_stacks_tip_height(),
+        Some(1)
+    );
+
+    let resp = response.decode_dry_run_deploy_response().unwrap();
+    assert!(resp.okay);
+    assert!(resp.cost.runtime > 0);
+    assert!(resp.cost.write_count > 0);
+    let interface = resp.interface.unwrap();
+    assert_eq!(interface.functions.len(), 2);
+    assert_eq!(interface.variables.len(), 2);
+    // only the unused constant is linted
+    assert_eq!(resp.diagnostics.len(), 1);
+    assert_eq!(resp.diagnostics[0].level, Level::Warning);
+    assert!(resp.diagnostics[0].message.contains("unused-constant"));
+
+    // fails analysis, with the location of the error
+    let response = responses.remove(0);
+    let resp = response.decode_dry_run_deploy_response().unwrap();
+    assert!(!resp.okay);
+    assert!(resp.interface.is_none());
+    assert!(resp.cost.runtime > 0);
+    assert_eq!(resp.diagnostics.len(), 1);
+    assert_eq!(resp.diagnostics[0].level, Level::Error);
+    assert_eq!(resp.diagnostics[0].spans[0].start_line, 2);
+
+    // fails to initialize
+    let response = responses.remove(0);
+    let resp = response.decode_dry_run_deploy_response().unwrap();
+    assert!(!resp.okay);
+    assert!(resp.interface.is_some());
+    assert_eq!(resp.diagnostics[0].level, Level::Error);
+    assert!(resp.diagnostics[0].message.contains("UnwrapFailure"));
+    assert_eq!(resp.diagnostics[0].spans[0].start_line, 1);
+
+    // already exists
+    let response = responses.remove(0);
+    let resp = response.decode_dry_run_deploy_response().unwrap();
+    assert!(!resp.okay);
+    assert!(resp.diagnostics[0]
+        .message
+        .contains("conflicts with existing contract"));
+
+    // non-existent tip
+    let response = responses.remove(0);
+    let (preamble, _payload) = response.destruct();
+    assert_eq!(preamble.status_code, 404);
+}
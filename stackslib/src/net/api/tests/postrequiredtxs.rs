@@ -0,0 +1,149 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use stacks_common::types::StacksEpochId;
+
+use super::TestRPC;
+use crate::chainstate::stacks::test::codec_all_transactions;
+use crate::chainstate::stacks::{
+    TransactionAnchorMode, TransactionPostConditionMode, TransactionVersion,
+};
+use crate::core::required_txs::{RequiredTxSet, RequiredTxStatus};
+use crate::net::api::*;
+use crate::net::connection::ConnectionOptions;
+use crate::net::httpcore::{RPCRequestHandler, StacksHttp, StacksHttpRequest};
+use crate::net::ProtocolFamily;
+
+#[test]
+fn test_try_parse_request() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let mut http = StacksHttp::new(addr.clone(), &ConnectionOptions::default());
+
+    let txs = codec_all_transactions(
+        &TransactionVersion::Testnet,
+        0x80000000,
+        &TransactionAnchorMode::Any,
+        &TransactionPostConditionMode::Allow,
+        StacksEpochId::latest(),
+    );
+    let txs = txs[0..3].to_vec();
+
+    let mut request = StacksHttpRequest::new_post_required_txs(addr.into(), &txs);
+    let bytes = request.try_serialize().unwrap();
+
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut handler =
+        postrequiredtxs::RPCPostRequiredTxsRequestHandler::new(Some("password".into()));
+
+    // missing authorization header
+    let bad_request = http.handle_try_parse_request(
+        &mut handler,
+        &parsed_preamble.expect_request(),
+        &bytes[offset..],
+    );
+    match bad_request {
+        Err(crate::net::Error::Http(crate::net::http::Error::Http(err_code, message))) => {
+            assert_eq!(err_code, 401);
+            assert_eq!(message, "Unauthorized");
+        }
+        _ => panic!("expected error"),
+    }
+
+    // add the authorization header
+    request.add_header("authorization".into(), "password".into());
+    let bytes = request.try_serialize().unwrap();
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut parsed_request = http
+        .handle_try_parse_request(
+            &mut handler,
+            &parsed_preamble.expect_request(),
+            &bytes[offset..],
+        )
+        .unwrap();
+
+    assert_eq!(handler.txs, Some(txs.clone()));
+
+    // parsed request consumes headers that would not be in a constructed request
+    parsed_request.clear_headers();
+    // but the authorization header should still be there
+    parsed_request.add_header("authorization".into(), "password".into());
+    let (preamble, contents) = parsed_request.destruct();
+
+    assert_eq!(&preamble, request.preamble());
+
+    handler.restart();
+    assert!(handler.auth.is_some());
+    assert!(handler.txs.is_none());
+
+    // empty sets and sets with duplicate transactions are rejected
+    for bad_txs in [vec![], vec![txs[0].clone(), txs[0].clone()]] {
+        let mut request = StacksHttpRequest::new_post_required_txs(addr.into(), &bad_txs);
+        request.add_header("authorization".into(), "password".into());
+        let bytes = request.try_serialize().unwrap();
+        let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+        let mut handler =
+            postrequiredtxs::RPCPostRequiredTxsRequestHandler::new(Some("password".into()));
+        assert!(http
+            .handle_try_parse_request(
+                &mut handler,
+                &parsed_preamble.expect_request(),
+                &bytes[offset..],
+            )
+            .is_err());
+    }
+}
+
+#[test]
+fn test_try_make_response() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+
+    let rpc_test = TestRPC::setup(function_name!());
+    let txs = rpc_test.sendable_txs[0..2].to_vec();
+
+    let mut requests = vec![];
+
+    // a good set
+    let mut request = StacksHttpRequest::new_post_required_txs(addr.into(), &txs);
+    request.add_header("authorization".into(), "password".into());
+    requests.push(request);
+
+    // a set with a transaction for another chain
+    let mut bad_tx = txs[0].clone();
+    bad_tx.chain_id = 0x00000001;
+    let mut request = StacksHttpRequest::new_post_required_txs(addr.into(), &[bad_tx]);
+    request.add_header("authorization".into(), "password".into());
+    requests.push(request);
+
+    let mut responses = rpc_test.run(requests);
+
+    let response = responses.remove(0);
+    debug!(
+        "Response:\n{}\n",
+        std::str::from_utf8(&response.try_serialize().unwrap()).unwrap()
+    );
+    let set = response.decode_required_tx_set().unwrap();
+    assert_eq!(set.set_id, RequiredTxSet::make_set_id(&txs));
+    assert_eq!(set.txs.len(), 2);
+    for (required_tx, tx) in set.txs.iter().zip(txs.iter()) {
+        assert_eq!(required_tx.txid, tx.txid());
+        assert_eq!(required_tx.status, RequiredTxStatus::Pending);
+    }
+
+    let response = responses.remove(0);
+    let (preamble, body) = response.destruct();
+    assert_eq!(preamble.status_code, 400);
+}
@@ -23,7 +23,7 @@ use std::sync::mpsc::{
 use std::time::Duration;
 use std::{io, net};
 
-use clarity::vm::costs::ExecutionCost;
+use clarity::vm::costs::{ExecutionCost, CLARITY_MEMORY_LIMIT};
 use clarity::vm::types::BOUND_VALUE_SERIALIZATION_HEX;
 use stacks_common::codec::{StacksMessageCodec, MAX_MESSAGE_LEN};
 use stacks_common::types::net::PeerAddress;
@@ -43,6 +43,7 @@ use crate::net::neighbors::{
     WALK_MAX_DURATION, WALK_MIN_DURATION, WALK_RESET_INTERVAL, WALK_RESET_PROB, WALK_RETRY_COUNT,
     WALK_SEED_PROBABILITY, WALK_STATE_TIMEOUT,
 };
+use crate::net::ratelimit::RPCRateLimits;
 use crate::net::{
     Error as net_error, MessageSequence, Preamble, ProtocolFamily, RelayData, StacksHttp, StacksP2P,
 };
@@ -383,6 +384,7 @@ pub struct ConnectionOptions {
     pub max_inflight_attachments: u64,
     pub max_attachment_retry_count: u64,
     pub read_only_call_limit: ExecutionCost,
+    pub read_only_call_memory_limit: u64,
     pub maximum_call_argument_size: u32,
     pub max_block_push_bandwidth: u64,
     pub max_microblocks_push_bandwidth: u64,
@@ -433,6 +435,12 @@ pub struct ConnectionOptions {
     pub nakamoto_unconfirmed_downloader_interval_ms: u128,
     /// The authorization token to enable privileged RPC endpoints
     pub auth_token: Option<String>,
+    /// Per-client rate limits for each class of RPC endpoint
+    pub rpc_rate_limits: RPCRateLimits,
+    /// How many expensive read-only RPC responses to cache (0 disables the cache)
+    pub rpc_response_cache_size: usize,
+    /// Allow read-only calls to return an execution trace (with `?trace=1`)
+    pub read_only_call_trace: bool,
 
     // fault injection
     /// Disable neighbor walk and discovery
@@ -533,6 +541,7 @@ impl std::default::Default for ConnectionOptions {
                 read_count: 30,
                 runtime: 1_000_000_000,
             },
+            read_only_call_memory_limit: CLARITY_MEMORY_LIMIT,
             maximum_call_argument_size: 20 * BOUND_VALUE_SERIALIZATION_HEX,
             max_block_push_bandwidth: 0, // infinite upload bandwidth allowed
             max_microblocks_push_bandwidth: 0, // infinite upload bandwidth allowed
@@ -565,6 +574,9 @@ impl std::default::Default for ConnectionOptions {
             nakamoto_inv_sync_burst_interval_ms: 1_000, // wait 1 second after a sortition before running inventory sync
             nakamoto_unconfirmed_downloader_interval_ms: 5_000, // run unconfirmed downloader once every 5 seconds
             auth_token: None,
+            rpc_rate_limits: RPCRateLimits::default(),
+            rpc_response_cache_size: 0,
+            read_only_call_trace: false,
 
             // no faults on by default
             disable_neighbor_walk: false,
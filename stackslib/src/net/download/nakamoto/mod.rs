@@ -171,6 +171,7 @@ pub use crate::net::download::nakamoto::tenure_downloader::{
     NakamotoTenureDownloadState, NakamotoTenureDownloader,
 };
 pub use crate::net::download::nakamoto::tenure_downloader_set::NakamotoTenureDownloaderSet;
+pub(crate) use crate::net::download::nakamoto::tenure_downloader_set::PeerDownloadScore;
 pub use crate::net::download::nakamoto::tenure_downloader_unconfirmed::{
     NakamotoUnconfirmedDownloadState, NakamotoUnconfirmedTenureDownloader,
 };
@@ -95,6 +95,66 @@ impl From<&mut NakamotoTenureDownloader> for CompletedTenure {
 }
 
 pub const PEER_DEPRIORITIZATION_TIME_SECS: u64 = 60;
+/// Longest time a peer can be deprioritized for, after repeated failures
+pub const PEER_MAX_DEPRIORITIZATION_TIME_SECS: u64 = 3600;
+/// Latency assumed of a peer we have not downloaded from yet.  This is deliberately modest, so
+/// that new peers get tried before slow ones.
+pub const PEER_UNKNOWN_LATENCY_MS: f64 = 1000.0;
+/// Time assumed lost to a failed request (about as long as a request takes to time out)
+pub const PEER_FAILURE_COST_MS: f64 = 5000.0;
+/// Weight of the latest response in a peer's moving-average latency
+const PEER_LATENCY_EWMA_ALPHA: f64 = 0.3;
+
+/// How well a peer has served tenure downloads, used to schedule requests on the fastest and
+/// most reliable peers first.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct PeerDownloadScore {
+    /// Exponentially-weighted moving average of the peer's response time, in milliseconds
+    pub(crate) latency_ms: Option<f64>,
+    /// Number of requests the peer answered
+    pub(crate) successes: u64,
+    /// Number of requests that failed on this peer
+    pub(crate) failures: u64,
+    /// Number of failures since the peer last answered a request
+    pub(crate) consecutive_failures: u32,
+}
+
+impl PeerDownloadScore {
+    pub(crate) fn record_success(&mut self, latency: Duration) {
+        let latency_ms = latency.as_secs_f64() * 1000.0;
+        self.latency_ms = Some(match self.latency_ms {
+            Some(avg) => avg + PEER_LATENCY_EWMA_ALPHA * (latency_ms - avg),
+            None => latency_ms,
+        });
+        self.successes = self.successes.saturating_add(1);
+        self.consecutive_failures = 0;
+    }
+
+    pub(crate) fn record_failure(&mut self) {
+        self.failures = self.failures.saturating_add(1);
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+    }
+
+    /// Expected time (in milliseconds) to get an answer from this peer: its latency, plus the
+    /// time lost to the failed attempts its failure rate implies.  Lower is better.
+    pub(crate) fn cost(&self) -> f64 {
+        let latency_ms = self.latency_ms.unwrap_or(PEER_UNKNOWN_LATENCY_MS);
+        // smoothed, so that a peer isn't written off (or trusted) on its first request
+        let success_rate =
+            (self.successes as f64 + 1.0) / (self.successes as f64 + self.failures as f64 + 2.0);
+        let expected_failures = (1.0 - success_rate) / success_rate;
+        latency_ms + expected_failures * PEER_FAILURE_COST_MS
+    }
+
+    /// How long to deprioritize this peer after its latest failure.  This doubles with each
+    /// consecutive failure, so that dead peers stop being retried every minute.
+    pub(crate) fn deprioritization_secs(&self) -> u64 {
+        let doublings = self.consecutive_failures.saturating_sub(1).min(16);
+        PEER_DEPRIORITIZATION_TIME_SECS
+            .saturating_mul(1 << doublings)
+            .min(PEER_MAX_DEPRIORITIZATION_TIME_SECS)
+    }
+}
 
 /// A set of confirmed downloader state machines assigned to one or more neighbors.  The block
 /// downloader runs tenure-downloaders in parallel, since the downloader for the N+1'st tenure
@@ -120,6 +180,10 @@ pub struct NakamotoTenureDownloaderSet {
     /// Peers that should be deprioritized because they're dead (maps to when they can be used
     /// again)
     pub(crate) deprioritized_peers: HashMap<NeighborAddress, u64>,
+    /// How well each peer has served downloads so far
+    pub(crate) peer_scores: HashMap<NeighborAddress, PeerDownloadScore>,
+    /// When the inflight request to each peer was sent
+    pub(crate) request_start_times: HashMap<NeighborAddress, Instant>,
 }
 
 impl NakamotoTenureDownloaderSet {
@@ -131,6 +195,8 @@ impl NakamotoTenureDownloaderSet {
             attempted_tenures: HashMap::new(),
             attempt_failed_tenures: HashMap::new(),
             deprioritized_peers: HashMap::new(),
+            peer_scores: HashMap::new(),
+            request_start_times: HashMap::new(),
         }
     }
 
@@ -144,27 +210,61 @@ impl NakamotoTenureDownloaderSet {
         }
     }
 
-    /// Mark a peer as deprioritized
+    /// Mark a peer as deprioritized, for a time that grows with its consecutive failures
     /// Implemented statically to appease the borrow checker.
     fn mark_deprioritized(
         deprioritized_peers: &mut HashMap<NeighborAddress, u64>,
+        peer_scores: &mut HashMap<NeighborAddress, PeerDownloadScore>,
         peer: &NeighborAddress,
     ) {
-        deprioritized_peers.insert(
-            peer.clone(),
-            get_epoch_time_secs() + PEER_DEPRIORITIZATION_TIME_SECS,
+        let score = peer_scores.entry(peer.clone()).or_default();
+        score.record_failure();
+        let deprioritization_secs = score.deprioritization_secs();
+        debug!(
+            "Deprioritize peer {peer} for {deprioritization_secs}s";
+            "consecutive_failures" => score.consecutive_failures
         );
+        deprioritized_peers.insert(peer.clone(), get_epoch_time_secs() + deprioritization_secs);
     }
 
     /// Mark a peer and its tenure as dead and failed
     fn mark_failed_and_deprioritize_peer(
         attempted_failed_tenures: &mut HashMap<ConsensusHash, u64>,
         deprioritized_peers: &mut HashMap<NeighborAddress, u64>,
+        peer_scores: &mut HashMap<NeighborAddress, PeerDownloadScore>,
         ch: &ConsensusHash,
         peer: &NeighborAddress,
     ) {
         Self::mark_failure(attempted_failed_tenures, ch);
-        Self::mark_deprioritized(deprioritized_peers, peer);
+        Self::mark_deprioritized(deprioritized_peers, peer_scores, peer);
+    }
+
+    /// Remove and return the neighbor best suited to serve a download: the one with the lowest
+    /// expected response time that isn't deprioritized.  Deprioritized neighbors are only
+    /// returned once there are no others.  Ties go to the neighbor latest in the list.
+    pub(crate) fn pop_best_neighbor(
+        neighbors: &mut Vec<NeighborAddress>,
+        peer_scores: &HashMap<NeighborAddress, PeerDownloadScore>,
+        deprioritized_peers: &HashMap<NeighborAddress, u64>,
+    ) -> Option<NeighborAddress> {
+        let now = get_epoch_time_secs();
+        let rank = |naddr: &NeighborAddress| {
+            let deprioritized = now < *deprioritized_peers.get(naddr).unwrap_or(&0);
+            let cost = peer_scores
+                .get(naddr)
+                .map(|score| score.cost())
+                .unwrap_or_else(|| PeerDownloadScore::default().cost());
+            (deprioritized, cost)
+        };
+        let mut best: Option<(usize, (bool, f64))> = None;
+        for (i, naddr) in neighbors.iter().enumerate() {
+            let naddr_rank = rank(naddr);
+            if best.map_or(true, |(_, best_rank)| naddr_rank <= best_rank) {
+                best = Some((i, naddr_rank));
+            }
+        }
+        let (i, _) = best?;
+        Some(neighbors.remove(i))
     }
 
     /// Assign the given peer to the given downloader state machine.  Allocate a slot for it if
@@ -418,7 +518,9 @@ impl NakamotoTenureDownloaderSet {
                 schedule.pop_front();
                 continue;
             }
-            let Some(naddr) = neighbors.pop() else {
+            let Some(naddr) =
+                Self::pop_best_neighbor(neighbors, &self.peer_scores, &self.deprioritized_peers)
+            else {
                 debug!("No more neighbors can serve tenure {ch}");
                 schedule.pop_front();
                 continue;
@@ -591,6 +693,7 @@ impl NakamotoTenureDownloaderSet {
                 Self::mark_failed_and_deprioritize_peer(
                     &mut self.attempt_failed_tenures,
                     &mut self.deprioritized_peers,
+                    &mut self.peer_scores,
                     &downloader.tenure_id_consensus_hash,
                     naddr,
                 );
@@ -602,12 +705,22 @@ impl NakamotoTenureDownloaderSet {
                 finished.push(naddr.clone());
                 continue;
             }
+            self.request_start_times
+                .insert(naddr.clone(), Instant::now());
         }
 
         // clear dead, broken, and done
         for naddr in addrs.iter() {
             if neighbor_rpc.is_dead_or_broken(network, naddr) {
                 debug!("Remove dead/broken downloader for {naddr}");
+                if self.request_start_times.remove(naddr).is_some() {
+                    // the connection broke with a request outstanding
+                    Self::mark_deprioritized(
+                        &mut self.deprioritized_peers,
+                        &mut self.peer_scores,
+                        naddr,
+                    );
+                }
                 self.clear_downloader(&naddr);
             }
         }
@@ -630,6 +743,7 @@ impl NakamotoTenureDownloaderSet {
                 continue;
             };
             debug!("Got response from {naddr}");
+            let latency = self.request_start_times.remove(&naddr).map(|t| t.elapsed());
 
             let Ok(blocks_opt) = downloader
                 .handle_next_download_response(response)
@@ -648,6 +762,7 @@ impl NakamotoTenureDownloaderSet {
                 Self::mark_failed_and_deprioritize_peer(
                     &mut self.attempt_failed_tenures,
                     &mut self.deprioritized_peers,
+                    &mut self.peer_scores,
                     &downloader.tenure_id_consensus_hash,
                     &naddr,
                 );
@@ -655,6 +770,13 @@ impl NakamotoTenureDownloaderSet {
                 continue;
             };
 
+            if let Some(latency) = latency {
+                self.peer_scores
+                    .entry(naddr.clone())
+                    .or_default()
+                    .record_success(latency);
+            }
+
             let Some(blocks) = blocks_opt else {
                 continue;
             };
@@ -684,6 +806,14 @@ impl NakamotoTenureDownloaderSet {
         for naddr in addrs.iter() {
             if neighbor_rpc.is_dead_or_broken(network, naddr) {
                 debug!("Remove dead/broken downloader for {naddr}");
+                if self.request_start_times.remove(naddr).is_some() {
+                    // the connection broke with a request outstanding
+                    Self::mark_deprioritized(
+                        &mut self.deprioritized_peers,
+                        &mut self.peer_scores,
+                        naddr,
+                    );
+                }
                 self.clear_downloader(naddr);
             }
         }
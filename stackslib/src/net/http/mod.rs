@@ -38,8 +38,8 @@ pub use crate::net::http::common::{
 };
 pub use crate::net::http::error::{
     http_error_from_code_and_text, http_reason, HttpBadRequest, HttpError, HttpErrorResponse,
-    HttpForbidden, HttpNotFound, HttpPaymentRequired, HttpServerError, HttpServiceUnavailable,
-    HttpUnauthorized,
+    HttpForbidden, HttpGone, HttpNotFound, HttpPaymentRequired, HttpServerError,
+    HttpServiceUnavailable, HttpTooManyRequests, HttpUnauthorized,
 };
 pub use crate::net::http::request::{
     HttpRequest, HttpRequestContents, HttpRequestPayload, HttpRequestPreamble,
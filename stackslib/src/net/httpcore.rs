@@ -22,6 +22,7 @@ use std::time::{Duration, Instant};
 use std::{fmt, io, mem};
 
 use clarity::vm::costs::ExecutionCost;
+use clarity::vm::database::ReadMode;
 use clarity::vm::types::{QualifiedContractIdentifier, BOUND_VALUE_SERIALIZATION_HEX};
 use clarity::vm::{ClarityName, ContractName};
 use percent_encoding::percent_decode_str;
@@ -53,6 +54,7 @@ use crate::net::http::{
     HttpVersion,
 };
 use crate::net::p2p::PeerNetwork;
+use crate::net::ratelimit::RPCRateLimitClass;
 use crate::net::server::HttpPeer;
 use crate::net::{Error as NetError, MessageSequence, ProtocolFamily, StacksNodeState, UrlString};
 
@@ -305,6 +307,8 @@ pub trait HttpRequestContentsExtensions {
     fn tip_request(&self) -> TipRequest;
     /// Determine if we should return a MARF proof
     fn get_with_proof(&self) -> bool;
+    /// Determine how to read from the Clarity DB, based on whether we should return a MARF proof
+    fn get_read_mode(&self) -> ReadMode;
 }
 
 impl HttpRequestContentsExtensions for HttpRequestContents {
@@ -340,6 +344,15 @@ impl HttpRequestContentsExtensions for HttpRequestContents {
             .unwrap_or("1".into());
         &proof_value == "1"
     }
+
+    /// Only ask the Clarity DB for a MARF proof if the request wants one
+    fn get_read_mode(&self) -> ReadMode {
+        if self.get_with_proof() {
+            ReadMode::WithProof
+        } else {
+            ReadMode::Plain
+        }
+    }
 }
 
 /// Work around Clone blanket implementations not being object-safe
@@ -375,6 +388,16 @@ pub trait RPCRequestHandler: HttpRequest + HttpResponse + RPCRequestHandlerClone
         state: &mut StacksNodeState,
     ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError>;
 
+    /// Which rate limit applies to requests to this endpoint.
+    /// By default, GET requests are reads and everything else is a write.
+    fn rate_limit_class(&self) -> RPCRateLimitClass {
+        if self.verb() == "GET" {
+            RPCRateLimitClass::Read
+        } else {
+            RPCRateLimitClass::Write
+        }
+    }
+
     /// Helper to get the canonical sortition tip
     fn get_canonical_burn_chain_tip(
         &self,
@@ -934,6 +957,10 @@ pub struct StacksHttp {
     pub maximum_call_argument_size: u32,
     /// Maximum execution budget of a read-only call
     pub read_only_call_limit: ExecutionCost,
+    /// Maximum memory a read-only call may use
+    pub read_only_call_memory_limit: u64,
+    /// Whether read-only calls may return an execution trace
+    pub read_only_call_trace: bool,
     /// The authorization token to enable access to privileged features, such as the block proposal RPC endpoint
     pub auth_token: Option<String>,
     /// Allow arbitrary responses to be handled in addition to request handlers
@@ -955,6 +982,8 @@ impl StacksHttp {
             request_handlers: vec![],
             maximum_call_argument_size: conn_opts.maximum_call_argument_size,
             read_only_call_limit: conn_opts.read_only_call_limit.clone(),
+            read_only_call_memory_limit: conn_opts.read_only_call_memory_limit,
+            read_only_call_trace: conn_opts.read_only_call_trace,
             auth_token: conn_opts.auth_token.clone(),
             allow_arbitrary_response: false,
         };
@@ -976,6 +1005,8 @@ impl StacksHttp {
             request_handlers: vec![],
             maximum_call_argument_size: conn_opts.maximum_call_argument_size,
             read_only_call_limit: conn_opts.read_only_call_limit.clone(),
+            read_only_call_memory_limit: conn_opts.read_only_call_memory_limit,
+            read_only_call_trace: conn_opts.read_only_call_trace,
             auth_token: conn_opts.auth_token.clone(),
             allow_arbitrary_response: true,
         }
@@ -1009,6 +1040,21 @@ impl StacksHttp {
         None
     }
 
+    /// Which rate limit applies to an inbound request.
+    /// Requests to unknown endpoints are treated as reads.
+    pub fn rate_limit_class(&self, request: &StacksHttpRequest) -> RPCRateLimitClass {
+        let Ok((decoded_path, _)) = decode_request_path(&request.preamble().path_and_query_str)
+        else {
+            return RPCRateLimitClass::Read;
+        };
+        request
+            .response_handler_index
+            .or_else(|| self.find_response_handler(&request.preamble().verb, &decoded_path))
+            .and_then(|index| self.request_handlers.get(index))
+            .map(|(_, _, handler)| handler.rate_limit_class())
+            .unwrap_or(RPCRateLimitClass::Read)
+    }
+
     /// Force the state machine to expect a response
     #[cfg(test)]
     pub fn set_response_handler(&mut self, request_verb: &str, request_path: &str) {
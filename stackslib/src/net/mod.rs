@@ -37,10 +37,12 @@ use libstackerdb::{
 };
 use rand::{thread_rng, RngCore};
 use regex::Regex;
+use rusqlite;
 use rusqlite::types::{ToSql, ToSqlOutput};
 use serde::de::Error as de_Error;
 use serde::ser::Error as ser_Error;
 use serde::{Deserialize, Serialize};
+use serde_json;
 use stacks_common::bitvec::BitVec;
 use stacks_common::codec::{
     read_next, write_next, Error as codec_error, StacksMessageCodec,
@@ -58,7 +60,7 @@ use stacks_common::util::secp256k1::{
     MessageSignature, Secp256k1PublicKey, MESSAGE_SIGNATURE_ENCODED_SIZE,
 };
 use stacks_common::util::{get_epoch_time_secs, log};
-use {rusqlite, serde_json, url};
+use url;
 
 use self::dns::*;
 use crate::burnchains::affirmation::AffirmationMap;
@@ -85,7 +87,7 @@ use crate::cost_estimates::metrics::CostMetric;
 use crate::cost_estimates::{CostEstimator, FeeEstimator, FeeRateEstimate};
 use crate::net::atlas::{Attachment, AttachmentInstance};
 use crate::net::dns::*;
-use crate::net::http::error::{HttpNotFound, HttpServerError};
+use crate::net::http::error::{HttpGone, HttpNotFound, HttpServerError};
 use crate::net::http::{
     Error as HttpErr, HttpRequestContents, HttpRequestPreamble, HttpResponsePreamble,
 };
@@ -93,6 +95,7 @@ use crate::net::httpcore::{
     HttpRequestContentsExtensions, StacksHttp, StacksHttpRequest, StacksHttpResponse, TipRequest,
 };
 use crate::net::p2p::{PeerNetwork, PendingMessages};
+use crate::net::rpccache::RPCResponseCacheKey;
 use crate::util_lib::bloom::{BloomFilter, BloomNodeHasher};
 use crate::util_lib::boot::boot_code_tx_auth;
 use crate::util_lib::db::{DBConn, Error as db_error};
@@ -135,8 +138,11 @@ pub mod p2p;
 /// p2p server and the http server.
 pub mod poll;
 pub mod prune;
+/// Implements token-bucket rate limiting of RPC requests
+pub mod ratelimit;
 pub mod relay;
 pub mod rpc;
+pub mod rpccache;
 pub mod server;
 pub mod stackerdb;
 pub mod unsolicited;
@@ -737,6 +743,22 @@ impl<'a> StacksNodeState<'a> {
         self.relay_message.take()
     }
 
+    /// Get a cached RPC response body, if there is one
+    pub fn get_cached_rpc_response(&mut self, key: &RPCResponseCacheKey) -> Option<Vec<u8>> {
+        self.with_node_state(|network, _, _, _, _| {
+            let canonical_tip = network.stacks_tip.block_id();
+            network.rpc_response_cache.get(&canonical_tip, key)
+        })
+    }
+
+    /// Cache an RPC response body
+    pub fn cache_rpc_response(&mut self, key: RPCResponseCacheKey, body: Vec<u8>) {
+        self.with_node_state(|network, _, _, _, _| {
+            let canonical_tip = network.stacks_tip.block_id();
+            network.rpc_response_cache.insert(&canonical_tip, key, body)
+        })
+    }
+
     /// Load up the canonical Stacks chain tip.  Note that this is subject to both burn chain block
     /// Stacks block availability -- different nodes with different partial replicas of the Stacks chain state
     /// will return different values here.
@@ -755,6 +777,7 @@ impl<'a> StacksNodeState<'a> {
     ///
     /// Returns the requested chain tip on success.
     /// If the chain tip could not be found, then it returns Err(HttpNotFound)
+    /// If the chain tip's state has been pruned, then it returns Err(HttpGone)
     /// If there was an error querying the DB, then it returns Err(HttpServerError)
     pub fn load_stacks_chain_tip(
         &mut self,
@@ -809,7 +832,19 @@ impl<'a> StacksNodeState<'a> {
                         }
                     }
                 }
-                TipRequest::SpecificTip(tip) => Ok(tip.clone()),
+                TipRequest::SpecificTip(tip) => match chainstate.clarity_state.is_pruned_at(&tip) {
+                    Ok(false) => Ok(tip),
+                    Ok(true) => Err(StacksHttpResponse::new_error(
+                        preamble,
+                        &HttpGone::new(format!("Pruned: state at {tip} is no longer available")),
+                    )),
+                    Err(e) => Err(StacksHttpResponse::new_error(
+                        preamble,
+                        &HttpServerError::new(format!(
+                            "Failed to check whether {tip} is pruned: {e:?}"
+                        )),
+                    )),
+                },
                 TipRequest::UseLatestAnchoredTip => {
                     match NakamotoChainState::get_canonical_block_header(chainstate.db(), sortdb) {
                         Ok(Some(tip)) => Ok(StacksBlockId::new(
@@ -2247,7 +2282,8 @@ pub mod test {
     use clarity::vm::database::STXBalance;
     use clarity::vm::types::*;
     use clarity::vm::ClarityVersion;
-    use rand::{Rng, RngCore};
+    use mio;
+    use rand::{self, Rng, RngCore};
     use stacks_common::address::*;
     use stacks_common::codec::StacksMessageCodec;
     use stacks_common::deps_common::bitcoin::network::serialize::BitcoinHash;
@@ -2258,7 +2294,6 @@ pub mod test {
     use stacks_common::util::secp256k1::*;
     use stacks_common::util::uint::*;
     use stacks_common::util::vrf::*;
-    use {mio, rand};
 
     use self::nakamoto::test_signers::TestSigners;
     use super::*;
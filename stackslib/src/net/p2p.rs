@@ -68,6 +68,7 @@ use crate::net::neighbors::*;
 use crate::net::poll::{NetworkPollState, NetworkState};
 use crate::net::prune::*;
 use crate::net::relay::{RelayerStats, *, *};
+use crate::net::rpccache::RPCResponseCache;
 use crate::net::server::*;
 use crate::net::stackerdb::{StackerDBConfig, StackerDBSync, StackerDBTx, StackerDBs};
 use crate::net::{Error as net_error, Neighbor, NeighborKey, *};
@@ -448,6 +449,9 @@ pub struct PeerNetwork {
 
     /// Thread handle for the async block proposal endpoint.
     block_proposal_thread: Option<JoinHandle<()>>,
+
+    /// Expensive read-only RPC responses, shared by all RPC conversations
+    pub rpc_response_cache: RPCResponseCache,
 }
 
 impl PeerNetwork {
@@ -494,6 +498,8 @@ impl PeerNetwork {
             stacker_db_sync_map.insert(contract_id.clone(), stacker_db_sync);
         }
 
+        let rpc_response_cache = RPCResponseCache::new(connection_opts.rpc_response_cache_size);
+
         let mut network = PeerNetwork {
             peer_version,
             epochs,
@@ -607,6 +613,8 @@ impl PeerNetwork {
             nakamoto_inv_generator: InvGenerator::new(),
 
             block_proposal_thread: None,
+
+            rpc_response_cache,
         };
 
         network.init_block_downloader();
@@ -5326,8 +5334,7 @@ mod test {
     use clarity::vm::ast::stack_depth_checker::AST_CALL_STACK_DEPTH_BUFFER;
     use clarity::vm::types::StacksAddressExtensions;
     use clarity::vm::MAX_CALL_STACK_DEPTH;
-    use rand;
-    use rand::RngCore;
+    use rand::{self, RngCore};
     use stacks_common::types::chainstate::BurnchainHeaderHash;
     use stacks_common::util::secp256k1::Secp256k1PrivateKey;
     use stacks_common::util::{log, sleep_ms};
@@ -0,0 +1,338 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+use stacks_common::util::hash::Sha256Sum;
+
+/// The most buckets the limiter tracks. Once there are this many, the least recently used
+/// buckets are dropped to make room for new clients.
+const MAX_BUCKETS: usize = 65536;
+
+/// How often buckets that have refilled completely, or have not been used for
+/// `BUCKET_IDLE_TIMEOUT`, are dropped. A full bucket is indistinguishable from a fresh one.
+const BUCKET_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How long a bucket may go unused before it is dropped
+const BUCKET_IDLE_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Endpoint classes that can be rate-limited independently
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RPCRateLimitClass {
+    /// Endpoints that only read chainstate
+    Read,
+    /// Endpoints that submit data to the node
+    Write,
+    /// Endpoints that evaluate Clarity code without committing it (e.g., read-only functions)
+    ReadOnlyCall,
+    /// Endpoints that require the node's authorization token
+    Authenticated,
+}
+
+/// A token-bucket rate limit: up to `burst` requests at once, refilled at `steady_rate`
+/// requests per second
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RPCRateLimit {
+    pub burst: u64,
+    pub steady_rate: f64,
+}
+
+/// Rate limits for each endpoint class. A class without a limit is not rate-limited.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RPCRateLimits {
+    pub read: Option<RPCRateLimit>,
+    pub write: Option<RPCRateLimit>,
+    pub read_only_call: Option<RPCRateLimit>,
+    pub authenticated: Option<RPCRateLimit>,
+}
+
+impl RPCRateLimits {
+    pub fn get(&self, class: RPCRateLimitClass) -> Option<&RPCRateLimit> {
+        match class {
+            RPCRateLimitClass::Read => self.read.as_ref(),
+            RPCRateLimitClass::Write => self.write.as_ref(),
+            RPCRateLimitClass::ReadOnlyCall => self.read_only_call.as_ref(),
+            RPCRateLimitClass::Authenticated => self.authenticated.as_ref(),
+        }
+    }
+}
+
+/// Who a request is charged to
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RPCRateLimitKey {
+    /// The client's IP address
+    Addr(IpAddr),
+    /// The valid credential presented to an authenticated endpoint. Only its hash is kept.
+    Credential(Sha256Sum),
+}
+
+impl RPCRateLimitKey {
+    /// Charge requests to authenticated endpoints that present the node's `auth_token` to
+    /// the credential, so that clients behind the same address do not share a budget.
+    /// Everything else, including requests with a wrong credential, is charged to the
+    /// client's address, so that rotating bogus credentials does not escape the limit.
+    pub fn new(
+        class: RPCRateLimitClass,
+        addr: IpAddr,
+        authorization: Option<&String>,
+        auth_token: Option<&String>,
+    ) -> Self {
+        match (class, authorization, auth_token) {
+            (RPCRateLimitClass::Authenticated, Some(credential), Some(auth_token))
+                if credential == auth_token =>
+            {
+                Self::Credential(Sha256Sum::from_data(credential.as_bytes()))
+            }
+            _ => Self::Addr(addr),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct TokenBucket {
+    tokens: f64,
+    /// When the bucket was last used
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn refill(&mut self, limit: &RPCRateLimit, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * limit.steady_rate).min(limit.burst as f64);
+        self.last_refill = now;
+    }
+
+    fn is_full(&self, limit: &RPCRateLimit, now: Instant) -> bool {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens + elapsed * limit.steady_rate >= limit.burst as f64
+    }
+
+    fn is_idle(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.last_refill) >= BUCKET_IDLE_TIMEOUT
+    }
+}
+
+/// Token-bucket rate limiter shared by all of the RPC server's conversations
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RPCRateLimiter {
+    limits: RPCRateLimits,
+    buckets: HashMap<(RPCRateLimitClass, RPCRateLimitKey), TokenBucket>,
+    /// When full and idle buckets were last dropped
+    last_sweep: Option<Instant>,
+}
+
+impl RPCRateLimiter {
+    pub fn new(limits: RPCRateLimits) -> Self {
+        Self {
+            limits,
+            buckets: HashMap::new(),
+            last_sweep: None,
+        }
+    }
+
+    /// The number of buckets being tracked
+    pub fn num_buckets(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Drop the buckets that have refilled completely or have not been used for a while.
+    /// If there are still `MAX_BUCKETS`, also drop the least recently used ones, so that there
+    /// is room for a new bucket.
+    fn evict(&mut self, now: Instant) {
+        let limits = &self.limits;
+        self.buckets
+            .retain(|(class, _), bucket| match limits.get(*class) {
+                Some(limit) => !bucket.is_full(limit, now) && !bucket.is_idle(now),
+                None => false,
+            });
+        self.last_sweep = Some(now);
+        if self.buckets.len() < MAX_BUCKETS {
+            return;
+        }
+        let mut last_used: Vec<_> = self
+            .buckets
+            .values()
+            .map(|bucket| bucket.last_refill)
+            .collect();
+        last_used.sort_unstable();
+        // keep a little headroom, so that this does not run for every new client
+        let cutoff = last_used[self.buckets.len() - MAX_BUCKETS * 9 / 10];
+        self.buckets.retain(|_, bucket| bucket.last_refill > cutoff);
+    }
+
+    /// Try to spend a token from `key`'s bucket for `class`.
+    /// Returns true if the request may proceed, and false if it must be rejected.
+    pub fn try_acquire(
+        &mut self,
+        class: RPCRateLimitClass,
+        key: RPCRateLimitKey,
+        now: Instant,
+    ) -> bool {
+        let Some(limit) = self.limits.get(class) else {
+            return true;
+        };
+        let limit = *limit;
+        let sweep_due = self
+            .last_sweep
+            .map_or(true, |last| now.saturating_duration_since(last) >= BUCKET_SWEEP_INTERVAL);
+        if sweep_due || self.buckets.len() >= MAX_BUCKETS {
+            self.evict(now);
+        }
+
+        let bucket = self
+            .buckets
+            .entry((class, key))
+            .or_insert_with(|| TokenBucket {
+                tokens: limit.burst as f64,
+                last_refill: now,
+            });
+        bucket.refill(&limit, now);
+        if bucket.tokens < 1.0 {
+            return false;
+        }
+        bucket.tokens -= 1.0;
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_rpc_rate_limiter() {
+        let mut limiter = RPCRateLimiter::new(RPCRateLimits {
+            read: Some(RPCRateLimit {
+                burst: 2,
+                steady_rate: 1.0,
+            }),
+            ..RPCRateLimits::default()
+        });
+        let alice = RPCRateLimitKey::Addr("127.0.0.1".parse().unwrap());
+        let bob = RPCRateLimitKey::Addr("127.0.0.2".parse().unwrap());
+        let now = Instant::now();
+
+        // burst is spent, then only the steady rate is allowed
+        assert!(limiter.try_acquire(RPCRateLimitClass::Read, alice.clone(), now));
+        assert!(limiter.try_acquire(RPCRateLimitClass::Read, alice.clone(), now));
+        assert!(!limiter.try_acquire(RPCRateLimitClass::Read, alice.clone(), now));
+
+        // other clients have their own budget
+        assert!(limiter.try_acquire(RPCRateLimitClass::Read, bob.clone(), now));
+
+        // unlimited classes are never rejected
+        for _ in 0..10 {
+            assert!(limiter.try_acquire(RPCRateLimitClass::Write, alice.clone(), now));
+        }
+
+        let later = now + Duration::from_millis(1500);
+        assert!(limiter.try_acquire(RPCRateLimitClass::Read, alice.clone(), later));
+        assert!(!limiter.try_acquire(RPCRateLimitClass::Read, alice.clone(), later));
+
+        // buckets never hold more than the burst
+        let much_later = now + Duration::from_secs(3600);
+        assert!(limiter.try_acquire(RPCRateLimitClass::Read, alice.clone(), much_later));
+        assert!(limiter.try_acquire(RPCRateLimitClass::Read, alice.clone(), much_later));
+        assert!(!limiter.try_acquire(RPCRateLimitClass::Read, alice, much_later));
+    }
+
+    #[test]
+    fn test_rpc_rate_limiter_eviction() {
+        let limit = RPCRateLimit {
+            burst: 2,
+            steady_rate: 0.001,
+        };
+        let mut limiter = RPCRateLimiter::new(RPCRateLimits {
+            read: Some(limit),
+            ..RPCRateLimits::default()
+        });
+        let now = Instant::now();
+        let key = |i: u32| RPCRateLimitKey::Addr(IpAddr::from(i.to_be_bytes()));
+
+        assert!(limiter.try_acquire(RPCRateLimitClass::Read, key(1), now));
+        assert!(limiter.try_acquire(RPCRateLimitClass::Read, key(2), now));
+        assert_eq!(limiter.num_buckets(), 2);
+
+        // buckets that are in use and not full are kept by a sweep
+        let later = now + BUCKET_SWEEP_INTERVAL;
+        assert!(limiter.try_acquire(RPCRateLimitClass::Read, key(1), later));
+        assert_eq!(limiter.num_buckets(), 2);
+
+        // buckets that go unused are dropped, even if they have not refilled
+        let much_later = later + BUCKET_IDLE_TIMEOUT;
+        assert!(limiter.try_acquire(RPCRateLimitClass::Read, key(3), much_later));
+        assert_eq!(limiter.num_buckets(), 1);
+
+        // the number of buckets is capped, by dropping the least recently used
+        for i in 0..(MAX_BUCKETS as u32 + 10) {
+            let at = much_later + Duration::from_micros(u64::from(i));
+            limiter.try_acquire(RPCRateLimitClass::Read, key(10 + i), at);
+            assert!(limiter.num_buckets() <= MAX_BUCKETS);
+        }
+        let last_key = key(10 + MAX_BUCKETS as u32 + 9);
+        assert!(limiter
+            .buckets
+            .contains_key(&(RPCRateLimitClass::Read, last_key)));
+        assert!(!limiter
+            .buckets
+            .contains_key(&(RPCRateLimitClass::Read, key(3))));
+    }
+
+    #[test]
+    fn test_rpc_rate_limit_key() {
+        let addr: IpAddr = "127.0.0.1".parse().unwrap();
+        let token = "password".to_string();
+        let bogus = "hunter2".to_string();
+        assert_eq!(
+            RPCRateLimitKey::new(RPCRateLimitClass::Read, addr, Some(&token), Some(&token)),
+            RPCRateLimitKey::Addr(addr)
+        );
+        assert_eq!(
+            RPCRateLimitKey::new(RPCRateLimitClass::Authenticated, addr, None, Some(&token)),
+            RPCRateLimitKey::Addr(addr)
+        );
+        assert_eq!(
+            RPCRateLimitKey::new(
+                RPCRateLimitClass::Authenticated,
+                addr,
+                Some(&token),
+                Some(&token)
+            ),
+            RPCRateLimitKey::Credential(Sha256Sum::from_data(token.as_bytes()))
+        );
+        // credentials that are not the node's auth token are charged to the address
+        assert_eq!(
+            RPCRateLimitKey::new(
+                RPCRateLimitClass::Authenticated,
+                addr,
+                Some(&bogus),
+                Some(&token)
+            ),
+            RPCRateLimitKey::Addr(addr)
+        );
+        assert_eq!(
+            RPCRateLimitKey::new(RPCRateLimitClass::Authenticated, addr, Some(&token), None),
+            RPCRateLimitKey::Addr(addr)
+        );
+    }
+}
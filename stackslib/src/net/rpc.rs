@@ -67,11 +67,12 @@ use crate::cost_estimates::{CostEstimator, FeeEstimator};
 use crate::net::atlas::{AtlasDB, Attachment, MAX_ATTACHMENT_INV_PAGES_PER_REQUEST};
 use crate::net::connection::{ConnectionHttp, ConnectionOptions, ReplyHandleHttp};
 use crate::net::db::PeerDB;
-use crate::net::http::{HttpRequestContents, HttpResponseContents};
+use crate::net::http::{HttpRequestContents, HttpResponseContents, HttpTooManyRequests};
 use crate::net::httpcore::{
     StacksHttp, StacksHttpMessage, StacksHttpRequest, StacksHttpResponse, HTTP_REQUEST_ID_RESERVED,
 };
 use crate::net::p2p::{PeerMap, PeerNetwork};
+use crate::net::ratelimit::{RPCRateLimitKey, RPCRateLimiter};
 use crate::net::relay::Relayer;
 use crate::net::stackerdb::{StackerDBTx, StackerDBs};
 use crate::net::{Error as net_error, StacksMessageType, StacksNodeState};
@@ -518,10 +519,12 @@ impl ConversationHttp {
     }
 
     /// Make progress on in-flight requests and replies.
+    /// Requests that exceed the client's rate limit are answered with an HTTP 429.
     /// Returns the list of messages we'll need to forward to the peer network
     pub fn chat(
         &mut self,
         node: &mut StacksNodeState,
+        rate_limiter: &mut RPCRateLimiter,
     ) -> Result<Vec<StacksMessageType>, net_error> {
         // handle in-bound HTTP request(s)
         let num_inbound = self.connection.inbox_len();
@@ -535,6 +538,27 @@ impl ConversationHttp {
 
             match msg {
                 StacksHttpMessage::Request(req) => {
+                    let rate_limit_class = self.connection.protocol.rate_limit_class(&req);
+                    let rate_limit_key = RPCRateLimitKey::new(
+                        rate_limit_class,
+                        self.peer_addr.ip(),
+                        req.preamble().headers.get("authorization"),
+                        self.connection.protocol.auth_token.as_ref(),
+                    );
+                    if !rate_limiter.try_acquire(rate_limit_class, rate_limit_key, Instant::now()) {
+                        self.last_request_timestamp = get_epoch_time_secs();
+                        self.reply_error(StacksHttpResponse::new_error(
+                            req.preamble(),
+                            &HttpTooManyRequests::new(format!(
+                                "Rate limit exceeded for {:?} requests",
+                                rate_limit_class
+                            )),
+                        ))?;
+
+                        info!("Rate-limited StacksHTTPRequest"; "verb" => %req.verb(), "path" => %req.request_path(), "conn_id" => self.conn_id, "peer_addr" => &self.peer_addr);
+                        continue;
+                    }
+
                     // new request that we can handle
                     self.total_request_count += 1;
                     self.last_request_timestamp = get_epoch_time_secs();
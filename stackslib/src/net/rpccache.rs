@@ -0,0 +1,182 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, HashMap};
+
+use stacks_common::types::chainstate::StacksBlockId;
+
+/// What a cached response was computed from
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RPCResponseCacheKey {
+    /// The endpoint's metrics identifier, e.g. `/v2/contracts/call-read/:principal/:contract_name/:func_name`
+    pub endpoint: String,
+    /// Everything else the response depends on, encoded by the endpoint
+    pub params: String,
+    /// The (confirmed) block the response was computed at
+    pub tip: StacksBlockId,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct RPCResponseCacheEntry {
+    body: Vec<u8>,
+    last_used: u64,
+}
+
+/// Cache of the bodies of expensive read-only RPC responses, shared by all of the RPC server's
+/// conversations.  A response computed at a confirmed block never changes, but most requests are
+/// made against the canonical tip, so the whole cache is dropped when the canonical tip changes.
+/// Once full, the least-recently-used response is evicted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RPCResponseCache {
+    /// Maximum number of responses to keep. The cache is disabled if 0.
+    capacity: usize,
+    /// The canonical Stacks tip when the cached responses were computed
+    canonical_tip: StacksBlockId,
+    entries: HashMap<RPCResponseCacheKey, RPCResponseCacheEntry>,
+    /// Keys, by when they were last used
+    lru: BTreeMap<u64, RPCResponseCacheKey>,
+    clock: u64,
+}
+
+impl RPCResponseCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            canonical_tip: StacksBlockId([0u8; 32]),
+            entries: HashMap::new(),
+            lru: BTreeMap::new(),
+            clock: 0,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drop everything if the canonical tip has changed since the cached responses were computed
+    fn check_canonical_tip(&mut self, canonical_tip: &StacksBlockId) {
+        if &self.canonical_tip == canonical_tip {
+            return;
+        }
+        self.canonical_tip = canonical_tip.clone();
+        self.entries.clear();
+        self.lru.clear();
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    /// Get the cached response body for `key`, if any
+    pub fn get(
+        &mut self,
+        canonical_tip: &StacksBlockId,
+        key: &RPCResponseCacheKey,
+    ) -> Option<Vec<u8>> {
+        self.check_canonical_tip(canonical_tip);
+        let now = self.tick();
+        let entry = self.entries.get_mut(key)?;
+        self.lru.remove(&entry.last_used);
+        self.lru.insert(now, key.clone());
+        entry.last_used = now;
+        Some(entry.body.clone())
+    }
+
+    /// Cache the response body for `key`
+    pub fn insert(
+        &mut self,
+        canonical_tip: &StacksBlockId,
+        key: RPCResponseCacheKey,
+        body: Vec<u8>,
+    ) {
+        if !self.is_enabled() {
+            return;
+        }
+        self.check_canonical_tip(canonical_tip);
+        let now = self.tick();
+        let entry = RPCResponseCacheEntry {
+            body,
+            last_used: now,
+        };
+        if let Some(old) = self.entries.insert(key.clone(), entry) {
+            self.lru.remove(&old.last_used);
+        }
+        self.lru.insert(now, key);
+
+        while self.entries.len() > self.capacity {
+            let Some(oldest) = self.lru.keys().next().cloned() else {
+                break;
+            };
+            if let Some(evicted) = self.lru.remove(&oldest) {
+                self.entries.remove(&evicted);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn key(params: &str, tip: u8) -> RPCResponseCacheKey {
+        RPCResponseCacheKey {
+            endpoint: "/v2/test".into(),
+            params: params.into(),
+            tip: StacksBlockId([tip; 32]),
+        }
+    }
+
+    #[test]
+    fn test_rpc_response_cache() {
+        let mut cache = RPCResponseCache::new(2);
+        let tip = StacksBlockId([1; 32]);
+
+        assert_eq!(cache.get(&tip, &key("a", 1)), None);
+        cache.insert(&tip, key("a", 1), vec![1]);
+        cache.insert(&tip, key("b", 1), vec![2]);
+        assert_eq!(cache.get(&tip, &key("a", 1)), Some(vec![1]));
+        assert_eq!(cache.get(&tip, &key("b", 1)), Some(vec![2]));
+
+        // responses at other tips are cached separately
+        assert_eq!(cache.get(&tip, &key("a", 2)), None);
+
+        // the least-recently used response is evicted
+        assert_eq!(cache.get(&tip, &key("a", 1)), Some(vec![1]));
+        cache.insert(&tip, key("c", 1), vec![3]);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&tip, &key("b", 1)), None);
+        assert_eq!(cache.get(&tip, &key("a", 1)), Some(vec![1]));
+        assert_eq!(cache.get(&tip, &key("c", 1)), Some(vec![3]));
+
+        // a new canonical tip drops everything
+        let new_tip = StacksBlockId([2; 32]);
+        assert_eq!(cache.get(&new_tip, &key("a", 1)), None);
+        assert!(cache.is_empty());
+
+        // a disabled cache stores nothing
+        let mut cache = RPCResponseCache::new(0);
+        cache.insert(&tip, key("a", 1), vec![1]);
+        assert_eq!(cache.get(&tip, &key("a", 1)), None);
+    }
+}
@@ -34,6 +34,7 @@ use crate::net::http::*;
 use crate::net::httpcore::*;
 use crate::net::p2p::{PeerMap, PeerNetwork};
 use crate::net::poll::*;
+use crate::net::ratelimit::RPCRateLimiter;
 use crate::net::rpc::*;
 use crate::net::{Error as net_error, *};
 
@@ -62,6 +63,9 @@ pub struct HttpPeer {
 
     /// connection options
     pub connection_opts: ConnectionOptions,
+
+    /// per-client request budgets, shared by all conversations
+    pub rate_limiter: RPCRateLimiter,
 }
 
 impl HttpPeer {
@@ -78,6 +82,7 @@ impl HttpPeer {
             http_server_handle: server_handle,
             http_server_addr: server_addr,
 
+            rate_limiter: RPCRateLimiter::new(conn_opts.rpc_rate_limits.clone()),
             connection_opts: conn_opts,
         }
     }
@@ -422,6 +427,7 @@ impl HttpPeer {
     /// forwarded to the peer network.
     fn process_http_conversation(
         node_state: &mut StacksNodeState,
+        rate_limiter: &mut RPCRateLimiter,
         event_id: usize,
         client_sock: &mut mio_net::TcpStream,
         convo: &mut ConversationHttp,
@@ -484,7 +490,7 @@ impl HttpPeer {
         // react to inbound messages -- do we need to send something out, or fulfill requests
         // to other threads?  Try to chat even if the recv() failed, since we'll want to at
         // least drain the conversation inbox.
-        let msgs = match convo.chat(node_state) {
+        let msgs = match convo.chat(node_state, rate_limiter) {
             Ok(msgs) => msgs,
             Err(e) => {
                 debug!(
@@ -572,6 +578,7 @@ impl HttpPeer {
                     debug!("Process HTTP data from {:?}", convo);
                     match HttpPeer::process_http_conversation(
                         node_state,
+                        &mut self.rate_limiter,
                         *event_id,
                         client_sock,
                         convo,
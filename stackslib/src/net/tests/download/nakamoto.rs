@@ -17,6 +17,7 @@
 use std::collections::{HashMap, VecDeque};
 use std::sync::mpsc::sync_channel;
 use std::thread;
+use std::time::Duration;
 
 use stacks_common::bitvec::BitVec;
 use stacks_common::types::chainstate::{
@@ -2092,6 +2093,72 @@ fn test_make_tenure_downloaders() {
     }
 }
 
+#[test]
+fn test_peer_download_scores() {
+    let naddr = |port| NeighborAddress {
+        addrbytes: PeerAddress([0xff; 16]),
+        port,
+        public_key_hash: Hash160([0xff; 20]),
+    };
+    let (fast, slow, flaky, fresh) = (naddr(1), naddr(2), naddr(3), naddr(4));
+
+    let mut scores = HashMap::new();
+    let mut score = PeerDownloadScore::default();
+    score.record_success(Duration::from_millis(50));
+    scores.insert(fast.clone(), score);
+
+    let mut score = PeerDownloadScore::default();
+    score.record_success(Duration::from_millis(5000));
+    scores.insert(slow.clone(), score);
+
+    let mut score = PeerDownloadScore::default();
+    score.record_success(Duration::from_millis(50));
+    for _ in 0..10 {
+        score.record_failure();
+    }
+    assert_eq!(score.consecutive_failures, 10);
+    scores.insert(flaky.clone(), score);
+
+    // failures inflate the cost of an otherwise-fast peer
+    assert!(scores[&fast].cost() < scores[&flaky].cost());
+    // unknown peers are tried before slow ones
+    assert!(PeerDownloadScore::default().cost() < scores[&slow].cost());
+
+    let mut deprioritized = HashMap::new();
+    let mut neighbors = vec![flaky.clone(), fast.clone(), slow.clone(), fresh.clone()];
+    let mut order = vec![];
+    while let Some(naddr) =
+        NakamotoTenureDownloaderSet::pop_best_neighbor(&mut neighbors, &scores, &deprioritized)
+    {
+        order.push(naddr);
+    }
+    assert_eq!(
+        order,
+        vec![fast.clone(), fresh.clone(), slow.clone(), flaky.clone()]
+    );
+
+    // deprioritized peers come last, regardless of score
+    deprioritized.insert(fast.clone(), get_epoch_time_secs() + 60);
+    let mut neighbors = vec![fast.clone(), slow.clone()];
+    assert_eq!(
+        NakamotoTenureDownloaderSet::pop_best_neighbor(&mut neighbors, &scores, &deprioritized),
+        Some(slow)
+    );
+
+    // backoff grows with consecutive failures, up to a limit, and resets on success
+    let mut score = PeerDownloadScore::default();
+    score.record_failure();
+    assert_eq!(score.deprioritization_secs(), 60);
+    score.record_failure();
+    assert_eq!(score.deprioritization_secs(), 120);
+    for _ in 0..20 {
+        score.record_failure();
+    }
+    assert_eq!(score.deprioritization_secs(), 3600);
+    score.record_success(Duration::from_millis(10));
+    assert_eq!(score.consecutive_failures, 0);
+}
+
 #[test]
 fn test_nakamoto_download_run_2_peers() {
     let observer = TestEventObserver::new();
@@ -20,6 +20,7 @@ use std::sync::mpsc::{channel, Receiver};
 use std::time::{Duration, Instant};
 use std::{str, thread};
 
+use clarity::vm::database::ReadMode;
 use stacks_common::codec::StacksMessageCodec;
 use stacks_common::types::chainstate::{StacksAddress, StacksBlockId, StacksPrivateKey};
 use stacks_common::types::net::{PeerAddress, PeerHost};
@@ -1054,6 +1055,24 @@ fn test_http_parse_proof_request_query() {
     assert!(proof_req);
 }
 
+#[test]
+fn test_http_parse_read_mode_query() {
+    let read_mode = HttpRequestContents::new()
+        .query_string(Some(""))
+        .get_read_mode();
+    assert_eq!(read_mode, ReadMode::WithProof);
+
+    let read_mode = HttpRequestContents::new()
+        .query_string(Some("proof=0"))
+        .get_read_mode();
+    assert_eq!(read_mode, ReadMode::Plain);
+
+    let read_mode = HttpRequestContents::new()
+        .query_string(Some("proof=1"))
+        .get_read_mode();
+    assert_eq!(read_mode, ReadMode::WithProof);
+}
+
 #[test]
 fn test_metrics_identifiers() {
     let convo = ConversationHttp::new(
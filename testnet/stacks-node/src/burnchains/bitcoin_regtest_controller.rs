@@ -30,8 +30,12 @@ use stacks::burnchains::bitcoin::address::{
 use stacks::burnchains::bitcoin::indexer::{
     BitcoinIndexer, BitcoinIndexerConfig, BitcoinIndexerRuntime,
 };
+use stacks::burnchains::bitcoin::op_builder::{
+    BurnOpBroadcaster, BurnOpBuilder, Error as BurnOpError, SignedBurnOp, Utxo as OpUtxo,
+};
 use stacks::burnchains::bitcoin::spv::SpvClient;
 use stacks::burnchains::bitcoin::BitcoinNetworkType;
+use stacks::burnchains::checkpoint::{list_checkpoints, prune_checkpoints, BurnchainCheckpoint};
 use stacks::burnchains::db::BurnchainDB;
 use stacks::burnchains::indexer::BurnchainIndexer;
 use stacks::burnchains::{
@@ -60,19 +64,20 @@ use stacks_common::deps_common::bitcoin::blockdata::transaction::{
     OutPoint, Transaction, TxIn, TxOut,
 };
 use stacks_common::deps_common::bitcoin::network::encodable::ConsensusEncodable;
-#[cfg(test)]
 use stacks_common::deps_common::bitcoin::network::serialize::deserialize as btc_deserialize;
 use stacks_common::deps_common::bitcoin::network::serialize::RawEncoder;
 use stacks_common::deps_common::bitcoin::util::hash::Sha256dHash;
 use stacks_common::types::chainstate::BurnchainHeaderHash;
 use stacks_common::types::net::PeerHost;
-use stacks_common::util::hash::{hex_bytes, Hash160};
+use stacks_common::util::hash::{hex_bytes, to_hex, Hash160};
 use stacks_common::util::secp256k1::Secp256k1PublicKey;
 use stacks_common::util::sleep_ms;
 use url::Url;
 
 use super::super::operations::BurnchainOpSigner;
 use super::super::Config;
+use super::failover::BitcoindFailover;
+use super::rpc_client::{make_burnchain_rpc_client, BurnchainRpcClient};
 use super::{BurnchainController, BurnchainTip, Error as BurnchainControllerError};
 use crate::config::BurnchainConfig;
 #[cfg(test)]
@@ -102,6 +107,8 @@ pub struct BitcoinRegtestController {
     ongoing_block_commit: Option<OngoingBlockCommit>,
     should_keep_running: Option<Arc<AtomicBool>>,
     allow_rbf: bool,
+    /// Which bitcoind to use, if failover nodes are configured
+    failover: Option<BitcoindFailover>,
 }
 
 #[derive(Clone)]
@@ -205,6 +212,52 @@ pub fn get_max_rbf(config: &Config) -> u64 {
     config.get_burnchain_config().max_rbf
 }
 
+fn is_p2tr_script(script_pub_key: &Script) -> bool {
+    let bytes = script_pub_key.as_bytes();
+    bytes.len() == 34 && bytes[0..2] == [0x51, 0x20]
+}
+
+fn is_p2wpkh_script(script_pub_key: &Script) -> bool {
+    let bytes = script_pub_key.as_bytes();
+    bytes.len() == 22 && bytes[0..2] == [0x00, 0x14]
+}
+
+/// Give `input`, which spends `script_pub_key`, a placeholder signature as large as the real
+/// one can be, so that the transaction's size can be measured before the wallet signs it
+fn set_placeholder_signature(
+    input: &mut TxIn,
+    script_pub_key: &Script,
+    public_key: &Secp256k1PublicKey,
+) {
+    // a DER signature and its sighash byte take at most 73 bytes
+    let placeholder_sig = vec![0u8; 73];
+    if is_p2tr_script(script_pub_key) {
+        input.script_sig = Script::from(vec![]);
+        input.witness = vec![vec![0u8; 64]];
+    } else if is_p2wpkh_script(script_pub_key) {
+        input.script_sig = Script::from(vec![]);
+        input.witness = vec![placeholder_sig, public_key.to_bytes_compressed()];
+    } else {
+        input.script_sig = Builder::new()
+            .push_slice(&placeholder_sig)
+            .push_slice(&public_key.to_bytes())
+            .into_script();
+        input.witness.clear();
+    }
+}
+
+/// The public key whose addresses hold the miner's UTXOs.  This is the signer's key, unless the
+/// wallet signs the miner's transactions for another key.
+pub fn get_funding_public_key(
+    config: &Config,
+    signer: &mut BurnchainOpSigner,
+) -> Secp256k1PublicKey {
+    match config.miner.psbt_signer_public_key {
+        Some(public_key) if config.miner.psbt_signing => public_key,
+        _ => signer.get_public_key(),
+    }
+}
+
 impl LeaderBlockCommitFees {
     pub fn fees_from_previous_tx(
         &self,
@@ -219,6 +272,15 @@ impl LeaderBlockCommitFees {
         fees
     }
 
+    /// The fees of a transaction that replaces the one that paid `self`, at `fee_rate`
+    pub fn fees_for_replacement(&self, fee_rate: u64) -> LeaderBlockCommitFees {
+        let mut fees = self.clone();
+        fees.spent_in_attempts = cmp::max(1, self.spent_in_attempts);
+        fees.fee_rate = fee_rate;
+        fees.is_rbf_enabled = true;
+        fees
+    }
+
     pub fn estimated_fees_from_payload(
         payload: &LeaderBlockCommitOp,
         config: &Config,
@@ -352,6 +414,7 @@ impl BitcoinRegtestController {
             runtime: indexer_runtime,
             should_keep_running: should_keep_running.clone(),
         };
+        let failover = BitcoindFailover::new(&config);
 
         Self {
             use_coordinator: coordinator_channel,
@@ -364,6 +427,7 @@ impl BitcoinRegtestController {
             ongoing_block_commit: None,
             should_keep_running,
             allow_rbf: true,
+            failover,
         }
     }
 
@@ -398,6 +462,7 @@ impl BitcoinRegtestController {
             runtime: indexer_runtime,
             should_keep_running: None,
         };
+        let failover = BitcoindFailover::new(&config);
 
         Self {
             use_coordinator: None,
@@ -410,6 +475,7 @@ impl BitcoinRegtestController {
             ongoing_block_commit: None,
             should_keep_running: None,
             allow_rbf: true,
+            failover,
         }
     }
 
@@ -531,6 +597,7 @@ impl BitcoinRegtestController {
             if !self.should_keep_running() {
                 return Err(BurnchainControllerError::CoordinatorClosed);
             }
+            self.check_bitcoind_failover();
 
             match burnchain.sync_with_indexer(
                 &mut self.indexer,
@@ -574,6 +641,7 @@ impl BitcoinRegtestController {
                 Err(e) => {
                     // keep trying
                     error!("Unable to sync with burnchain: {e}");
+                    self.schedule_bitcoind_health_check();
                     match e {
                         burnchain_error::CoordinatorClosed => {
                             return Err(BurnchainControllerError::CoordinatorClosed)
@@ -613,6 +681,10 @@ impl BitcoinRegtestController {
         self.chain_tip = Some(burnchain_tip.clone());
         debug!("Done receiving blocks");
 
+        if let Err(e) = self.checkpoint_burnchain(&burnchain) {
+            warn!("Failed to checkpoint the burnchain state: {e:?}");
+        }
+
         if self.config.burnchain.fault_injection_burnchain_block_delay > 0 && received {
             info!(
                 "Fault injection: delaying burnchain blocks by {} milliseconds",
@@ -624,6 +696,34 @@ impl BitcoinRegtestController {
         Ok((burnchain_tip, burnchain_height))
     }
 
+    /// Checkpoint the burnchain headers and burnchain DB, if `burnchain.checkpoint_interval`
+    /// blocks have been indexed since the latest checkpoint.  Old checkpoints beyond
+    /// `burnchain.checkpoint_retain` are deleted.
+    fn checkpoint_burnchain(&self, burnchain: &Burnchain) -> Result<(), burnchain_error> {
+        let interval = self.config.burnchain.checkpoint_interval;
+        if interval == 0 {
+            return Ok(());
+        }
+        let burnchain_db_path = burnchain.get_burnchaindb_path();
+        let tip = BurnchainDB::open(&burnchain_db_path, false)?.get_canonical_chain_tip()?;
+        let checkpoints_path = self.config.get_burnchain_checkpoints_path();
+        let latest_checkpoint_height = list_checkpoints(&checkpoints_path)?
+            .last()
+            .map(|(_, checkpoint)| checkpoint.block_height);
+        if matches!(latest_checkpoint_height, Some(height) if height.saturating_add(interval) > tip.block_height)
+        {
+            return Ok(());
+        }
+
+        BurnchainCheckpoint::create(
+            &self.config.get_spv_headers_file_path(),
+            &burnchain_db_path,
+            &checkpoints_path.join(tip.block_height.to_string()),
+        )?;
+        let retain = usize::try_from(self.config.burnchain.checkpoint_retain).unwrap_or(usize::MAX);
+        prune_checkpoints(&checkpoints_path, retain)
+    }
+
     fn should_keep_running(&self) -> bool {
         match self.should_keep_running {
             Some(ref should_keep_running) => should_keep_running.load(Ordering::SeqCst),
@@ -728,12 +828,40 @@ impl BitcoinRegtestController {
     /// Checks if the config-supplied wallet exists.
     /// If it does not exist, this function creates it.
     pub fn create_wallet_if_dne(&self) -> RPCResult<()> {
-        let wallets = BitcoinRPCRequest::list_wallets(&self.config)?;
+        self.rpc_client().create_wallet_if_dne()
+    }
 
-        if !wallets.contains(&self.config.burnchain.wallet_name) {
-            BitcoinRPCRequest::create_wallet(&self.config, &self.config.burnchain.wallet_name)?;
+    /// The client of the burnchain node's RPC interface that the config selects
+    /// If a bitcoind health check is due, run it, and switch to another bitcoind if the one in
+    /// use is unreachable or behind
+    fn check_bitcoind_failover(&mut self) {
+        let Some(failover) = self.failover.as_mut() else {
+            return;
+        };
+        let Some(endpoint) = failover.check(&self.config) else {
+            return;
+        };
+        endpoint.apply(&mut self.config.burnchain);
+        self.indexer.config.peer_host = endpoint.peer_host;
+        self.indexer.config.peer_port = endpoint.peer_port;
+        self.indexer.config.rpc_port = endpoint.rpc_port;
+        self.indexer.config.rpc_ssl = endpoint.rpc_ssl;
+        self.indexer.config.username = endpoint.username;
+        self.indexer.config.password = endpoint.password;
+        // drop the connection to the old bitcoind
+        self.indexer.runtime = BitcoinIndexerRuntime::new(self.indexer.runtime.network_id);
+    }
+
+    /// Run a bitcoind health check the next time one could be due, because the bitcoind in use
+    /// just failed
+    fn schedule_bitcoind_health_check(&mut self) {
+        if let Some(failover) = self.failover.as_mut() {
+            failover.check_now();
         }
-        Ok(())
+    }
+
+    fn rpc_client(&self) -> Box<dyn BurnchainRpcClient + '_> {
+        make_burnchain_rpc_client(&self.config)
     }
 
     pub fn get_utxos(
@@ -768,8 +896,7 @@ impl BitcoinRegtestController {
         let filter_addresses = vec![addr2str(&address)];
 
         let mut utxos = loop {
-            let result = BitcoinRPCRequest::list_unspent(
-                &self.config,
+            let result = self.rpc_client().list_unspent(
                 filter_addresses.clone(),
                 !self.allow_rbf, // if RBF is disabled, then we can use 0-conf txs
                 total_required,
@@ -798,12 +925,11 @@ impl BitcoinRegtestController {
                     // Assuming that miners are in charge of correctly operating their bitcoind nodes sounds
                     // reasonable to me.
                     // $ bitcoin-cli importaddress mxVFsFW5N4mu1HPkxPttorvocvzeZ7KZyk
-                    let _result = BitcoinRPCRequest::import_public_key(&self.config, &pubk);
+                    let _result = self.rpc_client().import_public_key(&pubk);
                     sleep_ms(1000);
                 }
 
-                let result = BitcoinRPCRequest::list_unspent(
-                    &self.config,
+                let result = self.rpc_client().list_unspent(
                     filter_addresses.clone(),
                     !self.allow_rbf, // if RBF is disabled, then we can use 0-conf txs
                     total_required,
@@ -852,7 +978,7 @@ impl BitcoinRegtestController {
         signer: &mut BurnchainOpSigner,
         _attempt: u64,
     ) -> Result<Transaction, BurnchainControllerError> {
-        let public_key = signer.get_public_key();
+        let public_key = get_funding_public_key(&self.config, signer);
 
         // reload the config to find satoshis_per_byte changes
         let btc_miner_fee = self.config.burnchain.leader_key_tx_estimated_size
@@ -896,7 +1022,7 @@ impl BitcoinRegtestController {
             &mut utxos,
             signer,
             true, // key register op requires change output to exist
-        );
+        )?;
 
         increment_btc_ops_sent_counter();
 
@@ -973,7 +1099,7 @@ impl BitcoinRegtestController {
         signer: &mut BurnchainOpSigner,
         utxo_to_use: Option<UTXO>,
     ) -> Result<Transaction, BurnchainControllerError> {
-        let public_key = signer.get_public_key();
+        let public_key = get_funding_public_key(&self.config, signer);
         let max_tx_size = OP_TX_TRANSFER_STACKS_ESTIM_SIZE;
         let (mut tx, mut utxos) = if let Some(utxo) = utxo_to_use {
             (
@@ -1030,7 +1156,7 @@ impl BitcoinRegtestController {
             &mut utxos,
             signer,
             false,
-        );
+        )?;
 
         increment_btc_ops_sent_counter();
 
@@ -1056,7 +1182,7 @@ impl BitcoinRegtestController {
         signer: &mut BurnchainOpSigner,
         utxo_to_use: Option<UTXO>,
     ) -> Result<Transaction, BurnchainControllerError> {
-        let public_key = signer.get_public_key();
+        let public_key = get_funding_public_key(&self.config, signer);
         let max_tx_size = OP_TX_DELEGATE_STACKS_ESTIM_SIZE;
 
         let (mut tx, mut utxos) = if let Some(utxo) = utxo_to_use {
@@ -1115,7 +1241,7 @@ impl BitcoinRegtestController {
             &mut utxos,
             signer,
             false,
-        );
+        )?;
 
         increment_btc_ops_sent_counter();
 
@@ -1136,7 +1262,7 @@ impl BitcoinRegtestController {
         signer: &mut BurnchainOpSigner,
         utxo_to_use: Option<UTXO>,
     ) -> Result<Transaction, BurnchainControllerError> {
-        let public_key = signer.get_public_key();
+        let public_key = get_funding_public_key(&self.config, signer);
         let max_tx_size = OP_TX_VOTE_AGG_ESTIM_SIZE;
 
         let (mut tx, mut utxos) = if let Some(utxo) = utxo_to_use {
@@ -1192,7 +1318,7 @@ impl BitcoinRegtestController {
             &mut utxos,
             signer,
             false,
-        );
+        )?;
 
         increment_btc_ops_sent_counter();
 
@@ -1233,7 +1359,7 @@ impl BitcoinRegtestController {
         payload: PreStxOp,
         signer: &mut BurnchainOpSigner,
     ) -> Result<Transaction, BurnchainControllerError> {
-        let public_key = signer.get_public_key();
+        let public_key = get_funding_public_key(&self.config, signer);
         let max_tx_size = OP_TX_PRE_STACKS_ESTIM_SIZE;
 
         let max_tx_size_any_op = OP_TX_ANY_ESTIM_SIZE;
@@ -1271,7 +1397,7 @@ impl BitcoinRegtestController {
             &mut utxos,
             signer,
             false,
-        );
+        )?;
 
         increment_btc_ops_sent_counter();
 
@@ -1303,7 +1429,7 @@ impl BitcoinRegtestController {
         signer: &mut BurnchainOpSigner,
         utxo_to_use: Option<UTXO>,
     ) -> Result<Transaction, BurnchainControllerError> {
-        let public_key = signer.get_public_key();
+        let public_key = get_funding_public_key(&self.config, signer);
         let max_tx_size = OP_TX_STACK_STX_ESTIM_SIZE;
 
         let (mut tx, mut utxos) = if let Some(utxo) = utxo_to_use {
@@ -1361,7 +1487,7 @@ impl BitcoinRegtestController {
             &mut utxos,
             signer,
             false,
-        );
+        )?;
 
         increment_btc_ops_sent_counter();
 
@@ -1433,7 +1559,7 @@ impl BitcoinRegtestController {
         previous_txids: &[Txid],
         burnchain_block_height: u64,
     ) -> Result<Transaction, BurnchainControllerError> {
-        let public_key = signer.get_public_key();
+        let public_key = get_funding_public_key(&self.config, signer);
         let (mut tx, mut utxos) = self.prepare_tx(
             epoch_id,
             &public_key,
@@ -1480,7 +1606,7 @@ impl BitcoinRegtestController {
             &mut utxos,
             signer,
             true, // block commit op requires change output to exist
-        );
+        )?;
 
         let serialized_tx = SerializedTx::new(tx.clone());
 
@@ -1535,12 +1661,19 @@ impl BitcoinRegtestController {
         let _ = self.sortdb_mut();
         let burnchain_db = self.burnchain_db.as_ref().expect("BurnchainDB not opened");
 
+        let spent_scripts: Vec<_> = ongoing_op
+            .utxos
+            .utxos
+            .iter()
+            .map(|utxo| utxo.script_pub_key.clone())
+            .collect();
         for txid in ongoing_op.txids.iter() {
             // check if ongoing_op is in the burnchain_db *or* has been confirmed via the bitcoin RPC
             let mined_op = burnchain_db.find_burnchain_op(&self.indexer, txid);
             let ongoing_tx_confirmed = mined_op.is_some()
                 || matches!(
-                    BitcoinRPCRequest::check_transaction_confirmed(&self.config, txid),
+                    self.rpc_client()
+                        .check_transaction_confirmed(txid, &spent_scripts),
                     Ok(true)
                 );
             if ongoing_tx_confirmed {
@@ -1656,6 +1789,154 @@ impl BitcoinRegtestController {
         res
     }
 
+    /// Replace the ongoing block-commit with one that pays a higher fee rate, if it is still
+    /// waiting to be mined and pays less than bitcoind's estimate of the fee rate needed to be
+    /// mined in the next block.  The replacement pays at most `burnchain.rbf_max_fee_rate`, and
+    /// the commit and its replacements spend at most `burnchain.rbf_max_tenure_spend` in total.
+    /// Returns the replacement's txid, if one was sent.
+    pub fn bump_block_commit_fee(
+        &mut self,
+        epoch_id: StacksEpochId,
+        signer: &mut BurnchainOpSigner,
+    ) -> Result<Option<Txid>, BurnchainControllerError> {
+        if !self.allow_rbf || self.ongoing_block_commit.is_none() {
+            return Ok(None);
+        }
+        self.check_bitcoind_failover();
+
+        let _ = self.sortdb_mut();
+        let burnchain_db = self.burnchain_db.as_ref().expect("BurnchainDB not opened");
+        let ongoing_op = self
+            .ongoing_block_commit
+            .as_ref()
+            .expect("BUG: no ongoing block commit");
+        let Some(last_txid) = ongoing_op.txids.last().cloned() else {
+            return Ok(None);
+        };
+        if ongoing_op.txids.iter().any(|txid| {
+            burnchain_db
+                .find_burnchain_op(&self.indexer, txid)
+                .is_some()
+        }) {
+            debug!("Ongoing block commit was mined, not bumping its fee");
+            return Ok(None);
+        }
+        let burn_chain_tip = burnchain_db
+            .get_canonical_chain_tip()
+            .map_err(|_| BurnchainControllerError::BurnchainError)?;
+
+        let mempool_fee_rate =
+            match BitcoinRPCRequest::get_mempool_fee_rate(&self.config, &last_txid) {
+                Ok(fee_rate) => fee_rate,
+                Err(e) => {
+                    warn!("Failed to query the mempool for the ongoing block commit: {e:?}");
+                    return Ok(None);
+                }
+            };
+        if mempool_fee_rate.is_none() {
+            let spent_scripts: Vec<_> = ongoing_op
+                .utxos
+                .utxos
+                .iter()
+                .map(|utxo| utxo.script_pub_key.clone())
+                .collect();
+            if let Ok(true) = self
+                .rpc_client()
+                .check_transaction_confirmed(&last_txid, &spent_scripts)
+            {
+                debug!("Ongoing block commit {last_txid} was confirmed, not bumping its fee");
+                return Ok(None);
+            }
+            info!("Ongoing block commit {last_txid} is no longer in the mempool");
+        }
+
+        let target_fee_rate = match BitcoinRPCRequest::estimate_next_block_fee_rate(&self.config) {
+            Ok(Some(fee_rate)) => fee_rate,
+            Ok(None) => {
+                debug!("No fee rate estimate available, not bumping the block commit fee");
+                return Ok(None);
+            }
+            Err(e) => {
+                warn!("Failed to estimate the next block's fee rate: {e:?}");
+                return Ok(None);
+            }
+        };
+        if matches!(mempool_fee_rate, Some(fee_rate) if fee_rate >= target_fee_rate) {
+            return Ok(None);
+        }
+
+        // an evicted commit is re-sent too, since it can't be mined otherwise
+        let burnchain_config = self.config.get_burnchain_config();
+        let max_fee_rate = burnchain_config.get_rbf_max_fee_rate();
+        let min_fee_rate = ongoing_op.fees.fee_rate + burnchain_config.rbf_fee_increment;
+        let fee_rate = cmp::min(cmp::max(target_fee_rate, min_fee_rate), max_fee_rate);
+        if fee_rate < min_fee_rate {
+            warn!(
+                "Block commit is outbid but its fee rate cannot be raised further";
+                "txid" => %last_txid,
+                "fee_rate" => ongoing_op.fees.fee_rate,
+                "target_fee_rate" => target_fee_rate,
+                "max_fee_rate" => max_fee_rate,
+            );
+            return Ok(None);
+        }
+
+        let fees = ongoing_op.fees.fees_for_replacement(fee_rate);
+        let mut projected_fees = fees.clone();
+        projected_fees.register_replacement(fees.final_size);
+        if let Some(max_spend) = burnchain_config.rbf_max_tenure_spend {
+            if projected_fees.total_spent() > max_spend {
+                warn!(
+                    "Bumping the block commit fee would exceed the tenure spend limit";
+                    "txid" => %last_txid,
+                    "fee_rate" => fee_rate,
+                    "projected_spend" => projected_fees.total_spent(),
+                    "max_spend" => max_spend,
+                );
+                return Ok(None);
+            }
+        }
+        if fees.estimated_amount_required() > ongoing_op.sum_utxos() {
+            warn!(
+                "Insufficient UTXOs to bump the block commit fee";
+                "txid" => %last_txid,
+                "fee_rate" => fee_rate,
+            );
+            return Ok(None);
+        }
+
+        info!(
+            "Bumping the fee of the ongoing block commit";
+            "txid" => %last_txid,
+            "mempool_fee_rate" => ?mempool_fee_rate,
+            "fee_rate" => fee_rate,
+            "target_fee_rate" => target_fee_rate,
+        );
+        let ongoing_op = self
+            .ongoing_block_commit
+            .take()
+            .expect("BUG: no ongoing block commit");
+        let res = self
+            .send_block_commit_operation_at_burnchain_height(
+                epoch_id,
+                ongoing_op.payload.clone(),
+                signer,
+                Some(ongoing_op.utxos.clone()),
+                None,
+                fees,
+                &ongoing_op.txids,
+                burn_chain_tip.block_height,
+            )
+            .and_then(|tx| self.send_transaction(SerializedTx::new(tx)));
+        match res {
+            Ok(txid) => Ok(Some(txid)),
+            Err(e) => {
+                self.ongoing_block_commit = Some(ongoing_op);
+                Err(e)
+            }
+        }
+    }
+
     pub(crate) fn get_miner_address(
         &self,
         epoch_id: StacksEpochId,
@@ -1663,7 +1944,13 @@ impl BitcoinRegtestController {
     ) -> BitcoinAddress {
         let (_, network_id) = self.config.burnchain.get_bitcoin_network();
 
-        if self.config.miner.segwit && epoch_id >= StacksEpochId::Epoch21 {
+        if self.config.miner.taproot && epoch_id >= StacksEpochId::Epoch21 {
+            let output_key = public_key
+                .to_taproot_output_key()
+                .expect("Public key incorrect");
+            BitcoinAddress::from_bytes_segwit_p2tr(network_id, &output_key)
+                .expect("Public key incorrect")
+        } else if self.config.miner.segwit && epoch_id >= StacksEpochId::Epoch21 {
             let hash160 = Hash160::from_data(&public_key.to_bytes_compressed());
             BitcoinAddress::from_bytes_segwit_p2wpkh(network_id, &hash160.0)
                 .expect("Public key incorrect")
@@ -1737,7 +2024,7 @@ impl BitcoinRegtestController {
         utxos_set: &mut UTXOSet,
         signer: &mut BurnchainOpSigner,
         force_change_output: bool,
-    ) {
+    ) -> Result<(), BurnchainControllerError> {
         // spend UTXOs in order by confirmations.  Spend the least-confirmed UTXO first, and in the
         // event of a tie, spend the smallest-value UTXO first.
         utxos_set.utxos.sort_by(|u1, u2| {
@@ -1787,12 +2074,35 @@ impl BitcoinRegtestController {
             force_change_output,
         );
         signer.dispose();
+
+        if self.config.miner.psbt_signing {
+            let unsigned_tx = SerializedTx::new(tx.clone());
+            let signed_tx_hex =
+                BitcoinRPCRequest::sign_with_wallet(&self.config, &unsigned_tx.to_hex()).map_err(
+                    |e| {
+                        error!("Bitcoin RPC error: wallet failed to sign transaction - {e:?}");
+                        BurnchainControllerError::SigningFailed(format!("{e:?}"))
+                    },
+                )?;
+            *tx = hex_bytes(&signed_tx_hex)
+                .ok()
+                .and_then(|bytes| btc_deserialize(&bytes).ok())
+                .ok_or_else(|| {
+                    BurnchainControllerError::SigningFailed(
+                        "Wallet returned an undecodable transaction".into(),
+                    )
+                })?;
+        }
+        Ok(())
     }
 
     /// Sign and serialize a tx, consuming the UTXOs in utxo_set and spending total_to_spend
     /// satoshis.  Uses the key in signer.
-    /// If self.config.miner.segwit is true, the transaction's change address will be a p2wpkh
-    /// output. Otherwise, it will be a p2pkh output.
+    /// If self.config.miner.taproot is true, the transaction's change address will be a p2tr
+    /// output. Otherwise, if self.config.miner.segwit is true, it will be a p2wpkh output.
+    /// Otherwise, it will be a p2pkh output.
+    /// If self.config.miner.psbt_signing is true, the inputs get placeholder signatures instead,
+    /// and the wallet signs the transaction in `finalize_tx()`.
     fn serialize_tx(
         &mut self,
         epoch_id: StacksEpochId,
@@ -1802,7 +2112,7 @@ impl BitcoinRegtestController {
         signer: &mut BurnchainOpSigner,
         force_change_output: bool,
     ) -> bool {
-        let mut public_key = signer.get_public_key();
+        let mut public_key = get_funding_public_key(&self.config, signer);
 
         let total_target = if force_change_output {
             tx_cost + DUST_UTXO_LIMIT
@@ -1834,7 +2144,13 @@ impl BitcoinRegtestController {
             "Payments value: {value:?}, total_consumed: {total_consumed:?}, total_spent: {total_target:?}"
         );
         if value >= DUST_UTXO_LIMIT {
-            let change_output = if self.config.miner.segwit && epoch_id >= StacksEpochId::Epoch21 {
+            let change_output = if self.config.miner.taproot && epoch_id >= StacksEpochId::Epoch21 {
+                // p2tr
+                let output_key = public_key
+                    .to_taproot_output_key()
+                    .expect("Public key incorrect");
+                SegwitBitcoinAddress::to_p2tr_tx_out(&output_key, value)
+            } else if self.config.miner.segwit && epoch_id >= StacksEpochId::Epoch21 {
                 // p2wpkh
                 public_key.set_compressed(true);
                 let change_address_hash = Hash160::from_data(&public_key.to_bytes());
@@ -1862,13 +2178,35 @@ impl BitcoinRegtestController {
             };
             tx.input.push(input);
         }
+        let prevouts: Vec<_> = utxos_set
+            .utxos
+            .iter()
+            .map(|utxo| TxOut {
+                value: utxo.amount,
+                script_pubkey: utxo.script_pub_key.clone(),
+            })
+            .collect();
         for (i, utxo) in utxos_set.utxos.iter().enumerate() {
             let script_pub_key = utxo.script_pub_key.clone();
             let sig_hash_all = 0x01;
 
-            let (sig_hash, is_segwit) = if script_pub_key.as_bytes().len() == 22
-                && script_pub_key.as_bytes()[0..2] == [0x00, 0x14]
-            {
+            if self.config.miner.psbt_signing {
+                set_placeholder_signature(&mut tx.input[i], &script_pub_key, &public_key);
+                continue;
+            }
+
+            if is_p2tr_script(&script_pub_key) {
+                // p2tr key path
+                let sighash = tx.taproot_key_spend_signature_hash(i, &prevouts);
+                let sig = signer
+                    .sign_taproot(sighash.as_bytes())
+                    .expect("Unable to sign message");
+                tx.input[i].script_sig = Script::from(vec![]);
+                tx.input[i].witness = vec![sig.to_vec()];
+                continue;
+            }
+
+            let (sig_hash, is_segwit) = if is_p2wpkh_script(&script_pub_key) {
                 // p2wpkh
                 (
                     tx.segwit_signature_hash(i, &script_pub_key, utxo.amount, sig_hash_all),
@@ -1918,7 +2256,8 @@ impl BitcoinRegtestController {
     ) -> Result<Txid, BurnchainControllerError> {
         debug!("Sending raw transaction: {}", transaction.to_hex());
 
-        BitcoinRPCRequest::send_raw_transaction(&self.config, transaction.to_hex())
+        self.rpc_client()
+            .send_raw_transaction(transaction.to_hex())
             .map(|_| {
                 debug!("Transaction {} sent successfully", &transaction.txid());
                 transaction.txid()
@@ -2087,6 +2426,41 @@ impl BitcoinRegtestController {
         transaction.map(SerializedTx::new)
     }
 
+    /// A `BurnOpBuilder` that lays out operations the way this controller does in `epoch_id`
+    pub fn burn_op_builder(&self, epoch_id: StacksEpochId) -> BurnOpBuilder {
+        BurnOpBuilder::new(self.config.burnchain.magic_bytes.clone())
+            .with_segwit_change(self.config.miner.segwit && epoch_id >= StacksEpochId::Epoch21)
+    }
+
+    /// UTXOs of `public_key` worth at least `total_required`, for a `BurnOpBuilder`.  They are in
+    /// the order the controller spends them: least-confirmed first, then smallest first.
+    pub fn get_burn_op_utxos(
+        &self,
+        epoch_id: StacksEpochId,
+        public_key: &Secp256k1PublicKey,
+        total_required: u64,
+    ) -> Option<Vec<OpUtxo>> {
+        let mut utxos = self
+            .get_utxos(epoch_id, public_key, total_required, None, 0)?
+            .utxos;
+        utxos.sort_by(|u1, u2| {
+            u1.confirmations
+                .cmp(&u2.confirmations)
+                .then(u1.amount.cmp(&u2.amount))
+        });
+        Some(
+            utxos
+                .into_iter()
+                .map(|utxo| OpUtxo {
+                    txid: utxo.txid,
+                    vout: utxo.vout,
+                    script_pub_key: utxo.script_pub_key,
+                    amount: utxo.amount,
+                })
+                .collect(),
+        )
+    }
+
     #[cfg(test)]
     pub fn get_raw_transaction(&self, txid: &Txid) -> Transaction {
         let txstr = BitcoinRPCRequest::get_raw_transaction(&self.config, txid).unwrap();
@@ -2251,8 +2625,13 @@ impl BurnchainController for BitcoinRegtestController {
         op_signer: &mut BurnchainOpSigner,
         attempt: u64,
     ) -> Result<Txid, BurnchainControllerError> {
+        self.check_bitcoind_failover();
         let transaction = self.make_operation_tx(epoch_id, operation, op_signer, attempt)?;
-        self.send_transaction(transaction)
+        let res = self.send_transaction(transaction);
+        if res.is_err() {
+            self.schedule_bitcoind_health_check();
+        }
+        res
     }
 
     #[cfg(test)]
@@ -2273,10 +2652,17 @@ impl BurnchainController for BitcoinRegtestController {
     }
 }
 
+impl BurnOpBroadcaster for BitcoinRegtestController {
+    fn broadcast(&self, op: &SignedBurnOp) -> Result<Txid, BurnOpError> {
+        self.send_transaction(SerializedTx::new(op.tx.clone()))
+            .map_err(|e| BurnOpError::BroadcastError(e.to_string()))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct UTXOSet {
-    bhh: BurnchainHeaderHash,
-    utxos: Vec<UTXO>,
+    pub(crate) bhh: BurnchainHeaderHash,
+    pub(crate) utxos: Vec<UTXO>,
 }
 
 impl UTXOSet {
@@ -2424,7 +2810,7 @@ pub enum RPCError {
     Bitcoind(String),
 }
 
-type RPCResult<T> = Result<T, RPCError>;
+pub type RPCResult<T> = Result<T, RPCError>;
 
 impl From<io::Error> for RPCError {
     fn from(ioe: io::Error) -> Self {
@@ -2443,7 +2829,9 @@ impl BitcoinRPCRequest {
         let url = {
             // some methods require a wallet ID
             let wallet_id = match payload.method.as_str() {
-                "importaddress" | "listunspent" => Some(config.burnchain.wallet_name.clone()),
+                "importaddress" | "importdescriptors" | "listunspent" | "walletprocesspsbt" => {
+                    Some(config.burnchain.wallet_name.clone())
+                }
                 _ => None,
             };
             let url = config.burnchain.get_rpc_url(wallet_id);
@@ -2524,6 +2912,79 @@ impl BitcoinRPCRequest {
         Ok(confirmations >= 1)
     }
 
+    /// Get the fee rate, in sats/vB, that a transaction in bitcoind's mempool pays.  Returns
+    /// `None` if the transaction isn't in the mempool (it may have been mined, replaced, or
+    /// evicted).
+    pub fn get_mempool_fee_rate(config: &Config, txid: &Txid) -> RPCResult<Option<u64>> {
+        let payload = BitcoinRPCRequest {
+            method: "getmempoolentry".to_string(),
+            params: vec![format!("{txid}").into()],
+            id: "stacks".to_string(),
+            jsonrpc: "2.0".to_string(),
+        };
+        let res = BitcoinRPCRequest::send(config, payload)?;
+        let entry = match res.get("result") {
+            Some(entry) if !entry.is_null() => entry,
+            _ => return Ok(None),
+        };
+        let vsize = entry
+            .get("vsize")
+            .and_then(|vsize| vsize.as_u64())
+            .filter(|vsize| *vsize > 0)
+            .ok_or_else(|| {
+                RPCError::Parsing("No numeric 'vsize' field in getmempoolentry response".into())
+            })?;
+        let fee = entry
+            .get("fees")
+            .and_then(|fees| fees.get("base"))
+            .and_then(|base| ParsedUTXO::serialized_btc_to_sat(&base.to_string()))
+            .ok_or_else(|| {
+                RPCError::Parsing("No 'fees.base' amount in getmempoolentry response".into())
+            })?;
+        Ok(Some(fee / vsize))
+    }
+
+    /// Get the fee rate, in sats/vB, that bitcoind estimates a transaction must pay to be mined
+    /// in the next block.  Returns `None` if bitcoind has too little data to estimate it.
+    /// The height and hash of the bitcoind's best block
+    pub fn get_best_block(config: &Config) -> RPCResult<(u64, String)> {
+        let payload = BitcoinRPCRequest {
+            method: "getblockchaininfo".to_string(),
+            params: vec![],
+            id: "stacks".to_string(),
+            jsonrpc: "2.0".to_string(),
+        };
+        let res = BitcoinRPCRequest::send(config, payload)?;
+        let result = res
+            .get("result")
+            .ok_or_else(|| RPCError::Parsing("Failed to get result".into()))?;
+        let block_height = result
+            .get("blocks")
+            .and_then(|blocks| blocks.as_u64())
+            .ok_or_else(|| RPCError::Parsing("Failed to get block height".into()))?;
+        let best_block_hash = result
+            .get("bestblockhash")
+            .and_then(|hash| hash.as_str())
+            .ok_or_else(|| RPCError::Parsing("Failed to get best block hash".into()))?;
+        Ok((block_height, best_block_hash.to_string()))
+    }
+
+    pub fn estimate_next_block_fee_rate(config: &Config) -> RPCResult<Option<u64>> {
+        let payload = BitcoinRPCRequest {
+            method: "estimatesmartfee".to_string(),
+            params: vec![1.into()],
+            id: "stacks".to_string(),
+            jsonrpc: "2.0".to_string(),
+        };
+        let res = BitcoinRPCRequest::send(config, payload)?;
+        // the estimate is in BTC/kvB
+        let sats_per_kvb = res
+            .get("result")
+            .and_then(|result| result.get("feerate"))
+            .and_then(|feerate| ParsedUTXO::serialized_btc_to_sat(&feerate.to_string()));
+        Ok(sats_per_kvb.map(|sats_per_kvb| sats_per_kvb.saturating_add(999) / 1000))
+    }
+
     pub fn generate_to_address(config: &Config, num_blocks: u64, address: String) -> RPCResult<()> {
         debug!("Generate {num_blocks} blocks to {address}");
         let payload = BitcoinRPCRequest {
@@ -2672,37 +3133,30 @@ impl BitcoinRPCRequest {
         Ok(())
     }
 
-    pub fn import_public_key(config: &Config, public_key: &Secp256k1PublicKey) -> RPCResult<()> {
-        let pkh = Hash160::from_data(&public_key.to_bytes())
-            .to_bytes()
-            .to_vec();
-        let (_, network_id) = config.burnchain.get_bitcoin_network();
-
-        // import both the legacy and segwit variants of this public key
-        let mut addresses = vec![BitcoinAddress::from_bytes_legacy(
-            network_id,
-            LegacyBitcoinAddressType::PublicKeyHash,
-            &pkh,
-        )
-        .expect("Public key incorrect")];
-
+    /// The output descriptors of the addresses that the miner may use for `public_key`
+    pub fn miner_descriptors(config: &Config, public_key: &Secp256k1PublicKey) -> Vec<String> {
+        let mut descriptors = vec![format!("pkh({})", public_key.to_hex())];
         if config.miner.segwit {
-            addresses.push(
-                BitcoinAddress::from_bytes_segwit_p2wpkh(network_id, &pkh)
-                    .expect("Public key incorrect"),
-            );
+            descriptors.push(format!(
+                "wpkh({})",
+                to_hex(&public_key.to_bytes_compressed())
+            ));
+        }
+        if config.miner.taproot {
+            descriptors.push(format!("tr({})", to_hex(&public_key.to_x_only_bytes())));
         }
+        descriptors
+    }
 
-        for address in addresses.into_iter() {
-            debug!(
-                "Import address {} for public key {}",
-                addr2str(&address),
-                public_key.to_hex()
-            );
+    /// Import the key descriptors of the miner's addresses for `public_key` into the wallet, so
+    /// that it tracks their UTXOs and knows which key spends them
+    pub fn import_public_key(config: &Config, public_key: &Secp256k1PublicKey) -> RPCResult<()> {
+        for descriptor in BitcoinRPCRequest::miner_descriptors(config, public_key).into_iter() {
+            debug!("Import descriptor {descriptor}");
 
             let payload = BitcoinRPCRequest {
                 method: "getdescriptorinfo".to_string(),
-                params: vec![format!("addr({})", &addr2str(&address)).into()],
+                params: vec![descriptor.clone().into()],
                 id: "stacks".to_string(),
                 jsonrpc: "2.0".to_string(),
             };
@@ -2714,14 +3168,13 @@ impl BitcoinRPCRequest {
                 .and_then(|obj| obj.get("checksum"))
                 .and_then(|checksum_val| checksum_val.as_str())
                 .ok_or(RPCError::Bitcoind(format!(
-                    "Did not receive an object with `checksum` from `getdescriptorinfo \"{}\"`",
-                    &addr2str(&address)
+                    "Did not receive an object with `checksum` from `getdescriptorinfo \"{descriptor}\"`"
                 )))?;
 
             let payload = BitcoinRPCRequest {
                 method: "importdescriptors".to_string(),
                 params: vec![
-                    json!([{ "desc": format!("addr({})#{checksum}", &addr2str(&address)), "timestamp": 0, "internal": true }]),
+                    json!([{ "desc": format!("{descriptor}#{checksum}"), "timestamp": 0, "internal": true }]),
                 ],
                 id: "stacks".to_string(),
                 jsonrpc: "2.0".to_string(),
@@ -2732,6 +3185,61 @@ impl BitcoinRPCRequest {
         Ok(())
     }
 
+    /// Have the wallet sign a transaction, by way of a PSBT: the wallet adds what it knows about
+    /// the inputs and signs those it can (itself, or with its external signer), then the
+    /// finalized transaction is extracted.  Any signatures already in `tx_hex` are discarded.
+    /// Returns the signed transaction's hex.
+    pub fn sign_with_wallet(config: &Config, tx_hex: &str) -> RPCResult<String> {
+        let get_result = |res: serde_json::Value, method: &str| -> RPCResult<serde_json::Value> {
+            match res.get("error") {
+                Some(e) if !e.is_null() => Err(RPCError::Bitcoind(format!("{method}: {e}"))),
+                _ => res.get("result").cloned().ok_or_else(|| {
+                    RPCError::Parsing(format!("No 'result' field in {method} response"))
+                }),
+            }
+        };
+
+        let payload = BitcoinRPCRequest {
+            method: "converttopsbt".to_string(),
+            params: vec![tx_hex.into(), true.into()],
+            id: "stacks".to_string(),
+            jsonrpc: "2.0".to_string(),
+        };
+        let psbt = get_result(BitcoinRPCRequest::send(config, payload)?, "converttopsbt")?;
+
+        let payload = BitcoinRPCRequest {
+            method: "walletprocesspsbt".to_string(),
+            params: vec![psbt, true.into(), "DEFAULT".into()],
+            id: "stacks".to_string(),
+            jsonrpc: "2.0".to_string(),
+        };
+        let processed = get_result(
+            BitcoinRPCRequest::send(config, payload)?,
+            "walletprocesspsbt",
+        )?;
+        let psbt = processed.get("psbt").cloned().ok_or_else(|| {
+            RPCError::Parsing("No 'psbt' field in walletprocesspsbt response".into())
+        })?;
+
+        let payload = BitcoinRPCRequest {
+            method: "finalizepsbt".to_string(),
+            params: vec![psbt, true.into()],
+            id: "stacks".to_string(),
+            jsonrpc: "2.0".to_string(),
+        };
+        let finalized = get_result(BitcoinRPCRequest::send(config, payload)?, "finalizepsbt")?;
+        if finalized.get("complete").and_then(|c| c.as_bool()) != Some(true) {
+            return Err(RPCError::Bitcoind(
+                "The wallet could not sign all of the transaction's inputs".into(),
+            ));
+        }
+        finalized
+            .get("hex")
+            .and_then(|hex| hex.as_str())
+            .map(|hex| hex.to_string())
+            .ok_or_else(|| RPCError::Parsing("No 'hex' field in finalizepsbt response".into()))
+    }
+
     /// Calls `listwallets` method through RPC call and returns wallet names as a vector of Strings
     pub fn list_wallets(config: &Config) -> RPCResult<Vec<String>> {
         let payload = BitcoinRPCRequest {
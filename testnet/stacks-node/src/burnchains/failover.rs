@@ -0,0 +1,314 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Failover between bitcoinds.  If `burnchain.failover_nodes` are configured, the burnchain
+//! controller periodically asks every bitcoind for its best block, and uses the most preferred
+//! one that is reachable and not behind the others.  Bitcoinds that report different blocks at
+//! the same height are on different forks (or one of them is misbehaving), which the operator is
+//! alerted to, since following either one may be wrong.
+
+use std::time::{Duration, Instant};
+
+use stacks::monitoring::{
+    increment_bitcoind_divergence_counter, increment_bitcoind_failover_counter,
+    update_bitcoind_healthy_count,
+};
+
+use super::bitcoin_regtest_controller::BitcoinRPCRequest;
+use crate::config::{BitcoindEndpoint, Config};
+
+/// What a health check found out about a bitcoind
+#[derive(Debug, Clone, PartialEq)]
+pub enum BitcoindHealth {
+    /// The bitcoind did not answer, or answered with an error
+    Unreachable(String),
+    /// The bitcoind's best block
+    Healthy {
+        block_height: u64,
+        best_block_hash: String,
+    },
+}
+
+impl BitcoindHealth {
+    fn block_height(&self) -> Option<u64> {
+        match self {
+            BitcoindHealth::Healthy { block_height, .. } => Some(*block_height),
+            BitcoindHealth::Unreachable(_) => None,
+        }
+    }
+}
+
+/// The index of the first bitcoind in `health` that is healthy and at most `max_lag` blocks
+/// behind the most advanced one, if any
+pub fn choose_bitcoind(health: &[BitcoindHealth], max_lag: u64) -> Option<usize> {
+    let max_height = health.iter().filter_map(|h| h.block_height()).max()?;
+    health.iter().position(|h| {
+        matches!(h.block_height(), Some(height) if height.saturating_add(max_lag) >= max_height)
+    })
+}
+
+/// Find two bitcoinds in `health` that report different best blocks at the same height
+pub fn find_divergence(health: &[BitcoindHealth]) -> Option<(usize, usize)> {
+    for (i, a) in health.iter().enumerate() {
+        let BitcoindHealth::Healthy {
+            block_height: height_a,
+            best_block_hash: hash_a,
+        } = a
+        else {
+            continue;
+        };
+        for (j, b) in health.iter().enumerate().skip(i + 1) {
+            let BitcoindHealth::Healthy {
+                block_height: height_b,
+                best_block_hash: hash_b,
+            } = b
+            else {
+                continue;
+            };
+            if height_a == height_b && hash_a != hash_b {
+                return Some((i, j));
+            }
+        }
+    }
+    None
+}
+
+/// Tracks which of the configured bitcoinds the node uses
+pub struct BitcoindFailover {
+    /// The primary bitcoind, then the failover nodes
+    endpoints: Vec<BitcoindEndpoint>,
+    /// The index of the bitcoind in use
+    active: usize,
+    next_check: Instant,
+    interval: Duration,
+    max_lag: u64,
+}
+
+impl BitcoindFailover {
+    /// Track the bitcoinds in `config`, or `None` if there are no failover nodes
+    pub fn new(config: &Config) -> Option<BitcoindFailover> {
+        if config.burnchain.failover_nodes.is_empty() {
+            return None;
+        }
+        let mut endpoints = vec![BitcoindEndpoint::from_burnchain_config(&config.burnchain)];
+        endpoints.extend(config.burnchain.failover_nodes.iter().cloned());
+        Some(BitcoindFailover {
+            endpoints,
+            active: 0,
+            next_check: Instant::now(),
+            interval: Duration::from_millis(config.burnchain.health_check_interval_ms),
+            max_lag: config.burnchain.failover_max_lag,
+        })
+    }
+
+    /// The bitcoind in use
+    pub fn active(&self) -> &BitcoindEndpoint {
+        &self.endpoints[self.active]
+    }
+
+    /// Check the bitcoinds' health on the next call to `check()`, e.g. because the one in use
+    /// just failed
+    pub fn check_now(&mut self) {
+        self.next_check = Instant::now();
+    }
+
+    /// If a health check is due, check every bitcoind's health.  Returns the bitcoind to switch
+    /// to, if the node should stop using the current one.
+    pub fn check(&mut self, config: &Config) -> Option<BitcoindEndpoint> {
+        self.check_with(|endpoint| probe_bitcoind(config, endpoint))
+    }
+
+    fn check_with<F>(&mut self, probe: F) -> Option<BitcoindEndpoint>
+    where
+        F: Fn(&BitcoindEndpoint) -> BitcoindHealth,
+    {
+        if Instant::now() < self.next_check {
+            return None;
+        }
+        self.next_check = Instant::now() + self.interval;
+
+        let health: Vec<_> = self.endpoints.iter().map(probe).collect();
+        let num_healthy = health.iter().filter(|h| h.block_height().is_some()).count();
+        update_bitcoind_healthy_count(i64::try_from(num_healthy).unwrap_or(i64::MAX));
+        for (endpoint, health) in self.endpoints.iter().zip(health.iter()) {
+            if let BitcoindHealth::Unreachable(e) = health {
+                warn!("Bitcoind health check failed"; "bitcoind" => %endpoint, "error" => e);
+            }
+        }
+
+        if let Some((i, j)) = find_divergence(&health) {
+            increment_bitcoind_divergence_counter();
+            error!(
+                "Bitcoinds report different blocks at the same height. Check whether they are on the same fork.";
+                "bitcoind" => %self.endpoints[i],
+                "best_block" => ?health[i],
+                "other_bitcoind" => %self.endpoints[j],
+                "other_best_block" => ?health[j],
+            );
+        }
+
+        let Some(chosen) = choose_bitcoind(&health, self.max_lag) else {
+            error!("No configured bitcoind is reachable"; "num_bitcoinds" => self.endpoints.len());
+            return None;
+        };
+        if chosen == self.active {
+            return None;
+        }
+        increment_bitcoind_failover_counter();
+        warn!(
+            "Switching bitcoind";
+            "from" => %self.endpoints[self.active],
+            "from_health" => ?health[self.active],
+            "to" => %self.endpoints[chosen],
+            "to_health" => ?health[chosen],
+        );
+        self.active = chosen;
+        Some(self.endpoints[chosen].clone())
+    }
+}
+
+/// Ask `endpoint` for its best block
+fn probe_bitcoind(config: &Config, endpoint: &BitcoindEndpoint) -> BitcoindHealth {
+    let mut probe_config = config.clone();
+    endpoint.apply(&mut probe_config.burnchain);
+    match BitcoinRPCRequest::get_best_block(&probe_config) {
+        Ok((block_height, best_block_hash)) => BitcoindHealth::Healthy {
+            block_height,
+            best_block_hash,
+        },
+        Err(e) => BitcoindHealth::Unreachable(format!("{e:?}")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn healthy(block_height: u64, best_block_hash: &str) -> BitcoindHealth {
+        BitcoindHealth::Healthy {
+            block_height,
+            best_block_hash: best_block_hash.to_string(),
+        }
+    }
+
+    fn unreachable() -> BitcoindHealth {
+        BitcoindHealth::Unreachable("connection refused".into())
+    }
+
+    #[test]
+    fn test_choose_bitcoind() {
+        assert_eq!(choose_bitcoind(&[], 2), None);
+        assert_eq!(choose_bitcoind(&[unreachable(), unreachable()], 2), None);
+        assert_eq!(
+            choose_bitcoind(&[healthy(100, "a"), healthy(101, "b")], 2),
+            Some(0)
+        );
+        assert_eq!(
+            choose_bitcoind(&[unreachable(), healthy(101, "b")], 2),
+            Some(1)
+        );
+        // a lagging bitcoind is passed over
+        assert_eq!(
+            choose_bitcoind(&[healthy(98, "a"), healthy(101, "b")], 2),
+            Some(1)
+        );
+        assert_eq!(
+            choose_bitcoind(&[healthy(98, "a"), healthy(101, "b")], 3),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_find_divergence() {
+        assert_eq!(
+            find_divergence(&[healthy(100, "a"), healthy(100, "a")]),
+            None
+        );
+        assert_eq!(
+            find_divergence(&[healthy(100, "a"), healthy(101, "b")]),
+            None
+        );
+        assert_eq!(
+            find_divergence(&[healthy(100, "a"), unreachable(), healthy(100, "b")]),
+            Some((0, 2))
+        );
+    }
+
+    #[test]
+    fn test_failover() {
+        let mut config = Config::default();
+        assert!(BitcoindFailover::new(&config).is_none());
+
+        config.burnchain.peer_host = "10.0.0.1".into();
+        config.burnchain.health_check_interval_ms = 0;
+        let mut backup = BitcoindEndpoint::from_burnchain_config(&config.burnchain);
+        backup.peer_host = "10.0.0.2".into();
+        config.burnchain.failover_nodes = vec![backup.clone()];
+        let primary = BitcoindEndpoint::from_burnchain_config(&config.burnchain);
+
+        let mut failover = BitcoindFailover::new(&config).unwrap();
+        assert_eq!(failover.active(), &primary);
+
+        let probe_with = |health: HashMap<String, BitcoindHealth>| {
+            move |endpoint: &BitcoindEndpoint| health[&endpoint.peer_host].clone()
+        };
+
+        // both healthy: stay on the primary
+        let both_healthy = HashMap::from([
+            ("10.0.0.1".to_string(), healthy(100, "a")),
+            ("10.0.0.2".to_string(), healthy(100, "a")),
+        ]);
+        assert_eq!(failover.check_with(probe_with(both_healthy.clone())), None);
+
+        // the primary goes down
+        let primary_down = HashMap::from([
+            ("10.0.0.1".to_string(), unreachable()),
+            ("10.0.0.2".to_string(), healthy(101, "b")),
+        ]);
+        assert_eq!(
+            failover.check_with(probe_with(primary_down.clone())),
+            Some(backup.clone())
+        );
+        assert_eq!(failover.active(), &backup);
+        assert_eq!(failover.check_with(probe_with(primary_down)), None);
+
+        // everything goes down: keep the current one
+        let all_down = HashMap::from([
+            ("10.0.0.1".to_string(), unreachable()),
+            ("10.0.0.2".to_string(), unreachable()),
+        ]);
+        assert_eq!(failover.check_with(probe_with(all_down)), None);
+        assert_eq!(failover.active(), &backup);
+
+        // the primary comes back
+        assert_eq!(
+            failover.check_with(probe_with(both_healthy.clone())),
+            Some(primary.clone())
+        );
+
+        // checks are rate-limited
+        failover.interval = Duration::from_secs(3600);
+        let primary_down = HashMap::from([
+            ("10.0.0.1".to_string(), unreachable()),
+            ("10.0.0.2".to_string(), healthy(101, "b")),
+        ]);
+        assert_eq!(failover.check_with(probe_with(both_healthy)), None);
+        assert_eq!(failover.check_with(probe_with(primary_down.clone())), None);
+        failover.check_now();
+        assert_eq!(failover.check_with(probe_with(primary_down)), Some(backup));
+    }
+}
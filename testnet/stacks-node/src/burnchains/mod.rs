@@ -1,5 +1,8 @@
 pub mod bitcoin_regtest_controller;
+pub mod failover;
 pub mod mocknet_controller;
+pub mod rpc_client;
+pub mod zmq;
 
 use std::time::Instant;
 
@@ -31,6 +34,8 @@ pub enum Error {
     NoUTXOs,
     #[error("Transaction submission failed: {0}")]
     TransactionSubmissionFailed(String),
+    #[error("Transaction signing failed: {0}")]
+    SigningFailed(String),
     #[error("Serializer error: {0}")]
     SerializerError(CodecError),
 }
@@ -0,0 +1,323 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The RPC interfaces that a miner can use to find its UTXOs and the fate of its transactions.
+//! bitcoind's wallet needs an unpruned chain to rescan when the miner's addresses are imported,
+//! so a pruned node is used through its UTXO set and its BIP158 block filters instead.
+
+use serde_json::json;
+use stacks::burnchains::bitcoin::blockfilter::BlockFilter;
+use stacks::burnchains::Txid;
+use stacks_common::deps_common::bitcoin::blockdata::script::Script;
+use stacks_common::deps_common::bitcoin::util::hash::Sha256dHash;
+use stacks_common::types::chainstate::BurnchainHeaderHash;
+use stacks_common::util::hash::hex_bytes;
+use stacks_common::util::secp256k1::Secp256k1PublicKey;
+
+use super::bitcoin_regtest_controller::{
+    BitcoinRPCRequest, ParsedUTXO, RPCError, RPCResult, UTXOSet, UTXO,
+};
+use crate::config::{BurnchainRpcBackend, Config};
+
+/// The RPC calls that the miner makes to find its UTXOs and the fate of its transactions
+pub trait BurnchainRpcClient {
+    /// Make sure that the configured wallet exists, if this client uses one
+    fn create_wallet_if_dne(&self) -> RPCResult<()>;
+
+    /// Start tracking the addresses of `public_key`, if this client needs to be told about them
+    fn import_public_key(&self, public_key: &Secp256k1PublicKey) -> RPCResult<()>;
+
+    /// Get the UTXOs of `addresses` worth at least `minimum_sum_amount` each, other than those
+    /// created by the transactions of `utxos_to_exclude`, along with the hash of the burnchain
+    /// block at `block_height`
+    fn list_unspent(
+        &self,
+        addresses: Vec<String>,
+        include_unsafe: bool,
+        minimum_sum_amount: u64,
+        utxos_to_exclude: &Option<UTXOSet>,
+        block_height: u64,
+    ) -> RPCResult<UTXOSet>;
+
+    /// Was the transaction `txid`, which spends outputs with the scripts `spent_scripts`,
+    /// confirmed by the burnchain?
+    fn check_transaction_confirmed(&self, txid: &Txid, spent_scripts: &[Script])
+        -> RPCResult<bool>;
+
+    /// Broadcast the hex-encoded transaction `tx`
+    fn send_raw_transaction(&self, tx: String) -> RPCResult<()>;
+}
+
+/// Make the RPC client that `config` selects
+pub fn make_burnchain_rpc_client(config: &Config) -> Box<dyn BurnchainRpcClient + '_> {
+    match config.burnchain.rpc_backend {
+        BurnchainRpcBackend::Bitcoind => Box::new(BitcoindRpcClient::new(config)),
+        BurnchainRpcBackend::BlockFilters => Box::new(BlockFilterRpcClient::new(config)),
+    }
+}
+
+/// A client of bitcoind's wallet
+pub struct BitcoindRpcClient<'a> {
+    config: &'a Config,
+}
+
+impl<'a> BitcoindRpcClient<'a> {
+    pub fn new(config: &'a Config) -> Self {
+        Self { config }
+    }
+}
+
+impl BurnchainRpcClient for BitcoindRpcClient<'_> {
+    fn create_wallet_if_dne(&self) -> RPCResult<()> {
+        let wallets = BitcoinRPCRequest::list_wallets(self.config)?;
+
+        if !wallets.contains(&self.config.burnchain.wallet_name) {
+            BitcoinRPCRequest::create_wallet(self.config, &self.config.burnchain.wallet_name)?;
+        }
+        Ok(())
+    }
+
+    fn import_public_key(&self, public_key: &Secp256k1PublicKey) -> RPCResult<()> {
+        BitcoinRPCRequest::import_public_key(self.config, public_key)
+    }
+
+    fn list_unspent(
+        &self,
+        addresses: Vec<String>,
+        include_unsafe: bool,
+        minimum_sum_amount: u64,
+        utxos_to_exclude: &Option<UTXOSet>,
+        block_height: u64,
+    ) -> RPCResult<UTXOSet> {
+        BitcoinRPCRequest::list_unspent(
+            self.config,
+            addresses,
+            include_unsafe,
+            minimum_sum_amount,
+            utxos_to_exclude,
+            block_height,
+        )
+    }
+
+    fn check_transaction_confirmed(
+        &self,
+        txid: &Txid,
+        _spent_scripts: &[Script],
+    ) -> RPCResult<bool> {
+        BitcoinRPCRequest::check_transaction_confirmed(self.config, txid)
+    }
+
+    fn send_raw_transaction(&self, tx: String) -> RPCResult<()> {
+        BitcoinRPCRequest::send_raw_transaction(self.config, tx)
+    }
+}
+
+/// A client of a (possibly pruned) bitcoind's UTXO set and BIP158 block filters.  It needs no
+/// wallet, but bitcoind must be started with `-blockfilterindex=1`.
+pub struct BlockFilterRpcClient<'a> {
+    config: &'a Config,
+}
+
+impl<'a> BlockFilterRpcClient<'a> {
+    pub fn new(config: &'a Config) -> Self {
+        Self { config }
+    }
+
+    /// Make a JSON-RPC call, and get its `result`
+    fn call(&self, method: &str, params: Vec<serde_json::Value>) -> RPCResult<serde_json::Value> {
+        let payload = BitcoinRPCRequest {
+            method: method.to_string(),
+            params,
+            id: "stacks".to_string(),
+            jsonrpc: "2.0".to_string(),
+        };
+        let mut res = BitcoinRPCRequest::send(self.config, payload)?;
+        if let Some(e) = res.get("error") {
+            if !e.is_null() {
+                return Err(RPCError::Bitcoind(format!("{method} failed: {e}")));
+            }
+        }
+        res.get_mut("result")
+            .map(serde_json::Value::take)
+            .ok_or_else(|| RPCError::Parsing(format!("No 'result' field in {method} response")))
+    }
+
+    fn get_block_hash(&self, height: u64) -> RPCResult<String> {
+        self.call("getblockhash", vec![height.into()])?
+            .as_str()
+            .map(String::from)
+            .ok_or_else(|| RPCError::Parsing("Expected a block hash from getblockhash".into()))
+    }
+
+    fn get_block_filter(&self, block_hash: &str) -> RPCResult<BlockFilter> {
+        let res = self.call("getblockfilter", vec![block_hash.into(), "basic".into()])?;
+        let filter_bytes = res
+            .get("filter")
+            .and_then(|filter| filter.as_str())
+            .and_then(|filter| hex_bytes(filter).ok())
+            .ok_or_else(|| RPCError::Parsing("Expected a hex filter from getblockfilter".into()))?;
+        let block_hash = Sha256dHash::from_hex(block_hash)
+            .map_err(|e| RPCError::Parsing(format!("Bad block hash {block_hash}: {e:?}")))?;
+        BlockFilter::from_bytes(&block_hash, &filter_bytes)
+            .map_err(|e| RPCError::Parsing(format!("Bad block filter: {e}")))
+    }
+}
+
+impl BurnchainRpcClient for BlockFilterRpcClient<'_> {
+    fn create_wallet_if_dne(&self) -> RPCResult<()> {
+        Ok(())
+    }
+
+    fn import_public_key(&self, _public_key: &Secp256k1PublicKey) -> RPCResult<()> {
+        Ok(())
+    }
+
+    /// Scan the UTXO set for the UTXOs of `addresses`.  The UTXO set only holds confirmed
+    /// outputs, so `include_unsafe` has no effect.
+    fn list_unspent(
+        &self,
+        addresses: Vec<String>,
+        _include_unsafe: bool,
+        minimum_sum_amount: u64,
+        utxos_to_exclude: &Option<UTXOSet>,
+        block_height: u64,
+    ) -> RPCResult<UTXOSet> {
+        let bhh_string = self.get_block_hash(block_height)?;
+        let bhh = BurnchainHeaderHash::from_hex(&bhh_string)
+            .map_err(|_| RPCError::Parsing("Failed to get bestblockhash".to_string()))?;
+
+        let descriptors: Vec<_> = addresses
+            .iter()
+            .map(|address| json!({ "desc": format!("addr({address})") }))
+            .collect();
+        let res = self.call("scantxoutset", vec!["start".into(), descriptors.into()])?;
+        let scan_height = res
+            .get("height")
+            .and_then(|height| height.as_u64())
+            .ok_or_else(|| {
+                RPCError::Parsing("No 'height' field in scantxoutset response".into())
+            })?;
+        let entries = res
+            .get("unspents")
+            .and_then(|unspents| unspents.as_array())
+            .ok_or_else(|| {
+                RPCError::Parsing("No 'unspents' field in scantxoutset response".into())
+            })?;
+
+        let txids_to_filter: Vec<_> = utxos_to_exclude
+            .as_ref()
+            .map(|utxos| utxos.utxos.iter().map(|utxo| utxo.txid).collect())
+            .unwrap_or_default();
+        let max_utxos = self
+            .config
+            .burnchain
+            .max_unspent_utxos
+            .and_then(|max| usize::try_from(max).ok())
+            .unwrap_or(usize::MAX);
+
+        let mut utxos = vec![];
+        for entry in entries.iter() {
+            let (Some(txid), Some(vout), Some(script_pub_key), Some(amount), Some(height)) = (
+                entry.get("txid").and_then(|txid| txid.as_str()),
+                entry.get("vout").and_then(|vout| vout.as_u64()),
+                entry.get("scriptPubKey").and_then(|script| script.as_str()),
+                entry.get("amount"),
+                entry.get("height").and_then(|height| height.as_u64()),
+            ) else {
+                warn!("Failed parsing UTXO: {entry}");
+                continue;
+            };
+            let Some(amount) = ParsedUTXO::serialized_btc_to_sat(&amount.to_string()) else {
+                continue;
+            };
+            if amount < minimum_sum_amount {
+                continue;
+            }
+            let (Ok(txid), Ok(vout), Ok(script_pub_key)) = (
+                Sha256dHash::from_hex(txid),
+                u32::try_from(vout),
+                hex_bytes(script_pub_key),
+            ) else {
+                warn!("Failed parsing UTXO: {entry}");
+                continue;
+            };
+            // Exclude UTXOs that we want to filter
+            if txids_to_filter.contains(&txid) {
+                continue;
+            }
+            utxos.push(UTXO {
+                txid,
+                vout,
+                script_pub_key: script_pub_key.into(),
+                amount,
+                confirmations: u32::try_from(scan_height.saturating_sub(height) + 1)
+                    .unwrap_or(u32::MAX),
+            });
+            if utxos.len() >= max_utxos {
+                break;
+            }
+        }
+
+        Ok(UTXOSet { bhh, utxos })
+    }
+
+    /// Search the most recent `block_filter_scan_depth` blocks for the transaction.  Only the
+    /// blocks whose filters match `spent_scripts` are fetched.
+    fn check_transaction_confirmed(
+        &self,
+        txid: &Txid,
+        spent_scripts: &[Script],
+    ) -> RPCResult<bool> {
+        let scripts: Vec<&[u8]> = spent_scripts
+            .iter()
+            .map(|script| script.as_bytes())
+            .collect();
+        if scripts.is_empty() {
+            return Ok(false);
+        }
+        let txid = format!("{txid}");
+        let tip_height = self
+            .call("getblockcount", vec![])?
+            .as_u64()
+            .ok_or_else(|| RPCError::Parsing("Expected a height from getblockcount".into()))?;
+        let lowest_height =
+            tip_height.saturating_sub(self.config.burnchain.block_filter_scan_depth);
+        for height in ((lowest_height + 1)..=tip_height).rev() {
+            let block_hash = self.get_block_hash(height)?;
+            let filter = self.get_block_filter(&block_hash)?;
+            let matched = filter
+                .match_any(&scripts)
+                .map_err(|e| RPCError::Parsing(format!("Bad block filter: {e}")))?;
+            if !matched {
+                continue;
+            }
+            let block = self.call("getblock", vec![block_hash.into(), 1.into()])?;
+            let found = block
+                .get("tx")
+                .and_then(|txids| txids.as_array())
+                .ok_or_else(|| RPCError::Parsing("No 'tx' field in getblock response".into()))?
+                .iter()
+                .any(|block_txid| block_txid.as_str() == Some(txid.as_str()));
+            if found {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn send_raw_transaction(&self, tx: String) -> RPCResult<()> {
+        BitcoinRPCRequest::send_raw_transaction(self.config, tx)
+    }
+}
@@ -0,0 +1,480 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! New-block notifications from bitcoind's ZeroMQ interface.  Without them, the run loop only
+//! learns of a new Bitcoin block when it next polls its peer, up to `burnchain.poll_time_secs`
+//! later.  With them, it syncs as soon as bitcoind publishes the block on its `hashblock` or
+//! `rawblock` topic.  The blocks themselves are still downloaded by the burnchain indexer, and
+//! the run loop keeps polling, so a lost or broken ZeroMQ connection only costs latency.
+//!
+//! This is a minimal ZMTP 3.0 SUB client: bitcoind's PUB sockets use the NULL security
+//! mechanism, and the only traffic that a subscriber sends after the handshake is its
+//! subscriptions.
+
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+
+use stacks_common::util::sleep_ms;
+
+/// The bitcoind topics that announce a new block
+pub const ZMQ_BLOCK_TOPICS: &[&str] = &["hashblock", "rawblock"];
+
+/// How long to wait for the ZeroMQ endpoint to accept a connection
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long a read blocks before the listener checks whether it should stop
+const READ_TIMEOUT: Duration = Duration::from_secs(1);
+/// How long to wait before reconnecting to the ZeroMQ endpoint
+const RECONNECT_DELAY_SECS: u64 = 5;
+
+/// The largest frame that the listener accepts.  Raw blocks are at most 4MB.
+const MAX_FRAME_SIZE: u64 = 8 * 1024 * 1024;
+
+const FLAG_MORE: u8 = 0x01;
+const FLAG_LONG: u8 = 0x02;
+const FLAG_COMMAND: u8 = 0x04;
+
+/// A ZMTP frame
+#[derive(Debug, Clone, PartialEq)]
+struct Frame {
+    flags: u8,
+    body: Vec<u8>,
+}
+
+impl Frame {
+    fn new(flags: u8, body: Vec<u8>) -> Frame {
+        Frame { flags, body }
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        if let Ok(len) = u8::try_from(self.body.len()) {
+            bytes.push(self.flags & !FLAG_LONG);
+            bytes.push(len);
+        } else {
+            bytes.push(self.flags | FLAG_LONG);
+            bytes.extend_from_slice(&(self.body.len() as u64).to_be_bytes());
+        }
+        bytes.extend_from_slice(&self.body);
+        bytes
+    }
+
+    /// Parse a frame from the start of `buf`, returning it and its length in bytes, or `None` if
+    /// `buf` doesn't hold a whole frame yet
+    fn parse(buf: &[u8]) -> io::Result<Option<(Frame, usize)>> {
+        let Some(flags) = buf.first().copied() else {
+            return Ok(None);
+        };
+        let (size, header_len) = if flags & FLAG_LONG != 0 {
+            let Some(size_bytes) = buf.get(1..9) else {
+                return Ok(None);
+            };
+            let mut size = [0u8; 8];
+            size.copy_from_slice(size_bytes);
+            (u64::from_be_bytes(size), 9)
+        } else {
+            let Some(size) = buf.get(1) else {
+                return Ok(None);
+            };
+            (u64::from(*size), 2)
+        };
+        if size > MAX_FRAME_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("ZMTP frame of {size} bytes is too big"),
+            ));
+        }
+        let end = header_len + size as usize;
+        let Some(body) = buf.get(header_len..end) else {
+            return Ok(None);
+        };
+        Ok(Some((Frame::new(flags, body.to_vec()), end)))
+    }
+
+    fn is_command(&self) -> bool {
+        self.flags & FLAG_COMMAND != 0
+    }
+
+    fn has_more(&self) -> bool {
+        self.flags & FLAG_MORE != 0
+    }
+}
+
+/// The ZMTP 3.0 greeting of a client that uses the NULL mechanism
+fn greeting() -> [u8; 64] {
+    let mut greeting = [0u8; 64];
+    greeting[0] = 0xff;
+    greeting[9] = 0x7f;
+    // version 3.0
+    greeting[10] = 3;
+    greeting[11] = 0;
+    greeting[12..16].copy_from_slice(b"NULL");
+    // as-server is 0, and the rest is filler
+    greeting
+}
+
+/// The READY command of a socket of type `socket_type`
+fn ready_command(socket_type: &str) -> Frame {
+    let mut body = vec![5];
+    body.extend_from_slice(b"READY");
+    body.push(11);
+    body.extend_from_slice(b"Socket-Type");
+    body.extend_from_slice(&(socket_type.len() as u32).to_be_bytes());
+    body.extend_from_slice(socket_type.as_bytes());
+    Frame::new(FLAG_COMMAND, body)
+}
+
+/// A connection to a ZeroMQ PUB socket
+pub struct ZmqSubscriber {
+    stream: TcpStream,
+    /// Bytes read but not yet parsed into frames
+    buf: Vec<u8>,
+}
+
+impl ZmqSubscriber {
+    /// Connect to the PUB socket at `endpoint` (`tcp://HOST:PORT`), and subscribe to `topics`
+    pub fn connect(endpoint: &str, topics: &[&str]) -> io::Result<ZmqSubscriber> {
+        let addr = endpoint
+            .strip_prefix("tcp://")
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Unsupported ZeroMQ endpoint {endpoint}"),
+                )
+            })?
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("Failed to resolve {endpoint}"),
+                )
+            })?;
+        let stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)?;
+        stream.set_read_timeout(Some(CONNECT_TIMEOUT))?;
+        stream.set_nodelay(true)?;
+        let mut subscriber = ZmqSubscriber {
+            stream,
+            buf: vec![],
+        };
+        subscriber.handshake()?;
+        for topic in topics {
+            // ZMTP 3.0 subscriptions are messages that start with 0x01
+            let mut body = vec![1];
+            body.extend_from_slice(topic.as_bytes());
+            subscriber
+                .stream
+                .write_all(&Frame::new(0, body).serialize())?;
+        }
+        subscriber.stream.set_read_timeout(Some(READ_TIMEOUT))?;
+        Ok(subscriber)
+    }
+
+    fn handshake(&mut self) -> io::Result<()> {
+        self.stream.write_all(&greeting())?;
+        let mut peer_greeting = [0u8; 64];
+        self.stream.read_exact(&mut peer_greeting)?;
+        if peer_greeting[0] != 0xff || peer_greeting[9] != 0x7f || peer_greeting[10] < 3 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Peer is not a ZMTP 3 endpoint",
+            ));
+        }
+        if peer_greeting[12..32] != greeting()[12..32] {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Peer does not use the NULL security mechanism",
+            ));
+        }
+
+        self.stream.write_all(&ready_command("SUB").serialize())?;
+        let ready = self.next_frame()?;
+        if !ready.is_command() || !ready.body.starts_with(b"\x05READY") {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Peer did not send READY",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Read the next frame.  If this times out, no data is lost, and it can be called again.
+    fn next_frame(&mut self) -> io::Result<Frame> {
+        loop {
+            if let Some((frame, len)) = Frame::parse(&self.buf)? {
+                self.buf.drain(..len);
+                return Ok(frame);
+            }
+            let mut chunk = [0u8; 65536];
+            let num_read = self.stream.read(&mut chunk)?;
+            if num_read == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "ZeroMQ connection closed",
+                ));
+            }
+            self.buf.extend_from_slice(&chunk[..num_read]);
+        }
+    }
+
+    /// Read the next message, as its list of frames.  Commands are skipped.  This returns
+    /// `Ok(None)` if no message arrives before the read timeout.
+    pub fn recv_message(&mut self) -> io::Result<Option<Vec<Vec<u8>>>> {
+        let mut message = vec![];
+        loop {
+            let frame = match self.next_frame() {
+                Ok(frame) => frame,
+                Err(e)
+                    if message.is_empty()
+                        && matches!(
+                            e.kind(),
+                            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                        ) =>
+                {
+                    return Ok(None);
+                }
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    // in the middle of a message
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            if frame.is_command() {
+                continue;
+            }
+            let more = frame.has_more();
+            message.push(frame.body);
+            if !more {
+                return Ok(Some(message));
+            }
+        }
+    }
+}
+
+/// Wakes up the run loop when a new burnchain block is announced
+#[derive(Default)]
+pub struct BurnchainBlockNotifier {
+    new_block: Mutex<bool>,
+    condvar: Condvar,
+}
+
+impl BurnchainBlockNotifier {
+    pub fn new() -> BurnchainBlockNotifier {
+        BurnchainBlockNotifier::default()
+    }
+
+    /// Announce a new block
+    pub fn notify(&self) {
+        let mut new_block = self
+            .new_block
+            .lock()
+            .expect("FATAL: notifier lock poisoned");
+        *new_block = true;
+        self.condvar.notify_all();
+    }
+
+    /// Wait up to `timeout` for a new block to be announced.  Returns whether one was, and
+    /// consumes the announcement.
+    pub fn wait(&self, timeout: Duration) -> bool {
+        let new_block = self
+            .new_block
+            .lock()
+            .expect("FATAL: notifier lock poisoned");
+        let (mut new_block, _) = self
+            .condvar
+            .wait_timeout_while(new_block, timeout, |new_block| !*new_block)
+            .expect("FATAL: notifier lock poisoned");
+        std::mem::replace(&mut *new_block, false)
+    }
+}
+
+fn wait_to_reconnect(keep_running: &AtomicBool) {
+    for _ in 0..RECONNECT_DELAY_SECS {
+        if !keep_running.load(Ordering::SeqCst) {
+            return;
+        }
+        sleep_ms(1_000);
+    }
+}
+
+/// Listen for new blocks on the ZeroMQ endpoint `endpoint`, and announce them through
+/// `notifier`, until `keep_running` is cleared.  The connection is re-established whenever it
+/// fails.
+pub fn run_block_listener(
+    endpoint: &str,
+    notifier: &BurnchainBlockNotifier,
+    keep_running: &AtomicBool,
+) {
+    while keep_running.load(Ordering::SeqCst) {
+        let mut subscriber = match ZmqSubscriber::connect(endpoint, ZMQ_BLOCK_TOPICS) {
+            Ok(subscriber) => subscriber,
+            Err(e) => {
+                warn!("Failed to subscribe to bitcoind's ZeroMQ block notifications, falling back to polling: {e}"; "endpoint" => endpoint);
+                wait_to_reconnect(keep_running);
+                continue;
+            }
+        };
+        info!("Subscribed to bitcoind's ZeroMQ block notifications"; "endpoint" => endpoint);
+
+        while keep_running.load(Ordering::SeqCst) {
+            match subscriber.recv_message() {
+                Ok(None) => {}
+                Ok(Some(message)) => {
+                    let Some(topic) = message.first() else {
+                        continue;
+                    };
+                    if ZMQ_BLOCK_TOPICS
+                        .iter()
+                        .any(|block_topic| topic.as_slice() == block_topic.as_bytes())
+                    {
+                        debug!("ZeroMQ: new burnchain block announced");
+                        notifier.notify();
+                    }
+                }
+                Err(e) => {
+                    warn!("Lost bitcoind's ZeroMQ block notifications, falling back to polling: {e}"; "endpoint" => endpoint);
+                    wait_to_reconnect(keep_running);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::TcpListener;
+    use std::sync::Arc;
+    use std::thread;
+
+    use super::*;
+
+    /// Accept one subscriber, check its handshake, and publish `messages` to it
+    fn run_publisher(listener: TcpListener, messages: Vec<Vec<Vec<u8>>>) {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut client_greeting = [0u8; 64];
+        stream.read_exact(&mut client_greeting).unwrap();
+        assert_eq!(client_greeting, greeting());
+        let mut server_greeting = greeting();
+        server_greeting[32] = 1;
+        stream.write_all(&server_greeting).unwrap();
+        stream.write_all(&ready_command("PUB").serialize()).unwrap();
+
+        let mut subscriber = ZmqSubscriber {
+            stream: stream.try_clone().unwrap(),
+            buf: vec![],
+        };
+        let ready = subscriber.next_frame().unwrap();
+        assert_eq!(ready, ready_command("SUB"));
+        for topic in ZMQ_BLOCK_TOPICS {
+            let subscription = subscriber.next_frame().unwrap();
+            assert_eq!(subscription.body[0], 1);
+            assert_eq!(&subscription.body[1..], topic.as_bytes());
+        }
+
+        for message in messages {
+            let num_frames = message.len();
+            for (i, body) in message.into_iter().enumerate() {
+                let flags = if i + 1 < num_frames { FLAG_MORE } else { 0 };
+                stream
+                    .write_all(&Frame::new(flags, body).serialize())
+                    .unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_frame_codec() {
+        let short = Frame::new(FLAG_MORE, vec![0xaa; 10]);
+        let bytes = short.serialize();
+        assert_eq!(bytes[..2], [FLAG_MORE, 10]);
+        assert_eq!(Frame::parse(&bytes).unwrap(), Some((short, 12)));
+        assert_eq!(Frame::parse(&bytes[..11]).unwrap(), None);
+
+        let long = Frame::new(0, vec![0xbb; 300]);
+        let bytes = long.serialize();
+        assert_eq!(bytes[0], FLAG_LONG);
+        assert_eq!(Frame::parse(&bytes).unwrap(), Some((long, 309)));
+        assert_eq!(Frame::parse(&bytes[..5]).unwrap(), None);
+
+        let mut too_big = vec![FLAG_LONG];
+        too_big.extend_from_slice(&(MAX_FRAME_SIZE + 1).to_be_bytes());
+        assert!(Frame::parse(&too_big).is_err());
+    }
+
+    #[test]
+    fn test_subscribe_to_blocks() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("tcp://{}", listener.local_addr().unwrap());
+        let hashblock = vec![
+            b"hashblock".to_vec(),
+            vec![0x11; 32],
+            1u32.to_le_bytes().to_vec(),
+        ];
+        let rawtx = vec![
+            b"rawtx".to_vec(),
+            vec![0x22; 300],
+            2u32.to_le_bytes().to_vec(),
+        ];
+        let publisher = thread::spawn(move || {
+            run_publisher(listener, vec![rawtx, hashblock]);
+        });
+
+        let mut subscriber = ZmqSubscriber::connect(&endpoint, ZMQ_BLOCK_TOPICS).unwrap();
+        let first = subscriber.recv_message().unwrap().unwrap();
+        assert_eq!(first[0], b"rawtx");
+        assert_eq!(first[1].len(), 300);
+        let second = subscriber.recv_message().unwrap().unwrap();
+        assert_eq!(second[0], b"hashblock");
+        assert_eq!(second[1], vec![0x11; 32]);
+        publisher.join().unwrap();
+
+        // the publisher hung up
+        assert!(subscriber.recv_message().is_err());
+        assert!(ZmqSubscriber::connect("ipc:///tmp/bitcoind", ZMQ_BLOCK_TOPICS).is_err());
+    }
+
+    #[test]
+    fn test_block_listener_notifies() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("tcp://{}", listener.local_addr().unwrap());
+        let publisher = thread::spawn(move || {
+            run_publisher(listener, vec![vec![b"rawblock".to_vec(), vec![0x33; 80]]]);
+        });
+
+        let notifier = Arc::new(BurnchainBlockNotifier::new());
+        let keep_running = Arc::new(AtomicBool::new(true));
+        let listener_thread = {
+            let notifier = notifier.clone();
+            let keep_running = keep_running.clone();
+            thread::spawn(move || run_block_listener(&endpoint, &notifier, &keep_running))
+        };
+
+        assert!(notifier.wait(Duration::from_secs(30)));
+        // the announcement is consumed
+        assert!(!notifier.wait(Duration::from_millis(10)));
+
+        publisher.join().unwrap();
+        keep_running.store(false, Ordering::SeqCst);
+        listener_thread.join().unwrap();
+    }
+}
@@ -30,12 +30,16 @@ use serde::Deserialize;
 use stacks::burnchains::affirmation::AffirmationMap;
 use stacks::burnchains::bitcoin::BitcoinNetworkType;
 use stacks::burnchains::{Burnchain, MagicBytes, PoxConstants, BLOCKSTACK_MAGIC_MAINNET};
+use stacks::chainstate::nakamoto::ordering::{TxOrderingRule, TX_ORDERING_RULES};
 use stacks::chainstate::nakamoto::signer_set::NakamotoSigners;
 use stacks::chainstate::stacks::boot::MINERS_NAME;
 use stacks::chainstate::stacks::index::marf::MARFOpenOpts;
 use stacks::chainstate::stacks::index::storage::TrieHashCalculationMode;
 use stacks::chainstate::stacks::miner::{BlockBuilderSettings, MinerStatus};
 use stacks::chainstate::stacks::MAX_BLOCK_LEN;
+use stacks::clarity_vm::database::audit::MarfReadAuditConfig;
+use stacks::clarity_vm::database::encryption::SideStoreCipher;
+use stacks::core::assembly::{block_assembly_strategy, BLOCK_ASSEMBLY_STRATEGIES};
 use stacks::core::mempool::{MemPoolWalkSettings, MemPoolWalkTxTypes};
 use stacks::core::{
     MemPoolDB, StacksEpoch, StacksEpochExtension, StacksEpochId,
@@ -50,6 +54,7 @@ use stacks::cost_estimates::metrics::{CostMetric, ProportionalDotProduct, UnitMe
 use stacks::cost_estimates::{CostEstimator, FeeEstimator, PessimisticEstimator, UnitEstimator};
 use stacks::net::atlas::AtlasConfig;
 use stacks::net::connection::ConnectionOptions;
+use stacks::net::ratelimit::{RPCRateLimit, RPCRateLimits};
 use stacks::net::{Neighbor, NeighborKey};
 use stacks::types::chainstate::BurnchainHeaderHash;
 use stacks::types::EpochList;
@@ -59,6 +64,7 @@ use stacks_common::consts::SIGNER_SLOTS_PER_USER;
 use stacks_common::types::chainstate::StacksAddress;
 use stacks_common::types::net::PeerAddress;
 use stacks_common::types::Address;
+use stacks_common::util::config::{check, load_toml};
 use stacks_common::util::get_epoch_time_ms;
 use stacks_common::util::hash::hex_bytes;
 use stacks_common::util::secp256k1::{Secp256k1PrivateKey, Secp256k1PublicKey};
@@ -84,6 +90,10 @@ pub const OP_TX_ANY_ESTIM_SIZE: u64 = fmax!(
     OP_TX_VOTE_AGG_ESTIM_SIZE
 );
 
+/// Prefix of the environment variables that override config file fields, e.g.
+/// `STACKS_CONFIG__NODE__RPC_BIND` overrides `node.rpc_bind`
+pub const CONFIG_ENV_PREFIX: &str = "STACKS_CONFIG__";
+
 const DEFAULT_MAX_RBF_RATE: u64 = 150; // 1.5x
 const DEFAULT_RBF_FEE_RATE_INCREMENT: u64 = 5;
 const INV_REWARD_CYCLES_TESTNET: u64 = 6;
@@ -92,6 +102,7 @@ const DEFAULT_FIRST_REJECTION_PAUSE_MS: u64 = 5_000;
 const DEFAULT_SUBSEQUENT_REJECTION_PAUSE_MS: u64 = 10_000;
 const DEFAULT_BLOCK_COMMIT_DELAY_MS: u64 = 20_000;
 const DEFAULT_TENURE_COST_LIMIT_PER_BLOCK_PERCENTAGE: u8 = 25;
+const DEFAULT_MICROBLOCK_ARCHIVE_DEPTH: u32 = 1_000;
 
 #[derive(Clone, Deserialize, Default, Debug)]
 #[serde(deny_unknown_fields)]
@@ -120,7 +131,7 @@ impl ConfigFile {
     #[allow(clippy::should_implement_trait)]
     pub fn from_str(content: &str) -> Result<ConfigFile, String> {
         let mut config: ConfigFile =
-            toml::from_str(content).map_err(|e| format!("Invalid toml: {e}"))?;
+            load_toml(content, CONFIG_ENV_PREFIX).map_err(|e| format!("Invalid toml: {e}"))?;
         if let Some(mstx_balance) = config.mstx_balance.take() {
             warn!("'mstx_balance' in the config is deprecated; please use 'ustx_balance' instead.");
             match config.ustx_balance {
@@ -659,6 +670,8 @@ impl Config {
                 Ok(StacksEpochId::Epoch25)
             } else if epoch_name == EPOCH_CONFIG_3_0_0 {
                 Ok(StacksEpochId::Epoch30)
+            } else if epoch_name == EPOCH_CONFIG_3_1_0 {
+                Ok(StacksEpochId::Epoch31)
             } else {
                 Err(format!("Unknown epoch name specified: {epoch_name}"))
             }?;
@@ -685,6 +698,7 @@ impl Config {
             StacksEpochId::Epoch24,
             StacksEpochId::Epoch25,
             StacksEpochId::Epoch30,
+            StacksEpochId::Epoch31,
         ];
         for (expected_epoch, configured_epoch) in expected_list
             .iter()
@@ -827,6 +841,18 @@ impl Config {
             return Err("Attempted to run mainnet node with `use_test_genesis_chainstate`".into());
         }
 
+        if is_mainnet && node.analysis_cost_limit.is_some() {
+            return Err("Attempted to run mainnet node with `analysis_cost_limit`".into());
+        }
+
+        if is_mainnet && node.contract_data_quota.is_some() {
+            return Err("Attempted to run mainnet node with `contract_data_quota`".into());
+        }
+
+        if is_mainnet && node.clarity_state_snapshot.is_some() {
+            return Err("Attempted to run mainnet node with `clarity_state_snapshot`".into());
+        }
+
         if node.stacker || node.miner {
             node.add_miner_stackerdb(is_mainnet);
             node.add_signers_stackerdbs(is_mainnet);
@@ -999,6 +1025,13 @@ impl Config {
         path.to_str().expect("Unable to produce path").to_string()
     }
 
+    /// The directory that holds the burnchain checkpoints
+    pub fn get_burnchain_checkpoints_path(&self) -> PathBuf {
+        let mut path = self.get_burnchain_path();
+        path.set_file_name("burnchain_checkpoints");
+        path
+    }
+
     pub fn get_peer_db_file_path(&self) -> String {
         let mut path = self.get_chainstate_path();
         path.set_file_name("peer.sqlite");
@@ -1059,9 +1092,16 @@ impl Config {
                 filter_origins: miner_config.filter_origins,
                 tenure_cost_limit_per_block_percentage: miner_config
                     .tenure_cost_limit_per_block_percentage,
+                skip_txs_over_tenure_budget: miner_config.skip_txs_over_tenure_budget,
+                tenure_budget: None,
+                block_assembly_strategy: block_assembly_strategy(
+                    &miner_config.block_assembly_strategy,
+                )
+                .expect("FATAL: unknown block assembly strategy"),
             },
             miner_status,
             confirm_microblocks: false,
+            tx_ordering: miner_config.tx_ordering_commitment,
         }
     }
 
@@ -1101,9 +1141,16 @@ impl Config {
                 filter_origins: miner_config.filter_origins,
                 tenure_cost_limit_per_block_percentage: miner_config
                     .tenure_cost_limit_per_block_percentage,
+                skip_txs_over_tenure_budget: miner_config.skip_txs_over_tenure_budget,
+                tenure_budget: None,
+                block_assembly_strategy: block_assembly_strategy(
+                    &miner_config.block_assembly_strategy,
+                )
+                .expect("FATAL: unknown block assembly strategy"),
             },
             miner_status,
             confirm_microblocks: true,
+            tx_ordering: None,
         }
     }
 
@@ -1201,6 +1248,110 @@ pub struct BurnchainConfig {
     /// This value is passed as the `maximumCount` query option to the
     /// `listunspent` RPC call.
     pub max_unspent_utxos: Option<u64>,
+    /// Which RPC interface the miner uses to find its UTXOs and the fate of its transactions
+    pub rpc_backend: BurnchainRpcBackend,
+    /// With the `block_filters` backend, how many of the most recent burnchain blocks to search
+    /// for an unconfirmed block-commit before giving up on it
+    pub block_filter_scan_depth: u64,
+    /// How often, in milliseconds, the miner checks whether its pending block-commit pays enough
+    /// to be mined in the next block, and replaces it with a higher fee if not.  0 disables this.
+    pub rbf_fee_check_interval_ms: u64,
+    /// The highest fee rate, in sats/vB, that a replacement block-commit may pay.  Defaults to
+    /// `max_rbf` percent of `satoshis_per_byte`.
+    pub rbf_max_fee_rate: Option<u64>,
+    /// The most satoshis that a block-commit and its replacements may spend in total, including
+    /// the burn and the fees of the replaced transactions.  Unlimited if not set.
+    pub rbf_max_tenure_spend: Option<u64>,
+    /// How often, in burnchain blocks, the node checkpoints its burnchain headers and burnchain
+    /// DB, so that a reindex can resume from the latest checkpoint.  0 disables checkpoints.
+    pub checkpoint_interval: u64,
+    /// How many of the latest burnchain checkpoints to keep
+    pub checkpoint_retain: u64,
+    /// bitcoind's ZeroMQ `hashblock` or `rawblock` publisher (`tcp://HOST:PORT`).  If set, the
+    /// node syncs the burnchain as soon as bitcoind announces a new block, instead of waiting up
+    /// to `poll_time_secs`.  The node keeps polling, in case notifications are lost.
+    pub zmq_endpoint: Option<String>,
+    /// Other bitcoinds to fail over to, in order of preference, if the one configured above is
+    /// unreachable or falls behind
+    pub failover_nodes: Vec<BitcoindEndpoint>,
+    /// How often, in milliseconds, the node checks the health of each bitcoind when
+    /// `failover_nodes` are configured
+    pub health_check_interval_ms: u64,
+    /// How many blocks a bitcoind may be behind the most advanced one before the node fails over
+    /// from it
+    pub failover_max_lag: u64,
+}
+
+/// The connection parameters of one bitcoind
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct BitcoindEndpoint {
+    pub peer_host: String,
+    pub peer_port: u16,
+    pub rpc_port: u16,
+    pub rpc_ssl: bool,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+impl BitcoindEndpoint {
+    /// The bitcoind that `config` currently points to
+    pub fn from_burnchain_config(config: &BurnchainConfig) -> BitcoindEndpoint {
+        BitcoindEndpoint {
+            peer_host: config.peer_host.clone(),
+            peer_port: config.peer_port,
+            rpc_port: config.rpc_port,
+            rpc_ssl: config.rpc_ssl,
+            username: config.username.clone(),
+            password: config.password.clone(),
+        }
+    }
+
+    /// Point `config` at this bitcoind
+    pub fn apply(&self, config: &mut BurnchainConfig) {
+        config.peer_host = self.peer_host.clone();
+        config.peer_port = self.peer_port;
+        config.rpc_port = self.rpc_port;
+        config.rpc_ssl = self.rpc_ssl;
+        config.username = self.username.clone();
+        config.password = self.password.clone();
+    }
+}
+
+impl std::fmt::Display for BitcoindEndpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}:{}", self.peer_host, self.rpc_port)
+    }
+}
+
+/// The RPC interface that the miner uses to find its UTXOs and the fate of its transactions.
+/// Either way, the node follows the burnchain by downloading blocks from its peer.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub enum BurnchainRpcBackend {
+    /// bitcoind's wallet RPC.  The node imports the miner's addresses into a watch-only wallet,
+    /// which requires rescanning an unpruned chain.
+    Bitcoind,
+    /// bitcoind's UTXO set and BIP158 block filters (`scantxoutset` and `getblockfilter`), which a
+    /// pruned node started with `-blockfilterindex=1` can serve without a wallet.  Unconfirmed
+    /// UTXOs are never spent.
+    BlockFilters,
+}
+
+impl Default for BurnchainRpcBackend {
+    fn default() -> Self {
+        BurnchainRpcBackend::Bitcoind
+    }
+}
+
+impl BurnchainRpcBackend {
+    fn parse(s: &str) -> Result<BurnchainRpcBackend, String> {
+        match s.to_lowercase().as_str() {
+            "bitcoind" => Ok(BurnchainRpcBackend::Bitcoind),
+            "block_filters" => Ok(BurnchainRpcBackend::BlockFilters),
+            _ => Err(format!(
+                "Bad burnchain RPC backend supplied in configuration file: {s}"
+            )),
+        }
+    }
 }
 
 impl BurnchainConfig {
@@ -1242,8 +1393,26 @@ impl BurnchainConfig {
             affirmation_overrides: HashMap::new(),
             fault_injection_burnchain_block_delay: 0,
             max_unspent_utxos: Some(1024),
+            rpc_backend: BurnchainRpcBackend::default(),
+            block_filter_scan_depth: 6,
+            rbf_fee_check_interval_ms: 30_000,
+            rbf_max_fee_rate: None,
+            rbf_max_tenure_spend: None,
+            checkpoint_interval: 0,
+            checkpoint_retain: 2,
+            zmq_endpoint: None,
+            failover_nodes: vec![],
+            health_check_interval_ms: 30_000,
+            failover_max_lag: 2,
         }
     }
+
+    /// The highest fee rate, in sats/vB, that a replacement block-commit may pay
+    pub fn get_rbf_max_fee_rate(&self) -> u64 {
+        self.rbf_max_fee_rate
+            .unwrap_or(self.satoshis_per_byte * self.max_rbf / 100)
+    }
+
     pub fn get_rpc_url(&self, wallet: Option<String>) -> String {
         let scheme = match self.rpc_ssl {
             true => "https://",
@@ -1291,6 +1460,7 @@ pub const EPOCH_CONFIG_2_3_0: &str = "2.3";
 pub const EPOCH_CONFIG_2_4_0: &str = "2.4";
 pub const EPOCH_CONFIG_2_5_0: &str = "2.5";
 pub const EPOCH_CONFIG_3_0_0: &str = "3.0";
+pub const EPOCH_CONFIG_3_1_0: &str = "3.1";
 
 #[derive(Clone, Deserialize, Default, Debug)]
 pub struct AffirmationOverride {
@@ -1337,6 +1507,29 @@ pub struct BurnchainConfigFile {
     pub affirmation_overrides: Option<Vec<AffirmationOverride>>,
     pub fault_injection_burnchain_block_delay: Option<u64>,
     pub max_unspent_utxos: Option<u64>,
+    pub rpc_backend: Option<String>,
+    pub block_filter_scan_depth: Option<u64>,
+    pub rbf_fee_check_interval_ms: Option<u64>,
+    pub rbf_max_fee_rate: Option<u64>,
+    pub rbf_max_tenure_spend: Option<u64>,
+    pub checkpoint_interval: Option<u64>,
+    pub checkpoint_retain: Option<u64>,
+    pub zmq_endpoint: Option<String>,
+    pub failover_nodes: Option<Vec<BitcoindEndpointConfigFile>>,
+    pub health_check_interval_ms: Option<u64>,
+    pub failover_max_lag: Option<u64>,
+}
+
+/// A bitcoind in `burnchain.failover_nodes`.  Unset fields are those of the primary bitcoind.
+#[derive(Clone, Deserialize, Default, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct BitcoindEndpointConfigFile {
+    pub peer_host: String,
+    pub peer_port: Option<u16>,
+    pub rpc_port: Option<u16>,
+    pub rpc_ssl: Option<bool>,
+    pub username: Option<String>,
+    pub password: Option<String>,
 }
 
 impl BurnchainConfigFile {
@@ -1570,8 +1763,62 @@ impl BurnchainConfigFile {
                     val
                 })
                 .or(default_burnchain_config.max_unspent_utxos),
+            rpc_backend: self
+                .rpc_backend
+                .as_deref()
+                .map(BurnchainRpcBackend::parse)
+                .transpose()?
+                .unwrap_or(default_burnchain_config.rpc_backend),
+            block_filter_scan_depth: self
+                .block_filter_scan_depth
+                .unwrap_or(default_burnchain_config.block_filter_scan_depth),
+            rbf_fee_check_interval_ms: self
+                .rbf_fee_check_interval_ms
+                .unwrap_or(default_burnchain_config.rbf_fee_check_interval_ms),
+            rbf_max_fee_rate: self.rbf_max_fee_rate,
+            rbf_max_tenure_spend: self.rbf_max_tenure_spend,
+            checkpoint_interval: self
+                .checkpoint_interval
+                .unwrap_or(default_burnchain_config.checkpoint_interval),
+            checkpoint_retain: self
+                .checkpoint_retain
+                .unwrap_or(default_burnchain_config.checkpoint_retain),
+            zmq_endpoint: self.zmq_endpoint,
+            failover_nodes: vec![],
+            health_check_interval_ms: self
+                .health_check_interval_ms
+                .unwrap_or(default_burnchain_config.health_check_interval_ms),
+            failover_max_lag: self
+                .failover_max_lag
+                .unwrap_or(default_burnchain_config.failover_max_lag),
         };
 
+        let primary = BitcoindEndpoint::from_burnchain_config(&config);
+        config.failover_nodes = self
+            .failover_nodes
+            .unwrap_or_default()
+            .into_iter()
+            .map(|node| BitcoindEndpoint {
+                peer_host: node.peer_host,
+                peer_port: node.peer_port.unwrap_or(primary.peer_port),
+                rpc_port: node.rpc_port.unwrap_or(primary.rpc_port),
+                rpc_ssl: node.rpc_ssl.unwrap_or(primary.rpc_ssl),
+                username: node.username.or(primary.username.clone()),
+                password: node.password.or(primary.password.clone()),
+            })
+            .collect();
+        if config.failover_nodes.contains(&primary) {
+            return Err("burnchain.failover_nodes must not include the primary bitcoind".into());
+        }
+
+        if let Some(endpoint) = config.zmq_endpoint.as_ref() {
+            if !endpoint.starts_with("tcp://") {
+                return Err(format!(
+                    "burnchain.zmq_endpoint must be a tcp:// endpoint, got {endpoint}"
+                ));
+            }
+        }
+
         if let BitcoinNetworkType::Mainnet = config.get_bitcoin_network().1 {
             // check that pox_2_activation hasn't been set in mainnet
             if config.pox_2_activation.is_some()
@@ -1648,6 +1895,54 @@ pub struct NodeConfig {
     pub chain_liveness_poll_time_secs: u64,
     /// stacker DBs we replicate
     pub stacker_dbs: Vec<QualifiedContractIdentifier>,
+    /// Budget for the analysis of contract deployments, independent of the block limit.
+    /// Not configurable in mainnet, since it changes which deployments are valid.
+    pub analysis_cost_limit: Option<ExecutionCost>,
+    /// If set, run side-store maintenance (incremental vacuum and analyze) this often, whenever
+    /// no block is being processed
+    pub side_store_maintenance_interval_secs: Option<u64>,
+    /// Maximum number of free pages to reclaim in one side-store maintenance step
+    pub side_store_maintenance_max_pages: u32,
+    /// If set, encrypt the Clarity side-store's contract data and metadata at rest
+    pub side_store_cipher: Option<SideStoreCipher>,
+    /// How many deserialized contract analyses to keep in memory (disabled if 0)
+    pub contract_analysis_cache_size: usize,
+    /// If set, load the analyses of the boot contracts and of up to this many of the
+    /// most-recently-used contracts into the contract analysis cache on startup
+    pub contract_analysis_warm_load: Option<usize>,
+    /// If set, discard the Clarity state that is only needed to read more than this many blocks
+    /// below the canonical tip (as part of side-store maintenance). RPC queries for older state
+    /// fail with HTTP 410. This must cover the deepest reorg the node should survive. Only
+    /// side-store values are discarded; the MARF tries are kept.
+    pub state_prune_depth: Option<u32>,
+    /// If set, delete the staging blocks and microblocks of the forks that are more than this
+    /// many blocks below the canonical tip (as part of side-store maintenance). Like
+    /// `state_prune_depth`, this must cover the deepest reorg the node should survive.
+    pub fork_prune_depth: Option<u32>,
+    /// If set, move the data of the processed microblocks more than `microblock_archive_depth`
+    /// blocks below the canonical tip to a SQLite archive at this path (as part of side-store
+    /// maintenance). RPC requests for archived microblocks fail with HTTP 410. If not set, all
+    /// microblock data stays in the chainstate and is served.
+    pub microblock_archive_path: Option<PathBuf>,
+    /// How many blocks below the canonical tip microblocks are archived
+    pub microblock_archive_depth: u32,
+    /// If set, restore the chainstate snapshot in this directory before first booting, instead
+    /// of syncing from genesis
+    pub fast_sync_snapshot: Option<PathBuf>,
+    /// The keys that `fast_sync_snapshot` may be signed with
+    pub fast_sync_trusted_signers: Vec<Secp256k1PublicKey>,
+    /// If set, log a MARF proof of every Clarity state read made while processing a block
+    pub marf_read_audit: Option<MarfReadAuditConfig>,
+    /// If set, the most bytes of data that each contract may store. Writes that would take a
+    /// contract over it fail. Every node of the network must use the same quota.
+    pub contract_data_quota: Option<u64>,
+    /// If set, memoize the read-only function calls made while processing each block, so that
+    /// repeated calls with the same arguments are not evaluated again. This does not change
+    /// the outcome of any transaction.
+    pub memoize_read_only_calls: bool,
+    /// If set, a Clarity state snapshot (see `stacks-inspect export-clarity-state`) to import
+    /// into the genesis block when the chainstate is first created
+    pub clarity_state_snapshot: Option<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -1925,6 +2220,22 @@ impl Default for NodeConfig {
             fault_injection_hide_blocks: false,
             chain_liveness_poll_time_secs: 300,
             stacker_dbs: vec![],
+            analysis_cost_limit: None,
+            side_store_maintenance_interval_secs: None,
+            side_store_maintenance_max_pages: 1000,
+            side_store_cipher: None,
+            contract_analysis_cache_size: 0,
+            contract_analysis_warm_load: None,
+            state_prune_depth: None,
+            fork_prune_depth: None,
+            microblock_archive_path: None,
+            microblock_archive_depth: DEFAULT_MICROBLOCK_ARCHIVE_DEPTH,
+            fast_sync_snapshot: None,
+            fast_sync_trusted_signers: vec![],
+            marf_read_audit: None,
+            contract_data_quota: None,
+            memoize_read_only_calls: false,
+            clarity_state_snapshot: None,
         }
     }
 }
@@ -2067,11 +2378,16 @@ impl NodeConfig {
             TrieHashCalculationMode::Immediate
         };
 
-        MARFOpenOpts::new(
+        let mut opts = MARFOpenOpts::new(
             hash_mode,
             self.marf_cache_strategy.as_deref().unwrap_or("noop"),
             false,
-        )
+        );
+        opts.side_store_cipher = self.side_store_cipher.clone();
+        opts.read_audit = self.marf_read_audit.clone();
+        opts.contract_data_quota = self.contract_data_quota;
+        opts.memoize_read_only_calls = self.memoize_read_only_calls;
+        opts
     }
 }
 
@@ -2086,6 +2402,16 @@ pub struct MinerConfig {
     pub block_reward_recipient: Option<PrincipalData>,
     /// If possible, mine with a p2wpkh address
     pub segwit: bool,
+    /// If possible, mine with a p2tr address that the miner's key spends by the key path
+    /// (BIP-86).  This takes precedence over `segwit`.
+    pub taproot: bool,
+    /// Have the burnchain wallet sign the miner's Bitcoin transactions, by way of PSBTs, instead
+    /// of signing them with the miner's key.  The wallet may hold the keys itself, or use an
+    /// external signer.
+    pub psbt_signing: bool,
+    /// With `psbt_signing`, the public key whose addresses hold the miner's UTXOs.  Only the
+    /// wallet (or its signer) needs its private key.  Defaults to the miner's key.
+    pub psbt_signer_public_key: Option<Secp256k1PublicKey>,
     /// Wait for a downloader pass before mining.
     /// This can only be disabled in testing; it can't be changed in the config file.
     pub wait_for_block_download: bool,
@@ -2135,6 +2461,13 @@ pub struct MinerConfig {
     pub block_commit_delay: Duration,
     /// The percentage of the remaining tenure cost limit to consume each block.
     pub tenure_cost_limit_per_block_percentage: Option<u8>,
+    /// Skip mempool transactions whose estimated cost can't fit in the rest of the tenure's budget.
+    pub skip_txs_over_tenure_budget: bool,
+    /// The name of the strategy that orders the transactions considered for a block.
+    pub block_assembly_strategy: String,
+    /// The transaction-ordering rule that the miner commits to in its Nakamoto blocks, if any.
+    /// It isn't recorded in the blocks, so it only helps with signers that require the same rule.
+    pub tx_ordering_commitment: Option<TxOrderingRule>,
 }
 
 impl Default for MinerConfig {
@@ -2147,6 +2480,9 @@ impl Default for MinerConfig {
             probability_pick_no_estimate_tx: 25,
             block_reward_recipient: None,
             segwit: false,
+            taproot: false,
+            psbt_signing: false,
+            psbt_signer_public_key: None,
             wait_for_block_download: true,
             nonce_cache_size: 1024 * 1024,
             candidate_retry_cache_size: 1024 * 1024,
@@ -2171,6 +2507,9 @@ impl Default for MinerConfig {
             tenure_cost_limit_per_block_percentage: Some(
                 DEFAULT_TENURE_COST_LIMIT_PER_BLOCK_PERCENTAGE,
             ),
+            skip_txs_over_tenure_budget: false,
+            block_assembly_strategy: "fee-rate".into(),
+            tx_ordering_commitment: None,
         }
     }
 }
@@ -2208,6 +2547,7 @@ pub struct ConnectionOptionsFile {
     pub read_only_call_limit_write_count: Option<u64>,
     pub read_only_call_limit_read_count: Option<u64>,
     pub read_only_call_limit_runtime: Option<u64>,
+    pub read_only_call_limit_memory: Option<u64>,
     pub maximum_call_argument_size: Option<u32>,
     pub download_interval: Option<u64>,
     pub inv_sync_interval: Option<u64>,
@@ -2223,6 +2563,40 @@ pub struct ConnectionOptionsFile {
     pub auth_token: Option<String>,
     pub antientropy_retry: Option<u64>,
     pub reject_blocks_pushed: Option<bool>,
+    pub read_rate_limit_burst: Option<u64>,
+    pub read_rate_limit_steady_rate: Option<f64>,
+    pub write_rate_limit_burst: Option<u64>,
+    pub write_rate_limit_steady_rate: Option<f64>,
+    pub read_only_call_rate_limit_burst: Option<u64>,
+    pub read_only_call_rate_limit_steady_rate: Option<f64>,
+    pub authenticated_rate_limit_burst: Option<u64>,
+    pub authenticated_rate_limit_steady_rate: Option<f64>,
+    /// How many read-only call responses to cache (disabled if not set)
+    pub rpc_response_cache_size: Option<usize>,
+    /// Allow read-only calls to return an execution trace (with `?trace=1`)
+    pub read_only_call_trace: Option<bool>,
+}
+
+/// A rate limit is enabled by setting both its burst and its steady rate
+fn rpc_rate_limit_from_file(
+    class: &str,
+    burst: Option<u64>,
+    steady_rate: Option<f64>,
+) -> Result<Option<RPCRateLimit>, String> {
+    match (burst, steady_rate) {
+        (None, None) => Ok(None),
+        (Some(burst), Some(steady_rate)) => {
+            if burst == 0 || !steady_rate.is_finite() || steady_rate <= 0.0 {
+                return Err(format!(
+                    "Invalid connection_options.{class}_rate_limit: burst and steady rate must be positive"
+                ));
+            }
+            Ok(Some(RPCRateLimit { burst, steady_rate }))
+        }
+        _ => Err(format!(
+            "Invalid connection_options.{class}_rate_limit: both {class}_rate_limit_burst and {class}_rate_limit_steady_rate must be set"
+        )),
+    }
 }
 
 impl ConnectionOptionsFile {
@@ -2236,6 +2610,28 @@ impl ConnectionOptionsFile {
                     .map_err(|e| format!("Invalid connection_option.public_ip_address: {e}"))
             })
             .transpose()?;
+        let rpc_rate_limits = RPCRateLimits {
+            read: rpc_rate_limit_from_file(
+                "read",
+                self.read_rate_limit_burst,
+                self.read_rate_limit_steady_rate,
+            )?,
+            write: rpc_rate_limit_from_file(
+                "write",
+                self.write_rate_limit_burst,
+                self.write_rate_limit_steady_rate,
+            )?,
+            read_only_call: rpc_rate_limit_from_file(
+                "read_only_call",
+                self.read_only_call_rate_limit_burst,
+                self.read_only_call_rate_limit_steady_rate,
+            )?,
+            authenticated: rpc_rate_limit_from_file(
+                "authenticated",
+                self.authenticated_rate_limit_burst,
+                self.authenticated_rate_limit_steady_rate,
+            )?,
+        };
         let mut read_only_call_limit = HELIUM_DEFAULT_CONNECTION_OPTIONS
             .read_only_call_limit
             .clone();
@@ -2321,6 +2717,9 @@ impl ConnectionOptionsFile {
             max_inflight_attachments: self
                 .max_inflight_attachments
                 .unwrap_or_else(|| HELIUM_DEFAULT_CONNECTION_OPTIONS.max_inflight_attachments),
+            read_only_call_memory_limit: self
+                .read_only_call_limit_memory
+                .unwrap_or_else(|| HELIUM_DEFAULT_CONNECTION_OPTIONS.read_only_call_memory_limit),
             maximum_call_argument_size: self
                 .maximum_call_argument_size
                 .unwrap_or_else(|| HELIUM_DEFAULT_CONNECTION_OPTIONS.maximum_call_argument_size),
@@ -2354,6 +2753,13 @@ impl ConnectionOptionsFile {
             antientropy_public: self.antientropy_public.unwrap_or(true),
             private_neighbors: self.private_neighbors.unwrap_or(true),
             auth_token: self.auth_token,
+            rpc_rate_limits,
+            rpc_response_cache_size: self
+                .rpc_response_cache_size
+                .unwrap_or(default.rpc_response_cache_size),
+            read_only_call_trace: self
+                .read_only_call_trace
+                .unwrap_or(default.read_only_call_trace),
             antientropy_retry: self.antientropy_retry.unwrap_or(default.antientropy_retry),
             reject_blocks_pushed: self
                 .reject_blocks_pushed
@@ -2400,6 +2806,56 @@ pub struct NodeConfigFile {
     pub stacker_dbs: Option<Vec<String>>,
     /// fault injection: fail to push blocks with this probability (0-100)
     pub fault_injection_block_push_fail_probability: Option<u8>,
+    /// Budget for the analysis of contract deployments, independent of the block limit
+    pub analysis_cost_limit: Option<ExecutionCost>,
+    /// Run side-store maintenance this often (disabled if not set)
+    pub side_store_maintenance_interval_secs: Option<u64>,
+    /// Maximum number of free pages to reclaim in one side-store maintenance step
+    pub side_store_maintenance_max_pages: Option<u32>,
+    /// Hex-encoded 32-byte key with which to encrypt the Clarity side-store (disabled if not set)
+    pub side_store_encryption_key: Option<String>,
+    /// Keys that side-store values may still be encrypted with, after rotating
+    /// `side_store_encryption_key`
+    pub side_store_encryption_previous_keys: Option<Vec<String>>,
+    /// Refuse to read unencrypted side-store values, once `reencrypt-side-store` has encrypted
+    /// them all (default: false)
+    pub side_store_encryption_required: Option<bool>,
+    /// How many deserialized contract analyses to keep in memory (disabled if not set)
+    pub contract_analysis_cache_size: Option<usize>,
+    /// Warm up the contract analysis cache on startup with the boot contracts and up to this
+    /// many of the most-recently-used contracts (disabled if not set)
+    pub contract_analysis_warm_load: Option<usize>,
+    /// Only keep the Clarity state needed to read this many blocks below the canonical tip
+    /// (keep all state if not set)
+    pub state_prune_depth: Option<u32>,
+    /// Delete the staging data of the forks this many blocks below the canonical tip (keep all
+    /// forks if not set)
+    pub fork_prune_depth: Option<u32>,
+    /// File to move the data of old microblocks to, after which they are no longer served (keep
+    /// and serve all microblocks if not set)
+    pub microblock_archive_path: Option<String>,
+    /// Archive the microblocks this many blocks below the canonical tip (default: 1000)
+    pub microblock_archive_depth: Option<u32>,
+    /// Directory of a chainstate snapshot to restore on first boot (sync from genesis if not
+    /// set)
+    pub fast_sync_snapshot: Option<String>,
+    /// Hex-encoded public keys that `fast_sync_snapshot` may be signed with
+    pub fast_sync_trusted_signers: Option<Vec<String>>,
+    /// File to log the Clarity state reads made while processing blocks to, with their MARF
+    /// proofs (disabled if not set)
+    pub marf_read_audit_path: Option<String>,
+    /// Rotate the read audit file once it would grow past this many bytes
+    pub marf_read_audit_max_file_size: Option<u64>,
+    /// Number of read audit files to keep, including the one being written
+    pub marf_read_audit_max_files: Option<u32>,
+    /// Most bytes of data (constants, data vars, map entries and tokens) that each contract may
+    /// store (unlimited if not set). Not allowed on mainnet.
+    pub contract_data_quota: Option<u64>,
+    /// Memoize the read-only function calls made while processing each block (default: false)
+    pub memoize_read_only_calls: Option<bool>,
+    /// Path of a Clarity state snapshot to seed the genesis block with, e.g. to test against a
+    /// copy of mainnet's state. Not allowed on mainnet.
+    pub clarity_state_snapshot: Option<String>,
 }
 
 impl NodeConfigFile {
@@ -2407,6 +2863,148 @@ impl NodeConfigFile {
         let rpc_bind = self.rpc_bind.unwrap_or(default_node_config.rpc_bind);
         let miner = self.miner.unwrap_or(default_node_config.miner);
         let stacker = self.stacker.unwrap_or(default_node_config.stacker);
+        let side_store_cipher = match self.side_store_encryption_key {
+            Some(key) => Some(
+                SideStoreCipher::from_hex(
+                    &key,
+                    &self.side_store_encryption_previous_keys.unwrap_or_default(),
+                )
+                .map_err(|e| format!("node.side_store_encryption_key: {e}"))?
+                .with_require_encrypted(self.side_store_encryption_required.unwrap_or(false)),
+            ),
+            None => {
+                check(
+                    self.side_store_encryption_previous_keys.is_none(),
+                    "node.side_store_encryption_previous_keys",
+                    "requires node.side_store_encryption_key",
+                )
+                .map_err(|e| e.to_string())?;
+                check(
+                    self.side_store_encryption_required.is_none(),
+                    "node.side_store_encryption_required",
+                    "requires node.side_store_encryption_key",
+                )
+                .map_err(|e| e.to_string())?;
+                default_node_config.side_store_cipher
+            }
+        };
+        let contract_analysis_cache_size = self
+            .contract_analysis_cache_size
+            .unwrap_or(default_node_config.contract_analysis_cache_size);
+        let contract_analysis_warm_load = self
+            .contract_analysis_warm_load
+            .or(default_node_config.contract_analysis_warm_load);
+        check(
+            contract_analysis_warm_load.is_none() || contract_analysis_cache_size > 0,
+            "node.contract_analysis_warm_load",
+            "requires node.contract_analysis_cache_size",
+        )
+        .map_err(|e| e.to_string())?;
+        let side_store_maintenance_interval_secs = self
+            .side_store_maintenance_interval_secs
+            .or(default_node_config.side_store_maintenance_interval_secs);
+        let state_prune_depth = self
+            .state_prune_depth
+            .or(default_node_config.state_prune_depth);
+        if let Some(state_prune_depth) = state_prune_depth {
+            check(
+                state_prune_depth > 0,
+                "node.state_prune_depth",
+                "must be positive",
+            )
+            .map_err(|e| e.to_string())?;
+            check(
+                side_store_maintenance_interval_secs.is_some(),
+                "node.state_prune_depth",
+                "requires node.side_store_maintenance_interval_secs",
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        let fork_prune_depth = self
+            .fork_prune_depth
+            .or(default_node_config.fork_prune_depth);
+        if let Some(fork_prune_depth) = fork_prune_depth {
+            check(
+                fork_prune_depth > 0,
+                "node.fork_prune_depth",
+                "must be positive",
+            )
+            .map_err(|e| e.to_string())?;
+            check(
+                side_store_maintenance_interval_secs.is_some(),
+                "node.fork_prune_depth",
+                "requires node.side_store_maintenance_interval_secs",
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        let microblock_archive_path = self
+            .microblock_archive_path
+            .map(PathBuf::from)
+            .or(default_node_config.microblock_archive_path);
+        let microblock_archive_depth = self
+            .microblock_archive_depth
+            .unwrap_or(default_node_config.microblock_archive_depth);
+        check(
+            microblock_archive_depth > 0,
+            "node.microblock_archive_depth",
+            "must be positive",
+        )
+        .map_err(|e| e.to_string())?;
+        check(
+            microblock_archive_path.is_none() || side_store_maintenance_interval_secs.is_some(),
+            "node.microblock_archive_path",
+            "requires node.side_store_maintenance_interval_secs",
+        )
+        .map_err(|e| e.to_string())?;
+        let fast_sync_snapshot = self
+            .fast_sync_snapshot
+            .map(PathBuf::from)
+            .or(default_node_config.fast_sync_snapshot);
+        let fast_sync_trusted_signers = match self.fast_sync_trusted_signers {
+            Some(keys) => keys
+                .iter()
+                .map(|key| {
+                    Secp256k1PublicKey::from_hex(key).map_err(|e| {
+                        format!("node.fast_sync_trusted_signers: invalid key {key}: {e}")
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            None => default_node_config.fast_sync_trusted_signers,
+        };
+        check(
+            fast_sync_snapshot.is_none() || !fast_sync_trusted_signers.is_empty(),
+            "node.fast_sync_snapshot",
+            "requires node.fast_sync_trusted_signers",
+        )
+        .map_err(|e| e.to_string())?;
+        let marf_read_audit = match self.marf_read_audit_path {
+            Some(path) => {
+                let mut config = MarfReadAuditConfig::new(PathBuf::from(path));
+                if let Some(max_file_size) = self.marf_read_audit_max_file_size {
+                    config.max_file_size = max_file_size;
+                }
+                if let Some(max_files) = self.marf_read_audit_max_files {
+                    check(
+                        max_files > 0,
+                        "node.marf_read_audit_max_files",
+                        "must be positive",
+                    )
+                    .map_err(|e| e.to_string())?;
+                    config.max_files = max_files;
+                }
+                Some(config)
+            }
+            None => {
+                check(
+                    self.marf_read_audit_max_file_size.is_none()
+                        && self.marf_read_audit_max_files.is_none(),
+                    "node.marf_read_audit_max_file_size",
+                    "requires node.marf_read_audit_path",
+                )
+                .map_err(|e| e.to_string())?;
+                default_node_config.marf_read_audit
+            }
+        };
         let node_config = NodeConfig {
             name: self.name.unwrap_or(default_node_config.name),
             seed: match self.seed {
@@ -2494,6 +3092,32 @@ impl NodeConfigFile {
             } else {
                 default_node_config.fault_injection_block_push_fail_probability
             },
+            analysis_cost_limit: self
+                .analysis_cost_limit
+                .or(default_node_config.analysis_cost_limit),
+            side_store_maintenance_interval_secs,
+            side_store_maintenance_max_pages: self
+                .side_store_maintenance_max_pages
+                .unwrap_or(default_node_config.side_store_maintenance_max_pages),
+            side_store_cipher,
+            contract_analysis_cache_size,
+            contract_analysis_warm_load,
+            state_prune_depth,
+            fork_prune_depth,
+            microblock_archive_path,
+            microblock_archive_depth,
+            fast_sync_snapshot,
+            fast_sync_trusted_signers,
+            marf_read_audit,
+            contract_data_quota: self
+                .contract_data_quota
+                .or(default_node_config.contract_data_quota),
+            memoize_read_only_calls: self
+                .memoize_read_only_calls
+                .unwrap_or(default_node_config.memoize_read_only_calls),
+            clarity_state_snapshot: self
+                .clarity_state_snapshot
+                .or(default_node_config.clarity_state_snapshot),
         };
         Ok(node_config)
     }
@@ -2521,6 +3145,9 @@ pub struct MinerConfigFile {
     pub probability_pick_no_estimate_tx: Option<u8>,
     pub block_reward_recipient: Option<String>,
     pub segwit: Option<bool>,
+    pub taproot: Option<bool>,
+    pub psbt_signing: Option<bool>,
+    pub psbt_signer_public_key: Option<String>,
     pub nonce_cache_size: Option<u64>,
     pub candidate_retry_cache_size: Option<u64>,
     pub unprocessed_block_deadline_secs: Option<u64>,
@@ -2542,6 +3169,9 @@ pub struct MinerConfigFile {
     pub subsequent_rejection_pause_ms: Option<u64>,
     pub block_commit_delay_ms: Option<u64>,
     pub tenure_cost_limit_per_block_percentage: Option<u8>,
+    pub skip_txs_over_tenure_budget: Option<bool>,
+    pub block_assembly_strategy: Option<String>,
+    pub tx_ordering_commitment: Option<String>,
 }
 
 impl MinerConfigFile {
@@ -2568,6 +3198,33 @@ impl MinerConfigFile {
             } else {
                 miner_default_config.tenure_cost_limit_per_block_percentage
             };
+
+        let block_assembly_strategy = match self.block_assembly_strategy {
+            Some(name) if block_assembly_strategy(&name).is_none() => {
+                return Err(format!(
+                    "miner.block_assembly_strategy must be one of {}",
+                    BLOCK_ASSEMBLY_STRATEGIES.join(", ")
+                ));
+            }
+            Some(name) => name,
+            None => miner_default_config.block_assembly_strategy,
+        };
+
+        let tx_ordering_commitment = match self.tx_ordering_commitment {
+            Some(name) => Some(TxOrderingRule::from_name(&name).ok_or_else(|| {
+                format!(
+                    "miner.tx_ordering_commitment must be one of {}",
+                    TX_ORDERING_RULES.join(", ")
+                )
+            })?),
+            None => miner_default_config.tx_ordering_commitment,
+        };
+
+        if self.psbt_signer_public_key.is_some() && self.psbt_signing != Some(true) {
+            return Err(
+                "miner.psbt_signer_public_key requires miner.psbt_signing = true".to_string(),
+            );
+        }
         Ok(MinerConfig {
             first_attempt_time_ms: self
                 .first_attempt_time_ms
@@ -2595,6 +3252,18 @@ impl MinerConfigFile {
                 })
                 .transpose()?,
             segwit: self.segwit.unwrap_or(miner_default_config.segwit),
+            taproot: self.taproot.unwrap_or(miner_default_config.taproot),
+            psbt_signing: self
+                .psbt_signing
+                .unwrap_or(miner_default_config.psbt_signing),
+            psbt_signer_public_key: self
+                .psbt_signer_public_key
+                .map(|key| {
+                    Secp256k1PublicKey::from_hex(&key).map_err(|e| {
+                        format!("miner.psbt_signer_public_key is not a valid public key: {e}")
+                    })
+                })
+                .transpose()?,
             wait_for_block_download: miner_default_config.wait_for_block_download,
             nonce_cache_size: self
                 .nonce_cache_size
@@ -2675,6 +3344,9 @@ impl MinerConfigFile {
             subsequent_rejection_pause_ms: self.subsequent_rejection_pause_ms.unwrap_or(miner_default_config.subsequent_rejection_pause_ms),
             block_commit_delay: self.block_commit_delay_ms.map(Duration::from_millis).unwrap_or(miner_default_config.block_commit_delay),
             tenure_cost_limit_per_block_percentage,
+            skip_txs_over_tenure_budget: self.skip_txs_over_tenure_budget.unwrap_or(miner_default_config.skip_txs_over_tenure_budget),
+            block_assembly_strategy,
+            tx_ordering_commitment,
         })
     }
 }
@@ -2736,6 +3408,8 @@ pub enum EventKeyType {
     MinedMicroblocks,
     StackerDBChunks,
     BlockProposal,
+    TenureChanges,
+    Reorgs,
 }
 
 impl EventKeyType {
@@ -2768,6 +3442,14 @@ impl EventKeyType {
             return Some(EventKeyType::BlockProposal);
         }
 
+        if raw_key == "tenure_changes" {
+            return Some(EventKeyType::TenureChanges);
+        }
+
+        if raw_key == "reorgs" {
+            return Some(EventKeyType::Reorgs);
+        }
+
         let comps: Vec<_> = raw_key.split("::").collect();
         if comps.len() == 1 {
             let split: Vec<_> = comps[0].split('.').collect();
@@ -2891,131 +3573,423 @@ mod tests {
     }
 
     #[test]
-    fn test_deny_unknown_fields() {
-        {
-            let err = ConfigFile::from_str(
-                r#"
-            [node]
-            name = "test"
-            unknown_field = "test"
-            "#,
-            )
-            .unwrap_err();
-            assert!(err.starts_with("Invalid toml: unknown field `unknown_field`"));
-        }
-
-        {
-            let err = ConfigFile::from_str(
-                r#"
-            [burnchain]
-            chain_id = 0x00000500
-            unknown_field = "test"
-            chain = "bitcoin"
-            "#,
-            )
-            .unwrap_err();
-            assert!(err.starts_with("Invalid toml: unknown field `unknown_field`"));
-        }
+    fn test_burnchain_rpc_backend() {
+        let config = Config::from_config_file(ConfigFile::from_str("").unwrap(), false).unwrap();
+        assert_eq!(config.burnchain.rpc_backend, BurnchainRpcBackend::Bitcoind);
+        assert_eq!(config.burnchain.block_filter_scan_depth, 6);
 
-        {
-            let err = ConfigFile::from_str(
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
                 r#"
-            [node]
-            rpc_bind = "0.0.0.0:20443"
-            unknown_field = "test"
-            p2p_bind = "0.0.0.0:20444"
-            "#,
+                [burnchain]
+                rpc_backend = "block_filters"
+                block_filter_scan_depth = 12
+                "#,
             )
-            .unwrap_err();
-            assert!(err.starts_with("Invalid toml: unknown field `unknown_field`"));
-        }
+            .unwrap(),
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            config.burnchain.rpc_backend,
+            BurnchainRpcBackend::BlockFilters
+        );
+        assert_eq!(config.burnchain.block_filter_scan_depth, 12);
 
-        {
-            let err = ConfigFile::from_str(
+        let err = Config::from_config_file(
+            ConfigFile::from_str(
                 r#"
-            [[ustx_balance]]
-            address = "ST3AM1A56AK2C1XAFJ4115ZSV26EB49BVQ10MGCS0"
-            amount = 10000000000000000
-            unknown_field = "test"
-            "#,
+                [burnchain]
+                rpc_backend = "electrum"
+                "#,
             )
-            .unwrap_err();
-            assert!(err.starts_with("Invalid toml: unknown field `unknown_field`"));
-        }
+            .unwrap(),
+            false,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            "Bad burnchain RPC backend supplied in configuration file: electrum"
+        );
+    }
 
-        {
-            let err = ConfigFile::from_str(
-                r#"
-            [[events_observer]]
-            endpoint = "localhost:30000"
-            unknown_field = "test"
-            events_keys = ["stackerdb", "block_proposal", "burn_blocks"]
-            "#,
-            )
-            .unwrap_err();
-            assert!(err.starts_with("Invalid toml: unknown field `unknown_field`"));
-        }
+    #[test]
+    fn test_rbf_fee_limits() {
+        let config = Config::from_config_file(ConfigFile::from_str("").unwrap(), false).unwrap();
+        assert_eq!(config.burnchain.rbf_fee_check_interval_ms, 30_000);
+        assert_eq!(config.burnchain.rbf_max_tenure_spend, None);
+        assert_eq!(
+            config.burnchain.get_rbf_max_fee_rate(),
+            DEFAULT_SATS_PER_VB * DEFAULT_MAX_RBF_RATE / 100
+        );
 
-        {
-            let err = ConfigFile::from_str(
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
                 r#"
-            [connection_options]
-            inbox_maxlen = 100
-            outbox_maxlen = 200
-            unknown_field = "test"
-            "#,
+                [burnchain]
+                satoshis_per_byte = 20
+                max_rbf = 200
+                rbf_fee_check_interval_ms = 0
+                rbf_max_tenure_spend = 1000000
+                "#,
             )
-            .unwrap_err();
-            assert!(err.starts_with("Invalid toml: unknown field `unknown_field`"));
-        }
+            .unwrap(),
+            false,
+        )
+        .unwrap();
+        assert_eq!(config.burnchain.rbf_fee_check_interval_ms, 0);
+        assert_eq!(config.burnchain.rbf_max_tenure_spend, Some(1_000_000));
+        assert_eq!(config.burnchain.get_rbf_max_fee_rate(), 40);
 
-        {
-            let err = ConfigFile::from_str(
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
                 r#"
-            [fee_estimation]
-            cost_estimator = "foo"
-            unknown_field = "test"
-            "#,
+                [burnchain]
+                satoshis_per_byte = 20
+                rbf_max_fee_rate = 100
+                "#,
             )
-            .unwrap_err();
-            assert!(err.starts_with("Invalid toml: unknown field `unknown_field`"));
-        }
+            .unwrap(),
+            false,
+        )
+        .unwrap();
+        assert_eq!(config.burnchain.get_rbf_max_fee_rate(), 100);
+    }
 
-        {
-            let err = ConfigFile::from_str(
-                r#"
-            [miner]
-            first_attempt_time_ms = 180_000
-            unknown_field = "test"
-            subsequent_attempt_time_ms = 360_000
-            "#,
-            )
-            .unwrap_err();
-            println!("{err}");
-            assert!(err.starts_with("Invalid toml: unknown field `unknown_field`"));
-        }
+    #[test]
+    fn test_burnchain_checkpoints() {
+        let config = Config::from_config_file(ConfigFile::from_str("").unwrap(), false).unwrap();
+        assert_eq!(config.burnchain.checkpoint_interval, 0);
+        assert_eq!(config.burnchain.checkpoint_retain, 2);
 
-        {
-            let err = ConfigFile::from_str(
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
                 r#"
-                [atlas]
-                attachments_max_size = 100
-                unknown_field = "test"
+                [burnchain]
+                checkpoint_interval = 1000
+                checkpoint_retain = 3
                 "#,
             )
-            .unwrap_err();
-            assert!(err.starts_with("Invalid toml: unknown field `unknown_field`"));
-        }
+            .unwrap(),
+            false,
+        )
+        .unwrap();
+        assert_eq!(config.burnchain.checkpoint_interval, 1000);
+        assert_eq!(config.burnchain.checkpoint_retain, 3);
+        assert_eq!(
+            config.get_burnchain_checkpoints_path(),
+            PathBuf::from(&config.node.working_dir)
+                .join("mocknet")
+                .join("burnchain_checkpoints")
+        );
     }
 
     #[test]
-    fn test_example_confs() {
-        // For each config file in the ../conf/ directory, we should be able to parse it
-        let conf_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("conf");
-        println!("Reading config files from: {conf_dir:?}");
-        let conf_files = fs::read_dir(conf_dir).unwrap();
+    fn test_failover_nodes() {
+        let config = Config::from_config_file(ConfigFile::from_str("").unwrap(), false).unwrap();
+        assert!(config.burnchain.failover_nodes.is_empty());
+        assert_eq!(config.burnchain.health_check_interval_ms, 30_000);
+        assert_eq!(config.burnchain.failover_max_lag, 2);
 
-        for entry in conf_files {
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [burnchain]
+                peer_host = "127.0.0.1"
+                rpc_port = 18443
+                peer_port = 18444
+                username = "user"
+                password = "pass"
+                health_check_interval_ms = 5000
+                failover_max_lag = 1
+
+                [[burnchain.failover_nodes]]
+                peer_host = "127.0.0.2"
+
+                [[burnchain.failover_nodes]]
+                peer_host = "127.0.0.3"
+                rpc_port = 8332
+                username = "other"
+                password = "secret"
+                "#,
+            )
+            .unwrap(),
+            false,
+        )
+        .unwrap();
+        assert_eq!(config.burnchain.health_check_interval_ms, 5000);
+        assert_eq!(config.burnchain.failover_max_lag, 1);
+        assert_eq!(
+            config.burnchain.failover_nodes,
+            vec![
+                BitcoindEndpoint {
+                    peer_host: "127.0.0.2".into(),
+                    peer_port: 18444,
+                    rpc_port: 18443,
+                    rpc_ssl: false,
+                    username: Some("user".into()),
+                    password: Some("pass".into()),
+                },
+                BitcoindEndpoint {
+                    peer_host: "127.0.0.3".into(),
+                    peer_port: 18444,
+                    rpc_port: 8332,
+                    rpc_ssl: false,
+                    username: Some("other".into()),
+                    password: Some("secret".into()),
+                },
+            ]
+        );
+
+        let mut burnchain = config.burnchain.clone();
+        config.burnchain.failover_nodes[1].apply(&mut burnchain);
+        assert_eq!(
+            BitcoindEndpoint::from_burnchain_config(&burnchain),
+            config.burnchain.failover_nodes[1]
+        );
+        assert_eq!(burnchain.get_rpc_url(None), "http://127.0.0.3:8332");
+
+        let err = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [burnchain]
+                peer_host = "127.0.0.1"
+
+                [[burnchain.failover_nodes]]
+                peer_host = "127.0.0.1"
+                "#,
+            )
+            .unwrap(),
+            false,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            "burnchain.failover_nodes must not include the primary bitcoind"
+        );
+    }
+
+    #[test]
+    fn test_zmq_endpoint() {
+        let config = Config::from_config_file(ConfigFile::from_str("").unwrap(), false).unwrap();
+        assert_eq!(config.burnchain.zmq_endpoint, None);
+
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [burnchain]
+                zmq_endpoint = "tcp://127.0.0.1:28332"
+                "#,
+            )
+            .unwrap(),
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            config.burnchain.zmq_endpoint.as_deref(),
+            Some("tcp://127.0.0.1:28332")
+        );
+
+        let err = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [burnchain]
+                zmq_endpoint = "ipc:///tmp/bitcoind.sock"
+                "#,
+            )
+            .unwrap(),
+            false,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            "burnchain.zmq_endpoint must be a tcp:// endpoint, got ipc:///tmp/bitcoind.sock"
+        );
+    }
+
+    #[test]
+    fn test_miner_wallet_signing() {
+        let config = Config::from_config_file(ConfigFile::from_str("").unwrap(), false).unwrap();
+        assert!(!config.miner.taproot);
+        assert!(!config.miner.psbt_signing);
+        assert!(config.miner.psbt_signer_public_key.is_none());
+
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [miner]
+                taproot = true
+                psbt_signing = true
+                psbt_signer_public_key = "02cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115"
+                "#,
+            )
+            .unwrap(),
+            false,
+        )
+        .unwrap();
+        assert!(config.miner.taproot);
+        assert!(config.miner.psbt_signing);
+        assert_eq!(
+            config.miner.psbt_signer_public_key.unwrap().to_hex(),
+            "02cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115"
+        );
+
+        let err = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [miner]
+                psbt_signer_public_key = "02cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115"
+                "#,
+            )
+            .unwrap(),
+            false,
+        )
+        .unwrap_err();
+        assert!(err.contains("psbt_signing"));
+
+        let err = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [miner]
+                psbt_signing = true
+                psbt_signer_public_key = "not a key"
+                "#,
+            )
+            .unwrap(),
+            false,
+        )
+        .unwrap_err();
+        assert!(err.contains("psbt_signer_public_key"));
+    }
+
+    #[test]
+    fn test_deny_unknown_fields() {
+        {
+            let err = ConfigFile::from_str(
+                r#"
+            [node]
+            name = "test"
+            unknown_field = "test"
+            "#,
+            )
+            .unwrap_err();
+            assert!(err.starts_with("Invalid toml: node: unknown field `unknown_field`"));
+        }
+
+        {
+            let err = ConfigFile::from_str(
+                r#"
+            [burnchain]
+            chain_id = 0x00000500
+            unknown_field = "test"
+            chain = "bitcoin"
+            "#,
+            )
+            .unwrap_err();
+            assert!(err.starts_with("Invalid toml: burnchain: unknown field `unknown_field`"));
+        }
+
+        {
+            let err = ConfigFile::from_str(
+                r#"
+            [node]
+            rpc_bind = "0.0.0.0:20443"
+            unknown_field = "test"
+            p2p_bind = "0.0.0.0:20444"
+            "#,
+            )
+            .unwrap_err();
+            assert!(err.starts_with("Invalid toml: node: unknown field `unknown_field`"));
+        }
+
+        {
+            let err = ConfigFile::from_str(
+                r#"
+            [[ustx_balance]]
+            address = "ST3AM1A56AK2C1XAFJ4115ZSV26EB49BVQ10MGCS0"
+            amount = 10000000000000000
+            unknown_field = "test"
+            "#,
+            )
+            .unwrap_err();
+            assert!(err.starts_with("Invalid toml: ustx_balance: unknown field `unknown_field`"));
+        }
+
+        {
+            let err = ConfigFile::from_str(
+                r#"
+            [[events_observer]]
+            endpoint = "localhost:30000"
+            unknown_field = "test"
+            events_keys = ["stackerdb", "block_proposal", "burn_blocks"]
+            "#,
+            )
+            .unwrap_err();
+            assert!(err.starts_with("Invalid toml: events_observer: unknown field `unknown_field`"));
+        }
+
+        {
+            let err = ConfigFile::from_str(
+                r#"
+            [connection_options]
+            inbox_maxlen = 100
+            outbox_maxlen = 200
+            unknown_field = "test"
+            "#,
+            )
+            .unwrap_err();
+            assert!(
+                err.starts_with("Invalid toml: connection_options: unknown field `unknown_field`")
+            );
+        }
+
+        {
+            let err = ConfigFile::from_str(
+                r#"
+            [fee_estimation]
+            cost_estimator = "foo"
+            unknown_field = "test"
+            "#,
+            )
+            .unwrap_err();
+            assert!(err.starts_with("Invalid toml: fee_estimation: unknown field `unknown_field`"));
+        }
+
+        {
+            let err = ConfigFile::from_str(
+                r#"
+            [miner]
+            first_attempt_time_ms = 180_000
+            unknown_field = "test"
+            subsequent_attempt_time_ms = 360_000
+            "#,
+            )
+            .unwrap_err();
+            println!("{err}");
+            assert!(err.starts_with("Invalid toml: miner: unknown field `unknown_field`"));
+        }
+
+        {
+            let err = ConfigFile::from_str(
+                r#"
+                [atlas]
+                attachments_max_size = 100
+                unknown_field = "test"
+                "#,
+            )
+            .unwrap_err();
+            assert!(err.starts_with("Invalid toml: atlas: unknown field `unknown_field`"));
+        }
+    }
+
+    #[test]
+    fn test_example_confs() {
+        // For each config file in the ../conf/ directory, we should be able to parse it
+        let conf_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("conf");
+        println!("Reading config files from: {conf_dir:?}");
+        let conf_files = fs::read_dir(conf_dir).unwrap();
+
+        for entry in conf_files {
             let entry = entry.unwrap();
             let path = entry.path();
             if path.is_file() {
@@ -3094,6 +4068,436 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_load_rpc_rate_limits() {
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [connection_options]
+                read_rate_limit_burst = 100
+                read_rate_limit_steady_rate = 20.0
+                read_only_call_rate_limit_burst = 10
+                read_only_call_rate_limit_steady_rate = 0.5
+                "#,
+            )
+            .unwrap(),
+            false,
+        )
+        .expect("Expected to be able to parse RPC rate limits from file");
+
+        assert_eq!(
+            config.connection_options.rpc_rate_limits,
+            RPCRateLimits {
+                read: Some(RPCRateLimit {
+                    burst: 100,
+                    steady_rate: 20.0
+                }),
+                read_only_call: Some(RPCRateLimit {
+                    burst: 10,
+                    steady_rate: 0.5
+                }),
+                ..RPCRateLimits::default()
+            }
+        );
+
+        Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [connection_options]
+                write_rate_limit_burst = 100
+                "#,
+            )
+            .unwrap(),
+            false,
+        )
+        .expect_err("Expected a rate limit without a steady rate to be rejected");
+    }
+
+    #[test]
+    fn should_load_rpc_response_cache_size() {
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [connection_options]
+                rpc_response_cache_size = 1000
+                "#,
+            )
+            .unwrap(),
+            false,
+        )
+        .expect("Expected to be able to parse RPC response cache size from file");
+        assert_eq!(config.connection_options.rpc_response_cache_size, 1000);
+    }
+
+    #[test]
+    fn should_load_side_store_encryption_keys() {
+        let key = "01".repeat(32);
+        let previous_key = "02".repeat(32);
+        let config = Config::from_config_file(
+            ConfigFile::from_str(&format!(
+                r#"
+                [node]
+                side_store_encryption_key = "{key}"
+                side_store_encryption_previous_keys = ["{previous_key}"]
+                "#
+            ))
+            .unwrap(),
+            false,
+        )
+        .expect("Expected to be able to parse side-store encryption keys from file");
+
+        let cipher = SideStoreCipher::from_hex(&key, &[previous_key.clone()]).unwrap();
+        assert_eq!(config.node.side_store_cipher, Some(cipher.clone()));
+        assert_eq!(config.node.get_marf_opts().side_store_cipher, Some(cipher));
+
+        let config = Config::from_config_file(
+            ConfigFile::from_str(&format!(
+                r#"
+                [node]
+                side_store_encryption_key = "{key}"
+                side_store_encryption_required = true
+                "#
+            ))
+            .unwrap(),
+            false,
+        )
+        .expect("Expected to be able to require encrypted side-store values");
+        assert!(config.node.side_store_cipher.unwrap().requires_encrypted());
+
+        Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [node]
+                side_store_encryption_required = true
+                "#,
+            )
+            .unwrap(),
+            false,
+        )
+        .expect_err("Expected requiring encryption without a key to be rejected");
+
+        Config::from_config_file(
+            ConfigFile::from_str(&format!(
+                r#"
+                [node]
+                side_store_encryption_previous_keys = ["{previous_key}"]
+                "#
+            ))
+            .unwrap(),
+            false,
+        )
+        .expect_err("Expected previous keys without a current key to be rejected");
+
+        Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [node]
+                side_store_encryption_key = "0101"
+                "#,
+            )
+            .unwrap(),
+            false,
+        )
+        .expect_err("Expected a short key to be rejected");
+    }
+
+    #[test]
+    fn should_load_marf_read_audit() {
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [node]
+                marf_read_audit_path = "/tmp/audit.log"
+                marf_read_audit_max_files = 3
+                "#,
+            )
+            .unwrap(),
+            false,
+        )
+        .expect("Expected to be able to parse the MARF read audit options from file");
+
+        let mut expected = MarfReadAuditConfig::new(PathBuf::from("/tmp/audit.log"));
+        expected.max_files = 3;
+        assert_eq!(config.node.marf_read_audit, Some(expected.clone()));
+        assert_eq!(config.node.get_marf_opts().read_audit, Some(expected));
+
+        Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [node]
+                marf_read_audit_max_files = 3
+                "#,
+            )
+            .unwrap(),
+            false,
+        )
+        .expect_err("Expected rotation options without a path to be rejected");
+    }
+
+    #[test]
+    fn should_load_clarity_state_snapshot() {
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [node]
+                clarity_state_snapshot = "/tmp/mainnet-state.jsonl"
+                "#,
+            )
+            .unwrap(),
+            false,
+        )
+        .expect("Expected to be able to parse the Clarity state snapshot from file");
+
+        assert_eq!(
+            config.node.clarity_state_snapshot.as_deref(),
+            Some("/tmp/mainnet-state.jsonl")
+        );
+
+        let err = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [burnchain]
+                mode = "mainnet"
+
+                [node]
+                clarity_state_snapshot = "/tmp/mainnet-state.jsonl"
+                "#,
+            )
+            .unwrap(),
+            false,
+        )
+        .expect_err("Expected a Clarity state snapshot to be rejected on mainnet");
+        assert_eq!(
+            err,
+            "Attempted to run mainnet node with `clarity_state_snapshot`"
+        );
+    }
+
+    #[test]
+    fn should_load_contract_data_quota() {
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [node]
+                contract_data_quota = 1000000
+                "#,
+            )
+            .unwrap(),
+            false,
+        )
+        .expect("Expected to be able to parse the contract data quota from file");
+
+        assert_eq!(config.node.contract_data_quota, Some(1_000_000));
+        assert_eq!(
+            config.node.get_marf_opts().contract_data_quota,
+            Some(1_000_000)
+        );
+
+        let err = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [burnchain]
+                mode = "mainnet"
+
+                [node]
+                contract_data_quota = 1000000
+                "#,
+            )
+            .unwrap(),
+            false,
+        )
+        .expect_err("Expected a contract data quota to be rejected on mainnet");
+        assert_eq!(
+            err,
+            "Attempted to run mainnet node with `contract_data_quota`"
+        );
+    }
+
+    #[test]
+    fn should_load_contract_analysis_warm_load() {
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [node]
+                contract_analysis_cache_size = 512
+                contract_analysis_warm_load = 100
+                "#,
+            )
+            .unwrap(),
+            false,
+        )
+        .expect("Expected to be able to parse contract analysis cache settings from file");
+        assert_eq!(config.node.contract_analysis_cache_size, 512);
+        assert_eq!(config.node.contract_analysis_warm_load, Some(100));
+
+        Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [node]
+                contract_analysis_warm_load = 100
+                "#,
+            )
+            .unwrap(),
+            false,
+        )
+        .expect_err("Expected warm-loading without a cache to be rejected");
+    }
+
+    #[test]
+    fn should_load_state_prune_depth() {
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [node]
+                side_store_maintenance_interval_secs = 600
+                state_prune_depth = 1000
+                "#,
+            )
+            .unwrap(),
+            false,
+        )
+        .expect("Expected to be able to parse state pruning settings from file");
+        assert_eq!(config.node.state_prune_depth, Some(1000));
+
+        for invalid in [
+            "state_prune_depth = 1000",
+            "side_store_maintenance_interval_secs = 600\nstate_prune_depth = 0",
+        ] {
+            Config::from_config_file(
+                ConfigFile::from_str(&format!("[node]\n{invalid}")).unwrap(),
+                false,
+            )
+            .expect_err("Expected invalid state pruning settings to be rejected");
+        }
+    }
+
+    #[test]
+    fn should_load_fork_prune_depth() {
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [node]
+                side_store_maintenance_interval_secs = 600
+                fork_prune_depth = 500
+                "#,
+            )
+            .unwrap(),
+            false,
+        )
+        .expect("Expected to be able to parse fork pruning settings from file");
+        assert_eq!(config.node.fork_prune_depth, Some(500));
+        assert_eq!(config.node.state_prune_depth, None);
+
+        for invalid in [
+            "fork_prune_depth = 500",
+            "side_store_maintenance_interval_secs = 600\nfork_prune_depth = 0",
+        ] {
+            Config::from_config_file(
+                ConfigFile::from_str(&format!("[node]\n{invalid}")).unwrap(),
+                false,
+            )
+            .expect_err("Expected invalid fork pruning settings to be rejected");
+        }
+    }
+
+    #[test]
+    fn should_load_microblock_archive() {
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [node]
+                side_store_maintenance_interval_secs = 600
+                microblock_archive_path = "/tmp/microblocks.sqlite"
+                microblock_archive_depth = 500
+                "#,
+            )
+            .unwrap(),
+            false,
+        )
+        .expect("Expected to be able to parse microblock archive settings from file");
+        assert_eq!(
+            config.node.microblock_archive_path,
+            Some(PathBuf::from("/tmp/microblocks.sqlite"))
+        );
+        assert_eq!(config.node.microblock_archive_depth, 500);
+
+        for invalid in [
+            "microblock_archive_path = \"/tmp/microblocks.sqlite\"",
+            "side_store_maintenance_interval_secs = 600\nmicroblock_archive_depth = 0",
+        ] {
+            Config::from_config_file(
+                ConfigFile::from_str(&format!("[node]\n{invalid}")).unwrap(),
+                false,
+            )
+            .expect_err("Expected invalid microblock archive settings to be rejected");
+        }
+    }
+
+    #[test]
+    fn should_load_fast_sync_snapshot() {
+        let signer = Secp256k1PublicKey::from_private(&Secp256k1PrivateKey::new());
+        let config = Config::from_config_file(
+            ConfigFile::from_str(&format!(
+                r#"
+                [node]
+                fast_sync_snapshot = "/tmp/snapshot"
+                fast_sync_trusted_signers = ["{}"]
+                "#,
+                signer.to_hex()
+            ))
+            .unwrap(),
+            false,
+        )
+        .expect("Expected to be able to parse fast sync settings from file");
+        assert_eq!(
+            config.node.fast_sync_snapshot,
+            Some(PathBuf::from("/tmp/snapshot"))
+        );
+        assert_eq!(config.node.fast_sync_trusted_signers, vec![signer]);
+
+        for invalid in [
+            "fast_sync_snapshot = \"/tmp/snapshot\"",
+            "fast_sync_snapshot = \"/tmp/snapshot\"\nfast_sync_trusted_signers = [\"00\"]",
+        ] {
+            Config::from_config_file(
+                ConfigFile::from_str(&format!("[node]\n{invalid}")).unwrap(),
+                false,
+            )
+            .expect_err("Expected invalid fast sync settings to be rejected");
+        }
+    }
+
+    #[test]
+    fn should_load_tx_ordering_commitment() {
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [miner]
+                tx_ordering_commitment = "salted-txid"
+                "#,
+            )
+            .unwrap(),
+            false,
+        )
+        .expect("Expected to be able to parse the transaction-ordering commitment from file");
+        assert_eq!(
+            config.miner.tx_ordering_commitment,
+            Some(TxOrderingRule::SaltedTxid)
+        );
+
+        Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [miner]
+                tx_ordering_commitment = "fee-rate"
+                "#,
+            )
+            .unwrap(),
+            false,
+        )
+        .expect_err("Expected an unknown transaction-ordering rule to be rejected");
+    }
+
     #[test]
     fn should_load_affirmation_map() {
         let affirmation_string = "nnnnnnnnnnnnnnnnnnnnnnnnnnnnnnnnppnnnnnnnnnnnnnnnnnnnnnnnnpppppnnnnnnnnnnnnnnnnnnnnnnnpppppppppppppppnnnnnnnnnnnnnnnnnnnnnnnppppppppppnnnnnnnnnnnnnnnnnnnppppnnnnnnnnnnnnnnnnnnnnnnnppppppppnnnnnnnnnnnnnnnnnnnnnnnppnppnnnnnnnnnnnnnnnnnnnnnnnppppnnnnnnnnnnnnnnnnnnnnnnnnnppppppnnnnnnnnnnnnnnnnnnnnnnnnnppnnnnnnnnnnnnnnnnnnnnnnnnnpppppppnnnnnnnnnnnnnnnnnnnnnnnnnnpnnnnnnnnnnnnnnnnnnnnnnnnnpppnppppppppppppppnnppppnpa";
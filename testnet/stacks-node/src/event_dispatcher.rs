@@ -39,6 +39,7 @@ use stacks::chainstate::stacks::boot::{
     NakamotoSignerEntry, PoxStartCycleInfo, RewardSet, RewardSetData, SIGNERS_NAME,
 };
 use stacks::chainstate::stacks::db::accounts::MinerReward;
+use stacks::chainstate::stacks::db::reorgs::StacksReorg;
 use stacks::chainstate::stacks::db::unconfirmed::ProcessedUnconfirmedState;
 use stacks::chainstate::stacks::db::{MinerRewardInfo, StacksBlockHeaderTypes, StacksHeaderInfo};
 use stacks::chainstate::stacks::events::{
@@ -47,7 +48,8 @@ use stacks::chainstate::stacks::events::{
 };
 use stacks::chainstate::stacks::miner::TransactionEvent;
 use stacks::chainstate::stacks::{
-    StacksBlock, StacksMicroblock, StacksTransaction, TransactionPayload,
+    StacksBlock, StacksMicroblock, StacksTransaction, TenureChangeCause, TenureChangePayload,
+    TransactionPayload,
 };
 use stacks::core::mempool::{MemPoolDropReason, MemPoolEventDispatcher, ProposalCallbackReceiver};
 use stacks::libstackerdb::StackerDBChunkData;
@@ -106,6 +108,8 @@ pub const PATH_BURN_BLOCK_SUBMIT: &str = "new_burn_block";
 pub const PATH_BLOCK_PROCESSED: &str = "new_block";
 pub const PATH_ATTACHMENT_PROCESSED: &str = "attachments/new";
 pub const PATH_PROPOSAL_RESPONSE: &str = "proposal_response";
+pub const PATH_TENURE_CHANGE: &str = "new_tenure";
+pub const PATH_REORG: &str = "reorg";
 
 /// This struct receives StackerDB event callbacks without registering
 /// over the JSON/RPC interface.
@@ -143,6 +147,91 @@ pub struct MinedMicroblockEvent {
     pub anchor_block: BlockHeaderHash,
 }
 
+/// A step in the lifecycle of a tenure
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TenureChangeKind {
+    /// A tenure began with a block found by its sortition's winner
+    Begin,
+    /// A tenure was extended into a new burn view
+    Extend,
+    /// A tenure ended because the next one began
+    End,
+}
+
+/// A tenure change, as carried out by a processed block's tenure-change transaction
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TenureChangeEvent {
+    pub kind: TenureChangeKind,
+    /// The cause of the tenure-change transaction
+    pub cause: String,
+    /// The tenure that began, was extended or ended
+    pub tenure_consensus_hash: String,
+    /// The hash160 of the tenure's miner key (unknown for the tenure that ended)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub miner_pubkey_hash: Option<String>,
+    /// The last-seen sortition as of the tenure change
+    pub burn_view_consensus_hash: String,
+    /// The last block of the previous tenure, or of the tenure before its extension
+    pub previous_tenure_end: String,
+    /// How many blocks the previous tenure, or the tenure before its extension, produced
+    pub previous_tenure_blocks: u32,
+    /// The block with the tenure-change transaction
+    pub index_block_hash: String,
+    pub block_height: u64,
+    pub burn_block_height: u32,
+    pub txid: String,
+}
+
+impl TenureChangeEvent {
+    /// The events of the tenure-change transaction `txid`, with `payload`, in the block described
+    /// by `metadata`
+    pub fn from_tenure_change(
+        metadata: &StacksHeaderInfo,
+        txid: &Txid,
+        payload: &TenureChangePayload,
+    ) -> Vec<TenureChangeEvent> {
+        let cause = match payload.cause {
+            TenureChangeCause::BlockFound => "block_found",
+            TenureChangeCause::Extended => "extended",
+        };
+        let make_event =
+            |kind, tenure_consensus_hash: &ConsensusHash, miner_pubkey_hash| TenureChangeEvent {
+                kind,
+                cause: cause.to_string(),
+                tenure_consensus_hash: format!("0x{tenure_consensus_hash}"),
+                miner_pubkey_hash,
+                burn_view_consensus_hash: format!("0x{}", payload.burn_view_consensus_hash),
+                previous_tenure_end: format!("0x{}", payload.previous_tenure_end),
+                previous_tenure_blocks: payload.previous_tenure_blocks,
+                index_block_hash: format!("0x{}", metadata.index_block_hash()),
+                block_height: metadata.stacks_block_height,
+                burn_block_height: metadata.burn_header_height,
+                txid: format!("0x{txid}"),
+            };
+        let miner_pubkey_hash = Some(format!("0x{}", payload.pubkey_hash));
+        match payload.cause {
+            TenureChangeCause::BlockFound => vec![
+                make_event(
+                    TenureChangeKind::End,
+                    &payload.prev_tenure_consensus_hash,
+                    None,
+                ),
+                make_event(
+                    TenureChangeKind::Begin,
+                    &payload.tenure_consensus_hash,
+                    miner_pubkey_hash,
+                ),
+            ],
+            TenureChangeCause::Extended => vec![make_event(
+                TenureChangeKind::Extend,
+                &payload.tenure_consensus_hash,
+                miner_pubkey_hash,
+            )],
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct MinedNakamotoBlockEvent {
     pub target_burn_height: u64,
@@ -772,6 +861,14 @@ impl EventObserver {
         self.send_payload(payload, PATH_BURN_BLOCK_SUBMIT);
     }
 
+    fn send_tenure_change(&self, payload: &serde_json::Value) {
+        self.send_payload(payload, PATH_TENURE_CHANGE);
+    }
+
+    fn send_reorg(&self, payload: &serde_json::Value) {
+        self.send_payload(payload, PATH_REORG);
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn make_new_block_processed_payload(
         &self,
@@ -914,6 +1011,11 @@ pub struct EventDispatcher {
     /// Index into `registered_observers` that will receive block proposal events (Nakamoto and
     /// later)
     block_proposal_observers_lookup: HashSet<u16>,
+    /// Index into `registered_observers` that will receive tenure change events (Nakamoto and
+    /// later)
+    tenure_observers_lookup: HashSet<u16>,
+    /// Index into `registered_observers` that will receive Stacks chain reorg events
+    reorg_observers_lookup: HashSet<u16>,
     /// Channel for sending StackerDB events to the miner coordinator
     pub stackerdb_channel: Arc<Mutex<StackerDBChannel>>,
 }
@@ -1097,6 +1199,10 @@ impl BlockEventDispatcher for EventDispatcher {
             recipient_info,
         )
     }
+
+    fn announce_reorg(&self, reorg: &StacksReorg) {
+        self.process_reorg(reorg)
+    }
 }
 
 impl Default for EventDispatcher {
@@ -1121,6 +1227,8 @@ impl EventDispatcher {
             mined_microblocks_observers_lookup: HashSet::new(),
             stackerdb_observers_lookup: HashSet::new(),
             block_proposal_observers_lookup: HashSet::new(),
+            tenure_observers_lookup: HashSet::new(),
+            reorg_observers_lookup: HashSet::new(),
         }
     }
 
@@ -1187,6 +1295,21 @@ impl EventDispatcher {
                             }
                         }
                     }
+                    StacksTransactionEvent::ContractEvent(event_data) => {
+                        // observers subscribe to `emit-event` events by contract and event name,
+                        //  as they do to `print` events by contract and topic
+                        let event_key = (
+                            event_data.contract_identifier.clone(),
+                            event_data.name.to_string(),
+                        );
+                        if let Some(observer_indexes) =
+                            self.contract_events_observers_lookup.get(&event_key)
+                        {
+                            for o_i in observer_indexes {
+                                dispatch_matrix[*o_i as usize].insert(i);
+                            }
+                        }
+                    }
                     StacksTransactionEvent::STXEvent(STXEventType::STXTransferEvent(_))
                     | StacksTransactionEvent::STXEvent(STXEventType::STXMintEvent(_))
                     | StacksTransactionEvent::STXEvent(STXEventType::STXBurnEvent(_))
@@ -1330,6 +1453,50 @@ impl EventDispatcher {
                 self.registered_observers[observer_id].send_payload(&payload, PATH_BLOCK_PROCESSED);
             }
         }
+
+        self.process_tenure_changes(metadata, receipts);
+    }
+
+    /// Sends the tenure lifecycle events of a processed block's tenure-change transaction to the
+    /// interested observers
+    fn process_tenure_changes(
+        &self,
+        metadata: &StacksHeaderInfo,
+        receipts: &[StacksTransactionReceipt],
+    ) {
+        let interested_observers = self.filter_observers(&self.tenure_observers_lookup, false);
+        if interested_observers.is_empty() {
+            return;
+        }
+
+        for receipt in receipts.iter() {
+            let TransactionOrigin::Stacks(ref tx) = receipt.transaction else {
+                continue;
+            };
+            let TransactionPayload::TenureChange(ref tenure_change) = tx.payload else {
+                continue;
+            };
+            for event in TenureChangeEvent::from_tenure_change(metadata, &tx.txid(), tenure_change)
+            {
+                let payload = serde_json::to_value(event).unwrap();
+                for observer in interested_observers.iter() {
+                    observer.send_tenure_change(&payload);
+                }
+            }
+        }
+    }
+
+    /// Sends a reorg of the canonical Stacks chain to the interested observers
+    pub fn process_reorg(&self, reorg: &StacksReorg) {
+        let interested_observers = self.filter_observers(&self.reorg_observers_lookup, false);
+        if interested_observers.is_empty() {
+            return;
+        }
+
+        let payload = serde_json::to_value(reorg).unwrap();
+        for observer in interested_observers.iter() {
+            observer.send_reorg(&payload);
+        }
     }
 
     /// Creates a list of observers that are interested in the new microblocks event,
@@ -1688,6 +1855,12 @@ impl EventDispatcher {
                 EventKeyType::BlockProposal => {
                     self.block_proposal_observers_lookup.insert(observer_index);
                 }
+                EventKeyType::TenureChanges => {
+                    self.tenure_observers_lookup.insert(observer_index);
+                }
+                EventKeyType::Reorgs => {
+                    self.reorg_observers_lookup.insert(observer_index);
+                }
             }
         }
 
@@ -1711,6 +1884,7 @@ mod test {
     use stacks::util::secp256k1::MessageSignature;
     use stacks_common::bitvec::BitVec;
     use stacks_common::types::chainstate::{BurnchainHeaderHash, StacksBlockId};
+    use stacks_common::util::hash::Hash160;
     use tempfile::tempdir;
     use tiny_http::{Method, Response, Server, StatusCode};
 
@@ -1840,6 +2014,62 @@ mod test {
         assert_eq!(event_signer_signature, signer_signature);
     }
 
+    #[test]
+    fn test_tenure_change_events() {
+        let metadata = StacksHeaderInfo::regtest_genesis();
+        let txid = Txid([0x01; 32]);
+        let mut payload = TenureChangePayload {
+            tenure_consensus_hash: ConsensusHash([0x02; 20]),
+            prev_tenure_consensus_hash: ConsensusHash([0x03; 20]),
+            burn_view_consensus_hash: ConsensusHash([0x02; 20]),
+            previous_tenure_end: StacksBlockId([0x04; 32]),
+            previous_tenure_blocks: 5,
+            cause: TenureChangeCause::BlockFound,
+            pubkey_hash: Hash160([0x05; 20]),
+        };
+
+        // a new tenure ends the previous one
+        let events = TenureChangeEvent::from_tenure_change(&metadata, &txid, &payload);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].kind, TenureChangeKind::End);
+        assert_eq!(
+            events[0].tenure_consensus_hash,
+            format!("0x{}", ConsensusHash([0x03; 20]))
+        );
+        assert!(events[0].miner_pubkey_hash.is_none());
+        assert_eq!(events[0].previous_tenure_blocks, 5);
+        assert_eq!(events[1].kind, TenureChangeKind::Begin);
+        assert_eq!(
+            events[1].tenure_consensus_hash,
+            format!("0x{}", ConsensusHash([0x02; 20]))
+        );
+        assert_eq!(
+            events[1].miner_pubkey_hash,
+            Some(format!("0x{}", Hash160([0x05; 20])))
+        );
+        assert_eq!(events[1].cause, "block_found");
+        assert_eq!(
+            events[1].index_block_hash,
+            format!("0x{}", metadata.index_block_hash())
+        );
+
+        let json = serde_json::to_value(&events[0]).unwrap();
+        assert_eq!(json.get("kind").unwrap().as_str().unwrap(), "end");
+        assert!(json.get("miner_pubkey_hash").is_none());
+
+        // an extension into a new burn view
+        payload.cause = TenureChangeCause::Extended;
+        payload.burn_view_consensus_hash = ConsensusHash([0x06; 20]);
+        let events = TenureChangeEvent::from_tenure_change(&metadata, &txid, &payload);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, TenureChangeKind::Extend);
+        assert_eq!(events[0].cause, "extended");
+        assert_eq!(
+            events[0].burn_view_consensus_hash,
+            format!("0x{}", ConsensusHash([0x06; 20]))
+        );
+    }
+
     #[test]
     fn test_send_request_connect_timeout() {
         let timeout_duration = Duration::from_secs(3);
@@ -12,6 +12,7 @@ extern crate slog;
 
 pub use stacks_common::util;
 use stacks_common::util::hash::hex_bytes;
+use stacks_common::util::secp256k1::Secp256k1PrivateKey;
 
 pub mod monitoring;
 
@@ -31,7 +32,8 @@ pub mod syncctl;
 pub mod tenure;
 
 use std::collections::HashMap;
-use std::{env, panic, process};
+use std::path::{Path, PathBuf};
+use std::{env, fs, panic, process};
 
 use backtrace::Backtrace;
 use pico_args::Arguments;
@@ -41,6 +43,7 @@ use stacks::chainstate::coordinator::{get_next_recipients, OnChainRewardSetProvi
 use stacks::chainstate::stacks::address::PoxAddress;
 use stacks::chainstate::stacks::db::blocks::DummyEventDispatcher;
 use stacks::chainstate::stacks::db::StacksChainState;
+use stacks::clarity_vm::database::marf::MarfedKV;
 #[cfg(not(any(target_os = "macos", target_os = "windows", target_arch = "arm")))]
 use tikv_jemallocator::Jemalloc;
 
@@ -55,7 +58,7 @@ pub use self::run_loop::{helium, neon};
 pub use self::tenure::Tenure;
 use crate::chain_data::MinerStats;
 use crate::neon_node::{BlockMinerThread, TipCandidate};
-use crate::run_loop::boot_nakamoto;
+use crate::run_loop::{boot_nakamoto, snapshot};
 
 #[cfg(not(any(target_os = "macos", target_os = "windows", target_arch = "arm")))]
 #[global_allocator]
@@ -96,6 +99,59 @@ fn cli_pick_best_tip(config_path: &str, at_stacks_height: Option<u64>) -> TipCan
     BlockMinerThread::inner_pick_best_tip(stacks_tips, HashMap::new()).unwrap()
 }
 
+/// Implementation of `reencrypt-side-store` CLI option
+fn cli_reencrypt_side_store(config_path: &str) -> u64 {
+    info!("Loading config at path {config_path}");
+    let config = match ConfigFile::from_path(config_path) {
+        Ok(config_file) => Config::from_config_file(config_file, true).unwrap(),
+        Err(e) => {
+            warn!("Invalid config file: {e}");
+            process::exit(1);
+        }
+    };
+    if config.node.side_store_cipher.is_none() {
+        warn!("No node.side_store_encryption_key is configured");
+        process::exit(1);
+    }
+    let clarity_state_path =
+        StacksChainState::vm_state_index_root_path(PathBuf::from(config.get_chainstate_path_str()));
+    let mut marf = MarfedKV::open(
+        clarity_state_path
+            .to_str()
+            .expect("FATAL: non-UTF-8 chainstate path"),
+        None,
+        Some(config.node.get_marf_opts()),
+    )
+    .unwrap();
+    marf.reencrypt_side_store().unwrap()
+}
+
+/// Implementation of `export-snapshot` CLI option
+fn cli_export_snapshot(config_path: &str, snapshot_dir: &str, signing_key_path: &str) -> String {
+    info!("Loading config at path {config_path}");
+    let config = match ConfigFile::from_path(config_path) {
+        Ok(config_file) => Config::from_config_file(config_file, true).unwrap(),
+        Err(e) => {
+            warn!("Invalid config file: {e}");
+            process::exit(1);
+        }
+    };
+    let signing_key = fs::read_to_string(signing_key_path)
+        .map_err(|e| e.to_string())
+        .and_then(|key| Secp256k1PrivateKey::from_hex(key.trim()).map_err(|e| e.to_string()))
+        .unwrap_or_else(|e| {
+            warn!("Invalid signing key at {signing_key_path}: {e}");
+            process::exit(1);
+        });
+    match snapshot::export(&config, Path::new(snapshot_dir), &signing_key) {
+        Ok(manifest) => manifest.contents.snapshot_id().to_hex(),
+        Err(e) => {
+            warn!("Failed to export chainstate snapshot: {e}");
+            process::exit(1);
+        }
+    }
+}
+
 /// Implementation of `get_miner_spend` CLI option
 #[allow(clippy::incompatible_msrv)]
 fn cli_get_miner_spend(
@@ -386,6 +442,24 @@ fn main() {
             println!("Best tip is {best_tip:?}");
             process::exit(0);
         }
+        "reencrypt-side-store" => {
+            let config_path: String = args.value_from_str("--config").unwrap();
+            args.finish();
+
+            let rewritten = cli_reencrypt_side_store(&config_path);
+            println!("Re-encrypted {rewritten} side-store values");
+            process::exit(0);
+        }
+        "export-snapshot" => {
+            let config_path: String = args.value_from_str("--config").unwrap();
+            let snapshot_dir: String = args.value_from_str("--out").unwrap();
+            let signing_key_path: String = args.value_from_str("--signing-key-file").unwrap();
+            args.finish();
+
+            let snapshot_id = cli_export_snapshot(&config_path, &snapshot_dir, &signing_key_path);
+            println!("Exported snapshot {snapshot_id}");
+            process::exit(0);
+        }
         "get-spend-amount" => {
             let config_path: String = args.value_from_str("--config").unwrap();
             let at_burnchain_height: Option<u64> =
@@ -485,6 +559,18 @@ key-for-seed\tOutput the associated secret key for a burnchain signer created wi
 \t\tCan be passed a config file for the seed via the `--config <file>` option *or* by supplying the hex seed on
 \t\tthe command line directly.
 
+reencrypt-side-store\tRe-encrypt the Clarity side-store with node.side_store_encryption_key, e.g. after
+\t\trotating keys. Run this while the node is stopped.
+\t\tArguments:
+\t\t  --config: path to the config file
+
+export-snapshot\tExport the chainstate to a signed snapshot that other nodes can restore with
+\t\tnode.fast_sync_snapshot, anchored at the current burnchain tip. Run this while the node is stopped.
+\t\tArguments:
+\t\t  --config: path to the config file
+\t\t  --out: directory to write the snapshot to
+\t\t  --signing-key-file: file holding the hex secp256k1 private key to sign the snapshot with
+
 replay-mock-mining\tReplay mock mined blocks from <dir>
 \t\tArguments:
 \t\t  --path: path to directory of mock mined blocks
@@ -16,7 +16,6 @@
 use std::collections::HashSet;
 use std::io::Write;
 use std::sync::mpsc::Receiver;
-use std::thread::JoinHandle;
 use std::{fs, thread};
 
 use stacks::burnchains::{BurnchainSigner, Txid};
@@ -38,6 +37,7 @@ use crate::burnchains::bitcoin_regtest_controller::addr2str;
 use crate::burnchains::Error as BurnchainsError;
 use crate::neon_node::{LeaderKeyRegistrationState, StacksNode as NeonNode};
 use crate::run_loop::boot_nakamoto::Neon2NakaData;
+use crate::run_loop::lifecycle::{self, Lifecycle, SubsystemHandle};
 use crate::run_loop::nakamoto::{Globals, RunLoop};
 use crate::run_loop::RegisteredKey;
 
@@ -57,7 +57,8 @@ pub const BLOCK_PROCESSOR_STACK_SIZE: usize = 32 * 1024 * 1024; // 32 MB
 pub type BlockCommits = HashSet<Txid>;
 
 /// Node implementation for both miners and followers.
-/// This struct is used to set up the node proper and launch the p2p thread and relayer thread.
+/// This struct is used to set up the node proper and register the p2p and relayer threads with
+/// the run loop's `Lifecycle`, which starts and stops them.
 /// It is further used by the main thread to communicate with these two threads.
 pub struct StacksNode {
     /// Atlas network configuration
@@ -66,10 +67,6 @@ pub struct StacksNode {
     pub globals: Globals,
     /// True if we're a miner
     is_miner: bool,
-    /// handle to the p2p thread
-    pub p2p_thread_handle: JoinHandle<()>,
-    /// handle to the relayer thread
-    pub relayer_thread_handle: JoinHandle<()>,
 }
 
 /// Types of errors that can arise during Nakamoto StacksNode operation
@@ -163,13 +160,16 @@ impl StacksNode {
         });
     }
 
+    /// Set up the node, and register its relayer and p2p threads with `lifecycle`. They are
+    /// started by `lifecycle.start_all()`.
     pub fn spawn(
         runloop: &RunLoop,
         globals: Globals,
         // relay receiver endpoint for the p2p thread, so the relayer can feed it data to push
         relay_recv: Receiver<RelayerDirective>,
         data_from_neon: Option<Neon2NakaData>,
-    ) -> StacksNode {
+        lifecycle: &mut Lifecycle,
+    ) -> Result<StacksNode, lifecycle::Error> {
         let config = runloop.config().clone();
         let is_miner = runloop.is_miner();
         let burnchain = runloop.get_burnchain();
@@ -239,37 +239,48 @@ impl StacksNode {
 
         StacksNode::set_monitoring_miner_address(&keychain, &relayer_thread);
 
-        let relayer_thread_handle = thread::Builder::new()
-            .name(format!("relayer-{}", &local_peer.data_url))
-            .stack_size(BLOCK_PROCESSOR_STACK_SIZE)
-            .spawn(move || {
-                relayer_thread.main(relay_recv);
-            })
-            .expect("FATAL: failed to start relayer thread");
+        let relayer_name = format!("relayer-{}", &local_peer.data_url);
+        let relayer_globals = globals.clone();
+        lifecycle.add_once(
+            lifecycle::RELAYER,
+            &[lifecycle::CHAINS_COORDINATOR],
+            move || {
+                let relayer_thread_handle = thread::Builder::new()
+                    .name(relayer_name)
+                    .stack_size(BLOCK_PROCESSOR_STACK_SIZE)
+                    .spawn(move || {
+                        relayer_thread.main(relay_recv);
+                    })?;
+                Ok(SubsystemHandle::new(relayer_thread_handle, move || {
+                    relayer_globals.signal_stop()
+                }))
+            },
+        )?;
 
         let p2p_event_dispatcher = runloop.get_event_dispatcher();
         let p2p_thread = PeerThread::new(runloop, p2p_net);
-        let p2p_thread_handle = thread::Builder::new()
-            .stack_size(BLOCK_PROCESSOR_STACK_SIZE)
-            .name(format!(
-                "p2p-({},{})",
-                &config.node.p2p_bind, &config.node.rpc_bind
-            ))
-            .spawn(move || {
-                p2p_thread.main(p2p_event_dispatcher);
-            })
-            .expect("FATAL: failed to start p2p thread");
+        let p2p_name = format!("p2p-({},{})", &config.node.p2p_bind, &config.node.rpc_bind);
+        let p2p_globals = globals.clone();
+        lifecycle.add_once(lifecycle::P2P, &[lifecycle::RELAYER], move || {
+            let p2p_thread_handle = thread::Builder::new()
+                .stack_size(BLOCK_PROCESSOR_STACK_SIZE)
+                .name(p2p_name)
+                .spawn(move || {
+                    p2p_thread.main(p2p_event_dispatcher);
+                })?;
+            Ok(SubsystemHandle::new(p2p_thread_handle, move || {
+                p2p_globals.signal_stop()
+            }))
+        })?;
 
         info!("Start HTTP server on: {}", &config.node.rpc_bind);
         info!("Start P2P server on: {}", &config.node.p2p_bind);
 
-        StacksNode {
+        Ok(StacksNode {
             atlas_config,
             globals,
             is_miner,
-            p2p_thread_handle,
-            relayer_thread_handle,
-        }
+        })
     }
 
     /// Notify the relayer that a new burn block has been processed by the sortition db,
@@ -377,12 +388,6 @@ impl StacksNode {
         // notify the relayer thread of the new sortition state
         self.relayer_burnchain_notify(block_snapshot)
     }
-
-    /// Join all inner threads
-    pub fn join(self) {
-        self.relayer_thread_handle.join().unwrap();
-        self.p2p_thread_handle.join().unwrap();
-    }
 }
 
 pub(crate) fn save_activated_vrf_key(path: &str, activated_key: &RegisteredKey) {
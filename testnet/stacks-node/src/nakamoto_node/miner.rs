@@ -427,6 +427,9 @@ impl BlockMinerThread {
                     );
                 }
 
+                // the signers accepted the block, so its required transactions are included
+                self.mark_required_txs_included(&new_block);
+
                 // update mined-block counters and mined-tenure counters
                 self.globals.counters.bump_naka_mined_blocks();
                 if self.last_block_mined.is_some() {
@@ -460,6 +463,20 @@ impl BlockMinerThread {
         }
     }
 
+    /// Record that the required transactions in `block` are included, now that the signers have
+    /// accepted it
+    fn mark_required_txs_included(&self, block: &NakamotoBlock) {
+        let txids: Vec<_> = block.txs.iter().map(|tx| tx.txid()).collect();
+        let res = self.config.connect_mempool_db().and_then(|mut mem_pool| {
+            mem_pool.mark_required_txs_included(&block.header.block_id(), &txids)
+        });
+        if let Err(e) = res {
+            warn!("Miner: failed to record included required transactions: {e:?}";
+                "stacks_block_id" => %block.header.block_id(),
+            );
+        }
+    }
+
     /// Load the signer set active for this miner's blocks. This is the
     ///  active reward set during `self.burn_election_block`. The miner
     ///  thread caches this information, and this method will consult
@@ -238,6 +238,8 @@ pub struct RelayerThread {
     last_committed: Option<LastCommit>,
     /// Timeout for waiting for the first block in a tenure before submitting a block commit
     new_tenure_timeout: Option<Instant>,
+    /// When to next check whether the last block commit pays enough to be mined
+    next_fee_check: Instant,
 }
 
 impl RelayerThread {
@@ -296,6 +298,7 @@ impl RelayerThread {
             next_initiative: Instant::now() + Duration::from_millis(next_initiative_delay),
             last_committed: None,
             new_tenure_timeout: None,
+            next_fee_check: Instant::now(),
         }
     }
 
@@ -1235,6 +1238,51 @@ impl RelayerThread {
         Ok(())
     }
 
+    /// If the last block commit is still pending in the current burnchain block, and pays less
+    /// than bitcoind estimates is needed to be mined in the next block, replace it with one that
+    /// pays more (within the configured limits).
+    fn check_block_commit_fee(&mut self) {
+        let interval_ms = self.config.burnchain.rbf_fee_check_interval_ms;
+        if !self.is_miner || interval_ms == 0 || Instant::now() < self.next_fee_check {
+            return;
+        }
+        self.next_fee_check = Instant::now() + Duration::from_millis(interval_ms);
+
+        let Some(last_committed) = self.last_committed.as_mut() else {
+            return;
+        };
+        if last_committed.txid.is_none() {
+            return;
+        }
+        let Ok(burn_tip) = SortitionDB::get_canonical_burn_chain_tip(self.sortdb.conn()) else {
+            return;
+        };
+        if burn_tip.consensus_hash != last_committed.get_burn_tip().consensus_hash {
+            // the commit's burn block has passed
+            return;
+        }
+
+        let mut op_signer = self.keychain.generate_op_signer();
+        match self
+            .bitcoin_controller
+            .bump_block_commit_fee(*last_committed.get_epoch_id(), &mut op_signer)
+        {
+            Ok(Some(txid)) => {
+                info!(
+                    "Relayer: Replaced block-commit with a higher fee";
+                    "burn_height" => burn_tip.block_height,
+                    "txid" => %txid,
+                );
+                last_committed.set_txid(&txid);
+                self.last_commits.insert(txid);
+            }
+            Ok(None) => {}
+            Err(e) => {
+                warn!("Relayer: Failed to bump the block-commit fee: {e}");
+            }
+        }
+    }
+
     /// Determine what the relayer should do to advance the chain.
     /// * If this isn't a miner, then it's always nothing.
     /// * Otherwise, if we haven't done so already, go register a VRF public key
@@ -1355,6 +1403,7 @@ impl RelayerThread {
             Instant::now() + Duration::from_millis(self.config.node.next_initiative_delay);
 
         while self.globals.keep_running() {
+            self.check_block_commit_fee();
             let raised_initiative = self.globals.take_initiative();
             let timed_out = Instant::now() >= self.next_initiative;
             let mut initiative_directive = if raised_initiative.is_some() || timed_out {
@@ -1401,6 +1450,13 @@ impl RelayerThread {
         // set termination flag so other threads die
         self.globals.signal_stop();
 
+        // don't leave the miner thread behind
+        if let Some(miner_thread) = self.miner_thread.take() {
+            if miner_thread.join().is_err() {
+                warn!("Relayer: miner thread panicked");
+            }
+        }
+
         debug!("Relayer exit!");
     }
 
@@ -600,6 +600,10 @@ impl SignCoordinator {
                         debug!("Received mock message. Ignoring.");
                         continue;
                     }
+                    SignerMessageV0::KeyRotation(_) => {
+                        debug!("Received key rotation announcement. Ignoring.");
+                        continue;
+                    }
                 };
             }
             // After gathering all signatures, return them if we've hit the threshold
@@ -217,7 +217,8 @@ use stacks_common::util::{get_epoch_time_ms, get_epoch_time_secs};
 
 use super::{BurnchainController, Config, EventDispatcher, Keychain};
 use crate::burnchains::bitcoin_regtest_controller::{
-    addr2str, burnchain_params_from_config, BitcoinRegtestController, OngoingBlockCommit,
+    addr2str, burnchain_params_from_config, get_funding_public_key, BitcoinRegtestController,
+    OngoingBlockCommit,
 };
 use crate::burnchains::{make_bitcoin_indexer, Error as BurnchainControllerError};
 use crate::chain_data::MinerStats;
@@ -360,6 +361,9 @@ pub(crate) fn open_chainstate_with_faults(
     )?;
 
     chainstate.fault_injection.hide_blocks = config.node.fault_injection_hide_blocks;
+    chainstate
+        .clarity_state
+        .set_analysis_cost_limit(config.node.analysis_cost_limit.clone());
     Ok(chainstate)
 }
 
@@ -1868,12 +1872,13 @@ impl BlockMinerThread {
     /// Get the list of possible burn addresses this miner is using
     pub fn get_miner_addrs(config: &Config, keychain: &Keychain) -> Vec<String> {
         let mut op_signer = keychain.generate_op_signer();
+        let funding_key = get_funding_public_key(config, &mut op_signer);
         let mut btc_addrs = vec![
             // legacy
             BitcoinAddress::from_bytes_legacy(
                 config.burnchain.get_bitcoin_network().1,
                 LegacyBitcoinAddressType::PublicKeyHash,
-                &Hash160::from_data(&op_signer.get_public_key().to_bytes()).0,
+                &Hash160::from_data(&funding_key.to_bytes()).0,
             )
             .expect("FATAL: failed to construct legacy bitcoin address"),
         ];
@@ -1882,11 +1887,23 @@ impl BlockMinerThread {
                 // segwit p2wpkh
                 BitcoinAddress::from_bytes_segwit_p2wpkh(
                     config.burnchain.get_bitcoin_network().1,
-                    &Hash160::from_data(&op_signer.get_public_key().to_bytes_compressed()).0,
+                    &Hash160::from_data(&funding_key.to_bytes_compressed()).0,
                 )
                 .expect("FATAL: failed to construct segwit p2wpkh address"),
             );
         }
+        if config.miner.taproot {
+            btc_addrs.push(
+                // segwit p2tr
+                BitcoinAddress::from_bytes_segwit_p2tr(
+                    config.burnchain.get_bitcoin_network().1,
+                    &funding_key
+                        .to_taproot_output_key()
+                        .expect("FATAL: failed to tweak taproot key"),
+                )
+                .expect("FATAL: failed to construct segwit p2tr address"),
+            );
+        }
         btc_addrs
             .into_iter()
             .map(|addr| format!("{addr}"))
@@ -306,6 +306,7 @@ impl Node {
                 get_namespaces(use_test_genesis_data)
             })),
             get_bulk_initial_names: Some(Box::new(move || get_names(use_test_genesis_data))),
+            boot_contract_provider: None,
         };
 
         let chain_state_result = StacksChainState::open_and_exec(
@@ -56,6 +56,29 @@ impl BurnchainOpSigner {
         Some(signature)
     }
 
+    /// Sign a taproot key-path spend.  See `Secp256k1PrivateKey::sign_taproot()`.
+    pub fn sign_taproot(&mut self, sighash: &[u8]) -> Option<[u8; 64]> {
+        if self.is_disposed {
+            debug!("Signer is disposed");
+            return None;
+        }
+
+        let signature = match self.secret_key.sign_taproot(sighash) {
+            Ok(r) => r,
+            Err(e) => {
+                debug!("Secret key error: {e:?}");
+                return None;
+            }
+        };
+        self.usages += 1;
+
+        if self.is_one_off && self.usages == 1 {
+            self.is_disposed = true;
+        }
+
+        Some(signature)
+    }
+
     pub fn dispose(&mut self) {
         self.is_disposed = true;
     }
@@ -0,0 +1,154 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Warm-up of the contract analysis cache. The first call to a contract after a restart pays
+//! for deserializing its analysis, which is a noticeable latency spike for large contracts like
+//! PoX. When enabled, a background thread loads the analyses of the boot contracts and of the
+//! contracts that were most recently used before the node last shut down.
+
+use std::path::PathBuf;
+use std::time::Instant;
+use std::{fs, thread};
+
+use clarity::vm::database::analysis_cache;
+use clarity::vm::types::QualifiedContractIdentifier;
+use stacks::chainstate::burn::db::sortdb::SortitionDB;
+use stacks::chainstate::stacks::boot::{
+    COSTS_1_NAME, COSTS_2_NAME, COSTS_3_NAME, POX_1_NAME, POX_2_NAME, POX_3_NAME, POX_4_NAME,
+    SIGNERS_NAME, SIGNERS_VOTING_NAME,
+};
+use stacks::chainstate::stacks::db::StacksChainState;
+use stacks::clarity_vm::database::marf::MarfedKV;
+use stacks::util_lib::boot::boot_code_id;
+use stacks_common::types::chainstate::StacksBlockId;
+
+use crate::Config;
+
+/// Boot contracts whose analyses are always warm-loaded
+const BOOT_CONTRACTS: [&str; 11] = [
+    POX_1_NAME,
+    POX_2_NAME,
+    POX_3_NAME,
+    POX_4_NAME,
+    COSTS_1_NAME,
+    COSTS_2_NAME,
+    COSTS_3_NAME,
+    "cost-voting",
+    "bns",
+    SIGNERS_NAME,
+    SIGNERS_VOTING_NAME,
+];
+
+/// Where the most-recently-used contracts are recorded between runs
+fn recent_contracts_path(config: &Config) -> PathBuf {
+    let mut path = config.get_chainstate_path();
+    path.push("recent-contracts.json");
+    path
+}
+
+/// Enable the contract analysis cache, if configured, and start warm-loading it
+pub fn start(config: &Config) {
+    analysis_cache::set_capacity(config.node.contract_analysis_cache_size);
+    let Some(recent_limit) = config.node.contract_analysis_warm_load else {
+        return;
+    };
+    let config = config.clone();
+    thread::Builder::new()
+        .name("analysis-warm-load".into())
+        .spawn(move || {
+            let start = Instant::now();
+            match warm_load(&config, recent_limit) {
+                Ok(loaded) => info!(
+                    "Warm-loaded contract analyses";
+                    "loaded" => loaded,
+                    "elapsed_ms" => start.elapsed().as_millis()
+                ),
+                Err(e) => warn!("Failed to warm-load contract analyses: {e}"),
+            }
+        })
+        .expect("FATAL: failed to start contract analysis warm-load thread");
+}
+
+/// Record the most-recently-used contracts, so that the next run can warm-load them
+pub fn stop(config: &Config) {
+    let Some(recent_limit) = config.node.contract_analysis_warm_load else {
+        return;
+    };
+    let recent: Vec<String> = analysis_cache::recently_used_contracts(recent_limit)
+        .iter()
+        .map(|contract| contract.to_string())
+        .collect();
+    let path = recent_contracts_path(config);
+    let result = serde_json::to_string(&recent)
+        .map_err(|e| e.to_string())
+        .and_then(|json| fs::write(&path, json).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        warn!("Failed to record recently-used contracts"; "path" => %path.display(), "error" => e);
+    }
+}
+
+fn load_recent_contracts(config: &Config, limit: usize) -> Vec<QualifiedContractIdentifier> {
+    let Ok(json) = fs::read_to_string(recent_contracts_path(config)) else {
+        return vec![];
+    };
+    let recent: Vec<String> = serde_json::from_str(&json).unwrap_or_else(|e| {
+        warn!("Ignoring malformed list of recently-used contracts: {e}");
+        vec![]
+    });
+    recent
+        .iter()
+        .filter_map(|contract| QualifiedContractIdentifier::parse(contract).ok())
+        .take(limit)
+        .collect()
+}
+
+fn warm_load(config: &Config, recent_limit: usize) -> Result<usize, String> {
+    let sortdb_path = config.get_burn_db_file_path();
+    if fs::metadata(&sortdb_path).is_err() {
+        // nothing to load on a fresh node
+        return Ok(0);
+    }
+    let sortdb = SortitionDB::open(&sortdb_path, false, config.get_burnchain().pox_constants)
+        .map_err(|e| format!("failed to open sortition DB: {e:?}"))?;
+    let (consensus_hash, block_hash) =
+        SortitionDB::get_canonical_stacks_chain_tip_hash(sortdb.conn())
+            .map_err(|e| format!("failed to load canonical Stacks tip: {e:?}"))?;
+    let tip = StacksBlockId::new(&consensus_hash, &block_hash);
+
+    let mut contracts: Vec<_> = BOOT_CONTRACTS
+        .iter()
+        .map(|name| boot_code_id(name, config.is_mainnet()))
+        .collect();
+    for contract in load_recent_contracts(config, recent_limit) {
+        if !contracts.contains(&contract) {
+            contracts.push(contract);
+        }
+    }
+
+    let clarity_state_path =
+        StacksChainState::vm_state_index_root_path(config.get_chainstate_path());
+    let mut marf = MarfedKV::open_replica(
+        clarity_state_path
+            .to_str()
+            .ok_or("non-UTF-8 chainstate path")?,
+        None,
+        Some(config.node.get_marf_opts()),
+    )
+    .map_err(|e| format!("failed to open Clarity state: {e:?}"))?;
+    let mut store = marf
+        .begin_read_only_checked(Some(&tip))
+        .map_err(|e| format!("failed to open Clarity state at {tip}: {e:?}"))?;
+    Ok(analysis_cache::warm_load(&mut store, &contracts))
+}
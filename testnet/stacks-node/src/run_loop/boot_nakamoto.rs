@@ -30,8 +30,11 @@ use crate::event_dispatcher::EventDispatcher;
 use crate::globals::NeonGlobals;
 use crate::neon::Counters;
 use crate::neon_node::LeaderKeyRegistrationState;
+use crate::run_loop::analysis_warm_load;
+use crate::run_loop::maintenance::SideStoreMaintenance;
 use crate::run_loop::nakamoto::RunLoop as NakaRunLoop;
 use crate::run_loop::neon::RunLoop as NeonRunLoop;
+use crate::run_loop::snapshot;
 use crate::Config;
 
 /// Data which should persist through transition from Neon => Nakamoto run loop
@@ -77,6 +80,8 @@ enum InnerLoops {
 
 impl BootRunLoop {
     pub fn new(config: Config) -> Result<Self, String> {
+        snapshot::restore_if_configured(&config)
+            .map_err(|e| format!("Failed to restore chainstate snapshot: {e}"))?;
         let (coordinator_channels, active_loop) = if !Self::reached_epoch_30_transition(&config)? {
             let neon = NeonRunLoop::new(config.clone());
             (
@@ -136,10 +141,17 @@ impl BootRunLoop {
     /// The main entry point for the run loop. This starts either a 2.x-neon or 3.x-nakamoto
     /// node depending on the current burnchain height.
     pub fn start(&mut self, burnchain_opt: Option<Burnchain>, mine_start: u64) {
+        // spans both run loops, so that it keeps running across the epoch-3.0 transition
+        let side_store_maintenance = SideStoreMaintenance::spawn(&self.config);
+        analysis_warm_load::start(&self.config);
         match self.active_loop {
             InnerLoops::Epoch2(_) => self.start_from_neon(burnchain_opt, mine_start),
             InnerLoops::Epoch3(_) => self.start_from_naka(burnchain_opt, mine_start),
         }
+        if let Some(side_store_maintenance) = side_store_maintenance {
+            side_store_maintenance.stop();
+        }
+        analysis_warm_load::stop(&self.config);
     }
 
     fn start_from_naka(&mut self, burnchain_opt: Option<Burnchain>, mine_start: u64) {
@@ -0,0 +1,424 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Lifecycle of the node's long-running subsystems (chains coordinator, relayer, p2p, ...).
+//!
+//! Each subsystem is registered with the names of the subsystems it depends on, and a starter
+//! that spawns its thread and says how to ask it to stop. `Lifecycle` starts subsystems after
+//! their dependencies and stops them before their dependencies, always joining their threads.
+//! If a subsystem fails to start, the ones already started are stopped again, and dropping the
+//! `Lifecycle` stops everything that is still running, so that an early exit from the run loop
+//! doesn't leave threads behind.
+//!
+//! Subsystems registered with `add()` can be restarted on their own (their dependents are
+//! restarted with them). Subsystems whose state can't be rebuilt are registered with
+//! `add_once()`.
+
+use std::collections::HashSet;
+use std::thread::JoinHandle;
+
+/// The chains coordinator thread
+pub const CHAINS_COORDINATOR: &str = "chains-coordinator";
+/// The relayer thread, which also runs the miner
+pub const RELAYER: &str = "relayer";
+/// The p2p thread, which also runs StackerDB sync and the HTTP server
+pub const P2P: &str = "p2p";
+/// The listener for bitcoind's ZeroMQ new-block notifications
+pub const ZMQ_LISTENER: &str = "zmq-listener";
+
+/// Errors from managing subsystems
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("No such subsystem: {0}")]
+    UnknownSubsystem(String),
+    #[error("Subsystem {0} is already registered")]
+    AlreadyRegistered(String),
+    #[error("Subsystem {subsystem} depends on unknown subsystem {dependency}")]
+    UnknownDependency {
+        subsystem: String,
+        dependency: String,
+    },
+    #[error("Subsystem {0} is part of a dependency cycle")]
+    DependencyCycle(String),
+    #[error("Subsystem {0} can't be restarted")]
+    NotRestartable(String),
+    #[error("Failed to start subsystem {subsystem}: {error}")]
+    StartFailed {
+        subsystem: String,
+        error: std::io::Error,
+    },
+}
+
+/// A running subsystem: its thread, and how to ask it to exit
+pub struct SubsystemHandle {
+    thread: JoinHandle<()>,
+    signal_stop: Box<dyn FnOnce() + Send>,
+}
+
+impl SubsystemHandle {
+    pub fn new(thread: JoinHandle<()>, signal_stop: impl FnOnce() + Send + 'static) -> Self {
+        Self {
+            thread,
+            signal_stop: Box::new(signal_stop),
+        }
+    }
+}
+
+type Starter = Box<dyn FnMut() -> Option<Result<SubsystemHandle, std::io::Error>>>;
+
+struct Subsystem {
+    name: &'static str,
+    depends_on: Vec<&'static str>,
+    /// Returns `None` if the subsystem can't be started again
+    starter: Starter,
+    handle: Option<SubsystemHandle>,
+}
+
+#[derive(Default)]
+pub struct Lifecycle {
+    /// In registration order
+    subsystems: Vec<Subsystem>,
+}
+
+impl Lifecycle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a subsystem that can be restarted. `starter` is called each time it's started.
+    pub fn add<F>(
+        &mut self,
+        name: &'static str,
+        depends_on: &[&'static str],
+        mut starter: F,
+    ) -> Result<(), Error>
+    where
+        F: FnMut() -> Result<SubsystemHandle, std::io::Error> + 'static,
+    {
+        self.register(name, depends_on, Box::new(move || Some(starter())))
+    }
+
+    /// Register a subsystem that can only be started once
+    pub fn add_once<F>(
+        &mut self,
+        name: &'static str,
+        depends_on: &[&'static str],
+        starter: F,
+    ) -> Result<(), Error>
+    where
+        F: FnOnce() -> Result<SubsystemHandle, std::io::Error> + 'static,
+    {
+        let mut starter = Some(starter);
+        self.register(
+            name,
+            depends_on,
+            Box::new(move || starter.take().map(|starter| starter())),
+        )
+    }
+
+    fn register(
+        &mut self,
+        name: &'static str,
+        depends_on: &[&'static str],
+        starter: Starter,
+    ) -> Result<(), Error> {
+        if self.position(name).is_some() {
+            return Err(Error::AlreadyRegistered(name.into()));
+        }
+        self.subsystems.push(Subsystem {
+            name,
+            depends_on: depends_on.to_vec(),
+            starter,
+            handle: None,
+        });
+        Ok(())
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.subsystems.iter().position(|s| s.name == name)
+    }
+
+    /// Indexes of the subsystems, with every subsystem after its dependencies
+    fn startup_order(&self) -> Result<Vec<usize>, Error> {
+        for subsystem in self.subsystems.iter() {
+            if let Some(dependency) = subsystem
+                .depends_on
+                .iter()
+                .find(|dependency| self.position(dependency).is_none())
+            {
+                return Err(Error::UnknownDependency {
+                    subsystem: subsystem.name.into(),
+                    dependency: (*dependency).into(),
+                });
+            }
+        }
+
+        let mut order = Vec::with_capacity(self.subsystems.len());
+        let mut placed = HashSet::new();
+        while order.len() < self.subsystems.len() {
+            let next = self.subsystems.iter().enumerate().find(|(ix, subsystem)| {
+                !placed.contains(ix)
+                    && subsystem.depends_on.iter().all(|dependency| {
+                        matches!(self.position(dependency), Some(dependency) if placed.contains(&dependency))
+                    })
+            });
+            let Some((ix, _)) = next else {
+                let stuck = self
+                    .subsystems
+                    .iter()
+                    .enumerate()
+                    .find(|(ix, _)| !placed.contains(ix))
+                    .map(|(_, subsystem)| subsystem.name)
+                    .unwrap_or_default();
+                return Err(Error::DependencyCycle(stuck.into()));
+            };
+            placed.insert(ix);
+            order.push(ix);
+        }
+        Ok(order)
+    }
+
+    /// The subsystems that depend on `name`, directly or indirectly, in startup order
+    fn dependents(&self, name: &str) -> Result<Vec<usize>, Error> {
+        let mut affected = HashSet::from([name]);
+        let mut dependents = vec![];
+        for ix in self.startup_order()? {
+            let subsystem = &self.subsystems[ix];
+            if subsystem
+                .depends_on
+                .iter()
+                .any(|dependency| affected.contains(dependency))
+            {
+                affected.insert(subsystem.name);
+                dependents.push(ix);
+            }
+        }
+        Ok(dependents)
+    }
+
+    pub fn is_running(&self, name: &str) -> bool {
+        self.position(name)
+            .map(|ix| self.subsystems[ix].handle.is_some())
+            .unwrap_or(false)
+    }
+
+    /// Start every registered subsystem that isn't running yet, dependencies first. If one fails
+    /// to start, everything is stopped again.
+    pub fn start_all(&mut self) -> Result<(), Error> {
+        let order = self.startup_order()?;
+        for ix in order {
+            if self.subsystems[ix].handle.is_some() {
+                continue;
+            }
+            if let Err(e) = self.start(ix) {
+                error!("Failed to start node subsystems, stopping them"; "err" => %e);
+                self.stop_all();
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    fn start(&mut self, ix: usize) -> Result<(), Error> {
+        let subsystem = &mut self.subsystems[ix];
+        let handle = (subsystem.starter)()
+            .ok_or_else(|| Error::NotRestartable(subsystem.name.into()))?
+            .map_err(|error| Error::StartFailed {
+                subsystem: subsystem.name.into(),
+                error,
+            })?;
+        debug!("Started subsystem {}", subsystem.name);
+        subsystem.handle = Some(handle);
+        Ok(())
+    }
+
+    fn stop(&mut self, ix: usize) {
+        let subsystem = &mut self.subsystems[ix];
+        let Some(handle) = subsystem.handle.take() else {
+            return;
+        };
+        info!("Stopping subsystem {}", subsystem.name);
+        (handle.signal_stop)();
+        if handle.thread.join().is_err() {
+            warn!("Subsystem {} panicked", subsystem.name);
+        }
+    }
+
+    /// Stop every running subsystem, dependents first
+    pub fn stop_all(&mut self) {
+        // registration order is a valid startup order if the dependencies can't be resolved
+        let order = self
+            .startup_order()
+            .unwrap_or_else(|_| (0..self.subsystems.len()).collect());
+        for ix in order.into_iter().rev() {
+            self.stop(ix);
+        }
+    }
+
+    /// Stop `name` and the subsystems that depend on it, then start them again
+    pub fn restart(&mut self, name: &str) -> Result<(), Error> {
+        let ix = self
+            .position(name)
+            .ok_or_else(|| Error::UnknownSubsystem(name.into()))?;
+        let dependents = self.dependents(name)?;
+        let running: Vec<_> = dependents
+            .iter()
+            .copied()
+            .filter(|ix| self.subsystems[*ix].handle.is_some())
+            .collect();
+
+        for dependent in running.iter().rev() {
+            self.stop(*dependent);
+        }
+        self.stop(ix);
+        self.start(ix)?;
+        for dependent in running {
+            self.start(dependent)?;
+        }
+        Ok(())
+    }
+
+    /// The running subsystems whose threads have exited without being stopped
+    pub fn exited(&self) -> Vec<&'static str> {
+        self.subsystems
+            .iter()
+            .filter(|subsystem| {
+                matches!(&subsystem.handle, Some(handle) if handle.thread.is_finished())
+            })
+            .map(|subsystem| subsystem.name)
+            .collect()
+    }
+}
+
+impl Drop for Lifecycle {
+    fn drop(&mut self) {
+        self.stop_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    use super::*;
+
+    type Events = Arc<Mutex<Vec<String>>>;
+
+    fn spawn(name: &'static str, events: &Events) -> Result<SubsystemHandle, std::io::Error> {
+        let keep_running = Arc::new(AtomicBool::new(true));
+        let thread_keep_running = keep_running.clone();
+        let thread = thread::Builder::new().name(name.into()).spawn(move || {
+            while thread_keep_running.load(Ordering::SeqCst) {
+                thread::sleep(Duration::from_millis(1));
+            }
+        })?;
+        events.lock().unwrap().push(format!("start {name}"));
+        let events = events.clone();
+        Ok(SubsystemHandle::new(thread, move || {
+            events.lock().unwrap().push(format!("stop {name}"));
+            keep_running.store(false, Ordering::SeqCst);
+        }))
+    }
+
+    fn take(events: &Events) -> Vec<String> {
+        std::mem::take(&mut *events.lock().unwrap())
+    }
+
+    #[test]
+    fn test_lifecycle_ordering() {
+        let events = Events::default();
+        let mut lifecycle = Lifecycle::new();
+        // registered out of order
+        for (name, depends_on) in [
+            ("p2p", &["relayer"][..]),
+            ("relayer", &["coordinator"][..]),
+            ("coordinator", &[][..]),
+        ] {
+            let events = events.clone();
+            lifecycle
+                .add(name, depends_on, move || spawn(name, &events))
+                .unwrap();
+        }
+        lifecycle.start_all().unwrap();
+        assert_eq!(
+            take(&events),
+            ["start coordinator", "start relayer", "start p2p"]
+        );
+        assert!(lifecycle.exited().is_empty());
+
+        lifecycle.restart("relayer").unwrap();
+        assert_eq!(
+            take(&events),
+            ["stop p2p", "stop relayer", "start relayer", "start p2p"]
+        );
+
+        drop(lifecycle);
+        assert_eq!(
+            take(&events),
+            ["stop p2p", "stop relayer", "stop coordinator"]
+        );
+    }
+
+    #[test]
+    fn test_lifecycle_failures() {
+        let events = Events::default();
+        let mut lifecycle = Lifecycle::new();
+        let coordinator_events = events.clone();
+        lifecycle
+            .add_once("coordinator", &[], move || {
+                spawn("coordinator", &coordinator_events)
+            })
+            .unwrap();
+        lifecycle
+            .add("relayer", &["coordinator"], || {
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::Other,
+                    "no threads left",
+                ))
+            })
+            .unwrap();
+
+        // a partial startup is undone
+        assert!(matches!(
+            lifecycle.start_all(),
+            Err(Error::StartFailed { subsystem, .. }) if subsystem == "relayer"
+        ));
+        assert_eq!(take(&events), ["start coordinator", "stop coordinator"]);
+        assert!(!lifecycle.is_running("coordinator"));
+        assert!(matches!(
+            lifecycle.restart("coordinator"),
+            Err(Error::NotRestartable(_))
+        ));
+
+        let mut lifecycle = Lifecycle::new();
+        lifecycle
+            .add("a", &["b"], || spawn("a", &Events::default()))
+            .unwrap();
+        lifecycle
+            .add("b", &["a"], || spawn("b", &Events::default()))
+            .unwrap();
+        assert!(matches!(
+            lifecycle.start_all(),
+            Err(Error::DependencyCycle(_))
+        ));
+        assert!(matches!(
+            lifecycle.add("a", &[], || spawn("a", &Events::default())),
+            Err(Error::AlreadyRegistered(_))
+        ));
+    }
+}
@@ -0,0 +1,321 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Background maintenance of the Clarity side-store. Long-running nodes see the side-store
+//! grow with free pages and its query plans degrade, so when enabled, this thread
+//! periodically runs a bounded incremental vacuum and analyze while no block is being
+//! processed. On pruned nodes, it first discards the staging data of old forks and the state
+//! that is older than the configured depths, measured from the canonical tip in the sortition DB.
+//! Nodes with a microblock archive also move old microblock data to it then. Each of these only
+//! runs once the tip has advanced by `PRUNE_STEP_BLOCKS` since it last ran, since each opens the
+//! chainstate and the state pass reads the whole live state.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use clarity::vm::database::SqliteConnection;
+use clarity::vm::errors::{Error as ClarityError, InterpreterError};
+use stacks::chainstate::burn::db::sortdb::SortitionDB;
+use stacks::chainstate::stacks::db::StacksChainState;
+use stacks::clarity_vm::database::marf::MarfedKV;
+use stacks_common::types::chainstate::StacksBlockId;
+
+use crate::Config;
+
+/// How long to wait before retrying a step that was skipped because a block was being
+/// processed
+const PAUSED_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The most microblocks to archive in one step
+const MICROBLOCK_ARCHIVE_BATCH: u64 = 10_000;
+
+/// How many blocks the prune heights must advance by before pruning runs again
+const PRUNE_STEP_BLOCKS: u64 = 100;
+
+/// The heights below which the maintenance thread last pruned, so that a pass only runs once
+/// there is enough new data to prune
+#[derive(Debug, Default)]
+struct PruneProgress {
+    forks_below: Option<u64>,
+    microblocks_below: Option<u64>,
+}
+
+/// Whether a prune pass to `below_height` is due, given the height of the last one
+fn prune_due(last_below: Option<u64>, below_height: u64) -> bool {
+    match last_below {
+        Some(last_below) => below_height >= last_below.saturating_add(PRUNE_STEP_BLOCKS),
+        None => below_height > 0,
+    }
+}
+
+/// Handle to the side-store maintenance thread
+pub struct SideStoreMaintenance {
+    keep_running: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+impl SideStoreMaintenance {
+    /// Start the maintenance thread, if it is enabled in the node config
+    pub fn spawn(config: &Config) -> Option<Self> {
+        let interval = Duration::from_secs(config.node.side_store_maintenance_interval_secs?);
+        let max_pages = config.node.side_store_maintenance_max_pages;
+        let side_store_path =
+            StacksChainState::vm_state_index_marf_path(config.get_chainstate_path());
+        let keep_running = Arc::new(AtomicBool::new(true));
+        let config = config.clone();
+
+        let thread_keep_running = keep_running.clone();
+        let handle = thread::Builder::new()
+            .name("side-store-maintenance".into())
+            .spawn(move || {
+                debug!(
+                    "side-store maintenance thread ID is {:?}",
+                    thread::current().id()
+                );
+                Self::run(
+                    &config,
+                    side_store_path,
+                    interval,
+                    max_pages,
+                    thread_keep_running,
+                )
+            })
+            .expect("FATAL: failed to start side-store maintenance thread");
+
+        Some(Self {
+            keep_running,
+            handle,
+        })
+    }
+
+    /// Stop the maintenance thread and wait for it to exit
+    pub fn stop(self) {
+        self.keep_running.store(false, Ordering::SeqCst);
+        self.handle.thread().unpark();
+        if let Err(e) = self.handle.join() {
+            warn!("Side-store maintenance thread panicked: {e:?}");
+        }
+    }
+
+    fn run(
+        config: &Config,
+        side_store_path: PathBuf,
+        interval: Duration,
+        max_pages: u32,
+        keep_running: Arc<AtomicBool>,
+    ) {
+        let mut next_step = Instant::now() + interval;
+        let mut progress = PruneProgress::default();
+        while keep_running.load(Ordering::SeqCst) {
+            let now = Instant::now();
+            if now < next_step {
+                thread::park_timeout(next_step - now);
+                continue;
+            }
+
+            // the side-store does not exist until the chainstate is instantiated
+            if !side_store_path.exists() {
+                next_step = Instant::now() + interval;
+                continue;
+            }
+
+            match Self::step(config, &side_store_path, max_pages, &mut progress) {
+                Ok(true) => {
+                    debug!("Ran side-store maintenance step"; "max_pages" => max_pages);
+                    next_step = Instant::now() + interval;
+                }
+                Ok(false) => {
+                    // a block is being processed
+                    next_step = Instant::now() + PAUSED_RETRY_INTERVAL;
+                }
+                Err(e) => {
+                    warn!("Failed to run side-store maintenance step: {e:?}");
+                    next_step = Instant::now() + interval;
+                }
+            }
+        }
+        debug!("Side-store maintenance thread exiting");
+    }
+
+    fn step(
+        config: &Config,
+        side_store_path: &Path,
+        max_pages: u32,
+        progress: &mut PruneProgress,
+    ) -> Result<bool, ClarityError> {
+        let path = side_store_path
+            .to_str()
+            .expect("FATAL: non-UTF-8 character in side-store path");
+        if config.node.state_prune_depth.is_some()
+            || config.node.fork_prune_depth.is_some()
+            || config.node.microblock_archive_path.is_some()
+        {
+            if SqliteConnection::is_maintenance_paused() {
+                return Ok(false);
+            }
+            Self::prune(config, path, progress)?;
+        }
+
+        let conn = SqliteConnection::inner_open(path)?;
+        SqliteConnection::run_maintenance_step(&conn, max_pages)
+    }
+
+    /// Discard the fork data and the state, and archive the microblocks, that are more than the
+    /// configured depths below the canonical tip, if the tip has advanced far enough since they
+    /// were last pruned
+    fn prune(
+        config: &Config,
+        side_store_path: &str,
+        progress: &mut PruneProgress,
+    ) -> Result<(), ClarityError> {
+        let sortdb_path = config.get_burn_db_file_path();
+        if fs::metadata(&sortdb_path).is_err() {
+            // nothing to prune on a fresh node
+            return Ok(());
+        }
+        let sortdb = SortitionDB::open(&sortdb_path, false, config.get_burnchain().pox_constants)
+            .map_err(|e| {
+            InterpreterError::DBError(format!("failed to open sortition DB: {e:?}"))
+        })?;
+        let (consensus_hash, block_hash, tip_height) =
+            SortitionDB::get_canonical_stacks_chain_tip_hash_and_height(sortdb.conn()).map_err(
+                |e| {
+                    InterpreterError::DBError(format!("failed to load canonical Stacks tip: {e:?}"))
+                },
+            )?;
+        let tip = StacksBlockId::new(&consensus_hash, &block_hash);
+
+        if let Some(keep_blocks) = config.node.fork_prune_depth {
+            let below_height = tip_height.saturating_sub(keep_blocks.into());
+            if prune_due(progress.forks_below, below_height) {
+                Self::prune_forks(config, below_height)?;
+                progress.forks_below = Some(below_height);
+            }
+        }
+        if let Some(archive_path) = config.node.microblock_archive_path.as_ref() {
+            let keep_blocks = config.node.microblock_archive_depth;
+            let below_height = tip_height.saturating_sub(keep_blocks.into());
+            if prune_due(progress.microblocks_below, below_height) {
+                Self::archive_microblocks(config, archive_path, below_height)?;
+                progress.microblocks_below = Some(below_height);
+            }
+        }
+        if let Some(keep_blocks) = config.node.state_prune_depth {
+            // the pruned height is kept in the side-store, so this holds across restarts
+            let pruned_height = {
+                let conn = SqliteConnection::inner_open(side_store_path)?;
+                SqliteConnection::get_pruned_height(&conn)?
+            };
+            if prune_due(
+                pruned_height.map(u64::from),
+                tip_height.saturating_sub(keep_blocks.into()),
+            ) {
+                Self::prune_state(config, &tip, keep_blocks)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Delete the staging data of the forks below `below_height`
+    fn prune_forks(config: &Config, below_height: u64) -> Result<(), ClarityError> {
+        let (mut chainstate, _) = StacksChainState::open(
+            config.is_mainnet(),
+            config.burnchain.chain_id,
+            &config.get_chainstate_path_str(),
+            Some(config.node.get_marf_opts()),
+        )
+        .map_err(|e| InterpreterError::DBError(format!("failed to open chainstate: {e:?}")))?;
+        let start = Instant::now();
+        let summary = chainstate
+            .prune_fork_data(below_height)
+            .map_err(|e| InterpreterError::DBError(format!("failed to prune forks: {e:?}")))?;
+        if summary.staging_blocks_deleted > 0 || summary.microblocks_deleted > 0 {
+            info!(
+                "Pruned fork data";
+                "below_height" => below_height,
+                "staging_blocks_deleted" => summary.staging_blocks_deleted,
+                "microblocks_deleted" => summary.microblocks_deleted,
+                "elapsed_ms" => start.elapsed().as_millis()
+            );
+        }
+        Ok(())
+    }
+
+    /// Move the data of the microblocks below `below_height` to the archive at `archive_path`
+    fn archive_microblocks(
+        config: &Config,
+        archive_path: &Path,
+        below_height: u64,
+    ) -> Result<(), ClarityError> {
+        let (mut chainstate, _) = StacksChainState::open(
+            config.is_mainnet(),
+            config.burnchain.chain_id,
+            &config.get_chainstate_path_str(),
+            Some(config.node.get_marf_opts()),
+        )
+        .map_err(|e| InterpreterError::DBError(format!("failed to open chainstate: {e:?}")))?;
+        let start = Instant::now();
+        let archived = chainstate
+            .archive_microblocks(archive_path, below_height, MICROBLOCK_ARCHIVE_BATCH)
+            .map_err(|e| {
+                InterpreterError::DBError(format!("failed to archive microblocks: {e:?}"))
+            })?;
+        if archived > 0 {
+            info!(
+                "Archived microblocks";
+                "below_height" => below_height,
+                "microblocks_archived" => archived,
+                "archive_path" => %archive_path.display(),
+                "elapsed_ms" => start.elapsed().as_millis()
+            );
+        }
+        Ok(())
+    }
+
+    /// Discard the state that is more than `keep_blocks` below `tip`
+    fn prune_state(
+        config: &Config,
+        tip: &StacksBlockId,
+        keep_blocks: u32,
+    ) -> Result<(), ClarityError> {
+        let clarity_state_path =
+            StacksChainState::vm_state_index_root_path(config.get_chainstate_path());
+        let mut marf_opts = config.node.get_marf_opts();
+        // the whole state is read once; don't keep it in memory
+        marf_opts.cache_strategy = "noop".into();
+        // unconfirmed tries must be readable, to keep their state
+        let mut marf = MarfedKV::open_unconfirmed(
+            clarity_state_path
+                .to_str()
+                .expect("FATAL: non-UTF-8 character in chainstate path"),
+            None,
+            Some(marf_opts),
+        )?;
+        let start = Instant::now();
+        if let Some(summary) = marf.prune_state(tip, keep_blocks)? {
+            info!(
+                "Pruned Clarity state";
+                "pruned_height" => summary.pruned_height,
+                "values_deleted" => summary.values_deleted,
+                "elapsed_ms" => start.elapsed().as_millis()
+            );
+        }
+        Ok(())
+    }
+}
@@ -1,22 +1,30 @@
+pub mod analysis_warm_load;
 pub mod boot_nakamoto;
 pub mod helium;
+pub mod lifecycle;
+pub mod maintenance;
 pub mod nakamoto;
 pub mod neon;
+pub mod snapshot;
+
+use std::fs;
+use std::io::BufReader;
 
 use clarity::vm::costs::ExecutionCost;
 use clarity::vm::database::BurnStateDB;
 use stacks::burnchains::{PoxConstants, Txid};
-use stacks::chainstate::stacks::db::StacksChainState;
+use stacks::chainstate::stacks::db::{ClarityTx, StacksChainState};
 use stacks::chainstate::stacks::events::StacksTransactionReceipt;
 use stacks::chainstate::stacks::{
     StacksBlock, TransactionAuth, TransactionPayload, TransactionSpendingCondition,
 };
+use stacks::clarity_vm::database::snapshot::ClarityStateSnapshot;
 use stacks_common::types::chainstate::StacksBlockId;
 use stacks_common::util::vrf::VRFPublicKey;
 
 use crate::stacks::chainstate::coordinator::BlockEventDispatcher;
 use crate::stacks::chainstate::stacks::index::ClarityMarfTrieId;
-use crate::{BurnchainController, BurnchainTip, ChainTip, EventDispatcher, Tenure};
+use crate::{BurnchainController, BurnchainTip, ChainTip, Config, EventDispatcher, Tenure};
 
 macro_rules! info_blue {
     ($($arg:tt)*) => ({
@@ -208,3 +216,30 @@ pub fn announce_boot_receipts(
         0,
     );
 }
+
+/// The genesis callback that imports the Clarity state snapshot in `config`, if it has one.
+/// The node can't start without the state it was configured with, so failing to import it is
+/// fatal.
+pub fn clarity_state_snapshot_import(config: &Config) -> Option<Box<dyn FnOnce(&mut ClarityTx)>> {
+    let path = config.node.clarity_state_snapshot.clone()?;
+    Some(Box::new(move |clarity_tx| {
+        let file = fs::File::open(&path)
+            .unwrap_or_else(|e| panic!("FATAL: failed to open Clarity state snapshot {path}: {e}"));
+        let snapshot = ClarityStateSnapshot::read_from(BufReader::new(file))
+            .unwrap_or_else(|e| panic!("FATAL: failed to read Clarity state snapshot {path}: {e}"));
+        info!(
+            "Importing Clarity state snapshot";
+            "path" => &path,
+            "block_id" => %snapshot.block_id,
+            "block_height" => snapshot.block_height,
+            "keys" => snapshot.data.len(),
+            "metadata" => snapshot.metadata.len(),
+        );
+        clarity_tx
+            .connection()
+            .import_state_snapshot(&snapshot)
+            .unwrap_or_else(|e| {
+                panic!("FATAL: failed to import Clarity state snapshot {path}: {e}")
+            });
+    }))
+}
@@ -13,10 +13,11 @@
 //
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
-use std::sync::atomic::AtomicBool;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::sync_channel;
 use std::sync::{Arc, Mutex};
 use std::thread::JoinHandle;
+use std::time::Duration;
 use std::{cmp, thread};
 
 use stacks::burnchains::bitcoin::address::{BitcoinAddress, LegacyBitcoinAddressType};
@@ -36,7 +37,9 @@ use stacks_common::util::hash::Hash160;
 use stacks_common::util::{get_epoch_time_secs, sleep_ms};
 use stx_genesis::GenesisData;
 
+use crate::burnchains::bitcoin_regtest_controller::get_funding_public_key;
 use crate::burnchains::make_bitcoin_indexer;
+use crate::burnchains::zmq::{run_block_listener, BurnchainBlockNotifier};
 use crate::globals::Globals as GenericGlobals;
 use crate::monitoring::{start_serving_monitoring_metrics, MonitoringError};
 use crate::nakamoto_node::{self, StacksNode, BLOCK_PROCESSOR_STACK_SIZE, RELAYER_MAX_BUFFER};
@@ -46,6 +49,8 @@ use crate::node::{
     use_test_genesis_chainstate,
 };
 use crate::run_loop::boot_nakamoto::Neon2NakaData;
+use crate::run_loop::clarity_state_snapshot_import;
+use crate::run_loop::lifecycle::{self, Lifecycle, SubsystemHandle};
 use crate::run_loop::neon;
 use crate::run_loop::neon::Counters;
 use crate::syncctl::{PoxSyncWatchdog, PoxSyncWatchdogComms};
@@ -170,23 +175,36 @@ impl RunLoop {
             if let Err(e) = burnchain.create_wallet_if_dne() {
                 warn!("Error when creating wallet: {e:?}");
             }
+            let funding_key = get_funding_public_key(&self.config, &mut op_signer);
             let mut btc_addrs = vec![(
                 StacksEpochId::Epoch2_05,
                 // legacy
                 BitcoinAddress::from_bytes_legacy(
                     self.config.burnchain.get_bitcoin_network().1,
                     LegacyBitcoinAddressType::PublicKeyHash,
-                    &Hash160::from_data(&op_signer.get_public_key().to_bytes()).0,
+                    &Hash160::from_data(&funding_key.to_bytes()).0,
                 )
                 .expect("FATAL: failed to construct legacy bitcoin address"),
             )];
-            if self.config.miner.segwit {
+            if self.config.miner.taproot {
+                btc_addrs.push((
+                    StacksEpochId::Epoch21,
+                    // segwit p2tr
+                    BitcoinAddress::from_bytes_segwit_p2tr(
+                        self.config.burnchain.get_bitcoin_network().1,
+                        &funding_key
+                            .to_taproot_output_key()
+                            .expect("FATAL: failed to tweak taproot key"),
+                    )
+                    .expect("FATAL: failed to construct segwit p2tr address"),
+                ));
+            } else if self.config.miner.segwit {
                 btc_addrs.push((
                     StacksEpochId::Epoch21,
                     // segwit p2wpkh
                     BitcoinAddress::from_bytes_segwit_p2wpkh(
                         self.config.burnchain.get_bitcoin_network().1,
-                        &Hash160::from_data(&op_signer.get_public_key().to_bytes_compressed()).0,
+                        &Hash160::from_data(&funding_key.to_bytes_compressed()).0,
                     )
                     .expect("FATAL: failed to construct segwit p2wpkh address"),
                 ));
@@ -196,8 +214,7 @@ impl RunLoop {
             for _ in 0..Self::UTXO_RETRY_COUNT {
                 for (epoch_id, btc_addr) in &btc_addrs {
                     info!("Miner node: checking UTXOs at address: {btc_addr}");
-                    let utxos =
-                        burnchain.get_utxos(*epoch_id, &op_signer.get_public_key(), 1, None, 0);
+                    let utxos = burnchain.get_utxos(*epoch_id, &funding_key, 1, None, 0);
                     if utxos.is_none() {
                         warn!("UTXOs not found for {btc_addr}. If this is unexpected, please ensure that your bitcoind instance is indexing transactions for the address {btc_addr} (importaddress)");
                     } else {
@@ -235,7 +252,7 @@ impl RunLoop {
         // instantiate chainstate
         let mut boot_data = ChainStateBootData {
             initial_balances,
-            post_flight_callback: None,
+            post_flight_callback: clarity_state_snapshot_import(&self.config),
             first_burnchain_block_hash: burnchain_config.first_block_hash,
             first_burnchain_block_height: burnchain_config.first_block_height as u32,
             first_burnchain_block_timestamp: burnchain_config.first_block_timestamp,
@@ -250,6 +267,7 @@ impl RunLoop {
                 get_namespaces(use_test_genesis_data)
             })),
             get_bulk_initial_names: Some(Box::new(move || get_names(use_test_genesis_data))),
+            boot_contract_provider: None,
         };
 
         let (chain_state_db, receipts) = StacksChainState::open_and_exec(
@@ -269,15 +287,43 @@ impl RunLoop {
         chain_state_db
     }
 
-    /// Instantiate the Stacks chain state and start the chains coordinator thread.
-    /// Returns the coordinator thread handle, and the receiving end of the coordinator's atlas
-    /// attachment channel.
+    /// If `burnchain.zmq_endpoint` is set, register the thread that listens for bitcoind's
+    /// new-block notifications with `lifecycle`, and return what it notifies.
+    fn register_zmq_listener(
+        &self,
+        lifecycle: &mut Lifecycle,
+    ) -> Result<Option<Arc<BurnchainBlockNotifier>>, lifecycle::Error> {
+        let Some(endpoint) = self.config.burnchain.zmq_endpoint.clone() else {
+            return Ok(None);
+        };
+        let block_notifier = Arc::new(BurnchainBlockNotifier::new());
+        let listener_notifier = block_notifier.clone();
+        lifecycle.add_once(lifecycle::ZMQ_LISTENER, &[], move || {
+            let keep_running = Arc::new(AtomicBool::new(true));
+            let listener_keep_running = keep_running.clone();
+            let listener_thread =
+                thread::Builder::new()
+                    .name("zmq-listener".into())
+                    .spawn(move || {
+                        run_block_listener(&endpoint, &listener_notifier, &listener_keep_running)
+                    })?;
+            Ok(SubsystemHandle::new(listener_thread, move || {
+                keep_running.store(false, Ordering::SeqCst)
+            }))
+        })?;
+        Ok(Some(block_notifier))
+    }
+
+    /// Instantiate the Stacks chain state and register the chains coordinator thread with
+    /// `lifecycle`.
     fn spawn_chains_coordinator(
         &mut self,
         burnchain_config: &Burnchain,
         coordinator_receivers: CoordinatorReceivers,
-        miner_status: Arc<Mutex<MinerStatus>>,
-    ) -> JoinHandle<()> {
+        globals: &Globals,
+        lifecycle: &mut Lifecycle,
+    ) -> Result<(), lifecycle::Error> {
+        let miner_status = globals.get_miner_status();
         let use_test_genesis_data = use_test_genesis_chainstate(&self.config);
 
         // load up genesis Atlas attachments
@@ -304,43 +350,47 @@ impl RunLoop {
         let coordinator_indexer =
             make_bitcoin_indexer(&self.config, Some(self.should_keep_running.clone()));
 
-        let coordinator_thread_handle = thread::Builder::new()
-            .name(format!(
-                "chains-coordinator-{}",
-                &moved_config.node.rpc_bind
-            ))
-            .stack_size(BLOCK_PROCESSOR_STACK_SIZE)
-            .spawn(move || {
-                debug!(
-                    "chains-coordinator thread ID is {:?}",
-                    thread::current().id()
-                );
-                let mut cost_estimator = moved_config.make_cost_estimator();
-                let mut fee_estimator = moved_config.make_fee_estimator();
-
-                let coord_config = ChainsCoordinatorConfig {
-                    always_use_affirmation_maps: moved_config.node.always_use_affirmation_maps,
-                    require_affirmed_anchor_blocks: moved_config
-                        .node
-                        .require_affirmed_anchor_blocks,
-                };
-                ChainsCoordinator::run(
-                    coord_config,
-                    chain_state_db,
-                    moved_burnchain_config,
-                    &coordinator_dispatcher,
-                    coordinator_receivers,
-                    moved_atlas_config,
-                    cost_estimator.as_deref_mut(),
-                    fee_estimator.as_deref_mut(),
-                    miner_status,
-                    coordinator_indexer,
-                    atlas_db,
-                );
-            })
-            .expect("FATAL: failed to start chains coordinator thread");
+        let coordinator_globals = globals.clone();
 
-        coordinator_thread_handle
+        lifecycle.add_once(lifecycle::CHAINS_COORDINATOR, &[], move || {
+            let coordinator_thread_handle = thread::Builder::new()
+                .name(format!(
+                    "chains-coordinator-{}",
+                    &moved_config.node.rpc_bind
+                ))
+                .stack_size(BLOCK_PROCESSOR_STACK_SIZE)
+                .spawn(move || {
+                    debug!(
+                        "chains-coordinator thread ID is {:?}",
+                        thread::current().id()
+                    );
+                    let mut cost_estimator = moved_config.make_cost_estimator();
+                    let mut fee_estimator = moved_config.make_fee_estimator();
+
+                    let coord_config = ChainsCoordinatorConfig {
+                        always_use_affirmation_maps: moved_config.node.always_use_affirmation_maps,
+                        require_affirmed_anchor_blocks: moved_config
+                            .node
+                            .require_affirmed_anchor_blocks,
+                    };
+                    ChainsCoordinator::run(
+                        coord_config,
+                        chain_state_db,
+                        moved_burnchain_config,
+                        &coordinator_dispatcher,
+                        coordinator_receivers,
+                        moved_atlas_config,
+                        cost_estimator.as_deref_mut(),
+                        fee_estimator.as_deref_mut(),
+                        miner_status,
+                        coordinator_indexer,
+                        atlas_db,
+                    );
+                })?;
+            Ok(SubsystemHandle::new(coordinator_thread_handle, move || {
+                coordinator_globals.coord().stop_chains_coordinator();
+            }))
+        })
     }
 
     /// Start Prometheus logging
@@ -458,12 +508,31 @@ impl RunLoop {
         );
         self.set_globals(globals.clone());
 
-        // have headers; boot up the chains coordinator and instantiate the chain state
-        let coordinator_thread_handle = self.spawn_chains_coordinator(
-            &burnchain_config,
-            coordinator_receivers,
-            globals.get_miner_status(),
-        );
+        // have headers; boot up the chains coordinator and instantiate the chain state.
+        // dropping the lifecycle stops and joins every subsystem it started, so returning early
+        // doesn't leave threads running.
+        let mut lifecycle = Lifecycle::new();
+        let block_notifier = match self.register_zmq_listener(&mut lifecycle) {
+            Ok(block_notifier) => block_notifier,
+            Err(e) => {
+                error!("Runloop: failed to register the ZeroMQ listener: {e}");
+                info!("Exiting stacks-node");
+                return;
+            }
+        };
+        if let Err(e) = self
+            .spawn_chains_coordinator(
+                &burnchain_config,
+                coordinator_receivers,
+                &globals,
+                &mut lifecycle,
+            )
+            .and_then(|_| lifecycle.start_all())
+        {
+            error!("Runloop: failed to start the chains coordinator: {e}");
+            info!("Exiting stacks-node");
+            return;
+        }
         self.start_prometheus();
 
         // We announce a new burn block so that the chains coordinator
@@ -490,7 +559,22 @@ impl RunLoop {
 
         // Boot up the p2p network and relayer, and figure out how many sortitions we have so far
         // (it could be non-zero if the node is resuming from chainstate)
-        let mut node = StacksNode::spawn(self, globals.clone(), relay_recv, data_from_neon);
+        let node = StacksNode::spawn(
+            self,
+            globals.clone(),
+            relay_recv,
+            data_from_neon,
+            &mut lifecycle,
+        );
+        let mut node = match node.and_then(|node| lifecycle.start_all().map(|_| node)) {
+            Ok(node) => node,
+            Err(e) => {
+                error!("Runloop: failed to start the node: {e}");
+                globals.signal_stop();
+                info!("Exiting stacks-node");
+                return;
+            }
+        };
 
         // Wait for all pending sortitions to process
         let burnchain_db = burnchain_config
@@ -528,16 +612,17 @@ impl RunLoop {
         let mut poll_deadline = 0;
 
         loop {
-            if !globals.keep_running() {
-                // The p2p thread relies on the same atomic_bool, it will
-                // discontinue its execution after completing its ongoing runloop epoch.
-                info!("Terminating p2p process");
-                info!("Terminating relayer");
-                info!("Terminating chains-coordinator");
+            let exited = lifecycle.exited();
+            if globals.keep_running() && !exited.is_empty() {
+                error!("Runloop: node subsystems exited unexpectedly, shutting down"; "subsystems" => ?exited);
+                globals.signal_stop();
+            }
 
-                globals.coord().stop_chains_coordinator();
-                coordinator_thread_handle.join().unwrap();
-                node.join();
+            if !globals.keep_running() {
+                // The p2p and relayer threads rely on the same atomic_bool, they will
+                // discontinue their execution after completing their ongoing runloop epoch.
+                // They are stopped before the chains coordinator they feed.
+                lifecycle.stop_all();
 
                 info!("Exiting stacks-node");
                 break;
@@ -585,8 +670,18 @@ impl RunLoop {
                 }
 
                 if poll_deadline > get_epoch_time_secs() {
-                    sleep_ms(1_000);
-                    continue;
+                    // sync early if bitcoind announces a new block
+                    let new_block = match block_notifier.as_ref() {
+                        Some(block_notifier) => block_notifier.wait(Duration::from_secs(1)),
+                        None => {
+                            sleep_ms(1_000);
+                            false
+                        }
+                    };
+                    if !new_block {
+                        continue;
+                    }
+                    debug!("Runloop: bitcoind announced a new burnchain block");
                 }
                 poll_deadline = get_epoch_time_secs() + self.config().burnchain.poll_time_secs;
 
@@ -651,6 +746,8 @@ impl RunLoop {
                         ) {
                             // relayer errored, exit.
                             error!("Runloop: Block relayer and miner errored, exiting."; "err" => ?e);
+                            globals.signal_stop();
+                            lifecycle.stop_all();
                             return;
                         }
                     }
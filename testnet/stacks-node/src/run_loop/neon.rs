@@ -29,7 +29,8 @@ use stacks_common::util::hash::Hash160;
 use stacks_common::util::{get_epoch_time_secs, sleep_ms};
 use stx_genesis::GenesisData;
 
-use super::RunLoopCallbacks;
+use super::{clarity_state_snapshot_import, RunLoopCallbacks};
+use crate::burnchains::bitcoin_regtest_controller::get_funding_public_key;
 use crate::burnchains::{make_bitcoin_indexer, Error};
 use crate::globals::NeonGlobals as Globals;
 use crate::monitoring::{start_serving_monitoring_metrics, MonitoringError};
@@ -385,23 +386,36 @@ impl RunLoop {
             if let Err(e) = burnchain.create_wallet_if_dne() {
                 warn!("Error when creating wallet: {e:?}");
             }
+            let funding_key = get_funding_public_key(&self.config, &mut op_signer);
             let mut btc_addrs = vec![(
                 StacksEpochId::Epoch2_05,
                 // legacy
                 BitcoinAddress::from_bytes_legacy(
                     self.config.burnchain.get_bitcoin_network().1,
                     LegacyBitcoinAddressType::PublicKeyHash,
-                    &Hash160::from_data(&op_signer.get_public_key().to_bytes()).0,
+                    &Hash160::from_data(&funding_key.to_bytes()).0,
                 )
                 .expect("FATAL: failed to construct legacy bitcoin address"),
             )];
-            if self.config.miner.segwit {
+            if self.config.miner.taproot {
+                btc_addrs.push((
+                    StacksEpochId::Epoch21,
+                    // segwit p2tr
+                    BitcoinAddress::from_bytes_segwit_p2tr(
+                        self.config.burnchain.get_bitcoin_network().1,
+                        &funding_key
+                            .to_taproot_output_key()
+                            .expect("FATAL: failed to tweak taproot key"),
+                    )
+                    .expect("FATAL: failed to construct segwit p2tr address"),
+                ));
+            } else if self.config.miner.segwit {
                 btc_addrs.push((
                     StacksEpochId::Epoch21,
                     // segwit p2wpkh
                     BitcoinAddress::from_bytes_segwit_p2wpkh(
                         self.config.burnchain.get_bitcoin_network().1,
-                        &Hash160::from_data(&op_signer.get_public_key().to_bytes_compressed()).0,
+                        &Hash160::from_data(&funding_key.to_bytes_compressed()).0,
                     )
                     .expect("FATAL: failed to construct segwit p2wpkh address"),
                 ));
@@ -411,8 +425,7 @@ impl RunLoop {
             for _ in 0..Self::UTXO_RETRY_COUNT {
                 for (epoch_id, btc_addr) in &btc_addrs {
                     info!("Miner node: checking UTXOs at address: {btc_addr}");
-                    let utxos =
-                        burnchain.get_utxos(*epoch_id, &op_signer.get_public_key(), 1, None, 0);
+                    let utxos = burnchain.get_utxos(*epoch_id, &funding_key, 1, None, 0);
                     if utxos.is_none() {
                         warn!("UTXOs not found for {btc_addr}. If this is unexpected, please ensure that your bitcoind instance is indexing transactions for the address {btc_addr} (importaddress)");
                     } else {
@@ -540,7 +553,7 @@ impl RunLoop {
         // instantiate chainstate
         let mut boot_data = ChainStateBootData {
             initial_balances,
-            post_flight_callback: None,
+            post_flight_callback: clarity_state_snapshot_import(&self.config),
             first_burnchain_block_hash: burnchain_config.first_block_hash,
             first_burnchain_block_height: burnchain_config.first_block_height as u32,
             first_burnchain_block_timestamp: burnchain_config.first_block_timestamp,
@@ -555,6 +568,7 @@ impl RunLoop {
                 get_namespaces(use_test_genesis_data)
             })),
             get_bulk_initial_names: Some(Box::new(move || get_names(use_test_genesis_data))),
+            boot_contract_provider: None,
         };
 
         info!("About to call open_and_exec");
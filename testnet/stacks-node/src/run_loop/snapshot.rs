@@ -0,0 +1,113 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Fast sync from chainstate snapshots. A node configured with `node.fast_sync_snapshot`
+//! restores that snapshot before it first boots, checks that the restored sortition DB is at
+//! the snapshot's Bitcoin anchor, and then syncs the rest of the burnchain and the Stacks chain
+//! as usual. The `export-snapshot` subcommand produces such snapshots from a stopped node.
+
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use stacks::chainstate::burn::db::sortdb::SortitionDB;
+use stacks::chainstate::snapshot::{
+    discard_restored_snapshot, export_snapshot, restore_snapshot, Error as SnapshotError,
+    SnapshotAnchor, SnapshotManifest,
+};
+use stacks_common::util::secp256k1::Secp256k1PrivateKey;
+
+use crate::Config;
+
+/// The working directory of the node for its network mode, which holds its chainstate
+fn node_dir(config: &Config) -> PathBuf {
+    let mut path = config.get_chainstate_path();
+    path.pop();
+    path
+}
+
+fn open_sortdb(config: &Config) -> Result<SortitionDB, SnapshotError> {
+    let sortdb = SortitionDB::open(
+        &config.get_burn_db_file_path(),
+        false,
+        config.get_burnchain().pox_constants,
+    )?;
+    Ok(sortdb)
+}
+
+/// Restore the configured snapshot, if the node has no chainstate yet
+pub fn restore_if_configured(config: &Config) -> Result<(), SnapshotError> {
+    let Some(snapshot_dir) = config.node.fast_sync_snapshot.as_ref() else {
+        return Ok(());
+    };
+    if config.get_chainstate_path().exists() {
+        debug!("Node already has chainstate; not restoring snapshot {snapshot_dir:?}");
+        return Ok(());
+    }
+
+    let node_dir = node_dir(config);
+    let start = Instant::now();
+    info!("Restoring chainstate snapshot"; "snapshot" => %snapshot_dir.display());
+    let manifest = restore_snapshot(
+        snapshot_dir,
+        &node_dir,
+        config.is_mainnet(),
+        config.burnchain.chain_id,
+        &config.node.fast_sync_trusted_signers,
+    )?;
+
+    let anchor = open_sortdb(config).and_then(|sortdb| Ok(SnapshotAnchor::load(&sortdb)?));
+    if anchor.as_ref().ok() != Some(&manifest.contents.anchor) {
+        warn!(
+            "Restored snapshot is not at its anchor";
+            "expected" => ?manifest.contents.anchor,
+            "restored" => ?anchor
+        );
+        discard_restored_snapshot(&node_dir);
+        return Err(SnapshotError::AnchorMismatch);
+    }
+
+    info!(
+        "Restored chainstate snapshot";
+        "snapshot_id" => %manifest.contents.snapshot_id(),
+        "burn_block_height" => manifest.contents.anchor.burn_block_height,
+        "burn_header_hash" => %manifest.contents.anchor.burn_header_hash,
+        "stacks_block_height" => manifest.contents.anchor.stacks_block_height,
+        "elapsed_ms" => start.elapsed().as_millis()
+    );
+    Ok(())
+}
+
+/// Export the chainstate of the stopped node to a snapshot in `snapshot_dir`, anchored at its
+/// canonical burnchain tip
+pub fn export(
+    config: &Config,
+    snapshot_dir: &Path,
+    signing_key: &Secp256k1PrivateKey,
+) -> Result<SnapshotManifest, SnapshotError> {
+    let anchor = SnapshotAnchor::load(&open_sortdb(config)?)?;
+    info!(
+        "Exporting chainstate snapshot";
+        "burn_block_height" => anchor.burn_block_height,
+        "stacks_block_height" => anchor.stacks_block_height
+    );
+    export_snapshot(
+        &node_dir(config),
+        snapshot_dir,
+        config.is_mainnet(),
+        config.burnchain.chain_id,
+        anchor,
+        signing_key,
+    )
+}
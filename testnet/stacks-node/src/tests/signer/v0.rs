@@ -2673,7 +2673,7 @@ fn empty_sortition() {
 
     info!("------------------------- Test Delayed Block is Rejected  -------------------------");
     let reward_cycle = signer_test.get_current_reward_cycle();
-    let mut stackerdb = StackerDB::new(
+    let mut stackerdb = StackerDB::<MessageSlotID>::new(
         &signer_test.running_nodes.conf.node.rpc_bind,
         StacksPrivateKey::new(), // We are just reading so don't care what the key is
         false,
@@ -2681,28 +2681,21 @@ fn empty_sortition() {
         SignerSlotID(0), // We are just reading so again, don't care about index.
     );
 
-    let signer_slot_ids: Vec<_> = signer_test
-        .get_signer_indices(reward_cycle)
-        .iter()
-        .map(|id| id.0)
-        .collect();
+    let signer_slot_ids = signer_test.get_signer_indices(reward_cycle);
     assert_eq!(signer_slot_ids.len(), num_signers);
 
     // The miner's proposed block should get rejected by all the signers
     let mut found_rejections = Vec::new();
     wait_for(short_timeout.as_secs(), || {
+        let mut latest_msgs = stackerdb
+            .get_all_latest_messages::<SignerMessage>(&signer_slot_ids)
+            .expect("Failed to get messages from stackerdb");
         for slot_id in signer_slot_ids.iter() {
             if found_rejections.contains(slot_id) {
                 continue;
             }
-            let mut latest_msgs = StackerDB::get_messages(
-                stackerdb
-                    .get_session_mut(&MessageSlotID::BlockResponse)
-                    .expect("Failed to get BlockResponse stackerdb session"),
-                &[*slot_id]
-            ).expect("Failed to get message from stackerdb");
-            assert!(latest_msgs.len() <= 1);
-            let Some(latest_msg) = latest_msgs.pop() else {
+            let Some(latest_msg) = latest_msgs.remove(slot_id).and_then(|mut msgs| msgs.pop())
+            else {
                 info!("No message yet from slot #{slot_id}, will wait to try again");
                 continue;
             };